@@ -1,7 +1,11 @@
+use unicode_properties::{GeneralCategoryGroup, UnicodeGeneralCategory};
+
 pub trait CharExt {
     fn is_opening_delimiter(self) -> bool;
     fn is_closing_delimiter(self) -> bool;
     fn column_count(self) -> usize;
+    fn is_grapheme_extend(self) -> bool;
+    fn is_rtl(self) -> bool;
     fn opposite_delimiter(&self) -> Option<char>;
 }
 
@@ -21,7 +25,32 @@ impl CharExt for char {
     }
 
     fn column_count(self) -> usize {
-        1
+        if self.is_grapheme_extend() {
+            0
+        } else {
+            1
+        }
+    }
+
+    // Combining marks, zero width joiners and emoji modifiers are drawn onto the char before them
+    // by the shaper, so they belong to its grapheme.
+    fn is_grapheme_extend(self) -> bool {
+        self.general_category_group() == GeneralCategoryGroup::Mark
+            || self == '\u{200D}'
+            || ('\u{1F3FB}'..='\u{1F3FF}').contains(&self)
+    }
+
+    // Letters of the right-to-left scripts, mainly Hebrew and Arabic. Their digits read left to
+    // right, so they are left out.
+    fn is_rtl(self) -> bool {
+        matches!(
+            self,
+            '\u{0590}'..='\u{08FF}'
+                | '\u{FB1D}'..='\u{FDFF}'
+                | '\u{FE70}'..='\u{FEFE}'
+                | '\u{10800}'..='\u{10FFF}'
+                | '\u{1E800}'..='\u{1EFFF}'
+        ) && !self.is_numeric()
     }
 
     fn opposite_delimiter(&self) -> Option<char> {
@@ -0,0 +1,177 @@
+use crate::{
+    decoration::DecorationType,
+    text::{Position, Text},
+};
+
+/// Replaces the text between `start` and `end` with `text`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextEdit {
+    pub start: Position,
+    pub end: Position,
+    pub text: String,
+}
+
+/// The edits to one file, by its path relative to the root of the workspace.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FileEdit {
+    pub file_name: String,
+    pub edits: Vec<TextEdit>,
+}
+
+/// A run of lines an edit changes, before and after, for showing what it will do.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Hunk {
+    pub line_index: usize,
+    pub old_lines: Vec<String>,
+    pub new_lines: Vec<String>,
+}
+
+/// Edits to any number of files, which are applied together or not at all.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct WorkspaceEdit {
+    pub files: Vec<FileEdit>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CodeActionKind {
+    QuickFix,
+    Refactor,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CodeAction {
+    pub title: String,
+    pub kind: CodeActionKind,
+    pub edit: WorkspaceEdit,
+}
+
+/// A problem reported for a range of a file, with the fixes its source knows about.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Diagnostic {
+    pub ty: DecorationType,
+    pub start: Position,
+    pub end: Position,
+    pub message: String,
+    pub fixes: Vec<CodeAction>,
+}
+
+/// What code actions are asked for: the selection in a file and the diagnostics on the lines it
+/// covers.
+pub struct CodeActionRequest<'a> {
+    pub file_name: &'a str,
+    pub text: &'a Text,
+    pub start: Position,
+    pub end: Position,
+    pub diagnostics: &'a [Diagnostic],
+}
+
+pub trait CodeActionProvider {
+    fn code_actions(&self, request: &CodeActionRequest) -> Vec<CodeAction>;
+}
+
+/// Collects the code actions of its providers, like a language server, a spell checker or a
+/// linter. The fixes that come with the diagnostics themselves are always offered first.
+#[derive(Default)]
+pub struct CodeActionRegistry {
+    providers: Vec<Box<dyn CodeActionProvider>>,
+}
+
+impl CodeActionRegistry {
+    pub fn register(&mut self, provider: Box<dyn CodeActionProvider>) {
+        self.providers.push(provider);
+    }
+
+    pub fn code_actions(&self, request: &CodeActionRequest) -> Vec<CodeAction> {
+        let mut actions: Vec<CodeAction> = Vec::new();
+        let providers = [&DiagnosticFixes as &dyn CodeActionProvider]
+            .into_iter()
+            .chain(self.providers.iter().map(|provider| provider.as_ref()));
+        for provider in providers {
+            for action in provider.code_actions(request) {
+                // several diagnostics often suggest the same fix
+                if !actions.iter().any(|other| other.edit == action.edit) {
+                    actions.push(action);
+                }
+            }
+        }
+        actions
+    }
+}
+
+/// Offers the fixes of the diagnostics at the selection.
+pub struct DiagnosticFixes;
+
+impl CodeActionProvider for DiagnosticFixes {
+    fn code_actions(&self, request: &CodeActionRequest) -> Vec<CodeAction> {
+        request
+            .diagnostics
+            .iter()
+            .filter(|diagnostic| {
+                diagnostic.start.line_index <= request.end.line_index
+                    && diagnostic.end.line_index >= request.start.line_index
+            })
+            .flat_map(|diagnostic| diagnostic.fixes.iter().cloned())
+            .collect()
+    }
+}
+
+impl FileEdit {
+    /// Checks that the edits fit `text` and don't overlap, so they can be applied as they are.
+    pub fn check(&self, text: &Text) -> Result<(), String> {
+        let lines = text.as_lines();
+        let is_valid = |position: Position| {
+            lines
+                .get(position.line_index)
+                .is_some_and(|line| line.is_char_boundary(position.byte_index))
+        };
+        let mut edits: Vec<&TextEdit> = self.edits.iter().collect();
+        edits.sort_by_key(|edit| edit.start);
+        for edit in &edits {
+            if edit.start > edit.end || !is_valid(edit.start) || !is_valid(edit.end) {
+                return Err(format!("an edit to {} is out of range", self.file_name));
+            }
+        }
+        if edits.windows(2).any(|pair| pair[0].end > pair[1].start) {
+            return Err(format!("the edits to {} overlap", self.file_name));
+        }
+        Ok(())
+    }
+
+    /// What the edits do to `text`, with edits on the same lines in one hunk. The edits must fit
+    /// `text`, see `check`.
+    pub fn hunks(&self, text: &Text) -> Vec<Hunk> {
+        let lines = text.as_lines();
+        let mut edits: Vec<&TextEdit> = self.edits.iter().collect();
+        edits.sort_by_key(|edit| edit.start);
+        let mut hunks = Vec::new();
+        let mut index = 0;
+        while index < edits.len() {
+            let first_line_index = edits[index].start.line_index;
+            let mut last_line_index = edits[index].end.line_index;
+            let mut end = index + 1;
+            while end < edits.len() && edits[end].start.line_index <= last_line_index {
+                last_line_index = last_line_index.max(edits[end].end.line_index);
+                end += 1;
+            }
+            let old_lines = &lines[first_line_index..=last_line_index];
+            let offset = |position: Position| {
+                old_lines[..position.line_index - first_line_index]
+                    .iter()
+                    .map(|line| line.len() + 1)
+                    .sum::<usize>()
+                    + position.byte_index
+            };
+            let mut new = old_lines.join("\n");
+            for edit in edits[index..end].iter().rev() {
+                new.replace_range(offset(edit.start)..offset(edit.end), &edit.text);
+            }
+            hunks.push(Hunk {
+                line_index: first_line_index,
+                old_lines: old_lines.to_vec(),
+                new_lines: new.split('\n').map(|line| line.to_string()).collect(),
+            });
+            index = end;
+        }
+        hunks
+    }
+}
@@ -1,14 +1,26 @@
 use {
     crate::{
+        code_action::CodeAction,
+        completion::{Completion, CompletionEngine},
+        signature_help::SignatureHelp,
         decoration::{Decoration, DecorationType},
+        diagnostic::Severity,
+        diff::LineChange,
         layout::{BlockElement, WrappedElement},
+        minimap::Minimap,
         selection::Affinity,
         session::{SelectionMode, Session},
         history::{NewGroup},
+        key_bindings::{EditorCommand, KeyChord, KeyLookup},
+        paste::{EscapedString, PasteFilter, PlainText, RawString, Reindent},
+        rich_text,
+        scroll_link::{ScrollPosition, ScrollTarget},
         settings::Settings,
+        state::{SavedSelection, SessionState},
         str::StrExt,
         text::Position,
         token::TokenKind,
+        vim::{self, InsertAt, Operator, Vim, VimCommand, VimMode},
         Line, Selection, Token,
     },
     makepad_widgets::*,
@@ -26,6 +38,12 @@ live_design! {
         delimiter_highlight: #f,
         error_decoration: #f00,
         warning_decoration: #0f0,
+        information_decoration: #3794ff,
+        added_line: #487e02,
+        modified_line: #1b81a8,
+        removed_line: #f14c4c,
+        search_match: #3a3d41,
+        current_search_match: #613214,
         
         unknown: #C0C0C0,
         branch_keyword: #C485BE,
@@ -117,6 +135,55 @@ live_design! {
         margin: 0,
 
         scroll_bars: <ScrollBars> {}
+        emoji_picker: <EmojiPicker> {}
+        popup_rows: 8,
+        draw_popup_bg: {
+            color: (THEME_COLOR_FLOATING_BG)
+            fn pixel(self) -> vec4 {
+                let sdf = Sdf2d::viewport(self.pos * self.rect_size)
+                sdf.box(1.0, 1.0, self.rect_size.x - 2.0, self.rect_size.y - 2.0, 2.0)
+                sdf.fill_keep(self.color)
+                sdf.stroke(mix(THEME_COLOR_BEVEL_LIGHT, THEME_COLOR_BEVEL_SHADOW, pow(self.pos.y, 0.35)), THEME_BEVELING)
+                return sdf.result;
+            }
+        }
+        draw_popup_selected: {
+            color: (THEME_COLOR_CTRL_HOVER)
+            fn pixel(self) -> vec4 {
+                let sdf = Sdf2d::viewport(self.pos * self.rect_size)
+                sdf.box(1.0, 0.0, self.rect_size.x - 2.0, self.rect_size.y, 2.0)
+                sdf.fill(self.color)
+                return sdf.result;
+            }
+        }
+        draw_popup_text: {
+            text_style: <THEME_FONT_CODE> {}
+            color: (THEME_COLOR_TEXT_DEFAULT),
+        }
+        draw_composition_bg: { color: (THEME_COLOR_BG_CONTAINER) }
+        draw_composition_text: {
+            text_style: <THEME_FONT_CODE> {}
+            color: (THEME_COLOR_TEXT_DEFAULT),
+        }
+        draw_composition_underline: { color: (THEME_COLOR_TEXT_DEFAULT) }
+        draw_signature_bg: {
+            color: (THEME_COLOR_FLOATING_BG)
+            fn pixel(self) -> vec4 {
+                let sdf = Sdf2d::viewport(self.pos * self.rect_size)
+                sdf.box(1.0, 1.0, self.rect_size.x - 2.0, self.rect_size.y - 2.0, 2.0)
+                sdf.fill_keep(self.color)
+                sdf.stroke(mix(THEME_COLOR_BEVEL_LIGHT, THEME_COLOR_BEVEL_SHADOW, pow(self.pos.y, 0.35)), THEME_BEVELING)
+                return sdf.result;
+            }
+        }
+        draw_signature_text: {
+            text_style: <THEME_FONT_CODE> {}
+            color: (THEME_COLOR_TEXT_DEFAULT),
+        }
+        draw_signature_active: {
+            text_style: <THEME_FONT_BOLD> {}
+            color: (THEME_COLOR_TEXT_HL),
+        }
         draw_bg: { color: (THEME_COLOR_BG_CONTAINER) }
         draw_gutter: {
             draw_depth: 1.0,
@@ -136,6 +203,25 @@ live_design! {
                 return incol
             }
         }
+        draw_diagnostic_marker: {
+            draw_depth: 1.0,
+            fn pixel(self) -> vec4 {
+                let sdf = Sdf2d::viewport(self.pos * self.rect_size)
+                let radius = min(self.rect_size.x, self.rect_size.y) * 0.3
+                sdf.circle(self.rect_size.x * 0.5, self.rect_size.y * 0.5, radius)
+                return sdf.fill(self.color)
+            }
+        }
+        draw_line_change: {
+            draw_depth: 1.0,
+        }
+        draw_minimap: {
+            draw_depth: 1.0,
+        }
+        draw_minimap_lens: {
+            draw_depth: 1.0,
+            color: #fff1
+        }
         draw_indent_guide: {
            // draw_depth: 1.0,
             color: (THEME_COLOR_U_2),
@@ -221,14 +307,41 @@ pub struct CodeEditor {
     #[walk] walk: Walk,
     #[live] scroll_bars: ScrollBars,
     #[live] draw_gutter: DrawText,
+    #[live] draw_diagnostic_marker: DrawColor,
+    #[live] draw_line_change: DrawColor,
+    #[live] draw_minimap: DrawColor,
+    #[live] draw_minimap_lens: DrawColor,
     #[live] draw_text: DrawCodeText,
     #[live] token_colors: TokenColors,
     #[live] draw_indent_guide: DrawIndentGuide,
     #[live] draw_decoration: DrawDecoration,
+    #[live] draw_search_match: DrawColor,
     #[live] draw_selection: DrawSelection,
     #[live] draw_cursor: DrawColor,
     #[live] draw_cursor_bg: DrawColor,
     #[live] draw_bg: DrawColor,
+    #[live] emoji_picker: Option<LivePtr>,
+    #[rust] emoji_picker_open: bool,
+    #[live] popup_draw_list: DrawList2d,
+    #[live] draw_popup_bg: DrawColor,
+    #[live] draw_popup_selected: DrawColor,
+    #[live] draw_popup_text: DrawText,
+    #[live(8usize)] popup_rows: usize,
+    #[rust] completion_engine: CompletionEngine,
+    #[rust] popup: Option<PopupList>,
+    #[live] signature_draw_list: DrawList2d,
+    #[live] draw_signature_bg: DrawColor,
+    #[live] draw_signature_text: DrawText,
+    #[live] draw_signature_active: DrawText,
+    #[rust] signature_help: Option<SignatureHelp>,
+    #[live] tooltip_draw_list: DrawList2d,
+    #[live] composition_draw_list: DrawList2d,
+    #[live] draw_composition_bg: DrawColor,
+    #[live] draw_composition_text: DrawText,
+    #[live] draw_composition_underline: DrawColor,
+    /// The preedit text of an input method, shown at the cursor until it is committed.
+    #[rust] composition: Option<TextCompositionEvent>,
+    #[rust] diagnostic_tooltip: Option<DiagnosticTooltip>,
     #[rust(KeepCursorInView::Off)] keep_cursor_in_view: KeepCursorInView,
     #[rust] last_cursor_screen_pos: Option<DVec2>,
 
@@ -236,16 +349,67 @@ pub struct CodeEditor {
     #[rust] gutter_rect: Rect,
     #[rust] viewport_rect: Rect,
     #[rust] unscrolled_rect: Rect,
+    /// The line to scroll to once the editor is drawn, from `restore_session_state`.
+    #[rust] restore_scroll_line: Option<f64>,
     #[rust] line_start: usize,
     #[rust] line_end: usize,
 
     #[live(true)] word_wrap: bool,
+    #[live(false)] show_minimap: bool,
+    #[live(100.0)] minimap_width: f64,
+    /// How much smaller than the code the minimap is.
+    #[live(0.15)] minimap_scale: f64,
+    #[rust] minimap: Minimap,
+    #[rust] minimap_rect: Rect,
+    #[rust] minimap_line_start: usize,
+    #[rust] minimap_drag: Option<MinimapDrag>,
+    #[live(false)] read_only: bool,
+    /// Whether copies also put the selection on the clipboard as html and rtf in the syntax
+    /// colors, for pasting into documents.
+    #[live(true)] copy_rich_text: bool,
 
     #[live(0.5)] blink_speed: f64,
 
     #[animator] animator: Animator,
 
     #[rust] blink_timer: Timer,
+    #[rust] paste_filter: Option<Box<dyn PasteFilter>>,
+    /// The chords pressed so far of a key sequence.
+    #[rust] pending_chords: Vec<KeyChord>,
+    #[rust] ignore_text_input: bool,
+    /// Whether keys are handled like in Vim, with normal, insert and visual modes.
+    #[live(false)] vim_emulation: bool,
+    #[rust] vim: Vim,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum PopupKind {
+    Completions,
+    CodeActions,
+}
+
+/// The completions or code actions shown below the cursor.
+struct PopupList {
+    kind: PopupKind,
+    items: Vec<String>,
+    /// What the items complete to, for a list of completions.
+    completions: Vec<Completion>,
+    selected: usize,
+    /// The first visible row, when there are more items than rows.
+    first: usize,
+}
+
+/// Where a drag of the minimap lens started, to scroll by how far it moved since.
+struct MinimapDrag {
+    start_y: f64,
+    start_scroll_y: f64,
+}
+
+/// The messages of the diagnostics under the mouse.
+struct DiagnosticTooltip {
+    lines: Vec<String>,
+    /// Where the mouse is, relative to the editor.
+    pos: DVec2,
 }
 
 enum KeepCursorInView {
@@ -274,6 +438,9 @@ impl KeepCursorInView {
     }
 }
 impl LiveHook for CodeEditor {
+    fn after_apply(&mut self, cx: &mut Cx, apply: &mut Apply, _index: usize, _nodes: &[LiveNode]) {
+        EmojiPickerGlobal::register(cx, apply, self.emoji_picker);
+    }
 }
 /*
 impl LiveHook for CodeEditor {
@@ -332,6 +499,13 @@ impl CodeEditor {
 
         self.cell_size =
             self.draw_text.text_style.font_size * self.draw_text.get_monospace_base(cx);
+        if let Some(line) = self.restore_scroll_line.take() {
+            // the size of what there is to scroll isn't known yet before the first draw
+            let scroll_pos = self.scroll_bars.get_scroll_pos();
+            let scroll_y = self.line_scroll_y(session, line);
+            self.scroll_bars
+                .set_scroll_pos_no_clip(cx, dvec2(scroll_pos.x, scroll_y));
+        }
         let last_added_selection =
             session.selections()[session.last_added_selection_index().unwrap()];
         let (cursor_x, cursor_y) = session.layout().logical_to_normalized_position(
@@ -418,6 +592,7 @@ impl CodeEditor {
         self.scroll_bars.begin(cx, walk, Layout::default());
 
         let turtle_rect = cx.turtle().rect();
+        // line numbers take up at least 4 columns, and diagnostic markers go after them
         let gutter_width = (session
             .document()
             .as_text()
@@ -425,6 +600,7 @@ impl CodeEditor {
             .len()
             .to_string()
             .column_count()
+            .max(4)
             + 3) as f64
             * self.cell_size.x;
         self.gutter_rect = Rect {
@@ -450,6 +626,9 @@ impl CodeEditor {
         self.gutter_rect.size -= pad_left_top;
         self.viewport_rect.pos += pad_left_top;
         self.viewport_rect.size -= pad_left_top;
+        if self.show_minimap {
+            self.viewport_rect.size.x -= self.minimap_width;
+        }
 
         session.set_wrap_column(if self.word_wrap {
             Some((self.viewport_rect.size.x / self.cell_size.x) as usize)
@@ -469,11 +648,18 @@ impl CodeEditor {
         self.draw_bg.draw_abs(cx, cx.turtle().unscrolled_rect());
 
         self.draw_gutter(cx, session);
+        self.draw_decoration_layer(cx, session, &session.search_state().decorations());
         self.draw_selection_layer(cx, session);
         self.draw_text_layer(cx, session);
         self.draw_indent_guide_layer(cx, session);
-        self.draw_decoration_layer(cx, session);
+        self.draw_decoration_layer(cx, session, &session.document().decorations());
+        let diagnostic_decorations = session
+            .document()
+            .diagnostics()
+            .decorations(&session.document().as_text());
+        self.draw_decoration_layer(cx, session, &diagnostic_decorations);
         self.draw_selection_layer(cx, session);
+        self.draw_minimap(cx, session);
 
         // Get the last added selection.
         // Get the normalized cursor position. To go from normalized to screen position, multiply by
@@ -485,6 +671,24 @@ impl CodeEditor {
         );
 
         self.scroll_bars.end(cx);
+        // the whole text is only worth building when a screen reader asks for it
+        if cx.is_accessibility_enabled() {
+            let text = session.document().as_text().to_string();
+            cx.access_node(
+                AccessNode::new(
+                    LiveId(self as *const _ as u64),
+                    self.scroll_bars.area(),
+                    AccessRole::TextEditor,
+                )
+                .with_name("Code editor")
+                .with_value(&text),
+            );
+        }
+        self.draw_emoji_picker(cx);
+        self.draw_popup(cx);
+        self.draw_signature_help(cx);
+        self.draw_diagnostic_tooltip(cx);
+        self.draw_composition(cx);
         if session.update_folds() {
             self.scroll_bars.area().redraw(cx);
         } else if self.keep_cursor_in_view.is_locked() {
@@ -492,10 +696,631 @@ impl CodeEditor {
         }
     }
 
+    /// Makes the editor a viewer, for instance for playing back a recording.
+    pub fn set_read_only(&mut self, read_only: bool) {
+        self.read_only = read_only;
+    }
+
+    pub fn is_minimap_visible(&self) -> bool {
+        self.show_minimap
+    }
+
+    pub fn set_minimap_visible(&mut self, cx: &mut Cx, show_minimap: bool) {
+        if self.show_minimap != show_minimap {
+            self.show_minimap = show_minimap;
+            self.minimap_drag = None;
+            self.redraw(cx);
+        }
+    }
+
+    pub fn is_vim_enabled(&self) -> bool {
+        self.vim_emulation
+    }
+
+    pub fn set_vim_enabled(&mut self, cx: &mut Cx, vim_emulation: bool) {
+        if self.vim_emulation != vim_emulation {
+            self.vim_emulation = vim_emulation;
+            self.vim = Vim::new();
+            self.redraw(cx);
+        }
+    }
+
+    /// The mode of the Vim emulation, if it's on.
+    pub fn vim_mode(&self) -> Option<VimMode> {
+        self.vim_emulation.then(|| self.vim.mode())
+    }
+
+    /// Runs the next paste through `paste_filter`, for 'paste as' commands from menus and the like.
+    pub fn set_paste_filter(&mut self, paste_filter: Box<dyn PasteFilter>) {
+        self.paste_filter = Some(paste_filter);
+    }
+
     pub fn set_key_focus(&mut self, cx: &mut Cx) {
         cx.set_key_focus(self.scroll_bars.area());
     }
 
+    pub fn has_key_focus(&self, cx: &Cx) -> bool {
+        cx.has_key_focus(self.scroll_bars.area())
+    }
+
+    pub fn area(&self) -> Area {
+        self.scroll_bars.area()
+    }
+
+    pub fn set_emoji_picker_open(&mut self, cx: &mut Cx) {
+        if self.emoji_picker.is_none() || self.read_only {
+            return;
+        }
+        let global = cx.global::<EmojiPickerGlobal>().clone();
+        let mut map = global.map.borrow_mut();
+        map.get_mut(&self.emoji_picker.unwrap()).unwrap().open(cx);
+        self.emoji_picker_open = true;
+        self.redraw(cx);
+    }
+
+    pub fn set_emoji_picker_closed(&mut self, cx: &mut Cx) {
+        if self.emoji_picker_open {
+            self.emoji_picker_open = false;
+            self.redraw(cx);
+        }
+    }
+
+    fn draw_emoji_picker(&mut self, cx: &mut Cx2d) {
+        if !self.emoji_picker_open {
+            return;
+        }
+        let Some(cursor_pos) = self.last_cursor_screen_pos else {
+            return;
+        };
+        let global = cx.global::<EmojiPickerGlobal>().clone();
+        let mut map = global.map.borrow_mut();
+        let picker = map.get_mut(&self.emoji_picker.unwrap()).unwrap();
+        let area = self.scroll_bars.area();
+        let caret = Rect {
+            pos: self.viewport_rect.pos - area.rect(cx).pos + cursor_pos,
+            size: self.cell_size,
+        };
+        picker.draw_at_caret(cx, area, caret);
+    }
+
+    /// Where completion providers for the language of the document go.
+    pub fn completion_engine_mut(&mut self) -> &mut CompletionEngine {
+        &mut self.completion_engine
+    }
+
+    pub fn is_completion_open(&self) -> bool {
+        self.popup
+            .as_ref()
+            .is_some_and(|popup| popup.kind == PopupKind::Completions)
+    }
+
+    /// Completes the word before the cursor. Opens the list if `open` is set, otherwise only
+    /// updates it if it is already open.
+    fn update_completions(&mut self, cx: &mut Cx, session: &Session, open: bool) {
+        if self.read_only || (!open && !self.is_completion_open()) {
+            return self.close_popup(cx);
+        }
+        let position = {
+            let selections = session.selections();
+            // completing in several places at once isn't supported
+            if selections.len() != 1 {
+                return self.close_popup(cx);
+            }
+            selections[0].cursor.position
+        };
+        let completions = self
+            .completion_engine
+            .complete(&session.document().as_text(), position);
+        let items = completions
+            .iter()
+            .map(|completion| completion.label.clone())
+            .collect();
+        self.open_popup(cx, PopupKind::Completions, items, completions);
+    }
+
+    /// Lists the code actions at the cursor, which the embedder looked up after a
+    /// `CodeEditorAction::CodeActionsRequested`. Choosing one gives a
+    /// `CodeEditorAction::ApplyCodeAction` with its index.
+    pub fn open_code_actions(&mut self, cx: &mut Cx, actions: &[CodeAction]) {
+        let items = actions.iter().map(|action| action.title.clone()).collect();
+        self.open_popup(cx, PopupKind::CodeActions, items, Vec::new());
+    }
+
+    fn open_popup(
+        &mut self,
+        cx: &mut Cx,
+        kind: PopupKind,
+        items: Vec<String>,
+        completions: Vec<Completion>,
+    ) {
+        if items.is_empty() {
+            return self.close_popup(cx);
+        }
+        self.popup = Some(PopupList {
+            kind,
+            items,
+            completions,
+            selected: 0,
+            first: 0,
+        });
+        self.popup_draw_list.redraw(cx);
+    }
+
+    fn close_popup(&mut self, cx: &mut Cx) {
+        if self.popup.take().is_some() {
+            self.popup_draw_list.redraw(cx);
+            self.redraw(cx);
+        }
+    }
+
+    fn select_popup_item(&mut self, cx: &mut Cx, delta: isize) {
+        let rows = self.popup_rows.max(1);
+        let Some(list) = &mut self.popup else {
+            return;
+        };
+        let count = list.items.len() as isize;
+        list.selected = (list.selected as isize + delta).rem_euclid(count) as usize;
+        if list.selected < list.first {
+            list.first = list.selected;
+        } else if list.selected >= list.first + rows {
+            list.first = list.selected + 1 - rows;
+        }
+        self.popup_draw_list.redraw(cx);
+    }
+
+    /// Replaces the word before the cursor with the selected completion, or asks for the
+    /// selected code action to be applied.
+    fn accept_popup_item(
+        &mut self,
+        cx: &mut Cx,
+        session: &mut Session,
+        actions: &mut Vec<CodeEditorAction>,
+    ) {
+        let Some(list) = self.popup.take() else {
+            return;
+        };
+        self.popup_draw_list.redraw(cx);
+        if list.kind == PopupKind::CodeActions {
+            actions.push(CodeEditorAction::ApplyCodeAction(list.selected));
+            return;
+        }
+        session.insert_completion(&list.completions[list.selected].text);
+        self.keep_cursor_in_view = KeepCursorInView::Once;
+        self.redraw(cx);
+        actions.push(CodeEditorAction::TextDidChange);
+    }
+
+    fn draw_popup(&mut self, cx: &mut Cx2d) {
+        let Some(list) = &self.popup else {
+            return;
+        };
+        let Some(cursor_pos) = self.last_cursor_screen_pos else {
+            return;
+        };
+        let rows = self.popup_rows.max(1).min(list.items.len());
+        let columns = list.items[list.first..list.first + rows]
+            .iter()
+            .map(|item| item.column_count())
+            .max()
+            .unwrap_or(0);
+        // the kinds of completions go in a column of their own after the labels
+        let kind_columns = list
+            .completions
+            .iter()
+            .skip(list.first)
+            .take(rows)
+            .map(|completion| completion.kind.name().column_count())
+            .max()
+            .unwrap_or(0);
+        let columns = if kind_columns > 0 {
+            columns + 2 + kind_columns
+        } else {
+            columns
+        };
+        let size = dvec2(
+            (columns.max(12) + 2) as f64 * self.cell_size.x,
+            rows as f64 * self.cell_size.y + 4.0,
+        );
+
+        self.popup_draw_list.begin_overlay_reuse(cx);
+        cx.begin_pass_sized_turtle(Layout::flow_down());
+
+        let area = self.scroll_bars.area();
+        let area_pos = area.rect(cx).pos;
+        let pass_size = cx.current_pass_size();
+        let caret = Rect {
+            pos: self.viewport_rect.pos - area_pos + cursor_pos,
+            size: self.cell_size,
+        };
+        let mut shift = dvec2(caret.pos.x, caret.pos.y + caret.size.y);
+        if area_pos.y + shift.y + size.y > pass_size.y {
+            shift.y = caret.pos.y - size.y;
+        }
+        shift.x = shift
+            .x
+            .min(pass_size.x - size.x - area_pos.x)
+            .max(-area_pos.x);
+        shift.y = shift.y.max(-area_pos.y);
+
+        self.draw_popup_bg
+            .begin(cx, Walk::fixed_size(size), Layout::default());
+        let origin = cx.turtle().rect().pos + dvec2(0.0, 2.0);
+        // the same size as the code, so the words line up with the one being typed
+        self.draw_popup_text.text_style.font_size = self.draw_text.text_style.font_size;
+        for (row, item) in list.items[list.first..list.first + rows].iter().enumerate() {
+            let pos = origin + dvec2(0.0, row as f64 * self.cell_size.y);
+            if list.first + row == list.selected {
+                self.draw_popup_selected.draw_abs(
+                    cx,
+                    Rect {
+                        pos,
+                        size: dvec2(size.x, self.cell_size.y),
+                    },
+                );
+            }
+            self.draw_popup_text
+                .draw_abs(cx, pos + dvec2(self.cell_size.x, 0.0), item);
+            if let Some(completion) = list.completions.get(list.first + row) {
+                let x = (columns - kind_columns + 1) as f64 * self.cell_size.x;
+                self.draw_popup_text
+                    .draw_abs(cx, pos + dvec2(x, 0.0), completion.kind.name());
+            }
+        }
+        self.draw_popup_bg.end(cx);
+
+        cx.end_pass_sized_turtle_with_shift(area, shift);
+        self.popup_draw_list.end(cx);
+    }
+
+    /// Shows the signature of the function whose call the cursor is in. Opens it if `open` is
+    /// set, otherwise only updates it if it is already open, for instance to move on to the next
+    /// parameter.
+    fn update_signature_help(&mut self, cx: &mut Cx, session: &Session, open: bool) {
+        if self.read_only || (!open && self.signature_help.is_none()) {
+            return;
+        }
+        let position = {
+            let selections = session.selections();
+            if selections.len() != 1 {
+                return self.close_signature_help(cx);
+            }
+            selections[0].cursor.position
+        };
+        let signature_help = self
+            .completion_engine
+            .signature_help(&session.document().as_text(), position);
+        if signature_help.is_none() {
+            return self.close_signature_help(cx);
+        }
+        if signature_help != self.signature_help {
+            self.signature_help = signature_help;
+            self.signature_draw_list.redraw(cx);
+        }
+    }
+
+    fn close_signature_help(&mut self, cx: &mut Cx) {
+        if self.signature_help.take().is_some() {
+            self.signature_draw_list.redraw(cx);
+            self.redraw(cx);
+        }
+    }
+
+    /// Draws the preedit text of an input method over the code at the cursor, underlined the way
+    /// the input method asks for, and puts its candidate window below the cursor.
+    fn draw_composition(&mut self, cx: &mut Cx2d) {
+        let Some(cursor_pos) = self.last_cursor_screen_pos else {
+            return;
+        };
+        let area = self.scroll_bars.area();
+        if !cx.has_key_focus(area) || self.read_only {
+            return;
+        }
+        let area_pos = area.rect(cx).pos;
+        let caret_pos = self.viewport_rect.pos - area_pos + cursor_pos;
+        cx.show_text_ime(area, caret_pos + dvec2(0.0, self.cell_size.y));
+        let Some(composition) = &self.composition else {
+            return;
+        };
+        if composition.preedit.is_empty() {
+            return;
+        }
+        let preedit = &composition.preedit;
+        let size = dvec2(
+            preedit.column_count() as f64 * self.cell_size.x,
+            self.cell_size.y,
+        );
+
+        self.composition_draw_list.begin_overlay_reuse(cx);
+        cx.begin_pass_sized_turtle(Layout::flow_down());
+        self.draw_composition_bg
+            .begin(cx, Walk::fixed_size(size), Layout::default());
+        let origin = cx.turtle().rect().pos;
+        self.draw_composition_text.text_style.font_size = self.draw_text.text_style.font_size;
+        self.draw_composition_text.draw_abs(cx, origin, preedit);
+        let column_x = |byte_index: usize| preedit[..byte_index].column_count() as f64;
+        for underline in &composition.underlines {
+            let thickness = if underline.thick { 2.0 } else { 1.0 };
+            let start_x = column_x(underline.start.min(preedit.len()));
+            let end_x = column_x(underline.end.min(preedit.len()));
+            self.draw_composition_underline.draw_abs(
+                cx,
+                Rect {
+                    // leave a gap between clauses that follow each other
+                    pos: origin + dvec2(start_x * self.cell_size.x + 1.0, size.y - thickness),
+                    size: dvec2((end_x - start_x) * self.cell_size.x - 2.0, thickness),
+                },
+            );
+        }
+        let cursor_x = column_x(composition.cursor.min(preedit.len()));
+        self.draw_composition_underline.draw_abs(
+            cx,
+            Rect {
+                pos: origin + dvec2(cursor_x * self.cell_size.x, 0.0),
+                size: dvec2(2.0, size.y),
+            },
+        );
+        self.draw_composition_bg.end(cx);
+        cx.end_pass_sized_turtle_with_shift(area, caret_pos);
+        self.composition_draw_list.end(cx);
+    }
+
+    fn draw_signature_help(&mut self, cx: &mut Cx2d) {
+        let Some(signature_help) = &self.signature_help else {
+            return;
+        };
+        let Some(cursor_pos) = self.last_cursor_screen_pos else {
+            return;
+        };
+        let label = &signature_help.signature.label;
+        let size = dvec2(
+            (label.column_count() + 2) as f64 * self.cell_size.x,
+            self.cell_size.y + 6.0,
+        );
+
+        self.signature_draw_list.begin_overlay_reuse(cx);
+        cx.begin_pass_sized_turtle(Layout::flow_down());
+
+        let area = self.scroll_bars.area();
+        let area_pos = area.rect(cx).pos;
+        let pass_size = cx.current_pass_size();
+        let caret = Rect {
+            pos: self.viewport_rect.pos - area_pos + cursor_pos,
+            size: self.cell_size,
+        };
+        // above the cursor, so it doesn't cover the completions
+        let mut shift = dvec2(caret.pos.x, caret.pos.y - size.y);
+        if area_pos.y + shift.y < 0.0 {
+            shift.y = caret.pos.y + caret.size.y;
+        }
+        shift.x = shift
+            .x
+            .min(pass_size.x - size.x - area_pos.x)
+            .max(-area_pos.x);
+
+        self.draw_signature_bg.begin(
+            cx,
+            Walk::fixed_size(size),
+            Layout {
+                padding: Padding {
+                    left: self.cell_size.x,
+                    ..Padding::default()
+                },
+                align: Align { x: 0.0, y: 0.5 },
+                ..Layout::flow_right()
+            },
+        );
+        self.draw_signature_text.text_style.font_size = self.draw_text.text_style.font_size;
+        self.draw_signature_active.text_style.font_size = self.draw_text.text_style.font_size;
+        let active = signature_help
+            .active_parameter
+            .map_or(label.len()..label.len(), |index| {
+                signature_help.signature.parameters[index].clone()
+            });
+        let align = Align { x: 0.0, y: 0.5 };
+        self.draw_signature_text
+            .draw_walk(cx, Walk::fit(), align, &label[..active.start]);
+        if !active.is_empty() {
+            self.draw_signature_active
+                .draw_walk(cx, Walk::fit(), align, &label[active.clone()]);
+        }
+        self.draw_signature_text
+            .draw_walk(cx, Walk::fit(), align, &label[active.end..]);
+        self.draw_signature_bg.end(cx);
+
+        cx.end_pass_sized_turtle_with_shift(area, shift);
+        self.signature_draw_list.end(cx);
+    }
+
+    /// Shows the messages of the diagnostics under the mouse at `abs`, or of those on the line
+    /// if it is over the gutter.
+    fn update_diagnostic_tooltip(&mut self, cx: &mut Cx, session: &Session, abs: DVec2) {
+        let ((position, _), is_in_gutter) = self.pick(session, abs);
+        let lines: Vec<String> = {
+            let diagnostics = session.document().diagnostics();
+            let diagnostics = if is_in_gutter || abs.x < self.viewport_rect.pos.x {
+                diagnostics.on_line(position.line_index)
+            } else {
+                diagnostics.at(position)
+            };
+            diagnostics
+                .into_iter()
+                .flat_map(|diagnostic| {
+                    let mut lines: Vec<String> = diagnostic
+                        .message
+                        .lines()
+                        .map(|line| line.to_string())
+                        .collect();
+                    if let (Some(source), Some(first)) = (&diagnostic.source, lines.first_mut()) {
+                        *first = format!("{}: {}", source, first);
+                    }
+                    lines
+                })
+                .collect()
+        };
+        if lines.is_empty() {
+            return self.close_diagnostic_tooltip(cx);
+        }
+        let pos = abs - self.scroll_bars.area().rect(cx).pos;
+        if self
+            .diagnostic_tooltip
+            .as_ref()
+            .map_or(true, |tooltip| tooltip.lines != lines)
+        {
+            self.diagnostic_tooltip = Some(DiagnosticTooltip { lines, pos });
+            self.tooltip_draw_list.redraw(cx);
+        }
+    }
+
+    fn close_diagnostic_tooltip(&mut self, cx: &mut Cx) {
+        if self.diagnostic_tooltip.take().is_some() {
+            self.tooltip_draw_list.redraw(cx);
+            self.redraw(cx);
+        }
+    }
+
+    fn draw_diagnostic_tooltip(&mut self, cx: &mut Cx2d) {
+        let Some(tooltip) = &self.diagnostic_tooltip else {
+            return;
+        };
+        let column_count = tooltip
+            .lines
+            .iter()
+            .map(|line| line.column_count())
+            .max()
+            .unwrap_or(0);
+        let size = dvec2(
+            (column_count + 2) as f64 * self.cell_size.x,
+            tooltip.lines.len() as f64 * self.cell_size.y + 6.0,
+        );
+
+        self.tooltip_draw_list.begin_overlay_reuse(cx);
+        cx.begin_pass_sized_turtle(Layout::flow_down());
+
+        let area = self.scroll_bars.area();
+        let area_pos = area.rect(cx).pos;
+        let pass_size = cx.current_pass_size();
+        // below the mouse, or above it if there is no room
+        let mut shift = dvec2(tooltip.pos.x, tooltip.pos.y + self.cell_size.y);
+        if area_pos.y + shift.y + size.y > pass_size.y {
+            shift.y = tooltip.pos.y - size.y;
+        }
+        shift.x = shift
+            .x
+            .min(pass_size.x - size.x - area_pos.x)
+            .max(-area_pos.x);
+
+        self.draw_signature_bg.begin(
+            cx,
+            Walk::fixed_size(size),
+            Layout {
+                padding: Padding {
+                    left: self.cell_size.x,
+                    top: 3.0,
+                    ..Padding::default()
+                },
+                ..Layout::flow_down()
+            },
+        );
+        self.draw_signature_text.text_style.font_size = self.draw_text.text_style.font_size;
+        for line in &tooltip.lines {
+            self.draw_signature_text
+                .draw_walk(cx, Walk::fit(), Align::default(), line);
+        }
+        self.draw_signature_bg.end(cx);
+
+        cx.end_pass_sized_turtle_with_shift(area, shift);
+        self.tooltip_draw_list.end(cx);
+    }
+
+    pub fn scroll_position(&mut self, session: &Session) -> ScrollPosition {
+        let scroll_y = self.scroll_bars.get_scroll_pos().y;
+        let max_scroll_y = (self.scroll_bars.get_scroll_view_total().y
+            - self.scroll_bars.get_scroll_view_visible().y)
+            .max(0.0);
+        let layout = session.layout();
+        let y = scroll_y / self.cell_size.y;
+        let line_index = layout.find_first_line_ending_after_y(y);
+        let line = layout.line(line_index);
+        ScrollPosition {
+            line: line_index as f64 + ((y - line.y()) / line.height()).clamp(0.0, 1.0),
+            ratio: if max_scroll_y > 0.0 {
+                scroll_y / max_scroll_y
+            } else {
+                0.0
+            },
+        }
+    }
+
+    /// Scrolls to where a linked view wants this one to be.
+    pub fn scroll_to_target(&mut self, cx: &mut Cx, session: &Session, target: ScrollTarget) {
+        let scroll_y = match target {
+            ScrollTarget::Ratio(ratio) => {
+                let max_scroll_y = (self.scroll_bars.get_scroll_view_total().y
+                    - self.scroll_bars.get_scroll_view_visible().y)
+                    .max(0.0);
+                ratio.clamp(0.0, 1.0) * max_scroll_y
+            }
+            ScrollTarget::Line(line) => self.line_scroll_y(session, line),
+        };
+        let scroll_pos = self.scroll_bars.get_scroll_pos();
+        self.scroll_bars.set_scroll_pos(cx, dvec2(scroll_pos.x, scroll_y));
+        self.redraw(cx);
+    }
+
+    /// How far to scroll down to have `line` at the top, see `ScrollPosition::line`.
+    fn line_scroll_y(&self, session: &Session, line: f64) -> f64 {
+        let layout = session.layout();
+        let line_count = layout.as_text().as_lines().len();
+        let line = line.max(0.0);
+        let line_index = (line as usize).min(line_count - 1);
+        let fraction = (line - line_index as f64).min(1.0);
+        let layout_line = layout.line(line_index);
+        (layout_line.y() + fraction * layout_line.height()) * self.cell_size.y
+    }
+
+    /// Where the selections of `session` are and how far it is scrolled, to be restored with
+    /// `restore_session_state` after a restart.
+    pub fn session_state(&mut self, session: &Session) -> SessionState {
+        let scroll_line = match self.restore_scroll_line {
+            Some(line) => line,
+            // never drawn, so never scrolled either
+            None if self.cell_size.y == 0.0 => 0.0,
+            None => self.scroll_position(session).line,
+        };
+        SessionState {
+            selections: session
+                .selections()
+                .iter()
+                .map(|selection| SavedSelection::from_selection(*selection))
+                .collect(),
+            scroll_line,
+        }
+    }
+
+    pub fn restore_session_state(
+        &mut self,
+        cx: &mut Cx,
+        session: &mut Session,
+        state: &SessionState,
+    ) {
+        for (index, selection) in state.selections.iter().enumerate() {
+            let (anchor, cursor) = selection.positions(session.document().as_text().as_lines());
+            if index == 0 {
+                session.set_selection(
+                    anchor,
+                    Affinity::Before,
+                    SelectionMode::Simple,
+                    NewGroup::No,
+                );
+            } else {
+                session.add_selection(anchor, Affinity::Before, SelectionMode::Simple);
+            }
+            session.move_to(cursor, Affinity::Before, NewGroup::No);
+        }
+        self.restore_scroll_line = Some(state.scroll_line);
+        self.redraw(cx);
+    }
+
     pub fn set_cursor_and_scroll(
         &mut self,
         cx: &mut Cx,
@@ -535,12 +1360,509 @@ impl CodeEditor {
         }
     }
 
+    /// Sets the font size, clamped to the range the font size commands use.
+    pub fn set_font_size(&mut self, font_size: f64) {
+        let font_size = font_size.clamp(3.0, 20.0);
+        if self.draw_text.text_style.font_size != font_size {
+            self.draw_text.text_style.font_size = font_size;
+            self.draw_gutter.text_style.font_size = font_size;
+            if let Some(pos) = self.last_cursor_screen_pos {
+                self.keep_cursor_in_view = KeepCursorInView::FontResize(pos);
+            }
+        }
+    }
+
     pub fn reset_cursor_blinker(&mut self, cx: &mut Cx) {
         self.animator_cut(cx, id!(blink.off));
         cx.stop_timer(self.blink_timer);
         self.blink_timer = cx.start_timeout(self.blink_speed)
     }
 
+    /// Runs `command`, which keys in `Settings::key_bindings` are bound to. Returns whether it
+    /// moved the cursor.
+    fn run_command(
+        &mut self,
+        cx: &mut Cx,
+        session: &mut Session,
+        command: EditorCommand,
+        actions: &mut Vec<CodeEditorAction>,
+    ) -> bool {
+        let is_vim_command_mode = self.vim_emulation && self.vim.mode() != VimMode::Insert;
+        if (self.read_only || is_vim_command_mode) && command.is_edit() {
+            return false;
+        }
+        let page_line_count = (self.line_end - self.line_start).saturating_sub(3);
+        let mut text_did_change = false;
+        match command {
+            EditorCommand::MoveLeft => session.move_left(true),
+            EditorCommand::MoveRight => session.move_right(true),
+            EditorCommand::MoveUp => session.move_up(true),
+            EditorCommand::MoveDown => session.move_down(true),
+            EditorCommand::MoveUpLine => session.move_up_line(true),
+            EditorCommand::MoveDownLine => session.move_down_line(true),
+            EditorCommand::MoveHome => session.home(true),
+            EditorCommand::MoveEnd => session.end(true),
+            EditorCommand::MoveToMatchingDelimiter => session.move_to_matching_delimiter(true),
+            EditorCommand::SelectLeft => session.move_left(false),
+            EditorCommand::SelectRight => session.move_right(false),
+            EditorCommand::SelectUp => session.move_up(false),
+            EditorCommand::SelectDown => session.move_down(false),
+            EditorCommand::SelectUpLine => session.move_up_line(false),
+            EditorCommand::SelectDownLine => session.move_down_line(false),
+            EditorCommand::SelectHome => session.home(false),
+            EditorCommand::SelectEnd => session.end(false),
+            EditorCommand::MovePageUp | EditorCommand::SelectPageUp => {
+                for _ in 0..page_line_count {
+                    session.move_up(command == EditorCommand::MovePageUp);
+                }
+            }
+            EditorCommand::MovePageDown | EditorCommand::SelectPageDown => {
+                for _ in 0..page_line_count {
+                    session.move_down(command == EditorCommand::MovePageDown);
+                }
+            }
+            EditorCommand::SelectEnclosing => session.select_enclosing(),
+            EditorCommand::ColumnSelectUp => session.move_column_selection(-1, 0),
+            EditorCommand::ColumnSelectDown => session.move_column_selection(1, 0),
+            EditorCommand::MoveElementLeft if session.is_column_selecting() => {
+                session.move_column_selection(0, -1)
+            }
+            EditorCommand::MoveElementRight if session.is_column_selecting() => {
+                session.move_column_selection(0, 1)
+            }
+            EditorCommand::MoveElementLeft => text_did_change = session.move_element_left(),
+            EditorCommand::MoveElementRight => text_did_change = session.move_element_right(),
+            EditorCommand::TransposeElements => {
+                if !session.transpose_elements() {
+                    return false;
+                }
+                text_did_change = true;
+            }
+            EditorCommand::Fold | EditorCommand::Unfold => {
+                let index = session.last_added_selection_index().unwrap_or(0);
+                let position = session.selections()[index].cursor.position;
+                let changed = if command == EditorCommand::Fold {
+                    session.fold_at(position)
+                } else {
+                    session.unfold_at(position)
+                };
+                if changed {
+                    self.redraw(cx);
+                }
+                return false;
+            }
+            EditorCommand::Enter => {
+                session.enter();
+                self.vim.record_text("\n");
+                text_did_change = true;
+            }
+            EditorCommand::Indent => {
+                session.indent();
+                text_did_change = true;
+            }
+            EditorCommand::Outdent => {
+                session.outdent();
+                text_did_change = true;
+            }
+            EditorCommand::Delete => {
+                session.delete();
+                text_did_change = true;
+            }
+            EditorCommand::Backspace => {
+                session.backspace();
+                self.vim.record_backspace();
+                text_did_change = true;
+            }
+            EditorCommand::ToggleComment => {
+                session.toggle_comment();
+                text_did_change = true;
+            }
+            EditorCommand::Undo | EditorCommand::Redo => {
+                let done = if command == EditorCommand::Undo {
+                    session.undo()
+                } else {
+                    session.redo()
+                };
+                if !done {
+                    return false;
+                }
+                cx.redraw_all();
+                text_did_change = true;
+            }
+            // the platform follows up with the actual paste as a text input event
+            EditorCommand::PasteReindented => {
+                self.paste_filter = Some(Box::new(Reindent));
+                return false;
+            }
+            EditorCommand::PasteEscaped => {
+                self.paste_filter = Some(Box::new(EscapedString));
+                return false;
+            }
+            EditorCommand::PasteRaw => {
+                self.paste_filter = Some(Box::new(RawString));
+                return false;
+            }
+            EditorCommand::FindNext | EditorCommand::FindPrevious => {
+                let found = if command == EditorCommand::FindNext {
+                    session.find_next()
+                } else {
+                    session.find_previous()
+                };
+                if !found {
+                    return false;
+                }
+            }
+            EditorCommand::OpenCompletions => {
+                self.update_completions(cx, session, true);
+                return false;
+            }
+            EditorCommand::OpenCodeActions => {
+                self.close_popup(cx);
+                actions.push(CodeEditorAction::CodeActionsRequested);
+                return false;
+            }
+            EditorCommand::IncreaseFontSize
+            | EditorCommand::DecreaseFontSize
+            | EditorCommand::ResetFontSize => {
+                match command {
+                    EditorCommand::IncreaseFontSize => self.increase_font_size(),
+                    EditorCommand::DecreaseFontSize => self.decrease_font_size(),
+                    _ => self.reset_font_size(),
+                }
+                self.redraw(cx);
+                return false;
+            }
+            EditorCommand::ToggleWordWrap => {
+                self.word_wrap = !self.word_wrap;
+                self.redraw(cx);
+                return false;
+            }
+        }
+        if text_did_change {
+            actions.push(CodeEditorAction::TextDidChange);
+        }
+        self.redraw(cx);
+        true
+    }
+
+    /// Runs a command of the Vim emulation with the edits and moves of the session, so it can
+    /// be undone like any other edit. Returns whether it moved the cursor.
+    fn run_vim_command(
+        &mut self,
+        cx: &mut Cx,
+        session: &mut Session,
+        command: VimCommand,
+        actions: &mut Vec<CodeEditorAction>,
+    ) -> bool {
+        if self.read_only && command.is_edit() {
+            if self.vim.mode() == VimMode::Insert {
+                self.vim.escape();
+            }
+            return false;
+        }
+        let index = session.last_added_selection_index().unwrap_or(0);
+        let selection = session.selections()[index];
+        let cursor = selection.cursor.position;
+        let mut text_did_change = false;
+        match command {
+            VimCommand::Move { motion, count } => {
+                let target = {
+                    let text = session.document().as_text();
+                    vim::motion_target(text.as_lines(), cursor, motion, count)
+                };
+                if self.vim.mode() == VimMode::Visual {
+                    session.move_to(target, Affinity::Before, NewGroup::No);
+                } else {
+                    set_cursor(session, target);
+                }
+            }
+            VimCommand::Operate {
+                operator,
+                motion,
+                count,
+            } => {
+                let (start, end, is_linewise) = {
+                    let text = session.document().as_text();
+                    vim::operator_range(text.as_lines(), cursor, motion, count, operator)
+                };
+                text_did_change = self.vim_operate(session, operator, start, end, is_linewise);
+            }
+            VimCommand::OperateLines { operator, count } => {
+                let (start, end) = {
+                    let text = session.document().as_text();
+                    vim::line_range(text.as_lines(), cursor.line_index, count, operator)
+                };
+                text_did_change = self.vim_operate(session, operator, start, end, true);
+            }
+            VimCommand::OperateSelection { operator } => {
+                // the char under the cursor is selected too, like in Vim
+                let start = selection.start();
+                let end = selection.end();
+                let end = Position {
+                    line_index: end.line_index,
+                    byte_index: vim::next_char_index(
+                        &session.document().as_text().as_lines()[end.line_index],
+                        end.byte_index,
+                    ),
+                };
+                text_did_change = self.vim_operate(session, operator, start, end, false);
+            }
+            VimCommand::Insert(insert_at) => {
+                let line = session.document().as_text().as_lines()[cursor.line_index].clone();
+                let line_start = Position {
+                    line_index: cursor.line_index,
+                    byte_index: 0,
+                };
+                match insert_at {
+                    InsertAt::Cursor => {}
+                    InsertAt::AfterCursor => set_cursor(
+                        session,
+                        Position {
+                            line_index: cursor.line_index,
+                            byte_index: vim::next_char_index(&line, cursor.byte_index),
+                        },
+                    ),
+                    InsertAt::LineStart => set_cursor(
+                        session,
+                        Position {
+                            line_index: cursor.line_index,
+                            byte_index: vim::first_non_blank_index(&line),
+                        },
+                    ),
+                    InsertAt::LineEnd => set_cursor(
+                        session,
+                        Position {
+                            line_index: cursor.line_index,
+                            byte_index: line.len(),
+                        },
+                    ),
+                    InsertAt::LineBelow => {
+                        set_cursor(
+                            session,
+                            Position {
+                                line_index: cursor.line_index,
+                                byte_index: line.len(),
+                            },
+                        );
+                        session.enter();
+                        text_did_change = true;
+                    }
+                    InsertAt::LineAbove => {
+                        let indent = line.indent().unwrap_or("").to_string();
+                        set_cursor(session, line_start);
+                        session.paste(format!("{}\n", indent).into());
+                        set_cursor(
+                            session,
+                            Position {
+                                line_index: cursor.line_index,
+                                byte_index: indent.len(),
+                            },
+                        );
+                        text_did_change = true;
+                    }
+                }
+            }
+            VimCommand::Put { before, count } => {
+                let register = self.vim.register().clone();
+                if register.text.is_empty() {
+                    return false;
+                }
+                let text = register.text.repeat(count);
+                if register.is_linewise {
+                    let (line_count, line_len) = {
+                        let text = session.document().as_text();
+                        let lines = text.as_lines();
+                        (lines.len(), lines[cursor.line_index].len())
+                    };
+                    let line_index = if before {
+                        cursor.line_index
+                    } else {
+                        cursor.line_index + 1
+                    };
+                    if line_index < line_count {
+                        set_cursor(
+                            session,
+                            Position {
+                                line_index,
+                                byte_index: 0,
+                            },
+                        );
+                        session.paste(text.into());
+                    } else {
+                        // there is no line below to put the lines before
+                        set_cursor(
+                            session,
+                            Position {
+                                line_index: cursor.line_index,
+                                byte_index: line_len,
+                            },
+                        );
+                        session.paste(format!("\n{}", text.trim_end_matches('\n')).into());
+                    }
+                    let text = session.document().as_text();
+                    let byte_index = vim::first_non_blank_index(&text.as_lines()[line_index]);
+                    drop(text);
+                    set_cursor(
+                        session,
+                        Position {
+                            line_index,
+                            byte_index,
+                        },
+                    );
+                } else {
+                    if !before {
+                        let byte_index = vim::next_char_index(
+                            &session.document().as_text().as_lines()[cursor.line_index],
+                            cursor.byte_index,
+                        );
+                        set_cursor(
+                            session,
+                            Position {
+                                line_index: cursor.line_index,
+                                byte_index,
+                            },
+                        );
+                    }
+                    session.paste(text.into());
+                    self.vim_step_back(session);
+                }
+                text_did_change = true;
+            }
+            VimCommand::Undo { count } | VimCommand::Redo { count } => {
+                let is_undo = matches!(command, VimCommand::Undo { .. });
+                for _ in 0..count {
+                    let done = if is_undo {
+                        session.undo()
+                    } else {
+                        session.redo()
+                    };
+                    if !done {
+                        break;
+                    }
+                    text_did_change = true;
+                }
+                if !text_did_change {
+                    return false;
+                }
+                cx.redraw_all();
+            }
+            VimCommand::EnterVisual | VimCommand::LeaveVisual => set_cursor(session, cursor),
+            VimCommand::LeaveInsert => self.vim_step_back(session),
+            VimCommand::Repeat(change) => {
+                let change = *change;
+                let enters_insert = change.command.enters_insert();
+                self.run_vim_command(cx, session, change.command, actions);
+                if !change.text.is_empty() {
+                    session.paste(change.text.as_str().into());
+                }
+                if enters_insert {
+                    self.vim_step_back(session);
+                }
+                text_did_change = true;
+            }
+        }
+        if text_did_change {
+            actions.push(CodeEditorAction::TextDidChange);
+        }
+        self.redraw(cx);
+        true
+    }
+
+    /// Deletes, changes or yanks the text from `start` to `end`, and puts it in the register.
+    /// Returns whether the text changed.
+    fn vim_operate(
+        &mut self,
+        session: &mut Session,
+        operator: Operator,
+        start: Position,
+        end: Position,
+        is_linewise: bool,
+    ) -> bool {
+        let cursor = session.selections()[session.last_added_selection_index().unwrap_or(0)]
+            .cursor
+            .position;
+        set_cursor(session, start);
+        session.move_to(end, Affinity::Before, NewGroup::No);
+        let text = if is_linewise {
+            // whole lines, whether or not the range started at the end of the line before
+            let text = session.document().as_text();
+            let lines = text.as_lines();
+            let first_line_index = if start.byte_index == lines[start.line_index].len()
+                && start.line_index < end.line_index
+            {
+                start.line_index + 1
+            } else {
+                start.line_index
+            };
+            let last_line_index = if end.byte_index == 0 && end.line_index > first_line_index {
+                end.line_index - 1
+            } else {
+                end.line_index
+            };
+            let mut string = lines[first_line_index..=last_line_index].join("\n");
+            string.push('\n');
+            string
+        } else {
+            session.copy()
+        };
+        self.vim.set_register(text, is_linewise);
+        if operator == Operator::Yank {
+            let position = if is_linewise && cursor.line_index == start.line_index {
+                cursor
+            } else {
+                start.min(cursor)
+            };
+            set_cursor(session, position);
+            return false;
+        }
+        let text_did_change = start != end;
+        if text_did_change {
+            session.delete();
+        }
+        if operator == Operator::Delete {
+            let text = session.document().as_text();
+            let lines = text.as_lines();
+            let line_index = start.line_index.min(lines.len() - 1);
+            let line = &lines[line_index];
+            let byte_index = if is_linewise {
+                vim::first_non_blank_index(line)
+            } else {
+                start.byte_index.min(vim::last_char_index(line))
+            };
+            drop(text);
+            set_cursor(
+                session,
+                Position {
+                    line_index,
+                    byte_index,
+                },
+            );
+        }
+        text_did_change
+    }
+
+    /// Moves the cursor back onto the char before it, where Vim leaves it after typing or
+    /// putting text.
+    fn vim_step_back(&mut self, session: &mut Session) {
+        let cursor = session.selections()[session.last_added_selection_index().unwrap_or(0)]
+            .cursor
+            .position;
+        let byte_index = {
+            let text = session.document().as_text();
+            text.as_lines()[cursor.line_index][..cursor.byte_index]
+                .char_indices()
+                .next_back()
+                .map_or(0, |(byte_index, _)| byte_index)
+        };
+        set_cursor(
+            session,
+            Position {
+                line_index: cursor.line_index,
+                byte_index,
+            },
+        );
+    }
+
     pub fn handle_event(
         &mut self,
         cx: &mut Cx,
@@ -551,10 +1873,12 @@ impl CodeEditor {
         
         self.animator_handle_event(cx, event);
 
+        session.document().set_time(cx.seconds_since_app_start());
         session.handle_changes();
 
         if self.scroll_bars.handle_event(cx, event).len()>0{
             self.redraw(cx);
+            actions.push(CodeEditorAction::Scrolled);
         };
         
         if self.blink_timer.is_event(event).is_some() {
@@ -565,180 +1889,190 @@ impl CodeEditor {
             }
             self.blink_timer = cx.start_timeout(self.blink_speed)
         }
-        let mut keyboard_moved_cursor = false;
-        match event.hits(cx, self.scroll_bars.area()) {
-            Hit::KeyFocusLost(_) => {
-                self.animator_play(cx, id!(focus.off));
-            }
-            Hit::KeyFocus(_) => {
-                self.animator_play(cx, id!(focus.on));
-            }
-            Hit::KeyDown(KeyEvent {
-                key_code: KeyCode::Escape,
-                is_repeat: false,
-                ..
-            }) => {
-                session.fold();
-                if !self.keep_cursor_in_view.is_locked() {
-                    self.keep_cursor_in_view = KeepCursorInView::LockStart;
+        if self.emoji_picker_open {
+            let global = cx.global::<EmojiPickerGlobal>().clone();
+            let mut map = global.map.borrow_mut();
+            let picker = map.get_mut(&self.emoji_picker.unwrap()).unwrap();
+            let mut picked = None;
+            let mut close = false;
+            picker.handle_event_with(cx, event, self.scroll_bars.area(), &mut |_, action| {
+                match action {
+                    EmojiPickerAction::Selected(symbol) => picked = Some(symbol),
+                    EmojiPickerAction::Closed => close = true,
+                    _ => (),
                 }
-                self.redraw(cx);
+            });
+            if let Some(symbol) = picked {
+                session.insert(symbol.into());
+                self.keep_cursor_in_view = KeepCursorInView::Once;
+                actions.push(CodeEditorAction::TextDidChange);
+                close = true;
             }
-            Hit::KeyUp(KeyEvent {
-                key_code: KeyCode::Escape,
-                ..
-            }) => {
-                session.unfold();
-                if !self.keep_cursor_in_view.is_locked() {
-                    self.keep_cursor_in_view = KeepCursorInView::LockStart;
-                }
-                self.redraw(cx);
+            if close {
+                self.set_emoji_picker_closed(cx);
             }
-            Hit::KeyDown(KeyEvent {
-                key_code: KeyCode::Minus,
-                modifiers: KeyModifiers { control, logo, .. },
-                ..
-            }) => {
-                if control || logo {
-                    self.decrease_font_size();
-                    self.redraw(cx);
+            // the picker gets the key input while it is open
+            if self.has_key_focus(cx) {
+                if let Event::KeyDown(_) | Event::KeyUp(_) | Event::TextInput(_) = event {
+                    return actions;
                 }
             }
-            Hit::KeyDown(KeyEvent {
-                key_code: KeyCode::Key0,
-                modifiers: KeyModifiers { control, logo, .. },
-                ..
-            }) => {
-                if control || logo {
-                    self.reset_font_size();
-                    self.redraw(cx);
+        }
+        if self.popup.is_some() && self.has_key_focus(cx) {
+            if let Event::KeyDown(KeyEvent { key_code, .. }) = event {
+                match key_code {
+                    KeyCode::ArrowUp => {
+                        self.select_popup_item(cx, -1);
+                        return actions;
+                    }
+                    KeyCode::ArrowDown => {
+                        self.select_popup_item(cx, 1);
+                        return actions;
+                    }
+                    KeyCode::ReturnKey | KeyCode::Tab => {
+                        self.accept_popup_item(cx, session, &mut actions);
+                        return actions;
+                    }
+                    KeyCode::Escape => {
+                        self.close_popup(cx);
+                        return actions;
+                    }
+                    _ => {}
                 }
             }
-            Hit::KeyDown(KeyEvent {
-                key_code: KeyCode::Equals,
-                modifiers: KeyModifiers { control, logo, .. },
+        }
+        if self.signature_help.is_some() && self.has_key_focus(cx) {
+            if let Event::KeyDown(KeyEvent {
+                key_code: KeyCode::Escape,
                 ..
-            }) => {
-                if control || logo {
-                    self.increase_font_size();
-                    self.redraw(cx);
-                }
+            }) = event
+            {
+                self.close_signature_help(cx);
+                return actions;
             }
-            Hit::KeyDown(KeyEvent {
-                key_code: KeyCode::KeyW,
-                modifiers: KeyModifiers { control, logo, .. },
-                ..
-            }) => {
-                if control || logo {
-                    self.word_wrap = !self.word_wrap;
-                    self.redraw(cx);
-                }
+        }
+        let mut keyboard_moved_cursor = false;
+        let mut typed = false;
+        let was_completing = self.is_completion_open();
+        let vim_mode = self.vim.mode();
+        match event.hits_with_options(
+            cx,
+            self.scroll_bars.area(),
+            HitOptions::new().with_multi_touch(true),
+        ) {
+            Hit::KeyFocusLost(_) => {
+                self.pending_chords.clear();
+                self.composition = None;
+                self.close_popup(cx);
+                self.close_signature_help(cx);
+                self.set_emoji_picker_closed(cx);
+                self.animator_play(cx, id!(focus.off));
+                actions.push(CodeEditorAction::KeyFocusLost);
             }
-            Hit::KeyDown(KeyEvent {
-                key_code: KeyCode::KeyA,
-                modifiers: KeyModifiers {control, logo, ..},
-                ..
-            }) => {
-                if control || logo {
-                    //session.select_all();
-                    self.redraw(cx);
-                }
+            Hit::KeyFocus(_) => {
+                self.animator_play(cx, id!(focus.on));
             }
-            Hit::KeyDown(KeyEvent {
-                key_code: KeyCode::ArrowLeft,
-                modifiers:
-                    KeyModifiers {
-                        shift,
-                        control,
-                        logo,
-                        ..
-                    },
-                ..
-            }) => {
-                if control || logo {
-                    //session.move_to_start_of_line(!shift);
-                } else {
-                    session.move_left(!shift);
-                }
-                keyboard_moved_cursor = true;
-                self.redraw(cx);
+            Hit::KeyDown(ke) if is_emoji_picker_key(&ke) => {
+                self.set_emoji_picker_open(cx);
             }
             Hit::KeyDown(KeyEvent {
-                key_code: KeyCode::ArrowRight,
-                modifiers:
-                    KeyModifiers {
-                        shift,
-                        control,
-                        logo,
-                        ..
-                    },
+                key_code: KeyCode::Escape,
                 ..
-            }) => {
-                if control || logo {
-                    //session.move_to_end_of_line(!shift);
-                } else {
-                    session.move_right(!shift);
+            }) if self.vim_emulation
+                && (self.vim.mode() != VimMode::Normal || self.vim.is_pending()) =>
+            {
+                if let Some(command) = self.vim.escape() {
+                    keyboard_moved_cursor =
+                        self.run_vim_command(cx, session, command, &mut actions);
                 }
-
-                keyboard_moved_cursor = true;
-                self.redraw(cx);
             }
             Hit::KeyDown(KeyEvent {
-                key_code: KeyCode::ArrowUp,
-                modifiers: KeyModifiers { shift, .. },
+                key_code: KeyCode::KeyR,
+                modifiers: KeyModifiers { control: true, .. },
                 ..
-            }) => {
-                session.move_up(!shift);
-                keyboard_moved_cursor = true;
-                self.redraw(cx);
+            }) if self.vim_emulation && self.vim.mode() == VimMode::Normal => {
+                if let Some(command) = self.vim.handle_control_char('r') {
+                    keyboard_moved_cursor =
+                        self.run_vim_command(cx, session, command, &mut actions);
+                }
             }
-            Hit::KeyDown(KeyEvent {
-                key_code: KeyCode::ArrowDown,
-                modifiers: KeyModifiers { shift, .. },
-                ..
-            }) => {
-                session.move_down(!shift);
-                keyboard_moved_cursor = true;
-                self.redraw(cx);
+            Hit::TextInput(_) if self.read_only && !self.vim_emulation => {}
+            Hit::TextCut(ce) if self.read_only => {
+                *ce.response.borrow_mut() = Some(session.copy());
             }
             Hit::KeyDown(KeyEvent {
-                key_code: KeyCode::Home,
-                modifiers: KeyModifiers { shift, .. },
+                key_code: KeyCode::Escape,
+                is_repeat: false,
                 ..
             }) => {
-                session.home(!shift);
-                keyboard_moved_cursor = true;
+                session.fold();
+                if !self.keep_cursor_in_view.is_locked() {
+                    self.keep_cursor_in_view = KeepCursorInView::LockStart;
+                }
                 self.redraw(cx);
             }
-            Hit::KeyDown(KeyEvent {
-                key_code: KeyCode::End,
-                modifiers: KeyModifiers { shift, .. },
+            Hit::KeyUp(KeyEvent {
+                key_code: KeyCode::Escape,
                 ..
             }) => {
-                session.end(!shift);
-                keyboard_moved_cursor = true;
+                session.unfold();
+                if !self.keep_cursor_in_view.is_locked() {
+                    self.keep_cursor_in_view = KeepCursorInView::LockStart;
+                }
                 self.redraw(cx);
             }
             Hit::KeyDown(KeyEvent {
-                key_code: KeyCode::PageUp,
-                modifiers: KeyModifiers { shift, .. },
+                key_code: KeyCode::Control | KeyCode::Alt | KeyCode::Shift | KeyCode::Logo,
                 ..
-            }) => {
-                for _ in 0..self.line_end - self.line_start - 3 {
-                    session.move_up(!shift);
+            }) => {}
+            Hit::KeyDown(ke) => {
+                // the text input that follows a key that was used is ignored, so plain keys can
+                // be bound without also being typed
+                self.ignore_text_input = true;
+                let lookup = session
+                    .settings()
+                    .key_bindings
+                    .lookup(&self.pending_chords, &ke);
+                match lookup {
+                    KeyLookup::Command(command) => {
+                        self.pending_chords.clear();
+                        keyboard_moved_cursor =
+                            self.run_command(cx, session, command, &mut actions);
+                        typed = command == EditorCommand::Backspace && was_completing;
+                    }
+                    KeyLookup::Pending(chord) => self.pending_chords.push(chord),
+                    KeyLookup::Unbound => {
+                        // a key that doesn't continue a sequence ends it
+                        self.ignore_text_input = !self.pending_chords.is_empty();
+                        self.pending_chords.clear();
+                    }
                 }
-                keyboard_moved_cursor = true;
-                self.redraw(cx);
             }
-            Hit::KeyDown(KeyEvent {
-                key_code: KeyCode::PageDown,
-                modifiers: KeyModifiers { shift, .. },
+            Hit::TextInput(TextInputEvent {
+                was_paste: false, ..
+            }) if self.ignore_text_input => {
+                self.ignore_text_input = false;
+            }
+            Hit::TextInput(TextInputEvent {
+                ref input,
+                was_paste: false,
                 ..
-            }) => {
-                for _ in 0..self.line_end - self.line_start - 3 {
-                    session.move_down(!shift);
+            }) if self.vim_emulation && self.vim.mode() != VimMode::Insert => {
+                // in normal and visual mode, typed chars are commands
+                for char in input.chars() {
+                    if let Some(command) = self.vim.handle_char(char) {
+                        keyboard_moved_cursor |=
+                            self.run_vim_command(cx, session, command, &mut actions);
+                    }
                 }
-                keyboard_moved_cursor = true;
+            }
+            Hit::TextInput(_) if self.read_only => {}
+            Hit::TextComposition(_) if self.read_only => {}
+            Hit::TextComposition(composition) => {
+                self.composition = match composition.phase {
+                    TextCompositionPhase::Commit => None,
+                    _ => Some(composition),
+                };
                 self.redraw(cx);
             }
             Hit::TextInput(TextInputEvent {
@@ -747,108 +2081,78 @@ impl CodeEditor {
                 ..
             }) if input.len() > 0 => {
                 session.insert(input.into());
+                self.vim.record_text(input);
                 self.redraw(cx);
                 keyboard_moved_cursor = true;
                 actions.push(CodeEditorAction::TextDidChange);
+                typed = true;
             }
             Hit::TextInput(TextInputEvent {
                 ref input,
                 was_paste: true,
                 ..
             }) if input.len() > 0 => {
-                session.paste(input.into());
-                self.redraw(cx);
-                keyboard_moved_cursor = true;
-                actions.push(CodeEditorAction::TextDidChange);
-            }
-            Hit::KeyDown(KeyEvent {
-                key_code: KeyCode::ReturnKey,
-                ..
-            }) => {
-                session.enter();
-                self.redraw(cx);
-                keyboard_moved_cursor = true;
-                actions.push(CodeEditorAction::TextDidChange);
-            }
-            Hit::KeyDown(KeyEvent {
-                key_code: KeyCode::Tab,
-                modifiers: KeyModifiers { shift: false, .. },
-                ..
-            }) => {
-                session.indent();
-                self.redraw(cx);
-                keyboard_moved_cursor = true;
-                actions.push(CodeEditorAction::TextDidChange);
-            }
-            Hit::KeyDown(KeyEvent {
-                key_code: KeyCode::Tab,
-                modifiers: KeyModifiers { shift: true, .. },
-                ..
-            }) => {
-                session.outdent();
-                self.redraw(cx);
-                keyboard_moved_cursor = true;
-                actions.push(CodeEditorAction::TextDidChange);
-            }
-            Hit::KeyDown(KeyEvent {
-                key_code: KeyCode::Delete,
-                ..
-            }) => {
-                session.delete();
-                self.redraw(cx);
-                keyboard_moved_cursor = true;
-                actions.push(CodeEditorAction::TextDidChange);
-            }
-            Hit::KeyDown(KeyEvent {
-                key_code: KeyCode::Backspace,
-                ..
-            }) => {
-                session.backspace();
+                match self.paste_filter.take() {
+                    Some(paste_filter) => session.paste_filtered(input, &*paste_filter),
+                    None => session.paste_filtered(input, &PlainText),
+                }
                 self.redraw(cx);
                 keyboard_moved_cursor = true;
                 actions.push(CodeEditorAction::TextDidChange);
             }
             Hit::TextCopy(ce) => {
                 *ce.response.borrow_mut() = Some(session.copy());
+                if self.copy_rich_text {
+                    *ce.rich_response.borrow_mut() = Some(self.rich_clipboard_text(session));
+                }
                 keyboard_moved_cursor = true;
             }
             Hit::TextCut(ce) => {
                 *ce.response.borrow_mut() = Some(session.copy());
+                if self.copy_rich_text {
+                    *ce.rich_response.borrow_mut() = Some(self.rich_clipboard_text(session));
+                }
                 session.delete();
                 keyboard_moved_cursor = true;
                 self.redraw(cx);
             }
-            Hit::KeyDown(KeyEvent {
-                key_code: KeyCode::KeyZ,
-                modifiers:
-                    KeyModifiers {
-                        logo: true,
-                        shift: false,
-                        ..
-                    },
-                ..
-            }) => {
-                if session.undo() {
-                    cx.redraw_all();
-                    actions.push(CodeEditorAction::TextDidChange);
-                    keyboard_moved_cursor = true;
+            Hit::FingerGesture(fg) => {
+                // pinching zooms the text, like the font size commands
+                if fg.phase == GesturePhase::Start {
+                    self.minimap_drag = None;
                 }
+                self.set_font_size(self.draw_text.text_style.font_size * fg.scale);
+                self.redraw(cx);
             }
-            Hit::KeyDown(KeyEvent {
-                key_code: KeyCode::KeyZ,
-                modifiers:
-                    KeyModifiers {
-                        logo: true,
-                        shift: true,
-                        ..
-                    },
-                ..
-            }) => {
-                if session.redo() {
-                    self.redraw(cx);
-                    actions.push(CodeEditorAction::TextDidChange);
-                    keyboard_moved_cursor = true;
+            Hit::FingerDown(FingerDownEvent { abs, .. })
+                if self.show_minimap && self.minimap_rect.contains(abs) =>
+            {
+                if !self.minimap_lens_rect().contains(abs) {
+                    // jump to where was clicked, with the lens centered on it
+                    let line_height = self.cell_size.y * self.minimap_scale;
+                    let line_count = session.document().as_text().as_lines().len();
+                    let line_index = (self.minimap_line_start
+                        + ((abs.y - self.minimap_rect.pos.y) / line_height) as usize)
+                        .min(line_count - 1);
+                    let y = session.layout().line(line_index).y() * self.cell_size.y
+                        - self.viewport_rect.size.y / 2.0;
+                    self.scroll_minimap_to(cx, y);
+                    actions.push(CodeEditorAction::Scrolled);
                 }
+                self.minimap_drag = Some(MinimapDrag {
+                    start_y: abs.y,
+                    start_scroll_y: self.scroll_bars.get_scroll_pos().y,
+                });
+            }
+            Hit::FingerMove(FingerMoveEvent { abs, .. }) if self.minimap_drag.is_some() => {
+                let drag = self.minimap_drag.as_ref().unwrap();
+                let y = drag.start_scroll_y
+                    + (abs.y - drag.start_y) * self.minimap_scroll_ratio(session);
+                self.scroll_minimap_to(cx, y);
+                actions.push(CodeEditorAction::Scrolled);
+            }
+            Hit::FingerUp(_) if self.minimap_drag.is_some() => {
+                self.minimap_drag = None;
             }
             Hit::FingerDown(FingerDownEvent {
                 abs,
@@ -919,8 +2223,48 @@ impl CodeEditor {
                 self.reset_cursor_blinker(cx);
                 self.keep_cursor_in_view = KeepCursorInView::Off;
             }
-            Hit::FingerHoverIn(_) | Hit::FingerHoverOver(_) => {
+            Hit::FingerHoverIn(FingerHoverEvent { abs, .. })
+            | Hit::FingerHoverOver(FingerHoverEvent { abs, .. })
+                if self.show_minimap && self.minimap_rect.contains(abs) =>
+            {
+                cx.set_cursor(MouseCursor::Default);
+                self.close_diagnostic_tooltip(cx);
+            }
+            Hit::FingerHoverIn(FingerHoverEvent { abs, .. })
+            | Hit::FingerHoverOver(FingerHoverEvent { abs, .. }) => {
+                cx.set_cursor(MouseCursor::Text);
+                self.update_diagnostic_tooltip(cx, session, abs);
+            }
+            Hit::FingerHoverOut(_) => {
+                self.close_diagnostic_tooltip(cx);
+            }
+            Hit::FingerDown(FingerDownEvent {
+                abs,
+                modifiers:
+                    KeyModifiers {
+                        alt: true,
+                        shift: true,
+                        ..
+                    },
+                ..
+            }) => {
+                self.animator_play(cx, id!(focus.on));
+                cx.set_key_focus(self.scroll_bars.area());
+                let ((cursor, _), _) = self.pick(session, abs);
+                session.extend_column_selection(cursor);
+                self.reset_cursor_blinker(cx);
+                self.keep_cursor_in_view = KeepCursorInView::Always(abs, cx.new_next_frame());
+                self.redraw(cx);
+            }
+            Hit::FingerMove(FingerMoveEvent { abs, .. }) if session.is_column_selecting() => {
+                self.reset_cursor_blinker(cx);
+                if let KeepCursorInView::Always(old_abs, _) = &mut self.keep_cursor_in_view {
+                    *old_abs = abs;
+                }
                 cx.set_cursor(MouseCursor::Text);
+                let ((cursor, _), _) = self.pick(session, abs);
+                session.extend_column_selection(cursor);
+                self.redraw(cx);
             }
             Hit::FingerDown(FingerDownEvent {
                 abs,
@@ -940,6 +2284,42 @@ impl CodeEditor {
             }
             _ => {}
         }
+        if self.vim.mode() != vim_mode {
+            actions.push(CodeEditorAction::VimModeChanged(self.vim.mode()));
+        }
+        if typed {
+            // only open by itself once there is enough of a word to go on
+            let open = {
+                let position = session.selections()[0].cursor.position;
+                crate::completion::word_before(&session.document().as_text(), position)
+                    .chars()
+                    .count()
+                    >= 2
+            };
+            self.update_completions(cx, session, open);
+        } else if keyboard_moved_cursor {
+            self.close_popup(cx);
+        }
+        match event {
+            Event::TextInput(TextInputEvent {
+                input,
+                was_paste: false,
+                ..
+            }) if typed => {
+                if input.ends_with(')') {
+                    self.close_signature_help(cx);
+                } else {
+                    self.update_signature_help(cx, session, input.ends_with(['(', ',']));
+                }
+            }
+            _ if keyboard_moved_cursor => self.update_signature_help(cx, session, false),
+            _ => {}
+        }
+        if let Event::MouseDown(_) = event {
+            self.close_popup(cx);
+            self.close_signature_help(cx);
+            self.close_diagnostic_tooltip(cx);
+        }
         if keyboard_moved_cursor {
             self.keep_cursor_in_view = KeepCursorInView::Once;
             self.reset_cursor_blinker(cx);
@@ -960,11 +2340,20 @@ impl CodeEditor {
         let mut line_index = self.line_start;
         let mut origin_y = session.layout().line(self.line_start).y();
         let mut buf = String::new();
+        let severities = session
+            .document()
+            .diagnostics()
+            .line_severities(self.line_start, self.line_end);
+        let marker_x = self.viewport_rect.pos.x - 2.0 * self.cell_size.x;
+        let line_changes = session.document().line_changes();
         for element in session
             .layout()
             .block_elements(self.line_start, self.line_end)
         {
             match element {
+                BlockElement::Line { line, .. } if line.scale() == 0.0 => {
+                    line_index += 1;
+                }
                 BlockElement::Line { line, .. } => {
                     self.draw_gutter.font_scale = line.scale();
                     buf.clear();
@@ -983,6 +2372,28 @@ impl CodeEditor {
                             ),
                         &buf,
                     );
+                    if let Some(Some(line_change)) = line_changes.get(line_index) {
+                        self.draw_line_change(cx, *line_change, origin_y, line.scale());
+                    }
+                    if let Some(Some(severity)) = severities.get(line_index - self.line_start) {
+                        self.draw_diagnostic_marker.color = match severity {
+                            Severity::Error => self.token_colors.error_decoration,
+                            Severity::Warning => self.token_colors.warning_decoration,
+                            Severity::Information | Severity::Hint => {
+                                self.token_colors.information_decoration
+                            }
+                        };
+                        self.draw_diagnostic_marker.draw_abs(
+                            cx,
+                            Rect {
+                                pos: dvec2(
+                                    marker_x,
+                                    origin_y * self.cell_size.y + self.gutter_rect.pos.y,
+                                ),
+                                size: dvec2(self.cell_size.x, line.scale() * self.cell_size.y),
+                            },
+                        );
+                    }
                     line_index += 1;
                     origin_y += line.height();
                 }
@@ -993,6 +2404,163 @@ impl CodeEditor {
         }
     }
 
+    /// Draws the bar at the left of the gutter that shows how the line differs from the
+    /// baseline of the document.
+    fn draw_line_change(
+        &mut self,
+        cx: &mut Cx2d,
+        line_change: LineChange,
+        origin_y: f64,
+        scale: f64,
+    ) {
+        let y = origin_y * self.cell_size.y + self.gutter_rect.pos.y;
+        let height = scale * self.cell_size.y;
+        let (color, rect) = match line_change {
+            LineChange::Added => (
+                self.token_colors.added_line,
+                Rect {
+                    pos: dvec2(self.gutter_rect.pos.x - 6.0, y),
+                    size: dvec2(3.0, height),
+                },
+            ),
+            LineChange::Modified => (
+                self.token_colors.modified_line,
+                Rect {
+                    pos: dvec2(self.gutter_rect.pos.x - 6.0, y),
+                    size: dvec2(3.0, height),
+                },
+            ),
+            // a notch on the edge between the lines
+            LineChange::RemovedAbove => (
+                self.token_colors.removed_line,
+                Rect {
+                    pos: dvec2(self.gutter_rect.pos.x - 8.0, y - 1.5),
+                    size: dvec2(7.0, 3.0),
+                },
+            ),
+            LineChange::RemovedBelow => (
+                self.token_colors.removed_line,
+                Rect {
+                    pos: dvec2(self.gutter_rect.pos.x - 8.0, y + height - 1.5),
+                    size: dvec2(7.0, 3.0),
+                },
+            ),
+        };
+        self.draw_line_change.color = color;
+        self.draw_line_change.draw_abs(cx, rect);
+    }
+
+    /// Draws the document zoomed out at the right of the editor, with a lens over the lines in
+    /// view. A document too long to fit scrolls along with the editor.
+    fn draw_minimap(&mut self, cx: &mut Cx2d, session: &Session) {
+        if !self.show_minimap {
+            return;
+        }
+        self.minimap_rect = Rect {
+            pos: dvec2(
+                self.unscrolled_rect.pos.x + self.unscrolled_rect.size.x - self.minimap_width,
+                self.unscrolled_rect.pos.y,
+            ),
+            size: dvec2(self.minimap_width, self.unscrolled_rect.size.y),
+        };
+        let line_height = self.cell_size.y * self.minimap_scale;
+        let column_width = self.cell_size.x * self.minimap_scale;
+        let document = session.document();
+        let text = document.as_text();
+        let lines = text.as_lines();
+        let visible_line_count = (self.minimap_rect.size.y / line_height) as usize;
+        // the minimap is as far down the document as the lines in view, so the lens reaches
+        // the bottom of it together with the editor
+        self.minimap_line_start = if lines.len() > visible_line_count {
+            let view_line_count = self.line_end.saturating_sub(self.line_start);
+            let fraction =
+                self.line_start as f64 / lines.len().saturating_sub(view_line_count).max(1) as f64;
+            (fraction.min(1.0) * (lines.len() - visible_line_count) as f64) as usize
+        } else {
+            0
+        };
+        let line_end = (self.minimap_line_start + visible_line_count + 1).min(lines.len());
+        self.minimap.update(
+            lines,
+            &document.layout().tokens,
+            self.minimap_line_start,
+            line_end,
+        );
+        let lens_rect = self.minimap_lens_rect();
+        self.draw_minimap_lens.draw_abs(cx, lens_rect);
+        let right = self.minimap_rect.pos.x + self.minimap_rect.size.x;
+        for line_index in self.minimap_line_start..line_end {
+            let y = self.minimap_rect.pos.y
+                + (line_index - self.minimap_line_start) as f64 * line_height;
+            for run in self.minimap.runs(line_index) {
+                let x = self.minimap_rect.pos.x + run.column as f64 * column_width;
+                if x >= right {
+                    break;
+                }
+                self.draw_minimap.color = self.token_colors.color(run.kind);
+                self.draw_minimap.draw_abs(
+                    cx,
+                    Rect {
+                        pos: dvec2(x, y),
+                        size: dvec2(
+                            (run.column_count as f64 * column_width).min(right - x),
+                            line_height * 0.8,
+                        ),
+                    },
+                );
+            }
+        }
+    }
+
+    /// The part of the minimap that shows the lines in view.
+    fn minimap_lens_rect(&self) -> Rect {
+        let line_height = self.cell_size.y * self.minimap_scale;
+        Rect {
+            pos: dvec2(
+                self.minimap_rect.pos.x,
+                self.minimap_rect.pos.y
+                    + (self.line_start as f64 - self.minimap_line_start as f64) * line_height,
+            ),
+            size: dvec2(
+                self.minimap_width,
+                self.line_end.saturating_sub(self.line_start) as f64 * line_height,
+            ),
+        }
+    }
+
+    /// How far the editor scrolls when the minimap lens is dragged by a pixel.
+    fn minimap_scroll_ratio(&self, session: &Session) -> f64 {
+        let line_height = self.cell_size.y * self.minimap_scale;
+        let line_count = session.document().as_text().as_lines().len();
+        let height = self
+            .minimap_rect
+            .size
+            .y
+            .min(line_count as f64 * line_height);
+        let lens_height = self.minimap_lens_rect().size.y;
+        session.layout().height() * self.cell_size.y / (height - lens_height).max(1.0)
+    }
+
+    fn scroll_minimap_to(&mut self, cx: &mut Cx, y: f64) {
+        let scroll_pos = self.scroll_bars.get_scroll_pos();
+        self.scroll_bars
+            .set_scroll_pos(cx, dvec2(scroll_pos.x, y.max(0.0)));
+        self.keep_cursor_in_view = KeepCursorInView::Off;
+        self.redraw(cx);
+    }
+
+    fn rich_clipboard_text(&self, session: &Session) -> RichClipboardText {
+        let runs: Vec<_> = session
+            .copy_token_runs()
+            .into_iter()
+            .map(|(string, kind)| (string, self.token_colors.color(kind)))
+            .collect();
+        RichClipboardText {
+            html: Some(rich_text::to_html(&runs, self.draw_bg.color)),
+            rtf: Some(rich_text::to_rtf(&runs)),
+        }
+    }
+
     fn draw_text_layer(&mut self, cx: &mut Cx2d, session: &Session) {
         let highlighted_delimiter_positions = session.highlighted_delimiter_positions();
         let mut line_index = self.line_start;
@@ -1002,6 +2570,9 @@ impl CodeEditor {
             .block_elements(self.line_start, self.line_end)
         {
             match element {
+                BlockElement::Line { line, .. } if line.scale() == 0.0 => {
+                    line_index += 1;
+                }
                 BlockElement::Line { line, .. } => {
                     self.draw_text.font_scale = line.scale();
                     let mut token_iter = line.tokens().iter().copied();
@@ -1015,6 +2586,10 @@ impl CodeEditor {
                                 is_inlay: false,
                                 mut text,
                             } => {
+                                let mut graphemes = text.visual_graphemes();
+                                let mut grapheme_byte_index = byte_index;
+                                let start_column_index = column_index;
+                                column_index += text.column_count();
                                 while !text.is_empty() {
                                     let token = match token_slot {
                                         Some(token) => {
@@ -1039,24 +2614,7 @@ impl CodeEditor {
                                     };
                                     let (text_0, text_1) = text.split_at(token.len);
                                     text = text_1;
-                                    self.draw_text.color = match token.kind {
-                                        TokenKind::Unknown => self.token_colors.unknown,
-                                        TokenKind::BranchKeyword => {
-                                            self.token_colors.branch_keyword
-                                        }
-                                        TokenKind::Comment => self.token_colors.comment,
-                                        TokenKind::Constant => self.token_colors.constant,
-                                        TokenKind::Delimiter => self.token_colors.delimiter,
-                                        TokenKind::Identifier => self.token_colors.identifier,
-                                        TokenKind::LoopKeyword => self.token_colors.loop_keyword,
-                                        TokenKind::Number => self.token_colors.number,
-                                        TokenKind::OtherKeyword => self.token_colors.other_keyword,
-                                        TokenKind::Punctuator => self.token_colors.punctuator,
-                                        TokenKind::String => self.token_colors.string,
-                                        TokenKind::Function => self.token_colors.function,
-                                        TokenKind::Typename => self.token_colors.typename,
-                                        TokenKind::Whitespace => self.token_colors.whitespace,
-                                    };
+                                    self.draw_text.color = self.token_colors.color(token.kind);
                                     self.draw_text.outline = 0.0;
                                     if let TokenKind::Delimiter = token.kind {
                                         if highlighted_delimiter_positions.contains(&Position {
@@ -1068,17 +2626,23 @@ impl CodeEditor {
                                                 self.token_colors.delimiter_highlight
                                         }
                                     }
-                                    for grapheme in text_0.graphemes() {
-                                        let (x, y) = line
-                                            .grid_to_normalized_position(row_index, column_index);
+                                    // Graphemes are drawn in the colour of the token they
+                                    // start in, at their column in display order.
+                                    byte_index += text_0.len();
+                                    while grapheme_byte_index < byte_index {
+                                        let (grapheme, grapheme_column_index, _) =
+                                            graphemes.next().unwrap();
+                                        let (x, y) = line.grid_to_normalized_position(
+                                            row_index,
+                                            start_column_index + grapheme_column_index,
+                                        );
                                         self.draw_text.draw_abs(
                                             cx,
                                             DVec2 { x, y: origin_y + y } * self.cell_size
                                                 + self.viewport_rect.pos,
                                             grapheme,
                                         );
-                                        byte_index += grapheme.len();
-                                        column_index += grapheme.column_count();
+                                        grapheme_byte_index += grapheme.len();
                                     }
                                 }
                             }
@@ -1153,9 +2717,13 @@ impl CodeEditor {
         }
     }
 
-    fn draw_decoration_layer(&mut self, cx: &mut Cx2d<'_>, session: &Session) {
+    fn draw_decoration_layer(
+        &mut self,
+        cx: &mut Cx2d<'_>,
+        session: &Session,
+        decorations: &[Decoration],
+    ) {
         let mut active_decoration = None;
-        let decorations = session.document().decorations();
         let mut decorations = decorations.iter();
         while decorations.as_slice().first().map_or(false, |decoration| {
             decoration.end().line_index < self.line_start
@@ -1248,45 +2816,50 @@ impl CodeEditor {
                                 is_inlay: false,
                                 text,
                             } => {
-                                for grapheme in text.graphemes() {
-                                    let (start_x, y) =
-                                        line.grid_to_normalized_position(row_index, column_index);
+                                for (grapheme, grapheme_column_index, is_rtl) in
+                                    text.visual_graphemes()
+                                {
+                                    let start_column_index = column_index + grapheme_column_index;
+                                    let (start_x, y) = line
+                                        .grid_to_normalized_position(row_index, start_column_index);
                                     let start_y = origin_y + y;
                                     let (end_x, _) = line.grid_to_normalized_position(
                                         row_index,
-                                        column_index + grapheme.column_count(),
+                                        start_column_index + grapheme.column_count(),
                                     );
                                     let end_y = start_y + line.scale();
                                     if (start_y..=end_y).contains(&position.y) {
                                         let mid_x = (start_x + end_x) / 2.0;
+                                        let before = (
+                                            Position {
+                                                line_index,
+                                                byte_index,
+                                            },
+                                            Affinity::After,
+                                        );
+                                        let after = (
+                                            Position {
+                                                line_index,
+                                                byte_index: byte_index + grapheme.len(),
+                                            },
+                                            Affinity::Before,
+                                        );
+                                        // A grapheme in a right-to-left run starts on its right.
+                                        let (left, right) = if is_rtl {
+                                            (after, before)
+                                        } else {
+                                            (before, after)
+                                        };
                                         if (start_x..=mid_x).contains(&position.x) {
-                                            return (
-                                                (
-                                                    Position {
-                                                        line_index,
-                                                        byte_index,
-                                                    },
-                                                    Affinity::After,
-                                                ),
-                                                false,
-                                            );
+                                            return (left, false);
                                         }
                                         if (mid_x..=end_x).contains(&position.x) {
-                                            return (
-                                                (
-                                                    Position {
-                                                        line_index,
-                                                        byte_index: byte_index + grapheme.len(),
-                                                    },
-                                                    Affinity::Before,
-                                                ),
-                                                false,
-                                            );
+                                            return (right, false);
                                         }
                                     }
                                     byte_index += grapheme.len();
-                                    column_index += grapheme.column_count();
                                 }
+                                column_index += text.column_count();
                             }
                             WrappedElement::Text {
                                 is_inlay: true,
@@ -1417,6 +2990,15 @@ impl CodeEditor {
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, DefaultNone)]
 pub enum CodeEditorAction {
     TextDidChange,
+    KeyFocusLost,
+    /// The user scrolled the view.
+    Scrolled,
+    /// The user asked for the code actions at the cursor, see `CodeEditor::open_code_actions`.
+    CodeActionsRequested,
+    /// The user chose the code action with this index from the list.
+    ApplyCodeAction(usize),
+    /// The Vim emulation switched to this mode.
+    VimModeChanged(VimMode),
     None
 }
 
@@ -1458,7 +3040,43 @@ impl<'a> DrawDecorationLayer<'a> {
                                 is_inlay: false,
                                 text,
                             } => {
-                                for grapheme in text.graphemes() {
+                                let text_column_index = column_index;
+                                for (grapheme, grapheme_column_index, is_rtl) in
+                                    text.visual_graphemes()
+                                {
+                                    let start_column_index =
+                                        text_column_index + grapheme_column_index;
+                                    let end_column_index =
+                                        start_column_index + grapheme.column_count();
+                                    if !is_rtl {
+                                        self.handle_event(
+                                            cx,
+                                            line_index,
+                                            line,
+                                            byte_index,
+                                            Affinity::After,
+                                            origin_y,
+                                            row_index,
+                                            start_column_index,
+                                        );
+                                        byte_index += grapheme.len();
+                                        column_index += grapheme.column_count();
+                                        self.handle_event(
+                                            cx,
+                                            line_index,
+                                            line,
+                                            byte_index,
+                                            Affinity::Before,
+                                            origin_y,
+                                            row_index,
+                                            end_column_index,
+                                        );
+                                        continue;
+                                    }
+                                    // Logically adjacent graphemes in a right-to-left run aren't
+                                    // visually adjacent, so the decoration is drawn one grapheme
+                                    // at a time there, from the left of the grapheme to its right.
+                                    self.move_decoration_start(line, row_index, end_column_index);
                                     self.handle_event(
                                         cx,
                                         line_index,
@@ -1467,8 +3085,27 @@ impl<'a> DrawDecorationLayer<'a> {
                                         Affinity::After,
                                         origin_y,
                                         row_index,
-                                        column_index,
+                                        end_column_index,
                                     );
+                                    if self.active_decoration.is_some() {
+                                        self.move_decoration_start(
+                                            line,
+                                            row_index,
+                                            start_column_index,
+                                        );
+                                        self.draw_decoration(
+                                            cx,
+                                            line,
+                                            origin_y,
+                                            row_index,
+                                            end_column_index,
+                                        );
+                                        self.move_decoration_start(
+                                            line,
+                                            row_index,
+                                            start_column_index,
+                                        );
+                                    }
                                     byte_index += grapheme.len();
                                     column_index += grapheme.column_count();
                                     self.handle_event(
@@ -1479,8 +3116,9 @@ impl<'a> DrawDecorationLayer<'a> {
                                         Affinity::Before,
                                         origin_y,
                                         row_index,
-                                        column_index,
+                                        start_column_index,
                                     );
+                                    self.move_decoration_start(line, row_index, column_index);
                                 }
                             }
                             WrappedElement::Text {
@@ -1576,6 +3214,13 @@ impl<'a> DrawDecorationLayer<'a> {
         }
     }
 
+    fn move_decoration_start(&mut self, line: Line<'_>, row_index: usize, column_index: usize) {
+        if let Some(active_decoration) = &mut self.active_decoration {
+            (active_decoration.start_x, _) =
+                line.grid_to_normalized_position(row_index, column_index);
+        }
+    }
+
     fn draw_decoration(
         &mut self,
         cx: &mut Cx2d,
@@ -1586,26 +3231,41 @@ impl<'a> DrawDecorationLayer<'a> {
     ) {
         let start_x = mem::take(&mut self.active_decoration.as_mut().unwrap().start_x);
         let (x, y) = line.grid_to_normalized_position(row_index, column_index);
-        self.code_editor.draw_decoration.color =
-            match self.active_decoration.as_mut().unwrap().decoration.ty {
-                DecorationType::Warning => self.code_editor.token_colors.warning_decoration,
-                DecorationType::Error => self.code_editor.token_colors.error_decoration,
-            };
-
-        self.code_editor.draw_decoration.draw_abs(
-            cx,
-            Rect {
-                pos: DVec2 {
-                    x: start_x,
-                    y: origin_y + y,
-                } * self.code_editor.cell_size
-                    + self.code_editor.viewport_rect.pos,
-                size: DVec2 {
-                    x: x - start_x,
-                    y: line.scale(),
-                } * self.code_editor.cell_size,
-            },
-        );
+        let rect = Rect {
+            pos: DVec2 {
+                x: start_x,
+                y: origin_y + y,
+            } * self.code_editor.cell_size
+                + self.code_editor.viewport_rect.pos,
+            size: DVec2 {
+                x: x - start_x,
+                y: line.scale(),
+            } * self.code_editor.cell_size,
+        };
+        let token_colors = &self.code_editor.token_colors;
+        match self.active_decoration.as_mut().unwrap().decoration.ty {
+            DecorationType::Warning => {
+                self.code_editor.draw_decoration.color = token_colors.warning_decoration;
+                self.code_editor.draw_decoration.draw_abs(cx, rect);
+            }
+            DecorationType::Error => {
+                self.code_editor.draw_decoration.color = token_colors.error_decoration;
+                self.code_editor.draw_decoration.draw_abs(cx, rect);
+            }
+            DecorationType::Information => {
+                self.code_editor.draw_decoration.color = token_colors.information_decoration;
+                self.code_editor.draw_decoration.draw_abs(cx, rect);
+            }
+            // search matches fill the whole cell, behind the text
+            DecorationType::SearchMatch => {
+                self.code_editor.draw_search_match.color = token_colors.search_match;
+                self.code_editor.draw_search_match.draw_abs(cx, rect);
+            }
+            DecorationType::CurrentSearchMatch => {
+                self.code_editor.draw_search_match.color = token_colors.current_search_match;
+                self.code_editor.draw_search_match.draw_abs(cx, rect);
+            }
+        }
     }
 }
 
@@ -1652,7 +3312,43 @@ impl<'a> DrawSelectionLayer<'a> {
                                 is_inlay: false,
                                 text,
                             } => {
-                                for grapheme in text.graphemes() {
+                                let text_column_index = column_index;
+                                for (grapheme, grapheme_column_index, is_rtl) in
+                                    text.visual_graphemes()
+                                {
+                                    let start_column_index =
+                                        text_column_index + grapheme_column_index;
+                                    let end_column_index =
+                                        start_column_index + grapheme.column_count();
+                                    if !is_rtl {
+                                        self.handle_event(
+                                            cx,
+                                            line_index,
+                                            line,
+                                            byte_index,
+                                            Affinity::After,
+                                            origin_y,
+                                            row_index,
+                                            start_column_index,
+                                        );
+                                        byte_index += grapheme.len();
+                                        column_index += grapheme.column_count();
+                                        self.handle_event(
+                                            cx,
+                                            line_index,
+                                            line,
+                                            byte_index,
+                                            Affinity::Before,
+                                            origin_y,
+                                            row_index,
+                                            end_column_index,
+                                        );
+                                        continue;
+                                    }
+                                    // Logically adjacent graphemes in a right-to-left run aren't
+                                    // visually adjacent, so the selection is drawn one grapheme
+                                    // at a time there, from the left of the grapheme to its right.
+                                    self.move_selection_start(line, row_index, end_column_index);
                                     self.handle_event(
                                         cx,
                                         line_index,
@@ -1661,8 +3357,27 @@ impl<'a> DrawSelectionLayer<'a> {
                                         Affinity::After,
                                         origin_y,
                                         row_index,
-                                        column_index,
+                                        end_column_index,
                                     );
+                                    if self.active_selection.is_some() {
+                                        self.move_selection_start(
+                                            line,
+                                            row_index,
+                                            start_column_index,
+                                        );
+                                        self.draw_selection(
+                                            cx,
+                                            line,
+                                            origin_y,
+                                            row_index,
+                                            end_column_index,
+                                        );
+                                        self.move_selection_start(
+                                            line,
+                                            row_index,
+                                            start_column_index,
+                                        );
+                                    }
                                     byte_index += grapheme.len();
                                     column_index += grapheme.column_count();
                                     self.handle_event(
@@ -1673,8 +3388,9 @@ impl<'a> DrawSelectionLayer<'a> {
                                         Affinity::Before,
                                         origin_y,
                                         row_index,
-                                        column_index,
+                                        start_column_index,
                                     );
+                                    self.move_selection_start(line, row_index, column_index);
                                 }
                             }
                             WrappedElement::Text {
@@ -1780,6 +3496,13 @@ impl<'a> DrawSelectionLayer<'a> {
         }
     }
 
+    fn move_selection_start(&mut self, line: Line<'_>, row_index: usize, column_index: usize) {
+        if let Some(active_selection) = &mut self.active_selection {
+            (active_selection.start_x, _) =
+                line.grid_to_normalized_position(row_index, column_index);
+        }
+    }
+
     fn draw_selection(
         &mut self,
         cx: &mut Cx2d,
@@ -1900,6 +3623,39 @@ struct TokenColors {
     error_decoration: Vec4,
     #[live]
     warning_decoration: Vec4,
+    #[live]
+    information_decoration: Vec4,
+    #[live]
+    added_line: Vec4,
+    #[live]
+    modified_line: Vec4,
+    #[live]
+    removed_line: Vec4,
+    #[live]
+    search_match: Vec4,
+    #[live]
+    current_search_match: Vec4,
+}
+
+impl TokenColors {
+    fn color(&self, kind: TokenKind) -> Vec4 {
+        match kind {
+            TokenKind::Unknown => self.unknown,
+            TokenKind::BranchKeyword => self.branch_keyword,
+            TokenKind::Comment => self.comment,
+            TokenKind::Constant => self.constant,
+            TokenKind::Delimiter => self.delimiter,
+            TokenKind::Identifier => self.identifier,
+            TokenKind::LoopKeyword => self.loop_keyword,
+            TokenKind::Number => self.number,
+            TokenKind::OtherKeyword => self.other_keyword,
+            TokenKind::Punctuator => self.punctuator,
+            TokenKind::String => self.string,
+            TokenKind::Function => self.function,
+            TokenKind::Typename => self.typename,
+            TokenKind::Whitespace => self.whitespace,
+        }
+    }
 }
 
 #[derive(Live, LiveHook, LiveRegister)]
@@ -1975,3 +3731,12 @@ impl DrawSelection {
         }
     }
 }
+
+fn set_cursor(session: &Session, position: Position) {
+    session.set_selection(
+        position,
+        Affinity::Before,
+        SelectionMode::Simple,
+        NewGroup::Yes,
+    );
+}
@@ -0,0 +1,708 @@
+use {
+    crate::{
+        selection::{Cursor, Selection, SelectionSet},
+        text::{Change, Drift, Edit, Position, Text},
+    },
+    makepad_widgets::makepad_micro_serde::*,
+    std::{collections::HashSet, mem},
+};
+
+/// Identifies a participant in a collaboration session. Every replica of a document needs a
+/// different one, like the connection id handed out by the collab server. Zero is taken by the
+/// text all replicas start from.
+pub type ParticipantId = u64;
+
+const INITIAL_PARTICIPANT_ID: ParticipantId = 0;
+
+/// How many ops of each participant a replica has applied.
+#[derive(Clone, Debug, Default, Eq, PartialEq, SerBin, DeBin, SerJson, DeJson)]
+pub struct VersionVector {
+    /// Sorted by participant, participants without ops are left out.
+    seqs: Vec<(ParticipantId, u64)>,
+}
+
+impl VersionVector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, participant_id: ParticipantId) -> u64 {
+        match self
+            .seqs
+            .binary_search_by_key(&participant_id, |&(participant_id, _)| participant_id)
+        {
+            Ok(index) => self.seqs[index].1,
+            Err(_) => 0,
+        }
+    }
+
+    /// Whether every op `other` has seen has been seen here too.
+    pub fn includes(&self, other: &Self) -> bool {
+        other
+            .seqs
+            .iter()
+            .all(|&(participant_id, seq)| self.get(participant_id) >= seq)
+    }
+
+    pub fn merge(&mut self, other: &Self) {
+        for &(participant_id, seq) in &other.seqs {
+            if seq > self.get(participant_id) {
+                self.set(participant_id, seq);
+            }
+        }
+    }
+
+    fn set(&mut self, participant_id: ParticipantId, seq: u64) {
+        match self
+            .seqs
+            .binary_search_by_key(&participant_id, |&(participant_id, _)| participant_id)
+        {
+            Ok(index) => self.seqs[index].1 = seq,
+            Err(index) => self.seqs.insert(index, (participant_id, seq)),
+        }
+    }
+}
+
+/// Identifies a char for good, even after it is deleted. Newer chars have a larger clock, and
+/// ties are broken by participant, which gives every replica the same order for them.
+#[derive(
+    Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, SerBin, DeBin, SerJson, DeJson,
+)]
+pub struct CharId {
+    pub clock: u64,
+    pub participant_id: ParticipantId,
+}
+
+/// The chars a participant inserted in one go, which have consecutive clocks.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, SerBin, DeBin, SerJson, DeJson)]
+pub struct CharRange {
+    pub start: CharId,
+    pub len: u64,
+}
+
+impl CharRange {
+    fn ids(self) -> impl Iterator<Item = CharId> {
+        (self.start.clock..self.start.clock + self.len).map(move |clock| CharId {
+            clock,
+            participant_id: self.start.participant_id,
+        })
+    }
+}
+
+/// A change to a document, made by one participant, that can be sent to the others.
+#[derive(Clone, Debug, SerBin, DeBin, SerJson, DeJson)]
+pub struct Op {
+    pub participant_id: ParticipantId,
+    /// How many ops the participant made before this one, plus one.
+    pub seq: u64,
+    /// What the participant had seen when it made the op, a replica has to have seen the same
+    /// before it can apply it.
+    pub version: VersionVector,
+    pub change: OpChange,
+}
+
+#[derive(Clone, Debug, SerBin, DeBin, SerJson, DeJson)]
+pub enum OpChange {
+    /// Inserts `text` right after the char `after`, or at the start for `None`. Its chars get
+    /// the ids from `start` on.
+    Insert {
+        after: Option<CharId>,
+        start: CharId,
+        text: String,
+    },
+    Delete {
+        ranges: Vec<CharRange>,
+    },
+}
+
+/// Where a cursor or the end of a selection is, as the char it comes right after, or `None` at
+/// the start. It stays put while others edit around it.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, SerBin, DeBin, SerJson, DeJson)]
+pub struct Anchor {
+    pub after: Option<CharId>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, SerBin, DeBin, SerJson, DeJson)]
+pub struct AnchoredSelection {
+    pub anchor: Anchor,
+    pub cursor: Anchor,
+}
+
+/// The selections of a participant, sent to the others whenever they change.
+#[derive(Clone, Debug, SerBin, DeBin, SerJson, DeJson)]
+pub struct Presence {
+    pub participant_id: ParticipantId,
+    pub selections: Vec<AnchoredSelection>,
+}
+
+/// Chars with consecutive ids that are either all deleted or all not.
+#[derive(Clone, Debug, SerBin, DeBin, SerJson, DeJson)]
+pub struct SnapshotRun {
+    pub start: CharId,
+    pub text: String,
+    pub is_deleted: bool,
+}
+
+/// The state of a replica, to start another one from for a participant that joins late.
+#[derive(Clone, Debug, SerBin, DeBin, SerJson, DeJson)]
+pub struct Snapshot {
+    pub version: VersionVector,
+    pub clock: u64,
+    pub runs: Vec<SnapshotRun>,
+}
+
+/// What collaborators send each other, directly or through the collab server, which only
+/// needs to pass them on.
+#[derive(Clone, Debug, SerBin, DeBin, SerJson, DeJson)]
+pub enum CollabMessage {
+    Ops(Vec<Op>),
+    Presence(Presence),
+    /// Asks for the ops that aren't part of the given version, see `Replica::ops_since`.
+    Sync(VersionVector),
+    Snapshot(Snapshot),
+    Leave(ParticipantId),
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Element {
+    id: CharId,
+    char: char,
+    is_deleted: bool,
+}
+
+/// One participant's copy of a document as a sequence CRDT (a replicated growable array).
+/// Every char has an id, deleted ones stay behind, and concurrent inserts at the same place
+/// are ordered by id, so replicas that have applied the same ops have the same text, in
+/// whatever order the ops arrived.
+///
+/// Local changes are turned into ops with `apply_local_change` and picked up with `take_ops`.
+/// Ops from others go into `apply_remote_ops`, which returns the edits that merge them into
+/// the local text, unsaved edits and all.
+#[derive(Clone, Debug)]
+pub struct Replica {
+    participant_id: ParticipantId,
+    clock: u64,
+    version: VersionVector,
+    elements: Vec<Element>,
+    /// Every op applied, to send to participants that missed some.
+    log: Vec<Op>,
+    /// Ops that arrived before ops they depend on.
+    pending: Vec<Op>,
+    /// Local ops that haven't been taken for sending yet.
+    outbox: Vec<Op>,
+    presences: Vec<Presence>,
+}
+
+impl Replica {
+    /// Every replica of a document has to start from the same text, like the saved file, or
+    /// from a snapshot of another one.
+    pub fn new(participant_id: ParticipantId, text: &Text) -> Self {
+        assert_ne!(participant_id, INITIAL_PARTICIPANT_ID);
+        let elements: Vec<_> = text
+            .to_string()
+            .chars()
+            .enumerate()
+            .map(|(index, char)| Element {
+                id: CharId {
+                    clock: index as u64 + 1,
+                    participant_id: INITIAL_PARTICIPANT_ID,
+                },
+                char,
+                is_deleted: false,
+            })
+            .collect();
+        Self {
+            participant_id,
+            clock: elements.len() as u64,
+            version: VersionVector::new(),
+            elements,
+            log: Vec::new(),
+            pending: Vec::new(),
+            outbox: Vec::new(),
+            presences: Vec::new(),
+        }
+    }
+
+    /// The replica starts without a log, so it can't help participants catch up on ops from
+    /// before the snapshot.
+    pub fn from_snapshot(participant_id: ParticipantId, snapshot: Snapshot) -> Self {
+        assert_ne!(participant_id, INITIAL_PARTICIPANT_ID);
+        let mut elements = Vec::new();
+        for run in snapshot.runs {
+            elements.extend(run.text.chars().enumerate().map(|(index, char)| Element {
+                id: CharId {
+                    clock: run.start.clock + index as u64,
+                    participant_id: run.start.participant_id,
+                },
+                char,
+                is_deleted: run.is_deleted,
+            }));
+        }
+        Self {
+            participant_id,
+            clock: snapshot.clock,
+            version: snapshot.version,
+            elements,
+            log: Vec::new(),
+            pending: Vec::new(),
+            outbox: Vec::new(),
+            presences: Vec::new(),
+        }
+    }
+
+    pub fn participant_id(&self) -> ParticipantId {
+        self.participant_id
+    }
+
+    pub fn version(&self) -> &VersionVector {
+        &self.version
+    }
+
+    pub fn text(&self) -> Text {
+        self.elements
+            .iter()
+            .filter(|element| !element.is_deleted)
+            .map(|element| element.char)
+            .collect()
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        let mut runs: Vec<SnapshotRun> = Vec::new();
+        let mut prev_element: Option<Element> = None;
+        for &element in &self.elements {
+            match (runs.last_mut(), prev_element) {
+                (Some(run), Some(prev_element))
+                    if element.id.participant_id == prev_element.id.participant_id
+                        && element.id.clock == prev_element.id.clock + 1
+                        && element.is_deleted == run.is_deleted =>
+                {
+                    run.text.push(element.char)
+                }
+                _ => runs.push(SnapshotRun {
+                    start: element.id,
+                    text: String::from(element.char),
+                    is_deleted: element.is_deleted,
+                }),
+            }
+            prev_element = Some(element);
+        }
+        Snapshot {
+            version: self.version.clone(),
+            clock: self.clock,
+            runs,
+        }
+    }
+
+    /// The ops this replica applied that aren't part of `version`, for a participant that
+    /// reconnects.
+    pub fn ops_since(&self, version: &VersionVector) -> Vec<Op> {
+        self.log
+            .iter()
+            .filter(|op| op.seq > version.get(op.participant_id))
+            .cloned()
+            .collect()
+    }
+
+    /// The ops made locally since the last time.
+    pub fn take_ops(&mut self) -> Vec<Op> {
+        mem::take(&mut self.outbox)
+    }
+
+    /// Turns a change that was just made to the local text into an op for the others.
+    pub fn apply_local_change(&mut self, change: &Change) {
+        let change = match *change {
+            Change::Insert(position, ref text) => {
+                let text = text.to_string();
+                if text.is_empty() {
+                    return;
+                }
+                let after = self.visible_index_before(position);
+                let start = CharId {
+                    clock: self.clock + 1,
+                    participant_id: self.participant_id,
+                };
+                let after = after.map(|index| self.elements[index].id);
+                // our new ids are the largest there are, so they go right after `after`
+                let index = self.element_index_after(after);
+                self.insert_elements(index, start, &text);
+                OpChange::Insert { after, start, text }
+            }
+            Change::Delete(start, length) => {
+                let end = start + length;
+                let mut ids = Vec::new();
+                let mut position = Position::zero();
+                for element in &mut self.elements {
+                    if element.is_deleted {
+                        continue;
+                    }
+                    if position >= end {
+                        break;
+                    }
+                    if position >= start {
+                        element.is_deleted = true;
+                        ids.push(element.id);
+                    }
+                    position = advance(position, element.char);
+                }
+                if ids.is_empty() {
+                    return;
+                }
+                OpChange::Delete {
+                    ranges: char_ranges(&ids),
+                }
+            }
+        };
+        let op = Op {
+            participant_id: self.participant_id,
+            seq: self.version.get(self.participant_id) + 1,
+            version: self.version.clone(),
+            change,
+        };
+        self.version.set(self.participant_id, op.seq);
+        self.log.push(op.clone());
+        self.outbox.push(op);
+    }
+
+    /// Applies ops from the others, which may arrive in any order and more than once. Ops
+    /// wait for the ones they depend on. Returns the edits to make to the local text, in order.
+    pub fn apply_remote_ops(&mut self, ops: impl IntoIterator<Item = Op>) -> Vec<Edit> {
+        self.pending.extend(ops);
+        let mut edits = Vec::new();
+        loop {
+            let version = &self.version;
+            self.pending
+                .retain(|op| op.seq > version.get(op.participant_id));
+            let index = match self.pending.iter().position(|op| {
+                op.seq == version.get(op.participant_id) + 1 && version.includes(&op.version)
+            }) {
+                Some(index) => index,
+                None => break,
+            };
+            let op = self.pending.swap_remove(index);
+            self.apply_remote_op(&op, &mut edits);
+            self.version.set(op.participant_id, op.seq);
+            self.log.push(op);
+        }
+        edits
+    }
+
+    pub fn apply_presence(&mut self, presence: Presence) {
+        if presence.participant_id == self.participant_id {
+            return;
+        }
+        self.remove_presence(presence.participant_id);
+        self.presences.push(presence);
+    }
+
+    /// Forgets the selections of a participant that left.
+    pub fn remove_presence(&mut self, participant_id: ParticipantId) {
+        self.presences
+            .retain(|presence| presence.participant_id != participant_id);
+    }
+
+    /// Our selections, to send to the others.
+    pub fn presence(&self, selections: &SelectionSet) -> Presence {
+        Presence {
+            participant_id: self.participant_id,
+            selections: selections
+                .as_selections()
+                .iter()
+                .map(|selection| AnchoredSelection {
+                    anchor: self.anchor(selection.anchor),
+                    cursor: self.anchor(selection.cursor.position),
+                })
+                .collect(),
+        }
+    }
+
+    /// Where the selections of the others are in the local text. Selections anchored to chars
+    /// this replica hasn't seen yet are left out until it has.
+    pub fn remote_selections(&self) -> Vec<(ParticipantId, Vec<Selection>)> {
+        self.presences
+            .iter()
+            .map(|presence| {
+                let selections = presence
+                    .selections
+                    .iter()
+                    .filter_map(|selection| {
+                        Some(Selection {
+                            anchor: self.resolve_anchor(selection.anchor)?,
+                            cursor: Cursor::from(self.resolve_anchor(selection.cursor)?),
+                        })
+                    })
+                    .collect();
+                (presence.participant_id, selections)
+            })
+            .collect()
+    }
+
+    pub fn anchor(&self, position: Position) -> Anchor {
+        Anchor {
+            after: self
+                .visible_index_before(position)
+                .map(|index| self.elements[index].id),
+        }
+    }
+
+    pub fn resolve_anchor(&self, anchor: Anchor) -> Option<Position> {
+        match anchor.after {
+            Some(id) => {
+                let index = self.elements.iter().position(|element| element.id == id)?;
+                Some(self.position_at(index + 1))
+            }
+            None => Some(Position::zero()),
+        }
+    }
+
+    fn apply_remote_op(&mut self, op: &Op, edits: &mut Vec<Edit>) {
+        match op.change {
+            OpChange::Insert {
+                after,
+                start,
+                ref text,
+            } => {
+                // chars inserted after the same one at the same time are ordered newest first
+                let mut index = self.element_index_after(after);
+                while index < self.elements.len() && self.elements[index].id > start {
+                    index += 1;
+                }
+                let position = self.position_at(index);
+                self.insert_elements(index, start, text);
+                edits.push(Edit {
+                    change: Change::Insert(position, Text::from(text.as_str())),
+                    drift: Drift::Before,
+                });
+            }
+            OpChange::Delete { ref ranges } => {
+                let ids: HashSet<_> = ranges.iter().flat_map(|range| range.ids()).collect();
+                // runs of chars next to each other in the text, from before any is deleted
+                let mut runs: Vec<(Position, Position)> = Vec::new();
+                let mut is_in_run = false;
+                let mut position = Position::zero();
+                for element in &mut self.elements {
+                    if element.is_deleted {
+                        continue;
+                    }
+                    let next_position = advance(position, element.char);
+                    if ids.contains(&element.id) {
+                        element.is_deleted = true;
+                        match runs.last_mut() {
+                            Some((_, end)) if is_in_run => *end = next_position,
+                            _ => runs.push((position, next_position)),
+                        }
+                        is_in_run = true;
+                    } else {
+                        is_in_run = false;
+                    }
+                    position = next_position;
+                }
+                // from the end, so the positions of the runs before stay the same
+                edits.extend(runs.into_iter().rev().map(|(start, end)| Edit {
+                    change: Change::Delete(start, end - start),
+                    drift: Drift::Before,
+                }));
+            }
+        }
+    }
+
+    /// Inserts the chars of `text` at `index`, with ids from `start` on.
+    fn insert_elements(&mut self, index: usize, start: CharId, text: &str) {
+        let elements: Vec<_> = text
+            .chars()
+            .enumerate()
+            .map(|(offset, char)| Element {
+                id: CharId {
+                    clock: start.clock + offset as u64,
+                    participant_id: start.participant_id,
+                },
+                char,
+                is_deleted: false,
+            })
+            .collect();
+        self.clock = self.clock.max(start.clock + elements.len() as u64 - 1);
+        self.elements.splice(index..index, elements);
+    }
+
+    /// The index right after the element with id `after`, or the start for `None`.
+    fn element_index_after(&self, after: Option<CharId>) -> usize {
+        match after {
+            Some(id) => {
+                self.elements
+                    .iter()
+                    .position(|element| element.id == id)
+                    .expect("ops are applied after the ones they depend on")
+                    + 1
+            }
+            None => 0,
+        }
+    }
+
+    /// The index of the last char that isn't deleted before `position`.
+    fn visible_index_before(&self, position: Position) -> Option<usize> {
+        let mut current_position = Position::zero();
+        let mut last_index = None;
+        for (index, element) in self.elements.iter().enumerate() {
+            if element.is_deleted {
+                continue;
+            }
+            if current_position >= position {
+                break;
+            }
+            current_position = advance(current_position, element.char);
+            last_index = Some(index);
+        }
+        last_index
+    }
+
+    /// The position in the text of the element at `index`, or of where one would be inserted.
+    fn position_at(&self, index: usize) -> Position {
+        self.elements[..index]
+            .iter()
+            .filter(|element| !element.is_deleted)
+            .fold(Position::zero(), |position, element| {
+                advance(position, element.char)
+            })
+    }
+}
+
+fn advance(position: Position, char: char) -> Position {
+    if char == '\n' {
+        Position {
+            line_index: position.line_index + 1,
+            byte_index: 0,
+        }
+    } else {
+        Position {
+            line_index: position.line_index,
+            byte_index: position.byte_index + char.len_utf8(),
+        }
+    }
+}
+
+/// Groups ids into runs that were inserted together.
+fn char_ranges(ids: &[CharId]) -> Vec<CharRange> {
+    let mut ranges: Vec<CharRange> = Vec::new();
+    for &id in ids {
+        match ranges.last_mut() {
+            Some(range)
+                if range.start.participant_id == id.participant_id
+                    && range.start.clock + range.len == id.clock =>
+            {
+                range.len += 1
+            }
+            _ => ranges.push(CharRange { start: id, len: 1 }),
+        }
+    }
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::text::Length};
+
+    fn position(byte_index: usize) -> Position {
+        Position {
+            line_index: 0,
+            byte_index,
+        }
+    }
+
+    /// Makes `change` to the text of `replica` and to the replica itself.
+    fn apply_local_change(text: &mut Text, replica: &mut Replica, change: Change) {
+        replica.apply_local_change(&change);
+        text.apply_change(change);
+    }
+
+    fn apply_remote_ops(text: &mut Text, replica: &mut Replica, ops: Vec<Op>) {
+        for edit in replica.apply_remote_ops(ops) {
+            text.apply_change(edit.change);
+        }
+        assert_eq!(*text, replica.text());
+    }
+
+    #[test]
+    fn insert_inside_concurrent_deletion_is_kept() {
+        let mut text_0 = Text::from("hello world");
+        let mut text_1 = text_0.clone();
+        let mut replica_0 = Replica::new(1, &text_0);
+        let mut replica_1 = Replica::new(2, &text_1);
+        apply_local_change(
+            &mut text_0,
+            &mut replica_0,
+            Change::Delete(
+                position(3),
+                Length {
+                    line_count: 0,
+                    byte_count: 5,
+                },
+            ),
+        );
+        apply_local_change(
+            &mut text_1,
+            &mut replica_1,
+            Change::Insert(position(5), Text::from("XY")),
+        );
+        let ops_0 = replica_0.take_ops();
+        let ops_1 = replica_1.take_ops();
+        apply_remote_ops(&mut text_0, &mut replica_0, ops_1);
+        apply_remote_ops(&mut text_1, &mut replica_1, ops_0);
+        assert_eq!(text_0.to_string(), "helXYrld");
+        assert_eq!(text_1.to_string(), "helXYrld");
+        assert_eq!(replica_0.version(), replica_1.version());
+    }
+
+    #[test]
+    fn ops_arriving_out_of_order_and_twice_are_applied_once() {
+        let mut text_0 = Text::from("ac");
+        let mut text_1 = text_0.clone();
+        let mut replica_0 = Replica::new(1, &text_0);
+        let mut replica_1 = Replica::new(2, &text_1);
+        apply_local_change(
+            &mut text_0,
+            &mut replica_0,
+            Change::Insert(position(1), Text::from("b")),
+        );
+        apply_local_change(
+            &mut text_0,
+            &mut replica_0,
+            Change::Insert(position(3), Text::from("\nd")),
+        );
+        let mut ops = replica_0.take_ops();
+        ops.reverse();
+        apply_remote_ops(&mut text_1, &mut replica_1, vec![ops[0].clone()]);
+        assert_eq!(text_1.to_string(), "ac");
+        apply_remote_ops(&mut text_1, &mut replica_1, ops.clone());
+        apply_remote_ops(&mut text_1, &mut replica_1, ops);
+        assert_eq!(text_1.to_string(), "abc\nd");
+        assert_eq!(text_0, text_1);
+    }
+
+    #[test]
+    fn replica_from_snapshot_catches_up() {
+        let mut text_0 = Text::from("abc");
+        let mut replica_0 = Replica::new(1, &text_0);
+        apply_local_change(
+            &mut text_0,
+            &mut replica_0,
+            Change::Delete(
+                position(1),
+                Length {
+                    line_count: 0,
+                    byte_count: 1,
+                },
+            ),
+        );
+        let snapshot = replica_0.snapshot();
+        apply_local_change(
+            &mut text_0,
+            &mut replica_0,
+            Change::Insert(position(2), Text::from("d")),
+        );
+        let mut replica_1 = Replica::from_snapshot(2, snapshot);
+        let mut text_1 = replica_1.text();
+        assert_eq!(text_1.to_string(), "ac");
+        let ops = replica_0.ops_since(replica_1.version());
+        apply_remote_ops(&mut text_1, &mut replica_1, ops);
+        assert_eq!(text_1.to_string(), "acd");
+    }
+}
@@ -0,0 +1,289 @@
+use {
+    crate::{
+        signature_help::{
+            call_context, FnSignatures, SignatureHelp, SignatureProvider, SignatureRequest,
+        },
+        text::{Position, Text},
+        Document,
+    },
+    std::collections::HashMap,
+};
+
+/// How many completions are offered at most.
+const MAX_COMPLETIONS: usize = 50;
+
+/// A suggestion for the word being typed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Completion {
+    /// What the list shows.
+    pub label: String,
+    pub kind: CompletionKind,
+    /// What replaces the word being typed.
+    pub text: String,
+    /// Higher is better. Only comparable between completions of the same provider.
+    pub score: f64,
+}
+
+impl Completion {
+    /// A word that completes as itself.
+    pub fn word(text: String, score: f64) -> Self {
+        Self {
+            label: text.clone(),
+            kind: CompletionKind::Word,
+            text,
+            score,
+        }
+    }
+}
+
+/// What a completion is, shown next to it in the list.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum CompletionKind {
+    /// A word found in a document, without knowing what it is.
+    Word,
+    Keyword,
+    Function,
+    Variable,
+    Field,
+    Type,
+    Module,
+    Snippet,
+}
+
+impl CompletionKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Word => "",
+            Self::Keyword => "keyword",
+            Self::Function => "fn",
+            Self::Variable => "var",
+            Self::Field => "field",
+            Self::Type => "type",
+            Self::Module => "mod",
+            Self::Snippet => "snippet",
+        }
+    }
+}
+
+/// What to complete: the word before the cursor in the document being edited.
+pub struct CompletionRequest<'a> {
+    pub text: &'a Text,
+    pub position: Position,
+    pub prefix: &'a str,
+    /// The other open documents.
+    pub documents: &'a [Document],
+}
+
+pub trait CompletionProvider {
+    fn complete(&self, request: &CompletionRequest) -> Vec<Completion>;
+}
+
+/// Collects completions from its providers. Earlier providers rank above later ones, and the
+/// words of the open documents come last, so there is something to complete even when nothing
+/// knows the language. Signature help for calls works the same way.
+#[derive(Default)]
+pub struct CompletionEngine {
+    providers: Vec<Box<dyn CompletionProvider>>,
+    fallback: WordCompletions,
+    signature_providers: Vec<Box<dyn SignatureProvider>>,
+    signature_fallback: FnSignatures,
+    documents: Vec<Document>,
+}
+
+impl CompletionEngine {
+    pub fn add_provider(&mut self, provider: Box<dyn CompletionProvider>) {
+        self.providers.push(provider);
+    }
+
+    pub fn add_signature_provider(&mut self, provider: Box<dyn SignatureProvider>) {
+        self.signature_providers.push(provider);
+    }
+
+    pub fn set_word_completions(&mut self, fallback: WordCompletions) {
+        self.fallback = fallback;
+    }
+
+    /// The other open documents, whose words are offered too.
+    pub fn set_documents(&mut self, documents: Vec<Document>) {
+        self.documents = documents;
+    }
+
+    pub fn complete(&self, text: &Text, position: Position) -> Vec<Completion> {
+        let prefix = word_before(text, position);
+        if prefix.is_empty() {
+            return Vec::new();
+        }
+        let request = CompletionRequest {
+            text,
+            position,
+            prefix,
+            documents: &self.documents,
+        };
+        let mut completions: Vec<Completion> = Vec::new();
+        let providers = self
+            .providers
+            .iter()
+            .map(|provider| provider.as_ref())
+            .chain([&self.fallback as &dyn CompletionProvider]);
+        for provider in providers {
+            let mut provided = provider.complete(&request);
+            provided.sort_by(|a, b| {
+                b.score
+                    .total_cmp(&a.score)
+                    .then_with(|| a.text.cmp(&b.text))
+            });
+            for completion in provided {
+                if completion.text != prefix
+                    && !completions
+                        .iter()
+                        .any(|other| other.text == completion.text)
+                {
+                    completions.push(completion);
+                }
+            }
+        }
+        completions.truncate(MAX_COMPLETIONS);
+        completions
+    }
+
+    /// The signature of the function whose call `position` is in, from the first provider that
+    /// knows it.
+    pub fn signature_help(&self, text: &Text, position: Position) -> Option<SignatureHelp> {
+        let call = call_context(text, position)?;
+        let request = SignatureRequest {
+            text,
+            position,
+            call: &call,
+            documents: &self.documents,
+        };
+        let signature = self
+            .signature_providers
+            .iter()
+            .map(|provider| provider.as_ref())
+            .chain([&self.signature_fallback as &dyn SignatureProvider])
+            .find_map(|provider| provider.signature(&request))?;
+        Some(SignatureHelp::new(signature, &call))
+    }
+}
+
+/// Offers the words of the open documents that start with what is being typed. Words close to
+/// the cursor and words that occur often rank higher, words that only match when ignoring case
+/// rank lower.
+pub struct WordCompletions {
+    pub min_word_len: usize,
+    pub include_other_documents: bool,
+}
+
+impl Default for WordCompletions {
+    fn default() -> Self {
+        Self {
+            min_word_len: 3,
+            include_other_documents: true,
+        }
+    }
+}
+
+impl CompletionProvider for WordCompletions {
+    fn complete(&self, request: &CompletionRequest) -> Vec<Completion> {
+        let mut scores: HashMap<&str, f64> = HashMap::new();
+        let prefix_lower = request.prefix.to_lowercase();
+        for (line_index, line) in request.text.as_lines().iter().enumerate() {
+            for (byte_index, word) in words(line) {
+                // the word being typed isn't a completion of itself
+                if line_index == request.position.line_index
+                    && byte_index + word.len() == request.position.byte_index
+                {
+                    continue;
+                }
+                let distance = line_index.abs_diff(request.position.line_index) as f64;
+                if let Some(weight) = self.matches(word, request.prefix, &prefix_lower) {
+                    *scores.entry(word).or_default() += weight / (1.0 + distance).sqrt();
+                }
+            }
+        }
+        let mut completions: Vec<Completion> = scores
+            .into_iter()
+            .map(|(text, score)| Completion::word(text.to_string(), score))
+            .collect();
+        if self.include_other_documents {
+            let mut other_scores: HashMap<String, f64> = HashMap::new();
+            for document in request.documents {
+                let text = document.as_text();
+                if std::ptr::eq(&*text, request.text) {
+                    continue;
+                }
+                for line in text.as_lines() {
+                    for (_, word) in words(line) {
+                        if let Some(weight) = self.matches(word, request.prefix, &prefix_lower) {
+                            // far away, as if it were a long way down the document
+                            *other_scores.entry(word.to_string()).or_default() += weight * 0.01;
+                        }
+                    }
+                }
+            }
+            for (text, score) in other_scores {
+                if let Some(completion) = completions.iter_mut().find(|c| c.text == text) {
+                    completion.score += score;
+                } else {
+                    completions.push(Completion::word(text, score));
+                }
+            }
+        }
+        completions
+    }
+}
+
+impl WordCompletions {
+    fn matches(&self, word: &str, prefix: &str, prefix_lower: &str) -> Option<f64> {
+        if word.len() < self.min_word_len || word.len() <= prefix.len() {
+            return None;
+        }
+        if word.starts_with(prefix) {
+            Some(1.0)
+        } else if word.to_lowercase().starts_with(prefix_lower) {
+            Some(0.25)
+        } else {
+            None
+        }
+    }
+}
+
+fn is_word_char(char: char) -> bool {
+    char.is_alphanumeric() || char == '_'
+}
+
+/// The words of a line with their byte index. Numbers aren't words.
+fn words(line: &str) -> impl Iterator<Item = (usize, &str)> {
+    let mut start = None;
+    line.char_indices()
+        .chain([(line.len(), ' ')])
+        .filter_map(move |(index, char)| {
+            if is_word_char(char) {
+                start.get_or_insert(index);
+                None
+            } else {
+                let start = start.take()?;
+                let word = &line[start..index];
+                (!word.starts_with(|char: char| char.is_ascii_digit())).then_some((start, word))
+            }
+        })
+}
+
+/// The part of the word before `position`, which is what completions complete.
+pub fn word_before(text: &Text, position: Position) -> &str {
+    let Some(line) = text.as_lines().get(position.line_index) else {
+        return "";
+    };
+    let before = &line[..position.byte_index.min(line.len())];
+    let start = before
+        .char_indices()
+        .rev()
+        .take_while(|(_, char)| is_word_char(*char))
+        .last()
+        .map_or(before.len(), |(index, _)| index);
+    let word = &before[start..];
+    if word.starts_with(|char: char| char.is_ascii_digit()) {
+        return "";
+    }
+    word
+}
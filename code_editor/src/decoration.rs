@@ -7,6 +7,9 @@ use {
 pub enum DecorationType {
     Error,
     Warning,
+    Information,
+    SearchMatch,
+    CurrentSearchMatch,
 }
 
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
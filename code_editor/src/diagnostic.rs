@@ -0,0 +1,184 @@
+use {
+    crate::{
+        decoration::{Decoration, DecorationType},
+        text::{Edit, Position, Text},
+    },
+    std::{ops::Deref, slice::Iter},
+};
+
+/// How bad a diagnostic is. Ordered from worst to mildest.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Severity {
+    Error,
+    Warning,
+    Information,
+    Hint,
+}
+
+impl Severity {
+    pub fn decoration_type(self) -> DecorationType {
+        match self {
+            Self::Error => DecorationType::Error,
+            Self::Warning => DecorationType::Warning,
+            Self::Information | Self::Hint => DecorationType::Information,
+        }
+    }
+}
+
+/// A problem in a document, reported by a compiler or language server.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub start: Position,
+    pub end: Position,
+    pub message: String,
+    /// What reported it, like `rustc` or `clippy`.
+    pub source: Option<String>,
+}
+
+impl Diagnostic {
+    pub fn contains(&self, position: Position) -> bool {
+        (self.start..=self.end).contains(&position)
+    }
+
+    pub fn apply_edit(&mut self, edit: &Edit) {
+        self.start = self.start.apply_edit(edit);
+        self.end = self.end.apply_edit(edit);
+    }
+}
+
+/// The diagnostics of a document, sorted by where they start.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct DiagnosticSet {
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl DiagnosticSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn as_diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    pub fn set(&mut self, mut diagnostics: Vec<Diagnostic>) {
+        for diagnostic in &mut diagnostics {
+            if diagnostic.start > diagnostic.end {
+                std::mem::swap(&mut diagnostic.start, &mut diagnostic.end);
+            }
+        }
+        diagnostics.sort_by_key(|diagnostic| (diagnostic.start, diagnostic.severity));
+        self.diagnostics = diagnostics;
+    }
+
+    pub fn clear(&mut self) {
+        self.diagnostics.clear();
+    }
+
+    pub fn apply_edit(&mut self, edit: &Edit) {
+        for diagnostic in &mut self.diagnostics {
+            diagnostic.apply_edit(edit);
+        }
+    }
+
+    /// The diagnostics whose range contains `position`, worst first.
+    pub fn at(&self, position: Position) -> Vec<&Diagnostic> {
+        let mut diagnostics: Vec<_> = self
+            .diagnostics
+            .iter()
+            .take_while(|diagnostic| diagnostic.start <= position)
+            .filter(|diagnostic| diagnostic.contains(position))
+            .collect();
+        diagnostics.sort_by_key(|diagnostic| diagnostic.severity);
+        diagnostics
+    }
+
+    /// The diagnostics that start on the line, worst first.
+    pub fn on_line(&self, line_index: usize) -> Vec<&Diagnostic> {
+        let mut diagnostics: Vec<_> = self
+            .diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.start.line_index == line_index)
+            .collect();
+        diagnostics.sort_by_key(|diagnostic| diagnostic.severity);
+        diagnostics
+    }
+
+    /// The worst severity of the diagnostics that start on each line from `line_start` up to
+    /// `line_end`, for markers in the gutter.
+    pub fn line_severities(&self, line_start: usize, line_end: usize) -> Vec<Option<Severity>> {
+        let mut severities: Vec<Option<Severity>> = vec![None; line_end.saturating_sub(line_start)];
+        for diagnostic in &self.diagnostics {
+            let line_index = diagnostic.start.line_index;
+            if (line_start..line_end).contains(&line_index) {
+                let severity = &mut severities[line_index - line_start];
+                *severity = Some(severity.map_or(diagnostic.severity, |severity| {
+                    severity.min(diagnostic.severity)
+                }));
+            }
+        }
+        severities
+    }
+
+    /// The ranges to underline, which can't overlap, so where they do the worse diagnostic
+    /// wins. Empty ranges, like those of a missing semicolon, are widened to the character
+    /// next to them so they can be seen.
+    pub fn decorations(&self, text: &Text) -> Vec<Decoration> {
+        let lines = text.as_lines();
+        let mut decorations: Vec<(Severity, Decoration)> = Vec::new();
+        for (index, diagnostic) in self.diagnostics.iter().enumerate() {
+            let (mut start, mut end) = (diagnostic.start, diagnostic.end);
+            if start == end {
+                let Some(line) = lines.get(start.line_index) else {
+                    continue;
+                };
+                if let Some(char) = line
+                    .get(start.byte_index..)
+                    .and_then(|rest| rest.chars().next())
+                {
+                    end.byte_index += char.len_utf8();
+                } else if let Some(char) = line
+                    .get(..start.byte_index)
+                    .and_then(|before| before.chars().next_back())
+                {
+                    start.byte_index -= char.len_utf8();
+                } else {
+                    continue;
+                }
+            }
+            let decoration =
+                Decoration::new(index, start, end, diagnostic.severity.decoration_type());
+            match decorations.last_mut() {
+                Some((severity, last)) if last.overlaps_with(decoration) => {
+                    if diagnostic.severity < *severity {
+                        *severity = diagnostic.severity;
+                        *last = decoration;
+                    }
+                }
+                _ => decorations.push((diagnostic.severity, decoration)),
+            }
+        }
+        decorations
+            .into_iter()
+            .map(|(_, decoration)| decoration)
+            .collect()
+    }
+}
+
+impl Deref for DiagnosticSet {
+    type Target = [Diagnostic];
+
+    fn deref(&self) -> &Self::Target {
+        self.diagnostics.as_slice()
+    }
+}
+
+impl<'a> IntoIterator for &'a DiagnosticSet {
+    type Item = &'a Diagnostic;
+    type IntoIter = Iter<'a, Diagnostic>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
@@ -0,0 +1,227 @@
+use {crate::text::Text, std::ops::Range};
+
+/// How many edits a diff looks for before it gives up and reports the rest as one hunk, to keep
+/// the time and memory of diffing texts that have little in common bounded.
+const MAX_EDIT_COUNT: usize = 2048;
+
+/// A run of lines in the old text that is replaced by a run of lines in the new text. Either
+/// run may be empty, for lines that were only added or only removed.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Hunk {
+    pub old: Range<usize>,
+    pub new: Range<usize>,
+}
+
+impl Hunk {
+    pub fn is_insertion(&self) -> bool {
+        self.old.is_empty()
+    }
+
+    pub fn is_deletion(&self) -> bool {
+        self.new.is_empty()
+    }
+}
+
+/// The hunks that turn `old` into `new`, in order, found with Myers' algorithm.
+pub fn diff<T: PartialEq>(old: &[T], new: &[T]) -> Vec<Hunk> {
+    // while typing, most lines at the start and the end are the same, and cheap to skip
+    let prefix = old
+        .iter()
+        .zip(new)
+        .take_while(|(old, new)| old == new)
+        .count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(old, new)| old == new)
+        .count();
+    let old_end = old.len() - suffix;
+    let new_end = new.len() - suffix;
+    let mut hunks = Vec::new();
+    let mut hunk: Option<Hunk> = None;
+    for (old_index, new_index, step) in shortest_edit(&old[prefix..old_end], &new[prefix..new_end])
+    {
+        let (old_index, new_index) = (old_index + prefix, new_index + prefix);
+        match step {
+            Step::Keep => hunks.extend(hunk.take()),
+            Step::Delete => {
+                hunk.get_or_insert(Hunk {
+                    old: old_index..old_index,
+                    new: new_index..new_index,
+                })
+                .old
+                .end = old_index + 1;
+            }
+            Step::Insert => {
+                hunk.get_or_insert(Hunk {
+                    old: old_index..old_index,
+                    new: new_index..new_index,
+                })
+                .new
+                .end = new_index + 1;
+            }
+        }
+    }
+    hunks.extend(hunk);
+    hunks
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Step {
+    Keep,
+    Delete,
+    Insert,
+}
+
+/// The steps of a shortest edit script from `old` to `new`, each with the indices in both it
+/// starts at.
+fn shortest_edit<T: PartialEq>(old: &[T], new: &[T]) -> Vec<(usize, usize, Step)> {
+    let (n, m) = (old.len() as isize, new.len() as isize);
+    let max = (old.len() + new.len()).min(MAX_EDIT_COUNT) as isize;
+    let offset = max + 1;
+    let mut v = vec![0isize; 2 * offset as usize + 1];
+    // the furthest x on each diagonal after each number of edits, to walk back along
+    let mut trace = Vec::new();
+    let mut found = false;
+    'outer: for d in 0..=max {
+        trace.push(v.clone());
+        for k in (-d..=d).step_by(2) {
+            let mut x = if k == -d
+                || (k != d && v[(offset + k - 1) as usize] < v[(offset + k + 1) as usize])
+            {
+                v[(offset + k + 1) as usize]
+            } else {
+                v[(offset + k - 1) as usize] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && old[x as usize] == new[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[(offset + k) as usize] = x;
+            if x >= n && y >= m {
+                found = true;
+                break 'outer;
+            }
+        }
+    }
+    if !found {
+        // too different to be worth it, so everything is replaced
+        let mut steps: Vec<_> = (0..old.len()).map(|x| (x, 0, Step::Delete)).collect();
+        steps.extend((0..new.len()).map(|y| (old.len(), y, Step::Insert)));
+        return steps;
+    }
+    let mut steps = Vec::new();
+    let (mut x, mut y) = (n, m);
+    for (d, v) in trace.iter().enumerate().rev() {
+        let d = d as isize;
+        let k = x - y;
+        let prev_k =
+            if k == -d || (k != d && v[(offset + k - 1) as usize] < v[(offset + k + 1) as usize]) {
+                k + 1
+            } else {
+                k - 1
+            };
+        let prev_x = if d == 0 {
+            0
+        } else {
+            v[(offset + prev_k) as usize]
+        };
+        let prev_y = prev_x - prev_k;
+        while x > prev_x && y > prev_y {
+            x -= 1;
+            y -= 1;
+            steps.push((x as usize, y as usize, Step::Keep));
+        }
+        if d > 0 {
+            if x == prev_x {
+                steps.push((prev_x as usize, prev_y as usize, Step::Insert));
+            } else {
+                steps.push((prev_x as usize, prev_y as usize, Step::Delete));
+            }
+        }
+        x = prev_x;
+        y = prev_y;
+    }
+    steps.reverse();
+    steps
+}
+
+/// How a line differs from the baseline, for the markers in the gutter.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum LineChange {
+    Added,
+    Modified,
+    /// Lines of the baseline were removed just above this one.
+    RemovedAbove,
+    /// Lines of the baseline were removed just below this one, at the end of the text.
+    RemovedBelow,
+}
+
+/// Keeps track of how a text differs from a baseline, like the last saved version or the one
+/// checked in.
+#[derive(Clone, Debug, Default)]
+pub struct ChangeTracker {
+    baseline: Option<Vec<String>>,
+    hunks: Vec<Hunk>,
+    line_changes: Vec<Option<LineChange>>,
+}
+
+impl ChangeTracker {
+    pub fn baseline(&self) -> Option<&[String]> {
+        self.baseline.as_deref()
+    }
+
+    /// Compares `text` against `baseline` from now on, or stops comparing for `None`.
+    pub fn set_baseline(&mut self, baseline: Option<&Text>, text: &Text) {
+        self.baseline = baseline.map(|baseline| baseline.as_lines().to_vec());
+        self.update(text);
+    }
+
+    pub fn hunks(&self) -> &[Hunk] {
+        &self.hunks
+    }
+
+    /// How each line of the text differs from the baseline, `None` for lines that don't.
+    pub fn line_changes(&self) -> &[Option<LineChange>] {
+        &self.line_changes
+    }
+
+    /// Compares the text with the baseline again after it was edited.
+    pub fn update(&mut self, text: &Text) {
+        let lines = text.as_lines();
+        self.line_changes.clear();
+        let Some(baseline) = &self.baseline else {
+            self.hunks.clear();
+            return;
+        };
+        self.hunks = diff(baseline, lines);
+        self.line_changes.resize(lines.len(), None);
+        for hunk in &self.hunks {
+            if hunk.is_deletion() {
+                let line_change = if hunk.new.start < lines.len() {
+                    &mut self.line_changes[hunk.new.start]
+                } else {
+                    &mut self.line_changes[lines.len() - 1]
+                };
+                if line_change.is_none() {
+                    *line_change = Some(if hunk.new.start < lines.len() {
+                        LineChange::RemovedAbove
+                    } else {
+                        LineChange::RemovedBelow
+                    });
+                }
+                continue;
+            }
+            let line_change = if hunk.is_insertion() {
+                LineChange::Added
+            } else {
+                LineChange::Modified
+            };
+            for line_index in hunk.new.clone() {
+                self.line_changes[line_index] = Some(line_change);
+            }
+        }
+    }
+}
@@ -0,0 +1,490 @@
+use {
+    crate::{
+        diff::{self, Hunk},
+        str::StrExt,
+        text::Text,
+    },
+    makepad_widgets::*,
+    std::fmt::Write,
+};
+
+live_design! {
+    import makepad_draw::shader::std::*;
+    import makepad_widgets::theme_desktop_dark::*;
+
+    DiffView = {{DiffView}} {
+        width: Fill, height: Fill,
+        scroll_bars: <ScrollBars> {}
+        draw_bg: { color: (THEME_COLOR_BG_CONTAINER) }
+        draw_gutter: {
+            text_style: <THEME_FONT_CODE> {},
+            color: (THEME_COLOR_TEXT_META),
+        }
+        draw_text: {
+            text_style: <THEME_FONT_CODE> {},
+            color: (THEME_COLOR_TEXT_DEFAULT),
+        }
+        draw_header: {
+            text_style: <THEME_FONT_CODE> {},
+            color: (THEME_COLOR_TEXT_META),
+        }
+        draw_button: {
+            text_style: <THEME_FONT_CODE> {},
+            color: (THEME_COLOR_TEXT_HL),
+        }
+        draw_header_bg: { color: #fff1 }
+        draw_selected: { color: #fff2 }
+        draw_inserted: { color: #487e0240 }
+        draw_deleted: { color: #f14c4c40 }
+        draw_filler: { color: #0003 }
+    }
+}
+
+/// What the user asked to do with a hunk of a `DiffView`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DiffViewAction {
+    /// Stage the hunk with this index, see `DiffView::hunks`.
+    StageHunk(usize),
+    /// The new lines of the hunk with this index were copied to the clipboard.
+    CopyHunk(usize),
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum HunkButton {
+    Stage,
+    Copy,
+}
+
+/// A row of the view. Side by side, a row can have a line of both texts, and in a hunk one of
+/// them may be missing. Unified, a row has one line, of the old text if it was deleted and of the
+/// new text otherwise.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Row {
+    Header(usize),
+    Lines {
+        old: Option<usize>,
+        new: Option<usize>,
+        hunk: Option<usize>,
+    },
+}
+
+/// Shows how two texts differ, side by side or unified, for instance to review changes before
+/// committing them. Lines that are the same are lined up, deleted lines are colored on the old
+/// side and inserted lines on the new side. Hunks can be selected, copied and staged.
+#[derive(Live, LiveHook, LiveRegister)]
+pub struct DiffView {
+    #[walk]
+    walk: Walk,
+    #[live]
+    scroll_bars: ScrollBars,
+    #[live]
+    draw_bg: DrawColor,
+    #[live]
+    draw_gutter: DrawText,
+    #[live]
+    draw_text: DrawText,
+    #[live]
+    draw_header: DrawText,
+    #[live]
+    draw_button: DrawText,
+    #[live]
+    draw_header_bg: DrawColor,
+    #[live]
+    draw_selected: DrawColor,
+    #[live]
+    draw_inserted: DrawColor,
+    #[live]
+    draw_deleted: DrawColor,
+    #[live]
+    draw_filler: DrawColor,
+    #[live(false)]
+    unified: bool,
+    #[rust]
+    old_lines: Vec<String>,
+    #[rust]
+    new_lines: Vec<String>,
+    #[rust]
+    hunks: Vec<Hunk>,
+    #[rust]
+    rows: Vec<Row>,
+    #[rust]
+    selected_hunk: Option<usize>,
+    #[rust]
+    cell_size: DVec2,
+    #[rust]
+    viewport_rect: Rect,
+    /// Where the buttons of the hunks in view were drawn, to find the one that was clicked.
+    #[rust]
+    buttons: Vec<(Rect, usize, HunkButton)>,
+}
+
+impl DiffView {
+    pub fn redraw(&mut self, cx: &mut Cx) {
+        self.scroll_bars.redraw(cx);
+    }
+
+    /// Shows how `new` differs from `old`.
+    pub fn set_texts(&mut self, cx: &mut Cx, old: &Text, new: &Text) {
+        self.old_lines = old.as_lines().to_vec();
+        self.new_lines = new.as_lines().to_vec();
+        self.hunks = diff::diff(&self.old_lines, &self.new_lines);
+        self.selected_hunk = None;
+        self.update_rows();
+        self.redraw(cx);
+    }
+
+    pub fn is_unified(&self) -> bool {
+        self.unified
+    }
+
+    pub fn set_unified(&mut self, cx: &mut Cx, unified: bool) {
+        if self.unified != unified {
+            self.unified = unified;
+            self.update_rows();
+            self.redraw(cx);
+        }
+    }
+
+    pub fn hunks(&self) -> &[Hunk] {
+        &self.hunks
+    }
+
+    pub fn old_lines(&self) -> &[String] {
+        &self.old_lines
+    }
+
+    pub fn new_lines(&self) -> &[String] {
+        &self.new_lines
+    }
+
+    pub fn selected_hunk(&self) -> Option<usize> {
+        self.selected_hunk
+    }
+
+    /// Selects the hunk with `index` and scrolls it into view.
+    pub fn select_hunk(&mut self, cx: &mut Cx, index: usize) {
+        if index >= self.hunks.len() {
+            return;
+        }
+        self.selected_hunk = Some(index);
+        if let Some(row_index) = self.rows.iter().position(|row| *row == Row::Header(index)) {
+            let y = row_index as f64 * self.cell_size.y;
+            self.scroll_bars.scroll_into_view(
+                cx,
+                Rect {
+                    pos: dvec2(0.0, y),
+                    size: dvec2(0.0, self.cell_size.y * 4.0),
+                },
+            );
+        }
+        self.redraw(cx);
+    }
+
+    /// The new lines of the hunk with `index`, as they would be pasted.
+    pub fn hunk_text(&self, index: usize) -> Option<String> {
+        let hunk = self.hunks.get(index)?;
+        Some(self.new_lines[hunk.new.clone()].join("\n"))
+    }
+
+    fn update_rows(&mut self) {
+        self.rows.clear();
+        let mut old_index = 0;
+        let mut new_index = 0;
+        for (hunk_index, hunk) in self.hunks.iter().enumerate() {
+            // the lines before the hunk are the same in both
+            while old_index < hunk.old.start {
+                self.rows.push(Row::Lines {
+                    old: Some(old_index),
+                    new: Some(new_index),
+                    hunk: None,
+                });
+                old_index += 1;
+                new_index += 1;
+            }
+            self.rows.push(Row::Header(hunk_index));
+            let hunk = Some(hunk_index);
+            if self.unified {
+                for old in self.hunks[hunk_index].old.clone() {
+                    self.rows.push(Row::Lines {
+                        old: Some(old),
+                        new: None,
+                        hunk,
+                    });
+                }
+                for new in self.hunks[hunk_index].new.clone() {
+                    self.rows.push(Row::Lines {
+                        old: None,
+                        new: Some(new),
+                        hunk,
+                    });
+                }
+            } else {
+                let old_range = self.hunks[hunk_index].old.clone();
+                let new_range = self.hunks[hunk_index].new.clone();
+                for offset in 0..old_range.len().max(new_range.len()) {
+                    self.rows.push(Row::Lines {
+                        old: Some(old_range.start + offset).filter(|old| old_range.contains(old)),
+                        new: Some(new_range.start + offset).filter(|new| new_range.contains(new)),
+                        hunk,
+                    });
+                }
+            }
+            old_index = self.hunks[hunk_index].old.end;
+            new_index = self.hunks[hunk_index].new.end;
+        }
+        while old_index < self.old_lines.len() {
+            self.rows.push(Row::Lines {
+                old: Some(old_index),
+                new: Some(new_index),
+                hunk: None,
+            });
+            old_index += 1;
+            new_index += 1;
+        }
+    }
+
+    fn row_at(&self, abs: DVec2) -> Option<Row> {
+        let row_index = (abs.y - self.viewport_rect.pos.y) / self.cell_size.y;
+        if row_index < 0.0 {
+            return None;
+        }
+        self.rows.get(row_index as usize).copied()
+    }
+
+    pub fn draw_walk_diff(&mut self, cx: &mut Cx2d, walk: Walk) {
+        self.cell_size =
+            self.draw_text.text_style.font_size * self.draw_text.get_monospace_base(cx);
+        self.scroll_bars.begin(cx, walk, Layout::default());
+        let turtle_rect = cx.turtle().rect();
+        self.draw_bg.draw_abs(cx, cx.turtle().unscrolled_rect());
+        let pad_left_top = dvec2(10.0, 10.0);
+        self.viewport_rect = Rect {
+            pos: turtle_rect.pos + pad_left_top,
+            size: turtle_rect.size - pad_left_top,
+        };
+        let scroll_pos = self.scroll_bars.get_scroll_pos();
+        let row_start = (scroll_pos.y / self.cell_size.y) as usize;
+        let row_end =
+            (row_start + (turtle_rect.size.y / self.cell_size.y) as usize + 2).min(self.rows.len());
+
+        let number_column_count = self
+            .old_lines
+            .len()
+            .max(self.new_lines.len())
+            .to_string()
+            .len();
+        let gutter_width = (number_column_count + 2) as f64 * self.cell_size.x;
+        let (column_width, column_count) = if self.unified {
+            (self.viewport_rect.size.x, usize::MAX)
+        } else {
+            let column_width = (self.viewport_rect.size.x / 2.0).max(gutter_width);
+            let column_count = ((column_width - gutter_width) / self.cell_size.x).max(1.0);
+            (column_width, column_count as usize - 1)
+        };
+        let mut max_width = 0.0f64;
+        let mut buf = String::new();
+        self.buttons.clear();
+        for row_index in row_start..row_end {
+            let y = self.viewport_rect.pos.y + row_index as f64 * self.cell_size.y;
+            let row_rect = Rect {
+                pos: dvec2(turtle_rect.pos.x, y),
+                size: dvec2(turtle_rect.size.x.max(max_width), self.cell_size.y),
+            };
+            match self.rows[row_index] {
+                Row::Header(hunk_index) => {
+                    self.draw_header_bg.draw_abs(cx, row_rect);
+                    let hunk = &self.hunks[hunk_index];
+                    buf.clear();
+                    let _ = write!(
+                        buf,
+                        "@@ -{},{} +{},{} @@",
+                        hunk.old.start + 1,
+                        hunk.old.len(),
+                        hunk.new.start + 1,
+                        hunk.new.len()
+                    );
+                    let mut x = self.viewport_rect.pos.x;
+                    self.draw_header.draw_abs(cx, dvec2(x, y), &buf);
+                    x += (buf.column_count() + 2) as f64 * self.cell_size.x;
+                    for (label, button) in
+                        [("Stage", HunkButton::Stage), ("Copy", HunkButton::Copy)]
+                    {
+                        self.draw_button.draw_abs(cx, dvec2(x, y), label);
+                        let width = label.column_count() as f64 * self.cell_size.x;
+                        self.buttons.push((
+                            Rect {
+                                pos: dvec2(x, y),
+                                size: dvec2(width, self.cell_size.y),
+                            },
+                            hunk_index,
+                            button,
+                        ));
+                        x += width + 2.0 * self.cell_size.x;
+                    }
+                }
+                Row::Lines { old, new, hunk } => {
+                    if hunk.is_some() && hunk == self.selected_hunk {
+                        self.draw_selected.draw_abs(cx, row_rect);
+                    }
+                    if self.unified {
+                        let (line_index, lines, prefix) = match (old, new) {
+                            (Some(old), None) => (old, &self.old_lines, "-"),
+                            (None, Some(new)) => (new, &self.new_lines, "+"),
+                            (_, new) => (new.unwrap(), &self.new_lines, " "),
+                        };
+                        match prefix {
+                            "-" => self.draw_deleted.draw_abs(cx, row_rect),
+                            "+" => self.draw_inserted.draw_abs(cx, row_rect),
+                            _ => {}
+                        }
+                        buf.clear();
+                        let _ = write!(
+                            buf,
+                            "{: >width$} {}",
+                            line_index + 1,
+                            prefix,
+                            width = number_column_count
+                        );
+                        self.draw_gutter
+                            .draw_abs(cx, dvec2(self.viewport_rect.pos.x, y), &buf);
+                        let line = &lines[line_index];
+                        let x = self.viewport_rect.pos.x + gutter_width;
+                        self.draw_text.draw_abs(cx, dvec2(x, y), line);
+                        max_width = max_width
+                            .max(gutter_width + line.column_count() as f64 * self.cell_size.x);
+                    } else {
+                        for (side, line_index) in [old, new].into_iter().enumerate() {
+                            let x = self.viewport_rect.pos.x + side as f64 * column_width;
+                            let side_rect = Rect {
+                                pos: dvec2(x, y),
+                                size: dvec2(column_width, self.cell_size.y),
+                            };
+                            let Some(line_index) = line_index else {
+                                self.draw_filler.draw_abs(cx, side_rect);
+                                continue;
+                            };
+                            if hunk.is_some() {
+                                if side == 0 {
+                                    self.draw_deleted.draw_abs(cx, side_rect);
+                                } else {
+                                    self.draw_inserted.draw_abs(cx, side_rect);
+                                }
+                            }
+                            buf.clear();
+                            let _ = write!(
+                                buf,
+                                "{: >width$}",
+                                line_index + 1,
+                                width = number_column_count
+                            );
+                            self.draw_gutter.draw_abs(cx, dvec2(x, y), &buf);
+                            let lines = if side == 0 {
+                                &self.old_lines
+                            } else {
+                                &self.new_lines
+                            };
+                            // lines are cut off at the middle, so the old side doesn't run into
+                            // the new side
+                            let line = &lines[line_index];
+                            let end = line
+                                .char_indices()
+                                .nth(column_count)
+                                .map_or(line.len(), |(index, _)| index);
+                            self.draw_text
+                                .draw_abs(cx, dvec2(x + gutter_width, y), &line[..end]);
+                        }
+                    }
+                }
+            }
+        }
+        cx.turtle_mut().set_used(
+            max_width + pad_left_top.x,
+            self.rows.len() as f64 * self.cell_size.y + self.viewport_rect.size.y,
+        );
+        self.scroll_bars.end(cx);
+    }
+
+    pub fn handle_event(&mut self, cx: &mut Cx, event: &Event) -> Vec<DiffViewAction> {
+        let mut actions = Vec::new();
+        if !self.scroll_bars.handle_event(cx, event).is_empty() {
+            self.redraw(cx);
+        }
+        match event.hits(cx, self.scroll_bars.area()) {
+            Hit::FingerDown(FingerDownEvent { abs, .. }) => {
+                cx.set_key_focus(self.scroll_bars.area());
+                if let Some(&(_, hunk_index, button)) =
+                    self.buttons.iter().find(|(rect, _, _)| rect.contains(abs))
+                {
+                    self.selected_hunk = Some(hunk_index);
+                    actions.push(self.press_button(cx, hunk_index, button));
+                } else {
+                    self.selected_hunk = match self.row_at(abs) {
+                        Some(Row::Header(hunk_index)) => Some(hunk_index),
+                        Some(Row::Lines { hunk, .. }) => hunk,
+                        None => None,
+                    };
+                }
+                self.redraw(cx);
+            }
+            Hit::FingerHoverIn(FingerHoverEvent { abs, .. })
+            | Hit::FingerHoverOver(FingerHoverEvent { abs, .. }) => {
+                if self.buttons.iter().any(|(rect, _, _)| rect.contains(abs)) {
+                    cx.set_cursor(MouseCursor::Hand);
+                } else {
+                    cx.set_cursor(MouseCursor::Default);
+                }
+            }
+            // alt up and down step through the hunks
+            Hit::KeyDown(KeyEvent {
+                key_code: KeyCode::ArrowDown,
+                modifiers: KeyModifiers { alt: true, .. },
+                ..
+            }) => {
+                let next = self.selected_hunk.map_or(0, |index| index + 1);
+                self.select_hunk(cx, next);
+            }
+            Hit::KeyDown(KeyEvent {
+                key_code: KeyCode::ArrowUp,
+                modifiers: KeyModifiers { alt: true, .. },
+                ..
+            }) => {
+                if let Some(prev) = self.selected_hunk.and_then(|index| index.checked_sub(1)) {
+                    self.select_hunk(cx, prev);
+                }
+            }
+            Hit::KeyDown(KeyEvent {
+                key_code: KeyCode::KeyS,
+                ..
+            }) => {
+                if let Some(hunk_index) = self.selected_hunk {
+                    actions.push(self.press_button(cx, hunk_index, HunkButton::Stage));
+                }
+            }
+            Hit::TextCopy(ce) => {
+                if let Some(hunk_index) = self.selected_hunk {
+                    *ce.response.borrow_mut() = self.hunk_text(hunk_index);
+                    actions.push(DiffViewAction::CopyHunk(hunk_index));
+                }
+            }
+            _ => {}
+        }
+        actions
+    }
+
+    fn press_button(
+        &mut self,
+        cx: &mut Cx,
+        hunk_index: usize,
+        button: HunkButton,
+    ) -> DiffViewAction {
+        match button {
+            HunkButton::Stage => DiffViewAction::StageHunk(hunk_index),
+            HunkButton::Copy => {
+                if let Some(text) = self.hunk_text(hunk_index) {
+                    cx.copy_to_clipboard(&text);
+                }
+                DiffViewAction::CopyHunk(hunk_index)
+            }
+        }
+    }
+}
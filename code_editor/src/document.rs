@@ -1,20 +1,26 @@
 use {
     crate::{
         char::CharExt,
+        collab::{Op, Presence, Replica},
         decoration::{Decoration, DecorationSet},
+        diagnostic::{Diagnostic, DiagnosticSet},
+        diff::{ChangeTracker, Hunk, LineChange},
+        edit_location::{EditLocation, EditLocationSet},
         history::{EditKind, History},
         inlays::{BlockInlay, InlineInlay},
         iter::IteratorExt,
+        recording::{Recorder, Recording},
         selection::SelectionSet,
         session::SessionId,
         settings::Settings,
         str::StrExt,
         text::{Change, Drift, Edit, Length, Position, Text},
         token::{Token, TokenKind},
-        tokenizer::Tokenizer,
+        tokenizer::{Highlighter, Tokenizer},
+        tokenizer_rules::TokenizerRules,
     },
     std::{
-        cell::{Ref, RefCell},
+        cell::{Cell, Ref, RefCell},
         cmp::Ordering,
         collections::HashMap,
         iter,
@@ -43,6 +49,12 @@ impl Document {
             }),
             tokenizer: RefCell::new(Tokenizer::new(line_count)),
             decorations: RefCell::new(decorations),
+            diagnostics: RefCell::new(DiagnosticSet::new()),
+            change_tracker: RefCell::new(ChangeTracker::default()),
+            edit_locations: RefCell::new(EditLocationSet::new()),
+            time: Cell::new(0.0),
+            recorder: RefCell::new(None),
+            replica: RefCell::new(None),
             edit_senders: RefCell::new(HashMap::new()),
         }));
         inner.update_indent_state();
@@ -53,6 +65,34 @@ impl Document {
         inner
     }
 
+    /// Highlights the document with declarative rules, or with the built in Rust tokenizer for
+    /// `None`.
+    pub fn set_tokenizer_rules(&self, rules: Option<Rc<TokenizerRules>>) {
+        let mut tokenizer = self.0.tokenizer.borrow_mut();
+        tokenizer.set_rules(rules);
+        tokenizer.update(
+            self.0.history.borrow().as_text(),
+            &mut self.0.layout.borrow_mut().tokens,
+        );
+    }
+
+    /// Highlights the document with `highlighter`, like a tokenizer for another language.
+    pub fn set_highlighter(&self, highlighter: Rc<dyn Highlighter>) {
+        let mut tokenizer = self.0.tokenizer.borrow_mut();
+        tokenizer.set_highlighter(highlighter);
+        tokenizer.update(
+            self.0.history.borrow().as_text(),
+            &mut self.0.layout.borrow_mut().tokens,
+        );
+    }
+
+    /// Sets the time in seconds the next edits happen at, which decides how they are grouped
+    /// for undo and when they happen in a recording. The code editor keeps it up to date from
+    /// `Cx`.
+    pub fn set_time(&self, time: f64) {
+        self.0.time.set(time);
+    }
+
     pub fn as_text(&self) -> Ref<'_, Text> {
         Ref::map(self.0.history.borrow(), |history| history.as_text())
     }
@@ -67,6 +107,95 @@ impl Document {
         })
     }
 
+    pub fn diagnostics(&self) -> Ref<'_, DiagnosticSet> {
+        self.0.diagnostics.borrow()
+    }
+
+    /// How each line differs from the baseline, see `set_baseline`.
+    pub fn line_changes(&self) -> Ref<'_, [Option<LineChange>]> {
+        Ref::map(self.0.change_tracker.borrow(), |change_tracker| {
+            change_tracker.line_changes()
+        })
+    }
+
+    pub fn hunks(&self) -> Ref<'_, [Hunk]> {
+        Ref::map(self.0.change_tracker.borrow(), |change_tracker| {
+            change_tracker.hunks()
+        })
+    }
+
+    pub fn edit_locations(&self) -> Ref<'_, [EditLocation]> {
+        Ref::map(self.0.edit_locations.borrow(), |edit_locations| {
+            edit_locations.as_locations()
+        })
+    }
+
+    /// Starts recording every edit made to the document from now on, for playback later.
+    pub fn start_recording(&self) {
+        *self.0.recorder.borrow_mut() = Some(Recorder::new(&self.as_text(), self.0.time.get()));
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.0.recorder.borrow().is_some()
+    }
+
+    pub fn stop_recording(&self) -> Option<Recording> {
+        self.0
+            .recorder
+            .borrow_mut()
+            .take()
+            .map(|recorder| recorder.into_recording())
+    }
+
+    /// Turns every edit made to the document from now on into an op for collaborators, see
+    /// `take_collab_ops`. The replica has to have the same text as the document.
+    pub fn start_collab(&self, replica: Replica) {
+        debug_assert!(replica.text() == *self.as_text());
+        *self.0.replica.borrow_mut() = Some(replica);
+    }
+
+    pub fn stop_collab(&self) -> Option<Replica> {
+        self.0.replica.borrow_mut().take()
+    }
+
+    pub fn replica(&self) -> Option<Ref<'_, Replica>> {
+        Ref::filter_map(self.0.replica.borrow(), |replica| replica.as_ref()).ok()
+    }
+
+    /// The ops made by edits to the document since the last time, to send to the others.
+    pub fn take_collab_ops(&self) -> Vec<Op> {
+        self.0
+            .replica
+            .borrow_mut()
+            .as_mut()
+            .map_or(Vec::new(), |replica| replica.take_ops())
+    }
+
+    /// Merges the ops of collaborators into the document, along with any edits that haven't
+    /// been sent yet. The selections of every session move along, and undo keeps reverting
+    /// only the edits made here.
+    pub fn apply_collab_ops(&self, ops: Vec<Op>) {
+        let edits = match &mut *self.0.replica.borrow_mut() {
+            Some(replica) => replica.apply_remote_ops(ops),
+            None => return,
+        };
+        if edits.is_empty() {
+            return;
+        }
+        let mut history = self.0.history.borrow_mut();
+        for edit in &edits {
+            history.apply_remote_edit(edit.clone());
+        }
+        drop(history);
+        self.update_after_edit_internal(None, None, &edits);
+    }
+
+    pub fn apply_collab_presence(&self, presence: Presence) {
+        if let Some(replica) = &mut *self.0.replica.borrow_mut() {
+            replica.apply_presence(presence);
+        }
+    }
+
     pub fn edit_selections(
         &self,
         session_id: SessionId,
@@ -76,7 +205,7 @@ impl Document {
         mut f: impl FnMut(Editor<'_>, Position, Length),
     ) {
         let mut history = self.0.history.borrow_mut();
-        history.push_or_extend_group(session_id, kind, selections);
+        history.push_or_extend_group(session_id, kind, selections, self.0.time.get());
         let mut edits = Vec::new();
         let mut line_ranges = Vec::new();
         let mut prev_start = Position::zero();
@@ -134,7 +263,7 @@ impl Document {
             prev_edit_start = edit_start;
         }
         drop(history);
-        self.autoindent(&line_ranges, settings.tab_column_count, &mut edits);
+        self.autoindent(&line_ranges, settings, &mut edits);
         self.update_after_edit(session_id, None, &edits);
     }
 
@@ -146,7 +275,7 @@ impl Document {
         mut f: impl FnMut(Editor, usize),
     ) {
         let mut history = self.0.history.borrow_mut();
-        history.push_or_extend_group(origin_id, kind, selections);
+        history.push_or_extend_group(origin_id, kind, selections, self.0.time.get());
         let mut edits = Vec::new();
         for line_range in selections
             .iter()
@@ -174,6 +303,27 @@ impl Document {
         self.update_after_edit(origin_id, None, &edits);
     }
 
+    /// Applies arbitrary edits as a single undo group. Unlike `edit_selections`, the closure
+    /// decides where the selections end up, so edits that move text around can carry the
+    /// cursors along with them.
+    pub fn edit_with_selections(
+        &self,
+        origin_id: SessionId,
+        kind: EditKind,
+        selections: &SelectionSet,
+        f: impl FnOnce(Editor<'_>) -> SelectionSet,
+    ) {
+        let mut history = self.0.history.borrow_mut();
+        history.push_or_extend_group(origin_id, kind, selections, self.0.time.get());
+        let mut edits = Vec::new();
+        let new_selections = f(Editor {
+            history: &mut *history,
+            edits: &mut edits,
+        });
+        drop(history);
+        self.update_after_edit(origin_id, Some(new_selections), &edits);
+    }
+
     pub fn add_decoration(&mut self, decoration: Decoration) {
         self.0.decorations.borrow_mut().add_decoration(decoration);
     }
@@ -182,6 +332,25 @@ impl Document {
         self.0.decorations.borrow_mut().clear()
     }
 
+    /// Replaces the diagnostics of the document. They move along with edits until they are
+    /// replaced again.
+    pub fn set_diagnostics(&self, diagnostics: Vec<Diagnostic>) {
+        self.0.diagnostics.borrow_mut().set(diagnostics);
+    }
+
+    pub fn clear_diagnostics(&self) {
+        self.0.diagnostics.borrow_mut().clear();
+    }
+
+    /// Compares the document against `baseline`, like the last saved version or the one
+    /// checked in, as it is edited. `None` stops comparing.
+    pub fn set_baseline(&self, baseline: Option<&Text>) {
+        self.0
+            .change_tracker
+            .borrow_mut()
+            .set_baseline(baseline, self.0.history.borrow().as_text());
+    }
+
     pub fn add_session(
         &mut self,
         session_id: SessionId,
@@ -197,15 +366,10 @@ impl Document {
         self.0.edit_senders.borrow_mut().remove(&session_id);
     }
 
-    fn autoindent(
-        &self,
-        line_ranges: &[Range<usize>],
-        indent_column_count: usize,
-        edits: &mut Vec<Edit>,
-    ) {
-        fn next_line_indent_column_count(line: &str, tab_column_count: usize) -> Option<usize> {
+    fn autoindent(&self, line_ranges: &[Range<usize>], settings: &Settings, edits: &mut Vec<Edit>) {
+        fn next_line_indent_column_count(line: &str, settings: &Settings) -> Option<usize> {
             if let Some(indent) = line.indent() {
-                let mut indent_column_count = indent.column_count();
+                let mut indent_column_count = settings.indent_column_count(indent);
                 if line
                     .chars()
                     .rev()
@@ -220,7 +384,7 @@ impl Document {
                     })
                     .unwrap_or(false)
                 {
-                    indent_column_count += tab_column_count;
+                    indent_column_count += settings.tab_column_count;
                 };
                 Some(indent_column_count)
             } else {
@@ -243,7 +407,7 @@ impl Document {
                 [..line_range.start]
                 .iter()
                 .rev()
-                .find_map(|line| next_line_indent_column_count(line, indent_column_count))
+                .find_map(|line| next_line_indent_column_count(line, settings))
                 .unwrap_or(0);
             for line in line_range {
                 if self.as_text().as_lines()[line]
@@ -259,15 +423,15 @@ impl Document {
                     })
                     .unwrap_or(false)
                 {
-                    desired_indentation_column_count -= 4;
+                    desired_indentation_column_count =
+                        desired_indentation_column_count.saturating_sub(settings.tab_column_count);
                 }
                 self.edit_lines_internal(line, edits, |line| {
-                    crate::session::reindent(line, |_| desired_indentation_column_count)
+                    crate::session::reindent(line, settings, |_| desired_indentation_column_count)
                 });
-                if let Some(next_line_indentation_column_count) = next_line_indent_column_count(
-                    &self.as_text().as_lines()[line],
-                    indent_column_count,
-                ) {
+                if let Some(next_line_indentation_column_count) =
+                    next_line_indent_column_count(&self.as_text().as_lines()[line], settings)
+                {
                     desired_indentation_column_count = next_line_indentation_column_count;
                 }
             }
@@ -346,6 +510,22 @@ impl Document {
         origin_id: SessionId,
         selections: Option<SelectionSet>,
         edits: &[Edit],
+    ) {
+        if let Some(replica) = &mut *self.0.replica.borrow_mut() {
+            for edit in edits {
+                replica.apply_local_change(&edit.change);
+            }
+        }
+        self.update_after_edit_internal(Some(origin_id), selections, edits);
+    }
+
+    /// Updates everything that depends on the text after `edits`, made by the session
+    /// `origin_id`, or by a collaborator for `None`.
+    fn update_after_edit_internal(
+        &self,
+        origin_id: Option<SessionId>,
+        selections: Option<SelectionSet>,
+        edits: &[Edit],
     ) {
         let mut layout = self.0.layout.borrow_mut();
         for edit in edits {
@@ -386,8 +566,27 @@ impl Document {
             decorations.apply_edit(edit);
         }
         drop(decorations);
+        let mut diagnostics = self.0.diagnostics.borrow_mut();
+        for edit in edits {
+            diagnostics.apply_edit(edit);
+        }
+        drop(diagnostics);
+        self.0
+            .change_tracker
+            .borrow_mut()
+            .update(self.0.history.borrow().as_text());
+        let mut edit_locations = self.0.edit_locations.borrow_mut();
+        for edit in edits {
+            edit_locations.apply_edit(edit);
+        }
+        drop(edit_locations);
+        if let Some(recorder) = &mut *self.0.recorder.borrow_mut() {
+            for edit in edits {
+                recorder.record_edit(edit, self.0.time.get());
+            }
+        }
         for (&session_id, edit_sender) in &*self.0.edit_senders.borrow() {
-            if session_id == origin_id {
+            if Some(session_id) == origin_id {
                 edit_sender
                     .send((selections.clone(), edits.to_vec()))
                     .unwrap();
@@ -684,6 +883,12 @@ struct DocumentInner {
     layout: RefCell<DocumentLayout>,
     tokenizer: RefCell<Tokenizer>,
     decorations: RefCell<DecorationSet>,
+    diagnostics: RefCell<DiagnosticSet>,
+    change_tracker: RefCell<ChangeTracker>,
+    edit_locations: RefCell<EditLocationSet>,
+    time: Cell<f64>,
+    recorder: RefCell<Option<Recorder>>,
+    replica: RefCell<Option<Replica>>,
     edit_senders: RefCell<HashMap<SessionId, Sender<(Option<SelectionSet>, Vec<Edit>)>>>,
 }
 
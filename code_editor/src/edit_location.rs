@@ -0,0 +1,89 @@
+use {
+    crate::text::{Change, Edit, Position},
+    std::{
+        ops::Deref,
+        slice::Iter,
+        sync::atomic::{AtomicU64, Ordering},
+    },
+};
+
+const MAX_EDIT_LOCATIONS: usize = 64;
+
+/// A position where the document was recently edited. The stamp is taken from a global counter
+/// so that locations from different documents can be merged into a single history.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct EditLocation {
+    pub stamp: u64,
+    pub position: Position,
+}
+
+impl EditLocation {
+    pub fn apply_edit(self, edit: &Edit) -> Self {
+        Self {
+            position: self.position.apply_edit(edit),
+            ..self
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct EditLocationSet {
+    locations: Vec<EditLocation>,
+}
+
+impl EditLocationSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn as_locations(&self) -> &[EditLocation] {
+        &self.locations
+    }
+
+    pub fn apply_edit(&mut self, edit: &Edit) {
+        for location in &mut self.locations {
+            *location = location.apply_edit(edit);
+        }
+        let position = match edit.change {
+            Change::Insert(position, ref text) => position + text.length(),
+            Change::Delete(start, _) => start,
+        };
+        self.push(position);
+    }
+
+    fn push(&mut self, position: Position) {
+        static STAMP: AtomicU64 = AtomicU64::new(0);
+
+        let stamp = STAMP.fetch_add(1, Ordering::SeqCst);
+        // typing on the same line moves the last location along instead of flooding the history
+        if let Some(last) = self.locations.last_mut() {
+            if last.position.line_index == position.line_index {
+                *last = EditLocation { stamp, position };
+                return;
+            }
+        }
+        self.locations
+            .retain(|location| location.position.line_index != position.line_index);
+        if self.locations.len() == MAX_EDIT_LOCATIONS {
+            self.locations.remove(0);
+        }
+        self.locations.push(EditLocation { stamp, position });
+    }
+}
+
+impl Deref for EditLocationSet {
+    type Target = [EditLocation];
+
+    fn deref(&self) -> &Self::Target {
+        self.locations.as_slice()
+    }
+}
+
+impl<'a> IntoIterator for &'a EditLocationSet {
+    type Item = &'a EditLocation;
+    type IntoIter = Iter<'a, EditLocation>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
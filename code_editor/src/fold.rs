@@ -0,0 +1,64 @@
+use crate::{str::StrExt, structure::Structure};
+
+/// Lines that can be folded away. The first line stays visible, and the lines after it up to
+/// and including the last line are hidden.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct FoldRange {
+    pub start_line_index: usize,
+    pub end_line_index: usize,
+}
+
+impl FoldRange {
+    pub fn contains_line(self, line_index: usize) -> bool {
+        (self.start_line_index..=self.end_line_index).contains(&line_index)
+    }
+
+    /// Whether the line is one that folding the range hides.
+    pub fn hides_line(self, line_index: usize) -> bool {
+        self.start_line_index < line_index && line_index <= self.end_line_index
+    }
+}
+
+/// The innermost range that starts at or contains the line. Delimiters that span lines are
+/// used when the tokenizer found any, and indentation otherwise.
+pub fn find_fold_range(
+    lines: &[String],
+    structure: &Structure,
+    line_index: usize,
+) -> Option<FoldRange> {
+    let delimiter_ranges = structure.fold_ranges();
+    if !delimiter_ranges.is_empty() {
+        return delimiter_ranges
+            .into_iter()
+            .filter(|range| range.contains_line(line_index))
+            .max_by_key(|range| range.start_line_index);
+    }
+    (0..=line_index)
+        .rev()
+        .filter_map(|start_line_index| indent_fold_range(lines, start_line_index))
+        .find(|range| range.contains_line(line_index))
+}
+
+/// The lines after `line_index` that are indented deeper than it, up to the last one that
+/// isn't blank.
+pub fn indent_fold_range(lines: &[String], line_index: usize) -> Option<FoldRange> {
+    // blank lines have no indent, and don't start a range
+    let indent_column_count = lines[line_index].indent()?.column_count();
+    let mut end_line_index = line_index;
+    for (index, line) in lines.iter().enumerate().skip(line_index + 1) {
+        let Some(indent) = line.indent() else {
+            continue;
+        };
+        if indent.column_count() <= indent_column_count {
+            break;
+        }
+        end_line_index = index;
+    }
+    if end_line_index == line_index {
+        return None;
+    }
+    Some(FoldRange {
+        start_line_index: line_index,
+        end_line_index,
+    })
+}
@@ -1,13 +1,18 @@
 use crate::{
     selection::SelectionSet,
     session::SessionId,
-    text::{Edit, Text},
+    text::{Change, Drift, Edit, Text},
 };
 
-#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+/// How long a pause in editing, in seconds, has to be to start a new undo group, even for
+/// edits that could be merged, like typing.
+const GROUP_TIMEOUT: f64 = 1.0;
+
+#[derive(Clone, Debug, Default, PartialEq)]
 pub struct History {
     text: Text,
     current_desc: Option<GroupDesc>,
+    last_edit_time: Option<f64>,
     undo_stack: Stack,
     redo_stack: Stack,
 }
@@ -36,18 +41,24 @@ impl History {
         session_id: SessionId,
         edit_kind: EditKind,
         selections: &SelectionSet,
+        time: f64,
     ) {
         let desc = GroupDesc {
             session_id,
             edit_kind,
         };
-        if !self
-            .current_desc
-            .map_or(false, |current_desc| current_desc.can_merge_with(desc))
+        let is_paused = self
+            .last_edit_time
+            .map_or(true, |last_edit_time| time - last_edit_time > GROUP_TIMEOUT);
+        if is_paused
+            || !self
+                .current_desc
+                .map_or(false, |current_desc| current_desc.can_merge_with(desc))
         {
             self.undo_stack.push_group(selections.clone());
             self.current_desc = Some(desc);
         }
+        self.last_edit_time = Some(time);
     }
 
     pub fn apply_edit(&mut self, edit: Edit) {
@@ -57,6 +68,14 @@ impl History {
         self.redo_stack.clear();
     }
 
+    /// Applies an edit someone else made, like a collaborator, which can't be undone here. The
+    /// undo and redo stacks are moved past it, so they keep reverting only our own edits.
+    pub fn apply_remote_edit(&mut self, edit: Edit) {
+        self.undo_stack.rebase(&edit.change);
+        self.redo_stack.rebase(&edit.change);
+        self.text.apply_change(edit.change);
+    }
+
     pub fn undo(
         &mut self,
         selections: &SelectionSet,
@@ -173,6 +192,84 @@ impl Stack {
         self.groups.clear();
         self.edits.clear();
     }
+
+    /// Moves the edits past `change`, a change to the text the top of the stack applies to.
+    /// Each edit applies to the text the one above it leaves behind, so the change is moved
+    /// back past every edit on the way down, along with the selections of the groups. An edit
+    /// can turn into more than one along the way, so the groups are renumbered afterwards.
+    fn rebase(&mut self, change: &Change) {
+        let mut changes = vec![change.clone()];
+        let mut rebased_edits = Vec::new();
+        let mut groups = self.groups.iter_mut().rev().peekable();
+        loop {
+            while let Some(group) = groups.next_if(|group| group.edit_start == self.edits.len()) {
+                for change in &changes {
+                    group.selections.apply_edit(
+                        &Edit {
+                            change: change.clone(),
+                            drift: Drift::After,
+                        },
+                        None,
+                    );
+                }
+                // Counted from the top until all edits are rebased.
+                group.edit_start = rebased_edits.len();
+            }
+            let Some(edit) = self.edits.pop() else {
+                break;
+            };
+            let (edit_changes, other_changes) =
+                transform(vec![edit.change], changes, Drift::Before);
+            changes = other_changes;
+            rebased_edits.extend(edit_changes.into_iter().map(|change| Edit {
+                change,
+                drift: edit.drift,
+            }));
+        }
+        rebased_edits.reverse();
+        for group in &mut self.groups {
+            group.edit_start = rebased_edits.len() - group.edit_start;
+        }
+        self.edits = rebased_edits;
+    }
+}
+
+/// Moves `changes` past `other_changes`, both applied in order to the same text, and the other
+/// way around, so applying either after the other ends up with the same text.
+fn transform(
+    mut changes: Vec<Change>,
+    mut other_changes: Vec<Change>,
+    drift: Drift,
+) -> (Vec<Change>, Vec<Change>) {
+    match (changes.len(), other_changes.len()) {
+        (0, _) | (_, 0) => (changes, other_changes),
+        (1, 1) => {
+            let change = changes.pop().unwrap();
+            let other_change = other_changes.pop().unwrap();
+            let other_drift = match drift {
+                Drift::Before => Drift::After,
+                Drift::After => Drift::Before,
+            };
+            (
+                change.clone().transform(&other_change, drift),
+                other_change.transform(&change, other_drift),
+            )
+        }
+        (1, _) => {
+            let rest = other_changes.split_off(1);
+            let (changes, mut other_changes) = transform(changes, other_changes, drift);
+            let (changes, rest) = transform(changes, rest, drift);
+            other_changes.extend(rest);
+            (changes, other_changes)
+        }
+        _ => {
+            let rest = changes.split_off(1);
+            let (mut changes, other_changes) = transform(changes, other_changes, drift);
+            let (rest, other_changes) = transform(rest, other_changes, drift);
+            changes.extend(rest);
+            (changes, other_changes)
+        }
+    }
 }
 
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
@@ -180,3 +277,64 @@ struct Group {
     selections: SelectionSet,
     edit_start: usize,
 }
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{document::Document, session::Session, text::Position},
+    };
+
+    fn insert(byte_index: usize, text: &str) -> Edit {
+        Edit {
+            change: Change::Insert(
+                Position {
+                    line_index: 0,
+                    byte_index,
+                },
+                Text::from(text),
+            ),
+            drift: Drift::Before,
+        }
+    }
+
+    #[test]
+    fn undo_keeps_remote_text_inserted_inside_own_insertion() {
+        let session = Session::new(Document::new(Text::new(), Default::default()));
+        let selections = SelectionSet::new();
+        let mut history = History::from(Text::from("xyz"));
+        history.push_or_extend_group(session.id(), EditKind::Insert, &selections, 0.0);
+        history.apply_edit(insert(0, "abc"));
+        history.apply_remote_edit(insert(1, "Q"));
+        assert_eq!(history.as_text().to_string(), "aQbcxyz");
+
+        let mut edits = Vec::new();
+        history.undo(&selections, &mut edits).unwrap();
+        assert_eq!(edits.len(), 2);
+        assert_eq!(history.as_text().to_string(), "Qxyz");
+
+        edits.clear();
+        history.redo(&selections, &mut edits).unwrap();
+        assert_eq!(history.as_text().to_string(), "aQbcxyz");
+    }
+
+    #[test]
+    fn pause_starts_new_group() {
+        let session = Session::new(Document::new(Text::new(), Default::default()));
+        let selections = SelectionSet::new();
+        let mut history = History::new();
+        history.push_or_extend_group(session.id(), EditKind::Insert, &selections, 0.0);
+        history.apply_edit(insert(0, "a"));
+        history.push_or_extend_group(session.id(), EditKind::Insert, &selections, 0.5);
+        history.apply_edit(insert(1, "b"));
+        history.push_or_extend_group(session.id(), EditKind::Insert, &selections, 2.0);
+        history.apply_edit(insert(2, "c"));
+
+        let mut edits = Vec::new();
+        history.undo(&selections, &mut edits).unwrap();
+        assert_eq!(history.as_text().to_string(), "ab");
+        edits.clear();
+        history.undo(&selections, &mut edits).unwrap();
+        assert_eq!(history.as_text().to_string(), "");
+    }
+}
@@ -0,0 +1,539 @@
+use makepad_widgets::{KeyCode, KeyEvent};
+
+/// The commands the editor runs when keys bound to them are pressed.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum EditorCommand {
+    MoveLeft,
+    MoveRight,
+    MoveUp,
+    MoveDown,
+    /// Moves up by line rather than by row, when lines are wrapped.
+    MoveUpLine,
+    MoveDownLine,
+    MoveHome,
+    MoveEnd,
+    MovePageUp,
+    MovePageDown,
+    MoveToMatchingDelimiter,
+    SelectLeft,
+    SelectRight,
+    SelectUp,
+    SelectDown,
+    SelectUpLine,
+    SelectDownLine,
+    SelectHome,
+    SelectEnd,
+    SelectPageUp,
+    SelectPageDown,
+    SelectEnclosing,
+    ColumnSelectUp,
+    ColumnSelectDown,
+    /// Swaps the element at the cursor with the one before it, or while selecting columns,
+    /// moves the column selection left.
+    MoveElementLeft,
+    MoveElementRight,
+    TransposeElements,
+    Fold,
+    Unfold,
+    Enter,
+    Indent,
+    Outdent,
+    Delete,
+    Backspace,
+    ToggleComment,
+    Undo,
+    Redo,
+    /// Reindents what the next paste pastes.
+    PasteReindented,
+    /// Escapes what the next paste pastes as a string literal.
+    PasteEscaped,
+    /// Wraps what the next paste pastes in a raw string literal.
+    PasteRaw,
+    FindNext,
+    FindPrevious,
+    OpenCompletions,
+    OpenCodeActions,
+    IncreaseFontSize,
+    DecreaseFontSize,
+    ResetFontSize,
+    ToggleWordWrap,
+}
+
+/// The names commands are bound with.
+const COMMAND_NAMES: &[(&str, EditorCommand)] = &[
+    ("move_left", EditorCommand::MoveLeft),
+    ("move_right", EditorCommand::MoveRight),
+    ("move_up", EditorCommand::MoveUp),
+    ("move_down", EditorCommand::MoveDown),
+    ("move_up_line", EditorCommand::MoveUpLine),
+    ("move_down_line", EditorCommand::MoveDownLine),
+    ("move_home", EditorCommand::MoveHome),
+    ("move_end", EditorCommand::MoveEnd),
+    ("move_page_up", EditorCommand::MovePageUp),
+    ("move_page_down", EditorCommand::MovePageDown),
+    (
+        "move_to_matching_delimiter",
+        EditorCommand::MoveToMatchingDelimiter,
+    ),
+    ("select_left", EditorCommand::SelectLeft),
+    ("select_right", EditorCommand::SelectRight),
+    ("select_up", EditorCommand::SelectUp),
+    ("select_down", EditorCommand::SelectDown),
+    ("select_up_line", EditorCommand::SelectUpLine),
+    ("select_down_line", EditorCommand::SelectDownLine),
+    ("select_home", EditorCommand::SelectHome),
+    ("select_end", EditorCommand::SelectEnd),
+    ("select_page_up", EditorCommand::SelectPageUp),
+    ("select_page_down", EditorCommand::SelectPageDown),
+    ("select_enclosing", EditorCommand::SelectEnclosing),
+    ("column_select_up", EditorCommand::ColumnSelectUp),
+    ("column_select_down", EditorCommand::ColumnSelectDown),
+    ("move_element_left", EditorCommand::MoveElementLeft),
+    ("move_element_right", EditorCommand::MoveElementRight),
+    ("transpose_elements", EditorCommand::TransposeElements),
+    ("fold", EditorCommand::Fold),
+    ("unfold", EditorCommand::Unfold),
+    ("enter", EditorCommand::Enter),
+    ("indent", EditorCommand::Indent),
+    ("outdent", EditorCommand::Outdent),
+    ("delete", EditorCommand::Delete),
+    ("backspace", EditorCommand::Backspace),
+    ("toggle_comment", EditorCommand::ToggleComment),
+    ("undo", EditorCommand::Undo),
+    ("redo", EditorCommand::Redo),
+    ("paste_reindented", EditorCommand::PasteReindented),
+    ("paste_escaped", EditorCommand::PasteEscaped),
+    ("paste_raw", EditorCommand::PasteRaw),
+    ("find_next", EditorCommand::FindNext),
+    ("find_previous", EditorCommand::FindPrevious),
+    ("open_completions", EditorCommand::OpenCompletions),
+    ("open_code_actions", EditorCommand::OpenCodeActions),
+    ("increase_font_size", EditorCommand::IncreaseFontSize),
+    ("decrease_font_size", EditorCommand::DecreaseFontSize),
+    ("reset_font_size", EditorCommand::ResetFontSize),
+    ("toggle_word_wrap", EditorCommand::ToggleWordWrap),
+];
+
+impl EditorCommand {
+    pub fn from_name(name: &str) -> Option<Self> {
+        COMMAND_NAMES
+            .iter()
+            .find(|(command_name, _)| *command_name == name)
+            .map(|(_, command)| *command)
+    }
+
+    pub fn name(self) -> &'static str {
+        COMMAND_NAMES
+            .iter()
+            .find(|(_, command)| *command == self)
+            .map(|(name, _)| *name)
+            .unwrap()
+    }
+
+    /// Whether the command changes the text, so a read only editor ignores it.
+    pub fn is_edit(self) -> bool {
+        match self {
+            Self::MoveElementLeft
+            | Self::MoveElementRight
+            | Self::TransposeElements
+            | Self::Enter
+            | Self::Indent
+            | Self::Outdent
+            | Self::Delete
+            | Self::Backspace
+            | Self::ToggleComment
+            | Self::Undo
+            | Self::Redo
+            | Self::PasteReindented
+            | Self::PasteEscaped
+            | Self::PasteRaw
+            | Self::OpenCompletions
+            | Self::OpenCodeActions => true,
+            _ => false,
+        }
+    }
+}
+
+/// The bindings the editor comes with.
+const DEFAULT_BINDINGS: &[(&str, EditorCommand)] = &[
+    ("left", EditorCommand::MoveLeft),
+    ("right", EditorCommand::MoveRight),
+    ("up", EditorCommand::MoveUp),
+    ("down", EditorCommand::MoveDown),
+    ("ctrl+up", EditorCommand::MoveUpLine),
+    ("ctrl+down", EditorCommand::MoveDownLine),
+    ("home", EditorCommand::MoveHome),
+    ("end", EditorCommand::MoveEnd),
+    ("pageup", EditorCommand::MovePageUp),
+    ("pagedown", EditorCommand::MovePageDown),
+    ("ctrl+shift+\\", EditorCommand::MoveToMatchingDelimiter),
+    ("shift+left", EditorCommand::SelectLeft),
+    ("shift+right", EditorCommand::SelectRight),
+    ("shift+up", EditorCommand::SelectUp),
+    ("shift+down", EditorCommand::SelectDown),
+    ("ctrl+shift+up", EditorCommand::SelectUpLine),
+    ("ctrl+shift+down", EditorCommand::SelectDownLine),
+    ("shift+home", EditorCommand::SelectHome),
+    ("shift+end", EditorCommand::SelectEnd),
+    ("shift+pageup", EditorCommand::SelectPageUp),
+    ("shift+pagedown", EditorCommand::SelectPageDown),
+    ("alt+up", EditorCommand::SelectEnclosing),
+    ("alt+shift+up", EditorCommand::ColumnSelectUp),
+    ("alt+shift+down", EditorCommand::ColumnSelectDown),
+    ("alt+shift+left", EditorCommand::MoveElementLeft),
+    ("alt+shift+right", EditorCommand::MoveElementRight),
+    ("ctrl+t", EditorCommand::TransposeElements),
+    ("ctrl+shift+[", EditorCommand::Fold),
+    ("ctrl+shift+]", EditorCommand::Unfold),
+    ("enter", EditorCommand::Enter),
+    ("shift+enter", EditorCommand::Enter),
+    ("tab", EditorCommand::Indent),
+    ("shift+tab", EditorCommand::Outdent),
+    ("delete", EditorCommand::Delete),
+    ("backspace", EditorCommand::Backspace),
+    ("shift+backspace", EditorCommand::Backspace),
+    ("mod+/", EditorCommand::ToggleComment),
+    ("cmd+z", EditorCommand::Undo),
+    ("cmd+shift+z", EditorCommand::Redo),
+    ("mod+shift+v", EditorCommand::PasteReindented),
+    ("mod+alt+v", EditorCommand::PasteEscaped),
+    ("mod+alt+shift+v", EditorCommand::PasteRaw),
+    ("f3", EditorCommand::FindNext),
+    ("shift+f3", EditorCommand::FindPrevious),
+    ("ctrl+space", EditorCommand::OpenCompletions),
+    ("mod+.", EditorCommand::OpenCodeActions),
+    ("mod+=", EditorCommand::IncreaseFontSize),
+    ("mod+-", EditorCommand::DecreaseFontSize),
+    ("mod+0", EditorCommand::ResetFontSize),
+    ("mod+w", EditorCommand::ToggleWordWrap),
+];
+
+/// A few bindings for those used to Emacs, on top of the default ones.
+const EMACS_BINDINGS: &[(&str, EditorCommand)] = &[
+    ("ctrl+b", EditorCommand::MoveLeft),
+    ("ctrl+f", EditorCommand::MoveRight),
+    ("ctrl+p", EditorCommand::MoveUp),
+    ("ctrl+n", EditorCommand::MoveDown),
+    ("ctrl+a", EditorCommand::MoveHome),
+    ("ctrl+e", EditorCommand::MoveEnd),
+    ("ctrl+d", EditorCommand::Delete),
+    ("ctrl+h", EditorCommand::Backspace),
+    ("ctrl+m", EditorCommand::Enter),
+    ("ctrl+x u", EditorCommand::Undo),
+    ("ctrl+s", EditorCommand::FindNext),
+    ("ctrl+r", EditorCommand::FindPrevious),
+    ("ctrl+x ctrl+;", EditorCommand::ToggleComment),
+];
+
+/// The names keys are written with, besides the letters and digits.
+const KEY_NAMES: &[(&str, KeyCode)] = &[
+    ("space", KeyCode::Space),
+    ("tab", KeyCode::Tab),
+    ("enter", KeyCode::ReturnKey),
+    ("escape", KeyCode::Escape),
+    ("backspace", KeyCode::Backspace),
+    ("delete", KeyCode::Delete),
+    ("insert", KeyCode::Insert),
+    ("up", KeyCode::ArrowUp),
+    ("down", KeyCode::ArrowDown),
+    ("left", KeyCode::ArrowLeft),
+    ("right", KeyCode::ArrowRight),
+    ("home", KeyCode::Home),
+    ("end", KeyCode::End),
+    ("pageup", KeyCode::PageUp),
+    ("pagedown", KeyCode::PageDown),
+    ("f1", KeyCode::F1),
+    ("f2", KeyCode::F2),
+    ("f3", KeyCode::F3),
+    ("f4", KeyCode::F4),
+    ("f5", KeyCode::F5),
+    ("f6", KeyCode::F6),
+    ("f7", KeyCode::F7),
+    ("f8", KeyCode::F8),
+    ("f9", KeyCode::F9),
+    ("f10", KeyCode::F10),
+    ("f11", KeyCode::F11),
+    ("f12", KeyCode::F12),
+    ("`", KeyCode::Backtick),
+    ("-", KeyCode::Minus),
+    ("=", KeyCode::Equals),
+    ("[", KeyCode::LBracket),
+    ("]", KeyCode::RBracket),
+    ("\\", KeyCode::Backslash),
+    (";", KeyCode::Semicolon),
+    ("'", KeyCode::Quote),
+    (",", KeyCode::Comma),
+    (".", KeyCode::Period),
+    ("/", KeyCode::Slash),
+];
+
+/// The keys written as the letter or digit they type.
+const CHAR_KEYS: &[KeyCode] = &[
+    KeyCode::KeyA,
+    KeyCode::KeyB,
+    KeyCode::KeyC,
+    KeyCode::KeyD,
+    KeyCode::KeyE,
+    KeyCode::KeyF,
+    KeyCode::KeyG,
+    KeyCode::KeyH,
+    KeyCode::KeyI,
+    KeyCode::KeyJ,
+    KeyCode::KeyK,
+    KeyCode::KeyL,
+    KeyCode::KeyM,
+    KeyCode::KeyN,
+    KeyCode::KeyO,
+    KeyCode::KeyP,
+    KeyCode::KeyQ,
+    KeyCode::KeyR,
+    KeyCode::KeyS,
+    KeyCode::KeyT,
+    KeyCode::KeyU,
+    KeyCode::KeyV,
+    KeyCode::KeyW,
+    KeyCode::KeyX,
+    KeyCode::KeyY,
+    KeyCode::KeyZ,
+    KeyCode::Key0,
+    KeyCode::Key1,
+    KeyCode::Key2,
+    KeyCode::Key3,
+    KeyCode::Key4,
+    KeyCode::Key5,
+    KeyCode::Key6,
+    KeyCode::Key7,
+    KeyCode::Key8,
+    KeyCode::Key9,
+];
+
+/// A key with the modifiers held, written like `ctrl+t`, `mod+shift+v` or `pageup`. `mod` is
+/// ctrl or cmd, whichever the platform uses for shortcuts.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct KeyChord {
+    pub key_code: KeyCode,
+    pub shift: bool,
+    pub alt: bool,
+    pub control: bool,
+    pub logo: bool,
+    /// Matches ctrl or cmd, instead of exactly `control` and `logo`.
+    pub primary: bool,
+}
+
+impl KeyChord {
+    pub fn parse(chord: &str) -> Result<Self, String> {
+        let (modifiers, key) = chord.rsplit_once('+').unwrap_or(("", chord));
+        let mut result = Self {
+            key_code: key_code_from_name(key).ok_or_else(|| format!("unknown key {:?}", key))?,
+            shift: false,
+            alt: false,
+            control: false,
+            logo: false,
+            primary: false,
+        };
+        for modifier in modifiers.split('+').filter(|modifier| !modifier.is_empty()) {
+            match modifier {
+                "shift" => result.shift = true,
+                "alt" => result.alt = true,
+                "ctrl" => result.control = true,
+                "cmd" => result.logo = true,
+                "mod" => result.primary = true,
+                _ => return Err(format!("unknown modifier {:?}", modifier)),
+            }
+        }
+        Ok(result)
+    }
+
+    pub fn matches(&self, event: &KeyEvent) -> bool {
+        let modifiers = &event.modifiers;
+        let primary_matches = if self.primary {
+            modifiers.control || modifiers.logo
+        } else {
+            modifiers.control == self.control && modifiers.logo == self.logo
+        };
+        event.key_code == self.key_code
+            && modifiers.shift == self.shift
+            && modifiers.alt == self.alt
+            && primary_matches
+    }
+
+    pub fn name(&self) -> String {
+        let mut name = String::new();
+        for (held, modifier) in [
+            (self.primary, "mod+"),
+            (self.control, "ctrl+"),
+            (self.logo, "cmd+"),
+            (self.alt, "alt+"),
+            (self.shift, "shift+"),
+        ] {
+            if held {
+                name.push_str(modifier);
+            }
+        }
+        match KEY_NAMES
+            .iter()
+            .find(|(_, key_code)| *key_code == self.key_code)
+        {
+            Some((key_name, _)) => name.push_str(key_name),
+            None => name.extend(self.key_code.to_char(false)),
+        }
+        name
+    }
+}
+
+fn key_code_from_name(name: &str) -> Option<KeyCode> {
+    if let Some((_, key_code)) = KEY_NAMES.iter().find(|(key_name, _)| *key_name == name) {
+        return Some(*key_code);
+    }
+    let mut chars = name.chars();
+    let char = chars.next().filter(|_| chars.next().is_none())?;
+    CHAR_KEYS
+        .iter()
+        .copied()
+        .find(|key_code| key_code.to_char(false) == Some(char))
+}
+
+/// Parses chords separated by spaces, like `ctrl+x u`.
+pub fn parse_chords(keys: &str) -> Result<Vec<KeyChord>, String> {
+    let chords = keys
+        .split_whitespace()
+        .map(KeyChord::parse)
+        .collect::<Result<Vec<_>, _>>()?;
+    if chords.is_empty() {
+        return Err("no keys".to_string());
+    }
+    Ok(chords)
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct KeyBinding {
+    pub chords: Vec<KeyChord>,
+    pub command: EditorCommand,
+}
+
+/// What a key does after the keys pressed before it.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum KeyLookup {
+    /// The keys run this command.
+    Command(EditorCommand),
+    /// The keys start a longer sequence, so the key is the next chord of it.
+    Pending(KeyChord),
+    Unbound,
+}
+
+/// Maps key chords, and sequences of them, to editor commands. Bindings can be added and
+/// removed at runtime, for instance to ship a preset on top of the defaults. A sequence that
+/// runs a command doesn't also start longer ones.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct KeyBindings {
+    bindings: Vec<KeyBinding>,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self::from_table(DEFAULT_BINDINGS)
+    }
+}
+
+impl KeyBindings {
+    /// No bindings at all.
+    pub fn empty() -> Self {
+        Self {
+            bindings: Vec::new(),
+        }
+    }
+
+    /// The default bindings, with some Emacs movement and editing keys on top.
+    pub fn emacs() -> Self {
+        let mut bindings = Self::default();
+        for (keys, command) in EMACS_BINDINGS {
+            bindings.bind(keys, *command).unwrap();
+        }
+        bindings
+    }
+
+    fn from_table(table: &[(&str, EditorCommand)]) -> Self {
+        let mut bindings = Self::empty();
+        for (keys, command) in table {
+            bindings.bind(keys, *command).unwrap();
+        }
+        bindings
+    }
+
+    pub fn as_bindings(&self) -> &[KeyBinding] {
+        &self.bindings
+    }
+
+    /// Binds `keys`, like `ctrl+x u`, to `command`, instead of what they were bound to. It
+    /// also takes over from earlier bindings of keys that match the same key presses, like
+    /// `mod+t` and `ctrl+t`.
+    pub fn bind(&mut self, keys: &str, command: EditorCommand) -> Result<(), String> {
+        let chords = parse_chords(keys)?;
+        self.bindings.retain(|binding| binding.chords != chords);
+        self.bindings.insert(0, KeyBinding { chords, command });
+        Ok(())
+    }
+
+    /// Binds `keys` to the command named `command`, like `move_left`. A command of `none`
+    /// unbinds the keys.
+    pub fn bind_by_name(&mut self, keys: &str, command: &str) -> Result<(), String> {
+        if command == "none" {
+            return self.unbind(keys);
+        }
+        let command = EditorCommand::from_name(command)
+            .ok_or_else(|| format!("unknown command {:?}", command))?;
+        self.bind(keys, command)
+    }
+
+    pub fn unbind(&mut self, keys: &str) -> Result<(), String> {
+        let chords = parse_chords(keys)?;
+        self.bindings.retain(|binding| binding.chords != chords);
+        Ok(())
+    }
+
+    /// Unbinds every key bound to `command`.
+    pub fn unbind_command(&mut self, command: EditorCommand) {
+        self.bindings.retain(|binding| binding.command != command);
+    }
+
+    /// The keys bound to `command`, for showing in menus and the like.
+    pub fn keys_for(&self, command: EditorCommand) -> Vec<String> {
+        self.bindings
+            .iter()
+            .filter(|binding| binding.command == command)
+            .map(|binding| {
+                binding
+                    .chords
+                    .iter()
+                    .map(|chord| chord.name())
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .collect()
+    }
+
+    /// What `event` does after the `pending` chords of a sequence.
+    pub fn lookup(&self, pending: &[KeyChord], event: &KeyEvent) -> KeyLookup {
+        let mut next = None;
+        for binding in &self.bindings {
+            let Some((chord, rest)) = binding
+                .chords
+                .strip_prefix(pending)
+                .and_then(|rest| rest.split_first())
+            else {
+                continue;
+            };
+            if !chord.matches(event) {
+                continue;
+            }
+            if rest.is_empty() {
+                return KeyLookup::Command(binding.command);
+            }
+            next.get_or_insert(*chord);
+        }
+        match next {
+            Some(chord) => KeyLookup::Pending(chord),
+            None => KeyLookup::Unbound,
+        }
+    }
+}
@@ -38,12 +38,17 @@ impl<'a> Layout<'a> {
     }
 
     pub fn find_first_line_ending_after_y(&self, y: f64) -> usize {
-        match self.session_layout.y[..self.session_layout.y.len() - 1]
+        let mut line = match self.session_layout.y[..self.session_layout.y.len() - 1]
             .binary_search_by(|current_y| current_y.partial_cmp(&y).unwrap())
         {
             Ok(line) => line,
             Err(line) => line.saturating_sub(1),
+        };
+        // Folded lines have no height, so they start where the next visible line does.
+        while line + 1 < self.session_layout.scale.len() && self.session_layout.scale[line] == 0.0 {
+            line += 1;
         }
+        line
     }
 
     pub fn find_first_line_starting_after_y(&self, y: f64) -> usize {
@@ -202,16 +207,24 @@ impl<'a> Line<'a> {
                     is_inlay: false,
                     text,
                 } => {
-                    for grapheme in text.graphemes() {
+                    // In a right-to-left run the position before a grapheme is on its right.
+                    for (grapheme, grapheme_column_index, is_rtl) in text.visual_graphemes() {
+                        let start_column_index = current_column_index + grapheme_column_index;
+                        let end_column_index = start_column_index + grapheme.column_count();
+                        let (before_column_index, after_column_index) = if is_rtl {
+                            (end_column_index, start_column_index)
+                        } else {
+                            (start_column_index, end_column_index)
+                        };
                         if current_byte_index == byte_index && affinity == Affinity::After {
-                            return (current_row_index, current_column_index);
+                            return (current_row_index, before_column_index);
                         }
                         current_byte_index += grapheme.len();
-                        current_column_index += grapheme.column_count();
                         if current_byte_index == byte_index && affinity == Affinity::Before {
-                            return (current_row_index, current_column_index);
+                            return (current_row_index, after_column_index);
                         }
                     }
+                    current_column_index += text.column_count();
                 }
                 WrappedElement::Text {
                     is_inlay: true,
@@ -248,16 +261,22 @@ impl<'a> Line<'a> {
                     is_inlay: false,
                     text,
                 } => {
-                    for grapheme in text.graphemes() {
-                        let next_column = current_column_index + grapheme.column_count();
+                    for (grapheme, grapheme_column_index, is_rtl) in text.visual_graphemes() {
+                        let start_column_index = current_column_index + grapheme_column_index;
+                        let end_column_index = start_column_index + grapheme.column_count();
                         if current_row_index == row_index
-                            && (current_column_index..next_column).contains(&column_index)
+                            && (start_column_index..end_column_index).contains(&column_index)
                         {
-                            return (current_byte_index, Affinity::After);
+                            // The left of a grapheme in a right-to-left run is after it.
+                            return if is_rtl {
+                                (current_byte_index + grapheme.len(), Affinity::Before)
+                            } else {
+                                (current_byte_index, Affinity::After)
+                            };
                         }
                         current_byte_index += grapheme.len();
-                        current_column_index = next_column;
                     }
+                    current_column_index += text.column_count();
                 }
                 WrappedElement::Text {
                     is_inlay: true,
@@ -2,28 +2,51 @@ pub use makepad_widgets;
 use makepad_widgets::*;
 
 pub mod char;
+pub mod code_action;
 pub mod code_editor;
+pub mod collab;
+pub mod completion;
 pub mod decoration;
+pub mod diagnostic;
+pub mod diff;
+pub mod diff_view;
 pub mod document;
+pub mod edit_location;
+pub mod fold;
 pub mod history;
 pub mod inlays;
 pub mod iter;
+pub mod key_bindings;
 pub mod layout;
+pub mod minimap;
+pub mod paste;
+pub mod recording;
+pub mod regex;
+pub mod rich_text;
+pub mod scroll_link;
+pub mod search;
 pub mod selection;
 pub mod session;
 pub mod settings;
+pub mod signature_help;
+pub mod state;
 pub mod str;
+pub mod structure;
 pub mod text;
 pub mod token;
 pub mod tokenizer;
+pub mod tokenizer_rules;
+pub mod vim;
 pub mod widgets;
 pub mod wrap;
 
 pub use self::{
     code_editor::CodeEditor, document::Document, history::History, layout::Line,
     selection::Selection, session::Session, settings::Settings, token::Token, tokenizer::Tokenizer,
+    tokenizer_rules::TokenizerRules,
 };
 
 pub fn live_design(cx: &mut Cx) {
     crate::code_editor::live_design(cx);
+    crate::diff_view::live_design(cx);
 }
@@ -0,0 +1,115 @@
+use crate::{
+    str::StrExt,
+    token::{Token, TokenKind},
+};
+
+/// A run of characters of the same kind on a line of a minimap, in columns.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct MinimapRun {
+    pub column: usize,
+    pub column_count: usize,
+    pub kind: TokenKind,
+}
+
+/// The runs a minimap draws for each line of a document. They are worked out when a line is
+/// first shown, and only again once its text or tokens changed, so scrolling and redrawing
+/// after an edit only redo the lines that are dirty.
+#[derive(Clone, Debug, Default)]
+pub struct Minimap {
+    lines: Vec<Option<MinimapLine>>,
+}
+
+#[derive(Clone, Debug)]
+struct MinimapLine {
+    text: String,
+    tokens: Vec<Token>,
+    runs: Vec<MinimapRun>,
+}
+
+impl Minimap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Brings the runs of the lines from `line_start` up to `line_end` up to date with `lines`
+    /// and their `tokens`. Returns how many lines were dirty.
+    pub fn update(
+        &mut self,
+        lines: &[String],
+        tokens: &[Vec<Token>],
+        line_start: usize,
+        line_end: usize,
+    ) -> usize {
+        self.lines.resize(lines.len(), None);
+        let mut dirty_count = 0;
+        for line_index in line_start..line_end.min(lines.len()) {
+            let text = &lines[line_index];
+            let tokens = tokens.get(line_index).map_or(&[][..], |tokens| tokens);
+            let line = &mut self.lines[line_index];
+            if line
+                .as_ref()
+                .is_some_and(|line| line.text == *text && line.tokens == tokens)
+            {
+                continue;
+            }
+            *line = Some(MinimapLine {
+                text: text.clone(),
+                tokens: tokens.to_vec(),
+                runs: runs(text, tokens),
+            });
+            dirty_count += 1;
+        }
+        dirty_count
+    }
+
+    /// The runs of the line, or none if it wasn't updated yet.
+    pub fn runs(&self, line_index: usize) -> &[MinimapRun] {
+        self.lines
+            .get(line_index)
+            .and_then(|line| line.as_ref())
+            .map_or(&[], |line| &line.runs)
+    }
+
+    pub fn clear(&mut self) {
+        self.lines.clear();
+    }
+}
+
+/// Splits `text` into runs by its `tokens`, leaving out whitespace and joining neighbours of the
+/// same kind. Text the tokens don't cover is `TokenKind::Unknown`.
+fn runs(text: &str, tokens: &[Token]) -> Vec<MinimapRun> {
+    let mut runs: Vec<MinimapRun> = Vec::new();
+    let mut rest = text;
+    let mut column = 0;
+    let rest_token = Token {
+        len: usize::MAX,
+        kind: TokenKind::Unknown,
+    };
+    for token in tokens.iter().copied().chain([rest_token]) {
+        if rest.is_empty() {
+            break;
+        }
+        let len = token.len.min(rest.len());
+        let (token_text, next) = match (rest.get(..len), rest.get(len..)) {
+            (Some(token_text), Some(next)) => (token_text, next),
+            _ => (rest, ""),
+        };
+        rest = next;
+        let column_count = token_text.column_count();
+        let is_blank = token.kind == TokenKind::Whitespace || token_text.trim().is_empty();
+        if !is_blank {
+            match runs.last_mut() {
+                Some(run) if run.kind == token.kind && run.column + run.column_count == column => {
+                    run.column_count += column_count;
+                }
+                _ => runs.push(MinimapRun {
+                    column,
+                    column_count,
+                    kind: token.kind,
+                }),
+            }
+        }
+        column += column_count;
+    }
+    runs
+}
@@ -0,0 +1,112 @@
+use crate::str::StrExt;
+
+/// Where pasted text is going to end up, so a filter can adapt the text to it.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct PasteContext {
+    /// The paste position is inside a string literal.
+    pub in_string: bool,
+    /// The indentation of the line the text is pasted into.
+    pub indent_column_count: usize,
+}
+
+/// Transforms clipboard contents before they are inserted. Filters are applied per selection,
+/// since every cursor can have a different context.
+pub trait PasteFilter {
+    fn filter(&self, text: &str, context: &PasteContext) -> String;
+}
+
+/// Normalizes line endings and drops the invisible characters that tend to come along when
+/// copying from web pages and word processors.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct PlainText;
+
+impl PasteFilter for PlainText {
+    fn filter(&self, text: &str, _context: &PasteContext) -> String {
+        text.replace("\r\n", "\n")
+            .chars()
+            .filter_map(|char| match char {
+                '\r' => Some('\n'),
+                '\u{a0}' => Some(' '),
+                '\u{200b}' | '\u{200c}' | '\u{200d}' | '\u{feff}' => None,
+                char => Some(char),
+            })
+            .collect()
+    }
+}
+
+/// Strips the common indentation of the pasted lines and indents them relative to the
+/// destination line instead.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Reindent;
+
+impl PasteFilter for Reindent {
+    fn filter(&self, text: &str, context: &PasteContext) -> String {
+        let text = PlainText.filter(text, context);
+        let mut lines = text.split('\n');
+        let first_line = lines.next().unwrap_or("").trim_start();
+        let lines: Vec<_> = lines.collect();
+        // the first line usually starts mid-line, so only the other lines decide the indent
+        let common_indent = lines
+            .iter()
+            .filter_map(|line| line.indent())
+            .map(|indent| indent.len())
+            .min()
+            .unwrap_or(0);
+        let mut string = first_line.to_string();
+        for line in lines {
+            string.push('\n');
+            if line.trim().is_empty() {
+                continue;
+            }
+            string.extend(std::iter::repeat(' ').take(context.indent_column_count));
+            string.push_str(&line[common_indent.min(line.indent().unwrap_or("").len())..]);
+        }
+        string
+    }
+}
+
+/// Pastes the text as the contents of a string literal: escaped when pasting inside a string,
+/// and wrapped in quotes otherwise.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct EscapedString;
+
+impl PasteFilter for EscapedString {
+    fn filter(&self, text: &str, context: &PasteContext) -> String {
+        let text = PlainText.filter(text, context);
+        let mut string = String::new();
+        for char in text.chars() {
+            match char {
+                '\\' => string.push_str("\\\\"),
+                '"' => string.push_str("\\\""),
+                '\n' => string.push_str("\\n"),
+                '\t' => string.push_str("\\t"),
+                char => string.push(char),
+            }
+        }
+        if context.in_string {
+            string
+        } else {
+            format!("\"{}\"", string)
+        }
+    }
+}
+
+/// Pastes the text as a raw string literal, with just enough `#`s to contain it. Raw strings
+/// can't be nested in other strings, so inside a string this falls back to escaping.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct RawString;
+
+impl PasteFilter for RawString {
+    fn filter(&self, text: &str, context: &PasteContext) -> String {
+        if context.in_string {
+            return EscapedString.filter(text, context);
+        }
+        let text = PlainText.filter(text, context);
+        let mut hash_count = 0;
+        while text.contains(&format!("\"{}", "#".repeat(hash_count))) {
+            hash_count += 1;
+        }
+        let hashes = "#".repeat(hash_count);
+        format!("r{}\"{}\"{}", hashes, text, hashes)
+    }
+}
@@ -0,0 +1,237 @@
+use {
+    crate::{
+        document::Document,
+        history::EditKind,
+        selection::{Cursor, Selection, SelectionSet},
+        session::Session,
+        text::{Change, Drift, Edit, Length, Position, Text},
+    },
+    makepad_widgets::makepad_micro_serde::*,
+};
+
+/// The full edit history of a document with timestamps, so it can be saved and played back.
+#[derive(Clone, Debug, Default, SerBin, DeBin, SerJson, DeJson)]
+pub struct Recording {
+    pub initial_text: String,
+    pub edits: Vec<RecordedEdit>,
+}
+
+/// An edit, in seconds since the start of the recording.
+#[derive(Clone, Debug, SerBin, DeBin, SerJson, DeJson)]
+pub struct RecordedEdit {
+    pub time: f64,
+    pub change: RecordedChange,
+}
+
+#[derive(Clone, Debug, SerBin, DeBin, SerJson, DeJson)]
+pub enum RecordedChange {
+    Insert {
+        line_index: usize,
+        byte_index: usize,
+        text: String,
+    },
+    Delete {
+        line_index: usize,
+        byte_index: usize,
+        line_count: usize,
+        byte_count: usize,
+    },
+}
+
+impl RecordedChange {
+    pub fn from_change(change: &Change) -> Self {
+        match change {
+            Change::Insert(position, text) => Self::Insert {
+                line_index: position.line_index,
+                byte_index: position.byte_index,
+                text: text.to_string(),
+            },
+            Change::Delete(start, length) => Self::Delete {
+                line_index: start.line_index,
+                byte_index: start.byte_index,
+                line_count: length.line_count,
+                byte_count: length.byte_count,
+            },
+        }
+    }
+
+    pub fn to_change(&self) -> Change {
+        match self {
+            Self::Insert {
+                line_index,
+                byte_index,
+                text,
+            } => Change::Insert(
+                Position {
+                    line_index: *line_index,
+                    byte_index: *byte_index,
+                },
+                Text::from(text.as_str()),
+            ),
+            Self::Delete {
+                line_index,
+                byte_index,
+                line_count,
+                byte_count,
+            } => Change::Delete(
+                Position {
+                    line_index: *line_index,
+                    byte_index: *byte_index,
+                },
+                Length {
+                    line_count: *line_count,
+                    byte_count: *byte_count,
+                },
+            ),
+        }
+    }
+}
+
+/// Records the edits made to a document while it is active.
+#[derive(Debug)]
+pub struct Recorder {
+    start_time: f64,
+    recording: Recording,
+}
+
+impl Recorder {
+    /// Starts recording at `time`, in seconds, on the same clock as the times of the edits.
+    pub fn new(initial_text: &Text, time: f64) -> Self {
+        Self {
+            start_time: time,
+            recording: Recording {
+                initial_text: initial_text.to_string(),
+                edits: Vec::new(),
+            },
+        }
+    }
+
+    pub fn record_edit(&mut self, edit: &Edit, time: f64) {
+        self.recording.edits.push(RecordedEdit {
+            time: time - self.start_time,
+            change: RecordedChange::from_change(&edit.change),
+        });
+    }
+
+    pub fn into_recording(self) -> Recording {
+        self.recording
+    }
+}
+
+/// Plays a recording back into a fresh document, typing replay style. Drive it with `advance`
+/// and draw `session()` in a read only code editor.
+#[derive(Debug)]
+pub struct Playback {
+    recording: Recording,
+    session: Session,
+    next_edit_index: usize,
+    time: f64,
+    speed: f64,
+    is_playing: bool,
+}
+
+impl Playback {
+    pub fn new(recording: Recording) -> Self {
+        let document = Document::new(
+            Text::from(recording.initial_text.as_str()),
+            Default::default(),
+        );
+        Self {
+            recording,
+            session: Session::new(document),
+            next_edit_index: 0,
+            time: 0.0,
+            speed: 1.0,
+            is_playing: false,
+        }
+    }
+
+    pub fn session(&self) -> &Session {
+        &self.session
+    }
+
+    pub fn session_mut(&mut self) -> &mut Session {
+        &mut self.session
+    }
+
+    pub fn speed(&self) -> f64 {
+        self.speed
+    }
+
+    pub fn set_speed(&mut self, speed: f64) {
+        self.speed = speed.max(0.0);
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.is_playing
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.next_edit_index == self.recording.edits.len()
+    }
+
+    pub fn play(&mut self) {
+        self.is_playing = true;
+    }
+
+    pub fn pause(&mut self) {
+        self.is_playing = false;
+    }
+
+    /// Applies the edits that happened in the next `time_delta` seconds of wall clock time,
+    /// scaled by the playback speed. Returns whether anything changed.
+    pub fn advance(&mut self, time_delta: f64) -> bool {
+        if !self.is_playing {
+            return false;
+        }
+        self.time += time_delta * self.speed;
+        let mut changed = false;
+        while let Some(edit) = self.recording.edits.get(self.next_edit_index) {
+            if edit.time > self.time {
+                break;
+            }
+            self.apply_edit(edit.change.to_change());
+            self.next_edit_index += 1;
+            changed = true;
+        }
+        if self.is_finished() {
+            self.is_playing = false;
+        }
+        changed
+    }
+
+    /// Applies the next edit right away, for stepping through a recording while debugging.
+    pub fn step(&mut self) -> bool {
+        match self.recording.edits.get(self.next_edit_index) {
+            Some(edit) => {
+                self.time = edit.time;
+                self.apply_edit(edit.change.to_change());
+                self.next_edit_index += 1;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn apply_edit(&mut self, change: Change) {
+        let cursor_position = match &change {
+            Change::Insert(position, text) => *position + text.length(),
+            Change::Delete(start, _) => *start,
+        };
+        self.session.document().edit_with_selections(
+            self.session.id(),
+            EditKind::Other,
+            &SelectionSet::new(),
+            |mut editor| {
+                editor.apply_edit(Edit {
+                    change,
+                    drift: Drift::Before,
+                });
+                let mut selections = SelectionSet::new();
+                selections.set_selection(Selection::from(Cursor::from(cursor_position)));
+                selections
+            },
+        );
+        self.session.handle_changes();
+    }
+}
@@ -0,0 +1,1539 @@
+use {
+    crate::text::Position,
+    std::{cell::Cell, collections::HashMap},
+};
+
+/// The byte ranges of a match and of its groups, see `Regex::captures`.
+pub type Captures = Vec<Option<(usize, usize)>>;
+
+/// Text a `Regex` can search, addressed by byte index as if it were one string, so it can be
+/// made of separate chunks without joining them.
+pub trait Input {
+    /// The length of the text in bytes.
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The char that starts at byte `index`, if `index` isn't the end of the text.
+    fn char_at(&self, index: usize) -> Option<char>;
+
+    /// The char that ends at byte `index`, if `index` isn't the start of the text.
+    fn char_before(&self, index: usize) -> Option<char>;
+
+    /// The text from byte `index` to the end of the chunk it's in, which is only empty at the
+    /// end of the text. Searching goes through the text a chunk at a time, as that's faster
+    /// than a char at a time.
+    fn chunk_at(&self, index: usize) -> &str;
+
+    /// Appends the text from byte `start` to byte `end` to `out`.
+    fn push_range(&self, start: usize, end: usize, out: &mut String) {
+        let mut index = start;
+        while let Some(char) = self.char_at(index).filter(|_| index < end) {
+            out.push(char);
+            index += char.len_utf8();
+        }
+    }
+}
+
+impl Input for str {
+    fn len(&self) -> usize {
+        str::len(self)
+    }
+
+    fn char_at(&self, index: usize) -> Option<char> {
+        self[index..].chars().next()
+    }
+
+    fn char_before(&self, index: usize) -> Option<char> {
+        self[..index].chars().next_back()
+    }
+
+    fn chunk_at(&self, index: usize) -> &str {
+        &self[index..]
+    }
+
+    fn push_range(&self, start: usize, end: usize, out: &mut String) {
+        out.push_str(&self[start..end]);
+    }
+}
+
+/// The lines of a document as an `Input`, with a newline between each two of them, like the
+/// lines of a `Text`. Matches can span lines.
+pub struct Lines<'a> {
+    lines: &'a [String],
+    /// The byte index where each line starts.
+    starts: Vec<usize>,
+    /// The line the last lookup was in, as searches mostly move forward through the text.
+    last_line: Cell<usize>,
+}
+
+impl<'a> Lines<'a> {
+    pub fn new(lines: &'a [String]) -> Self {
+        let mut start = 0;
+        let starts = lines
+            .iter()
+            .map(|line| {
+                let line_start = start;
+                start += line.len() + 1;
+                line_start
+            })
+            .collect();
+        Self {
+            lines,
+            starts,
+            last_line: Cell::new(0),
+        }
+    }
+
+    /// The line that byte `index` is in, where the newline after a line belongs to the line.
+    fn line_index(&self, index: usize) -> usize {
+        let last_line = self.last_line.get();
+        let is_in_last_line = self
+            .starts
+            .get(last_line)
+            .is_some_and(|start| *start <= index)
+            && self
+                .starts
+                .get(last_line + 1)
+                .map_or(true, |end| index < *end);
+        if is_in_last_line {
+            return last_line;
+        }
+        let line_index = self
+            .starts
+            .partition_point(|start| *start <= index)
+            .saturating_sub(1);
+        self.last_line.set(line_index);
+        line_index
+    }
+
+    /// The byte index of `position`, to start a search there.
+    pub fn offset(&self, position: Position) -> usize {
+        self.starts.get(position.line_index).copied().unwrap_or(0) + position.byte_index
+    }
+
+    /// The position of byte `index`, to turn a match into a range in the document.
+    pub fn position(&self, index: usize) -> Position {
+        let line_index = self.line_index(index);
+        Position {
+            line_index,
+            byte_index: index - self.starts.get(line_index).copied().unwrap_or(0),
+        }
+    }
+}
+
+impl<'a> Input for Lines<'a> {
+    fn len(&self) -> usize {
+        match (self.starts.last(), self.lines.last()) {
+            (Some(start), Some(line)) => start + line.len(),
+            _ => 0,
+        }
+    }
+
+    fn char_at(&self, index: usize) -> Option<char> {
+        let position = self.position(index);
+        let line = self.lines.get(position.line_index)?;
+        match line[position.byte_index..].chars().next() {
+            Some(char) => Some(char),
+            None if position.line_index + 1 < self.lines.len() => Some('\n'),
+            None => None,
+        }
+    }
+
+    fn char_before(&self, index: usize) -> Option<char> {
+        let position = self.position(index);
+        if position.byte_index == 0 {
+            return (position.line_index > 0).then_some('\n');
+        }
+        self.lines[position.line_index][..position.byte_index]
+            .chars()
+            .next_back()
+    }
+
+    fn chunk_at(&self, index: usize) -> &str {
+        let position = self.position(index);
+        let Some(line) = self.lines.get(position.line_index) else {
+            return "";
+        };
+        match &line[position.byte_index..] {
+            "" if position.line_index + 1 < self.lines.len() => "\n",
+            rest => rest,
+        }
+    }
+}
+
+/// A small regular expression, enough for the patterns in settings files. Searching takes time
+/// linear in the length of the text, whatever the pattern.
+///
+/// Supports literals, `.`, `[abc]`/`[a-z]`/`[^abc]` classes, the `\d` `\w` `\s` classes and
+/// their negations, `^` and `$`, the `\b` word boundary and its negation `\B`, capturing
+/// `(..)`, named `(?P<name>..)`/`(?<name>..)` and non capturing `(?:..)` groups with `|`
+/// alternatives, and the `*`, `+`, `?` and `{n}`/`{n,}`/`{n,m}` repetitions, which are greedy
+/// unless followed by a `?`. A leading `(?flags)` sets flags for the whole pattern: `i` makes it
+/// case insensitive, `m` lets `^` and `$` match at the start and end of every line, `s` lets
+/// `.` match a newline, and `U` swaps which repetitions are greedy.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Regex {
+    flags: Flags,
+    group_count: usize,
+    /// The group numbers of the groups that have a name.
+    group_names: Vec<(usize, String)>,
+    /// The nodes one of which matches the first char of every match, or `None` if a match can
+    /// be empty. Searching skips the places where none of them match.
+    first_nodes: Option<Vec<RegexNode>>,
+    program: Program,
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+struct Flags {
+    ignore_case: bool,
+    multi_line: bool,
+    dot_matches_newline: bool,
+    swap_greed: bool,
+}
+
+impl Flags {
+    /// Splits the flags off the start of `pattern`.
+    fn parse(pattern: &str) -> Result<(Self, &str), String> {
+        let mut flags = Self::default();
+        let Some((letters, rest)) = pattern
+            .strip_prefix("(?")
+            .and_then(|group| group.split_once(')'))
+            .filter(|(letters, _)| {
+                !letters.is_empty() && letters.chars().all(|char| char.is_ascii_alphabetic())
+            })
+        else {
+            return Ok((flags, pattern));
+        };
+        for letter in letters.chars() {
+            match letter {
+                'i' => flags.ignore_case = true,
+                'm' => flags.multi_line = true,
+                's' => flags.dot_matches_newline = true,
+                'U' => flags.swap_greed = true,
+                letter => return Err(format!("Unknown flag {}", letter)),
+            }
+        }
+        Ok((flags, rest))
+    }
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+enum RegexNode {
+    Char(char),
+    AnyChar,
+    Class {
+        negated: bool,
+        items: Vec<ClassItem>,
+    },
+    LineStart,
+    LineEnd,
+    WordBoundary {
+        negated: bool,
+    },
+    Group {
+        alternatives: Vec<Vec<RegexNode>>,
+        capture: Option<usize>,
+    },
+    Repeat {
+        node: Box<RegexNode>,
+        min: usize,
+        max: Option<usize>,
+        greedy: bool,
+    },
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+enum ClassItem {
+    Range(char, char),
+    Digit(bool),
+    Word(bool),
+    Space(bool),
+}
+
+impl RegexNode {
+    /// Whether the node matches `char`, for the nodes that match a single char.
+    fn matches_char(&self, char: char, flags: Flags) -> bool {
+        match self {
+            RegexNode::Char(expected) => {
+                char == *expected
+                    || flags.ignore_case && char.to_lowercase().eq(expected.to_lowercase())
+            }
+            RegexNode::AnyChar => char != '\n' || flags.dot_matches_newline,
+            RegexNode::Class { negated, items } => {
+                let is_match = items.iter().any(|item| item.is_match(char))
+                    || flags.ignore_case && {
+                        let lower = char.to_lowercase().next().unwrap_or(char);
+                        let upper = char.to_uppercase().next().unwrap_or(char);
+                        items
+                            .iter()
+                            .any(|item| item.is_match(lower) || item.is_match(upper))
+                    };
+                is_match != *negated
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Collects the single char nodes that can match the first char of `nodes` into `first`, and
+/// returns whether `nodes` can match without taking any chars.
+fn first_nodes(nodes: &[RegexNode], first: &mut Vec<RegexNode>) -> bool {
+    for node in nodes {
+        let can_be_empty = match node {
+            RegexNode::Char(_) | RegexNode::AnyChar | RegexNode::Class { .. } => {
+                if !first.contains(node) {
+                    first.push(node.clone());
+                }
+                false
+            }
+            // these don't take a char, so the first char comes after them
+            RegexNode::LineStart | RegexNode::LineEnd | RegexNode::WordBoundary { .. } => true,
+            RegexNode::Group { alternatives, .. } => {
+                alternatives.iter().fold(false, |can_be_empty, sequence| {
+                    first_nodes(sequence, first) || can_be_empty
+                })
+            }
+            RegexNode::Repeat { node, min, .. } => {
+                first_nodes(std::slice::from_ref(node), first) || *min == 0
+            }
+        };
+        if !can_be_empty {
+            return false;
+        }
+    }
+    true
+}
+
+impl ClassItem {
+    fn is_match(&self, char: char) -> bool {
+        match *self {
+            ClassItem::Range(start, end) => char >= start && char <= end,
+            ClassItem::Digit(negated) => char.is_ascii_digit() != negated,
+            ClassItem::Word(negated) => is_word_char(char) != negated,
+            ClassItem::Space(negated) => char.is_whitespace() != negated,
+        }
+    }
+}
+
+fn is_word_char(char: char) -> bool {
+    char.is_alphanumeric() || char == '_'
+}
+
+/// The largest count a `{n}`/`{n,}`/`{n,m}` repetition can have, as the repeated node is
+/// written out that many times.
+const MAX_COUNT: usize = 1000;
+
+struct Parser<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+    group_count: usize,
+    group_names: Vec<(usize, String)>,
+    swap_greed: bool,
+}
+
+impl<'a> Parser<'a> {
+    fn alternatives(&mut self, in_group: bool) -> Result<Vec<Vec<RegexNode>>, String> {
+        let mut alternatives = vec![Vec::new()];
+        loop {
+            let Some(char) = self.chars.next() else {
+                if in_group {
+                    return Err("Missing )".to_string());
+                }
+                return Ok(alternatives);
+            };
+            let node = match char {
+                ')' if in_group => return Ok(alternatives),
+                ')' => return Err("Unmatched )".to_string()),
+                '|' => {
+                    alternatives.push(Vec::new());
+                    continue;
+                }
+                '(' => {
+                    let name = if self.chars.peek() == Some(&'?') {
+                        self.chars.next();
+                        match self.chars.next() {
+                            Some(':') => None,
+                            Some('P') if self.chars.next() == Some('<') => Some(self.group_name()?),
+                            Some('<') => Some(self.group_name()?),
+                            _ => return Err("Unsupported group".to_string()),
+                        }
+                    } else {
+                        Some(String::new())
+                    };
+                    let capture = name.map(|name| {
+                        // groups are numbered by their opening paren, from 1, named or not
+                        self.group_count += 1;
+                        if !name.is_empty() {
+                            self.group_names.push((self.group_count, name));
+                        }
+                        self.group_count
+                    });
+                    RegexNode::Group {
+                        alternatives: self.alternatives(true)?,
+                        capture,
+                    }
+                }
+                '*' | '+' | '?' | '{' => {
+                    let sequence = alternatives.last_mut().unwrap();
+                    let Some(node) = sequence.pop() else {
+                        return Err(format!("Nothing to repeat before {}", char));
+                    };
+                    let (min, max) = match char {
+                        '*' => (0, None),
+                        '+' => (1, None),
+                        '?' => (0, Some(1)),
+                        _ => self.counts()?,
+                    };
+                    let lazy = self.chars.next_if_eq(&'?').is_some();
+                    sequence.push(RegexNode::Repeat {
+                        node: Box::new(node),
+                        min,
+                        max,
+                        greedy: lazy == self.swap_greed,
+                    });
+                    continue;
+                }
+                '.' => RegexNode::AnyChar,
+                '^' => RegexNode::LineStart,
+                '$' => RegexNode::LineEnd,
+                '[' => self.class()?,
+                // outside of a class these are word boundaries, not characters
+                '\\' if matches!(self.chars.peek(), Some('b' | 'B')) => RegexNode::WordBoundary {
+                    negated: self.chars.next() == Some('B'),
+                },
+                '\\' => match self.escape()? {
+                    ClassItem::Range(start, _) => RegexNode::Char(start),
+                    item => RegexNode::Class {
+                        negated: false,
+                        items: vec![item],
+                    },
+                },
+                char => RegexNode::Char(char),
+            };
+            alternatives.last_mut().unwrap().push(node);
+        }
+    }
+
+    fn group_name(&mut self) -> Result<String, String> {
+        let mut name = String::new();
+        loop {
+            match self.chars.next() {
+                Some('>') if !name.is_empty() => break,
+                Some(char) if is_word_char(char) => name.push(char),
+                _ => return Err("Expected a group name and >".to_string()),
+            }
+        }
+        if self.group_names.iter().any(|(_, other)| *other == name) {
+            return Err(format!("Duplicate group name {}", name));
+        }
+        Ok(name)
+    }
+
+    fn number(&mut self) -> Option<usize> {
+        let mut digits = String::new();
+        while let Some(char) = self.chars.peek().filter(|char| char.is_ascii_digit()) {
+            digits.push(*char);
+            self.chars.next();
+        }
+        digits.parse().ok()
+    }
+
+    fn counts(&mut self) -> Result<(usize, Option<usize>), String> {
+        let min = self.number().ok_or("Expected a count after {")?;
+        let max = match self.chars.next() {
+            Some('}') => Some(min),
+            Some(',') => {
+                let max = self.number();
+                if self.chars.next() != Some('}') {
+                    return Err("Missing }".to_string());
+                }
+                max
+            }
+            _ => return Err("Expected , or }".to_string()),
+        };
+        if min.max(max.unwrap_or(0)) > MAX_COUNT {
+            return Err(format!("Counts above {} aren't supported", MAX_COUNT));
+        }
+        Ok((min, max))
+    }
+
+    fn escape(&mut self) -> Result<ClassItem, String> {
+        let char = self.chars.next().ok_or("Pattern ends in \\")?;
+        Ok(match char {
+            'd' => ClassItem::Digit(false),
+            'D' => ClassItem::Digit(true),
+            'w' => ClassItem::Word(false),
+            'W' => ClassItem::Word(true),
+            's' => ClassItem::Space(false),
+            'S' => ClassItem::Space(true),
+            't' => ClassItem::Range('\t', '\t'),
+            'n' => ClassItem::Range('\n', '\n'),
+            char => ClassItem::Range(char, char),
+        })
+    }
+
+    fn class(&mut self) -> Result<RegexNode, String> {
+        let negated = self.chars.peek() == Some(&'^');
+        if negated {
+            self.chars.next();
+        }
+        let mut items = Vec::new();
+        loop {
+            let item = match self.chars.next() {
+                None => return Err("Missing ]".to_string()),
+                // a ] right at the start is part of the class
+                Some(']') if !items.is_empty() => return Ok(RegexNode::Class { negated, items }),
+                Some('\\') => self.escape()?,
+                Some(char) => ClassItem::Range(char, char),
+            };
+            match item {
+                ClassItem::Range(start, _) if self.chars.peek() == Some(&'-') => {
+                    self.chars.next();
+                    match self.chars.next() {
+                        Some(']') => {
+                            items.push(ClassItem::Range(start, start));
+                            items.push(ClassItem::Range('-', '-'));
+                            return Ok(RegexNode::Class { negated, items });
+                        }
+                        Some(end) => items.push(ClassItem::Range(start, end)),
+                        None => return Err("Missing ]".to_string()),
+                    }
+                }
+                item => items.push(item),
+            }
+        }
+    }
+}
+
+impl Regex {
+    pub fn new(pattern: &str) -> Result<Self, String> {
+        let (flags, pattern) = Flags::parse(pattern)?;
+        let mut parser = Parser {
+            chars: pattern.chars().peekable(),
+            group_count: 0,
+            group_names: Vec::new(),
+            swap_greed: flags.swap_greed,
+        };
+        let alternatives = parser.alternatives(false)?;
+        let mut first = Vec::new();
+        let can_be_empty = alternatives.iter().fold(false, |can_be_empty, sequence| {
+            first_nodes(sequence, &mut first) || can_be_empty
+        });
+        Ok(Self {
+            program: Program::new(&alternatives)?,
+            flags,
+            group_count: parser.group_count,
+            group_names: parser.group_names,
+            first_nodes: (!can_be_empty).then_some(first),
+        })
+    }
+
+    /// Whether a match can take a newline, so it can span lines.
+    pub fn matches_newline(&self) -> bool {
+        self.program.instructions.iter().any(|instruction| {
+            matches!(instruction, Instruction::Char(node) if node.matches_char('\n', self.flags))
+        })
+    }
+
+    /// The number of capturing groups in the pattern, named ones included.
+    pub fn group_count(&self) -> usize {
+        self.group_count
+    }
+
+    /// The number of the group called `name`, which is its index in `Captures`.
+    pub fn group_index(&self, name: &str) -> Option<usize> {
+        self.group_names
+            .iter()
+            .find(|(_, group_name)| group_name == name)
+            .map(|(index, _)| *index)
+    }
+
+    /// The byte range of the group called `name` in `captures`, if it took part in the match.
+    pub fn named(&self, captures: &Captures, name: &str) -> Option<(usize, usize)> {
+        captures.get(self.group_index(name)?).copied().flatten()
+    }
+
+    /// Fills in the groups of a match in `text` in `replacement`. `$1` and `${1}` stand for a
+    /// group by number, with `$0` the whole match, `$name` and `${name}` for a named group, and
+    /// `$$` for a `$`. Groups that didn't take part in the match or don't exist become nothing.
+    pub fn expand<I: Input + ?Sized>(
+        &self,
+        text: &I,
+        captures: &Captures,
+        replacement: &str,
+    ) -> String {
+        let mut out = String::new();
+        let mut chars = replacement.chars().peekable();
+        while let Some(char) = chars.next() {
+            if char != '$' {
+                out.push(char);
+                continue;
+            }
+            if chars.next_if_eq(&'$').is_some() {
+                out.push('$');
+                continue;
+            }
+            let braced = chars.next_if_eq(&'{').is_some();
+            let mut reference = String::new();
+            while let Some(char) = chars.next_if(|char| is_word_char(*char)) {
+                reference.push(char);
+            }
+            if reference.is_empty() || braced && chars.next_if_eq(&'}').is_none() {
+                // not a group reference, so it stays as it is
+                out.push('$');
+                if braced {
+                    out.push('{');
+                }
+                out.push_str(&reference);
+                continue;
+            }
+            let group = match reference.parse::<usize>() {
+                Ok(index) => captures.get(index).copied().flatten(),
+                Err(_) => self.named(captures, &reference),
+            };
+            if let Some((start, end)) = group {
+                text.push_range(start, end, &mut out);
+            }
+        }
+        out
+    }
+
+    /// The byte range of the first match in `text`.
+    pub fn find<I: Input + ?Sized>(&self, text: &I) -> Option<(usize, usize)> {
+        self.find_iter(text).next()
+    }
+
+    pub fn is_match<I: Input + ?Sized>(&self, text: &I) -> bool {
+        self.find(text).is_some()
+    }
+
+    /// The end of the match that starts at byte `start` of `text`, if there is one. `^` still
+    /// only matches at the start of `text`, or after a newline with the `m` flag.
+    pub fn match_at<I: Input + ?Sized>(&self, text: &I, start: usize) -> Option<usize> {
+        let mut threads = Threads::new(self.program.instructions.len());
+        self.program
+            .find(
+                text,
+                self.flags,
+                self.first_nodes.as_deref(),
+                start,
+                true,
+                &mut threads,
+            )
+            .map(|(_, end)| end)
+    }
+
+    /// The byte ranges of the first match in `text` and of its groups. The whole match is at
+    /// index 0, and groups that didn't take part in the match are `None`.
+    pub fn captures<I: Input + ?Sized>(&self, text: &I) -> Option<Captures> {
+        let (start, end) = self.find(text)?;
+        self.program
+            .captures(text, self.flags, self.group_count, start, end)
+    }
+
+    /// Like `captures`, but for the match that starts at byte `start` of `text`.
+    pub fn captures_at<I: Input + ?Sized>(&self, text: &I, start: usize) -> Option<Captures> {
+        let end = self.match_at(text, start)?;
+        self.program
+            .captures(text, self.flags, self.group_count, start, end)
+    }
+
+    /// The byte ranges of the matches in `text` that don't overlap, from left to right.
+    pub fn find_iter<'a, I: Input + ?Sized>(
+        &'a self,
+        text: &'a I,
+    ) -> impl Iterator<Item = (usize, usize)> + 'a {
+        self.find_iter_in(text, 0, text.len())
+    }
+
+    /// Like `find_iter`, but only the matches that start from byte `start` up to byte
+    /// `last_start`, which can end after it.
+    ///
+    /// A `Dfa` finds where each match ends, and where it starts is found from the last place
+    /// before it where no match was under way, which is mostly the start itself. So it takes a
+    /// table lookup per char, and the Pike VM only runs for the matches that start later.
+    pub fn find_iter_in<'a, I: Input + ?Sized>(
+        &'a self,
+        text: &'a I,
+        start: usize,
+        last_start: usize,
+    ) -> impl Iterator<Item = (usize, usize)> + 'a {
+        let mut dfa = Dfa::new(&self.program, self.flags, self.first_nodes.as_deref());
+        let mut threads = Threads::new(self.program.instructions.len());
+        let mut start = Some(start);
+        std::iter::from_fn(move || {
+            let (search_start, end) = dfa.find_end(text, start?, last_start, false)?;
+            // mostly the match starts right there, and then it's the one found
+            let match_start = if dfa
+                .find_end(text, search_start, search_start, true)
+                .is_some()
+            {
+                search_start
+            } else {
+                self.program
+                    .find(
+                        text,
+                        self.flags,
+                        self.first_nodes.as_deref(),
+                        search_start,
+                        false,
+                        &mut threads,
+                    )?
+                    .0
+            };
+            // an empty match moves on by a char, so it doesn't match again right there
+            start = if end > match_start {
+                Some(end)
+            } else {
+                text.char_at(end).map(|char| end + char.len_utf8())
+            };
+            Some((match_start, end))
+        })
+    }
+}
+
+/// The pattern as instructions for a Pike VM, which follows all the ways it can match at once
+/// instead of trying them one after the other, so a search never takes more than the length of
+/// the text times the number of instructions.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+struct Program {
+    instructions: Vec<Instruction>,
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+enum Instruction {
+    /// A `Char`, `AnyChar` or `Class` node.
+    Char(RegexNode),
+    LineStart,
+    LineEnd,
+    WordBoundary {
+        negated: bool,
+    },
+    /// Goes on at both, where the first one takes priority.
+    Split(usize, usize),
+    /// Stores the current byte index in a slot of the captures, the start of group `n` in slot
+    /// `2 * n` and its end in the one after.
+    Save(usize),
+    Jump(usize),
+    Match,
+}
+
+/// The most instructions a `Program` can have, so nested counted repetitions like
+/// `(a{1000}){1000}` are an error rather than taking up gigabytes.
+const MAX_INSTRUCTIONS: usize = 100_000;
+
+impl Program {
+    fn new(alternatives: &[Vec<RegexNode>]) -> Result<Self, String> {
+        // counted from the nodes first, so a program that's too large is never made
+        if alternatives_size(alternatives) >= MAX_INSTRUCTIONS {
+            return Err("Pattern too large".to_string());
+        }
+        let mut program = Self {
+            instructions: Vec::new(),
+        };
+        program.alternatives(alternatives);
+        program.instructions.push(Instruction::Match);
+        Ok(program)
+    }
+
+    fn alternatives(&mut self, alternatives: &[Vec<RegexNode>]) {
+        let mut jumps = Vec::new();
+        for (index, sequence) in alternatives.iter().enumerate() {
+            if index + 1 == alternatives.len() {
+                self.sequence(sequence);
+                break;
+            }
+            let split = self.instructions.len();
+            self.instructions.push(Instruction::Jump(0));
+            self.sequence(sequence);
+            jumps.push(self.instructions.len());
+            self.instructions.push(Instruction::Jump(0));
+            self.instructions[split] = Instruction::Split(split + 1, self.instructions.len());
+        }
+        for jump in jumps {
+            self.instructions[jump] = Instruction::Jump(self.instructions.len());
+        }
+    }
+
+    fn sequence(&mut self, nodes: &[RegexNode]) {
+        for node in nodes {
+            self.node(node);
+        }
+    }
+
+    fn node(&mut self, node: &RegexNode) {
+        match node {
+            RegexNode::Char(_) | RegexNode::AnyChar | RegexNode::Class { .. } => {
+                self.instructions.push(Instruction::Char(node.clone()))
+            }
+            RegexNode::LineStart => self.instructions.push(Instruction::LineStart),
+            RegexNode::LineEnd => self.instructions.push(Instruction::LineEnd),
+            RegexNode::WordBoundary { negated } => self
+                .instructions
+                .push(Instruction::WordBoundary { negated: *negated }),
+            RegexNode::Group {
+                alternatives,
+                capture,
+            } => {
+                if let Some(group) = capture {
+                    self.instructions.push(Instruction::Save(group * 2));
+                }
+                self.alternatives(alternatives);
+                if let Some(group) = capture {
+                    self.instructions.push(Instruction::Save(group * 2 + 1));
+                }
+            }
+            RegexNode::Repeat {
+                node,
+                min,
+                max,
+                greedy,
+            } => {
+                // counted repetitions are written out, the required ones first
+                for _ in 0..*min {
+                    self.node(node);
+                }
+                let optional_count = match max {
+                    Some(max) => max.saturating_sub(*min),
+                    None => 1,
+                };
+                for _ in 0..optional_count {
+                    let split = self.instructions.len();
+                    self.instructions.push(Instruction::Jump(0));
+                    self.node(node);
+                    if max.is_none() {
+                        self.instructions.push(Instruction::Jump(split));
+                    }
+                    let end = self.instructions.len();
+                    self.instructions[split] = if *greedy {
+                        Instruction::Split(split + 1, end)
+                    } else {
+                        Instruction::Split(end, split + 1)
+                    };
+                }
+            }
+        }
+    }
+
+    /// The leftmost match that starts at or after byte `start` of `text`, or only one that
+    /// starts at `start` if `anchored`. Of the ways the pattern can match there, the end is that
+    /// of the first one in order of priority, as `captures` would find it. Matches only start
+    /// where one of `first_nodes` matches, if there are any.
+    fn find<I: Input + ?Sized>(
+        &self,
+        text: &I,
+        flags: Flags,
+        first_nodes: Option<&[RegexNode]>,
+        start: usize,
+        anchored: bool,
+        threads: &mut Threads,
+    ) -> Option<(usize, usize)> {
+        threads.current.clear();
+        threads.next.clear();
+        threads.added_at.fill(usize::MAX);
+        let mut found = None;
+        let mut index = start;
+        loop {
+            let char = text.char_at(index);
+            let can_start = found.is_none()
+                && (!anchored || index == start)
+                && first_nodes.map_or(true, |first_nodes| {
+                    char.is_some_and(|char| {
+                        first_nodes
+                            .iter()
+                            .any(|node| node.matches_char(char, flags))
+                    })
+                });
+            if can_start {
+                // after the threads that started earlier, as those take priority
+                self.add(text, flags, threads, 0, index, index, false);
+            }
+            if threads.current.is_empty() && (found.is_some() || anchored) {
+                break;
+            }
+            let next = index + char.map_or(0, |char| char.len_utf8());
+            let current = std::mem::take(&mut threads.current);
+            for &(pc, match_start) in &current {
+                match &self.instructions[pc] {
+                    Instruction::Char(node) => {
+                        if char.is_some_and(|char| node.matches_char(char, flags)) {
+                            self.add(text, flags, threads, pc + 1, match_start, next, true);
+                        }
+                    }
+                    Instruction::Match => {
+                        // the threads after this one have a lower priority, so they're dropped
+                        found = Some((match_start, index));
+                        break;
+                    }
+                    _ => unreachable!(),
+                }
+            }
+            threads.current = current;
+            threads.current.clear();
+            std::mem::swap(&mut threads.current, &mut threads.next);
+            if char.is_none() {
+                break;
+            }
+            index = next;
+        }
+        found
+    }
+
+    /// Adds a thread at instruction `pc` to the threads at byte `index`, the next ones if
+    /// `is_next`, following jumps, splits and assertions up to the instructions that take a
+    /// char or match.
+    #[allow(clippy::too_many_arguments)]
+    fn add<I: Input + ?Sized>(
+        &self,
+        text: &I,
+        flags: Flags,
+        threads: &mut Threads,
+        pc: usize,
+        match_start: usize,
+        index: usize,
+        is_next: bool,
+    ) {
+        threads.stack.push(pc);
+        while let Some(pc) = threads.stack.pop() {
+            if threads.added_at[pc] == index {
+                continue;
+            }
+            threads.added_at[pc] = index;
+            match self.instructions[pc] {
+                Instruction::Char(_) | Instruction::Match => {
+                    let list = if is_next {
+                        &mut threads.next
+                    } else {
+                        &mut threads.current
+                    };
+                    list.push((pc, match_start));
+                }
+                Instruction::LineStart => {
+                    if is_line_start(text, flags, index) {
+                        threads.stack.push(pc + 1);
+                    }
+                }
+                Instruction::LineEnd => {
+                    if is_line_end(text, flags, index) {
+                        threads.stack.push(pc + 1);
+                    }
+                }
+                Instruction::WordBoundary { negated } => {
+                    if is_word_boundary(text, index) != negated {
+                        threads.stack.push(pc + 1);
+                    }
+                }
+                Instruction::Split(first, second) => {
+                    threads.stack.push(second);
+                    threads.stack.push(first);
+                }
+                Instruction::Jump(to) => threads.stack.push(to),
+                Instruction::Save(_) => threads.stack.push(pc + 1),
+            }
+        }
+    }
+
+    /// The groups of the match from byte `start` to byte `end` of `text`, as found by `find`.
+    /// This backtracks through the ways the pattern can match in order of priority, but never
+    /// tries an instruction at the same byte twice, as that failed the first time, so it takes
+    /// time linear in the length of the match.
+    fn captures<I: Input + ?Sized>(
+        &self,
+        text: &I,
+        flags: Flags,
+        group_count: usize,
+        start: usize,
+        end: usize,
+    ) -> Option<Captures> {
+        enum Job {
+            Try(usize, usize),
+            Restore(usize, Option<usize>),
+        }
+
+        let width = end - start + 1;
+        let mut tried = vec![0u64; (self.instructions.len() * width).div_ceil(64)];
+        let mut slots = vec![None; (group_count + 1) * 2];
+        let mut jobs = vec![Job::Try(0, start)];
+        while let Some(job) = jobs.pop() {
+            let (mut pc, mut index) = match job {
+                Job::Try(pc, index) => (pc, index),
+                Job::Restore(slot, value) => {
+                    slots[slot] = value;
+                    continue;
+                }
+            };
+            // the match ends at `end`, so going past it fails anyway
+            while index <= end {
+                let bit = pc * width + index - start;
+                if tried[bit / 64] & 1 << (bit % 64) != 0 {
+                    break;
+                }
+                tried[bit / 64] |= 1 << (bit % 64);
+                match self.instructions[pc] {
+                    Instruction::Char(ref node) => match text.char_at(index) {
+                        Some(char) if node.matches_char(char, flags) => {
+                            pc += 1;
+                            index += char.len_utf8();
+                        }
+                        _ => break,
+                    },
+                    Instruction::LineStart if is_line_start(text, flags, index) => pc += 1,
+                    Instruction::LineEnd if is_line_end(text, flags, index) => pc += 1,
+                    Instruction::WordBoundary { negated }
+                        if is_word_boundary(text, index) != negated =>
+                    {
+                        pc += 1
+                    }
+                    Instruction::LineStart
+                    | Instruction::LineEnd
+                    | Instruction::WordBoundary { .. } => break,
+                    Instruction::Split(first, second) => {
+                        jobs.push(Job::Try(second, index));
+                        pc = first;
+                    }
+                    Instruction::Jump(to) => pc = to,
+                    Instruction::Save(slot) => {
+                        jobs.push(Job::Restore(slot, slots[slot]));
+                        slots[slot] = Some(index);
+                        pc += 1;
+                    }
+                    Instruction::Match if index == end => {
+                        slots[0] = Some(start);
+                        slots[1] = Some(end);
+                        return Some(
+                            slots
+                                .chunks(2)
+                                .map(|slots| slots[0].zip(slots[1]))
+                                .collect(),
+                        );
+                    }
+                    Instruction::Match => break,
+                }
+            }
+        }
+        None
+    }
+}
+
+/// The number of instructions `Program::alternatives` writes for `alternatives`, saturating
+/// instead of overflowing.
+fn alternatives_size(alternatives: &[Vec<RegexNode>]) -> usize {
+    alternatives
+        .iter()
+        .map(|sequence| {
+            sequence
+                .iter()
+                .fold(0, |size: usize, node| size.saturating_add(node_size(node)))
+        })
+        .fold(
+            alternatives.len().saturating_sub(1) * 2,
+            usize::saturating_add,
+        )
+}
+
+/// The number of instructions `Program::node` writes for `node`.
+fn node_size(node: &RegexNode) -> usize {
+    match node {
+        RegexNode::Group {
+            alternatives,
+            capture,
+        } => alternatives_size(alternatives).saturating_add(if capture.is_some() { 2 } else { 0 }),
+        RegexNode::Repeat { node, min, max, .. } => {
+            let size = node_size(node);
+            let optional = match max {
+                Some(max) => max
+                    .saturating_sub(*min)
+                    .saturating_mul(size.saturating_add(1)),
+                None => size.saturating_add(2),
+            };
+            size.saturating_mul(*min).saturating_add(optional)
+        }
+        _ => 1,
+    }
+}
+
+/// The threads of a `Program` run, by the instruction they're at and where their match started,
+/// in order of priority.
+struct Threads {
+    current: Vec<(usize, usize)>,
+    next: Vec<(usize, usize)>,
+    /// The last index every instruction was added at, so it's added only once there.
+    added_at: Vec<usize>,
+    stack: Vec<usize>,
+}
+
+impl Threads {
+    /// Room for the threads of a program with `len` instructions, which a search reuses for
+    /// every match.
+    fn new(len: usize) -> Self {
+        Self {
+            current: Vec::new(),
+            next: Vec::new(),
+            added_at: vec![usize::MAX; len],
+            stack: Vec::new(),
+        }
+    }
+}
+
+/// A lazy DFA that runs a `Program` the way the Pike VM would, where a state stands for the
+/// instructions the threads are at, in order of priority. The states and the transitions
+/// between them are worked out the first time a search needs them, so going through text takes
+/// a table lookup per char. It finds where matches end, but not where they start or their
+/// groups, which is left to `Program::find` and `Program::captures`.
+struct Dfa<'a> {
+    program: &'a Program,
+    flags: Flags,
+    states: Vec<DfaState>,
+    state_ids: HashMap<(Vec<usize>, LookBehind), usize>,
+    /// About how many bytes the states take up, to start over when there are too many.
+    size: usize,
+    /// How many times the states were thrown away.
+    clears: usize,
+    /// The states with only `DFA_START`, by `LookBehind`.
+    idle_states: [Option<usize>; 4],
+    /// Which ASCII chars a match can start with, from `Regex::first_nodes`, to skip the others
+    /// while no match is under way.
+    first_chars: Option<Box<[bool; 128]>>,
+    /// Like `Threads::added_at`, but by transition worked out rather than by byte index.
+    added_at: Vec<usize>,
+    generation: usize,
+    stack: Vec<usize>,
+}
+
+/// A `Dfa` state, by the instructions its threads go on at next, where `DFA_START` stands for
+/// the threads that start at the next char, and the kind of char before it.
+struct DfaState {
+    pcs: Vec<usize>,
+    look_behind: LookBehind,
+    /// The transitions on ASCII chars other than `\r`, see `DFA_UNKNOWN`.
+    ascii: Box<[u32; 128]>,
+    /// The transitions on other chars, by the char and whether a `\r` comes before a `\n`.
+    other: HashMap<(char, bool), u32>,
+}
+
+/// The pc in a `DfaState` that starts a thread at every char until there's a match.
+const DFA_START: usize = usize::MAX;
+
+/// A transition is the next state shifted left by 3, with these bits for whether there's a
+/// match before the char, and whether the next state has no threads, or only `DFA_START`.
+const DFA_MATCH: u32 = 1;
+const DFA_DEAD: u32 = 2;
+const DFA_IDLE: u32 = 4;
+/// A transition that hasn't been worked out yet.
+const DFA_UNKNOWN: u32 = u32::MAX;
+
+/// How many bytes of states a `Dfa` keeps before it throws them away and starts over.
+const DFA_CACHE_SIZE: usize = 4 << 20;
+
+/// What the char before a byte index is, as far as `^` and `\b` care.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+enum LookBehind {
+    TextStart,
+    Newline,
+    Word,
+    Other,
+}
+
+impl LookBehind {
+    fn new(char: char) -> Self {
+        if char == '\n' {
+            Self::Newline
+        } else if is_word_char(char) {
+            Self::Word
+        } else {
+            Self::Other
+        }
+    }
+}
+
+impl<'a> Dfa<'a> {
+    fn new(program: &'a Program, flags: Flags, first_nodes: Option<&[RegexNode]>) -> Self {
+        let first_chars = first_nodes.map(|first_nodes| {
+            Box::new(std::array::from_fn(|byte| {
+                let char = byte as u8 as char;
+                first_nodes
+                    .iter()
+                    .any(|node| node.matches_char(char, flags))
+            }))
+        });
+        Self {
+            program,
+            flags,
+            states: Vec::new(),
+            state_ids: HashMap::new(),
+            size: 0,
+            clears: 0,
+            idle_states: [None; 4],
+            first_chars,
+            added_at: vec![0; program.instructions.len()],
+            generation: 0,
+            stack: Vec::new(),
+        }
+    }
+
+    /// Where the leftmost match that starts from byte `start` up to byte `last_start` of `text`
+    /// ends, or only a match that starts at `start` if `anchored`, and a byte index at or before
+    /// its start, after which the Pike VM finds the same match. Its end is that of the first way
+    /// the pattern matches in order of priority, as for `Program::find`.
+    fn find_end<I: Input + ?Sized>(
+        &mut self,
+        text: &I,
+        start: usize,
+        last_start: usize,
+        anchored: bool,
+    ) -> Option<(usize, usize)> {
+        let look_behind = match text.char_before(start) {
+            Some(char) if start > 0 => LookBehind::new(char),
+            _ => LookBehind::TextStart,
+        };
+        let mut state = if anchored {
+            self.state(vec![0], look_behind)
+        } else {
+            self.idle_state(look_behind)
+        };
+        let mut is_idle = !anchored;
+        let mut search_start = start;
+        let mut found = None;
+        let mut index = start;
+        loop {
+            let chunk = text.chunk_at(index);
+            if chunk.is_empty() {
+                break;
+            }
+            let mut offset = 0;
+            while let Some(&byte) = chunk.as_bytes().get(offset) {
+                if is_idle {
+                    if index + offset > last_start {
+                        return None;
+                    }
+                    if let Some(first_chars) = &self.first_chars {
+                        let end = chunk.len().min(last_start + 1 - index);
+                        let skipped = chunk.as_bytes()[offset..end]
+                            .iter()
+                            .position(|&byte| !byte.is_ascii() || first_chars[byte as usize])
+                            .unwrap_or(end - offset);
+                        if skipped > 0 {
+                            // the skipped chars are all ASCII
+                            offset += skipped;
+                            let char = chunk.as_bytes()[offset - 1] as char;
+                            state = self.idle_state(LookBehind::new(char));
+                            continue;
+                        }
+                    }
+                    // no match is under way, so the next one starts here or later
+                    search_start = index + offset;
+                }
+                let (transition, len) = match self.states[state].ascii.get(byte as usize) {
+                    Some(&transition) if transition != DFA_UNKNOWN => (transition, 1),
+                    _ => {
+                        let char = chunk[offset..].chars().next().unwrap();
+                        let is_crlf =
+                            char == '\r' && text.char_at(index + offset + 1) == Some('\n');
+                        (self.transition(state, char, is_crlf), char.len_utf8())
+                    }
+                };
+                if transition & DFA_MATCH != 0 {
+                    found = Some(index + offset);
+                }
+                if transition & DFA_DEAD != 0 {
+                    return found.map(|end| (search_start, end));
+                }
+                is_idle = transition & DFA_IDLE != 0;
+                state = (transition >> 3) as usize;
+                offset += len;
+            }
+            index += chunk.len();
+        }
+        if is_idle && index > last_start {
+            return None;
+        }
+        if self.follow(state, None, false).0 {
+            found = Some(index);
+        }
+        found.map(|end| (search_start, end))
+    }
+
+    fn transition(&mut self, state: usize, char: char, is_crlf: bool) -> u32 {
+        let is_ascii = char.is_ascii() && char != '\r';
+        let transition = if is_ascii {
+            self.states[state].ascii[char as usize]
+        } else {
+            self.states[state]
+                .other
+                .get(&(char, is_crlf))
+                .copied()
+                .unwrap_or(DFA_UNKNOWN)
+        };
+        if transition != DFA_UNKNOWN {
+            return transition;
+        }
+        let (is_match, pcs) = self.follow(state, Some(char), is_crlf);
+        let clears = self.clears;
+        let next = self.state(pcs, LookBehind::new(char));
+        let next_pcs = &self.states[next].pcs;
+        let mut transition = (next as u32) << 3;
+        if is_match {
+            transition |= DFA_MATCH;
+        }
+        if next_pcs.is_empty() {
+            transition |= DFA_DEAD;
+        }
+        if *next_pcs == [DFA_START] {
+            transition |= DFA_IDLE;
+        }
+        // when the states were thrown away to make room, `state` is gone too
+        if self.clears == clears {
+            if is_ascii {
+                self.states[state].ascii[char as usize] = transition;
+            } else {
+                self.size += 16;
+                self.states[state].other.insert((char, is_crlf), transition);
+            }
+        }
+        transition
+    }
+
+    /// Follows the threads of `state` up to the instructions that take a char or match, at a
+    /// byte index before `char`, or at the end of the text if it's `None`. Returns whether one
+    /// of them matches there, and the instructions the threads that take `char` go on at.
+    fn follow(&mut self, state: usize, char: Option<char>, is_crlf: bool) -> (bool, Vec<usize>) {
+        self.generation += 1;
+        let look_behind = self.states[state].look_behind;
+        let mut next = Vec::new();
+        for &pc in &self.states[state].pcs {
+            self.stack.push(if pc == DFA_START { 0 } else { pc });
+            while let Some(pc) = self.stack.pop() {
+                if self.added_at[pc] == self.generation {
+                    continue;
+                }
+                self.added_at[pc] = self.generation;
+                let holds = match self.program.instructions[pc] {
+                    Instruction::Char(ref node) => {
+                        if char.is_some_and(|char| node.matches_char(char, self.flags)) {
+                            next.push(pc + 1);
+                        }
+                        continue;
+                    }
+                    // the threads after this one have a lower priority, so they're dropped
+                    Instruction::Match => {
+                        self.stack.clear();
+                        return (true, next);
+                    }
+                    Instruction::LineStart => {
+                        look_behind == LookBehind::TextStart
+                            || self.flags.multi_line && look_behind == LookBehind::Newline
+                    }
+                    Instruction::LineEnd => {
+                        char.is_none() || self.flags.multi_line && (char == Some('\n') || is_crlf)
+                    }
+                    Instruction::WordBoundary { negated } => {
+                        let is_boundary =
+                            (look_behind == LookBehind::Word) != char.is_some_and(is_word_char);
+                        is_boundary != negated
+                    }
+                    Instruction::Split(first, second) => {
+                        self.stack.push(second);
+                        self.stack.push(first);
+                        continue;
+                    }
+                    Instruction::Jump(to) => {
+                        self.stack.push(to);
+                        continue;
+                    }
+                    Instruction::Save(_) => true,
+                };
+                if holds {
+                    self.stack.push(pc + 1);
+                }
+            }
+            if pc == DFA_START {
+                next.push(DFA_START);
+            }
+        }
+        (false, next)
+    }
+
+    fn idle_state(&mut self, look_behind: LookBehind) -> usize {
+        if let Some(state) = self.idle_states[look_behind as usize] {
+            return state;
+        }
+        let state = self.state(vec![DFA_START], look_behind);
+        self.idle_states[look_behind as usize] = Some(state);
+        state
+    }
+
+    /// The state for threads at `pcs` after a `look_behind` char, which is made if it's new.
+    fn state(&mut self, pcs: Vec<usize>, look_behind: LookBehind) -> usize {
+        let key = (pcs, look_behind);
+        if let Some(&id) = self.state_ids.get(&key) {
+            return id;
+        }
+        let size = std::mem::size_of::<DfaState>() + 128 * 4 + key.0.len() * 16;
+        if self.size + size > DFA_CACHE_SIZE {
+            self.states.clear();
+            self.state_ids.clear();
+            self.size = 0;
+            self.clears += 1;
+            self.idle_states = [None; 4];
+        }
+        self.size += size;
+        let id = self.states.len();
+        self.states.push(DfaState {
+            pcs: key.0.clone(),
+            look_behind,
+            ascii: Box::new([DFA_UNKNOWN; 128]),
+            other: HashMap::new(),
+        });
+        self.state_ids.insert(key, id);
+        id
+    }
+}
+
+fn is_line_start<I: Input + ?Sized>(text: &I, flags: Flags, index: usize) -> bool {
+    index == 0 || flags.multi_line && text.char_before(index) == Some('\n')
+}
+
+fn is_line_end<I: Input + ?Sized>(text: &I, flags: Flags, index: usize) -> bool {
+    index == text.len()
+        || flags.multi_line
+            && match text.char_at(index) {
+                Some('\n') => true,
+                Some('\r') => text.char_at(index + 1) == Some('\n'),
+                _ => false,
+            }
+}
+
+fn is_word_boundary<I: Input + ?Sized>(text: &I, index: usize) -> bool {
+    text.char_before(index).is_some_and(is_word_char)
+        != text.char_at(index).is_some_and(is_word_char)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn find_all(pattern: &str, text: &str) -> Vec<(usize, usize)> {
+        Regex::new(pattern).unwrap().find_iter(text).collect()
+    }
+
+    /// The matches as the Pike VM alone finds them, to check the `Dfa` against.
+    fn find_all_pike(pattern: &str, text: &str) -> Vec<(usize, usize)> {
+        let regex = Regex::new(pattern).unwrap();
+        let mut threads = Threads::new(regex.program.instructions.len());
+        let mut matches = Vec::new();
+        let mut start = 0;
+        while let Some((match_start, end)) = regex.program.find(
+            text,
+            regex.flags,
+            regex.first_nodes.as_deref(),
+            start,
+            false,
+            &mut threads,
+        ) {
+            matches.push((match_start, end));
+            start = if end > match_start {
+                end
+            } else {
+                match text.char_at(end) {
+                    Some(char) => end + char.len_utf8(),
+                    None => break,
+                }
+            };
+        }
+        matches
+    }
+
+    #[test]
+    fn matches_in_order_of_priority() {
+        assert_eq!(find_all("a|ab", "ab"), [(0, 1)]);
+        assert_eq!(find_all("ab|a", "ab"), [(0, 2)]);
+        assert_eq!(find_all("a+", "aaa"), [(0, 3)]);
+        assert_eq!(find_all("a+?", "aaa"), [(0, 1), (1, 2), (2, 3)]);
+        assert_eq!(find_all("(?U)a+", "aaa"), [(0, 1), (1, 2), (2, 3)]);
+        assert_eq!(find_all("x*", "axx"), [(0, 0), (1, 3), (3, 3)]);
+        assert_eq!(find_all("a{2,3}", "aaaaaaa"), [(0, 3), (3, 6)]);
+    }
+
+    #[test]
+    fn assertions() {
+        assert_eq!(find_all("\\bfoo\\b", "foo food foo"), [(0, 3), (9, 12)]);
+        assert_eq!(find_all("o\\B", "foo"), [(1, 2)]);
+        assert_eq!(find_all("(?m)^b$", "a\nb\nc"), [(2, 3)]);
+        assert_eq!(find_all("(?m)b$", "ab\r\nb\rc"), [(1, 2)]);
+        assert_eq!(find_all("^a|a$", "aba"), [(0, 1), (2, 3)]);
+        assert_eq!(find_all("(?i)AMET", "Amet amet"), [(0, 4), (5, 9)]);
+    }
+
+    #[test]
+    fn matches_across_lines() {
+        let lines = ["foo bar".to_string(), "baz qux".to_string()];
+        let lines = Lines::new(&lines);
+        let find_all = |pattern| {
+            Regex::new(pattern)
+                .unwrap()
+                .find_iter(&lines)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(find_all("bar\\nbaz"), [(4, 11)]);
+        assert_eq!(find_all("(?m)\\w+$"), [(4, 7), (12, 15)]);
+        assert_eq!(find_all("(?s)r.b"), [(6, 9)]);
+        assert_eq!(find_all("r.b"), []);
+    }
+
+    #[test]
+    fn dfa_finds_what_the_pike_vm_finds() {
+        let text = "Lorem ipsum dolor sit amet,\nconsectetur adipiscing elit,\r\nsed do é eiusmod.";
+        for pattern in [
+            "\\w+t\\b",
+            "amet\\b",
+            "[a-z]+ing",
+            "(?m)^\\w+",
+            "(?m)\\W$",
+            "s\\w*?t",
+            "(a|ab)(c|bcd)?",
+            "\\s*",
+            "(?i)[^aeiou ]{2,}",
+            ".é",
+            "\\B\\w\\b",
+        ] {
+            assert_eq!(
+                find_all(pattern, text),
+                find_all_pike(pattern, text),
+                "{}",
+                pattern
+            );
+        }
+    }
+
+    #[test]
+    fn captures() {
+        let regex = Regex::new("(?P<user>\\w+)@(?<host>\\w+)(\\.(\\w+))?").unwrap();
+        let text = "mail bob@example.com now";
+        let captures = regex.captures(text).unwrap();
+        assert_eq!(
+            captures,
+            [
+                Some((5, 20)),
+                Some((5, 8)),
+                Some((9, 16)),
+                Some((16, 20)),
+                Some((17, 20))
+            ]
+        );
+        assert_eq!(
+            regex.expand(text, &captures, "${host}:$user $4 $$"),
+            "example:bob com $"
+        );
+        assert_eq!(regex.captures_at(text, 6).unwrap()[1], Some((6, 8)));
+    }
+
+    #[test]
+    fn large_patterns_are_errors() {
+        assert!(Regex::new("x{1000}").is_ok());
+        assert!(Regex::new("x{20000000}").is_err());
+        assert!(Regex::new("x{2,20000000}").is_err());
+        assert!(Regex::new("(a{1000}){1000}{100}").is_err());
+    }
+}
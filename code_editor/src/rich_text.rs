@@ -0,0 +1,83 @@
+use {
+    makepad_widgets::*,
+    std::fmt::Write,
+};
+
+/// Serializes colored text runs to an HTML fragment that keeps the highlighting when
+/// pasted into mail clients, word processors and the like.
+pub fn to_html(runs: &[(String, Vec4)], background: Vec4) -> String {
+    let mut html = String::new();
+    write!(
+        &mut html,
+        "<pre style=\"background-color:{};font-family:Menlo,Consolas,monospace\">",
+        css_color(background)
+    )
+    .unwrap();
+    for (string, color) in runs {
+        write!(&mut html, "<span style=\"color:{}\">", css_color(*color)).unwrap();
+        for char in string.chars() {
+            match char {
+                '<' => html.push_str("&lt;"),
+                '>' => html.push_str("&gt;"),
+                '&' => html.push_str("&amp;"),
+                '"' => html.push_str("&quot;"),
+                char => html.push(char),
+            }
+        }
+        html.push_str("</span>");
+    }
+    html.push_str("</pre>");
+    html
+}
+
+/// Serializes colored text runs to an RTF document with a color table built from the runs.
+pub fn to_rtf(runs: &[(String, Vec4)]) -> String {
+    let mut colors: Vec<[u8; 3]> = Vec::new();
+    let mut body = String::new();
+    for (string, color) in runs {
+        let rgb = rgb_bytes(*color);
+        let index = match colors.iter().position(|color| *color == rgb) {
+            Some(index) => index,
+            None => {
+                colors.push(rgb);
+                colors.len() - 1
+            }
+        };
+        // color 0 is the implicit 'auto' entry at the start of the table
+        write!(&mut body, "\\cf{} ", index + 1).unwrap();
+        for char in string.chars() {
+            match char {
+                '\\' => body.push_str("\\\\"),
+                '{' => body.push_str("\\{"),
+                '}' => body.push_str("\\}"),
+                '\n' => body.push_str("\\line "),
+                '\t' => body.push_str("\\tab "),
+                char if char.is_ascii() => body.push(char),
+                char => {
+                    let mut buffer = [0; 2];
+                    for unit in char.encode_utf16(&mut buffer) {
+                        write!(&mut body, "\\u{}?", *unit as i16).unwrap();
+                    }
+                }
+            }
+        }
+    }
+    let mut rtf = String::from("{\\rtf1\\ansi\\deff0{\\fonttbl{\\f0\\fmodern Menlo;}}{\\colortbl;");
+    for [r, g, b] in colors {
+        write!(&mut rtf, "\\red{}\\green{}\\blue{};", r, g, b).unwrap();
+    }
+    rtf.push_str("}\\f0 ");
+    rtf.push_str(&body);
+    rtf.push('}');
+    rtf
+}
+
+fn rgb_bytes(color: Vec4) -> [u8; 3] {
+    let byte = |c: f32| (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+    [byte(color.x), byte(color.y), byte(color.z)]
+}
+
+fn css_color(color: Vec4) -> String {
+    let [r, g, b] = rgb_bytes(color);
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
@@ -0,0 +1,117 @@
+use makepad_widgets::LiveId;
+
+/// Where a view is scrolled to.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ScrollPosition {
+    /// The line at the top of the view, with the fraction of it that is scrolled out of view.
+    pub line: f64,
+    /// How far the view is scrolled, from 0 at the top to 1 at the bottom.
+    pub ratio: f64,
+}
+
+/// Where a linked view should scroll to.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ScrollTarget {
+    Ratio(f64),
+    Line(f64),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ScrollLinkMode {
+    /// Both views are scrolled the same fraction of their height.
+    Proportional,
+    /// Lines in the first view are mapped onto lines in the second by these pairs of anchor
+    /// lines, sorted by line, and interpolated in between. An empty list maps each line onto
+    /// itself.
+    LineMapping(Vec<(usize, usize)>),
+}
+
+/// Two views that scroll together.
+#[derive(Clone, Debug)]
+pub struct ScrollLink {
+    pub a: LiveId,
+    pub b: LiveId,
+    pub mode: ScrollLinkMode,
+}
+
+impl ScrollLink {
+    pub fn contains(&self, view_id: LiveId) -> bool {
+        self.a == view_id || self.b == view_id
+    }
+
+    /// Where the other view should scroll to when `view_id` scrolled to `position`.
+    pub fn follow(&self, view_id: LiveId, position: ScrollPosition) -> Option<(LiveId, ScrollTarget)> {
+        let (other_id, forward) = if view_id == self.a {
+            (self.b, true)
+        } else if view_id == self.b {
+            (self.a, false)
+        } else {
+            return None;
+        };
+        let target = match &self.mode {
+            ScrollLinkMode::Proportional => ScrollTarget::Ratio(position.ratio),
+            ScrollLinkMode::LineMapping(anchors) => {
+                ScrollTarget::Line(map_line(anchors, position.line, forward))
+            }
+        };
+        Some((other_id, target))
+    }
+}
+
+fn map_line(anchors: &[(usize, usize)], line: f64, forward: bool) -> f64 {
+    let anchors: Vec<(f64, f64)> = anchors
+        .iter()
+        .map(|&(a, b)| {
+            if forward {
+                (a as f64, b as f64)
+            } else {
+                (b as f64, a as f64)
+            }
+        })
+        .collect();
+    let index = anchors.partition_point(|&(from, _)| from <= line);
+    match (index.checked_sub(1).map(|index| anchors[index]), anchors.get(index)) {
+        (Some((from_start, to_start)), Some(&(from_end, to_end))) => {
+            let t = (line - from_start) / (from_end - from_start);
+            to_start + t * (to_end - to_start)
+        }
+        // past the last anchor, or before the first, lines map one to one
+        (Some((from, to)), None) | (None, Some(&(from, to))) => to + line - from,
+        (None, None) => line,
+    }
+}
+
+/// The scroll links between views, which keep each other in sync.
+#[derive(Clone, Debug, Default)]
+pub struct ScrollLinks {
+    links: Vec<ScrollLink>,
+}
+
+impl ScrollLinks {
+    /// Links two views, replacing any links they had before.
+    pub fn link(&mut self, a: LiveId, b: LiveId, mode: ScrollLinkMode) {
+        self.unlink(a);
+        self.unlink(b);
+        self.links.push(ScrollLink { a, b, mode });
+    }
+
+    pub fn unlink(&mut self, view_id: LiveId) {
+        self.links.retain(|link| !link.contains(view_id));
+    }
+
+    pub fn link_of(&self, view_id: LiveId) -> Option<&ScrollLink> {
+        self.links.iter().find(|link| link.contains(view_id))
+    }
+
+    pub fn is_linked(&self, view_id: LiveId) -> bool {
+        self.link_of(view_id).is_some()
+    }
+
+    /// Where the views linked to `view_id` should scroll to, when it was scrolled to `position`.
+    pub fn follow(&self, view_id: LiveId, position: ScrollPosition) -> Vec<(LiveId, ScrollTarget)> {
+        self.links
+            .iter()
+            .filter_map(|link| link.follow(view_id, position))
+            .collect()
+    }
+}
@@ -0,0 +1,433 @@
+use crate::{
+    code_action::TextEdit,
+    decoration::{Decoration, DecorationType},
+    regex::{Lines, Regex},
+    text::{Change, Edit, Position, Text},
+};
+
+/// What to look for in a document.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct SearchQuery {
+    pub text: String,
+    /// Whether `text` is a regex rather than text to find as it is.
+    pub is_regex: bool,
+    pub match_case: bool,
+    /// Whether matches have to start and end at a word boundary.
+    pub whole_word: bool,
+}
+
+impl SearchQuery {
+    pub fn to_regex(&self) -> Result<Regex, String> {
+        let (mut flags, body) = if self.is_regex {
+            let (flags, body) = split_flags(&self.text);
+            (flags.to_string(), body.to_string())
+        } else {
+            (String::new(), escape(&self.text))
+        };
+        if !self.match_case && !flags.contains('i') {
+            flags.push('i');
+        }
+        let mut pattern = String::new();
+        if !flags.is_empty() {
+            pattern.push_str(&format!("(?{})", flags));
+        }
+        if self.whole_word {
+            pattern.push_str(&format!("\\b(?:{})\\b", body));
+        } else {
+            pattern.push_str(&body);
+        }
+        Regex::new(&pattern)
+    }
+}
+
+/// The matches of a search in a document, and which of them is the current one.
+#[derive(Clone, Debug, Default)]
+pub struct SearchState {
+    query: SearchQuery,
+    regex: Option<Regex>,
+    matches: Vec<(Position, Position)>,
+    current: Option<usize>,
+}
+
+impl SearchState {
+    pub fn query(&self) -> &SearchQuery {
+        &self.query
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.regex.is_some()
+    }
+
+    /// The start and end of each match, in the order they are in the document.
+    pub fn matches(&self) -> &[(Position, Position)] {
+        &self.matches
+    }
+
+    pub fn current_index(&self) -> Option<usize> {
+        self.current
+    }
+
+    pub fn current(&self) -> Option<(Position, Position)> {
+        self.matches.get(self.current?).copied()
+    }
+
+    /// Searches `text` for `query`, or stops searching if the query text is empty. The first
+    /// match at or after `position` becomes the current one.
+    pub fn set_query(
+        &mut self,
+        query: SearchQuery,
+        text: &Text,
+        position: Position,
+    ) -> Result<(), String> {
+        let regex = if query.text.is_empty() {
+            None
+        } else {
+            Some(query.to_regex()?)
+        };
+        self.query = query;
+        self.regex = regex;
+        self.update(text);
+        self.seek(position);
+        Ok(())
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Searches `text` again after it was edited. The current match keeps its index, so after
+    /// replacing it the one after it becomes current.
+    pub fn update(&mut self, text: &Text) {
+        self.matches.clear();
+        if let Some(regex) = &self.regex {
+            let lines = Lines::new(text.as_lines());
+            self.matches.extend(
+                regex
+                    .find_iter(&lines)
+                    .filter(|(start, end)| start != end)
+                    .map(|(start, end)| (lines.position(start), lines.position(end))),
+            );
+        }
+        self.current = match self.matches.len() {
+            0 => None,
+            len => self.current.map(|current| current.min(len - 1)),
+        };
+    }
+
+    /// Moves the matches along with `edits`, which were made to get `text`, and searches again
+    /// only the lines the edits changed. A pattern that can match a newline has matches that
+    /// span lines, so then the whole text is searched again. The current match keeps its index,
+    /// as for `update`.
+    pub fn apply_edits(&mut self, text: &Text, edits: &[Edit]) {
+        let Some(regex) = &self.regex else {
+            return;
+        };
+        if regex.matches_newline() {
+            self.update(text);
+            return;
+        }
+        // the first and last line of each edit, moved along with the edits after it
+        let mut changed = Vec::new();
+        for edit in edits {
+            for (start, end) in self.matches.iter_mut().chain(&mut changed) {
+                *start = start.apply_edit(edit);
+                *end = end.apply_edit(edit);
+            }
+            changed.push(match edit.change {
+                Change::Insert(position, ref text) => (position, position + text.length()),
+                Change::Delete(start, _) => (start, start),
+            });
+        }
+        let mut line_ranges: Vec<(usize, usize)> = changed
+            .iter()
+            .map(|(start, end)| (start.line_index, end.line_index))
+            .collect();
+        line_ranges.sort();
+        line_ranges.dedup_by(|(start, end), (_, previous_end)| {
+            let is_merged = *start <= *previous_end + 1;
+            if is_merged {
+                *previous_end = (*previous_end).max(*end);
+            }
+            is_merged
+        });
+        self.matches.retain(|(start, _)| {
+            !line_ranges
+                .iter()
+                .any(|(first, last)| (*first..=*last).contains(&start.line_index))
+        });
+        let lines = Lines::new(text.as_lines());
+        for (first, last) in line_ranges {
+            let start = lines.offset(Position {
+                line_index: first,
+                byte_index: 0,
+            });
+            let last_start = lines.offset(Position {
+                line_index: last,
+                byte_index: text.as_lines()[last].len(),
+            });
+            let index = self.partition_point(lines.position(start));
+            let found: Vec<_> = regex
+                .find_iter_in(&lines, start, last_start)
+                .filter(|(start, end)| start != end)
+                .map(|(start, end)| (lines.position(start), lines.position(end)))
+                .collect();
+            self.matches.splice(index..index, found);
+        }
+        self.current = match self.matches.len() {
+            0 => None,
+            len => self.current.map(|current| current.min(len - 1)),
+        };
+    }
+
+    /// Makes the first match at or after `position` current, wrapping around at the end.
+    pub fn seek(&mut self, position: Position) -> Option<(Position, Position)> {
+        self.current = self.index_at_or_after(position);
+        self.current()
+    }
+
+    /// Makes the match after the current one current if `selection` is the current one, or
+    /// else the first match at or after the start of `selection`, wrapping around at the end.
+    pub fn next(&mut self, selection: (Position, Position)) -> Option<(Position, Position)> {
+        match self.current {
+            Some(current) if self.current() == Some(selection) => {
+                self.current = Some((current + 1) % self.matches.len());
+                self.current()
+            }
+            _ => self.seek(selection.0),
+        }
+    }
+
+    /// Makes the match before the current one current if `selection` is the current one, or
+    /// else the last match before the start of `selection`, wrapping around at the start.
+    pub fn previous(&mut self, selection: (Position, Position)) -> Option<(Position, Position)> {
+        let len = self.matches.len();
+        if len == 0 {
+            return None;
+        }
+        self.current = Some(match self.current {
+            Some(current) if self.current() == Some(selection) => (current + len - 1) % len,
+            _ => (self.partition_point(selection.0) + len - 1) % len,
+        });
+        self.current()
+    }
+
+    /// The edit that replaces the match at `index` with `replacement`, where a regex query
+    /// fills in groups as in `Regex::expand`.
+    pub fn replace_edit(&self, text: &Text, index: usize, replacement: &str) -> Option<TextEdit> {
+        let lines = Lines::new(text.as_lines());
+        self.edit(&lines, *self.matches.get(index)?, replacement)
+    }
+
+    /// The edits that replace every match with `replacement`.
+    pub fn replace_all_edits(&self, text: &Text, replacement: &str) -> Vec<TextEdit> {
+        let lines = Lines::new(text.as_lines());
+        self.matches
+            .iter()
+            .filter_map(|range| self.edit(&lines, *range, replacement))
+            .collect()
+    }
+
+    /// The matches as decorations to draw, with the current one standing out.
+    pub fn decorations(&self) -> Vec<Decoration> {
+        self.matches
+            .iter()
+            .enumerate()
+            .map(|(index, (start, end))| {
+                let ty = if Some(index) == self.current {
+                    DecorationType::CurrentSearchMatch
+                } else {
+                    DecorationType::SearchMatch
+                };
+                Decoration::new(index, *start, *end, ty)
+            })
+            .collect()
+    }
+
+    fn edit(
+        &self,
+        lines: &Lines,
+        (start, end): (Position, Position),
+        replacement: &str,
+    ) -> Option<TextEdit> {
+        let regex = self.regex.as_ref()?;
+        let text = if self.query.is_regex {
+            let captures = regex.captures_at(lines, lines.offset(start))?;
+            regex.expand(lines, &captures, replacement)
+        } else {
+            replacement.to_string()
+        };
+        Some(TextEdit { start, end, text })
+    }
+
+    fn partition_point(&self, position: Position) -> usize {
+        self.matches.partition_point(|(start, _)| *start < position)
+    }
+
+    fn index_at_or_after(&self, position: Position) -> Option<usize> {
+        if self.matches.is_empty() {
+            return None;
+        }
+        Some(self.partition_point(position) % self.matches.len())
+    }
+}
+
+/// A match of a `ProjectSearch`, in the document with the key `document`.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct SearchHit<K> {
+    pub document: K,
+    pub start: Position,
+    pub end: Position,
+    /// The line the match starts in, to show it in a list of results.
+    pub line_text: String,
+}
+
+/// A search across many documents, like the open documents of a workspace and the files on disk
+/// that aren't open. Hits come out of iterators as they are found, so a caller can show the
+/// first ones, or stop, before every document is searched.
+#[derive(Clone, Debug)]
+pub struct ProjectSearch {
+    query: SearchQuery,
+    regex: Regex,
+}
+
+impl ProjectSearch {
+    pub fn new(query: SearchQuery) -> Result<Self, String> {
+        let regex = query.to_regex()?;
+        Ok(Self { query, regex })
+    }
+
+    pub fn query(&self) -> &SearchQuery {
+        &self.query
+    }
+
+    /// The hits in `text`, which belong to `document`.
+    pub fn search_text<'a, K: Clone + 'a>(
+        &'a self,
+        document: K,
+        text: &'a Text,
+    ) -> impl Iterator<Item = SearchHit<K>> + 'a {
+        let lines = Lines::new(text.as_lines());
+        let ranges: Vec<(usize, usize)> = self
+            .regex
+            .find_iter(&lines)
+            .filter(|(start, end)| start != end)
+            .collect();
+        ranges.into_iter().map(move |(start, end)| {
+            let start = lines.position(start);
+            SearchHit {
+                document: document.clone(),
+                start,
+                end: lines.position(end),
+                line_text: text.as_lines()[start.line_index].clone(),
+            }
+        })
+    }
+
+    /// The hits in each of `documents`, one document after the other.
+    pub fn search<'a, K: Clone + 'a>(
+        &'a self,
+        documents: impl IntoIterator<Item = (K, &'a Text)> + 'a,
+    ) -> impl Iterator<Item = SearchHit<K>> + 'a {
+        documents
+            .into_iter()
+            .flat_map(move |(document, text)| self.search_text(document, text))
+    }
+
+    /// Like `search`, for documents that aren't loaded, like files on disk. `load` is called for
+    /// each document when it is its turn, and documents it returns `None` for are skipped.
+    pub fn search_with<'a, K: Clone + 'a>(
+        &'a self,
+        documents: impl IntoIterator<Item = K> + 'a,
+        mut load: impl FnMut(&K) -> Option<Text> + 'a,
+    ) -> impl Iterator<Item = SearchHit<K>> + 'a {
+        documents.into_iter().flat_map(move |document| {
+            let hits: Vec<_> = match load(&document) {
+                Some(text) => self.search_text(document, &text).collect(),
+                None => Vec::new(),
+            };
+            hits
+        })
+    }
+}
+
+/// Splits a leading `(?flags)` group off `pattern`, so it can stay in front when the rest is
+/// wrapped in another group.
+fn split_flags(pattern: &str) -> (&str, &str) {
+    pattern
+        .strip_prefix("(?")
+        .and_then(|group| group.split_once(')'))
+        .filter(|(letters, _)| {
+            !letters.is_empty() && letters.chars().all(|char| char.is_ascii_alphabetic())
+        })
+        .unwrap_or(("", pattern))
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::new();
+    for char in text.chars() {
+        if "\\.+*?()|[]{}^$".contains(char) {
+            escaped.push('\\');
+        }
+        escaped.push(char);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::text::{Drift, Length},
+    };
+
+    fn search(pattern: &str, text: &Text) -> SearchState {
+        let mut state = SearchState::default();
+        let query = SearchQuery {
+            text: pattern.to_string(),
+            is_regex: true,
+            match_case: true,
+            whole_word: false,
+        };
+        state.set_query(query, text, Position::zero()).unwrap();
+        state
+    }
+
+    fn position(line_index: usize, byte_index: usize) -> Position {
+        Position {
+            line_index,
+            byte_index,
+        }
+    }
+
+    fn length(line_count: usize, byte_count: usize) -> Length {
+        Length {
+            line_count,
+            byte_count,
+        }
+    }
+
+    #[test]
+    fn edits_find_what_searching_again_finds() {
+        let edits = [
+            Change::Insert(position(1, 0), "fo\no ".into()),
+            Change::Delete(position(0, 1), length(0, 3)),
+            Change::Insert(position(4, 3), "o".into()),
+            Change::Delete(position(2, 3), length(1, 0)),
+        ]
+        .map(|change| Edit {
+            change,
+            drift: Drift::Before,
+        });
+        for pattern in ["fo+", "^\\w+", "o$", "o\\no"] {
+            let mut text = Text::from("foo bar\nbaz foo\nqux\nfoo\nfo fo");
+            let mut state = search(pattern, &text);
+            for edit in &edits {
+                text.apply_change(edit.change.clone());
+            }
+            state.apply_edits(&text, &edits);
+            let expected = search(pattern, &text);
+            assert!(!expected.matches().is_empty(), "{}", pattern);
+            assert_eq!(state.matches(), expected.matches(), "{}", pattern);
+        }
+    }
+}
@@ -116,6 +116,62 @@ impl From<Cursor> for Selection {
     }
 }
 
+/// A rectangle of text, from the column of the anchor to the column of the cursor on every line
+/// from the line of the anchor to the line of the cursor. It is edited as a selection per line.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct ColumnSelection {
+    pub anchor: ColumnPosition,
+    pub cursor: ColumnPosition,
+}
+
+impl ColumnSelection {
+    pub fn new(anchor: ColumnPosition, cursor: ColumnPosition) -> Self {
+        Self { anchor, cursor }
+    }
+
+    /// The selection on each line of the rectangle, from top to bottom. Lines that end before
+    /// a column of the rectangle are selected up to their end.
+    pub fn to_selections(self, lines: &[String]) -> Vec<Selection> {
+        let start_line_index = self.anchor.line_index.min(self.cursor.line_index);
+        let end_line_index = self
+            .anchor
+            .line_index
+            .max(self.cursor.line_index)
+            .min(lines.len().saturating_sub(1));
+        (start_line_index..=end_line_index)
+            .map(|line_index| {
+                let line = &lines[line_index];
+                Selection {
+                    anchor: Position {
+                        line_index,
+                        byte_index: line.byte_index_at_column(self.anchor.column_index),
+                    },
+                    cursor: Cursor::from(Position {
+                        line_index,
+                        byte_index: line.byte_index_at_column(self.cursor.column_index),
+                    }),
+                }
+            })
+            .collect()
+    }
+}
+
+/// A place in the grid of the text, which can be past the end of its line.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct ColumnPosition {
+    pub line_index: usize,
+    pub column_index: usize,
+}
+
+impl ColumnPosition {
+    pub fn from_position(position: Position, lines: &[String]) -> Self {
+        Self {
+            line_index: position.line_index,
+            column_index: lines[position.line_index][..position.byte_index].column_count(),
+        }
+    }
+}
+
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub struct SelectionSet {
     selections: Vec<Selection>,
@@ -176,6 +232,21 @@ impl SelectionSet {
         self.selections.push(selection);
     }
 
+    /// Replaces the selections with `selections`, which have to be sorted by where they start.
+    /// Returns where the selection at `retained_index` ended up after merging overlaps.
+    pub fn set_selections(
+        &mut self,
+        selections: Vec<Selection>,
+        retained_index: Option<usize>,
+    ) -> Option<usize> {
+        if selections.is_empty() {
+            *self = Self::default();
+            return Some(0);
+        }
+        self.selections = selections;
+        self.normalize_all_selections(retained_index)
+    }
+
     fn normalize_selection(&mut self, index: usize) -> usize {
         let mut index = index;
         while index > 0 {
@@ -306,7 +377,7 @@ impl Cursor {
         if !self.is_at_first_row_of_line(layout) {
             return self.move_to_prev_row_of_line(layout);
         }
-        if !self.is_at_first_line() {
+        if prev_visible_line_index(layout, self.position.line_index).is_some() {
             return self.move_to_last_row_of_prev_line(layout);
         }
         self.move_to_start_of_line()
@@ -316,12 +387,28 @@ impl Cursor {
         if !self.is_at_last_row_of_line(layout) {
             return self.move_to_next_row_of_line(layout);
         }
-        if !self.is_at_last_line(layout.as_text().as_lines().len()) {
+        if next_visible_line_index(layout, self.position.line_index).is_some() {
             return self.move_to_first_row_of_next_line(layout);
         }
         self.move_to_end_of_line(layout.as_text().as_lines())
     }
 
+    /// Like `move_up`, but to the line above even if the line is wrapped over several rows.
+    pub fn move_up_line(self, lines: &[String]) -> Self {
+        if !self.is_at_first_line() {
+            return self.move_to_column_of_line(lines, self.position.line_index - 1);
+        }
+        self.move_to_start_of_line()
+    }
+
+    /// Like `move_down`, but to the line below even if the line is wrapped over several rows.
+    pub fn move_down_line(self, lines: &[String]) -> Self {
+        if !self.is_at_last_line(lines.len()) {
+            return self.move_to_column_of_line(lines, self.position.line_index + 1);
+        }
+        self.move_to_end_of_line(lines)
+    }
+
     pub fn home(self, lines: &[String]) -> Self {
         if !self.is_at_start_of_line() {
             let indent_len = lines[self.position.line_index].indent().unwrap_or("").len();
@@ -455,6 +542,22 @@ impl Cursor {
         }
     }
 
+    /// Moves to the same column of another line, counting columns from the start of the line
+    /// rather than from the start of the row.
+    pub fn move_to_column_of_line(self, lines: &[String], line_index: usize) -> Self {
+        let column_index = self.preferred_column_index.unwrap_or_else(|| {
+            lines[self.position.line_index][..self.position.byte_index].column_count()
+        });
+        Self {
+            position: Position {
+                line_index,
+                byte_index: lines[line_index].byte_index_at_column(column_index),
+            },
+            affinity: Affinity::Before,
+            preferred_column_index: Some(column_index),
+        }
+    }
+
     pub fn move_to_prev_row_of_line(self, layout: &Layout<'_>) -> Self {
         let line = layout.line(self.position.line_index);
         let (row_index, mut column_index) =
@@ -498,12 +601,14 @@ impl Cursor {
         if let Some(preferred_column_index) = self.preferred_column_index {
             column_index = preferred_column_index;
         }
-        let prev_line = layout.line(self.position.line_index - 1);
+        let prev_line_index = prev_visible_line_index(layout, self.position.line_index)
+            .unwrap_or(self.position.line_index - 1);
+        let prev_line = layout.line(prev_line_index);
         let (byte_index, affinity) =
             prev_line.grid_to_logical_position(prev_line.row_count() - 1, column_index);
         Self {
             position: Position {
-                line_index: self.position.line_index - 1,
+                line_index: prev_line_index,
                 byte_index,
             },
             affinity,
@@ -518,11 +623,13 @@ impl Cursor {
         if let Some(preferred_column_index) = self.preferred_column_index {
             column_index = preferred_column_index;
         }
-        let next_line = layout.line(self.position.line_index + 1);
+        let next_line_index = next_visible_line_index(layout, self.position.line_index)
+            .unwrap_or(self.position.line_index + 1);
+        let next_line = layout.line(next_line_index);
         let (byte_index, affinity) = next_line.grid_to_logical_position(0, column_index);
         Self {
             position: Position {
-                line_index: self.position.line_index + 1,
+                line_index: next_line_index,
                 byte_index,
             },
             affinity,
@@ -539,6 +646,19 @@ impl Cursor {
     }
 }
 
+/// The nearest line above `line_index` that isn't folded away.
+fn prev_visible_line_index(layout: &Layout<'_>, line_index: usize) -> Option<usize> {
+    (0..line_index)
+        .rev()
+        .find(|&index| layout.line(index).scale() > 0.0)
+}
+
+/// The nearest line below `line_index` that isn't folded away.
+fn next_visible_line_index(layout: &Layout<'_>, line_index: usize) -> Option<usize> {
+    (line_index + 1..layout.as_text().as_lines().len())
+        .find(|&index| layout.line(index).scale() > 0.0)
+}
+
 impl From<Position> for Cursor {
     fn from(position: Position) -> Self {
         Self {
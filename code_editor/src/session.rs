@@ -1,21 +1,28 @@
 use {
     crate::{
         char::CharExt,
+        code_action::TextEdit,
+        completion,
         document::Document,
+        fold::{self, FoldRange},
         history::{EditKind,NewGroup},
         layout::{BlockElement, Layout, WrappedElement},
-        selection::{Affinity, Cursor, SelectionSet},
+        paste::{PasteContext, PasteFilter},
+        search::{SearchQuery, SearchState},
+        selection::{Affinity, ColumnPosition, ColumnSelection, Cursor, SelectionSet},
         str::StrExt,
+        structure::{Structure, Swap},
         text::{Change, Drift, Edit, Length, Position, Text},
+        token::TokenKind,
         wrap,
         wrap::WrapData,
         Selection, Settings,
     },
     std::{
         cell::{Cell, Ref, RefCell},
-        collections::HashSet,
+        collections::{BTreeMap, HashSet},
         fmt::Write,
-        iter, mem,
+        mem,
         rc::Rc,
         sync::{atomic, atomic::AtomicUsize, mpsc, mpsc::Receiver},
     },
@@ -30,6 +37,7 @@ pub struct Session {
     selection_state: RefCell<SelectionState>,
     wrap_column: Cell<Option<usize>>,
     fold_state: RefCell<FoldState>,
+    search_state: RefCell<SearchState>,
     edit_receiver: Receiver<(Option<SelectionSet>, Vec<Edit>)>,
 }
 
@@ -56,13 +64,16 @@ impl Session {
                 last_added_selection_index: Some(0),
                 injected_char_stack: Vec::new(),
                 highlighted_delimiter_positions: HashSet::new(),
+                column_selection: None,
             }),
             wrap_column: Cell::new(None),
             fold_state: RefCell::new(FoldState {
                 folding_lines: HashSet::new(),
                 folded_lines: HashSet::new(),
                 unfolding_lines: HashSet::new(),
+                collapsed: BTreeMap::new(),
             }),
+            search_state: RefCell::new(SearchState::default()),
             edit_receiver,
         };
         for line in 0..line_count {
@@ -81,6 +92,11 @@ impl Session {
         &self.settings
     }
 
+    /// Replaces the settings, like when the language of the document is known.
+    pub fn set_settings(&mut self, settings: Rc<Settings>) {
+        self.settings = settings;
+    }
+
     pub fn document(&self) -> &Document {
         &self.document
     }
@@ -113,6 +129,10 @@ impl Session {
         })
     }
 
+    pub fn search_state(&self) -> Ref<'_, SearchState> {
+        self.search_state.borrow()
+    }
+
     pub fn set_wrap_column(&self, wrap_column: Option<usize>) {
         if self.wrap_column.get() == wrap_column {
             return;
@@ -135,6 +155,7 @@ impl Session {
             drop(layout);
             if indent_level >= self.settings.fold_level
                 && !fold_state.folded_lines.contains(&line_index)
+                && !fold_state.is_line_hidden(line_index)
             {
                 self.layout.borrow_mut().fold_column[line_index] =
                     self.settings.fold_level * self.settings.tab_column_count;
@@ -191,6 +212,92 @@ impl Session {
         true
     }
 
+    /// Folds the innermost foldable range at `position`, hiding every line of it but the first.
+    /// Cursors on the hidden lines move to the end of the first line. Returns whether there
+    /// was a range to fold.
+    pub fn fold_at(&self, position: Position) -> bool {
+        let Some(range) = fold::find_fold_range(
+            self.document.as_text().as_lines(),
+            &self.structure(),
+            position.line_index,
+        ) else {
+            return false;
+        };
+        let mut fold_state = self.fold_state.borrow_mut();
+        if fold_state.collapsed.get(&range.start_line_index) == Some(&range.end_line_index) {
+            return false;
+        }
+        fold_state
+            .collapsed
+            .insert(range.start_line_index, range.end_line_index);
+        drop(fold_state);
+        self.update_collapsed_scale(range);
+        self.update_y();
+        let end_of_start_line = Position {
+            line_index: range.start_line_index,
+            byte_index: self.document.as_text().as_lines()[range.start_line_index].len(),
+        };
+        let mut selection_state = self.selection_state.borrow_mut();
+        let last_added_selection_index = selection_state.last_added_selection_index;
+        selection_state.last_added_selection_index = selection_state
+            .selections
+            .update_all_selections(last_added_selection_index, |selection| {
+                if range.hides_line(selection.cursor.position.line_index)
+                    || range.hides_line(selection.anchor.line_index)
+                {
+                    Selection::from(Cursor::from(end_of_start_line))
+                } else {
+                    selection
+                }
+            });
+        drop(selection_state);
+        self.update_highlighted_delimiter_positions();
+        true
+    }
+
+    /// Unfolds the folded range that starts at the line of `position`, or else the innermost
+    /// one that contains it. Returns whether there was one.
+    pub fn unfold_at(&self, position: Position) -> bool {
+        let line_index = position.line_index;
+        let mut fold_state = self.fold_state.borrow_mut();
+        let range = match fold_state.collapsed.get(&line_index) {
+            Some(&end_line_index) => FoldRange {
+                start_line_index: line_index,
+                end_line_index,
+            },
+            None => match fold_state.innermost_range_containing(line_index) {
+                Some(range) => range,
+                None => return false,
+            },
+        };
+        fold_state.collapsed.remove(&range.start_line_index);
+        drop(fold_state);
+        self.update_collapsed_scale(range);
+        self.update_y();
+        true
+    }
+
+    pub fn toggle_fold_at(&self, position: Position) -> bool {
+        self.unfold_at(position) || self.fold_at(position)
+    }
+
+    pub fn is_line_hidden(&self, line_index: usize) -> bool {
+        self.fold_state.borrow().is_line_hidden(line_index)
+    }
+
+    /// The ranges that are folded, in the order they start in.
+    pub fn folded_ranges(&self) -> Vec<FoldRange> {
+        self.fold_state
+            .borrow()
+            .collapsed
+            .iter()
+            .map(|(&start_line_index, &end_line_index)| FoldRange {
+                start_line_index,
+                end_line_index,
+            })
+            .collect()
+    }
+
     pub fn set_selection(&self, position: Position, affinity: Affinity, mode: SelectionMode, new_group:NewGroup) {
         let selection = grow_selection(
             Selection::from(Cursor {
@@ -207,7 +314,9 @@ impl Session {
         selection_state.selections.set_selection(selection);
         selection_state.last_added_selection_index = Some(0);
         selection_state.injected_char_stack.clear();
+        selection_state.column_selection = None;
         drop(selection_state);
+        self.reveal_selections();
         self.update_highlighted_delimiter_positions();
         if let NewGroup::Yes = new_group{
             self.document().force_new_group();
@@ -230,7 +339,9 @@ impl Session {
         selection_state.last_added_selection_index =
             Some(selection_state.selections.add_selection(selection));
         selection_state.injected_char_stack.clear();
+        selection_state.column_selection = None;
         drop(selection_state);
+        self.reveal_selections();
         self.update_highlighted_delimiter_positions();
         self.document().force_new_group();
     }
@@ -256,13 +367,74 @@ impl Session {
                 }),
         );
         selection_state.injected_char_stack.clear();
+        selection_state.column_selection = None;
         drop(selection_state);
+        self.reveal_selections();
         self.update_highlighted_delimiter_positions();
         if let NewGroup::Yes = new_group{
             self.document().force_new_group();
         }
     }
 
+    pub fn is_column_selecting(&self) -> bool {
+        self.selection_state.borrow().column_selection.is_some()
+    }
+
+    /// Selects the rectangle from the cursor of the last added selection, or from the corner
+    /// the rectangle being selected started at, to `position`, as a selection per line.
+    pub fn extend_column_selection(&self, position: Position) {
+        let cursor = ColumnPosition::from_position(position, self.document.as_text().as_lines());
+        self.update_column_selection(|_| cursor);
+    }
+
+    /// Moves the moving corner of the rectangle being selected by lines and columns, starting a
+    /// rectangle at the cursor of the last added selection if there is none.
+    pub fn move_column_selection(&self, line_delta: isize, column_delta: isize) {
+        let line_count = self.document.as_text().as_lines().len();
+        self.update_column_selection(|cursor| ColumnPosition {
+            line_index: cursor
+                .line_index
+                .saturating_add_signed(line_delta)
+                .min(line_count - 1),
+            column_index: cursor.column_index.saturating_add_signed(column_delta),
+        });
+    }
+
+    fn update_column_selection(&self, f: impl FnOnce(ColumnPosition) -> ColumnPosition) {
+        let text = self.document.as_text();
+        let lines = text.as_lines();
+        let mut selection_state = self.selection_state.borrow_mut();
+        let column_selection = selection_state.column_selection.unwrap_or_else(|| {
+            let position = selection_state
+                .last_added_selection_index
+                .map_or(Position::default(), |index| {
+                    selection_state.selections[index].cursor.position
+                });
+            let position = ColumnPosition::from_position(position, lines);
+            ColumnSelection::new(position, position)
+        });
+        let column_selection =
+            ColumnSelection::new(column_selection.anchor, f(column_selection.cursor));
+        let selections = column_selection.to_selections(lines);
+        // the cursor is on the first or the last line of the rectangle
+        let cursor_index =
+            if column_selection.cursor.line_index < column_selection.anchor.line_index {
+                0
+            } else {
+                selections.len() - 1
+            };
+        selection_state.mode = SelectionMode::Simple;
+        selection_state.last_added_selection_index = selection_state
+            .selections
+            .set_selections(selections, Some(cursor_index));
+        selection_state.injected_char_stack.clear();
+        selection_state.column_selection = Some(column_selection);
+        drop(selection_state);
+        drop(text);
+        self.update_highlighted_delimiter_positions();
+        self.document.force_new_group();
+    }
+
     pub fn move_left(&self, reset_anchor: bool) {
         self.modify_selections(reset_anchor, |selection, layout| {
             selection.update_cursor(|cursor| cursor.move_left(layout.as_text().as_lines()))
@@ -287,6 +459,41 @@ impl Session {
         });
     }
 
+    pub fn move_up_line(&self, reset_anchor: bool) {
+        self.modify_selections(reset_anchor, |selection, layout| {
+            selection.update_cursor(|cursor| cursor.move_up_line(layout.as_text().as_lines()))
+        });
+    }
+
+    pub fn move_down_line(&self, reset_anchor: bool) {
+        self.modify_selections(reset_anchor, |selection, layout| {
+            selection.update_cursor(|cursor| cursor.move_down_line(layout.as_text().as_lines()))
+        });
+    }
+
+    /// Moves each cursor next to a delimiter to the delimiter that pairs with it.
+    pub fn move_to_matching_delimiter(&self, reset_anchor: bool) {
+        let structure = self.structure();
+        self.modify_selections(reset_anchor, |selection, layout| {
+            selection.update_cursor(|cursor| {
+                match find_highlighted_delimiter_pair(
+                    &structure,
+                    layout.as_text().as_lines(),
+                    cursor.position,
+                ) {
+                    Some((opening_position, closing_position)) => {
+                        if cursor.position >= closing_position {
+                            Cursor::from(opening_position)
+                        } else {
+                            Cursor::from(closing_position)
+                        }
+                    }
+                    None => cursor,
+                }
+            })
+        });
+    }
+
     pub fn home(&self, reset_anchor: bool) {
         self.modify_selections(reset_anchor, |selection, layout| {
             selection.update_cursor(|cursor| cursor.home(layout.as_text().as_lines()))
@@ -308,7 +515,8 @@ impl Session {
             let mut selection_state = self.selection_state.borrow_mut();
             if char == ' ' {
                 edit_kind = EditKind::InsertSpace;
-            } else if char == '"' || char.is_opening_delimiter() {
+            } else if self.settings.auto_close_pairs && (char == '"' || char.is_opening_delimiter())
+            {
                 if selection_state
                     .selections
                     .iter()
@@ -352,6 +560,10 @@ impl Session {
                 uninject_char = Some(selection_state.injected_char_stack.pop().unwrap());
             }
             drop(selection_state);
+        } else {
+            // only typing single chars is merged into one undo step, longer text is a step of
+            // its own
+            edit_kind = EditKind::Other;
         }
         self.document.edit_selections(
             self.id,
@@ -403,6 +615,46 @@ impl Session {
         );
     }
 
+    /// Replaces the word before each cursor, which is what is being completed, with `text`, as
+    /// one undo step.
+    pub fn insert_completion(&self, text: &str) {
+        self.selection_state
+            .borrow_mut()
+            .injected_char_stack
+            .clear();
+        self.document.edit_selections(
+            self.id,
+            EditKind::Other,
+            &self.selection_state.borrow().selections,
+            &self.settings,
+            |mut editor, position, length| {
+                editor.apply_edit(Edit {
+                    change: Change::Delete(position, length),
+                    drift: Drift::Before,
+                });
+                let prefix_len = completion::word_before(editor.as_text(), position).len();
+                let start = Position {
+                    line_index: position.line_index,
+                    byte_index: position.byte_index - prefix_len,
+                };
+                editor.apply_edit(Edit {
+                    change: Change::Delete(
+                        start,
+                        Length {
+                            line_count: 0,
+                            byte_count: prefix_len,
+                        },
+                    ),
+                    drift: Drift::Before,
+                });
+                editor.apply_edit(Edit {
+                    change: Change::Insert(start, text.into()),
+                    drift: Drift::Before,
+                });
+            },
+        );
+    }
+
     pub fn paste(&self, text: Text) {
         self.document.edit_selections(
             self.id,
@@ -422,6 +674,55 @@ impl Session {
         );
     }
     
+    /// Pastes `text` after running it through `filter`, once for every selection.
+    pub fn paste_filtered(&self, text: &str, filter: &dyn PasteFilter) {
+        let texts: Vec<Text> = self
+            .selection_state
+            .borrow()
+            .selections
+            .iter()
+            .map(|selection| filter.filter(text, &self.paste_context(selection.start())).into())
+            .collect();
+        let mut texts = texts.into_iter();
+        self.document.edit_selections(
+            self.id,
+            EditKind::Other,
+            &self.selection_state.borrow().selections,
+            &self.settings,
+            |mut editor, position, length| {
+                editor.apply_edit(Edit {
+                    change: Change::Delete(position, length),
+                    drift: Drift::Before,
+                });
+                editor.apply_edit(Edit {
+                    change: Change::Insert(position, texts.next().unwrap()),
+                    drift: Drift::Before,
+                });
+            },
+        );
+    }
+
+    fn paste_context(&self, position: Position) -> PasteContext {
+        let text = self.document.as_text();
+        let layout = self.document.layout();
+        let mut in_string = false;
+        let mut byte_index = 0;
+        for token in &layout.tokens[position.line_index] {
+            if byte_index >= position.byte_index {
+                break;
+            }
+            byte_index += token.len;
+            in_string = token.kind == TokenKind::String && position.byte_index < byte_index;
+        }
+        PasteContext {
+            in_string,
+            indent_column_count: text.as_lines()[position.line_index]
+                .indent()
+                .unwrap_or("")
+                .len(),
+        }
+    }
+
     pub fn paste_grouped(&self, text: Text, group:u64) {
         self.document.edit_selections(
             self.id,
@@ -712,63 +1013,368 @@ impl Session {
     }
 
     pub fn indent(&self) {
+        let tab_column_count = self.settings.tab_column_count;
+        self.reindent_lines(|indent_column_count| {
+            indent_column_count + tab_column_count - indent_column_count % tab_column_count
+        });
+    }
+
+    pub fn outdent(&self) {
+        let tab_column_count = self.settings.tab_column_count;
+        self.reindent_lines(|indent_column_count| {
+            indent_column_count
+                - indent_column_count
+                    .min((indent_column_count + tab_column_count - 1) % tab_column_count + 1)
+        });
+    }
+
+    /// Changes the indentation of every selected line from its column count to `f` of it.
+    fn reindent_lines(&self, f: impl Fn(usize) -> usize) {
         self.document.edit_linewise(
             self.id,
             EditKind::Other,
             &self.selection_state.borrow().selections,
             |mut editor, line_index| {
-                let indent_column_count = editor.as_text().as_lines()[line_index]
-                    .indent()
-                    .unwrap_or("")
-                    .len();
-                let column_count = self.settings.tab_column_count
-                    - indent_column_count % self.settings.tab_column_count;
-                editor.apply_edit(Edit {
-                    change: Change::Insert(
-                        Position {
-                            line_index,
-                            byte_index: indent_column_count,
-                        },
-                        iter::repeat(' ').take(column_count).collect(),
-                    ),
-                    drift: Drift::Before,
-                });
+                let (byte_index, delete_byte_count, insert_text) =
+                    reindent(&editor.as_text().as_lines()[line_index], &self.settings, &f);
+                let position = Position {
+                    line_index,
+                    byte_index,
+                };
+                if delete_byte_count > 0 {
+                    editor.apply_edit(Edit {
+                        change: Change::Delete(
+                            position,
+                            Length {
+                                line_count: 0,
+                                byte_count: delete_byte_count,
+                            },
+                        ),
+                        drift: Drift::Before,
+                    });
+                }
+                if !insert_text.is_empty() {
+                    editor.apply_edit(Edit {
+                        change: Change::Insert(position, insert_text.into()),
+                        drift: Drift::Before,
+                    });
+                }
             },
         );
     }
 
-    pub fn outdent(&self) {
+    /// Comments out the selected lines with line comments, or uncomments them if every line of
+    /// them that isn't blank already is. If every selection is within a line, the selections
+    /// are wrapped in block comments instead, or unwrapped if they already are.
+    pub fn toggle_comment(&self) {
+        let comment_syntax = &self.settings.comment_syntax;
+        let is_within_line = self
+            .selection_state
+            .borrow()
+            .selections
+            .iter()
+            .all(|selection| {
+                !selection.is_empty() && selection.start().line_index == selection.end().line_index
+            });
+        match (&comment_syntax.line, &comment_syntax.block) {
+            (_, Some((start, end))) if is_within_line => self.toggle_block_comment(start, end),
+            (Some(prefix), _) => self.toggle_line_comment(prefix),
+            (None, Some((start, end))) => self.toggle_block_comment(start, end),
+            (None, None) => {}
+        }
+    }
+
+    fn toggle_line_comment(&self, prefix: &str) {
+        let is_commented = {
+            let text = self.document.as_text();
+            let lines = text.as_lines();
+            let selection_state = self.selection_state.borrow();
+            selection_state
+                .selections
+                .iter()
+                .flat_map(|selection| selection.line_range())
+                .filter(|&line_index| lines[line_index].indent().is_some())
+                .all(|line_index| lines[line_index].trim_start().starts_with(prefix))
+        };
         self.document.edit_linewise(
             self.id,
             EditKind::Other,
             &self.selection_state.borrow().selections,
             |mut editor, line_index| {
-                let indent_column_count = editor.as_text().as_lines()[line_index]
-                    .indent()
-                    .unwrap_or("")
-                    .len();
-                let column_count = indent_column_count.min(
-                    (indent_column_count + self.settings.tab_column_count - 1)
-                        % self.settings.tab_column_count
-                        + 1,
-                );
-                editor.apply_edit(Edit {
-                    change: Change::Delete(
-                        Position {
-                            line_index,
-                            byte_index: indent_column_count - column_count,
-                        },
+                let line = &editor.as_text().as_lines()[line_index];
+                let Some(indent) = line.indent() else {
+                    return;
+                };
+                let position = Position {
+                    line_index,
+                    byte_index: indent.len(),
+                };
+                let change = if is_commented {
+                    let mut byte_count = prefix.len();
+                    if line[indent.len() + prefix.len()..].starts_with(' ') {
+                        byte_count += 1;
+                    }
+                    Change::Delete(
+                        position,
                         Length {
                             line_count: 0,
-                            byte_count: column_count,
+                            byte_count,
                         },
-                    ),
+                    )
+                } else {
+                    Change::Insert(position, format!("{} ", prefix).into())
+                };
+                editor.apply_edit(Edit {
+                    change,
                     drift: Drift::Before,
                 });
             },
         );
     }
 
+    fn toggle_block_comment(&self, start: &str, end: &str) {
+        self.document.edit_selections(
+            self.id,
+            EditKind::Other,
+            &self.selection_state.borrow().selections,
+            &self.settings,
+            |mut editor, position, length| {
+                let lines = editor.as_text().as_lines();
+                let end_position = position + length;
+                let before = &lines[position.line_index][..position.byte_index];
+                let after = &lines[end_position.line_index][end_position.byte_index..];
+                // the comment markers are either just outside the selection, as they are after
+                // commenting, or just inside it
+                let markers = if before.ends_with(start) && after.starts_with(end) {
+                    Some((
+                        Position {
+                            line_index: position.line_index,
+                            byte_index: position.byte_index - start.len(),
+                        },
+                        end_position,
+                    ))
+                } else if length.line_count > 0 || length.byte_count >= start.len() + end.len() {
+                    let inner_before = &lines[position.line_index][position.byte_index..];
+                    let inner_after = &lines[end_position.line_index][..end_position.byte_index];
+                    (inner_before.starts_with(start) && inner_after.ends_with(end)).then(|| {
+                        (
+                            position,
+                            Position {
+                                line_index: end_position.line_index,
+                                byte_index: end_position.byte_index - end.len(),
+                            },
+                        )
+                    })
+                } else {
+                    None
+                };
+                match markers {
+                    Some((start_position, end_position)) => {
+                        // the end goes first, so the start stays where it is
+                        editor.apply_edit(Edit {
+                            change: Change::Delete(
+                                end_position,
+                                Length {
+                                    line_count: 0,
+                                    byte_count: end.len(),
+                                },
+                            ),
+                            drift: Drift::Before,
+                        });
+                        editor.apply_edit(Edit {
+                            change: Change::Delete(
+                                start_position,
+                                Length {
+                                    line_count: 0,
+                                    byte_count: start.len(),
+                                },
+                            ),
+                            drift: Drift::Before,
+                        });
+                    }
+                    None => {
+                        editor.apply_edit(Edit {
+                            change: Change::Insert(end_position, end.into()),
+                            drift: Drift::After,
+                        });
+                        editor.apply_edit(Edit {
+                            change: Change::Insert(position, start.into()),
+                            drift: Drift::Before,
+                        });
+                    }
+                }
+            },
+        );
+    }
+
+    /// Grows every selection to the enclosing comma separated element, then to the contents of
+    /// the enclosing delimiters, then to the delimiters themselves.
+    pub fn select_enclosing(&self) {
+        let structure = self.structure();
+        self.modify_selections(false, |selection, layout| {
+            let lines = layout.as_text().as_lines();
+            let (start, end) = (selection.start(), selection.end());
+            let (opening_position, closing_position) =
+                match structure.enclosing_delimiters(start, end) {
+                    Some(delimiters) => delimiters,
+                    None => return selection,
+                };
+            let contents_start = Position {
+                line_index: opening_position.line_index,
+                byte_index: opening_position.byte_index + 1,
+            };
+            let (new_start, new_end) = if let Some(&element) = structure
+                .elements(lines, opening_position, closing_position)
+                .iter()
+                .find(|(element_start, element_end)| {
+                    *element_start <= start
+                        && end <= *element_end
+                        && (*element_start, *element_end) != (start, end)
+                }) {
+                element
+            } else if (contents_start, closing_position) != (start, end) {
+                (contents_start, closing_position)
+            } else {
+                (
+                    opening_position,
+                    Position {
+                        line_index: closing_position.line_index,
+                        byte_index: closing_position.byte_index + 1,
+                    },
+                )
+            };
+            Selection {
+                anchor: new_start,
+                cursor: Cursor::from(new_end),
+            }
+        });
+    }
+
+    pub fn move_element_left(&self) -> bool {
+        self.swap_elements(SwapMode::MoveLeft)
+    }
+
+    pub fn move_element_right(&self) -> bool {
+        self.swap_elements(SwapMode::MoveRight)
+    }
+
+    pub fn transpose_elements(&self) -> bool {
+        self.swap_elements(SwapMode::Transpose)
+    }
+
+    /// Applies edits computed elsewhere, like a quick fix, as a single undo step. The edits are
+    /// in terms of the current text and must not overlap, see `FileEdit::check`.
+    pub fn apply_text_edits(&self, edits: &[TextEdit]) {
+        if edits.is_empty() {
+            return;
+        }
+        let mut edits: Vec<&TextEdit> = edits.iter().collect();
+        edits.sort_by_key(|edit| edit.start);
+        let selections = self.selection_state.borrow().selections.clone();
+        self.document
+            .edit_with_selections(self.id, EditKind::Other, &selections, |mut editor| {
+                let mut selections = selections.clone();
+                // from the end, so the positions of the edits before stay valid
+                for edit in edits.into_iter().rev() {
+                    for edit in [
+                        Edit {
+                            change: Change::Delete(edit.start, edit.end - edit.start),
+                            drift: Drift::Before,
+                        },
+                        Edit {
+                            change: Change::Insert(edit.start, edit.text.as_str().into()),
+                            drift: Drift::Before,
+                        },
+                    ] {
+                        selections.apply_edit(&edit, None);
+                        editor.apply_edit(edit);
+                    }
+                }
+                selections
+            });
+    }
+
+    /// Searches the document for `query` and returns the number of matches. The first match
+    /// at or after the cursor becomes the current one, without selecting it.
+    pub fn set_search_query(&self, query: SearchQuery) -> Result<usize, String> {
+        let (start, _) = self.search_selection();
+        let mut search_state = self.search_state.borrow_mut();
+        search_state.set_query(query, &self.document.as_text(), start)?;
+        Ok(search_state.matches().len())
+    }
+
+    pub fn clear_search(&self) {
+        self.search_state.borrow_mut().clear();
+    }
+
+    /// Selects the next match of the search, and returns whether there was one.
+    pub fn find_next(&self) -> bool {
+        let selection = self.search_selection();
+        let range = self.search_state.borrow_mut().next(selection);
+        self.select_search_match(range)
+    }
+
+    /// Selects the previous match of the search, and returns whether there was one.
+    pub fn find_previous(&self) -> bool {
+        let selection = self.search_selection();
+        let range = self.search_state.borrow_mut().previous(selection);
+        self.select_search_match(range)
+    }
+
+    /// Replaces the current match of the search and selects the one after it. Returns whether
+    /// there was a match to replace.
+    pub fn replace_current(&self, replacement: &str) -> bool {
+        let search_state = self.search_state.borrow();
+        let Some((index, edit)) = search_state.current_index().and_then(|index| {
+            let edit = search_state.replace_edit(&self.document.as_text(), index, replacement)?;
+            Some((index, edit))
+        }) else {
+            return false;
+        };
+        let matches = search_state.matches();
+        let next = matches[(index + 1) % matches.len()];
+        drop(search_state);
+        // selected before the edit, so the edit moves the selection along with the text
+        self.select_search_match(Some(next));
+        let end = edit.start + Text::from(edit.text.as_str()).length();
+        self.apply_text_edits(&[edit]);
+        // the edit would only reach `update_after_edit` later, but the next match is needed now
+        self.handle_changes();
+        self.search_state.borrow_mut().seek(end);
+        true
+    }
+
+    /// Replaces every match of the search, as a single undo step, and returns how many there
+    /// were.
+    pub fn replace_all(&self, replacement: &str) -> usize {
+        let edits = self
+            .search_state
+            .borrow()
+            .replace_all_edits(&self.document.as_text(), replacement);
+        self.apply_text_edits(&edits);
+        edits.len()
+    }
+
+    /// The start and end of the selection that searches go from.
+    fn search_selection(&self) -> (Position, Position) {
+        let selection_state = self.selection_state.borrow();
+        selection_state
+            .last_added_selection_index
+            .and_then(|index| selection_state.selections.as_selections().get(index))
+            .map_or((Position::default(), Position::default()), |selection| {
+                (selection.start(), selection.end())
+            })
+    }
+
+    fn select_search_match(&self, range: Option<(Position, Position)>) -> bool {
+        let Some((start, end)) = range else {
+            return false;
+        };
+        self.set_selection(start, Affinity::After, SelectionMode::Simple, NewGroup::Yes);
+        self.move_to(end, Affinity::Before, NewGroup::No);
+        true
+    }
+
     pub fn copy(&self) -> String {
         let mut string = String::new();
         for selection in &self.selection_state.borrow().selections {
@@ -784,6 +1390,49 @@ impl Session {
         string
     }
 
+    /// Same selection contents as [`Session::copy`], but split into runs of a single token
+    /// kind so a rich clipboard flavor can be built from them.
+    pub fn copy_token_runs(&self) -> Vec<(String, TokenKind)> {
+        let mut runs: Vec<(String, TokenKind)> = Vec::new();
+        let mut push_run = |string: &str, kind: TokenKind| {
+            if string.is_empty() {
+                return;
+            }
+            match runs.last_mut() {
+                Some((last_string, last_kind)) if *last_kind == kind => last_string.push_str(string),
+                _ => runs.push((string.to_string(), kind)),
+            }
+        };
+        let text = self.document.as_text();
+        let layout = self.document.layout();
+        let lines = text.as_lines();
+        for selection in &self.selection_state.borrow().selections {
+            let start = selection.start();
+            let end = selection.end();
+            for line_index in start.line_index..=end.line_index {
+                let line = &lines[line_index];
+                let start_byte = if line_index == start.line_index { start.byte_index } else { 0 };
+                let end_byte = if line_index == end.line_index { end.byte_index } else { line.len() };
+                let mut byte_index = 0;
+                for token in &layout.tokens[line_index] {
+                    let token_start = byte_index.max(start_byte);
+                    byte_index += token.len;
+                    let token_end = byte_index.min(end_byte);
+                    if token_start < token_end {
+                        push_run(&line[token_start..token_end], token.kind);
+                    }
+                }
+                if byte_index < end_byte {
+                    push_run(&line[byte_index.max(start_byte)..end_byte], TokenKind::Unknown);
+                }
+                if line_index != end.line_index {
+                    push_run("\n", TokenKind::Whitespace);
+                }
+            }
+        }
+        runs
+    }
+
     pub fn undo(&self) -> bool {
         self.selection_state
             .borrow_mut()
@@ -802,12 +1451,141 @@ impl Session {
             .redo(self.id, &self.selection_state.borrow().selections)
     }
 
-    pub fn handle_changes(&mut self) {
+    pub fn handle_changes(&self) {
         while let Ok((selections, edits)) = self.edit_receiver.try_recv() {
             self.update_after_edit(selections, &edits);
         }
     }
 
+    fn structure(&self) -> Structure {
+        Structure::new(
+            self.document.as_text().as_lines(),
+            &self.document.layout().tokens,
+        )
+    }
+
+    fn swap_elements(&self, mode: SwapMode) -> bool {
+        let structure = self.structure();
+        let text = self.document.as_text();
+        let lines = text.as_lines();
+        let selections = self.selection_state.borrow().selections.clone();
+        let mut swaps = Vec::new();
+        let mut swap_end = Position::zero();
+        for &selection in &selections {
+            let (start, end) = (selection.start(), selection.end());
+            let (opening_position, closing_position) =
+                match structure.enclosing_delimiters(start, end) {
+                    Some(delimiters) => delimiters,
+                    None => {
+                        swaps.push(None);
+                        continue;
+                    }
+                };
+            let elements = structure.elements(lines, opening_position, closing_position);
+            let containing_index = elements.iter().position(|(element_start, element_end)| {
+                *element_start <= start && end <= *element_end
+            });
+            let swap = match mode {
+                SwapMode::MoveLeft => containing_index
+                    .filter(|&index| index > 0)
+                    .map(|index| {
+                        let swap = Swap::new(lines, elements[index - 1], elements[index]);
+                        let offset = |position: Position| position - elements[index].0;
+                        (swap, offset(selection.anchor), offset(selection.cursor.position))
+                    }),
+                SwapMode::MoveRight => containing_index
+                    .filter(|&index| index + 1 < elements.len())
+                    .map(|index| {
+                        let swap = Swap::new(lines, elements[index], elements[index + 1]);
+                        let first_offset = swap.first_offset();
+                        let offset =
+                            |position: Position| first_offset + (position - elements[index].0);
+                        (swap, offset(selection.anchor), offset(selection.cursor.position))
+                    }),
+                SwapMode::Transpose => {
+                    // swap the element at the cursor, or the one before it, with the next one
+                    let index = containing_index.or_else(|| {
+                        elements
+                            .iter()
+                            .rposition(|(_, element_end)| *element_end < start)
+                    });
+                    index
+                        .map(|index| index.min(elements.len().saturating_sub(2)))
+                        .filter(|&index| index + 1 < elements.len())
+                        .map(|index| {
+                            let swap = Swap::new(lines, elements[index], elements[index + 1]);
+                            let length = swap.length;
+                            (swap, length, length)
+                        })
+                }
+            };
+            // several cursors in the same element would produce overlapping swaps
+            let swap = swap.filter(|(swap, _, _)| swap.start >= swap_end);
+            if let Some((swap, _, _)) = &swap {
+                swap_end = swap.start + swap.length;
+            }
+            swaps.push(swap);
+        }
+        drop(text);
+        if swaps.iter().all(Option::is_none) {
+            return false;
+        }
+        self.document.edit_with_selections(
+            self.id,
+            EditKind::Other,
+            &selections,
+            |mut editor| {
+                let mut edits: Vec<Edit> = Vec::new();
+                let mut new_selections = Vec::new();
+                for (&selection, swap) in selections.iter().zip(swaps) {
+                    match swap {
+                        Some((swap, anchor_offset, cursor_offset)) => {
+                            let mut start = swap.start;
+                            for edit in &edits {
+                                start = start.apply_edit(edit);
+                            }
+                            for edit in [
+                                Edit {
+                                    change: Change::Delete(start, swap.length),
+                                    drift: Drift::Before,
+                                },
+                                Edit {
+                                    change: Change::Insert(start, swap.text.into()),
+                                    drift: Drift::After,
+                                },
+                            ] {
+                                editor.apply_edit(edit.clone());
+                                edits.push(edit);
+                            }
+                            new_selections.push(Selection {
+                                anchor: start + anchor_offset,
+                                cursor: Cursor {
+                                    position: start + cursor_offset,
+                                    ..selection.cursor
+                                },
+                            });
+                        }
+                        None => {
+                            let mut selection = selection;
+                            for edit in &edits {
+                                selection = selection.apply_edit(edit);
+                            }
+                            new_selections.push(selection);
+                        }
+                    }
+                }
+                let mut selections = SelectionSet::new();
+                let mut new_selections = new_selections.into_iter();
+                selections.set_selection(new_selections.next().unwrap());
+                for selection in new_selections {
+                    selections.add_selection(selection);
+                }
+                selections
+            },
+        );
+        true
+    }
+
     fn modify_selections(
         &self,
         reset_anchor: bool,
@@ -830,14 +1608,17 @@ impl Session {
                 selection
             });
         selection_state.injected_char_stack.clear();
+        selection_state.column_selection = None;
         drop(selection_state);
         drop(layout);
+        self.reveal_selections();
         self.update_highlighted_delimiter_positions();
         self.document().force_new_group();
     }
 
     fn update_after_edit(&self, selections: Option<SelectionSet>, edits: &[Edit]) {
         for edit in edits {
+            self.update_collapsed_after_edit(edit);
             match edit.change {
                 Change::Insert(point, ref text) => {
                     self.layout.borrow_mut().column_count[point.line_index] = None;
@@ -897,6 +1678,7 @@ impl Session {
         }
         self.update_y();
         let mut selection_state = self.selection_state.borrow_mut();
+        selection_state.column_selection = None;
         if let Some(selections) = selections {
             selection_state.selections = selections;
         } else {
@@ -908,7 +1690,92 @@ impl Session {
             }
         }
         drop(selection_state);
+        self.reveal_selections();
         self.update_highlighted_delimiter_positions();
+        let mut search_state = self.search_state.borrow_mut();
+        if search_state.is_active() {
+            search_state.apply_edits(&self.document.as_text(), edits);
+        }
+    }
+
+    /// Unfolds the ranges an edit reaches into, and moves the ones after it along with their
+    /// lines. Called before the layout of the edit is updated.
+    fn update_collapsed_after_edit(&self, edit: &Edit) {
+        let (start_line_index, line_count, is_insert) = match edit.change {
+            Change::Insert(point, ref text) => (point.line_index, text.length().line_count, true),
+            Change::Delete(start, length) => (start.line_index, length.line_count, false),
+        };
+        let end_line_index = start_line_index + line_count;
+        let mut fold_state = self.fold_state.borrow_mut();
+        let mut unfolded_ranges = Vec::new();
+        let mut collapsed = BTreeMap::new();
+        for (&start, &end) in &fold_state.collapsed {
+            if start < end_line_index && start_line_index <= end {
+                unfolded_ranges.push(FoldRange {
+                    start_line_index: start,
+                    end_line_index: end,
+                });
+            } else if start > start_line_index {
+                if is_insert {
+                    collapsed.insert(start + line_count, end + line_count);
+                } else {
+                    collapsed.insert(start - line_count, end - line_count);
+                }
+            } else {
+                collapsed.insert(start, end);
+            }
+        }
+        // The lines are shown while they still have their indices from before the edit.
+        for range in &unfolded_ranges {
+            fold_state.collapsed.remove(&range.start_line_index);
+        }
+        drop(fold_state);
+        for range in unfolded_ranges {
+            self.update_collapsed_scale(range);
+        }
+        self.fold_state.borrow_mut().collapsed = collapsed;
+    }
+
+    /// Hides the lines of `range` that are in a folded range, and shows the others.
+    fn update_collapsed_scale(&self, range: FoldRange) {
+        let fold_state = &mut *self.fold_state.borrow_mut();
+        let mut layout = self.layout.borrow_mut();
+        for line_index in range.start_line_index + 1..=range.end_line_index {
+            if fold_state.is_line_hidden(line_index) {
+                fold_state.folding_lines.remove(&line_index);
+                fold_state.folded_lines.remove(&line_index);
+                fold_state.unfolding_lines.remove(&line_index);
+                layout.scale[line_index] = 0.0;
+            } else {
+                layout.scale[line_index] = 1.0;
+            }
+        }
+        layout.y.truncate(range.start_line_index + 1);
+    }
+
+    /// Unfolds the folded ranges that hide a cursor.
+    fn reveal_selections(&self) {
+        let line_indices: Vec<_> = self
+            .selections()
+            .iter()
+            .map(|selection| selection.cursor.position.line_index)
+            .collect();
+        let mut is_revealed = false;
+        for line_index in line_indices {
+            loop {
+                let mut fold_state = self.fold_state.borrow_mut();
+                let Some(range) = fold_state.hiding_range(line_index) else {
+                    break;
+                };
+                fold_state.collapsed.remove(&range.start_line_index);
+                drop(fold_state);
+                self.update_collapsed_scale(range);
+                is_revealed = true;
+            }
+        }
+        if is_revealed {
+            self.update_y();
+        }
     }
 
     fn update_y(&self) {
@@ -984,12 +1851,14 @@ impl Session {
         let mut highlighted_delimiter_positions =
             mem::take(&mut selection_state.highlighted_delimiter_positions);
         highlighted_delimiter_positions.clear();
+        let structure = self.structure();
         for selection in &selection_state.selections {
             if !selection.is_empty() {
                 continue;
             }
             if let Some((opening_delimiter_position, closing_delimiter_position)) =
                 find_highlighted_delimiter_pair(
+                    &structure,
                     self.document.as_text().as_lines(),
                     selection.cursor.position,
                 )
@@ -1011,6 +1880,13 @@ impl Drop for Session {
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub struct SessionId(usize);
 
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+enum SwapMode {
+    MoveLeft,
+    MoveRight,
+    Transpose,
+}
+
 #[derive(Debug)]
 pub struct SessionLayout {
     pub y: Vec<f64>,
@@ -1035,6 +1911,8 @@ struct SelectionState {
     last_added_selection_index: Option<usize>,
     injected_char_stack: Vec<char>,
     highlighted_delimiter_positions: HashSet<Position>,
+    /// The rectangle the selections were made from, while it is being dragged out.
+    column_selection: Option<ColumnSelection>,
 }
 
 #[derive(Debug)]
@@ -1042,13 +1920,46 @@ struct FoldState {
     folding_lines: HashSet<usize>,
     folded_lines: HashSet<usize>,
     unfolding_lines: HashSet<usize>,
+    /// The end of each folded range, by its start.
+    collapsed: BTreeMap<usize, usize>,
+}
+
+impl FoldState {
+    fn is_line_hidden(&self, line_index: usize) -> bool {
+        self.hiding_range(line_index).is_some()
+    }
+
+    fn hiding_range(&self, line_index: usize) -> Option<FoldRange> {
+        self.collapsed
+            .range(..line_index)
+            .map(|(&start_line_index, &end_line_index)| FoldRange {
+                start_line_index,
+                end_line_index,
+            })
+            .find(|range| range.hides_line(line_index))
+    }
+
+    fn innermost_range_containing(&self, line_index: usize) -> Option<FoldRange> {
+        self.collapsed
+            .range(..=line_index)
+            .rev()
+            .map(|(&start_line_index, &end_line_index)| FoldRange {
+                start_line_index,
+                end_line_index,
+            })
+            .find(|range| range.contains_line(line_index))
+    }
 }
 
-pub fn reindent(string: &str, f: impl FnOnce(usize) -> usize) -> (usize, usize, String) {
+pub fn reindent(
+    string: &str,
+    settings: &Settings,
+    f: impl FnOnce(usize) -> usize,
+) -> (usize, usize, String) {
     let indentation = string.indent().unwrap_or("");
-    let indentation_column_count = indentation.column_count();
+    let indentation_column_count = settings.indent_column_count(indentation);
     let new_indentation_column_count = f(indentation_column_count);
-    let new_indentation = new_indentation(new_indentation_column_count);
+    let new_indentation = settings.indentation(new_indentation_column_count);
     let len = indentation.longest_common_prefix(&new_indentation).len();
     (
         len,
@@ -1178,145 +2089,30 @@ fn grow_selection(
     }
 }
 
-fn new_indentation(column_count: usize) -> String {
-    iter::repeat(' ').take(column_count).collect()
-}
-
+/// The pair of delimiters next to `position`, preferring the one after it.
 fn find_highlighted_delimiter_pair(
+    structure: &Structure,
     lines: &[String],
     position: Position,
 ) -> Option<(Position, Position)> {
-    // Cursor is before an opening delimiter
-    match lines[position.line_index][position.byte_index..]
-        .chars()
-        .next()
-    {
-        Some(ch) if ch.is_opening_delimiter() => {
-            let opening_delimiter_position = position;
-            if let Some(closing_delimiter_position) = find_closing_delimiter(
-                lines,
-                Position {
-                    line_index: position.line_index,
-                    byte_index: position.byte_index + ch.len_utf8(),
-                },
-                ch,
-            ) {
-                return Some((opening_delimiter_position, closing_delimiter_position));
-            }
-        }
-        _ => {}
-    }
-    // Cursor is before a closing delimiter
-    match lines[position.line_index][position.byte_index..]
-        .chars()
-        .next()
-    {
-        Some(ch) if ch.is_closing_delimiter() => {
-            let closing_delimiter_position = position;
-            if let Some(opening_delimiter_position) = find_opening_delimiter(lines, position, ch) {
-                return Some((opening_delimiter_position, closing_delimiter_position));
-            }
-        }
-        _ => {}
-    }
-    // Cursor is after a closing delimiter
-    match lines[position.line_index][..position.byte_index]
+    let line = &lines[position.line_index];
+    let after = (position.byte_index < line.len()).then_some(position);
+    let before = line[..position.byte_index]
         .chars()
         .next_back()
-    {
-        Some(ch) if ch.is_closing_delimiter() => {
-            let closing_delimiter_position = Position {
-                line_index: position.line_index,
-                byte_index: position.byte_index - ch.len_utf8(),
-            };
-            if let Some(opening_delimiter_position) =
-                find_opening_delimiter(lines, closing_delimiter_position, ch)
-            {
-                return Some((opening_delimiter_position, closing_delimiter_position));
-            }
-        }
-        _ => {}
-    }
-    // Cursor is after an opening delimiter
-    match lines[position.line_index][..position.byte_index]
-        .chars()
-        .next_back()
-    {
-        Some(ch) if ch.is_opening_delimiter() => {
-            let opening_delimiter_position = Position {
-                line_index: position.line_index,
-                byte_index: position.byte_index - ch.len_utf8(),
-            };
-            if let Some(closing_delimiter_position) = find_closing_delimiter(lines, position, ch) {
-                return Some((opening_delimiter_position, closing_delimiter_position));
-            }
-        }
-        _ => {}
-    }
-    None
-}
-
-fn find_opening_delimiter(
-    lines: &[String],
-    position: Position,
-    closing_delimiter: char,
-) -> Option<Position> {
-    let mut delimiter_stack = vec![closing_delimiter];
-    let mut position = position;
-    loop {
-        for char in lines[position.line_index][..position.byte_index]
-            .chars()
-            .rev()
-        {
-            position.byte_index -= char.len_utf8();
-            if char.is_closing_delimiter() {
-                delimiter_stack.push(char);
-            }
-            if char.is_opening_delimiter() {
-                if delimiter_stack.last() != Some(&char.opposite_delimiter().unwrap()) {
-                    return None;
-                }
-                delimiter_stack.pop().unwrap();
-                if delimiter_stack.is_empty() {
-                    return Some(position);
-                }
-            }
-        }
-        if position.line_index == 0 {
-            return None;
-        }
-        position.line_index -= 1;
-        position.byte_index = lines[position.line_index].len();
-    }
-}
-
-fn find_closing_delimiter(
-    lines: &[String],
-    position: Position,
-    opening_delimiter: char,
-) -> Option<Position> {
-    let mut delimiter_stack = vec![opening_delimiter];
-    let mut position = position;
-    loop {
-        for char in lines[position.line_index][position.byte_index..].chars() {
-            if char.is_opening_delimiter() {
-                delimiter_stack.push(char);
-            }
-            if char.is_closing_delimiter() {
-                if delimiter_stack.last() != Some(&char.opposite_delimiter().unwrap()) {
-                    return None;
-                }
-                delimiter_stack.pop().unwrap();
-                if delimiter_stack.is_empty() {
-                    return Some(position);
-                }
-            }
-            position.byte_index += char.len_utf8();
-        }
-        if position.line_index == lines.len() - 1 {
-            return None;
-        }
-        position.line_index += 1;
-        position.byte_index = 0;
-    }
+        .map(|char| Position {
+            line_index: position.line_index,
+            byte_index: position.byte_index - char.len_utf8(),
+        });
+    after
+        .into_iter()
+        .chain(before)
+        .find_map(|delimiter_position| {
+            let matching_position = structure.matching_delimiter(delimiter_position)?;
+            Some(if delimiter_position < matching_position {
+                (delimiter_position, matching_position)
+            } else {
+                (matching_position, delimiter_position)
+            })
+        })
 }
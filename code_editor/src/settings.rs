@@ -1,19 +1,79 @@
+use {crate::key_bindings::KeyBindings, std::iter};
+
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub struct Settings {
+    /// The width of one level of indentation.
     pub tab_column_count: usize,
+    /// Whether new indentation is made of tabs, rather than spaces. A tab is counted as one
+    /// level of indentation.
+    pub indent_with_tabs: bool,
     pub fold_level: usize,
     pub word_separators: Vec<char>,
+    /// Whether typing an opening delimiter or quote inserts the closing one too, which typing
+    /// it again then steps over.
+    pub auto_close_pairs: bool,
+    pub comment_syntax: CommentSyntax,
+    /// The keys that run editor commands.
+    pub key_bindings: KeyBindings,
 }
 
 impl Default for Settings {
     fn default() -> Self {
         Self {
             tab_column_count: 4,
+            indent_with_tabs: false,
             fold_level: 2,
             word_separators: vec![
                 ' ', '`', '~', '!', '@', '#', '$', '%', '^', '&', '*', '(', ')', '-', '=', '+',
                 '[', '{', ']', '}', '\\', '|', ';', ':', '\'', '"', '.', '<', '>', '/', '?', ',',
             ],
+            auto_close_pairs: true,
+            comment_syntax: CommentSyntax::default(),
+            key_bindings: KeyBindings::default(),
+        }
+    }
+}
+
+impl Settings {
+    /// The number of columns `indent` indents by.
+    pub fn indent_column_count(&self, indent: &str) -> usize {
+        indent
+            .chars()
+            .map(|char| match char {
+                '\t' => self.tab_column_count,
+                _ => 1,
+            })
+            .sum()
+    }
+
+    /// The indentation that indents by `column_count` columns.
+    pub fn indentation(&self, column_count: usize) -> String {
+        if self.indent_with_tabs {
+            let mut indentation: String = iter::repeat('\t')
+                .take(column_count / self.tab_column_count)
+                .collect();
+            indentation.extend(iter::repeat(' ').take(column_count % self.tab_column_count));
+            indentation
+        } else {
+            iter::repeat(' ').take(column_count).collect()
+        }
+    }
+}
+
+/// How the language of a document writes comments, for toggling them.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct CommentSyntax {
+    /// What starts a comment that runs to the end of the line, like `//`.
+    pub line: Option<String>,
+    /// What starts and ends a comment that can start and end anywhere, like `/*` and `*/`.
+    pub block: Option<(String, String)>,
+}
+
+impl Default for CommentSyntax {
+    fn default() -> Self {
+        Self {
+            line: Some("//".to_string()),
+            block: Some(("/*".to_string(), "*/".to_string())),
         }
     }
 }
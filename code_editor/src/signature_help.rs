@@ -0,0 +1,324 @@
+use {
+    crate::{
+        text::{Position, Text},
+        Document,
+    },
+    std::ops::Range,
+};
+
+/// How many lines before the cursor are searched for the call it is in.
+const MAX_CALL_LINES: usize = 32;
+
+/// Keywords that start a function definition in the languages the heuristic knows about.
+const FN_KEYWORDS: &[&str] = &["fn", "def", "function", "func"];
+
+/// The signature of a function, as shown while typing a call to it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Signature {
+    pub label: String,
+    /// Where each parameter is in `label`, as byte ranges.
+    pub parameters: Vec<Range<usize>>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SignatureHelp {
+    pub signature: Signature,
+    /// The parameter the cursor is at, if the signature has one there.
+    pub active_parameter: Option<usize>,
+}
+
+/// The call the cursor is in.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CallContext {
+    pub callee: String,
+    /// How many arguments come before the cursor.
+    pub argument_index: usize,
+    /// Whether it is a method call like `a.b(..)`, where the receiver isn't an argument.
+    pub is_method: bool,
+}
+
+pub struct SignatureRequest<'a> {
+    pub text: &'a Text,
+    pub position: Position,
+    pub call: &'a CallContext,
+    /// The other open documents.
+    pub documents: &'a [Document],
+}
+
+pub trait SignatureProvider {
+    fn signature(&self, request: &SignatureRequest) -> Option<Signature>;
+}
+
+/// Finds the definition of the function being called in the open documents by looking for a
+/// keyword like `fn` followed by its name. Good enough when nothing knows the language, but it
+/// can't tell apart functions with the same name.
+#[derive(Default)]
+pub struct FnSignatures;
+
+impl SignatureProvider for FnSignatures {
+    fn signature(&self, request: &SignatureRequest) -> Option<Signature> {
+        find_signature(request.text, &request.call.callee).or_else(|| {
+            request.documents.iter().find_map(|document| {
+                let text = document.as_text();
+                if std::ptr::eq(&*text, request.text) {
+                    return None;
+                }
+                find_signature(&text, &request.call.callee)
+            })
+        })
+    }
+}
+
+impl SignatureHelp {
+    pub fn new(signature: Signature, call: &CallContext) -> Self {
+        // the receiver of a method call is its first parameter, but isn't written between the
+        // parentheses
+        let skip_self = call.is_method
+            && signature
+                .parameters
+                .first()
+                .is_some_and(|range| signature.label[range.clone()].contains("self"));
+        let index = call.argument_index + skip_self as usize;
+        Self {
+            active_parameter: (index < signature.parameters.len()).then_some(index),
+            signature,
+        }
+    }
+}
+
+fn is_ident_char(char: char) -> bool {
+    char.is_alphanumeric() || char == '_'
+}
+
+/// Finds the innermost unclosed call before `position`. Strings, character literals and line
+/// comments are skipped, so parentheses and commas in them don't count.
+pub fn call_context(text: &Text, position: Position) -> Option<CallContext> {
+    struct Open {
+        delimiter: char,
+        call: Option<CallContext>,
+    }
+
+    let lines = text.as_lines();
+    let last_line = lines.get(position.line_index)?;
+    let first_line_index = position.line_index.saturating_sub(MAX_CALL_LINES);
+    let mut stack: Vec<Open> = Vec::new();
+    for (line_index, line) in lines[first_line_index..=position.line_index]
+        .iter()
+        .enumerate()
+    {
+        let line = if first_line_index + line_index == position.line_index {
+            &last_line[..position.byte_index.min(last_line.len())]
+        } else {
+            line.as_str()
+        };
+        let mut chars = line.char_indices().peekable();
+        while let Some((index, char)) = chars.next() {
+            match char {
+                '"' => {
+                    while let Some((_, char)) = chars.next() {
+                        match char {
+                            '\\' => {
+                                chars.next();
+                            }
+                            '"' => break,
+                            _ => {}
+                        }
+                    }
+                }
+                // a character literal, but not a lifetime
+                '\'' if matches!(line[index + 1..].chars().nth(1), Some('\'')) => {
+                    chars.next();
+                    chars.next();
+                }
+                '/' if matches!(chars.peek(), Some((_, '/'))) => break,
+                '(' => stack.push(Open {
+                    delimiter: '(',
+                    call: callee_before(&line[..index]),
+                }),
+                '[' | '{' => stack.push(Open {
+                    delimiter: char,
+                    call: None,
+                }),
+                ')' | ']' | '}' => {
+                    stack.pop();
+                }
+                ',' => {
+                    if let Some(Open {
+                        delimiter: '(',
+                        call: Some(call),
+                    }) = stack.last_mut()
+                    {
+                        call.argument_index += 1;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    // arguments can contain blocks and closures, but a call doesn't go past a block it's in
+    stack
+        .into_iter()
+        .rev()
+        .take_while(|open| open.delimiter != '{')
+        .find(|open| open.delimiter == '(')?
+        .call
+}
+
+/// The function called by a `(` that comes after `before`, unless it is a definition or
+/// something like `if (`.
+fn callee_before(before: &str) -> Option<CallContext> {
+    let before = before.trim_end();
+    let start = before
+        .char_indices()
+        .rev()
+        .take_while(|(_, char)| is_ident_char(*char))
+        .last()?
+        .0;
+    let callee = &before[start..];
+    if callee.starts_with(|char: char| char.is_ascii_digit())
+        || matches!(callee, "if" | "while" | "for" | "match" | "return" | "in")
+    {
+        return None;
+    }
+    let rest = before[..start].trim_end();
+    let word_before = rest
+        .rsplit(|char: char| !is_ident_char(char))
+        .next()
+        .unwrap_or("");
+    if FN_KEYWORDS.contains(&word_before) {
+        return None;
+    }
+    Some(CallContext {
+        callee: callee.to_string(),
+        argument_index: 0,
+        is_method: rest.ends_with('.'),
+    })
+}
+
+/// Finds `fn name(..)` or the like in `text` and turns it into a signature. The parameters can
+/// span lines, which are joined.
+pub fn find_signature(text: &Text, name: &str) -> Option<Signature> {
+    let lines = text.as_lines();
+    for (line_index, line) in lines.iter().enumerate() {
+        for (start, _) in line.match_indices(name) {
+            let before = &line[..start];
+            let end = start + name.len();
+            if !before.ends_with(char::is_whitespace) || line[end..].starts_with(is_ident_char) {
+                continue;
+            }
+            let before = before.trim_end();
+            let Some(keyword) = FN_KEYWORDS.iter().find(|keyword| {
+                before.ends_with(*keyword)
+                    && !before[..before.len() - keyword.len()].ends_with(is_ident_char)
+            }) else {
+                continue;
+            };
+            let rest = std::iter::once(&line[end..])
+                .chain(
+                    lines[line_index + 1..]
+                        .iter()
+                        .take(MAX_CALL_LINES)
+                        .map(|line| line.as_str()),
+                )
+                .collect::<Vec<_>>()
+                .join(" ");
+            if let Some(signature) = parse_signature(keyword, name, &rest) {
+                return Some(signature);
+            }
+        }
+    }
+    None
+}
+
+/// Parses what comes after the name of a function in its definition: generics, parameters and
+/// a return type.
+fn parse_signature(keyword: &str, name: &str, rest: &str) -> Option<Signature> {
+    let mut rest = rest.trim_start();
+    if rest.starts_with('<') {
+        rest = &rest[matching(rest, '<', '>')? + 1..];
+        rest = rest.trim_start();
+    }
+    if !rest.starts_with('(') {
+        return None;
+    }
+    let close = matching(rest, '(', ')')?;
+    let parameters = split_parameters(&rest[1..close]);
+    let after = rest[close + 1..].trim_start();
+    let return_type = after.strip_prefix("->").map(|return_type| {
+        let end = return_type.find(['{', ';']).unwrap_or(return_type.len());
+        let return_type = &return_type[..end];
+        let return_type = return_type
+            .find(" where ")
+            .map_or(return_type, |index| &return_type[..index]);
+        collapse_whitespace(return_type.trim().trim_end_matches(':'))
+    });
+
+    let mut label = format!("{} {}(", keyword, name);
+    let mut ranges = Vec::new();
+    for (index, parameter) in parameters.iter().enumerate() {
+        if index > 0 {
+            label.push_str(", ");
+        }
+        ranges.push(label.len()..label.len() + parameter.len());
+        label.push_str(parameter);
+    }
+    label.push(')');
+    if let Some(return_type) = return_type.filter(|return_type| !return_type.is_empty()) {
+        label.push_str(" -> ");
+        label.push_str(&return_type);
+    }
+    Some(Signature {
+        label,
+        parameters: ranges,
+    })
+}
+
+/// The byte index of the delimiter that closes the one `string` starts with.
+fn matching(string: &str, open: char, close: char) -> Option<usize> {
+    let mut depth = 0;
+    let mut prev = ' ';
+    for (index, char) in string.char_indices() {
+        // the arrow of a closure type isn't a closing angle bracket
+        if char == open {
+            depth += 1;
+        } else if char == close && !(close == '>' && prev == '-') {
+            depth -= 1;
+            if depth == 0 {
+                return Some(index);
+            }
+        }
+        prev = char;
+    }
+    None
+}
+
+/// Splits a parameter list at the commas that aren't nested in a type or a pattern.
+fn split_parameters(parameters: &str) -> Vec<String> {
+    let mut result = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    let mut prev = ' ';
+    for (index, char) in parameters.char_indices() {
+        match char {
+            '(' | '[' | '{' | '<' => depth += 1,
+            '>' if prev == '-' => {}
+            ')' | ']' | '}' | '>' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                result.push(&parameters[start..index]);
+                start = index + 1;
+            }
+            _ => {}
+        }
+        prev = char;
+    }
+    result.push(&parameters[start..]);
+    result
+        .into_iter()
+        .map(collapse_whitespace)
+        .filter(|parameter| !parameter.is_empty())
+        .collect()
+}
+
+fn collapse_whitespace(string: &str) -> String {
+    string.split_whitespace().collect::<Vec<_>>().join(" ")
+}
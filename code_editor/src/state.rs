@@ -0,0 +1,54 @@
+use {
+    crate::{selection::Selection, text::Position},
+    makepad_widgets::makepad_micro_serde::*,
+};
+
+/// What is needed to show a document the way it was before a restart: where its selections
+/// were and how far it was scrolled. See `CodeEditor::session_state`.
+#[derive(Clone, Debug, Default, PartialEq, SerBin, DeBin, SerJson, DeJson, SerRon, DeRon)]
+pub struct SessionState {
+    pub selections: Vec<SavedSelection>,
+    /// The line at the top of the view, with the fraction of it that is scrolled out of view.
+    pub scroll_line: f64,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, SerBin, DeBin, SerJson, DeJson, SerRon, DeRon)]
+pub struct SavedSelection {
+    pub anchor_line_index: usize,
+    pub anchor_byte_index: usize,
+    pub cursor_line_index: usize,
+    pub cursor_byte_index: usize,
+}
+
+impl SavedSelection {
+    pub fn from_selection(selection: Selection) -> Self {
+        Self {
+            anchor_line_index: selection.anchor.line_index,
+            anchor_byte_index: selection.anchor.byte_index,
+            cursor_line_index: selection.cursor.position.line_index,
+            cursor_byte_index: selection.cursor.position.byte_index,
+        }
+    }
+
+    /// The anchor and the cursor, moved back onto `lines` in case the file got shorter since
+    /// it was saved.
+    pub fn positions(&self, lines: &[String]) -> (Position, Position) {
+        (
+            clamp_position(lines, self.anchor_line_index, self.anchor_byte_index),
+            clamp_position(lines, self.cursor_line_index, self.cursor_byte_index),
+        )
+    }
+}
+
+fn clamp_position(lines: &[String], line_index: usize, byte_index: usize) -> Position {
+    let line_index = line_index.min(lines.len() - 1);
+    let line = &lines[line_index];
+    let mut byte_index = byte_index.min(line.len());
+    while !line.is_char_boundary(byte_index) {
+        byte_index -= 1;
+    }
+    Position {
+        line_index,
+        byte_index,
+    }
+}
@@ -2,6 +2,7 @@ use crate::char::CharExt;
 
 pub trait StrExt {
     fn column_count(&self) -> usize;
+    fn byte_index_at_column(&self, column_index: usize) -> usize;
     fn indent_level(&self, indent_column_count: usize) -> usize;
     fn next_indent_level(&self, indent_column_count: usize) -> usize;
     fn prev_indent_level(&self, indent_column_count: usize) -> usize;
@@ -11,12 +12,28 @@ pub trait StrExt {
     fn longest_common_prefix(&self, other: &str) -> &str;
     fn graphemes(&self) -> Graphemes<'_>;
     fn grapheme_indices(&self) -> GraphemeIndices<'_>;
+    fn visual_graphemes(&self) -> VisualGraphemes<'_>;
     fn split_whitespace_boundaries(&self) -> SplitWhitespaceBoundaries<'_>;
 }
 
 impl StrExt for str {
     fn column_count(&self) -> usize {
-        self.chars().map(|char| char.column_count()).sum()
+        // A grapheme takes up the columns of its first char, and at least one even when that is a
+        // stray mark.
+        self.graphemes()
+            .map(|grapheme| grapheme.chars().next().unwrap().column_count().max(1))
+            .sum()
+    }
+
+    fn byte_index_at_column(&self, column_index: usize) -> usize {
+        let mut column = 0;
+        for (byte_index, grapheme) in self.grapheme_indices() {
+            if column >= column_index {
+                return byte_index;
+            }
+            column += grapheme.column_count();
+        }
+        self.len()
     }
 
     fn indent_level(&self, indent_column_count: usize) -> usize {
@@ -80,6 +97,15 @@ impl StrExt for str {
         }
     }
 
+    fn visual_graphemes(&self) -> VisualGraphemes<'_> {
+        VisualGraphemes {
+            graphemes: self.graphemes(),
+            column_index: 0,
+            rtl_run_len: 0,
+            rtl_run_mirror: 0,
+        }
+    }
+
     fn split_whitespace_boundaries(&self) -> SplitWhitespaceBoundaries<'_> {
         SplitWhitespaceBoundaries { string: self }
     }
@@ -97,9 +123,15 @@ impl<'a> Iterator for Graphemes<'a> {
         if self.string.is_empty() {
             return None;
         }
-        let mut end = 1;
-        while !self.string.is_char_boundary(end) {
-            end += 1;
+        let mut chars = self.string.chars();
+        let mut prev_char = chars.next().unwrap();
+        let mut end = prev_char.len_utf8();
+        for char in chars {
+            if !char.is_grapheme_extend() && prev_char != '\u{200D}' {
+                break;
+            }
+            end += char.len_utf8();
+            prev_char = char;
         }
         let (grapheme, string) = self.string.split_at(end);
         self.string = string;
@@ -112,9 +144,14 @@ impl<'a> DoubleEndedIterator for Graphemes<'a> {
         if self.string.is_empty() {
             return None;
         }
-        let mut start = self.string.len() - 1;
-        while !self.string.is_char_boundary(start) {
-            start -= 1;
+        let mut start = self.string.len();
+        let mut chars = self.string.chars().rev().peekable();
+        while let Some(char) = chars.next() {
+            start -= char.len_utf8();
+            let joins_prev_char = char.is_grapheme_extend() || chars.peek() == Some(&'\u{200D}');
+            if !joins_prev_char {
+                break;
+            }
         }
         let (string, grapheme) = self.string.split_at(start);
         self.string = string;
@@ -144,6 +181,65 @@ impl<'a> DoubleEndedIterator for GraphemeIndices<'a> {
     }
 }
 
+/// Iterates over the graphemes of a string in logical order, together with the column each one is
+/// displayed at and whether it is part of a right-to-left run.
+///
+/// A right-to-left run starts at a right-to-left letter, and takes in the neutral graphemes
+/// (whitespace and punctuation) up to the last right-to-left letter before the next left-to-right
+/// letter or digit. Its graphemes are displayed mirrored within the columns of the run. This is a
+/// basic form of bidi reordering; embedding levels and mirrored brackets aren't supported.
+#[derive(Clone, Debug)]
+pub struct VisualGraphemes<'a> {
+    graphemes: Graphemes<'a>,
+    column_index: usize,
+    rtl_run_len: usize,
+    // The start column plus the end column of the right-to-left run.
+    rtl_run_mirror: usize,
+}
+
+impl<'a> Iterator for VisualGraphemes<'a> {
+    type Item = (&'a str, usize, bool);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let grapheme = self.graphemes.next()?;
+        let column_count = grapheme.column_count();
+        if self.rtl_run_len == 0 && grapheme.chars().next().unwrap().is_rtl() {
+            let mut run_len = grapheme.len();
+            let mut run_column_count = column_count;
+            let mut neutral_len = 0;
+            let mut neutral_column_count = 0;
+            for grapheme in self.graphemes.clone() {
+                let char = grapheme.chars().next().unwrap();
+                if char.is_rtl() {
+                    run_len += neutral_len + grapheme.len();
+                    run_column_count += neutral_column_count + grapheme.column_count();
+                    neutral_len = 0;
+                    neutral_column_count = 0;
+                } else if char.is_alphanumeric() {
+                    break;
+                } else {
+                    neutral_len += grapheme.len();
+                    neutral_column_count += grapheme.column_count();
+                }
+            }
+            self.rtl_run_len = run_len;
+            self.rtl_run_mirror = 2 * self.column_index + run_column_count;
+        }
+        let item = if self.rtl_run_len > 0 {
+            self.rtl_run_len -= grapheme.len();
+            (
+                grapheme,
+                self.rtl_run_mirror - self.column_index - column_count,
+                true,
+            )
+        } else {
+            (grapheme, self.column_index, false)
+        };
+        self.column_index += column_count;
+        Some(item)
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct SplitWhitespaceBoundaries<'a> {
     string: &'a str,
@@ -179,3 +275,64 @@ impl<'a> Iterator for SplitWhitespaceBoundaries<'a> {
         Some(string_0)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn columns(string: &str) -> Vec<(&str, usize, bool)> {
+        string.visual_graphemes().collect()
+    }
+
+    #[test]
+    fn left_to_right_text_keeps_its_columns() {
+        assert_eq!(
+            columns("ab c"),
+            [
+                ("a", 0, false),
+                ("b", 1, false),
+                (" ", 2, false),
+                ("c", 3, false)
+            ]
+        );
+    }
+
+    #[test]
+    fn right_to_left_runs_are_mirrored() {
+        // Two Hebrew words with a space between them form one run, the trailing space and the
+        // Latin letter do not.
+        assert_eq!(
+            columns("a\u{5D0}\u{5D1} \u{5D2} b"),
+            [
+                ("a", 0, false),
+                ("\u{5D0}", 4, true),
+                ("\u{5D1}", 3, true),
+                (" ", 2, true),
+                ("\u{5D2}", 1, true),
+                (" ", 5, false),
+                ("b", 6, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn digits_end_a_right_to_left_run() {
+        assert_eq!(
+            columns("\u{627}\u{628}12"),
+            [
+                ("\u{627}", 1, true),
+                ("\u{628}", 0, true),
+                ("1", 2, false),
+                ("2", 3, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn marks_stay_with_their_letter() {
+        assert_eq!(
+            columns("\u{5D0}\u{5B8}\u{5D1}"),
+            [("\u{5D0}\u{5B8}", 1, true), ("\u{5D1}", 0, true)]
+        );
+    }
+}
@@ -0,0 +1,241 @@
+use crate::{
+    char::CharExt,
+    fold::FoldRange,
+    text::{Length, Position},
+    token::{Token, TokenKind},
+};
+
+/// Bracket and comma structure of a document, derived from its tokens. Delimiters and commas
+/// inside strings and comments are ignored, which is enough to find argument lists, array
+/// elements and the like without a full parser.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Structure {
+    marks: Vec<(Position, char)>,
+}
+
+impl Structure {
+    pub fn new(lines: &[String], tokens: &[Vec<Token>]) -> Self {
+        let mut marks = Vec::new();
+        for (line_index, (line, tokens)) in lines.iter().zip(tokens.iter()).enumerate() {
+            let mut byte_index = 0;
+            for token in tokens {
+                if let TokenKind::Delimiter | TokenKind::Punctuator = token.kind {
+                    for (index, char) in line[byte_index..byte_index + token.len].char_indices() {
+                        if char.is_opening_delimiter() || char.is_closing_delimiter() || char == ',' {
+                            marks.push((
+                                Position {
+                                    line_index,
+                                    byte_index: byte_index + index,
+                                },
+                                char,
+                            ));
+                        }
+                    }
+                }
+                byte_index += token.len;
+            }
+        }
+        Self { marks }
+    }
+
+    /// Returns the positions of the innermost opening and closing delimiter that strictly enclose
+    /// the range from `start` to `end`.
+    pub fn enclosing_delimiters(&self, start: Position, end: Position) -> Option<(Position, Position)> {
+        let mut stack = Vec::new();
+        for &(position, char) in &self.marks {
+            if char.is_opening_delimiter() {
+                stack.push(position);
+            } else if char.is_closing_delimiter() {
+                if let Some(opening_position) = stack.pop() {
+                    // inner pairs close before outer ones, so the first match is the innermost
+                    if opening_position < start && position >= end {
+                        return Some((opening_position, position));
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// Returns the position of the delimiter that pairs with the one at `position`, if there is a
+    /// delimiter there and it has a match of the right kind.
+    pub fn matching_delimiter(&self, position: Position) -> Option<Position> {
+        let index = self
+            .marks
+            .binary_search_by_key(&position, |&(position, _)| position)
+            .ok()?;
+        let delimiter = self.marks[index].1;
+        let mut stack = vec![delimiter];
+        if delimiter.is_opening_delimiter() {
+            for &(position, char) in &self.marks[index + 1..] {
+                if char.is_opening_delimiter() {
+                    stack.push(char);
+                } else if char.is_closing_delimiter() {
+                    if stack.pop()?.opposite_delimiter() != Some(char) {
+                        return None;
+                    }
+                    if stack.is_empty() {
+                        return Some(position);
+                    }
+                }
+            }
+        } else if delimiter.is_closing_delimiter() {
+            for &(position, char) in self.marks[..index].iter().rev() {
+                if char.is_closing_delimiter() {
+                    stack.push(char);
+                } else if char.is_opening_delimiter() {
+                    if stack.pop()?.opposite_delimiter() != Some(char) {
+                        return None;
+                    }
+                    if stack.is_empty() {
+                        return Some(position);
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// Returns the ranges of lines between pairs of delimiters that span more than two lines,
+    /// from the line of the opening delimiter to the line before the closing one. Of the pairs
+    /// that open on the same line, only the outermost is kept.
+    pub fn fold_ranges(&self) -> Vec<FoldRange> {
+        let mut ranges: Vec<FoldRange> = Vec::new();
+        let mut stack = Vec::new();
+        for &(position, char) in &self.marks {
+            if char.is_opening_delimiter() {
+                stack.push(position);
+            } else if char.is_closing_delimiter() {
+                let Some(opening_position) = stack.pop() else {
+                    continue;
+                };
+                if position.line_index < opening_position.line_index + 2 {
+                    continue;
+                }
+                let range = FoldRange {
+                    start_line_index: opening_position.line_index,
+                    end_line_index: position.line_index - 1,
+                };
+                // inner pairs close first, so an outer pair on the same line replaces them
+                ranges.retain(|other| other.start_line_index != range.start_line_index);
+                ranges.push(range);
+            }
+        }
+        ranges.sort();
+        ranges
+    }
+
+    /// Returns the comma separated elements between a pair of delimiters, without surrounding
+    /// whitespace. Empty elements, such as the one after a trailing comma, are skipped.
+    pub fn elements(
+        &self,
+        lines: &[String],
+        opening_position: Position,
+        closing_position: Position,
+    ) -> Vec<(Position, Position)> {
+        let mut elements = Vec::new();
+        let mut depth = 0;
+        let mut element_start = next_position(opening_position);
+        for &(position, char) in &self.marks {
+            if position <= opening_position || position >= closing_position {
+                continue;
+            }
+            if char.is_opening_delimiter() {
+                depth += 1;
+            } else if char.is_closing_delimiter() {
+                depth -= 1;
+            } else if depth == 0 {
+                push_trimmed(&mut elements, lines, element_start, position);
+                element_start = next_position(position);
+            }
+        }
+        push_trimmed(&mut elements, lines, element_start, closing_position);
+        elements
+    }
+}
+
+/// A swap of two elements, as the replacement text for the range spanning both of them.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Swap {
+    pub start: Position,
+    pub length: Length,
+    pub text: String,
+    pub first_length: Length,
+    pub separator_length: Length,
+    pub second_length: Length,
+}
+
+impl Swap {
+    pub fn new(lines: &[String], first: (Position, Position), second: (Position, Position)) -> Self {
+        let first_text = slice(lines, first.0, first.1);
+        let separator_text = slice(lines, first.1, second.0);
+        let second_text = slice(lines, second.0, second.1);
+        Self {
+            start: first.0,
+            length: second.1 - first.0,
+            text: format!("{}{}{}", second_text, separator_text, first_text),
+            first_length: first.1 - first.0,
+            separator_length: second.0 - first.1,
+            second_length: second.1 - second.0,
+        }
+    }
+
+    /// Where the first element starts after the swap, relative to the start of the swap.
+    pub fn first_offset(&self) -> Length {
+        self.second_length + self.separator_length
+    }
+}
+
+fn next_position(position: Position) -> Position {
+    Position {
+        line_index: position.line_index,
+        byte_index: position.byte_index + 1,
+    }
+}
+
+fn push_trimmed(
+    elements: &mut Vec<(Position, Position)>,
+    lines: &[String],
+    start: Position,
+    end: Position,
+) {
+    let mut start = start;
+    let mut end = end;
+    while start < end {
+        match lines[start.line_index][start.byte_index..].chars().next() {
+            Some(char) if char.is_whitespace() => start.byte_index += char.len_utf8(),
+            Some(_) => break,
+            None => {
+                start.line_index += 1;
+                start.byte_index = 0;
+            }
+        }
+    }
+    while start < end {
+        match lines[end.line_index][..end.byte_index].chars().next_back() {
+            Some(char) if char.is_whitespace() => end.byte_index -= char.len_utf8(),
+            Some(_) => break,
+            None => {
+                end.line_index -= 1;
+                end.byte_index = lines[end.line_index].len();
+            }
+        }
+    }
+    if start < end {
+        elements.push((start, end));
+    }
+}
+
+fn slice(lines: &[String], start: Position, end: Position) -> String {
+    if start.line_index == end.line_index {
+        return lines[start.line_index][start.byte_index..end.byte_index].to_string();
+    }
+    let mut string = lines[start.line_index][start.byte_index..].to_string();
+    for line in &lines[start.line_index + 1..end.line_index] {
+        string.push('\n');
+        string.push_str(line);
+    }
+    string.push('\n');
+    string.push_str(&lines[end.line_index][..end.byte_index]);
+    string
+}
@@ -202,6 +202,35 @@ impl Change {
             Self::Delete(start, length) => Change::Insert(start, text.slice(start, length)),
         }
     }
+
+    /// Moves this change past `other`, a change made to the same text, so it can be applied
+    /// after it. Where both insert at the same position, `drift` decides which side the text of
+    /// `other` ends up on, so moving `other` past this change needs the opposite one for both
+    /// orders to end up with the same text. Text inserted by `other` inside a deletion is kept,
+    /// which splits the deletion in two around it.
+    pub fn transform(self, other: &Change, drift: Drift) -> Vec<Self> {
+        match self {
+            Self::Insert(position, text) => {
+                vec![Self::Insert(position.apply_change(other, drift), text)]
+            }
+            Self::Delete(start, length) => {
+                let end = start + length;
+                match *other {
+                    Change::Insert(position, ref text) if start < position && position < end => {
+                        vec![
+                            Self::Delete(start, position - start),
+                            Self::Delete(start + text.length(), end - position),
+                        ]
+                    }
+                    _ => {
+                        let start = start.apply_change(other, Drift::Before);
+                        let end = end.apply_change(other, Drift::After).max(start);
+                        vec![Self::Delete(start, end - start)]
+                    }
+                }
+            }
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
@@ -216,10 +245,16 @@ impl Position {
     }
 
     pub fn apply_edit(self, edit: &Edit) -> Self {
-        match edit.change {
+        self.apply_change(&edit.change, edit.drift)
+    }
+
+    /// Like `apply_edit`, where `drift` decides which side of text inserted right here the
+    /// position ends up on.
+    pub fn apply_change(self, change: &Change, drift: Drift) -> Self {
+        match *change {
             Change::Insert(point, ref text) => match self.cmp(&point) {
                 Ordering::Less => self,
-                Ordering::Equal => match edit.drift {
+                Ordering::Equal => match drift {
                     Drift::Before => point + text.length() + (self - point),
                     Drift::After => self,
                 },
@@ -344,3 +379,101 @@ pub enum Drift {
     Before,
     After,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn position(line_index: usize, byte_index: usize) -> Position {
+        Position {
+            line_index,
+            byte_index,
+        }
+    }
+
+    fn length(line_count: usize, byte_count: usize) -> Length {
+        Length {
+            line_count,
+            byte_count,
+        }
+    }
+
+    /// Applies `change` and `other_change` to `text` in both orders, moving the second one
+    /// past the first, and returns both results.
+    fn merge(text: &str, change: &Change, other_change: &Change) -> (String, String) {
+        let mut text_0 = Text::from(text);
+        text_0.apply_change(change.clone());
+        for other_change in other_change.clone().transform(change, Drift::After) {
+            text_0.apply_change(other_change);
+        }
+        let mut text_1 = Text::from(text);
+        text_1.apply_change(other_change.clone());
+        for change in change.clone().transform(other_change, Drift::Before) {
+            text_1.apply_change(change);
+        }
+        (text_0.to_string(), text_1.to_string())
+    }
+
+    #[test]
+    fn insert_inside_concurrent_deletion_is_kept() {
+        let delete = Change::Delete(position(0, 3), length(0, 5));
+        let insert = Change::Insert(position(0, 5), Text::from("XY"));
+        let (text_0, text_1) = merge("hello world", &delete, &insert);
+        assert_eq!(text_0, "helXYrld");
+        assert_eq!(text_1, "helXYrld");
+    }
+
+    #[test]
+    fn deletion_is_split_around_concurrent_insert() {
+        let delete = Change::Delete(position(0, 1), length(2, 1));
+        let insert = Change::Insert(position(1, 1), Text::from("X\nY"));
+        assert_eq!(
+            delete.clone().transform(&insert, Drift::Before),
+            [
+                Change::Delete(position(0, 1), length(1, 1)),
+                Change::Delete(position(1, 1), length(1, 1)),
+            ]
+        );
+        assert_eq!(
+            insert.clone().transform(&delete, Drift::After),
+            [Change::Insert(position(0, 1), Text::from("X\nY"))]
+        );
+        let (text_0, text_1) = merge("ab\ncd\nef", &delete, &insert);
+        assert_eq!(text_0, "aX\nYf");
+        assert_eq!(text_1, "aX\nYf");
+    }
+
+    #[test]
+    fn concurrent_changes_converge() {
+        let text = Text::from("ab\ncd\nef");
+        let mut positions = Vec::new();
+        for (line_index, line) in text.as_lines().iter().enumerate() {
+            for byte_index in 0..=line.len() {
+                positions.push(position(line_index, byte_index));
+            }
+        }
+        let mut changes = Vec::new();
+        for &start in &positions {
+            changes.push(Change::Insert(start, Text::from("X")));
+            changes.push(Change::Insert(start, Text::from("Y\nZ")));
+            for &end in &positions {
+                if start < end {
+                    changes.push(Change::Delete(start, end - start));
+                }
+            }
+        }
+        for change in &changes {
+            for other_change in &changes {
+                let (text_0, text_1) = merge("ab\ncd\nef", change, other_change);
+                assert_eq!(text_0, text_1, "{:?} and {:?}", change, other_change);
+                for change in [change, other_change] {
+                    if let Change::Insert(_, text) = change {
+                        for line in text.as_lines() {
+                            assert!(text_0.contains(line.as_str()));
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
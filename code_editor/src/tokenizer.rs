@@ -1,18 +1,79 @@
-use crate::{
-    text::{Change, Text},
-    token::TokenKind,
-    Token,
+use {
+    crate::{
+        text::{Change, Text},
+        token::TokenKind,
+        tokenizer_rules::TokenizerRules,
+        Token,
+    },
+    std::{fmt, rc::Rc},
 };
 
-#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+/// Something that highlights a document one line at a time, like the built in Rust tokenizer or
+/// a `TokenizerRules`. Each line starts in the state the line before ended in, which is how
+/// comments and strings span lines. The `Tokenizer` remembers the states, and after an edit only
+/// highlights the lines that changed, and the lines after them until they start in the same state
+/// as before.
+pub trait Highlighter: fmt::Debug {
+    /// The state the first line starts in.
+    fn initial_state(&self) -> State;
+
+    /// Returns the tokens of a line that starts in `state`, and the state it ends in.
+    fn highlight_line(&self, state: State, line: &str) -> (Vec<Token>, State);
+}
+
+/// The built in tokenizer, for Rust.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct RustHighlighter;
+
+impl Highlighter for RustHighlighter {
+    fn initial_state(&self) -> State {
+        State::default()
+    }
+
+    fn highlight_line(&self, mut state: State, line: &str) -> (Vec<Token>, State) {
+        let mut tokens = Vec::new();
+        let mut cursor = Cursor::new(line);
+        loop {
+            let (next_state, token) = state.next(&mut cursor);
+            state = next_state;
+            match token {
+                Some(token) => tokens.push(token),
+                None => break,
+            }
+        }
+        (tokens, state)
+    }
+}
+
+#[derive(Clone, Debug)]
 pub struct Tokenizer {
     state: Vec<Option<(State, State)>>,
+    highlighter: Rc<dyn Highlighter>,
 }
 
 impl Tokenizer {
     pub fn new(line_count: usize) -> Self {
         Self {
             state: (0..line_count).map(|_| None).collect(),
+            highlighter: Rc::new(RustHighlighter),
+        }
+    }
+
+    /// Highlights with `highlighter` from now on. Every line is highlighted again on the next
+    /// update.
+    pub fn set_highlighter(&mut self, highlighter: Rc<dyn Highlighter>) {
+        self.highlighter = highlighter;
+        for state in &mut self.state {
+            *state = None;
+        }
+    }
+
+    /// Tokenizes with declarative rules instead of the built in Rust tokenizer, or with the
+    /// built in one again for `None`. Every line is tokenized again on the next update.
+    pub fn set_rules(&mut self, rules: Option<Rc<TokenizerRules>>) {
+        match rules {
+            Some(rules) => self.set_highlighter(rules),
+            None => self.set_highlighter(Rc::new(RustHighlighter)),
         }
     }
 
@@ -38,25 +99,36 @@ impl Tokenizer {
         }
     }
 
+    /// Highlights the lines that changed since the last update, and the lines after them that
+    /// now start in another state.
     pub fn update(&mut self, text: &Text, tokens: &mut [Vec<Token>]) {
-        let mut state = State::default();
-        for line in 0..text.as_lines().len() {
+        let Some(first_line) = self.state.iter().position(|state| state.is_none()) else {
+            return;
+        };
+        let last_line = self
+            .state
+            .iter()
+            .rposition(|state| state.is_none())
+            .unwrap();
+        let mut state = match first_line {
+            0 => self.highlighter.initial_state(),
+            _ => self.state[first_line - 1].unwrap().1,
+        };
+        for line in first_line..text.as_lines().len() {
             match self.state[line] {
                 Some((start_state, end_state)) if state == start_state => {
+                    if line > last_line {
+                        // the lines after this one are as they were
+                        break;
+                    }
                     state = end_state;
                 }
                 _ => {
                     let start_state = state;
-                    let mut new_tokens = Vec::new();
-                    let mut cursor = Cursor::new(&text.as_lines()[line]);
-                    loop {
-                        let (next_state, token) = state.next(&mut cursor);
-                        state = next_state;
-                        match token {
-                            Some(token) => new_tokens.push(token),
-                            None => break,
-                        }
-                    }
+                    let (new_tokens, end_state) = self
+                        .highlighter
+                        .highlight_line(start_state, &text.as_lines()[line]);
+                    state = end_state;
                     self.state[line] = Some((start_state, state));
                     tokens[line] = new_tokens;
                 }
@@ -71,6 +143,9 @@ pub enum State {
     BlockCommentTail(BlockCommentTailState),
     DoubleQuotedStringTail(DoubleQuotedStringTailState),
     RawDoubleQuotedStringTail(RawDoubleQuotedStringTailState),
+    /// The state of a `Highlighter` other than the built in one, like the index of the state
+    /// `TokenizerRules` are in.
+    Custom(usize),
 }
 
 impl Default for State {
@@ -90,6 +165,7 @@ impl State {
             State::BlockCommentTail(state) => state.next(cursor),
             State::DoubleQuotedStringTail(state) => state.next(cursor),
             State::RawDoubleQuotedStringTail(state) => state.next(cursor),
+            State::Custom(_) => unreachable!(),
         };
         let end = cursor.index;
         assert!(start < end);
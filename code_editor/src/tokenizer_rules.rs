@@ -0,0 +1,208 @@
+use {
+    crate::{
+        regex::Regex,
+        token::{Token, TokenKind},
+        tokenizer::{Highlighter, State},
+    },
+    makepad_toml_parser::{parse_toml, Toml},
+};
+
+/// Syntax highlighting for a file type, declared as ordered lists of regex to token kind rules
+/// instead of written as a tokenizer in Rust.
+///
+/// The rules are grouped in states. Every line is tokenized starting in the state the line
+/// before ended in, so strings and comments can span lines by switching to a state of their own
+/// and back. At each position the first rule of the current state that matches wins. Whitespace
+/// that no rule matches becomes whitespace, anything else unknown.
+///
+/// The rules are loaded from TOML like this:
+///
+/// ```toml
+/// name = "ini"
+/// extensions = ["ini", "cfg"]
+/// # files start in the first state
+/// states = ["initial", "string"]
+///
+/// [initial]
+/// rules = [
+///     ["comment", ";.*"],
+///     ["typename", "^\\[[^\\]]*\\]"],
+///     # a third entry switches to another state after the match
+///     ["string", "\"", "string"],
+///     ["identifier", "\\w+"],
+///     ["punctuator", "="],
+/// ]
+///
+/// [string]
+/// rules = [
+///     ["string", "\\\\."],
+///     ["string", "\"", "initial"],
+///     ["string", "[^\"\\\\]+"],
+/// ]
+/// ```
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct TokenizerRules {
+    pub name: String,
+    pub extensions: Vec<String>,
+    states: Vec<Vec<TokenizerRule>>,
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+struct TokenizerRule {
+    kind: TokenKind,
+    regex: Regex,
+    next_state: Option<usize>,
+}
+
+fn token_kind_from_name(name: &str) -> Option<TokenKind> {
+    Some(match name {
+        "unknown" => TokenKind::Unknown,
+        "branch_keyword" => TokenKind::BranchKeyword,
+        "comment" => TokenKind::Comment,
+        "constant" => TokenKind::Constant,
+        "delimiter" => TokenKind::Delimiter,
+        "identifier" => TokenKind::Identifier,
+        "loop_keyword" => TokenKind::LoopKeyword,
+        "keyword" => TokenKind::OtherKeyword,
+        "number" => TokenKind::Number,
+        "punctuator" => TokenKind::Punctuator,
+        "typename" => TokenKind::Typename,
+        "function" => TokenKind::Function,
+        "string" => TokenKind::String,
+        "whitespace" => TokenKind::Whitespace,
+        _ => return None,
+    })
+}
+
+fn strings(value: Option<Toml>, key: &str) -> Result<Vec<String>, String> {
+    match value {
+        Some(Toml::Array(values)) => values
+            .into_iter()
+            .map(|value| {
+                value
+                    .into_str()
+                    .ok_or(format!("{} must only contain strings", key))
+            })
+            .collect(),
+        Some(_) => Err(format!("{} must be an array of strings", key)),
+        None => Ok(Vec::new()),
+    }
+}
+
+impl TokenizerRules {
+    pub fn from_toml(data: &str) -> Result<Self, String> {
+        let mut toml = parse_toml(data).map_err(|err| format!("{:?}", err))?;
+        let name = toml
+            .remove("name")
+            .and_then(|name| name.into_str())
+            .ok_or("name is missing")?;
+        let extensions = strings(toml.remove("extensions"), "extensions")?;
+        let state_names = strings(toml.remove("states"), "states")?;
+        if state_names.is_empty() {
+            return Err("states is missing".to_string());
+        }
+        let mut states = Vec::new();
+        for state_name in &state_names {
+            let key = format!("{}.rules", state_name);
+            let Some(Toml::Array(rules)) = toml.remove(&key) else {
+                return Err(format!("{} is missing", key));
+            };
+            let mut state = Vec::new();
+            for rule in rules {
+                let Toml::Array(rule) = rule else {
+                    return Err(format!("the rules in {} must be arrays", key));
+                };
+                let mut rule = rule.into_iter().map(|value| value.into_str());
+                let (Some(Some(kind)), Some(Some(pattern))) = (rule.next(), rule.next()) else {
+                    return Err(format!(
+                        "the rules in {} must start with a kind and a pattern",
+                        key
+                    ));
+                };
+                let next_state = match rule.next() {
+                    Some(Some(next)) => Some(
+                        state_names
+                            .iter()
+                            .position(|name| *name == next)
+                            .ok_or(format!("unknown state {}", next))?,
+                    ),
+                    Some(None) => return Err(format!("the states in {} must be strings", key)),
+                    None => None,
+                };
+                state.push(TokenizerRule {
+                    kind: token_kind_from_name(&kind)
+                        .ok_or(format!("unknown token kind {}", kind))?,
+                    regex: Regex::new(&pattern).map_err(|err| format!("{}: {}", pattern, err))?,
+                    next_state,
+                });
+            }
+            states.push(state);
+        }
+        Ok(Self {
+            name,
+            extensions,
+            states,
+        })
+    }
+
+    /// Tokenizes a line that starts in `state`, and returns the state it ends in.
+    pub fn tokenize_line(&self, mut state: usize, line: &str) -> (Vec<Token>, usize) {
+        let mut tokens: Vec<Token> = Vec::new();
+        let mut index = 0;
+        let mut after_unmatched = false;
+        while index < line.len() {
+            let found = self.states[state].iter().find_map(|rule| {
+                // an empty match would never get anywhere
+                let end = rule
+                    .regex
+                    .match_at(line, index)
+                    .filter(|end| *end > index)?;
+                Some((end, rule))
+            });
+            if let Some((end, rule)) = found {
+                tokens.push(Token {
+                    len: end - index,
+                    kind: rule.kind,
+                });
+                if let Some(next_state) = rule.next_state {
+                    state = next_state;
+                }
+                index = end;
+                after_unmatched = false;
+                continue;
+            }
+            let char = line[index..].chars().next().unwrap();
+            let kind = if char.is_whitespace() {
+                TokenKind::Whitespace
+            } else {
+                TokenKind::Unknown
+            };
+            match tokens.last_mut() {
+                // a run of chars that no rule matches is one token
+                Some(last) if after_unmatched && last.kind == kind => last.len += char.len_utf8(),
+                _ => tokens.push(Token {
+                    len: char.len_utf8(),
+                    kind,
+                }),
+            }
+            index += char.len_utf8();
+            after_unmatched = true;
+        }
+        (tokens, state)
+    }
+}
+
+impl Highlighter for TokenizerRules {
+    fn initial_state(&self) -> State {
+        State::Custom(0)
+    }
+
+    fn highlight_line(&self, state: State, line: &str) -> (Vec<Token>, State) {
+        let state = match state {
+            State::Custom(state) => state,
+            _ => 0,
+        };
+        let (tokens, state) = self.tokenize_line(state, line);
+        (tokens, State::Custom(state))
+    }
+}
@@ -0,0 +1,778 @@
+use crate::text::Position;
+
+/// The mode of the Vim emulation.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum VimMode {
+    #[default]
+    Normal,
+    Insert,
+    Visual,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Motion {
+    Left,
+    Right,
+    Up,
+    Down,
+    /// `w`, to the start of the next word.
+    WordStart,
+    /// `b`, to the start of the previous word.
+    WordBackward,
+    /// `e`, to the end of the next word.
+    WordEnd,
+    /// `0`
+    LineStart,
+    /// `$`
+    LineEnd,
+    /// `gg` and `G`, to the first non-blank of the line with this number, or of the last line
+    /// for `None`.
+    GotoLine(Option<usize>),
+}
+
+impl Motion {
+    /// Whether an operator with this motion works on whole lines.
+    pub fn is_linewise(self) -> bool {
+        matches!(self, Self::Up | Self::Down | Self::GotoLine(_))
+    }
+
+    /// Whether an operator with this motion includes the char the motion ends on.
+    pub fn is_inclusive(self) -> bool {
+        matches!(self, Self::WordEnd | Self::LineEnd)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Operator {
+    Delete,
+    Change,
+    Yank,
+}
+
+/// Where `i`, `a`, `I`, `A`, `o` and `O` start inserting.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum InsertAt {
+    Cursor,
+    AfterCursor,
+    LineStart,
+    LineEnd,
+    LineBelow,
+    LineAbove,
+}
+
+/// What a key typed in normal or visual mode asks the editor to do. The mode it leaves the
+/// emulation in is already set when it's returned.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum VimCommand {
+    Move {
+        motion: Motion,
+        count: usize,
+    },
+    Operate {
+        operator: Operator,
+        motion: Motion,
+        count: usize,
+    },
+    /// `dd`, `cc` and `yy`.
+    OperateLines {
+        operator: Operator,
+        count: usize,
+    },
+    /// An operator typed in visual mode, on the selection and the char under the cursor.
+    OperateSelection {
+        operator: Operator,
+    },
+    Insert(InsertAt),
+    /// Puts the register after the cursor, or before it for `P`.
+    Put {
+        before: bool,
+        count: usize,
+    },
+    Undo {
+        count: usize,
+    },
+    Redo {
+        count: usize,
+    },
+    EnterVisual,
+    LeaveVisual,
+    LeaveInsert,
+    /// `.`, which runs the last change again and then types the text that was typed after it.
+    Repeat(Box<Change>),
+}
+
+impl VimCommand {
+    fn with_count(self, count: usize) -> Self {
+        match self {
+            Self::Move { motion, .. } => Self::Move { motion, count },
+            Self::Operate {
+                operator, motion, ..
+            } => Self::Operate {
+                operator,
+                motion,
+                count,
+            },
+            Self::OperateLines { operator, .. } => Self::OperateLines { operator, count },
+            Self::Put { before, .. } => Self::Put { before, count },
+            command => command,
+        }
+    }
+
+    /// Whether the command changes the text, or leaves the editor in insert mode to do so.
+    pub fn is_edit(&self) -> bool {
+        match self {
+            Self::Operate { operator, .. }
+            | Self::OperateLines { operator, .. }
+            | Self::OperateSelection { operator } => *operator != Operator::Yank,
+            Self::Insert(_)
+            | Self::Put { .. }
+            | Self::Undo { .. }
+            | Self::Redo { .. }
+            | Self::Repeat(_) => true,
+            _ => false,
+        }
+    }
+
+    /// Whether the command leaves the editor in insert mode.
+    pub fn enters_insert(&self) -> bool {
+        match self {
+            Self::Operate { operator, .. }
+            | Self::OperateLines { operator, .. }
+            | Self::OperateSelection { operator } => *operator == Operator::Change,
+            Self::Insert(_) => true,
+            _ => false,
+        }
+    }
+
+    /// Whether `.` repeats the command.
+    fn is_change(&self) -> bool {
+        match self {
+            Self::Operate { operator, .. } | Self::OperateLines { operator, .. } => {
+                *operator != Operator::Yank
+            }
+            Self::Insert(_) | Self::Put { .. } => true,
+            _ => false,
+        }
+    }
+}
+
+/// A change `.` can repeat: the command, and the text typed in the insert mode it entered.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Change {
+    pub command: VimCommand,
+    pub text: String,
+}
+
+/// The contents of the unnamed register.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Register {
+    pub text: String,
+    /// Whether it holds whole lines, which are put on their own lines.
+    pub is_linewise: bool,
+}
+
+/// The state of the Vim emulation: the mode, the keys typed so far of a command, the register
+/// and the last change for `.`. It turns typed chars into [`VimCommand`]s, which the editor
+/// runs on its session, so everything they do can be undone like any other edit.
+#[derive(Clone, Debug, Default)]
+pub struct Vim {
+    mode: VimMode,
+    count: Option<usize>,
+    operator: Option<(Operator, Option<usize>)>,
+    is_g_pending: bool,
+    register: Register,
+    last_change: Option<Change>,
+    /// The change that entered insert mode, and the text typed since.
+    recording: Option<Change>,
+}
+
+impl Vim {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn mode(&self) -> VimMode {
+        self.mode
+    }
+
+    /// Whether part of a command was typed.
+    pub fn is_pending(&self) -> bool {
+        self.count.is_some() || self.operator.is_some() || self.is_g_pending
+    }
+
+    pub fn register(&self) -> &Register {
+        &self.register
+    }
+
+    pub fn set_register(&mut self, text: String, is_linewise: bool) {
+        self.register = Register { text, is_linewise };
+    }
+
+    /// Handles a char typed in normal or visual mode.
+    pub fn handle_char(&mut self, char: char) -> Option<VimCommand> {
+        if self.mode == VimMode::Insert {
+            return None;
+        }
+        if let Some(digit) = char.to_digit(10) {
+            if digit != 0 || self.count.is_some() {
+                self.count = Some(
+                    self.count
+                        .unwrap_or(0)
+                        .saturating_mul(10)
+                        .saturating_add(digit as usize),
+                );
+                return None;
+            }
+        }
+        if self.is_g_pending {
+            self.is_g_pending = false;
+            return match char {
+                'g' => {
+                    let line = self.count.take().unwrap_or(1);
+                    self.motion(Motion::GotoLine(Some(line)))
+                }
+                _ => {
+                    self.reset();
+                    None
+                }
+            };
+        }
+        let motion = match char {
+            'h' => Some(Motion::Left),
+            'j' => Some(Motion::Down),
+            'k' => Some(Motion::Up),
+            'l' => Some(Motion::Right),
+            'w' => Some(Motion::WordStart),
+            'b' => Some(Motion::WordBackward),
+            'e' => Some(Motion::WordEnd),
+            '0' => Some(Motion::LineStart),
+            '$' => Some(Motion::LineEnd),
+            'G' => Some(Motion::GotoLine(self.count.take())),
+            _ => None,
+        };
+        if let Some(motion) = motion {
+            return self.motion(motion);
+        }
+        let operator = match char {
+            'd' => Some(Operator::Delete),
+            'c' => Some(Operator::Change),
+            'y' => Some(Operator::Yank),
+            _ => None,
+        };
+        if let Some(operator) = operator {
+            return self.operator(operator);
+        }
+        if char == 'g' {
+            self.is_g_pending = true;
+            return None;
+        }
+        if self.operator.is_some() {
+            // not a motion, so the operator is cancelled
+            self.reset();
+            return None;
+        }
+        let count = self.count.take().unwrap_or(1);
+        let command = match (self.mode, char) {
+            (VimMode::Normal, 'x') => VimCommand::Operate {
+                operator: Operator::Delete,
+                motion: Motion::Right,
+                count,
+            },
+            (VimMode::Normal, 'X') => VimCommand::Operate {
+                operator: Operator::Delete,
+                motion: Motion::Left,
+                count,
+            },
+            (VimMode::Normal, 'D') => VimCommand::Operate {
+                operator: Operator::Delete,
+                motion: Motion::LineEnd,
+                count,
+            },
+            (VimMode::Normal, 'C') => VimCommand::Operate {
+                operator: Operator::Change,
+                motion: Motion::LineEnd,
+                count,
+            },
+            (VimMode::Normal, 'i') => VimCommand::Insert(InsertAt::Cursor),
+            (VimMode::Normal, 'a') => VimCommand::Insert(InsertAt::AfterCursor),
+            (VimMode::Normal, 'I') => VimCommand::Insert(InsertAt::LineStart),
+            (VimMode::Normal, 'A') => VimCommand::Insert(InsertAt::LineEnd),
+            (VimMode::Normal, 'o') => VimCommand::Insert(InsertAt::LineBelow),
+            (VimMode::Normal, 'O') => VimCommand::Insert(InsertAt::LineAbove),
+            (VimMode::Normal, 'p') => VimCommand::Put {
+                before: false,
+                count,
+            },
+            (VimMode::Normal, 'P') => VimCommand::Put {
+                before: true,
+                count,
+            },
+            (VimMode::Normal, 'u') => VimCommand::Undo { count },
+            (VimMode::Normal, 'v') => VimCommand::EnterVisual,
+            (VimMode::Normal, '.') => {
+                let mut change = self.last_change.clone()?;
+                if count != 1 {
+                    change.command = change.command.with_count(count);
+                    self.last_change = Some(change.clone());
+                }
+                return Some(VimCommand::Repeat(Box::new(change)));
+            }
+            (VimMode::Visual, 'x') => VimCommand::OperateSelection {
+                operator: Operator::Delete,
+            },
+            (VimMode::Visual, 'v') => VimCommand::LeaveVisual,
+            _ => return None,
+        };
+        Some(self.finish(command))
+    }
+
+    /// Handles a char typed with control held in normal mode, for `Ctrl-R`.
+    pub fn handle_control_char(&mut self, char: char) -> Option<VimCommand> {
+        if self.mode != VimMode::Normal || char != 'r' {
+            return None;
+        }
+        let count = self.count.take().unwrap_or(1);
+        self.reset();
+        Some(VimCommand::Redo { count })
+    }
+
+    /// Handles escape, which cancels a pending command or returns to normal mode.
+    pub fn escape(&mut self) -> Option<VimCommand> {
+        self.reset();
+        match self.mode {
+            VimMode::Normal => None,
+            VimMode::Insert => {
+                self.mode = VimMode::Normal;
+                self.last_change = self.recording.take().or(self.last_change.take());
+                Some(VimCommand::LeaveInsert)
+            }
+            VimMode::Visual => {
+                self.mode = VimMode::Normal;
+                Some(VimCommand::LeaveVisual)
+            }
+        }
+    }
+
+    /// Records text typed in insert mode, so `.` can type it again.
+    pub fn record_text(&mut self, text: &str) {
+        if let Some(recording) = &mut self.recording {
+            recording.text.push_str(text);
+        }
+    }
+
+    /// Records a backspace typed in insert mode.
+    pub fn record_backspace(&mut self) {
+        if let Some(recording) = &mut self.recording {
+            recording.text.pop();
+        }
+    }
+
+    fn motion(&mut self, motion: Motion) -> Option<VimCommand> {
+        let count = self.count.take();
+        let command = match self.operator.take() {
+            Some((operator, operator_count)) => VimCommand::Operate {
+                operator,
+                motion,
+                count: operator_count.unwrap_or(1) * count.unwrap_or(1),
+            },
+            None => VimCommand::Move {
+                motion,
+                count: count.unwrap_or(1),
+            },
+        };
+        Some(self.finish(command))
+    }
+
+    fn operator(&mut self, operator: Operator) -> Option<VimCommand> {
+        if self.mode == VimMode::Visual {
+            self.reset();
+            return Some(self.finish(VimCommand::OperateSelection { operator }));
+        }
+        let count = self.count.take();
+        match self.operator.take() {
+            Some((pending, pending_count)) if pending == operator => {
+                let command = VimCommand::OperateLines {
+                    operator,
+                    count: pending_count.unwrap_or(1) * count.unwrap_or(1),
+                };
+                Some(self.finish(command))
+            }
+            Some(_) => None,
+            None => {
+                self.operator = Some((operator, count));
+                None
+            }
+        }
+    }
+
+    /// Updates the mode and the last change for a command that is about to be run.
+    fn finish(&mut self, command: VimCommand) -> VimCommand {
+        self.reset();
+        match &command {
+            VimCommand::EnterVisual => self.mode = VimMode::Visual,
+            VimCommand::OperateSelection { .. } | VimCommand::LeaveVisual => {
+                self.mode = VimMode::Normal
+            }
+            _ => {}
+        }
+        if command.enters_insert() {
+            self.mode = VimMode::Insert;
+            self.recording = command.is_change().then(|| Change {
+                command: command.clone(),
+                text: String::new(),
+            });
+        } else if command.is_change() {
+            self.last_change = Some(Change {
+                command: command.clone(),
+                text: String::new(),
+            });
+        }
+        command
+    }
+
+    fn reset(&mut self) {
+        self.count = None;
+        self.operator = None;
+        self.is_g_pending = false;
+    }
+}
+
+/// Where `motion` moves the cursor from `position` to, `count` times, in normal mode.
+pub fn motion_target(
+    lines: &[String],
+    position: Position,
+    motion: Motion,
+    count: usize,
+) -> Position {
+    let target = raw_motion_target(lines, position, motion, count);
+    match motion {
+        // the cursor stays on the last char of a line in normal mode
+        Motion::Right | Motion::WordStart | Motion::LineEnd => Position {
+            line_index: target.line_index,
+            byte_index: target
+                .byte_index
+                .min(last_char_index(&lines[target.line_index])),
+        },
+        _ => target,
+    }
+}
+
+/// The text an operator with `motion` works on, from `position`, and whether it is whole lines.
+pub fn operator_range(
+    lines: &[String],
+    position: Position,
+    motion: Motion,
+    count: usize,
+    operator: Operator,
+) -> (Position, Position, bool) {
+    if motion.is_linewise() {
+        let target = raw_motion_target(lines, position, motion, count);
+        let start_line = position.line_index.min(target.line_index);
+        let end_line = position.line_index.max(target.line_index);
+        let (start, end) = line_range(lines, start_line, end_line - start_line + 1, operator);
+        return (start, end, true);
+    }
+    // like in Vim, `cw` changes the rest of the word but not the blanks after it
+    let motion = if operator == Operator::Change
+        && motion == Motion::WordStart
+        && !char_at(lines, position).is_whitespace()
+    {
+        Motion::WordEnd
+    } else {
+        motion
+    };
+    let mut target = raw_motion_target(lines, position, motion, count);
+    if motion == Motion::WordStart && target.line_index > position.line_index {
+        // a word motion that ends on a later line stops at the end of the line before
+        let line_index = (position.line_index..target.line_index)
+            .rev()
+            .find(|&line_index| {
+                line_index == position.line_index || !lines[line_index].trim().is_empty()
+            })
+            .unwrap();
+        target = Position {
+            line_index,
+            byte_index: lines[line_index].len(),
+        };
+    }
+    if motion.is_inclusive() {
+        target = next_position(lines, target).unwrap_or(Position {
+            line_index: target.line_index,
+            byte_index: lines[target.line_index].len(),
+        });
+    }
+    let (start, end) = if target < position {
+        (target, position)
+    } else {
+        (position, target)
+    };
+    (start, end, false)
+}
+
+/// The range of `count` whole lines from `line_index`. For a delete it includes a line break,
+/// so the lines are gone, but for a change it keeps one, leaving an empty line to type on.
+pub fn line_range(
+    lines: &[String],
+    line_index: usize,
+    count: usize,
+    operator: Operator,
+) -> (Position, Position) {
+    let last_line_index = lines.len() - 1;
+    let end_line_index = (line_index + count.max(1) - 1).min(last_line_index);
+    let line_end = Position {
+        line_index: end_line_index,
+        byte_index: lines[end_line_index].len(),
+    };
+    match operator {
+        Operator::Change => (
+            Position {
+                line_index,
+                byte_index: 0,
+            },
+            line_end,
+        ),
+        _ if end_line_index < last_line_index => (
+            Position {
+                line_index,
+                byte_index: 0,
+            },
+            Position {
+                line_index: end_line_index + 1,
+                byte_index: 0,
+            },
+        ),
+        Operator::Delete if line_index > 0 => (
+            Position {
+                line_index: line_index - 1,
+                byte_index: lines[line_index - 1].len(),
+            },
+            line_end,
+        ),
+        _ => (
+            Position {
+                line_index,
+                byte_index: 0,
+            },
+            line_end,
+        ),
+    }
+}
+
+/// The byte index of the first char of the line that isn't blank.
+pub fn first_non_blank_index(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
+/// The byte index just past the char at `byte_index`, or the end of the line.
+pub fn next_char_index(line: &str, byte_index: usize) -> usize {
+    line[byte_index..]
+        .chars()
+        .next()
+        .map_or(byte_index, |char| byte_index + char.len_utf8())
+}
+
+/// The byte index of the last char of the line, where the cursor is after `$`.
+pub fn last_char_index(line: &str) -> usize {
+    line.char_indices().last().map_or(0, |(index, _)| index)
+}
+
+fn raw_motion_target(
+    lines: &[String],
+    position: Position,
+    motion: Motion,
+    count: usize,
+) -> Position {
+    let line = &lines[position.line_index];
+    match motion {
+        Motion::Left => {
+            let byte_index = line[..position.byte_index]
+                .char_indices()
+                .rev()
+                .take(count)
+                .last()
+                .map_or(position.byte_index, |(index, _)| index);
+            Position {
+                line_index: position.line_index,
+                byte_index,
+            }
+        }
+        Motion::Right => {
+            let byte_index = line[position.byte_index..]
+                .char_indices()
+                .nth(count)
+                .map_or(line.len(), |(index, _)| position.byte_index + index);
+            Position {
+                line_index: position.line_index,
+                byte_index,
+            }
+        }
+        Motion::Up | Motion::Down => {
+            let line_index = if motion == Motion::Up {
+                position.line_index.saturating_sub(count)
+            } else {
+                (position.line_index + count).min(lines.len() - 1)
+            };
+            let line = &lines[line_index];
+            let mut byte_index = position.byte_index.min(last_char_index(line));
+            while !line.is_char_boundary(byte_index) {
+                byte_index -= 1;
+            }
+            Position {
+                line_index,
+                byte_index,
+            }
+        }
+        Motion::WordStart => (0..count).fold(position, |position, _| word_start(lines, position)),
+        Motion::WordBackward => {
+            (0..count).fold(position, |position, _| word_backward(lines, position))
+        }
+        Motion::WordEnd => (0..count).fold(position, |position, _| word_end(lines, position)),
+        Motion::LineStart => Position {
+            line_index: position.line_index,
+            byte_index: 0,
+        },
+        Motion::LineEnd => {
+            let line_index = (position.line_index + count - 1).min(lines.len() - 1);
+            Position {
+                line_index,
+                byte_index: last_char_index(&lines[line_index]),
+            }
+        }
+        Motion::GotoLine(line) => {
+            let line_index = line.map_or(lines.len(), |line| line.clamp(1, lines.len())) - 1;
+            Position {
+                line_index,
+                byte_index: first_non_blank_index(&lines[line_index]),
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum CharClass {
+    Blank,
+    Word,
+    Punctuation,
+}
+
+fn char_class(char: char) -> CharClass {
+    if char.is_whitespace() {
+        CharClass::Blank
+    } else if char.is_alphanumeric() || char == '_' {
+        CharClass::Word
+    } else {
+        CharClass::Punctuation
+    }
+}
+
+/// The char at `position`, where the end of a line is a line break.
+fn char_at(lines: &[String], position: Position) -> char {
+    lines[position.line_index][position.byte_index..]
+        .chars()
+        .next()
+        .unwrap_or('\n')
+}
+
+fn is_empty_line(lines: &[String], position: Position) -> bool {
+    lines[position.line_index].is_empty()
+}
+
+fn next_position(lines: &[String], position: Position) -> Option<Position> {
+    let line = &lines[position.line_index];
+    match line[position.byte_index..].chars().next() {
+        Some(char) => Some(Position {
+            line_index: position.line_index,
+            byte_index: position.byte_index + char.len_utf8(),
+        }),
+        None if position.line_index + 1 < lines.len() => Some(Position {
+            line_index: position.line_index + 1,
+            byte_index: 0,
+        }),
+        None => None,
+    }
+}
+
+fn prev_position(lines: &[String], position: Position) -> Option<Position> {
+    match lines[position.line_index][..position.byte_index]
+        .char_indices()
+        .next_back()
+    {
+        Some((byte_index, _)) => Some(Position {
+            line_index: position.line_index,
+            byte_index,
+        }),
+        None if position.line_index > 0 => Some(Position {
+            line_index: position.line_index - 1,
+            byte_index: lines[position.line_index - 1].len(),
+        }),
+        None => None,
+    }
+}
+
+fn word_start(lines: &[String], start: Position) -> Position {
+    let class = char_class(char_at(lines, start));
+    let mut position = start;
+    if class != CharClass::Blank {
+        loop {
+            let Some(next) = next_position(lines, position) else {
+                return position;
+            };
+            position = next;
+            if char_class(char_at(lines, position)) != class {
+                break;
+            }
+        }
+    }
+    // an empty line counts as a word
+    while char_class(char_at(lines, position)) == CharClass::Blank
+        && !(is_empty_line(lines, position) && position.line_index != start.line_index)
+    {
+        let Some(next) = next_position(lines, position) else {
+            break;
+        };
+        position = next;
+    }
+    position
+}
+
+fn word_end(lines: &[String], start: Position) -> Position {
+    let Some(mut position) = next_position(lines, start) else {
+        return start;
+    };
+    while char_class(char_at(lines, position)) == CharClass::Blank {
+        let Some(next) = next_position(lines, position) else {
+            return prev_position(lines, position).unwrap_or(position);
+        };
+        position = next;
+    }
+    let class = char_class(char_at(lines, position));
+    while let Some(next) = next_position(lines, position) {
+        if char_class(char_at(lines, next)) != class {
+            break;
+        }
+        position = next;
+    }
+    position
+}
+
+fn word_backward(lines: &[String], start: Position) -> Position {
+    let Some(mut position) = prev_position(lines, start) else {
+        return start;
+    };
+    while char_class(char_at(lines, position)) == CharClass::Blank
+        && !(is_empty_line(lines, position) && position.line_index != start.line_index)
+    {
+        let Some(prev) = prev_position(lines, position) else {
+            return position;
+        };
+        position = prev;
+    }
+    let class = char_class(char_at(lines, position));
+    while let Some(prev) = prev_position(lines, position) {
+        if prev.line_index != position.line_index || char_class(char_at(lines, prev)) != class {
+            break;
+        }
+        position = prev;
+    }
+    position
+}
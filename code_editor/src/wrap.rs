@@ -22,16 +22,35 @@ pub fn compute_wrap_data(line: Line<'_>, wrap_column: usize) -> WrapData {
             InlineElement::Text { text, .. } => {
                 for string in text.split_whitespace_boundaries() {
                     let column_count: usize = string.chars().map(|char| char.column_count()).sum();
-                    if column_index + column_count > wrap_column {
+                    if column_index + column_count > wrap_column
+                        && is_row_started(&wraps, byte_index)
+                    {
                         column_index = indent_column_count;
                         wraps.push(byte_index);
                     }
-                    column_index += column_count;
-                    byte_index += string.len();
+                    if column_index + column_count <= wrap_column {
+                        column_index += column_count;
+                        byte_index += string.len();
+                        continue;
+                    }
+                    // a word that is wider than a whole row is broken between graphemes
+                    for grapheme in string.graphemes() {
+                        let column_count = grapheme.column_count();
+                        if column_index + column_count > wrap_column
+                            && is_row_started(&wraps, byte_index)
+                        {
+                            column_index = indent_column_count;
+                            wraps.push(byte_index);
+                        }
+                        column_index += column_count;
+                        byte_index += grapheme.len();
+                    }
                 }
             }
             InlineElement::Widget(widget) => {
-                if column_index + widget.column_count > wrap_column {
+                if column_index + widget.column_count > wrap_column
+                    && is_row_started(&wraps, byte_index)
+                {
                     column_index = indent_column_count;
                     wraps.push(byte_index);
                 }
@@ -45,3 +64,9 @@ pub fn compute_wrap_data(line: Line<'_>, wrap_column: usize) -> WrapData {
         indent_column_count,
     }
 }
+
+/// Whether the row that `byte_index` is in has anything on it before `byte_index`, as wrapping
+/// before that would leave the row empty.
+fn is_row_started(wraps: &[usize], byte_index: usize) -> bool {
+    byte_index > wraps.last().copied().unwrap_or(0)
+}
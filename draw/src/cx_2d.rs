@@ -36,6 +36,8 @@ pub struct Cx2d<'a> {
     pub (crate) draw_event: &'a DrawEvent,
     pub (crate) pass_stack: Vec<PassStackItem>,
     pub (crate) overlay_id: Option<DrawListId>,
+    /// The overlays that were active when the nested overlays began.
+    pub (crate) outer_overlay_ids: Vec<Option<DrawListId>>,
     //pub (crate) overlay_sweep_lock: Option<Rc<RefCell<Area>>>,
     pub draw_list_stack: Vec<DrawListId>,
     pub (crate) turtles: Vec<Turtle>,
@@ -80,6 +82,7 @@ impl<'a> Cx2d<'a> {
         let icon_atlas_rc = cx.get_global::<CxIconAtlasRc>().clone();
         Self {
             overlay_id: None,
+            outer_overlay_ids: Vec::new(),
             fonts_atlas_rc,
             cx: cx,
             draw_event,
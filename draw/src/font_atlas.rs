@@ -160,7 +160,81 @@ impl LiveHook for Font {
     }
 }
 
+/// Loads fonts at runtime, next to the ones referenced as dependencies from the DSL.
+/// The `name` acts as the font's path, so a `Font` with that path in the DSL picks it up too.
+pub trait CxFontsApi {
+    fn load_font_from_bytes(&mut self, name: &str, data: Vec<u8>) -> Result<Font, String>;
+    fn load_font_from_path(&mut self, path: &str) -> Result<Font, String>;
+    fn font_metrics(&mut self, font: &Font) -> Option<FontMetrics>;
+}
+
+/// Font wide metrics in font units, see `scale` to turn them into logical pixels.
+#[derive(Clone, Copy, Debug)]
+pub struct FontMetrics {
+    pub units_per_em: f64,
+    pub ascender: f64,
+    pub descender: f64,
+    pub line_gap: f64,
+}
+
+impl FontMetrics {
+    pub fn scale(&self, font_size: f64) -> f64 {
+        font_size * 96.0 / (72.0 * self.units_per_em)
+    }
+    
+    pub fn line_height(&self, font_size: f64) -> f64 {
+        (self.ascender - self.descender + self.line_gap) * self.scale(font_size)
+    }
+}
+
+impl CxFontsApi for Cx {
+    fn load_font_from_bytes(&mut self, name: &str, data: Vec<u8>) -> Result<Font, String> {
+        Cx2d::lazy_construct_font_atlas(self);
+        let atlas = self.get_global::<CxFontsAtlasRc>().clone();
+        let font_id = atlas.0.borrow_mut().load_font_from_bytes(name, Rc::new(data)) ?;
+        // a font that replaced another under the same name needs its glyphs redrawn
+        self.redraw_all();
+        Ok(Font {
+            font_id: Some(font_id),
+            path: LiveDependency::new(name)
+        })
+    }
+    
+    fn load_font_from_path(&mut self, path: &str) -> Result<Font, String> {
+        let data = std::fs::read(path).map_err( | err | format!("Cannot read font {} {}", path, err)) ?;
+        self.load_font_from_bytes(path, data)
+    }
+    
+    fn font_metrics(&mut self, font: &Font) -> Option<FontMetrics> {
+        if !self.has_global::<CxFontsAtlasRc>() {
+            return None
+        }
+        let atlas = self.get_global::<CxFontsAtlasRc>().0.borrow_mut();
+        let cxfont = atlas.fonts.get(font.font_id?)?.as_ref()?;
+        Some(FontMetrics {
+            units_per_em: cxfont.ttf_font.units_per_em,
+            ascender: cxfont.ttf_font.ascender,
+            descender: cxfont.ttf_font.descender,
+            line_gap: cxfont.ttf_font.line_gap,
+        })
+    }
+}
+
 impl CxFontsAtlas {
+    pub fn load_font_from_bytes(&mut self, name: &str, data: Rc<Vec<u8>>) -> Result<usize, String> {
+        let cxfont = CxFont::load_from_ttf_bytes(data).map_err( | err | format!("Error loading font {} {:?}", name, err)) ?;
+        if let Some(font_id) = self.path_to_font_id.get(name).cloned() {
+            // the old glyphs are still in the atlas, so start it over
+            self.fonts[font_id] = Some(cxfont);
+            self.reset_fonts_atlas();
+            return Ok(font_id)
+        }
+        let font_id = self.fonts.len();
+        self.fonts.push(Some(cxfont));
+        self.path_to_font_id.insert(name.to_string(), font_id);
+        Ok(font_id)
+    }
+    
     pub fn get_font_by_path(&mut self, cx: &mut Cx, path: &str) -> usize {
         if path.len() == 0{
             return 0
@@ -396,7 +470,19 @@ pub struct CxFont {
 
 pub struct ShapeCache {
     pub keys: VecDeque<(Direction, Rc<str>)>,
-    pub glyph_ids: HashMap<(Direction, Rc<str>), Vec<usize>>,
+    pub glyphs: HashMap<(Direction, Rc<str>), Vec<ShapedGlyph>>,
+}
+
+// A glyph as placed by the shaper, with its advance and offset in font units. Ligatures show up
+// as one glyph for several chars and combining marks as zero advance glyphs offset onto their
+// base, so these have to be used instead of the glyph's own metrics.
+#[derive(Clone, Copy, Debug)]
+pub struct ShapedGlyph {
+    pub id: usize,
+    // The byte index in the shaped string of the first char of the cluster this glyph belongs to.
+    pub cluster: usize,
+    pub advance: f64,
+    pub offset: DVec2,
 }
 
 impl ShapeCache {
@@ -406,14 +492,14 @@ impl ShapeCache {
     pub fn new() -> Self {
         Self {
             keys: VecDeque::new(),
-            glyph_ids: HashMap::new(),
+            glyphs: HashMap::new(),
         }
     }
 
     // If there is an entry for the given key in the cache, returns the corresponding list of
-    // glyph indices for that key. Otherwise, uses the given UnicodeBuffer and OwnedFace to
-    // compute the list of glyph indices for the key, inserts that in the cache and then returns
-    // the corresponding list.
+    // shaped glyphs for that key. Otherwise, uses the given UnicodeBuffer and OwnedFace to
+    // shape the key, inserts the glyphs in the cache and then returns the corresponding list.
+    // The glyphs are in visual order, so for right-to-left text the clusters go backwards.
     //
     // This method takes a UnicodeBuffer by value, and then returns the same buffer by value. This
     // is necessary because rustybuzz::shape consumes the UnicodeBuffer and then returns a
@@ -423,16 +509,16 @@ impl ShapeCache {
     //
     // Note that owned_font_face should be the same as the CxFont to which this cache belongs,
     // otherwise you will not get correct results.
-    pub fn get_or_compute_glyph_ids(
+    pub fn get_or_compute_glyphs(
         &mut self, 
         key: (Direction, &str),
         mut rustybuzz_buffer: UnicodeBuffer,
         owned_font_face: &crate::owned_font_face::OwnedFace
-    ) -> (&[usize], UnicodeBuffer) {
-        if !self.glyph_ids.contains_key(&key as &dyn ShapeCacheKey) {
+    ) -> (&[ShapedGlyph], UnicodeBuffer) {
+        if !self.glyphs.contains_key(&key as &dyn ShapeCacheKey) {
             if self.keys.len() == Self::MAX_SIZE {
                 for run in self.keys.drain(..Self::MAX_SIZE / 2) {
-                    self.glyph_ids.remove(&run);
+                    self.glyphs.remove(&run);
                 }
             }
 
@@ -440,14 +526,19 @@ impl ShapeCache {
             rustybuzz_buffer.set_direction(direction);
             rustybuzz_buffer.push_str(string);
             let glyph_buffer = owned_font_face.with_ref( | face | makepad_rustybuzz::shape(face, &[], rustybuzz_buffer));
-            let glyph_ids: Vec<_> = glyph_buffer.glyph_infos().iter().map( | glyph | glyph.glyph_id as usize).collect();
+            let glyphs: Vec<_> = glyph_buffer.glyph_infos().iter().zip(glyph_buffer.glyph_positions()).map( | (info, pos) | ShapedGlyph {
+                id: info.glyph_id as usize,
+                cluster: info.cluster as usize,
+                advance: pos.x_advance as f64,
+                offset: dvec2(pos.x_offset as f64, pos.y_offset as f64),
+            }).collect();
             rustybuzz_buffer = glyph_buffer.clear();
 
             let owned_string: Rc<str> = string.into();
             self.keys.push_back((direction, owned_string.clone()));
-            self.glyph_ids.insert((direction, owned_string), glyph_ids);
+            self.glyphs.insert((direction, owned_string), glyphs);
         }
-        (&self.glyph_ids[&key as &dyn ShapeCacheKey], rustybuzz_buffer)
+        (&self.glyphs[&key as &dyn ShapeCacheKey], rustybuzz_buffer)
     }
 }
 
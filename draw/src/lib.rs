@@ -12,11 +12,12 @@ pub mod font_atlas;
 pub mod geometry;
 pub mod nav;
 pub mod icon_atlas;
+pub mod picking;
 mod owned_font_face;
  
 pub use crate::{
     match_event::MatchEvent, 
-    font_atlas::Font,
+    font_atlas::{Font, FontMetrics, CxFontsApi},
     turtle::{
         Layout,
         Walk,
@@ -37,6 +38,11 @@ pub use crate::{
         NavItem,
         NavScrollIndex
     },
+    picking::{
+        PickingBuffer,
+        PickResult,
+        PickTrigger,
+    },
     draw_list_2d::{
         DrawList2d,
         ManyInstances,
@@ -48,6 +48,7 @@ pub trait MatchEvent{
     fn handle_key_down(&mut self, _cx: &mut Cx, _e:&KeyEvent){}
     fn handle_key_up(&mut self, _cx: &mut Cx, _e:&KeyEvent){}
     fn handle_back_pressed(&mut self, _cx: &mut Cx){}
+    fn handle_navigate(&mut self, _cx: &mut Cx, _e:&NavigateEvent){}
 
     fn match_event(&mut self, cx:&mut Cx, event:&Event){
         match event{
@@ -71,6 +72,7 @@ pub trait MatchEvent{
             Event::KeyDown(e)=>self.handle_key_down(cx, e),
             Event::KeyUp(e)=>self.handle_key_up(cx, e),
             Event::BackPressed=>self.handle_back_pressed(cx),
+            Event::Navigate(e)=>self.handle_navigate(cx, e),
             _=>()
         }
     }
@@ -58,13 +58,14 @@ impl Overlay {
     }
     
     pub fn begin(&self, cx:&mut Cx2d){
-        // mark our overlay_id on cx
-        cx.overlay_id = Some(self.draw_list.id());
+        // mark our overlay_id on cx, keeping the one we're nested in
+        let outer_overlay_id = cx.overlay_id.replace(self.draw_list.id());
+        cx.outer_overlay_ids.push(outer_overlay_id);
        // cx.overlay_sweep_lock = Some(self.sweep_lock.clone());
     }
     
     pub fn end(&self, cx:&mut Cx2d){
-        cx.overlay_id = None;
+        cx.overlay_id = cx.outer_overlay_ids.pop().flatten();
         let parent_id = cx.draw_list_stack.last().cloned().unwrap();
         let redraw_id = cx.redraw_id;
         cx.draw_lists[parent_id].append_sub_list(redraw_id, self.draw_list.id());
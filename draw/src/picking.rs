@@ -0,0 +1,129 @@
+use {
+    crate::{
+        makepad_platform::*,
+        cx_2d::Cx2d,
+        draw_list_2d::DrawList2d,
+        turtle::Layout,
+    }
+};
+
+/// What asked for a pick.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PickTrigger {
+    Hover,
+    Down,
+}
+
+/// The primitive under a position, as read back from the id buffer.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PickResult {
+    pub abs: DVec2,
+    /// The id the primitive was drawn with, `None` if nothing was drawn there.
+    pub id: Option<u32>,
+    pub trigger: PickTrigger,
+}
+
+/// An id buffer for widgets with too many primitives to hit-test on the CPU, like charts with
+/// millions of points or node graphs. The widget draws its primitives a second time between
+/// `begin` and `end`, with shaders that output `PickId::to_color(id)` instead of their color.
+/// A pick reads back the pixel under the finger after the next draw, so the exact primitive is
+/// known without any geometric tests. The result arrives a frame later than the hit.
+pub struct PickingBuffer {
+    pass: Pass,
+    color_texture: Texture,
+    draw_list: DrawList2d,
+    area: Area,
+    pending: Option<(DVec2, PickTrigger)>,
+}
+
+impl PickingBuffer {
+    pub fn new(cx: &mut Cx) -> Self {
+        let pass = Pass::new_with_name(cx, "picking");
+        let color_texture = Texture::new_with_format(cx, TextureFormat::RenderBGRAu8 {
+            size: TextureSize::Auto,
+        });
+        pass.add_color_texture(cx, &color_texture, PassClearColor::ClearWith(vec4(0.0, 0.0, 0.0, 0.0)));
+        Self {
+            pass,
+            color_texture,
+            draw_list: DrawList2d::new(cx),
+            area: Area::Empty,
+            pending: None,
+        }
+    }
+
+    pub fn color_texture(&self) -> &Texture {
+        &self.color_texture
+    }
+
+    /// Starts drawing into the id buffer, which covers `area`. Primitives are drawn at the same
+    /// absolute positions as on screen.
+    pub fn begin(&mut self, cx: &mut Cx2d, area: Area) {
+        self.area = area;
+        cx.make_child_pass(&self.pass);
+        cx.begin_pass(&self.pass, None);
+        cx.set_pass_area(&self.pass, area);
+        self.draw_list.begin_always(cx);
+        cx.begin_pass_sized_turtle_no_clip(Layout::flow_down());
+    }
+
+    pub fn end(&mut self, cx: &mut Cx2d) {
+        cx.end_pass_sized_turtle_no_clip();
+        self.draw_list.end(cx);
+        cx.end_pass(&self.pass);
+    }
+
+    /// Asks for the primitive under `abs`. A pick for a finger down replaces a pending hover,
+    /// but not the other way around.
+    pub fn pick(&mut self, cx: &mut Cx, abs: DVec2, trigger: PickTrigger) {
+        if let Some((_, PickTrigger::Down)) = self.pending {
+            if trigger == PickTrigger::Hover {
+                return
+            }
+        }
+        let origin = self.area.rect(cx).pos.floor();
+        self.pending = Some((abs, trigger));
+        self.pass.request_read_back(cx, Rect {
+            pos: abs - origin,
+            size: dvec2(1.0, 1.0),
+        });
+    }
+
+    /// Picks for the hits the widget got from `event.hits`, as the alternative to testing them
+    /// against its geometry.
+    pub fn handle_hit(&mut self, cx: &mut Cx, hit: &Hit) {
+        match hit {
+            Hit::FingerHoverIn(fe) | Hit::FingerHoverOver(fe) => self.pick(cx, fe.abs, PickTrigger::Hover),
+            Hit::FingerDown(fe) => self.pick(cx, fe.abs, PickTrigger::Down),
+            _ => ()
+        }
+    }
+
+    /// Returns the result of the pending pick once its pixels were read back.
+    pub fn handle_event(&mut self, cx: &mut Cx, event: &Event) -> Option<PickResult> {
+        if let Event::PassReadBack(rb) = event {
+            if rb.pass_id != self.pass.pass_id() {
+                return None
+            }
+            let (abs, trigger) = self.pending.take() ?;
+            let origin = self.area.rect(cx).pos.floor();
+            let id = rb.pixel_at(abs - origin).and_then(PickId::from_pixel);
+            return Some(PickResult {abs, id, trigger})
+        }
+        None
+    }
+}
+
+/// The encoding of ids as colors in the id buffer, the counterpart of `PickId::to_color` in the
+/// shader library. Ids go up to 2^24 - 2, and a cleared pixel means nothing was picked.
+pub struct PickId;
+
+impl PickId {
+    pub fn from_pixel(pixel: u32) -> Option<u32> {
+        let value = pixel & 0xffffff;
+        if pixel >> 24 == 0 || value == 0 {
+            return None
+        }
+        Some(value - 1)
+    }
+}
@@ -12,6 +12,7 @@ use {
 
 
 live_design!{
+    import makepad_draw::shader::std::*;
     
     DrawIcon = {{DrawIcon}} {
         color: #fff
@@ -66,6 +67,10 @@ live_design!{
             let col = self.get_color(); //color!(white);//get_color();
             return vec4(s * col.rgb * self.u_brightness * col.a, s * col.a);
         }
+        
+        fn fragment(self) -> vec4 {
+            return Pal::to_pass_color_space(self.pixel(), self.color_space);
+        }
     }
 }
 
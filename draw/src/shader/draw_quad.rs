@@ -9,6 +9,7 @@ use {
 };
 
 live_design!{
+    import makepad_draw::shader::std::*;
     
     DrawQuad = {{DrawQuad}} {
         varying pos: vec2
@@ -50,6 +51,10 @@ live_design!{
         fn pixel(self) -> vec4 {
             return #f0f
         }
+        
+        fn fragment(self) -> vec4 {
+            return Pal::to_pass_color_space(self.pixel(), self.color_space);
+        }
     }
 }
 
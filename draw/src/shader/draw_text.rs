@@ -11,6 +11,7 @@ use {
 
 
 live_design!{
+    import makepad_draw::shader::std::*;
     
     DrawText = {{DrawText}} {
         //debug: true;
@@ -131,6 +132,10 @@ live_design!{
             let x16 =  self.sample_color(scale, self.tex_coord1.xy+vec2(dxt * d3,dyt *d3));            
             return (x1+x2+x3+x4+x5+x6+x7+x8+x9+x10+x11+x12+x13+x14+x15+x16)/16 ;*/
         }
+        
+        fn fragment(self) -> vec4 {
+            return Pal::to_pass_color_space(self.pixel(), self.color_space);
+        }
     }
 }
 
@@ -442,9 +447,9 @@ impl DrawText {
             for (run_level, run_range) in runs_with_level_and_range {
                 // FIXME(eddyb) UBA/`unicode_bidi` only offers a LTR/RTL distinction,
                 // even if `rustybuzz` has vertical `Direction`s as well.
-                let (glyph_ids, new_rustybuzz_buffer) = cxfont
+                let (shaped_glyphs, new_rustybuzz_buffer) = cxfont
                     .shape_cache
-                    .get_or_compute_glyph_ids(
+                    .get_or_compute_glyphs(
                     (
                             if run_level.is_rtl() {
                                 makepad_rustybuzz::Direction::RightToLeft
@@ -457,10 +462,13 @@ impl DrawText {
                         owned_font_face
                     );
                 rustybuzz_buffer = new_rustybuzz_buffer;
-                for &glyph_id in glyph_ids {
+                for shaped_glyph in shaped_glyphs {
+                    let glyph_id = shaped_glyph.id;
                     let glyph = owned_font_face.with_ref(|face| font.get_glyph_by_id(face, glyph_id).unwrap());
                     
-                    let advance = glyph.horizontal_metrics.advance_width * font_size_logical * self.font_scale;
+                    // the shaper's advance, not the glyph's own, so ligatures and marks line up
+                    let advance = shaped_glyph.advance * font_size_logical * self.font_scale;
+                    let offset = shaped_glyph.offset * font_size_logical * self.font_scale;
                     
                     // HACK(eddyb) this is a different padding from the SDF padding,
                     // this allows the glyph rasterization to avoid touching the
@@ -485,9 +493,9 @@ impl DrawText {
                     let w = w_dpx * self.font_scale / dpi_factor;
                     let h = h_dpx * self.font_scale / dpi_factor;
                     
-                    let delta_x = font_size_logical * self.font_scale * glyph.bounds.p_min.x - pad;
+                    let delta_x = font_size_logical * self.font_scale * glyph.bounds.p_min.x - pad + offset.x;
                     let delta_y = -(font_size_logical * self.font_scale * glyph.bounds.p_min.y - pad)
-                        + self.text_style.font_size * self.font_scale * self.text_style.top_drop;
+                        + self.text_style.font_size * self.font_scale * self.text_style.top_drop - offset.y;
                     // give the callback a chance to do things
                     //et scaled_min_pos_x = walk_x + delta_x;
                     //let scaled_min_pos_y = pos.y - delta_y;
@@ -95,6 +95,17 @@ live_design!{
         }
     }
     
+    PickId = {
+        // encodes a pick id for the id buffer of a PickingBuffer, 0 stays free for nothing picked
+        fn to_color(id: float) -> vec4 {
+            let v = id + 1.0;
+            let b = mod(v, 256.0);
+            let g = mod(floor(v / 256.0), 256.0);
+            let r = floor(v / 65536.0);
+            return vec4(r / 255.0, g / 255.0, b / 255.0, 1.0);
+        }
+    }
+    
     Pal = {
         
         fn premul(v: vec4) -> vec4 {
@@ -152,6 +163,60 @@ live_design!{
             let e: float = 1.0e-10;
             return vec4(abs(q.z + (q.w - q.y) / (6.0 * d + e)), d / (q.x + e), q.x, c.w);
         }
+
+        fn srgb_to_linear(c: vec3) -> vec3 {
+            return mix(c / 12.92, pow((c + 0.055) / 1.055, vec3(2.4)), step(vec3(0.04045), c));
+        }
+
+        fn linear_to_srgb(c: vec3) -> vec3 {
+            return mix(c * 12.92, 1.055 * pow(c, vec3(1.0 / 2.4)) - 0.055, step(vec3(0.0031308), c));
+        }
+
+        fn srgb_to_display_p3(c: vec4) -> vec4 {
+            let lin = Pal::srgb_to_linear(c.xyz);
+            let p3 = vec3(
+                dot(vec3(0.8224621, 0.1775380, 0.0), lin),
+                dot(vec3(0.0331941, 0.9668058, 0.0), lin),
+                dot(vec3(0.0170827, 0.0723974, 0.9105199), lin)
+            );
+            return vec4(Pal::linear_to_srgb(p3), c.w);
+        }
+
+        fn display_p3_to_srgb(c: vec4) -> vec4 {
+            let lin = Pal::srgb_to_linear(c.xyz);
+            let srgb = vec3(
+                dot(vec3(1.2249401, -0.2249404, 0.0), lin),
+                dot(vec3(-0.0420569, 1.0420571, 0.0), lin),
+                dot(vec3(-0.0196376, -0.0786361, 1.0982735), lin)
+            );
+            return vec4(Pal::linear_to_srgb(srgb), c.w);
+        }
+
+        // converts a premultiplied texture sample from the color space the texture is tagged with
+        // (the draw_texture_color_space uniform) to sRGB, like the colors of the DSL
+        fn from_texture_color_space(c: vec4, color_space: float) -> vec4 {
+            if color_space < 0.5 || c.w <= 0.0 {
+                return c;
+            }
+            let straight = c.xyz / c.w;
+            if color_space > 1.5 {
+                return vec4(Pal::linear_to_srgb(straight) * c.w, c.w);
+            }
+            return vec4(Pal::display_p3_to_srgb(vec4(straight, 1.0)).xyz * c.w, c.w);
+        }
+
+        // converts the premultiplied sRGB output of a pixel shader to the color space of the pass
+        // (the color_space uniform), the transfer functions only work on the straight color
+        fn to_pass_color_space(c: vec4, color_space: float) -> vec4 {
+            if color_space < 0.5 || c.w <= 0.0 {
+                return c;
+            }
+            let straight = c.xyz / c.w;
+            if color_space > 1.5 {
+                return vec4(Pal::srgb_to_linear(straight) * c.w, c.w);
+            }
+            return vec4(Pal::srgb_to_display_p3(vec4(straight, 1.0)).xyz * c.w, c.w);
+        }
     }
     
     Sdf2d = struct {
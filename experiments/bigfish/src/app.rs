@@ -263,11 +263,11 @@ impl MatchEvent for App {
         }
 
         if self.ui.button(id!(savedialogbutton)).clicked(&actions) {
-            cx.open_system_savefile_dialog();
+            cx.save_file_dialog(live_id!(save_dialog), FileDialog::new());
         }
 
         if self.ui.button(id!(loaddialogbutton)).clicked(&actions) {
-           cx.open_system_openfile_dialog();
+           cx.open_file_dialog(live_id!(load_dialog), FileDialog::new());
         }
     }
 }
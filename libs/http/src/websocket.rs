@@ -125,7 +125,7 @@ impl ServerWebSocketMessageHeader {
         if self.masked {
             match self.len {
                 6 => Some(&self.data[2..6]),
-                10 => Some(&self.data[6..10]),
+                8 => Some(&self.data[4..8]),
                 14 => Some(&self.data[10..14]),
                 _ => None
             }
@@ -168,7 +168,7 @@ impl ServerWebSocket {
                 ServerWebSocket::build_message(header, &data.to_string().into_bytes())
             }
             ServerWebSocketMessage::Binary(data)=>{
-                let header = ServerWebSocketMessageHeader::from_len(data.len(), ServerWebSocketMessageFormat::Text, true);
+                let header = ServerWebSocketMessageHeader::from_len(data.len(), ServerWebSocketMessageFormat::Binary, true);
                 ServerWebSocket::build_message(header, &data)
             }
             _=>panic!()
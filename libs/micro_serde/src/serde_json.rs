@@ -334,6 +334,17 @@ impl DeJsonState {
         Err(self.err_token("ident"))
     }
     
+    /// Reads the four hex digits after `\u`, leaving the character after them current.
+    fn hex4(&mut self, i: &mut Chars) -> Result<u32, DeJsonErr> {
+        let mut code = 0;
+        for _ in 0..4 {
+            self.next(i);
+            code = code * 16 + self.cur.to_digit(16).ok_or_else( || self.err_parse("string")) ?;
+        }
+        self.next(i);
+        Ok(code)
+    }
+    
     pub fn next_tok(&mut self, i: &mut Chars) -> Result<(), DeJsonErr> {
         while self.cur == '\n' || self.cur == '\r' || self.cur == '\t' || self.cur == ' ' {
             self.next(i);
@@ -455,6 +466,20 @@ impl DeJsonState {
                             'r'=>self.strbuf.push('\r'),
                             't'=>self.strbuf.push('\t'),
                             '0'=>self.strbuf.push('\0'),
+                            'u'=>{
+                                let mut code = self.hex4(i) ?;
+                                // characters outside the basic plane come as a surrogate pair
+                                if (0xd800..0xdc00).contains(&code) && self.cur == '\\' {
+                                    self.next(i);
+                                    if self.cur != 'u' {
+                                        return Err(self.err_parse("string"));
+                                    }
+                                    let low = self.hex4(i) ?;
+                                    code = 0x10000 + ((code - 0xd800) << 10) + (low.wrapping_sub(0xdc00) & 0x3ff);
+                                }
+                                self.strbuf.push(char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER));
+                                continue;
+                            }
                             '\0'=>{
                                 return Err(self.err_parse("string"));
                             },
@@ -686,6 +711,63 @@ impl JsonValue{
         }
         None
     }
+    pub fn array(&self)->Option<&Vec<JsonValue>>{
+        if let JsonValue::Array(arr) = self{
+            return Some(arr)
+        }
+        None
+    }
+    pub fn u64(&self)->Option<u64>{
+        match self{
+            JsonValue::U64(v)=>Some(*v),
+            JsonValue::I64(v) if *v >= 0=>Some(*v as u64),
+            JsonValue::F64(v) if *v >= 0.0=>Some(*v as u64),
+            _=>None
+        }
+    }
+    pub fn bool(&self)->Option<bool>{
+        if let JsonValue::Bool(v) = self{
+            return Some(*v)
+        }
+        None
+    }
+}
+
+impl SerJson for JsonValue{
+    fn ser_json(&self, d: usize, s: &mut SerJsonState) {
+        match self{
+            JsonValue::String(v)=>v.ser_json(d, s),
+            JsonValue::Char(v)=>v.to_string().ser_json(d, s),
+            JsonValue::U64(v)=>v.ser_json(d, s),
+            JsonValue::I64(v)=>v.ser_json(d, s),
+            JsonValue::F64(v)=>v.ser_json(d, s),
+            JsonValue::Bool(v)=>v.ser_json(d, s),
+            JsonValue::BareIdent(v)=>s.out.push_str(v),
+            JsonValue::Null | JsonValue::Undefined=>s.out.push_str("null"),
+            JsonValue::Object(obj)=>{
+                s.out.push('{');
+                for (index, (key, value)) in obj.iter().enumerate(){
+                    if index != 0{
+                        s.out.push(',');
+                    }
+                    key.ser_json(d + 1, s);
+                    s.out.push(':');
+                    value.ser_json(d + 1, s);
+                }
+                s.out.push('}');
+            }
+            JsonValue::Array(arr)=>{
+                s.out.push('[');
+                for (index, item) in arr.iter().enumerate(){
+                    if index != 0{
+                        s.out.push(',');
+                    }
+                    item.ser_json(d + 1, s);
+                }
+                s.out.push(']');
+            }
+        }
+    }
 }
 
 impl DeJson for JsonValue{
@@ -376,6 +376,12 @@ pub const WM_MBUTTONDOWN: u32 = 519u32;
 
 pub const WM_MBUTTONUP: u32 = 520u32;
 
+pub const WM_XBUTTONDOWN: u32 = 523u32;
+
+pub const WM_XBUTTONUP: u32 = 524u32;
+
+pub const XBUTTON1: u16 = 1u16;
+
 pub const WM_KEYDOWN: u32 = 256u32;
 
 pub const WM_SYSKEYDOWN: u32 = 260u32;
@@ -13903,6 +13909,29 @@ pub unsafe fn CloseClipboard() -> ::windows_core::Result<()> {
     CloseClipboard().ok()
 }
 
+pub unsafe fn IsClipboardFormatAvailable(format: u32) -> ::windows_core::Result<()> {
+    ::windows_targets::link!("user32.dll" "system" fn IsClipboardFormatAvailable(format : u32) -> super::super::Foundation:: BOOL);
+    IsClipboardFormatAvailable(format).ok()
+}
+
+pub unsafe fn EnumClipboardFormats(format: u32) -> u32 {
+    ::windows_targets::link!("user32.dll" "system" fn EnumClipboardFormats(format : u32) -> u32);
+    EnumClipboardFormats(format)
+}
+
+pub unsafe fn RegisterClipboardFormatW<P0>(lpszformat: P0) -> u32
+where
+    P0: ::windows_core::IntoParam<::windows_core::PCWSTR>,
+{
+    ::windows_targets::link!("user32.dll" "system" fn RegisterClipboardFormatW(lpszformat : ::windows_core::PCWSTR) -> u32);
+    RegisterClipboardFormatW(lpszformat.into_param().abi())
+}
+
+pub unsafe fn GetClipboardFormatNameW(format: u32, lpszformatname: &mut [u16]) -> i32 {
+    ::windows_targets::link!("user32.dll" "system" fn GetClipboardFormatNameW(format : u32, lpszformatname : ::windows_core::PWSTR, cchmaxcount : i32) -> i32);
+    GetClipboardFormatNameW(format, ::core::mem::transmute(lpszformatname.as_ptr()), lpszformatname.len() as _)
+}
+
 }
 pub mod Com{
 pub const STGM_READ: STGM = STGM(0u32);
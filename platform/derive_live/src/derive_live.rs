@@ -254,7 +254,7 @@ fn derive_live_impl_inner(parser: &mut TokenParser, tb: &mut TokenBuilder) -> Re
             
             
             if field.attrs.iter().any( | a | a.name == "live") || field.attrs.iter().any( | a | a.name == "animator") {
-                tb.add("        LiveId(").suf_u64(LiveId::from_str(&field.name).0).add(")=>self.").ident(&field.name).add(".apply(cx, apply, index, nodes),");
+                tb.add("        LiveId(").suf_u64(LiveId::from_str(&field.name).0).add(")=>apply.track_field(LiveId(").suf_u64(LiveId::from_str(&field.name).0).add("), | apply | self.").ident(&field.name).add(".apply(cx, apply, index, nodes)),");
             }
             else if field.attrs.iter().any( | a | a.name == "walk") {
                 for field in &fields {
@@ -265,7 +265,7 @@ fn derive_live_impl_inner(parser: &mut TokenParser, tb: &mut TokenBuilder) -> Re
                   }
                 }
                 for f in walk_fields{
-                    tb.add("        live_id!(").ident(f).add(")=>self.").ident(&field.name).add(".").ident(f).add(".apply(cx, apply, index, nodes),");
+                    tb.add("        live_id!(").ident(f).add(")=>apply.track_field(live_id!(").ident(f).add("), | apply | self.").ident(&field.name).add(".").ident(f).add(".apply(cx, apply, index, nodes)),");
                 }
             }
             else if field.attrs.iter().any( | a | a.name == "layout") {
@@ -277,7 +277,7 @@ fn derive_live_impl_inner(parser: &mut TokenParser, tb: &mut TokenBuilder) -> Re
                   }
                 }
                 for f in layout_fields{
-                    tb.add("        live_id!(").ident(f).add(")=>self.").ident(&field.name).add(".").ident(f).add(".apply(cx, apply, index, nodes),");
+                    tb.add("        live_id!(").ident(f).add(")=>apply.track_field(live_id!(").ident(f).add("), | apply | self.").ident(&field.name).add(".").ident(f).add(".apply(cx, apply, index, nodes)),");
                 }
             }
         }
@@ -325,6 +325,7 @@ fn derive_live_impl_inner(parser: &mut TokenParser, tb: &mut TokenBuilder) -> Re
         tb.add("LiveApply for").ident(&struct_name).stream(generic.clone()).stream(where_clause.clone()).add("{");
         
         tb.add("    fn apply(&mut self, cx: &mut Cx, apply:&mut Apply, start_index: usize, nodes: &[LiveNode])->usize {");
+        tb.add("        let outer_tracking = apply.begin_tracking();");
         tb.add("        self.deref_before_apply(cx, apply, start_index, nodes);");
         if animator_field.is_some() { // apply the default states
             tb.add("    let mut animator_index = None;");
@@ -334,6 +335,7 @@ fn derive_live_impl_inner(parser: &mut TokenParser, tb: &mut TokenBuilder) -> Re
         tb.add("            if !nodes[start_index].value.is_structy_type(){");
         tb.add("                cx.apply_error_wrong_type_for_struct(live_error_origin!(), start_index, nodes, struct_id);");
         tb.add("                <Self as LiveHook>::after_apply(self, cx, apply, start_index, nodes);");
+        tb.add("                apply.end_tracking(outer_tracking);");
         tb.add("                return nodes.skip_node(start_index);");
         tb.add("            }");
         
@@ -359,7 +361,7 @@ fn derive_live_impl_inner(parser: &mut TokenParser, tb: &mut TokenBuilder) -> Re
         }
                 
         tb.add("        self.deref_after_apply(cx, apply, start_index, nodes);");
-        
+        tb.add("        apply.end_tracking(outer_tracking);");
         
         tb.add("        return index;");
         tb.add("    }");
@@ -605,7 +607,7 @@ fn derive_live_impl_inner(parser: &mut TokenParser, tb: &mut TokenBuilder) -> Re
         tb.add("                match variant{");
         for item in &items {
             if let EnumKind::Bare = item.kind {
-                tb.add("            LiveId(").suf_u64(LiveId::from_str(&item.name).0).add(")=>{index += 1;*self = Self::").ident(&item.name).add("},");
+                tb.add("            LiveId(").suf_u64(LiveId::from_str(&item.name).0).add(")=>{index += 1;if !matches!(self, Self::").ident(&item.name).add("){apply.mark_changed();}*self = Self::").ident(&item.name).add("},");
             }
         }
         tb.add("                    _=>{");
@@ -621,7 +623,7 @@ fn derive_live_impl_inner(parser: &mut TokenParser, tb: &mut TokenBuilder) -> Re
             if let EnumKind::Named(fields) = &item.kind {
                 tb.add("            LiveId(").suf_u64(LiveId::from_str(&item.name).0).add(")=>{");
                 tb.add("                if let Self::").ident(&item.name).add("{..} = self{}");
-                tb.add("                else{*self = ");
+                tb.add("                else{apply.mark_changed();*self = ");
                 item.gen_new(tb) ?;
                 tb.add("                }");
                 tb.add("                if let Self::").ident(&item.name).add("{");
@@ -664,7 +666,7 @@ fn derive_live_impl_inner(parser: &mut TokenParser, tb: &mut TokenBuilder) -> Re
                 tb.add("            LiveId(").suf_u64(LiveId::from_str(&item.name).0).add(")=>{");
                 
                 tb.add("                if let Self::").ident(&item.name).add("{..} = self{}");
-                tb.add("                else{*self = ");
+                tb.add("                else{apply.mark_changed();*self = ");
                 item.gen_new(tb) ?;
                 tb.add("                }");
                 
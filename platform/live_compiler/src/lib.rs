@@ -9,13 +9,16 @@ pub mod live_node;
 pub mod live_node_vec;
 pub mod live_document;
 pub mod live_registry; 
+pub mod live_file_watcher;
 pub mod live_expander;
 pub mod live_ptr;
 pub mod live_eval;
 pub mod live_component;
 pub mod live_node_cbor;
+pub mod live_node_json;
 //pub mod live_node_cbor;
 pub mod live_node_reader;
+pub mod live_node_writer;
 
 pub use makepad_math;
 pub use makepad_derive_live;
@@ -39,6 +42,7 @@ pub use {
             live_eval,
             LiveEval
         },
+        live_file_watcher::LiveFileWatcher,
         live_registry::{
             LiveFileChange,
             LiveRegistry,
@@ -61,6 +65,10 @@ pub use {
        live_node_cbor::{
             LiveNodeSliceToCbor,
             LiveNodeVecFromCbor
+        },
+        live_node_json::{
+            LiveNodeSliceToJson,
+            LiveNodeVecFromJson
         },/*
         live_node_msgpack::{
             LiveNodeSliceToMsgPack,
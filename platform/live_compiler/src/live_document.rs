@@ -2,9 +2,11 @@
 use {
     crate::{
         span::{TextPos, TextSpan},
-        live_token::{TokenWithSpan,LiveTokenId},
-        live_node::LiveNode,
+        live_token::{TokenWithSpan, LiveToken, LiveTokenId},
+        live_node::{LiveNode, LiveEditInfo},
         live_node::LiveDesignInfo,
+        live_node_vec::LiveNodeSliceApi,
+        makepad_live_id::*,
     }
 };
 
@@ -74,6 +76,42 @@ impl LiveOriginal {
     pub fn token_id_to_span(&self, token_id: LiveTokenId) -> TextSpan {
         self.tokens[token_id.token_index()].span
     }
+    
+    /// The `.{..}` edit info nodes of the slot `edit_info` points to.
+    pub fn edit_info_nodes(&self, edit_info: LiveEditInfo) -> &[LiveNode] {
+        let index = edit_info.edit_info_index();
+        &self.edit_info[index..self.edit_info.skip_node(index)]
+    }
+    
+    /// The span of the source the node at `index` was parsed from: from its name through
+    /// the end of its value, the closing bracket included for objects and arrays.
+    pub fn node_span(&self, index: usize) -> Option<TextSpan> {
+        let start = self.nodes[index].origin.token_id() ?.token_index();
+        // the value runs up to the next property, a delimiter or the bracket closing its parent
+        let next_index = self.nodes.skip_node(index);
+        let limit = match self.nodes.get(next_index) {
+            Some(node) if !node.is_close() => node.origin.token_id() ?.token_index(),
+            _ => self.tokens.len() - 1
+        };
+        let mut end = start;
+        let mut depth = 0;
+        for index in start..limit {
+            match self.tokens[index].token {
+                LiveToken::Open(_) => depth += 1,
+                LiveToken::Close(_) if depth == 0 => break,
+                LiveToken::Close(_) => depth -= 1,
+                LiveToken::Punct(live_id!(,)) | LiveToken::Punct(live_id!(;)) if depth == 0 => break,
+                LiveToken::Eof => break,
+                _ => ()
+            }
+            end = index;
+        }
+        Some(TextSpan {
+            file_id: self.tokens[start].span.file_id,
+            start: self.tokens[start].span.start,
+            end: self.tokens[end].span.end
+        })
+    }
 }
 
 
@@ -26,6 +26,27 @@ pub enum LiveEval {
     String(Rc<String>),
 }
 
+impl LiveEval {
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Self::Float64(v) => Some(*v),
+            Self::Int64(v) => Some(*v as f64),
+            _ => None
+        }
+    }
+    
+    /// The text a value adds to a string, as in `("Page " + 2)`.
+    fn to_concat_string(&self) -> Option<String> {
+        match self {
+            Self::String(v) => Some(v.to_string()),
+            Self::Int64(v) => Some(v.to_string()),
+            Self::Float64(v) => Some(v.to_string()),
+            Self::Bool(v) => Some(v.to_string()),
+            _ => None
+        }
+    }
+}
+
 impl LiveError {
     fn eval_error_wrong_value_in_expression(origin: LiveErrorOrigin, index: usize, nodes: &[LiveNode], ty: &str) ->Self{
         Self::eval_error(origin, index, nodes, format!("wrong value in expression of type {} value: {:?}", ty, nodes[index].value))
@@ -60,10 +81,13 @@ pub fn live_eval(live_registry: &LiveRegistry, start: usize, index: &mut usize,
     Ok(match &nodes[*index].value {
         LiveValue::Str(_) |
         LiveValue::InlineString(_) => {
-            LiveEval::String(Rc::new(live_registry.live_node_as_string(&nodes[*index]).unwrap()))
+            *index += 1;
+            LiveEval::String(Rc::new(live_registry.live_node_as_string(&nodes[*index - 1]).unwrap()))
+        }
+        LiveValue::Dependency(v) | LiveValue::String(v) => {
+            *index += 1;
+            LiveEval::String(v.clone())
         }
-        LiveValue::Dependency(v)=>LiveEval::String(v.clone()),
-        LiveValue::String(v)=>LiveEval::String(v.clone()),
         LiveValue::Float32(v) => {
             *index += 1;
             LiveEval::Float64(*v as f64)
@@ -221,26 +245,66 @@ pub fn live_eval(live_registry: &LiveRegistry, start: usize, index: &mut usize,
                         }
                     }
                 }
-                live_id!(mix) if *args == 3 => {
+                live_id!(mix) | live_id!(lerp) if *args == 3 => {
                     let a = live_eval(live_registry, start, index, nodes)?;
                     let b = live_eval(live_registry, start, index, nodes)?;
                     let c = live_eval(live_registry, start, index, nodes)?;
-                    if let LiveEval::Vec4(va) = a {
-                        if let LiveEval::Vec4(vb) = b {
-                            if let LiveEval::Float64(vc) = c {
-                                let vc = vc as f32;
-                                // ok so how do we blend this eh.
-                                return Ok(LiveEval::Vec4(vec4(
-                                    va.x + (vb.x - va.x) * vc,
-                                    va.y + (vb.y - va.y) * vc,
-                                    va.z + (vb.z - va.z) * vc,
-                                    va.w + (vb.w - va.w) * vc
-                                )))
+                    if let Some(t) = c.as_f64() {
+                        match (a, b) {
+                            (LiveEval::Vec2(va), LiveEval::Vec2(vb)) => return Ok(LiveEval::Vec2(va + (vb - va) * t as f32)),
+                            (LiveEval::Vec3(va), LiveEval::Vec3(vb)) => return Ok(LiveEval::Vec3(va + (vb - va) * t as f32)),
+                            (LiveEval::Vec4(va), LiveEval::Vec4(vb)) => return Ok(LiveEval::Vec4(va + (vb - va) * t as f32)),
+                            (a, b) => if let (Some(va), Some(vb)) = (a.as_f64(), b.as_f64()) {
+                                return Ok(LiveEval::Float64(va + (vb - va) * t))
                             }
-                            
                         }
                     }
                 }
+                live_id!(min) | live_id!(max) if *args == 2 => {
+                    let a = live_eval(live_registry, start, index, nodes)?;
+                    let b = live_eval(live_registry, start, index, nodes)?;
+                    let is_min = *ident == live_id!(min);
+                    if let (LiveEval::Int64(va), LiveEval::Int64(vb)) = (&a, &b) {
+                        return Ok(LiveEval::Int64(if is_min {*va.min(vb)} else {*va.max(vb)}))
+                    }
+                    if let (Some(va), Some(vb)) = (a.as_f64(), b.as_f64()) {
+                        return Ok(LiveEval::Float64(if is_min {va.min(vb)} else {va.max(vb)}))
+                    }
+                }
+                live_id!(clamp) if *args == 3 => {
+                    let v = live_eval(live_registry, start, index, nodes)?;
+                    let lo = live_eval(live_registry, start, index, nodes)?;
+                    let hi = live_eval(live_registry, start, index, nodes)?;
+                    if let (LiveEval::Int64(v), LiveEval::Int64(lo), LiveEval::Int64(hi)) = (&v, &lo, &hi) {
+                        return Ok(LiveEval::Int64(*v.max(lo).min(hi)))
+                    }
+                    if let (Some(v), Some(lo), Some(hi)) = (v.as_f64(), lo.as_f64(), hi.as_f64()) {
+                        return Ok(LiveEval::Float64(v.max(lo).min(hi)))
+                    }
+                }
+                live_id!(floor) | live_id!(ceil) if *args == 1 => {
+                    match live_eval(live_registry, start, index, nodes)? {
+                        LiveEval::Int64(v) => return Ok(LiveEval::Int64(v)),
+                        LiveEval::Float64(v) => return Ok(LiveEval::Float64(
+                            if *ident == live_id!(floor) {v.floor()} else {v.ceil()}
+                        )),
+                        _ => ()
+                    }
+                }
+                live_id!(rgb) | live_id!(rgba) if *args == if *ident == live_id!(rgb) {3} else {4} => {
+                    // components go from 0.0 to 1.0, like the ones of colors in shaders
+                    let mut c = [1.0; 4];
+                    let mut is_number = true;
+                    for component in c.iter_mut().take(*args) {
+                        match live_eval(live_registry, start, index, nodes)?.as_f64() {
+                            Some(v) => *component = v as f32,
+                            None => is_number = false
+                        }
+                    }
+                    if is_number {
+                        return Ok(LiveEval::Vec4(vec4(c[0], c[1], c[2], c[3])))
+                    }
+                }
                 live_id!(hsvmod) if *args == 4 => {
                     let orig = live_eval(live_registry, start, index, nodes)?;
                     let hmod = live_eval(live_registry, start, index, nodes)?;
@@ -397,6 +461,12 @@ pub fn live_eval(live_registry: &LiveRegistry, start: usize, index: &mut usize,
                     }
                     _ => return Err(LiveError::eval_error_binop_undefined_in_expression(live_error_origin!(), *index, nodes, *op, a, b))
                 },
+                LiveBinOp::Add if matches!(a, LiveEval::String(_)) || matches!(b, LiveEval::String(_)) => {
+                    match (a.to_concat_string(), b.to_concat_string()) {
+                        (Some(va), Some(vb)) => LiveEval::String(Rc::new(va + &vb)),
+                        _ => return Err(LiveError::eval_error_binop_undefined_in_expression(live_error_origin!(), *index, nodes, *op, a, b))
+                    }
+                }
                 LiveBinOp::Add => match a {
                     LiveEval::Int64(va) => match b {
                         LiveEval::Int64(vb) => LiveEval::Int64(va + vb),
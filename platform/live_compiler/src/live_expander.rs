@@ -1,14 +1,18 @@
 use std::cmp::Ordering;
 
 use {
-    std::rc::Rc,
+    std::{
+        rc::Rc,
+        borrow::Cow,
+    },
     crate::{
         makepad_live_id::*,
         makepad_live_tokenizer::{live_error_origin, LiveErrorOrigin},
         live_ptr::{LiveFileId, LivePtr, LiveFileGeneration},
         live_error::{LiveError},
         live_document::{LiveOriginal, LiveExpanded},
-        live_node::{LiveValue, LiveNode, LiveFieldKind, LivePropType},
+        live_eval::{live_eval, LiveEval},
+        live_node::{LiveValue, LiveNode, LiveFieldKind, LivePropType, LiveIdAsProp},
         live_node_vec::{LiveNodeSliceApi, LiveNodeVecApi},
         live_registry::{LiveRegistry, LiveScopeTarget},
     }
@@ -53,6 +57,8 @@ impl<'a> LiveExpander<'a> {
             value: LiveValue::Root {id_resolve: Box::default()}
         });
         let mut current_parent = vec![(LiveId(0), 0usize)];
+        // only copied once an `if` or `for` is replaced by the props of its body
+        let mut in_nodes = Cow::Borrowed(&in_doc.nodes[..]);
         let mut in_index = 1;
         let mut lazy_define_value = None;
         loop {
@@ -63,11 +69,11 @@ impl<'a> LiveExpander<'a> {
                 }
             }
             
-            if in_index >= in_doc.nodes.len() - 1 {
+            if in_index >= in_nodes.len() - 1 {
                 break;
             }
             
-            let in_node = &in_doc.nodes[in_index];
+            let in_node = &in_nodes[in_index];
             let in_value = &in_node.value;
             
             match in_value {
@@ -81,9 +87,16 @@ impl<'a> LiveExpander<'a> {
                     // lets verify it points anywhere
                     let mut found = false;
                     let is_glob = in_node.id == LiveId::empty();
-                    if let Some(nodes) = self.live_registry.module_id_to_expanded_nodes(live_import.module_id) {
-                        let file_id = self.live_registry.module_id_to_file_id(live_import.module_id).unwrap();
-                        let mut node_iter = Some(1);
+                    let in_module = self.live_registry.file_id_to_file(self.in_file_id).module_id;
+                    let module_id = self.live_registry.themed_module_id(live_import.module_id, in_module);
+                    if let Some(nodes) = self.live_registry.module_id_to_expanded_nodes(module_id) {
+                        let file_id = self.live_registry.module_id_to_file_id(module_id).unwrap();
+                        // imports from inside an object, like a library of shader functions, walk down to it first
+                        let mut parent_index = Some(0);
+                        for object_id in &live_import.object_path {
+                            parent_index = parent_index.and_then( | index | nodes.child_by_name(index, object_id.as_instance()));
+                        }
+                        let mut node_iter = parent_index.filter( | index | nodes[*index].is_open()).and_then( | index | nodes.first_child(index));
                         while let Some(index) = node_iter {
                             if is_glob{
                                 if let LiveValue::Root {id_resolve} = &mut out_doc.nodes[0].value {
@@ -109,12 +122,25 @@ impl<'a> LiveExpander<'a> {
                         self.errors.push(LiveError {
                             origin: live_error_origin!(),
                             span: in_node.origin.token_id().unwrap().into(),
-                            message: format!("Import statement nothing found {}::{} as {}", live_import.module_id, live_import.import_id, in_node.id)
+                            message: format!("Import statement nothing found {}::{}{} as {}", live_import.module_id, live_import.object_path.iter().map( | id | format!("{}::", id)).collect::<String>(), live_import.import_id, in_node.id)
                         });
                     }
                     in_index += 1;
                     continue;
                 }
+                LiveValue::If | LiveValue::For {..} => {
+                    let next_index = in_nodes.skip_node(in_index);
+                    match self.unroll_control_flow(&in_nodes, in_index, current_parent.last().unwrap().1, &out_doc.nodes) {
+                        Ok(props) => {
+                            in_nodes.to_mut().splice(in_index..next_index, props);
+                        }
+                        Err(err) => {
+                            self.errors.push(err);
+                            in_index = next_index;
+                        }
+                    }
+                    continue;
+                }
                 _ => ()
             }
             
@@ -145,10 +171,10 @@ impl<'a> LiveExpander<'a> {
                         
                         // POTENTIAL SHIFT
                         let old_len = out_doc.nodes.len();
-                        out_doc.nodes.splice(overwrite..next_index, in_doc.nodes.node_slice(in_index).iter().cloned());
+                        out_doc.nodes.splice(overwrite..next_index, in_nodes.node_slice(in_index).iter().cloned());
                         self.shift_parent_stack(&mut current_parent, &out_doc.nodes, overwrite, old_len, out_doc.nodes.len());
                         
-                        in_index = in_doc.nodes.skip_node(in_index);
+                        in_index = in_nodes.skip_node(in_index);
                         out_doc.nodes[overwrite].origin.inherit_origin(out_origin);
                         continue;
                     }
@@ -190,10 +216,10 @@ impl<'a> LiveExpander<'a> {
                     if in_node.is_expr() {
                         // splice it in
                         let old_len = out_doc.nodes.len();
-                        out_doc.nodes.splice(insert_point..insert_point, in_doc.nodes.node_slice(in_index).iter().cloned());
+                        out_doc.nodes.splice(insert_point..insert_point, in_nodes.node_slice(in_index).iter().cloned());
                         self.shift_parent_stack(&mut current_parent, &out_doc.nodes, insert_point - 1, old_len, out_doc.nodes.len());
                         
-                        in_index = in_doc.nodes.skip_node(in_index);
+                        in_index = in_nodes.skip_node(in_index);
                         continue;
                     }
                     
@@ -455,5 +481,131 @@ impl<'a> LiveExpander<'a> {
         }
     }
     
+    /// The props an `if` or `for` at `index` stands for: the body of the branch its condition
+    /// picks, or a copy of its body for every number in its range, with the loop variable
+    /// replaced by that number. Ids it uses are looked up among the props of `parent_index`
+    /// that are expanded so far, and then in the scope of the file.
+    fn unroll_control_flow(&self, nodes: &[LiveNode], index: usize, parent_index: usize, out_nodes: &[LiveNode]) -> Result<Vec<LiveNode>, LiveError> {
+        let mut props = Vec::new();
+        match nodes[index].value {
+            LiveValue::If => {
+                let cond = nodes.child_by_name(index, live_id!(cond).as_field()).unwrap();
+                let branch = match self.eval_control_expr(nodes, cond, parent_index, out_nodes) ? {
+                    LiveEval::Bool(true) => live_id!(then),
+                    LiveEval::Bool(false) => live_id!(else),
+                    value => return Err(Self::control_flow_error(&nodes[cond], format!("if needs a bool condition, not {:?}", value)))
+                };
+                if let Some(body) = nodes.child_by_name(index, branch.as_field()) {
+                    Self::copy_control_body(nodes, body, nodes[index].id, 0, None, &mut props);
+                }
+            }
+            LiveValue::For {binding} => {
+                let mut range = [0; 2];
+                for (bound, id) in range.iter_mut().zip([live_id!(start), live_id!(end)]) {
+                    let expr = nodes.child_by_name(index, id.as_field()).unwrap();
+                    *bound = match self.eval_control_expr(nodes, expr, parent_index, out_nodes) ? {
+                        LiveEval::Int64(v) => v,
+                        value => return Err(Self::control_flow_error(&nodes[expr], format!("for needs an integer range, not {:?}", value)))
+                    };
+                }
+                let body = nodes.child_by_name(index, live_id!(body).as_field()).unwrap();
+                for i in range[0]..range[1] {
+                    Self::copy_control_body(nodes, body, nodes[index].id, i, Some(binding), &mut props);
+                }
+            }
+            _ => ()
+        }
+        Ok(props)
+    }
+    
+    /// Copies the props of a control flow body. Named props in the body of a `for` get the
+    /// number of their copy, so `item` becomes `live_id_num!(item, i)`, and nameless ones get
+    /// ids that don't collide with the nameless props around them.
+    fn copy_control_body(nodes: &[LiveNode], body: usize, control_id: LiveId, copy: i64, binding: Option<LiveId>, props: &mut Vec<LiveNode>) {
+        let mut child = nodes.first_child(body);
+        while let Some(index) = child {
+            let start = props.len();
+            props.extend(nodes.node_slice(index).iter().cloned());
+            let id = props[start].id;
+            props[start].id = if id.is_unique() {
+                LiveId(LiveId::from_num(control_id.0, copy as u64).id_append(id).0 & 0x7fff_ffff_ffff_ffff)
+            }
+            else if binding.is_some() {
+                match id.as_string( | name | name.map( | name | name.to_string())) {
+                    Some(name) => LiveId::from_str_num_with_lut(&name, copy as u64).unwrap(),
+                    None => LiveId::from_num(id.0, copy as u64)
+                }
+            }
+            else {
+                id
+            };
+            if let Some(binding) = binding {
+                for node in &mut props[start..] {
+                    if let LiveValue::Id(id) | LiveValue::BareEnum(id) = node.value {
+                        if id == binding {
+                            node.value = LiveValue::Int64(copy);
+                        }
+                    }
+                }
+            }
+            child = nodes.next_child(index);
+        }
+    }
+    
+    /// Evaluates the expression of an `if` or `for` with the ids in it replaced by the values
+    /// they refer to, as the document they are in isn't expanded yet.
+    fn eval_control_expr(&self, nodes: &[LiveNode], index: usize, parent_index: usize, out_nodes: &[LiveNode]) -> Result<LiveEval, LiveError> {
+        let mut expr = Vec::new();
+        for node in nodes.node_slice(index) {
+            if let LiveValue::Id(id) = node.value {
+                let value = self.control_id_value(id, parent_index, out_nodes).ok_or_else( || {
+                    Self::control_flow_error(node, format!("cant find value of {}", id))
+                }) ?;
+                expr.push(LiveNode {origin: node.origin, id: node.id, value});
+            }
+            else {
+                expr.push(node.clone());
+            }
+        }
+        live_eval(self.live_registry, 0, &mut 1, &expr)
+    }
+    
+    fn control_id_value(&self, id: LiveId, parent_index: usize, out_nodes: &[LiveNode]) -> Option<LiveValue> {
+        let (nodes, index) = if let Some(index) = out_nodes.child_by_name(parent_index, id.as_field()) {
+            (out_nodes, index)
+        }
+        else {
+            match self.live_registry.find_scope_target(id, out_nodes) ? {
+                LiveScopeTarget::LocalPtr(index) => (out_nodes, index),
+                LiveScopeTarget::LivePtr(ptr) => self.live_registry.ptr_to_nodes_index(ptr),
+            }
+        };
+        let eval = match &nodes[index].value {
+            // expressions of expanded documents can find their ids themselves
+            LiveValue::Expr {expand_index: Some(_)} => live_eval(self.live_registry, index, &mut (index + 1), nodes),
+            LiveValue::Expr {..} => self.eval_control_expr(nodes, index, parent_index, out_nodes),
+            LiveValue::Id(_) => return None,
+            value if value.is_value_type() => return Some(value.clone()),
+            _ => return None
+        };
+        Some(match eval.ok() ? {
+            LiveEval::Float64(v) => LiveValue::Float64(v),
+            LiveEval::Vec2(v) => LiveValue::Vec2(v),
+            LiveEval::Vec3(v) => LiveValue::Vec3(v),
+            LiveEval::Vec4(v) => LiveValue::Vec4(v),
+            LiveEval::Int64(v) => LiveValue::Int64(v),
+            LiveEval::Bool(v) => LiveValue::Bool(v),
+            LiveEval::String(v) => LiveValue::String(v),
+        })
+    }
+    
+    fn control_flow_error(node: &LiveNode, message: String) -> LiveError {
+        LiveError {
+            origin: live_error_origin!(),
+            span: node.origin.token_id().unwrap().into(),
+            message
+        }
+    }
+    
 }
 
@@ -0,0 +1,68 @@
+use {
+    std::time::SystemTime,
+    crate::live_registry::{LiveFileChange, LiveRegistry},
+};
+
+/// Watches the source files the live documents of a registry were registered from, so edits
+/// to them can be fed back through `LiveRegistry::process_file_changes` while the app runs.
+/// It only looks at the disk when polled, which a platform does from a thread of its own.
+pub struct LiveFileWatcher {
+    files: Vec<WatchedLiveFile>,
+}
+
+struct WatchedLiveFile {
+    path: String,
+    file_name: String,
+    modified: Option<SystemTime>,
+    content: Option<String>,
+}
+
+impl LiveFileWatcher {
+    /// Watches every live file of `live_registry` that can be found on disk through its cargo
+    /// manifest path.
+    pub fn new(live_registry: &LiveRegistry) -> Self {
+        let mut files = Vec::new();
+        for file in &live_registry.live_files {
+            if let Some(start) = file.file_name.find("src/") {
+                files.push(WatchedLiveFile {
+                    path: format!("{}/{}", file.cargo_manifest_path, &file.file_name[start..]),
+                    file_name: file.file_name.clone(),
+                    modified: None,
+                    content: None,
+                });
+            }
+        }
+        Self {files}
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// The files whose content changed since the last poll. The first poll only reads what is
+    /// there. A file is only read again once its modification time moved, and a save that
+    /// didn't change its content isn't reported.
+    pub fn poll(&mut self) -> Vec<LiveFileChange> {
+        let mut changes = Vec::new();
+        for file in &mut self.files {
+            let modified = std::fs::metadata(&file.path).and_then( | metadata | metadata.modified()).ok();
+            if file.content.is_some() && modified.is_some() && modified == file.modified {
+                continue;
+            }
+            file.modified = modified;
+            let Ok(next) = std::fs::read_to_string(&file.path) else {continue};
+            match &file.content {
+                Some(content) if *content == next => (),
+                Some(_) => {
+                    changes.push(LiveFileChange {
+                        file_name: file.file_name.clone(),
+                        content: next.clone()
+                    });
+                    file.content = Some(next);
+                }
+                None => file.content = Some(next)
+            }
+        }
+        changes
+    }
+}
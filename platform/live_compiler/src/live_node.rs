@@ -105,6 +105,12 @@ pub enum LiveValue {
     Class {live_type: LiveType, class_parent: LivePtr, design_info:LiveDesignInfoIndex},
     Close,
     
+    // compile time control flow, which the expander replaces with the props of a body.
+    // `if` has a `cond` expression, a `then` and an optional `else` object,
+    // `for` has `start` and `end` expressions and a `body` object
+    If,
+    For {binding: LiveId},
+    
     // shader code and other DSLs
     DSL {
         token_start: u32,
@@ -117,6 +123,8 @@ pub enum LiveValue {
 #[derive(Clone, Debug, PartialEq)]
 pub struct LiveImport{
     pub module_id: LiveModuleId,
+    // the capitalised objects between the module and the import, as in `module::Object::*`
+    pub object_path: Vec<LiveId>,
     pub import_id: LiveId,
 }
 
@@ -250,7 +258,10 @@ impl LiveIdAsProp for LiveId {
 }
 
 #[derive(Copy, Clone, PartialEq)]
-pub struct LiveNodeOrigin(u64);
+pub struct LiveNodeOrigin {
+    token_id: u64,
+    first_def: u64
+}
 
 impl fmt::Debug for LiveNodeOrigin {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -258,20 +269,20 @@ impl fmt::Debug for LiveNodeOrigin {
     }
 }
 
-// this layout can be reshuffled and just be made bigger.
-// However it keeps the LiveNode size at 40 bytes which is nice for now.
-
-// 10 bit file id (1024)
-// 18 bit token id (262k tokens, avg tokensize: 5 = 1.25 megs of code used to find original token of property
-
-// 10 bit first def file_id
-// 18 bit first def token_id
-
-// 6 bits (64) edit_info index
+// token_id: the token the property was parsed from
+// 48 bits LiveTokenId (16 bit file id, 32 bit token index)
+// 8 bits edit_info index
+// 5 bits unused
 // 1 bit node_has_prefix
 // 2 bits LivePropType
 
-// ok if we are a DSL node then what else do we need. we need a node index pointer.
+// first_def: the token the property was first defined at, in whichever file
+// 48 bits LiveTokenId
+
+const ORIGIN_TOKEN_ID_MASK: u64 = 0x0000_ffff_ffff_ffff;
+const ORIGIN_EDIT_INFO_MASK: u64 = 0x00ff_0000_0000_0000;
+const ORIGIN_NODE_HAS_PREFIX: u64 = 0x2000_0000_0000_0000;
+const ORIGIN_PROP_TYPE_MASK: u64 = 0xC000_0000_0000_0000;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 #[repr(usize)]
@@ -284,41 +295,41 @@ pub enum LivePropType {
 
 impl LiveNodeOrigin {
     pub fn empty() -> Self {
-        Self (0)
+        Self {token_id: 0, first_def: 0}
     }
     
     pub fn field() -> Self {
-        Self (0).with_prop_type(LivePropType::Field)
+        Self::empty().with_prop_type(LivePropType::Field)
     }
     
     pub fn instance() -> Self {
-        Self (0).with_prop_type(LivePropType::Instance)
+        Self::empty().with_prop_type(LivePropType::Instance)
     }
     
     
     pub fn from_token_id(token_id: LiveTokenId) -> Self {
-        Self ((token_id.to_bits() as u64) | ((token_id.to_bits() as u64) << 28))
+        Self {token_id: token_id.to_bits(), first_def: token_id.to_bits()}
     }
     
     pub fn token_id(&self) -> Option<LiveTokenId> {
-        LiveTokenId::from_bits((self.0 & 0x0fff_ffff) as u32)
+        LiveTokenId::from_bits(self.token_id & ORIGIN_TOKEN_ID_MASK)
     }
     
     
     pub fn set_first_def(&mut self, token_id: Option<LiveTokenId>) -> &mut Self {
         if let Some(token_id) = token_id {
-            self.0 = (self.0 & 0xff00_0000_0fff_ffff) | ((token_id.to_bits() as u64) << 28);
+            self.first_def = token_id.to_bits();
         }
         self
     }
     
     pub fn first_def(&self) -> Option<LiveTokenId> {
-        LiveTokenId::from_bits(((self.0 >> 28) & 0x0fff_ffff) as u32)
+        LiveTokenId::from_bits(self.first_def)
     }
     
     pub fn set_edit_info(&mut self, edit_info: Option<LiveEditInfo>) -> &mut Self {
         if let Some(edit_info) = edit_info {
-            self.0 = (self.0 & 0xE0FF_FFFF_FFFF_FFFF) | ((edit_info.to_bits() as u64) << 56);
+            self.token_id = (self.token_id & !ORIGIN_EDIT_INFO_MASK) | ((edit_info.to_bits() as u64) << 48);
         }
         self
     }
@@ -329,12 +340,12 @@ impl LiveNodeOrigin {
     }
     
     pub fn edit_info(&self) -> Option<LiveEditInfo> {
-        LiveEditInfo::from_bits(((self.0 & 0x1f00_0000_0000_0000) >> 56) as u32)
+        LiveEditInfo::from_bits(((self.token_id & ORIGIN_EDIT_INFO_MASK) >> 48) as u32)
     }
     
     pub fn set_node_has_prefix(&mut self, node_has_prefix: bool) {
         if node_has_prefix {
-            self.0 |= 0x2000_0000_0000_0000;
+            self.token_id |= ORIGIN_NODE_HAS_PREFIX;
         }
     }
     
@@ -344,24 +355,24 @@ impl LiveNodeOrigin {
     }
     
     pub fn node_has_prefix(&self) -> bool {
-        self.0 & 0x2000_0000_0000_0000 != 0
+        self.token_id & ORIGIN_NODE_HAS_PREFIX != 0
     }
     
     pub fn with_prop_type(mut self, prop_type: LivePropType) -> Self {
-        self.0 |= (prop_type as u64) << 62; //0x8000_0000_0000_0000;
+        self.set_prop_type(prop_type);
         self
     }
     
     pub fn set_prop_type(&mut self, prop_type: LivePropType) {
-        self.0 = (self.0 & (!0xC000_0000_0000_0000)) | ((prop_type as u64) << 62);
+        self.token_id = (self.token_id & !ORIGIN_PROP_TYPE_MASK) | ((prop_type as u64) << 62);
     }
     
     pub fn prop_type(&self) -> LivePropType {
-        LivePropType::from_usize(((self.0 & 0xC000_0000_0000_0000) >> 62) as usize)
+        LivePropType::from_usize(((self.token_id & ORIGIN_PROP_TYPE_MASK) >> 62) as usize)
     }
     
     pub fn has_prop_type(&self, origin: LivePropType) -> bool {
-        (self.0 & 0xC000_0000_0000_0000) >> 62 == origin as u64
+        (self.token_id & ORIGIN_PROP_TYPE_MASK) >> 62 == origin as u64
     }
     
     pub fn inherit_origin(&mut self, origin: Self) {
@@ -395,14 +406,14 @@ impl fmt::Debug for LiveEditInfo {
 
 impl LiveEditInfo {
     pub fn new(edit_info_index: usize) -> Self {
-        if edit_info_index & 0xf != 0 || edit_info_index > 0x3e0 {
+        if edit_info_index & 0xf != 0 || edit_info_index > 0xfe0 {
             panic!();
         }
         LiveEditInfo(((edit_info_index as u32) >> 4) + 1)
     }
     
     pub fn edit_info_index(&self) -> usize {
-        (((self.0) as usize - 1) << 4) & 0x3f0
+        (((self.0) as usize - 1) << 4) & 0xff0
     }
     
     pub fn to_bits(&self) -> u32 {self.0}
@@ -513,6 +524,8 @@ impl LiveValue {
             Self::Clone {..} | // subnodes including this one
             Self::Class {..} | 
             Self::Deref {..} | 
+            Self::If |
+            Self::For {..} |
             Self::Root {..} => true, // subnodes including this one
             _ => false
         }
@@ -746,6 +759,8 @@ impl LiveValue {
             
             Self::DSL {..} => 31,
             Self::Import {..} => 32,
+            Self::If => 33,
+            Self::For {..} => 34,
             //Self::Registry {..} => 30,
         }
     }
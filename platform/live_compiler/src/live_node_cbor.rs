@@ -389,6 +389,12 @@ impl<T> LiveNodeSliceToCbor for T where T: AsRef<[LiveNode]> {
                 }
                 LiveValue::IdPath(..) => {
                     return Err("Cannot serialise LiveValue::IdPath".into())
+                }
+                LiveValue::If => {
+                    return Err("Cannot serialise LiveValue::If".into())
+                }
+                LiveValue::For {..} => {
+                    return Err("Cannot serialise LiveValue::For".into())
                 }                
             }
             index += 1;
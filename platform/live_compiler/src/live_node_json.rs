@@ -0,0 +1,403 @@
+use {
+    std::{
+        rc::Rc,
+        str::Chars,
+        fmt::Write,
+    },
+    crate::{
+        makepad_live_id::*,
+        makepad_math::*,
+        makepad_micro_serde::*,
+        makepad_live_tokenizer::colorhex::hex_bytes_to_u32,
+        live_node::*,
+    }
+};
+
+/// Writes live node trees as JSON, so design tools and build pipelines can consume them.
+/// Values map to JSON like this:
+///
+/// - `None` is `null`. Bools, strings and numbers are themselves, and floats are always
+///   written with a fraction, so `1.0` reads back as a float and `1` as an integer.
+/// - An object is a JSON object of its properties, in order. Field properties use their name
+///   as key, instance properties end in `=` (`"button="`) and nameless instances are `"="`.
+/// - A clone such as `<View> {..}` is an object with `"@clone": "View"` in front of its
+///   properties. An array is a JSON array.
+/// - Colors are `{"@color": "#rrggbbaa"}` and vectors are `{"@vec2": [x, y]}`,
+///   `{"@vec3": [x, y, z]}` and `{"@vec4": [x, y, z, w]}`.
+/// - Ids are `{"@id": "name"}`, id paths `{"@id_path": ["a", "b"]}` and dependencies
+///   `{"@dep": "crate://self/resources/icon.svg"}`.
+/// - Enums are `{"@enum": "Variant"}`, with `"@args": [..]` after it for a tuple variant or
+///   `"@fields": {..}` for a named one.
+///
+/// Expressions, shader code, classes and imports only exist in parsed documents and have no
+/// JSON form.
+pub trait LiveNodeSliceToJson {
+    fn to_json(&self, parent_index: usize) -> Result<String, String>;
+}
+
+/// Reads the JSON that `LiveNodeSliceToJson` writes, and JSON generated in the same shapes,
+/// back into live nodes. Integers become `Int64` and numbers with a fraction `Float64`, like
+/// the live parser makes of them.
+pub trait LiveNodeVecFromJson {
+    fn from_json(&mut self, json: &str) -> Result<(), DeJsonErr>;
+}
+
+impl<T> LiveNodeSliceToJson for T where T: AsRef<[LiveNode]> {
+    fn to_json(&self, parent_index: usize) -> Result<String, String> {
+        let mut out = String::new();
+        write_json_value(self.as_ref(), parent_index, &mut out) ?;
+        Ok(out)
+    }
+}
+
+/// Writes the value at `index` and returns the index after it.
+fn write_json_value(nodes: &[LiveNode], index: usize, out: &mut String) -> Result<usize, String> {
+    let node = nodes.get(index).ok_or("Unexpected end of nodes") ?;
+    match &node.value {
+        LiveValue::None => out.push_str("null"),
+        LiveValue::Str(s) => write_json_string(s, out),
+        LiveValue::String(s) => write_json_string(s, out),
+        LiveValue::InlineString(s) => write_json_string(s.as_str(), out),
+        LiveValue::Bool(v) => write!(out, "{}", v).unwrap(),
+        LiveValue::Int64(v) => write!(out, "{}", v).unwrap(),
+        LiveValue::Uint64(v) => write!(out, "{}", v).unwrap(),
+        LiveValue::Float32(v) => write_json_float(*v as f64, out) ?,
+        LiveValue::Float64(v) => write_json_float(*v, out) ?,
+        LiveValue::Color(v) => write!(out, "{{\"@color\":\"#{:08x}\"}}", v).unwrap(),
+        LiveValue::Vec2(v) => write_json_vec("@vec2", &[v.x, v.y], out) ?,
+        LiveValue::Vec3(v) => write_json_vec("@vec3", &[v.x, v.y, v.z], out) ?,
+        LiveValue::Vec4(v) => write_json_vec("@vec4", &[v.x, v.y, v.z, v.w], out) ?,
+        LiveValue::Id(id) => {
+            out.push_str("{\"@id\":");
+            write_json_id(*id, out) ?;
+            out.push('}');
+        }
+        LiveValue::IdPath(path) => {
+            out.push_str("{\"@id_path\":[");
+            for (i, id) in path.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_json_id(*id, out) ?;
+            }
+            out.push_str("]}");
+        }
+        LiveValue::Dependency(path) => {
+            out.push_str("{\"@dep\":");
+            write_json_string(path, out);
+            out.push('}');
+        }
+        LiveValue::BareEnum(variant) => {
+            out.push_str("{\"@enum\":");
+            write_json_id(*variant, out) ?;
+            out.push('}');
+        }
+        LiveValue::TupleEnum(variant) => {
+            out.push_str("{\"@enum\":");
+            write_json_id(*variant, out) ?;
+            out.push_str(",\"@args\":");
+            let index = write_json_array(nodes, index + 1, out) ?;
+            out.push('}');
+            return Ok(index)
+        }
+        LiveValue::NamedEnum(variant) => {
+            out.push_str("{\"@enum\":");
+            write_json_id(*variant, out) ?;
+            out.push_str(",\"@fields\":{");
+            let index = write_json_props(nodes, index + 1, false, out) ?;
+            out.push_str("}}");
+            return Ok(index)
+        }
+        LiveValue::Array => return write_json_array(nodes, index + 1, out),
+        LiveValue::Object | LiveValue::Root {..} => {
+            out.push('{');
+            let index = write_json_props(nodes, index + 1, false, out) ?;
+            out.push('}');
+            return Ok(index)
+        }
+        LiveValue::Clone {clone, ..} => {
+            out.push_str("{\"@clone\":");
+            write_json_id(*clone, out) ?;
+            let index = write_json_props(nodes, index + 1, true, out) ?;
+            out.push('}');
+            return Ok(index)
+        }
+        value => return Err(format!("Cannot convert {:?} to JSON", value))
+    }
+    Ok(index + 1)
+}
+
+/// The items from `index` up to the close of their array. Returns the index after the close.
+fn write_json_array(nodes: &[LiveNode], mut index: usize, out: &mut String) -> Result<usize, String> {
+    out.push('[');
+    let mut first = true;
+    while !nodes.get(index).ok_or("Unexpected end of nodes") ?.is_close() {
+        if !first {
+            out.push(',');
+        }
+        first = false;
+        index = write_json_value(nodes, index, out) ?;
+    }
+    out.push(']');
+    Ok(index + 1)
+}
+
+/// The properties from `index` up to the close of their object, as keys and values without
+/// the braces around them. Returns the index after the close.
+fn write_json_props(nodes: &[LiveNode], mut index: usize, mut needs_comma: bool, out: &mut String) -> Result<usize, String> {
+    loop {
+        let node = nodes.get(index).ok_or("Unexpected end of nodes") ?;
+        if node.is_close() {
+            return Ok(index + 1)
+        }
+        if needs_comma {
+            out.push(',');
+        }
+        needs_comma = true;
+        let prop_type = node.origin.prop_type();
+        if prop_type == LivePropType::Nameless || node.id.is_unique() || node.id.is_empty() {
+            out.push_str("\"=\"");
+        }
+        else if prop_type == LivePropType::Instance {
+            write_json_string(&format!("{}=", id_name(node.id) ?), out);
+        }
+        else {
+            write_json_id(node.id, out) ?;
+        }
+        out.push(':');
+        index = write_json_value(nodes, index, out) ?;
+    }
+}
+
+fn id_name(id: LiveId) -> Result<String, String> {
+    id.as_string( | name | name.map( | name | name.to_string()))
+        .ok_or_else( || format!("LiveId {:016x} has no name to write", id.0))
+}
+
+fn write_json_id(id: LiveId, out: &mut String) -> Result<(), String> {
+    write_json_string(&id_name(id) ?, out);
+    Ok(())
+}
+
+fn write_json_string(s: &str, out: &mut String) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => write!(out, "\\u{:04x}", c as u32).unwrap(),
+            c => out.push(c)
+        }
+    }
+    out.push('"');
+}
+
+fn write_json_float(v: f64, out: &mut String) -> Result<(), String> {
+    if !v.is_finite() {
+        return Err(format!("Cannot convert {} to JSON", v))
+    }
+    let start = out.len();
+    write!(out, "{}", v).unwrap();
+    if !out[start..].contains('.') {
+        out.push_str(".0");
+    }
+    Ok(())
+}
+
+fn write_json_vec(tag: &str, values: &[f32], out: &mut String) -> Result<(), String> {
+    write!(out, "{{\"{}\":[", tag).unwrap();
+    for (i, v) in values.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        write_json_float(*v as f64, out) ?;
+    }
+    out.push_str("]}");
+    Ok(())
+}
+
+impl LiveNodeVecFromJson for Vec<LiveNode> {
+    fn from_json(&mut self, json: &str) -> Result<(), DeJsonErr> {
+        let mut s = DeJsonState::default();
+        let mut i = json.chars();
+        s.next(&mut i);
+        s.next_tok(&mut i) ?;
+        read_json_value(self, LiveId(0), LiveNodeOrigin::field(), &mut s, &mut i) ?;
+        if s.tok != DeJsonTok::Eof {
+            return Err(s.err_token("end of input"))
+        }
+        Ok(())
+    }
+}
+
+fn read_json_value(nodes: &mut Vec<LiveNode>, id: LiveId, origin: LiveNodeOrigin, s: &mut DeJsonState, i: &mut Chars) -> Result<(), DeJsonErr> {
+    let value = match s.tok {
+        DeJsonTok::Null => LiveValue::None,
+        DeJsonTok::Bool(v) => LiveValue::Bool(v),
+        DeJsonTok::U64(v) if v > i64::MAX as u64 => LiveValue::Uint64(v),
+        DeJsonTok::U64(v) => LiveValue::Int64(v as i64),
+        DeJsonTok::I64(v) => LiveValue::Int64(v),
+        DeJsonTok::F64(v) => LiveValue::Float64(v),
+        DeJsonTok::Str => LiveValue::String(Rc::new(s.as_string() ?)),
+        DeJsonTok::BlockOpen => {
+            nodes.push(LiveNode {id, origin, value: LiveValue::Array});
+            return read_json_array(nodes, s, i)
+        }
+        DeJsonTok::CurlyOpen => return read_json_object(nodes, id, origin, s, i),
+        _ => return Err(s.err_token("value"))
+    };
+    nodes.push(LiveNode {id, origin, value});
+    s.next_tok(i)
+}
+
+/// Reads an array from its `[` and closes it.
+fn read_json_array(nodes: &mut Vec<LiveNode>, s: &mut DeJsonState, i: &mut Chars) -> Result<(), DeJsonErr> {
+    s.block_open(i) ?;
+    let origin = LiveNodeOrigin::field().with_prop_type(LivePropType::Nameless);
+    let mut counter = 1;
+    while s.tok != DeJsonTok::BlockClose {
+        read_json_value(nodes, LiveId(counter), origin, s, i) ?;
+        counter += 1;
+        s.eat_comma_block(i) ?;
+    }
+    s.block_close(i) ?;
+    nodes.push(LiveNode {id: LiveId(0), origin: LiveNodeOrigin::field(), value: LiveValue::Close});
+    Ok(())
+}
+
+/// Reads a JSON object from its `{`, which is either a plain object or one of the tagged
+/// shapes `LiveNodeSliceToJson` describes.
+fn read_json_object(nodes: &mut Vec<LiveNode>, id: LiveId, origin: LiveNodeOrigin, s: &mut DeJsonState, i: &mut Chars) -> Result<(), DeJsonErr> {
+    s.curly_open(i) ?;
+    if s.tok != DeJsonTok::Str || !s.strbuf.starts_with('@') {
+        nodes.push(LiveNode {id, origin, value: LiveValue::Object});
+        return read_json_props(nodes, s, i)
+    }
+    let tag = s.as_string() ?;
+    s.next_colon(i) ?;
+    let value = match tag.as_str() {
+        "@color" => {
+            let color = s.as_string() ?;
+            let color = hex_bytes_to_u32(color.trim_start_matches('#').as_bytes())
+                .map_err( | _ | s.err_parse("color")) ?;
+            s.next_tok(i) ?;
+            LiveValue::Color(color)
+        }
+        "@vec2" => {
+            let v = read_json_floats::<2>(s, i) ?;
+            LiveValue::Vec2(vec2(v[0], v[1]))
+        }
+        "@vec3" => {
+            let v = read_json_floats::<3>(s, i) ?;
+            LiveValue::Vec3(vec3(v[0], v[1], v[2]))
+        }
+        "@vec4" => {
+            let v = read_json_floats::<4>(s, i) ?;
+            LiveValue::Vec4(vec4(v[0], v[1], v[2], v[3]))
+        }
+        "@id" => LiveValue::Id(read_json_id(s, i) ?),
+        "@id_path" => {
+            let mut path = Vec::new();
+            s.block_open(i) ?;
+            while s.tok != DeJsonTok::BlockClose {
+                path.push(read_json_id(s, i) ?);
+                s.eat_comma_block(i) ?;
+            }
+            s.block_close(i) ?;
+            LiveValue::IdPath(Rc::new(path))
+        }
+        "@dep" => {
+            let path = s.as_string() ?;
+            s.next_tok(i) ?;
+            LiveValue::Dependency(Rc::new(path))
+        }
+        "@enum" => {
+            let variant = read_json_id(s, i) ?;
+            s.eat_comma_curly(i) ?;
+            if s.tok == DeJsonTok::CurlyClose {
+                LiveValue::BareEnum(variant)
+            }
+            else {
+                let tag = s.as_string() ?;
+                s.next_colon(i) ?;
+                match tag.as_str() {
+                    "@args" => {
+                        nodes.push(LiveNode {id, origin, value: LiveValue::TupleEnum(variant)});
+                        read_json_array(nodes, s, i) ?;
+                    }
+                    "@fields" => {
+                        nodes.push(LiveNode {id, origin, value: LiveValue::NamedEnum(variant)});
+                        s.curly_open(i) ?;
+                        read_json_props(nodes, s, i) ?;
+                    }
+                    _ => return Err(s.err_exp(&tag))
+                }
+                s.eat_comma_curly(i) ?;
+                return s.curly_close(i)
+            }
+        }
+        "@clone" => {
+            let clone = read_json_id(s, i) ?;
+            nodes.push(LiveNode {id, origin, value: LiveValue::Clone {clone, design_info: LiveDesignInfoIndex::invalid()}});
+            s.eat_comma_curly(i) ?;
+            return read_json_props(nodes, s, i)
+        }
+        _ => return Err(s.err_exp(&tag))
+    };
+    nodes.push(LiveNode {id, origin, value});
+    s.eat_comma_curly(i) ?;
+    s.curly_close(i)
+}
+
+/// Reads properties up to the `}` of their object and closes it.
+fn read_json_props(nodes: &mut Vec<LiveNode>, s: &mut DeJsonState, i: &mut Chars) -> Result<(), DeJsonErr> {
+    let mut nameless_id = 1;
+    while s.tok != DeJsonTok::CurlyClose {
+        let key = s.as_string() ?;
+        let (id, prop_type) = if key == "=" {
+            nameless_id += 1;
+            (LiveId(nameless_id - 1), LivePropType::Instance)
+        }
+        else if let Some(name) = key.strip_suffix('=') {
+            (json_id(s, name) ?, LivePropType::Instance)
+        }
+        else {
+            (json_id(s, &key) ?, LivePropType::Field)
+        };
+        s.next_colon(i) ?;
+        read_json_value(nodes, id, LiveNodeOrigin::field().with_prop_type(prop_type), s, i) ?;
+        s.eat_comma_curly(i) ?;
+    }
+    s.curly_close(i) ?;
+    nodes.push(LiveNode {id: LiveId(0), origin: LiveNodeOrigin::field(), value: LiveValue::Close});
+    Ok(())
+}
+
+fn json_id(s: &DeJsonState, name: &str) -> Result<LiveId, DeJsonErr> {
+    if name.is_empty() {
+        return Err(s.err_parse("empty id"))
+    }
+    LiveId::from_str_with_lut(name).map_err( | other | s.err_msg(&format!("Id {} collides with {}", name, other)))
+}
+
+fn read_json_id(s: &mut DeJsonState, i: &mut Chars) -> Result<LiveId, DeJsonErr> {
+    let name = s.as_string() ?;
+    let id = json_id(s, &name) ?;
+    s.next_tok(i) ?;
+    Ok(id)
+}
+
+fn read_json_floats<const N: usize>(s: &mut DeJsonState, i: &mut Chars) -> Result<[f32; N], DeJsonErr> {
+    let mut values = [0.0; N];
+    s.block_open(i) ?;
+    for value in &mut values {
+        *value = s.as_f64() ? as f32;
+        s.next_tok(i) ?;
+        s.eat_comma_block(i) ?;
+    }
+    s.block_close(i) ?;
+    Ok(values)
+}
@@ -750,7 +750,15 @@ impl<T> LiveNodeSliceApi for T where T: AsRef<[LiveNode]> {
                 LiveValue::Root {..} => {
                     writeln!(f, "{}{} <Root>", node.id, pt).unwrap();
                     stack_depth += 1;
-                }, // subnodes including this one                
+                }, // subnodes including this one
+                LiveValue::If => {
+                    writeln!(f, "{}{} <If>", node.id, pt).unwrap();
+                    stack_depth += 1;
+                },
+                LiveValue::For {binding} => {
+                    writeln!(f, "{}{} <For> {}", node.id, pt, binding).unwrap();
+                    stack_depth += 1;
+                },                
                 LiveValue::Close => {
                     if stack_depth == 0 {
                         writeln!(f, "<CloseMisaligned> {}", node.id).unwrap();
@@ -771,7 +779,11 @@ impl<T> LiveNodeSliceApi for T where T: AsRef<[LiveNode]> {
                     writeln!(f, "<DSL> {} :token_start:{}, token_count:{} expand_index:{:?}", node.id, token_start, token_count, expand_index).unwrap();
                 },
                 LiveValue::Import(live_import) => {
-                    writeln!(f, "<Import> {}::{} as {}", live_import.module_id, live_import.import_id, node.id).unwrap();
+                    write!(f, "<Import> {}::", live_import.module_id).unwrap();
+                    for object_id in &live_import.object_path {
+                        write!(f, "{}::", object_id).unwrap();
+                    }
+                    writeln!(f, "{} as {}", live_import.import_id, node.id).unwrap();
                 }
                 /*LiveValue::Registry(component_id) => {
                     writeln!(f, "<Registry> {}::{}", component_id, node.id).unwrap();
@@ -0,0 +1,428 @@
+use {
+    std::fmt::Write,
+    crate::{
+        makepad_live_id::*,
+        makepad_live_tokenizer::Delim,
+        live_node::{LiveNode, LiveValue, LivePropType, LiveBinOp, LiveUnOp, LiveDesignInfoIndex},
+        live_node_vec::LiveNodeSliceApi,
+        live_registry::LiveRegistry,
+        live_token::{LiveToken, TokenWithSpan},
+    }
+};
+
+const INDENT: &str = "    ";
+
+impl LiveRegistry {
+    /// Writes a document back out as live DSL source, for instance `original.nodes` after a tool
+    /// edited them. Only the body of the root object is written, as it appears in `live_design!`.
+    pub fn live_document_to_dsl(&self, nodes: &[LiveNode]) -> String {
+        let mut writer = LiveDslWriter {registry: self, nodes, out: String::new()};
+        if nodes.first().is_some_and( | node | node.is_open()) {
+            writer.write_body(1, 0);
+        }
+        writer.out
+    }
+
+    /// Writes the node at `index` and its children as a single live DSL property, such as
+    /// `button = <Button> {...}`.
+    pub fn live_node_to_dsl(&self, nodes: &[LiveNode], index: usize) -> String {
+        let mut writer = LiveDslWriter {registry: self, nodes, out: String::new()};
+        writer.write_prop(index, 0);
+        writer.out
+    }
+}
+
+struct LiveDslWriter<'a> {
+    registry: &'a LiveRegistry,
+    nodes: &'a [LiveNode],
+    out: String,
+}
+
+impl<'a> LiveDslWriter<'a> {
+    fn indent(&mut self, depth: usize) {
+        for _ in 0..depth {
+            self.out.push_str(INDENT);
+        }
+    }
+
+    /// The properties from `index` up to the close of the object they are in, one per line.
+    /// Returns the index after the close.
+    fn write_body(&mut self, mut index: usize, depth: usize) -> usize {
+        while index < self.nodes.len() {
+            if self.nodes[index].is_close() {
+                return index + 1
+            }
+            self.indent(depth);
+            index = self.write_prop(index, depth);
+            self.out.push('\n');
+        }
+        index
+    }
+
+    /// A property with its name, as in a class body. Returns the index after it.
+    fn write_prop(&mut self, index: usize, depth: usize) -> usize {
+        let node = &self.nodes[index];
+        match &node.value {
+            LiveValue::DSL {token_start, token_count, ..} => {
+                self.write_dsl(node, *token_start as usize, *token_count as usize, depth);
+                return index + 1
+            }
+            LiveValue::Import(import) => {
+                write!(self.out, "import {}::{}::", import.module_id.0, import.module_id.1).unwrap();
+                for object_id in &import.object_path {
+                    write!(self.out, "{}::", object_id).unwrap();
+                }
+                if import.import_id.is_empty() {
+                    self.out.push('*');
+                }
+                else {
+                    write!(self.out, "{}", import.import_id).unwrap();
+                    if node.id != import.import_id {
+                        write!(self.out, " as {}", node.id).unwrap();
+                    }
+                }
+                return index + 1
+            }
+            _ => ()
+        }
+        let prop_type = node.origin.prop_type();
+        // nameless instances in a class body get counted ids
+        let is_nameless = prop_type == LivePropType::Nameless || node.id.is_unique() || node.id.is_empty();
+        if !is_nameless {
+            if node.origin.node_has_prefix() {
+                if let Some(prefix) = self.prefix_of(node) {
+                    write!(self.out, "{} ", prefix).unwrap();
+                }
+            }
+            let assign = if prop_type == LivePropType::Field {":"} else {" ="};
+            write!(self.out, "{}{} ", node.id, assign).unwrap();
+        }
+        self.write_value(index, depth)
+    }
+
+    /// The keyword in front of a property such as `instance hover: 0.0`, which only the tokens
+    /// the property was parsed from still have.
+    fn prefix_of(&self, node: &LiveNode) -> Option<LiveId> {
+        let token_id = node.origin.token_id()?;
+        let tokens = &self.registry.live_files.get(token_id.file_id()?.to_index())?.original.tokens;
+        match tokens.get(token_id.token_index().checked_sub(1)?)?.token {
+            LiveToken::Ident(prefix) => Some(prefix),
+            _ => None
+        }
+    }
+
+    /// A value without its name. Returns the index after it.
+    fn write_value(&mut self, index: usize, depth: usize) -> usize {
+        let node = &self.nodes[index];
+        match &node.value {
+            LiveValue::Object | LiveValue::Root {..} => {
+                self.out.push('{');
+                return self.write_block(index + 1, depth)
+            }
+            LiveValue::Clone {clone, design_info} => {
+                if *clone == live_id!(struct) {
+                    self.out.push_str("struct {");
+                }
+                else {
+                    write!(self.out, "<{}", clone).unwrap();
+                    if let Some(info) = self.design_info(node, *design_info) {
+                        write!(self.out, " {}", info).unwrap();
+                    }
+                    self.out.push_str("> {");
+                }
+                return self.write_block(index + 1, depth)
+            }
+            LiveValue::Class {live_type, ..} => {
+                let type_name = self.registry.live_type_infos.get(live_type).map_or(LiveId(0), | info | info.type_name);
+                write!(self.out, "{{{{{}}}}} {{", type_name).unwrap();
+                return self.write_block(index + 1, depth)
+            }
+            LiveValue::Deref {live_type, clone, ..} => {
+                let type_name = self.registry.live_type_infos.get(live_type).map_or(LiveId(0), | info | info.type_name);
+                write!(self.out, "{{{{{}}}}}<{}> {{", type_name, clone).unwrap();
+                return self.write_block(index + 1, depth)
+            }
+            LiveValue::NamedEnum(variant) => {
+                write!(self.out, "{} {{", variant).unwrap();
+                return self.write_block(index + 1, depth)
+            }
+            LiveValue::If => {
+                self.out.push_str("if ");
+                let mut index = self.write_value(index + 1, depth);
+                self.out.push(' ');
+                index = self.write_value(index, depth);
+                if !self.nodes[index].is_close() {
+                    self.out.push_str(" else ");
+                    // an `else` that only holds another `if` was an `else if`
+                    let first = index + 1;
+                    if self.nodes[first].value == LiveValue::If && self.nodes[self.nodes.skip_node(first)].is_close() {
+                        self.write_value(first, depth);
+                        index = self.nodes.skip_node(index);
+                    }
+                    else {
+                        index = self.write_value(index, depth);
+                    }
+                }
+                return index + 1
+            }
+            LiveValue::For {binding} => {
+                write!(self.out, "for {} in ", binding).unwrap();
+                let index = self.write_value(index + 1, depth);
+                self.out.push_str("..");
+                let index = self.write_value(index, depth);
+                self.out.push(' ');
+                return self.write_value(index, depth) + 1
+            }
+            LiveValue::TupleEnum(variant) => {
+                write!(self.out, "{}(", variant).unwrap();
+                let index = self.write_list(index + 1, depth);
+                self.out.push(')');
+                return index
+            }
+            LiveValue::Array => {
+                self.out.push('[');
+                let index = self.write_list(index + 1, depth);
+                self.out.push(']');
+                return index
+            }
+            LiveValue::Expr {..} => {
+                self.out.push('(');
+                let index = self.write_expr(index + 1);
+                self.out.push(')');
+                return index + 1
+            }
+            LiveValue::Dependency(path) => {
+                self.out.push_str("dep(");
+                write_string(&mut self.out, path);
+                self.out.push(')');
+            }
+            LiveValue::IdPath(path) => {
+                for (i, id) in path.iter().enumerate() {
+                    if i > 0 {
+                        self.out.push('.');
+                    }
+                    write!(self.out, "{}", id).unwrap();
+                }
+            }
+            LiveValue::Id(id) | LiveValue::BareEnum(id) => write!(self.out, "{}", id).unwrap(),
+            value => self.write_scalar(value)
+        }
+        index + 1
+    }
+
+    fn write_scalar(&mut self, value: &LiveValue) {
+        match value {
+            LiveValue::None => self.out.push_str("None"),
+            LiveValue::Str(s) => write_string(&mut self.out, s),
+            LiveValue::String(s) => write_string(&mut self.out, s),
+            LiveValue::InlineString(s) => write_string(&mut self.out, s.as_str()),
+            LiveValue::Bool(v) => write!(self.out, "{}", v).unwrap(),
+            LiveValue::Int64(v) => write!(self.out, "{}", v).unwrap(),
+            LiveValue::Uint64(v) => write!(self.out, "{}", v).unwrap(),
+            LiveValue::Float32(v) => write_float(&mut self.out, format!("{}", v)),
+            LiveValue::Float64(v) => write_float(&mut self.out, format!("{}", v)),
+            LiveValue::Color(v) => write!(self.out, "#{:08x}", v).unwrap(),
+            LiveValue::Vec2(v) => self.write_vec("vec2", &[v.x, v.y]),
+            LiveValue::Vec3(v) => self.write_vec("vec3", &[v.x, v.y, v.z]),
+            LiveValue::Vec4(v) => self.write_vec("vec4", &[v.x, v.y, v.z, v.w]),
+            _ => ()
+        }
+    }
+
+    fn write_vec(&mut self, name: &str, values: &[f32]) {
+        write!(self.out, "{}(", name).unwrap();
+        for (i, v) in values.iter().enumerate() {
+            if i > 0 {
+                self.out.push_str(", ");
+            }
+            write_float(&mut self.out, format!("{}", v));
+        }
+        self.out.push(')');
+    }
+
+    /// The body of an object or class after its `{`, with the closing brace on its own line
+    /// unless it is empty.
+    fn write_block(&mut self, index: usize, depth: usize) -> usize {
+        if self.nodes.get(index).is_some_and( | node | node.is_close()) {
+            self.out.push('}');
+            return index + 1
+        }
+        self.out.push('\n');
+        let index = self.write_body(index, depth + 1);
+        self.indent(depth);
+        self.out.push('}');
+        index
+    }
+
+    /// The items of an array or a tuple enum, separated by commas, up to and past the close.
+    fn write_list(&mut self, mut index: usize, depth: usize) -> usize {
+        let mut first = true;
+        while index < self.nodes.len() {
+            if self.nodes[index].is_close() {
+                return index + 1
+            }
+            if !first {
+                self.out.push_str(", ");
+            }
+            first = false;
+            index = self.write_value(index, depth);
+        }
+        index
+    }
+
+    /// An expression, stored in prefix order. Operands of operators are put in parentheses so
+    /// precedence never has to be worked out. Returns the index after it.
+    fn write_expr(&mut self, index: usize) -> usize {
+        let Some(node) = self.nodes.get(index) else {return index};
+        match &node.value {
+            LiveValue::ExprBinOp(op) => {
+                let index = self.write_operand(index + 1);
+                write!(self.out, " {} ", bin_op_str(*op)).unwrap();
+                self.write_operand(index)
+            }
+            LiveValue::ExprUnOp(op) => {
+                self.out.push_str(match op {LiveUnOp::Not => "!", LiveUnOp::Neg => "-"});
+                self.write_operand(index + 1)
+            }
+            LiveValue::ExprCall {ident: live_id!(cond), args: 3} => {
+                // the parser turns `a ? b : c` into a call
+                let index = self.write_operand(index + 1);
+                self.out.push_str(" ? ");
+                let index = self.write_operand(index);
+                self.out.push_str(" : ");
+                self.write_operand(index)
+            }
+            LiveValue::ExprCall {ident, args} => {
+                write!(self.out, "{}(", ident).unwrap();
+                let mut index = index + 1;
+                for arg in 0..*args {
+                    if arg > 0 {
+                        self.out.push_str(", ");
+                    }
+                    index = self.write_expr(index);
+                }
+                self.out.push(')');
+                index
+            }
+            LiveValue::ExprMember(ident) => {
+                let index = self.write_operand(index + 1);
+                write!(self.out, ".{}", ident).unwrap();
+                index
+            }
+            LiveValue::Id(id) => {
+                write!(self.out, "{}", id).unwrap();
+                index + 1
+            }
+            value => {
+                self.write_scalar(value);
+                index + 1
+            }
+        }
+    }
+
+    fn write_operand(&mut self, index: usize) -> usize {
+        let is_op = self.nodes.get(index).is_some_and( | node | matches!(
+            node.value,
+            LiveValue::ExprBinOp(_) | LiveValue::ExprUnOp(_) | LiveValue::ExprCall {ident: live_id!(cond), args: 3}
+        ));
+        if is_op {
+            self.out.push('(');
+        }
+        let index = self.write_expr(index);
+        if is_op {
+            self.out.push(')');
+        }
+        index
+    }
+
+    fn design_info(&self, node: &LiveNode, design_info: LiveDesignInfoIndex) -> Option<String> {
+        if design_info.is_invalid() {
+            return None
+        }
+        let file_id = node.origin.token_id()?.file_id()?;
+        let info = self.registry.live_files.get(file_id.to_index())?.original.design_info.get(design_info.index())?;
+        Some(info.to_string())
+    }
+
+    /// A `fn` written back from the tokens it was parsed from, keeping its line breaks and its
+    /// indentation relative to the `fn`.
+    fn write_dsl(&mut self, node: &LiveNode, token_start: usize, token_count: usize, depth: usize) {
+        let tokens = node.origin.token_id()
+            .and_then( | token_id | token_id.file_id())
+            .and_then( | file_id | self.registry.live_files.get(file_id.to_index()))
+            .and_then( | file | file.original.tokens.get(token_start..token_start + token_count));
+        let Some(tokens) = tokens else {
+            write!(self.out, "fn {}() {{}}", node.id).unwrap();
+            return
+        };
+        let base_column = tokens[0].span.start.column;
+        let mut prev: Option<&TokenWithSpan> = None;
+        for token in tokens {
+            if let Some(prev) = prev {
+                if token.span.start.line > prev.span.end.line {
+                    self.out.push('\n');
+                    self.indent(depth);
+                    for _ in base_column..token.span.start.column {
+                        self.out.push(' ');
+                    }
+                }
+                else if token.span.start.column > prev.span.end.column {
+                    self.out.push(' ');
+                }
+            }
+            write_token(&mut self.out, &token.token);
+            prev = Some(token);
+        }
+    }
+}
+
+fn write_token(out: &mut String, token: &LiveToken) {
+    match token {
+        LiveToken::String(s) => write_string(out, s),
+        LiveToken::Float(v) => write_float(out, format!("{}", v)),
+        LiveToken::Color(v) => write!(out, "#{:08x}", v).unwrap(),
+        LiveToken::Open(Delim::Brace) => out.push('{'),
+        LiveToken::Close(Delim::Brace) => out.push('}'),
+        token => write!(out, "{}", token).unwrap()
+    }
+}
+
+/// The tokenizer only knows the `\\`, `\"` and `\n` escapes, everything else is written as is.
+fn write_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            c => out.push(c)
+        }
+    }
+    out.push('"');
+}
+
+/// Floats keep a `.` so they don't read back as integers.
+fn write_float(out: &mut String, float: String) {
+    let is_int_like = float.bytes().all( | b | b.is_ascii_digit() || b == b'-');
+    out.push_str(&float);
+    if is_int_like {
+        out.push_str(".0");
+    }
+}
+
+fn bin_op_str(op: LiveBinOp) -> &'static str {
+    match op {
+        LiveBinOp::Or => "||",
+        LiveBinOp::And => "&&",
+        LiveBinOp::Eq => "==",
+        LiveBinOp::Ne => "!=",
+        LiveBinOp::Lt => "<",
+        LiveBinOp::Le => "<=",
+        LiveBinOp::Gt => ">",
+        LiveBinOp::Ge => ">=",
+        LiveBinOp::Add => "+",
+        LiveBinOp::Sub => "-",
+        LiveBinOp::Mul => "*",
+        LiveBinOp::Div => "/",
+    }
+}
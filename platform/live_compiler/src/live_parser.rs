@@ -181,6 +181,7 @@ impl<'a> LiveParser<'a> {
         let first_module_id = self.expect_ident() ?;
         self.expect_token(LiveToken::Punct(live_id!(::))) ?;
         let mut module = String::new();
+        let mut object_path = Vec::new();
         let mut last_id = LiveId(0);
         
         module.push_str(&format!("{}", first_module_id));
@@ -192,7 +193,13 @@ impl<'a> LiveParser<'a> {
                     if !self.accept_token(LiveToken::Punct(live_id!(::))) {
                         break;
                     }
-                    module.push_str(&format!("::{}", id));
+                    // modules are snake_case, so a capitalised segment starts a path into an object
+                    if !object_path.is_empty() || id.as_string(| s | s.map_or(false, | s | s.starts_with(| c: char | c.is_ascii_uppercase()))) {
+                        object_path.push(id);
+                    }
+                    else {
+                        module.push_str(&format!("::{}", id));
+                    }
                 },
                 LiveToken::Punct(live_id!(*)) => {
                     self.skip_token();
@@ -215,6 +222,7 @@ impl<'a> LiveParser<'a> {
             id: last_id,
             value: LiveValue::Import(Box::new(LiveImport{
                 module_id: LiveModuleId(crate_id, LiveId::from_str_with_lut(&module).unwrap()),
+                object_path,
                 import_id,
             }))
         });
@@ -279,8 +287,8 @@ impl<'a> LiveParser<'a> {
             }
             let edit_info_index = ld.edit_info.len();
             
-            if edit_info_index > 0x3e0 {
-                return Err(self.error("Used more than 64 .{..} edit info fields in a file, we dont have the bitspace for that in our u64.".to_string(), live_error_origin!()))
+            if edit_info_index > 0xfe0 {
+                return Err(self.error("Used more than 254 .{..} edit info fields in a file, we dont have the bitspace for that in LiveNodeOrigin.".to_string(), live_error_origin!()))
             }
             
             ld.edit_info.push(LiveNode {
@@ -890,6 +898,20 @@ impl<'a> LiveParser<'a> {
                     let token_id = self.get_token_id();
                     self.skip_token();
                     
+                    if matches!(prop_id, live_id!(if) | live_id!(for)) && !matches!(
+                        self.peek_token(),
+                        LiveToken::Punct(live_id!(:)) | LiveToken::Punct(live_id!(=))
+                    ) {
+                        if prop_id == live_id!(if) {
+                            self.expect_if(LiveId(nameless_id), token_id, ld) ?;
+                        }
+                        else {
+                            self.expect_for(LiveId(nameless_id), token_id, ld) ?;
+                        }
+                        nameless_id += 1;
+                        self.accept_optional_delim();
+                        continue;
+                    }
                     //let span = self.begin_span();
                     // next
                     // there is another token coming
@@ -941,6 +963,77 @@ impl<'a> LiveParser<'a> {
         Err(self.error("Eof in class body".to_string(), live_error_origin!()))
     }
     
+    /// `if cond {..} else if cond {..} else {..}` in a class body, after the `if`. Its bodies
+    /// hold props like the class body does, and the expander keeps the ones of the branch that
+    /// `cond` picks.
+    fn expect_if(&mut self, id: LiveId, token_id: LiveTokenId, ld: &mut LiveOriginal) -> Result<(), LiveError> {
+        ld.nodes.push(LiveNode {
+            origin: LiveNodeOrigin::from_token_id(token_id).with_prop_type(LivePropType::Instance),
+            id,
+            value: LiveValue::If
+        });
+        self.expect_expression(live_id!(cond), LiveNodeOrigin::from_token_id(self.get_token_id()), ld) ?;
+        self.expect_control_body(live_id!(then), ld) ?;
+        if self.accept_token(LiveToken::Ident(live_id!(else))) {
+            let token_id = self.get_token_id();
+            if self.accept_token(LiveToken::Ident(live_id!(if))) {
+                ld.nodes.push(LiveNode {
+                    origin: LiveNodeOrigin::from_token_id(token_id),
+                    id: live_id!(else),
+                    value: LiveValue::Object
+                });
+                self.expect_if(LiveId(1), token_id, ld) ?;
+                ld.nodes.push(LiveNode {
+                    origin: LiveNodeOrigin::from_token_id(self.get_token_id()),
+                    id: live_id!(else),
+                    value: LiveValue::Close
+                });
+            }
+            else {
+                self.expect_control_body(live_id!(else), ld) ?;
+            }
+        }
+        ld.nodes.push(LiveNode {
+            origin: LiveNodeOrigin::from_token_id(self.get_token_id()),
+            id,
+            value: LiveValue::Close
+        });
+        Ok(())
+    }
+    
+    /// `for i in start..end {..}` in a class body, after the `for`. The expander puts a copy
+    /// of the props of its body in place for every `i`.
+    fn expect_for(&mut self, id: LiveId, token_id: LiveTokenId, ld: &mut LiveOriginal) -> Result<(), LiveError> {
+        let binding = self.expect_ident() ?;
+        self.expect_token(LiveToken::Ident(live_id!(in))) ?;
+        ld.nodes.push(LiveNode {
+            origin: LiveNodeOrigin::from_token_id(token_id).with_prop_type(LivePropType::Instance),
+            id,
+            value: LiveValue::For {binding}
+        });
+        self.expect_expression(live_id!(start), LiveNodeOrigin::from_token_id(self.get_token_id()), ld) ?;
+        self.expect_token(LiveToken::Punct(live_id!(..))) ?;
+        self.expect_expression(live_id!(end), LiveNodeOrigin::from_token_id(self.get_token_id()), ld) ?;
+        self.expect_control_body(live_id!(body), ld) ?;
+        ld.nodes.push(LiveNode {
+            origin: LiveNodeOrigin::from_token_id(self.get_token_id()),
+            id,
+            value: LiveValue::Close
+        });
+        Ok(())
+    }
+    
+    fn expect_control_body(&mut self, id: LiveId, ld: &mut LiveOriginal) -> Result<(), LiveError> {
+        let token_id = self.get_token_id();
+        self.expect_token(LiveToken::Open(Delim::Brace)) ?;
+        ld.nodes.push(LiveNode {
+            origin: LiveNodeOrigin::from_token_id(token_id),
+            id,
+            value: LiveValue::Object
+        });
+        self.expect_live_class(false, id, ld)
+    }
+    
     pub fn expect_prop_type(&mut self)->Result<LivePropType, LiveError>{
         Ok(if self.accept_token(LiveToken::Punct(live_id!(:))){
             LivePropType::Field
@@ -1072,6 +1165,13 @@ impl<'a> LiveParser<'a> {
                         value: LiveValue::Color(v)
                     });
                 }
+                Expr::String {token_id, v} => {
+                    ld.nodes.push(LiveNode {
+                        origin: LiveNodeOrigin::from_token_id(token_id).with_prop_type(LivePropType::Nameless),
+                        id: LiveId::empty(),
+                        value: LiveValue::String(v)
+                    });
+                }
             }
         }
         
@@ -1262,6 +1362,11 @@ impl<'a> LiveParser<'a> {
                 let token_id = self.get_token_id();
                 Ok(Expr::Color {token_id, v})
             }
+            LiveToken::String(v) => {
+                self.skip_token();
+                let token_id = self.get_token_id();
+                Ok(Expr::String {token_id, v})
+            }
             LiveToken::Open(Delim::Paren) => {
                 self.skip_token();
                 let expr = self.expect_expr() ?;
@@ -1332,6 +1437,10 @@ enum Expr {
     Color {
         token_id: LiveTokenId,
         v: u32
+    },
+    String {
+        token_id: LiveTokenId,
+        v: Rc<String>
     }
 }
 
@@ -46,7 +46,9 @@ pub struct LiveRegistry {
     //pub ignore_no_dsl: HashSet<LiveId>,
     pub main_module: Option<LiveTypeInfo>,
     pub components: LiveComponentRegistries,
-    pub package_root: Option<String>
+    pub package_root: Option<String>,
+    pub (crate) live_theme: LiveId,
+    pub (crate) theme_modules: BTreeMap<LiveModuleId, BTreeMap<LiveId, LiveModuleId>>,
 }
 
 impl Default for LiveRegistry {
@@ -58,7 +60,9 @@ impl Default for LiveRegistry {
             live_files: Vec::new(),
             live_type_infos: Default::default(),
             components: LiveComponentRegistries::default(),
-            package_root: None
+            package_root: None,
+            live_theme: LiveId::empty(),
+            theme_modules: Default::default(),
         }
     }
 }
@@ -527,6 +531,16 @@ impl LiveRegistry {
         self.live_files[token_id.file_id().unwrap().to_index()].original.token_id_to_span(token_id)
     }
     
+    /// The span of the source `node` was parsed from, from its name through its value, for a
+    /// node of an original or an expanded document. Use `TextSpan::to_byte_range` with the
+    /// content of the file to get at the bytes.
+    pub fn node_to_source_span(&self, node: &LiveNode) -> Option<TextSpan> {
+        let token_id = node.origin.token_id() ?;
+        let original = &self.live_files.get(token_id.file_id() ?.to_index()) ?.original;
+        let index = original.nodes.iter().rposition( | node | node.origin.token_id() == Some(token_id)) ?;
+        original.node_span(index)
+    }
+    
     pub fn tokenize_from_str(source: &str, start_pos: TextPos, file_id: LiveFileId) -> Result<Vec<TokenWithSpan>, LiveError> {
         let mut chars = Vec::new();
         chars.extend(source.chars());
@@ -679,6 +693,55 @@ impl LiveRegistry {
         Ok(tokens)
     }
     
+    /// Registers the module `module_id` as the `theme` variant of the theme module `base`. It
+    /// defines the names `base` does, and imports of `base` resolve to it while `theme` is set
+    /// with `set_live_theme`, except in the variants of `base` themselves, so a variant can
+    /// import `base` and only override what differs.
+    pub fn register_live_theme(&mut self, base: LiveModuleId, theme: LiveId, module_id: LiveModuleId) {
+        self.theme_modules.entry(base).or_default().insert(theme, module_id);
+        // files importing base have to be expanded after every variant of it
+        for file in &mut self.live_files {
+            if file.deps.contains(&base) && file.module_id != module_id {
+                file.deps.insert(module_id);
+            }
+        }
+    }
+    
+    pub fn live_theme(&self) -> LiveId {
+        self.live_theme
+    }
+    
+    /// The module an import of `module_id` in `in_module` resolves to under the current theme.
+    pub fn themed_module_id(&self, module_id: LiveModuleId, in_module: LiveModuleId) -> LiveModuleId {
+        if let Some(variants) = self.theme_modules.get(&module_id) {
+            if variants.values().all( | variant | *variant != in_module) {
+                if let Some(variant) = variants.get(&self.live_theme) {
+                    return *variant
+                }
+            }
+        }
+        module_id
+    }
+    
+    /// Switches imports of theme modules over to their `theme` variant, and expands again the
+    /// documents that import one. `LiveId::empty()` is the theme the base modules define.
+    /// Returns whether the theme changed.
+    pub fn set_live_theme(&mut self, theme: LiveId, errors: &mut Vec<LiveError>) -> bool {
+        if self.live_theme == theme {
+            return false
+        }
+        self.live_theme = theme;
+        let theme_modules = &self.theme_modules;
+        for file in &mut self.live_files {
+            if file.deps.iter().any( | dep | theme_modules.contains_key(dep)) {
+                file.reexpand = true;
+                file.generation.next_gen();
+            }
+        }
+        self.expand_all_documents(errors);
+        true
+    }
+    
     pub fn process_file_changes(&mut self, changes: Vec<LiveFileChange>, errors:&mut Vec<LiveError >){
         let mut any_changes = false;
         for change in changes {
@@ -799,6 +862,12 @@ impl LiveRegistry {
             }
         }
         
+        for dep in deps.clone() {
+            if let Some(variants) = self.theme_modules.get(&dep) {
+                deps.extend(variants.values().filter( | variant | **variant != own_module_id));
+            }
+        }
+        
         let live_file = LiveFile {
             cargo_manifest_path: cargo_manifest_path.to_string(),
             reexpand: true,
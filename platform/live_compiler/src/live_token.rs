@@ -189,24 +189,24 @@ impl fmt::Display for LiveToken {
 impl LiveTokenId {
     pub fn new(file_id: LiveFileId, token: usize) -> Self {
         let file_id = file_id.to_index();
-        if file_id > 0x3fe || token > 0x3ffff {
+        if file_id > 0xfffe || token > 0xffff_ffff {
             panic!();
         }
         LiveTokenId(
-            (((file_id as u32 + 1) & 0x3ff) << 18) | ((token as u32) & 0x3ffff)
+            ((file_id as u64 + 1) << 32) | (token as u64)
         )
     }
     
     pub fn is_empty(&self) -> bool {
-        ((self.0 >> 18) & 0x3ff) == 0
+        ((self.0 >> 32) & 0xffff) == 0
     }
     
     pub fn token_index(&self) -> usize {
-        (self.0 & 0x3ffff) as usize
+        (self.0 & 0xffff_ffff) as usize
     }
     
     pub fn file_id(&self) -> Option<LiveFileId> {
-        let id = (self.0 >> 18) & 0x3ff;
+        let id = (self.0 >> 32) & 0xffff;
         if id == 0{
             None
         }
@@ -215,12 +215,12 @@ impl LiveTokenId {
         }
     }
     
-    pub fn to_bits(&self) -> u32 {self.0}
-    pub fn from_bits(v: u32) -> Option<Self> {
-        if (v & 0xf000_0000) != 0 {
+    pub fn to_bits(&self) -> u64 {self.0}
+    pub fn from_bits(v: u64) -> Option<Self> {
+        if (v & 0xffff_0000_0000_0000) != 0 {
             panic!();
         }
-        if ((v >> 18) & 0x3ff) == 0 {
+        if ((v >> 32) & 0xffff) == 0 {
             None
         } else {
             Some(Self(v))
@@ -229,7 +229,7 @@ impl LiveTokenId {
 }
 
 #[derive(Clone, Default, Copy, Eq, Ord, Hash, PartialOrd, PartialEq)]
-pub struct LiveTokenId(u32);
+pub struct LiveTokenId(u64);
 
 impl fmt::Debug for LiveTokenId {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
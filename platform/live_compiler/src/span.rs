@@ -1,5 +1,5 @@
 use{
-    std::fmt,
+    std::{fmt, ops::Range},
     crate::{
    //     makepad_live_tokenizer::Position,
         live_token::LiveTokenId,
@@ -40,6 +40,26 @@ impl From<LiveTokenId> for TokenSpan {
     }
 }
 
+impl TextSpan {
+    /// The byte range of the span in `text`, the content of the file it is in, counting lines
+    /// from 0 and columns in chars.
+    pub fn to_byte_range(&self, text: &str) -> Option<Range<usize>> {
+        Some(self.start.to_byte_offset(text) ?..self.end.to_byte_offset(text) ?)
+    }
+}
+
+impl TextPos {
+    pub fn to_byte_offset(&self, text: &str) -> Option<usize> {
+        let mut line_start = 0;
+        for _ in 0..self.line {
+            line_start += text[line_start..].find('\n') ? + 1;
+        }
+        let line = &text[line_start..];
+        let column = line.char_indices().map( | (index, _) | index).chain(Some(line.len())).nth(self.column as usize) ?;
+        Some(line_start + column)
+    }
+}
+
 impl fmt::Display for TextSpan {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "Span(start:{}, end:{}, file_id:{})", self.start, self.end, self.file_id.to_index())
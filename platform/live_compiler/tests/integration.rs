@@ -1,4 +1,34 @@
+use makepad_live_compiler::*;
+use makepad_live_compiler::span::TextPos;
+
 #[test]
 fn main() {
     // todo :)
-}
\ No newline at end of file
+}
+
+fn register(registry: &mut LiveRegistry, name: &str, source: String) -> LiveFileId {
+    let module_id = LiveModuleId(LiveId::from_str("test"), LiveId::from_str(name));
+    registry.register_live_file(&format!("{}.rs", name), "", module_id, source, vec![], TextPos::default())
+        .unwrap_or_else( | err | panic!("{}", err))
+}
+
+fn strings(registry: &LiveRegistry, file_id: LiveFileId) -> Vec<String> {
+    registry.live_files[file_id.to_index()].original.nodes.iter().filter_map( | node | match &node.value {
+        LiveValue::Str(s) => Some(s.to_string()),
+        LiveValue::String(s) => Some(s.to_string()),
+        LiveValue::InlineString(s) => Some(s.as_str().to_string()),
+        _ => None
+    }).collect()
+}
+
+#[test]
+fn dsl_string_round_trip() {
+    let mut registry = LiveRegistry::default();
+    let source = "Foo = {quote: \"say \\\"hi\\\"\", slash: \"a\\\\b\", newline: \"a\\nb\", tab: \"a\tb\"}";
+    let a = register(&mut registry, "a", source.to_string());
+    assert_eq!(strings(&registry, a), ["say \"hi\"", "a\\b", "a\nb", "a\tb"]);
+
+    let dsl = registry.live_document_to_dsl(&registry.live_files[a.to_index()].original.nodes);
+    let b = register(&mut registry, "b", dsl);
+    assert_eq!(strings(&registry, a), strings(&registry, b));
+}
@@ -76,7 +76,45 @@ pub trait BackendWriter {
     
     fn write_ty_lit(&self, string: &mut String, ty_lit: TyLit);
     fn write_builtin_call_ident(&self, string: &mut String, ident: Ident, arg_exprs: &[Expr]);
-    
+
+    // the defaults below produce the C-like syntax shared by glsl, metal and hlsl
+
+    fn write_fn_def_start(&self, string: &mut String, ident: &dyn fmt::Display, return_ty: &Ty) {
+        self.write_var_decl(string, "", false, false, ident, return_ty);
+        write!(string, "(").unwrap();
+    }
+
+    fn write_fn_def_end(&self, string: &mut String, _return_ty: &Ty) {
+        write!(string, ") ").unwrap();
+    }
+
+    fn write_let_decl(&self, string: &mut String, ident: &dyn fmt::Display, ty: &Ty) {
+        self.write_var_decl(string, "", false, false, ident, ty);
+    }
+
+    fn write_for_var_decl(&self, string: &mut String, ident: Ident) {
+        write!(string, "int {}", ident).unwrap();
+    }
+
+    // writes cond exprs as select(false, true, cond) instead of (cond ? true : false)
+    fn cond_expr_is_select(&self) -> bool {
+        false
+    }
+
+    // inout params are passed as pointers: &arg at the callsite, (*param) in the body
+    fn inout_is_ptr(&self) -> bool {
+        false
+    }
+
+    // multi component swizzles can't be assigned to, so split them up per component
+    fn needs_swizzle_assign_split(&self) -> bool {
+        false
+    }
+
+    // assignments are statements, not expressions, so they can't be wrapped in parentheses
+    fn assign_is_stmt(&self) -> bool {
+        false
+    }
 }
 
 pub struct BlockGenerator<'a> {
@@ -100,9 +138,9 @@ pub fn generate_cons_fn(backend_writer:&dyn BackendWriter, string: &mut String,
     if !backend_writer.use_cons_fn(&cons_name) {
         return
     }
-    backend_writer.write_ty_lit(string, ty_lit);
-    write!(string, " {}(", cons_name).unwrap();
-    
+    let ty = ty_lit.to_ty();
+    backend_writer.write_fn_def_start(string, &cons_name, &ty);
+
     let mut sep = "";
     if param_tys.len() == 1 {
         backend_writer.write_var_decl(string, sep, false, false, &Ident(live_id!(x)), &param_tys[0]);
@@ -114,14 +152,29 @@ pub fn generate_cons_fn(backend_writer:&dyn BackendWriter, string: &mut String,
         }
     }
     
-    writeln!(string, ") {{").unwrap();
+    backend_writer.write_fn_def_end(string, &ty);
+    writeln!(string, "{{").unwrap();
     write!(string, "    return ").unwrap();
     backend_writer.write_ty_lit(string, ty_lit);
     write!(string, "(").unwrap();
-    let ty = ty_lit.to_ty();
     if param_tys.len() == 1 {
         let param_ty = &param_tys[0];
         match param_ty {
+            Ty::Float if matches!(ty, Ty::Mat2 | Ty::Mat3 | Ty::Mat4) => {
+                // a scalar makes a diagonal matrix
+                let size = match ty {
+                    Ty::Mat2 => 2,
+                    Ty::Mat3 => 3,
+                    _ => 4,
+                };
+                let mut sep = "";
+                for col_index in 0..size {
+                    for row_index in 0..size {
+                        write!(string, "{}{}", sep, if col_index == row_index {"x"} else {"0.0"}).unwrap();
+                        sep = ", ";
+                    }
+                }
+            }
             Ty::Bool | Ty::Int | Ty::Float => {
                 let mut sep = "";
                 for _ in 0..ty.slots() {
@@ -285,9 +338,11 @@ impl<'a> BlockGenerator<'a> {
         } else {
             -1
         };
+        write!(self.string, "for (").unwrap();
+        self.backend_writer.write_for_var_decl(self.string, ident);
         write!(
             self.string,
-            "for (int {0} = {1}; {0} {2} {3}; {0} {4} {5}) ",
+            " = {1}; {0} {2} {3}; {0} {4} {5}) ",
             ident,
             if from <= to {from} else {from - 1},
             if from <= to {"<"} else {">="},
@@ -358,11 +413,8 @@ impl<'a> BlockGenerator<'a> {
         expr: &Option<Expr>,
         shadow: &Cell<Option<ScopeSymShadow >>
     ) {
-        self.backend_writer.write_var_decl(
+        self.backend_writer.write_let_decl(
             &mut self.string,
-            "",
-            false, 
-            false,
             &DisplayVarName(ident, shadow.get().unwrap()),
             ty.borrow().as_ref().unwrap()
         );
@@ -566,6 +618,16 @@ impl<'a> ExprGenerator<'a> {
         expr_if_true: &Expr,
         expr_if_false: &Expr,
     ) {
+        if self.backend_writer.cond_expr_is_select() {
+            write!(self.string, "select(").unwrap();
+            self.generate_expr(expr_if_false);
+            write!(self.string, ", ").unwrap();
+            self.generate_expr(expr_if_true);
+            write!(self.string, ", ").unwrap();
+            self.generate_expr(expr);
+            write!(self.string, ")").unwrap();
+            return
+        }
         write!(self.string, "(").unwrap();
         self.generate_expr(expr);
         write!(self.string, " ? ").unwrap();
@@ -576,7 +638,34 @@ impl<'a> ExprGenerator<'a> {
     }
     
     fn generate_bin_expr(&mut self, _span: TokenSpan, op: BinOp, left_expr: &Expr, right_expr: &Expr) {
-        
+
+        if self.backend_writer.needs_swizzle_assign_split() {
+            if let (BinOp::Assign | BinOp::AddAssign | BinOp::SubAssign | BinOp::MulAssign | BinOp::DivAssign, ExprKind::Field {expr, field_ident, ..}) = (op, &left_expr.kind) {
+                let swizzle = field_ident.to_string();
+                let is_vec = match expr.ty.borrow().as_ref().unwrap() {
+                    Ty::Bvec2 | Ty::Bvec3 | Ty::Bvec4 | Ty::Ivec2 | Ty::Ivec3 | Ty::Ivec4 | Ty::Vec2 | Ty::Vec3 | Ty::Vec4 => true,
+                    _ => false
+                };
+                if is_vec && swizzle.len() > 1 {
+                    // evaluate the right side once, then assign it component by component
+                    let rhs_is_scalar = right_expr.ty.borrow().as_ref().unwrap().slots() == 1;
+                    write!(self.string, "{{let swizzle_rhs = ").unwrap();
+                    self.generate_expr(right_expr);
+                    write!(self.string, "; ").unwrap();
+                    for (index, component) in swizzle.chars().enumerate() {
+                        self.generate_expr(expr);
+                        write!(self.string, ".{} {} swizzle_rhs", component, op).unwrap();
+                        if !rhs_is_scalar {
+                            write!(self.string, ".{}", ['x', 'y', 'z', 'w'][index]).unwrap();
+                        }
+                        write!(self.string, "; ").unwrap();
+                    }
+                    write!(self.string, "}}").unwrap();
+                    return
+                }
+            }
+        }
+
         // if left_expr or right_expr is a matrix, HLSL needs to use mul()
         let left_is_mat = match left_expr.ty.borrow().as_ref().unwrap() {
             Ty::Mat2 | Ty::Mat3 | Ty::Mat4 => true,
@@ -650,6 +739,24 @@ impl<'a> ExprGenerator<'a> {
             }
         }
         
+        if self.backend_writer.assign_is_stmt() {
+            if let BinOp::Assign | BinOp::AddAssign | BinOp::SubAssign | BinOp::MulAssign | BinOp::DivAssign = op {
+                // chained assignments become consecutive statements: a = b = c turns into b = c; a = b
+                if let ExprKind::Bin {op: BinOp::Assign, left_expr: ref inner_left_expr, ..} = right_expr.kind {
+                    self.generate_expr(right_expr);
+                    write!(self.string, "; ").unwrap();
+                    self.generate_expr(left_expr);
+                    write!(self.string, " {} ", op).unwrap();
+                    self.generate_expr(inner_left_expr);
+                    return
+                }
+                self.generate_expr(left_expr);
+                write!(self.string, " {} ", op).unwrap();
+                self.generate_expr(right_expr);
+                return
+            }
+        }
+        
         write!(self.string, "(").unwrap();
         self.generate_expr(left_expr);
         write!(self.string, " {} ", op).unwrap();
@@ -710,8 +817,9 @@ impl<'a> ExprGenerator<'a> {
                 fn_def.ident
             )).unwrap();
             
+            let param_offset = fn_def.params.len() - arg_exprs.len();
             let mut sep = "";
-            for arg_expr in arg_exprs {
+            for (arg_index, arg_expr) in arg_exprs.iter().enumerate() {
                 // check if the args is a closure, ifso skip it
                 match arg_expr.ty.borrow().as_ref().unwrap(){
                     Ty::ClosureDef(_)=>{
@@ -721,7 +829,7 @@ impl<'a> ExprGenerator<'a> {
                 }
                 
                 write!(self.string, "{}", sep).unwrap();
-                self.generate_expr(arg_expr);
+                self.generate_call_arg(&fn_def.params[arg_index + param_offset], arg_expr);
                 sep = ", ";
             }
             // and now the closed over values
@@ -747,10 +855,11 @@ impl<'a> ExprGenerator<'a> {
         }
         else {
             write!(self.string, "{}_{} (", fn_def.fn_ptr, fn_def.ident).unwrap();
+            let param_offset = fn_def.params.len() - arg_exprs.len();
             let mut sep = "";
-            for arg_expr in arg_exprs {
+            for (arg_index, arg_expr) in arg_exprs.iter().enumerate() {
                 write!(self.string, "{}", sep).unwrap();
-                self.generate_expr(arg_expr);
+                self.generate_call_arg(&fn_def.params[arg_index + param_offset], arg_expr);
                 sep = ", ";
             }
 
@@ -760,6 +869,20 @@ impl<'a> ExprGenerator<'a> {
         }
     }
     
+    fn generate_call_arg(&mut self, param: &Param, arg_expr: &Expr) {
+        if param.is_inout && self.backend_writer.inout_is_ptr() {
+            write!(self.string, "&").unwrap();
+        }
+        self.generate_expr(arg_expr);
+    }
+    
+    fn is_inout_param(&self, ident: Ident, shadow: ScopeSymShadow) -> bool {
+        if let Some(fn_def) = self.fn_def {
+            return fn_def.params.iter().any( | param | param.is_inout && param.ident == ident && param.shadow.get() == Some(shadow))
+        }
+        false
+    }
+    
     fn generate_field_expr(&mut self, _span: TokenSpan, expr: &Expr, field_ident: Ident, ty:&Ty) {
         match expr.ty.borrow().as_ref() {
             Some(Ty::DrawShader(_)) => {
@@ -922,6 +1045,10 @@ impl<'a> ExprGenerator<'a> {
     fn generate_var_expr(&mut self, _span: TokenSpan, kind: &Cell<Option<VarKind >>, _ty: &Option<Ty>) {
         // ok so we have a few varkinds
         match kind.get().unwrap() {
+            VarKind::Local {ident, shadow} | VarKind::MutLocal {ident, shadow}
+                if self.backend_writer.inout_is_ptr() && self.is_inout_param(ident, shadow) => {
+                write!(self.string, "(*{})", DisplayVarName(ident, shadow)).unwrap();
+            }
             VarKind::Local {ident, shadow} => {
                 write!(self.string, "{}", DisplayVarName(ident, shadow)).unwrap();
             }
@@ -960,15 +1087,11 @@ pub struct FnDefGenerator<'a> {
 impl<'a> FnDefGenerator<'a> {
     pub fn generate_fn_def(&mut self) {
         
-        self.backend_writer.write_var_decl(
+        self.backend_writer.write_fn_def_start(
             &mut self.string,
-            "",
-            false,
-            false,
             &DisplayFnName(self.fn_def.fn_ptr, self.fn_def.ident), // here we must expand IdentPath to something
             self.fn_def.return_ty.borrow().as_ref().unwrap()
         );
-        write!(self.string, "(").unwrap();
         let mut sep = "";
         for param in &self.fn_def.params {
             if !param.shadow.get().is_none() {
@@ -985,7 +1108,7 @@ impl<'a> FnDefGenerator<'a> {
             }
         }
         self.backend_writer.write_fn_def_hidden_params(self.string, self.fn_def.hidden_args.borrow().as_ref().unwrap(), sep);
-        write!(self.string, ") ").unwrap();
+        self.backend_writer.write_fn_def_end(self.string, self.fn_def.return_ty.borrow().as_ref().unwrap());
         self.generate_block(&self.fn_def.block);
         writeln!(self.string).unwrap();
         //self.visited.insert(self.decl.ident_path);
@@ -1077,11 +1200,8 @@ impl<'a> FnDefWithClosureArgsGenerator<'a> {
     
     pub fn generate_fn_def_with_closure_args(&mut self) {
         
-        self.backend_writer.write_var_decl(
+        self.backend_writer.write_fn_def_start(
             &mut self.string,
-            "",
-            false,
-            false,
             &DisplayFnNameWithClosureArgs(
                 self.closure_site_info.site_index,
                 self.call_def.fn_ptr,
@@ -1089,7 +1209,6 @@ impl<'a> FnDefWithClosureArgsGenerator<'a> {
             ), // here we must expand IdentPath to something
             self.fn_def.return_ty.borrow().as_ref().unwrap()
         );
-        write!(self.string, "(").unwrap();
         let mut sep = "";
         for param in &self.fn_def.params {
             if !param.shadow.get().is_none() {
@@ -1125,7 +1244,7 @@ impl<'a> FnDefWithClosureArgsGenerator<'a> {
         merged_hidden_args.extend(self.call_def.hidden_args.borrow().as_ref().unwrap().iter().cloned());
         self.backend_writer.write_fn_def_hidden_params(self.string, &merged_hidden_args, sep);
         
-        write!(self.string, ") ").unwrap();
+        self.backend_writer.write_fn_def_end(self.string, self.fn_def.return_ty.borrow().as_ref().unwrap());
         // alright so here the block is generated.. however
         // we need to know the names and the closed-over-args passthrough
         self.generate_block(&self.fn_def.block);
@@ -1168,17 +1287,13 @@ impl<'a> ClosureDefGenerator<'a> {
         
         let mut sep = "";
         
-        if let TyExprKind::ClosureDecl {params, return_ty, ..} = &fn_param.ty_expr.kind {
+        let return_ty = if let TyExprKind::ClosureDecl {params, return_ty, ..} = &fn_param.ty_expr.kind {
             
-            self.backend_writer.write_var_decl(
+            self.backend_writer.write_fn_def_start(
                 &mut self.string,
-                "",
-                false,
-                false,
                 &DisplayClosureName(self.call_def.fn_ptr, self.closure_site_arg.closure_def_index), // here we must expand IdentPath to something
                 return_ty.borrow().as_ref().unwrap(),
             );
-            write!(self.string, "(").unwrap();
             
             // ok we have now params and names
             for (param_index, param) in params.iter().enumerate() {
@@ -1196,10 +1311,11 @@ impl<'a> ClosureDefGenerator<'a> {
                     sep = ", ";
                 }
             }
+            return_ty
         }
         else {
             panic!()
-        }
+        };
         
         for sym in self.closure_def.closed_over_syms.borrow().as_ref().unwrap() {
             if self.backend_writer.write_var_decl(
@@ -1219,7 +1335,8 @@ impl<'a> ClosureDefGenerator<'a> {
         merged_hidden_args.extend(self.call_def.hidden_args.borrow().as_ref().unwrap().iter().cloned());
         self.backend_writer.write_fn_def_hidden_params(self.string, &merged_hidden_args, sep);
         
-        writeln!(self.string, ") {{").unwrap();
+        self.backend_writer.write_fn_def_end(self.string, return_ty.borrow().as_ref().unwrap());
+        writeln!(self.string, "{{").unwrap();
         
         match &self.closure_def.kind {
             ClosureDefKind::Expr(expr) => {
@@ -404,7 +404,7 @@ impl<'a> DrawShaderGenerator<'a> {
         
         write!(self.string, "    return ").unwrap();
         
-        let pixel_def = self.shader_registry.draw_shader_method_decl_from_ident(self.draw_shader_def, Ident(live_id!(pixel))).unwrap();
+        let pixel_def = self.shader_registry.draw_shader_pixel_entry(self.draw_shader_def);
         write!(self.string, "    {}", DisplayFnName(pixel_def.fn_ptr, pixel_def.ident)).unwrap();
         
         write!(self.string, "(").unwrap();
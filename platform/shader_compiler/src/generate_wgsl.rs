@@ -0,0 +1,857 @@
+use {
+    std::{
+        fmt,
+        fmt::Write,
+        collections::BTreeSet,
+    },
+    crate::{
+        makepad_live_id::{
+            live_id,
+            LiveId,
+        },
+        generate::*,
+        shader_ast::*,
+        shader_registry::ShaderRegistry
+    }
+};
+
+// all tables are bound as read-only storage buffers of f32 in group 0,
+// followed by the default sampler and then the textures in field order
+pub const WGSL_TABLE_BINDINGS: [&str; 6] = ["pass", "view", "draw", "user", "live", "const"];
+pub const WGSL_SAMPLER_BINDING: usize = 6;
+pub const WGSL_TEXTURE_BINDING_BASE: usize = 7;
+
+pub fn generate_shader(draw_shader_def: &DrawShaderDef, const_table: &DrawShaderConstTable, shader_registry: &ShaderRegistry) -> String {
+    let mut string = String::new();
+    DrawShaderGenerator {
+        draw_shader_def,
+        const_table,
+        shader_registry,
+        string: &mut string,
+        backend_writer: &WgslBackendWriter {shader_registry, const_table}
+    }
+    .generate_shader();
+    string
+}
+
+struct DrawShaderGenerator<'a> {
+    draw_shader_def: &'a DrawShaderDef,
+    shader_registry: &'a ShaderRegistry,
+    string: &'a mut String,
+    const_table: &'a DrawShaderConstTable,
+    backend_writer: &'a dyn BackendWriter
+}
+
+impl<'a> DrawShaderGenerator<'a> {
+    fn generate_shader(&mut self) {
+        let packed_geometries_slots = self.compute_packed_geometries_slots();
+        let packed_instances_slots = self.compute_packed_instances_slots();
+        let packed_varyings_slots = self.compute_packed_varyings_slots();
+
+        self.generate_bindings();
+        self.generate_builtin_helpers();
+        self.generate_private_decls();
+        self.generate_packed_var_decls("packed_geometry", packed_geometries_slots);
+        self.generate_packed_var_decls("packed_instance", packed_instances_slots);
+        self.generate_packed_var_decls("packed_varying", packed_varyings_slots);
+        write!(self.string, "\n").unwrap();
+        self.generate_vertex_input_struct(packed_geometries_slots, packed_instances_slots);
+        self.generate_varyings_struct(packed_varyings_slots);
+        self.generate_shader_body();
+        self.generate_vertex_main(packed_geometries_slots, packed_instances_slots, packed_varyings_slots);
+        self.generate_pixel_main(packed_varyings_slots);
+    }
+
+    fn generate_bindings(&mut self) {
+        // shaders freely take derivatives inside branches, like they do in glsl
+        writeln!(self.string, "diagnostic(off, derivative_uniformity);").unwrap();
+        for (index, table) in WGSL_TABLE_BINDINGS.iter().enumerate() {
+            writeln!(self.string, "@group(0) @binding({}) var<storage, read> {}_table: array<f32>;", index, table).unwrap();
+        }
+        writeln!(self.string, "@group(0) @binding({}) var default_sampler: sampler;", WGSL_SAMPLER_BINDING).unwrap();
+        let mut index = 0;
+        for field in &self.draw_shader_def.fields {
+            if let DrawShaderFieldKind::Texture {..} = field.kind {
+                write!(self.string, "@group(0) @binding({}) var ", WGSL_TEXTURE_BINDING_BASE + index).unwrap();
+                self.write_var_decl(&DisplayDsIdent(field.ident), field.ty_expr.ty.borrow().as_ref().unwrap());
+                writeln!(self.string, ";").unwrap();
+                index += 1;
+            }
+        }
+        write!(self.string, "\n").unwrap();
+    }
+
+    fn generate_builtin_helpers(&mut self) {
+        let mut builtins = BTreeSet::new();
+        for fn_iter in self.draw_shader_def.all_fns.borrow().iter() {
+            let fn_def = self.shader_registry.all_fns.get(fn_iter).unwrap();
+            builtins.extend(fn_def.builtin_deps.borrow().as_ref().unwrap().iter().cloned());
+        }
+
+        const FLOAT_TYS: [(&str, &str); 3] = [("vec2", "vec2<f32>"), ("vec3", "vec3<f32>"), ("vec4", "vec4<f32>")];
+        const INT_TYS: [(&str, &str); 3] = [("ivec2", "vec2<i32>"), ("ivec3", "vec3<i32>"), ("ivec4", "vec4<i32>")];
+        const BOOL_TYS: [(&str, &str); 3] = [("bvec2", "vec2<bool>"), ("bvec3", "vec3<bool>"), ("bvec4", "vec4<bool>")];
+
+        for builtin in &builtins {
+            match builtin.0 {
+                live_id!(sample2dOES) if builtins.contains(&Ident(live_id!(sample2d))) => (),
+                live_id!(sample2d) | live_id!(sample2dOES) => {
+                    writeln!(self.string, "fn sample2d(tex: texture_2d<f32>, pos: vec2<f32>) -> vec4<f32> {{return textureSampleLevel(tex, default_sampler, pos, 0.0);}}").unwrap();
+                }
+                live_id!(sample2d_rt) => {
+                    writeln!(self.string, "fn sample2d_rt(tex: texture_2d<f32>, pos: vec2<f32>) -> vec4<f32> {{return textureSampleLevel(tex, default_sampler, pos, 0.0);}}").unwrap();
+                }
+                live_id!(mod) => {
+                    // glsl mod rounds towards negative infinity, the wgsl % operator truncates
+                    writeln!(self.string, "fn mod_float_float(x: f32, y: f32) -> f32 {{return x - y * floor(x / y);}}").unwrap();
+                    for (name, ty) in FLOAT_TYS {
+                        writeln!(self.string, "fn mod_{0}_{0}(x: {1}, y: {1}) -> {1} {{return x - y * floor(x / y);}}", name, ty).unwrap();
+                        writeln!(self.string, "fn mod_{0}_float(x: {1}, y: f32) -> {1} {{return x - y * floor(x / y);}}", name, ty).unwrap();
+                    }
+                }
+                live_id!(min) | live_id!(max) => {
+                    // wgsl wants all arguments to be the same type, so scalars are splatted here
+                    for (name, ty) in FLOAT_TYS {
+                        writeln!(self.string, "fn {0}_{1}_float(a: {2}, b: f32) -> {2} {{return {0}(a, {2}(b));}}", builtin, name, ty).unwrap();
+                    }
+                }
+                live_id!(clamp) => {
+                    for (name, ty) in FLOAT_TYS {
+                        writeln!(self.string, "fn clamp_{0}_float_float(x: {1}, a: f32, b: f32) -> {1} {{return clamp(x, {1}(a), {1}(b));}}", name, ty).unwrap();
+                    }
+                }
+                live_id!(step) => {
+                    for (name, ty) in FLOAT_TYS {
+                        writeln!(self.string, "fn step_float_{0}(edge: f32, x: {1}) -> {1} {{return step({1}(edge), x);}}", name, ty).unwrap();
+                    }
+                }
+                live_id!(smoothstep) => {
+                    for (name, ty) in FLOAT_TYS {
+                        writeln!(self.string, "fn smoothstep_float_float_{0}(a: f32, b: f32, x: {1}) -> {1} {{return smoothstep({1}(a), {1}(b), x);}}", name, ty).unwrap();
+                    }
+                }
+                live_id!(equal) | live_id!(notEqual) | live_id!(lessThan) | live_id!(lessThanEqual) | live_id!(greaterThan) | live_id!(greaterThanEqual) => {
+                    let op = match builtin.0 {
+                        live_id!(equal) => "==",
+                        live_id!(notEqual) => "!=",
+                        live_id!(lessThan) => "<",
+                        live_id!(lessThanEqual) => "<=",
+                        live_id!(greaterThan) => ">",
+                        _ => ">=",
+                    };
+                    let is_equality = matches!(builtin.0, live_id!(equal) | live_id!(notEqual));
+                    for (index, (name, ty)) in FLOAT_TYS.iter().chain(INT_TYS.iter()).chain(BOOL_TYS.iter()).enumerate() {
+                        if index >= 6 && !is_equality {
+                            break;
+                        }
+                        writeln!(self.string, "fn {0}_{1}(a: {2}, b: {2}) -> {3} {{return a {4} b;}}", builtin, name, ty, BOOL_TYS[index % 3].1, op).unwrap();
+                    }
+                }
+                live_id!(not) => {
+                    for (name, ty) in BOOL_TYS {
+                        writeln!(self.string, "fn not_{0}(a: {1}) -> {1} {{return !a;}}", name, ty).unwrap();
+                    }
+                }
+                live_id!(matrixCompMult) => {
+                    writeln!(self.string, "fn matrixCompMult_mat2(a: mat2x2<f32>, b: mat2x2<f32>) -> mat2x2<f32> {{return mat2x2<f32>(a[0] * b[0], a[1] * b[1]);}}").unwrap();
+                    writeln!(self.string, "fn matrixCompMult_mat3(a: mat3x3<f32>, b: mat3x3<f32>) -> mat3x3<f32> {{return mat3x3<f32>(a[0] * b[0], a[1] * b[1], a[2] * b[2]);}}").unwrap();
+                    writeln!(self.string, "fn matrixCompMult_mat4(a: mat4x4<f32>, b: mat4x4<f32>) -> mat4x4<f32> {{return mat4x4<f32>(a[0] * b[0], a[1] * b[1], a[2] * b[2], a[3] * b[3]);}}").unwrap();
+                }
+                live_id!(inverse) => {
+                    writeln!(self.string, "{}", WGSL_INVERSE_MAT4).unwrap();
+                }
+                _ => ()
+            }
+        }
+        write!(self.string, "\n").unwrap();
+    }
+
+    fn generate_private_decls(&mut self) {
+        for (live_ref, ty) in self.draw_shader_def.all_live_refs.borrow().iter() {
+            write!(self.string, "var<private> ").unwrap();
+            self.write_var_decl(live_ref, ty);
+            writeln!(self.string, ";").unwrap();
+        }
+        for field in &self.draw_shader_def.fields {
+            match field.kind {
+                DrawShaderFieldKind::Uniform {..} |
+                DrawShaderFieldKind::Geometry {..} |
+                DrawShaderFieldKind::Instance {..} |
+                DrawShaderFieldKind::Varying {..} => {
+                    write!(self.string, "var<private> ").unwrap();
+                    self.write_var_decl(&DisplayDsIdent(field.ident), field.ty_expr.ty.borrow().as_ref().unwrap());
+                    writeln!(self.string, ";").unwrap();
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn generate_packed_var_decls(&mut self, packed_var_name: &str, mut packed_vars_size: usize) {
+        let mut packed_var_index = 0;
+        while packed_vars_size > 0 {
+            let packed_var_size = packed_vars_size.min(4);
+            writeln!(
+                self.string,
+                "var<private> {}_{}: {};",
+                packed_var_name,
+                packed_var_index,
+                packed_ty_name(packed_var_size)
+            ).unwrap();
+            packed_vars_size -= packed_var_size;
+            packed_var_index += 1;
+        }
+    }
+
+    fn generate_vertex_input_struct(&mut self, packed_geometries_slots: usize, packed_instances_slots: usize) {
+        if packed_geometries_slots + packed_instances_slots == 0 {
+            return
+        }
+        writeln!(self.string, "struct VertexInput {{").unwrap();
+        let mut location = 0;
+        for (packed_var_name, mut packed_vars_size) in [("packed_geometry", packed_geometries_slots), ("packed_instance", packed_instances_slots)] {
+            let mut packed_var_index = 0;
+            while packed_vars_size > 0 {
+                let packed_var_size = packed_vars_size.min(4);
+                writeln!(
+                    self.string,
+                    "    @location({}) {}_{}: {},",
+                    location,
+                    packed_var_name,
+                    packed_var_index,
+                    packed_ty_name(packed_var_size)
+                ).unwrap();
+                packed_vars_size -= packed_var_size;
+                packed_var_index += 1;
+                location += 1;
+            }
+        }
+        writeln!(self.string, "}};\n").unwrap();
+    }
+
+    fn generate_varyings_struct(&mut self, mut packed_varyings_slots: usize) {
+        writeln!(self.string, "struct Varyings {{").unwrap();
+        writeln!(self.string, "    @builtin(position) position: vec4<f32>,").unwrap();
+        let mut packed_var_index = 0;
+        while packed_varyings_slots > 0 {
+            let packed_var_size = packed_varyings_slots.min(4);
+            writeln!(
+                self.string,
+                "    @location({0}) packed_varying_{0}: {1},",
+                packed_var_index,
+                packed_ty_name(packed_var_size)
+            ).unwrap();
+            packed_varyings_slots -= packed_var_size;
+            packed_var_index += 1;
+        }
+        writeln!(self.string, "}};\n").unwrap();
+    }
+
+    fn generate_shader_body(&mut self) {
+        let mut all_constructor_fns = BTreeSet::new();
+        for fn_iter in self.draw_shader_def.all_fns.borrow().iter() {
+            let fn_def = self.shader_registry.all_fns.get(fn_iter).unwrap();
+            all_constructor_fns.extend(fn_def.constructor_fn_deps.borrow().as_ref().unwrap().iter().cloned());
+        }
+
+        for struct_ptr in self.draw_shader_def.all_structs.borrow().iter().rev() {
+            let struct_def = self.shader_registry.structs.get(struct_ptr).unwrap();
+            self.generate_struct_def(*struct_ptr, struct_def);
+        }
+
+        for (ty_lit, param_tys) in all_constructor_fns {
+            generate_cons_fn(self.backend_writer, self.string, ty_lit, &param_tys);
+        }
+        write!(self.string, "\n").unwrap();
+
+        let all_fns = self.draw_shader_def.all_fns.borrow();
+        for fn_iter in all_fns.iter().rev() {
+            let const_table_offset = self.const_table.offsets.get(fn_iter).cloned();
+            let fn_def = self.shader_registry.all_fns.get(fn_iter).unwrap();
+            if fn_def.has_closure_args() {
+                for call_iter in all_fns.iter().rev() {
+                    // any function that depends on us, will have the closures we need
+                    let call_def = self.shader_registry.all_fns.get(call_iter).unwrap();
+                    if call_def.callees.borrow().as_ref().unwrap().contains(&fn_iter) {
+                        FnDefWithClosureArgsGenerator::generate_fn_def_with_all_closures(
+                            &mut self.string,
+                            self.shader_registry,
+                            fn_def,
+                            call_def,
+                            self.backend_writer,
+                            const_table_offset
+                        );
+                    }
+                }
+                continue
+            }
+            FnDefGenerator {
+                fn_def,
+                const_table_offset,
+                shader_registry: self.shader_registry,
+                backend_writer: self.backend_writer,
+                string: self.string,
+            }
+            .generate_fn_def();
+            write!(self.string, "\n").unwrap();
+        }
+    }
+
+    fn generate_struct_def(&mut self, struct_ptr: StructPtr, struct_def: &StructDef) {
+        writeln!(self.string, "struct {} {{", struct_ptr).unwrap();
+        for field in &struct_def.fields {
+            write!(self.string, "    ").unwrap();
+            self.write_var_decl(
+                &DisplayStructField(field.ident),
+                field.ty_expr.ty.borrow().as_ref().unwrap(),
+            );
+            writeln!(self.string, ",").unwrap();
+        }
+        writeln!(self.string, "}};").unwrap();
+    }
+
+    fn generate_vertex_main(&mut self, packed_geometries_slots: usize, packed_instances_slots: usize, packed_varyings_slots: usize) {
+        writeln!(self.string, "@vertex").unwrap();
+        if packed_geometries_slots + packed_instances_slots > 0 {
+            writeln!(self.string, "fn vertex_main(input: VertexInput) -> Varyings {{").unwrap();
+        }
+        else {
+            writeln!(self.string, "fn vertex_main() -> Varyings {{").unwrap();
+        }
+        self.generate_uniform_block_unpack();
+        self.generate_live_unpack();
+
+        for (packed_var_name, packed_vars_size) in [("packed_geometry", packed_geometries_slots), ("packed_instance", packed_instances_slots)] {
+            for packed_var_index in 0..(packed_vars_size + 3) / 4 {
+                writeln!(self.string, "    {0}_{1} = input.{0}_{1};", packed_var_name, packed_var_index).unwrap();
+            }
+        }
+
+        let mut geometry_unpacker = VarUnpacker::new("packed_geometry", packed_geometries_slots, &mut self.string);
+        for decl in &self.draw_shader_def.fields {
+            if let DrawShaderFieldKind::Geometry {..} = decl.kind {
+                geometry_unpacker.unpack_var(decl.ident, decl.ty_expr.ty.borrow().as_ref().unwrap());
+            }
+        }
+        let mut instance_unpacker = VarUnpacker::new("packed_instance", packed_instances_slots, &mut self.string);
+        for decl in &self.draw_shader_def.fields {
+            if let DrawShaderFieldKind::Instance {..} = decl.kind {
+                instance_unpacker.unpack_var(decl.ident, decl.ty_expr.ty.borrow().as_ref().unwrap());
+            }
+        }
+        write!(self.string, "\n").unwrap();
+        let vertex_def = self.shader_registry.draw_shader_method_decl_from_ident(self.draw_shader_def, Ident(live_id!(vertex))).unwrap();
+
+        writeln!(self.string, "    var varyings: Varyings;").unwrap();
+        writeln!(self.string, "    varyings.position = {}();", DisplayFnName(vertex_def.fn_ptr, vertex_def.ident)).unwrap();
+        write!(self.string, "\n").unwrap();
+
+        let mut varying_packer = VarPacker::new("packed_varying", packed_varyings_slots, &mut self.string);
+        for decl in &self.draw_shader_def.fields {
+            match &decl.kind {
+                DrawShaderFieldKind::Geometry {is_used_in_pixel_shader, ..} if is_used_in_pixel_shader.get() => {
+                    varying_packer.pack_var(decl.ident, decl.ty_expr.ty.borrow().as_ref().unwrap());
+                }
+                DrawShaderFieldKind::Instance {is_used_in_pixel_shader, ..} if is_used_in_pixel_shader.get() => {
+                    varying_packer.pack_var(decl.ident, decl.ty_expr.ty.borrow().as_ref().unwrap());
+                }
+                DrawShaderFieldKind::Varying {..} => {
+                    varying_packer.pack_var(decl.ident, decl.ty_expr.ty.borrow().as_ref().unwrap());
+                }
+                _ => {}
+            }
+        }
+        for packed_var_index in 0..(packed_varyings_slots + 3) / 4 {
+            writeln!(self.string, "    varyings.packed_varying_{0} = packed_varying_{0};", packed_var_index).unwrap();
+        }
+        writeln!(self.string, "    return varyings;").unwrap();
+        writeln!(self.string, "}}\n").unwrap();
+    }
+
+    fn generate_pixel_main(&mut self, packed_varyings_slots: usize) {
+        writeln!(self.string, "@fragment").unwrap();
+        writeln!(self.string, "fn pixel_main(varyings: Varyings) -> @location(0) vec4<f32> {{").unwrap();
+        self.generate_uniform_block_unpack();
+        self.generate_live_unpack();
+
+        for packed_var_index in 0..(packed_varyings_slots + 3) / 4 {
+            writeln!(self.string, "    packed_varying_{0} = varyings.packed_varying_{0};", packed_var_index).unwrap();
+        }
+
+        let mut varying_unpacker = VarUnpacker::new("packed_varying", packed_varyings_slots, &mut self.string);
+        for decl in &self.draw_shader_def.fields {
+            match &decl.kind {
+                DrawShaderFieldKind::Geometry {is_used_in_pixel_shader, ..} if is_used_in_pixel_shader.get() => {
+                    varying_unpacker.unpack_var(decl.ident, decl.ty_expr.ty.borrow().as_ref().unwrap());
+                }
+                DrawShaderFieldKind::Instance {is_used_in_pixel_shader, ..} if is_used_in_pixel_shader.get() => {
+                    varying_unpacker.unpack_var(decl.ident, decl.ty_expr.ty.borrow().as_ref().unwrap());
+                }
+                DrawShaderFieldKind::Varying {..} => {
+                    varying_unpacker.unpack_var(decl.ident, decl.ty_expr.ty.borrow().as_ref().unwrap());
+                }
+                _ => {}
+            }
+        }
+        let pixel_def = self.shader_registry.draw_shader_pixel_entry(self.draw_shader_def);
+        write!(self.string, "\n").unwrap();
+        writeln!(self.string, "    return {}();", DisplayFnName(pixel_def.fn_ptr, pixel_def.ident)).unwrap();
+        writeln!(self.string, "}}").unwrap();
+    }
+
+    fn generate_uniform_block_unpack(&mut self) {
+        for (ident, vec) in self.draw_shader_def.fields_as_uniform_blocks() {
+            let table = format!("{}_table", ident);
+            let mut slots = 0;
+            for (index, _item) in vec {
+                let field = &self.draw_shader_def.fields[index];
+                write!(self.string, "    {} = ", &DisplayDsIdent(field.ident)).unwrap();
+                let ty_expr = field.ty_expr.ty.borrow();
+                self.write_uniform_ty_unpack(ty_expr.as_ref().unwrap(), &table, slots);
+                writeln!(self.string, ";").unwrap();
+                slots += ty_expr.as_ref().unwrap().slots();
+            }
+        }
+    }
+
+    fn generate_live_unpack(&mut self) {
+        let mut slots = 0;
+        for (live_ref, ty) in self.draw_shader_def.all_live_refs.borrow().iter() {
+            write!(self.string, "    {} = ", &live_ref).unwrap();
+            self.write_uniform_ty_unpack(ty, "live_table", slots);
+            writeln!(self.string, ";").unwrap();
+            slots += ty.slots();
+        }
+    }
+
+    fn write_uniform_ty_unpack(&mut self, ty: &Ty, prefix: &str, s: usize) {
+        let (cons, scalar, len) = match ty {
+            Ty::Bool => ("", "bool", 1),
+            Ty::Int => ("", "i32", 1),
+            Ty::Float | Ty::Enum {..} => ("", "f32", 1),
+            Ty::Bvec2 => ("vec2<bool>", "bool", 2),
+            Ty::Bvec3 => ("vec3<bool>", "bool", 3),
+            Ty::Bvec4 => ("vec4<bool>", "bool", 4),
+            Ty::Ivec2 => ("vec2<i32>", "i32", 2),
+            Ty::Ivec3 => ("vec3<i32>", "i32", 3),
+            Ty::Ivec4 => ("vec4<i32>", "i32", 4),
+            Ty::Vec2 => ("vec2<f32>", "f32", 2),
+            Ty::Vec3 => ("vec3<f32>", "f32", 3),
+            Ty::Vec4 => ("vec4<f32>", "f32", 4),
+            Ty::Mat2 => ("mat2x2<f32>", "f32", 4),
+            Ty::Mat3 => ("mat3x3<f32>", "f32", 9),
+            Ty::Mat4 => ("mat4x4<f32>", "f32", 16),
+            _ => panic!("unexpected as initializeable type {:?}", ty),
+        };
+        if len > 1 {
+            write!(self.string, "{}(", cons).unwrap();
+        }
+        for i in 0..len {
+            if i != 0 {
+                write!(self.string, ", ").unwrap();
+            }
+            match scalar {
+                "bool" => write!(self.string, "({}[{}] > 0.5)", prefix, s + i),
+                "i32" => write!(self.string, "i32({}[{}])", prefix, s + i),
+                _ => write!(self.string, "{}[{}]", prefix, s + i),
+            }.unwrap();
+        }
+        if len > 1 {
+            write!(self.string, ")").unwrap();
+        }
+    }
+
+    fn compute_packed_geometries_slots(&self) -> usize {
+        let mut packed_attributes_size = 0;
+        for field in &self.draw_shader_def.fields {
+            packed_attributes_size += match field.kind {
+                DrawShaderFieldKind::Geometry {..} => field.ty_expr.ty.borrow().as_ref().unwrap().slots(),
+                _ => 0,
+            }
+        }
+        packed_attributes_size
+    }
+
+    fn compute_packed_instances_slots(&self) -> usize {
+        let mut packed_instances_size = 0;
+        for field in &self.draw_shader_def.fields {
+            packed_instances_size += match field.kind {
+                DrawShaderFieldKind::Instance {..} => field.ty_expr.ty.borrow().as_ref().unwrap().slots(),
+                _ => 0,
+            }
+        }
+        packed_instances_size
+    }
+
+    fn compute_packed_varyings_slots(&self) -> usize {
+        let mut packed_varyings_size = 0;
+        for field in &self.draw_shader_def.fields {
+            packed_varyings_size += match &field.kind {
+                DrawShaderFieldKind::Geometry {is_used_in_pixel_shader, ..} if is_used_in_pixel_shader.get() => {
+                    field.ty_expr.ty.borrow().as_ref().unwrap().slots()
+                }
+                DrawShaderFieldKind::Instance {is_used_in_pixel_shader, ..} if is_used_in_pixel_shader.get() => {
+                    field.ty_expr.ty.borrow().as_ref().unwrap().slots()
+                }
+                DrawShaderFieldKind::Varying {..} => field.ty_expr.ty.borrow().as_ref().unwrap().slots(),
+                _ => 0,
+            }
+        }
+        packed_varyings_size
+    }
+
+    fn write_var_decl(&mut self, ident: &dyn fmt::Display, ty: &Ty) {
+        self.backend_writer.write_var_decl(&mut self.string, "", false, false, ident, ty);
+    }
+}
+
+fn packed_ty_name(size: usize) -> &'static str {
+    match size {
+        1 => "f32",
+        2 => "vec2<f32>",
+        3 => "vec3<f32>",
+        4 => "vec4<f32>",
+        _ => panic!(),
+    }
+}
+
+// wgsl can't assign to multi component swizzles, so the (un)packers copy one slot at a time
+fn write_var_slot(string: &mut String, ident: Ident, ty: &Ty, slot: usize) {
+    match ty {
+        Ty::Float | Ty::Enum(_) => write!(string, "{}", DisplayDsIdent(ident)),
+        Ty::Mat2 => write!(string, "{}[{}][{}]", DisplayDsIdent(ident), slot / 2, slot % 2),
+        Ty::Mat3 => write!(string, "{}[{}][{}]", DisplayDsIdent(ident), slot / 3, slot % 3),
+        Ty::Mat4 => write!(string, "{}[{}][{}]", DisplayDsIdent(ident), slot / 4, slot % 4),
+        _ => write!(string, "{}[{}]", DisplayDsIdent(ident), slot),
+    }.unwrap()
+}
+
+fn write_packed_slot(string: &mut String, packed_var_name: &str, packed_vars_size: usize, slot: usize) {
+    let packed_var_index = slot / 4;
+    write!(string, "{}_{}", packed_var_name, packed_var_index).unwrap();
+    if packed_vars_size - packed_var_index * 4 > 1 {
+        write!(string, "[{}]", slot % 4).unwrap();
+    }
+}
+
+struct VarPacker<'a> {
+    packed_var_name: &'a str,
+    packed_vars_size: usize,
+    packed_slot: usize,
+    string: &'a mut String,
+}
+
+impl<'a> VarPacker<'a> {
+    fn new(
+        packed_var_name: &'a str,
+        packed_vars_size: usize,
+        string: &'a mut String,
+    ) -> VarPacker<'a> {
+        VarPacker {
+            packed_var_name,
+            packed_vars_size,
+            packed_slot: 0,
+            string,
+        }
+    }
+
+    fn pack_var(&mut self, ident: Ident, ty: &Ty) {
+        for var_slot in 0..ty.slots() {
+            write!(self.string, "    ").unwrap();
+            write_packed_slot(self.string, self.packed_var_name, self.packed_vars_size, self.packed_slot);
+            write!(self.string, " = ").unwrap();
+            write_var_slot(self.string, ident, ty, var_slot);
+            writeln!(self.string, ";").unwrap();
+            self.packed_slot += 1;
+        }
+    }
+}
+
+struct VarUnpacker<'a> {
+    packed_var_name: &'a str,
+    packed_vars_size: usize,
+    packed_slot: usize,
+    string: &'a mut String,
+}
+
+impl<'a> VarUnpacker<'a> {
+    fn new(
+        packed_var_name: &'a str,
+        packed_vars_size: usize,
+        string: &'a mut String,
+    ) -> VarUnpacker<'a> {
+        VarUnpacker {
+            packed_var_name,
+            packed_vars_size,
+            packed_slot: 0,
+            string,
+        }
+    }
+
+    fn unpack_var(&mut self, ident: Ident, ty: &Ty) {
+        for var_slot in 0..ty.slots() {
+            write!(self.string, "    ").unwrap();
+            write_var_slot(self.string, ident, ty, var_slot);
+            write!(self.string, " = ").unwrap();
+            write_packed_slot(self.string, self.packed_var_name, self.packed_vars_size, self.packed_slot);
+            writeln!(self.string, ";").unwrap();
+            self.packed_slot += 1;
+        }
+    }
+}
+
+const WGSL_INVERSE_MAT4: &str = "fn inverse_mat4(m: mat4x4<f32>) -> mat4x4<f32> {
+    let b00 = m[0][0] * m[1][1] - m[0][1] * m[1][0];
+    let b01 = m[0][0] * m[1][2] - m[0][2] * m[1][0];
+    let b02 = m[0][0] * m[1][3] - m[0][3] * m[1][0];
+    let b03 = m[0][1] * m[1][2] - m[0][2] * m[1][1];
+    let b04 = m[0][1] * m[1][3] - m[0][3] * m[1][1];
+    let b05 = m[0][2] * m[1][3] - m[0][3] * m[1][2];
+    let b06 = m[2][0] * m[3][1] - m[2][1] * m[3][0];
+    let b07 = m[2][0] * m[3][2] - m[2][2] * m[3][0];
+    let b08 = m[2][0] * m[3][3] - m[2][3] * m[3][0];
+    let b09 = m[2][1] * m[3][2] - m[2][2] * m[3][1];
+    let b10 = m[2][1] * m[3][3] - m[2][3] * m[3][1];
+    let b11 = m[2][2] * m[3][3] - m[2][3] * m[3][2];
+    let det = b00 * b11 - b01 * b10 + b02 * b09 + b03 * b08 - b04 * b07 + b05 * b06;
+    return mat4x4<f32>(
+        m[1][1] * b11 - m[1][2] * b10 + m[1][3] * b09,
+        m[0][2] * b10 - m[0][1] * b11 - m[0][3] * b09,
+        m[3][1] * b05 - m[3][2] * b04 + m[3][3] * b03,
+        m[2][2] * b04 - m[2][1] * b05 - m[2][3] * b03,
+        m[1][2] * b08 - m[1][0] * b11 - m[1][3] * b07,
+        m[0][0] * b11 - m[0][2] * b08 + m[0][3] * b07,
+        m[3][2] * b02 - m[3][0] * b05 - m[3][3] * b01,
+        m[2][0] * b05 - m[2][2] * b02 + m[2][3] * b01,
+        m[1][0] * b10 - m[1][1] * b08 + m[1][3] * b06,
+        m[0][1] * b08 - m[0][0] * b10 - m[0][3] * b06,
+        m[3][0] * b04 - m[3][1] * b02 + m[3][3] * b00,
+        m[2][1] * b02 - m[2][0] * b04 - m[2][3] * b00,
+        m[1][1] * b07 - m[1][0] * b09 - m[1][2] * b06,
+        m[0][0] * b09 - m[0][1] * b07 + m[0][2] * b06,
+        m[3][1] * b01 - m[3][0] * b03 - m[3][2] * b00,
+        m[2][0] * b03 - m[2][1] * b01 + m[2][2] * b00
+    ) * (1.0 / det);
+}";
+
+struct WgslBackendWriter<'a> {
+    pub shader_registry: &'a ShaderRegistry,
+    const_table: &'a DrawShaderConstTable
+}
+
+impl<'a> WgslBackendWriter<'a> {
+    fn write_ty(&self, string: &mut String, ty: &Ty) {
+        match ty {
+            Ty::Void => (),
+            Ty::Bool => self.write_ty_lit(string, TyLit::Bool),
+            Ty::Int => self.write_ty_lit(string, TyLit::Int),
+            Ty::Float => self.write_ty_lit(string, TyLit::Float),
+            Ty::Bvec2 => self.write_ty_lit(string, TyLit::Bvec2),
+            Ty::Bvec3 => self.write_ty_lit(string, TyLit::Bvec3),
+            Ty::Bvec4 => self.write_ty_lit(string, TyLit::Bvec4),
+            Ty::Ivec2 => self.write_ty_lit(string, TyLit::Ivec2),
+            Ty::Ivec3 => self.write_ty_lit(string, TyLit::Ivec3),
+            Ty::Ivec4 => self.write_ty_lit(string, TyLit::Ivec4),
+            Ty::Vec2 => self.write_ty_lit(string, TyLit::Vec2),
+            Ty::Vec3 => self.write_ty_lit(string, TyLit::Vec3),
+            Ty::Vec4 => self.write_ty_lit(string, TyLit::Vec4),
+            Ty::Mat2 => self.write_ty_lit(string, TyLit::Mat2),
+            Ty::Mat3 => self.write_ty_lit(string, TyLit::Mat3),
+            Ty::Mat4 => self.write_ty_lit(string, TyLit::Mat4),
+            Ty::Texture2D => self.write_ty_lit(string, TyLit::Texture2D),
+            Ty::TextureOES => self.write_ty_lit(string, TyLit::TextureOES),
+            Ty::Array {elem_ty, len} => {
+                write!(string, "array<").unwrap();
+                self.write_ty(string, elem_ty);
+                write!(string, ", {}>", len).unwrap();
+            }
+            Ty::Struct(ptr) => write!(string, "{}", ptr).unwrap(),
+            Ty::Enum(_) => write!(string, "f32").unwrap(),
+            Ty::DrawShader(_) | Ty::ClosureDef {..} | Ty::ClosureDecl => panic!(),
+        }
+    }
+}
+
+impl<'a> BackendWriter for WgslBackendWriter<'a> {
+    fn get_struct_cons_type(&self) -> StructConsType {
+        StructConsType::Paren
+    }
+
+    fn enum_is_float(&self) -> bool {
+        true
+    }
+
+    fn needs_mul_fn_for_matrix_multiplication(&self) -> bool {
+        false
+    }
+
+    fn needs_unpack_for_matrix_multiplication(&self) -> bool {
+        false
+    }
+
+    fn const_table_is_vec4(&self) -> bool {
+        false
+    }
+
+    fn use_cons_fn(&self, what: &str) -> bool {
+        // wgsl has no matrix resize or scalar to diagonal matrix constructors
+        match what {
+            "consfn_mat2_float" => true,
+            "consfn_mat3_float" => true,
+            "consfn_mat4_float" => true,
+            "consfn_mat2_mat3" => true,
+            "consfn_mat2_mat4" => true,
+            "consfn_mat3_mat2" => true,
+            "consfn_mat3_mat4" => true,
+            "consfn_mat4_mat2" => true,
+            "consfn_mat4_mat3" => true,
+            _ => false
+        }
+    }
+
+    fn write_var_decl(
+        &self,
+        string: &mut String,
+        sep: &'static str,
+        is_inout: bool,
+        _is_packed: bool,
+        ident: &dyn fmt::Display,
+        ty: &Ty,
+    ) -> bool {
+        match ty {
+            Ty::Void | Ty::DrawShader(_) | Ty::ClosureDef {..} | Ty::ClosureDecl => {
+                return false
+            }
+            _ => ()
+        }
+        write!(string, "{}{}: ", sep, ident).unwrap();
+        if is_inout {
+            write!(string, "ptr<function, ").unwrap();
+            self.write_ty(string, ty);
+            write!(string, ">").unwrap();
+        }
+        else {
+            self.write_ty(string, ty);
+        }
+        true
+    }
+
+    fn write_fn_def_start(&self, string: &mut String, ident: &dyn fmt::Display, _return_ty: &Ty) {
+        write!(string, "fn {}(", ident).unwrap();
+    }
+
+    fn write_fn_def_end(&self, string: &mut String, return_ty: &Ty) {
+        write!(string, ")").unwrap();
+        if *return_ty != Ty::Void {
+            write!(string, " -> ").unwrap();
+            self.write_ty(string, return_ty);
+        }
+        write!(string, " ").unwrap();
+    }
+
+    fn write_let_decl(&self, string: &mut String, ident: &dyn fmt::Display, ty: &Ty) {
+        write!(string, "var ").unwrap();
+        self.write_var_decl(string, "", false, false, ident, ty);
+    }
+
+    fn write_for_var_decl(&self, string: &mut String, ident: Ident) {
+        write!(string, "var {}: i32", ident).unwrap();
+    }
+
+    fn cond_expr_is_select(&self) -> bool {
+        true
+    }
+
+    fn inout_is_ptr(&self) -> bool {
+        true
+    }
+
+    fn needs_swizzle_assign_split(&self) -> bool {
+        true
+    }
+    
+    fn assign_is_stmt(&self) -> bool {
+        true
+    }
+
+    fn write_call_expr_hidden_args(&self, _string: &mut String, _hidden_args: &BTreeSet<HiddenArgKind >, _sep: &str) {
+    }
+
+    fn write_fn_def_hidden_params(&self, _string: &mut String, _hidden_args: &BTreeSet<HiddenArgKind >, _sep: &str) {
+    }
+
+    fn generate_live_value_prefix(&self, _string: &mut String) {
+    }
+
+    fn generate_draw_shader_field_expr(&self, string: &mut String, field_ident: Ident, _ty: &Ty) {
+        write!(string, "{}", &DisplayDsIdent(field_ident)).unwrap();
+    }
+
+    fn write_ty_lit(&self, string: &mut String, ty_lit: TyLit) {
+        write!(
+            string,
+            "{}",
+            match ty_lit {
+                TyLit::Bool => "bool",
+                TyLit::Int => "i32",
+                TyLit::Float => "f32",
+                TyLit::Bvec2 => "vec2<bool>",
+                TyLit::Bvec3 => "vec3<bool>",
+                TyLit::Bvec4 => "vec4<bool>",
+                TyLit::Ivec2 => "vec2<i32>",
+                TyLit::Ivec3 => "vec3<i32>",
+                TyLit::Ivec4 => "vec4<i32>",
+                TyLit::Vec2 => "vec2<f32>",
+                TyLit::Vec3 => "vec3<f32>",
+                TyLit::Vec4 => "vec4<f32>",
+                TyLit::Mat2 => "mat2x2<f32>",
+                TyLit::Mat3 => "mat3x3<f32>",
+                TyLit::Mat4 => "mat4x4<f32>",
+                TyLit::Texture2D => "texture_2d<f32>",
+                TyLit::TextureOES => "texture_2d<f32>",
+            }
+        )
+            .unwrap();
+    }
+
+    fn write_builtin_call_ident(&self, string: &mut String, ident: Ident, arg_exprs: &[Expr]) {
+        match ident {
+            Ident(live_id!(atan)) if arg_exprs.len() == 2 => {
+                write!(string, "atan2").unwrap()
+            }
+            Ident(live_id!(dFdx)) => {
+                write!(string, "dpdx").unwrap()
+            }
+            Ident(live_id!(dFdy)) => {
+                write!(string, "dpdy").unwrap()
+            }
+            Ident(live_id!(sample2dOES)) => {
+                write!(string, "sample2d").unwrap()
+            }
+            Ident(live_id!(inverse)) => {
+                write!(string, "inverse_mat4").unwrap()
+            }
+            Ident(live_id!(mod)) => {
+                // these map onto the helpers generated in generate_builtin_helpers
+                write!(string, "mod_{}_{}", arg_exprs[0].ty.borrow().as_ref().unwrap(), arg_exprs[1].ty.borrow().as_ref().unwrap()).unwrap()
+            }
+            Ident(live_id!(inversesqrt)) => {
+                write!(string, "inverseSqrt").unwrap()
+            }
+            Ident(live_id!(min)) | Ident(live_id!(max)) | Ident(live_id!(clamp)) | Ident(live_id!(step)) | Ident(live_id!(smoothstep))
+                if arg_exprs.iter().any( | arg | arg.ty != arg_exprs[0].ty) => {
+                write!(string, "{}", ident).unwrap();
+                for arg in arg_exprs {
+                    write!(string, "_{}", arg.ty.borrow().as_ref().unwrap()).unwrap();
+                }
+            }
+            Ident(live_id!(equal)) | Ident(live_id!(notEqual)) | Ident(live_id!(lessThan)) | Ident(live_id!(lessThanEqual)) |
+            Ident(live_id!(greaterThan)) | Ident(live_id!(greaterThanEqual)) | Ident(live_id!(not)) | Ident(live_id!(matrixCompMult)) => {
+                write!(string, "{}_{}", ident, arg_exprs[0].ty.borrow().as_ref().unwrap()).unwrap()
+            }
+            _ => {
+                write!(string, "{}", ident).unwrap()
+            }
+        }
+    }
+}
@@ -143,6 +143,14 @@ impl ShaderRegistry {
         None
     }
     
+    /// The entry point of the pixel stage. A draw shader can wrap its `pixel` method in a
+    /// `fragment` method that post processes the color, like converting it for the pass.
+    pub fn draw_shader_pixel_entry(&self, draw_shader_def: &DrawShaderDef) -> &FnDef {
+        self.draw_shader_method_decl_from_ident(draw_shader_def, Ident(live_id!(fragment)))
+            .or_else( || self.draw_shader_method_decl_from_ident(draw_shader_def, Ident(live_id!(pixel))))
+            .unwrap()
+    }
+    
     pub fn struct_method_decl_from_ident(&self, struct_def: &StructDef, ident: Ident) -> Option<&FnDef> {
         for fn_node_ptr in &struct_def.methods {
             let fn_decl = self.all_fns.get(fn_node_ptr).unwrap();
@@ -504,8 +512,10 @@ impl ShaderRegistry {
         draw_shader_def.add_uniform(id_lut!(dpi_factor), id_lut!(pass), Ty::Float, TokenSpan::default());
         draw_shader_def.add_uniform(id_lut!(dpi_dilate), id_lut!(pass), Ty::Float, TokenSpan::default());
         draw_shader_def.add_uniform(id_lut!(time), id_lut!(pass), Ty::Float, TokenSpan::default());
+        draw_shader_def.add_uniform(id_lut!(color_space), id_lut!(pass), Ty::Float, TokenSpan::default());
         draw_shader_def.add_uniform(id_lut!(view_transform), id_lut!(view), Ty::Mat4, TokenSpan::default());
         draw_shader_def.add_uniform(id_lut!(draw_zbias), id_lut!(draw), Ty::Float, TokenSpan::default());
+        draw_shader_def.add_uniform(id_lut!(draw_texture_color_space), id_lut!(draw), Ty::Float, TokenSpan::default());
         
         let (doc, class_node) = live_registry.ptr_to_doc_node(draw_shader_ptr.0);
 
@@ -0,0 +1,263 @@
+use {
+    std::collections::HashMap,
+    crate::{
+        makepad_live_id::LiveId,
+        makepad_math::Rect,
+        area::Area,
+        window::WindowId,
+        cx::Cx,
+    }
+};
+
+/// What an accessibility node is, the platform layer maps it onto the roles of the OS API.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AccessRole {
+    Group,
+    Label,
+    Button,
+    CheckBox,
+    RadioButton,
+    Slider,
+    Link,
+    TextInput,
+    TextEditor,
+    TabList,
+    Tab,
+    List,
+    ListItem,
+}
+
+impl AccessRole {
+    /// The role as it is named in the macOS accessibility API.
+    pub fn to_macos_role(&self) -> &'static str {
+        match self {
+            Self::Group => "AXGroup",
+            Self::Label => "AXStaticText",
+            Self::Button => "AXButton",
+            Self::CheckBox => "AXCheckBox",
+            Self::RadioButton => "AXRadioButton",
+            Self::Slider => "AXSlider",
+            Self::Link => "AXLink",
+            Self::TextInput => "AXTextField",
+            Self::TextEditor => "AXTextArea",
+            Self::TabList => "AXTabGroup",
+            Self::Tab => "AXRadioButton",
+            Self::List => "AXList",
+            Self::ListItem => "AXGroup",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct AccessState {
+    pub focused: bool,
+    pub selected: bool,
+    pub checked: Option<bool>,
+    pub disabled: bool,
+}
+
+/// An element of the accessibility tree, as a widget declares it while it draws.
+#[derive(Clone, Debug)]
+pub struct AccessNode {
+    pub id: LiveId,
+    pub parent: Option<LiveId>,
+    pub area: Area,
+    pub role: AccessRole,
+    pub name: String,
+    pub value: String,
+    pub state: AccessState,
+}
+
+impl AccessNode {
+    pub fn new(id: LiveId, area: Area, role: AccessRole) -> Self {
+        Self {
+            id,
+            parent: None,
+            area,
+            role,
+            name: String::new(),
+            value: String::new(),
+            state: AccessState::default(),
+        }
+    }
+
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.name = name.to_string();
+        self
+    }
+
+    pub fn with_value(mut self, value: &str) -> Self {
+        self.value = value.to_string();
+        self
+    }
+
+    pub fn with_checked(mut self, checked: bool) -> Self {
+        self.state.checked = Some(checked);
+        self
+    }
+
+    pub fn with_selected(mut self, selected: bool) -> Self {
+        self.state.selected = selected;
+        self
+    }
+
+    pub fn with_disabled(mut self, disabled: bool) -> Self {
+        self.state.disabled = disabled;
+        self
+    }
+}
+
+/// A node as the platform layer gets it, with its window and rect resolved.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AccessElement {
+    pub id: LiveId,
+    pub parent: Option<LiveId>,
+    pub window_id: WindowId,
+    pub role: AccessRole,
+    pub name: String,
+    pub value: String,
+    pub state: AccessState,
+    /// In logical coordinates relative to the window.
+    pub rect: Rect,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AccessNotification {
+    FocusChanged(LiveId),
+    ValueChanged(LiveId),
+}
+
+/// The accessibility tree. It stays empty until the platform layer sees an assistive
+/// technology and turns it on, so apps don't pay for it otherwise.
+#[derive(Default)]
+pub struct CxAccessibility {
+    pub(crate) enabled: bool,
+    pub(crate) nodes: Vec<AccessNode>,
+    group_stack: Vec<LiveId>,
+    last_focus: Option<LiveId>,
+    last_values: HashMap<LiveId, String>,
+    pub(crate) elements: Vec<AccessElement>,
+    pub(crate) elements_changed: bool,
+    pub(crate) notifications: Vec<AccessNotification>,
+}
+
+impl Cx {
+    pub fn is_accessibility_enabled(&self) -> bool {
+        self.accessibility.enabled
+    }
+
+    /// Called by the platform layer when a screen reader starts or stops asking for the tree.
+    pub fn set_accessibility_enabled(&mut self, enabled: bool) {
+        if self.accessibility.enabled != enabled {
+            self.accessibility = CxAccessibility {
+                enabled,
+                ..Default::default()
+            };
+            if enabled {
+                self.redraw_all();
+            }
+        }
+    }
+
+    /// Declares a node, call it after drawing the area it stands for. A node declared
+    /// again with the same id replaces the old one, nodes whose area isn't drawn anymore go away.
+    pub fn access_node(&mut self, mut node: AccessNode) {
+        if !self.accessibility.enabled {
+            return
+        }
+        node.parent = self.accessibility.group_stack.last().cloned();
+        if let Some(old) = self.accessibility.nodes.iter_mut().find( | n | n.id == node.id) {
+            *old = node;
+        }
+        else {
+            self.accessibility.nodes.push(node);
+        }
+    }
+
+    /// Nodes declared until `end_access_group` become children of this one.
+    pub fn begin_access_group(&mut self, id: LiveId, role: AccessRole, name: &str) {
+        if !self.accessibility.enabled {
+            return
+        }
+        self.access_node(AccessNode::new(id, Area::Empty, role).with_name(name));
+        self.accessibility.group_stack.push(id);
+    }
+
+    /// Ends a group, with the area that covers it.
+    pub fn end_access_group(&mut self, area: Area) {
+        if !self.accessibility.enabled {
+            return
+        }
+        if let Some(id) = self.accessibility.group_stack.pop() {
+            if let Some(node) = self.accessibility.nodes.iter_mut().find( | n | n.id == id) {
+                node.area = area;
+            }
+        }
+    }
+
+    /// The resolved tree, in the order the nodes were first declared.
+    pub fn access_elements(&self) -> &[AccessElement] {
+        &self.accessibility.elements
+    }
+
+    #[allow(dead_code)]
+    pub(crate) fn take_access_notifications(&mut self) -> Vec<AccessNotification> {
+        std::mem::take(&mut self.accessibility.notifications)
+    }
+
+    /// Resolves the declared nodes after a draw, and queues focus and value notifications.
+    pub(crate) fn update_accessibility(&mut self) {
+        if !self.accessibility.enabled {
+            return
+        }
+        self.accessibility.group_stack.clear();
+        let nodes = std::mem::take(&mut self.accessibility.nodes);
+        let nodes: Vec<AccessNode> = nodes.into_iter().filter( | n | n.area.is_valid(self)).collect();
+
+        let mut elements = Vec::with_capacity(nodes.len());
+        let mut focus = None;
+        for node in &nodes {
+            let window_id = if let Some(window_id) = self.window_id_of(&node.area) {window_id} else {continue};
+            let mut state = node.state;
+            state.focused = self.keyboard.has_key_focus(node.area);
+            if state.focused {
+                focus = Some(node.id);
+            }
+            elements.push(AccessElement {
+                id: node.id,
+                parent: node.parent,
+                window_id,
+                role: node.role,
+                name: node.name.clone(),
+                value: node.value.clone(),
+                state,
+                rect: node.area.clipped_rect(self),
+            });
+        }
+        self.accessibility.nodes = nodes;
+
+        let acc = &mut self.accessibility;
+        for element in &elements {
+            if let Some(last_value) = acc.last_values.get_mut(&element.id) {
+                if *last_value != element.value {
+                    *last_value = element.value.clone();
+                    acc.notifications.push(AccessNotification::ValueChanged(element.id));
+                }
+            }
+            else {
+                acc.last_values.insert(element.id, element.value.clone());
+            }
+        }
+        acc.last_values.retain( | id, _ | elements.iter().any( | e | e.id == *id));
+        if let Some(focus_id) = focus {
+            if acc.last_focus != Some(focus_id) {
+                acc.notifications.push(AccessNotification::FocusChanged(focus_id));
+            }
+        }
+        acc.last_focus = focus;
+        if acc.elements != elements {
+            acc.elements = elements;
+            acc.elements_changed = true;
+        }
+    }
+}
@@ -277,6 +277,46 @@ impl AudioBuffer {
         }
     }
     
+    pub fn apply_gain(&mut self, gain: f32) {
+        if gain != 1.0 {
+            for sample in &mut self.data {
+                *sample *= gain;
+            }
+        }
+    }
+    
+    /// Pans a stereo buffer from left (-1) to right (1) with equal power, so it sounds
+    /// as loud in the middle as on the sides. Other buffers are left alone.
+    pub fn apply_pan(&mut self, pan: f32) {
+        if self.channel_count != 2 || pan == 0.0 {
+            return
+        }
+        let angle = (pan.max(-1.0).min(1.0) + 1.0) * std::f32::consts::FRAC_PI_4;
+        // scaled so the middle stays at unity gain
+        let left_gain = angle.cos() * std::f32::consts::SQRT_2;
+        let right_gain = angle.sin() * std::f32::consts::SQRT_2;
+        let (left, right) = self.stereo_mut();
+        for sample in left {
+            *sample *= left_gain;
+        }
+        for sample in right {
+            *sample *= right_gain;
+        }
+    }
+    
+    /// Adds `other` scaled by `gain`. A mono buffer goes into every channel.
+    pub fn mix_from(&mut self, other: &AudioBuffer, gain: f32) {
+        let frame_count = self.frame_count.min(other.frame_count);
+        for c in 0..self.channel_count {
+            let from = if other.channel_count == 1 {0} else if c < other.channel_count {c} else {break};
+            let from = &other.channel(from)[0..frame_count];
+            let to = &mut self.channel_mut(c)[0..frame_count];
+            for i in 0..frame_count {
+                to[i] += from[i] * gain;
+            }
+        }
+    }
+    
     pub fn copy_from_interleaved(&mut self, channel_count:usize, interleaved:&[f32]){
         let frame_count = interleaved.len() / channel_count;
         self.resize(frame_count, channel_count);
@@ -0,0 +1,274 @@
+use {
+    std::sync::Arc,
+    crate::{
+        audio::{AudioInfo, AudioBuffer},
+        live_atomic::{f32a, AtomicGetSet},
+    }
+};
+
+/// A step in an `AudioNodeGraph`. A node gets a buffer holding the mix of everything
+/// connected to it, sources write over it and effects work on it in place.
+/// It runs on the audio thread, so it shouldn't lock or allocate.
+pub trait AudioNode: Send {
+    fn process(&mut self, info: AudioInfo, buffer: &mut AudioBuffer);
+}
+
+impl<F> AudioNode for F where F: FnMut(AudioInfo, &mut AudioBuffer) + Send {
+    fn process(&mut self, info: AudioInfo, buffer: &mut AudioBuffer) {
+        self(info, buffer)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct AudioNodeId(pub usize);
+
+struct GraphNode {
+    node: Box<dyn AudioNode>,
+    inputs: Vec<AudioNodeId>,
+    buffer: AudioBuffer,
+}
+
+/// Sources and effects wired together, rendered from inside an audio output callback:
+///
+/// ```ignore
+/// let mut graph = AudioNodeGraph::new();
+/// let synth = graph.add(synth);
+/// let gain = graph.add(AudioGain::new(0.5));
+/// graph.connect(synth, gain);
+/// graph.set_output(gain);
+/// cx.audio_output_graph(0, graph);
+/// ```
+///
+/// Only what the output node depends on is rendered. A connection that closes a loop
+/// hears the block its source rendered before.
+#[derive(Default)]
+pub struct AudioNodeGraph {
+    nodes: Vec<Option<GraphNode>>,
+    output: Option<AudioNodeId>,
+    order: Vec<usize>,
+    order_dirty: bool,
+}
+
+impl AudioNodeGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add<N: AudioNode + 'static>(&mut self, node: N) -> AudioNodeId {
+        self.add_box(Box::new(node))
+    }
+
+    pub fn add_box(&mut self, node: Box<dyn AudioNode>) -> AudioNodeId {
+        let node = GraphNode {
+            node,
+            inputs: Vec::new(),
+            buffer: AudioBuffer::default()
+        };
+        self.order_dirty = true;
+        if let Some(index) = self.nodes.iter().position( | n | n.is_none()) {
+            self.nodes[index] = Some(node);
+            AudioNodeId(index)
+        }
+        else {
+            self.nodes.push(Some(node));
+            AudioNodeId(self.nodes.len() - 1)
+        }
+    }
+
+    /// Removes a node and every connection to it.
+    pub fn remove(&mut self, node_id: AudioNodeId) -> Option<Box<dyn AudioNode>> {
+        let node = self.nodes.get_mut(node_id.0)?.take()?;
+        for other in self.nodes.iter_mut().flatten() {
+            other.inputs.retain( | input | *input != node_id);
+        }
+        if self.output == Some(node_id) {
+            self.output = None;
+        }
+        self.order_dirty = true;
+        Some(node.node)
+    }
+
+    /// Feeds the output of `from` into `to`, which mixes it with its other inputs.
+    pub fn connect(&mut self, from: AudioNodeId, to: AudioNodeId) {
+        if !self.contains(from) {
+            return
+        }
+        if let Some(Some(node)) = self.nodes.get_mut(to.0) {
+            if !node.inputs.contains(&from) {
+                node.inputs.push(from);
+                self.order_dirty = true;
+            }
+        }
+    }
+
+    pub fn disconnect(&mut self, from: AudioNodeId, to: AudioNodeId) {
+        if let Some(Some(node)) = self.nodes.get_mut(to.0) {
+            node.inputs.retain( | input | *input != from);
+            self.order_dirty = true;
+        }
+    }
+
+    /// Connects the nodes one after the other, and returns the last one.
+    pub fn chain(&mut self, nodes: &[AudioNodeId]) -> Option<AudioNodeId> {
+        for pair in nodes.windows(2) {
+            self.connect(pair[0], pair[1]);
+        }
+        nodes.last().cloned()
+    }
+
+    /// The node whose buffer goes to the device.
+    pub fn set_output(&mut self, node_id: AudioNodeId) {
+        self.output = Some(node_id);
+        self.order_dirty = true;
+    }
+
+    pub fn contains(&self, node_id: AudioNodeId) -> bool {
+        matches!(self.nodes.get(node_id.0), Some(Some(_)))
+    }
+
+    fn update_order(&mut self) {
+        fn visit(nodes: &[Option<GraphNode>], index: usize, state: &mut [u8], order: &mut Vec<usize>) {
+            // 1 is on the stack, which means a loop, 2 is done
+            if state[index] != 0 {
+                return
+            }
+            state[index] = 1;
+            if let Some(node) = &nodes[index] {
+                for input in &node.inputs {
+                    visit(nodes, input.0, state, order);
+                }
+            }
+            state[index] = 2;
+            order.push(index);
+        }
+        self.order.clear();
+        if let Some(output) = self.output {
+            if self.contains(output) {
+                let mut state = vec![0u8; self.nodes.len()];
+                visit(&self.nodes, output.0, &mut state, &mut self.order);
+            }
+        }
+        self.order_dirty = false;
+    }
+
+    /// Renders a block into `output`, call it from the audio output callback.
+    pub fn process(&mut self, info: AudioInfo, output: &mut AudioBuffer) {
+        if self.order_dirty {
+            self.update_order();
+        }
+        let output_index = if let Some(&index) = self.order.last() {index} else {
+            output.zero();
+            return
+        };
+        for i in 0..self.order.len() {
+            let index = self.order[i];
+            let mut buffer = std::mem::take(&mut self.nodes[index].as_mut().unwrap().buffer);
+            buffer.resize_like(output);
+            buffer.zero();
+            let node = self.nodes[index].as_ref().unwrap();
+            for input in &node.inputs {
+                if let Some(input) = &self.nodes[input.0] {
+                    // an input that hasn't rendered yet has a buffer of another size
+                    if input.buffer.frame_count() == buffer.frame_count() {
+                        buffer.mix_from(&input.buffer, 1.0);
+                    }
+                }
+            }
+            let node = self.nodes[index].as_mut().unwrap();
+            node.node.process(info, &mut buffer);
+            node.buffer = buffer;
+        }
+        output.copy_from(&self.nodes[output_index].as_ref().unwrap().buffer);
+    }
+}
+
+/// Scales the signal, the gain can be changed from the UI thread through `gain()`.
+pub struct AudioGain {
+    gain: Arc<f32a>,
+}
+
+impl AudioGain {
+    pub fn new(gain: f32) -> Self {
+        Self {gain: Arc::new(gain.into())}
+    }
+
+    pub fn gain(&self) -> Arc<f32a> {
+        self.gain.clone()
+    }
+}
+
+impl AudioNode for AudioGain {
+    fn process(&mut self, _info: AudioInfo, buffer: &mut AudioBuffer) {
+        buffer.apply_gain(self.gain.get());
+    }
+}
+
+/// Places the signal between left (-1) and right (1), see `AudioBuffer::apply_pan`.
+pub struct AudioPan {
+    pan: Arc<f32a>,
+}
+
+impl AudioPan {
+    pub fn new(pan: f32) -> Self {
+        Self {pan: Arc::new(pan.into())}
+    }
+
+    pub fn pan(&self) -> Arc<f32a> {
+        self.pan.clone()
+    }
+}
+
+impl AudioNode for AudioPan {
+    fn process(&mut self, _info: AudioInfo, buffer: &mut AudioBuffer) {
+        buffer.apply_pan(self.pan.get());
+    }
+}
+
+struct MixerChannel {
+    node: Box<dyn AudioNode>,
+    gain: Arc<f32a>,
+    pan: Arc<f32a>,
+    buffer: AudioBuffer,
+}
+
+/// Mixes nodes of its own, each with a gain and a pan, on top of what is connected to it.
+#[derive(Default)]
+pub struct AudioMixer {
+    channels: Vec<MixerChannel>,
+}
+
+impl AudioMixer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a channel and returns its gain and pan, to be changed from the UI thread.
+    pub fn add<N: AudioNode + 'static>(&mut self, node: N, gain: f32, pan: f32) -> (Arc<f32a>, Arc<f32a>) {
+        let gain = Arc::new(gain.into());
+        let pan = Arc::new(pan.into());
+        self.channels.push(MixerChannel {
+            node: Box::new(node),
+            gain: Arc::clone(&gain),
+            pan: Arc::clone(&pan),
+            buffer: AudioBuffer::default()
+        });
+        (gain, pan)
+    }
+
+    pub fn with<N: AudioNode + 'static>(mut self, node: N, gain: f32, pan: f32) -> Self {
+        self.add(node, gain, pan);
+        self
+    }
+}
+
+impl AudioNode for AudioMixer {
+    fn process(&mut self, info: AudioInfo, buffer: &mut AudioBuffer) {
+        for channel in &mut self.channels {
+            channel.buffer.resize_like(buffer);
+            channel.buffer.zero();
+            channel.node.process(info, &mut channel.buffer);
+            channel.buffer.apply_pan(channel.pan.get());
+            buffer.mix_from(&channel.buffer, channel.gain.get());
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use {
+    crate::{
+        makepad_micro_serde::*,
+        makepad_live_tokenizer::{LiveErrorOrigin, live_error_origin},
+        makepad_live_compiler::{
+            LiveValue,
+            LiveTypeInfo,
+            LiveModuleId,
+            LiveType,
+            LiveId,
+            LiveNode,
+            LiveNodeSliceApi
+        },
+        makepad_derive_live::*,
+        makepad_math::*,
+        live_traits::*,
+        cx::Cx,
+    }
+};
+
+/// The color space the pixels of a pass, texture or window surface are encoded in.
+/// Colors written in the DSL are always sRGB, so a pass tagged `DisplayP3` converts them
+/// before output and the OS compositor takes care of mapping them to the physical display.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Live, LiveHook, SerBin, DeBin, SerJson, DeJson)]
+#[live_ignore]
+pub enum ColorSpace {
+    #[pick] Srgb,
+    DisplayP3,
+    LinearSrgb,
+}
+
+impl Default for ColorSpace {
+    fn default() -> Self {
+        Self::Srgb
+    }
+}
+
+// linear sRGB primaries -> linear Display P3 primaries (both D65)
+const SRGB_TO_DISPLAY_P3: [[f32; 3]; 3] = [
+    [0.8224621, 0.1775380, 0.0000000],
+    [0.0331941, 0.9668058, 0.0000000],
+    [0.0170827, 0.0723974, 0.9105199],
+];
+
+const DISPLAY_P3_TO_SRGB: [[f32; 3]; 3] = [
+    [1.2249401, -0.2249404, 0.0000000],
+    [-0.0420569, 1.0420571, 0.0000000],
+    [-0.0196376, -0.0786361, 1.0982735],
+];
+
+impl ColorSpace {
+    /// The value of the `color_space` pass uniform shaders can branch on.
+    pub fn to_uniform(&self) -> f32 {
+        match self {
+            Self::Srgb => 0.0,
+            Self::DisplayP3 => 1.0,
+            Self::LinearSrgb => 2.0,
+        }
+    }
+
+    pub fn is_wide_gamut(&self) -> bool {
+        match self {
+            Self::DisplayP3 => true,
+            Self::Srgb | Self::LinearSrgb => false,
+        }
+    }
+
+    pub fn is_linear(&self) -> bool {
+        match self {
+            Self::LinearSrgb => true,
+            Self::Srgb | Self::DisplayP3 => false,
+        }
+    }
+
+    /// Converts a straight (non premultiplied) color from `self` into `to`. Alpha is left untouched.
+    pub fn convert(&self, to: ColorSpace, color: Vec4) -> Vec4 {
+        if *self == to {
+            return color
+        }
+        let mut rgb = [color.x, color.y, color.z];
+        if !self.is_linear() {
+            for c in &mut rgb {
+                *c = srgb_to_linear(*c);
+            }
+        }
+        match (self, to) {
+            (Self::DisplayP3, Self::Srgb) | (Self::DisplayP3, Self::LinearSrgb) => {
+                rgb = mul_mat3(&DISPLAY_P3_TO_SRGB, rgb);
+            }
+            (Self::Srgb, Self::DisplayP3) | (Self::LinearSrgb, Self::DisplayP3) => {
+                rgb = mul_mat3(&SRGB_TO_DISPLAY_P3, rgb);
+            }
+            _ => ()
+        }
+        if !to.is_linear() {
+            for c in &mut rgb {
+                *c = linear_to_srgb(*c);
+            }
+        }
+        vec4(rgb[0], rgb[1], rgb[2], color.w)
+    }
+}
+
+fn mul_mat3(m: &[[f32; 3]; 3], v: [f32; 3]) -> [f32; 3] {
+    [
+        m[0][0] * v[0] + m[0][1] * v[1] + m[0][2] * v[2],
+        m[1][0] * v[0] + m[1][1] * v[1] + m[1][2] * v[2],
+        m[2][0] * v[0] + m[2][1] * v[1] + m[2][2] * v[2],
+    ]
+}
+
+/// The sRGB electro-optical transfer function, also used by Display P3.
+pub fn srgb_to_linear(c: f32) -> f32 {
+    if c <= 0.04045 {
+        c / 12.92
+    }
+    else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+pub fn linear_to_srgb(c: f32) -> f32 {
+    if c <= 0.0031308 {
+        c * 12.92
+    }
+    else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    }
+}
@@ -36,6 +36,23 @@ impl<K: std::cmp::Eq + std::hash::Hash + Copy,V> ComponentMap<K,V>{
         self.map.retain( | k, v | visible.contains(&k) || cb(k,v));
         self.visible.clear();
     } 
+    
+    /// Like `retain_visible_and`, but hands the components it removes to `removed`, so they can
+    /// go back in a `ComponentPool`.
+    pub fn retain_visible_into<CB, RB>(&mut self, keep:CB, mut removed:RB)
+    where CB: Fn(&K, &V)->bool, RB: FnMut(K, V)
+    {
+        let remove: Vec<K> = self.map.iter()
+            .filter( | (k, v) | !self.visible.contains(k) && !keep(k, v))
+            .map( | (k, _) | *k)
+            .collect();
+        for k in remove{
+            if let Some(v) = self.map.remove(&k){
+                removed(k, v);
+            }
+        }
+        self.visible.clear();
+    }
 
     pub fn get_or_insert<'a, CB>(&'a mut self, cx:&mut Cx, key:K, cb:CB)->&'a mut V
     where CB: FnOnce(&mut Cx)->V{
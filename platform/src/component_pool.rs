@@ -0,0 +1,70 @@
+use {
+    std::collections::HashMap,
+    crate::{
+        makepad_live_compiler::*,
+        live_traits::*,
+        cx::Cx
+    }
+};
+
+/// Components that can go back in a `ComponentPool` and be handed out again.
+pub trait Recycle {
+    /// Clears the state the component built up while in use. The pool applies the template
+    /// again when it hands the component out, which puts the live fields back.
+    fn recycle(&mut self, cx: &mut Cx);
+}
+
+/// Components that went out of use, kept per template so they can be handed out again instead
+/// of being made anew, with their draw lists and GPU buffers.
+pub struct ComponentPool<K, V>{
+    free: HashMap<K, Vec<V>>,
+    /// How many components are kept per template, the rest are dropped.
+    pub max_per_template: usize,
+}
+
+impl<K, V> Default for ComponentPool<K, V>{
+    fn default()->Self{
+        Self{
+            free: HashMap::new(),
+            max_per_template: 64,
+        }
+    }
+}
+
+impl<K: std::cmp::Eq + std::hash::Hash + Copy, V: LiveApply + LiveNew + Recycle> ComponentPool<K, V>{
+    /// Hands out a pooled component made from `template`, reset to the template, or makes a new
+    /// one if there is none.
+    pub fn take_or_new(&mut self, cx:&mut Cx, template:K, live_ptr:Option<LivePtr>)->V{
+        if let Some(live_ptr) = live_ptr{
+            if let Some(mut component) = self.free.get_mut(&template).and_then( | free | free.pop()){
+                cx.get_nodes_from_live_ptr(live_ptr, | cx, file_id, index, nodes | {
+                    component.apply(cx, &mut ApplyFrom::NewFromDoc {file_id}.into(), index, nodes)
+                });
+                return component
+            }
+        }
+        V::new_from_ptr(cx, live_ptr)
+    }
+
+    /// Puts a component that went out of use back in the pool.
+    pub fn put(&mut self, cx:&mut Cx, template:K, mut component:V){
+        let free = self.free.entry(template).or_default();
+        if free.len() < self.max_per_template{
+            component.recycle(cx);
+            free.push(component);
+        }
+    }
+
+    /// Drops the pooled components, for when their template changed.
+    pub fn clear(&mut self){
+        self.free.clear();
+    }
+
+    pub fn len(&self)->usize{
+        self.free.values().map( | free | free.len()).sum()
+    }
+
+    pub fn is_empty(&self)->bool{
+        self.len() == 0
+    }
+}
@@ -13,18 +13,22 @@ use {
     crate::{
         makepad_live_compiler::{
             LiveRegistry,
-            LiveFileChange
+            LiveFileChange,
+            LiveId,
         },
         makepad_shader_compiler::ShaderRegistry,
         draw_shader::CxDrawShaders,
         draw_matrix::CxDrawMatrixPool,
         os::{CxOs},
         debug::Debug,
+        event_trace::EventTrace,
+        accessibility::CxAccessibility,
         performance_stats::PerformanceStats,
         event::{
             DrawEvent,
             CxFingers,
             CxDragDrop,
+            CxGamepads,
             Event,
             Trigger,
             CxKeyboard,
@@ -37,6 +41,8 @@ use {
         window::CxWindowPool,
         draw_list::CxDrawListPool,
         web_socket::WebSocket,
+        file_dialogs::DialogEventChannel,
+        midi::MidiInput,
         pass::CxPassPool,
         texture::{CxTexturePool,TextureFormat,Texture},
         geometry::{
@@ -81,6 +87,9 @@ pub struct Cx {
     pub fingers: CxFingers,
     pub (crate) ime_area: Area,
     pub (crate) drag_drop: CxDragDrop,
+    pub (crate) accessibility: CxAccessibility,
+    pub (crate) gamepads: CxGamepads,
+    pub (crate) midi_input_events: Option<MidiInput>,
     
     pub (crate) platform_ops: Vec<CxOsOp>,
     
@@ -96,6 +105,7 @@ pub struct Cx {
 
     pub (crate) live_file_change_receiver: std::sync::mpsc::Receiver<Vec<LiveFileChange>>,
     pub (crate) live_file_change_sender: std::sync::mpsc::Sender<Vec<LiveFileChange >>,
+    pub (crate) live_theme_pending: Option<LiveId>,
 
     pub shader_registry: ShaderRegistry,
     
@@ -108,12 +118,17 @@ pub struct Cx {
     pub (crate) self_ref: Option<Rc<RefCell<Cx>>>,
     
     pub debug: Debug,
+    
+    pub event_trace: EventTrace,
 
     #[allow(dead_code)]
     pub(crate) executor: Option<Executor>,
     pub(crate) spawner: Spawner,
     
     pub(crate) studio_web_socket: Option<WebSocket>,
+    pub(crate) web_sockets: HashMap<LiveId, WebSocket>,
+    pub(crate) http_requests: HashMap<LiveId, usize>,
+    pub(crate) dialog_events: DialogEventChannel,
     pub(crate) studio_http: String,
     
     pub performance_stats: PerformanceStats,
@@ -237,9 +252,15 @@ impl Cx {
             keyboard: Default::default(),
             fingers: Default::default(),
             drag_drop: Default::default(),
+            accessibility: Default::default(),
+            gamepads: Default::default(),
+            midi_input_events: None,
             ime_area: Default::default(),
             platform_ops: Default::default(),
             studio_web_socket: None,
+            web_sockets: Default::default(),
+            http_requests: Default::default(),
+            dialog_events: Default::default(),
             studio_http: "".to_string(),
             new_next_frames: Default::default(),
             
@@ -251,6 +272,7 @@ impl Cx {
             
             live_file_change_receiver: recv,
             live_file_change_sender: send,
+            live_theme_pending: None,
             
             shader_registry: ShaderRegistry::new(),
             
@@ -260,6 +282,8 @@ impl Cx {
             
             debug: Default::default(),
             
+            event_trace: Default::default(),
+            
             globals: Default::default(),
 
             executor: Some(executor),
@@ -1,24 +1,27 @@
-use crate::file_dialogs::FileDialog;
+use crate::file_dialogs::{Dialog, DialogResult, FileDialog, MessageDialog};
 
 use {
     crate::{
         area::Area,
+        color_space::ColorSpace,
         cursor::MouseCursor,
         cx::{Cx, CxRef, OsType, XrCapabilities},
         draw_list::DrawListId,
-        event::{DragItem, HttpRequest, NextFrame, Timer, Trigger, VideoSource},
+        event::{ClipboardFlavor, DialogEvent, DragItem, Event, GamepadId, HttpRequest, NetworkResponse, NetworkResponsesEvent, NextFrame, Timer, Trigger, VideoSource},
         gpu_info::GpuInfo,
         macos_menu::MacosMenu,
         makepad_futures::executor::Spawner,
         makepad_live_id::*,
-        makepad_math::{DVec2, Rect},
+        makepad_math::{DVec2, Rect, Vec4},
         pass::{CxPassParent, CxPassRect, PassId},
         texture::Texture,
+        thread::SignalToUI,
         window::WindowId,
     },
     std::{
         any::{Any, TypeId},
         rc::Rc,
+        sync::mpsc::Sender,
     },
 };
 
@@ -51,6 +54,7 @@ pub enum CxOsOp {
     NormalizeWindow(WindowId),
     RestoreWindow(WindowId),
     SetTopmost(WindowId, bool),
+    SetWindowColorSpace(WindowId, ColorSpace),
 
     XrStartPresenting,
     XrStopPresenting,
@@ -70,11 +74,23 @@ pub enum CxOsOp {
     UpdateMacosMenu(MacosMenu),
     ShowClipboardActions(String),
     CopyToClipboard(String),
+    WriteClipboard(Vec<ClipboardFlavor>),
+    ReadClipboard(LiveId),
+
+    GamepadRumble {
+        gamepad_id: GamepadId,
+        strong: f32,
+        weak: f32,
+        duration: f64,
+    },
 
     HttpRequest {
         request_id: LiveId,
         request: HttpRequest,
     },
+    CancelHttpRequest {
+        request_id: LiveId,
+    },
 
     PrepareVideoPlayback(LiveId, VideoSource, u32, bool, bool),
     BeginVideoPlayback(LiveId),
@@ -85,10 +101,7 @@ pub enum CxOsOp {
     CleanupVideoPlaybackResources(LiveId),
     UpdateVideoSurfaceTexture(LiveId),
     
-    SaveFileDialog(FileDialog),
-    SelectFileDialog(FileDialog),
-    SaveFolderDialog(FileDialog),
-    SelectFolderDialog(FileDialog),    
+    ShowDialog(LiveId, Dialog),
 }
 
 impl Cx {
@@ -183,11 +196,23 @@ impl Cx {
 
     /// Copies the given string to the clipboard.
     /// 
-    /// Due to lack of platform clipboard support, it does not work on Web or tvOS.
+    /// Due to lack of platform clipboard support, it does not work on tvOS.
     pub fn copy_to_clipboard(&mut self, content: &str) {
         self.platform_ops.push(CxOsOp::CopyToClipboard(content.to_owned()));
     }
 
+    /// Replaces the clipboard with the given flavors of the same content, like plain text
+    /// next to its html. Flavors a platform has no place for are left out.
+    pub fn write_clipboard(&mut self, flavors: Vec<ClipboardFlavor>) {
+        self.platform_ops.push(CxOsOp::WriteClipboard(flavors));
+    }
+
+    /// Reads every flavor on the clipboard. They arrive as `Event::ClipboardRead` with
+    /// the given request id, on the web only after the user allowed the read.
+    pub fn read_clipboard(&mut self, request_id: LiveId) {
+        self.platform_ops.push(CxOsOp::ReadClipboard(request_id));
+    }
+
     pub fn start_dragging(&mut self, items: Vec<DragItem>) {
         self.platform_ops.iter().for_each(|p| {
             if let CxOsOp::StartDragging { .. } = p {
@@ -259,6 +284,25 @@ impl Cx {
         return 1.0;
     }
 
+    /// The window an area is drawn in, following passes drawn into other passes.
+    pub fn window_id_of(&self, area: &Area) -> Option<WindowId> {
+        let mut pass_id_walk = self.draw_lists.checked_index(area.draw_list_id()?)?.pass_id?;
+        for _ in 0..25 {
+            match self.passes[pass_id_walk].parent {
+                CxPassParent::Window(window_id) => {
+                    return Some(window_id);
+                }
+                CxPassParent::Pass(next_pass_id) => {
+                    pass_id_walk = next_pass_id;
+                }
+                _ => {
+                    break;
+                }
+            }
+        }
+        None
+    }
+
     pub fn get_delegated_dpi_factor(&mut self, pass_id: PassId) -> f64 {
         let mut pass_id_walk = pass_id;
         for _ in 0..25 {
@@ -280,6 +324,36 @@ impl Cx {
         1.0
     }
 
+    pub fn get_delegated_color_space(&self, pass_id: PassId) -> ColorSpace {
+        let mut pass_id_walk = pass_id;
+        for _ in 0..25 {
+            if let Some(color_space) = self.passes[pass_id_walk].color_space {
+                return color_space;
+            }
+            // a pass drawing into textures draws in the color space they are tagged with
+            if let Some(color_texture) = self.passes[pass_id_walk].color_textures.first() {
+                return self.textures[color_texture.texture.texture_id()].color_space;
+            }
+            match self.passes[pass_id_walk].parent {
+                CxPassParent::Window(window_id) => {
+                    return self.windows[window_id].color_space;
+                }
+                CxPassParent::Pass(next_pass_id) => {
+                    pass_id_walk = next_pass_id;
+                }
+                _ => {
+                    break;
+                }
+            }
+        }
+        ColorSpace::Srgb
+    }
+    
+    /// Clear colors are sRGB like every other color, this converts one into what the pass draws in.
+    pub fn get_delegated_clear_color(&self, pass_id: PassId, color: Vec4) -> Vec4 {
+        ColorSpace::Srgb.convert(self.get_delegated_color_space(pass_id), color)
+    }
+
     pub fn redraw_pass_and_parent_passes(&mut self, pass_id: PassId) {
         let mut walk_pass_id = pass_id;
         loop {
@@ -364,6 +438,16 @@ impl Cx {
         }
     }
 
+    /// Redraws everything drawn into one window, leaving the other windows alone.
+    pub fn redraw_window(&mut self, window_id: WindowId) {
+        if !self.windows.is_valid(window_id) {
+            return;
+        }
+        if let Some(main_pass_id) = self.windows[window_id].main_pass_id {
+            self.redraw_pass_and_child_passes(main_pass_id);
+        }
+    }
+
     pub fn redraw_all(&mut self) {
         self.new_draw_event.redraw_all = true;
     }
@@ -488,27 +572,44 @@ impl Cx {
         &self.spawner
     }
 
+    /// Sends a request in the background, it reports back with `Event::NetworkResponses`
+    /// carrying `request_id`: progress while downloading, then a response or an error.
     pub fn http_request(&mut self, request_id: LiveId, request: HttpRequest) {
+        *self.http_requests.entry(request_id).or_insert(0) += 1;
         self.platform_ops.push(CxOsOp::HttpRequest {
             request_id,
             request,
         });
     }
-    /*
-        pub fn web_socket_open(&mut self, request_id: LiveId, request: HttpRequest) {
-            self.platform_ops.push(CxOsOp::WebSocketOpen{
-                request,
-                request_id,
-            });
+
+    /// Stops every running request with this id, nothing is delivered for them anymore.
+    pub fn cancel_http_request(&mut self, request_id: LiveId) {
+        if self.http_requests.remove(&request_id).is_some() {
+            self.platform_ops.push(CxOsOp::CancelHttpRequest {request_id});
         }
+    }
 
-        pub fn web_socket_send_binary(&mut self, request_id: LiveId, data: Vec<u8>) {
-            self.platform_ops.push(CxOsOp::WebSocketSendBinary{
-                request_id,
-                data,
-            });
+    pub(crate) fn call_network_responses(&mut self, mut responses: NetworkResponsesEvent) {
+        // drops whatever a cancelled request still managed to send
+        let http_requests = &mut self.http_requests;
+        responses.retain(|item| {
+            let running = match http_requests.get_mut(&item.request_id) {
+                Some(running) => running,
+                None => return false,
+            };
+            if let NetworkResponse::HttpResponse(_) | NetworkResponse::HttpRequestError(_) = item.response {
+                *running -= 1;
+                if *running == 0 {
+                    http_requests.remove(&item.request_id);
+                }
+            }
+            true
+        });
+        if responses.len() > 0 {
+            self.call_event_handler(&Event::NetworkResponses(responses));
         }
-    */
+    }
+
     pub fn prepare_video_playback(
         &mut self,
         video_id: LiveId,
@@ -557,22 +658,44 @@ impl Cx {
         println!("Num textures: {}", self.textures.0.pool.len());
     }
 
-    pub fn open_system_savefile_dialog(&mut self) {
-        self.platform_ops.push(CxOsOp::SaveFileDialog(FileDialog::new()));
+    /// Shows a dialog to pick files to open. When the user is done it reports back with
+    /// `Event::Dialog(DialogEvent::Closed{..})` carrying `dialog_id`.
+    pub fn open_file_dialog(&mut self, dialog_id: LiveId, dialog: FileDialog) {
+        self.platform_ops.push(CxOsOp::ShowDialog(dialog_id, Dialog::OpenFile(dialog)));
     }
 
-    pub fn open_system_openfile_dialog(&mut self) {
-        self.platform_ops.push(CxOsOp::SelectFileDialog(FileDialog::new()));
+    pub fn save_file_dialog(&mut self, dialog_id: LiveId, dialog: FileDialog) {
+        self.platform_ops.push(CxOsOp::ShowDialog(dialog_id, Dialog::SaveFile(dialog)));
     }
 
-    pub fn open_system_savefolder_dialog(&mut self) {
-        self.platform_ops.push(CxOsOp::SaveFolderDialog(FileDialog::new()));
+    pub fn select_folder_dialog(&mut self, dialog_id: LiveId, dialog: FileDialog) {
+        self.platform_ops.push(CxOsOp::ShowDialog(dialog_id, Dialog::SelectFolder(dialog)));
+    }
+
+    pub fn message_dialog(&mut self, dialog_id: LiveId, dialog: MessageDialog) {
+        self.platform_ops.push(CxOsOp::ShowDialog(dialog_id, Dialog::Message(dialog)));
+    }
 
+    /// Reports the result of a dialog. The in-engine dialogs that stand in for native ones use this.
+    pub fn close_dialog(&mut self, dialog_id: LiveId, result: DialogResult) {
+        let _ = self.dialog_events.sender.send(DialogEvent::Closed {dialog_id, result});
+        SignalToUI::set_ui_signal();
     }
 
-    pub fn open_system_openfolder_dialog(&mut self) {
-        self.platform_ops.push(CxOsOp::SelectFolderDialog(FileDialog::new()));
+    pub(crate) fn dialog_sender(&self) -> Sender<DialogEvent> {
+        self.dialog_events.sender.clone()
+    }
 
+    /// For platforms without a native dialog, lets the widgets draw one.
+    pub(crate) fn show_dialog_fallback(&mut self, dialog_id: LiveId, dialog: Dialog) {
+        let _ = self.dialog_events.sender.send(DialogEvent::ShowFallback {dialog_id, dialog});
+        SignalToUI::set_ui_signal();
+    }
+
+    pub(crate) fn handle_dialog_events(&mut self) {
+        while let Ok(event) = self.dialog_events.receiver.try_recv() {
+            self.call_event_handler(&Event::Dialog(event));
+        }
     }
 }
 
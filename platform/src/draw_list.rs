@@ -9,6 +9,7 @@ use {
             CxOsView,
         },
         pass::PassId,
+        color_space::ColorSpace,
         id_pool::*,
         draw_shader::{
             CxDrawShaderOptions,
@@ -91,7 +92,7 @@ pub struct DrawUniforms {
     //pub draw_clip_y2: f32,
     //pub draw_scroll: Vec4,
     pub draw_zbias: f32,
-    pub pad1: f32,
+    pub draw_texture_color_space: f32,
     pub pad2: f32,
     pub pad3: f32
 }
@@ -108,6 +109,12 @@ impl DrawUniforms {
     pub fn set_zbias(&mut self, zbias: f32) {
         self.draw_zbias = zbias;
     }
+    
+    /// Sets the color space of the texture in the first slot, shaders convert its samples to sRGB
+    /// with it.
+    pub fn set_texture_color_space(&mut self, color_space: ColorSpace) {
+        self.draw_texture_color_space = color_space.to_uniform();
+    }
     /*
     pub fn set_clip(&mut self, clip: (Vec2, Vec2)) {
         self.draw_clip_x1 = clip.0.x;
@@ -158,6 +158,14 @@ impl DrawVars {
     }
     
     pub fn init_shader(&mut self, cx: &mut Cx, apply: &mut Apply, draw_shader_ptr: DrawShaderPtr, geometry_fields: &dyn GeometryFields) {
+        let old_shader_id = self.draw_shader.map( | draw_shader | draw_shader.draw_shader_id);
+        self.init_shader_inner(cx, apply, draw_shader_ptr, geometry_fields);
+        if self.draw_shader.map( | draw_shader | draw_shader.draw_shader_id) != old_shader_id {
+            apply.mark_changed();
+        }
+    }
+    
+    fn init_shader_inner(&mut self, cx: &mut Cx, apply: &mut Apply, draw_shader_ptr: DrawShaderPtr, geometry_fields: &dyn GeometryFields) {
         self.draw_shader = None;
         
         if cx.draw_shaders.error_set.contains(&draw_shader_ptr) {
@@ -565,21 +573,22 @@ impl DrawVars {
     
     pub fn apply_slots(cx: &mut Cx, slots: usize, output: &mut [f32], offset: usize, apply: &mut Apply, index: usize, nodes: &[LiveNode]) -> usize {
         match slots {
+            // the values start out as the current ones, so applying the same value isn't a change
             1 => {
-                let mut v: f32 = 0.0;
+                let mut v: f32 = output[offset + 0];
                 let index = v.apply(cx, apply, index, nodes);
                 output[offset + 0] = v;
                 return index;
             }
             2 => {
-                let mut v: Vec2 = Vec2::default();
+                let mut v: Vec2 = vec2(output[offset + 0], output[offset + 1]);
                 let index = v.apply(cx, apply, index, nodes);
                 output[offset + 0] = v.x;
                 output[offset + 1] = v.y;
                 return index;
             }
             3 => {
-                let mut v: Vec3 = Vec3::default();
+                let mut v: Vec3 = vec3(output[offset + 0], output[offset + 1], output[offset + 2]);
                 let index = v.apply(cx, apply, index, nodes);
                 output[offset + 0] = v.x;
                 output[offset + 1] = v.y;
@@ -587,7 +596,7 @@ impl DrawVars {
                 return index;
             }
             4 => {
-                let mut v: Vec4 = Vec4::default();
+                let mut v: Vec4 = vec4(output[offset + 0], output[offset + 1], output[offset + 2], output[offset + 3]);
                 let index = v.apply(cx, apply, index, nodes);
                 output[offset + 0] = v.x;
                 output[offset + 1] = v.y;
@@ -656,7 +665,10 @@ impl DrawVars {
             self.init_slicer(cx);
         }
         self.geometry_id = geometry_fields.get_geometry_id();
-        self.update_area_with_self(cx, index, nodes);
+        // re-applying the same values would only repaint for nothing
+        if apply.any_changed() {
+            self.update_area_with_self(cx, index, nodes);
+        }
     }
     
 }
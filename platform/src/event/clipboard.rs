@@ -0,0 +1,109 @@
+use crate::{
+    makepad_live_id::LiveId,
+    event::keyboard::TextClipboardEvent,
+};
+
+/// One representation of the clipboard contents. A copy puts the same content on the
+/// clipboard in as many flavors as it has, so each app reading it can pick the richest one
+/// it understands.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ClipboardFlavor {
+    Text(String),
+    Html(String),
+    Rtf(String),
+    /// Encoded image bytes, like a png file, with their mime type.
+    Image {mime: String, data: Vec<u8>},
+    /// App defined data, keyed by a mime type like `application/x-myapp-nodes`.
+    Custom {mime: String, data: Vec<u8>},
+}
+
+impl ClipboardFlavor {
+    pub fn png(data: Vec<u8>) -> Self {
+        Self::Image {mime: "image/png".to_string(), data}
+    }
+
+    pub fn custom(mime: &str, data: Vec<u8>) -> Self {
+        Self::Custom {mime: mime.to_string(), data}
+    }
+
+    /// The mime type platforms without types of their own store the flavor under.
+    pub fn mime(&self) -> &str {
+        match self {
+            Self::Text(_) => "text/plain",
+            Self::Html(_) => "text/html",
+            Self::Rtf(_) => "text/rtf",
+            Self::Image {mime, ..} | Self::Custom {mime, ..} => mime
+        }
+    }
+
+    pub fn data(&self) -> &[u8] {
+        match self {
+            Self::Text(text) | Self::Html(text) | Self::Rtf(text) => text.as_bytes(),
+            Self::Image {data, ..} | Self::Custom {data, ..} => data
+        }
+    }
+
+    /// Turns bytes read from a platform clipboard back into a flavor.
+    pub fn from_mime(mime: &str, data: Vec<u8>) -> Self {
+        let text = | data: Vec<u8> | String::from_utf8_lossy(&data).into_owned();
+        match mime.split(';').next().unwrap_or("").trim() {
+            "text/plain" => Self::Text(text(data)),
+            "text/html" => Self::Html(text(data)),
+            "text/rtf" | "application/rtf" => Self::Rtf(text(data)),
+            mime if mime.starts_with("image/") => Self::Image {mime: mime.to_string(), data},
+            _ => Self::Custom {mime: mime.to_string(), data}
+        }
+    }
+}
+
+/// The clipboard contents, as requested with `cx.read_clipboard`.
+#[derive(Clone, Debug, Default)]
+pub struct ClipboardReadEvent {
+    pub request_id: LiveId,
+    /// Every flavor the clipboard holds that we could read, empty if the clipboard is empty.
+    pub flavors: Vec<ClipboardFlavor>,
+}
+
+impl ClipboardReadEvent {
+    pub fn text(&self) -> Option<&str> {
+        self.flavors.iter().find_map( | flavor | if let ClipboardFlavor::Text(text) = flavor {Some(text.as_str())} else {None})
+    }
+
+    pub fn html(&self) -> Option<&str> {
+        self.flavors.iter().find_map( | flavor | if let ClipboardFlavor::Html(html) = flavor {Some(html.as_str())} else {None})
+    }
+
+    pub fn rtf(&self) -> Option<&str> {
+        self.flavors.iter().find_map( | flavor | if let ClipboardFlavor::Rtf(rtf) = flavor {Some(rtf.as_str())} else {None})
+    }
+
+    /// The first image on the clipboard, with its mime type.
+    pub fn image(&self) -> Option<(&str, &[u8])> {
+        self.flavors.iter().find_map( | flavor | if let ClipboardFlavor::Image {mime, data} = flavor {Some((mime.as_str(), data.as_slice()))} else {None})
+    }
+
+    pub fn custom(&self, mime: &str) -> Option<&[u8]> {
+        self.flavors.iter().find_map( | flavor | match flavor {
+            ClipboardFlavor::Custom {mime: m, data} if m == mime => Some(data.as_slice()),
+            _ => None
+        })
+    }
+}
+
+impl TextClipboardEvent {
+    /// The flavors to put on the clipboard for the response to this copy, the plain text
+    /// first.
+    pub fn flavors(&self) -> Option<Vec<ClipboardFlavor >> {
+        let text = self.response.borrow().clone() ?;
+        let mut flavors = vec![ClipboardFlavor::Text(text)];
+        if let Some(rich) = self.rich_response.borrow().as_ref() {
+            if let Some(html) = &rich.html {
+                flavors.push(ClipboardFlavor::Html(html.clone()));
+            }
+            if let Some(rtf) = &rich.rtf {
+                flavors.push(ClipboardFlavor::Rtf(rtf.clone()));
+            }
+        }
+        Some(flavors)
+    }
+}
@@ -11,12 +11,14 @@ use {
         },
         cx::Cx,
         area::Area,
+        window::WindowId,
     },
 };
 
 
 #[derive(Clone, Debug)]
 pub struct DragEvent {
+    pub window_id: WindowId,
     pub modifiers: KeyModifiers,
     pub handled: Cell<bool>,
     pub abs: DVec2,
@@ -26,6 +28,7 @@ pub struct DragEvent {
 
 #[derive(Clone, Debug)]
 pub struct DropEvent {
+    pub window_id: WindowId,
     pub modifiers: KeyModifiers,
     pub handled: Cell<bool>,
     pub abs: DVec2,
@@ -34,6 +37,7 @@ pub struct DropEvent {
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct DragHitEvent {
+    pub window_id: WindowId,
     pub modifiers: KeyModifiers,
     pub abs: DVec2,
     pub rect: Rect,
@@ -44,6 +48,7 @@ pub struct DragHitEvent {
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct DropHitEvent {
+    pub window_id: WindowId,
     pub modifiers: KeyModifiers,
     pub abs: DVec2,
     pub rect: Rect,
@@ -68,7 +73,39 @@ pub enum DragResponse {
 #[derive(Clone, Debug, PartialEq)]
 pub enum DragItem {
     FilePath {path: String, internal_id: Option<LiveId>},
-    String {value: String, internal_id: Option<LiveId>}
+    String {value: String, internal_id: Option<LiveId>},
+    /// A file dragged in from outside on the web, where there are no paths. The browser only
+    /// hands out the files on drop, so while dragging the name and data are empty.
+    FileData {name: String, data: Rc<Vec<u8>>},
+}
+
+impl DragItem {
+    /// The item for a `file://` uri from an external drag, like a line of a `text/uri-list`.
+    pub fn from_file_uri(uri: &str) -> Option<Self> {
+        let path = uri.strip_prefix("file://") ?;
+        // skip the host, which is empty or localhost for local files
+        let path = &path[path.find('/') ?..];
+        Some(DragItem::FilePath {path: decode_uri_path(path), internal_id: None})
+    }
+}
+
+/// Decodes the `%xx` escapes in the path of a file uri.
+pub (crate) fn decode_uri_path(path: &str) -> String {
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            if let Some(byte) = std::str::from_utf8(&bytes[i + 1..i + 3]).ok().and_then( | hex | u8::from_str_radix(hex, 16).ok()) {
+                decoded.push(byte);
+                i += 3;
+                continue
+            }
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
 }
 
 /*
@@ -108,7 +145,11 @@ impl Event {
     }
     
     pub fn drag_hits_with_options(&self, cx: &mut Cx, area: Area, options: HitOptions) -> DragHit {
+        // drags over another window never hit this area
+        let in_window = | window_id | cx.window_id_of(&area).map_or(true, | area_window_id | area_window_id == window_id);
         match self {
+            Event::Drag(event) if !in_window(event.window_id) => DragHit::NoHit,
+            Event::Drop(event) if !in_window(event.window_id) => DragHit::NoHit,
             Event::Drag(event) => {
                 let rect = area.clipped_rect(cx);
                 if area == cx.drag_drop.drag_area {
@@ -117,6 +158,7 @@ impl Event {
                         cx.drag_drop.next_drag_area = area;
                         event.handled.set(true);
                         DragHit::Drag(DragHitEvent {
+                            window_id: event.window_id,
                             rect,
                             modifiers: event.modifiers,
                             abs: event.abs,
@@ -127,6 +169,7 @@ impl Event {
                     } else {
                         //log!("drag_hist_with_options: Drag, in drag area, event not handled or rect ({:?}) doesn't contain ({},{}) with margin {:?}",rect,event.abs.x,event.abs.y,options.margin);
                         DragHit::Drag(DragHitEvent {
+                            window_id: event.window_id,
                             rect,
                             modifiers: event.modifiers,
                             state: DragState::Out,
@@ -141,6 +184,7 @@ impl Event {
                         cx.drag_drop.next_drag_area = area;
                         event.handled.set(true);
                         DragHit::Drag(DragHitEvent {
+                            window_id: event.window_id,
                             modifiers: event.modifiers,
                             rect,
                             state: DragState::In,
@@ -161,6 +205,7 @@ impl Event {
                     cx.drag_drop.next_drag_area = Area::default();
                     event.handled.set(true);
                     DragHit::Drop(DropHitEvent {
+                        window_id: event.window_id,
                         modifiers: event.modifiers,
                         rect,
                         abs: event.abs,
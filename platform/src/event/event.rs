@@ -16,12 +16,17 @@ use {
             drag_drop::*,
             network::*,
             video_playback::*,
+            read_back::*,
+            clipboard::*,
+            gamepad::*,
         },
         action::ActionsBuf,
         animator::Ease,
         audio::AudioDevicesEvent,
-        midi::MidiPortsEvent,
+        midi::{MidiPortsEvent, MidiInputEvent},
         video::VideoInputsEvent,
+        web_socket::WebSocketMessage,
+        file_dialogs::{Dialog, DialogResult},
         draw_list::DrawListId,
     },
 };
@@ -133,7 +138,13 @@ pub enum Event {
     WindowDragQuery(WindowDragQueryEvent),
     WindowCloseRequested(WindowCloseRequestedEvent),
     WindowClosed(WindowClosedEvent),
+    /// The user asked to quit the application, for instance with cmd+Q on macOS. Set
+    /// `accept_quit` to false to stay open, and call `cx.quit()` later to quit after all.
+    AppQuitRequested(AppQuitRequestedEvent),
     WindowGeomChange(WindowGeomChangeEvent),
+    /// The scale factor of a window changed, for instance because it was moved to a display
+    /// with another one. The window gets redrawn at the new scale.
+    DpiChanged(DpiChangedEvent),
     VirtualKeyboard(VirtualKeyboardEvent),
     ClearAtlasses,
 
@@ -143,6 +154,7 @@ pub enum Event {
     MouseLeave(MouseLeaveEvent),
     TouchUpdate(TouchUpdateEvent),
     Scroll(ScrollEvent), // this is the MouseWheel / touch scroll event sent by the OS
+    Navigate(NavigateEvent),
 
     Timer(TimerEvent),
 
@@ -154,18 +166,33 @@ pub enum Event {
     KeyDown(KeyEvent),
     KeyUp(KeyEvent),
     TextInput(TextInputEvent),
+    TextComposition(TextCompositionEvent),
     TextCopy(TextClipboardEvent),
     TextCut(TextClipboardEvent),
+    /// The clipboard contents, as requested with `cx.read_clipboard`.
+    ClipboardRead(ClipboardReadEvent),
 
     Drag(DragEvent),
     Drop(DropEvent),
     DragEnd,
 
+    /// A game controller was plugged in or paired, see `cx.gamepads()` for the connected ones.
+    GamepadConnected(GamepadConnectedEvent),
+    GamepadDisconnected(GamepadDisconnectedEvent),
+    GamepadButton(GamepadButtonEvent),
+    GamepadAxis(GamepadAxisEvent),
+
     Actions(ActionsBuf),
     AudioDevices(AudioDevicesEvent),
     MidiPorts(MidiPortsEvent),
+    /// A message from a MIDI input, once `use_midi_inputs` has been called.
+    MidiInput(MidiInputEvent),
     VideoInputs(VideoInputsEvent),
     NetworkResponses(NetworkResponsesEvent),
+    /// Something happened on a socket opened with `Cx::web_socket_open`.
+    WebSocket(WebSocketEvent),
+    /// A dialog opened with `Cx::open_file_dialog` and friends was closed, or needs drawing.
+    Dialog(DialogEvent),
 
     VideoPlaybackPrepared(VideoPlaybackPreparedEvent),
     VideoTextureUpdated(VideoTextureUpdatedEvent),
@@ -173,6 +200,8 @@ pub enum Event {
     VideoPlaybackResourcesReleased(VideoPlaybackResourcesReleasedEvent),
     VideoDecodingError(VideoDecodingErrorEvent),
     TextureHandleReady(TextureHandleReadyEvent),
+    /// Pixels read back from a pass after it was drawn.
+    PassReadBack(PassReadBackEvent),
 
     BackPressed,
     #[cfg(target_arch = "wasm32")]
@@ -246,6 +275,19 @@ impl Event{
             48=>"MouseLeave",
             49=>"Actions",
             50=>"BackPressed",
+            52=>"AppQuitRequested",
+            53=>"PassReadBack",
+            54=>"Navigate",
+            55=>"TextComposition",
+            56=>"ClipboardRead",
+            57=>"DpiChanged",
+            58=>"GamepadConnected",
+            59=>"GamepadDisconnected",
+            60=>"GamepadButton",
+            61=>"GamepadAxis",
+            62=>"MidiInput",
+            63=>"WebSocket",
+            64=>"Dialog",
 
             #[cfg(target_arch = "wasm32")]
             51=>"ToWasmMsg",
@@ -315,6 +357,19 @@ impl Event{
             Self::MouseLeave(_)=>48,
             Self::Actions(_)=>49,
             Self::BackPressed=>50,
+            Self::AppQuitRequested(_)=>52,
+            Self::PassReadBack(_)=>53,
+            Self::Navigate(_)=>54,
+            Self::TextComposition(_)=>55,
+            Self::ClipboardRead(_)=>56,
+            Self::DpiChanged(_)=>57,
+            Self::GamepadConnected(_)=>58,
+            Self::GamepadDisconnected(_)=>59,
+            Self::GamepadButton(_)=>60,
+            Self::GamepadAxis(_)=>61,
+            Self::MidiInput(_)=>62,
+            Self::WebSocket(_)=>63,
+            Self::Dialog(_)=>64,
 
             #[cfg(target_arch = "wasm32")]
             Self::ToWasmMsg(_)=>51,
@@ -330,6 +385,7 @@ pub enum Hit{
     KeyUp(KeyEvent),
     Trigger(TriggerHitEvent),
     TextInput(TextInputEvent),
+    TextComposition(TextCompositionEvent),
     TextCopy(TextClipboardEvent),
     TextCut(TextClipboardEvent),
 
@@ -340,6 +396,7 @@ pub enum Hit{
     FingerHoverOver(FingerHoverEvent),
     FingerHoverOut(FingerHoverEvent),
     FingerUp(FingerUpEvent),
+    FingerGesture(FingerGestureEvent),
 
     BackPressed,
 
@@ -452,15 +509,17 @@ pub struct Trigger{
 pub struct TriggerHitEvent(pub Vec<Trigger>);
 
 #[derive(Clone, Debug)]
-pub struct WebSocketErrorEvent {
+pub struct WebSocketEvent {
     pub socket_id: LiveId,
-    pub error: String
+    pub message: WebSocketMessage
 }
 
 #[derive(Clone, Debug)]
-pub struct WebSocketMessageEvent {
-    pub socket_id: LiveId,
-    pub data: Vec<u8>
+pub enum DialogEvent {
+    Closed{dialog_id: LiveId, result: DialogResult},
+    /// There is no native dialog for this on the platform. The `FallbackDialog` in the widgets
+    /// window draws one and reports back with `Cx::close_dialog`.
+    ShowFallback{dialog_id: LiveId, dialog: Dialog},
 }
 
 #[derive(Clone, Debug, Default, Eq, PartialEq, Copy, Hash)]
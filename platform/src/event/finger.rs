@@ -96,6 +96,23 @@ pub struct ScrollEvent {
     pub time: f64
 }
 
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NavigateDirection {
+    Back,
+    Forward
+}
+
+/// The user asked to go back or forward, like in a browser, with the back and forward buttons
+/// of a mouse or by swiping sideways on a trackpad. These mouse buttons send no mouse down
+/// and up events.
+#[derive(Clone, Debug)]
+pub struct NavigateEvent {
+    pub window_id: WindowId,
+    pub direction: NavigateDirection,
+    pub abs: DVec2,
+    pub time: f64
+}
+
 
 // Touch events
 
@@ -216,10 +233,64 @@ pub struct CxDigitHover {
     area: Area,
 }
 
+/// Two touches captured by the same area, which turn into pinch and rotate gestures.
+#[derive(Clone)]
+pub struct CxDigitGesture {
+    area: Area,
+    digits: [(DigitId, DVec2); 2],
+    abs_start: DVec2,
+    ended: bool,
+}
+
+impl CxDigitGesture {
+    fn center(&self) -> DVec2 {
+        (self.digits[0].1 + self.digits[1].1) * 0.5
+    }
+    
+    /// Moves the fingers to where the touches are now, and returns the scale, rotation
+    /// and pan since the last update.
+    fn update(&mut self, touches: &[TouchPoint]) -> (f64, f64, DVec2) {
+        let old_delta = self.digits[1].1 - self.digits[0].1;
+        let old_center = self.center();
+        for (digit_id, abs) in &mut self.digits {
+            if let Some(t) = touches.iter().find( | t | DigitId::from(live_id_num!(touch, t.uid)) == *digit_id) {
+                *abs = t.abs;
+            }
+        }
+        let new_delta = self.digits[1].1 - self.digits[0].1;
+        let scale = if old_delta.length() > 0.0 {new_delta.length() / old_delta.length()} else {1.0};
+        let mut rotation = new_delta.y.atan2(new_delta.x) - old_delta.y.atan2(old_delta.x);
+        if rotation > std::f64::consts::PI {
+            rotation -= 2.0 * std::f64::consts::PI;
+        }
+        else if rotation < -std::f64::consts::PI {
+            rotation += 2.0 * std::f64::consts::PI;
+        }
+        (scale, rotation, self.center() - old_center)
+    }
+    
+    fn event(&self, e: &TouchUpdateEvent, phase: GesturePhase, (scale, rotation, pan): (f64, f64, DVec2), rect: Rect) -> FingerGestureEvent {
+        FingerGestureEvent {
+            window_id: e.window_id,
+            phase,
+            digit_ids: [self.digits[0].0, self.digits[1].0],
+            abs: self.center(),
+            abs_start: self.abs_start,
+            scale,
+            rotation,
+            pan,
+            modifiers: e.modifiers.clone(),
+            time: e.time,
+            rect,
+        }
+    }
+}
+
 #[derive(Default, Clone)]
 pub struct CxFingers {
     pub first_mouse_button: Option<(usize, WindowId)>,
     captures: Vec<CxDigitCapture>,
+    gestures: Vec<CxDigitGesture>,
     tap: CxDigitTap,
     hovers: Vec<CxDigitHover>,
     sweep_lock: Option<Area>,
@@ -266,6 +337,11 @@ impl CxFingers {
                 capture.sweep_area = new_area;
             }
         }
+        for gesture in &mut self.gestures {
+            if gesture.area == old_area {
+                gesture.area = new_area;
+            }
+        }
         if self.sweep_lock == Some(old_area) {
             self.sweep_lock = Some(new_area);
         }
@@ -329,6 +405,15 @@ impl CxFingers {
         self.captures.iter_mut().find( | v | v.area == area)
     }
     
+    pub (crate) fn find_digit_area_capture(&mut self, digit_id: DigitId, area: Area) -> Option<&mut CxDigitCapture> {
+        self.captures.iter_mut().find( | v | v.digit_id == digit_id && v.area == area)
+    }
+    
+    /// True while two fingers on the area are pinching or rotating it.
+    pub fn is_area_in_gesture(&self, area: Area) -> bool {
+        self.gestures.iter().any( | g | g.area == area && !g.ended)
+    }
+    
     pub fn is_area_captured(&self, area: Area) -> bool {
         self.captures.iter().find( | v | v.area == area).is_some()
     }
@@ -387,6 +472,8 @@ impl CxFingers {
                 }
             }
         }
+        let captures = &self.captures;
+        self.gestures.retain( | g | g.digits.iter().any( | (digit_id, _) | captures.iter().any( | c | c.digit_id == *digit_id)));
         self.switch_captures();
     }
     
@@ -556,6 +643,35 @@ pub struct FingerHoverEvent {
     pub rect: Rect,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GesturePhase {
+    Start,
+    Move,
+    End,
+}
+
+/// Two fingers on an area that asked for multi-touch with `HitOptions::with_multi_touch`.
+/// The scale, rotation and pan are relative to the previous event, so a view can apply them
+/// to its zoom and pan as they come in.
+#[derive(Clone, Debug)]
+pub struct FingerGestureEvent {
+    pub window_id: WindowId,
+    pub phase: GesturePhase,
+    pub digit_ids: [DigitId; 2],
+    /// The point between the two fingers.
+    pub abs: DVec2,
+    pub abs_start: DVec2,
+    /// The factor by which the distance between the fingers changed.
+    pub scale: f64,
+    /// How far the fingers turned, in radians, clockwise on screen.
+    pub rotation: f64,
+    /// How far the point between the fingers moved.
+    pub pan: DVec2,
+    pub modifiers: KeyModifiers,
+    pub time: f64,
+    pub rect: Rect,
+}
+
 #[derive(Clone, Debug)]
 pub struct FingerScrollEvent {
     pub window_id: WindowId,
@@ -583,6 +699,9 @@ pub struct HitOptions {
     pub margin: Option<Margin>,
     pub sweep_area: Area,
     pub capture_overload: bool,
+    /// Lets a second touch join the one that holds the area, and sends `Hit::FingerGesture`
+    /// for the two of them instead of finger events.
+    pub multi_touch: bool,
 }
 
 impl HitOptions {
@@ -608,6 +727,12 @@ impl HitOptions {
             ..self
         }
     }
+    pub fn with_multi_touch(self, multi_touch: bool) -> Self {
+        Self {
+            multi_touch,
+            ..self
+        }
+    }
 }
 
 
@@ -642,6 +767,11 @@ impl Event {
         if !area.is_valid(cx) {
             return Hit::Nothing
         }
+        // pointers only hit areas drawn in the window they are over
+        let area_window_id = cx.window_id_of(&area);
+        let hit_test = | window_id: WindowId, abs: DVec2, rect: &Rect, margin: &Option<Margin> | {
+            area_window_id.map_or(true, | area_window_id | area_window_id == window_id) && hit_test(abs, rect, margin)
+        };
         match self {
             Event::KeyFocus(kf) => {
                 if area == kf.prev {
@@ -666,6 +796,11 @@ impl Event {
                     return Hit::TextInput(ti.clone())
                 }
             },
+            Event::TextComposition(tc) => {
+                if cx.keyboard.has_key_focus(area) {
+                    return Hit::TextComposition(tc.clone())
+                }
+            },
             Event::TextCopy(tc) => {
                 if cx.keyboard.has_key_focus(area) {
                     return Hit::TextCopy(tc.clone());
@@ -680,7 +815,7 @@ impl Event {
                 let digit_id = live_id!(mouse).into();
                 
                 let rect = area.clipped_rect(&cx);
-                if hit_test(e.abs, &rect, &options.margin) {
+                if hit_test(e.window_id, e.abs, &rect, &options.margin) {
                     //fe.handled = true;
                     let device = DigitDevice::Mouse {
                         button: 0,
@@ -707,6 +842,48 @@ impl Event {
                         uid: t.uid,
                     };
                     
+                    if options.multi_touch {
+                        let rect = area.clipped_rect(&cx);
+                        if let Some(gesture) = cx.fingers.gestures.iter_mut().find( | g | g.area == area && g.digits.iter().any( | (d, _) | *d == digit_id)) {
+                            match t.state {
+                                // the finger that stays after a gesture only reports when it goes up
+                                TouchState::Stop if gesture.ended => (),
+                                _ if gesture.ended => continue,
+                                TouchState::Move => {
+                                    let delta = gesture.update(&e.touches);
+                                    return Hit::FingerGesture(gesture.event(e, GesturePhase::Move, delta, rect))
+                                }
+                                TouchState::Stop => {
+                                    gesture.ended = true;
+                                    return Hit::FingerGesture(gesture.event(e, GesturePhase::End, (1.0, 0.0, DVec2::default()), rect))
+                                }
+                                TouchState::Start | TouchState::Stable => continue,
+                            }
+                        }
+                        else if let TouchState::Start = t.state {
+                            // a second finger on an area that holds one turns them into a gesture
+                            let other = cx.fingers.captures.iter().find( | c | c.area == area).and_then( | c | {
+                                e.touches.iter().find( | o | DigitId::from(live_id_num!(touch, o.uid)) == c.digit_id).map( | o | (c.digit_id, o.abs))
+                            });
+                            if let Some(other) = other {
+                                if (options.capture_overload || t.handled.get().is_empty())
+                                    && hit_test(e.window_id, t.abs, &rect, &options.margin) {
+                                    cx.fingers.capture_digit(digit_id, area, options.sweep_area, e.time, t.abs);
+                                    t.handled.set(area);
+                                    let gesture = CxDigitGesture {
+                                        area,
+                                        digits: [other, (digit_id, t.abs)],
+                                        abs_start: (other.1 + t.abs) * 0.5,
+                                        ended: false,
+                                    };
+                                    let event = gesture.event(e, GesturePhase::Start, (1.0, 0.0, DVec2::default()), rect);
+                                    cx.fingers.gestures.push(gesture);
+                                    return Hit::FingerGesture(event)
+                                }
+                            }
+                        }
+                    }
+                    
                     match t.state {
                         TouchState::Start => {
                             
@@ -719,7 +896,7 @@ impl Event {
                             }
                             
                             let rect = area.clipped_rect(&cx);
-                            if !hit_test(t.abs, &rect, &options.margin) {
+                            if !hit_test(e.window_id, t.abs, &rect, &options.margin) {
                                 continue;
                             }
                             
@@ -740,7 +917,7 @@ impl Event {
                         TouchState::Stop => {
                             let tap_count = cx.fingers.tap_count();
                             let rect = area.clipped_rect(&cx);
-                            if let Some(capture) = cx.fingers.find_area_capture(area) {
+                            if let Some(capture) = cx.fingers.find_digit_area_capture(digit_id, area) {
                                 return Hit::FingerUp(FingerUpEvent {
                                     abs_start: capture.abs_start,
                                     rect: rect,
@@ -766,7 +943,7 @@ impl Event {
                             if !options.sweep_area.is_empty() {
                                 if let Some(capture) = cx.fingers.find_digit_capture(digit_id) {
                                     if capture.switch_capture.is_none()
-                                        && hit_test(t.abs, &rect, &options.margin) {
+                                        && hit_test(e.window_id, t.abs, &rect, &options.margin) {
                                         if t.handled.get().is_empty() {
                                             t.handled.set(area);
                                             if capture.area == area {
@@ -819,7 +996,7 @@ impl Event {
                                     }
                                 }
                             }
-                            else if let Some(capture) = cx.fingers.find_area_capture(area) {
+                            else if let Some(capture) = cx.fingers.find_digit_area_capture(digit_id, area) {
                                 return Hit::FingerMove(FingerMoveEvent {
                                     window_id: e.window_id,
                                     abs: t.abs,
@@ -830,7 +1007,7 @@ impl Event {
                                     time: e.time,
                                     abs_start: capture.abs_start,
                                     rect,
-                                    is_over: hit_test(t.abs, &rect, &options.margin),
+                                    is_over: hit_test(e.window_id, t.abs, &rect, &options.margin),
                                 })
                             }
                         }
@@ -857,7 +1034,7 @@ impl Event {
                     if !options.sweep_area.is_empty() {
                         if let Some(capture) = cx.fingers.find_digit_capture(digit_id) {
                             if capture.switch_capture.is_none()
-                                && hit_test(e.abs, &rect, &options.margin) {
+                                && hit_test(e.window_id, e.abs, &rect, &options.margin) {
                                 if e.handled.get().is_empty() {
                                     e.handled.set(area);
                                     if capture.area == area {
@@ -923,7 +1100,7 @@ impl Event {
                             time: e.time,
                             abs_start: capture.abs_start,
                             rect,
-                            is_over: hit_test(e.abs, &rect, &options.margin),
+                            is_over: hit_test(e.window_id, e.abs, &rect, &options.margin),
                         });
                         cx.fingers.new_hover_area(digit_id, area);
                         return event
@@ -947,7 +1124,7 @@ impl Event {
                     };
                     
                     if hover_last == area {
-                        if handled_area.is_empty() && hit_test(e.abs, &rect, &options.margin) {
+                        if handled_area.is_empty() && hit_test(e.window_id, e.abs, &rect, &options.margin) {
                             e.handled.set(area);
                             cx.fingers.new_hover_area(digit_id, area);
                             return Hit::FingerHoverOver(fhe)
@@ -957,7 +1134,7 @@ impl Event {
                         }
                     }
                     else {
-                        if handled_area.is_empty() && hit_test(e.abs, &rect, &options.margin) {
+                        if handled_area.is_empty() && hit_test(e.window_id, e.abs, &rect, &options.margin) {
                             //let any_captured = cx.fingers.get_digit_for_captured_area(area);
                             cx.fingers.new_hover_area(digit_id, area);
                             e.handled.set(area);
@@ -982,7 +1159,7 @@ impl Event {
                 }
                 
                 let rect = area.clipped_rect(&cx);
-                if !hit_test(e.abs, &rect, &options.margin) {
+                if !hit_test(e.window_id, e.abs, &rect, &options.margin) {
                     return Hit::Nothing
                 }
                 
@@ -1026,7 +1203,7 @@ impl Event {
                 let rect = area.clipped_rect(&cx);
                 
                 if let Some(capture) = cx.fingers.find_area_capture(area) {
-                    let is_over = hit_test(e.abs, &rect, &options.margin);
+                    let is_over = hit_test(e.window_id, e.abs, &rect, &options.margin);
                     let event = Hit::FingerUp(FingerUpEvent {
                         abs_start: capture.abs_start,
                         rect: rect,
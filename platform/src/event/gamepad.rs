@@ -0,0 +1,218 @@
+use crate::{
+    cx::Cx,
+    cx_api::CxOsOp,
+    event::Event,
+};
+
+/// Identifies a controller for as long as it stays connected.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct GamepadId(pub u64);
+
+/// The buttons of a controller, in the layout of the W3C standard gamepad. The face
+/// buttons are named after where they sit, so an Xbox A and a PlayStation cross are both `South`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum GamepadButton {
+    South,
+    East,
+    West,
+    North,
+    LeftBumper,
+    RightBumper,
+    LeftTrigger,
+    RightTrigger,
+    Select,
+    Start,
+    LeftStick,
+    RightStick,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+    Home,
+}
+
+impl GamepadButton {
+    pub const ALL: [GamepadButton; 17] = [
+        Self::South,
+        Self::East,
+        Self::West,
+        Self::North,
+        Self::LeftBumper,
+        Self::RightBumper,
+        Self::LeftTrigger,
+        Self::RightTrigger,
+        Self::Select,
+        Self::Start,
+        Self::LeftStick,
+        Self::RightStick,
+        Self::DPadUp,
+        Self::DPadDown,
+        Self::DPadLeft,
+        Self::DPadRight,
+        Self::Home,
+    ];
+
+    /// The index of the button in the standard mapping, and in `GamepadState::buttons`.
+    pub fn index(&self) -> usize {
+        *self as usize
+    }
+}
+
+/// The sticks of a controller, from -1 to 1. Up is negative, like on the web.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum GamepadAxis {
+    LeftX,
+    LeftY,
+    RightX,
+    RightY,
+}
+
+impl GamepadAxis {
+    pub const ALL: [GamepadAxis; 4] = [Self::LeftX, Self::LeftY, Self::RightX, Self::RightY];
+
+    pub fn index(&self) -> usize {
+        *self as usize
+    }
+}
+
+/// A snapshot of a controller. Buttons go from 0 to 1, the triggers are analog.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct GamepadState {
+    pub buttons: [f32; 17],
+    pub axes: [f32; 4],
+}
+
+impl GamepadState {
+    pub fn button(&self, button: GamepadButton) -> f32 {
+        self.buttons[button.index()]
+    }
+
+    pub fn is_pressed(&self, button: GamepadButton) -> bool {
+        self.buttons[button.index()] > 0.5
+    }
+
+    pub fn axis(&self, axis: GamepadAxis) -> f32 {
+        self.axes[axis.index()]
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct GamepadConnectedEvent {
+    pub gamepad_id: GamepadId,
+    pub name: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct GamepadDisconnectedEvent {
+    pub gamepad_id: GamepadId,
+}
+
+#[derive(Clone, Debug)]
+pub struct GamepadButtonEvent {
+    pub gamepad_id: GamepadId,
+    pub button: GamepadButton,
+    pub pressed: bool,
+    pub value: f32,
+    pub time: f64,
+}
+
+#[derive(Clone, Debug)]
+pub struct GamepadAxisEvent {
+    pub gamepad_id: GamepadId,
+    pub axis: GamepadAxis,
+    pub value: f32,
+    pub time: f64,
+}
+
+#[derive(Clone, Debug)]
+pub struct CxGamepad {
+    pub gamepad_id: GamepadId,
+    pub name: String,
+    pub state: GamepadState,
+}
+
+/// The connected controllers. Platforms poll their devices and hand over snapshots,
+/// which get turned into events here so every platform sends the same ones.
+#[derive(Default)]
+pub struct CxGamepads {
+    pub(crate) gamepads: Vec<CxGamepad>,
+}
+
+impl Cx {
+    pub fn gamepads(&self) -> &[CxGamepad] {
+        &self.gamepads.gamepads
+    }
+
+    pub fn gamepad_state(&self, gamepad_id: GamepadId) -> Option<&GamepadState> {
+        self.gamepads.gamepads.iter().find( | g | g.gamepad_id == gamepad_id).map( | g | &g.state)
+    }
+
+    /// Rumbles a controller with the strong (low frequency) and weak (high frequency)
+    /// motors at 0 to 1, for `duration` seconds. Controllers without motors ignore it.
+    pub fn gamepad_rumble(&mut self, gamepad_id: GamepadId, strong: f32, weak: f32, duration: f64) {
+        self.platform_ops.push(CxOsOp::GamepadRumble {
+            gamepad_id,
+            strong: strong.clamp(0.0, 1.0),
+            weak: weak.clamp(0.0, 1.0),
+            duration
+        });
+    }
+
+    #[allow(dead_code)]
+    pub(crate) fn gamepad_connected(&mut self, gamepad_id: GamepadId, name: &str) {
+        if self.gamepads.gamepads.iter().any( | g | g.gamepad_id == gamepad_id) {
+            return
+        }
+        self.gamepads.gamepads.push(CxGamepad {
+            gamepad_id,
+            name: name.to_string(),
+            state: GamepadState::default()
+        });
+        self.call_event_handler(&Event::GamepadConnected(GamepadConnectedEvent {
+            gamepad_id,
+            name: name.to_string()
+        }));
+    }
+
+    #[allow(dead_code)]
+    pub(crate) fn gamepad_disconnected(&mut self, gamepad_id: GamepadId) {
+        if let Some(index) = self.gamepads.gamepads.iter().position( | g | g.gamepad_id == gamepad_id) {
+            self.gamepads.gamepads.remove(index);
+            self.call_event_handler(&Event::GamepadDisconnected(GamepadDisconnectedEvent {gamepad_id}));
+        }
+    }
+
+    /// Sends an event for every button and axis that changed since the last snapshot.
+    #[allow(dead_code)]
+    pub(crate) fn gamepad_update(&mut self, gamepad_id: GamepadId, state: GamepadState, time: f64) {
+        let old = if let Some(gamepad) = self.gamepads.gamepads.iter_mut().find( | g | g.gamepad_id == gamepad_id) {
+            std::mem::replace(&mut gamepad.state, state)
+        }
+        else {
+            return
+        };
+        for button in GamepadButton::ALL {
+            let value = state.button(button);
+            if value != old.button(button) {
+                self.call_event_handler(&Event::GamepadButton(GamepadButtonEvent {
+                    gamepad_id,
+                    button,
+                    pressed: value > 0.5,
+                    value,
+                    time
+                }));
+            }
+        }
+        for axis in GamepadAxis::ALL {
+            let value = state.axis(axis);
+            if value != old.axis(axis) {
+                self.call_event_handler(&Event::GamepadAxis(GamepadAxisEvent {
+                    gamepad_id,
+                    axis,
+                    value,
+                    time
+                }));
+            }
+        }
+    }
+}
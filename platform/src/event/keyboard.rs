@@ -128,9 +128,55 @@ pub struct TextInputEvent {
     pub was_paste: bool
 }
 
+/// Where an input method composition is at. While composing, the preedit text is shown inline
+/// at the cursor without being part of the document; on `Commit` it goes away again and the
+/// composed text arrives as a regular `TextInputEvent`.
+#[derive(Clone, Copy, Debug, Default, SerBin, DeBin, SerJson, DeJson, PartialEq)]
+pub enum TextCompositionPhase {
+    #[default] Start,
+    Update,
+    Commit,
+}
+
+/// A byte range of the preedit text to underline. The clause the input method is currently
+/// converting is drawn thick.
+#[derive(Clone, Debug, Default, SerBin, DeBin, SerJson, DeJson, PartialEq)]
+pub struct TextCompositionUnderline {
+    pub start: usize,
+    pub end: usize,
+    pub thick: bool,
+}
+
+#[derive(Clone, Debug, Default, SerBin, DeBin, SerJson, DeJson, PartialEq)]
+pub struct TextCompositionEvent {
+    pub phase: TextCompositionPhase,
+    pub preedit: String,
+    pub underlines: Vec<TextCompositionUnderline>,
+    /// The cursor inside the preedit text, as a byte index.
+    pub cursor: usize,
+}
+
+impl TextCompositionEvent {
+    pub fn commit() -> Self {
+        Self {
+            phase: TextCompositionPhase::Commit,
+            ..Default::default()
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct TextClipboardEvent {
-    pub response: Rc<RefCell<Option<String>>>
+    pub response: Rc<RefCell<Option<String>>>,
+    pub rich_response: Rc<RefCell<Option<RichClipboardText>>>
+}
+
+/// Styled flavors of a text copy. Platforms that support it put these on the clipboard
+/// next to the plain text response, others ignore them.
+#[derive(Clone, Debug, Default)]
+pub struct RichClipboardText {
+    pub html: Option<String>,
+    pub rtf: Option<String>,
 }
 
 impl Default for KeyCode {
@@ -139,7 +185,7 @@ impl Default for KeyCode {
 
 
 // lowest common denominator keymap between desktop and web
-#[derive(Live, LiveHook, Clone, Copy, Debug, SerBin, DeBin, SerJson, DeJson, PartialEq)]
+#[derive(Live, LiveHook, Clone, Copy, Debug, SerBin, DeBin, SerJson, DeJson, Eq, Hash, PartialEq)]
 pub enum KeyCode {
     #[pick] Escape,
 
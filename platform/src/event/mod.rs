@@ -6,6 +6,9 @@ pub mod xr;
 pub mod drag_drop;
 pub mod network;
 pub mod video_playback;
+pub mod read_back;
+pub mod clipboard;
+pub mod gamepad;
 
 pub use event::*;
 pub use finger::*;
@@ -15,3 +18,6 @@ pub use xr::*;
 pub use drag_drop::*;
 pub use network::*;
 pub use video_playback::*;
+pub use read_back::*;
+pub use clipboard::*;
+pub use gamepad::*;
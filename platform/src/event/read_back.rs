@@ -0,0 +1,41 @@
+use crate::{
+    makepad_math::{DVec2, Rect},
+    pass::PassId,
+};
+
+/// The pixels read back from a pass, as requested with `Pass::request_read_back`.
+#[derive(Clone, Debug)]
+pub struct PassReadBackEvent {
+    pub pass_id: PassId,
+    /// The requested rect, in logical pass coordinates.
+    pub rect: Rect,
+    /// The size of the read back region in physical pixels.
+    pub width: usize,
+    pub height: usize,
+    /// Rows from the top down, in the same BGRA layout as `TextureFormat::VecBGRAu8_32`.
+    pub pixels: Vec<u32>,
+}
+
+impl PassReadBackEvent {
+    /// The pixel at a logical position inside the requested rect.
+    pub fn pixel_at(&self, pos: DVec2) -> Option<u32> {
+        if self.width == 0 || self.height == 0 || self.rect.size.x <= 0.0 || self.rect.size.y <= 0.0 {
+            return None
+        }
+        let x = ((pos.x - self.rect.pos.x) / self.rect.size.x * self.width as f64).floor();
+        let y = ((pos.y - self.rect.pos.y) / self.rect.size.y * self.height as f64).floor();
+        if x < 0.0 || y < 0.0 || x >= self.width as f64 || y >= self.height as f64 {
+            return None
+        }
+        self.pixels.get(y as usize * self.width + x as usize).cloned()
+    }
+    
+    /// The pixels as tightly packed RGBA bytes, rows from the top down.
+    pub fn to_rgba(&self) -> Vec<u8> {
+        let mut rgba = Vec::with_capacity(self.pixels.len() * 4);
+        for pixel in &self.pixels {
+            rgba.extend_from_slice(&[(pixel >> 16) as u8, (pixel >> 8) as u8, *pixel as u8, (pixel >> 24) as u8]);
+        }
+        rgba
+    }
+}
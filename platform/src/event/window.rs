@@ -28,6 +28,13 @@ pub struct WindowGeomChangeEvent {
     pub new_geom: WindowGeom,
 }
 
+#[derive(Clone, Debug)]
+pub struct DpiChangedEvent {
+    pub window_id: WindowId,
+    pub old_dpi_factor: f64,
+    pub new_dpi_factor: f64,
+}
+
 #[derive(Clone, Debug)]
 pub struct WindowMovedEvent {
     pub window_id: WindowId,
@@ -41,6 +48,11 @@ pub struct WindowCloseRequestedEvent {
     pub accept_close: Rc<Cell<bool>>
 }
 
+#[derive(Clone, Debug)]
+pub struct AppQuitRequestedEvent {
+    pub accept_quit: Rc<Cell<bool>>
+}
+
 #[derive(Clone, Debug)]
 pub struct WindowClosedEvent {
     pub window_id: WindowId
@@ -0,0 +1,210 @@
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+use crate::makepad_math::DVec2;
+use crate::cx::Cx;
+use crate::cx_api::CxOsApi;
+use crate::window::WindowId;
+use crate::event::Event;
+use crate::os::cx_stdin::*;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EventTraceKind {
+    Pointer,
+    Keyboard,
+    Action,
+    Other,
+}
+
+#[derive(Clone, Debug)]
+pub struct EventTraceEntry {
+    pub event_id: u64,
+    /// Seconds since the start of the app.
+    pub time: f64,
+    pub kind: EventTraceKind,
+    pub name: String,
+    /// Where a pointer event happened, relative to its window.
+    pub abs: Option<DVec2>,
+    pub detail: String,
+    /// The stdin message that plays the event again, for input events.
+    pub replay: Option<HostToStdin>,
+}
+
+#[derive(Default)]
+pub struct EventTraceInner {
+    pub enabled: bool,
+    pub capacity: usize,
+    pub entries: VecDeque<EventTraceEntry>,
+    pub generation: u64,
+}
+
+/// Records the events the app gets and the actions its widgets send, for a trace panel.
+/// Draw and NextFrame events aren't recorded, they'd push everything else out.
+#[derive(Clone, Default)]
+pub struct EventTrace(Rc<RefCell<EventTraceInner >>);
+
+impl EventTrace {
+    pub fn is_enabled(&self) -> bool {
+        self.0.borrow().enabled
+    }
+
+    pub fn set_enabled(&self, enabled: bool) {
+        let mut inner = self.0.borrow_mut();
+        inner.enabled = enabled;
+        if inner.capacity == 0 {
+            inner.capacity = 2000;
+        }
+    }
+
+    /// How many entries are kept, older ones are dropped.
+    pub fn set_capacity(&self, capacity: usize) {
+        let mut inner = self.0.borrow_mut();
+        inner.capacity = capacity;
+        while inner.entries.len() > capacity {
+            inner.entries.pop_front();
+        }
+    }
+
+    pub fn clear(&self) {
+        let mut inner = self.0.borrow_mut();
+        inner.entries.clear();
+        inner.generation += 1;
+    }
+
+    pub fn entries(&self) -> Vec<EventTraceEntry> {
+        self.0.borrow().entries.iter().cloned().collect()
+    }
+
+    /// Goes up with every change to the entries, so a panel can tell when to redraw.
+    pub fn generation(&self) -> u64 {
+        self.0.borrow().generation
+    }
+
+    fn push(&self, entry: EventTraceEntry) {
+        let mut inner = self.0.borrow_mut();
+        if inner.capacity == 0 {
+            return
+        }
+        if inner.entries.len() >= inner.capacity {
+            inner.entries.pop_front();
+        }
+        inner.entries.push_back(entry);
+        inner.generation += 1;
+    }
+
+    pub (crate) fn record(&self, cx: &Cx, event: &Event) {
+        let event_id = cx.event_id;
+        let time = cx.seconds_since_app_start();
+        let entry = | kind, abs, detail, replay | EventTraceEntry {
+            event_id,
+            time,
+            kind,
+            name: event.name().to_string(),
+            abs,
+            detail,
+            replay
+        };
+        // the stdin protocol has positions relative to the screen
+        let window_pos = | window_id: WindowId | cx.windows[window_id].window_geom.position;
+        match event {
+            Event::Draw(_) | Event::NextFrame(_) => (),
+            Event::Actions(actions) => for action in actions {
+                let mut detail = format!("{:?}", action);
+                if detail.len() > 300 {
+                    let mut end = 300;
+                    while !detail.is_char_boundary(end) {
+                        end -= 1;
+                    }
+                    detail.truncate(end);
+                    detail.push_str("..");
+                }
+                self.push(EventTraceEntry {
+                    event_id,
+                    time,
+                    kind: EventTraceKind::Action,
+                    name: "Action".to_string(),
+                    abs: None,
+                    detail,
+                    replay: None,
+                });
+            }
+            Event::MouseDown(e) => {
+                let pos = e.abs + window_pos(e.window_id);
+                self.push(entry(EventTraceKind::Pointer, Some(e.abs), format!("button {}", e.button), Some(HostToStdin::MouseDown(StdinMouseDown {
+                    button: e.button,
+                    x: pos.x,
+                    y: pos.y,
+                    time: e.time,
+                    modifiers: StdinKeyModifiers::from_key_modifiers(&e.modifiers)
+                }))));
+            }
+            Event::MouseUp(e) => {
+                let pos = e.abs + window_pos(e.window_id);
+                self.push(entry(EventTraceKind::Pointer, Some(e.abs), format!("button {}", e.button), Some(HostToStdin::MouseUp(StdinMouseUp {
+                    time: e.time,
+                    button: e.button,
+                    x: pos.x,
+                    y: pos.y,
+                    modifiers: StdinKeyModifiers::from_key_modifiers(&e.modifiers)
+                }))));
+            }
+            Event::MouseMove(e) => {
+                let pos = e.abs + window_pos(e.window_id);
+                self.push(entry(EventTraceKind::Pointer, Some(e.abs), String::new(), Some(HostToStdin::MouseMove(StdinMouseMove {
+                    time: e.time,
+                    x: pos.x,
+                    y: pos.y,
+                    modifiers: StdinKeyModifiers::from_key_modifiers(&e.modifiers)
+                }))));
+            }
+            Event::Scroll(e) => {
+                let pos = e.abs + window_pos(e.window_id);
+                self.push(entry(EventTraceKind::Pointer, Some(e.abs), format!("by {:.1},{:.1}", e.scroll.x, e.scroll.y), Some(HostToStdin::Scroll(StdinScroll {
+                    time: e.time,
+                    sx: e.scroll.x,
+                    sy: e.scroll.y,
+                    x: pos.x,
+                    y: pos.y,
+                    is_mouse: e.is_mouse,
+                    modifiers: StdinKeyModifiers::from_key_modifiers(&e.modifiers)
+                }))));
+            }
+            Event::MouseLeave(e) => {
+                self.push(entry(EventTraceKind::Pointer, Some(e.abs), String::new(), None));
+            }
+            Event::TouchUpdate(e) => {
+                let abs = e.touches.first().map( | touch | touch.abs);
+                self.push(entry(EventTraceKind::Pointer, abs, format!("{} touches", e.touches.len()), None));
+            }
+            Event::KeyDown(e) => {
+                self.push(entry(EventTraceKind::Keyboard, None, format!("{:?}", e.key_code), Some(HostToStdin::KeyDown(e.clone()))));
+            }
+            Event::KeyUp(e) => {
+                self.push(entry(EventTraceKind::Keyboard, None, format!("{:?}", e.key_code), Some(HostToStdin::KeyUp(e.clone()))));
+            }
+            Event::TextInput(e) => {
+                self.push(entry(EventTraceKind::Keyboard, None, format!("{:?}", e.input), Some(HostToStdin::TextInput(e.clone()))));
+            }
+            Event::TextComposition(e) => {
+                self.push(entry(EventTraceKind::Keyboard, None, format!("{:?} {:?}", e.phase, e.preedit), Some(HostToStdin::TextComposition(e.clone()))));
+            }
+            Event::KeyFocus(_) | Event::KeyFocusLost(_) | Event::TextCopy(_) | Event::TextCut(_) => {
+                self.push(entry(EventTraceKind::Keyboard, None, String::new(), None));
+            }
+            _ => {
+                self.push(entry(EventTraceKind::Other, None, String::new(), None));
+            }
+        }
+    }
+}
+
+/// The input events of `entries` as stdin messages, one JSON object per line. Fed to the
+/// stdin of an app running under the studio's stdin loop they play the same input again.
+pub fn event_trace_replay(entries: &[EventTraceEntry]) -> String {
+    let mut out = String::new();
+    for replay in entries.iter().filter_map( | entry | entry.replay.as_ref()) {
+        out.push_str(&replay.to_json());
+        out.push('\n');
+    }
+    out
+}
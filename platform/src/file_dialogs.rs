@@ -1,9 +1,15 @@
 // mildly stripped down version of native_dialog_rs dialog interface.
-use std::path::{PathBuf};
+use {
+    std::{
+        path::{PathBuf},
+        sync::mpsc::{channel, Sender, Receiver},
+    },
+    crate::event::DialogEvent,
+};
 
 
 /// Represents a set of file extensions and their description.
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Filter {
     pub description: String,
     pub extensions: Vec<String>,
@@ -11,12 +17,13 @@ pub struct Filter {
 
 /// Builds and shows file dialogs.
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct FileDialog {
     pub filename: Option<String>,
     pub location: Option<PathBuf>,
     pub filters: Vec<Filter>,
     pub title: Option<String>,
+    pub multiple: bool,
 }
 
 impl FileDialog {
@@ -27,6 +34,7 @@ impl FileDialog {
             location: None,
             filters: vec![],           
             title: None,
+            multiple: false,
         }
     }
 
@@ -81,15 +89,112 @@ impl FileDialog {
         self
     }
 
+    /// Lets an open dialog pick more than one file. Save and folder dialogs ignore this.
+    pub fn set_multiple(mut self, multiple: bool) -> Self {
+        self.multiple = multiple;
+        self
+    }
+}
+
+impl Default for FileDialog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum MessageDialogLevel {
+    #[default]
+    Info,
+    Warning,
+    Error,
+}
 
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum MessageDialogButtons {
+    #[default]
+    Ok,
+    OkCancel,
+    YesNo,
 }
 
+/// Builds a dialog that shows a message and waits for a button to be pressed.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MessageDialog {
+    pub title: Option<String>,
+    pub message: String,
+    pub level: MessageDialogLevel,
+    pub buttons: MessageDialogButtons,
+}
 
+impl MessageDialog {
+    pub fn new(message: String) -> Self {
+        MessageDialog {
+            message,
+            ..Default::default()
+        }
+    }
 
-impl Default for FileDialog {
+    /// Sets the window title for the dialog.
+    pub fn set_title(mut self, title: String) -> Self {
+        self.title = Some(title);
+        self
+    }
+
+    /// Sets the icon of the dialog, platforms without icons ignore this.
+    pub fn set_level(mut self, level: MessageDialogLevel) -> Self {
+        self.level = level;
+        self
+    }
+
+    pub fn set_buttons(mut self, buttons: MessageDialogButtons) -> Self {
+        self.buttons = buttons;
+        self
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Dialog {
+    OpenFile(FileDialog),
+    SaveFile(FileDialog),
+    SelectFolder(FileDialog),
+    Message(MessageDialog),
+}
+
+impl Dialog {
+    pub fn title(&self) -> &str {
+        match self {
+            Self::OpenFile(d) => d.title.as_deref().unwrap_or("Open File"),
+            Self::SaveFile(d) => d.title.as_deref().unwrap_or("Save File"),
+            Self::SelectFolder(d) => d.title.as_deref().unwrap_or("Select Folder"),
+            Self::Message(d) => d.title.as_deref().unwrap_or(""),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum DialogResult {
+    /// The picked paths, only an open dialog with `set_multiple` gives more than one.
+    Paths(Vec<PathBuf>),
+    /// Ok or Yes was pressed in a message dialog.
+    Accepted,
+    /// Cancel or No was pressed, or the dialog was closed.
+    Cancelled,
+}
+
+/// Dialogs finish on their own time, often on another thread, their events come back through here.
+pub(crate) struct DialogEventChannel {
+    pub receiver: Receiver<DialogEvent>,
+    pub sender: Sender<DialogEvent>,
+}
+
+impl Default for DialogEventChannel {
     fn default() -> Self {
-        Self::new()
+        let (sender, receiver) = channel();
+        Self {
+            sender,
+            receiver
+        }
     }
 }
 
@@ -19,6 +19,7 @@ pub mod live_atomic;
 
 pub mod thread;
 pub mod audio;
+pub mod audio_node_graph;
 pub mod midi;
 pub mod video;
 pub mod scope;
@@ -34,14 +35,18 @@ mod area;
 mod window;
 mod pass;
 mod texture;
+mod color_space;
 mod cursor;
 mod macos_menu;
 mod animator;
 mod gpu_info;
 mod geometry;
 mod debug;
+mod event_trace;
+mod accessibility;
 mod component_map;
 mod component_list;
+mod component_pool;
 mod performance_stats;
 pub mod studio;
 
@@ -120,6 +125,7 @@ pub use {
     },
     component_map::ComponentMap,
     component_list::ComponentList,
+    component_pool::{ComponentPool, Recycle},
     makepad_shader_compiler::{
         ShaderRegistry,
         ShaderEnum,
@@ -151,9 +157,11 @@ pub use {
         },
         midi::*,
         audio::*,
+        audio_node_graph::*,
         thread::*,
         video::*,
         web_socket::{WebSocket,WebSocketMessage},
+        file_dialogs::{FileDialog, MessageDialog, MessageDialogLevel, MessageDialogButtons, Dialog, DialogResult},
         event::{
             VirtualKeyboardEvent,
             HttpRequest,
@@ -161,6 +169,8 @@ pub use {
             HttpMethod,
             NetworkResponse,
             NetworkResponsesEvent,
+            WebSocketEvent,
+            DialogEvent,
             Margin,
             KeyCode,
             Event,
@@ -176,22 +186,44 @@ pub use {
             MouseDownEvent,
             MouseMoveEvent,
             MouseUpEvent,
+            NavigateEvent,
+            NavigateDirection,
             FingerDownEvent,
             FingerMoveEvent,
             FingerUpEvent,
             HoverState,
             FingerHoverEvent,
             FingerScrollEvent,
+            FingerGestureEvent,
+            GesturePhase,
             WindowGeomChangeEvent,
+            DpiChangedEvent,
             WindowMovedEvent,
             NextFrameEvent,
             TimerEvent,
             KeyEvent,
             KeyFocusEvent,
             TextInputEvent,
+            TextCompositionEvent,
+            TextCompositionPhase,
+            TextCompositionUnderline,
             TextClipboardEvent,
+            RichClipboardText,
+            ClipboardFlavor,
+            ClipboardReadEvent,
+            GamepadId,
+            GamepadButton,
+            GamepadAxis,
+            GamepadState,
+            CxGamepad,
+            GamepadConnectedEvent,
+            GamepadDisconnectedEvent,
+            GamepadButtonEvent,
+            GamepadAxisEvent,
             WindowCloseRequestedEvent,
             WindowClosedEvent,
+            AppQuitRequestedEvent,
+            PassReadBackEvent,
             WindowDragQueryResponse,
             WindowDragQueryEvent,
             XRButton,
@@ -213,10 +245,24 @@ pub use {
             ActionCast,
             ActionTrait
         },
+        event_trace::{
+            EventTrace,
+            EventTraceKind,
+            EventTraceEntry,
+            event_trace_replay,
+        },
+        accessibility::{
+            AccessRole,
+            AccessState,
+            AccessNode,
+            AccessElement,
+            AccessNotification,
+        },
         cursor::MouseCursor,
+        color_space::ColorSpace,
         macos_menu::MacosMenu,
         draw_matrix::DrawMatrix,
-        window::WindowHandle,
+        window::{WindowHandle, WindowId},
         pass::{
             PassId,
             CxPassParent,
@@ -248,6 +294,7 @@ pub use {
             ToLiveValue,
             Apply,
             ApplyFrom,
+            ApplyTracking,
         },
         animator::{
             Ease,
@@ -4,6 +4,7 @@ use {
         makepad_live_tokenizer::{LiveErrorOrigin, live_error_origin},
         makepad_live_compiler::{
             LiveFileChange,
+            LiveFileWatcher,
             TextPos,
             LiveValue,
             LiveNode,
@@ -186,44 +187,41 @@ impl Cx {
         }
     }
     
+    /// Polls the source files of the live documents every `milis` on a thread of its own, and
+    /// hands edits to `handle_live_edit` so the app restyles without a restart.
     pub fn start_disk_live_file_watcher(&mut self, milis:u64){
-        let live_registry = self.live_registry.borrow();
-        
-        let mut file_list:Vec<(String,String, Option<String>)> = Vec::new();
-        for file in &live_registry.live_files {
-            if let Some(start) = file.file_name.find("src/"){
-                let path = format!("{}/{}", file.cargo_manifest_path, &file.file_name[start..]);
-                file_list.push((path, file.file_name.clone(), None));
-            }
+        let mut watcher = LiveFileWatcher::new(&self.live_registry.borrow());
+        if watcher.is_empty(){
+            return
         }
         let send = self.live_file_change_sender.clone();
         std::thread::spawn(move || loop{
-            let mut changed_files = Vec::new();
-            for (full_path, file_name, content) in &mut file_list{
-                let next = std::fs::read_to_string(&full_path);
-                if let Ok(next) = next{
-                    if let Some(content_str) = content{
-                        if content_str != &next{
-                            crate::log!("Live reloading application: {}",file_name.clone());
-                            changed_files.push(LiveFileChange{
-                                file_name:file_name.clone(), 
-                                content: next.clone()
-                            });
-                            *content = Some(next);
-                        }
-                    }
-                    else{
-                        *content = Some(next);
-                    }
-                }
-            }
+            let changed_files = watcher.poll();
             if changed_files.len()>0{
-                send.send(changed_files).unwrap();
+                for change in &changed_files{
+                    crate::log!("Live reloading application: {}", change.file_name);
+                }
+                if send.send(changed_files).is_err(){
+                    return
+                }
             }
             std::thread::sleep(std::time::Duration::from_millis(milis));
         });
     }
     
+    /// Switches the theme that imports of theme modules resolve to, see
+    /// `LiveRegistry::register_live_theme`. Like a live edit, the documents are expanded again
+    /// and the app gets an `Event::LiveEdit` to apply them, which reaches every `DrawVars`.
+    /// Shaders whose code didn't change are reused instead of compiled again.
+    pub fn set_live_theme(&mut self, theme: LiveId){
+        if self.live_registry.borrow().live_theme() != theme{
+            self.live_theme_pending = Some(theme);
+        }
+        else{
+            self.live_theme_pending = None;
+        }
+    }
+    
     pub fn handle_live_edit(&mut self)->bool{
         // lets poll our studio connection
         let mut all_changes:Vec<LiveFileChange> = Vec::new();
@@ -255,10 +253,16 @@ impl Cx {
         while let Ok(changes) = self.live_file_change_receiver.try_recv(){
             all_changes.extend(changes);
         }
-        if all_changes.len()>0{
+        let live_theme = self.live_theme_pending.take();
+        if all_changes.len()>0 || live_theme.is_some(){
             let mut live_registry = self.live_registry.borrow_mut();
             let mut errs = Vec::new();
-            live_registry.process_file_changes(all_changes, &mut errs);
+            if let Some(live_theme) = live_theme{
+                live_registry.set_live_theme(live_theme, &mut errs);
+            }
+            if all_changes.len()>0{
+                live_registry.process_file_changes(all_changes, &mut errs);
+            }
             for err in errs {
                 
                 // alright we need to output the correct error
@@ -16,9 +16,14 @@ macro_rules!get_component {
     }
 }
  
+/// Applies a primitive, without telling whether its value changed.
+pub trait LiveApplyPrimitive {
+    fn apply_primitive(&mut self, cx: &mut Cx, apply: &mut Apply, index: usize, nodes: &[LiveNode]) -> usize;
+}
+
 #[macro_export]
 macro_rules!live_primitive {
-    ( $ ty: ty, $ default: expr, $ apply: item, $ to_live_value: item) => {
+    ( $ ty: ty, $ default: expr, fn apply $ params: tt -> usize $ apply: block, $ to_live_value: item) => {
         impl LiveHook for $ ty {}
         impl ToLiveValue for $ ty {
             $ to_live_value
@@ -28,11 +33,21 @@ macro_rules!live_primitive {
                 out.push(LiveNode::from_id_value(id, self.to_live_value()));
             } 
         }
+        impl LiveApplyPrimitive for $ ty {
+            fn apply_primitive $ params -> usize $ apply
+        }
         impl LiveApply for $ ty {
             //fn type_id(&self) -> TypeId {
             //    TypeId::of::< $ ty>()
             // }
-            $ apply
+            fn apply(&mut self, cx: &mut Cx, apply: &mut Apply, index: usize, nodes: &[LiveNode]) -> usize {
+                let old = self.clone();
+                let index = self.apply_primitive(cx, apply, index, nodes);
+                if *self != old {
+                    apply.mark_changed();
+                }
+                index
+            }
         }
         impl LiveNew for $ ty {
             fn live_design_with(_cx:&mut Cx){}
@@ -850,11 +865,18 @@ live_primitive!(
     }
 );
 
+#[derive(Clone)]
 pub enum RcStringMut{
     Rc(Rc<String>),
     String(String)
 }
 
+impl PartialEq for RcStringMut{
+    fn eq(&self, other: &Self) -> bool {
+        self.as_ref() == other.as_ref()
+    }
+}
+
 impl Default for RcStringMut{
     fn default()->Self{Self::String(String::new())}
 }
@@ -1029,10 +1051,11 @@ impl LiveIdToEnum for &[LiveId;1]{
     }
 }*/
 
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, PartialEq)]
 pub struct LiveDependency(Rc<String>);
 
 impl LiveDependency{
+    pub fn new(path:&str)->Self{Self(Rc::new(path.to_string()))}
     pub fn as_str(&self)->&str{&self.0}
     pub fn as_ref(&self)->&Rc<String>{&self.0}
 }
@@ -193,13 +193,71 @@ impl<T, const N:usize> LiveRead for [T;N]  where T: LiveRead {
 pub struct Apply<'a,'b,'c> {
     pub from: ApplyFrom,
     pub scope: Option<&'c mut Scope<'a,'b>>,
+    /// Whether a value of the object being applied, or of its children, changed.
+    value_changed: bool,
+    /// The fields of the object being applied whose value changed.
+    changed_fields: Vec<LiveId>,
+}
+
+/// The change tracking state of the object an apply is nested in, see `Apply::begin_tracking`.
+pub struct ApplyTracking {
+    value_changed: bool,
+    changed_fields: Vec<LiveId>,
+}
+
+impl<'a,'b,'c> Apply<'a,'b,'c> {
+    /// Whether applying changed the value of `field` of the object being applied. Meant for
+    /// `after_apply` and `after_apply_from`, to skip work that only depends on other fields.
+    /// Fields that `apply_reset` puts back to their default don't count.
+    pub fn changed(&self, field: LiveId) -> bool {
+        self.changed_fields.contains(&field)
+    }
+    
+    /// Whether applying changed any value of the object being applied so far, including the
+    /// values of its children. Re-applying the same values doesn't.
+    pub fn any_changed(&self) -> bool {
+        self.value_changed
+    }
+    
+    /// Marks a value as changed, for apply implementations that replace a value without being
+    /// able to compare it, like creating a component.
+    pub fn mark_changed(&mut self) {
+        self.value_changed = true;
+    }
+    
+    /// Starts tracking the changes of an object, returning those of the object it is nested in.
+    pub fn begin_tracking(&mut self) -> ApplyTracking {
+        ApplyTracking {
+            value_changed: std::mem::replace(&mut self.value_changed, false),
+            changed_fields: std::mem::take(&mut self.changed_fields),
+        }
+    }
+    
+    /// Goes back to tracking the object `outer` came from, which changed if this one did.
+    pub fn end_tracking(&mut self, outer: ApplyTracking) {
+        self.changed_fields = outer.changed_fields;
+        self.value_changed |= outer.value_changed;
+    }
+    
+    /// Applies a field with `apply_field`, and records the field as changed if its value did.
+    pub fn track_field(&mut self, field: LiveId, apply_field: impl FnOnce(&mut Self) -> usize) -> usize {
+        let outer_changed = std::mem::replace(&mut self.value_changed, false);
+        let index = apply_field(self);
+        if self.value_changed && !self.changed_fields.contains(&field) {
+            self.changed_fields.push(field);
+        }
+        self.value_changed |= outer_changed;
+        index
+    }
 }
 
 impl ApplyFrom{
     fn with_scope<'a, 'b, 'c>(self, scope:&'c mut Scope<'a,'b>)->Apply<'a, 'b, 'c>{
         Apply{
             from: self,
-            scope: Some(scope)
+            scope: Some(scope),
+            value_changed: false,
+            changed_fields: Vec::new(),
         }
     }
 }
@@ -220,6 +278,8 @@ impl<'a,'b, 'c> From<ApplyFrom> for Apply<'a,'b,'c> {
         Self {
             from,
             scope: None,
+            value_changed: false,
+            changed_fields: Vec::new(),
         }
     }
 }
@@ -284,6 +344,7 @@ impl<T> LiveApply for Option<T> where T: LiveApply + LiveNew + 'static {
             let mut inner = T::new(cx);
             let index = inner.apply(cx, apply, index, nodes);
             *self = Some(inner);
+            apply.mark_changed();
             index
         }
     }
@@ -309,6 +370,10 @@ impl<T> LiveHook for Vec<T> where T: LiveApply + LiveNew + 'static {}
 impl<T> LiveApply for Vec<T> where T: LiveApply + LiveNew + 'static {
     fn apply(&mut self, cx: &mut Cx, apply: &mut Apply, index: usize, nodes: &[LiveNode]) -> usize {
         // we can only apply from an Array
+        // the items are made anew, so they can't tell if they changed
+        if !self.is_empty() || nodes[index].is_array() && !nodes[index + 1].is_close() {
+            apply.mark_changed();
+        }
         self.clear();
         if nodes[index].is_array(){
             let mut index = index + 1;
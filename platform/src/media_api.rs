@@ -1,5 +1,6 @@
 use crate::{
     audio::{AudioDeviceId, AudioInfo, AudioBuffer,AudioInputFn,AudioOutputFn},
+    audio_node_graph::AudioNodeGraph,
     video::*,
     midi::*,
 };
@@ -22,6 +23,11 @@ pub trait CxMediaApi {
         self.audio_input_box(index, Box::new(f))
     }
     
+    /// Renders the graph into an output, the graph moves to the audio thread.
+    fn audio_output_graph(&mut self, index:usize, mut graph: AudioNodeGraph){
+        self.audio_output(index, move | info, output | graph.process(info, output))
+    }
+    
     fn audio_output_box(&mut self, index:usize, f: AudioOutputFn);
     fn audio_input_box(&mut self, index:usize, f: AudioInputFn);
 
@@ -1,7 +1,12 @@
 use {
+    std::sync::OnceLock,
+    std::time::Instant,
     crate::{
         os::{OsMidiOutput,OsMidiInput},
         makepad_live_id::{LiveId, FromLiveId},
+        cx::Cx,
+        event::Event,
+        media_api::CxMediaApi,
     }
 };
 
@@ -52,16 +57,58 @@ impl std::fmt::Debug for MidiPortDesc {
     }
 }
 
+/// A message from one of the inputs passed to `use_midi_inputs`.
+#[derive(Clone, Copy, Debug)]
+pub struct MidiInputEvent {
+    pub port_id: MidiPortId,
+    pub data: MidiData,
+    /// When the message came in, in seconds. The clock is only good for comparing
+    /// MIDI messages with each other, it doesn't start where the event times do.
+    pub time: f64,
+}
+
+/// The clock of `MidiInputEvent::time` on the platforms that have to stamp the messages themselves.
+#[allow(dead_code)]
+pub(crate) fn midi_time() -> f64 {
+    static START: OnceLock<Instant> = OnceLock::new();
+    START.get_or_init(Instant::now).elapsed().as_secs_f64()
+}
+
 #[derive(Default)]
 pub struct MidiInput(pub (crate) Option<OsMidiInput>);
 unsafe impl Send for MidiInput {}
 
 impl MidiInput {
     pub fn receive(&mut self) -> Option<(MidiPortId, MidiData)> {
+        self.receive_event().map( | e | (e.port_id, e.data))
+    }
+    
+    pub fn receive_event(&mut self) -> Option<MidiInputEvent> {
         self.0.as_mut().unwrap().receive()
     }
 }
 
+impl Cx {
+    /// From the first `use_midi_inputs` on, the messages also come in as `Event::MidiInput`.
+    pub(crate) fn enable_midi_input_events(&mut self) {
+        if self.midi_input_events.is_none() {
+            self.midi_input_events = Some(self.midi_input());
+        }
+    }
+    
+    pub(crate) fn handle_midi_input_events(&mut self) {
+        let mut events = Vec::new();
+        if let Some(input) = &mut self.midi_input_events {
+            while let Some(e) = input.receive_event() {
+                events.push(e);
+            }
+        }
+        for e in events {
+            self.call_event_handler(&Event::MidiInput(e));
+        }
+    }
+}
+
 #[derive(Default)]
 pub struct MidiOutput(pub (crate) Option<OsMidiOutput>);
 unsafe impl Send for MidiOutput {}
@@ -45,6 +45,7 @@ impl Cx{
                 descs
             }));
         }
+        self.handle_midi_input_events();
     }
     
 }
@@ -90,6 +91,7 @@ impl CxMediaApi for Cx {
     
     fn use_midi_inputs(&mut self, ports: &[MidiPortId]) {
         self.os.media.core_midi().lock().unwrap().use_midi_inputs(ports);
+        self.enable_midi_input_events();
     }
     
     fn use_midi_outputs(&mut self, ports: &[MidiPortId]) {
@@ -147,6 +147,14 @@ extern {
     pub static NSPasteboardTypeFileURL: ObjcId;
     pub static NSPasteboardTypeURL: ObjcId;
     pub static NSPasteboardTypeString: ObjcId;
+    pub fn NSAccessibilityPostNotification(element: ObjcId, notification: ObjcId);
+}
+
+#[cfg(target_os = "macos")]
+#[link(name = "GameController", kind = "framework")]
+extern {
+    pub static GCControllerDidConnectNotification: ObjcId;
+    pub static GCControllerDidDisconnectNotification: ObjcId;
 }
 
 #[link(name = "Vision", kind = "framework")]
@@ -174,6 +182,11 @@ extern "C" {
     pub fn CGMainDisplayID() -> u32;
     pub fn CGDisplayPixelsHigh(display: u32) -> u64;
     pub fn CGColorCreateGenericRGB(red: f64, green: f64, blue: f64, alpha: f64) -> ObjcId;
+    pub fn CGColorSpaceCreateWithName(name: ObjcId) -> ObjcId;
+    pub fn CGColorSpaceRelease(space: ObjcId);
+    pub static kCGColorSpaceSRGB: ObjcId;
+    pub static kCGColorSpaceDisplayP3: ObjcId;
+    pub static kCGColorSpaceLinearSRGB: ObjcId;
 }
 
 #[link(name = "Metal", kind = "framework")]
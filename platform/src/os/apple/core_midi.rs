@@ -14,14 +14,11 @@ use {
 #[derive(Clone)]
 pub struct OsMidiOutput(pub (crate) Arc<Mutex<CoreMidiAccess >>);
 
-pub struct OsMidiInput(mpsc::Receiver<(MidiPortId, MidiData) >);
+pub struct OsMidiInput(mpsc::Receiver<MidiInputEvent>);
 
 impl OsMidiInput {
-    pub fn receive(&mut self) -> Option<(MidiPortId, MidiData)> {
-        if let Ok((port_id, data)) = self.0.try_recv() {
-            return Some((port_id, data))
-        }
-        None
+    pub fn receive(&mut self) -> Option<MidiInputEvent> {
+        self.0.try_recv().ok()
     }
 }
 
@@ -77,7 +74,7 @@ pub struct CoreMidiPort {
     desc: MidiPortDesc
 }
 
-type MidiInputSenders = Arc<Mutex<Vec<mpsc::Sender<(MidiPortId, MidiData) >> >>;
+type MidiInputSenders = Arc<Mutex<Vec<mpsc::Sender<MidiInputEvent> >> >;
 
 pub struct CoreMidiAccess {
     change_signal: SignalToUI,
@@ -100,6 +97,7 @@ impl CoreMidiAccess {
         let mut midi_receive = objc_block!(move | event_list: &MIDIEventList, user_data: u64 | {
             let midi_port_id = MidiPortId(LiveId(user_data));
             let mut senders = senders.lock().unwrap();
+            let time = midi_time();
             let packets = unsafe {std::slice::from_raw_parts(event_list.packet.as_ptr(), event_list.numPackets as usize)};
             for packet in packets {
                 for i in 0 .. packet.wordCount.min(64) {
@@ -112,8 +110,9 @@ impl CoreMidiAccess {
                         (ump & 0xff) as u8
                     ];
                     if ty == 0x02 { // midi 1.0 channel voice
+                        let event = MidiInputEvent {port_id: midi_port_id, data: MidiData {data}, time};
                         senders.retain( | s | {
-                            s.send((midi_port_id, MidiData {data})).is_ok()
+                            s.send(event).is_ok()
                         });
                     }
                 }
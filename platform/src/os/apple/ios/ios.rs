@@ -18,18 +18,20 @@ use {
                     ios_event::IosEvent,
                     ios_app::{IosApp, init_ios_app_global,get_ios_app_global}
                 },
-                url_session::{make_http_request},
+                url_session::AppleHttpRequests,
             },
             apple_classes::init_apple_classes_global,
             apple_media::CxAppleMedia,
             metal::{MetalCx, DrawPassMode},
         },
         pass::{CxPassParent},
+        color_space::ColorSpace,
         thread::SignalToUI,
         window::CxWindowPool,
         event::{
             Event,
-            NetworkResponseChannel
+            ClipboardFlavor,
+            ClipboardReadEvent
         },
         cx_api::{CxOsApi, CxOsOp},
         cx::{Cx, OsType},
@@ -89,12 +91,9 @@ impl Cx {
     }
 
     pub(crate) fn handle_networking_events(&mut self) {
-        let mut out = Vec::new();
-        while let Ok(event) = self.os.network_response.receiver.try_recv(){
-            out.push(event);
-        }
-        if out.len()>0{
-            self.call_event_handler(& Event::NetworkResponses(out))
+        let responses = self.os.http_requests.responses();
+        if responses.len() > 0 {
+            self.call_network_responses(responses);
         }
     }
 
@@ -148,6 +147,8 @@ impl Cx {
                     // check signals
                     if SignalToUI::check_and_clear_ui_signal(){
                         self.handle_media_signals();
+                        self.handle_web_socket_events();
+                        self.handle_dialog_events();
                         self.call_event_handler(&Event::Signal);
                     }
                     if self.handle_live_edit(){
@@ -283,6 +284,10 @@ impl Cx {
                 CxOsOp::SetTopmost(_window_id, _is_topmost) => {
                     todo!()
                 }
+                CxOsOp::SetWindowColorSpace(window_id, _color_space) => {
+                    // the surface is always presented as sRGB, so that's what the window draws in
+                    self.windows[window_id].color_space = ColorSpace::Srgb;
+                }
                 CxOsOp::XrStartPresenting => {
                     //todo!()
                 },
@@ -308,7 +313,10 @@ impl Cx {
                 CxOsOp::UpdateMacosMenu(_menu) => {
                 },
                 CxOsOp::HttpRequest{request_id, request} => {
-                    make_http_request(request_id, request, self.os.network_response.sender.clone());
+                    self.os.http_requests.request(request_id, request);
+                },
+                CxOsOp::CancelHttpRequest{request_id} => {
+                    self.os.http_requests.cancel(request_id);
                 },
                 CxOsOp::ShowClipboardActions(_request) => {
                     crate::log!("Show clipboard actions not supported yet");
@@ -316,6 +324,17 @@ impl Cx {
                 CxOsOp::CopyToClipboard(content) => {
                     get_ios_app_global().copy_to_clipboard(&content);
                 }
+                CxOsOp::WriteClipboard(flavors) => {
+                    // only the text flavor for now
+                    if let Some(ClipboardFlavor::Text(text)) = flavors.iter().find( | flavor | matches!(flavor, ClipboardFlavor::Text(_))) {
+                        get_ios_app_global().copy_to_clipboard(text);
+                    }
+                }
+                CxOsOp::ReadClipboard(request_id) => {
+                    crate::log!("Reading the clipboard not supported yet");
+                    self.call_event_handler(&Event::ClipboardRead(ClipboardReadEvent {request_id, flavors: Vec::new()}));
+                }
+                CxOsOp::GamepadRumble {..} => {}
                 CxOsOp::PrepareVideoPlayback(_, _, _, _, _) => todo!(),
                 CxOsOp::BeginVideoPlayback(_) => todo!(),
                 CxOsOp::PauseVideoPlayback(_) => todo!(),
@@ -325,10 +344,9 @@ impl Cx {
                 CxOsOp::CleanupVideoPlaybackResources(_) => todo!(),
                 CxOsOp::UpdateVideoSurfaceTexture(_) => todo!(),
 
-                CxOsOp::SaveFileDialog(_) => todo!(),
-                CxOsOp::SelectFileDialog(_) => todo!(),
-                CxOsOp::SaveFolderDialog(_) => todo!(),
-                CxOsOp::SelectFolderDialog(_) => todo!(),
+                CxOsOp::ShowDialog(dialog_id, dialog) => {
+                    self.show_dialog_fallback(dialog_id, dialog);
+                },
 
             }
         }
@@ -390,6 +408,6 @@ pub struct CxOs {
     pub (crate) media: CxAppleMedia,
     pub (crate) bytes_written: usize,
     pub (crate) draw_calls_done: usize,
-    pub (crate) network_response: NetworkResponseChannel,
+    pub (crate) http_requests: AppleHttpRequests,
 }
 
@@ -44,20 +44,22 @@ pub fn define_mtk_view() -> *const Class {
         YES
     }
     
-    fn on_touch(this: &Object, event: ObjcId, state: TouchState) {
+    fn on_touch(this: &Object, event: ObjcId) {
         unsafe {
             let enumerator: ObjcId = msg_send![event, allTouches];
             let size: u64 = msg_send![enumerator, count];
             let enumerator: ObjcId = msg_send![enumerator, objectEnumerator];
             
-            for touch_id in 0..size {
+            for _ in 0..size {
                 let ios_touch: ObjcId = msg_send![enumerator, nextObject];
-                let uid_obj: ObjcId = msg_send![ios_touch, estimationUpdateIndex];
-                let uid: u64 = if uid_obj != nil {
-                    msg_send![uid_obj, intValue]
-                }
-                else {
-                    touch_id as u64
+                // a UITouch lives as long as the touch it stands for, so its address is a stable id
+                let uid = ios_touch as u64;
+                let phase: i64 = msg_send![ios_touch, phase];
+                let state = match phase {
+                    0 => TouchState::Start,
+                    1 => TouchState::Move,
+                    3 | 4 => TouchState::Stop,
+                    _ => TouchState::Stable,
                 };
                 let p: NSPoint = msg_send![ios_touch, locationInView: this];
                 get_ios_app_global().update_touch(uid, dvec2(p.x, p.y), state);
@@ -66,22 +68,22 @@ pub fn define_mtk_view() -> *const Class {
     }
     
     extern "C" fn touches_began(this: &Object, _: Sel, _: ObjcId, event: ObjcId) {
-        on_touch(this, event, TouchState::Start);
+        on_touch(this, event);
         IosApp::send_touch_update();
     }
     
     extern "C" fn touches_moved(this: &Object, _: Sel, _: ObjcId, event: ObjcId) {
-        on_touch(this, event, TouchState::Move);
+        on_touch(this, event);
         IosApp::send_touch_update();
     }
     
     extern "C" fn touches_ended(this: &Object, _: Sel, _: ObjcId, event: ObjcId) {
-        on_touch(this, event, TouchState::Stop);
+        on_touch(this, event);
         IosApp::send_touch_update();
     }
     
     extern "C" fn touches_canceled(this: &Object, _: Sel, _: ObjcId, event: ObjcId) {
-        on_touch(this, event, TouchState::Stop);
+        on_touch(this, event);
         IosApp::send_touch_update();
     }
     
@@ -23,16 +23,19 @@ use {
                         get_macos_app_global,
                         init_macos_app_global
                     },
-                    macos_window::MacosWindow
+                    macos_window::MacosWindow,
+                    macos_gamepad::MacosGamepads,
+                    macos_dialogs::show_dialog,
                 },
                 apple_classes::init_apple_classes_global,
-                url_session::{make_http_request},
+                url_session::AppleHttpRequests,
             },
             metal_xpc::start_xpc_service,
             apple_media::CxAppleMedia,
             metal::{MetalCx, DrawPassMode},
         },
         pass::CxPassParent,
+        color_space::ColorSpace,
         thread::SignalToUI,
         cx_stdin::PollTimers,
         window::WindowId,
@@ -40,7 +43,7 @@ use {
             WindowGeom,
             MouseUpEvent,
             Event,
-            NetworkResponseChannel
+            ClipboardReadEvent
         },
         window::CxWindowPool,
         cx_api::{CxOsApi, CxOsOp},
@@ -65,7 +68,8 @@ impl MetalWindow {
         metal_cx: &MetalCx,
         inner_size: DVec2,
         position: Option<DVec2>,
-        title: &str
+        title: &str,
+        color_space: ColorSpace
     ) -> MetalWindow {
         
         let ca_layer: ObjcId = unsafe {msg_send![class!(CAMetalLayer), new]};
@@ -92,13 +96,30 @@ impl MetalWindow {
             let () = msg_send![view, setLayer: ca_layer];
         }
         
-        MetalWindow {
+        let mut metal_window = MetalWindow {
             is_resizing: false,
             window_id,
             cal_size: DVec2::default(),
             ca_layer,
             window_geom: cocoa_window.get_window_geom(),
             cocoa_window
+        };
+        metal_window.set_color_space(color_space);
+        metal_window
+    }
+    
+    pub (crate) fn set_color_space(&mut self, color_space: ColorSpace) {
+        // tagging the layer lets the compositor color match our pixels to the display,
+        // untagged layers get shown as raw display values which oversaturates on P3 screens
+        unsafe {
+            let name = match color_space {
+                ColorSpace::Srgb => kCGColorSpaceSRGB,
+                ColorSpace::DisplayP3 => kCGColorSpaceDisplayP3,
+                ColorSpace::LinearSrgb => kCGColorSpaceLinearSRGB,
+            };
+            let cg_color_space = CGColorSpaceCreateWithName(name);
+            let () = msg_send![self.ca_layer, setColorspace: cg_color_space];
+            CGColorSpaceRelease(cg_color_space);
         }
     }
     
@@ -216,12 +237,9 @@ impl Cx {
     }
     
     pub (crate) fn handle_networking_events(&mut self) {
-        let mut out = Vec::new();
-        while let Ok(event) = self.os.network_response.receiver.try_recv() {
-            out.push(event);
-        }
-        if out.len()>0 {
-            self.call_event_handler(&Event::NetworkResponses(out))
+        let responses = self.os.http_requests.responses();
+        if responses.len() > 0 {
+            self.call_network_responses(responses);
         }
     }
     
@@ -245,9 +263,11 @@ impl Cx {
             MacosEvent::MouseMove(_) |
             MacosEvent::MouseUp(_) |
             MacosEvent::Scroll(_) |
+            MacosEvent::Navigate(_) |
             MacosEvent::KeyDown(_) |
             MacosEvent::KeyUp(_) |
-            MacosEvent::TextInput(_) => {
+            MacosEvent::TextInput(_) |
+            MacosEvent::TextComposition(_) => {
                 self.os.keep_alive_counter = KEEP_ALIVE_COUNT;
             }
             MacosEvent::Timer(te) => {
@@ -260,6 +280,8 @@ impl Cx {
                     // check signals
                     if SignalToUI::check_and_clear_ui_signal() {
                         self.handle_media_signals();
+                        self.handle_web_socket_events();
+                        self.handle_dialog_events();
                         self.call_event_handler(&Event::Signal);
                     }
                     if self.handle_live_edit() {
@@ -267,6 +289,7 @@ impl Cx {
                         self.redraw_all();
                     }
                     self.handle_networking_events();
+                    self.handle_macos_gamepads(self.seconds_since_app_start());
                     
                     return EventFlow::Poll;
                 }
@@ -306,16 +329,9 @@ impl Cx {
                     }
                     window.window_geom = re.new_geom.clone();
                     self.windows[re.window_id].window_geom = re.new_geom.clone();
-                    
-                    // redraw just this windows root draw list
-                    if re.old_geom.dpi_factor != re.new_geom.dpi_factor || re.old_geom.inner_size != re.new_geom.inner_size {
-                        if let Some(main_pass_id) = self.windows[re.window_id].main_pass_id {
-                            self.redraw_pass_and_child_passes(main_pass_id);
-                        }
-                    }
                 }
-                // ok lets not redraw all, just this window
-                self.call_event_handler(&Event::WindowGeomChange(re));
+                // redraws just this window, at its new size or scale
+                self.call_window_geom_change_event(re);
             }
             MacosEvent::WindowClosed(wc) => {
                 // lets remove the window from the set
@@ -339,6 +355,7 @@ impl Cx {
                     self.call_draw_event();
                     self.mtl_compile_shaders(&metal_cx);
                 }
+                self.handle_accessibility(metal_windows);
                 // ok here we send out to all our childprocesses
                 
                 self.handle_repaint(metal_windows, metal_cx);
@@ -366,15 +383,24 @@ impl Cx {
             MacosEvent::Scroll(e) => {
                 self.call_event_handler(&Event::Scroll(e.into()))
             }
+            MacosEvent::Navigate(e) => {
+                self.call_event_handler(&Event::Navigate(e))
+            }
             MacosEvent::WindowDragQuery(e) => {
                 self.call_event_handler(&Event::WindowDragQuery(e))
             }
             MacosEvent::WindowCloseRequested(e) => {
                 self.call_event_handler(&Event::WindowCloseRequested(e))
             }
+            MacosEvent::AppQuitRequested(e) => {
+                self.call_event_handler(&Event::AppQuitRequested(e))
+            }
             MacosEvent::TextInput(e) => {
                 self.call_event_handler(&Event::TextInput(e))
             }
+            MacosEvent::TextComposition(e) => {
+                self.call_event_handler(&Event::TextComposition(e))
+            }
             MacosEvent::Drag(e) => {
                 self.call_event_handler(&Event::Drag(e));
                 self.drag_drop.cycle_drag();
@@ -419,6 +445,9 @@ impl Cx {
             MacosEvent::MacosMenuCommand(e) => {
                 self.call_event_handler(&Event::MacosMenuCommand(e))
             }
+            MacosEvent::AccessibilityRequested => {
+                self.set_accessibility_enabled(true);
+            }
         }
         
         if self.any_passes_dirty() || self.need_redrawing()/* || self.new_next_frames.len() != 0 */|| paint_dirty {
@@ -428,6 +457,21 @@ impl Cx {
         }
     }
     
+    /// Mirrors the accessibility tree into the windows and posts its notifications.
+    fn handle_accessibility(&mut self, metal_windows: &mut Vec<MetalWindow>) {
+        if self.accessibility.elements_changed {
+            self.accessibility.elements_changed = false;
+            for window in metal_windows.iter_mut() {
+                window.cocoa_window.update_accessibility(&self.accessibility.elements);
+            }
+        }
+        for notification in self.take_access_notifications() {
+            for window in metal_windows.iter() {
+                window.cocoa_window.post_accessibility_notification(notification);
+            }
+        }
+    }
+    
     fn handle_platform_ops(&mut self, metal_windows: &mut Vec<MetalWindow>, metal_cx: &MetalCx)->EventFlow {
         while let Some(op) = self.platform_ops.pop() {
             match op {
@@ -438,11 +482,14 @@ impl Cx {
                         &metal_cx,
                         window.create_inner_size.unwrap_or(dvec2(800., 600.)),
                         window.create_position,
-                        &window.create_title
+                        &window.create_title,
+                        window.color_space
                     );
                     window.window_geom = metal_window.window_geom.clone();
                     metal_windows.push(metal_window);
                     window.is_created = true;
+                    // windows opened while the app runs have to draw their first frame
+                    self.redraw_window(window_id);
                 },
                 CxOsOp::CloseWindow(window_id) => {
                     if let Some(metal_window) = metal_windows.iter_mut().find( | w | w.window_id == window_id) {
@@ -478,6 +525,11 @@ impl Cx {
                 CxOsOp::SetTopmost(_window_id, _is_topmost) => {
                     todo!()
                 }
+                CxOsOp::SetWindowColorSpace(window_id, color_space) => {
+                    if let Some(metal_window) = metal_windows.iter_mut().find( | w | w.window_id == window_id) {
+                        metal_window.set_color_space(color_space);
+                    }
+                }
                 CxOsOp::XrStartPresenting => {
                     //todo!()
                 },
@@ -499,6 +551,9 @@ impl Cx {
                 CxOsOp::StartTimer {timer_id, interval, repeats} => {
                     get_macos_app_global().start_timer(timer_id, interval, repeats);
                 },
+                CxOsOp::GamepadRumble {..} => {
+                    // rumble needs CoreHaptics on macOS, which isn't wired up
+                },
                 CxOsOp::StopTimer(timer_id) => {
                     get_macos_app_global().stop_timer(timer_id);
                 },
@@ -513,7 +568,10 @@ impl Cx {
                     get_macos_app_global().update_macos_menu(&menu)
                 },
                 CxOsOp::HttpRequest {request_id, request} => {
-                    make_http_request(request_id, request, self.os.network_response.sender.clone());
+                    self.os.http_requests.request(request_id, request);
+                },
+                CxOsOp::CancelHttpRequest {request_id} => {
+                    self.os.http_requests.cancel(request_id);
                 },
                 CxOsOp::ShowClipboardActions(_request) => {
                     crate::log!("Show clipboard actions not supported yet");
@@ -521,6 +579,13 @@ impl Cx {
                 CxOsOp::CopyToClipboard(content) => {
                     get_macos_app_global().copy_to_clipboard(&content);
                 },
+                CxOsOp::WriteClipboard(flavors) => {
+                    get_macos_app_global().write_clipboard(&flavors);
+                },
+                CxOsOp::ReadClipboard(request_id) => {
+                    let flavors = get_macos_app_global().read_clipboard();
+                    self.call_event_handler(&Event::ClipboardRead(ClipboardReadEvent {request_id, flavors}));
+                },
                 CxOsOp::PrepareVideoPlayback(_, _, _, _, _) => todo!(),
                 CxOsOp::BeginVideoPlayback(_) => todo!(),
                 CxOsOp::PauseVideoPlayback(_) => todo!(),
@@ -530,24 +595,8 @@ impl Cx {
                 CxOsOp::CleanupVideoPlaybackResources(_) => todo!(),
                 CxOsOp::UpdateVideoSurfaceTexture(_) => todo!(),
 
-                CxOsOp::SaveFileDialog(settings) => 
-                {
-                    get_macos_app_global().open_save_file_dialog(settings);
-                }
-                
-                CxOsOp::SelectFileDialog(settings) => 
-                {
-                    get_macos_app_global().open_select_file_dialog(settings);                   
-                }
-                
-                CxOsOp::SaveFolderDialog(settings) => 
-                {
-                    get_macos_app_global().open_save_folder_dialog(settings);
-                }
-                
-                CxOsOp::SelectFolderDialog(settings) => 
-                {
-                    get_macos_app_global().open_select_folder_dialog(settings);
+                CxOsOp::ShowDialog(dialog_id, dialog) => {
+                    show_dialog(dialog_id, dialog, self.dialog_sender());
                 }
             }
         }
@@ -606,8 +655,9 @@ pub struct CxOs {
     pub (crate) media: CxAppleMedia,
     pub (crate) bytes_written: usize,
     pub (crate) draw_calls_done: usize,
-    pub (crate) network_response: NetworkResponseChannel,
+    pub (crate) http_requests: AppleHttpRequests,
     pub (crate) stdin_timers: PollTimers,
     pub (crate) start_time: Option<Instant>,
+    pub (crate) gamepads: MacosGamepads,
     pub metal_device: Option<ObjcId>,
 }
@@ -1,9 +1,7 @@
-use crate::file_dialogs::FileDialog;
-
 use {
     std::{
         rc::Rc,
-        cell::{RefCell},
+        cell::{Cell, RefCell},
         time::Instant,
         collections::{HashMap},
         os::raw::{c_void},
@@ -40,8 +38,11 @@ use {
             KeyEvent,
             TextInputEvent,
             TextClipboardEvent,
+            ClipboardFlavor,
             TimerEvent,
             KeyModifiers,
+            AppQuitRequestedEvent,
+            NavigateDirection,
         },
         cursor::MouseCursor,
         macos_menu::{
@@ -129,6 +130,8 @@ pub struct MacosApp {
     startup_focus_hack_ran: bool,
     event_callback: Option<Box<dyn FnMut(MacosEvent) -> EventFlow >>,
     event_flow: EventFlow,
+    // set once the app itself decided to exit, so quitting doesn't ask it again
+    quit_confirmed: bool,
     
     pub cursors: HashMap<MouseCursor, ObjcId>,
     pub current_cursor: MouseCursor,
@@ -157,6 +160,7 @@ impl MacosApp {
                 timers: Vec::new(),
                 cocoa_windows: Vec::new(),
                 event_flow: EventFlow::Poll,
+                quit_confirmed: false,
                 last_key_mod: KeyModifiers {..Default::default()},
                 event_callback: Some(event_callback),
                 cursors: HashMap::new(),
@@ -401,34 +405,24 @@ impl MacosApp {
                         },
                         KeyCode::KeyC => if modifiers.logo || modifiers.control {
                             let pasteboard: ObjcId = get_macos_app_global().pasteboard;
-                            let response = Rc::new(RefCell::new(None));
-                            MacosApp::do_callback(
-                                MacosEvent::TextCopy(TextClipboardEvent {
-                                    response: response.clone()
-                                })
-                            );
-                            let response = response.borrow();
-                            if let Some(response) = response.as_ref() {
-                                let nsstring = str_to_nsstring(&response);
-                                let array: ObjcId = msg_send![class!(NSArray), arrayWithObject: NSStringPboardType];
-                                let () = msg_send![pasteboard, declareTypes: array owner: nil];
-                                let () = msg_send![pasteboard, setString: nsstring forType: NSStringPboardType];
+                            let event = TextClipboardEvent {
+                                response: Rc::new(RefCell::new(None)),
+                                rich_response: Rc::new(RefCell::new(None))
+                            };
+                            MacosApp::do_callback(MacosEvent::TextCopy(event.clone()));
+                            if let Some(flavors) = event.flavors() {
+                                Self::set_pasteboard_flavors(pasteboard, &flavors);
                             }
                         },
                         KeyCode::KeyX => if modifiers.logo || modifiers.control {
                             let pasteboard: ObjcId = get_macos_app_global().pasteboard;
-                            let response = Rc::new(RefCell::new(None));
-                            MacosApp::do_callback(
-                                MacosEvent::TextCut(TextClipboardEvent {
-                                    response: response.clone()
-                                })
-                            );
-                            let response = response.borrow();
-                            if let Some(response) = response.as_ref() {
-                                let nsstring = str_to_nsstring(&response);
-                                let array: ObjcId = msg_send![class!(NSArray), arrayWithObject: NSStringPboardType];
-                                let () = msg_send![pasteboard, declareTypes: array owner: nil];
-                                let () = msg_send![pasteboard, setString: nsstring forType: NSStringPboardType];
+                            let event = TextClipboardEvent {
+                                response: Rc::new(RefCell::new(None)),
+                                rich_response: Rc::new(RefCell::new(None))
+                            };
+                            MacosApp::do_callback(MacosEvent::TextCut(event.clone()));
+                            if let Some(flavors) = event.flavors() {
+                                Self::set_pasteboard_flavors(pasteboard, &flavors);
                             }
                         },
                         _ => {}
@@ -512,6 +506,26 @@ impl MacosApp {
                     cocoa_window.send_scroll(DVec2 {x: -dx * 32., y: -dy * 32.}, get_event_key_modifier(ns_event), true);
                 }
             },
+            NSEventType::NSEventTypeSwipe => {
+                let window: ObjcId = msg_send![ns_event, window];
+                if window == nil {
+                    return
+                }
+                let window_delegate: ObjcId = msg_send![window, delegate];
+                if window_delegate == nil {
+                    return
+                }
+                let ptr: *mut c_void = *(*window_delegate).get_ivar("macos_window_ptr");
+                let cocoa_window = &mut *(ptr as *mut MacosWindow);
+                // swiping right goes back, like in a browser
+                let dx: f64 = msg_send![ns_event, deltaX];
+                if dx > 0.0 {
+                    cocoa_window.send_navigate(NavigateDirection::Back);
+                }
+                else if dx < 0.0 {
+                    cocoa_window.send_navigate(NavigateDirection::Forward);
+                }
+            },
             NSEventType::NSEventTypePressure => {
             },
             _ => (),
@@ -572,6 +586,7 @@ impl MacosApp {
             let event_flow = callback(event);
             get_macos_app_global().event_flow = event_flow;
             if let EventFlow::Exit = event_flow {
+                get_macos_app_global().quit_confirmed = true;
                 unsafe {
                     let ns_app: ObjcId = msg_send![class!(NSApplication), sharedApplication];
                     let () = msg_send![ns_app, terminate: nil];
@@ -717,12 +732,34 @@ impl MacosApp {
     }*/
     
     pub fn send_command_event(command: LiveId) {
+        if command == live_id!(quit) {
+            // quit the way the dock menu does, so it goes past applicationShouldTerminate
+            unsafe {
+                let ns_app: ObjcId = msg_send![class!(NSApplication), sharedApplication];
+                let () = msg_send![ns_app, terminate: nil];
+            }
+            return
+        }
         MacosApp::do_callback(
             MacosEvent::MacosMenuCommand(command)
         );
         MacosApp::do_callback(MacosEvent::Paint);
     }
     
+    /// Asks the app whether it is fine to quit. Returns true when there is nobody to ask, for
+    /// instance when the app already decided to exit itself.
+    pub fn send_quit_requested_event() -> bool {
+        if get_macos_app_global().quit_confirmed || get_macos_app_global().event_callback.is_none() {
+            return true
+        }
+        let accept_quit = Rc::new(Cell::new(true));
+        MacosApp::do_callback(MacosEvent::AppQuitRequested(AppQuitRequestedEvent {
+            accept_quit: accept_quit.clone()
+        }));
+        MacosApp::do_callback(MacosEvent::Paint);
+        accept_quit.get()
+    }
+    
     pub fn send_paint_event() {
         MacosApp::do_callback(MacosEvent::Paint);
     }
@@ -745,6 +782,70 @@ impl MacosApp {
         };
     }*/
 
+    // Puts each flavor on the pasteboard under its own type. Types without a system name
+    // are declared with their mime type, which other makepad apps read back the same way.
+    unsafe fn set_pasteboard_flavors(pasteboard: ObjcId, flavors: &[ClipboardFlavor]) {
+        let types: ObjcId = msg_send![class!(NSMutableArray), array];
+        for flavor in flavors {
+            let () = msg_send![types, addObject: pasteboard_type(flavor)];
+        }
+        let () = msg_send![pasteboard, declareTypes: types owner: nil];
+        for flavor in flavors {
+            match flavor {
+                ClipboardFlavor::Text(text) | ClipboardFlavor::Html(text) | ClipboardFlavor::Rtf(text) => {
+                    let () = msg_send![pasteboard, setString: str_to_nsstring(text) forType: pasteboard_type(flavor)];
+                }
+                ClipboardFlavor::Image {data, ..} | ClipboardFlavor::Custom {data, ..} => {
+                    let nsdata: ObjcId = msg_send![class!(NSData), dataWithBytes: data.as_ptr() length: data.len()];
+                    let () = msg_send![pasteboard, setData: nsdata forType: pasteboard_type(flavor)];
+                }
+            }
+        }
+    }
+    
+    pub fn write_clipboard(&mut self, flavors: &[ClipboardFlavor]) {
+        unsafe {Self::set_pasteboard_flavors(self.pasteboard, flavors)}
+    }
+    
+    /// Reads every pasteboard type we know a flavor for, skipping ones that map to a flavor
+    /// we already read.
+    pub fn read_clipboard(&mut self) -> Vec<ClipboardFlavor> {
+        let mut flavors: Vec<ClipboardFlavor> = Vec::new();
+        unsafe {
+            let pasteboard: ObjcId = self.pasteboard;
+            let types: ObjcId = msg_send![pasteboard, types];
+            if types == nil {
+                return flavors
+            }
+            let count: usize = msg_send![types, count];
+            for i in 0..count {
+                let ty: ObjcId = msg_send![types, objectAtIndex: i];
+                let Some(mime) = mime_for_pasteboard_type(&nsstring_to_string(ty)) else {
+                    continue
+                };
+                if flavors.iter().any( | flavor | flavor.mime() == mime) {
+                    continue
+                }
+                if mime == "text/plain" {
+                    let nsstring: ObjcId = msg_send![pasteboard, stringForType: ty];
+                    if nsstring != nil {
+                        flavors.push(ClipboardFlavor::Text(nsstring_to_string(nsstring)));
+                    }
+                    continue
+                }
+                let data: ObjcId = msg_send![pasteboard, dataForType: ty];
+                if data == nil {
+                    continue
+                }
+                let bytes: *const u8 = msg_send![data, bytes];
+                let length: usize = msg_send![data, length];
+                let data = if length > 0 {std::slice::from_raw_parts(bytes, length).to_vec()} else {Vec::new()};
+                flavors.push(ClipboardFlavor::from_mime(&mime, data));
+            }
+        }
+        flavors
+    }
+    
     pub fn copy_to_clipboard(&mut self, content: &str) {
         unsafe {
             let pasteboard: ObjcId = self.pasteboard;
@@ -754,25 +855,39 @@ impl MacosApp {
             let () = msg_send![pasteboard, setString: nsstring forType: NSStringPboardType];
         }
     }
+}
 
-    pub fn open_save_file_dialog(&mut self, _settings: FileDialog)
-    {
-        println!("open save file dialog!");
-    }
+// The system pasteboard types for the flavors other apps know about.
+const PASTEBOARD_TYPES: [(&str, &str); 6] = [
+    ("text/html", "public.html"),
+    ("text/rtf", "public.rtf"),
+    ("image/png", "public.png"),
+    ("image/jpeg", "public.jpeg"),
+    ("image/tiff", "public.tiff"),
+    ("image/gif", "com.compuserve.gif"),
+];
 
-    pub fn open_select_file_dialog(&mut self, _settings: FileDialog )
-    {
-        println!("open select file dialog!");
+unsafe fn pasteboard_type(flavor: &ClipboardFlavor) -> ObjcId {
+    if let ClipboardFlavor::Text(_) = flavor {
+        return NSStringPboardType
     }
-
-    pub fn open_save_folder_dialog(&mut self,  _settings: FileDialog)
-    {
-        println!("open save folder dialog!");
+    let mime = flavor.mime();
+    match PASTEBOARD_TYPES.iter().find( | (m, _) | *m == mime) {
+        Some((_, ty)) => str_to_nsstring(ty),
+        None => str_to_nsstring(mime)
     }
+}
 
-    pub fn open_select_folder_dialog(&mut self, _settings: FileDialog)
-    {
-        println!("open select folder dialog!");
+fn mime_for_pasteboard_type(ty: &str) -> Option<String> {
+    if ty == "public.utf8-plain-text" || ty == "NSStringPboardType" {
+        return Some("text/plain".to_string())
     }
-
+    if let Some((mime, _)) = PASTEBOARD_TYPES.iter().find( | (_, t) | *t == ty) {
+        return Some(mime.to_string())
+    }
+    // types declared by makepad apps with their mime type
+    if ty.contains('/') && !ty.contains(' ') {
+        return Some(ty.to_string())
+    }
+    None
 }
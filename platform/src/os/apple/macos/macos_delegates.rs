@@ -39,7 +39,12 @@ use {
             DragEvent,
             DropEvent,
             DragItem,
-            DragResponse
+            DragResponse,
+            NavigateDirection,
+            TextCompositionEvent,
+            TextCompositionPhase,
+            TextCompositionUnderline,
+            drag_drop::decode_uri_path,
         },
     }
 };
@@ -74,8 +79,22 @@ pub fn define_macos_timer_delegate() -> *const Class {
 
 pub fn define_app_delegate() -> *const Class {
     
+    extern fn application_should_terminate(_this: &Object, _: Sel, _: ObjcId) -> u64 {
+        // NSTerminateNow is 1, NSTerminateCancel is 0
+        if MacosApp::send_quit_requested_event() {
+            1
+        }
+        else {
+            0
+        }
+    }
+    
     let superclass = class!(NSObject);
-    let decl = ClassDecl::new("NSAppDelegate", superclass).unwrap();
+    let mut decl = ClassDecl::new("NSAppDelegate", superclass).unwrap();
+    
+    unsafe {
+        decl.add_method(sel!(applicationShouldTerminate:), application_should_terminate as extern fn(&Object, Sel, ObjcId) -> u64);
+    }
 
     return decl.register();
 }
@@ -274,8 +293,8 @@ pub fn define_macos_window_delegate() -> *const Class {
         decl.add_method(sel!(windowDidEndLiveResize:), window_did_end_live_resize as extern fn(&Object, Sel, ObjcId));
         
         decl.add_method(sel!(windowDidMove:), window_did_move as extern fn(&Object, Sel, ObjcId));
-        decl.add_method(sel!(windowChangedScreen:), window_did_change_screen as extern fn(&Object, Sel, ObjcId));
-        decl.add_method(sel!(windowChangedBackingProperties:), window_did_change_backing_properties as extern fn(&Object, Sel, ObjcId));
+        decl.add_method(sel!(windowDidChangeScreen:), window_did_change_screen as extern fn(&Object, Sel, ObjcId));
+        decl.add_method(sel!(windowDidChangeBackingProperties:), window_did_change_backing_properties as extern fn(&Object, Sel, ObjcId));
         decl.add_method(sel!(windowDidBecomeKey:), window_did_become_key as extern fn(&Object, Sel, ObjcId));
         decl.add_method(sel!(windowDidResignKey:), window_did_resign_key as extern fn(&Object, Sel, ObjcId));
         
@@ -389,14 +408,21 @@ pub fn define_cocoa_view_class() -> *const Class {
         let cw = get_cocoa_window(this);
         let modifiers = get_event_key_modifier(event);
         let button = get_event_mouse_button(event);
-        cw.send_mouse_down(button, modifiers);
+        // the back and forward buttons only navigate
+        match button {
+            3 => cw.send_navigate(NavigateDirection::Back),
+            4 => cw.send_navigate(NavigateDirection::Forward),
+            _ => cw.send_mouse_down(button, modifiers)
+        }
     }
     
     extern fn other_mouse_up(this: &Object, _sel: Sel, event: ObjcId) {
         let cw = get_cocoa_window(this);
         let modifiers = get_event_key_modifier(event);
         let button = get_event_mouse_button(event);
-        cw.send_mouse_up(button, modifiers);
+        if button != 3 && button != 4 {
+            cw.send_mouse_up(button, modifiers);
+        }
     }
     
     fn mouse_pos_from_event(view: &Object, event: ObjcId) -> DVec2 {
@@ -507,24 +533,58 @@ pub fn define_cocoa_view_class() -> *const Class {
         }
     }
     
-    extern fn set_marked_text(this: &mut Object, _sel: Sel, string: ObjcId, _selected_range: NSRange, _replacement_range: NSRange) {
-        unsafe {
+    extern fn set_marked_text(this: &mut Object, _sel: Sel, string: ObjcId, selected_range: NSRange, _replacement_range: NSRange) {
+        let (was_composing, preedit) = unsafe {
             let marked_text_ref: &mut ObjcId = this.get_mut_ivar("markedText");
+            let was_composing = (*marked_text_ref).length() > 0;
             let _: () = msg_send![(*marked_text_ref), release];
             let marked_text = NSMutableAttributedString::alloc(nil);
             let has_attr = msg_send![string, isKindOfClass: class!(NSAttributedString)];
-            if has_attr {
+            let characters = if has_attr {
                 marked_text.init_with_attributed_string(string);
+                msg_send![string, string]
             } else {
                 marked_text.init_with_string(string);
+                string
             };
             *marked_text_ref = marked_text;
+            (was_composing, nsstring_to_string(characters))
+        };
+        let cw = get_cocoa_window(this);
+        if preedit.is_empty() {
+            if was_composing {
+                cw.send_text_composition(TextCompositionEvent::commit());
+            }
+            return
         }
+        // the selected range is in utf16 units, and is the clause being converted
+        let byte_index = | utf16_index: u64 | {
+            preedit.char_indices().scan(0, | utf16, (index, c) | {
+                let at = *utf16;
+                *utf16 += c.len_utf16() as u64;
+                Some((index, at))
+            }).find( | (_, at) | *at >= utf16_index).map_or(preedit.len(), | (index, _) | index)
+        };
+        let start = byte_index(selected_range.location);
+        let end = byte_index(selected_range.location + selected_range.length);
+        let mut underlines = vec![TextCompositionUnderline {start: 0, end: preedit.len(), thick: false}];
+        if end > start {
+            underlines.push(TextCompositionUnderline {start, end, thick: true});
+        }
+        cw.send_text_composition(TextCompositionEvent {
+            phase: if was_composing {TextCompositionPhase::Update} else {TextCompositionPhase::Start},
+            cursor: end,
+            preedit,
+            underlines,
+        });
     }
     
     extern fn unmark_text(this: &Object, _sel: Sel) {
         unsafe {
             let marked_text: ObjcId = *this.get_ivar("markedText");
+            if marked_text.length() > 0 {
+                get_cocoa_window(this).send_text_composition(TextCompositionEvent::commit());
+            }
             let mutable_string = marked_text.mutable_string();
             let _: () = msg_send![mutable_string, setString: get_apple_class_global().const_empty_string.as_id()];
             let input_context: ObjcId = msg_send![this, inputContext];
@@ -667,6 +727,7 @@ pub fn define_cocoa_view_class() -> *const Class {
         };
         
         window.do_callback(MacosEvent::Drag(DragEvent {
+            window_id: window.window_id,
             modifiers,
             handled: Cell::new(false),
             abs: pos,
@@ -742,7 +803,8 @@ pub fn define_cocoa_view_class() -> *const Class {
                     };
                     items.push(DragItem::FilePath {
                         internal_id,
-                        path: if path == "makepad_internal_empty" {"".to_string()}else {path}
+                        // paths of files from finder come percent encoded
+                        path: if path == "makepad_internal_empty" {"".to_string()}else {decode_uri_path(&path)}
                     });
                 }
             }
@@ -762,6 +824,7 @@ pub fn define_cocoa_view_class() -> *const Class {
         let window = get_cocoa_window(this);
         let (items, pos) = get_drag_items_from_pasteboard(this, sender);
         window.do_callback(MacosEvent::Drop(DropEvent {
+            window_id: window.window_id,
             modifiers,
             handled: Cell::new(false),
             abs: pos,
@@ -783,6 +846,40 @@ pub fn define_cocoa_view_class() -> *const Class {
     }*/
     
     
+    extern fn accessibility_children(this: &Object, _: Sel) -> ObjcId {
+        let cw = get_cocoa_window(this);
+        // the first time a screen reader asks, start building the accessibility tree
+        if !cw.access_requested {
+            cw.access_requested = true;
+            cw.do_callback(MacosEvent::AccessibilityRequested);
+        }
+        let roots: Vec<ObjcId> = cw.access_roots.iter().filter_map( | id | cw.access_elements.get(id).cloned()).collect();
+        unsafe {msg_send![class!(NSArray), arrayWithObjects: roots.as_ptr() count: roots.len()]}
+    }
+    
+    extern fn accessibility_focused_ui_element(this: &Object, _: Sel) -> ObjcId {
+        let cw = get_cocoa_window(this);
+        if let Some(element) = cw.access_focus.and_then( | id | cw.access_elements.get(&id)) {
+            return *element
+        }
+        this as *const _ as ObjcId
+    }
+    
+    extern fn accessibility_hit_test(this: &Object, _: Sel, point: NSPoint) -> ObjcId {
+        let cw = get_cocoa_window(this);
+        // children come after their parents, so the last hit is the deepest one
+        for id in cw.access_order.iter().rev() {
+            if let Some(element) = cw.access_elements.get(id) {
+                let frame: NSRect = unsafe {msg_send![*element, accessibilityFrame]};
+                if point.x >= frame.origin.x && point.x < frame.origin.x + frame.size.width
+                    && point.y >= frame.origin.y && point.y < frame.origin.y + frame.size.height {
+                    return *element
+                }
+            }
+        }
+        this as *const _ as ObjcId
+    }
+    
     let superclass = class!(NSView);
     let mut decl = ClassDecl::new("RenderViewClass", superclass).unwrap();
     unsafe {
@@ -836,6 +933,10 @@ pub fn define_cocoa_view_class() -> *const Class {
         
         decl.add_method(sel!(displayLayer:), display_layer as extern fn(&Object, Sel, ObjcId));
         
+        decl.add_method(sel!(accessibilityChildren), accessibility_children as extern fn(&Object, Sel) -> ObjcId);
+        decl.add_method(sel!(accessibilityFocusedUIElement), accessibility_focused_ui_element as extern fn(&Object, Sel) -> ObjcId);
+        decl.add_method(sel!(accessibilityHitTest:), accessibility_hit_test as extern fn(&Object, Sel, NSPoint) -> ObjcId);
+        
         #[cfg(target_os = "macos")]{
             decl.add_method(sel!(draggingSession: endedAtPoint: operation:), dragging_session_ended_at_point_operation as extern fn(&Object, Sel, ObjcId, NSPoint, NSDragOperation));
             decl.add_method(sel!(draggingEntered:), dragging_entered as extern fn(&Object, Sel, ObjcId) -> NSDragOperation);
@@ -0,0 +1,137 @@
+use {
+    std::{
+        path::PathBuf,
+        sync::mpsc::Sender,
+    },
+    crate::{
+        makepad_live_id::LiveId,
+        makepad_objc_sys::objc_block,
+        thread::SignalToUI,
+        event::DialogEvent,
+        file_dialogs::{Dialog, DialogResult, MessageDialogLevel, MessageDialogButtons},
+        os::{
+            apple::apple_sys::*,
+            apple_util::{
+                nsstring_to_string,
+                str_to_nsstring,
+            },
+        },
+    }
+};
+
+const NS_MODAL_RESPONSE_OK: i64 = 1;
+const NS_ALERT_FIRST_BUTTON_RETURN: i64 = 1000;
+
+const NS_ALERT_STYLE_WARNING: u64 = 0;
+const NS_ALERT_STYLE_INFORMATIONAL: u64 = 1;
+const NS_ALERT_STYLE_CRITICAL: u64 = 2;
+
+unsafe fn url_to_path(url: ObjcId) -> PathBuf {
+    let path: ObjcId = msg_send![url, path];
+    PathBuf::from(nsstring_to_string(path))
+}
+
+/// Open and save panels run on their own without blocking our loop, the completion handler sends
+/// the result. Alerts have no such thing without a window to attach to, so they run modal.
+pub fn show_dialog(dialog_id: LiveId, dialog: Dialog, sender: Sender<DialogEvent>) {
+    unsafe {
+        match &dialog {
+            Dialog::OpenFile(d) | Dialog::SaveFile(d) | Dialog::SelectFolder(d) => {
+                let is_save = matches!(dialog, Dialog::SaveFile(_));
+                let is_folder = matches!(dialog, Dialog::SelectFolder(_));
+                let panel: ObjcId = if is_save {
+                    msg_send![class!(NSSavePanel), savePanel]
+                }
+                else {
+                    let panel: ObjcId = msg_send![class!(NSOpenPanel), openPanel];
+                    let () = msg_send![panel, setCanChooseFiles: if is_folder {NO} else {YES}];
+                    let () = msg_send![panel, setCanChooseDirectories: if is_folder {YES} else {NO}];
+                    let () = msg_send![panel, setCanCreateDirectories: if is_folder {YES} else {NO}];
+                    let () = msg_send![panel, setAllowsMultipleSelection: if d.multiple && !is_folder {YES} else {NO}];
+                    panel
+                };
+                // panels haven't shown their title in a long time, the message is what the user sees
+                let () = msg_send![panel, setTitle: str_to_nsstring(dialog.title())];
+                let () = msg_send![panel, setMessage: str_to_nsstring(dialog.title())];
+                if let Some(location) = &d.location {
+                    let url: ObjcId = msg_send![class!(NSURL), fileURLWithPath: str_to_nsstring(&location.to_string_lossy())];
+                    let () = msg_send![panel, setDirectoryURL: url];
+                }
+                if let Some(filename) = &d.filename {
+                    if is_save {
+                        let () = msg_send![panel, setNameFieldStringValue: str_to_nsstring(filename)];
+                    }
+                }
+                if d.filters.len() > 0 && !is_folder {
+                    let types: ObjcId = msg_send![class!(NSMutableArray), array];
+                    for filter in &d.filters {
+                        for ext in &filter.extensions {
+                            let () = msg_send![types, addObject: str_to_nsstring(ext)];
+                        }
+                    }
+                    let () = msg_send![panel, setAllowedFileTypes: types];
+                }
+                let () = msg_send![panel, retain];
+                let handler = objc_block!(move | response: i64 | {
+                    let result = if response == NS_MODAL_RESPONSE_OK {
+                        let mut paths = Vec::new();
+                        if is_save {
+                            let url: ObjcId = msg_send![panel, URL];
+                            paths.push(url_to_path(url));
+                        }
+                        else {
+                            let urls: ObjcId = msg_send![panel, URLs];
+                            let count: u64 = msg_send![urls, count];
+                            for i in 0..count {
+                                let url: ObjcId = msg_send![urls, objectAtIndex: i];
+                                paths.push(url_to_path(url));
+                            }
+                        }
+                        DialogResult::Paths(paths)
+                    }
+                    else {
+                        DialogResult::Cancelled
+                    };
+                    let _ = sender.send(DialogEvent::Closed {dialog_id, result});
+                    SignalToUI::set_ui_signal();
+                    let () = msg_send![panel, release];
+                });
+                let () = msg_send![panel, beginWithCompletionHandler: &handler];
+            }
+            Dialog::Message(d) => {
+                let alert: ObjcId = msg_send![class!(NSAlert), new];
+                if let Some(title) = &d.title {
+                    let () = msg_send![alert, setMessageText: str_to_nsstring(title)];
+                    let () = msg_send![alert, setInformativeText: str_to_nsstring(&d.message)];
+                }
+                else {
+                    let () = msg_send![alert, setMessageText: str_to_nsstring(&d.message)];
+                }
+                let style = match d.level {
+                    MessageDialogLevel::Info => NS_ALERT_STYLE_INFORMATIONAL,
+                    MessageDialogLevel::Warning => NS_ALERT_STYLE_WARNING,
+                    MessageDialogLevel::Error => NS_ALERT_STYLE_CRITICAL,
+                };
+                let () = msg_send![alert, setAlertStyle: style];
+                let buttons: &[&str] = match d.buttons {
+                    MessageDialogButtons::Ok => &["OK"],
+                    MessageDialogButtons::OkCancel => &["OK", "Cancel"],
+                    MessageDialogButtons::YesNo => &["Yes", "No"],
+                };
+                for button in buttons {
+                    let _: ObjcId = msg_send![alert, addButtonWithTitle: str_to_nsstring(button)];
+                }
+                let response: i64 = msg_send![alert, runModal];
+                let () = msg_send![alert, release];
+                let result = if response == NS_ALERT_FIRST_BUTTON_RETURN {
+                    DialogResult::Accepted
+                }
+                else {
+                    DialogResult::Cancelled
+                };
+                let _ = sender.send(DialogEvent::Closed {dialog_id, result});
+                SignalToUI::set_ui_signal();
+            }
+        }
+    }
+}
@@ -7,11 +7,14 @@ use crate::{
         MouseUpEvent,
         MouseMoveEvent,
         ScrollEvent,
+        NavigateEvent,
         WindowGeomChangeEvent,
         WindowDragQueryEvent,
         WindowCloseRequestedEvent,
         WindowClosedEvent,
+        AppQuitRequestedEvent,
         TextInputEvent,
+        TextCompositionEvent,
         KeyEvent,
         DragEvent,
         DropEvent,
@@ -34,10 +37,13 @@ pub enum MacosEvent {
     MouseUp(MouseUpEvent),
     MouseMove(MouseMoveEvent),
     Scroll(ScrollEvent),
+    Navigate(NavigateEvent),
     
     WindowDragQuery(WindowDragQueryEvent),
     WindowCloseRequested(WindowCloseRequestedEvent),
+    AppQuitRequested(AppQuitRequestedEvent),
     TextInput(TextInputEvent),
+    TextComposition(TextCompositionEvent),
     Drag(DragEvent),
     Drop(DropEvent),
     DragEnd,
@@ -47,4 +53,5 @@ pub enum MacosEvent {
     TextCut(TextClipboardEvent),
     Timer(TimerEvent),
     MacosMenuCommand(LiveId),
+    AccessibilityRequested,
 }
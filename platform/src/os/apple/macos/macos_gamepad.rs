@@ -0,0 +1,121 @@
+use {
+    crate::{
+        os::apple::{
+            apple_sys::*,
+            apple_util::nsstring_to_string,
+        },
+        event::{GamepadId, GamepadButton, GamepadAxis, GamepadState},
+        cx::Cx,
+    }
+};
+
+#[derive(Clone)]
+struct GcGamepad {
+    gamepad_id: GamepadId,
+    controller: ObjcId,
+}
+
+/// Controllers through the GameController framework, which maps every pad it knows onto
+/// the extended gamepad profile. The controllers are polled on the UI timer.
+#[derive(Default)]
+pub struct MacosGamepads {
+    gamepads: Vec<GcGamepad>,
+    last_id: u64,
+}
+
+unsafe fn button_value(gamepad: ObjcId, button: Sel) -> f32 {
+    // the optional buttons only exist on newer versions of the framework
+    let responds: BOOL = msg_send![gamepad, respondsToSelector: button];
+    if responds == NO {
+        return 0.0
+    }
+    let input: ObjcId = msg_send![gamepad, performSelector: button];
+    if input == nil {
+        return 0.0
+    }
+    let value: f32 = msg_send![input, value];
+    value
+}
+
+unsafe fn extended_gamepad_state(gamepad: ObjcId) -> GamepadState {
+    let mut state = GamepadState::default();
+    let buttons = [
+        (sel!(buttonA), GamepadButton::South),
+        (sel!(buttonB), GamepadButton::East),
+        (sel!(buttonX), GamepadButton::West),
+        (sel!(buttonY), GamepadButton::North),
+        (sel!(leftShoulder), GamepadButton::LeftBumper),
+        (sel!(rightShoulder), GamepadButton::RightBumper),
+        (sel!(leftTrigger), GamepadButton::LeftTrigger),
+        (sel!(rightTrigger), GamepadButton::RightTrigger),
+        (sel!(buttonOptions), GamepadButton::Select),
+        (sel!(buttonMenu), GamepadButton::Start),
+        (sel!(leftThumbstickButton), GamepadButton::LeftStick),
+        (sel!(rightThumbstickButton), GamepadButton::RightStick),
+        (sel!(buttonHome), GamepadButton::Home),
+    ];
+    for (sel, button) in buttons {
+        state.buttons[button.index()] = button_value(gamepad, sel);
+    }
+    let dpad: ObjcId = msg_send![gamepad, dpad];
+    for (sel, button) in [
+        (sel!(up), GamepadButton::DPadUp),
+        (sel!(down), GamepadButton::DPadDown),
+        (sel!(left), GamepadButton::DPadLeft),
+        (sel!(right), GamepadButton::DPadRight),
+    ] {
+        state.buttons[button.index()] = button_value(dpad, sel);
+    }
+    // the framework has up as positive, the standard mapping has it negative
+    for (stick, x, y) in [
+        (sel!(leftThumbstick), GamepadAxis::LeftX, GamepadAxis::LeftY),
+        (sel!(rightThumbstick), GamepadAxis::RightX, GamepadAxis::RightY),
+    ] {
+        let stick: ObjcId = msg_send![gamepad, performSelector: stick];
+        let x_axis: ObjcId = msg_send![stick, xAxis];
+        let y_axis: ObjcId = msg_send![stick, yAxis];
+        let x_value: f32 = msg_send![x_axis, value];
+        let y_value: f32 = msg_send![y_axis, value];
+        state.axes[x.index()] = x_value;
+        state.axes[y.index()] = -y_value;
+    }
+    state
+}
+
+impl Cx {
+    pub(crate) fn handle_macos_gamepads(&mut self, time: f64) {
+        unsafe {
+            let controllers: ObjcId = msg_send![class!(GCController), controllers];
+            let count: usize = msg_send![controllers, count];
+            let mut present = Vec::with_capacity(count);
+            for i in 0..count {
+                let controller: ObjcId = msg_send![controllers, objectAtIndex: i];
+                let gamepad: ObjcId = msg_send![controller, extendedGamepad];
+                if gamepad == nil {
+                    continue
+                }
+                present.push(controller);
+                let gamepad_id = if let Some(g) = self.os.gamepads.gamepads.iter().find( | g | g.controller == controller) {
+                    g.gamepad_id
+                }
+                else {
+                    let _: () = msg_send![controller, retain];
+                    self.os.gamepads.last_id += 1;
+                    let gamepad_id = GamepadId(self.os.gamepads.last_id);
+                    self.os.gamepads.gamepads.push(GcGamepad {gamepad_id, controller});
+                    let name: ObjcId = msg_send![controller, vendorName];
+                    let name = if name != nil {nsstring_to_string(name)} else {"Game Controller".to_string()};
+                    self.gamepad_connected(gamepad_id, &name);
+                    gamepad_id
+                };
+                self.gamepad_update(gamepad_id, extended_gamepad_state(gamepad), time);
+            }
+            let gone: Vec<GcGamepad> = self.os.gamepads.gamepads.iter().filter( | g | !present.contains(&g.controller)).cloned().collect();
+            for gamepad in gone {
+                self.os.gamepads.gamepads.retain( | g | g.controller != gamepad.controller);
+                let _: () = msg_send![gamepad.controller, release];
+                self.gamepad_disconnected(gamepad.gamepad_id);
+            }
+        }
+    }
+}
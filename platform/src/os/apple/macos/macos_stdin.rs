@@ -19,7 +19,6 @@ use {
         texture::{Texture, TextureFormat},
         thread::SignalToUI,
         os::{
-            url_session::{make_http_request},
             apple_sys::*,
             metal_xpc::{
                 xpc_service_proxy,
@@ -163,6 +162,9 @@ impl Cx {
                 HostToStdin::TextInput(e) => {
                     self.call_event_handler(&Event::TextInput(e));
                 }
+                HostToStdin::TextComposition(e) => {
+                    self.call_event_handler(&Event::TextComposition(e));
+                }
                 HostToStdin::MouseDown(e) => {
                     self.fingers.process_tap_count(
                         dvec2(e.x, e.y),
@@ -217,12 +219,7 @@ impl Cx {
                             new_geom,
                             old_geom
                         };
-                        if re.old_geom.dpi_factor != re.new_geom.dpi_factor || re.old_geom.inner_size != re.new_geom.inner_size {
-                            if let Some(main_pass_id) = self.windows[re.window_id].main_pass_id {
-                                self.redraw_pass_and_child_passes(main_pass_id);
-                            }
-                        }
-                        self.call_event_handler(&Event::WindowGeomChange(re));
+                        self.call_window_geom_change_event(re);
                     }        
                 }
                 HostToStdin::Swapchain(new_swapchain) => {
@@ -269,6 +266,8 @@ impl Cx {
                     }
                     if SignalToUI::check_and_clear_ui_signal() {
                         self.handle_media_signals();
+                        self.handle_web_socket_events();
+                        self.handle_dialog_events();
                         self.call_event_handler(&Event::Signal);
                     }
                     for event in self.os.stdin_timers.get_dispatch() {
@@ -412,7 +411,13 @@ impl Cx {
                     self.os.stdin_timers.timers.remove(&timer_id);
                 },
                 CxOsOp::HttpRequest {request_id, request} => {
-                    make_http_request(request_id, request, self.os.network_response.sender.clone());
+                    self.os.http_requests.request(request_id, request);
+                },
+                CxOsOp::CancelHttpRequest {request_id} => {
+                    self.os.http_requests.cancel(request_id);
+                },
+                CxOsOp::ShowDialog(dialog_id, dialog) => {
+                    self.show_dialog_fallback(dialog_id, dialog);
                 },
                 _ => ()
                 /*
@@ -2,13 +2,17 @@ use {
     std::{
         rc::Rc,
         cell::Cell,
+        collections::HashMap,
         os::raw::{c_void}
     },
     crate::{
+        makepad_live_id::LiveId,
         makepad_math::{
             DVec2,
+            Rect,
         },
         window::WindowId,
+        accessibility::{AccessElement, AccessNotification},
         os::{
             apple::apple_sys::*,
             apple::apple_util::{
@@ -24,6 +28,8 @@ use {
         area::Area,
         event::{
             ScrollEvent,
+            NavigateEvent,
+            NavigateDirection,
             MouseUpEvent,
             MouseDownEvent,
             MouseMoveEvent,
@@ -35,6 +41,7 @@ use {
             WindowCloseRequestedEvent,
             WindowClosedEvent,
             TextInputEvent,
+            TextCompositionEvent,
             DragItem,
         },
     }
@@ -51,6 +58,13 @@ pub struct MacosWindow {
     window_delegate: ObjcId,
     live_resize_timer: ObjcId,
     last_window_geom: Option<WindowGeom>,
+    /// The NSAccessibilityElements that stand for the accessibility tree of this window.
+    pub(crate) access_elements: HashMap<LiveId, ObjcId>,
+    /// The ids of the elements in drawing order, and the ones at the top of the tree.
+    pub(crate) access_order: Vec<LiveId>,
+    pub(crate) access_roots: Vec<LiveId>,
+    pub(crate) access_focus: Option<LiveId>,
+    pub(crate) access_requested: bool,
 }
 
 impl MacosWindow {
@@ -75,6 +89,11 @@ impl MacosWindow {
                 last_window_geom: None,
                 ime_spot: DVec2::default(),
                 last_mouse_pos: DVec2::default(),
+                access_elements: HashMap::new(),
+                access_order: Vec::new(),
+                access_roots: Vec::new(),
+                access_focus: None,
+                access_requested: false,
             }
         }
     }
@@ -245,6 +264,90 @@ impl MacosWindow {
         }
     }
     
+    /// Mirrors the accessibility elements of this window into NSAccessibilityElements,
+    /// reusing the ones that were made before so VoiceOver keeps its place.
+    pub fn update_accessibility(&mut self, elements: &[AccessElement]) {
+        let elements: Vec<&AccessElement> = elements.iter().filter( | e | e.window_id == self.window_id).collect();
+        unsafe {
+            self.access_elements.retain( | id, element | {
+                if elements.iter().any( | e | e.id == *id) {
+                    return true
+                }
+                let () = msg_send![*element, release];
+                false
+            });
+            let view_rect: NSRect = msg_send![self.view, bounds];
+            let window_rect = Rect {pos: DVec2::default(), size: DVec2 {x: view_rect.size.width, y: view_rect.size.height}};
+            for e in &elements {
+                let element = *self.access_elements.entry(e.id).or_insert_with( || {
+                    let element: ObjcId = msg_send![class!(NSAccessibilityElement), new];
+                    element
+                });
+                let role = str_to_nsstring(e.role.to_macos_role());
+                let () = msg_send![element, setAccessibilityRole: role];
+                let () = msg_send![role, release];
+                let label = str_to_nsstring(&e.name);
+                let () = msg_send![element, setAccessibilityLabel: label];
+                let () = msg_send![label, release];
+                if let Some(checked) = e.state.checked {
+                    let value: ObjcId = msg_send![class!(NSNumber), numberWithBool: if checked {YES} else {NO}];
+                    let () = msg_send![element, setAccessibilityValue: value];
+                }
+                else {
+                    let value = str_to_nsstring(&e.value);
+                    let () = msg_send![element, setAccessibilityValue: value];
+                    let () = msg_send![value, release];
+                }
+                let () = msg_send![element, setAccessibilityEnabled: if e.state.disabled {NO} else {YES}];
+                let () = msg_send![element, setAccessibilitySelected: if e.state.selected {YES} else {NO}];
+                let () = msg_send![element, setAccessibilityFocused: if e.state.focused {YES} else {NO}];
+                
+                // frames are relative to the parent, with the origin at the bottom left
+                let parent_rect = e.parent.and_then( | id | elements.iter().find( | p | p.id == id)).map( | p | p.rect).unwrap_or(window_rect);
+                let frame = NSRect {
+                    origin: NSPoint {
+                        x: e.rect.pos.x - parent_rect.pos.x,
+                        y: (parent_rect.pos.y + parent_rect.size.y) - (e.rect.pos.y + e.rect.size.y)
+                    },
+                    size: NSSize {width: e.rect.size.x, height: e.rect.size.y}
+                };
+                let () = msg_send![element, setAccessibilityFrameInParentSpace: frame];
+            }
+            // now that every element exists, link them up
+            for e in &elements {
+                let element = self.access_elements[&e.id];
+                let parent = e.parent.and_then( | id | self.access_elements.get(&id).cloned()).unwrap_or(self.view);
+                let () = msg_send![element, setAccessibilityParent: parent];
+                let children: Vec<ObjcId> = elements.iter()
+                    .filter( | c | c.parent == Some(e.id))
+                    .map( | c | self.access_elements[&c.id])
+                    .collect();
+                let children: ObjcId = msg_send![class!(NSArray), arrayWithObjects: children.as_ptr() count: children.len()];
+                let () = msg_send![element, setAccessibilityChildren: children];
+            }
+        }
+        self.access_order = elements.iter().map( | e | e.id).collect();
+        self.access_roots = elements.iter()
+            .filter( | e | e.parent.map_or(true, | id | !self.access_elements.contains_key(&id)))
+            .map( | e | e.id)
+            .collect();
+        self.access_focus = elements.iter().find( | e | e.state.focused).map( | e | e.id);
+    }
+    
+    pub fn post_accessibility_notification(&self, notification: AccessNotification) {
+        let (id, name) = match notification {
+            AccessNotification::FocusChanged(id) => (id, "AXFocusedUIElementChanged"),
+            AccessNotification::ValueChanged(id) => (id, "AXValueChanged"),
+        };
+        if let Some(element) = self.access_elements.get(&id) {
+            unsafe {
+                let name = str_to_nsstring(name);
+                NSAccessibilityPostNotification(*element, name);
+                let () = msg_send![name, release];
+            }
+        }
+    }
+    
     pub fn do_callback(&mut self, event: MacosEvent) {
         MacosApp::do_callback(event);
     }
@@ -357,6 +460,15 @@ impl MacosWindow {
         }));
     }
     
+    pub fn send_navigate(&mut self, direction: NavigateDirection) {
+        self.do_callback(MacosEvent::Navigate(NavigateEvent {
+            window_id: self.window_id,
+            direction,
+            abs: self.last_mouse_pos,
+            time: self.time_now()
+        }));
+    }
+    
     pub fn send_mouse_up(&mut self, button: usize, modifiers: KeyModifiers) {
         self.do_callback(MacosEvent::MouseUp(MouseUpEvent {
             button,
@@ -424,6 +536,10 @@ impl MacosWindow {
         }))
     }
     
+    pub fn send_text_composition(&mut self, event: TextCompositionEvent) {
+        self.do_callback(MacosEvent::TextComposition(event))
+    }
+    
     #[cfg(target_os = "macos")]
     pub fn start_dragging(&mut self,items: Vec<DragItem>) {
         let ns_event: ObjcId =  unsafe{
@@ -5,5 +5,7 @@ pub mod macos_app;
 pub mod macos_delegates;
 pub mod macos_event;
 pub mod macos_window;
+pub mod macos_gamepad;
+pub mod macos_dialogs;
 pub use self::macos::*;
 //pub use self::macos_stdin::*;
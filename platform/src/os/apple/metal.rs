@@ -105,6 +105,11 @@ impl Cx {
                 draw_call.draw_uniforms.set_zbias(*zbias);
                 *zbias += zbias_step;
                 
+                // the color space of the first texture, shaders convert its samples to sRGB with it
+                let texture_color_space = draw_call.texture_slots[0].as_ref()
+                    .map( | texture | self.textures[texture.texture_id()].color_space).unwrap_or_default();
+                draw_call.draw_uniforms.set_texture_color_space(texture_color_space);
+                
                 if draw_call.uniforms_dirty {
                     draw_call.uniforms_dirty = false;
                 }
@@ -260,6 +265,7 @@ impl Cx {
         metal_cx: &mut MetalCx,
         mode: DrawPassMode,
     ) {
+        self.skip_read_back(pass_id);
         let draw_list_id = if let Some(draw_list_id) = self.passes[pass_id].main_draw_list_id{
             draw_list_id
         }
@@ -294,11 +300,13 @@ impl Cx {
         }
         
         self.passes[pass_id].set_dpi_factor(dpi_factor);
+        let color_space = self.get_delegated_color_space(pass_id);
+        self.passes[pass_id].set_color_space_uniform(color_space);
         
         if let DrawPassMode::MTKView(_) = mode{
             let color_attachments:ObjcId = unsafe{msg_send![render_pass_descriptor, colorAttachments]};
             let color_attachment:ObjcId = unsafe{msg_send![color_attachments, objectAtIndexedSubscript: 0]};
-            let color = self.passes[pass_id].clear_color;
+            let color = self.get_delegated_clear_color(pass_id, self.passes[pass_id].clear_color);
             unsafe {
                 let () = msg_send![color_attachment, setLoadAction: MTLLoadAction::Clear];
                 let () = msg_send![color_attachment, setClearColor: MTLClearColor {
@@ -318,7 +326,7 @@ impl Cx {
                 color_attachment,
                 setTexture: first_texture
             ]};
-            let color = self.passes[pass_id].clear_color;
+            let color = self.get_delegated_clear_color(pass_id, self.passes[pass_id].clear_color);
             unsafe {
                 let () = msg_send![color_attachment, setLoadAction: MTLLoadAction::Clear];
                 let () = msg_send![color_attachment, setClearColor: MTLClearColor {
@@ -354,6 +362,7 @@ impl Cx {
                 unsafe {msg_send![color_attachment, setStoreAction: MTLStoreAction::Store]}
                 match color_texture.clear_color {
                     PassClearColor::InitWith(color) => {
+                        let color = self.get_delegated_clear_color(pass_id, color);
                         if is_initial {
                             unsafe {
                                 let () = msg_send![color_attachment, setLoadAction: MTLLoadAction::Clear];
@@ -370,6 +379,7 @@ impl Cx {
                         }
                     },
                     PassClearColor::ClearWith(color) => {
+                        let color = self.get_delegated_clear_color(pass_id, color);
                         unsafe {
                             let () = msg_send![color_attachment, setLoadAction: MTLLoadAction::Clear];
                             let () = msg_send![color_attachment, setClearColor: MTLClearColor {
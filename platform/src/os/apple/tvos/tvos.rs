@@ -18,18 +18,19 @@ use {
                     tvos_event::TvosEvent,
                     tvos_app::{TvosApp, init_tvos_app_global,get_tvos_app_global}
                 },
-                url_session::{make_http_request},
+                url_session::AppleHttpRequests,
             },
             apple_classes::init_apple_classes_global,
             apple_media::CxAppleMedia,
             metal::{MetalCx, DrawPassMode},
         },
         pass::{CxPassParent},
+        color_space::ColorSpace,
         thread::SignalToUI,
         window::CxWindowPool,
         event::{
             Event,
-            NetworkResponseChannel
+            ClipboardReadEvent
         },
         cx_api::{CxOsApi, CxOsOp},
         cx::{Cx, OsType},
@@ -93,12 +94,9 @@ impl Cx {
     }
 
     pub(crate) fn handle_networking_events(&mut self) {
-        let mut out = Vec::new();
-        while let Ok(event) = self.os.network_response.receiver.try_recv(){
-            out.push(event);
-        }
-        if out.len()>0{
-            self.call_event_handler(&Event::NetworkResponses(out))
+        let responses = self.os.http_requests.responses();
+        if responses.len() > 0 {
+            self.call_network_responses(responses);
         }
     }
     
@@ -143,6 +141,8 @@ impl Cx {
                 if te.timer_id == 0 {
                    if SignalToUI::check_and_clear_ui_signal(){
                         self.handle_media_signals();
+                        self.handle_web_socket_events();
+                        self.handle_dialog_events();
                         self.call_event_handler(&Event::Signal);
                     }
                     if self.handle_live_edit(){
@@ -229,6 +229,10 @@ impl Cx {
                 CxOsOp::SetTopmost(_window_id, _is_topmost) => {
                     todo!()
                 }
+                CxOsOp::SetWindowColorSpace(window_id, _color_space) => {
+                    // the surface is always presented as sRGB, so that's what the window draws in
+                    self.windows[window_id].color_space = ColorSpace::Srgb;
+                }
                 CxOsOp::XrStartPresenting => {
                     //todo!()
                 },
@@ -254,7 +258,10 @@ impl Cx {
                 CxOsOp::UpdateMacosMenu(_menu) => {
                 },
                 CxOsOp::HttpRequest{request_id, request} => {
-                    make_http_request(request_id, request, self.os.network_response.sender.clone());
+                    self.os.http_requests.request(request_id, request);
+                },
+                CxOsOp::CancelHttpRequest{request_id} => {
+                    self.os.http_requests.cancel(request_id);
                 },
                 CxOsOp::ShowClipboardActions(_request) => {
                     crate::log!("Show clipboard actions not supported yet");
@@ -262,6 +269,14 @@ impl Cx {
                 CxOsOp::CopyToClipboard(_request) => {
                     crate::error!("Clipboard actions not yet implemented for tvOS");
                 }
+                CxOsOp::WriteClipboard(_flavors) => {
+                    crate::error!("Clipboard actions not yet implemented for tvOS");
+                }
+                CxOsOp::ReadClipboard(request_id) => {
+                    crate::error!("Clipboard actions not yet implemented for tvOS");
+                    self.call_event_handler(&Event::ClipboardRead(ClipboardReadEvent {request_id, flavors: Vec::new()}));
+                }
+                CxOsOp::GamepadRumble {..} => {}
                 CxOsOp::PrepareVideoPlayback(_, _, _, _, _) => todo!(),
                 CxOsOp::BeginVideoPlayback(_) => todo!(),
                 CxOsOp::PauseVideoPlayback(_) => todo!(),
@@ -271,10 +286,9 @@ impl Cx {
                 CxOsOp::CleanupVideoPlaybackResources(_) => todo!(),
                 CxOsOp::UpdateVideoSurfaceTexture(_) => todo!(),
 
-                CxOsOp::SaveFileDialog(_) => todo!(),
-                CxOsOp::SelectFileDialog(_) => todo!(),
-                CxOsOp::SaveFolderDialog(_) => todo!(),
-                CxOsOp::SelectFolderDialog(_) => todo!(),
+                CxOsOp::ShowDialog(dialog_id, dialog) => {
+                    self.show_dialog_fallback(dialog_id, dialog);
+                },
                 
             }
         }
@@ -329,6 +343,6 @@ pub struct CxOs {
     pub (crate) media: CxAppleMedia,
     pub (crate) bytes_written: usize,
     pub (crate) draw_calls_done: usize,
-    pub (crate) network_response: NetworkResponseChannel,
+    pub (crate) http_requests: AppleHttpRequests,
 }
 
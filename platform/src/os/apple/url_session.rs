@@ -2,6 +2,7 @@
 use {
     std::{
         ptr,
+        collections::HashMap,
         sync::mpsc::{Sender},
         sync::Arc,
     },
@@ -21,6 +22,7 @@ use {
         web_socket::WebSocketMessage,
         event::{
             NetworkResponseItem,
+            NetworkResponseChannel,
             NetworkResponse,
             HttpRequest,
             HttpResponse
@@ -30,15 +32,28 @@ use {
 
 pub fn define_web_socket_delegate() -> *const Class {
     
-    extern fn did_open_with_protocol(_codethis: &Object, _: Sel, _web_socket_task: ObjcId, _open_with_protocol: ObjcId) {
-        crate::log!("DID OPEN WITH PROTOCOL");
-        //let sender_box: u64 = *this.get_ivar("sender_box");
-        // TODO send Open and Close websocket messages
-        // lets turn t
+    extern fn did_open_with_protocol(this: &Object, _: Sel, _web_socket_task: ObjcId, _open_with_protocol: ObjcId) {
+        unsafe {
+            let sender_box: u64 = *this.get_ivar("sender_box");
+            if sender_box != 0 {
+                let sender = &*(sender_box as *const Sender<WebSocketMessage>);
+                let _ = sender.send(WebSocketMessage::Opened);
+                SignalToUI::set_ui_signal();
+            }
+        }
     }
     
-    extern fn did_close_with_code(_this: &Object, _: Sel, _web_socket_task: ObjcId, _code: usize, _reason: ObjcId) {
-        crate::log!("DID CLOSE WITH CODE");
+    extern fn did_close_with_code(this: &Object, _: Sel, _web_socket_task: ObjcId, _code: usize, _reason: ObjcId) {
+        unsafe {
+            let sender_box: u64 = *this.get_ivar("sender_box");
+            if sender_box != 0 {
+                // the task doesn't call its delegate after closing
+                let sender = Box::from_raw(sender_box as *mut Sender<WebSocketMessage>);
+                let _ = sender.send(WebSocketMessage::Closed);
+                SignalToUI::set_ui_signal();
+                (*(this as *const Object as *mut Object)).set_ivar("sender_box", 0u64);
+            }
+        }
     }
     
     let superclass = class!(NSObject);
@@ -124,8 +139,8 @@ impl OsWebSocket{
             let handler = objc_block!(move | error: ObjcId | {
                 if error != ptr::null_mut() {
                     let error_str: String = nsstring_to_string(msg_send![error, localizedDescription]);
-                    println!("WEBSOCKET ERROR {}", error_str);
-                    rx_sender.send(WebSocketMessage::Error(error_str)).unwrap();
+                    let _ = rx_sender.send(WebSocketMessage::Error(error_str));
+                    SignalToUI::set_ui_signal();
                 }
             });
                        
@@ -168,6 +183,8 @@ impl OsWebSocket{
             //let session  = self.ns_url_session.unwrap();
             let data_task: ObjcId = msg_send![session, webSocketTaskWithRequest: ns_request];
             let web_socket_delegate_instance: ObjcId = msg_send![get_apple_class_global().web_socket_delegate, new];
+            let sender_box = Box::into_raw(Box::new(rx_sender.clone())) as u64;
+            (*web_socket_delegate_instance).set_ivar("sender_box", sender_box);
             
             let () = msg_send![data_task, setMaximumMessageSize:5*1024*1024];
              
@@ -176,7 +193,7 @@ impl OsWebSocket{
                 let handler = objc_block!(move | message: ObjcId, error: ObjcId | {
                     if error != ptr::null_mut() {
                         let error_str: String = nsstring_to_string(msg_send![error, localizedDescription]);
-                        rx_sender.send(WebSocketMessage::Error(error_str)).unwrap();
+                        let _ = rx_sender.send(WebSocketMessage::Error(error_str));
                         SignalToUI::set_ui_signal();
                         return;
                     }
@@ -187,13 +204,13 @@ impl OsWebSocket{
                         let length: usize = msg_send![data, length];
                         let data_bytes: &[u8] = std::slice::from_raw_parts(bytes, length);
                         let message = WebSocketMessage::Binary(data_bytes.to_vec());
-                        rx_sender.send(message).unwrap();
+                        let _ = rx_sender.send(message);
                         SignalToUI::set_ui_signal();
                     }
                     else { // string
                         let string: ObjcId = msg_send![message, string];
                         let message = WebSocketMessage::String(nsstring_to_string(string));
-                        rx_sender.send(message).unwrap();
+                        let _ = rx_sender.send(message);
                         SignalToUI::set_ui_signal();
                     }
                     set_message_receive_handler(data_task.clone(), rx_sender.clone())
@@ -214,7 +231,44 @@ impl OsWebSocket{
 }
 
 
-pub fn make_http_request(request_id: LiveId, request: HttpRequest, networking_sender: Sender<NetworkResponseItem>) {
+/// Keeps the running data tasks around so a request can be cancelled.
+#[derive(Default)]
+pub struct AppleHttpRequests {
+    responses: NetworkResponseChannel,
+    tasks: HashMap<LiveId, ObjcId>,
+}
+
+impl AppleHttpRequests {
+    pub fn request(&mut self, request_id: LiveId, request: HttpRequest) {
+        let task = make_http_request(request_id, request, self.responses.sender.clone());
+        if let Some(task) = self.tasks.insert(request_id, task) {
+            unsafe {let () = msg_send![task, release];}
+        }
+    }
+    
+    pub fn cancel(&mut self, request_id: LiveId) {
+        if let Some(task) = self.tasks.remove(&request_id) {
+            unsafe {
+                let () = msg_send![task, cancel];
+                let () = msg_send![task, release];
+            }
+        }
+    }
+    
+    pub fn responses(&mut self) -> Vec<NetworkResponseItem> {
+        let responses: Vec<NetworkResponseItem> = self.responses.receiver.try_iter().collect();
+        for item in &responses {
+            if let NetworkResponse::HttpResponse(_) | NetworkResponse::HttpRequestError(_) = item.response {
+                if let Some(task) = self.tasks.remove(&item.request_id) {
+                    unsafe {let () = msg_send![task, release];}
+                }
+            }
+        }
+        responses
+    }
+}
+
+fn make_http_request(request_id: LiveId, request: HttpRequest, networking_sender: Sender<NetworkResponseItem>) -> ObjcId {
     unsafe {
         let ignore_ssl_cert = request.ignore_ssl_cert;
         let ns_request = make_ns_request(&request);
@@ -271,8 +325,10 @@ pub fn make_http_request(request_id: LiveId, request: HttpRequest, networking_se
         };
                 
         let data_task: ObjcId = msg_send![session, dataTaskWithRequest: ns_request completionHandler: &response_handler];
+        let () = msg_send![data_task, retain];
                 
         // Run the request task
         let () = msg_send![data_task, resume];
+        data_task
     }
 }
\ No newline at end of file
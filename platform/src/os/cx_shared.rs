@@ -15,6 +15,9 @@ use {
             Event,
             KeyFocusEvent,
             NextFrameEvent,
+            PassReadBackEvent,
+            WindowGeomChangeEvent,
+            DpiChangedEvent,
         },
         studio::{AppToStudio,EventSample},
     }
@@ -33,6 +36,21 @@ impl Cx {
         }
     }
     
+    /// Answers a pending read back with no pixels, for passes drawn where the backend can't read
+    /// back yet, so whoever asked isn't left waiting.
+    #[allow(dead_code)]
+    pub (crate) fn skip_read_back(&mut self, pass_id: PassId) {
+        if let Some(rect) = self.passes[pass_id].read_back_rect.take() {
+            self.call_event_handler(&Event::PassReadBack(PassReadBackEvent {
+                pass_id,
+                rect,
+                width: 0,
+                height: 0,
+                pixels: Vec::new(),
+            }));
+        }
+    }
+    
     pub (crate) fn any_passes_dirty(&self) -> bool {
         for pass_id in self.passes.id_iter() {
             if self.passes[pass_id].paint_dirty {
@@ -112,6 +130,9 @@ impl Cx {
     
     pub (crate) fn inner_call_event_handler(&mut self, event: &Event) {
         self.event_id += 1;
+        if self.event_trace.is_enabled(){
+            self.event_trace.record(self, event);
+        }
         if Cx::has_studio_web_socket(){
             let start = self.seconds_since_app_start();
             let mut event_handler = self.event_handler.take().unwrap();
@@ -181,6 +202,27 @@ impl Cx {
         self.handle_triggers();
         self.handle_actions();
     }
+    
+    /// Sends a window geometry change. A window that got another size or scale is redrawn, and
+    /// a change of scale is followed by a `DpiChanged` for the window.
+    #[allow(dead_code)]
+    pub (crate) fn call_window_geom_change_event(&mut self, re: WindowGeomChangeEvent) {
+        let window_id = re.window_id;
+        let old_dpi_factor = re.old_geom.dpi_factor;
+        let new_dpi_factor = re.new_geom.dpi_factor;
+        if old_dpi_factor != new_dpi_factor || re.old_geom.inner_size != re.new_geom.inner_size {
+            self.redraw_window(window_id);
+        }
+        self.call_event_handler(&Event::WindowGeomChange(re));
+        // the first geometry of a window isn't a change of scale
+        if old_dpi_factor != new_dpi_factor && old_dpi_factor > 0.0 {
+            self.call_event_handler(&Event::DpiChanged(DpiChangedEvent {
+                window_id,
+                old_dpi_factor,
+                new_dpi_factor,
+            }));
+        }
+    }
 
     // helpers
     
@@ -196,6 +238,7 @@ impl Cx {
         let mut draw_event = DrawEvent::default();
         std::mem::swap(&mut draw_event, &mut self.new_draw_event);
         self.call_event_handler(&Event::Draw(draw_event));
+        self.update_accessibility();
     }
 
     pub (crate) fn call_next_frame_event(&mut self, time: f64) {
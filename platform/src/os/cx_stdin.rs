@@ -13,6 +13,7 @@ use {
             KeyModifiers,
             Event,
             TextInputEvent,
+            TextCompositionEvent,
             TimerEvent,
             KeyEvent,
             ScrollEvent,
@@ -462,6 +463,7 @@ pub enum HostToStdin{
     KeyDown(KeyEvent),
     KeyUp(KeyEvent),
     TextInput(TextInputEvent),
+    TextComposition(TextCompositionEvent),
     Scroll(StdinScroll),
     /*ReloadFile{
         file:String,
@@ -20,7 +20,7 @@ use {
 #[derive(Clone)]
 pub struct OsMidiOutput(pub (crate) Arc<Mutex<AlsaMidiAccess >>);
 
-pub struct OsMidiInput(mpsc::Receiver<(MidiPortId, MidiData) >);
+pub struct OsMidiInput(mpsc::Receiver<MidiInputEvent>);
 
 impl OsMidiOutput {
     pub fn send(&self, port_id: Option<MidiPortId>, d: MidiData) {
@@ -31,15 +31,12 @@ impl OsMidiOutput {
 }
 
 impl OsMidiInput {
-    pub fn receive(&mut self) -> Option<(MidiPortId, MidiData)> {
-        if let Ok((port_id, data)) = self.0.try_recv() {
-            return Some((port_id, data))
-        }
-        None
+    pub fn receive(&mut self) -> Option<MidiInputEvent> {
+        self.0.try_recv().ok()
     }
 }
 
-type InputSenders = Arc<Mutex<Vec<mpsc::Sender<(MidiPortId, MidiData) >> >>;
+type InputSenders = Arc<Mutex<Vec<mpsc::Sender<MidiInputEvent> >> >;
 
 #[derive(Clone)]
 pub struct AlsaMidiOutput {
@@ -352,14 +349,15 @@ impl AlsaMidiAccess {
                         None
                     }
                 };
-                if let Some(msg) = msg {
+                if let Some(data) = msg {
                     if let Some(port_id) = midi_access_clone.lock().unwrap().find_port(
                         (*ev).source.client as i32,
                         (*ev).source.port as i32
                     ) {
+                        let event = MidiInputEvent {port_id, data, time: midi_time()};
                         let mut senders = input_senders.lock().unwrap();
                         senders.retain( | s | {
-                            s.send((port_id, msg)).is_ok()
+                            s.send(event).is_ok()
                         });
                         if senders.len()>0 {
                             // make sure our eventloop runs
@@ -23,6 +23,7 @@ use {
     crate::{
         cx_api::{CxOsOp, CxOsApi},
         cx_stdin::{PollTimers,PollTimer},
+        color_space::ColorSpace,
         makepad_math::*,
         makepad_live_id::*,
         makepad_live_compiler::LiveFileChange,
@@ -177,7 +178,8 @@ impl Cx {
                                 if makepad_keycode == KeyCode::KeyC {
                                     let response = Rc::new(RefCell::new(None));
                                     e = Event::TextCopy(TextClipboardEvent {
-                                        response: response.clone()
+                                        response: response.clone(),
+                                        rich_response: Rc::new(RefCell::new(None))
                                     });
                                     self.call_event_handler(&e);
                                     // let response = response.borrow();
@@ -187,7 +189,8 @@ impl Cx {
                                 } else if makepad_keycode == KeyCode::KeyX {
                                     let response = Rc::new(RefCell::new(None));
                                     let e = Event::TextCut(TextClipboardEvent {
-                                        response: response.clone()
+                                        response: response.clone(),
+                                        rich_response: Rc::new(RefCell::new(None))
                                     });
                                     self.call_event_handler(&e);
                                     // let response = response.borrow();
@@ -250,7 +253,7 @@ impl Cx {
                         }
                     }
                     FromJavaMessage::HttpResponse {request_id, metadata_id, status_code, headers, body} => {
-                        let e = Event::NetworkResponses(vec![
+                        self.call_network_responses(vec![
                             NetworkResponseItem {
                                 request_id: LiveId(request_id),
                                 response: NetworkResponse::HttpResponse(HttpResponse::new(
@@ -261,16 +264,14 @@ impl Cx {
                                 ))
                             }
                         ]);
-                        self.call_event_handler(&e);
                     }
                     FromJavaMessage::HttpRequestError {request_id, error, ..} => {
-                        let e = Event::NetworkResponses(vec![
+                        self.call_network_responses(vec![
                             NetworkResponseItem {
                                 request_id: LiveId(request_id),
                                 response: NetworkResponse::HttpRequestError(error)
                             }
                         ]);
-                        self.call_event_handler(&e);
                     }
                     FromJavaMessage::WebSocketMessage {message, sender} => {
                         let mut ws_message_parser = WebSocketImpl::new();
@@ -278,11 +279,11 @@ impl Cx {
                             match result {
                                 Ok(WebSocketMessageImpl::Text(text_msg)) => {
                                     let message = WebSocketMessage::String(text_msg.to_string());
-                                    sender.send(message).unwrap();
+                                    let _ = sender.send(message);
                                 },
                                 Ok(WebSocketMessageImpl::Binary(data)) => {
                                     let message = WebSocketMessage::Binary(data.to_vec());
-                                    sender.send(message).unwrap();
+                                    let _ = sender.send(message);
                                 },
                                 Err(e) => {
                                     println!("Websocket message parse error {:?}", e);
@@ -290,14 +291,17 @@ impl Cx {
                                 _ => ()
                             }
                         });
+                        SignalToUI::set_ui_signal();
                     }
                     FromJavaMessage::WebSocketClosed {sender} => {
                         let message = WebSocketMessage::Closed;
-                        sender.send(message).unwrap();
+                        let _ = sender.send(message);
+                        SignalToUI::set_ui_signal();
                     }
                     FromJavaMessage::WebSocketError {error, sender} => {
                         let message = WebSocketMessage::Error(error);
-                        sender.send(message).unwrap();
+                        let _ = sender.send(message);
+                        SignalToUI::set_ui_signal();
                     }
                     FromJavaMessage::MidiDeviceOpened {name, midi_device} => {
                         self.os.media.android_midi().lock().unwrap().midi_device_opened(name, midi_device);
@@ -386,8 +390,12 @@ impl Cx {
 
             if SignalToUI::check_and_clear_ui_signal() {
                 self.handle_media_signals();
+                self.handle_web_socket_events();
+                self.handle_dialog_events();
                 self.call_event_handler(&Event::Signal);
             }
+            // AMidi has no callbacks, the inputs are read every time around
+            self.handle_midi_input_events();
 
             let to_dispatch = self.get_video_updates();
             for video_id in to_dispatch {
@@ -577,7 +585,8 @@ impl Cx {
     pub fn from_java_on_cut_to_clipboard(&mut self, to_java: AndroidToJava) {
         let e = Event::TextCut(
             TextClipboardEvent {
-                response: Rc::new(RefCell::new(None))
+                response: Rc::new(RefCell::new(None)),
+                rich_response: Rc::new(RefCell::new(None))
             }
         );
         self.call_event_handler(&e);
@@ -603,6 +612,7 @@ impl Cx {
         &mut self,
         pass_id: PassId,
     ) {
+        self.skip_read_back(pass_id);
         let draw_list_id = self.passes[pass_id].main_draw_list_id.unwrap();
 
         self.setup_render_pass(pass_id);
@@ -739,6 +749,12 @@ impl Cx {
                 CxOsOp::HttpRequest {request_id, request} => {
                     unsafe {android_jni::to_java_http_request(request_id, request);}
                 },
+                CxOsOp::CancelHttpRequest {..} => {
+                    // the java side can't abort a request, its response is dropped when it arrives
+                },
+                CxOsOp::ShowDialog(dialog_id, dialog) => {
+                    self.show_dialog_fallback(dialog_id, dialog);
+                },
                 CxOsOp::PrepareVideoPlayback(video_id, source, external_texture_id, autoplay, should_loop) => {
                     unsafe {
                         let env = attach_jni_env();
@@ -781,6 +797,10 @@ impl Cx {
                         android_jni::to_java_cleanup_video_playback_resources(env, video_id);
                     }
                 },
+                CxOsOp::SetWindowColorSpace(window_id, _color_space) => {
+                    // the surface is always presented as sRGB, so that's what the window draws in
+                    self.windows[window_id].color_space = ColorSpace::Srgb;
+                },
                 _ => ()
             }
         }
@@ -106,6 +106,7 @@ impl CxMediaApi for Cx {
     
     fn use_midi_inputs(&mut self, ports: &[MidiPortId]) {
        self.os.media.android_midi().lock().unwrap().use_midi_inputs(ports);
+       self.enable_midi_input_events();
     }
     
     fn use_midi_outputs(&mut self, ports: &[MidiPortId]) {
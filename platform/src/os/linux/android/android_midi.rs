@@ -30,17 +30,14 @@ impl OsMidiOutput {
 
 pub struct OsMidiInput {
     amidi: Arc<Mutex<AndroidMidiAccess >>,
-    recv: mpsc::Receiver<(MidiPortId, MidiData) >
+    recv: mpsc::Receiver<MidiInputEvent>
 }
 
 impl OsMidiInput {
-    pub fn receive(&mut self) -> Option<(MidiPortId, MidiData)> {
+    pub fn receive(&mut self) -> Option<MidiInputEvent> {
         let mut amidi = self.amidi.lock().unwrap();
         amidi.read_inputs();
-        if let Ok((port_id, data)) = self.recv.try_recv() {
-            return Some((port_id, data))
-        }
-        None
+        self.recv.try_recv().ok()
     }
 }
 
@@ -111,7 +108,7 @@ pub struct AndroidMidiAccess {
     state: AndroidMidiState,
     change_signal: SignalToUI,
     devices: Vec<AndroidMidiDevicePtr>,
-    senders: Vec<mpsc::Sender<(MidiPortId, MidiData) >>,
+    senders: Vec<mpsc::Sender<MidiInputEvent>>,
     outputs: Vec<AndroidMidiOutput>,
     inputs: Vec<AndroidMidiInput>
 }
@@ -152,10 +149,12 @@ impl AndroidMidiAccess {
                 )};
                 if messages == 1{ 
                     any_messages = true;
+                    let time = midi_time();
                     for i in (0..bytes_recv as usize).step_by(3){
                         let data = MidiData {data:[data[i], data[i+1],data[i+2]]};
+                        let event = MidiInputEvent {port_id: input.port_id, data, time};
                         self.senders.retain( | s | {
-                            s.send((input.port_id, data)).is_ok()
+                            s.send(event).is_ok()
                         });
                     }
                 } 
@@ -23,7 +23,7 @@ impl OsWebSocket{
                 ServerWebSocket::build_message(header, &data.to_string().into_bytes())
             }
             WebSocketMessage::Binary(data)=>{
-                let header = ServerWebSocketMessageHeader::from_len(data.len(), ServerWebSocketMessageFormat::Text, true);
+                let header = ServerWebSocketMessageHeader::from_len(data.len(), ServerWebSocketMessageFormat::Binary, true);
                 ServerWebSocket::build_message(header, &data)
             }
             _=>panic!()
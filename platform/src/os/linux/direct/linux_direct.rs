@@ -9,7 +9,8 @@ use {
     self::super::super::{
         gl_sys,
         select_timer::SelectTimers,
-        linux_media::CxLinuxMedia
+        linux_media::CxLinuxMedia,
+        linux_gamepad::LinuxGamepads,
     },
     crate::{
         cx_api::{CxOsOp, CxOsApi},
@@ -23,9 +24,11 @@ use {
         },
         window::CxWindowPool,
         pass::CxPassParent,
+        color_space::ColorSpace,
         cx::{Cx, OsType,},
         gpu_info::GpuPerformance,
         os::cx_native::EventFlow,
+        os::native_http::NativeHttpRequests,
         pass::{PassClearColor, PassClearDepth, PassId},
     }
 };
@@ -110,6 +113,7 @@ impl Cx {
                     event
                 );
             }
+            cx.handle_linux_gamepads(direct_app.timers.time_now());
             event_flow = cx.direct_event_callback(&mut direct_app, DirectEvent::Paint);
         }
     }
@@ -178,8 +182,11 @@ impl Cx {
                 if e.timer_id == 0 {
                     if Signal::check_and_clear_ui_signal() {
                         self.handle_media_signals();
+                        self.handle_web_socket_events();
+                        self.handle_dialog_events();
                         self.call_event_handler(&Event::Signal);
                     }
+                    self.handle_networking_events();
                 }
                 else {
                     self.call_event_handler(&Event::Timer(e))
@@ -198,6 +205,7 @@ impl Cx {
         pass_id: PassId,
         direct_app: &mut DirectApp,
     ) {
+        self.skip_read_back(pass_id);
         let draw_list_id = self.passes[pass_id].main_draw_list_id.unwrap();
         
         self.setup_render_pass(pass_id);
@@ -219,6 +227,7 @@ impl Cx {
                 PassClearColor::ClearWith(color) => color
             }
         };
+        let clear_color = self.get_delegated_clear_color(pass_id, clear_color);
         let clear_depth = match self.passes[pass_id].clear_depth {
             PassClearDepth::InitWith(depth) => depth,
             PassClearDepth::ClearWith(depth) => depth
@@ -249,6 +258,13 @@ impl Cx {
         }
     }
     
+    pub (crate) fn handle_networking_events(&mut self) {
+        let responses = self.os.http_requests.responses();
+        if responses.len() > 0 {
+            self.call_network_responses(responses);
+        }
+    }
+    
     pub (crate) fn handle_repaint(&mut self, direct_app: &mut DirectApp) {
         //opengl_cx.make_current();
         let mut passes_todo = Vec::new();
@@ -297,6 +313,22 @@ impl Cx {
                 CxOsOp::StopTimer(timer_id) => {
                     direct_app.timers.stop_timer(timer_id);
                 },
+                CxOsOp::GamepadRumble {gamepad_id, strong, weak, duration} => {
+                    self.os.gamepads.rumble(gamepad_id, strong, weak, duration);
+                },
+                CxOsOp::HttpRequest {request_id, request} => {
+                    self.os.http_requests.request(request_id, request);
+                },
+                CxOsOp::CancelHttpRequest {request_id} => {
+                    self.os.http_requests.cancel(request_id);
+                },
+                CxOsOp::ShowDialog(dialog_id, dialog) => {
+                    self.show_dialog_fallback(dialog_id, dialog);
+                },
+                CxOsOp::SetWindowColorSpace(window_id, _color_space) => {
+                    // the framebuffer is always scanned out as sRGB, so that's what the window draws in
+                    self.windows[window_id].color_space = ColorSpace::Srgb;
+                },
                 _ => ()
             }
         }
@@ -319,5 +351,7 @@ impl CxOsApi for Cx {
 #[derive(Default)]
 pub struct CxOs {
     pub (crate) media: CxLinuxMedia,
+    pub (crate) gamepads: LinuxGamepads,
+    pub (crate) http_requests: NativeHttpRequests,
 }
 
@@ -9,6 +9,7 @@ pub use core::ptr::null_mut;
 pub const EGL_NO_CONTEXT: EGLContext = 0 as EGLContext;
 pub const EGL_NO_SURFACE: EGLSurface = 0 as EGLSurface;
 
+pub const EGL_PBUFFER_BIT: u32 = 1;
 pub const EGL_WINDOW_BIT: u32 = 4;
 
 pub const EGL_OPENGL_ES2_BIT: u32 = 4;
@@ -33,6 +34,7 @@ pub const EGL_GL_TEXTURE_2D_KHR: u32 = 12465;
 
 pub const EGL_PLATFORM_X11_EXT: u32 = 12757;
 pub const EGL_PLATFORM_GBM_KHR: u32 = 12759;
+pub const EGL_PLATFORM_SURFACELESS_MESA: u32 = 12765;
 
 pub const EGL_LINUX_DMA_BUF_EXT: u32 = 12912;
 pub const EGL_LINUX_DRM_FOURCC_EXT: u32 = 12913;
@@ -239,7 +241,7 @@ unsafe extern "C" fn(
 ),
 >;
 
-struct Module(::std::ptr::NonNull<::std::os::raw::c_void>);
+pub(crate) struct Module(::std::ptr::NonNull<::std::os::raw::c_void>);
 
 pub struct LibEgl {
     pub eglBindAPI: PFNEGLBINDAPIPROC,
@@ -29,17 +29,22 @@ type size_t = usize;
 
 pub const FD_SETSIZE: usize = 1024;
 pub const EPIPE: c_int = 32;
+pub const O_RDONLY: c_int = 0;
 pub const O_RDWR: c_int = 2;
+pub const O_NONBLOCK: c_int = 0o4000;
+pub const EAGAIN: c_int = 11;
 
 #[repr(C)]
 pub struct fd_set {
     fds_bits: [c_ulong; FD_SETSIZE / ULONG_SIZE],
 }
 
+pub const LC_CTYPE: c_int = 0;
 pub const RTLD_LAZY: c_int = 1;
 pub const RTLD_LOCAL: c_int = 0;
     
 extern "C"{
+    pub fn setlocale(category: c_int, locale: *const c_char) -> *mut c_char;
     pub fn dlopen(filename: *const c_char, flag: c_int) -> *mut c_void;
     pub fn dlclose(handle: *mut c_void) -> c_int;
     pub fn dlsym(handle: *mut c_void, symbol: *const c_char) -> *mut c_void;
@@ -55,6 +60,8 @@ extern "C"{
         timeout: *mut timeval,
     ) -> c_int;
     pub fn read(fd: c_int, buf: *mut c_void, count: size_t) -> c_int;
+    pub fn write(fd: c_int, buf: *const c_void, count: size_t) -> c_int;
+    pub fn ioctl(fd: c_int, request: c_ulong, ...) -> c_int;
 }
 
 pub unsafe fn FD_SET(fd: c_int, set: *mut fd_set) -> () {
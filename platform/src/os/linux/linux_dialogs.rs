@@ -0,0 +1,160 @@
+use {
+    std::{
+        env,
+        path::PathBuf,
+        process::Command,
+        sync::mpsc::Sender,
+    },
+    crate::{
+        makepad_live_id::LiveId,
+        thread::SignalToUI,
+        event::DialogEvent,
+        file_dialogs::{Dialog, DialogResult, FileDialog, MessageDialogLevel, MessageDialogButtons},
+    }
+};
+
+fn find_in_path(program: &str) -> bool {
+    env::var_os("PATH").map_or(false, | paths | env::split_paths(&paths).any( | dir | dir.join(program).is_file()))
+}
+
+fn start_path(dialog: &FileDialog) -> Option<PathBuf> {
+    match (&dialog.location, &dialog.filename) {
+        (Some(location), Some(filename)) => Some(location.join(filename)),
+        (Some(location), None) => Some(location.join("")),
+        (None, Some(filename)) => Some(PathBuf::from(filename)),
+        (None, None) => None
+    }
+}
+
+fn zenity_command(dialog: &Dialog) -> Command {
+    let mut cmd = Command::new("zenity");
+    cmd.arg(format!("--title={}", dialog.title()));
+    match dialog {
+        Dialog::OpenFile(d) | Dialog::SaveFile(d) | Dialog::SelectFolder(d) => {
+            cmd.arg("--file-selection");
+            match dialog {
+                Dialog::OpenFile(_) if d.multiple => {
+                    cmd.args(["--multiple", "--separator=\n"]);
+                }
+                Dialog::SaveFile(_) => {
+                    cmd.arg("--save");
+                }
+                Dialog::SelectFolder(_) => {
+                    cmd.arg("--directory");
+                }
+                _ => ()
+            }
+            if let Some(path) = start_path(d) {
+                cmd.arg(format!("--filename={}", path.display()));
+            }
+            if !matches!(dialog, Dialog::SelectFolder(_)) {
+                for filter in &d.filters {
+                    let patterns: Vec<String> = filter.extensions.iter().map( | ext | format!("*.{}", ext)).collect();
+                    cmd.arg(format!("--file-filter={} | {}", filter.description, patterns.join(" ")));
+                }
+            }
+        }
+        Dialog::Message(d) => {
+            match d.buttons {
+                MessageDialogButtons::Ok => {
+                    cmd.arg(match d.level {
+                        MessageDialogLevel::Info => "--info",
+                        MessageDialogLevel::Warning => "--warning",
+                        MessageDialogLevel::Error => "--error",
+                    });
+                }
+                MessageDialogButtons::OkCancel => {
+                    cmd.args(["--question", "--ok-label=OK", "--cancel-label=Cancel"]);
+                }
+                MessageDialogButtons::YesNo => {
+                    cmd.arg("--question");
+                }
+            }
+            cmd.args(["--no-markup", &format!("--text={}", d.message)]);
+        }
+    }
+    cmd
+}
+
+fn kdialog_command(dialog: &Dialog) -> Command {
+    let mut cmd = Command::new("kdialog");
+    cmd.args(["--title", dialog.title()]);
+    match dialog {
+        Dialog::OpenFile(d) | Dialog::SaveFile(d) | Dialog::SelectFolder(d) => {
+            let start = start_path(d).unwrap_or(PathBuf::from("."));
+            match dialog {
+                Dialog::OpenFile(_) => {
+                    cmd.arg("--getopenfilename");
+                    if d.multiple {
+                        cmd.args(["--multiple", "--separate-output"]);
+                    }
+                }
+                Dialog::SaveFile(_) => {
+                    cmd.arg("--getsavefilename");
+                }
+                _ => {
+                    cmd.arg("--getexistingdirectory");
+                }
+            }
+            cmd.arg(start);
+            if !matches!(dialog, Dialog::SelectFolder(_)) && d.filters.len() > 0 {
+                let filters: Vec<String> = d.filters.iter().map( | filter | {
+                    let patterns: Vec<String> = filter.extensions.iter().map( | ext | format!("*.{}", ext)).collect();
+                    format!("{} ({})", filter.description, patterns.join(" "))
+                }).collect();
+                cmd.arg(filters.join("\n"));
+            }
+        }
+        Dialog::Message(d) => {
+            match d.buttons {
+                MessageDialogButtons::Ok => {
+                    cmd.arg(match d.level {
+                        MessageDialogLevel::Info => "--msgbox",
+                        MessageDialogLevel::Warning => "--sorry",
+                        MessageDialogLevel::Error => "--error",
+                    });
+                    cmd.arg(&d.message);
+                }
+                MessageDialogButtons::OkCancel => {
+                    cmd.args(["--yesno", &d.message, "--yes-label", "OK", "--no-label", "Cancel"]);
+                }
+                MessageDialogButtons::YesNo => {
+                    cmd.args(["--yesno", &d.message]);
+                }
+            }
+        }
+    }
+    cmd
+}
+
+/// Shows a dialog with zenity or kdialog, whichever is installed, as a child process waited on
+/// by a thread of its own. Without either of them the dialog is handed back.
+pub fn show_dialog(dialog_id: LiveId, dialog: Dialog, sender: Sender<DialogEvent>) -> Result<(), Dialog> {
+    let mut cmd = if find_in_path("zenity") {
+        zenity_command(&dialog)
+    }
+    else if find_in_path("kdialog") {
+        kdialog_command(&dialog)
+    }
+    else {
+        return Err(dialog)
+    };
+    std::thread::spawn(move || {
+        let result = match cmd.output() {
+            Ok(output) if output.status.success() => {
+                if let Dialog::Message(_) = dialog {
+                    DialogResult::Accepted
+                }
+                else {
+                    let paths: Vec<PathBuf> = String::from_utf8_lossy(&output.stdout).lines()
+                        .filter( | line | !line.is_empty()).map(PathBuf::from).collect();
+                    if paths.len() > 0 {DialogResult::Paths(paths)} else {DialogResult::Cancelled}
+                }
+            }
+            _ => DialogResult::Cancelled
+        };
+        let _ = sender.send(DialogEvent::Closed {dialog_id, result});
+        SignalToUI::set_ui_signal();
+    });
+    Ok(())
+}
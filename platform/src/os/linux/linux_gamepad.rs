@@ -0,0 +1,347 @@
+use {
+    std::{
+        ffi::CString,
+        os::raw::{c_int, c_ulong, c_void},
+        collections::HashSet,
+    },
+    self::super::libc_sys,
+    crate::{
+        event::{GamepadId, GamepadButton, GamepadAxis, GamepadState},
+        cx::Cx,
+    }
+};
+
+const EV_KEY: u16 = 0x01;
+const EV_ABS: u16 = 0x03;
+const EV_FF: u16 = 0x15;
+
+const BTN_GAMEPAD: u16 = 0x130;
+const KEY_CNT: usize = 0x300;
+const ABS_CNT: usize = 0x40;
+const FF_RUMBLE: u16 = 0x50;
+
+// the evdev codes of the standard mapping, the kernel already names the face buttons by position
+const BUTTON_CODES: [(u16, GamepadButton); 17] = [
+    (0x130, GamepadButton::South),
+    (0x131, GamepadButton::East),
+    (0x134, GamepadButton::West),
+    (0x133, GamepadButton::North),
+    (0x136, GamepadButton::LeftBumper),
+    (0x137, GamepadButton::RightBumper),
+    (0x138, GamepadButton::LeftTrigger),
+    (0x139, GamepadButton::RightTrigger),
+    (0x13a, GamepadButton::Select),
+    (0x13b, GamepadButton::Start),
+    (0x13d, GamepadButton::LeftStick),
+    (0x13e, GamepadButton::RightStick),
+    (0x220, GamepadButton::DPadUp),
+    (0x221, GamepadButton::DPadDown),
+    (0x222, GamepadButton::DPadLeft),
+    (0x223, GamepadButton::DPadRight),
+    (0x13c, GamepadButton::Home),
+];
+
+const ABS_X: u16 = 0x00;
+const ABS_Y: u16 = 0x01;
+const ABS_Z: u16 = 0x02;
+const ABS_RX: u16 = 0x03;
+const ABS_RY: u16 = 0x04;
+const ABS_RZ: u16 = 0x05;
+const ABS_GAS: u16 = 0x09;
+const ABS_BRAKE: u16 = 0x0a;
+const ABS_HAT0X: u16 = 0x10;
+const ABS_HAT0Y: u16 = 0x11;
+
+#[repr(C)]
+#[derive(Default, Clone, Copy)]
+struct InputEvent {
+    time: libc_sys::timeval,
+    ty: u16,
+    code: u16,
+    value: i32,
+}
+
+#[repr(C)]
+#[derive(Default, Clone, Copy)]
+struct InputAbsInfo {
+    value: i32,
+    minimum: i32,
+    maximum: i32,
+    fuzz: i32,
+    flat: i32,
+    resolution: i32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct FfRumbleEffect {
+    strong_magnitude: u16,
+    weak_magnitude: u16,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct FfPeriodicEffect {
+    waveform: u16,
+    period: u16,
+    magnitude: i16,
+    offset: i16,
+    phase: u16,
+    envelope: [u16; 4],
+    custom_len: u32,
+    custom_data: *mut i16,
+}
+
+// only here to give the union the size and alignment of the kernel's
+#[repr(C)]
+#[derive(Clone, Copy)]
+union FfEffectData {
+    rumble: FfRumbleEffect,
+    _periodic: FfPeriodicEffect,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct FfEffect {
+    ty: u16,
+    id: i16,
+    direction: u16,
+    trigger_button: u16,
+    trigger_interval: u16,
+    replay_length: u16,
+    replay_delay: u16,
+    u: FfEffectData,
+}
+
+const fn ioc(dir: c_ulong, nr: c_ulong, size: usize) -> c_ulong {
+    (dir << 30) | ((size as c_ulong) << 16) | ((b'E' as c_ulong) << 8) | nr
+}
+
+const IOC_WRITE: c_ulong = 1;
+const IOC_READ: c_ulong = 2;
+
+fn eviocgname(len: usize) -> c_ulong {ioc(IOC_READ, 0x06, len)}
+fn eviocgbit(ev: u16, len: usize) -> c_ulong {ioc(IOC_READ, 0x20 + ev as c_ulong, len)}
+fn eviocgabs(abs: u16) -> c_ulong {ioc(IOC_READ, 0x40 + abs as c_ulong, std::mem::size_of::<InputAbsInfo>())}
+fn eviocsff() -> c_ulong {ioc(IOC_WRITE, 0x80, std::mem::size_of::<FfEffect>())}
+
+fn test_bit(bits: &[u8], bit: usize) -> bool {
+    bits[bit / 8] & (1 << (bit % 8)) != 0
+}
+
+struct EvdevGamepad {
+    gamepad_id: GamepadId,
+    path: String,
+    fd: c_int,
+    abs_info: Vec<(u16, InputAbsInfo)>,
+    state: GamepadState,
+    can_rumble: bool,
+    rumble_effect: i16,
+}
+
+impl EvdevGamepad {
+    unsafe fn open(path: &str, gamepad_id: GamepadId) -> Option<(Self, String)> {
+        let c_path = CString::new(path).ok() ?;
+        // rumble needs write access, but reading is enough for the rest
+        let mut fd = libc_sys::open(c_path.as_ptr(), libc_sys::O_RDWR | libc_sys::O_NONBLOCK);
+        if fd < 0 {
+            fd = libc_sys::open(c_path.as_ptr(), libc_sys::O_RDONLY | libc_sys::O_NONBLOCK);
+        }
+        if fd < 0 {
+            return None
+        }
+        let mut key_bits = [0u8; KEY_CNT / 8];
+        if libc_sys::ioctl(fd, eviocgbit(EV_KEY, key_bits.len()), key_bits.as_mut_ptr()) < 0
+            || !test_bit(&key_bits, BTN_GAMEPAD as usize) {
+            libc_sys::close(fd);
+            return None
+        }
+        let mut name = [0u8; 256];
+        let len = libc_sys::ioctl(fd, eviocgname(name.len()), name.as_mut_ptr()).max(0) as usize;
+        let name = String::from_utf8_lossy(&name[0..len.min(name.len())]).trim_end_matches('\0').to_string();
+
+        let mut abs_bits = [0u8; ABS_CNT / 8];
+        libc_sys::ioctl(fd, eviocgbit(EV_ABS, abs_bits.len()), abs_bits.as_mut_ptr());
+        let mut abs_info = Vec::new();
+        for code in [ABS_X, ABS_Y, ABS_Z, ABS_RX, ABS_RY, ABS_RZ, ABS_GAS, ABS_BRAKE, ABS_HAT0X, ABS_HAT0Y] {
+            if test_bit(&abs_bits, code as usize) {
+                let mut info = InputAbsInfo::default();
+                if libc_sys::ioctl(fd, eviocgabs(code), &mut info as *mut InputAbsInfo) >= 0 && info.maximum > info.minimum {
+                    abs_info.push((code, info));
+                }
+            }
+        }
+        let mut ff_bits = [0u8; 128 / 8];
+        let can_rumble = libc_sys::ioctl(fd, eviocgbit(EV_FF, ff_bits.len()), ff_bits.as_mut_ptr()) >= 0
+            && test_bit(&ff_bits, FF_RUMBLE as usize);
+
+        let mut gamepad = Self {
+            gamepad_id,
+            path: path.to_string(),
+            fd,
+            abs_info,
+            state: GamepadState::default(),
+            can_rumble,
+            rumble_effect: -1,
+        };
+        for (code, info) in gamepad.abs_info.clone() {
+            gamepad.handle_abs(code, info.value);
+        }
+        Some((gamepad, name))
+    }
+
+    /// Reads everything that is queued, returns false when the device is gone.
+    unsafe fn read_events(&mut self) -> bool {
+        let mut event = InputEvent::default();
+        let size = std::mem::size_of::<InputEvent>();
+        loop {
+            let len = libc_sys::read(self.fd, &mut event as *mut InputEvent as *mut c_void, size);
+            if len < 0 {
+                return std::io::Error::last_os_error().raw_os_error() == Some(libc_sys::EAGAIN)
+            }
+            if len as usize != size {
+                return true
+            }
+            match event.ty {
+                EV_KEY => for (code, button) in BUTTON_CODES {
+                    if code == event.code {
+                        self.state.buttons[button.index()] = if event.value != 0 {1.0} else {0.0};
+                    }
+                },
+                EV_ABS => self.handle_abs(event.code, event.value),
+                _ => ()
+            }
+        }
+    }
+
+    fn handle_abs(&mut self, code: u16, value: i32) {
+        let info = if let Some((_, info)) = self.abs_info.iter().find( | (c, _) | *c == code) {*info} else {return};
+        let range = (info.maximum - info.minimum) as f32;
+        // 0 to 1 for the triggers, -1 to 1 for the sticks with the flat area around the center cut off
+        let unit = (value - info.minimum) as f32 / range;
+        let centered = if ((value - info.minimum) * 2 - (info.maximum - info.minimum)).abs() <= info.flat * 2 {0.0} else {unit * 2.0 - 1.0};
+        let state = &mut self.state;
+        match code {
+            ABS_X => state.axes[GamepadAxis::LeftX.index()] = centered,
+            ABS_Y => state.axes[GamepadAxis::LeftY.index()] = centered,
+            ABS_RX => state.axes[GamepadAxis::RightX.index()] = centered,
+            ABS_RY => state.axes[GamepadAxis::RightY.index()] = centered,
+            ABS_Z | ABS_BRAKE => state.buttons[GamepadButton::LeftTrigger.index()] = unit,
+            ABS_RZ | ABS_GAS => state.buttons[GamepadButton::RightTrigger.index()] = unit,
+            ABS_HAT0X => {
+                state.buttons[GamepadButton::DPadLeft.index()] = if centered < 0.0 {1.0} else {0.0};
+                state.buttons[GamepadButton::DPadRight.index()] = if centered > 0.0 {1.0} else {0.0};
+            }
+            ABS_HAT0Y => {
+                state.buttons[GamepadButton::DPadUp.index()] = if centered < 0.0 {1.0} else {0.0};
+                state.buttons[GamepadButton::DPadDown.index()] = if centered > 0.0 {1.0} else {0.0};
+            }
+            _ => ()
+        }
+    }
+
+    unsafe fn rumble(&mut self, strong: f32, weak: f32, duration: f64) {
+        if !self.can_rumble {
+            return
+        }
+        let mut effect = FfEffect {
+            ty: FF_RUMBLE,
+            id: self.rumble_effect,
+            direction: 0,
+            trigger_button: 0,
+            trigger_interval: 0,
+            replay_length: (duration * 1000.0).clamp(0.0, u16::MAX as f64) as u16,
+            replay_delay: 0,
+            u: FfEffectData {
+                rumble: FfRumbleEffect {
+                    strong_magnitude: (strong * u16::MAX as f32) as u16,
+                    weak_magnitude: (weak * u16::MAX as f32) as u16,
+                }
+            }
+        };
+        // uploading with id -1 makes the kernel allocate one, after that the effect gets updated
+        if libc_sys::ioctl(self.fd, eviocsff(), &mut effect as *mut FfEffect) < 0 {
+            return
+        }
+        self.rumble_effect = effect.id;
+        let play = InputEvent {
+            time: Default::default(),
+            ty: EV_FF,
+            code: effect.id as u16,
+            value: 1,
+        };
+        libc_sys::write(self.fd, &play as *const InputEvent as *const c_void, std::mem::size_of::<InputEvent>());
+    }
+}
+
+impl Drop for EvdevGamepad {
+    fn drop(&mut self) {
+        unsafe {libc_sys::close(self.fd);}
+    }
+}
+
+/// Game controllers read straight from evdev, so it works the same under X11 and without
+/// a display server. Devices show up as they get plugged in because the directory is rescanned.
+#[derive(Default)]
+pub struct LinuxGamepads {
+    gamepads: Vec<EvdevGamepad>,
+    not_gamepads: HashSet<String>,
+    last_scan: Option<f64>,
+    last_id: u64,
+}
+
+impl LinuxGamepads {
+    pub fn rumble(&mut self, gamepad_id: GamepadId, strong: f32, weak: f32, duration: f64) {
+        if let Some(gamepad) = self.gamepads.iter_mut().find( | g | g.gamepad_id == gamepad_id) {
+            unsafe {gamepad.rumble(strong, weak, duration)}
+        }
+    }
+}
+
+impl Cx {
+    pub(crate) fn handle_linux_gamepads(&mut self, time: f64) {
+        if self.os.gamepads.last_scan.map_or(true, | last | time - last > 1.0) {
+            self.os.gamepads.last_scan = Some(time);
+            let mut paths = Vec::new();
+            if let Ok(entries) = std::fs::read_dir("/dev/input") {
+                for entry in entries.flatten() {
+                    let path = entry.path().to_string_lossy().to_string();
+                    if entry.file_name().to_string_lossy().starts_with("event") {
+                        paths.push(path);
+                    }
+                }
+            }
+            let gamepads = &mut self.os.gamepads;
+            // forget devices that are gone, the number of a new one can be reused
+            gamepads.not_gamepads.retain( | p | paths.contains(p));
+            paths.retain( | p | !gamepads.not_gamepads.contains(p) && !gamepads.gamepads.iter().any( | g | g.path == *p));
+            for path in paths {
+                let gamepad_id = GamepadId(self.os.gamepads.last_id + 1);
+                if let Some((gamepad, name)) = unsafe {EvdevGamepad::open(&path, gamepad_id)} {
+                    let state = gamepad.state;
+                    self.os.gamepads.last_id += 1;
+                    self.os.gamepads.gamepads.push(gamepad);
+                    self.gamepad_connected(gamepad_id, &name);
+                    self.gamepad_update(gamepad_id, state, time);
+                }
+                else {
+                    self.os.gamepads.not_gamepads.insert(path);
+                }
+            }
+        }
+        let mut i = 0;
+        while i < self.os.gamepads.gamepads.len() {
+            let gamepad = &mut self.os.gamepads.gamepads[i];
+            let gamepad_id = gamepad.gamepad_id;
+            if unsafe {gamepad.read_events()} {
+                let state = gamepad.state;
+                self.gamepad_update(gamepad_id, state, time);
+                i += 1;
+            }
+            else {
+                self.os.gamepads.gamepads.remove(i);
+                self.gamepad_disconnected(gamepad_id);
+            }
+        }
+    }
+}
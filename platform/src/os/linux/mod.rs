@@ -25,13 +25,22 @@ pub mod alsa_audio;
 pub mod alsa_midi;
 #[cfg(not(target_os="android"))]
 pub mod select_timer;
+#[cfg(not(target_os="android"))]
+pub mod linux_gamepad;
 #[cfg(not(target_os="android"))] 
 pub mod pulse_audio; 
 #[cfg(not(target_os="android"))]
 pub mod pulse_sys;
 
+#[cfg(not(target_os="android"))]
+pub mod openssl_sys;
+#[cfg(not(target_os="android"))]
+pub mod net_stream;
+
 #[cfg(not(target_os="android"))]
 mod web_socket;
+#[cfg(not(target_os="android"))]
+pub mod linux_dialogs;
 
 #[cfg(target_os="android")]
 pub mod android;
@@ -0,0 +1,133 @@
+use crate::os::linux::openssl_sys::*;
+use std::net::TcpStream;
+use std::io::{self, Read, Write};
+use std::os::unix::io::AsRawFd;
+use std::ffi::CString;
+use std::time::Duration;
+
+pub struct TlsStream{
+    lib: LibSsl,
+    ctx: *mut SSL_CTX,
+    ssl: *mut SSL,
+    tcp: TcpStream,
+}
+
+impl TlsStream{
+    fn connect(tcp: TcpStream, host: &str, verify: bool)->Result<TlsStream, String>{
+        let lib = LibSsl::try_load().ok_or("Cannot load libssl for a secure websocket".to_string())?;
+        unsafe{
+            let ctx = (lib.SSL_CTX_new)((lib.TLS_client_method)());
+            if ctx.is_null(){
+                return Err("Cannot create an SSL context".into())
+            }
+            (lib.SSL_CTX_set_default_verify_paths)(ctx);
+            (lib.SSL_CTX_set_verify)(ctx, if verify{SSL_VERIFY_PEER}else{SSL_VERIFY_NONE}, std::ptr::null());
+            let ssl = (lib.SSL_new)(ctx);
+            let stream = TlsStream{lib, ctx, ssl, tcp};
+            if ssl.is_null(){
+                return Err("Cannot create an SSL connection".into())
+            }
+            let host = CString::new(host).unwrap();
+            (stream.lib.SSL_set_fd)(ssl, stream.tcp.as_raw_fd());
+            (stream.lib.SSL_ctrl)(ssl, SSL_CTRL_SET_TLSEXT_HOSTNAME, TLSEXT_NAMETYPE_host_name, host.as_ptr() as *mut _);
+            if verify{
+                (stream.lib.SSL_set1_host)(ssl, host.as_ptr());
+            }
+            if (stream.lib.SSL_connect)(ssl) != 1{
+                return Err("TLS handshake failed".into())
+            }
+            Ok(stream)
+        }
+    }
+
+    fn check(&self, ret: i32)->io::Result<usize>{
+        if ret > 0{
+            return Ok(ret as usize)
+        }
+        match unsafe{(self.lib.SSL_get_error)(self.ssl, ret)}{
+            SSL_ERROR_ZERO_RETURN=>Ok(0),
+            // the read timeout of the socket ran out
+            SSL_ERROR_WANT_READ | SSL_ERROR_WANT_WRITE=>Err(io::ErrorKind::WouldBlock.into()),
+            _=>Err(io::Error::new(io::ErrorKind::Other, "TLS error"))
+        }
+    }
+}
+
+impl Read for TlsStream{
+    fn read(&mut self, buf: &mut [u8])->io::Result<usize>{
+        let ret = unsafe{(self.lib.SSL_read)(self.ssl, buf.as_mut_ptr() as *mut _, buf.len() as i32)};
+        self.check(ret)
+    }
+}
+
+impl Write for TlsStream{
+    fn write(&mut self, buf: &[u8])->io::Result<usize>{
+        let ret = unsafe{(self.lib.SSL_write)(self.ssl, buf.as_ptr() as *const _, buf.len() as i32)};
+        self.check(ret)
+    }
+
+    fn flush(&mut self)->io::Result<()>{
+        Ok(())
+    }
+}
+
+impl Drop for TlsStream{
+    fn drop(&mut self){
+        unsafe{
+            if !self.ssl.is_null(){
+                (self.lib.SSL_shutdown)(self.ssl);
+                (self.lib.SSL_free)(self.ssl);
+            }
+            (self.lib.SSL_CTX_free)(self.ctx);
+        }
+    }
+}
+
+pub enum NetStream{
+    Tcp(TcpStream),
+    Tls(TlsStream)
+}
+
+impl Read for NetStream{
+    fn read(&mut self, buf: &mut [u8])->io::Result<usize>{
+        match self{
+            NetStream::Tcp(s)=>s.read(buf),
+            NetStream::Tls(s)=>s.read(buf),
+        }
+    }
+}
+
+impl Write for NetStream{
+    fn write(&mut self, buf: &[u8])->io::Result<usize>{
+        match self{
+            NetStream::Tcp(s)=>s.write(buf),
+            NetStream::Tls(s)=>s.write(buf),
+        }
+    }
+
+    fn flush(&mut self)->io::Result<()>{
+        Ok(())
+    }
+}
+
+
+impl NetStream{
+    pub fn set_read_timeout(&self, timeout: Option<Duration>)->io::Result<()>{
+        match self{
+            NetStream::Tcp(s)=>s.set_read_timeout(timeout),
+            NetStream::Tls(s)=>s.tcp.set_read_timeout(timeout),
+        }
+    }
+}
+
+/// Opens a tcp connection, secured with the system libssl when `tls` is set.
+pub fn connect_net_stream(host: &str, port: &str, tls: bool, verify: bool)->Result<NetStream, String>{
+    let tcp = TcpStream::connect(format!("{}:{}", host, port)).map_err( | e | format!("Error connecting to {}: {}", host, e)) ?;
+    let _ = tcp.set_nodelay(true);
+    if tls{
+        Ok(NetStream::Tls(TlsStream::connect(tcp, host, verify) ?))
+    }
+    else{
+        Ok(NetStream::Tcp(tcp))
+    }
+}
@@ -12,11 +12,11 @@ use {
         makepad_shader_compiler::generate_glsl,
         cx::{Cx, OsType, OsType::Android},
         texture::{Texture, TextureFormat, TexturePixel, CxTexture},
-        makepad_math::{Mat4, DVec2, Vec4},
+        makepad_math::{Mat4, DVec2, Rect, Vec4},
         pass::{PassClearColor, PassClearDepth, PassId},
         draw_list::DrawListId,
         draw_shader::{CxDrawShaderMapping, DrawShaderTextureInput},
-        event::{Event, TextureHandleReadyEvent}
+        event::{Event, PassReadBackEvent, TextureHandleReadyEvent}
     },
 };
 
@@ -80,6 +80,11 @@ impl Cx {
                 draw_call.draw_uniforms.set_zbias(*zbias);
                 *zbias += zbias_step;
                 
+                // the color space of the first texture, shaders convert its samples to sRGB with it
+                let texture_color_space = draw_call.texture_slots[0].as_ref()
+                    .map( | texture | self.textures[texture.texture_id()].color_space).unwrap_or_default();
+                draw_call.draw_uniforms.set_texture_color_space(texture_color_space);
+                
                 let instances = (draw_item.instances.as_ref().unwrap().len() / sh.mapping.instances.total_slots) as u64;
                 
                 if instances == 0 {
@@ -272,6 +277,8 @@ impl Cx {
         
         self.passes[pass_id].set_matrix(pass_rect.pos, pass_rect.size);
         self.passes[pass_id].set_dpi_factor(dpi_factor);
+        let color_space = self.get_delegated_color_space(pass_id);
+        self.passes[pass_id].set_color_space_uniform(color_space);
         Some(pass_rect.size)
     }
 
@@ -335,7 +342,7 @@ impl Cx {
                     let size = dpi_factor * pass_size;
                     cxtexture.update_render_target(size.x as usize, size.y as usize);
                     if cxtexture.check_initial(){
-                       clear_color = _clear_color;
+                       clear_color = self.get_delegated_clear_color(pass_id, _clear_color);
                        clear_flags |= gl_sys::COLOR_BUFFER_BIT;
                     }
                 },
@@ -343,7 +350,7 @@ impl Cx {
                     let cxtexture = &mut self.textures[color_texture.texture.texture_id()];
                     let size = dpi_factor * pass_size;
                     cxtexture.update_render_target(size.x as usize, size.y as usize);
-                    clear_color = _clear_color;
+                    clear_color = self.get_delegated_clear_color(pass_id, _clear_color);
                     clear_flags |= gl_sys::COLOR_BUFFER_BIT;
                 }
             }
@@ -401,7 +408,7 @@ impl Cx {
         // HACK(eddyb) drain error queue, so that we can check erors below.
         while unsafe { gl_sys::GetError() } != 0 {}
 
-        unsafe {
+        let viewport_y = unsafe {
             let (x, mut y) = (0, 0);
             let width = (pass_size.x * dpi_factor) as u32;
             let height = (pass_size.y * dpi_factor) as u32;
@@ -421,7 +428,8 @@ impl Cx {
             gl_sys::Viewport(x as i32, y as i32, width as i32, height as i32);
             
             assert_eq!(gl_sys::GetError(), 0, "glViewport({x}, {y}, {width}, {height}) failed");
-        }
+            y as i32
+        };
 
         if clear_flags != 0 {
             unsafe {
@@ -444,10 +452,59 @@ impl Cx {
             zbias_step,
         );
         
+        let read_back = self.passes[pass_id].read_back_rect.take().map( | rect | {
+            Self::read_back_pixels(pass_id, rect, dpi_factor, pass_size, viewport_y)
+        });
+        
         unsafe {
             gl_sys::BindFramebuffer(gl_sys::FRAMEBUFFER, 0);
             //gl_sys::Finish();
         }
+        
+        if let Some(event) = read_back {
+            self.call_event_handler(&Event::PassReadBack(event));
+        }
+    }
+    
+    /// Reads the rect back from the framebuffer that is still bound. The viewport sits at the
+    /// top of the render target (see the HACK above), and GL rows run bottom up.
+    pub(crate) fn read_back_pixels(pass_id: PassId, rect: Rect, dpi_factor: f64, pass_size: DVec2, viewport_y: i32) -> PassReadBackEvent {
+        let pass_height = (pass_size.y * dpi_factor) as i32;
+        let x = ((rect.pos.x * dpi_factor).floor() as i32).max(0);
+        let y = ((rect.pos.y * dpi_factor).floor() as i32).max(0);
+        let width = ((rect.size.x * dpi_factor).ceil() as i32).min((pass_size.x * dpi_factor) as i32 - x).max(0);
+        let height = ((rect.size.y * dpi_factor).ceil() as i32).min(pass_height - y).max(0);
+        let mut rgba = vec![0u8; (width * height * 4) as usize];
+        if width > 0 && height > 0 {
+            unsafe {
+                gl_sys::PixelStorei(gl_sys::PACK_ALIGNMENT, 1);
+                gl_sys::ReadPixels(
+                    x,
+                    viewport_y + pass_height - y - height,
+                    width,
+                    height,
+                    gl_sys::RGBA,
+                    gl_sys::UNSIGNED_BYTE,
+                    rgba.as_mut_ptr() as *mut _
+                );
+            }
+        }
+        let (width, height) = (width as usize, height as usize);
+        let mut pixels = Vec::with_capacity(width * height);
+        for row in (0..height).rev() {
+            for col in 0..width {
+                let i = (row * width + col) * 4;
+                let (r, g, b, a) = (rgba[i] as u32, rgba[i + 1] as u32, rgba[i + 2] as u32, rgba[i + 3] as u32);
+                pixels.push((a << 24) | (r << 16) | (g << 8) | b);
+            }
+        }
+        PassReadBackEvent {
+            pass_id,
+            rect,
+            width,
+            height,
+            pixels,
+        }
     }
     
     pub fn opengl_compile_shaders(&mut self) {
@@ -0,0 +1,72 @@
+#![allow(non_camel_case_types)]
+#![allow(non_snake_case)]
+#![allow(non_upper_case_globals)]
+
+use self::super::egl_sys::Module;
+use std::os::raw::{
+    c_void,
+    c_char,
+    c_int,
+    c_long,
+};
+
+pub enum SSL_METHOD {}
+pub enum SSL_CTX {}
+pub enum SSL {}
+
+pub const SSL_VERIFY_NONE: c_int = 0;
+pub const SSL_VERIFY_PEER: c_int = 1;
+
+pub const SSL_ERROR_WANT_READ: c_int = 2;
+pub const SSL_ERROR_WANT_WRITE: c_int = 3;
+pub const SSL_ERROR_ZERO_RETURN: c_int = 6;
+
+pub const SSL_CTRL_SET_TLSEXT_HOSTNAME: c_int = 55;
+pub const TLSEXT_NAMETYPE_host_name: c_long = 0;
+
+// libssl is opened at runtime so apps don't link against a specific version of it
+pub struct LibSsl {
+    pub TLS_client_method: unsafe extern "C" fn() -> *const SSL_METHOD,
+    pub SSL_CTX_new: unsafe extern "C" fn(method: *const SSL_METHOD) -> *mut SSL_CTX,
+    pub SSL_CTX_free: unsafe extern "C" fn(ctx: *mut SSL_CTX),
+    pub SSL_CTX_set_default_verify_paths: unsafe extern "C" fn(ctx: *mut SSL_CTX) -> c_int,
+    pub SSL_CTX_set_verify: unsafe extern "C" fn(ctx: *mut SSL_CTX, mode: c_int, callback: *const c_void),
+    pub SSL_new: unsafe extern "C" fn(ctx: *mut SSL_CTX) -> *mut SSL,
+    pub SSL_free: unsafe extern "C" fn(ssl: *mut SSL),
+    pub SSL_set_fd: unsafe extern "C" fn(ssl: *mut SSL, fd: c_int) -> c_int,
+    pub SSL_set1_host: unsafe extern "C" fn(ssl: *mut SSL, hostname: *const c_char) -> c_int,
+    pub SSL_ctrl: unsafe extern "C" fn(ssl: *mut SSL, cmd: c_int, larg: c_long, parg: *mut c_void) -> c_long,
+    pub SSL_connect: unsafe extern "C" fn(ssl: *mut SSL) -> c_int,
+    pub SSL_read: unsafe extern "C" fn(ssl: *mut SSL, buf: *mut c_void, num: c_int) -> c_int,
+    pub SSL_write: unsafe extern "C" fn(ssl: *mut SSL, buf: *const c_void, num: c_int) -> c_int,
+    pub SSL_get_error: unsafe extern "C" fn(ssl: *const SSL, ret: c_int) -> c_int,
+    pub SSL_shutdown: unsafe extern "C" fn(ssl: *mut SSL) -> c_int,
+    _keep_module_alive: Module,
+}
+
+impl LibSsl {
+    pub fn try_load() -> Option<LibSsl> {
+        let module = Module::load("libssl.so.3")
+            .or_else(|_| Module::load("libssl.so.1.1"))
+            .or_else(|_| Module::load("libssl.so")).ok()?;
+
+        Some(LibSsl {
+            TLS_client_method: module.get_symbol("TLS_client_method").ok()?,
+            SSL_CTX_new: module.get_symbol("SSL_CTX_new").ok()?,
+            SSL_CTX_free: module.get_symbol("SSL_CTX_free").ok()?,
+            SSL_CTX_set_default_verify_paths: module.get_symbol("SSL_CTX_set_default_verify_paths").ok()?,
+            SSL_CTX_set_verify: module.get_symbol("SSL_CTX_set_verify").ok()?,
+            SSL_new: module.get_symbol("SSL_new").ok()?,
+            SSL_free: module.get_symbol("SSL_free").ok()?,
+            SSL_set_fd: module.get_symbol("SSL_set_fd").ok()?,
+            SSL_set1_host: module.get_symbol("SSL_set1_host").ok()?,
+            SSL_ctrl: module.get_symbol("SSL_ctrl").ok()?,
+            SSL_connect: module.get_symbol("SSL_connect").ok()?,
+            SSL_read: module.get_symbol("SSL_read").ok()?,
+            SSL_write: module.get_symbol("SSL_write").ok()?,
+            SSL_get_error: module.get_symbol("SSL_get_error").ok()?,
+            SSL_shutdown: module.get_symbol("SSL_shutdown").ok()?,
+            _keep_module_alive: module,
+        })
+    }
+}
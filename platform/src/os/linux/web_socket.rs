@@ -1,19 +1,77 @@
-
 use crate::event::HttpRequest;
 use crate::web_socket::{WebSocketMessage};
-use std::sync::mpsc::{channel, Sender};
-use std::net::TcpStream;
-use std::io::{Read};
-use makepad_http::utils::write_bytes_to_tcp_stream_no_error;
-use makepad_http::websocket::{ServerWebSocket, ServerWebSocketMessageFormat, ServerWebSocketMessageHeader, ServerWebSocketMessage, SERVER_WEB_SOCKET_PONG_MESSAGE};
+use crate::thread::SignalToUI;
+use crate::os::linux::net_stream::{NetStream, connect_net_stream};
+use std::sync::mpsc::{channel, Sender, TryRecvError};
+use std::io::{self, Read, Write};
+use std::time::Duration;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use makepad_http::digest::base64_encode;
+use makepad_http::websocket::{ServerWebSocket, ServerWebSocketMessage};
 
 pub struct OsWebSocket{
     sender: Option<Sender<WebSocketMessage>>
 }
 
+fn random_u64()->u64{
+    RandomState::new().build_hasher().finish()
+}
+
+fn write_frame(stream: &mut NetStream, opcode: u8, data: &[u8])->io::Result<()>{
+    // frames from a client have to be masked
+    let mut frame = vec![128 | opcode];
+    if data.len() < 126{
+        frame.push(128 | data.len() as u8);
+    }
+    else if data.len() < 65536{
+        frame.push(128 | 126);
+        frame.extend_from_slice(&(data.len() as u16).to_be_bytes());
+    }
+    else{
+        frame.push(128 | 127);
+        frame.extend_from_slice(&(data.len() as u64).to_be_bytes());
+    }
+    let mask = (random_u64() as u32).to_be_bytes();
+    frame.extend_from_slice(&mask);
+    frame.extend(data.iter().enumerate().map( | (i, b) | b ^ mask[i & 3]));
+    stream.write_all(&frame)
+}
+
+fn connect(request: &HttpRequest)->Result<(NetStream, Vec<u8>), String>{
+    let split = request.split_url();
+    let tls = matches!(split.proto, "https" | "wss");
+    let mut stream = connect_net_stream(split.host, split.port, tls, !request.ignore_ssl_cert) ?;
+
+    let mut key = random_u64().to_le_bytes().to_vec();
+    key.extend_from_slice(&random_u64().to_le_bytes());
+    let mut http_request = format!("GET /{} HTTP/1.1\r\nHost: {}\r\nConnection: Upgrade\r\nUpgrade: websocket\r\nSec-WebSocket-Version: 13\r\nSec-WebSocket-Key: {}\r\n", split.file, split.host, base64_encode(&key));
+    http_request.push_str(&request.get_headers_string());
+    http_request.push_str("\r\n");
+    stream.write_all(http_request.as_bytes()).map_err( | e | format!("Error writing request to websocket: {}", e)) ?;
+
+    // read the upgrade response, whatever comes after it are the first frames
+    let mut response = Vec::new();
+    let mut buffer = [0u8; 4096];
+    let header_end = loop{
+        if let Some(pos) = response.windows(4).position( | w | w == b"\r\n\r\n"){
+            break pos + 4;
+        }
+        match stream.read(&mut buffer){
+            Ok(0)=>return Err("Websocket closed during handshake".into()),
+            Ok(n)=>response.extend_from_slice(&buffer[0..n]),
+            Err(e)=>return Err(format!("Error reading websocket handshake: {}", e))
+        }
+    };
+    let status = String::from_utf8_lossy(&response[0..header_end]).lines().next().unwrap_or("").to_string();
+    if status.split(' ').nth(1) != Some("101"){
+        return Err(format!("Websocket upgrade refused: {}", status))
+    }
+    Ok((stream, response[header_end..].to_vec()))
+}
+
 impl OsWebSocket{
     pub fn send_message(&mut self, message:WebSocketMessage)->Result<(),()>{
-        // lets encode the message into a membuffer and send it to the write thread
         if let Some(sender) = &mut self.sender{
             if sender.send(message).is_err(){
                 return Err(());
@@ -22,108 +80,94 @@ impl OsWebSocket{
         }
         Err(())
     }
-                    
+
     pub fn open(_socket_id:u64, request: HttpRequest, rx_sender:Sender<WebSocketMessage>)->OsWebSocket{
-        // parse the url
-        let split = request.split_url();
-        // strip off any hashes
-        // alright we have proto, host, port and hash now
-        // lets open a tcpstream
-        let stream = TcpStream::connect(format!("{}:{}", split.host, split.port));
-        // alright lets construct a http request
-        // lets join the headers
-        
-        let mut http_request = format!("GET {} HTTP/1.1\r\nHost: {}\r\nConnection: Upgrade\r\nUpgrade: websocket\r\nSec-WebSocket-Version: 13\r\nSec-WebSocket-Key: SxJdXBRtW7Q4awLDhflO0Q==\r\n", split.file, split.host);
-        http_request.push_str(&request.get_headers_string());
-        http_request.push_str("\r\n"); 
-        
-        // lets write the http request
-        if stream.is_err(){
-            rx_sender.send(WebSocketMessage::Error("Error connecting websocket tcpstream".into())).unwrap();
-            return OsWebSocket{sender:None}
-        }
-        let mut stream = stream.unwrap();
-        if write_bytes_to_tcp_stream_no_error(&mut stream, http_request.as_bytes()){
-            rx_sender.send(WebSocketMessage::Error("Error writing request to websocket".into())).unwrap();
-            return OsWebSocket{sender:None}
-        }
-        
-        // lets start the thread
-        let mut input_stream = stream.try_clone().unwrap();
-        let mut output_stream = stream.try_clone().unwrap();
         let (sender, receiver) = channel();
-        
-        let _writer_thread = std::thread::spawn(move || {
-            while let Ok(msg) = receiver.recv(){
-                match msg{
-                    WebSocketMessage::Binary(data)=>{
-                        let header = ServerWebSocketMessageHeader::from_len(data.len(), ServerWebSocketMessageFormat::Binary, false);
-                        if write_bytes_to_tcp_stream_no_error(&mut output_stream, header.as_slice()) ||
-                        write_bytes_to_tcp_stream_no_error(&mut output_stream, &data){
-                            break;
+        let send = move | msg | {
+            let ok = rx_sender.send(msg).is_ok();
+            SignalToUI::set_ui_signal();
+            ok
+        };
+
+        // one thread per socket connects, then reads and writes with a short read timeout
+        // so it never needs the stream from two threads at once, which tls doesn't allow
+        std::thread::spawn(move || {
+            let (mut stream, first_data) = match connect(&request){
+                Ok(v)=>v,
+                Err(e)=>{
+                    send(WebSocketMessage::Error(e));
+                    send(WebSocketMessage::Closed);
+                    return
+                }
+            };
+            let _ = stream.set_read_timeout(Some(Duration::from_millis(10)));
+            send(WebSocketMessage::Opened);
+
+            let mut web_socket = ServerWebSocket::new();
+            let mut buffer = vec![0u8; 65536];
+            let mut data = first_data;
+            loop{
+                let mut pongs = Vec::new();
+                let mut closed = false;
+                web_socket.parse(&data, | result | {
+                    match result {
+                        Ok(ServerWebSocketMessage::Ping(data))=>pongs.push(data.to_vec()),
+                        Ok(ServerWebSocketMessage::Pong(_))=>(),
+                        Ok(ServerWebSocketMessage::Text(text))=>{
+                            send(WebSocketMessage::String(text.into()));
                         }
-                    }
-                    WebSocketMessage::String(data)=>{
-                        let header = ServerWebSocketMessageHeader::from_len(data.len(), ServerWebSocketMessageFormat::Binary, false);
-                        if write_bytes_to_tcp_stream_no_error(&mut output_stream, header.as_slice()) ||
-                        write_bytes_to_tcp_stream_no_error(&mut output_stream, &data.as_bytes()){
-                            break;
+                        Ok(ServerWebSocketMessage::Binary(data))=>{
+                            send(WebSocketMessage::Binary(data.into()));
+                        }
+                        Ok(ServerWebSocketMessage::Close)=>closed = true,
+                        Err(e)=>{
+                            send(WebSocketMessage::Error(format!("Websocket error {:?}", e)));
                         }
                     }
-                    _=>{
-                        crate::error!("WebSocketMessage of this type sending not implemented");
+                });
+                if closed{
+                    let _ = write_frame(&mut stream, 8, &[]);
+                    send(WebSocketMessage::Closed);
+                    return
+                }
+                for pong in pongs{
+                    let _ = write_frame(&mut stream, 10, &pong);
+                }
+                loop{
+                    let result = match receiver.try_recv(){
+                        Ok(WebSocketMessage::Binary(data))=>write_frame(&mut stream, 2, &data),
+                        Ok(WebSocketMessage::String(data))=>write_frame(&mut stream, 1, data.as_bytes()),
+                        Ok(_)=>Ok(()),
+                        Err(TryRecvError::Empty)=>break,
+                        Err(TryRecvError::Disconnected)=>{
+                            // the socket was dropped on our side
+                            let _ = write_frame(&mut stream, 8, &[]);
+                            return
+                        }
+                    };
+                    if let Err(e) = result{
+                        send(WebSocketMessage::Error(format!("Error writing to websocket: {}", e)));
+                        send(WebSocketMessage::Closed);
+                        return
                     }
                 }
-            }
-        });
-        
-        let _reader_thread = std::thread::spawn(move || {
-            let mut web_socket = ServerWebSocket::new();
-            let mut done = false;
-            while !done {
-                let mut buffer = [0u8; 65535];
-                match input_stream.read(&mut buffer) {
-                    Ok(bytes_read) => {
-                        web_socket.parse(&buffer[0..bytes_read], | result | {
-                            match result {
-                                Ok(ServerWebSocketMessage::Ping(_)) => {
-                                    println!("ping!");
-                                    if write_bytes_to_tcp_stream_no_error(&mut input_stream, &SERVER_WEB_SOCKET_PONG_MESSAGE){
-                                        done = true;
-                                        let _ = rx_sender.send(WebSocketMessage::Error("Pong message send failed".into()));
-                                    }
-                                },
-                                Ok(ServerWebSocketMessage::Pong(_)) => {
-                                },
-                                Ok(ServerWebSocketMessage::Text(text)) => {
-                                    if rx_sender.send(WebSocketMessage::String(text.into())).is_err(){
-                                        done = true;
-                                    };
-                                    println!("text => {}", text);
-                                },
-                                Ok(ServerWebSocketMessage::Binary(data)) => {
-                                    if rx_sender.send(WebSocketMessage::Binary(data.into())).is_err(){
-                                        done = true;
-                                    };
-                                    println!("binary!");
-                                },
-                                Ok(ServerWebSocketMessage::Close) => {
-                                    let _ = rx_sender.send(WebSocketMessage::Closed);
-                                    done = true;
-                                },
-                                Err(e) => {
-                                    eprintln!("Websocket error {:?}", e);
-                                }
-                            }
-                        });                            
+                data.clear();
+                match stream.read(&mut buffer){
+                    Ok(0)=>{
+                        send(WebSocketMessage::Closed);
+                        return
                     }
-                    Err(e) => {
-                        eprintln!("Failed to receive data: {}", e);
+                    Ok(n)=>data.extend_from_slice(&buffer[0..n]),
+                    Err(e) if e.kind() == io::ErrorKind::WouldBlock || e.kind() == io::ErrorKind::TimedOut=>(),
+                    Err(e)=>{
+                        send(WebSocketMessage::Error(format!("Error reading from websocket: {}", e)));
+                        send(WebSocketMessage::Closed);
+                        return
                     }
                 }
             }
         });
-                
+
         OsWebSocket{sender:Some(sender)}
     }
-}
\ No newline at end of file
+}
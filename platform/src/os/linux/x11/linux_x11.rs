@@ -6,12 +6,14 @@ use {
         OpenglWindow,
         OpenglCx
     },
+    self::super::linux_x11_headless::HeadlessWindow,
     self::super::super::{
         egl_sys,
         x11::xlib_event::*,
         x11::xlib_app::*,
         x11::x11_sys,
-        linux_media::CxLinuxMedia
+        linux_media::CxLinuxMedia,
+        linux_gamepad::LinuxGamepads,
     },
     crate::{
         cx_api::{CxOsOp, CxOsApi}, 
@@ -20,10 +22,13 @@ use {
         thread::SignalToUI,
         event::Event,
         pass::CxPassParent,
+        color_space::ColorSpace,
         cx::{Cx, OsType,LinuxWindowParams}, 
         os::cx_stdin::{PollTimers},
         gpu_info::GpuPerformance,
         os::cx_native::EventFlow,
+        os::native_http::NativeHttpRequests,
+        os::linux::linux_dialogs::show_dialog,
     }
 };
 
@@ -106,15 +111,9 @@ impl Cx {
                     
                     window.window_geom = re.new_geom.clone();
                     self.windows[re.window_id].window_geom = re.new_geom.clone();
-                    // redraw just this windows root draw list
-                    if re.old_geom.inner_size != re.new_geom.inner_size {
-                        if let Some(main_pass_id) = self.windows[re.window_id].main_pass_id {
-                            self.redraw_pass_and_child_passes(main_pass_id);
-                        }
-                    }
                 }
-                // ok lets not redraw all, just this window
-                self.call_event_handler(&Event::WindowGeomChange(re));
+                // redraws just this window, at its new size or scale
+                self.call_window_geom_change_event(re);
             }
             XlibEvent::WindowClosed(wc) => {
                 let window_id = wc.window_id;
@@ -165,6 +164,9 @@ impl Cx {
             XlibEvent::Scroll(e) => {
                 self.call_event_handler(&Event::Scroll(e.into()))
             }
+            XlibEvent::Navigate(e) => {
+                self.call_event_handler(&Event::Navigate(e))
+            }
             XlibEvent::WindowDragQuery(e) => {
                 self.call_event_handler(&Event::WindowDragQuery(e))
             }
@@ -174,14 +176,20 @@ impl Cx {
             XlibEvent::TextInput(e) => {
                 self.call_event_handler(&Event::TextInput(e))
             }
+            XlibEvent::TextComposition(e) => {
+                self.call_event_handler(&Event::TextComposition(e))
+            }
             XlibEvent::Drag(e) => {
-                self.call_event_handler(&Event::Drag(e))
+                self.call_event_handler(&Event::Drag(e));
+                self.drag_drop.cycle_drag();
             }
             XlibEvent::Drop(e) => {
-                self.call_event_handler(&Event::Drop(e))
+                self.call_event_handler(&Event::Drop(e));
+                self.drag_drop.cycle_drag();
             }
             XlibEvent::DragEnd => {
-                self.call_event_handler(&Event::DragEnd)
+                self.call_event_handler(&Event::DragEnd);
+                self.drag_drop.cycle_drag();
             }
             XlibEvent::KeyDown(e) => {
                 self.keyboard.process_key_down(e.clone());
@@ -197,13 +205,24 @@ impl Cx {
             XlibEvent::TextCut(e) => {
                 self.call_event_handler(&Event::TextCut(e))
             }
+            XlibEvent::ClipboardRead(e) => {
+                self.call_event_handler(&Event::ClipboardRead(e))
+            }
             XlibEvent::Timer(e) => {
                 //println!("TIMER! {:?}", std::time::Instant::now());
                 if e.timer_id == 0{
                     if SignalToUI::check_and_clear_ui_signal(){
                         self.handle_media_signals();
+                        self.handle_web_socket_events();
+                        self.handle_dialog_events();
                         self.call_event_handler(&Event::Signal);
                     }
+                    if self.handle_live_edit(){
+                        self.call_event_handler(&Event::LiveEdit);
+                        self.redraw_all();
+                    }
+                    self.handle_networking_events();
+                    self.handle_linux_gamepads(self.seconds_since_app_start());
                 }
                 else{
                     self.call_event_handler(&Event::Timer(e))
@@ -220,6 +239,10 @@ impl Cx {
     }
 
     pub(crate) fn handle_networking_events(&mut self) {
+        let responses = self.os.http_requests.responses();
+        if responses.len() > 0 {
+            self.call_network_responses(responses);
+        }
     }
     
     pub (crate) fn handle_repaint(&mut self, opengl_windows: &mut Vec<OpenglWindow>) {
@@ -264,6 +287,8 @@ impl Cx {
                     window.window_geom = opengl_window.window_geom.clone();
                     opengl_windows.push(opengl_window);
                     window.is_created = true;
+                    // windows opened while the app runs have to draw their first frame
+                    self.redraw_window(window_id);
                 },
                 CxOsOp::CloseWindow(window_id) => {
                     if let Some(index) = opengl_windows.iter().position( | w | w.window_id == window_id) {
@@ -296,12 +321,23 @@ impl Cx {
                 CxOsOp::ShowClipboardActions(_) =>{
                 },
                 CxOsOp::CopyToClipboard(content) => {
-                    if let Some(window) = opengl_windows.get(0) {
-                        unsafe {
-                            xlib_app.copy_to_clipboard(&content, window.xlib_window.window.unwrap(), x11_sys::CurrentTime as u64)
-                        }
+                    unsafe {
+                        xlib_app.copy_to_clipboard(&content, x11_sys::CurrentTime as u64)
+                    }
+                }
+                CxOsOp::WriteClipboard(flavors) => {
+                    unsafe {
+                        xlib_app.write_clipboard(flavors, x11_sys::CurrentTime as u64)
                     }
                 }
+                CxOsOp::ReadClipboard(request_id) => {
+                    if let Some(e) = unsafe {xlib_app.read_clipboard(request_id, x11_sys::CurrentTime as u64)} {
+                        self.call_event_handler(&Event::ClipboardRead(e))
+                    }
+                }
+                CxOsOp::GamepadRumble {gamepad_id, strong, weak, duration} => {
+                    self.os.gamepads.rumble(gamepad_id, strong, weak, duration);
+                }
                 CxOsOp::FullscreenWindow(_window_id) => {
                     todo!()
                 },
@@ -311,14 +347,21 @@ impl Cx {
                 CxOsOp::SetTopmost(_window_id, _is_topmost) => {
                     todo!()
                 }
+                CxOsOp::SetWindowColorSpace(window_id, _color_space) => {
+                    // the surface is always presented as sRGB, so that's what the window draws in
+                    self.windows[window_id].color_space = ColorSpace::Srgb;
+                }
                 CxOsOp::XrStartPresenting => {
                     //todo!()
                 },
                 CxOsOp::XrStopPresenting => {
                     //todo!()
                 },
-                CxOsOp::ShowTextIME(_area, _pos) => {
-                    //todo!()
+                CxOsOp::ShowTextIME(area, pos) => {
+                    let pos = area.clipped_rect(self).pos + pos;
+                    opengl_windows.iter_mut().for_each( | w | {
+                        w.xlib_window.set_ime_spot(pos);
+                    });
                 }
                 CxOsOp::HideTextIME => {
                     //todo!()
@@ -336,8 +379,11 @@ impl Cx {
                 },
                 CxOsOp::UpdateMacosMenu(_menu) => {
                 },
-                CxOsOp::HttpRequest{request_id:_, request:_} => {
-                    todo!()
+                CxOsOp::HttpRequest{request_id, request} => {
+                    self.os.http_requests.request(request_id, request);
+                },
+                CxOsOp::CancelHttpRequest{request_id} => {
+                    self.os.http_requests.cancel(request_id);
                 },
                 CxOsOp::PrepareVideoPlayback(_, _, _, _, _) => todo!(),
                 CxOsOp::BeginVideoPlayback(_) => todo!(),
@@ -348,10 +394,11 @@ impl Cx {
                 CxOsOp::CleanupVideoPlaybackResources(_) => todo!(),
                 CxOsOp::UpdateVideoSurfaceTexture(_) => todo!(),
 
-                CxOsOp::SaveFileDialog(_) => todo!(),
-                CxOsOp::SelectFileDialog(_) => todo!(),
-                CxOsOp::SaveFolderDialog(_) => todo!(),
-                CxOsOp::SelectFolderDialog(_) => todo!(),
+                CxOsOp::ShowDialog(dialog_id, dialog) => {
+                    if let Err(dialog) = show_dialog(dialog_id, dialog, self.dialog_sender()) {
+                        self.show_dialog_fallback(dialog_id, dialog);
+                    }
+                },
             }
         }
         ret
@@ -362,6 +409,9 @@ impl CxOsApi for Cx {
     fn init_cx_os(&mut self) {
         self.os.start_time = Some(Instant::now());
         self.live_expand();
+        if std::env::args().find( | v | v == "--stdin-loop").is_none() {
+            self.start_disk_live_file_watcher(100);
+        }
         self.live_scan_dependencies();
         self.native_load_dependencies();
     }
@@ -378,9 +428,12 @@ impl CxOsApi for Cx {
 #[derive(Default)]
 pub struct CxOs {
     pub(crate) media: CxLinuxMedia,
+    pub(crate) gamepads: LinuxGamepads,
     pub (crate) stdin_timers: PollTimers,
     pub (crate) start_time: Option<Instant>,
     // HACK(eddyb) generalize this to EGL, properly.
     pub(super) opengl_cx: Option<OpenglCx>,
+    pub(super) headless_windows: Vec<HeadlessWindow>,
+    pub(crate) http_requests: NativeHttpRequests,
 }
 
@@ -0,0 +1,245 @@
+use {
+    std::ptr,
+    self::super::opengl_x11::OpenglCx,
+    self::super::super::egl_sys,
+    crate::{
+        makepad_live_id::*,
+        makepad_math::*,
+        event::{Event, PassReadBackEvent, WindowGeom, WindowGeomChangeEvent, WindowClosedEvent},
+        texture::{Texture, TextureFormat, TextureSize},
+        thread::SignalToUI,
+        window::WindowId,
+        pass::CxPassParent,
+        cx_api::{CxOsOp, CxOsApi},
+        cx::{Cx, OsType, LinuxWindowParams},
+        gpu_info::GpuPerformance,
+        os::cx_stdin::PollTimer,
+        gl_sys,
+    }
+};
+
+/// The offscreen render target that stands in for a native window in headless mode.
+pub(crate) struct HeadlessWindow {
+    window_id: WindowId,
+    texture: Texture,
+}
+
+impl Cx {
+
+    /// Starts a `Cx` without a display server. Rendering goes through a surfaceless EGL context,
+    /// which falls back to Mesa's software rasterizer when there is no GPU, and every window
+    /// draws into an offscreen texture. Nothing happens until the caller steps the loop with
+    /// `headless_step`, which makes it usable for snapshot tests in CI.
+    pub fn headless_start(&mut self) {
+        self.os_type = OsType::LinuxWindow(LinuxWindowParams {
+            custom_window_chrome: false
+        });
+        self.gpu_info.performance = GpuPerformance::Tier1;
+        if self.os.opengl_cx.is_none() {
+            self.os.opengl_cx = Some(unsafe {
+                OpenglCx::from_egl_platform_display(
+                    egl_sys::EGL_PLATFORM_SURFACELESS_MESA,
+                    ptr::null_mut::<()>(),
+                )
+            });
+        }
+        self.call_event_handler(&Event::Startup);
+        self.redraw_all();
+        self.headless_step();
+    }
+
+    /// Runs one iteration of the event loop: platform ops, signals, timers, next frames,
+    /// drawing and painting every window into its offscreen texture.
+    pub fn headless_step(&mut self) {
+        if SignalToUI::check_and_clear_ui_signal() {
+            self.handle_media_signals();
+            self.handle_web_socket_events();
+            self.handle_dialog_events();
+            self.call_event_handler(&Event::Signal);
+        }
+        for event in self.os.stdin_timers.get_dispatch() {
+            self.call_event_handler(&event);
+        }
+        self.handle_networking_events();
+        self.headless_handle_platform_ops();
+
+        if self.new_next_frames.len() != 0 {
+            self.call_next_frame_event(self.seconds_since_app_start());
+        }
+        if self.need_redrawing() {
+            self.call_draw_event();
+            self.os.opengl_cx.as_ref().unwrap().make_current();
+            self.opengl_compile_shaders();
+        }
+        // drawing can open windows, give them their render target before painting
+        self.headless_handle_platform_ops();
+        self.headless_handle_repaint();
+    }
+
+    /// Steps the loop until nothing is left to draw or paint, or `max_steps` is reached.
+    /// Returns false if the app was still busy, for instance because of a running animation.
+    pub fn headless_settle(&mut self, max_steps: usize) -> bool {
+        for _ in 0..max_steps {
+            self.headless_step();
+            if self.platform_ops.len() == 0 && self.new_next_frames.len() == 0 && !self.need_redrawing() && !self.any_passes_dirty() {
+                return true
+            }
+        }
+        false
+    }
+
+    /// Sends an input event to the app the way a native window would, including the
+    /// finger and keyboard bookkeeping, and steps the loop once.
+    pub fn headless_send_event(&mut self, event: Event) {
+        match event {
+            Event::MouseDown(e) => {
+                self.fingers.process_tap_count(e.abs, e.time);
+                self.fingers.mouse_down(e.button, e.window_id);
+                self.call_event_handler(&Event::MouseDown(e))
+            }
+            Event::MouseMove(e) => {
+                self.call_event_handler(&Event::MouseMove(e));
+                self.fingers.cycle_hover_area(live_id!(mouse).into());
+                self.fingers.switch_captures();
+            }
+            Event::MouseUp(e) => {
+                let button = e.button;
+                self.call_event_handler(&Event::MouseUp(e));
+                self.fingers.mouse_up(button);
+                self.fingers.cycle_hover_area(live_id!(mouse).into());
+            }
+            Event::KeyDown(e) => {
+                self.keyboard.process_key_down(e.clone());
+                self.call_event_handler(&Event::KeyDown(e))
+            }
+            Event::KeyUp(e) => {
+                self.keyboard.process_key_up(e.clone());
+                self.call_event_handler(&Event::KeyUp(e))
+            }
+            event => self.call_event_handler(&event)
+        }
+        self.headless_step();
+    }
+
+    /// Resizes a headless window, as if the user dragged its border or moved it to a
+    /// screen with another scale.
+    pub fn headless_resize_window(&mut self, window_id: WindowId, inner_size: DVec2, dpi_factor: f64) {
+        let old_geom = self.windows[window_id].window_geom.clone();
+        let new_geom = WindowGeom {
+            inner_size,
+            dpi_factor,
+            ..old_geom.clone()
+        };
+        self.windows[window_id].window_geom = new_geom.clone();
+        self.call_window_geom_change_event(WindowGeomChangeEvent {
+            window_id,
+            old_geom,
+            new_geom,
+        });
+        self.headless_step();
+    }
+
+    /// The windows that are open in headless mode, in the order they were created.
+    pub fn headless_window_ids(&self) -> Vec<WindowId> {
+        self.os.headless_windows.iter().map( | w | w.window_id).collect()
+    }
+
+    /// Reads back the last frame painted into a headless window. The pixels use the
+    /// layout of `PassReadBackEvent`, `PassReadBackEvent::to_rgba` converts them.
+    pub fn headless_read_pixels(&mut self, window_id: WindowId) -> Option<PassReadBackEvent> {
+        let window = self.os.headless_windows.iter().find( | w | w.window_id == window_id) ?;
+        let texture_id = window.texture.texture_id();
+        let pass_id = self.windows[window_id].main_pass_id ?;
+        let gl_framebuffer = self.passes[pass_id].os.gl_framebuffer ?;
+        let dpi_factor = self.passes[pass_id].dpi_factor ?;
+        let pass_size = self.get_pass_rect(pass_id, dpi_factor) ?.size;
+        let alloc_height = self.textures[texture_id].alloc.as_ref() ?.height as i32;
+        self.os.opengl_cx.as_ref().unwrap().make_current();
+        unsafe {
+            gl_sys::BindFramebuffer(gl_sys::FRAMEBUFFER, gl_framebuffer);
+        }
+        // the viewport sits at the top of the render target, see `draw_pass_to_texture`
+        let viewport_y = (alloc_height - (pass_size.y * dpi_factor) as i32).max(0);
+        let event = Self::read_back_pixels(
+            pass_id,
+            Rect {pos: DVec2::default(), size: pass_size},
+            dpi_factor,
+            pass_size,
+            viewport_y
+        );
+        unsafe {
+            gl_sys::BindFramebuffer(gl_sys::FRAMEBUFFER, 0);
+        }
+        Some(event)
+    }
+
+    fn headless_handle_repaint(&mut self) {
+        self.os.opengl_cx.as_ref().unwrap().make_current();
+        let mut passes_todo = Vec::new();
+        self.compute_pass_repaint_order(&mut passes_todo);
+        self.repaint_id += 1;
+        let time = self.seconds_since_app_start() as f32;
+        for &pass_id in &passes_todo {
+            self.passes[pass_id].set_time(time);
+            match self.passes[pass_id].parent.clone() {
+                CxPassParent::Window(window_id) => {
+                    let texture = self.os.headless_windows.iter().find( | w | w.window_id == window_id).map( | w | w.texture.clone());
+                    if let Some(texture) = texture {
+                        self.passes[pass_id].paint_dirty = false;
+                        self.draw_pass_to_texture(pass_id, &texture);
+                    }
+                }
+                CxPassParent::Pass(_) | CxPassParent::None => {
+                    self.draw_pass_to_magic_texture(pass_id);
+                }
+            }
+        }
+        // make sure the frame is complete before anyone reads it back
+        unsafe {gl_sys::Finish();}
+    }
+
+    fn headless_handle_platform_ops(&mut self) {
+        while let Some(op) = self.platform_ops.pop() {
+            match op {
+                CxOsOp::CreateWindow(window_id) => {
+                    let texture = Texture::new_with_format(self, TextureFormat::RenderBGRAu8 {
+                        size: TextureSize::Auto
+                    });
+                    self.os.headless_windows.push(HeadlessWindow {window_id, texture});
+                    let window = &mut self.windows[window_id];
+                    window.window_geom = WindowGeom {
+                        inner_size: window.create_inner_size.unwrap_or(dvec2(800., 600.)),
+                        position: window.create_position.unwrap_or_default(),
+                        dpi_factor: window.dpi_override.unwrap_or(1.0),
+                        ..Default::default()
+                    };
+                    window.is_created = true;
+                    self.redraw_window(window_id);
+                },
+                CxOsOp::CloseWindow(window_id) => {
+                    if let Some(index) = self.os.headless_windows.iter().position( | w | w.window_id == window_id) {
+                        self.os.headless_windows.remove(index);
+                        self.windows[window_id].is_created = false;
+                        self.call_event_handler(&Event::WindowClosed(WindowClosedEvent {window_id}));
+                    }
+                },
+                CxOsOp::StartTimer {timer_id, interval, repeats} => {
+                    self.os.stdin_timers.timers.insert(timer_id, PollTimer::new(interval, repeats));
+                },
+                CxOsOp::StopTimer(timer_id) => {
+                    self.os.stdin_timers.timers.remove(&timer_id);
+                },
+                CxOsOp::HttpRequest {request_id, request} => {
+                    self.os.http_requests.request(request_id, request);
+                },
+                CxOsOp::CancelHttpRequest {request_id} => {
+                    self.os.http_requests.cancel(request_id);
+                },
+                CxOsOp::ShowDialog(dialog_id, dialog) => {
+                    self.show_dialog_fallback(dialog_id, dialog);
+                },
+                _ => ()
+            }
+        }
+    }
+}
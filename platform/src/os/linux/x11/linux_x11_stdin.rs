@@ -11,10 +11,11 @@ use {
         event::Event,
         CxOsApi,
         window::CxWindowPool,
-        event::WindowGeom,
+        event::{WindowGeom, WindowGeomChangeEvent},
         texture::{Texture, TextureFormat},
         thread::SignalToUI,
         os::cx_stdin::{aux_chan, HostToStdin, PresentableDraw, StdinToHost, Swapchain, PollTimer},
+        os::linux::linux_dialogs::show_dialog,
         pass::{CxPassParent, PassClearColor, CxPassColorTexture},
         cx_api::CxOsOp,
         cx::Cx,
@@ -128,6 +129,9 @@ impl Cx {
                 HostToStdin::TextInput(e) => {
                     self.call_event_handler(&Event::TextInput(e));
                 }
+                HostToStdin::TextComposition(e) => {
+                    self.call_event_handler(&Event::TextComposition(e));
+                }
                 HostToStdin::MouseDown(e) => {
                     self.fingers.process_tap_count(
                         dvec2(e.x,e.y),
@@ -165,13 +169,21 @@ impl Cx {
                     self.call_event_handler(&Event::Scroll(e.into_event(window_id,pos)))
                 }
                 HostToStdin::WindowGeomChange { dpi_factor, left, top, width, height, window_id } => {
-                    self.windows[CxWindowPool::from_usize(window_id)].window_geom = WindowGeom {
+                    let window_id = CxWindowPool::from_usize(window_id);
+                    let old_geom = self.windows[window_id].window_geom.clone();
+                    let new_geom = WindowGeom {
                         dpi_factor,
                         position: dvec2(left, top),
                         inner_size: dvec2(width, height),
                         ..Default::default()
                     };
+                    self.windows[window_id].window_geom = new_geom.clone();
                     self.redraw_all();
+                    self.call_window_geom_change_event(WindowGeomChangeEvent {
+                        window_id,
+                        old_geom,
+                        new_geom
+                    });
                 }
                 HostToStdin::Swapchain(new_swapchain) => {
                     let new_swapchain = new_swapchain.images_map(|pi| {
@@ -226,6 +238,8 @@ impl Cx {
                     // check signals
                     if SignalToUI::check_and_clear_ui_signal(){
                         self.handle_media_signals();
+                        self.handle_web_socket_events();
+                        self.handle_dialog_events();
                         self.call_event_handler(&Event::Signal);
                     }
                     for event in self.os.stdin_timers.get_dispatch() {
@@ -282,6 +296,17 @@ impl Cx {
                 CxOsOp::StopTimer(timer_id) => {
                     self.os.stdin_timers.timers.remove(&timer_id);
                 },
+                CxOsOp::HttpRequest {request_id, request} => {
+                    self.os.http_requests.request(request_id, request);
+                },
+                CxOsOp::CancelHttpRequest {request_id} => {
+                    self.os.http_requests.cancel(request_id);
+                },
+                CxOsOp::ShowDialog(dialog_id, dialog) => {
+                    if let Err(dialog) = show_dialog(dialog_id, dialog, self.dialog_sender()) {
+                        self.show_dialog_fallback(dialog_id, dialog);
+                    }
+                },
                 _ => ()
                 /*
                 CxOsOp::CloseWindow(_window_id) => {},
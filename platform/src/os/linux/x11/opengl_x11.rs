@@ -31,6 +31,7 @@ impl Cx {
         pass_id: PassId,
         opengl_window: &mut OpenglWindow,
     ) {
+        self.skip_read_back(pass_id);
         let draw_list_id = self.passes[pass_id].main_draw_list_id.unwrap();
         
         self.setup_render_pass(pass_id);
@@ -56,6 +57,7 @@ impl Cx {
                 PassClearColor::ClearWith(color) => color
             }
         };
+        let clear_color = self.get_delegated_clear_color(pass_id, clear_color);
         let clear_depth = match self.passes[pass_id].clear_depth {
             PassClearDepth::InitWith(depth) => depth,
             PassClearDepth::ClearWith(depth) => depth
@@ -317,8 +319,15 @@ impl OpenglCx {
             "can't bind EGL_OPENGL_ES_API",
         );
 
-        // Choose framebuffer configuration.
+        // Choose framebuffer configuration, a surfaceless display has no window configs.
+        let surface_type = if egl_platform == egl_sys::EGL_PLATFORM_SURFACELESS_MESA {
+            egl_sys::EGL_PBUFFER_BIT
+        } else {
+            egl_sys::EGL_WINDOW_BIT
+        };
         let cfg_attribs = [
+            egl_sys::EGL_SURFACE_TYPE,
+            surface_type,
             egl_sys::EGL_RED_SIZE,
             8,
             egl_sys::EGL_GREEN_SIZE,
@@ -9,6 +9,7 @@ use std::os::raw::{
     c_void,
     c_char,
     c_uchar,
+    c_ushort,
 };
 
 
@@ -34,6 +35,7 @@ pub type XrmDatabase = *mut _XrmHashBucketRec;
 pub type XKeyPressedEvent = XKeyEvent;
 pub type XComposeStatus = _XComposeStatus;
 pub type GC = *mut _XGC;
+pub type XIMFeedback = c_ulong;
 
 pub const None: u32 = 0;
 pub const True: u32 = 1;
@@ -45,6 +47,11 @@ pub const CurrentTime: u32 = 0;
 pub const SelectionNotify: u32 = 31;
 pub const AnyPropertyType: u32 = 0;
 pub const SelectionRequest: u32 = 30;
+pub const SelectionClear: u32 = 29;
+pub const PropertyNotify: u32 = 28;
+pub const PropertyNewValue: u32 = 0;
+pub const PropertyDelete: u32 = 1;
+pub const Success: u32 = 0;
 pub const PropModeReplace: u32 = 0;
 pub const DestroyNotify: u32 = 17;
 pub const ConfigureNotify: u32 = 22;
@@ -53,6 +60,8 @@ pub const LeaveNotify: u32 = 8;
 pub const MotionNotify: u32 = 6;
 pub const AllocNone: u32 = 0;
 pub const InputOutput: u32 = 1;
+pub const InputOnly: u32 = 2;
+pub const CopyFromParent: u32 = 0;
 pub const ClientMessage: u32 = 33;
 pub const KeyPress: u32 = 2;
 pub const KeyRelease: u32 = 3;
@@ -80,6 +89,7 @@ pub const VisibilityChangeMask: u32 = 65536;
 pub const FocusChangeMask: u32 = 2097152;
 pub const EnterWindowMask: u32 = 16;
 pub const LeaveWindowMask: u32 = 32;
+pub const PropertyChangeMask: u32 = 4194304;
 pub const XBufferOverflow: i32 = -1;
 
 // Added, from https://community.khronos.org/t/list-for-xevent-structures-type-component/70768
@@ -89,12 +99,29 @@ pub const VisibilityUnobscured: i32 = 0;
 pub const VisibilityPartiallyObscured: i32 = 1;
 pub const VisibilityFullyObscured: i32 = 2;
 
+pub const XIMPreeditCallbacks: u32 = 2;
 pub const XIMPreeditNothing: u32 = 8;
 pub const XIMStatusNothing: u32 = 1024;
 
+pub const XIMReverse: XIMFeedback = 1;
+pub const XIMUnderline: XIMFeedback = 2;
+pub const XIMHighlight: XIMFeedback = 4;
+
+pub const XIMForwardChar: c_int = 0;
+pub const XIMBackwardChar: c_int = 1;
+pub const XIMLineStart: c_int = 8;
+pub const XIMLineEnd: c_int = 9;
+pub const XIMAbsolutePosition: c_int = 10;
+
 pub const XNInputStyle: &'static [u8; 11usize] = b"inputStyle\0";
 pub const XNClientWindow: &'static [u8; 13usize] = b"clientWindow\0";
 pub const XNFocusWindow: &'static [u8; 12usize] = b"focusWindow\0";
+pub const XNPreeditAttributes: &'static [u8; 18usize] = b"preeditAttributes\0";
+pub const XNPreeditStartCallback: &'static [u8; 21usize] = b"preeditStartCallback\0";
+pub const XNPreeditDoneCallback: &'static [u8; 20usize] = b"preeditDoneCallback\0";
+pub const XNPreeditDrawCallback: &'static [u8; 20usize] = b"preeditDrawCallback\0";
+pub const XNPreeditCaretCallback: &'static [u8; 21usize] = b"preeditCaretCallback\0";
+pub const XNSpotLocation: &'static [u8; 13usize] = b"spotLocation\0";
 
 pub const Mod1Mask: u32 = 8;
 pub const ShiftMask: u32 = 1;
@@ -262,6 +289,11 @@ extern "C" {
         arg3: c_int,
     ) -> Atom;
     
+    pub fn XGetAtomName(
+        arg1: *mut Display,
+        arg2: Atom,
+    ) -> *mut c_char;
+    
     pub fn XrmInitialize();
     
     pub fn XCloseIM(arg1: XIM) -> c_int;
@@ -311,6 +343,17 @@ extern "C" {
     pub fn XDefaultScreen(arg1: *mut Display) -> c_int;
     
     pub fn XRootWindow(arg1: *mut Display, arg2: c_int) -> Window;
+    
+    pub fn XTranslateCoordinates(
+        arg1: *mut Display,
+        arg2: Window,
+        arg3: Window,
+        arg4: c_int,
+        arg5: c_int,
+        arg6: *mut c_int,
+        arg7: *mut c_int,
+        arg8: *mut Window,
+    ) -> c_int;
 
     pub fn XGetVisualInfo(
         arg1: *mut Display,
@@ -360,6 +403,16 @@ extern "C" {
     
     pub fn XCreateIC(arg1: XIM, ...) -> XIC;
     
+    pub fn XSetICValues(arg1: XIC, ...) -> *mut c_char;
+    
+    pub fn XSetICFocus(arg1: XIC);
+    
+    pub fn XVaCreateNestedList(arg1: c_int, ...) -> *mut c_void;
+    
+    pub fn XSetLocaleModifiers(arg1: *const c_char) -> *mut c_char;
+    
+    pub fn XFilterEvent(arg1: *mut XEvent, arg2: Window) -> c_int;
+    
     pub fn XDestroyWindow(arg1: *mut Display, arg2: Window) -> c_int;
     
     pub fn XIconifyWindow(
@@ -378,6 +431,8 @@ extern "C" {
     
     pub fn XrmGetStringDatabase(arg1: *const c_char) -> XrmDatabase;
     
+    pub fn XrmDestroyDatabase(arg1: XrmDatabase);
+    
     pub fn XrmGetResource(
         arg1: XrmDatabase,
         arg2: *const c_char,
@@ -411,6 +466,16 @@ extern "C" {
         arg4: Time,
     ) -> c_int;
     
+    pub fn XGetSelectionOwner(arg1: *mut Display, arg2: Atom) -> Window;
+    
+    pub fn XSelectInput(arg1: *mut Display, arg2: Window, arg3: c_long) -> c_int;
+    
+    pub fn XDeleteProperty(arg1: *mut Display, arg2: Window, arg3: Atom) -> c_int;
+    
+    pub fn XMaxRequestSize(arg1: *mut Display) -> c_long;
+    
+    pub fn XExtendedMaxRequestSize(arg1: *mut Display) -> c_long;
+    
     pub fn Xutf8LookupString(
         arg1: XIC,
         arg2: *mut XKeyPressedEvent,
@@ -455,6 +520,58 @@ pub struct _XIC {
     _unused: [u8; 0],
 }
 
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct XPoint {
+    pub x: c_short,
+    pub y: c_short,
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct XIMCallback {
+    pub client_data: XPointer,
+    pub callback: ::std::option::Option<
+    unsafe extern "C" fn(ic: XIC, client_data: XPointer, call_data: XPointer),
+    >,
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct XICCallback {
+    pub client_data: XPointer,
+    pub callback: ::std::option::Option<
+    unsafe extern "C" fn(ic: XIC, client_data: XPointer, call_data: XPointer) -> c_int,
+    >,
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct XIMText {
+    pub length: c_ushort,
+    pub feedback: *mut XIMFeedback,
+    pub encoding_is_wchar: c_int,
+    /// Either a multibyte string in the locale's encoding, or wide chars.
+    pub string: *mut c_char,
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct XIMPreeditDrawCallbackStruct {
+    pub caret: c_int,
+    pub chg_first: c_int,
+    pub chg_length: c_int,
+    pub text: *mut XIMText,
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct XIMPreeditCaretCallbackStruct {
+    pub position: c_int,
+    pub direction: c_int,
+    pub style: c_int,
+}
+
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
 pub struct _XComposeStatus {
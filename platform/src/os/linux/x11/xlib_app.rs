@@ -4,17 +4,22 @@ use {
         mem,
         rc::Rc,
         cell::{Cell, RefCell},
-        os::raw::{c_char, c_int, c_uint, c_ulong, c_void, c_uchar, c_long},
+        os::raw::{c_char, c_int, c_uint, c_ulong, c_long},
+        ffi::{CStr, CString},
         ptr,
     },
     self::super::{
         x11_sys,
         xlib_event::XlibEvent,
         xlib_window::*,
+        xlib_clipboard::{XlibClipboard, XlibClipboardEvent},
         super::select_timer::SelectTimers,
+        super::libc_sys,
     },
     crate::{
         makepad_math::DVec2,
+        makepad_live_id::LiveId,
+        window::CxWindowPool,
         event::*,
         cursor::MouseCursor,
         os::cx_native::EventFlow,
@@ -39,7 +44,7 @@ pub struct XlibApp {
     pub display: *mut x11_sys::Display,
     event_loop_running: bool,
     pub xim: x11_sys::XIM,
-    pub clipboard: String,
+    pub clipboard: XlibClipboard,
     pub display_fd: c_int,
     //pub signal_fds: [c_int; 2],
     pub window_map: HashMap<c_ulong, *mut XlibWindow>,
@@ -56,6 +61,8 @@ pub struct XlibApp {
     pub internal_cursor: MouseCursor,
     pub atoms: XlibAtoms,
     pub dnd: Dnd,
+    /// The scale of the desktop, from the Xft.dpi resource. It changes when the resources do.
+    pub dpi_factor: f64,
 }
 
 impl XlibApp {
@@ -63,10 +70,19 @@ impl XlibApp {
         unsafe {
             let display = x11_sys::XOpenDisplay(ptr::null());
             let display_fd = x11_sys::XConnectionNumber(display);
+            // the input method is picked by the XMODIFIERS of the environment, and it sends its
+            // preedit text in the encoding of the locale
+            libc_sys::setlocale(libc_sys::LC_CTYPE, "\0".as_ptr() as *const c_char);
+            x11_sys::XSetLocaleModifiers("\0".as_ptr() as *const c_char);
             let xim = x11_sys::XOpenIM(display, ptr::null_mut(), ptr::null_mut(), ptr::null_mut());
             //let mut signal_fds = [0, 0];
             //libc_sys::pipe(signal_fds.as_mut_ptr());
             x11_sys::XrmInitialize();
+            // the desktop keeps its scale in the resources on the root window, we get told when
+            // they change so windows can be redrawn at the new scale
+            let root_window = x11_sys::XRootWindow(display, x11_sys::XDefaultScreen(display));
+            x11_sys::XSelectInput(display, root_window, x11_sys::PropertyChangeMask as c_long);
+            let dpi_factor = xft_dpi_factor(x11_sys::XResourceManagerString(display));
             XlibApp {
                 event_loop_running: true,
                 event_callback: Some(event_callback),
@@ -75,7 +91,7 @@ impl XlibApp {
                 display,
                 display_fd,
                 //signal_fds,
-                clipboard: String::new(),
+                clipboard: XlibClipboard::new(display),
                 last_scroll_time: 0.0,
                 last_click_time: 0.0,
                 last_click_pos: (0, 0),
@@ -86,6 +102,49 @@ impl XlibApp {
                 current_cursor: MouseCursor::Default,
                 internal_cursor: MouseCursor::Default,
                 dnd: Dnd::new(display),
+                dpi_factor,
+            }
+        }
+    }
+    
+    /// Rereads the scale of the desktop after the resources changed, and sends the windows a
+    /// geometry change if it is another one.
+    unsafe fn update_dpi_factor(&mut self) {
+        let root_window = x11_sys::XRootWindow(self.display, x11_sys::XDefaultScreen(self.display));
+        let mut actual_type = 0;
+        let mut actual_format = 0;
+        let mut nitems = 0;
+        let mut bytes_after = 0;
+        let mut prop = ptr::null_mut();
+        x11_sys::XGetWindowProperty(
+            self.display,
+            root_window,
+            self.atoms.resource_manager,
+            0,
+            c_long::MAX / 4,
+            0,
+            31, // XA_STRING
+            &mut actual_type,
+            &mut actual_format,
+            &mut nitems,
+            &mut bytes_after,
+            &mut prop
+        );
+        if prop.is_null() {
+            return
+        }
+        // the property isn't nul terminated
+        let resources = CString::new(std::slice::from_raw_parts(prop, nitems as usize).to_vec());
+        x11_sys::XFree(prop as *mut _);
+        let dpi_factor = match resources {
+            Ok(resources) => xft_dpi_factor(resources.as_ptr()),
+            Err(_) => return
+        };
+        if dpi_factor != self.dpi_factor {
+            self.dpi_factor = dpi_factor;
+            let windows: Vec<*mut XlibWindow> = self.window_map.values().copied().collect();
+            for window_ptr in windows {
+                (*window_ptr).send_change_event();
             }
         }
     }
@@ -97,109 +156,35 @@ impl XlibApp {
             let mut event = mem::MaybeUninit::uninit();
             x11_sys::XNextEvent(self.display, event.as_mut_ptr());
             let mut event = event.assume_init();
+            // key presses that go into a composition are eaten by the input method
+            if x11_sys::XFilterEvent(&mut event, 0) != 0 {
+                continue;
+            }
             match event.type_ as u32 {
                 x11_sys::SelectionNotify => {
                     let selection = event.xselection;
                     if selection.property == self.dnd.atoms.selection {
-                        self.dnd.handle_selection_event(&selection);
-                    } else {
-                        // first get the size of the thing
-                        let mut actual_type = mem::MaybeUninit::uninit();
-                        let mut actual_format = mem::MaybeUninit::uninit();
-                        let mut n_items = mem::MaybeUninit::uninit();
-                        let mut bytes_to_read = mem::MaybeUninit::uninit();
-                        let mut ret = mem::MaybeUninit::uninit();
-                        x11_sys::XGetWindowProperty(
-                            self.display,
-                            selection.requestor,
-                            selection.property,
-                            0,
-                            0,
-                            0,
-                            x11_sys::AnyPropertyType as c_ulong,
-                            actual_type.as_mut_ptr(),
-                            actual_format.as_mut_ptr(),
-                            n_items.as_mut_ptr(),
-                            bytes_to_read.as_mut_ptr(),
-                            ret.as_mut_ptr()
-                        );
-                        //let actual_type = actual_type.assume_init();
-                        //let actual_format = actual_format.assume_init();
-                        //let n_items = n_items.assume_init();
-                        let bytes_to_read = bytes_to_read.assume_init();
-                        //let mut ret = ret.assume_init();
-                        let mut bytes_after = mem::MaybeUninit::uninit();
-                        x11_sys::XGetWindowProperty(
-                            self.display,
-                            selection.requestor,
-                            selection.property,
-                            0,
-                            bytes_to_read as c_long,
-                            0,
-                            x11_sys::AnyPropertyType as c_ulong,
-                            actual_type.as_mut_ptr(),
-                            actual_format.as_mut_ptr(),
-                            n_items.as_mut_ptr(),
-                            bytes_after.as_mut_ptr(),
-                            ret.as_mut_ptr()
-                        );
-                        let ret = ret.assume_init();
-                        //let bytes_after = bytes_after.assume_init();
-                        if ret != ptr::null_mut() && bytes_to_read > 0 {
-                            let utf8_slice = std::slice::from_raw_parts::<u8>(ret as *const _ as *const u8, bytes_to_read as usize);
-                            if let Ok(utf8_string) = String::from_utf8(utf8_slice.to_vec()) {
-                                self.do_callback(XlibEvent::TextInput(TextInputEvent {
-                                    input: utf8_string,
-                                    was_paste: true,
-                                    replace_last: false
-                                }));
-                            }
-                            x11_sys::XFree(ret as *mut _ as *mut c_void);
+                        if let Some(event) = self.dnd.handle_selection_event(&selection) {
+                            self.do_callback(XlibEvent::Drop(event));
                         }
                     }
+                    else if let Some(event) = self.clipboard.handle_selection_event(&selection) {
+                        self.send_clipboard_event(event);
+                    }
                 },
                 x11_sys::SelectionRequest => {
-                    let request = event.xselectionrequest;
-                    let mut response = x11_sys::XSelectionEvent {
-                        type_: x11_sys::SelectionNotify as i32,
-                        serial: 0,
-                        send_event: 0,
-                        display: self.display,
-                        requestor: request.requestor,
-                        selection: request.selection,
-                        target: request.target,
-                        time: request.time,
-                        property: request.property,
-                    };
-                    if request.target == self.atoms.targets {
-                        let mut targets = [self.atoms.utf8_string];
-                        x11_sys::XChangeProperty(
-                            self.display,
-                            request.requestor,
-                            request.property,
-                            4,
-                            32,
-                            x11_sys::PropModeReplace as i32,
-                            targets.as_mut() as *mut _ as *mut c_uchar,
-                            targets.len() as i32
-                        );
-                    }
-                    else if request.target == self.atoms.utf8_string {
-                        x11_sys::XChangeProperty(
-                            self.display,
-                            request.requestor,
-                            request.property,
-                            self.atoms.utf8_string,
-                            8,
-                            x11_sys::PropModeReplace as i32,
-                            self.clipboard.as_ptr() as *const _ as *const c_uchar,
-                            self.clipboard.len() as i32
-                        );
+                    self.clipboard.handle_selection_request_event(&event.xselectionrequest);
+                },
+                x11_sys::SelectionClear => {
+                    self.clipboard.handle_selection_clear_event(&event.xselectionclear);
+                },
+                x11_sys::PropertyNotify => {
+                    if event.xproperty.atom == self.atoms.resource_manager {
+                        self.update_dpi_factor();
                     }
-                    else {
-                        response.property = 0;
+                    else if let Some(event) = self.clipboard.handle_property_event(&event.xproperty) {
+                        self.send_clipboard_event(event);
                     }
-                    x11_sys::XSendEvent(self.display, request.requestor, 1, 0, &mut response as *mut _ as *mut x11_sys::XEvent);
                 },
                 x11_sys::DestroyNotify => { // our window got destroyed
                     let destroy_window = event.xdestroywindow;
@@ -355,6 +340,14 @@ impl XlibApp {
                             }))
                             
                         }
+                        else if button.button == 8 || button.button == 9 {
+                            self.do_callback(XlibEvent::Navigate(NavigateEvent {
+                                window_id: window.window_id,
+                                direction: if button.button == 8 {NavigateDirection::Back} else {NavigateDirection::Forward},
+                                abs: window.last_mouse_pos,
+                                time: time_now
+                            }))
+                        }
                         else {
                             // do all the 'nonclient' area messaging to the window manager
                             if let Some(last_nc_mode) = window.last_nc_mode {
@@ -410,7 +403,9 @@ impl XlibApp {
                 },
                 x11_sys::ButtonRelease => { // mouse up
                     let button = event.xbutton;
-                    if let Some(window_ptr) = self.window_map.get(&button.window) {
+                    // the back and forward buttons only navigate
+                    let navigates = button.button == 8 || button.button == 9;
+                    if let (false, Some(window_ptr)) = (navigates, self.window_map.get(&button.window)) {
                         let window = &mut (**window_ptr);
                         window.send_mouse_up(button.button as usize, self.xkeystate_to_modifiers(button.state))
                     }
@@ -426,14 +421,9 @@ impl XlibApp {
                                 match key_code {
                                     KeyCode::KeyV => { // paste
                                         // request the pasteable text from the other side
-                                        x11_sys::XConvertSelection(
-                                            self.display,
-                                            self.atoms.clipboard,
-                                            self.atoms.utf8_string,
-                                            self.atoms.clipboard,
-                                            window.window.unwrap(),
-                                            event.xkey.time
-                                        );
+                                        if let Some(text) = self.clipboard.request_paste(event.xkey.time) {
+                                            self.send_paste(text);
+                                        }
                                         /*
                                         self.do_callback(&mut vec![
                                             Event::TextInput(TextInputEvent {
@@ -445,23 +435,23 @@ impl XlibApp {
                                         */
                                     }
                                     KeyCode::KeyC => {
-                                        let response = Rc::new(RefCell::new(None));
-                                        self.do_callback(XlibEvent::TextCopy(TextClipboardEvent {
-                                            response: response.clone()
-                                        }));
-                                        let response = response.borrow();
-                                        if let Some(response) = response.as_ref() {
-                                            self.copy_to_clipboard(response, window.window.unwrap(), event.xkey.time);
+                                        let clipboard_event = TextClipboardEvent {
+                                            response: Rc::new(RefCell::new(None)),
+                                            rich_response: Rc::new(RefCell::new(None))
+                                        };
+                                        self.do_callback(XlibEvent::TextCopy(clipboard_event.clone()));
+                                        if let Some(flavors) = clipboard_event.flavors() {
+                                            self.clipboard.copy(flavors, event.xkey.time);
                                         }
                                     }
                                     KeyCode::KeyX => {
-                                        let response = Rc::new(RefCell::new(None));
-                                        self.do_callback(XlibEvent::TextCut(TextClipboardEvent {
-                                            response: response.clone()
-                                        }));
-                                        let response = response.borrow();
-                                        if let Some(response) = response.as_ref() {
-                                            self.copy_to_clipboard(response, window.window.unwrap(), event.xkey.time);
+                                        let clipboard_event = TextClipboardEvent {
+                                            response: Rc::new(RefCell::new(None)),
+                                            rich_response: Rc::new(RefCell::new(None))
+                                        };
+                                        self.do_callback(XlibEvent::TextCut(clipboard_event.clone()));
+                                        if let Some(flavors) = clipboard_event.flavors() {
+                                            self.clipboard.copy(flavors, event.xkey.time);
                                         }
                                     }
                                     _ => ()
@@ -479,11 +469,11 @@ impl XlibApp {
                         }else {false};
                         
                         if !block_text {
-                            // decode the character
-                            let mut buffer = [0u8; 32];
+                            // decode the character, or the text an input method composed
+                            let mut buffer = vec![0u8; 32];
                             let mut keysym = mem::MaybeUninit::uninit();
                             let mut status = mem::MaybeUninit::uninit();
-                            let count = x11_sys::Xutf8LookupString(
+                            let mut count = x11_sys::Xutf8LookupString(
                                 window.xic.unwrap(),
                                 &mut event.xkey,
                                 buffer.as_mut_ptr() as *mut c_char,
@@ -491,6 +481,17 @@ impl XlibApp {
                                 keysym.as_mut_ptr(),
                                 status.as_mut_ptr(),
                             );
+                            if status.assume_init() == x11_sys::XBufferOverflow {
+                                buffer.resize(count as usize, 0);
+                                count = x11_sys::Xutf8LookupString(
+                                    window.xic.unwrap(),
+                                    &mut event.xkey,
+                                    buffer.as_mut_ptr() as *mut c_char,
+                                    buffer.len() as c_int,
+                                    keysym.as_mut_ptr(),
+                                    status.as_mut_ptr(),
+                                );
+                            }
                             //let keysym = keysym.assume_init();
                             let status = status.assume_init();
                             if status != x11_sys::XBufferOverflow {
@@ -524,13 +525,22 @@ impl XlibApp {
                         }
                     }
                     if event.message_type == self.dnd.atoms.enter {
-                        self.dnd.handle_enter_event(&event);
+                        let window_id = self.window_map.get(&event.window).map_or(CxWindowPool::id_zero(), | window_ptr | (**window_ptr).window_id);
+                        self.dnd.handle_enter_event(&event, window_id);
                     } else if event.message_type == self.dnd.atoms.drop {
-                        self.dnd.handle_drop_event(&event);
+                        if let Some(event) = self.dnd.handle_drop_event(&event) {
+                            self.do_callback(XlibEvent::Drop(event));
+                        }
                     } else if event.message_type == self.dnd.atoms.leave {
                         self.dnd.handle_leave_event(&event);
+                        self.do_callback(XlibEvent::DragEnd);
                     } else if event.message_type == self.dnd.atoms.position {
-                        self.dnd.handle_position_event(&event);
+                        let dpi_factor = self.window_map.get(&event.window).map_or(1.0, | window_ptr | (**window_ptr).last_window_geom.dpi_factor);
+                        if let Some(event) = self.dnd.handle_position_event(&event, dpi_factor) {
+                            let response = event.response.clone();
+                            self.do_callback(XlibEvent::Drag(event));
+                            self.dnd.send_status_event(response.get());
+                        }
                     }
                 },
                 x11_sys::Expose => {
@@ -612,6 +622,10 @@ impl XlibApp {
     
     pub fn terminate_event_loop(&mut self) {
         self.event_loop_running = false;
+        if !self.display.is_null() {
+            // so what we copied can still be pasted after we are gone
+            unsafe {self.clipboard.save_to_clipboard_manager()};
+        }
         if !self.xim.is_null() {
             unsafe {x11_sys::XCloseIM(self.xim)};
             self.xim = ptr::null_mut();
@@ -866,22 +880,37 @@ impl XlibApp {
         }
     }
 
-    pub unsafe fn copy_to_clipboard(&mut self, text: &String, window_id: c_ulong, time: u64) {
-        // store the text on the clipboard
-        self.clipboard = text.clone();
-        // lets set the owner
-        x11_sys::XSetSelectionOwner(
-            self.display,
-            self.atoms.clipboard,
-            window_id,
-            time
-        );
-        x11_sys::XFlush(self.display);
+    pub unsafe fn copy_to_clipboard(&mut self, text: &str, time: u64) {
+        self.clipboard.copy(vec![ClipboardFlavor::Text(text.to_string())], time);
+    }
+    
+    pub unsafe fn write_clipboard(&mut self, flavors: Vec<ClipboardFlavor>, time: u64) {
+        self.clipboard.copy(flavors, time);
+    }
+    
+    /// Starts reading the clipboard. The flavors arrive as `XlibEvent::ClipboardRead`, unless
+    /// they can be returned right away.
+    pub unsafe fn read_clipboard(&mut self, request_id: LiveId, time: u64) -> Option<ClipboardReadEvent> {
+        self.clipboard.request_read(request_id, time)
+    }
+    
+    fn send_clipboard_event(&mut self, event: XlibClipboardEvent) {
+        match event {
+            XlibClipboardEvent::Paste(text) => self.send_paste(text),
+            XlibClipboardEvent::Read(event) => self.do_callback(XlibEvent::ClipboardRead(event)),
+        }
+    }
+    
+    fn send_paste(&mut self, text: String) {
+        self.do_callback(XlibEvent::TextInput(TextInputEvent {
+            input: text,
+            was_paste: true,
+            replace_last: false
+        }));
     }
 }
 
 pub struct XlibAtoms {
-    pub clipboard: x11_sys::Atom,
     pub net_wm_moveresize: x11_sys::Atom,
     pub wm_delete_window: x11_sys::Atom,
     pub wm_protocols: x11_sys::Atom,
@@ -889,18 +918,12 @@ pub struct XlibAtoms {
     pub net_wm_state: x11_sys::Atom,
     pub new_wm_state_maximized_horz: x11_sys::Atom,
     pub new_wm_state_maximized_vert: x11_sys::Atom,
-    pub targets: x11_sys::Atom,
-    pub utf8_string: x11_sys::Atom,
-    pub text: x11_sys::Atom,
-    pub multiple: x11_sys::Atom,
-    pub text_plain: x11_sys::Atom,
-    pub atom: x11_sys::Atom,
+    pub resource_manager: x11_sys::Atom,
 }
 
 impl XlibAtoms {
     fn new(display: *mut x11_sys::Display) -> Self {
         unsafe {Self {
-            clipboard: x11_sys::XInternAtom(display, "CLIPBOARD\0".as_ptr() as *const _, 0),
             net_wm_moveresize: x11_sys::XInternAtom(display, "_NET_WM_MOVERESIZE\0".as_ptr() as *const _, 0),
             wm_delete_window: x11_sys::XInternAtom(display, "WM_DELETE_WINDOW\0".as_ptr() as *const _, 0),
             wm_protocols: x11_sys::XInternAtom(display, "WM_PROTOCOLS\0".as_ptr() as *const _, 0),
@@ -908,13 +931,36 @@ impl XlibAtoms {
             net_wm_state: x11_sys::XInternAtom(display, "_NET_WM_STATE\0".as_ptr() as *const _, 0),
             new_wm_state_maximized_horz: x11_sys::XInternAtom(display, "_NET_WM_STATE_MAXIMIZED_HORZ\0".as_ptr() as *const _, 0),
             new_wm_state_maximized_vert: x11_sys::XInternAtom(display, "_NET_WM_STATE_MAXIMIZED_VERT\0".as_ptr() as *const _, 0),
-            targets: x11_sys::XInternAtom(display, "TARGETS\0".as_ptr() as *const _, 0),
-            utf8_string: x11_sys::XInternAtom(display, "UTF8_STRING\0".as_ptr() as *const _, 1),
-            atom: x11_sys::XInternAtom(display, "ATOM\0".as_ptr() as *const _, 0),
-            text: x11_sys::XInternAtom(display, "TEXT\0".as_ptr() as *const _, 0),
-            text_plain: x11_sys::XInternAtom(display, "text/plain\0".as_ptr() as *const _, 0),
-            multiple: x11_sys::XInternAtom(display, "MULTIPLE\0".as_ptr() as *const _, 0),
+            resource_manager: x11_sys::XInternAtom(display, "RESOURCE_MANAGER\0".as_ptr() as *const _, 0),
         }}
     }
 }
 
+/// The scale factor the Xft.dpi entry of the X resources asks for, 1.0 if there is none.
+unsafe fn xft_dpi_factor(resources: *const c_char) -> f64 {
+    if resources.is_null() {
+        return 1.0
+    }
+    let db = x11_sys::XrmGetStringDatabase(resources);
+    let mut ty = mem::MaybeUninit::uninit();
+    let mut value = mem::MaybeUninit::uninit();
+    let found = x11_sys::XrmGetResource(
+        db,
+        "Xft.dpi\0".as_ptr() as * const _,
+        "String\0".as_ptr() as * const _,
+        ty.as_mut_ptr(),
+        value.as_mut_ptr()
+    );
+    let value = value.assume_init();
+    // TODO find some other way to figure it out when there is no Xft.dpi
+    let dpi_factor = if found == 0 || value.addr.is_null() {
+        1.0
+    }
+    else {
+        CStr::from_ptr(value.addr).to_str().ok()
+            .and_then( | dpi | dpi.trim().parse::<f64>().ok())
+            .map_or(1.0, | dpi | dpi / 96.0)
+    };
+    x11_sys::XrmDestroyDatabase(db);
+    dpi_factor
+}
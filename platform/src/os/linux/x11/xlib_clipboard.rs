@@ -0,0 +1,699 @@
+use {
+    std::{
+        ffi::{CStr, CString},
+        mem,
+        os::raw::{c_int, c_long, c_uchar, c_ulong, c_void},
+        ptr,
+        thread,
+        time::{Duration, Instant},
+    },
+    self::super::x11_sys,
+    crate::{
+        makepad_live_id::LiveId,
+        event::{ClipboardFlavor, ClipboardReadEvent},
+    },
+};
+
+// How long we wait at exit for a clipboard manager to take over the clipboard.
+const CLIPBOARD_MANAGER_TIMEOUT: Duration = Duration::from_secs(2);
+
+// The largest chunk we put in a property at once, servers limit the size of a request.
+const MAX_CHUNK_SIZE: usize = 256 * 1024;
+
+/// The X11 clipboard. Text is offered and read in whatever encoding the other side supports,
+/// the other flavors under their mime type, transfers too large for a single request go in
+/// chunks with the INCR protocol, and when the app exits the clipboard is handed to a
+/// clipboard manager, so copied text can still be pasted afterwards.
+///
+/// A hidden window owns the clipboard, so it stays ours when the window we copied from
+/// closes, and pastes are received on it as well.
+pub struct XlibClipboard {
+    pub atoms: ClipboardAtoms,
+    pub display: *mut x11_sys::Display,
+    pub window: x11_sys::Window,
+    /// The flavors we own the clipboard with.
+    pub flavors: Vec<ClipboardFlavor>,
+    paste: Option<Paste>,
+    read: Option<FlavorRead>,
+    transfers: Vec<IncrTransfer>,
+    chunk_size: usize,
+}
+
+enum Paste {
+    /// We asked which targets the owner supports.
+    Targets,
+    /// We asked for the text in a target, and try the others in order if that fails.
+    Convert {fallbacks: Vec<x11_sys::Atom>},
+    /// The owner sends the text in chunks.
+    Incr {type_: x11_sys::Atom, data: Vec<u8>},
+}
+
+/// The result of a clipboard event.
+pub enum XlibClipboardEvent {
+    Paste(String),
+    Read(ClipboardReadEvent),
+}
+
+// A read of every flavor on the clipboard, for cx.read_clipboard. It converts the targets one
+// after the other, to its own property so it doesn't get in the way of pasting.
+struct FlavorRead {
+    request_id: LiveId,
+    /// The target being converted, TARGETS at first.
+    target: x11_sys::Atom,
+    /// The mime type of the flavor the target becomes.
+    mime: String,
+    /// The targets still to convert, with their mime types.
+    pending: Vec<(x11_sys::Atom, String)>,
+    /// The chunks of a transfer with INCR, and their type.
+    incr: Option<(x11_sys::Atom, Vec<u8>)>,
+    flavors: Vec<ClipboardFlavor>,
+}
+
+// A selection we send in chunks. The requestor deletes the property when it read a chunk,
+// which is when we write the next one.
+struct IncrTransfer {
+    requestor: x11_sys::Window,
+    property: x11_sys::Atom,
+    type_: x11_sys::Atom,
+    data: Vec<u8>,
+    offset: usize,
+}
+
+impl XlibClipboard {
+    pub unsafe fn new(display: *mut x11_sys::Display) -> XlibClipboard {
+        let root = x11_sys::XRootWindow(display, x11_sys::XDefaultScreen(display));
+        let window = x11_sys::XCreateWindow(
+            display,
+            root,
+            0,
+            0,
+            1,
+            1,
+            0,
+            x11_sys::CopyFromParent as c_int,
+            x11_sys::InputOnly as std::os::raw::c_uint,
+            ptr::null_mut(),
+            0,
+            ptr::null_mut()
+        );
+        // we need to know when pasted chunks arrive
+        x11_sys::XSelectInput(display, window, x11_sys::PropertyChangeMask as c_long);
+        let max_request_size = match x11_sys::XExtendedMaxRequestSize(display) {
+            0 => x11_sys::XMaxRequestSize(display),
+            size => size
+        };
+        XlibClipboard {
+            atoms: ClipboardAtoms::new(display),
+            display,
+            window,
+            flavors: Vec::new(),
+            paste: None,
+            read: None,
+            transfers: Vec::new(),
+            // the request size is in units of 4 bytes, and the request itself takes some room
+            chunk_size: (max_request_size as usize * 4).saturating_sub(256).clamp(4096, MAX_CHUNK_SIZE),
+        }
+    }
+
+    /// Takes ownership of the clipboard with the given flavors.
+    pub unsafe fn copy(&mut self, flavors: Vec<ClipboardFlavor>, time: x11_sys::Time) {
+        self.flavors = flavors;
+        x11_sys::XSetSelectionOwner(self.display, self.atoms.clipboard, self.window, time);
+        x11_sys::XFlush(self.display);
+    }
+
+    fn text(&self) -> Option<&str> {
+        self.flavors.iter().find_map( | flavor | if let ClipboardFlavor::Text(text) = flavor {Some(text.as_str())} else {None})
+    }
+
+    /// Starts reading the clipboard. The text arrives with the events we get in return, unless
+    /// we own the clipboard ourselves, then it is returned right away.
+    pub unsafe fn request_paste(&mut self, time: x11_sys::Time) -> Option<String> {
+        let owner = x11_sys::XGetSelectionOwner(self.display, self.atoms.clipboard);
+        if owner == self.window {
+            return self.text().map( | text | text.to_string());
+        }
+        if owner == x11_sys::None as x11_sys::Window {
+            return None;
+        }
+        // First we ask the owner which targets it supports, to pick the best one.
+        self.paste = Some(Paste::Targets);
+        self.convert(self.atoms.targets, self.atoms.paste, time);
+        None
+    }
+
+    /// Starts reading every flavor on the clipboard. They arrive with the events we get in
+    /// return, unless we own the clipboard or it is empty, then they are returned right away.
+    pub unsafe fn request_read(&mut self, request_id: LiveId, time: x11_sys::Time) -> Option<ClipboardReadEvent> {
+        let owner = x11_sys::XGetSelectionOwner(self.display, self.atoms.clipboard);
+        if owner == self.window || owner == x11_sys::None as x11_sys::Window {
+            let flavors = if owner == self.window {self.flavors.clone()} else {Vec::new()};
+            return Some(ClipboardReadEvent {request_id, flavors});
+        }
+        // a read still going on gets its answer, with what it has so far
+        let done = self.read.take().map( | read | ClipboardReadEvent {request_id: read.request_id, flavors: read.flavors});
+        self.read = Some(FlavorRead {
+            request_id,
+            target: self.atoms.targets,
+            mime: String::new(),
+            pending: Vec::new(),
+            incr: None,
+            flavors: Vec::new(),
+        });
+        self.convert(self.atoms.targets, self.atoms.read, time);
+        done
+    }
+
+    unsafe fn convert(&self, target: x11_sys::Atom, property: x11_sys::Atom, time: x11_sys::Time) {
+        x11_sys::XConvertSelection(
+            self.display,
+            self.atoms.clipboard,
+            target,
+            property,
+            self.window,
+            time
+        );
+        x11_sys::XFlush(self.display);
+    }
+
+    /// Handles the owner of the clipboard answering a paste or read request. Returns the pasted
+    /// text or the read flavors, if they are complete.
+    pub unsafe fn handle_selection_event(&mut self, event: &x11_sys::XSelectionEvent) -> Option<XlibClipboardEvent> {
+        if event.selection != self.atoms.clipboard {
+            return None;
+        }
+        // A failed conversion doesn't name our property, so those go by the target.
+        let is_read = event.property == self.atoms.read || (
+            event.property == x11_sys::None as x11_sys::Atom
+                && self.paste.is_none()
+                && self.read.as_ref().map_or(false, | read | read.target == event.target)
+        );
+        if is_read {
+            return self.handle_read_selection_event(event).map(XlibClipboardEvent::Read);
+        }
+        self.handle_paste_selection_event(event).map(XlibClipboardEvent::Paste)
+    }
+
+    unsafe fn handle_paste_selection_event(&mut self, event: &x11_sys::XSelectionEvent) -> Option<String> {
+        let paste = self.paste.take() ?;
+        if event.property == x11_sys::None as x11_sys::Atom {
+            // The owner could not convert to the target. Owners that don't answer TARGETS
+            // usually still know these.
+            let mut fallbacks = match paste {
+                Paste::Targets => vec![self.atoms.utf8_string, self.atoms.string],
+                Paste::Convert {fallbacks} => fallbacks,
+                Paste::Incr {..} => return None,
+            };
+            if !fallbacks.is_empty() {
+                let target = fallbacks.remove(0);
+                self.paste = Some(Paste::Convert {fallbacks});
+                self.convert(target, self.atoms.paste, event.time);
+            }
+            return None;
+        }
+        let (type_, data) = self.read_property(self.window, event.property, true) ?;
+        match paste {
+            Paste::Targets => {
+                let targets = atoms_from_bytes(&data);
+                let mut fallbacks: Vec<x11_sys::Atom> = self.atoms.text_targets()
+                    .into_iter()
+                    .filter( | target | targets.contains(target))
+                    .collect();
+                if fallbacks.is_empty() {
+                    return None;
+                }
+                let target = fallbacks.remove(0);
+                self.paste = Some(Paste::Convert {fallbacks});
+                self.convert(target, self.atoms.paste, event.time);
+                None
+            }
+            Paste::Convert {..} if type_ == self.atoms.incr => {
+                // Deleting the property, which read_property did, tells the owner to send the
+                // first chunk.
+                self.paste = Some(Paste::Incr {type_: x11_sys::None as x11_sys::Atom, data: Vec::new()});
+                None
+            }
+            Paste::Convert {..} => Some(self.decode(type_, &data)),
+            Paste::Incr {..} => None,
+        }
+    }
+
+    unsafe fn handle_read_selection_event(&mut self, event: &x11_sys::XSelectionEvent) -> Option<ClipboardReadEvent> {
+        let mut read = self.read.take() ?;
+        // a failed conversion just leaves out the flavor
+        if event.property != x11_sys::None as x11_sys::Atom {
+            if let Some((type_, data)) = self.read_property(self.window, event.property, true) {
+                if type_ == self.atoms.incr {
+                    read.incr = Some((x11_sys::None as x11_sys::Atom, Vec::new()));
+                    self.read = Some(read);
+                    return None;
+                }
+                if read.target == self.atoms.targets {
+                    read.pending = self.read_targets(&atoms_from_bytes(&data));
+                }
+                else {
+                    self.add_read_flavor(&mut read, type_, data);
+                }
+            }
+        }
+        self.read_next(read, event.time)
+    }
+
+    // Picks the targets to read: the best text target, the first image, preferring png, and
+    // everything else with a mime type.
+    unsafe fn read_targets(&self, targets: &[x11_sys::Atom]) -> Vec<(x11_sys::Atom, String)> {
+        let mut pending = Vec::new();
+        if let Some(text) = self.atoms.text_targets().into_iter().find( | target | targets.contains(target)) {
+            pending.push((text, "text/plain".to_string()));
+        }
+        let mut image = None;
+        for target in targets {
+            let Some(name) = self.atom_name(*target) else {
+                continue
+            };
+            let mime = name.split(';').next().unwrap_or("").trim().to_string();
+            if !mime.contains('/') || mime == "text/plain" || pending.iter().any( | (_, m) | *m == mime) {
+                continue
+            }
+            if mime.starts_with("image/") {
+                if image.is_none() || mime == "image/png" {
+                    image = Some((*target, mime));
+                }
+                continue
+            }
+            pending.push((*target, mime));
+        }
+        pending.extend(image);
+        // they get popped from the end
+        pending.reverse();
+        pending
+    }
+
+    fn add_read_flavor(&self, read: &mut FlavorRead, type_: x11_sys::Atom, data: Vec<u8>) {
+        let flavor = if read.mime == "text/plain" {
+            ClipboardFlavor::Text(self.decode(type_, &data))
+        }
+        else {
+            ClipboardFlavor::from_mime(&read.mime, data)
+        };
+        read.flavors.push(flavor);
+    }
+
+    // Converts the next target of a read, or returns the read if it's done.
+    unsafe fn read_next(&mut self, mut read: FlavorRead, time: x11_sys::Time) -> Option<ClipboardReadEvent> {
+        let Some((target, mime)) = read.pending.pop() else {
+            return Some(ClipboardReadEvent {request_id: read.request_id, flavors: read.flavors});
+        };
+        read.target = target;
+        read.mime = mime;
+        self.read = Some(read);
+        self.convert(target, self.atoms.read, time);
+        None
+    }
+
+    /// Handles properties changing, for transfers in chunks. Returns the pasted text or the
+    /// read flavors, if a transfer in chunks completed them.
+    pub unsafe fn handle_property_event(&mut self, event: &x11_sys::XPropertyEvent) -> Option<XlibClipboardEvent> {
+        if event.state == x11_sys::PropertyDelete as c_int {
+            // the requestor read a chunk we sent, time for the next one
+            if let Some(index) = self.transfers.iter().position( | transfer | {
+                transfer.requestor == event.window && transfer.property == event.atom
+            }) {
+                if self.send_next_chunk(index) {
+                    self.transfers.remove(index);
+                }
+            }
+            return None;
+        }
+        if event.window == self.window && event.atom == self.atoms.read {
+            return self.handle_read_property_event(event).map(XlibClipboardEvent::Read);
+        }
+        if event.window != self.window || event.atom != self.atoms.paste {
+            return None;
+        }
+        let Some(Paste::Incr {..}) = &self.paste else {
+            return None;
+        };
+        let (chunk_type, chunk) = self.read_property(self.window, self.atoms.paste, true) ?;
+        let Some(Paste::Incr {type_, data}) = &mut self.paste else {
+            return None;
+        };
+        if !chunk.is_empty() {
+            *type_ = chunk_type;
+            data.extend_from_slice(&chunk);
+            return None;
+        }
+        // an empty chunk ends the transfer
+        let Some(Paste::Incr {type_, data}) = self.paste.take() else {
+            return None;
+        };
+        Some(XlibClipboardEvent::Paste(self.decode(type_, &data)))
+    }
+
+    unsafe fn handle_read_property_event(&mut self, event: &x11_sys::XPropertyEvent) -> Option<ClipboardReadEvent> {
+        if !self.read.as_ref().map_or(false, | read | read.incr.is_some()) {
+            return None;
+        }
+        let (chunk_type, chunk) = self.read_property(self.window, self.atoms.read, true) ?;
+        let mut read = self.read.take() ?;
+        let (type_, data) = read.incr.as_mut() ?;
+        if !chunk.is_empty() {
+            *type_ = chunk_type;
+            data.extend_from_slice(&chunk);
+            self.read = Some(read);
+            return None;
+        }
+        // an empty chunk ends the transfer
+        let (type_, data) = read.incr.take() ?;
+        if read.target == self.atoms.targets {
+            read.pending = self.read_targets(&atoms_from_bytes(&data));
+        }
+        else {
+            self.add_read_flavor(&mut read, type_, data);
+        }
+        self.read_next(read, event.time)
+    }
+
+    /// Handles another client taking over the clipboard.
+    pub fn handle_selection_clear_event(&mut self, event: &x11_sys::XSelectionClearEvent) {
+        if event.window == self.window && event.selection == self.atoms.clipboard {
+            self.flavors.clear();
+        }
+    }
+
+    /// Handles another client asking for the clipboard we own.
+    pub unsafe fn handle_selection_request_event(&mut self, request: &x11_sys::XSelectionRequestEvent) {
+        // Obsolete clients leave out the property, then the target doubles as the property.
+        let property = if request.property == x11_sys::None as x11_sys::Atom {
+            request.target
+        } else {
+            request.property
+        };
+        let converted = request.selection == self.atoms.clipboard
+            && !self.flavors.is_empty()
+            && self.convert_for(request.requestor, request.target, property);
+        let mut response = x11_sys::XSelectionEvent {
+            type_: x11_sys::SelectionNotify as i32,
+            serial: 0,
+            send_event: 0,
+            display: self.display,
+            requestor: request.requestor,
+            selection: request.selection,
+            target: request.target,
+            time: request.time,
+            property: if converted {property} else {x11_sys::None as x11_sys::Atom},
+        };
+        x11_sys::XSendEvent(self.display, request.requestor, 0, 0, &mut response as *mut _ as *mut x11_sys::XEvent);
+        x11_sys::XFlush(self.display);
+    }
+
+    // Writes the clipboard to the property of the requestor in the given target. Returns
+    // whether we support the target.
+    unsafe fn convert_for(&mut self, requestor: x11_sys::Window, target: x11_sys::Atom, property: x11_sys::Atom) -> bool {
+        if target == self.atoms.targets {
+            let mut targets = vec![self.atoms.targets, self.atoms.multiple, self.atoms.save_targets];
+            for flavor in &self.flavors {
+                match flavor {
+                    ClipboardFlavor::Text(_) => targets.extend(self.atoms.text_targets()),
+                    flavor => targets.push(self.intern(flavor.mime())),
+                }
+            }
+            self.change_property(requestor, property, self.atoms.atom, 32, &atoms_to_bytes(&targets), targets.len());
+            return true;
+        }
+        if target == self.atoms.save_targets {
+            // a clipboard manager asks this to learn we support being saved, nothing to send
+            self.change_property(requestor, property, self.atoms.null, 32, &[], 0);
+            return true;
+        }
+        if target == self.atoms.multiple {
+            // The property holds pairs of targets and properties to convert each of. The ones
+            // we can't convert get their property replaced by None.
+            let Some((_, data)) = self.read_property(requestor, property, false) else {
+                return false;
+            };
+            let mut pairs = atoms_from_bytes(&data);
+            for pair in pairs.chunks_exact_mut(2) {
+                if pair[0] == self.atoms.multiple || !self.convert_for(requestor, pair[0], pair[1]) {
+                    pair[1] = x11_sys::None as x11_sys::Atom;
+                }
+            }
+            self.change_property(requestor, property, self.atoms.atom_pair, 32, &atoms_to_bytes(&pairs), pairs.len());
+            return true;
+        }
+        let converted = match self.flavor_for(target) {
+            Some(flavor) => Some((target, flavor.data().to_vec())),
+            None => self.text().and_then( | text | self.text_for(target, text))
+        };
+        let Some((type_, data)) = converted else {
+            return false;
+        };
+        if data.len() <= self.chunk_size {
+            self.change_property(requestor, property, type_, 8, &data, data.len());
+            return true;
+        }
+        // Too large for one request, so we send it in chunks. The INCR property tells the
+        // requestor, and it deletes the property to ask for the first chunk.
+        x11_sys::XSelectInput(self.display, requestor, x11_sys::PropertyChangeMask as c_long);
+        let size = [data.len() as c_ulong];
+        self.change_property(requestor, property, self.atoms.incr, 32, &atoms_to_bytes(&size), 1);
+        self.transfers.retain( | transfer | transfer.requestor != requestor || transfer.property != property);
+        self.transfers.push(IncrTransfer {
+            requestor,
+            property,
+            type_,
+            data,
+            offset: 0,
+        });
+        true
+    }
+
+    // The text in a text target.
+    fn text_for(&self, target: x11_sys::Atom, text: &str) -> Option<(x11_sys::Atom, Vec<u8>)> {
+        if target == self.atoms.string {
+            // STRING is Latin-1
+            Some((self.atoms.string, text.chars().map( | c | if (c as u32) < 256 {c as u8} else {b'?'}).collect()))
+        }
+        else if self.atoms.text_targets().contains(&target) {
+            // TEXT lets us pick the type, UTF8_STRING is understood by everyone asking for it
+            let type_ = if target == self.atoms.text {self.atoms.utf8_string} else {target};
+            Some((type_, text.as_bytes().to_vec()))
+        }
+        else {
+            None
+        }
+    }
+
+    // The flavor other than text we offer in a target, which is named after its mime type.
+    unsafe fn flavor_for(&self, target: x11_sys::Atom) -> Option<&ClipboardFlavor> {
+        if self.flavors.iter().all( | flavor | matches!(flavor, ClipboardFlavor::Text(_))) {
+            return None;
+        }
+        let name = self.atom_name(target) ?;
+        self.flavors.iter().find( | flavor | !matches!(flavor, ClipboardFlavor::Text(_)) && flavor.mime() == name)
+    }
+
+    unsafe fn intern(&self, name: &str) -> x11_sys::Atom {
+        let name = CString::new(name).unwrap_or_default();
+        x11_sys::XInternAtom(self.display, name.as_ptr(), 0)
+    }
+
+    unsafe fn atom_name(&self, atom: x11_sys::Atom) -> Option<String> {
+        if atom == x11_sys::None as x11_sys::Atom {
+            return None;
+        }
+        let name = x11_sys::XGetAtomName(self.display, atom);
+        if name.is_null() {
+            return None;
+        }
+        let string = CStr::from_ptr(name).to_string_lossy().into_owned();
+        x11_sys::XFree(name as *mut c_void);
+        Some(string)
+    }
+
+    // Writes the next chunk of a transfer. Returns whether the transfer is done.
+    unsafe fn send_next_chunk(&mut self, index: usize) -> bool {
+        let transfer = &mut self.transfers[index];
+        let end = (transfer.offset + self.chunk_size).min(transfer.data.len());
+        let chunk = transfer.data[transfer.offset..end].to_vec();
+        let (requestor, property, type_) = (transfer.requestor, transfer.property, transfer.type_);
+        let done = chunk.is_empty();
+        transfer.offset = end;
+        // an empty chunk tells the requestor the transfer is done
+        self.change_property(requestor, property, type_, 8, &chunk, chunk.len());
+        if done {
+            x11_sys::XSelectInput(self.display, requestor, x11_sys::NoEventMask as c_long);
+        }
+        done
+    }
+
+    unsafe fn change_property(&self, window: x11_sys::Window, property: x11_sys::Atom, type_: x11_sys::Atom, format: c_int, data: &[u8], count: usize) {
+        x11_sys::XChangeProperty(
+            self.display,
+            window,
+            property,
+            type_,
+            format,
+            x11_sys::PropModeReplace as c_int,
+            data.as_ptr() as *const c_uchar,
+            count as c_int
+        );
+    }
+
+    // Reads a whole property, in as many requests as it takes. Items of format 32 come back as
+    // longs, which is how they end up in the bytes as well.
+    unsafe fn read_property(&self, window: x11_sys::Window, property: x11_sys::Atom, delete: bool) -> Option<(x11_sys::Atom, Vec<u8>)> {
+        let mut data = Vec::new();
+        let mut offset = 0;
+        let type_ = loop {
+            let mut actual_type = 0;
+            let mut actual_format = 0;
+            let mut n_items = 0;
+            let mut bytes_after = 0;
+            let mut prop = ptr::null_mut();
+            let status = x11_sys::XGetWindowProperty(
+                self.display,
+                window,
+                property,
+                offset,
+                (self.chunk_size / 4) as c_long,
+                x11_sys::False as c_int,
+                x11_sys::AnyPropertyType as c_ulong,
+                &mut actual_type,
+                &mut actual_format,
+                &mut n_items,
+                &mut bytes_after,
+                &mut prop,
+            );
+            if status != x11_sys::Success as c_int {
+                return None;
+            }
+            let item_size = match actual_format {
+                16 => mem::size_of::<std::os::raw::c_short>(),
+                32 => mem::size_of::<c_long>(),
+                _ => 1
+            };
+            if !prop.is_null() {
+                data.extend_from_slice(std::slice::from_raw_parts(prop as *const u8, n_items as usize * item_size));
+                x11_sys::XFree(prop as *mut c_void);
+            }
+            // the offset is in units of 4 bytes, whatever the format
+            offset += (n_items as c_long * actual_format as c_long) / 32;
+            if bytes_after == 0 {
+                break actual_type;
+            }
+        };
+        if delete {
+            x11_sys::XDeleteProperty(self.display, window, property);
+        }
+        Some((type_, data))
+    }
+
+    fn decode(&self, type_: x11_sys::Atom, data: &[u8]) -> String {
+        if type_ == self.atoms.string {
+            data.iter().map( | byte | *byte as char).collect()
+        }
+        else {
+            String::from_utf8_lossy(data).into_owned()
+        }
+    }
+
+    /// Hands the clipboard to a clipboard manager, if we own it and one is running, so it
+    /// outlives the app. Blocks until the manager copied it, or gave up.
+    pub unsafe fn save_to_clipboard_manager(&mut self) {
+        if self.flavors.is_empty()
+            || x11_sys::XGetSelectionOwner(self.display, self.atoms.clipboard) != self.window
+            || x11_sys::XGetSelectionOwner(self.display, self.atoms.clipboard_manager) == x11_sys::None as x11_sys::Window {
+            return;
+        }
+        // The manager answers SAVE_TARGETS by asking us for the clipboard in the targets it
+        // wants, and then notifies us it is done.
+        x11_sys::XConvertSelection(
+            self.display,
+            self.atoms.clipboard_manager,
+            self.atoms.save_targets,
+            self.atoms.paste,
+            self.window,
+            x11_sys::CurrentTime as x11_sys::Time
+        );
+        x11_sys::XFlush(self.display);
+        let start = Instant::now();
+        while start.elapsed() < CLIPBOARD_MANAGER_TIMEOUT {
+            while x11_sys::XPending(self.display) != 0 {
+                let mut event = mem::MaybeUninit::uninit();
+                x11_sys::XNextEvent(self.display, event.as_mut_ptr());
+                let event = event.assume_init();
+                match event.type_ as u32 {
+                    x11_sys::SelectionRequest => {
+                        self.handle_selection_request_event(&event.xselectionrequest);
+                    }
+                    x11_sys::PropertyNotify => {
+                        self.handle_property_event(&event.xproperty);
+                    }
+                    x11_sys::SelectionNotify if event.xselection.selection == self.atoms.clipboard_manager => {
+                        return;
+                    }
+                    _ => ()
+                }
+            }
+            thread::sleep(Duration::from_millis(5));
+        }
+    }
+}
+
+fn atoms_from_bytes(data: &[u8]) -> Vec<x11_sys::Atom> {
+    data.chunks_exact(mem::size_of::<x11_sys::Atom>())
+        .map( | bytes | x11_sys::Atom::from_ne_bytes(bytes.try_into().unwrap()))
+        .collect()
+}
+
+fn atoms_to_bytes(atoms: &[x11_sys::Atom]) -> Vec<u8> {
+    atoms.iter().flat_map( | atom | atom.to_ne_bytes()).collect()
+}
+
+pub struct ClipboardAtoms {
+    pub atom: x11_sys::Atom,
+    pub atom_pair: x11_sys::Atom,
+    pub clipboard: x11_sys::Atom,
+    pub clipboard_manager: x11_sys::Atom,
+    pub incr: x11_sys::Atom,
+    pub multiple: x11_sys::Atom,
+    pub null: x11_sys::Atom,
+    pub paste: x11_sys::Atom,
+    pub read: x11_sys::Atom,
+    pub save_targets: x11_sys::Atom,
+    pub string: x11_sys::Atom,
+    pub targets: x11_sys::Atom,
+    pub text: x11_sys::Atom,
+    pub text_plain: x11_sys::Atom,
+    pub text_plain_utf8: x11_sys::Atom,
+    pub utf8_string: x11_sys::Atom,
+}
+
+impl ClipboardAtoms {
+    pub unsafe fn new(display: *mut x11_sys::Display) -> ClipboardAtoms {
+        ClipboardAtoms {
+            atom: x11_sys::XInternAtom(display, "ATOM\0".as_ptr() as *const _, 0),
+            atom_pair: x11_sys::XInternAtom(display, "ATOM_PAIR\0".as_ptr() as *const _, 0),
+            clipboard: x11_sys::XInternAtom(display, "CLIPBOARD\0".as_ptr() as *const _, 0),
+            clipboard_manager: x11_sys::XInternAtom(display, "CLIPBOARD_MANAGER\0".as_ptr() as *const _, 0),
+            incr: x11_sys::XInternAtom(display, "INCR\0".as_ptr() as *const _, 0),
+            multiple: x11_sys::XInternAtom(display, "MULTIPLE\0".as_ptr() as *const _, 0),
+            null: x11_sys::XInternAtom(display, "NULL\0".as_ptr() as *const _, 0),
+            paste: x11_sys::XInternAtom(display, "MAKEPAD_CLIPBOARD\0".as_ptr() as *const _, 0),
+            read: x11_sys::XInternAtom(display, "MAKEPAD_CLIPBOARD_READ\0".as_ptr() as *const _, 0),
+            save_targets: x11_sys::XInternAtom(display, "SAVE_TARGETS\0".as_ptr() as *const _, 0),
+            string: x11_sys::XInternAtom(display, "STRING\0".as_ptr() as *const _, 0),
+            targets: x11_sys::XInternAtom(display, "TARGETS\0".as_ptr() as *const _, 0),
+            text: x11_sys::XInternAtom(display, "TEXT\0".as_ptr() as *const _, 0),
+            text_plain: x11_sys::XInternAtom(display, "text/plain\0".as_ptr() as *const _, 0),
+            text_plain_utf8: x11_sys::XInternAtom(display, "text/plain;charset=utf-8\0".as_ptr() as *const _, 0),
+            utf8_string: x11_sys::XInternAtom(display, "UTF8_STRING\0".as_ptr() as *const _, 0),
+        }
+    }
+
+    /// The targets we offer and read text in, the best ones first.
+    pub fn text_targets(&self) -> Vec<x11_sys::Atom> {
+        vec![self.utf8_string, self.text_plain_utf8, self.text_plain, self.string, self.text]
+    }
+}
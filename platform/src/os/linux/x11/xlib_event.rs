@@ -5,15 +5,18 @@ use {
             MouseUpEvent,
             MouseMoveEvent,
             ScrollEvent,
+            NavigateEvent,
             WindowGeomChangeEvent,
             WindowDragQueryEvent,
             WindowCloseRequestedEvent,
             WindowClosedEvent,
             TextInputEvent,
+            TextCompositionEvent,
             KeyEvent,
             DragEvent,
             DropEvent,
             TextClipboardEvent,
+            ClipboardReadEvent,
             TimerEvent,
         },
     }
@@ -31,10 +34,12 @@ pub enum XlibEvent {
     MouseUp(MouseUpEvent),
     MouseMove(MouseMoveEvent),
     Scroll(ScrollEvent),
+    Navigate(NavigateEvent),
     
     WindowDragQuery(WindowDragQueryEvent),
     WindowCloseRequested(WindowCloseRequestedEvent),
     TextInput(TextInputEvent),
+    TextComposition(TextCompositionEvent),
     Drag(DragEvent),
     Drop(DropEvent),
     DragEnd,
@@ -42,5 +47,6 @@ pub enum XlibEvent {
     KeyUp(KeyEvent),
     TextCopy(TextClipboardEvent),
     TextCut(TextClipboardEvent),
+    ClipboardRead(ClipboardReadEvent),
     Timer(TimerEvent),
 }
@@ -3,9 +3,10 @@ use {
         mem,
         cell::Cell,
         rc::Rc,
-        os::raw::{c_ulong, c_long, c_void, c_char},
+        os::raw::{c_ulong, c_long, c_void, c_char, c_int, c_short},
+        ffi::CStr,
         ptr,
-        ffi::{CStr,CString}, 
+        slice,
     },
     self::super::{
         x11_sys,
@@ -14,7 +15,7 @@ use {
     },
     crate::{
         area::Area,
-        window::WindowId,
+        window::{WindowId, CxWindowPool},
         makepad_math::{DVec2},
         event::*,
         cursor::MouseCursor,
@@ -34,6 +35,10 @@ pub struct XlibWindow {
     pub last_window_geom: WindowGeom,
     
     pub ime_spot: DVec2,
+    /// The chars the input method is composing, with their XIMFeedback.
+    pub preedit: Vec<(char, x11_sys::XIMFeedback)>,
+    pub preedit_caret: usize,
+    pub is_composing: bool,
     pub current_cursor: MouseCursor,
     pub last_mouse_pos: DVec2,
 }
@@ -62,6 +67,9 @@ impl XlibWindow {
             last_window_geom: WindowGeom::default(),
             last_nc_mode: None,
             ime_spot: DVec2::default(),
+            preedit: Vec::new(),
+            preedit_caret: 0,
+            is_composing: false,
             current_cursor: MouseCursor::Default,
             last_mouse_pos: DVec2::default(),
         }
@@ -150,16 +158,53 @@ impl XlibWindow {
             let title_bytes = format!("{}\0", title);
             x11_sys::XStoreName(display, window, title_bytes.as_bytes().as_ptr() as *const c_char);
             
-            let xic = x11_sys::XCreateIC(
+            // ask the input method to hand us its preedit text so we can show it at the cursor,
+            // input methods that can't do that show it in a window of their own
+            let client_data = self as *mut XlibWindow as x11_sys::XPointer;
+            let start_callback = x11_sys::XICCallback {client_data, callback: Some(preedit_start)};
+            let done_callback = x11_sys::XIMCallback {client_data, callback: Some(preedit_done)};
+            let draw_callback = x11_sys::XIMCallback {client_data, callback: Some(preedit_draw)};
+            let caret_callback = x11_sys::XIMCallback {client_data, callback: Some(preedit_caret)};
+            let preedit_attributes = x11_sys::XVaCreateNestedList(
+                0,
+                x11_sys::XNPreeditStartCallback.as_ptr(),
+                &start_callback as *const x11_sys::XICCallback,
+                x11_sys::XNPreeditDoneCallback.as_ptr(),
+                &done_callback as *const x11_sys::XIMCallback,
+                x11_sys::XNPreeditDrawCallback.as_ptr(),
+                &draw_callback as *const x11_sys::XIMCallback,
+                x11_sys::XNPreeditCaretCallback.as_ptr(),
+                &caret_callback as *const x11_sys::XIMCallback,
+                ptr::null_mut() as *mut c_void
+            );
+            let mut xic = x11_sys::XCreateIC(
                 get_xlib_app_global().xim,
                 x11_sys::XNInputStyle.as_ptr(),
-                (x11_sys::XIMPreeditNothing | x11_sys::XIMStatusNothing) as i32,
+                (x11_sys::XIMPreeditCallbacks | x11_sys::XIMStatusNothing) as c_ulong,
                 x11_sys::XNClientWindow.as_ptr(),
                 window,
                 x11_sys::XNFocusWindow.as_ptr(),
                 window,
+                x11_sys::XNPreeditAttributes.as_ptr(),
+                preedit_attributes,
                 ptr::null_mut() as *mut c_void
             );
+            x11_sys::XFree(preedit_attributes);
+            if xic.is_null() {
+                xic = x11_sys::XCreateIC(
+                    get_xlib_app_global().xim,
+                    x11_sys::XNInputStyle.as_ptr(),
+                    (x11_sys::XIMPreeditNothing | x11_sys::XIMStatusNothing) as i32,
+                    x11_sys::XNClientWindow.as_ptr(),
+                    window,
+                    x11_sys::XNFocusWindow.as_ptr(),
+                    window,
+                    ptr::null_mut() as *mut c_void
+                );
+            }
+            if !xic.is_null() {
+                x11_sys::XSetICFocus(xic);
+            }
             
             // Create a window
             get_xlib_app_global().window_map.insert(window, self);
@@ -299,6 +344,30 @@ impl XlibWindow {
     
     pub fn set_ime_spot(&mut self, spot: DVec2) {
         self.ime_spot = spot;
+        // the input method puts its candidate window at the spot
+        let Some(xic) = self.xic.filter( | xic | !xic.is_null()) else {
+            return
+        };
+        let dpi_factor = self.get_dpi_factor();
+        let spot = x11_sys::XPoint {
+            x: (spot.x * dpi_factor) as c_short,
+            y: (spot.y * dpi_factor) as c_short,
+        };
+        unsafe {
+            let preedit_attributes = x11_sys::XVaCreateNestedList(
+                0,
+                x11_sys::XNSpotLocation.as_ptr(),
+                &spot as *const x11_sys::XPoint,
+                ptr::null_mut() as *mut c_void
+            );
+            x11_sys::XSetICValues(
+                xic,
+                x11_sys::XNPreeditAttributes.as_ptr(),
+                preedit_attributes,
+                ptr::null_mut() as *mut c_void
+            );
+            x11_sys::XFree(preedit_attributes);
+        }
     }
     
     pub fn get_position(&self) -> DVec2 {
@@ -354,33 +423,7 @@ impl XlibWindow {
     }
     
     pub fn get_dpi_factor(&self) -> f64 {
-        unsafe {
-            //return 2.0;
-            let display = get_xlib_app_global().display;
-            let resource_string = x11_sys::XResourceManagerString(display);
-            if resource_string == std::ptr::null_mut() {
-                return 1.0
-            }
-            let db = x11_sys::XrmGetStringDatabase(resource_string);
-            let mut ty = mem::MaybeUninit::uninit();
-            let mut value = mem::MaybeUninit::uninit();
-            x11_sys::XrmGetResource(
-                db,
-                "Xft.dpi\0".as_ptr() as * const _,
-                "String\0".as_ptr() as * const _,
-                ty.as_mut_ptr(),
-                value.as_mut_ptr()
-            );
-            //let ty = ty.assume_init();
-            let value = value.assume_init();
-            if value.addr == std::ptr::null_mut() {
-                return 1.0; // TODO find some other way to figure it out
-            }
-            else {
-                let dpi: f64 = CStr::from_ptr(value.addr).to_str().unwrap().parse().unwrap();
-                return dpi / 96.0;
-            }
-        }
+        get_xlib_app_global().dpi_factor
     }
     
     pub fn time_now(&self) -> f64 {
@@ -470,6 +513,103 @@ impl XlibWindow {
         }))
     }
     
+    pub fn send_preedit(&mut self) {
+        if self.preedit.is_empty() {
+            if self.is_composing {
+                self.is_composing = false;
+                self.do_callback(XlibEvent::TextComposition(TextCompositionEvent::commit()));
+            }
+            return
+        }
+        let preedit: String = self.preedit.iter().map( | (c, _) | c).collect();
+        let byte_index = | char_index: usize | {
+            preedit.char_indices().nth(char_index).map_or(preedit.len(), | (index, _) | index)
+        };
+        let mut underlines = vec![TextCompositionUnderline {start: 0, end: preedit.len(), thick: false}];
+        // the clause being converted is drawn highlighted by the input method
+        let is_converting = | feedback: x11_sys::XIMFeedback | feedback & (x11_sys::XIMReverse | x11_sys::XIMHighlight) != 0;
+        let mut index = 0;
+        while index < self.preedit.len() {
+            if !is_converting(self.preedit[index].1) {
+                index += 1;
+                continue;
+            }
+            let start = index;
+            while index < self.preedit.len() && is_converting(self.preedit[index].1) {
+                index += 1;
+            }
+            underlines.push(TextCompositionUnderline {start: byte_index(start), end: byte_index(index), thick: true});
+        }
+        let phase = if self.is_composing {TextCompositionPhase::Update} else {TextCompositionPhase::Start};
+        self.is_composing = true;
+        self.do_callback(XlibEvent::TextComposition(TextCompositionEvent {
+            phase,
+            cursor: byte_index(self.preedit_caret),
+            preedit,
+            underlines,
+        }));
+    }
+}
+
+unsafe extern "C" fn preedit_start(_xic: x11_sys::XIC, client_data: x11_sys::XPointer, _call_data: x11_sys::XPointer) -> c_int {
+    let window = &mut *(client_data as *mut XlibWindow);
+    window.preedit.clear();
+    window.preedit_caret = 0;
+    // no limit on the length of the preedit text
+    -1
+}
+
+unsafe extern "C" fn preedit_done(_xic: x11_sys::XIC, client_data: x11_sys::XPointer, _call_data: x11_sys::XPointer) {
+    let window = &mut *(client_data as *mut XlibWindow);
+    window.preedit.clear();
+    window.send_preedit();
+}
+
+unsafe extern "C" fn preedit_draw(_xic: x11_sys::XIC, client_data: x11_sys::XPointer, call_data: x11_sys::XPointer) {
+    let window = &mut *(client_data as *mut XlibWindow);
+    let draw = &*(call_data as *const x11_sys::XIMPreeditDrawCallbackStruct);
+    let first = (draw.chg_first.max(0) as usize).min(window.preedit.len());
+    let end = (first + draw.chg_length.max(0) as usize).min(window.preedit.len());
+    let mut chars = Vec::new();
+    if let Some(text) = draw.text.as_ref() {
+        let feedback = | index: usize | if text.feedback.is_null() {0} else {*text.feedback.add(index)};
+        if text.string.is_null() {
+            // only the feedback of the changed chars is new
+            for index in 0..(text.length as usize).min(end - first) {
+                window.preedit[first + index].1 = feedback(index);
+            }
+            window.preedit_caret = draw.caret.max(0) as usize;
+            window.send_preedit();
+            return
+        }
+        let string: Vec<char> = if text.encoding_is_wchar != 0 {
+            slice::from_raw_parts(text.string as *const u32, text.length as usize).iter().filter_map( | c | char::from_u32(*c)).collect()
+        }
+        else {
+            CStr::from_ptr(text.string).to_string_lossy().chars().collect()
+        };
+        chars = string.into_iter().enumerate().map( | (index, c) | (c, feedback(index))).collect();
+    }
+    window.preedit.splice(first..end, chars);
+    window.preedit_caret = draw.caret.max(0) as usize;
+    window.send_preedit();
+}
+
+unsafe extern "C" fn preedit_caret(_xic: x11_sys::XIC, client_data: x11_sys::XPointer, call_data: x11_sys::XPointer) {
+    let window = &mut *(client_data as *mut XlibWindow);
+    let caret = &mut *(call_data as *mut x11_sys::XIMPreeditCaretCallbackStruct);
+    // the input method asks where the caret ends up, we only know about moving within the line
+    let position = match caret.direction {
+        x11_sys::XIMForwardChar => window.preedit_caret + 1,
+        x11_sys::XIMBackwardChar => window.preedit_caret.saturating_sub(1),
+        x11_sys::XIMLineStart => 0,
+        x11_sys::XIMLineEnd => window.preedit.len(),
+        x11_sys::XIMAbsolutePosition => caret.position.max(0) as usize,
+        _ => window.preedit_caret,
+    };
+    window.preedit_caret = position.min(window.preedit.len());
+    caret.position = window.preedit_caret as c_int;
+    window.send_preedit();
 }
 
 
@@ -514,7 +654,16 @@ pub struct Dnd {
     pub atoms: DndAtoms,
     pub display: *mut x11_sys::Display,
     pub type_list: Option<Vec<x11_sys::Atom >>,
-    pub selection: Option<CString>,
+    /// The files being dragged, once the source has converted the selection to a URI list.
+    pub items: Option<Rc<Vec<DragItem >>>,
+    pub source_window: x11_sys::Window,
+    pub target_window: x11_sys::Window,
+    /// The makepad window the drag is over.
+    pub window_id: WindowId,
+    /// The last position of the drag, in the logical coordinates of the target window.
+    pub position: DVec2,
+    /// Set when the drop came in before the URI list did.
+    pub drop_pending: bool,
 }
 
 impl Dnd {
@@ -523,7 +672,12 @@ impl Dnd {
             atoms: DndAtoms::new(display),
             display,
             type_list: None,
-            selection: None,
+            items: None,
+            source_window: 0,
+            target_window: 0,
+            window_id: CxWindowPool::id_zero(),
+            position: DVec2::default(),
+            drop_pending: false,
         }
     }
     
@@ -549,7 +703,7 @@ impl Dnd {
     }
     
     /// Handles a XDndEnter event.
-    pub unsafe fn handle_enter_event(&mut self, event: &x11_sys::XClientMessageEvent) {
+    pub unsafe fn handle_enter_event(&mut self, event: &x11_sys::XClientMessageEvent, window_id: WindowId) {
         // The XDndEnter event is sent by the source window when a drag begins. That is, the mouse
         // enters the client rectangle of the target window. The target window is supposed to
         // respond to this by requesting the list of types supported by the source.
@@ -560,29 +714,51 @@ impl Dnd {
         // If the has_more_types flags is set, we have to obtain the list of supported types from
         // the XDndTypeList property. Otherwise, we can obtain the list of supported types from the
         // event itself.
-        self.type_list = Some(if has_more_types {
+        let type_list = if has_more_types {
             self.get_type_list_property(source_window)
         } else {
-            event.data.l[2..4]
+            event.data.l[2..5]
                 .iter()
                 .map( | &l | l as x11_sys::Atom)
                 .filter( | &atom | atom != x11_sys::None as x11_sys::Atom)
                 .collect()
-        });
+        };
+        
+        self.source_window = source_window;
+        self.target_window = event.window;
+        self.window_id = window_id;
+        self.items = None;
+        self.drop_pending = false;
+        
+        // We only accept files, so if the source has a URI list we ask for it right away. The
+        // source responds with a XSelectionEvent, which usually arrives before the first
+        // XDndPosition event is answered.
+        if type_list.contains(&self.atoms.uri_list) {
+            self.convert_selection(event.window, x11_sys::CurrentTime as x11_sys::Time);
+        }
+        self.type_list = Some(type_list);
     }
     
-    /// Handles a XDndDrop event.
-    pub unsafe fn handle_drop_event(&mut self, event: &x11_sys::XClientMessageEvent) {
-        // The XDndLeave event is sent by the source window when a drag is confirmed. That is, the
+    /// Handles a XDndDrop event. Returns the drop event if we have the files, otherwise it is
+    /// returned by `handle_selection_event` once they come in.
+    pub unsafe fn handle_drop_event(&mut self, event: &x11_sys::XClientMessageEvent) -> Option<DropEvent> {
+        // The XDndDrop event is sent by the source window when a drag is confirmed. That is, the
         // mouse button is released while the mouse is inside the client rectangle of the target
-        // window. The target window is supposed to respond to this by requesting that the selection
-        // representing the thing being dragged is converted to the appropriate data type (in our
-        // case, a URI list). The source window, in turn, is supposed to respond this by sending a
-        // selection event containing the data to the source window.
+        // window. The target window is supposed to respond to this by reading the selection, and
+        // sending a XDndFinished event to the source window when it is done.
         
-        let target_window = event.window as x11_sys::Window;
-        self.convert_selection(target_window);
-        self.type_list = None;
+        if self.items.is_some() {
+            return self.take_drop_event()
+        }
+        if self.type_list.as_ref().map_or(false, | type_list | type_list.contains(&self.atoms.uri_list)) {
+            // the URI list hasn't come in yet, ask again with the time of the drop
+            self.drop_pending = true;
+            self.convert_selection(event.window, event.data.l[2] as x11_sys::Time);
+            return None
+        }
+        self.send_finished_event(DragResponse::None);
+        self.reset();
+        None
     }
     
     /// Handles a XDndLeave event.
@@ -591,53 +767,93 @@ impl Dnd {
         // mouse leaves the client rectangle of the target window. The target window is supposed to
         // repsond this this by pretending the drag never happened.
         
-        self.type_list = None;
+        self.reset();
     }
     
-    /// Handles a XDndPosition event.
-    pub unsafe fn handle_position_event(&mut self, event: &x11_sys::XClientMessageEvent) {
+    /// Handles a XDndPosition event. Returns the drag event to send if we have the files, and
+    /// otherwise tells the source we can't accept the drag yet. The response of the returned
+    /// event has to be sent back with `send_status_event`.
+    pub unsafe fn handle_position_event(&mut self, event: &x11_sys::XClientMessageEvent, dpi_factor: f64) -> Option<DragEvent> {
         // The XDndPosition event is sent by the source window after the XDndEnter event, every time
         // the mouse is moved. The target window is supposed to respond to this by sending a status
         // event to the source window notifying whether it can accept the drag at this position.
         
-        let target_window = event.window as x11_sys::Window;
-        let source_window = event.data.l[0] as x11_sys::Window;
-        
-        // For now we accept te drag if and only if the list of types supported by the source
-        // includes a uri list.
-        //
-        // TODO: Extend this test by taking into account the position of the mouse as well.
-        let accepted = self.type_list.as_ref().map_or(false, | type_list | type_list.contains(&self.atoms.uri_list));
+        // The position is packed into one long, in root window coordinates.
+        let root_x = ((event.data.l[2] >> 16) & 0xffff) as std::os::raw::c_int;
+        let root_y = (event.data.l[2] & 0xffff) as std::os::raw::c_int;
+        let default_screen = x11_sys::XDefaultScreen(self.display);
+        let root_window = x11_sys::XRootWindow(self.display, default_screen);
+        let mut x = 0;
+        let mut y = 0;
+        let mut child = 0;
+        x11_sys::XTranslateCoordinates(self.display, root_window, event.window, root_x, root_y, &mut x, &mut y, &mut child);
+        self.position = DVec2 {x: x as f64 / dpi_factor, y: y as f64 / dpi_factor};
         
-        // Notify the source window whether we can accept the drag at this position.
-        self.send_status_event(source_window, target_window, accepted);
-        
-        // If this is the first time we've accepted the drag, request that the drag-and-drop
-        // selection be converted to a URI list. The target window is supposed to respond to this by
-        // sending a XSelectionEvent containing the URI list.
-        
-        // Since this is an asynchronous operation, its possible for another XDndPosition event to
-        // come in before the response to the first conversion request has been received. In this
-        // case, a second conversion request will be sent, the response to which will be ignored.
-        if accepted && self.selection.is_none() {
-        }
+        // Until the source has sent us the URI list there is nothing to drag over the app. Since
+        // we don't ask the source to stop sending positions, we get another chance on the next one.
+        let Some(items) = self.items.clone() else {
+            self.send_status_event(DragResponse::None);
+            return None
+        };
+        Some(DragEvent {
+            window_id: self.window_id,
+            modifiers: KeyModifiers::default(),
+            handled: Cell::new(false),
+            abs: self.position,
+            items,
+            response: Rc::new(Cell::new(DragResponse::None)),
+        })
     }
     
-    /// Handles a XSelectionEvent.
-    pub unsafe fn handle_selection_event(&mut self, _event: &x11_sys::XSelectionEvent) {
-        // The XSelectionEvent is sent by the source window in response to a request by the source
+    /// Handles a XSelectionEvent. Returns the drop event when the drop was waiting for the files.
+    pub unsafe fn handle_selection_event(&mut self, event: &x11_sys::XSelectionEvent) -> Option<DropEvent> {
+        // The XSelectionEvent is sent by the source window in response to a request by the target
         // window to convert the selection representing the thing being dragged to the appropriate
-        // data type. This request is always sent in response to a XDndDrop event, so this event
-        // should only be received after a drop operation has completed.
+        // data type. The data itself is stored in a property on the target window.
         
-        //let source_window = event.requestor;
-        //let selection = CString::new(self.get_selection_property(source_window)).unwrap();
+        let uri_list = self.get_selection_property(event.requestor);
+        let uri_list = String::from_utf8_lossy(&uri_list);
+        let items: Vec<DragItem> = uri_list
+            .lines()
+            .map( | line | line.trim())
+            .filter( | line | !line.starts_with('#'))
+            .filter_map(DragItem::from_file_uri)
+            .collect();
+        self.items = Some(Rc::new(items));
         
-        // TODO: Actually use the selection
+        if self.drop_pending {
+            return self.take_drop_event()
+        }
+        None
+    }
+    
+    /// Makes the drop event for the files we have, telling the source we took them.
+    unsafe fn take_drop_event(&mut self) -> Option<DropEvent> {
+        let items = self.items.take() ?;
+        self.send_finished_event(if items.is_empty() {DragResponse::None} else {DragResponse::Copy});
+        let position = self.position;
+        let window_id = self.window_id;
+        self.reset();
+        if items.is_empty() {
+            return None
+        }
+        Some(DropEvent {
+            window_id,
+            modifiers: KeyModifiers::default(),
+            handled: Cell::new(false),
+            abs: position,
+            items,
+        })
     }
     
-    /// Gets the XDndSelection property from the source window.
-    pub unsafe fn get_selection_property(&mut self, source_window: x11_sys::Window) -> Vec< std::os::raw::c_uchar> {
+    fn reset(&mut self) {
+        self.type_list = None;
+        self.items = None;
+        self.drop_pending = false;
+    }
+    
+    /// Gets the XDndSelection property from the given window.
+    pub unsafe fn get_selection_property(&mut self, window: x11_sys::Window) -> Vec< std::os::raw::c_uchar> {
         let mut selection = Vec::new();
         let mut offset = 0;
         let length = 1024;
@@ -649,7 +865,7 @@ impl Dnd {
         loop {
             x11_sys::XGetWindowProperty(
                 self.display,
-                source_window,
+                window,
                 self.atoms.selection,
                 offset,
                 length,
@@ -661,11 +877,15 @@ impl Dnd {
                 &mut bytes_after,
                 &mut prop,
             );
+            if prop.is_null() {
+                break;
+            }
             selection.extend_from_slice(std::slice::from_raw_parts(prop as *mut  std::os::raw::c_uchar, nitems as usize));
             x11_sys::XFree(prop as *mut c_void);
             if bytes_after == 0 {
                 break;
             }
+            // the offset is in 32 bit units
             offset += length;
         };
         selection
@@ -706,11 +926,40 @@ impl Dnd {
         type_list
     }
     
-    /// Sends a XDndStatus event to the target window.
-    pub unsafe fn send_status_event(&mut self, source_window: x11_sys::Window, target_window: x11_sys::Window, accepted: bool) {
+    /// Sends a XDndStatus event to the source window, accepting the drag with the action for the
+    /// response, or rejecting it.
+    pub unsafe fn send_status_event(&mut self, response: DragResponse) {
+        let action = self.action_for(response);
+        self.send_client_message(self.atoms.status, | data | {
+            data.l[1] = if action.is_some() {1 << 0} else {0};
+            data.l[2] = 0;
+            data.l[3] = 0;
+            data.l[4] = action.unwrap_or(self.atoms.none) as c_long;
+        });
+    }
+    
+    /// Sends a XDndFinished event to the source window, once we are done with the drop.
+    pub unsafe fn send_finished_event(&mut self, response: DragResponse) {
+        let action = self.action_for(response);
+        self.send_client_message(self.atoms.finished, | data | {
+            data.l[1] = if action.is_some() {1 << 0} else {0};
+            data.l[2] = action.unwrap_or(self.atoms.none) as c_long;
+        });
+    }
+    
+    fn action_for(&self, response: DragResponse) -> Option<x11_sys::Atom> {
+        match response {
+            DragResponse::None => None,
+            DragResponse::Copy => Some(self.atoms.action_copy),
+            DragResponse::Link => Some(self.atoms.action_link),
+            DragResponse::Move => Some(self.atoms.action_move),
+        }
+    }
+    
+    unsafe fn send_client_message(&self, message_type: x11_sys::Atom, fill: impl FnOnce(&mut x11_sys::XClientMessageEvent__bindgen_ty_1)) {
         x11_sys::XSendEvent(
             self.display,
-            source_window,
+            self.source_window,
             x11_sys::False as  std::os::raw::c_int,
             x11_sys::NoEventMask as  std::os::raw::c_long,
             &mut x11_sys::XClientMessageEvent {
@@ -718,16 +967,13 @@ impl Dnd {
                 serial: 0,
                 send_event: 0,
                 display: self.display,
-                window: source_window,
-                message_type: self.atoms.status,
+                window: self.source_window,
+                message_type,
                 format: 32,
                 data: {
                     let mut data = mem::zeroed::<x11_sys::XClientMessageEvent__bindgen_ty_1>();
-                    data.l[0] = target_window as c_long;
-                    data.l[1] = if accepted {1 << 0} else {0};
-                    data.l[2] = 0;
-                    data.l[3] = 0;
-                    data.l[4] = if accepted {self.atoms.action_private} else {self.atoms.none} as c_long;
+                    data.l[0] = self.target_window as c_long;
+                    fill(&mut data);
                     data
                 }
             } as *mut x11_sys::XClientMessageEvent as *mut x11_sys::XEvent
@@ -737,23 +983,27 @@ impl Dnd {
     
     // Requests that the selection representing the thing being dragged is converted to the
     // appropriate data type (in our case, a URI list).
-    pub unsafe fn convert_selection(&self, target_window: x11_sys::Window) {
+    pub unsafe fn convert_selection(&self, target_window: x11_sys::Window, time: x11_sys::Time) {
         x11_sys::XConvertSelection(
             self.display,
             self.atoms.selection,
             self.atoms.uri_list,
             self.atoms.selection,
             target_window,
-            x11_sys::CurrentTime as x11_sys::Time,
+            time,
         );
     }
 }
 
 pub struct DndAtoms {
+    pub action_copy: x11_sys::Atom,
+    pub action_link: x11_sys::Atom,
+    pub action_move: x11_sys::Atom,
     pub action_private: x11_sys::Atom,
     pub aware: x11_sys::Atom,
     pub drop: x11_sys::Atom,
     pub enter: x11_sys::Atom,
+    pub finished: x11_sys::Atom,
     pub leave: x11_sys::Atom,
     pub none: x11_sys::Atom,
     pub position: x11_sys::Atom,
@@ -766,10 +1016,14 @@ pub struct DndAtoms {
 impl DndAtoms {
     pub unsafe fn new(display: *mut x11_sys::Display) -> DndAtoms {
         DndAtoms {
+            action_copy: x11_sys::XInternAtom(display, "XdndActionCopy\0".as_ptr() as *const _, 0),
+            action_link: x11_sys::XInternAtom(display, "XdndActionLink\0".as_ptr() as *const _, 0),
+            action_move: x11_sys::XInternAtom(display, "XdndActionMove\0".as_ptr() as *const _, 0),
             action_private: x11_sys::XInternAtom(display, "XdndActionPrivate\0".as_ptr() as *const _, 0),
             aware: x11_sys::XInternAtom(display, "XdndAware\0".as_ptr() as *const _, 0),
             drop: x11_sys::XInternAtom(display, "XdndDrop\0".as_ptr() as *const _, 0),
             enter: x11_sys::XInternAtom(display, "XdndEnter\0".as_ptr() as *const _, 0),
+            finished: x11_sys::XInternAtom(display, "XdndFinished\0".as_ptr() as *const _, 0),
             leave: x11_sys::XInternAtom(display, "XdndLeave\0".as_ptr() as *const _, 0),
             none: x11_sys::XInternAtom(display, "None\0".as_ptr() as *const _, 0),
             position: x11_sys::XInternAtom(display, "XdndPosition\0".as_ptr() as *const _, 0),
@@ -5,6 +5,9 @@ pub mod cx_native;
 #[macro_use]
 pub mod cx_shared;
 
+#[cfg(any(target_os = "linux", target_os = "windows"))]
+pub mod native_http;
+
 pub mod cx_stdin;
 
 #[cfg(any(target_os = "macos", target_os="ios", target_os="tvos"))]
@@ -0,0 +1,288 @@
+use {
+    std::{
+        io::{self, Read, Write},
+        sync::{
+            Arc,
+            Weak,
+            atomic::{AtomicBool, Ordering},
+            mpsc::Sender,
+        },
+        time::{Duration, Instant},
+    },
+    crate::{
+        makepad_live_id::LiveId,
+        thread::SignalToUI,
+        os::net_stream::{NetStream, connect_net_stream},
+        event::{
+            HttpMethod,
+            HttpRequest,
+            HttpResponse,
+            NetworkResponse,
+            NetworkResponseItem,
+            NetworkResponseChannel,
+        },
+    }
+};
+
+const MAX_REDIRECTS: usize = 8;
+
+struct HttpHead {
+    status_code: u16,
+    headers: Vec<(String, String)>,
+}
+
+impl HttpHead {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers.iter().find( | (key, _) | key.eq_ignore_ascii_case(name)).map( | (_, value) | value.as_str())
+    }
+}
+
+/// A plain HTTP/1.1 client for the platforms that have no system one we use.
+/// Every request runs on a thread of its own.
+#[derive(Default)]
+pub struct NativeHttpRequests {
+    responses: NetworkResponseChannel,
+    running: Vec<(LiveId, Weak<AtomicBool>)>,
+}
+
+impl NativeHttpRequests {
+    pub fn request(&mut self, request_id: LiveId, request: HttpRequest) {
+        // a request thread holds on to its cancel flag until it is done
+        self.running.retain( | (_, cancel) | cancel.strong_count() > 0);
+        let cancel = Arc::new(AtomicBool::new(false));
+        self.running.push((request_id, Arc::downgrade(&cancel)));
+        make_http_request(request_id, request, self.responses.sender.clone(), cancel);
+    }
+
+    pub fn cancel(&mut self, request_id: LiveId) {
+        for (id, cancel) in &self.running {
+            if *id == request_id {
+                if let Some(cancel) = cancel.upgrade() {
+                    cancel.store(true, Ordering::SeqCst);
+                }
+            }
+        }
+        self.running.retain( | (id, _) | *id != request_id);
+    }
+
+    pub fn responses(&self) -> Vec<NetworkResponseItem> {
+        self.responses.receiver.try_iter().collect()
+    }
+}
+
+fn make_http_request(request_id: LiveId, request: HttpRequest, sender: Sender<NetworkResponseItem>, cancel: Arc<AtomicBool>) {
+    std::thread::spawn(move || {
+        let send = | response | {
+            let _ = sender.send(NetworkResponseItem {request_id, response});
+            SignalToUI::set_ui_signal();
+        };
+        match run_request(request, &cancel, &send) {
+            Ok(Some(response)) => send(NetworkResponse::HttpResponse(response)),
+            Ok(None) => (),
+            Err(error) => if !cancel.load(Ordering::SeqCst) {
+                send(NetworkResponse::HttpRequestError(error))
+            }
+        }
+    });
+}
+
+fn run_request(mut request: HttpRequest, cancel: &AtomicBool, send: &dyn Fn(NetworkResponse)) -> Result<Option<HttpResponse>, String> {
+    for _ in 0..MAX_REDIRECTS {
+        let split = request.split_url();
+        let tls = match split.proto {
+            "https" => true,
+            "http" => false,
+            proto => return Err(format!("Unsupported protocol {}", proto))
+        };
+        let mut stream = connect_net_stream(split.host, split.port, tls, !request.ignore_ssl_cert) ?;
+        // a short read timeout lets a cancel get through while waiting on the server
+        let _ = stream.set_read_timeout(Some(Duration::from_millis(100)));
+
+        let body = request.body.as_deref().unwrap_or(&[]);
+        let mut head = format!("{} /{} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n", request.method.to_string(), split.file, split.host);
+        if !request.headers.keys().any( | key | key.eq_ignore_ascii_case("user-agent")) {
+            head.push_str("User-Agent: makepad\r\n");
+        }
+        if body.len() > 0 || request.method != HttpMethod::GET {
+            head.push_str(&format!("Content-Length: {}\r\n", body.len()));
+        }
+        head.push_str(&request.get_headers_string());
+        head.push_str("\r\n");
+        stream.write_all(head.as_bytes()).map_err( | e | format!("Error sending request: {}", e)) ?;
+        stream.write_all(body).map_err( | e | format!("Error sending request body: {}", e)) ?;
+
+        let mut reader = HttpReader {stream, buffer: Vec::new(), cancel};
+        let head = reader.read_head() ?;
+        if head.status_code / 100 == 3 {
+            if let Some(location) = head.header("location") {
+                request.url = resolve_location(&request.url, location);
+                if head.status_code == 303 || (head.status_code <= 302 && request.method == HttpMethod::POST) {
+                    request.method = HttpMethod::GET;
+                    request.body = None;
+                }
+                continue;
+            }
+        }
+        let body = if request.method == HttpMethod::HEAD || head.status_code == 204 || head.status_code == 304 {
+            Vec::new()
+        }
+        else if head.header("transfer-encoding").map_or(false, | v | v.to_ascii_lowercase().contains("chunked")) {
+            reader.read_chunked(send) ?
+        }
+        else {
+            let length = head.header("content-length").and_then( | v | v.trim().parse().ok());
+            reader.read_body(length, send) ?
+        };
+        if cancel.load(Ordering::SeqCst) {
+            return Ok(None)
+        }
+        let mut response = HttpResponse::new(request.metadata_id, head.status_code, String::new(), Some(body));
+        for (key, value) in head.headers {
+            response.set_header(key, value);
+        }
+        return Ok(Some(response))
+    }
+    Err("Too many redirects".into())
+}
+
+fn resolve_location(url: &str, location: &str) -> String {
+    if location.contains("://") {
+        return location.to_string()
+    }
+    let (proto, rest) = url.split_once("://").unwrap_or(("http", url));
+    let host = rest.split('/').next().unwrap_or(rest);
+    if location.starts_with('/') {
+        format!("{}://{}{}", proto, host, location)
+    }
+    else {
+        let dir = rest.rsplit_once('/').map_or(rest, | (dir, _) | dir);
+        format!("{}://{}/{}", proto, dir, location)
+    }
+}
+
+struct HttpReader<'a> {
+    stream: NetStream,
+    buffer: Vec<u8>,
+    cancel: &'a AtomicBool,
+}
+
+impl<'a> HttpReader<'a> {
+    /// Reads more data into the buffer, returns false at the end of the stream.
+    fn fill(&mut self) -> Result<bool, String> {
+        let mut data = [0u8; 65536];
+        loop {
+            if self.cancel.load(Ordering::SeqCst) {
+                return Err("Cancelled".into())
+            }
+            match self.stream.read(&mut data) {
+                Ok(0) => return Ok(false),
+                Ok(n) => {
+                    self.buffer.extend_from_slice(&data[0..n]);
+                    return Ok(true)
+                }
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock || e.kind() == io::ErrorKind::TimedOut || e.kind() == io::ErrorKind::Interrupted => (),
+                Err(e) => return Err(format!("Error reading response: {}", e))
+            }
+        }
+    }
+
+    fn read_line(&mut self) -> Result<String, String> {
+        loop {
+            if let Some(pos) = self.buffer.windows(2).position( | w | w == b"\r\n") {
+                let line = String::from_utf8_lossy(&self.buffer[0..pos]).to_string();
+                self.buffer.drain(0..pos + 2);
+                return Ok(line)
+            }
+            if !self.fill() ? {
+                return Err("Connection closed before the end of the response".into())
+            }
+        }
+    }
+
+    fn read_head(&mut self) -> Result<HttpHead, String> {
+        loop {
+            let status = self.read_line() ?;
+            let status_code = status.split(' ').nth(1).and_then( | code | code.parse::<u16>().ok()).ok_or(format!("Invalid response: {}", status)) ?;
+            let mut headers = Vec::new();
+            loop {
+                let line = self.read_line() ?;
+                if line.is_empty() {
+                    break;
+                }
+                if let Some((key, value)) = line.split_once(':') {
+                    headers.push((key.trim().to_string(), value.trim().to_string()));
+                }
+            }
+            // skip informational responses like 100 continue
+            if status_code / 100 != 1 {
+                return Ok(HttpHead {status_code, headers})
+            }
+        }
+    }
+
+    fn read_body(&mut self, length: Option<usize>, send: &dyn Fn(NetworkResponse)) -> Result<Vec<u8>, String> {
+        let mut progress = Progress::new(length);
+        loop {
+            if let Some(length) = length {
+                if self.buffer.len() >= length {
+                    self.buffer.truncate(length);
+                    break;
+                }
+            }
+            if !self.fill() ? {
+                if length.is_some() {
+                    return Err("Connection closed before the end of the response".into())
+                }
+                break;
+            }
+            progress.update(self.buffer.len(), send);
+        }
+        Ok(std::mem::take(&mut self.buffer))
+    }
+
+    fn read_chunked(&mut self, send: &dyn Fn(NetworkResponse)) -> Result<Vec<u8>, String> {
+        let mut body = Vec::new();
+        let mut progress = Progress::new(None);
+        loop {
+            let line = self.read_line() ?;
+            let size = line.split(';').next().unwrap_or("").trim();
+            let size = usize::from_str_radix(size, 16).map_err( | _ | format!("Invalid chunk size: {}", line)) ?;
+            if size == 0 {
+                // the trailer ends with an empty line
+                while !self.read_line() ?.is_empty() {}
+                return Ok(body)
+            }
+            while self.buffer.len() < size + 2 {
+                if !self.fill() ? {
+                    return Err("Connection closed before the end of the response".into())
+                }
+                progress.update(body.len() + self.buffer.len(), send);
+            }
+            body.extend_from_slice(&self.buffer[0..size]);
+            self.buffer.drain(0..size + 2);
+            progress.update(body.len(), send);
+        }
+    }
+}
+
+/// Sends progress at most every 50ms so a fast download doesn't flood the event loop.
+struct Progress {
+    total: Option<usize>,
+    last: Instant,
+}
+
+impl Progress {
+    fn new(total: Option<usize>) -> Self {
+        Self {total, last: Instant::now()}
+    }
+
+    fn update(&mut self, loaded: usize, send: &dyn Fn(NetworkResponse)) {
+        if self.last.elapsed() >= Duration::from_millis(50) {
+            self.last = Instant::now();
+            send(NetworkResponse::HttpProgress {
+                loaded: loaded as u64,
+                total: self.total.unwrap_or(0) as u64
+            });
+        }
+    }
+}
@@ -94,7 +94,41 @@ impl FromWasmSetMouseCursor {
 
 #[derive(FromWasm)]
 pub struct FromWasmTextCopyResponse {
-    pub response: String
+    pub response: String,
+    /// The styled html of the response, empty if there is none.
+    pub html: String
+}
+
+/// How the page should show a drag of files over it, answered to `ToWasmDrag`.
+#[derive(FromWasm)]
+pub struct FromWasmDragResponse {
+    /// One of the `dropEffect` values `none`, `copy`, `link` or `move`.
+    pub drop_effect: String
+}
+
+#[derive(FromWasm, ToWasm)]
+pub struct WClipboardFlavor {
+    pub mime: String,
+    pub data: WasmDataU8
+}
+
+#[derive(FromWasm)]
+pub struct FromWasmWriteClipboard {
+    pub flavors: Vec<WClipboardFlavor>
+}
+
+#[derive(FromWasm)]
+pub struct FromWasmReadClipboard {
+    pub request_id_lo: u32,
+    pub request_id_hi: u32,
+}
+
+#[derive(FromWasm)]
+pub struct FromWasmGamepadRumble {
+    pub index: u32,
+    pub strong: f32,
+    pub weak: f32,
+    pub duration: f64,
 }
 
 #[derive(FromWasm)]
@@ -164,6 +198,12 @@ pub struct FromWasmHTTPRequest {
     pub body: WasmDataU8,
 }
 
+#[derive(FromWasm)]
+pub struct FromWasmCancelHTTPRequest {
+    pub request_id_lo: u32,
+    pub request_id_hi: u32,
+}
+
 
 
 // WebGL API
@@ -178,6 +218,17 @@ pub struct FromWasmCompileWebGLShader {
     pub textures: Vec<WTextureInput>
 }
 
+// WebGPU API
+
+#[derive(FromWasm)]
+pub struct FromWasmCompileWebGPUShader {
+    pub shader_id: usize,
+    pub wgsl: String,
+    pub geometry_slots: usize,
+    pub instance_slots: usize,
+    pub textures: Vec<WTextureInput>
+}
+
 #[derive(FromWasm)]
 pub struct FromWasmAllocArrayBuffer {
     pub buffer_id: usize,
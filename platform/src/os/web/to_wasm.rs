@@ -1,5 +1,8 @@
 use {
-    std::cell::Cell,
+    std::{
+        cell::Cell,
+        rc::Rc,
+    },
     crate::{
         makepad_live_id::*,
         makepad_wasm_bridge::*,
@@ -7,6 +10,7 @@ use {
         cx::{OsType, XrCapabilities, WebParams},
         window::CxWindowPool,
         area::Area,
+        os::web::from_wasm::WClipboardFlavor,
         //midi::{MidiData},
         event::{
             XRButton,
@@ -23,6 +27,16 @@ use {
             ScrollEvent,
             KeyEvent,
             TextInputEvent,
+            TextCompositionEvent,
+            TextCompositionPhase,
+            TextCompositionUnderline,
+            ClipboardFlavor,
+            ClipboardReadEvent,
+            DragEvent,
+            DragItem,
+            DragResponse,
+            DropEvent,
+            GamepadState,
             WindowGeom
         },
     }
@@ -32,7 +46,8 @@ use {
 pub struct WGpuInfo {
     pub min_uniform_vectors: u32,
     pub vendor: String,
-    pub renderer: String
+    pub renderer: String,
+    pub webgpu: bool
 }
 
 #[derive(ToWasm)]
@@ -311,6 +326,63 @@ impl From<ToWasmScroll> for ScrollEvent {
     }
 }
 
+// Drag and drop of files from outside the browser
+
+#[derive(ToWasm)]
+pub struct ToWasmDrag {
+    pub x: f64,
+    pub y: f64,
+    pub modifiers: u32,
+    /// The browser only tells us how many files there are until they are dropped.
+    pub file_count: u32,
+}
+
+impl ToWasmDrag {
+    pub fn into_drag_event(self, response: Rc<Cell<DragResponse >>) -> DragEvent {
+        DragEvent {
+            window_id: CxWindowPool::id_zero(),
+            modifiers: unpack_key_modifier(self.modifiers),
+            handled: Cell::new(false),
+            abs: dvec2(self.x, self.y),
+            items: Rc::new((0..self.file_count).map( | _ | DragItem::FileData {name: String::new(), data: Rc::new(Vec::new())}).collect()),
+            response
+        }
+    }
+}
+
+#[derive(ToWasm)]
+pub struct WDroppedFile {
+    pub name: String,
+    pub data: WasmDataU8
+}
+
+#[derive(ToWasm)]
+pub struct ToWasmDrop {
+    pub x: f64,
+    pub y: f64,
+    pub modifiers: u32,
+    pub files: Vec<WDroppedFile>
+}
+
+impl Into<DropEvent> for ToWasmDrop {
+    fn into(self) -> DropEvent {
+        DropEvent {
+            window_id: CxWindowPool::id_zero(),
+            modifiers: unpack_key_modifier(self.modifiers),
+            handled: Cell::new(false),
+            abs: dvec2(self.x, self.y),
+            items: Rc::new(self.files.into_iter().map( | file | DragItem::FileData {
+                name: file.name,
+                data: Rc::new(file.data.into_vec_u8())
+            }).collect())
+        }
+    }
+}
+
+#[derive(ToWasm)]
+pub struct ToWasmDragEnd {
+}
+
 // Keyboard API
 
 
@@ -490,10 +562,55 @@ impl Into<TextInputEvent> for ToWasmTextInput {
     }
 }
 
+#[derive(ToWasm)]
+pub struct ToWasmTextComposition {
+    pub start: bool,
+    pub commit: bool,
+    pub preedit: String,
+}
+
+impl Into<TextCompositionEvent> for ToWasmTextComposition {
+    fn into(self) -> TextCompositionEvent {
+        if self.commit {
+            return TextCompositionEvent::commit()
+        }
+        // the browser doesn't tell us about clauses, so underline it all with the cursor at the end
+        TextCompositionEvent {
+            phase: if self.start {TextCompositionPhase::Start} else {TextCompositionPhase::Update},
+            underlines: vec![TextCompositionUnderline {start: 0, end: self.preedit.len(), thick: false}],
+            cursor: self.preedit.len(),
+            preedit: self.preedit,
+        }
+    }
+}
+
 #[derive(ToWasm)]
 pub struct ToWasmTextCopy {
 }
 
+#[derive(ToWasm)]
+pub struct ToWasmClipboardRead {
+    pub request_id_lo: u32,
+    pub request_id_hi: u32,
+    pub flavors: Vec<WClipboardFlavor>
+}
+
+impl Into<ClipboardReadEvent> for ToWasmClipboardRead {
+    fn into(self) -> ClipboardReadEvent {
+        let mut flavors: Vec<ClipboardFlavor> = Vec::new();
+        for flavor in self.flavors {
+            let flavor = ClipboardFlavor::from_mime(&flavor.mime, flavor.data.into_vec_u8());
+            if !flavors.iter().any( | f | f.mime() == flavor.mime()) {
+                flavors.push(flavor);
+            }
+        }
+        ClipboardReadEvent {
+            request_id: LiveId::from_lo_hi(self.request_id_lo, self.request_id_hi),
+            flavors
+        }
+    }
+}
+
 // Keyboard API
 
 
@@ -596,6 +713,40 @@ impl ToWasmXRUpdate {
     }
 }
 
+#[derive(ToWasm)]
+pub struct ToWasmGamepadConnected {
+    pub index: u32,
+    pub name: String,
+}
+
+#[derive(ToWasm)]
+pub struct ToWasmGamepadDisconnected {
+    pub index: u32,
+}
+
+/// The state of a gamepad from the Gamepad API, only sent when it changed.
+#[derive(ToWasm)]
+pub struct ToWasmGamepadUpdate {
+    pub index: u32,
+    pub time: f64,
+    pub buttons: Vec<f32>,
+    pub axes: Vec<f32>,
+}
+
+impl ToWasmGamepadUpdate {
+    pub fn to_gamepad_state(&self) -> GamepadState {
+        // the browser already applies the standard mapping when it knows the pad
+        let mut state = GamepadState::default();
+        for (value, button) in self.buttons.iter().zip(state.buttons.iter_mut()) {
+            *button = *value;
+        }
+        for (value, axis) in self.axes.iter().zip(state.axes.iter_mut()) {
+            *axis = *value;
+        }
+        state
+    }
+}
+
 #[derive(ToWasm)]
 pub struct ToWasmAppGotFocus {}
 
@@ -628,13 +779,6 @@ pub struct ToWasmHttpResponseProgress {
     pub total: u32
 }
 
-#[derive(ToWasm)]
-pub struct ToWasmHttpUploadProgress {
-    pub request_id_lo: u32,
-    pub request_id_hi: u32,
-    pub loaded: u32,
-    pub total: u32
-}
 /*
 #[derive(ToWasm)]
 pub struct ToWasmWebSocketClose {
@@ -673,6 +817,7 @@ pub struct ToWasmWebSocketBinary {
 pub struct ToWasmMidiInputData {
     pub uid: String,
     pub data: u32,
+    pub time: f64,
 }
 
 #[derive(ToWasm)]
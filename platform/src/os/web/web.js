@@ -23,7 +23,10 @@ export class WasmWebBrowser extends WasmBridge {
         this.handlers = {};
         this.timers = [];
         this.text_copy_response = "";
+        this.text_copy_html = "";
+        this.drag_response = "none";
         this.web_sockets = [];
+        this.http_requests = {};
         this.window_info = {}
         this.xr_capabilities = {
             vr_supported: false,
@@ -85,6 +88,7 @@ export class WasmWebBrowser extends WasmBridge {
         this.bind_mouse_and_touch();
         this.bind_keyboard();
         this.bind_screen_resize();
+        this.bind_gamepads();
         this.focus_keyboard_input();
         this.to_wasm.ToWasmRedrawAll();
         this.start_signal_poll();
@@ -213,6 +217,68 @@ export class WasmWebBrowser extends WasmBridge {
     
     FromWasmTextCopyResponse(args) {
         this.text_copy_response = args.response
+        this.text_copy_html = args.html
+    }
+    
+    FromWasmDragResponse(args) {
+        this.drag_response = args.drop_effect
+    }
+    
+    FromWasmGamepadRumble(args) {
+        let pad = navigator.getGamepads()[args.index];
+        if (pad && pad.vibrationActuator) {
+            pad.vibrationActuator.playEffect("dual-rumble", {
+                duration: args.duration * 1000.0,
+                strongMagnitude: args.strong,
+                weakMagnitude: args.weak
+            }).catch(_ => {});
+        }
+    }
+    
+    FromWasmWriteClipboard(args) {
+        let items = {};
+        for (let flavor of args.flavors) {
+            // browsers only take a few types, apps can add their own prefixed with 'web '
+            let type = flavor.mime;
+            if (ClipboardItem.supports && !ClipboardItem.supports(type)) {
+                type = "web " + type;
+            }
+            if (!ClipboardItem.supports || ClipboardItem.supports(type)) {
+                items[type] = new Blob([this.clone_data_u8(flavor.data)], {type: flavor.mime});
+            }
+            this.free_data_u8(flavor.data);
+        }
+        navigator.clipboard.write([new ClipboardItem(items)]).catch(e => {
+            console.error("Cannot write to the clipboard", e)
+        });
+    }
+    
+    FromWasmReadClipboard(args) {
+        let respond = flavors => {
+            this.to_wasm.ToWasmClipboardRead({
+                request_id_lo: args.request_id_lo,
+                request_id_hi: args.request_id_hi,
+                flavors: flavors
+            });
+            this.do_wasm_pump();
+        };
+        // the browser asks the user for permission first
+        navigator.clipboard.read().then(async items => {
+            let flavors = [];
+            for (let item of items) {
+                for (let type of item.types) {
+                    let blob = await item.getType(type);
+                    flavors.push({
+                        mime: type.startsWith("web ")? type.slice(4): type,
+                        data: await blob.arrayBuffer()
+                    });
+                }
+            }
+            respond(flavors);
+        }).catch(e => {
+            console.error("Cannot read the clipboard", e);
+            respond([]);
+        });
     }
     
     FromWasmShowTextIME(args) {
@@ -368,6 +434,7 @@ export class WasmWebBrowser extends WasmBridge {
                         this.to_wasm.ToWasmMidiInputData({
                             uid,
                             data: (data[0] << 16) | (data[1] << 8) | data[2],
+                            time: e.timeStamp / 1000.0,
                         });
                         this.do_wasm_pump();
                     }
@@ -490,98 +557,99 @@ export class WasmWebBrowser extends WasmBridge {
         }, 0.016 * 1000.0);
     }
 
-    parse_and_set_headers(request, headers_string) {
-        let lines = headers_string.split("\r\n");
-        for (let line of lines) {
+    FromWasmHTTPRequest(args) {
+        const key = args.request_id_lo + "_" + args.request_id_hi;
+        const controller = new AbortController();
+        (this.http_requests[key] = this.http_requests[key] || []).push(controller);
+        const finish = () => {
+            let list = this.http_requests[key].filter(c => c !== controller);
+            if (list.length == 0) delete this.http_requests[key];
+            else this.http_requests[key] = list;
+        };
+        
+        let headers = new Headers();
+        for (let line of args.headers.split("\r\n")) {
             let parts = line.split(": ");
             if (parts.length == 2) {
-                request.setRequestHeader(parts[0], parts[1]);
+                headers.append(parts[0], parts[1]);
             }
         }
-    }
-
-    FromWasmHTTPRequest(args) {
-        const req = new XMLHttpRequest();
-        req.open(args.method, args.url);
-        req.responseType = "arraybuffer";
-        this.parse_and_set_headers(req, args.headers);
-
-        // TODO decode in appropiate format
-        const decoder = new TextDecoder('UTF-8', { fatal: true });
-        let body = decoder.decode(this.clone_data_u8(args.body));
-
-        req.addEventListener("load", event => {
-            let responseEvent = event.target;
-
+        let init = {method: args.method, headers: headers, signal: controller.signal};
+        if (args.body.len > 0) {
+            init.body = this.clone_data_u8(args.body);
+        }
+        this.free_data_u8(args.body);
+        
+        fetch(args.url, init).then(async response => {
+            const total = parseInt(response.headers.get("content-length") || "0") || 0;
+            let chunks = [];
+            let loaded = 0;
+            let last_progress = performance.now();
+            if (response.body) {
+                const reader = response.body.getReader();
+                for (;;) {
+                    const {done, value} = await reader.read();
+                    if (done) break;
+                    chunks.push(value);
+                    loaded += value.length;
+                    if (performance.now() - last_progress > 50) {
+                        last_progress = performance.now();
+                        this.to_wasm.ToWasmHttpResponseProgress({
+                            request_id_lo: args.request_id_lo,
+                            request_id_hi: args.request_id_hi,
+                            loaded: loaded,
+                            total: total,
+                        });
+                        this.do_wasm_pump();
+                    }
+                }
+            }
+            let body = new Uint8Array(loaded);
+            let offset = 0;
+            for (let chunk of chunks) {
+                body.set(chunk, offset);
+                offset += chunk.length;
+            }
+            let response_headers = "";
+            response.headers.forEach((value, name) => {
+                response_headers += name + ": " + value + "\r\n";
+            });
+            finish();
             this.to_wasm.ToWasmHTTPResponse({
                 request_id_lo: args.request_id_lo,
                 request_id_hi: args.request_id_hi,
-                status: responseEvent.status,
-                body: responseEvent.response,
-                headers: responseEvent.getAllResponseHeaders()
+                metadata_id_lo: args.metadata_id_lo,
+                metadata_id_hi: args.metadata_id_hi,
+                status: response.status,
+                headers: response_headers,
+                body: body
             });
             this.do_wasm_pump();
-        });
-
-        req.addEventListener("error", event => {
-            let errorMessage = "An error occurred with the HTTP request.";
+        }).catch(error => {
+            finish();
+            // a cancelled request reports nothing
+            if (error.name == "AbortError") {
+                return
+            }
+            let message = "An error occurred with the HTTP request: " + error.message;
             if (!navigator.onLine) {
-                errorMessage = "The browser is offline.";
+                message = "The browser is offline.";
             }
-
-            this.to_wasm.ToWasmHttpRequestError({
-                request_id_lo: args.request_id_lo,
-                request_id_hi: args.request_id_hi,
-                error: errorMessage,
-            });
-            this.do_wasm_pump();
-        });
-
-        req.addEventListener("timeout", event => {
-            this.to_wasm.ToWasmHttpRequestError({
-                request_id_lo: args.request_id_lo,
-                request_id_hi: args.request_id_hi,
-                error: "The HTTP request timed out.",
-            });
-            this.do_wasm_pump();
-        });
-
-        req.addEventListener("abort", event => {
             this.to_wasm.ToWasmHttpRequestError({
                 request_id_lo: args.request_id_lo,
                 request_id_hi: args.request_id_hi,
-                error: "The HTTP request was aborted.",
+                error: message,
             });
             this.do_wasm_pump();
         });
-
-        req.addEventListener("progress", event => {
-            console.log("progress", event);
-            if (event.lengthComputable) {
-                this.to_wasm.ToWasmHttpResponseProgress({
-                    request_id_lo: args.request_id_lo,
-                    request_id_hi: args.request_id_hi,
-                    loaded: event.loaded,
-                    total: event.total,
-                });
-                this.do_wasm_pump();
-            }
-        });
-
-        req.upload.addEventListener("progress", (event) => {
-            if (event.lengthComputable) {
-                this.to_wasm.ToWasmHttpUploadProgress({
-                    request_id_lo: args.request_id_lo,
-                    request_id_hi: args.request_id_hi,
-                    loaded: event.loaded,
-                    total: event.total,
-                });
-                this.do_wasm_pump();
-            }
-          });
-
-        req.send(body);
-        this.free_data_u8(args.body);
+    }
+    
+    FromWasmCancelHTTPRequest(args) {
+        const key = args.request_id_lo + "_" + args.request_id_hi;
+        for (let controller of this.http_requests[key] || []) {
+            controller.abort();
+        }
+        delete this.http_requests[key];
     }
     
     // calling into wasm
@@ -729,6 +797,52 @@ export class WasmWebBrowser extends WasmBridge {
         window.addEventListener('orientationchange', _ => this.handlers.on_screen_resize())
     }
     
+    bind_gamepads() {
+        // the Gamepad API has no input events, so the pads get polled every frame while any is connected
+        this.gamepad_timestamps = {};
+        let poll = () => {
+            this.gamepad_poll_id = 0;
+            let changed = false;
+            for (let pad of navigator.getGamepads()) {
+                if (!pad || this.gamepad_timestamps[pad.index] === pad.timestamp) {
+                    continue
+                }
+                this.gamepad_timestamps[pad.index] = pad.timestamp;
+                this.to_wasm.ToWasmGamepadUpdate({
+                    index: pad.index,
+                    time: pad.timestamp / 1000.0,
+                    buttons: pad.buttons.map(b => b.value),
+                    axes: pad.axes.slice()
+                });
+                changed = true;
+            }
+            if (changed) {
+                this.do_wasm_pump();
+            }
+            if (Object.keys(this.gamepad_timestamps).length > 0) {
+                this.gamepad_poll_id = window.requestAnimationFrame(poll);
+            }
+        };
+        
+        this.handlers.on_gamepad_connected = e => {
+            this.gamepad_timestamps[e.gamepad.index] = undefined;
+            this.to_wasm.ToWasmGamepadConnected({index: e.gamepad.index, name: e.gamepad.id});
+            this.do_wasm_pump();
+            if (!this.gamepad_poll_id) {
+                poll();
+            }
+        }
+        
+        this.handlers.on_gamepad_disconnected = e => {
+            delete this.gamepad_timestamps[e.gamepad.index];
+            this.to_wasm.ToWasmGamepadDisconnected({index: e.gamepad.index});
+            this.do_wasm_pump();
+        }
+        
+        window.addEventListener('gamepadconnected', e => this.handlers.on_gamepad_connected(e))
+        window.addEventListener('gamepaddisconnected', e => this.handlers.on_gamepad_disconnected(e))
+    }
+    
     bind_mouse_and_touch() {
         
         var canvas = this.canvas
@@ -871,6 +985,49 @@ export class WasmWebBrowser extends WasmBridge {
         
         canvas.addEventListener('contextmenu', e => this.handlers.on_contextmenu(e))
         
+        // files dragged in from outside the browser
+        this.handlers.on_drag_over = e => {
+            if (!e.dataTransfer.types.includes("Files")) {
+                return
+            }
+            e.preventDefault();
+            this.drag_response = "none";
+            this.to_wasm.ToWasmDrag({
+                x: e.pageX,
+                y: e.pageY,
+                modifiers: pack_key_modifier(e),
+                file_count: Array.from(e.dataTransfer.items).filter(item => item.kind == "file").length
+            });
+            this.do_wasm_pump();
+            e.dataTransfer.dropEffect = this.drag_response;
+        }
+        
+        this.handlers.on_drag_leave = e => {
+            this.to_wasm.ToWasmDragEnd();
+            this.do_wasm_pump();
+        }
+        
+        this.handlers.on_drop = async e => {
+            if (!e.dataTransfer.types.includes("Files")) {
+                return
+            }
+            e.preventDefault();
+            // the event is gone after the first await, so read everything from it first
+            let x = e.pageX;
+            let y = e.pageY;
+            let modifiers = pack_key_modifier(e);
+            let files = [];
+            for (let file of Array.from(e.dataTransfer.files)) {
+                files.push({name: file.name, data: await file.arrayBuffer()});
+            }
+            this.to_wasm.ToWasmDrop({x, y, modifiers, files});
+            this.do_wasm_pump();
+        }
+        
+        canvas.addEventListener('dragover', e => this.handlers.on_drag_over(e))
+        canvas.addEventListener('dragleave', e => this.handlers.on_drag_leave(e))
+        canvas.addEventListener('drop', e => this.handlers.on_drop(e))
+        
         function touch_to_wasm_wtouch(t, state) {
             return {
                 state,
@@ -1043,7 +1200,18 @@ export class WasmWebBrowser extends WasmBridge {
         this.neutralize_ime = false;
         var last_len = 0;
         
+        // with styled text to copy we fill the clipboard ourselves, instead of letting the
+        // browser copy the selected plain text from the textarea
+        let set_clipboard_data = e => {
+            if (this.text_copy_html && e.clipboardData) {
+                e.clipboardData.setData('text/plain', this.text_copy_response);
+                e.clipboardData.setData('text/html', this.text_copy_html);
+                e.preventDefault();
+            }
+        }
+        
         this.handlers.on_cut = e => {
+            set_clipboard_data(e);
             setTimeout(_ => {
                 ta.value = "";
                 last_len = 0;
@@ -1053,6 +1221,7 @@ export class WasmWebBrowser extends WasmBridge {
         ta.addEventListener('cut', e => this.handlers.on_cut(e));
         
         this.handlers.on_copy = e => {
+            set_clipboard_data(e);
             setTimeout(_ => {
                 ta.value = "";
                 last_len = 0;
@@ -1071,7 +1240,38 @@ export class WasmWebBrowser extends WasmBridge {
         
         ta.addEventListener('select', e => this.handlers.on_select(e))
         
+        var is_composing = false;
+        
+        this.handlers.on_composition = (e, start, commit) => {
+            is_composing = !commit;
+            this.to_wasm.ToWasmTextComposition({
+                start: start,
+                commit: commit,
+                preedit: commit ? "" : (e.data || ""),
+            });
+            if (commit) {
+                if (e.data) {
+                    this.to_wasm.ToWasmTextInput({
+                        was_paste: false,
+                        input: e.data,
+                        replace_last: false,
+                    });
+                }
+                // the composed text is sent, so the input event that may follow has nothing left to send
+                ta.value = "";
+                last_len = 0;
+            }
+            this.do_wasm_pump();
+        }
+        
+        ta.addEventListener('compositionstart', e => this.handlers.on_composition(e, true, false));
+        ta.addEventListener('compositionupdate', e => this.handlers.on_composition(e, false, false));
+        ta.addEventListener('compositionend', e => this.handlers.on_composition(e, false, true));
+        
         this.handlers.on_input = e => {
+            if (is_composing) {
+                return
+            }
             if (ta.value.length > 0) {
                 if (was_paste) {
                     was_paste = false;
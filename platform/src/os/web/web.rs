@@ -2,7 +2,7 @@
 use {
     std::panic,
     std::rc::Rc,
-    std::cell::RefCell,
+    std::cell::{Cell, RefCell},
     self::super::{
         web_media::CxWebMedia,
         from_wasm::*,
@@ -22,7 +22,9 @@ use {
             NetworkResponse,
             Event,
             XRInput,
+            GamepadId,
             TextClipboardEvent,
+            DragResponse,
             TimerEvent,
             MouseDownEvent,
             MouseMoveEvent,
@@ -33,6 +35,7 @@ use {
             WindowGeomChangeEvent
         },
         pass::CxPassParent,
+        color_space::ColorSpace,
         cx_api::{CxOsApi, CxOsOp},
         cx::{Cx},
     }
@@ -61,6 +64,7 @@ impl Cx {
                         tw.gpu_info.vendor,
                         tw.gpu_info.renderer
                     );
+                    self.os.webgpu = tw.gpu_info.webgpu;
                     self.os_type = tw.browser_info.into();
                     self.xr_capabilities = tw.xr_capabilities.into();
                     
@@ -98,11 +102,11 @@ impl Cx {
                         self.os.window_geom = new_geom.clone();
                         let id_zero = CxWindowPool::id_zero();
                         self.windows[id_zero].window_geom = new_geom.clone();
-                        self.call_event_handler(&Event::WindowGeomChange(WindowGeomChangeEvent {
+                        self.call_window_geom_change_event(WindowGeomChangeEvent {
                             window_id: id_zero,
                             old_geom: old_geom,
                             new_geom: new_geom
-                        }));
+                        });
                         self.redraw_all();
                     }
                 }
@@ -168,19 +172,74 @@ impl Cx {
                     self.call_event_handler(&Event::TextInput(tw.into()));
                 }
                 
+                live_id!(ToWasmTextComposition) => {
+                    let tw = ToWasmTextComposition::read_to_wasm(&mut to_wasm);
+                    self.call_event_handler(&Event::TextComposition(tw.into()));
+                }
+                
                 live_id!(ToWasmTextCopy) => {
-                    let response = Rc::new(RefCell::new(None));
-                    self.call_event_handler(&Event::TextCopy(TextClipboardEvent {
-                        response: response.clone()
-                    }));
-                    let response = response.borrow_mut().take();
+                    let event = TextClipboardEvent {
+                        response: Rc::new(RefCell::new(None)),
+                        rich_response: Rc::new(RefCell::new(None))
+                    };
+                    self.call_event_handler(&Event::TextCopy(event.clone()));
+                    let response = event.response.borrow_mut().take();
                     if let Some(response) = response {
-                        self.os.from_wasm(FromWasmTextCopyResponse {response});
+                        let html = event.rich_response.borrow_mut().take().and_then( | rich | rich.html).unwrap_or_default();
+                        self.os.from_wasm(FromWasmTextCopyResponse {response, html});
                     }
                 }
                 
+                live_id!(ToWasmClipboardRead) => {
+                    let tw = ToWasmClipboardRead::read_to_wasm(&mut to_wasm);
+                    self.call_event_handler(&Event::ClipboardRead(tw.into()));
+                }
+                
+                live_id!(ToWasmGamepadConnected) => {
+                    let tw = ToWasmGamepadConnected::read_to_wasm(&mut to_wasm);
+                    self.gamepad_connected(GamepadId(tw.index as u64), &tw.name);
+                }
+                
+                live_id!(ToWasmGamepadDisconnected) => {
+                    let tw = ToWasmGamepadDisconnected::read_to_wasm(&mut to_wasm);
+                    self.gamepad_disconnected(GamepadId(tw.index as u64));
+                }
+                
+                live_id!(ToWasmGamepadUpdate) => {
+                    let tw = ToWasmGamepadUpdate::read_to_wasm(&mut to_wasm);
+                    self.gamepad_update(GamepadId(tw.index as u64), tw.to_gamepad_state(), tw.time);
+                }
+                
+                live_id!(ToWasmDrag) => {
+                    let response = Rc::new(Cell::new(DragResponse::None));
+                    let e = ToWasmDrag::read_to_wasm(&mut to_wasm).into_drag_event(response.clone());
+                    self.call_event_handler(&Event::Drag(e));
+                    self.drag_drop.cycle_drag();
+                    self.os.from_wasm(FromWasmDragResponse {
+                        drop_effect: match response.get() {
+                            DragResponse::None => "none",
+                            DragResponse::Copy => "copy",
+                            DragResponse::Link => "link",
+                            DragResponse::Move => "move",
+                        }.to_string()
+                    });
+                }
+                
+                live_id!(ToWasmDrop) => {
+                    let tw = ToWasmDrop::read_to_wasm(&mut to_wasm);
+                    self.call_event_handler(&Event::Drop(tw.into()));
+                    self.drag_drop.cycle_drag();
+                }
+                
+                live_id!(ToWasmDragEnd) => {
+                    self.call_event_handler(&Event::DragEnd);
+                    self.drag_drop.cycle_drag();
+                }
+                
                 live_id!(ToWasmSignal) =>{
                     self.handle_media_signals();
+                    self.handle_web_socket_events();
+                    self.handle_dialog_events();
                     self.call_event_handler(&Event::Signal);
                 }
                 
@@ -249,13 +308,6 @@ impl Cx {
                     });
                 }
 
-                live_id!(ToWasmHttpUploadProgress) => {
-                    let tw = ToWasmHttpUploadProgress::read_to_wasm(&mut to_wasm);
-                    network_responses.push(NetworkResponseItem{
-                        request_id: LiveId::from_lo_hi(tw.request_id_lo, tw.request_id_hi),
-                        response: NetworkResponse::HttpProgress{loaded:tw.loaded as u64, total:tw.total as u64}
-                    });
-                }
                 /*
                 live_id!(ToWasmWebSocketClose) => {
                     let tw = ToWasmWebSocketClose::read_to_wasm(&mut to_wasm);
@@ -319,6 +371,7 @@ impl Cx {
                 live_id!(ToWasmMidiInputData)=>{
                     let tw = ToWasmMidiInputData::read_to_wasm(&mut to_wasm);
                     self.os.web_midi().lock().unwrap().to_wasm_midi_input_data(tw);
+                    self.handle_midi_input_events();
                 }
                 msg_id => {
                     // swap the message into an event to avoid a copy
@@ -338,13 +391,18 @@ impl Cx {
         if let Some(time) = is_animation_frame {
             if self.need_redrawing() {
                 self.call_draw_event();
-                self.webgl_compile_shaders();
+                if self.os.webgpu {
+                    self.webgpu_compile_shaders();
+                }
+                else {
+                    self.webgl_compile_shaders();
+                }
             }
             self.handle_repaint(time);
         }
 
         if network_responses.len() != 0 {
-            self.call_event_handler(&Event::NetworkResponses(network_responses));
+            self.call_network_responses(network_responses);
         }
         
         if self.handle_live_edit(){ 
@@ -422,6 +480,10 @@ impl Cx {
                 CxOsOp::SetTopmost(_window_id, _is_topmost) => {
                     todo!()
                 }
+                CxOsOp::SetWindowColorSpace(window_id, _color_space) => {
+                    // the surface is always presented as sRGB, so that's what the window draws in
+                    self.windows[window_id].color_space = ColorSpace::Srgb;
+                }
                 CxOsOp::XrStartPresenting => {
                     self.os.from_wasm(FromWasmXrStartPresenting {});
                 },
@@ -437,8 +499,32 @@ impl Cx {
                 },
                 CxOsOp::ShowClipboardActions(_) =>{
                 }
-                CxOsOp::CopyToClipboard(_) =>{
-                    crate::error!("Clipboard actions not supported in web")
+                CxOsOp::CopyToClipboard(content) =>{
+                    self.os.from_wasm(FromWasmWriteClipboard {
+                        flavors: vec![WClipboardFlavor {mime: "text/plain".into(), data: WasmDataU8::from_vec_u8(content.into_bytes())}]
+                    });
+                }
+                CxOsOp::WriteClipboard(flavors) =>{
+                    self.os.from_wasm(FromWasmWriteClipboard {
+                        flavors: flavors.iter().map( | flavor | WClipboardFlavor {
+                            mime: flavor.mime().to_string(),
+                            data: WasmDataU8::from_vec_u8(flavor.data().to_vec())
+                        }).collect()
+                    });
+                }
+                CxOsOp::ReadClipboard(request_id) =>{
+                    self.os.from_wasm(FromWasmReadClipboard {
+                        request_id_lo: request_id.lo(),
+                        request_id_hi: request_id.hi(),
+                    });
+                }
+                CxOsOp::GamepadRumble {gamepad_id, strong, weak, duration} => {
+                    self.os.from_wasm(FromWasmGamepadRumble {
+                        index: gamepad_id.0 as u32,
+                        strong,
+                        weak,
+                        duration
+                    });
                 }
                 CxOsOp::SetCursor(cursor) => {
                     self.os.from_wasm(FromWasmSetMouseCursor::new(cursor));
@@ -472,6 +558,12 @@ impl Cx {
                         body: WasmDataU8::from_vec_u8(request.body.unwrap_or(Vec::new())),
                     });
                 },
+                CxOsOp::CancelHttpRequest{request_id} => {
+                    self.os.from_wasm(FromWasmCancelHTTPRequest {
+                        request_id_lo: request_id.lo(),
+                        request_id_hi: request_id.hi(),
+                    });
+                },
                 /*
                 CxOsOp::WebSocketOpen{request_id, request}=>{
                     let headers = request.get_headers_string();
@@ -506,10 +598,9 @@ impl Cx {
                 CxOsOp::UnmuteVideoPlayback(_) => todo!(),
                 CxOsOp::CleanupVideoPlaybackResources(_) => todo!(),
                 CxOsOp::UpdateVideoSurfaceTexture(_) => todo!(),
-                CxOsOp::SaveFileDialog(_) => todo!(),
-                CxOsOp::SelectFileDialog(_) => todo!(),
-                CxOsOp::SaveFolderDialog(_) => todo!(),
-                CxOsOp::SelectFolderDialog(_) => todo!(),    
+                CxOsOp::ShowDialog(dialog_id, dialog) => {
+                    self.show_dialog_fallback(dialog_id, dialog);
+                },
             }
         }
     }
@@ -537,7 +628,15 @@ impl CxOsApi for Cx {
             ToWasmKeyDown::to_js_code(),
             ToWasmKeyUp::to_js_code(),
             ToWasmTextInput::to_js_code(),
+            ToWasmTextComposition::to_js_code(),
             ToWasmTextCopy::to_js_code(),
+            ToWasmClipboardRead::to_js_code(),
+            ToWasmDrag::to_js_code(),
+            ToWasmDrop::to_js_code(),
+            ToWasmDragEnd::to_js_code(),
+            ToWasmGamepadConnected::to_js_code(),
+            ToWasmGamepadDisconnected::to_js_code(),
+            ToWasmGamepadUpdate::to_js_code(),
             ToWasmTimerFired::to_js_code(),
             ToWasmPaintDirty::to_js_code(),
             ToWasmRedrawAll::to_js_code(),
@@ -547,7 +646,6 @@ impl CxOsApi for Cx {
             ToWasmHTTPResponse::to_js_code(),
             ToWasmHttpRequestError::to_js_code(),
             ToWasmHttpResponseProgress::to_js_code(),
-            ToWasmHttpUploadProgress::to_js_code(),
             /*ToWasmWebSocketOpen::to_js_code(),
             ToWasmWebSocketClose::to_js_code(),
             ToWasmWebSocketError::to_js_code(),
@@ -569,10 +667,15 @@ impl CxOsApi for Cx {
             FromWasmSetDocumentTitle::to_js_code(),
             FromWasmSetMouseCursor::to_js_code(),
             FromWasmTextCopyResponse::to_js_code(),
+            FromWasmWriteClipboard::to_js_code(),
+            FromWasmReadClipboard::to_js_code(),
+            FromWasmDragResponse::to_js_code(),
+            FromWasmGamepadRumble::to_js_code(),
             FromWasmShowTextIME::to_js_code(),
             FromWasmHideTextIME::to_js_code(),
             FromWasmCreateThread::to_js_code(),
             FromWasmHTTPRequest::to_js_code(),
+            FromWasmCancelHTTPRequest::to_js_code(),
             /*FromWasmWebSocketOpen::to_js_code(),
             FromWasmWebSocketSendString::to_js_code(),
             FromWasmWebSocketSendBinary::to_js_code(),*/
@@ -580,6 +683,7 @@ impl CxOsApi for Cx {
             FromWasmXrStopPresenting::to_js_code(),
             
             FromWasmCompileWebGLShader::to_js_code(),
+            FromWasmCompileWebGPUShader::to_js_code(),
             FromWasmAllocArrayBuffer::to_js_code(),
             FromWasmAllocIndexBuffer::to_js_code(),
             FromWasmAllocVao::to_js_code(),
@@ -670,6 +774,8 @@ pub struct CxOs {
     pub (crate) vertex_buffers: usize,
     pub (crate) index_buffers: usize,
     pub (crate) vaos: usize,
+    // set when the page runs on the WebGPU backend instead of WebGL
+    pub (crate) webgpu: bool,
     
     pub (crate) xr_last_inputs: Option<Vec<XRInput >>,
     
@@ -689,6 +795,7 @@ impl Default for CxOs{
             vertex_buffers: 0,
             index_buffers: 0,
             vaos: 0,
+            webgpu: false,
                     
             xr_last_inputs: None,
                     
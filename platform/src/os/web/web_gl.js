@@ -524,14 +524,23 @@ export class WasmWebGL extends WasmWebBrowser {
     }
     
     init_webgl_context() {
-        let mqString = '(resolution: ' + window.devicePixelRatio + 'dppx)'
-        let mq = matchMedia(mqString);
+        // a resolution query matches one pixel ratio only, so when the page moves to a display
+        // with another one we watch for the next change with a new query
+        let watch_dpi_factor = () => {
+            let mqString = '(resolution: ' + window.devicePixelRatio + 'dppx)'
+            let mq = matchMedia(mqString);
+            mq.addEventListener("change", _ => {
+                this.handlers.on_screen_resize();
+                watch_dpi_factor();
+            }, {once: true});
+        }
+        let mq = matchMedia('(resolution: 1dppx)');
         if (mq && mq.addEventListener) {
-            mq.addEventListener("change", this.handlers.on_screen_resize);
+            watch_dpi_factor();
         }
         else { // poll for it. yes. its terrible
             window.setInterval(_ => {
-                if (window.devicePixelRation != this.dpi_factor) {
+                if (window.devicePixelRatio != this.window_info.dpi_factor) {
                     this.handlers.on_screen_resize();
                 }
             }, 1000);
@@ -573,7 +582,8 @@ export class WasmWebGL extends WasmWebBrowser {
         this.gpu_info = {
             min_uniforms: Math.min(max_vertex_uniforms, max_fragment_uniforms),
             vendor: "unknown",
-            renderer: "unknown"
+            renderer: "unknown",
+            webgpu: false
         }
         let debug_info = gl.getExtension('WEBGL_debug_renderer_info');
         
@@ -2,6 +2,7 @@ use {
     crate::{
         makepad_shader_compiler::{
             generate_glsl,
+            generate_wgsl,
         },
         makepad_wasm_bridge::*,
         makepad_math::*,
@@ -71,6 +72,11 @@ impl Cx {
                 draw_call.draw_uniforms.set_zbias(*zbias);
                 *zbias += zbias_step;
                 
+                // the color space of the first texture, shaders convert its samples to sRGB with it
+                let texture_color_space = draw_call.texture_slots[0].as_ref()
+                    .map( | texture | self.textures[texture.texture_id()].color_space).unwrap_or_default();
+                draw_call.draw_uniforms.set_texture_color_space(texture_color_space);
+                
                 // update/alloc textures?
                 for i in 0..sh.mapping.textures.len() {
                     let texture_id = if let Some(texture) = &draw_call.texture_slots[i] {
@@ -202,6 +208,8 @@ impl Cx {
         let dpi_factor = self.passes[pass_id].dpi_factor.unwrap();
         let pass_rect = self.get_pass_rect(pass_id, dpi_factor).unwrap();
         self.passes[pass_id].set_dpi_factor(dpi_factor);
+        let color_space = self.get_delegated_color_space(pass_id);
+        self.passes[pass_id].set_color_space_uniform(color_space);
         self.passes[pass_id].set_matrix(pass_rect.pos, pass_rect.size);
         pass_rect.size 
     }
@@ -210,6 +218,7 @@ impl Cx {
         &mut self,
         pass_id: PassId,
     ) {
+        self.skip_read_back(pass_id);
         let draw_list_id = self.passes[pass_id].main_draw_list_id.unwrap();
         
         // get the color and depth
@@ -222,6 +231,7 @@ impl Cx {
                 PassClearColor::ClearWith(color) => color
             }
         };
+        let clear_color = self.get_delegated_clear_color(pass_id, clear_color);
         let clear_depth = match self.passes[pass_id].clear_depth {
             PassClearDepth::InitWith(depth) => depth,
             PassClearDepth::ClearWith(depth) => depth
@@ -248,6 +258,7 @@ impl Cx {
     }
     
     pub fn draw_pass_to_texture(&mut self, pass_id: PassId) {
+        self.skip_read_back(pass_id);
         let draw_list_id = self.passes[pass_id].main_draw_list_id.unwrap();
         
         let pass_size = self.setup_render_pass(pass_id);
@@ -270,14 +281,14 @@ impl Cx {
                     color_targets[index] = WColorTarget{
                         texture_id: color_texture.texture.texture_id().0,
                         init_only: true,
-                        clear_color: clear_color.into()
+                        clear_color: self.get_delegated_clear_color(pass_id, clear_color).into()
                     };
                 },
                 PassClearColor::ClearWith(clear_color) => {
                     color_targets[index] = WColorTarget{
                         texture_id: color_texture.texture.texture_id().0,
                         init_only: false,
-                        clear_color: clear_color.into()
+                        clear_color: self.get_delegated_clear_color(pass_id, clear_color).into()
                     };
                 }
             }
@@ -378,6 +389,44 @@ impl Cx {
         }
         self.draw_shaders.compile_set.clear();
     }
+    
+    pub fn webgpu_compile_shaders(&mut self) {
+        for draw_shader_ptr in &self.draw_shaders.compile_set {
+            if let Some(item) = self.draw_shaders.ptr_to_item.get(&draw_shader_ptr) {
+                let cx_shader = &mut self.draw_shaders.shaders[item.draw_shader_id];
+                let draw_shader_def = self.shader_registry.draw_shader_defs.get(&draw_shader_ptr);
+                
+                // the vertex and pixel entrypoints live in one wgsl module
+                let wgsl = generate_wgsl::generate_shader(
+                    draw_shader_def.as_ref().unwrap(),
+                    &cx_shader.mapping.const_table,
+                    &self.shader_registry
+                );
+                
+                if cx_shader.mapping.flags.debug {
+                    crate::log!("{}", wgsl);
+                }
+                for (index, ds) in self.draw_shaders.os_shaders.iter().enumerate() {
+                    if ds.vertex == wgsl {
+                        cx_shader.os_shader_id = Some(index);
+                        break;
+                    }
+                }
+                if cx_shader.os_shader_id.is_none() {
+                    self.os.from_wasm(FromWasmCompileWebGPUShader{
+                        shader_id: item.draw_shader_id,
+                        wgsl: wgsl.clone(),
+                        geometry_slots: cx_shader.mapping.geometries.total_slots,
+                        instance_slots: cx_shader.mapping.instances.total_slots,
+                        textures:cx_shader.mapping.textures.iter().map(|v| v.to_from_wasm_texture_input()).collect()
+                    });
+                    cx_shader.os_shader_id = Some(self.draw_shaders.os_shaders.len());
+                    self.draw_shaders.os_shaders.push(CxOsDrawShader{vertex: wgsl, pixel: String::new()});
+                }
+            }
+        }
+        self.draw_shaders.compile_set.clear();
+    }
 }
 
 impl CxOsDrawShader{
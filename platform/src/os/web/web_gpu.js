@@ -0,0 +1,478 @@
+import {WasmWebBrowser} from "./web.js";
+
+// the storage buffer bindings every generated wgsl shader declares, see generate_wgsl.rs
+const TABLE_BINDINGS = 6;
+const SAMPLER_BINDING = 6;
+const TEXTURE_BINDING_BASE = 7;
+
+export class WasmWebGPU extends WasmWebBrowser {
+    constructor(wasm, dispatch, canvas) {
+        super (wasm, dispatch, canvas);
+        if (wasm === undefined) {
+            return
+        }
+        this.draw_shaders = [];
+        this.array_buffers = [];
+        this.index_buffers = [];
+        this.vaos = [];
+        this.textures = [];
+        this.frame = undefined;
+        this.destroy_after_submit = [];
+
+        this.init_webgpu_context().then(ok => {
+            if (ok) {
+                this.load_deps();
+            }
+        });
+    }
+
+    // the wasm side queues up all the render commands for a frame in one go,
+    // so we record them and submit a single command buffer when it returns
+    do_wasm_pump() {
+        super.do_wasm_pump();
+        this.submit_frame();
+    }
+
+    // WebGPU API
+
+    async query_xr_capabilities() {
+        // WebXR only has a WebGL layer, so leave the capabilities off
+    }
+
+    FromWasmXrStartPresenting(args) {
+        console.log("WebXR is not supported on the WebGPU backend")
+    }
+
+    FromWasmXrStopPresenting() {
+    }
+
+    FromWasmCompileWebGPUShader(args) {
+        function vertex_buffer_layout(slots, location, step_mode) {
+            if (slots == 0) {
+                return null
+            }
+            let attributes = [];
+            for (let i = 0; i < slots; i += 4) {
+                let size = Math.min(slots - i, 4);
+                attributes.push({
+                    shaderLocation: location ++,
+                    offset: i * 4,
+                    format: size == 1? "float32": "float32x" + size
+                });
+            }
+            return {
+                arrayStride: slots * 4,
+                stepMode: step_mode,
+                attributes
+            }
+        }
+
+        let device = this.device;
+        let module = device.createShaderModule({code: args.wgsl});
+        module.getCompilationInfo().then(info => {
+            for (let msg of info.messages) {
+                if (msg.type == "error") {
+                    console.log(
+                        msg.lineNum + ":" + msg.linePos + " " + msg.message,
+                        add_line_numbers_to_string(args.wgsl)
+                    )
+                }
+            }
+        });
+
+        let visibility = GPUShaderStage.VERTEX | GPUShaderStage.FRAGMENT;
+        let entries = [];
+        for (let i = 0; i < TABLE_BINDINGS; i ++) {
+            entries.push({binding: i, visibility, buffer: {type: "read-only-storage"}});
+        }
+        entries.push({binding: SAMPLER_BINDING, visibility, sampler: {type: "filtering"}});
+        for (let i = 0; i < args.textures.length; i ++) {
+            entries.push({binding: TEXTURE_BINDING_BASE + i, visibility, texture: {sampleType: "float"}});
+        }
+        let bind_group_layout = device.createBindGroupLayout({entries});
+
+        let geom_layout = vertex_buffer_layout(args.geometry_slots, 0, "vertex");
+        let inst_layout = vertex_buffer_layout(
+            args.instance_slots,
+            geom_layout? geom_layout.attributes.length: 0,
+            "instance"
+        );
+
+        this.draw_shaders[args.shader_id] = {
+            wgsl: args.wgsl,
+            module,
+            bind_group_layout,
+            pipeline_layout: device.createPipelineLayout({bindGroupLayouts: [bind_group_layout]}),
+            vertex_buffers: [geom_layout, inst_layout],
+            texture_count: args.textures.length,
+            geometry_slots: args.geometry_slots,
+            instance_slots: args.instance_slots,
+            pipelines: {},
+        };
+    }
+
+    FromWasmAllocIndexBuffer(args) {
+        let array = new Uint32Array(this.memory.buffer, args.data.ptr, args.data.len);
+        this.alloc_buffer(this.index_buffers, args.buffer_id, array, GPUBufferUsage.INDEX);
+    }
+
+    FromWasmAllocArrayBuffer(args) {
+        let array = new Float32Array(this.memory.buffer, args.data.ptr, args.data.len);
+        this.alloc_buffer(this.array_buffers, args.buffer_id, array, GPUBufferUsage.VERTEX);
+    }
+
+    FromWasmAllocVao(args) {
+        // there are no vertex array objects in WebGPU, the buffers are bound per draw
+        this.vaos[args.vao_id] = {
+            shader_id: args.shader_id,
+            geom_ib_id: args.geom_ib_id,
+            geom_vb_id: args.geom_vb_id,
+            inst_vb_id: args.inst_vb_id
+        };
+    }
+
+    FromWasmDrawCall(args) {
+        let pass = this.frame && this.frame.passes[this.frame.passes.length - 1];
+        if (pass === undefined) {
+            return
+        }
+        let shader = this.draw_shaders[args.shader_id];
+        let vao = this.vaos[args.vao_id];
+        let index_buffer = this.index_buffers[vao.geom_ib_id];
+        let geom_buffer = this.array_buffers[vao.geom_vb_id];
+        let instance_buffer = this.array_buffers[vao.inst_vb_id];
+
+        let textures = [];
+        for (let i = 0; i < shader.texture_count; i ++) {
+            let texture_id = args.textures[i];
+            let tex_obj = texture_id !== undefined? this.textures[texture_id]: undefined;
+            textures.push(tex_obj !== undefined? tex_obj.view: this.dummy_texture_view);
+        }
+
+        // the wasm memory is only valid during this call, so copy the tables out now
+        pass.draws.push({
+            shader,
+            tables: [
+                this.push_table(args.pass_uniforms),
+                this.push_table(args.view_uniforms),
+                this.push_table(args.draw_uniforms),
+                this.push_table(args.user_uniforms),
+                this.push_table(args.live_uniforms),
+                this.push_table(args.const_table),
+            ],
+            textures,
+            index_buffer: index_buffer.gpu_buf,
+            geom_buffer: geom_buffer.gpu_buf,
+            instance_buffer: instance_buffer.gpu_buf,
+            indices: index_buffer.length,
+            instances: instance_buffer.length / shader.instance_slots,
+        });
+    }
+
+    FromWasmAllocTextureImage2D_BGRAu8_32(args) {
+        // the pixels are u32 0xAARRGGBB so in memory they are already in bgra order
+        let data_array = new Uint8Array(this.memory.buffer, args.data.ptr, args.width * args.height * 4);
+        let tex_obj = this.alloc_texture(args.texture_id, args.width, args.height, "bgra8unorm", GPUTextureUsage.COPY_DST);
+        this.device.queue.writeTexture(
+            {texture: tex_obj.gpu_tex},
+            data_array,
+            {bytesPerRow: args.width * 4},
+            [args.width, args.height]
+        );
+    }
+
+    FromWasmAllocTextureImage2D_Ru8(args) {
+        let data_array = new Uint8Array(this.memory.buffer, args.data.ptr, args.width * args.height);
+        let tex_obj = this.alloc_texture(args.texture_id, args.width, args.height, "r8unorm", GPUTextureUsage.COPY_DST);
+        this.device.queue.writeTexture(
+            {texture: tex_obj.gpu_tex},
+            data_array,
+            {bytesPerRow: args.width},
+            [args.width, args.height]
+        );
+    }
+
+    FromWasmBeginRenderTexture(args) {
+        let clear = false;
+        let clear_color;
+        let tex_obj;
+        for (let i = 0; i < args.color_targets.length; i ++) {
+            let tgt = args.color_targets[i];
+            let old_tex_obj = this.textures[tgt.texture_id];
+            tex_obj = this.alloc_texture(tgt.texture_id, args.width, args.height, "rgba8unorm", GPUTextureUsage.RENDER_ATTACHMENT);
+            clear_color = tgt.clear_color;
+            if (tex_obj !== old_tex_obj || !tgt.init_only) {
+                clear = true;
+            }
+        }
+        // TODO implement depth target
+        this.begin_frame_pass({
+            view: tex_obj.view,
+            format: "rgba8unorm",
+            clear,
+            clear_color,
+        });
+    }
+
+    FromWasmBeginRenderCanvas(args) {
+        this.begin_frame_pass({
+            view: undefined,
+            format: this.canvas_format,
+            clear: true,
+            clear_color: args.clear_color,
+        });
+    }
+
+    FromWasmSetDefaultDepthAndBlendMode() {
+        // depth testing is off and the premultiplied blend is part of every pipeline
+    }
+
+    begin_frame_pass(pass) {
+        if (this.frame === undefined) {
+            this.frame = {passes: []};
+        }
+        pass.draws = [];
+        this.frame.passes.push(pass);
+    }
+
+    push_table(table) {
+        // every table gets its own aligned range in this frame's uniform buffer
+        let align = this.table_align >> 2;
+        let offset = this.table_len;
+        let len = table.ptr != 0? table.len: 0;
+        let size = Math.max(len, 4);
+        let end = offset + Math.ceil(size / align) * align;
+        if (end > this.table_data.length) {
+            let table_data = new Float32Array(Math.max(end, this.table_data.length * 2));
+            table_data.set(this.table_data.subarray(0, offset));
+            this.table_data = table_data;
+        }
+        if (len != 0) {
+            this.table_data.set(new Float32Array(this.memory.buffer, table.ptr, len), offset);
+        }
+        this.table_len = end;
+        return {offset: offset * 4, size: size * 4};
+    }
+
+    alloc_buffer(buffers, buffer_id, array, usage) {
+        let buf = buffers[buffer_id];
+        if (buf === undefined || buf.gpu_buf.size < array.byteLength) {
+            if (buf !== undefined) {
+                this.destroy_after_submit.push(buf.gpu_buf);
+            }
+            buf = buffers[buffer_id] = {
+                gpu_buf: this.device.createBuffer({
+                    size: Math.max(array.byteLength, 4),
+                    usage: usage | GPUBufferUsage.COPY_DST
+                }),
+            };
+        }
+        buf.length = array.length;
+        if (array.byteLength != 0) {
+            this.device.queue.writeBuffer(buf.gpu_buf, 0, array);
+        }
+    }
+
+    alloc_texture(texture_id, width, height, format, usage) {
+        let tex_obj = this.textures[texture_id];
+        if (tex_obj !== undefined && tex_obj.width == width && tex_obj.height == height && tex_obj.format == format) {
+            return tex_obj
+        }
+        if (tex_obj !== undefined) {
+            this.destroy_after_submit.push(tex_obj.gpu_tex);
+        }
+        let gpu_tex = this.device.createTexture({
+            size: [Math.max(width, 1), Math.max(height, 1)],
+            format,
+            usage: usage | GPUTextureUsage.TEXTURE_BINDING
+        });
+        return this.textures[texture_id] = {
+            gpu_tex,
+            view: gpu_tex.createView(),
+            width,
+            height,
+            format
+        };
+    }
+
+    get_pipeline(shader, format) {
+        let pipeline = shader.pipelines[format];
+        if (pipeline !== undefined) {
+            return pipeline
+        }
+        let blend_component = {srcFactor: "one", dstFactor: "one-minus-src-alpha", operation: "add"};
+        return shader.pipelines[format] = this.device.createRenderPipeline({
+            layout: shader.pipeline_layout,
+            vertex: {
+                module: shader.module,
+                entryPoint: "vertex_main",
+                buffers: shader.vertex_buffers
+            },
+            fragment: {
+                module: shader.module,
+                entryPoint: "pixel_main",
+                targets: [{
+                    format,
+                    blend: {color: blend_component, alpha: blend_component}
+                }]
+            },
+            primitive: {topology: "triangle-list"}
+        });
+    }
+
+    submit_frame() {
+        let frame = this.frame;
+        if (frame === undefined) {
+            return
+        }
+        this.frame = undefined;
+        let device = this.device;
+
+        // upload all the tables of this frame in one go
+        let table_bytes = this.table_len * 4;
+        if (this.table_buffer === undefined || this.table_buffer.size < table_bytes) {
+            if (this.table_buffer !== undefined) {
+                this.destroy_after_submit.push(this.table_buffer);
+            }
+            this.table_buffer = device.createBuffer({
+                size: Math.max(table_bytes, this.table_data.byteLength),
+                usage: GPUBufferUsage.STORAGE | GPUBufferUsage.COPY_DST
+            });
+        }
+        if (table_bytes != 0) {
+            device.queue.writeBuffer(this.table_buffer, 0, this.table_data, 0, this.table_len);
+        }
+        this.table_len = 0;
+
+        let encoder = device.createCommandEncoder();
+        let canvas_view;
+        for (let pass of frame.passes) {
+            let view = pass.view;
+            if (view === undefined) {
+                canvas_view = canvas_view || this.context.getCurrentTexture().createView();
+                view = canvas_view;
+            }
+            let c = pass.clear_color;
+            let render_pass = encoder.beginRenderPass({
+                colorAttachments: [{
+                    view,
+                    clearValue: {r: c.r, g: c.g, b: c.b, a: c.a},
+                    loadOp: pass.clear? "clear": "load",
+                    storeOp: "store"
+                }]
+            });
+            for (let draw of pass.draws) {
+                let shader = draw.shader;
+                let entries = [];
+                for (let i = 0; i < TABLE_BINDINGS; i ++) {
+                    entries.push({
+                        binding: i,
+                        resource: {buffer: this.table_buffer, offset: draw.tables[i].offset, size: draw.tables[i].size}
+                    });
+                }
+                entries.push({binding: SAMPLER_BINDING, resource: this.sampler});
+                for (let i = 0; i < draw.textures.length; i ++) {
+                    entries.push({binding: TEXTURE_BINDING_BASE + i, resource: draw.textures[i]});
+                }
+                render_pass.setPipeline(this.get_pipeline(shader, pass.format));
+                render_pass.setBindGroup(0, device.createBindGroup({layout: shader.bind_group_layout, entries}));
+                if (shader.geometry_slots != 0) {
+                    render_pass.setVertexBuffer(0, draw.geom_buffer);
+                }
+                if (shader.instance_slots != 0) {
+                    render_pass.setVertexBuffer(1, draw.instance_buffer);
+                }
+                render_pass.setIndexBuffer(draw.index_buffer, "uint32");
+                render_pass.drawIndexed(draw.indices, draw.instances);
+            }
+            render_pass.end();
+        }
+        device.queue.submit([encoder.finish()]);
+
+        for (let resource of this.destroy_after_submit) {
+            resource.destroy();
+        }
+        this.destroy_after_submit.length = 0;
+    }
+
+    async init_webgpu_context() {
+        // a resolution query matches one pixel ratio only, so when the page moves to a display
+        // with another one we watch for the next change with a new query
+        let watch_dpi_factor = () => {
+            let mqString = '(resolution: ' + window.devicePixelRatio + 'dppx)'
+            let mq = matchMedia(mqString);
+            mq.addEventListener("change", _ => {
+                this.handlers.on_screen_resize();
+                watch_dpi_factor();
+            }, {once: true});
+        }
+        let mq = matchMedia('(resolution: 1dppx)');
+        if (mq && mq.addEventListener) {
+            watch_dpi_factor();
+        }
+        else { // poll for it. yes. its terrible
+            window.setInterval(_ => {
+                if (window.devicePixelRatio != this.window_info.dpi_factor) {
+                    this.handlers.on_screen_resize();
+                }
+            }, 1000);
+        }
+
+        let canvas = this.canvas;
+        let adapter = navigator.gpu && await navigator.gpu.requestAdapter({powerPreference: "low-power"});
+        if (!adapter) {
+            var span = document.createElement('span')
+            span.style.color = 'white'
+            canvas.parentNode.replaceChild(span, canvas)
+            span.innerHTML = "Sorry, this page needs browser support for WebGPU to run<br/>Update to the latest Chrome, Edge, Firefox or Safari, or load the page without the webgpu option to use WebGL."
+            return false
+        }
+        let device = this.device = await adapter.requestDevice();
+        device.lost.then(info => console.log("WebGPU device lost: " + info.message));
+
+        this.context = canvas.getContext('webgpu');
+        this.canvas_format = navigator.gpu.getPreferredCanvasFormat();
+        this.context.configure({
+            device,
+            format: this.canvas_format,
+            alphaMode: canvas.getAttribute("noalpha")? "opaque": "premultiplied"
+        });
+
+        this.sampler = device.createSampler({
+            magFilter: "linear",
+            minFilter: "linear",
+            addressModeU: "clamp-to-edge",
+            addressModeV: "clamp-to-edge"
+        });
+        // bound to the texture slots a draw call leaves empty
+        let dummy_texture = device.createTexture({
+            size: [1, 1],
+            format: "rgba8unorm",
+            usage: GPUTextureUsage.TEXTURE_BINDING
+        });
+        this.dummy_texture_view = dummy_texture.createView();
+
+        this.table_align = device.limits.minStorageBufferOffsetAlignment;
+        this.table_data = new Float32Array(64 * 1024);
+        this.table_len = 0;
+        this.table_buffer = undefined;
+
+        let info = adapter.info || {};
+        this.gpu_info = {
+            min_uniform_vectors: device.limits.maxUniformBufferBindingSize >> 4,
+            vendor: info.vendor || "unknown",
+            renderer: info.architecture || info.device || "unknown",
+            webgpu: true
+        }
+        return true
+    }
+}
+
+function add_line_numbers_to_string(code) {
+    var lines = code.split('\n')
+    var out = ''
+    for (let i = 0; i < lines.length; i ++) {
+        out += (i + 1) + ': ' + lines[i] + '\n'
+    }
+    return out
+}
@@ -17,14 +17,11 @@ pub struct OsMidiOutput {
     sender: mpsc::Sender<(Option<MidiPortId>, MidiData)>
 }
 
-pub struct OsMidiInput(mpsc::Receiver<(MidiPortId, MidiData) >);
+pub struct OsMidiInput(mpsc::Receiver<MidiInputEvent>);
 
 impl OsMidiInput {
-    pub fn receive(&mut self) -> Option<(MidiPortId, MidiData)> {
-        if let Ok((port_id, data)) = self.0.try_recv() {
-            return Some((port_id, data))
-        }
-        None
+    pub fn receive(&mut self) -> Option<MidiInputEvent> {
+        self.0.try_recv().ok()
     }
 }
 impl OsMidiOutput {
@@ -37,7 +34,7 @@ impl OsMidiOutput {
 #[derive(Default)]
 pub struct WebMidiAccess {
     output_receivers: Vec<mpsc::Receiver<(Option<MidiPortId>, MidiData) >>,
-    input_senders: Vec<mpsc::Sender<(MidiPortId, MidiData) >>,
+    input_senders: Vec<mpsc::Sender<MidiInputEvent>>,
     change_signal: SignalToUI,
     ports: Vec<WebMidiPort>,
 }
@@ -66,7 +63,8 @@ impl WebMidiAccess {
     }
     
     pub fn create_midi_output(&mut self) -> MidiOutput {
-        let (send, _recv) = mpsc::channel();
+        let (send, recv) = mpsc::channel();
+        self.output_receivers.push(recv);
         MidiOutput(Some(OsMidiOutput {
             sender: send
         }))
@@ -96,8 +94,9 @@ impl WebMidiAccess {
     pub fn to_wasm_midi_input_data(&mut self, tw:ToWasmMidiInputData){
         if let Some(port) = self.ports.iter().find(|v| v.uid == tw.uid){
             let data = MidiData{data:[((tw.data>>16)&0xff) as u8,((tw.data>>8)&0xff) as u8,((tw.data>>0)&0xff) as u8]};
+            let event = MidiInputEvent {port_id: port.desc.port_id, data, time: tw.time};
             self.input_senders.retain(|send|{
-                send.send((port.desc.port_id, data)).is_ok()
+                send.send(event).is_ok()
             })
         }
     }
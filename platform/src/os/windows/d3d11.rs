@@ -188,6 +188,11 @@ impl Cx {
                 draw_call.draw_uniforms.set_zbias(*zbias);
                 *zbias += zbias_step;
                 
+                // the color space of the first texture, shaders convert its samples to sRGB with it
+                let texture_color_space = draw_call.texture_slots[0].as_ref()
+                    .map( | texture | self.textures[texture.texture_id()].color_space).unwrap_or_default();
+                draw_call.draw_uniforms.set_texture_color_space(texture_color_space);
+                
                 draw_item.os.draw_uniforms.update_with_f32_constant_data(d3d11_cx, draw_call.draw_uniforms.as_slice());
                 
                 if draw_call.uniforms_dirty {
@@ -313,6 +318,8 @@ impl Cx {
         self.passes[pass_id].paint_dirty = false;
 
         self.passes[pass_id].set_dpi_factor(dpi_factor);
+        let color_space = self.get_delegated_color_space(pass_id);
+        self.passes[pass_id].set_color_space_uniform(color_space);
         
         let viewport = D3D11_VIEWPORT {
             Width: (pass_rect.size.x * dpi_factor) as f32,
@@ -333,7 +340,7 @@ impl Cx {
         
         if let Some(render_target) = first_target {
             color_textures.push(Some(render_target.clone()));
-            let color = self.passes[pass_id].clear_color;
+            let color = self.get_delegated_clear_color(pass_id, self.passes[pass_id].clear_color);
             let color = [color.x, color.y, color.z, color.w];
             unsafe {d3d11_cx.context.ClearRenderTargetView(first_target.as_ref().unwrap(), &color)}
         }
@@ -349,11 +356,13 @@ impl Cx {
                 match color_texture.clear_color {
                     PassClearColor::InitWith(color) => {
                         if is_initial {
+                            let color = self.get_delegated_clear_color(pass_id, color);
                             let color = [color.x, color.y, color.z, color.w];
                             unsafe {d3d11_cx.context.ClearRenderTargetView(render_target.as_ref().unwrap(), &color)}
                         }
                     },
                     PassClearColor::ClearWith(color) => {
+                        let color = self.get_delegated_clear_color(pass_id, color);
                         let color = [color.x, color.y, color.z, color.w];
                         unsafe {d3d11_cx.context.ClearRenderTargetView(render_target.as_ref().unwrap(), &color)}
                     }
@@ -409,6 +418,7 @@ impl Cx {
     }
     
     pub fn draw_pass_to_window(&mut self, pass_id: PassId, vsync: bool, d3d11_window: &mut D3d11Window, d3d11_cx: &D3d11Cx) {
+        self.skip_read_back(pass_id);
         // let time1 = Cx::profile_time_ns();
         let draw_list_id = self.passes[pass_id].main_draw_list_id.unwrap();
         
@@ -433,6 +443,7 @@ impl Cx {
     }
     
     pub fn draw_pass_to_texture(&mut self, pass_id: PassId,  d3d11_cx: &D3d11Cx,texture_id: TextureId) {
+        self.skip_read_back(pass_id);
         // let time1 = Cx::profile_time_ns();
         let draw_list_id = self.passes[pass_id].main_draw_list_id.unwrap();
         
@@ -451,6 +462,7 @@ impl Cx {
     }
     
     pub fn draw_pass_to_magic_texture(&mut self, pass_id: PassId,  d3d11_cx: &D3d11Cx) {
+        self.skip_read_back(pass_id);
         // let time1 = Cx::profile_time_ns();
         let draw_list_id = self.passes[pass_id].main_draw_list_id.unwrap();
         
@@ -21,8 +21,8 @@ use crate::{
 
 // This is where all binary conversion code goes between makepad DragItem and Windows HGLOBAL/DROPFILES structure
 
-// convert incoming STGMEDIUM from internal or external source to DragItem
-pub fn convert_medium_to_dragitem(medium: STGMEDIUM) -> Option<DragItem> {
+// convert incoming STGMEDIUM from internal or external source to DragItems, one per file
+pub fn convert_medium_to_dragitems(medium: STGMEDIUM) -> Option<Vec<DragItem>> {
 
     // get size and raw pointer
     let hglobal_size = unsafe { GlobalSize(medium.u.hGlobal) };
@@ -110,12 +110,13 @@ pub fn convert_medium_to_dragitem(medium: STGMEDIUM) -> Option<DragItem> {
     let _ = unsafe { GlobalUnlock(medium.u.hGlobal) };
     unsafe { ReleaseStgMedium(&medium as *const STGMEDIUM as *mut STGMEDIUM) };
 
-    if filenames.len() != 1 {
-        log!("dropping multiple files is not supported");
+    if filenames.len() == 0 {
+        log!("drag object has no files");
         return None;
     }
 
-    Some(DragItem::FilePath { path: filenames[0].clone(),internal_id, })
+    // internal drags only ever carry one file
+    Some(filenames.into_iter().map(|path| DragItem::FilePath { path,internal_id, }).collect())
 }
 
 // create new internal DROPFILES structure from DragItem
@@ -143,10 +143,10 @@ impl IDropTarget_Vtbl {
 
 #[derive(Clone)]
 pub enum DropTargetMessage {
-    Enter(MODIFIERKEYS_FLAGS,POINTL,DROPEFFECT,DragItem),
+    Enter(MODIFIERKEYS_FLAGS,POINTL,DROPEFFECT,Vec<DragItem>),
     Leave,
-    Over(MODIFIERKEYS_FLAGS,POINTL,DROPEFFECT,DragItem),
-    Drop(MODIFIERKEYS_FLAGS,POINTL,DROPEFFECT,DragItem),
+    Over(MODIFIERKEYS_FLAGS,POINTL,DROPEFFECT,Vec<DragItem>),
+    Drop(MODIFIERKEYS_FLAGS,POINTL,DROPEFFECT,Vec<DragItem>),
 }
 
 // This uses WM_USER to send user messages back to the message queue of the window; careful when using WM_USER elsewhere
@@ -154,7 +154,7 @@ pub const WM_DROPTARGET: u32 = WM_USER + 0;
 
 #[derive(Clone)]
 pub struct DropTarget {
-    pub drag_item: RefCell<Option<DragItem>>,  // Windows only provides the data item for Enter and Drop, but makepad needs it for Over as well
+    pub drag_items: RefCell<Option<Vec<DragItem>>>,  // Windows only provides the data items for Enter and Drop, but makepad needs it for Over as well
     pub hwnd: HWND,  // which window to send the messages to
 }
 
@@ -169,7 +169,7 @@ implement_com!{
 }
 
 
-fn create_dragitem_from_idataobject(data_object: &IDataObject) -> Option<DragItem> {
+fn create_dragitems_from_idataobject(data_object: &IDataObject) -> Option<Vec<DragItem>> {
 
     // obtain enumerator for all DATADIR_GET formats of this object
     let enum_formats = unsafe { data_object.EnumFormatEtc(DATADIR_GET.0 as u32).unwrap() };
@@ -198,8 +198,8 @@ fn create_dragitem_from_idataobject(data_object: &IDataObject) -> Option<DragIte
         // get data medium of the object
         let medium = unsafe { data_object.GetData(format).unwrap() };
 
-        // convert to DragItem
-        convert_medium_to_dragitem(medium)
+        // convert to DragItems
+        convert_medium_to_dragitems(medium)
     }
     else {
         log!("CF_HDROP format not found on data object");
@@ -219,20 +219,20 @@ impl IDropTarget_Impl for DropTarget {
             return Ok(());
         }
 
-        // convert _p_data_obj to DragItem
-        let drag_item_opt = create_dragitem_from_idataobject(_p_data_obj.unwrap());
+        // convert _p_data_obj to DragItems
+        let drag_items_opt = create_dragitems_from_idataobject(_p_data_obj.unwrap());
 
         // ignore if conversion fails
-        if let None = drag_item_opt {
+        if let None = drag_items_opt {
             return Ok(());
         }
 
         // store locally for Over messages
-        self.drag_item.replace(drag_item_opt.clone());
+        self.drag_items.replace(drag_items_opt.clone());
 
         // allocate message
         let effect = unsafe { *_pdweffect };
-        let param = Box::new(DropTargetMessage::Enter(_grf_key_state,*_pt,effect,drag_item_opt.unwrap()));
+        let param = Box::new(DropTargetMessage::Enter(_grf_key_state,*_pt,effect,drag_items_opt.unwrap()));
 
         // send to window for further processing
         unsafe { SendMessageW(
@@ -250,8 +250,8 @@ impl IDropTarget_Impl for DropTarget {
         // allocate message
         let param = Box::new(DropTargetMessage::Leave);
 
-        // forget the locally stored data item
-        self.drag_item.replace(None);
+        // forget the locally stored data items
+        self.drag_items.replace(None);
 
         // send to window for further processing
         unsafe { SendMessageW(
@@ -266,14 +266,14 @@ impl IDropTarget_Impl for DropTarget {
 
     fn DragOver(&self,_grf_key_state: MODIFIERKEYS_FLAGS,_pt: &POINTL,_pdweffect: *mut DROPEFFECT) -> core::Result<()> {
 
-        // if for some reason there is no current drag item, exit
-        if let None = *self.drag_item.borrow() {
+        // if for some reason there are no current drag items, exit
+        if let None = *self.drag_items.borrow() {
             return Ok(());
         }
 
         // allocate message
         let effect = unsafe { *_pdweffect };
-        let param = Box::new(DropTargetMessage::Over(_grf_key_state,*_pt,effect,self.drag_item.borrow().clone().unwrap()));
+        let param = Box::new(DropTargetMessage::Over(_grf_key_state,*_pt,effect,self.drag_items.borrow().clone().unwrap()));
 
         // send to window for further processing
         unsafe { SendMessageW(
@@ -295,20 +295,20 @@ impl IDropTarget_Impl for DropTarget {
             return Ok(());
         }
 
-        // convert _p_data_obj to DragItem
-        let drag_item_opt = create_dragitem_from_idataobject(_p_data_obj.unwrap());
+        // convert _p_data_obj to DragItems
+        let drag_items_opt = create_dragitems_from_idataobject(_p_data_obj.unwrap());
 
         // ignore if conversion fails
-        if let None = drag_item_opt {
+        if let None = drag_items_opt {
             return Ok(());
         }
 
         // forget the locally stored one, after Drop we don't need it anymore
-        self.drag_item.replace(None);
+        self.drag_items.replace(None);
 
         // allocate message
         let effect = unsafe { *_pdweffect };
-        let param = Box::new(DropTargetMessage::Drop(_grf_key_state,*_pt,effect,drag_item_opt.unwrap().clone()));
+        let param = Box::new(DropTargetMessage::Drop(_grf_key_state,*_pt,effect,drag_items_opt.unwrap()));
 
         // send to window for further processing
         unsafe { SendMessageW(
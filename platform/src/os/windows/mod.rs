@@ -4,6 +4,9 @@ pub mod implement_com;
 pub mod win32_app;
 pub mod win32_window;
 pub mod win32_event;
+pub mod win32_clipboard;
+pub mod windows_gamepad;
+pub mod windows_dialogs;
 pub mod dropfiles;
 pub mod droptarget;
 pub mod dropsource;
@@ -17,6 +20,7 @@ pub mod media_foundation;
 pub mod d3d11;
 pub mod windows;
 pub mod windows_stdin;
+pub mod net_stream;
 mod web_socket;
 
 pub(crate) use crate::os::windows::d3d11::*; 
@@ -0,0 +1,13 @@
+use std::net::TcpStream;
+
+// there is no tls on windows yet, secure connections need schannel
+pub type NetStream = TcpStream;
+
+pub fn connect_net_stream(host: &str, port: &str, tls: bool, _verify: bool)->Result<NetStream, String>{
+    if tls{
+        return Err("Secure connections are not supported on Windows yet".into())
+    }
+    let tcp = TcpStream::connect(format!("{}:{}", host, port)).map_err( | e | format!("Error connecting to {}: {}", host, e)) ?;
+    let _ = tcp.set_nodelay(true);
+    Ok(tcp)
+}
@@ -530,7 +530,7 @@ type EnableNonClientDpiScaling = unsafe extern "system" fn (hwnd: HWND) -> BOOL;
 
 // Helper function to dynamically load function pointer.
 // `library` and `function` must be zero-terminated.
-fn get_function_impl(library: &str, function: &str) -> FARPROC {
+pub(crate) fn get_function_impl(library: &str, function: &str) -> FARPROC {
     // Library names we will use are ASCII so we can use the A version to avoid string conversion.
     
     let module = unsafe {LoadLibraryA(PCSTR::from_raw(library.as_ptr()))};
@@ -0,0 +1,195 @@
+use {
+    crate::{
+        event::ClipboardFlavor,
+        os::windows::win32_app::encode_wide,
+        windows::{
+            core::PCWSTR,
+            Win32::{
+                Foundation::{
+                    HANDLE,
+                    HGLOBAL,
+                },
+                System::{
+                    Memory::{
+                        GlobalLock,
+                        GlobalAlloc,
+                        GlobalSize,
+                        GlobalUnlock,
+                        GLOBAL_ALLOC_FLAGS,
+                    },
+                    Ole::CF_UNICODETEXT,
+                    WindowsProgramming::GMEM_DDESHARE,
+                    DataExchange::{
+                        OpenClipboard,
+                        EmptyClipboard,
+                        GetClipboardData,
+                        SetClipboardData,
+                        CloseClipboard,
+                        EnumClipboardFormats,
+                        RegisterClipboardFormatW,
+                        GetClipboardFormatNameW,
+                    },
+                },
+            },
+        },
+    },
+};
+
+// The names of the registered formats other apps put html, rtf and png images in. Flavors
+// without one are registered under their mime type.
+const HTML_FORMAT: &str = "HTML Format";
+const RTF_FORMAT: &str = "Rich Text Format";
+const PNG_FORMAT: &str = "PNG";
+
+/// Replaces the clipboard with the given flavors.
+pub unsafe fn write_clipboard(flavors: &[ClipboardFlavor]) {
+    if OpenClipboard(None).is_err() {
+        return
+    }
+    EmptyClipboard().unwrap();
+    for flavor in flavors {
+        let (format, data) = match flavor {
+            ClipboardFlavor::Text(text) => {
+                let data = encode_wide(text);
+                (CF_UNICODETEXT.0 as u32, data.iter().flat_map( | c | c.to_ne_bytes()).collect())
+            }
+            ClipboardFlavor::Html(html) => (register_format(HTML_FORMAT), encode_cf_html(html)),
+            ClipboardFlavor::Rtf(rtf) => (register_format(RTF_FORMAT), rtf.bytes().chain(Some(0)).collect()),
+            ClipboardFlavor::Image {mime, data} if mime == "image/png" => (register_format(PNG_FORMAT), data.clone()),
+            ClipboardFlavor::Image {mime, data} | ClipboardFlavor::Custom {mime, data} => (register_format(mime), data.clone()),
+        };
+        if format == 0 {
+            continue
+        }
+        let Ok(h_data) = GlobalAlloc(GLOBAL_ALLOC_FLAGS(GMEM_DDESHARE), data.len().max(1)) else {
+            continue
+        };
+        let ptr = GlobalLock(h_data) as *mut u8;
+        std::ptr::copy_nonoverlapping(data.as_ptr(), ptr, data.len());
+        let _ = GlobalUnlock(h_data);
+        let _ = SetClipboardData(format, std::mem::transmute::<_, HANDLE>(h_data));
+    }
+    CloseClipboard().unwrap();
+}
+
+/// Reads every format on the clipboard we know a flavor for.
+pub unsafe fn read_clipboard() -> Vec<ClipboardFlavor> {
+    let mut flavors: Vec<ClipboardFlavor> = Vec::new();
+    if OpenClipboard(None).is_err() {
+        return flavors
+    }
+    let mut format = EnumClipboardFormats(0);
+    while format != 0 {
+        let flavor = if format == CF_UNICODETEXT.0 as u32 {
+            read_text().map(ClipboardFlavor::Text)
+        }
+        else {
+            match format_name(format).as_deref() {
+                Some(HTML_FORMAT) => read_data(format).map( | data | ClipboardFlavor::Html(decode_cf_html(&data))),
+                Some(RTF_FORMAT) => read_data(format).map( | data | ClipboardFlavor::Rtf(String::from_utf8_lossy(trim_nul(&data)).into_owned())),
+                Some(PNG_FORMAT) => read_data(format).map(ClipboardFlavor::png),
+                // formats registered by makepad apps under a mime type
+                Some(name) if name.contains('/') => read_data(format).map( | data | ClipboardFlavor::from_mime(name, data)),
+                _ => None
+            }
+        };
+        if let Some(flavor) = flavor {
+            if !flavors.iter().any( | f | f.mime() == flavor.mime()) {
+                flavors.push(flavor);
+            }
+        }
+        format = EnumClipboardFormats(format);
+    }
+    CloseClipboard().unwrap();
+    flavors
+}
+
+/// Reads the plain text on the clipboard, for pasting.
+pub unsafe fn read_clipboard_text() -> Option<String> {
+    if OpenClipboard(None).is_err() {
+        return None
+    }
+    let text = read_text();
+    CloseClipboard().unwrap();
+    text
+}
+
+// The clipboard has to be open for these.
+
+unsafe fn read_text() -> Option<String> {
+    let data = read_data(CF_UNICODETEXT.0 as u32) ?;
+    let data: Vec<u16> = data.chunks_exact(2).map( | c | u16::from_ne_bytes([c[0], c[1]])).take_while( | c | *c != 0).collect();
+    if data.is_empty() {
+        return None
+    }
+    String::from_utf16(&data).ok()
+}
+
+// The size of clipboard memory can be rounded up, so formats that don't store their length
+// can come back with padding at the end.
+unsafe fn read_data(format: u32) -> Option<Vec<u8>> {
+    let h_data: HGLOBAL = std::mem::transmute(GetClipboardData(format).ok() ?);
+    let ptr = GlobalLock(h_data) as *const u8;
+    if ptr.is_null() {
+        return None
+    }
+    let data = std::slice::from_raw_parts(ptr, GlobalSize(h_data)).to_vec();
+    let _ = GlobalUnlock(h_data);
+    Some(data)
+}
+
+unsafe fn register_format(name: &str) -> u32 {
+    let name = encode_wide(name);
+    RegisterClipboardFormatW(PCWSTR(name.as_ptr()))
+}
+
+unsafe fn format_name(format: u32) -> Option<String> {
+    let mut name = [0u16; 256];
+    let len = GetClipboardFormatNameW(format, &mut name);
+    if len <= 0 {
+        return None
+    }
+    String::from_utf16(&name[..len as usize]).ok()
+}
+
+fn trim_nul(data: &[u8]) -> &[u8] {
+    let end = data.iter().position( | b | *b == 0).unwrap_or(data.len());
+    &data[..end]
+}
+
+// CF_HTML is utf8 html with a header that holds the byte offsets of the document and of the
+// copied fragment in it.
+fn encode_cf_html(html: &str) -> Vec<u8> {
+    let header = | start_html: usize, end_html: usize, start_fragment: usize, end_fragment: usize | format!(
+        "Version:0.9\r\nStartHTML:{:010}\r\nEndHTML:{:010}\r\nStartFragment:{:010}\r\nEndFragment:{:010}\r\n",
+        start_html, end_html, start_fragment, end_fragment
+    );
+    let prefix = "<html><body>\r\n<!--StartFragment-->";
+    let suffix = "<!--EndFragment-->\r\n</body></html>";
+    let start_html = header(0, 0, 0, 0).len();
+    let start_fragment = start_html + prefix.len();
+    let end_fragment = start_fragment + html.len();
+    let end_html = end_fragment + suffix.len();
+    let mut out = header(start_html, end_html, start_fragment, end_fragment);
+    out.push_str(prefix);
+    out.push_str(html);
+    out.push_str(suffix);
+    out.into_bytes().into_iter().chain(Some(0)).collect()
+}
+
+fn decode_cf_html(data: &[u8]) -> String {
+    let data = trim_nul(data);
+    let header = String::from_utf8_lossy(&data[..data.len().min(512)]).into_owned();
+    let offset = | key: &str | -> Option<usize> {
+        let start = header.find(key) ? + key.len();
+        header[start..].lines().next() ?.trim().parse().ok()
+    };
+    let range = match (offset("StartFragment:"), offset("EndFragment:")) {
+        (Some(start), Some(end)) if start <= end && end <= data.len() => start..end,
+        _ => match offset("StartHTML:") {
+            Some(start) if start <= data.len() => start..data.len(),
+            _ => 0..data.len()
+        }
+    };
+    String::from_utf8_lossy(&data[range]).into_owned()
+}
@@ -7,11 +7,13 @@ use {
             MouseMoveEvent,
             MouseLeaveEvent,
             ScrollEvent,
+            NavigateEvent,
             WindowGeomChangeEvent,
             WindowDragQueryEvent,
             WindowCloseRequestedEvent,
             WindowClosedEvent,
             TextInputEvent,
+            TextCompositionEvent,
             KeyEvent,
             DragEvent,
             DropEvent,
@@ -36,10 +38,12 @@ pub enum Win32Event {
     MouseMove(MouseMoveEvent),
     MouseLeave(MouseLeaveEvent),
     Scroll(ScrollEvent),
+    Navigate(NavigateEvent),
     
     WindowDragQuery(WindowDragQueryEvent),
     WindowCloseRequested(WindowCloseRequestedEvent),
     TextInput(TextInputEvent),
+    TextComposition(TextCompositionEvent),
     Drag(DragEvent),
     Drop(DropEvent),
     DragEnd,
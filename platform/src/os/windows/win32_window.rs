@@ -5,8 +5,6 @@ use {
             Cell,
         },
         rc::Rc,
-        ffi::OsStr,
-        os::windows::ffi::OsStrExt,
         mem,
     },
     
@@ -19,8 +17,6 @@ use {
             Win32::{
                 Foundation::{
                     HWND,
-                    HANDLE,
-                    HGLOBAL,
                     WPARAM,
                     LPARAM,
                     LRESULT,
@@ -29,15 +25,7 @@ use {
                     POINTL,
                 },
                 System::{
-                    Memory::{
-                        GlobalLock,
-                        GlobalAlloc,
-                        GlobalSize,
-                        GlobalUnlock,
-                        GLOBAL_ALLOC_FLAGS,
-                    },
                     Ole::{
-                        CF_UNICODETEXT,
                         //RegisterDragDrop,
                         //IDropTarget,
                         DROPEFFECT,
@@ -50,14 +38,6 @@ use {
                         MK_CONTROL,
                         MK_SHIFT,
                     },
-                    WindowsProgramming::GMEM_DDESHARE,
-                    DataExchange::{
-                        OpenClipboard,
-                        EmptyClipboard,
-                        GetClipboardData,
-                        SetClipboardData,
-                        CloseClipboard,
-                    },
                     LibraryLoader::GetModuleHandleW,
                 },
                 UI::{
@@ -97,6 +77,8 @@ use {
                         SW_MINIMIZE,
                         SWP_NOMOVE,
                         SWP_NOSIZE,
+                        SWP_NOZORDER,
+                        SWP_NOACTIVATE,
                         WM_ACTIVATE,
                         WM_NCCALCSIZE,
                         WM_NCHITTEST,
@@ -110,12 +92,19 @@ use {
                         WM_RBUTTONUP,
                         WM_MBUTTONDOWN,
                         WM_MBUTTONUP,
+                        WM_XBUTTONDOWN,
+                        WM_XBUTTONUP,
+                        XBUTTON1,
                         WM_KEYDOWN,
                         WM_SYSKEYDOWN,
                         WM_CLOSE,
                         WM_KEYUP,
                         WM_SYSKEYUP,
                         WM_CHAR,
+                        WM_IME_SETCONTEXT,
+                        WM_IME_STARTCOMPOSITION,
+                        WM_IME_COMPOSITION,
+                        WM_IME_ENDCOMPOSITION,
                         WM_ENTERSIZEMOVE,
                         WM_EXITSIZEMOVE,
                         WM_SIZE,
@@ -137,6 +126,25 @@ use {
                         MARGINS,
                         WM_MOUSELEAVE
                     },
+                    Input::Ime::{
+                        ImmGetContext,
+                        ImmReleaseContext,
+                        ImmGetCompositionStringW,
+                        ImmSetCompositionWindow,
+                        ImmSetCandidateWindow,
+                        IME_COMPOSITION_STRING,
+                        COMPOSITIONFORM,
+                        CANDIDATEFORM,
+                        CFS_POINT,
+                        CFS_CANDIDATEPOS,
+                        GCS_COMPSTR,
+                        GCS_COMPATTR,
+                        GCS_CURSORPOS,
+                        GCS_RESULTSTR,
+                        ATTR_TARGET_CONVERTED,
+                        ATTR_TARGET_NOTCONVERTED,
+                        ISC_SHOWUICOMPOSITIONWINDOW,
+                    },
                     Input::KeyboardAndMouse::{
                         VIRTUAL_KEY,
                         ReleaseCapture,
@@ -252,6 +260,7 @@ use {
                         VK_RIGHT,
                     },
                 },
+                Globalization::HIMC,
                 Graphics::{
                     Dwm::DwmExtendFrameIntoClientArea,
                     Gdi::ScreenToClient,
@@ -268,6 +277,7 @@ use {
                 get_win32_app_global,
             },
             win32_event::*,
+            win32_clipboard,
             droptarget::*,
         },
         window::WindowId,
@@ -295,6 +305,7 @@ pub struct Win32Window {
     pub mouse_buttons_down: usize,
     pub last_key_mod: KeyModifiers,
     pub ime_spot: DVec2,
+    pub is_composing: bool,
     pub current_cursor: MouseCursor,
     pub last_mouse_pos: DVec2,
     pub ignore_wmsize: usize,
@@ -346,7 +357,7 @@ impl Win32Window {
         ) };
 
         // create DropTarget object that accesses the same data object, convert to COM and give to Microsoft
-        let drop_target: IDropTarget = DropTarget { drag_item: RefCell::new(None),hwnd, }.into();
+        let drop_target: IDropTarget = DropTarget { drag_items: RefCell::new(None),hwnd, }.into();
         unsafe { RegisterDragDrop(hwnd, &drop_target).unwrap() };
 
         Win32Window {
@@ -355,6 +366,7 @@ impl Win32Window {
             last_window_geom: WindowGeom::default(),
             last_key_mod: KeyModifiers::default(),
             ime_spot: DVec2::default(),
+            is_composing: false,
             current_cursor: MouseCursor::Default,
             last_mouse_pos: DVec2::default(),
             ignore_wmsize: 0,
@@ -521,6 +533,13 @@ impl Win32Window {
             WM_RBUTTONUP => window.send_mouse_up(1, Self::get_key_modifiers()),
             WM_MBUTTONDOWN => window.send_mouse_down(2, Self::get_key_modifiers()),
             WM_MBUTTONUP => window.send_mouse_up(2, Self::get_key_modifiers()),
+            WM_XBUTTONDOWN => {
+                let direction = if (wparam.0 >> 16) as u16 == XBUTTON1 {NavigateDirection::Back} else {NavigateDirection::Forward};
+                window.send_navigate(direction);
+                // the x buttons want TRUE back instead of the usual 0
+                return LRESULT(1)
+            },
+            WM_XBUTTONUP => return LRESULT(1),
             WM_KEYDOWN | WM_SYSKEYDOWN => {
                 // detect control/cmd - c / v / x
                 let modifiers = Self::get_key_modifiers();
@@ -531,54 +550,34 @@ impl Win32Window {
                 if modifiers.control || modifiers.logo {
                     match key_code {
                         KeyCode::KeyV => { // paste
-                            if let Ok(()) = OpenClipboard(None) {
-                                let mut data: Vec<u16> = Vec::new();
-                                let h_clipboard_data = GetClipboardData(CF_UNICODETEXT.0 as u32).unwrap();
-                                let h_clipboard_ptr = GlobalLock(std::mem::transmute::<_,HGLOBAL>(h_clipboard_data)) as *mut u16;
-                                let clipboard_size = GlobalSize(std::mem::transmute::<_,HGLOBAL>(h_clipboard_data));
-                                if clipboard_size > 2 {
-                                    data.resize((clipboard_size >> 1) - 1, 0);
-                                    std::ptr::copy_nonoverlapping(h_clipboard_ptr, data.as_mut_ptr(), data.len());
-                                    GlobalUnlock(std::mem::transmute::<_,HGLOBAL>(h_clipboard_data)).unwrap();
-                                    CloseClipboard().unwrap();
-                                    if let Ok(utf8) = String::from_utf16(&data) {
-                                        window.do_callback(
-                                            Win32Event::TextInput(TextInputEvent {
-                                                input: utf8,
-                                                was_paste: true,
-                                                replace_last: false
-                                            })
-                                        );
-                                    }
-                                }
-                                else {
-                                    GlobalUnlock(std::mem::transmute::<_,HGLOBAL>(h_clipboard_data)).unwrap();
-                                    CloseClipboard().unwrap();
-                                }
+                            if let Some(text) = win32_clipboard::read_clipboard_text() {
+                                window.do_callback(
+                                    Win32Event::TextInput(TextInputEvent {
+                                        input: text,
+                                        was_paste: true,
+                                        replace_last: false
+                                    })
+                                );
                             }
                         }
                         KeyCode::KeyC => {
-                            let response = Rc::new(RefCell::new(None));
-                            window.do_callback(
-                                Win32Event::TextCut(TextClipboardEvent {
-                                    response: response.clone()
-                                })
-                            );
-                            let response = response.borrow();
-                            if let Some(response) = response.as_ref() {
-                                Self::copy_to_clipboard(response);
+                            let event = TextClipboardEvent {
+                                response: Rc::new(RefCell::new(None)),
+                                rich_response: Rc::new(RefCell::new(None))
+                            };
+                            window.do_callback(Win32Event::TextCopy(event.clone()));
+                            if let Some(flavors) = event.flavors() {
+                                win32_clipboard::write_clipboard(&flavors);
                             }
                         },
                         KeyCode::KeyX => {
-                            let response = Rc::new(RefCell::new(None));
-                            window.do_callback(
-                                Win32Event::TextCut(TextClipboardEvent {
-                                    response: response.clone()
-                                })
-                            );
-                            let response = response.borrow();
-                            if let Some(response) = response.as_ref() {
-                                Self::copy_to_clipboard(response);
+                            let event = TextClipboardEvent {
+                                response: Rc::new(RefCell::new(None)),
+                                rich_response: Rc::new(RefCell::new(None))
+                            };
+                            window.do_callback(Win32Event::TextCut(event.clone()));
+                            if let Some(flavors) = event.flavors() {
+                                win32_clipboard::write_clipboard(&flavors);
                             }
                         }
                         _ => ()
@@ -618,6 +617,45 @@ impl Win32Window {
                     }
                 }
             },
+            WM_IME_SETCONTEXT => {
+                // the composition is drawn by the app, at the cursor
+                let lparam = LPARAM(lparam.0 & !(ISC_SHOWUICOMPOSITIONWINDOW as isize));
+                return DefWindowProcW(hwnd, msg, wparam, lparam)
+            },
+            WM_IME_STARTCOMPOSITION => {
+                window.set_ime_windows_pos();
+                return LRESULT(0)
+            },
+            WM_IME_COMPOSITION => {
+                let himc = ImmGetContext(hwnd);
+                let flags = lparam.0 as u32;
+                if flags & GCS_RESULTSTR.0 != 0 {
+                    // handled here, so no WM_CHAR follows for the composed text
+                    let result = Self::get_composition_utf16(himc, GCS_RESULTSTR);
+                    window.send_composition(&[], &[], 0);
+                    window.send_text_input(String::from_utf16_lossy(&result), false);
+                }
+                if flags & GCS_COMPSTR.0 != 0 {
+                    let preedit = Self::get_composition_utf16(himc, GCS_COMPSTR);
+                    let attributes = if flags & GCS_COMPATTR.0 != 0 {
+                        Self::get_composition_bytes(himc, GCS_COMPATTR)
+                    } else {
+                        Vec::new()
+                    };
+                    let cursor = if flags & GCS_CURSORPOS.0 != 0 {
+                        ImmGetCompositionStringW(himc, GCS_CURSORPOS, None, 0).max(0) as usize
+                    } else {
+                        preedit.len()
+                    };
+                    window.send_composition(&preedit, &attributes, cursor);
+                }
+                ImmReleaseContext(hwnd, himc);
+                return LRESULT(0)
+            },
+            WM_IME_ENDCOMPOSITION => {
+                window.send_composition(&[], &[], 0);
+                return LRESULT(0)
+            },
             WM_ENTERSIZEMOVE => {
                 get_win32_app_global().start_resize();
                 window.do_callback(Win32Event::WindowResizeLoopStart(window.window_id));
@@ -626,7 +664,23 @@ impl Win32Window {
                 get_win32_app_global().stop_resize();
                 window.do_callback(Win32Event::WindowResizeLoopStop(window.window_id));
             },
-            WM_SIZE | WM_DPICHANGED => {
+            WM_SIZE => {
+                window.send_change_event();
+            },
+            WM_DPICHANGED => {
+                // the window moved to a monitor with another scale, take on the size windows
+                // suggests for it so it stays as big to the eye. this sends a WM_SIZE, but if the
+                // size in pixels stays the same that doesn't come so we send the change here too
+                let rect = &*(lparam.0 as *const RECT);
+                SetWindowPos(
+                    hwnd,
+                    HWND::default(),
+                    rect.left,
+                    rect.top,
+                    rect.right - rect.left,
+                    rect.bottom - rect.top,
+                    SWP_NOZORDER | SWP_NOACTIVATE
+                ).unwrap();
                 window.send_change_event();
             },
             WM_CLOSE => { // close requested
@@ -656,13 +710,12 @@ impl Win32Window {
                 match *message {
 
                     DropTargetMessage::Leave => {
-                        if get_win32_app_global().is_dragging_internal.get() {
-                            // TODO: cancel DoDragDrop somehow
-                            window.do_callback(Win32Event::DragEnd);
-                        }
+                        // TODO: cancel DoDragDrop somehow when dragging internally
+                        // external drags end here too, so drop targets can clear their hover
+                        window.do_callback(Win32Event::DragEnd);
                     },
-                    DropTargetMessage::Enter(flags,mut point,effect,drag_item) |
-                    DropTargetMessage::Over(flags,mut point,effect,drag_item) => {
+                    DropTargetMessage::Enter(flags,mut point,effect,drag_items) |
+                    DropTargetMessage::Over(flags,mut point,effect,drag_items) => {
                         
                         // decode message
                         unsafe { ScreenToClient(window.hwnd,&mut point as *mut POINTL as *mut POINT) };
@@ -677,6 +730,7 @@ impl Win32Window {
                         window.do_callback(
                             Win32Event::Drag(
                                 DragEvent {
+                                    window_id: window.window_id,
                                     modifiers: KeyModifiers {
                                         shift: (flags & MK_SHIFT) != MODIFIERKEYS_FLAGS(0),
                                         control: (flags & MK_CONTROL) != MODIFIERKEYS_FLAGS(0),
@@ -685,25 +739,26 @@ impl Win32Window {
                                     },
                                     handled: Cell::new(false),
                                     abs: DVec2 { x: point.x as f64 / dpi_factor,y: point.y as f64 / dpi_factor, },
-                                    items: Rc::new(vec![drag_item]),
+                                    items: Rc::new(drag_items),
                                     response: Rc::new(Cell::new(response)),
                                 }
                             )
                         );        
                     }, 
 
-                    DropTargetMessage::Drop(flags,mut point,_effect,drag_item) => {
+                    DropTargetMessage::Drop(flags,mut point,_effect,drag_items) => {
 
                         // decode message
                         unsafe { ScreenToClient(window.hwnd,&mut point as *mut POINTL as *mut POINT) };
 
-                        //log!("dropping at ({},{}), flags: {:04X}, response: {:?}, drag_item: {:?}",point.x,point.y,flags.0,response,drag_item);
+                        //log!("dropping at ({},{}), flags: {:04X}, response: {:?}, drag_item: {:?}",point.x,point.y,flags.0,response,drag_items);
                         let dpi_factor = window.get_dpi_factor();
 
                         // send to makepad
                         window.do_callback(
                             Win32Event::Drop(
                                 DropEvent {
+                                    window_id: window.window_id,
                                     modifiers: KeyModifiers {
                                         shift: (flags & MK_SHIFT) != MODIFIERKEYS_FLAGS(0),
                                         control: (flags & MK_CONTROL) != MODIFIERKEYS_FLAGS(0),
@@ -712,7 +767,7 @@ impl Win32Window {
                                     },
                                     handled: Cell::new(false),
                                     abs: DVec2 { x: point.x as f64 / dpi_factor,y: point.y as f64 / dpi_factor, },
-                                    items: Rc::new(vec![drag_item]),
+                                    items: Rc::new(drag_items),
                                 }
                             )
                         );
@@ -736,23 +791,7 @@ impl Win32Window {
     }
 
     pub unsafe fn copy_to_clipboard(text: &String) {
-        // plug it into the windows clipboard
-        // make utf16 dta
-        if let Ok(()) = OpenClipboard(None) {
-            EmptyClipboard().unwrap();
-
-            let data: Vec<u16> = OsStr::new(text).encode_wide().chain(Some(0).into_iter()).collect();
-
-            let h_clipboard_data = GlobalAlloc(GLOBAL_ALLOC_FLAGS(GMEM_DDESHARE), 2 * data.len()).expect("GlobalAlloc for clipboard failed");
-
-            let h_clipboard_ptr = GlobalLock(h_clipboard_data) as *mut u16;
-
-            std::ptr::copy_nonoverlapping(data.as_ptr(), h_clipboard_ptr, data.len());
-
-            GlobalUnlock(h_clipboard_data).unwrap();
-            SetClipboardData(CF_UNICODETEXT.0 as u32, std::mem::transmute::<_,HANDLE>(h_clipboard_data)).unwrap();
-            CloseClipboard().unwrap();
-        }
+        win32_clipboard::write_clipboard(&[ClipboardFlavor::Text(text.clone())]);
     }
     
     pub fn get_mouse_pos_from_lparam(&self, lparam: LPARAM) -> DVec2 {
@@ -881,6 +920,48 @@ impl Win32Window {
     
     pub fn set_ime_spot(&mut self, spot: DVec2) {
         self.ime_spot = spot;
+        self.set_ime_windows_pos();
+    }
+    
+    // the input method puts its composition and candidate windows at the spot
+    fn set_ime_windows_pos(&self) {
+        let dpi_factor = self.get_dpi_factor();
+        let pos = POINT {
+            x: (self.ime_spot.x * dpi_factor) as i32,
+            y: (self.ime_spot.y * dpi_factor) as i32
+        };
+        unsafe {
+            let himc = ImmGetContext(self.hwnd);
+            if himc.0 == 0 {
+                return
+            }
+            ImmSetCompositionWindow(himc, &COMPOSITIONFORM {
+                dwStyle: CFS_POINT,
+                ptCurrentPos: pos,
+                rcArea: RECT::default()
+            });
+            ImmSetCandidateWindow(himc, &CANDIDATEFORM {
+                dwIndex: 0,
+                dwStyle: CFS_CANDIDATEPOS,
+                ptCurrentPos: pos,
+                rcArea: RECT::default()
+            });
+            ImmReleaseContext(self.hwnd, himc);
+        }
+    }
+    
+    unsafe fn get_composition_bytes(himc: HIMC, kind: IME_COMPOSITION_STRING) -> Vec<u8> {
+        let len = ImmGetCompositionStringW(himc, kind, None, 0);
+        if len <= 0 {
+            return Vec::new()
+        }
+        let mut buffer = vec![0u8; len as usize];
+        ImmGetCompositionStringW(himc, kind, Some(buffer.as_mut_ptr() as *mut _), len as u32);
+        buffer
+    }
+    
+    unsafe fn get_composition_utf16(himc: HIMC, kind: IME_COMPOSITION_STRING) -> Vec<u16> {
+        Self::get_composition_bytes(himc, kind).chunks_exact(2).map( | c | u16::from_le_bytes([c[0], c[1]])).collect()
     }
     
     pub fn get_position(&self) -> DVec2 {
@@ -1049,6 +1130,15 @@ impl Win32Window {
         }));
     }
     
+    pub fn send_navigate(&mut self, direction: NavigateDirection) {
+        self.do_callback(Win32Event::Navigate(NavigateEvent {
+            window_id: self.window_id,
+            direction,
+            abs: self.last_mouse_pos,
+            time: self.time_now(),
+        }));
+    }
+    
     pub fn send_scroll(&mut self, scroll: DVec2, modifiers: KeyModifiers, is_mouse: bool) {
         self.do_callback(
             Win32Event::Scroll(ScrollEvent {
@@ -1084,6 +1174,54 @@ impl Win32Window {
         }))
     }
     
+    // the attributes hold one byte for each utf16 unit of the preedit text, and the cursor is a
+    // utf16 index too. an empty preedit ends the composition
+    pub fn send_composition(&mut self, preedit: &[u16], attributes: &[u8], cursor: usize) {
+        if preedit.is_empty() {
+            if self.is_composing {
+                self.is_composing = false;
+                self.do_callback(Win32Event::TextComposition(TextCompositionEvent::commit()));
+            }
+            return
+        }
+        let mut byte_indices = Vec::with_capacity(preedit.len() + 1);
+        let mut text = String::new();
+        for c in char::decode_utf16(preedit.iter().copied()) {
+            let c = c.unwrap_or(char::REPLACEMENT_CHARACTER);
+            for _ in 0..c.len_utf16() {
+                byte_indices.push(text.len());
+            }
+            text.push(c);
+        }
+        byte_indices.push(text.len());
+        let byte_index = | utf16_index: usize | byte_indices[utf16_index.min(preedit.len())];
+        let mut underlines = vec![TextCompositionUnderline {start: 0, end: text.len(), thick: false}];
+        // the clause being converted is the target
+        let is_target = | index: usize | attributes.get(index).map_or(false, | a | {
+            *a as u32 == ATTR_TARGET_CONVERTED || *a as u32 == ATTR_TARGET_NOTCONVERTED
+        });
+        let mut index = 0;
+        while index < preedit.len() {
+            if !is_target(index) {
+                index += 1;
+                continue;
+            }
+            let start = index;
+            while index < preedit.len() && is_target(index) {
+                index += 1;
+            }
+            underlines.push(TextCompositionUnderline {start: byte_index(start), end: byte_index(index), thick: true});
+        }
+        let phase = if self.is_composing {TextCompositionPhase::Update} else {TextCompositionPhase::Start};
+        self.is_composing = true;
+        self.do_callback(Win32Event::TextComposition(TextCompositionEvent {
+            phase,
+            cursor: byte_index(cursor),
+            preedit: text,
+            underlines,
+        }));
+    }
+    
     pub fn virtual_key_to_key_code(wparam: WPARAM) -> KeyCode {
         match VIRTUAL_KEY(wparam.0 as u16) {
             VK_ESCAPE => KeyCode::Escape,
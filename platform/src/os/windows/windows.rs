@@ -12,15 +12,20 @@ use {
         os::{
             windows::{
                 windows_media::CxWindowsMedia,
+                windows_gamepad::WindowsGamepads,
+                windows_dialogs::show_dialog,
                 win32_event::*,
                 d3d11::{D3d11Window, D3d11Cx},
                 win32_app::*,
                 win32_window::Win32Window,
+                win32_clipboard,
             },
             cx_native::EventFlow,
+            native_http::NativeHttpRequests,
         },
         makepad_math::*,
         pass::CxPassParent,
+        color_space::ColorSpace,
         cx_api::{CxOsApi, CxOsOp},
         window::CxWindowPool,
         windows::Win32::Graphics::Direct3D11::ID3D11Device,
@@ -83,6 +88,8 @@ impl Cx {
                 if time.timer_id == 0{
                     if SignalToUI::check_and_clear_ui_signal() {
                         self.handle_media_signals();
+                        self.handle_web_socket_events();
+                        self.handle_dialog_events();
                         self.call_event_handler(&Event::Signal);
                     }
                     if self.handle_live_edit() {
@@ -90,6 +97,7 @@ impl Cx {
                         self.redraw_all();
                     }
                     self.handle_networking_events();
+                    self.handle_windows_gamepads(self.seconds_since_app_start());
                     return EventFlow::Poll;
                 }
             }
@@ -130,17 +138,9 @@ impl Cx {
                                         
                     window.window_geom = re.new_geom.clone();
                     self.windows[re.window_id].window_geom = re.new_geom.clone();
-                    // redraw just this windows root draw list
-                    if re.old_geom.inner_size != re.new_geom.inner_size {
-                        
-                        if let Some(main_pass_id) = self.windows[re.window_id].main_pass_id {
-                            self.redraw_pass_and_child_passes(main_pass_id);
-                        }
-                    }
                 }
-                // ok lets not redraw all, just this window
                 self.redraw_all();
-                self.call_event_handler(&Event::WindowGeomChange(re));
+                self.call_window_geom_change_event(re);
             }
             Win32Event::WindowClosed(wc) => {
                 let window_id = wc.window_id;
@@ -195,6 +195,9 @@ impl Cx {
             Win32Event::Scroll(e) => {
                 self.call_event_handler(&Event::Scroll(e.into()))
             }
+            Win32Event::Navigate(e) => {
+                self.call_event_handler(&Event::Navigate(e))
+            }
             Win32Event::WindowDragQuery(e) => {
                 self.call_event_handler(&Event::WindowDragQuery(e))
             }
@@ -204,6 +207,9 @@ impl Cx {
             Win32Event::TextInput(e) => {
                 self.call_event_handler(&Event::TextInput(e))
             }
+            Win32Event::TextComposition(e) => {
+                self.call_event_handler(&Event::TextComposition(e))
+            }
             Win32Event::Drag(e) => {
                 self.call_event_handler(&Event::Drag(e));
                 self.drag_drop.cycle_drag();
@@ -223,6 +229,8 @@ impl Cx {
                 }));
                 self.fingers.mouse_up(0);
                 self.fingers.cycle_hover_area(live_id!(mouse).into());
+                self.call_event_handler(&Event::DragEnd);
+                self.drag_drop.cycle_drag();
             }
             Win32Event::KeyDown(e) => {
                 self.keyboard.process_key_down(e.clone());
@@ -244,6 +252,8 @@ impl Cx {
             Win32Event::Signal => {
                 if SignalToUI::check_and_clear_ui_signal() {
                     self.handle_media_signals();
+                    self.handle_web_socket_events();
+                    self.handle_dialog_events();
                     self.call_event_handler(&Event::Signal);
                 }
             }
@@ -284,6 +294,10 @@ impl Cx {
     }
     
     pub (crate) fn handle_networking_events(&mut self) {
+        let responses = self.os.http_requests.responses();
+        if responses.len() > 0 {
+            self.call_network_responses(responses);
+        }
     }
     
     fn handle_platform_ops(&mut self, d3d11_windows: &mut Vec<D3d11Window>, d3d11_cx: &D3d11Cx) -> EventFlow {
@@ -303,6 +317,8 @@ impl Cx {
                     window.window_geom = d3d11_window.window_geom.clone();
                     d3d11_windows.push(d3d11_window);
                     window.is_created = true;
+                    // windows opened while the app runs have to draw their first frame
+                    self.redraw_window(window_id);
                 },
                 CxOsOp::CloseWindow(window_id) => {
                     if let Some(index) = d3d11_windows.iter().position( | w | w.window_id == window_id) {
@@ -347,6 +363,10 @@ impl Cx {
                         window.win32_window.set_topmost(is_topmost);
                     }
                 }
+                CxOsOp::SetWindowColorSpace(window_id, _color_space) => {
+                    // the swapchain is always presented as sRGB, so that's what the window draws in
+                    self.windows[window_id].color_space = ColorSpace::Srgb;
+                }
                 CxOsOp::ShowClipboardActions(_) => {
                 },
                 CxOsOp::CopyToClipboard(content) => {
@@ -354,14 +374,30 @@ impl Cx {
                         Win32Window::copy_to_clipboard(&content);
                     }
                 },
+                CxOsOp::WriteClipboard(flavors) => {
+                    unsafe {
+                        win32_clipboard::write_clipboard(&flavors);
+                    }
+                },
+                CxOsOp::ReadClipboard(request_id) => {
+                    let flavors = unsafe {win32_clipboard::read_clipboard()};
+                    self.call_event_handler(&Event::ClipboardRead(ClipboardReadEvent {request_id, flavors}));
+                },
+                CxOsOp::GamepadRumble {gamepad_id, strong, weak, duration} => {
+                    let time = self.seconds_since_app_start();
+                    self.os.gamepads.rumble(gamepad_id, strong, weak, duration, time);
+                },
                 CxOsOp::XrStartPresenting => {
                     //todo!()
                 },
                 CxOsOp::XrStopPresenting => {
                     //todo!()
                 },
-                CxOsOp::ShowTextIME(_area, _pos) => {
-                    //todo!()
+                CxOsOp::ShowTextIME(area, pos) => {
+                    let pos = area.clipped_rect(self).pos + pos;
+                    d3d11_windows.iter_mut().for_each( | w | {
+                        w.win32_window.set_ime_spot(pos);
+                    });
                 }
                 CxOsOp::HideTextIME => {
                     //todo!()
@@ -380,8 +416,11 @@ impl Cx {
                 },
                 CxOsOp::UpdateMacosMenu(_menu) => {
                 },
-                CxOsOp::HttpRequest {request_id: _, request: _} => {
-                    todo!("HttpRequest not implemented yet on windows, we'll get there");
+                CxOsOp::HttpRequest {request_id, request} => {
+                    self.os.http_requests.request(request_id, request);
+                },
+                CxOsOp::CancelHttpRequest {request_id} => {
+                    self.os.http_requests.cancel(request_id);
                 },
                 CxOsOp::PrepareVideoPlayback(_, _, _, _, _) => todo!(),
                 CxOsOp::BeginVideoPlayback(_) => todo!(),
@@ -391,10 +430,11 @@ impl Cx {
                 CxOsOp::UnmuteVideoPlayback(_) => todo!(),
                 CxOsOp::CleanupVideoPlaybackResources(_) => todo!(),
                 CxOsOp::UpdateVideoSurfaceTexture(_) => todo!(),
-                CxOsOp::SaveFileDialog(_) =>  todo!(),
-                CxOsOp::SelectFileDialog(_) =>  todo!(),
-                CxOsOp::SaveFolderDialog(_) =>  todo!(),
-                CxOsOp::SelectFolderDialog(_) =>  todo!(),
+                CxOsOp::ShowDialog(dialog_id, dialog) => {
+                    if let Err(dialog) = show_dialog(dialog_id, dialog, self.dialog_sender()) {
+                        self.show_dialog_fallback(dialog_id, dialog);
+                    }
+                },
             }
         }
         ret
@@ -425,6 +465,8 @@ impl CxOsApi for Cx {
 pub struct CxOs {
     pub (crate) start_time: Option<Instant>,
     pub (crate) media: CxWindowsMedia,
+    pub (crate) gamepads: WindowsGamepads,
     pub (crate) d3d11_device: Option<ID3D11Device>,
+    pub (crate) http_requests: NativeHttpRequests,
    //pub (crate) new_frame_being_rendered: Option<crate::cx_stdin::PresentableDraw>,
 }
@@ -0,0 +1,207 @@
+#![allow(non_snake_case)]
+use {
+    std::{
+        mem,
+        ptr,
+        ffi::c_void,
+        path::PathBuf,
+        sync::mpsc::Sender,
+    },
+    crate::{
+        makepad_live_id::LiveId,
+        thread::SignalToUI,
+        event::DialogEvent,
+        file_dialogs::{Dialog, DialogResult, FileDialog, MessageDialogLevel, MessageDialogButtons},
+        os::windows::win32_app::{get_function_impl, encode_wide},
+        windows::Win32::System::Com::{CoInitializeEx, COINIT_APARTMENTTHREADED},
+    }
+};
+
+#[repr(C)]
+struct OPENFILENAMEW {
+    lStructSize: u32,
+    hwndOwner: isize,
+    hInstance: isize,
+    lpstrFilter: *const u16,
+    lpstrCustomFilter: *mut u16,
+    nMaxCustFilter: u32,
+    nFilterIndex: u32,
+    lpstrFile: *mut u16,
+    nMaxFile: u32,
+    lpstrFileTitle: *mut u16,
+    nMaxFileTitle: u32,
+    lpstrInitialDir: *const u16,
+    lpstrTitle: *const u16,
+    Flags: u32,
+    nFileOffset: u16,
+    nFileExtension: u16,
+    lpstrDefExt: *const u16,
+    lCustData: isize,
+    lpfnHook: *const c_void,
+    lpTemplateName: *const u16,
+    pvReserved: *mut c_void,
+    dwReserved: u32,
+    FlagsEx: u32,
+}
+
+#[repr(C)]
+struct BROWSEINFOW {
+    hwndOwner: isize,
+    pidlRoot: *const c_void,
+    pszDisplayName: *mut u16,
+    lpszTitle: *const u16,
+    ulFlags: u32,
+    lpfn: *const c_void,
+    lParam: isize,
+    iImage: i32,
+}
+
+type GetFileNameW = unsafe extern "system" fn (ofn: *mut OPENFILENAMEW) -> i32;
+type SHBrowseForFolderW = unsafe extern "system" fn (bi: *const BROWSEINFOW) -> *mut c_void;
+type SHGetPathFromIDListW = unsafe extern "system" fn (pidl: *const c_void, path: *mut u16) -> i32;
+type CoTaskMemFree = unsafe extern "system" fn (pv: *const c_void);
+type MessageBoxW = unsafe extern "system" fn (hwnd: isize, text: *const u16, caption: *const u16, utype: u32) -> i32;
+
+const OFN_OVERWRITEPROMPT: u32 = 0x2;
+const OFN_NOCHANGEDIR: u32 = 0x8;
+const OFN_ALLOWMULTISELECT: u32 = 0x200;
+const OFN_PATHMUSTEXIST: u32 = 0x800;
+const OFN_FILEMUSTEXIST: u32 = 0x1000;
+const OFN_EXPLORER: u32 = 0x80000;
+
+const BIF_RETURNONLYFSDIRS: u32 = 0x1;
+const BIF_NEWDIALOGSTYLE: u32 = 0x40;
+
+const MB_OKCANCEL: u32 = 0x1;
+const MB_YESNO: u32 = 0x4;
+const MB_ICONERROR: u32 = 0x10;
+const MB_ICONWARNING: u32 = 0x30;
+const MB_ICONINFORMATION: u32 = 0x40;
+const IDOK: i32 = 1;
+const IDYES: i32 = 6;
+
+const MAX_PATH: usize = 260;
+
+fn from_wide(data: &[u16]) -> String {
+    let end = data.iter().position( | c | *c == 0).unwrap_or(data.len());
+    String::from_utf16_lossy(&data[0..end])
+}
+
+unsafe fn file_name_dialog(get_file_name: GetFileNameW, dialog: &Dialog, d: &FileDialog) -> DialogResult {
+    // the filter is a list of description and pattern pairs, all zero terminated
+    let mut filter = Vec::new();
+    for f in &d.filters {
+        let patterns: Vec<String> = f.extensions.iter().map( | ext | format!("*.{}", ext)).collect();
+        filter.extend(encode_wide(&f.description));
+        filter.extend(encode_wide(patterns.join(";")));
+    }
+    filter.push(0);
+
+    let mut file = vec![0u16; 32768];
+    if let Some(filename) = &d.filename {
+        let name = encode_wide(filename);
+        if name.len() < file.len() {
+            file[0..name.len()].copy_from_slice(&name);
+        }
+    }
+    let initial_dir = d.location.as_ref().map( | location | encode_wide(location.as_os_str()));
+    let title = encode_wide(dialog.title());
+
+    let mut flags = OFN_EXPLORER | OFN_NOCHANGEDIR | OFN_PATHMUSTEXIST;
+    if let Dialog::SaveFile(_) = dialog {
+        flags |= OFN_OVERWRITEPROMPT;
+    }
+    else {
+        flags |= OFN_FILEMUSTEXIST;
+        if d.multiple {
+            flags |= OFN_ALLOWMULTISELECT;
+        }
+    }
+    let mut ofn: OPENFILENAMEW = mem::zeroed();
+    ofn.lStructSize = mem::size_of::<OPENFILENAMEW>() as u32;
+    ofn.lpstrFilter = if d.filters.len() > 0 {filter.as_ptr()} else {ptr::null()};
+    ofn.lpstrFile = file.as_mut_ptr();
+    ofn.nMaxFile = file.len() as u32;
+    ofn.lpstrInitialDir = initial_dir.as_ref().map_or(ptr::null(), | dir | dir.as_ptr());
+    ofn.lpstrTitle = title.as_ptr();
+    ofn.Flags = flags;
+
+    if get_file_name(&mut ofn) == 0 {
+        return DialogResult::Cancelled
+    }
+    // with more than one file picked we get the folder followed by the file names
+    let mut parts = file.split( | c | *c == 0).take_while( | part | part.len() > 0).map(String::from_utf16_lossy);
+    let first = PathBuf::from(parts.next().unwrap_or_default());
+    let rest: Vec<PathBuf> = parts.map( | name | first.join(name)).collect();
+    if rest.len() > 0 {DialogResult::Paths(rest)} else {DialogResult::Paths(vec![first])}
+}
+
+unsafe fn folder_dialog(browse: SHBrowseForFolderW, get_path: SHGetPathFromIDListW, free: CoTaskMemFree, dialog: &Dialog) -> DialogResult {
+    let _ = CoInitializeEx(None, COINIT_APARTMENTTHREADED);
+    let title = encode_wide(dialog.title());
+    let mut display_name = vec![0u16; MAX_PATH];
+    let mut bi: BROWSEINFOW = mem::zeroed();
+    bi.pszDisplayName = display_name.as_mut_ptr();
+    bi.lpszTitle = title.as_ptr();
+    bi.ulFlags = BIF_RETURNONLYFSDIRS | BIF_NEWDIALOGSTYLE;
+    let pidl = browse(&bi);
+    if pidl.is_null() {
+        return DialogResult::Cancelled
+    }
+    let mut path = vec![0u16; MAX_PATH];
+    let ok = get_path(pidl, path.as_mut_ptr());
+    free(pidl);
+    if ok == 0 {
+        return DialogResult::Cancelled
+    }
+    DialogResult::Paths(vec![PathBuf::from(from_wide(&path))])
+}
+
+/// Shows the common dialogs from comdlg32 and shell32 on a thread of their own, so their modal
+/// loop doesn't run inside ours. If a dll can't be loaded the dialog is handed back.
+pub fn show_dialog(dialog_id: LiveId, dialog: Dialog, sender: Sender<DialogEvent>) -> Result<(), Dialog> {
+    let get_open = get_function_impl("comdlg32.dll\0", "GetOpenFileNameW\0").map( | f | unsafe {mem::transmute::<_, GetFileNameW>(f)});
+    let get_save = get_function_impl("comdlg32.dll\0", "GetSaveFileNameW\0").map( | f | unsafe {mem::transmute::<_, GetFileNameW>(f)});
+    let browse = get_function_impl("shell32.dll\0", "SHBrowseForFolderW\0").map( | f | unsafe {mem::transmute::<_, SHBrowseForFolderW>(f)});
+    let get_path = get_function_impl("shell32.dll\0", "SHGetPathFromIDListW\0").map( | f | unsafe {mem::transmute::<_, SHGetPathFromIDListW>(f)});
+    let free = get_function_impl("ole32.dll\0", "CoTaskMemFree\0").map( | f | unsafe {mem::transmute::<_, CoTaskMemFree>(f)});
+    let message_box = get_function_impl("user32.dll\0", "MessageBoxW\0").map( | f | unsafe {mem::transmute::<_, MessageBoxW>(f)});
+
+    let available = match &dialog {
+        Dialog::OpenFile(_) => get_open.is_some(),
+        Dialog::SaveFile(_) => get_save.is_some(),
+        Dialog::SelectFolder(_) => browse.is_some() && get_path.is_some() && free.is_some(),
+        Dialog::Message(_) => message_box.is_some(),
+    };
+    if !available {
+        return Err(dialog)
+    }
+    std::thread::spawn(move || {
+        let result = unsafe {match &dialog {
+            Dialog::OpenFile(d) => file_name_dialog(get_open.unwrap(), &dialog, d),
+            Dialog::SaveFile(d) => file_name_dialog(get_save.unwrap(), &dialog, d),
+            Dialog::SelectFolder(_) => folder_dialog(browse.unwrap(), get_path.unwrap(), free.unwrap(), &dialog),
+            Dialog::Message(d) => {
+                let buttons = match d.buttons {
+                    MessageDialogButtons::Ok => 0,
+                    MessageDialogButtons::OkCancel => MB_OKCANCEL,
+                    MessageDialogButtons::YesNo => MB_YESNO,
+                };
+                let icon = match d.level {
+                    MessageDialogLevel::Info => MB_ICONINFORMATION,
+                    MessageDialogLevel::Warning => MB_ICONWARNING,
+                    MessageDialogLevel::Error => MB_ICONERROR,
+                };
+                let text = encode_wide(&d.message);
+                let caption = encode_wide(dialog.title());
+                match message_box.unwrap()(0, text.as_ptr(), caption.as_ptr(), buttons | icon) {
+                    IDOK | IDYES => DialogResult::Accepted,
+                    _ => DialogResult::Cancelled
+                }
+            }
+        }};
+        let _ = sender.send(DialogEvent::Closed {dialog_id, result});
+        SignalToUI::set_ui_signal();
+    });
+    Ok(())
+}
@@ -0,0 +1,187 @@
+use {
+    std::mem,
+    crate::{
+        event::{GamepadId, GamepadButton, GamepadAxis, GamepadState},
+        cx::Cx,
+        os::windows::win32_app::get_function_impl,
+    }
+};
+
+#[allow(non_snake_case)]
+#[repr(C)]
+#[derive(Default, Clone, Copy)]
+struct XINPUT_GAMEPAD {
+    wButtons: u16,
+    bLeftTrigger: u8,
+    bRightTrigger: u8,
+    sThumbLX: i16,
+    sThumbLY: i16,
+    sThumbRX: i16,
+    sThumbRY: i16,
+}
+
+#[allow(non_snake_case)]
+#[repr(C)]
+#[derive(Default, Clone, Copy)]
+struct XINPUT_STATE {
+    dwPacketNumber: u32,
+    Gamepad: XINPUT_GAMEPAD,
+}
+
+#[allow(non_snake_case)]
+#[repr(C)]
+#[derive(Default, Clone, Copy)]
+struct XINPUT_VIBRATION {
+    wLeftMotorSpeed: u16,
+    wRightMotorSpeed: u16,
+}
+
+type XInputGetState = unsafe extern "system" fn (user_index: u32, state: *mut XINPUT_STATE) -> u32;
+type XInputSetState = unsafe extern "system" fn (user_index: u32, vibration: *mut XINPUT_VIBRATION) -> u32;
+
+const XUSER_MAX_COUNT: usize = 4;
+const ERROR_SUCCESS: u32 = 0;
+
+const XINPUT_BUTTONS: [(u16, GamepadButton); 14] = [
+    (0x0001, GamepadButton::DPadUp),
+    (0x0002, GamepadButton::DPadDown),
+    (0x0004, GamepadButton::DPadLeft),
+    (0x0008, GamepadButton::DPadRight),
+    (0x0010, GamepadButton::Start),
+    (0x0020, GamepadButton::Select),
+    (0x0040, GamepadButton::LeftStick),
+    (0x0080, GamepadButton::RightStick),
+    (0x0100, GamepadButton::LeftBumper),
+    (0x0200, GamepadButton::RightBumper),
+    (0x1000, GamepadButton::South),
+    (0x2000, GamepadButton::East),
+    (0x4000, GamepadButton::West),
+    (0x8000, GamepadButton::North),
+];
+
+const XINPUT_LEFT_THUMB_DEADZONE: i16 = 7849;
+const XINPUT_RIGHT_THUMB_DEADZONE: i16 = 8689;
+
+fn thumb(value: i16, deadzone: i16) -> f32 {
+    if value.unsigned_abs() <= deadzone as u16 {0.0} else {(value as f32 / 32767.0).max(-1.0)}
+}
+
+fn xinput_to_state(gamepad: &XINPUT_GAMEPAD) -> GamepadState {
+    let mut state = GamepadState::default();
+    for (mask, button) in XINPUT_BUTTONS {
+        if gamepad.wButtons & mask != 0 {
+            state.buttons[button.index()] = 1.0;
+        }
+    }
+    state.buttons[GamepadButton::LeftTrigger.index()] = gamepad.bLeftTrigger as f32 / 255.0;
+    state.buttons[GamepadButton::RightTrigger.index()] = gamepad.bRightTrigger as f32 / 255.0;
+    // XInput has up as positive, the standard mapping has it negative
+    state.axes[GamepadAxis::LeftX.index()] = thumb(gamepad.sThumbLX, XINPUT_LEFT_THUMB_DEADZONE);
+    state.axes[GamepadAxis::LeftY.index()] = -thumb(gamepad.sThumbLY, XINPUT_LEFT_THUMB_DEADZONE);
+    state.axes[GamepadAxis::RightX.index()] = thumb(gamepad.sThumbRX, XINPUT_RIGHT_THUMB_DEADZONE);
+    state.axes[GamepadAxis::RightY.index()] = -thumb(gamepad.sThumbRY, XINPUT_RIGHT_THUMB_DEADZONE);
+    state
+}
+
+#[derive(Default, Clone, Copy)]
+struct XInputSlot {
+    gamepad_id: Option<GamepadId>,
+    packet_number: u32,
+    rumble_until: Option<f64>,
+}
+
+/// Controllers through XInput, which covers Xbox pads and everything that poses as one.
+/// XInput has no notifications, so the four slots are polled, empty ones only once a second
+/// because asking an empty slot is slow.
+#[derive(Default)]
+pub struct WindowsGamepads {
+    loaded: bool,
+    get_state: Option<XInputGetState>,
+    set_state: Option<XInputSetState>,
+    slots: [XInputSlot; XUSER_MAX_COUNT],
+    last_scan: Option<f64>,
+    last_id: u64,
+}
+
+impl WindowsGamepads {
+    fn load(&mut self) {
+        if self.loaded {
+            return
+        }
+        self.loaded = true;
+        for library in ["xinput1_4.dll\0", "xinput1_3.dll\0", "xinput9_1_0.dll\0"] {
+            self.get_state = get_function_impl(library, "XInputGetState\0").map( | f | unsafe {mem::transmute::<_, XInputGetState>(f)});
+            self.set_state = get_function_impl(library, "XInputSetState\0").map( | f | unsafe {mem::transmute::<_, XInputSetState>(f)});
+            if self.get_state.is_some() {
+                break
+            }
+        }
+    }
+
+    fn set_vibration(&self, user_index: usize, strong: f32, weak: f32) {
+        if let Some(set_state) = self.set_state {
+            let mut vibration = XINPUT_VIBRATION {
+                wLeftMotorSpeed: (strong * u16::MAX as f32) as u16,
+                wRightMotorSpeed: (weak * u16::MAX as f32) as u16,
+            };
+            unsafe {set_state(user_index as u32, &mut vibration);}
+        }
+    }
+
+    pub fn rumble(&mut self, gamepad_id: GamepadId, strong: f32, weak: f32, duration: f64, time: f64) {
+        if let Some(user_index) = self.slots.iter().position( | s | s.gamepad_id == Some(gamepad_id)) {
+            self.set_vibration(user_index, strong, weak);
+            // XInput rumbles until told otherwise, the poll stops it
+            self.slots[user_index].rumble_until = Some(time + duration);
+        }
+    }
+}
+
+impl Cx {
+    pub(crate) fn handle_windows_gamepads(&mut self, time: f64) {
+        self.os.gamepads.load();
+        let get_state = if let Some(get_state) = self.os.gamepads.get_state {get_state} else {return};
+        let scan = self.os.gamepads.last_scan.map_or(true, | last | time - last > 1.0);
+        if scan {
+            self.os.gamepads.last_scan = Some(time);
+        }
+        for user_index in 0..XUSER_MAX_COUNT {
+            let slot = self.os.gamepads.slots[user_index];
+            if slot.gamepad_id.is_none() && !scan {
+                continue
+            }
+            let mut xstate = XINPUT_STATE::default();
+            let connected = unsafe {get_state(user_index as u32, &mut xstate)} == ERROR_SUCCESS;
+            match (slot.gamepad_id, connected) {
+                (None, true) => {
+                    self.os.gamepads.last_id += 1;
+                    let gamepad_id = GamepadId(self.os.gamepads.last_id);
+                    self.os.gamepads.slots[user_index] = XInputSlot {
+                        gamepad_id: Some(gamepad_id),
+                        packet_number: xstate.dwPacketNumber,
+                        rumble_until: None,
+                    };
+                    self.gamepad_connected(gamepad_id, &format!("XInput Controller {}", user_index + 1));
+                    self.gamepad_update(gamepad_id, xinput_to_state(&xstate.Gamepad), time);
+                }
+                (Some(gamepad_id), true) => {
+                    if let Some(until) = slot.rumble_until {
+                        if time >= until {
+                            self.os.gamepads.set_vibration(user_index, 0.0, 0.0);
+                            self.os.gamepads.slots[user_index].rumble_until = None;
+                        }
+                    }
+                    if slot.packet_number != xstate.dwPacketNumber {
+                        self.os.gamepads.slots[user_index].packet_number = xstate.dwPacketNumber;
+                        self.gamepad_update(gamepad_id, xinput_to_state(&xstate.Gamepad), time);
+                    }
+                }
+                (Some(gamepad_id), false) => {
+                    self.os.gamepads.slots[user_index] = XInputSlot::default();
+                    self.gamepad_disconnected(gamepad_id);
+                }
+                (None, false) => ()
+            }
+        }
+    }
+}
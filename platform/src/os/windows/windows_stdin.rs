@@ -10,12 +10,13 @@ use {
         makepad_micro_serde::*,
         event::Event,
         window::CxWindowPool,
-        event::WindowGeom,
+        event::{WindowGeom, WindowGeomChangeEvent},
         CxOsApi,
         texture::{Texture,  TextureFormat},
         thread::SignalToUI,
         os::{
             d3d11::D3d11Cx,
+            windows_dialogs::show_dialog,
             cx_stdin::{HostToStdin, PresentableDraw, StdinToHost, Swapchain},
         },
         pass::{CxPassParent},
@@ -138,6 +139,9 @@ impl Cx {
                 HostToStdin::TextInput(e) => {
                     self.call_event_handler(&Event::TextInput(e));
                 }
+                HostToStdin::TextComposition(e) => {
+                    self.call_event_handler(&Event::TextComposition(e));
+                }
                 HostToStdin::MouseDown(e) => {
                     self.fingers.process_tap_count(
                         dvec2(e.x, e.y),
@@ -175,13 +179,21 @@ impl Cx {
                     self.call_event_handler(&Event::Scroll(e.into_event(window_id, pos)));
                 }
                 HostToStdin::WindowGeomChange { dpi_factor, left, top, width, height, window_id } => {
-                    self.windows[CxWindowPool::from_usize(window_id)].window_geom = WindowGeom {
+                    let window_id = CxWindowPool::from_usize(window_id);
+                    let old_geom = self.windows[window_id].window_geom.clone();
+                    let new_geom = WindowGeom {
                         dpi_factor,
                         position: dvec2(left, top),
                         inner_size: dvec2(width, height),
                         ..Default::default()
                     };
+                    self.windows[window_id].window_geom = new_geom.clone();
                     self.redraw_all();
+                    self.call_window_geom_change_event(WindowGeomChangeEvent {
+                        window_id,
+                        old_geom,
+                        new_geom
+                    });
                 }
                 HostToStdin::Swapchain(new_swapchain) => {
                     let new_swapchain = new_swapchain.images_map(|pi| {
@@ -213,6 +225,8 @@ impl Cx {
                     // check signals
                     if SignalToUI::check_and_clear_ui_signal() {
                         self.handle_media_signals();
+                        self.handle_web_socket_events();
+                        self.handle_dialog_events();
                         self.call_event_handler(&Event::Signal);
                     }
                     if self.handle_live_edit() {
@@ -301,6 +315,17 @@ impl Cx {
                 CxOsOp::SetCursor(cursor) => {
                     let _ = io::stdout().write_all(StdinToHost::SetCursor(cursor).to_json().as_bytes());
                 },
+                CxOsOp::HttpRequest {request_id, request} => {
+                    self.os.http_requests.request(request_id, request);
+                },
+                CxOsOp::CancelHttpRequest {request_id} => {
+                    self.os.http_requests.cancel(request_id);
+                },
+                CxOsOp::ShowDialog(dialog_id, dialog) => {
+                    if let Err(dialog) = show_dialog(dialog_id, dialog, self.dialog_sender()) {
+                        self.show_dialog_fallback(dialog_id, dialog);
+                    }
+                },
                 _ => ()
                 /*
                 CxOsOp::CloseWindow(_window_id) => {},
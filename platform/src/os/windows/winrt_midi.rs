@@ -28,7 +28,7 @@ use {
 
 type WindowsResult<T> = crate::windows::core::Result<T>;
 
-pub struct OsMidiInput(mpsc::Receiver<(MidiPortId, MidiData) >);
+pub struct OsMidiInput(mpsc::Receiver<MidiInputEvent>);
 
 #[derive(Clone)]
 pub struct OsMidiOutput(pub (crate) Arc<Mutex<WinRTMidiAccess >>);
@@ -40,15 +40,12 @@ impl OsMidiOutput {
 }
 
 impl OsMidiInput {
-    pub fn receive(&mut self) -> Option<(MidiPortId, MidiData)> {
-        if let Ok((port_id, data)) = self.0.try_recv() {
-            return Some((port_id, data))
-        }
-        None
+    pub fn receive(&mut self) -> Option<MidiInputEvent> {
+        self.0.try_recv().ok()
     }
 }
 
-type InputSenders = Arc<Mutex<Vec<mpsc::Sender<(MidiPortId, MidiData) >> >>;
+type InputSenders = Arc<Mutex<Vec<mpsc::Sender<MidiInputEvent> >> >;
 
 #[derive(Clone)]
 pub struct WinRTMidiPort {
@@ -243,11 +240,10 @@ impl WinRTMidiAccess {
                                         let data_reader = DataReader::FromBuffer(&raw_data).unwrap();
                                         let mut data = [0u8;3];
                                         if data_reader.ReadBytes(&mut data).is_ok(){
+                                            let event = MidiInputEvent {port_id, data: MidiData {data}, time: midi_time()};
                                             let mut senders = input_senders.lock().unwrap();
                                             senders.retain( | s | {
-                                                s.send((port_id, MidiData {
-                                                    data,
-                                                })).is_ok()
+                                                s.send(event).is_ok()
                                             });
                                             if senders.len()>0 {
                                                 // make sure our eventloop runs
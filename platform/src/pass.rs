@@ -11,6 +11,7 @@ use crate::{
     makepad_math::*,
     id_pool::*,
     area::Area,
+    color_space::ColorSpace,
     window::WindowId,
     os::CxOsPass,
     cx::Cx,
@@ -113,6 +114,7 @@ impl LiveApply for Pass {
             match nodes[index].id {
                 live_id!(clear_color) => cx.passes[self.pass_id()].clear_color = LiveNew::new_apply_mut_index(cx, apply, &mut index, nodes),
                 live_id!(dont_clear) => cx.passes[self.pass_id()].dont_clear = LiveNew::new_apply_mut_index(cx, apply, &mut index, nodes),
+                live_id!(color_space) => cx.passes[self.pass_id()].color_space = Some(LiveNew::new_apply_mut_index(cx, apply, &mut index, nodes)),
                 _ => {
                     cx.apply_error_no_matching_field(live_error_origin!(), index, nodes);
                     index = nodes.skip_node(index);
@@ -167,6 +169,28 @@ impl Pass {
         None
     }
         
+    /// Tags the output of this pass with a color space. Without a tag the pass draws in
+    /// the color space of its color texture, or of its parent pass or window if it has none.
+    pub fn set_color_space(&self, cx: &mut Cx, color_space: ColorSpace) {
+        let cxpass = &mut cx.passes[self.pass_id()];
+        cxpass.color_space = Some(color_space);
+        cxpass.paint_dirty = true;
+    }
+    
+    pub fn color_space(&self, cx: &mut Cx) -> ColorSpace {
+        cx.get_delegated_color_space(self.pass_id())
+    }
+    
+    /// Reads back the pixels of the first color texture under `rect`, in logical pass
+    /// coordinates, the next time the pass is drawn. They arrive as `Event::PassReadBack`.
+    /// Only one request is kept per pass, a newer one replaces the pending one. Backends that
+    /// can't read back yet send the event with no pixels.
+    pub fn request_read_back(&self, cx: &mut Cx, rect: Rect) {
+        let cxpass = &mut cx.passes[self.pass_id()];
+        cxpass.read_back_rect = Some(rect);
+        cxpass.paint_dirty = true;
+    }
+    
     pub fn set_window_clear_color(&self, cx: &mut Cx, clear_color: Vec4) {
         let cxpass = &mut cx.passes[self.pass_id()];
         cxpass.clear_color = clear_color;
@@ -237,7 +261,7 @@ pub struct PassUniforms {
     dpi_factor: f32,
     dpi_dilate: f32,
     time: f32,
-    pad2: f32
+    color_space: f32
 }
 
 impl PassUniforms {
@@ -271,6 +295,7 @@ pub struct CxPass {
     pub depth_init: f64,
     pub clear_color: Vec4,
     pub dpi_factor: Option<f64>,
+    pub color_space: Option<ColorSpace>,
     pub main_draw_list_id: Option<DrawListId>,
     pub parent: CxPassParent,
     pub paint_dirty: bool,
@@ -279,6 +304,7 @@ pub struct CxPass {
     pub view_scale: DVec2,
     pub pass_uniforms: PassUniforms,
     pub zbias_step: f32,
+    pub read_back_rect: Option<Rect>,
     pub os: CxOsPass,
 }
 
@@ -290,10 +316,12 @@ impl Default for CxPass {
             debug_name: String::new(),
             matrix_mode: PassMatrixMode::Ortho,
             zbias_step: 0.001,
+            read_back_rect: None,
             pass_uniforms: PassUniforms::default(),
             color_textures: Vec::new(),
             depth_texture: None,
             dpi_factor: None,
+            color_space: None,
             clear_depth: PassClearDepth::ClearWith(1.0),
             clear_color: Vec4::default(),
             depth_init: 1.0,
@@ -326,6 +354,10 @@ impl CxPass {
         self.pass_uniforms.dpi_dilate = dpi_dilate as f32;
     }
     
+    pub fn set_color_space_uniform(&mut self, color_space: ColorSpace) {
+        self.pass_uniforms.color_space = color_space.to_uniform();
+    }
+    
     pub fn set_matrix(&mut self, offset: DVec2, size: DVec2) {
         
         let offset = offset + self.view_shift;
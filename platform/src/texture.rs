@@ -2,6 +2,7 @@ use {
     crate::{
         id_pool::*,
         cx::Cx,
+        color_space::ColorSpace,
         os::CxOsTexture,
     },
     std::rc::Rc,
@@ -461,6 +462,17 @@ impl Texture {
         &mut cx.textures[self.texture_id()].format
     }
     
+    /// Tags the pixel data of this texture. A pass drawing into it draws in this color space, and
+    /// draw calls with it in their first texture slot get it in the `draw_texture_color_space`
+    /// uniform, `Pal::from_texture_color_space` converts their samples back to sRGB with it.
+    pub fn set_color_space(&self, cx: &mut Cx, color_space: ColorSpace) {
+        cx.textures[self.texture_id()].color_space = color_space;
+    }
+    
+    pub fn color_space(&self, cx: &Cx) -> ColorSpace {
+        cx.textures[self.texture_id()].color_space
+    }
+    
     pub fn swap_vec_u32(&self, cx: &mut Cx, image: &mut Vec<u32>) {
         let cxtexture = &mut cx.textures[self.texture_id()];
         match &mut cxtexture.format{
@@ -509,5 +521,6 @@ impl Texture {
 pub struct CxTexture {
     pub (crate) format: TextureFormat,
     pub (crate) alloc: Option<TextureAlloc>,
+    pub color_space: ColorSpace,
     pub os: CxOsTexture,
 }
@@ -1,6 +1,11 @@
 use {
+    std::sync::mpsc,
     crate::{
         makepad_live_id::{LiveId, FromLiveId},
+        cx::Cx,
+        texture::{Texture, TextureFormat},
+        thread::ToUIReceiver,
+        media_api::CxMediaApi,
     }
 };
 
@@ -140,7 +145,114 @@ pub struct VideoBufferRef<'a>{
 }
 
 
+fn yuv_to_bgra(y: u8, u: u8, v: u8)->u32{
+    fn clip(a: i32) -> u32 {
+        a.max(0).min(255) as u32
+    }
+    let c = y as i32 - 16;
+    let d = u as i32 - 128;
+    let e = v as i32 - 128;
+    (clip((298 * c + 409 * e + 128) >> 8) << 16)
+    | (clip((298 * c - 100 * d - 208 * e + 128) >> 8) << 8)
+    | (clip((298 * c + 516 * d + 128) >> 8) << 0)
+    | (255 << 24)
+}
+
 impl<'a> VideoBufferRef<'a>{
+    pub fn as_bytes(&self)->&[u8]{
+        match self.data{
+            VideoBufferRefData::U8(data)=>data,
+            VideoBufferRefData::U32(data)=>unsafe{
+                std::slice::from_raw_parts(data.as_ptr() as *const u8, data.len() * 4)
+            }
+        }
+    }
+    
+    /// Converts the frame to pixels for a `TextureFormat::VecBGRAu8_32` texture, which
+    /// draws with the regular image shaders. Returns false for the formats that need a
+    /// decoder (MJPEG) or a frame that is too short for its format.
+    pub fn to_bgra(&self, bgra:&mut Vec<u32>)->bool{
+        let width = self.format.width;
+        let height = self.format.height;
+        let bytes = self.as_bytes();
+        if width == 0 || height == 0{
+            return false
+        }
+        // packed formats can have padding at the end of each row
+        let stride = bytes.len() / height;
+        bgra.resize(width * height, 0);
+        match self.format.pixel_format{
+            VideoPixelFormat::YUY2=>{
+                if stride < width * 2{
+                    return false
+                }
+                for y in 0..height{
+                    let row = &bytes[y * stride..];
+                    for x in (0..width & !1).step_by(2){
+                        let (y0, u, y1, v) = (row[x * 2], row[x * 2 + 1], row[x * 2 + 2], row[x * 2 + 3]);
+                        bgra[y * width + x] = yuv_to_bgra(y0, u, v);
+                        bgra[y * width + x + 1] = yuv_to_bgra(y1, u, v);
+                    }
+                }
+            }
+            VideoPixelFormat::NV12=>{
+                if bytes.len() < width * height * 3 / 2{
+                    return false
+                }
+                let (luma, chroma) = bytes.split_at(width * height);
+                for y in 0..height{
+                    for x in 0..width{
+                        let c = (y / 2) * (width & !1) + (x & !1);
+                        bgra[y * width + x] = yuv_to_bgra(luma[y * width + x], chroma[c], chroma[c + 1]);
+                    }
+                }
+            }
+            VideoPixelFormat::YUV420=>{
+                let chroma_width = (width + 1) / 2;
+                let chroma_size = chroma_width * ((height + 1) / 2);
+                if bytes.len() < width * height + 2 * chroma_size{
+                    return false
+                }
+                let (luma, chroma) = bytes.split_at(width * height);
+                let (u_plane, v_plane) = chroma.split_at(chroma_size);
+                for y in 0..height{
+                    for x in 0..width{
+                        let c = (y / 2) * chroma_width + x / 2;
+                        bgra[y * width + x] = yuv_to_bgra(luma[y * width + x], u_plane[c], v_plane[c]);
+                    }
+                }
+            }
+            VideoPixelFormat::RGB24=>{
+                if stride < width * 3{
+                    return false
+                }
+                for y in 0..height{
+                    let row = &bytes[y * stride..];
+                    for x in 0..width{
+                        let (b, g, r) = (row[x * 3], row[x * 3 + 1], row[x * 3 + 2]);
+                        bgra[y * width + x] = (255 << 24) | ((r as u32) << 16) | ((g as u32) << 8) | b as u32;
+                    }
+                }
+            }
+            VideoPixelFormat::GRAY=>{
+                if stride < width{
+                    return false
+                }
+                for y in 0..height{
+                    let row = &bytes[y * stride..];
+                    for x in 0..width{
+                        let l = row[x] as u32;
+                        bgra[y * width + x] = (255 << 24) | (l << 16) | (l << 8) | l;
+                    }
+                }
+            }
+            VideoPixelFormat::MJPEG | VideoPixelFormat::Unsupported(_)=>{
+                return false
+            }
+        }
+        true
+    }
+    
     pub fn to_buffer(&self)->VideoBuffer{
         VideoBuffer{
             format: self.format.clone(),
@@ -206,6 +318,76 @@ impl VideoBuffer{
     }
 }
 
+struct VideoInputFrame{
+    width: usize,
+    height: usize,
+    bgra: Vec<u32>,
+}
+
+/// Shows a video input in a texture. The frames are converted to BGRA on the capture
+/// thread, so the texture draws with the regular image shaders. Pick the device and
+/// format with `use_video_input` as usual.
+pub struct VideoInputTexture{
+    texture: Texture,
+    size: Option<(usize, usize)>,
+    frames: ToUIReceiver<VideoInputFrame>,
+    recycle: mpsc::Sender<Vec<u32>>,
+}
+
+impl VideoInputTexture{
+    /// Takes over the callback of video input `index`.
+    pub fn new(cx:&mut Cx, index:usize)->Self{
+        let frames = ToUIReceiver::<VideoInputFrame>::default();
+        let sender = frames.sender();
+        let (recycle, recycled) = mpsc::channel::<Vec<u32>>();
+        cx.video_input(index, move | frame | {
+            let mut bgra = recycled.try_recv().unwrap_or_default();
+            if frame.to_bgra(&mut bgra){
+                let _ = sender.send(VideoInputFrame{
+                    width: frame.format.width,
+                    height: frame.format.height,
+                    bgra
+                });
+            }
+        });
+        Self{
+            texture: Texture::new(cx),
+            size: None,
+            frames,
+            recycle
+        }
+    }
+    
+    pub fn texture(&self)->&Texture{
+        &self.texture
+    }
+    
+    /// The size of the frames, once the first one came in.
+    pub fn size(&self)->Option<(usize, usize)>{
+        self.size
+    }
+    
+    /// Uploads the newest frame, frames that came in since are skipped. Call it on
+    /// `Event::Signal`, it returns whether the texture changed and needs a redraw.
+    pub fn handle_signal(&mut self, cx:&mut Cx)->bool{
+        let mut frame = if let Ok(frame) = self.frames.try_recv(){frame} else {return false};
+        while let Ok(newer) = self.frames.try_recv(){
+            let _ = self.recycle.send(std::mem::replace(&mut frame, newer).bgra);
+        }
+        // the texture starts out as the shared null texture, it never gets written to
+        if self.size != Some((frame.width, frame.height)){
+            self.size = Some((frame.width, frame.height));
+            self.texture = Texture::new_with_format(cx, TextureFormat::VecBGRAu8_32{
+                width: frame.width,
+                height: frame.height,
+                data: vec![],
+            });
+        }
+        self.texture.swap_vec_u32(cx, &mut frame.bgra);
+        let _ = self.recycle.send(frame.bgra);
+        true
+    }
+}
 
 #[derive(Clone, Copy, Debug)]
 pub struct VideoFormat {
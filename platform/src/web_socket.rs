@@ -4,7 +4,8 @@ use crate::{
     cx_api::*,
     Cx,
     studio::{AppToStudio,AppToStudioVec},
-    event::{HttpMethod,HttpRequest},
+    event::{Event,HttpMethod,HttpRequest,WebSocketEvent},
+    makepad_live_id::LiveId,
     makepad_micro_serde::*
 };
 #[allow(unused_imports)]
@@ -42,6 +43,7 @@ pub struct WebSocket{
     pub rx_receiver: Receiver<WebSocketMessage>,
 }
 
+#[derive(Clone, Debug)]
 pub enum WebSocketMessage{
     Error(String),
     Binary(Vec<u8>),
@@ -85,7 +87,7 @@ impl Cx{
                     }
                     WebSocketThreadMsg::SendMessage{socket_id, message}=>{
                         if let Some(socket) = sockets.lock().unwrap().borrow_mut().get_mut(&socket_id){
-                            socket.send_message(message).unwrap();
+                            let _ = socket.send_message(message);
                         }
                     }
                     WebSocketThreadMsg::AppToStudio{message}=>{
@@ -99,7 +101,7 @@ impl Cx{
             }
             if app_to_studio.0.len()>0{
                 if let Some(socket) = sockets.lock().unwrap().borrow_mut().get_mut(&0){
-                    socket.send_message(WebSocketMessage::Binary(app_to_studio.serialize_bin())).unwrap()
+                    let _ = socket.send_message(WebSocketMessage::Binary(app_to_studio.serialize_bin()));
                 }
             }
         });
@@ -129,7 +131,7 @@ impl Cx{
                         }
                         WebSocketThreadMsg::SendMessage{socket_id, message}=>{
                             if let Some(socket) = sockets.get_mut(&socket_id){
-                                socket.send_message(message).unwrap();
+                                let _ = socket.send_message(message);
                             }
                         }
                         WebSocketThreadMsg::AppToStudio{message}=>{
@@ -153,7 +155,7 @@ impl Cx{
                     if Instant::now().duration_since(first_time) >= collect_time{
                         // lets send it
                         if let Some(socket) = sockets.get_mut(&0){
-                            socket.send_message(WebSocketMessage::Binary(app_to_studio.serialize_bin())).unwrap();
+                            let _ = socket.send_message(WebSocketMessage::Binary(app_to_studio.serialize_bin()));
                         }
                         app_to_studio.0.clear();
                         first_message = None;
@@ -192,6 +194,39 @@ impl Cx{
         self.start_studio_websocket(studio_http);
     }
     
+    /// Opens a websocket that reports back through `Event::WebSocket` with this `socket_id`.
+    /// Opening another socket with the same id closes the previous one.
+    pub fn web_socket_open(&mut self, socket_id: LiveId, request: HttpRequest){
+        self.web_sockets.insert(socket_id, WebSocket::open(request));
+    }
+    
+    pub fn web_socket_send_binary(&mut self, socket_id: LiveId, data: Vec<u8>)->Result<(),()>{
+        self.web_sockets.get_mut(&socket_id).ok_or(()) ?.send_binary(data)
+    }
+    
+    pub fn web_socket_send_string(&mut self, socket_id: LiveId, data: String)->Result<(),()>{
+        self.web_sockets.get_mut(&socket_id).ok_or(()) ?.send_string(data)
+    }
+    
+    pub fn web_socket_close(&mut self, socket_id: LiveId){
+        self.web_sockets.remove(&socket_id);
+    }
+    
+    pub(crate) fn handle_web_socket_events(&mut self){
+        let mut events = Vec::new();
+        for (socket_id, socket) in &mut self.web_sockets{
+            while let Ok(message) = socket.try_recv(){
+                events.push(WebSocketEvent{socket_id: *socket_id, message});
+            }
+        }
+        for e in events{
+            if let WebSocketMessage::Closed = e.message{
+                self.web_sockets.remove(&e.socket_id);
+            }
+            self.call_event_handler(&Event::WebSocket(e));
+        }
+    }
+    
     pub fn send_studio_message(msg:AppToStudio){
         if !Cx::has_studio_web_socket(){
             return
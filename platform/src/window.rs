@@ -8,6 +8,7 @@ use {
             WindowGeom
         },
         pass::{Pass, PassId, CxPassParent},
+        color_space::ColorSpace,
         cx::Cx,
         cx_api::CxOsOp,
         live_traits::*,
@@ -16,7 +17,7 @@ use {
 
 pub struct WindowHandle(PoolId);
 
-#[derive(Clone, Debug, PartialEq, Copy)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Copy)]
 pub struct WindowId(usize, u64);
 
 impl WindowId{
@@ -61,6 +62,24 @@ impl CxWindowPool {
         return (WindowId(0, self.0.pool[0].generation), self.0.pool[0].item.window_geom.position)
     }
     
+    /// True if `window_id` is `top_id` or a window owned by it, following owners up.
+    pub fn is_window_or_owned_by(&self, window_id: WindowId, top_id: WindowId)->bool{
+        let mut walk = window_id;
+        for _ in 0..25{
+            if walk == top_id{
+                return true
+            }
+            if !self.is_valid(walk){
+                return false
+            }
+            match self.0.pool[walk.0].item.owner{
+                Some(owner)=>walk = owner,
+                None=>return false
+            }
+        }
+        false
+    }
+    
     pub fn is_valid(&self, v: WindowId)->bool{
         if v.0 < self.0.pool.len(){
             if self.0.pool[v.0].generation == v.1{
@@ -109,6 +128,7 @@ impl LiveNew for WindowHandle {
         cxwindow.create_title = "Makepad".to_string();
         cxwindow.create_inner_size = None;
         cxwindow.create_position = None;
+        cxwindow.owner = None;
         cx.platform_ops.push(CxOsOp::CreateWindow(window.window_id()));
         window
     }
@@ -160,6 +180,10 @@ impl LiveApply for WindowHandle {
                     //log!("DPI OVERRIDE {}", v);
                     cx.windows[self.window_id()].dpi_override = Some(v);
                 }
+                live_id!(color_space) => {
+                    let v:ColorSpace = LiveNew::new_apply_mut_index(cx, apply, &mut index, nodes);
+                    self.set_color_space(cx, v);
+                }
                 live_id!(topmost) => {
                     let v:bool = LiveNew::new_apply_mut_index(cx, apply, &mut index, nodes);
                     self.set_topmost(cx, v);
@@ -176,6 +200,35 @@ impl LiveApply for WindowHandle {
 
 
 impl WindowHandle {
+    /// Opens another native window from a running app. Give it a pass with `set_pass` and draw
+    /// into that, the window shows up once the platform has created it.
+    pub fn open(cx: &mut Cx, title: &str, inner_size: DVec2, position: Option<DVec2>) -> Self {
+        let window = <Self as LiveNew>::new(cx);
+        let cxwindow = &mut cx.windows[window.window_id()];
+        cxwindow.create_title = title.to_string();
+        cxwindow.create_inner_size = Some(inner_size);
+        cxwindow.create_position = position;
+        window
+    }
+    
+    /// Makes the window belong to another one. Events in an owned window are routed to the
+    /// widgets of its owner, and closing it doesn't close the app.
+    pub fn set_owner(&self, cx: &mut Cx, owner: Option<WindowId>) {
+        cx.windows[self.window_id()].owner = owner;
+    }
+    
+    pub fn owner(&self, cx: &Cx) -> Option<WindowId> {
+        cx.windows[self.window_id()].owner
+    }
+    
+    pub fn is_created(&self, cx: &Cx) -> bool {
+        cx.windows[self.window_id()].is_created
+    }
+    
+    pub fn redraw(&self, cx: &mut Cx) {
+        cx.redraw_window(self.window_id());
+    }
+    
     pub fn set_pass(&self, cx: &mut Cx, pass: &Pass) {
         cx.windows[self.window_id()].main_pass_id = Some(pass.pass_id());
         cx.passes[pass.pass_id()].parent = CxPassParent::Window(self.window_id());
@@ -229,6 +282,20 @@ impl WindowHandle {
         cx.push_unique_platform_op(CxOsOp::SetTopmost(self.window_id(), set_topmost));
     }
     
+    /// Requests a color space for the window surface. Displays that can't show wide gamut
+    /// colors (or platforms that can't tag their surface) fall back to sRGB.
+    pub fn set_color_space(&mut self, cx: &mut Cx, color_space: ColorSpace) {
+        let window = &mut cx.windows[self.window_id()];
+        if window.color_space != color_space {
+            window.color_space = color_space;
+            cx.push_unique_platform_op(CxOsOp::SetWindowColorSpace(self.window_id(), color_space));
+        }
+    }
+    
+    pub fn color_space(&self, cx: &Cx) -> ColorSpace {
+        cx.windows[self.window_id()].color_space
+    }
+    
     pub fn restore(&mut self, cx: &mut Cx) {
         cx.push_unique_platform_op(CxOsOp::RestoreWindow(self.window_id()));
     }
@@ -245,9 +312,12 @@ pub struct CxWindow {
     pub create_inner_size: Option<DVec2>,
     pub kind_id: usize,
     pub dpi_override: Option<f64>,
+    pub color_space: ColorSpace,
     pub is_created: bool,
     pub window_geom: WindowGeom,
     pub main_pass_id: Option<PassId>,
+    /// The window this one was opened for, if any.
+    pub owner: Option<WindowId>,
 }
 
 impl CxWindow {
@@ -0,0 +1,172 @@
+use crate::makepad_micro_serde::{SerBin, DeBin, DeBinErr};
+
+/// What a file contains, decided when it is opened so binaries don't end up in the text editor.
+#[derive(Clone, Debug, PartialEq, SerBin, DeBin)]
+pub enum FileContentKind {
+    /// Text, with the language if it could be detected.
+    Text {language: Option<String>},
+    /// Text too big to load, which is read memory-mapped and can't be edited.
+    LargeText {size: u64},
+    Image,
+    Binary,
+}
+
+impl FileContentKind {
+    pub fn is_text(&self) -> bool {
+        matches!(self, Self::Text {..})
+    }
+
+    pub fn is_large_text(&self) -> bool {
+        matches!(self, Self::LargeText {..})
+    }
+}
+
+/// Files bigger than this are never opened as editable text, whatever their content. Text is
+/// opened memory-mapped and read only instead, with its lines indexed as they are asked for.
+pub const MAX_TEXT_FILE_SIZE: usize = 32 * 1024 * 1024;
+
+/// How much of a binary file is shown in the hex view.
+pub const MAX_HEX_VIEW_SIZE: usize = 64 * 1024;
+
+// Only this much of the start of a file is sniffed.
+const SNIFF_SIZE: usize = 8000;
+
+pub fn detect_content_kind(path: &str, bytes: &[u8]) -> FileContentKind {
+    if is_image(bytes) {
+        return FileContentKind::Image
+    }
+    if bytes.len() > MAX_TEXT_FILE_SIZE || is_binary(bytes) {
+        return FileContentKind::Binary
+    }
+    FileContentKind::Text {
+        language: detect_language_from_first_line(bytes).or_else( | | detect_language_from_path(path))
+    }
+}
+
+/// The kind of a file of `size` bytes that is too big to read whole, from its first bytes.
+pub fn detect_large_content_kind(head: &[u8], size: u64) -> FileContentKind {
+    if is_image(head) {
+        return FileContentKind::Image
+    }
+    if is_binary(head) {
+        return FileContentKind::Binary
+    }
+    FileContentKind::LargeText {size}
+}
+
+fn is_image(bytes: &[u8]) -> bool {
+    bytes.starts_with(b"\x89PNG\r\n\x1a\n")
+        || bytes.starts_with(b"\xff\xd8\xff")
+        || bytes.starts_with(b"GIF87a")
+        || bytes.starts_with(b"GIF89a")
+        || bytes.starts_with(b"BM")
+        || bytes.len() > 12 && &bytes[0..4] == b"RIFF" && &bytes[8..12] == b"WEBP"
+}
+
+fn is_binary(bytes: &[u8]) -> bool {
+    let sniff = &bytes[..bytes.len().min(SNIFF_SIZE)];
+    if sniff.contains(&0) {
+        return true
+    }
+    // Text can have the odd control character, but not many.
+    let control_count = sniff.iter().filter( | byte | {
+        **byte < 0x20 && !matches!(**byte, b'\n' | b'\r' | b'\t' | 0x0c | 0x1b)
+    }).count();
+    control_count * 10 > sniff.len()
+}
+
+fn detect_language_from_first_line(bytes: &[u8]) -> Option<String> {
+    let end = bytes.iter().take(SNIFF_SIZE).position( | byte | *byte == b'\n').unwrap_or(bytes.len().min(SNIFF_SIZE));
+    let first_line = String::from_utf8_lossy(&bytes[..end]);
+    let first_line = first_line.trim();
+    // #!/usr/bin/env python3 or #!/bin/sh
+    if let Some(shebang) = first_line.strip_prefix("#!") {
+        let mut words = shebang.split_whitespace();
+        let mut program = words.next()?.rsplit('/').next()?;
+        if program == "env" {
+            program = words.find( | word | !word.starts_with('-'))?;
+        }
+        let program = program.trim_end_matches( | char: char | char.is_ascii_digit() || char == '.');
+        return Some(match program {
+            "sh" | "bash" | "zsh" | "dash" => "shell",
+            "node" | "deno" => "javascript",
+            "python" => "python",
+            "ruby" => "ruby",
+            "perl" => "perl",
+            "rust-script" | "cargo" => "rust",
+            program => program,
+        }.to_string())
+    }
+    // emacs style `-*- mode: rust -*-` and vim style `vim: ft=rust` modelines
+    if let Some(start) = first_line.find("-*-") {
+        let rest = &first_line[start + 3..];
+        let modeline = &rest[..rest.find("-*-")?];
+        for part in modeline.split(';') {
+            if let Some((key, value)) = part.split_once(':') {
+                if key.trim().eq_ignore_ascii_case("mode") {
+                    return Some(value.trim().to_lowercase())
+                }
+            }
+        }
+        if !modeline.contains(':') {
+            return Some(modeline.trim().to_lowercase())
+        }
+    }
+    if let Some(start) = first_line.find("vim:").or_else( | | first_line.find("vi:")) {
+        for part in first_line[start..].split( | char: char | char == ':' || char.is_whitespace()) {
+            if let Some(value) = part.strip_prefix("ft=").or_else( | | part.strip_prefix("filetype=")) {
+                return Some(value.to_lowercase())
+            }
+        }
+    }
+    if first_line.starts_with("<?xml") {
+        return Some("xml".to_string())
+    }
+    if first_line.to_lowercase().starts_with("<!doctype html") {
+        return Some("html".to_string())
+    }
+    None
+}
+
+fn detect_language_from_path(path: &str) -> Option<String> {
+    let name = path.rsplit('/').next()?;
+    let extension = name.rsplit_once('.').map( | (_, extension) | extension)?;
+    Some(match extension {
+        "rs" => "rust",
+        "toml" => "toml",
+        "md" => "markdown",
+        "js" | "mjs" => "javascript",
+        "ts" => "typescript",
+        "py" => "python",
+        "sh" => "shell",
+        "json" => "json",
+        "html" | "htm" => "html",
+        "css" => "css",
+        "glsl" | "frag" | "vert" => "glsl",
+        "c" | "h" => "c",
+        "cpp" | "hpp" | "cc" => "cpp",
+        "ron" => "ron",
+        _ => return None
+    }.to_string())
+}
+
+/// Formats bytes as a classic hex dump, for showing binaries read-only in the text editor.
+pub fn hex_dump(bytes: &[u8]) -> String {
+    let mut string = String::new();
+    for (index, chunk) in bytes.chunks(16).enumerate() {
+        string.push_str(&format!("{:08x}  ", index * 16));
+        for column in 0..16 {
+            match chunk.get(column) {
+                Some(byte) => string.push_str(&format!("{:02x} ", byte)),
+                None => string.push_str("   "),
+            }
+            if column == 7 {
+                string.push(' ');
+            }
+        }
+        string.push(' ');
+        string.extend(chunk.iter().map( | byte | if byte.is_ascii_graphic() || *byte == b' ' {*byte as char} else {'.'}));
+        string.push('\n');
+    }
+    string
+}
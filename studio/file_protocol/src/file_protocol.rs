@@ -2,6 +2,9 @@ use {
     crate::{
         makepad_live_id::*,
         makepad_micro_serde::{SerBin, DeBin, DeBinErr},
+        content_kind::FileContentKind,
+        search::{SearchRequest, SearchResult},
+        large_file::{LargeFileLines, LargeFileMatches},
     },
 };
 
@@ -37,9 +40,27 @@ pub enum FileRequest {
     /// on the server.
     OpenFile(String, u64),
     /// Requests the collab server to apply the given delta to the given revision of the file with
-    /// the given id.
-    SaveFile(String, String, u64, bool),
-
+    /// the given id. The last field is the hash of the file content the client last saw on disk;
+    /// if given, the save is refused when the file was modified by someone else in the meantime.
+    SaveFile(String, String, u64, bool, Option<u64>),
+    /// Requests the collab server to search the contents of the files under the given roots.
+    Search(SearchRequest),
+    /// Requests `count` lines from `start` of the large file with the given id, which was opened
+    /// as `FileContentKind::LargeText`.
+    ReadLines {id: u64, start: usize, count: usize},
+    /// Requests at most `max_matches` matches of a query in the large file with the given id,
+    /// searching from `from_line`.
+    SearchLargeFile {id: u64, query: String, from_line: usize, max_matches: usize},
+    /// Requests the collab server to unmap the large file with the given id.
+    CloseLargeFile(u64),
+    /// Like `OpenFile`, but loads the file as text whatever its size, to make a large file
+    /// editable.
+    OpenFileAsText(String, u64),
+    /// Requests the collab server to watch the file with the given path as it grows, and send its
+    /// last lines and then every line appended to it as `FileNotification::FileAppended`.
+    FollowFile(String, u64),
+    /// Requests the collab server to stop watching the file with the given id.
+    UnfollowFile(u64),
 }
 
 /// A type for representing either a response or a notification from the collab server.
@@ -57,11 +78,20 @@ pub enum FileResponse {
     /// The result of requesting the collab server to return its file tree.
     LoadFileTree(Result<FileTreeData, FileError>),
     /// The result of requesting the collab server to add the client as a participant to the file
-    /// with the given id.
-    OpenFile(Result<(String, String, u64), FileError>),
+    /// with the given id. Files that aren't text are sent as a hex dump of their start, and
+    /// large text files without any content, which is read with `ReadLines`.
+    OpenFile(Result<(String, String, u64, FileContentKind), FileError>),
     /// The result of requesting the collab server to apply a delta to a revision of the file with
     /// the given id.
     SaveFile(Result<(String,String,String, u64, bool), FileError>),
+    /// The matches for the search request with the given id.
+    Search(Result<(u64, Vec<SearchResult>), FileError>),
+    ReadLines(Result<LargeFileLines, FileError>),
+    SearchLargeFile(Result<LargeFileMatches, FileError>),
+    CloseLargeFile,
+    /// The id of the file that is now followed.
+    FollowFile(Result<u64, FileError>),
+    UnfollowFile,
 }
 
 /// A type for representing data about a file tree.
@@ -97,6 +127,9 @@ pub struct DirectoryEntry {
 #[derive(Clone, Debug, SerBin, DeBin)]
 pub enum FileNotification {
     FileChangedOnDisk,
+    /// Lines that were appended to the followed file with the given id. `reset` is set when the
+    /// file got shorter, because it was truncated or replaced, and everything before is gone.
+    FileAppended {id: u64, text: String, reset: bool},
     // Notifies the client that another client applied the given delta to the file with the given
     // id. This is only sent for files for which the client is a participant.
    // DeltaWasApplied(TextFileId),
@@ -106,7 +139,14 @@ pub enum FileNotification {
 #[derive(Clone, Debug, SerBin, DeBin)]
 pub enum FileError {
     Unknown(String),
-    CannotOpen(String)
+    CannotOpen(String),
+    /// The file on disk no longer has the content the client last saw.
+    ExternallyModified(String)
+}
+
+/// Hashes file content for the external modification check of `FileRequest::SaveFile`.
+pub fn file_content_hash(content: &str) -> u64 {
+    LiveId::from_str(content).0
 }
 
 /// An identifier for files on the collab server.
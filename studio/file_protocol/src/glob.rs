@@ -0,0 +1,254 @@
+/// A compiled glob pattern, matched against `/` separated paths relative to a root.
+///
+/// Supports `*` (anything but `/`), `**` (anything, including `/`), `?` (a single character) and
+/// `[abc]`/`[a-z]`/`[!abc]` character classes. Patterns follow gitignore conventions: a pattern
+/// without a `/` matches the file name at any depth, a leading `/` anchors it to the root, and a
+/// trailing `/` only matches directories.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Glob {
+    tokens: Vec<GlobToken>,
+    anchored: bool,
+    directory_only: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum GlobToken {
+    Char(char),
+    AnyChar,
+    Star,
+    DoubleStar,
+    Class {negated: bool, ranges: Vec<(char, char)>},
+}
+
+impl Glob {
+    pub fn new(pattern: &str) -> Self {
+        let mut pattern = pattern.trim();
+        let directory_only = pattern.ends_with('/');
+        if directory_only {
+            pattern = &pattern[..pattern.len() - 1];
+        }
+        let anchored = pattern.contains('/');
+        let pattern = pattern.strip_prefix('/').unwrap_or(pattern);
+        let mut tokens = Vec::new();
+        let mut chars = pattern.chars().peekable();
+        while let Some(char) = chars.next() {
+            tokens.push(match char {
+                '*' => if chars.peek() == Some(&'*') {
+                    chars.next();
+                    // `**/` also matches zero directories
+                    if chars.peek() == Some(&'/') {
+                        chars.next();
+                    }
+                    GlobToken::DoubleStar
+                }
+                else {
+                    GlobToken::Star
+                },
+                '?' => GlobToken::AnyChar,
+                '[' => {
+                    let negated = matches!(chars.peek(), Some('!') | Some('^'));
+                    if negated {
+                        chars.next();
+                    }
+                    let mut ranges = Vec::new();
+                    while let Some(char) = chars.next() {
+                        if char == ']' {
+                            break
+                        }
+                        if chars.peek() == Some(&'-') {
+                            chars.next();
+                            match chars.next() {
+                                Some(']') | None => {
+                                    ranges.push((char, char));
+                                    ranges.push(('-', '-'));
+                                    break
+                                }
+                                Some(end) => ranges.push((char, end))
+                            }
+                        }
+                        else {
+                            ranges.push((char, char));
+                        }
+                    }
+                    GlobToken::Class {negated, ranges}
+                }
+                '\\' => GlobToken::Char(chars.next().unwrap_or('\\')),
+                char => GlobToken::Char(char),
+            });
+        }
+        Self {tokens, anchored, directory_only}
+    }
+
+    pub fn is_match(&self, path: &str, is_dir: bool) -> bool {
+        if self.directory_only && !is_dir {
+            return false
+        }
+        let path: Vec<char> = path.chars().collect();
+        if self.anchored {
+            return match_tokens(&self.tokens, &path)
+        }
+        // unanchored patterns match the last path component
+        let name_start = path.iter().rposition( | char | *char == '/').map_or(0, | index | index + 1);
+        match_tokens(&self.tokens, &path[name_start..])
+    }
+}
+
+fn match_tokens(tokens: &[GlobToken], path: &[char]) -> bool {
+    match tokens.split_first() {
+        None => path.is_empty(),
+        Some((token, rest)) => match token {
+            GlobToken::Char(char) => path.first() == Some(char) && match_tokens(rest, &path[1..]),
+            GlobToken::AnyChar => matches!(path.first(), Some(char) if *char != '/') && match_tokens(rest, &path[1..]),
+            GlobToken::Class {negated, ranges} => match path.first() {
+                Some(char) if *char != '/' => {
+                    let in_class = ranges.iter().any( | (start, end) | start <= char && char <= end);
+                    in_class != *negated && match_tokens(rest, &path[1..])
+                }
+                _ => false
+            }
+            GlobToken::Star => {
+                for index in 0..=path.len() {
+                    if match_tokens(rest, &path[index..]) {
+                        return true
+                    }
+                    if index < path.len() && path[index] == '/' {
+                        return false
+                    }
+                }
+                false
+            }
+            GlobToken::DoubleStar => (0..=path.len()).any( | index | match_tokens(rest, &path[index..])),
+        }
+    }
+}
+
+/// Include and exclude globs, as used for scoping searches and for ignore files.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GlobSet {
+    include: Vec<Glob>,
+    exclude: Vec<Glob>,
+}
+
+impl GlobSet {
+    pub fn new(include: &[String], exclude: &[String]) -> Self {
+        Self {
+            include: include.iter().filter( | pattern | !pattern.trim().is_empty()).map( | pattern | Glob::new(pattern)).collect(),
+            exclude: exclude.iter().filter( | pattern | !pattern.trim().is_empty()).map( | pattern | Glob::new(pattern)).collect(),
+        }
+    }
+
+    /// Parses the contents of a `.gitignore` style file. Lines starting with `!` re-include
+    /// paths, which we approximate by taking them out of the exclude list.
+    pub fn from_ignore_file(data: &str) -> Self {
+        let mut exclude = Vec::new();
+        let mut reinclude = Vec::new();
+        for line in data.lines() {
+            let line = line.trim_end();
+            if line.is_empty() || line.starts_with('#') {
+                continue
+            }
+            if let Some(line) = line.strip_prefix('!') {
+                reinclude.push(line.to_string());
+            }
+            else {
+                exclude.push(line.to_string());
+            }
+        }
+        exclude.retain( | pattern | !reinclude.contains(pattern));
+        Self::new(&[], &exclude)
+    }
+
+    pub fn is_excluded(&self, path: &str, is_dir: bool) -> bool {
+        self.exclude.iter().any( | glob | glob.is_match(path, is_dir))
+    }
+
+    /// Whether a path passes the filters. Directories are only checked against the excludes,
+    /// since include patterns usually name files.
+    pub fn is_match(&self, path: &str, is_dir: bool) -> bool {
+        if self.is_excluded(path, is_dir) {
+            return false
+        }
+        is_dir || self.include.is_empty() || self.include.iter().any( | glob | glob.is_match(path, is_dir))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unanchored_patterns_match_the_file_name_at_any_depth() {
+        let glob = Glob::new("*.rs");
+        assert!(glob.is_match("main.rs", false));
+        assert!(glob.is_match("src/os/main.rs", false));
+        assert!(!glob.is_match("src/main.rs/readme.md", false));
+        assert!(!glob.is_match("main.rsx", false));
+    }
+
+    #[test]
+    fn anchored_patterns_match_from_the_root() {
+        let glob = Glob::new("/target");
+        assert!(glob.is_match("target", true));
+        assert!(!glob.is_match("studio/target", true));
+        let glob = Glob::new("src/*.rs");
+        assert!(glob.is_match("src/lib.rs", false));
+        assert!(!glob.is_match("src/os/lib.rs", false));
+    }
+
+    #[test]
+    fn double_star_crosses_directories() {
+        let glob = Glob::new("src/**/*.rs");
+        assert!(glob.is_match("src/lib.rs", false));
+        assert!(glob.is_match("src/os/linux/lib.rs", false));
+        assert!(!glob.is_match("examples/lib.rs", false));
+        let glob = Glob::new("**/tests");
+        assert!(glob.is_match("tests", true));
+        assert!(glob.is_match("code_editor/tests", true));
+    }
+
+    #[test]
+    fn any_char_and_classes() {
+        let glob = Glob::new("file?.[a-c]");
+        assert!(glob.is_match("file1.a", false));
+        assert!(glob.is_match("fileX.c", false));
+        assert!(!glob.is_match("file1.d", false));
+        assert!(!glob.is_match("file.a", false));
+        let glob = Glob::new("[!_]*");
+        assert!(glob.is_match("main.rs", false));
+        assert!(!glob.is_match("_build", false));
+        let glob = Glob::new("[a-]");
+        assert!(glob.is_match("-", false));
+        assert!(!glob.is_match("b", false));
+        let glob = Glob::new("\\*");
+        assert!(glob.is_match("*", false));
+        assert!(!glob.is_match("a", false));
+    }
+
+    #[test]
+    fn trailing_slash_only_matches_directories() {
+        let glob = Glob::new("build/");
+        assert!(glob.is_match("build", true));
+        assert!(glob.is_match("web/build", true));
+        assert!(!glob.is_match("build", false));
+    }
+
+    #[test]
+    fn glob_set_filters() {
+        let set = GlobSet::new(&["*.rs".to_string(), " ".to_string()], &["target/".to_string()]);
+        assert!(set.is_match("src/lib.rs", false));
+        assert!(!set.is_match("readme.md", false));
+        assert!(set.is_match("src", true));
+        assert!(!set.is_match("target", true));
+        assert!(GlobSet::default().is_match("readme.md", false));
+    }
+
+    #[test]
+    fn ignore_files() {
+        let set = GlobSet::from_ignore_file("# build output\n/target\n\n*.log\n*.tmp\n!*.tmp\n");
+        assert!(set.is_excluded("target", true));
+        assert!(!set.is_excluded("studio/target", true));
+        assert!(set.is_excluded("logs/run.log", false));
+        assert!(!set.is_excluded("run.tmp", false));
+        assert!(!set.is_excluded("src/lib.rs", false));
+    }
+}
@@ -0,0 +1,33 @@
+use crate::makepad_micro_serde::{SerBin, DeBin, DeBinErr};
+
+/// A run of lines of a large file, as returned for `FileRequest::ReadLines`.
+#[derive(Clone, Debug, PartialEq, SerBin, DeBin)]
+pub struct LargeFileLines {
+    pub id: u64,
+    /// The index of the first line in `lines`.
+    pub start: usize,
+    pub lines: Vec<String>,
+    /// How many lines the server has found so far.
+    pub indexed_lines: usize,
+    /// Whether the whole file was indexed, and so `indexed_lines` is the line count.
+    pub complete: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, SerBin, DeBin)]
+pub struct LargeFileMatch {
+    pub line: usize,
+    pub column_start: usize,
+    pub column_end: usize,
+}
+
+/// A batch of matches in a large file, as returned for `FileRequest::SearchLargeFile`.
+#[derive(Clone, Debug, PartialEq, SerBin, DeBin)]
+pub struct LargeFileMatches {
+    pub id: u64,
+    pub query: String,
+    pub matches: Vec<LargeFileMatch>,
+    /// The line to continue the search from, to get the next batch.
+    pub next_line: usize,
+    /// Whether the search reached the end of the file.
+    pub done: bool,
+}
@@ -1,5 +1,13 @@
+pub mod content_kind;
 pub mod file_protocol;
+pub mod glob;
+pub mod large_file;
+pub mod search;
 
+pub use content_kind::*;
 pub use file_protocol::*;
+pub use glob::*;
+pub use large_file::*;
+pub use search::*;
 pub use makepad_live_id;
 pub use makepad_micro_serde;
@@ -0,0 +1,71 @@
+use crate::makepad_micro_serde::{SerBin, DeBin, DeBinErr};
+
+/// Which files a find-in-files request looks at.
+#[derive(Clone, Debug, PartialEq, SerBin, DeBin)]
+pub enum SearchScope {
+    /// Only the documents open in the editor, searched on the client so unsaved edits are found.
+    OpenFiles,
+    /// A single folder, relative to the root.
+    Folder(String),
+    /// Every root of the workspace.
+    Workspace,
+}
+
+#[derive(Clone, Debug, PartialEq, SerBin, DeBin)]
+pub struct SearchRequest {
+    pub id: u64,
+    pub query: String,
+    pub case_sensitive: bool,
+    pub whole_word: bool,
+    /// The folders to search, relative to the root. An empty string is the root itself.
+    pub roots: Vec<String>,
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, SerBin, DeBin)]
+pub struct SearchResult {
+    pub path: String,
+    pub line: usize,
+    pub column_start: usize,
+    pub column_end: usize,
+    pub line_text: String,
+}
+
+/// Finds all occurrences of `query` in `text`, as (line, byte start, byte end) triples. Shared
+/// by the file server and by the client when searching open documents.
+pub fn find_in_text(text: &str, query: &str, case_sensitive: bool, whole_word: bool) -> Vec<(usize, usize, usize)> {
+    let mut matches = Vec::new();
+    if query.is_empty() {
+        return matches
+    }
+    let query = if case_sensitive {query.to_string()} else {query.to_lowercase()};
+    for (line_index, line) in text.lines().enumerate() {
+        // lowercasing can change byte lengths, in which case we fall back to a case sensitive search
+        let lowered;
+        let haystack = if case_sensitive {
+            line
+        }
+        else {
+            lowered = line.to_lowercase();
+            if lowered.len() == line.len() {&lowered} else {line}
+        };
+        let mut start = 0;
+        while let Some(index) = haystack[start..].find(&query) {
+            let column_start = start + index;
+            let column_end = column_start + query.len();
+            if !whole_word || is_word_boundary(line, column_start) && is_word_boundary(line, column_end) {
+                matches.push((line_index, column_start, column_end));
+            }
+            start = column_end;
+        }
+    }
+    matches
+}
+
+fn is_word_boundary(line: &str, index: usize) -> bool {
+    let is_word_char = | char: char | char.is_alphanumeric() || char == '_';
+    let before = line[..index].chars().next_back().map_or(false, is_word_char);
+    let after = line[index..].chars().next().map_or(false, is_word_char);
+    before != after || !before
+}
@@ -4,16 +4,31 @@ use {
             DirectoryEntry,
             FileNodeData,
             FileTreeData,
+            FileContentKind,
             FileError,
             FileNotification,
             FileRequest,
             FileResponse,
+            GlobSet,
+            SearchRequest,
+            SearchResult,
+            detect_content_kind,
+            detect_large_content_kind,
+            file_content_hash,
+            find_in_text,
+            hex_dump,
+            MAX_HEX_VIEW_SIZE,
+            MAX_TEXT_FILE_SIZE,
         },
+        large_file::LargeFile,
+        follow::Follower,
     },
     std::{
         cmp::Ordering,
+        collections::HashMap,
         fmt,
         fs,
+        io::Read,
         path::{Path, PathBuf},
         sync::{Arc, RwLock},
     },
@@ -29,10 +44,17 @@ pub struct FileServer {
 impl FileServer {
     /// Creates a new collab server rooted at the given path.
     pub fn new<P: Into<PathBuf >> (root_path: P) -> FileServer {
+        let root_path = root_path.into();
+        let ignore = fs::read_to_string(root_path.join(".gitignore"))
+            .map( | data | GlobSet::from_ignore_file(&data))
+            .unwrap_or_default();
         FileServer {
             next_connection_id: 0,
             shared: Arc::new(RwLock::new(Shared {
-                root_path: root_path.into(),
+                root_path,
+                ignore,
+                large_files: HashMap::new(),
+                followers: HashMap::new(),
             })),
         }
     }
@@ -47,7 +69,7 @@ impl FileServer {
         FileServerConnection {
             _connection_id:connection_id,
             shared: self.shared.clone(),
-            _notification_sender: notification_sender
+            notification_sender
         }
     }
 }
@@ -59,7 +81,7 @@ pub struct FileServerConnection {
     // State is shared between every connection.
     shared: Arc<RwLock<Shared >>,
     // Used to send notifications for this connection.
-    _notification_sender: Box<dyn NotificationSender>,
+    notification_sender: Box<dyn NotificationSender>,
 }
 
 impl FileServerConnection {
@@ -73,7 +95,27 @@ impl FileServerConnection {
         match request {
             FileRequest::LoadFileTree {with_data} => FileResponse::LoadFileTree(self.load_file_tree(with_data)),
             FileRequest::OpenFile(path,id) => FileResponse::OpenFile(self.open_file(path, id)),
-            FileRequest::SaveFile(path, delta, id, was_patch) => FileResponse::SaveFile(self.save_file(path, delta, id, was_patch)),
+            FileRequest::SaveFile(path, delta, id, was_patch, disk_hash) => FileResponse::SaveFile(self.save_file(path, delta, id, was_patch, disk_hash)),
+            FileRequest::Search(request) => FileResponse::Search(self.search(request)),
+            FileRequest::ReadLines {id, start, count} => FileResponse::ReadLines(
+                self.with_large_file(id, | file | file.read_lines(id, start, count))
+            ),
+            FileRequest::SearchLargeFile {id, query, from_line, max_matches} => FileResponse::SearchLargeFile(
+                self.with_large_file(id, | file | file.search(id, query, from_line, max_matches))
+            ),
+            FileRequest::CloseLargeFile(id) => {
+                self.shared.write().unwrap().large_files.remove(&id);
+                FileResponse::CloseLargeFile
+            }
+            FileRequest::OpenFileAsText(path, id) => {
+                self.shared.write().unwrap().large_files.remove(&id);
+                FileResponse::OpenFile(self.open_file_as_text(path, id))
+            }
+            FileRequest::FollowFile(path, id) => FileResponse::FollowFile(self.follow_file(path, id)),
+            FileRequest::UnfollowFile(id) => {
+                self.shared.write().unwrap().followers.remove(&id);
+                FileResponse::UnfollowFile
+            }
         }
     }
     
@@ -81,7 +123,7 @@ impl FileServerConnection {
     fn load_file_tree(&self, with_data: bool) -> Result<FileTreeData, FileError> {
         // A recursive helper function for traversing the entries of a directory and creating the
         // data structures that describe them.
-        fn get_directory_entries(path: &Path, with_data: bool) -> Result<Vec<DirectoryEntry>, FileError> {
+        fn get_directory_entries(path: &Path, root_path: &Path, ignore: &GlobSet, with_data: bool) -> Result<Vec<DirectoryEntry>, FileError> {
             let mut entries = Vec::new();
             for entry in fs::read_dir(path).map_err( | error | FileError::Unknown(error.to_string())) ? {
                 // We can't get the entry for some unknown reason. Raise an error.
@@ -105,6 +147,9 @@ impl FileServerConnection {
                     // Skip over entries with a non UTF-8 file name.
                     continue;
                 }
+                if ignore.is_excluded(&relative_path(root_path, &entry_path), entry_path.is_dir()) {
+                    continue;
+                }
                 // Create a `DirectoryEntry` for this entry and add it to the list of entries.
                 entries.push(DirectoryEntry {
                     name: entry.file_name().to_string_lossy().to_string(),
@@ -112,7 +157,7 @@ impl FileServerConnection {
                         // If this entry is a subdirectory, recursively create `DirectoryEntry`'s
                         // for its entries as well.
                         FileNodeData::Directory {
-                            entries: get_directory_entries(&entry_path, with_data) ?,
+                            entries: get_directory_entries(&entry_path, root_path, ignore, with_data) ?,
                         }
                     } else if entry_path.is_file() {
                         if with_data {
@@ -151,13 +196,75 @@ impl FileServerConnection {
         }
         
         let root_path = self.shared.read().unwrap().root_path.clone();
+        let ignore = self.shared.read().unwrap().ignore.clone();
         
         let root = FileNodeData::Directory {
-            entries: get_directory_entries(&root_path, with_data) ?,
+            entries: get_directory_entries(&root_path, &root_path, &ignore, with_data) ?,
         };
         Ok(FileTreeData {root_path: "".into(), root})
     }
     
+    // Handles a `Search` request.
+    fn search(&self, request: SearchRequest) -> Result<(u64, Vec<SearchResult>), FileError> {
+        // Files bigger than this are most likely not source code, skip them.
+        const MAX_FILE_SIZE: u64 = 8 * 1024 * 1024;
+        
+        fn search_directory(path: &Path, root_path: &Path, ignore: &GlobSet, filter: &GlobSet, request: &SearchRequest, results: &mut Vec<SearchResult>) -> Result<(), FileError> {
+            for entry in fs::read_dir(path).map_err( | error | FileError::Unknown(error.to_string())) ? {
+                let entry = entry.map_err( | error | FileError::Unknown(error.to_string())) ?;
+                let entry_path = entry.path();
+                let name = entry.file_name().to_string_lossy().to_string();
+                let is_dir = entry_path.is_dir();
+                // Same skipping rules as the file tree.
+                if is_dir && name == "target" || name.starts_with('.') {
+                    continue;
+                }
+                let child_path = relative_path(root_path, &entry_path);
+                if ignore.is_excluded(&child_path, is_dir) || !filter.is_match(&child_path, is_dir) {
+                    continue;
+                }
+                if is_dir {
+                    search_directory(&entry_path, root_path, ignore, filter, request, results) ?;
+                    continue;
+                }
+                if entry.metadata().map_or(true, | metadata | metadata.len() > MAX_FILE_SIZE) {
+                    continue;
+                }
+                let bytes = match fs::read(&entry_path) {
+                    Ok(bytes) => bytes,
+                    Err(_) => continue
+                };
+                if !detect_content_kind(&child_path, &bytes).is_text() {
+                    continue;
+                }
+                let text = String::from_utf8_lossy(&bytes);
+                let lines: Vec<&str> = text.lines().collect();
+                for (line, column_start, column_end) in find_in_text(&text, &request.query, request.case_sensitive, request.whole_word) {
+                    results.push(SearchResult {
+                        path: child_path.clone(),
+                        line,
+                        column_start,
+                        column_end,
+                        line_text: lines[line].to_string(),
+                    });
+                }
+            }
+            Ok(())
+        }
+        
+        let root_path = self.shared.read().unwrap().root_path.clone();
+        let ignore = self.shared.read().unwrap().ignore.clone();
+        let filter = GlobSet::new(&request.include, &request.exclude);
+        let mut results = Vec::new();
+        for root in &request.roots {
+            let path = self.make_full_path(root);
+            if path.is_dir() {
+                search_directory(&path, &root_path, &ignore, &filter, &request, &mut results) ?;
+            }
+        }
+        Ok((request.id, results))
+    }
+    
     fn make_full_path(&self, child_path:&String)->PathBuf{
         let mut path = self.shared.read().unwrap().root_path.clone();
         path.push(child_path);
@@ -165,9 +272,16 @@ impl FileServerConnection {
     }
     
     // Handles an `OpenFile` request.
-    fn open_file(&self, child_path: String, id:u64) -> Result<(String, String, u64), FileError> {
+    fn open_file(&self, child_path: String, id:u64) -> Result<(String, String, u64, FileContentKind), FileError> {
         let path = self.make_full_path(&child_path);
         
+        let size = fs::metadata(&path).map_err(
+            | error | FileError::Unknown(error.to_string())
+        ) ?.len();
+        if size > MAX_TEXT_FILE_SIZE as u64 {
+            return self.open_large_file(child_path, &path, size, id)
+        }
+        
         let bytes = fs::read(&path).map_err(
             | error | FileError::Unknown(error.to_string())
         ) ?;
@@ -179,8 +293,63 @@ impl FileServerConnection {
             .map( | line | line.chars().collect::<Vec<_ >> ())
             .collect::<Vec<_ >>());*/
         
+        let kind = detect_content_kind(&child_path, &bytes);
+        if !kind.is_text() {
+            // never send huge binaries over, the hex view only shows the start
+            let text = hex_dump(&bytes[..bytes.len().min(MAX_HEX_VIEW_SIZE)]);
+            return Ok((child_path, text, id, kind))
+        }
         let text = String::from_utf8_lossy(&bytes);
-        Ok((child_path, text.to_string(), id))
+        Ok((child_path, text.to_string(), id, kind))
+    }
+    
+    // Opens a file that is too big to send whole. Only the start of it is read, and text is
+    // memory-mapped for `ReadLines` and `SearchLargeFile`.
+    fn open_large_file(&self, child_path: String, path: &Path, size: u64, id: u64) -> Result<(String, String, u64, FileContentKind), FileError> {
+        let mut head = Vec::new();
+        fs::File::open(path)
+            .and_then( | file | file.take(MAX_HEX_VIEW_SIZE as u64).read_to_end(&mut head))
+            .map_err( | error | FileError::Unknown(error.to_string())) ?;
+        let kind = detect_large_content_kind(&head, size);
+        if !kind.is_large_text() {
+            return Ok((child_path, hex_dump(&head), id, kind))
+        }
+        let file = LargeFile::open(path).map_err( | error | FileError::Unknown(error.to_string())) ?;
+        self.shared.write().unwrap().large_files.insert(id, file);
+        Ok((child_path, String::new(), id, kind))
+    }
+    
+    // Handles an `OpenFileAsText` request, which loads a file as text whatever its size.
+    fn open_file_as_text(&self, child_path: String, id: u64) -> Result<(String, String, u64, FileContentKind), FileError> {
+        let path = self.make_full_path(&child_path);
+        let bytes = fs::read(&path).map_err(
+            | error | FileError::Unknown(error.to_string())
+        ) ?;
+        // the size alone would make it binary, so only the start decides the language
+        let kind = match detect_content_kind(&child_path, &bytes[..bytes.len().min(MAX_HEX_VIEW_SIZE)]) {
+            kind @ FileContentKind::Text {..} => kind,
+            _ => FileContentKind::Text {language: None}
+        };
+        let text = String::from_utf8_lossy(&bytes).to_string();
+        Ok((child_path, text, id, kind))
+    }
+    
+    // Handles a `FollowFile` request. Following a file again starts over from its tail.
+    fn follow_file(&self, child_path: String, id: u64) -> Result<u64, FileError> {
+        let path = self.make_full_path(&child_path);
+        let follower = Follower::spawn(path, id, self.notification_sender.box_clone()).map_err(
+            | error | FileError::CannotOpen(error.to_string())
+        ) ?;
+        self.shared.write().unwrap().followers.insert(id, follower);
+        Ok(id)
+    }
+    
+    fn with_large_file<R>(&self, id: u64, f: impl FnOnce(&mut LargeFile) -> R) -> Result<R, FileError> {
+        let mut shared = self.shared.write().unwrap();
+        match shared.large_files.get_mut(&id) {
+            Some(file) => Ok(f(file)),
+            None => Err(FileError::CannotOpen(format!("large file {} is not open", id)))
+        }
     }
     
     // Handles an `ApplyDelta` request.
@@ -189,13 +358,21 @@ impl FileServerConnection {
         child_path: String,
         new_content: String,
         id: u64,
-        was_patch: bool
+        was_patch: bool,
+        disk_hash: Option<u64>
     ) -> Result<(String, String, String, u64, bool), FileError> {
         let path = self.make_full_path(&child_path);
         
         let old_content = String::from_utf8_lossy(&fs::read(&path).map_err(
             | error | FileError::Unknown(error.to_string())
         ) ?).to_string();
+        
+        // Don't overwrite changes made outside of the editor since the client last saw the file.
+        if let Some(disk_hash) = disk_hash {
+            if file_content_hash(&old_content) != disk_hash {
+                return Err(FileError::ExternallyModified(child_path))
+            }
+        }
 
         fs::write(&path, &new_content).map_err(
             | error | FileError::Unknown(error.to_string())
@@ -205,6 +382,15 @@ impl FileServerConnection {
     }
 }
 
+// Returns the `/` separated path of `path` relative to `root_path`, as used by the client.
+fn relative_path(root_path: &Path, path: &Path) -> String {
+    let path = path.strip_prefix(root_path).unwrap_or(path);
+    path.components()
+        .map( | component | component.as_os_str().to_string_lossy().to_string())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
 /// A trait for sending notifications over a connection.
 pub trait NotificationSender: Send {
     /// This method is necessary to create clones of boxed trait objects.
@@ -240,6 +426,12 @@ impl fmt::Debug for dyn NotificationSender {
 #[derive(Debug)]
 struct Shared {
     root_path: PathBuf,
+    // The rules from the .gitignore file in the root, if any
+    ignore: GlobSet,
+    // The files opened as `FileContentKind::LargeText`, by id
+    large_files: HashMap<u64, LargeFile>,
+    // The files that are watched as they grow, by id
+    followers: HashMap<u64, Follower>,
 }
 
 /// An identifier for a connection.
@@ -0,0 +1,125 @@
+use {
+    crate::{
+        file_server::NotificationSender,
+        makepad_file_protocol::FileNotification,
+    },
+    std::{
+        fs::{self, File},
+        io::{self, Read, Seek, SeekFrom},
+        path::PathBuf,
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        },
+        thread,
+        time::Duration,
+    },
+};
+
+// How often the length of a followed file is checked.
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+// How much of the end of the file is sent when following starts.
+const TAIL_SIZE: u64 = 256 * 1024;
+
+// How much is read per check at most, so a log that grows very fast is sent in pieces.
+const MAX_READ_SIZE: u64 = 4 * 1024 * 1024;
+
+/// A thread that watches a file grow, like `tail -f`, and sends the lines appended to it as
+/// notifications. Only whole lines are sent, the rest of a line waits until its line end is
+/// written. The thread stops when this is dropped.
+#[derive(Debug)]
+pub struct Follower {
+    running: Arc<AtomicBool>,
+}
+
+impl Follower {
+    pub fn spawn(path: PathBuf, id: u64, sender: Box<dyn NotificationSender>) -> io::Result<Self> {
+        let len = fs::metadata(&path) ?.len();
+        let running = Arc::new(AtomicBool::new(true));
+        thread::spawn({
+            let running = running.clone();
+            move || {
+                let mut follow = Follow {
+                    path,
+                    id,
+                    sender,
+                    offset: len.saturating_sub(TAIL_SIZE),
+                    pending: Vec::new(),
+                };
+                // the tail starts at the first whole line
+                let skip_first_line = follow.offset > 0;
+                follow.read(true, skip_first_line);
+                while running.load(Ordering::Relaxed) {
+                    thread::sleep(POLL_INTERVAL);
+                    if running.load(Ordering::Relaxed) {
+                        follow.poll();
+                    }
+                }
+            }
+        });
+        Ok(Self {running})
+    }
+}
+
+impl Drop for Follower {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+    }
+}
+
+struct Follow {
+    path: PathBuf,
+    id: u64,
+    sender: Box<dyn NotificationSender>,
+    // how far the file was read
+    offset: u64,
+    // the start of a line whose line end wasn't written yet
+    pending: Vec<u8>,
+}
+
+impl Follow {
+    fn poll(&mut self) {
+        // a file that is gone may be recreated by log rotation, so it is simply checked again
+        let Ok(metadata) = fs::metadata(&self.path) else {
+            return
+        };
+        if metadata.len() < self.offset {
+            self.offset = 0;
+            self.pending.clear();
+            self.read(true, false);
+        }
+        else if metadata.len() > self.offset {
+            self.read(false, false);
+        }
+    }
+
+    fn read(&mut self, reset: bool, skip_first_line: bool) {
+        let mut bytes = Vec::new();
+        let read = File::open(&self.path).and_then( | mut file | {
+            file.seek(SeekFrom::Start(self.offset)) ?;
+            file.take(MAX_READ_SIZE).read_to_end(&mut bytes)
+        });
+        if read.is_err() {
+            return
+        }
+        self.offset += bytes.len() as u64;
+        let mut bytes = &bytes[..];
+        if skip_first_line {
+            let start = bytes.iter().position( | byte | *byte == b'\n').map_or(bytes.len(), | end | end + 1);
+            bytes = &bytes[start..];
+        }
+        self.pending.extend_from_slice(bytes);
+        let text = match self.pending.iter().rposition( | byte | *byte == b'\n') {
+            Some(end) => {
+                let text = String::from_utf8_lossy(&self.pending[..end + 1]).to_string();
+                self.pending.drain(..end + 1);
+                text
+            }
+            None => String::new()
+        };
+        if reset || !text.is_empty() {
+            self.sender.send_notification(FileNotification::FileAppended {id: self.id, text, reset});
+        }
+    }
+}
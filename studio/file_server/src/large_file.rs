@@ -0,0 +1,247 @@
+use {
+    crate::makepad_file_protocol::{
+        find_in_text,
+        LargeFileLines,
+        LargeFileMatch,
+        LargeFileMatches,
+    },
+    std::{
+        fmt,
+        fs::File,
+        io,
+        path::Path,
+    },
+};
+
+// Every this many lines the byte offset of the line start is kept, which keeps the index small
+// for files with hundreds of millions of lines.
+const LINES_PER_CHECKPOINT: usize = 256;
+
+// How many bytes are scanned for line ends at a time, when a line further on is asked for.
+const INDEX_CHUNK_SIZE: usize = 4 * 1024 * 1024;
+
+// How many bytes one search request looks through at most, so a search for something rare
+// doesn't hold up the other requests. The client asks for the next batch.
+const SEARCH_BUDGET: usize = 64 * 1024 * 1024;
+
+/// A read only memory map of a whole file.
+pub struct MappedFile {
+    ptr: *const u8,
+    len: usize,
+    #[cfg(windows)]
+    mapping: *mut std::ffi::c_void,
+}
+
+// The mapping is read only, so sharing it between threads is fine.
+unsafe impl Send for MappedFile {}
+unsafe impl Sync for MappedFile {}
+
+impl MappedFile {
+    #[cfg(unix)]
+    pub fn open(path: &Path) -> io::Result<Self> {
+        use std::os::unix::io::AsRawFd;
+        extern "C" {
+            fn mmap(addr: *mut std::ffi::c_void, len: usize, prot: i32, flags: i32, fd: i32, offset: i64) -> *mut std::ffi::c_void;
+        }
+        const PROT_READ: i32 = 1;
+        const MAP_PRIVATE: i32 = 2;
+        let file = File::open(path) ?;
+        let len = file.metadata() ?.len() as usize;
+        if len == 0 {
+            return Ok(Self {ptr: std::ptr::null(), len})
+        }
+        let ptr = unsafe {mmap(std::ptr::null_mut(), len, PROT_READ, MAP_PRIVATE, file.as_raw_fd(), 0)};
+        if ptr as isize == -1 {
+            return Err(io::Error::last_os_error())
+        }
+        // the mapping stays valid after the file is closed
+        Ok(Self {ptr: ptr as *const u8, len})
+    }
+
+    #[cfg(windows)]
+    pub fn open(path: &Path) -> io::Result<Self> {
+        use std::{ffi::c_void, os::windows::io::AsRawHandle};
+        #[link(name = "kernel32")]
+        extern "system" {
+            fn CreateFileMappingW(file: *mut c_void, attributes: *mut c_void, protect: u32, size_high: u32, size_low: u32, name: *const u16) -> *mut c_void;
+            fn MapViewOfFile(mapping: *mut c_void, access: u32, offset_high: u32, offset_low: u32, len: usize) -> *mut c_void;
+            fn CloseHandle(handle: *mut c_void) -> i32;
+        }
+        const PAGE_READONLY: u32 = 0x02;
+        const FILE_MAP_READ: u32 = 0x04;
+        let file = File::open(path) ?;
+        let len = file.metadata() ?.len() as usize;
+        if len == 0 {
+            return Ok(Self {ptr: std::ptr::null(), len, mapping: std::ptr::null_mut()})
+        }
+        unsafe {
+            let mapping = CreateFileMappingW(file.as_raw_handle() as *mut c_void, std::ptr::null_mut(), PAGE_READONLY, 0, 0, std::ptr::null());
+            if mapping.is_null() {
+                return Err(io::Error::last_os_error())
+            }
+            let ptr = MapViewOfFile(mapping, FILE_MAP_READ, 0, 0, 0);
+            if ptr.is_null() {
+                let error = io::Error::last_os_error();
+                CloseHandle(mapping);
+                return Err(error)
+            }
+            Ok(Self {ptr: ptr as *const u8, len, mapping})
+        }
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        if self.ptr.is_null() {
+            return &[]
+        }
+        unsafe {std::slice::from_raw_parts(self.ptr, self.len)}
+    }
+}
+
+impl Drop for MappedFile {
+    #[cfg(unix)]
+    fn drop(&mut self) {
+        extern "C" {
+            fn munmap(addr: *mut std::ffi::c_void, len: usize) -> i32;
+        }
+        if !self.ptr.is_null() {
+            unsafe {munmap(self.ptr as *mut _, self.len);}
+        }
+    }
+
+    #[cfg(windows)]
+    fn drop(&mut self) {
+        use std::ffi::c_void;
+        #[link(name = "kernel32")]
+        extern "system" {
+            fn UnmapViewOfFile(base: *const c_void) -> i32;
+            fn CloseHandle(handle: *mut c_void) -> i32;
+        }
+        if !self.ptr.is_null() {
+            unsafe {
+                UnmapViewOfFile(self.ptr as *const c_void);
+                CloseHandle(self.mapping);
+            }
+        }
+    }
+}
+
+/// A memory-mapped text file with a line index that is only built as far as lines are asked
+/// for, so a multi-GB log can be viewed and searched without reading it into memory.
+pub struct LargeFile {
+    map: MappedFile,
+    // the byte offset of every `LINES_PER_CHECKPOINT`th line
+    checkpoints: Vec<usize>,
+    // how many lines start before `indexed_bytes`
+    indexed_lines: usize,
+    indexed_bytes: usize,
+}
+
+impl fmt::Debug for LargeFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LargeFile")
+            .field("len", &self.map.len)
+            .field("indexed_lines", &self.indexed_lines)
+            .finish()
+    }
+}
+
+impl LargeFile {
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let map = MappedFile::open(path) ?;
+        let indexed_lines = if map.len > 0 {1} else {0};
+        Ok(Self {
+            map,
+            checkpoints: vec![0],
+            indexed_lines,
+            indexed_bytes: 0,
+        })
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.indexed_bytes >= self.map.len
+    }
+
+    // Scans for line ends until the start of `line` is known or the file ends.
+    fn index_to_line(&mut self, line: usize) {
+        let bytes = self.map.bytes();
+        while self.indexed_lines <= line && self.indexed_bytes < bytes.len() {
+            let end = (self.indexed_bytes + INDEX_CHUNK_SIZE).min(bytes.len());
+            for (offset, byte) in bytes[self.indexed_bytes..end].iter().enumerate() {
+                if *byte == b'\n' {
+                    let start = self.indexed_bytes + offset + 1;
+                    // a newline at the very end doesn't start another line
+                    if start == bytes.len() {
+                        continue
+                    }
+                    if self.indexed_lines % LINES_PER_CHECKPOINT == 0 {
+                        self.checkpoints.push(start);
+                    }
+                    self.indexed_lines += 1;
+                }
+            }
+            self.indexed_bytes = end;
+        }
+    }
+
+    // The byte range of a line, without its line end, if the file has that many lines.
+    fn line_range(&mut self, line: usize) -> Option<(usize, usize)> {
+        self.index_to_line(line);
+        if line >= self.indexed_lines {
+            return None
+        }
+        let bytes = self.map.bytes();
+        let mut start = self.checkpoints[line / LINES_PER_CHECKPOINT];
+        for _ in 0..line % LINES_PER_CHECKPOINT {
+            start += bytes[start..].iter().position( | byte | *byte == b'\n').unwrap() + 1;
+        }
+        let mut end = bytes[start..].iter().position( | byte | *byte == b'\n').map_or(bytes.len(), | len | start + len);
+        if end > start && bytes[end - 1] == b'\r' {
+            end -= 1;
+        }
+        Some((start, end))
+    }
+
+    pub fn read_lines(&mut self, id: u64, start: usize, count: usize) -> LargeFileLines {
+        let mut lines = Vec::new();
+        for line in start..start + count {
+            let Some((line_start, line_end)) = self.line_range(line) else {
+                break
+            };
+            lines.push(String::from_utf8_lossy(&self.map.bytes()[line_start..line_end]).to_string());
+        }
+        LargeFileLines {
+            id,
+            start,
+            lines,
+            indexed_lines: self.indexed_lines,
+            complete: self.is_complete(),
+        }
+    }
+
+    /// Searches a checkpoint's worth of lines at a time, until enough matches are found or the
+    /// search budget is used up.
+    pub fn search(&mut self, id: u64, query: String, from_line: usize, max_matches: usize) -> LargeFileMatches {
+        let mut matches = Vec::new();
+        let mut line = from_line;
+        let mut searched = 0;
+        loop {
+            let block_end = line + LINES_PER_CHECKPOINT;
+            let Some((start, _)) = self.line_range(line) else {
+                return LargeFileMatches {id, query, matches, next_line: line, done: true}
+            };
+            let end = match self.line_range(block_end) {
+                Some((end, _)) => end,
+                None => self.map.len
+            };
+            let text = String::from_utf8_lossy(&self.map.bytes()[start..end]);
+            for (line_index, column_start, column_end) in find_in_text(&text, &query, false, false) {
+                matches.push(LargeFileMatch {line: line + line_index, column_start, column_end});
+            }
+            line = block_end;
+            searched += end - start;
+            if matches.len() >= max_matches || searched >= SEARCH_BUDGET {
+                return LargeFileMatches {id, query, matches, next_line: line, done: false}
+            }
+        }
+    }
+}
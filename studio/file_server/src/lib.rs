@@ -3,6 +3,10 @@
 pub mod file_server;
 #[cfg(not(target_arch = "wasm32"))]
 pub use file_server::*;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod large_file;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod follow;
 
 pub use makepad_micro_serde;
 pub use makepad_live_id;
@@ -1,29 +1,46 @@
 use crate::{
     makepad_code_editor::code_editor::*,
+    makepad_code_editor::code_action::CodeActionRegistry,
     makepad_code_editor::selection::Affinity,
     makepad_code_editor::session::SelectionMode,
     makepad_code_editor::history::NewGroup,
     makepad_widgets::*,
-    makepad_micro_serde::*,
     makepad_widgets::file_tree::*,
     makepad_platform::os::cx_stdin::*,
     file_system::file_system::*,
+    file_system::ConnectionState,
     studio_editor::*,
     run_view::*,
     makepad_platform::studio::{JumpToFile,EditFile, PatchFile},
     run_list::*,
     log_list::*,
     makepad_code_editor::text::{Position},
+    makepad_code_editor::scroll_link::{ScrollLinkMode, ScrollLinks},
+    makepad_code_editor::search::SearchQuery,
+    makepad_file_protocol::SearchScope,
+    unsaved_changes::{CloseTarget, UnsavedChanges},
+    workspace_trust::{WorkspaceTrust, WorkspaceTrustSettings},
+    persistent_state::{PersistentState, TabSession},
+    tool_environment::import_login_shell_environment,
     build_manager::{
         build_manager::{
             BuildManager,
             BuildManagerAction
         },
-    }
+        build_protocol::{LogItem, LogItemBare},
+    },
+    makepad_platform::log::LogLevel,
+    evaluator::Evaluator,
+    tab_kinds::TabKindRegistry,
+    navigation_history::NavigationHistory,
+    tab_switcher::*,
+    search_results::*,
+    keymap::{Keymap, KeymapResult, KeymapSettings},
+    settings::SettingsFile,
+    key_hints::*,
 }; 
-use std::fs::File;
-use std::io::Write;
 use std::env;
+use std::path::{Path, PathBuf};
   
 live_design!{
     import crate::app_ui::*;
@@ -37,6 +54,18 @@ live_design!{
 pub struct App {
     #[live] ui: WidgetRef,
     #[rust] data: AppData,
+    #[rust] unsaved_changes: UnsavedChanges,
+    #[rust] workspace_trust: WorkspaceTrustSettings,
+    #[rust] root_path: PathBuf,
+    #[rust] tab_kinds: TabKindRegistry,
+    #[rust] scroll_links: ScrollLinks,
+    /// The workspace edit whose preview is shown.
+    #[rust] previewed_workspace_edit: Option<u64>,
+    #[rust] keymap: Keymap,
+    /// Ends a pending key sequence when its next key doesn't come.
+    #[rust] keymap_timer: Timer,
+    /// The tabs of the restored layout, whose files are opened once the file tree is loaded.
+    #[rust] restored_tab_sessions: Vec<TabSession>,
 }
 
 impl LiveRegister for App{
@@ -47,8 +76,13 @@ impl LiveRegister for App{
         crate::log_list::live_design(cx);
         crate::profiler::live_design(cx);
         crate::run_view::live_design(cx);
+        crate::large_file_view::live_design(cx);
+        crate::follow_view::live_design(cx);
         crate::studio_editor::live_design(cx);
         crate::studio_file_tree::live_design(cx);
+        crate::tab_switcher::live_design(cx);
+        crate::search_results::live_design(cx);
+        crate::key_hints::live_design(cx);
         crate::app_ui::live_design(cx);
         // for macos
         cx.start_stdin_service();
@@ -58,22 +92,265 @@ impl LiveRegister for App{
 app_main!(App);
 
 impl App {
+    pub fn go_to_edit_location(&mut self, cx: &mut Cx, file_id: LiveId, pos: Position) {
+        let dock = self.ui.dock(id!(dock));
+        if let Some(tab_id) = self.data.file_system.file_node_id_to_tab_id(file_id){
+            dock.select_tab(cx, tab_id);
+            if let Some(mut editor) = dock.item(tab_id).studio_editor(id!(editor)).borrow_mut() {
+                if let Some(session) = self.data.file_system.get_session_mut(tab_id) {
+                    editor.editor.set_cursor_and_scroll(cx, pos, session);
+                    editor.editor.set_key_focus(cx);
+                }
+            }
+        }
+        else{
+            // the tab was closed but the document is still around, reopen it
+            self.open_file_tab(cx, file_id);
+        }
+    }
+    
+    pub fn close_tab(&mut self, cx: &mut Cx, tab_id: LiveId) {
+        let dock = self.ui.dock(id!(dock));
+        dock.close_tab(cx, tab_id);
+        if self.data.build_manager.handle_tab_close(tab_id) {
+            self.ui.log_list(id!(log_list)).redraw(cx);
+            self.ui.view(id!(run_list)).redraw(cx);
+        }
+        self.scroll_links.unlink(tab_id);
+        self.data.file_system.remove_tab(tab_id);
+        self.data.file_system.ensure_unique_tab_names(cx, &dock);
+    }
+    
+    pub fn close_target(&mut self, cx: &mut Cx, target: CloseTarget) {
+        match target {
+            CloseTarget::Tab(tab_id) => self.close_tab(cx, tab_id),
+            // the studio has a single window, closing it quits
+            CloseTarget::App => cx.quit(),
+        }
+    }
+    
+    fn open_tab_switcher(&mut self, cx: &mut Cx) {
+        let dock = self.ui.dock(id!(dock));
+        let file_system = &self.data.file_system;
+        let items = dock.tabs_by_recent_use().into_iter().map( | tab_id | TabSwitcherItem {
+            tab_id,
+            name: dock.tab_title(tab_id).unwrap_or_default(),
+            path: file_system.tab_id_to_file_node_id.get(&tab_id)
+                .map( | file_id | file_system.file_node_relative_path(*file_id))
+                .unwrap_or_default(),
+        }).collect();
+        self.ui.tab_switcher(id!(tab_switcher)).open(cx, items);
+    }
+    
+    /// The contents of the last used tab as plain text, in reading order.
+    fn panel_reading_text(&mut self, tab_id: LiveId) -> String {
+        let dock = self.ui.dock(id!(dock));
+        let mut out = String::new();
+        let mut scope = Scope::with_data(&mut self.data);
+        scope.with_id(tab_id, | scope | dock.item(tab_id).reading_text(scope, &mut out));
+        out
+    }
+    
+    /// Runs a command of the keymap.
+    fn run_command(&mut self, cx: &mut Cx, command: &str) {
+        let action = match command {
+            "recompile" => AppAction::StartRecompile,
+            "clear_log" => AppAction::ClearLog,
+            "reload_file_tree" => AppAction::ReloadFileTree,
+            "save_all_files" => AppAction::SaveAllFiles,
+            "prev_edit_location" => AppAction::PrevEditLocation,
+            "next_edit_location" => AppAction::NextEditLocation,
+            "copy_panel_contents" => AppAction::CopyPanelContents,
+            _ => {
+                log!("Unknown command {:?} in the keymap", command);
+                return
+            }
+        };
+        cx.action(action)
+    }
+    
+    fn end_key_sequence(&mut self, cx: &mut Cx) {
+        cx.stop_timer(self.keymap_timer);
+        self.ui.key_hints(id!(key_hints)).hide(cx);
+        cx.revert_key_focus();
+    }
+    
+    fn switch_to_tab(&mut self, cx: &mut Cx, tab_id: LiveId) {
+        let dock = self.ui.dock(id!(dock));
+        dock.select_tab(cx, tab_id);
+        if let Some(mut editor) = dock.item(tab_id).studio_editor(id!(editor)).borrow_mut() {
+            editor.editor.set_key_focus(cx);
+        }
+    }
+    
+    pub fn set_workspace_trust(&mut self, cx: &mut Cx, trust: WorkspaceTrust) {
+        self.workspace_trust.set_trust(&self.root_path, trust);
+        self.workspace_trust.save();
+        self.data.build_manager.set_trust(cx, trust);
+        self.ui.view(id!(workspace_trust)).set_visible_and_redraw(cx, false);
+        self.ui.view(id!(run_list)).redraw(cx);
+    }
+    
+    /// Runs the query of the search panel. Typing or changing an option only searches the open
+    /// documents, which is quick, and the rest of the workspace is searched on return.
+    fn run_search(&mut self, cx: &mut Cx, scope: SearchScope) {
+        let query = SearchQuery {
+            text: self.ui.text_input(id!(search_input)).text(),
+            is_regex: self.ui.check_box(id!(search_regex)).selected(cx),
+            match_case: self.ui.check_box(id!(search_match_case)).selected(cx),
+            whole_word: self.ui.check_box(id!(search_whole_word)).selected(cx),
+        };
+        self.data.file_system.search_project(cx, scope, query, Vec::new(), Vec::new());
+    }
+    
+    /// Links the scrolling of the editor tab to the other code editor on screen, or unlinks it
+    /// if it was linked already. Editors showing documents of the same length scroll line by
+    /// line, others proportionally.
+    pub fn toggle_scroll_link(&mut self, tab_id: LiveId) {
+        if self.scroll_links.is_linked(tab_id) {
+            self.scroll_links.unlink(tab_id);
+            return
+        }
+        let dock = self.ui.dock(id!(dock));
+        let mut other_tab_id = None;
+        if let Some(mut dock) = dock.borrow_mut() {
+            for (item_id, item) in dock.visible_items() {
+                if item_id != tab_id && item.studio_editor(id!(editor)).borrow().is_some() {
+                    other_tab_id = Some(item_id);
+                    break;
+                }
+            }
+        }
+        if let Some(other_tab_id) = other_tab_id {
+            let line_count = | file_system: &mut FileSystem, tab_id | {
+                let session = file_system.get_session_mut(tab_id)?;
+                let line_count = session.document().as_text().as_lines().len();
+                Some(line_count)
+            };
+            let mode = if line_count(&mut self.data.file_system, tab_id) == line_count(&mut self.data.file_system, other_tab_id) {
+                ScrollLinkMode::LineMapping(Vec::new())
+            }
+            else {
+                ScrollLinkMode::Proportional
+            };
+            self.scroll_links.link(tab_id, other_tab_id, mode);
+        }
+    }
+    
+    pub fn sync_linked_scroll(&mut self, cx: &mut Cx, tab_id: LiveId) {
+        let dock = self.ui.dock(id!(dock));
+        let position = match (
+            dock.item(tab_id).studio_editor(id!(editor)).borrow_mut(),
+            self.data.file_system.get_session_mut(tab_id)
+        ) {
+            (Some(mut editor), Some(session)) => editor.editor.scroll_position(session),
+            _ => return
+        };
+        for (other_tab_id, target) in self.scroll_links.follow(tab_id, position) {
+            if let Some(mut editor) = dock.item(other_tab_id).studio_editor(id!(editor)).borrow_mut() {
+                if let Some(session) = self.data.file_system.get_session_mut(other_tab_id) {
+                    editor.editor.scroll_to_target(cx, session, target);
+                }
+            }
+        }
+    }
+    
+    /// Opens a new tab for the file, of the kind registered for its path.
+    pub fn open_file_tab(&mut self, cx: &mut Cx, file_id: LiveId) {
+        let path = self.data.file_system.file_node_path(file_id);
+        let tab_kind = match self.tab_kinds.for_file(&path) {
+            Some(tab_kind) => tab_kind,
+            None => return
+        };
+        let dock = self.ui.dock(id!(dock));
+        let tab_id = dock.unique_tab_id(file_id.0);
+        self.data.file_system.request_open_file(tab_id, file_id);
+        let (tab_bar, pos) = dock.find_tab_bar_of_tab(tab_kind.open_next_to).unwrap();
+        dock.create_and_select_tab(cx, tab_bar, tab_id, tab_kind.kind, "".to_string(), live_id!(CloseableTab), Some(pos));
+        // lets scan the entire doc for duplicates
+        self.data.file_system.ensure_unique_tab_names(cx, &dock)
+    }
+    
+    /// The file of a dragged item, for paths dragged from the file tree and for absolute paths
+    /// of workspace files dragged in from outside the app.
+    fn dragged_file_id(&self, item: &DragItem) -> Option<LiveId> {
+        let DragItem::FilePath {path, internal_id: None} = item else {
+            return None
+        };
+        let path = match Path::new(path).strip_prefix(&self.root_path) {
+            Ok(relative) => relative.to_string_lossy().replace('\\', "/"),
+            Err(_) => path.clone()
+        };
+        self.data.file_system.path_to_file_node_id(&path)
+    }
+    
     pub fn open_code_file_by_path(&mut self, cx: &mut Cx, path: &str) {
         if let Some(file_id) = self.data.file_system.path_to_file_node_id(&path) {
-            let dock = self.ui.dock(id!(dock));            
-            let tab_id = dock.unique_tab_id(file_id.0);
-            self.data.file_system.request_open_file(tab_id, file_id);
-            let (tab_bar, pos) = dock.find_tab_bar_of_tab(live_id!(edit_first)).unwrap();
-            dock.create_and_select_tab(cx, tab_bar, tab_id, live_id!(CodeEditor), "".to_string(), live_id!(CloseableTab), Some(pos));
-            self.data.file_system.ensure_unique_tab_names(cx, &dock)
+            self.open_file_tab(cx, file_id);
         }
     }
+    
+    /// Opens the files of the tabs of the restored layout, and closes the tabs of files that
+    /// are gone since.
+    fn reopen_restored_tabs(&mut self, cx: &mut Cx) {
+        if self.restored_tab_sessions.is_empty() {
+            return
+        }
+        let dock = self.ui.dock(id!(dock));
+        for tab_session in std::mem::take(&mut self.restored_tab_sessions) {
+            let tab_id = tab_session.tab_id.0;
+            match self.data.file_system.path_to_file_node_id(&tab_session.path) {
+                Some(file_id) => {
+                    self.data.file_system.request_open_file(tab_id, file_id);
+                    self.data.file_system.restored_sessions.insert(tab_id, tab_session.state);
+                }
+                None => dock.close_tab(cx, tab_id)
+            }
+        }
+        self.data.file_system.ensure_unique_tab_names(cx, &dock)
+    }
+    
+    /// Writes the dock layout and where the open files were at to disk, for the next start.
+    fn save_state(&mut self, mut dock_items: Vec<DockItemStore>) {
+        let tab_kinds = &self.tab_kinds;
+        dock_items.retain(|di| {
+            if let DockItemStore::Tab{kind,..} = di{
+                return tab_kinds.is_persistent(kind.0)
+            }
+            true 
+        });
+        let dock = self.ui.dock(id!(dock));
+        let file_system = &mut self.data.file_system;
+        let tabs: Vec<(LiveId, LiveId)> = file_system.tab_id_to_file_node_id.iter()
+            .map(|(tab_id, file_id)| (*tab_id, *file_id))
+            .collect();
+        let mut tab_sessions = Vec::new();
+        for (tab_id, file_id) in tabs {
+            let Some(path) = file_system.file_node_id_to_path(file_id) else {
+                continue
+            };
+            // a tab that wasn't shown since the start still has the state it was restored with
+            let state = match file_system.restored_sessions.get(&tab_id) {
+                Some(state) => state.clone(),
+                None => match (dock.item(tab_id).studio_editor(id!(editor)).borrow_mut(), file_system.get_session_mut(tab_id)) {
+                    (Some(mut editor), Some(session)) => editor.editor.session_state(session),
+                    _ => continue
+                }
+            };
+            tab_sessions.push(TabSession {tab_id: LiveIdStore(tab_id), path, state});
+        }
+        PersistentState {dock_items, tab_sessions}.save();
+    }
 }
 
 #[derive(Default)]
 pub struct AppData{ 
     pub build_manager: BuildManager,
     pub file_system: FileSystem,
+    pub evaluator: Evaluator,
+    pub navigation_history: NavigationHistory,
+    /// Where language servers, linters and the like offer their quick fixes.
+    pub code_actions: CodeActionRegistry,
 }
 
 // all global app commands coming in from keybindings, and UI components
@@ -91,11 +368,17 @@ pub enum AppAction{
     ReloadFileTree,
     RecompileStarted,
     ClearLog, 
+    PrevEditLocation,
+    NextEditLocation,
+    SaveAllFiles,
+    CopyPanelContents,
     None
 }
 
 impl MatchEvent for App{
     fn handle_startup(&mut self, cx:&mut Cx){
+        // before anything spawns a tool, or a thread
+        import_login_shell_environment();
         let mut root = "./".to_string();
         for arg in std::env::args(){
             if let Some(prefix) = arg.strip_prefix("--root="){
@@ -106,16 +389,29 @@ impl MatchEvent for App{
         let root_path = env::current_dir().unwrap().join(root);
                 
         self.data.file_system.init(cx, &root_path);
+        // a workspace we haven't seen before stays restricted until the user decides
+        self.workspace_trust = WorkspaceTrustSettings::load();
+        self.keymap = Keymap::from_settings(&KeymapSettings::load());
+        self.data.build_manager.trust = self.workspace_trust.trust_for(&root_path);
+        if self.data.build_manager.trust.is_none() {
+            self.ui.view(id!(workspace_trust)).set_visible(true);
+        }
         self.data.build_manager.init(cx, &root_path);
+        self.data.evaluator.init(&root_path);
+        self.root_path = root_path;
+        if let Some(state) = PersistentState::load() {
+            if self.ui.dock(id!(dock)).load_store_items(cx, &state.dock_items) {
+                self.restored_tab_sessions = state.tab_sessions;
+            }
+        }
         //self.data.build_manager.discover_external_ip(cx);
         self.data.build_manager.start_http_server();
     }
     
     fn handle_action(&mut self, cx:&mut Cx, action:&Action){
         let dock = self.ui.dock(id!(dock));
-        let file_tree = self.ui.view(id!(file_tree));
+        let file_tree = self.ui.file_tree(id!(file_tree));
         let log_list = self.ui.log_list(id!(log_list));
-        let run_list = self.ui.view(id!(run_list));
         let profiler = self.ui.view(id!(profiler));
         match action.cast(){
             AppAction::JumpTo(jt)=>{
@@ -133,16 +429,23 @@ impl MatchEvent for App{
                     }
                     else{
                         // lets open the editor
-                        let tab_id = dock.unique_tab_id(file_id.0);
-                        self.data.file_system.request_open_file(tab_id, file_id);
-                        // lets add a file tab 'somewhere'
-                        let (tab_bar, pos) = dock.find_tab_bar_of_tab(live_id!(edit_first)).unwrap();
-                        dock.create_and_select_tab(cx, tab_bar, tab_id, live_id!(StudioEditor), "".to_string(), live_id!(CloseableTab), Some(pos));
-                        // lets scan the entire doc for duplicates
-                        self.data.file_system.ensure_unique_tab_names(cx, &dock)
+                        self.open_file_tab(cx, file_id);
                     }
                 }
             }
+            AppAction::SaveAllFiles=>{
+                self.data.file_system.save_all_dirty_files();
+            }
+            AppAction::PrevEditLocation=>{
+                if let Some((file_id, location)) = self.data.file_system.prev_edit_location(){
+                    self.go_to_edit_location(cx, file_id, location.position);
+                }
+            }
+            AppAction::NextEditLocation=>{
+                if let Some((file_id, location)) = self.data.file_system.next_edit_location(){
+                    self.go_to_edit_location(cx, file_id, location.position);
+                }
+            }
             AppAction::PatchFile(ef)=>{
                 let start = Position{line_index: ef.line as usize, byte_index:ef.column_start as usize};
                 let end = Position{line_index: ef.line as usize, byte_index:ef.column_end as usize};
@@ -207,6 +510,12 @@ impl MatchEvent for App{
             AppAction::RedrawFile(file_id)=>{
                 self.data.file_system.redraw_view_by_file_id(cx, file_id, &dock);
             }
+            AppAction::CopyPanelContents=>{
+                if let Some(tab_id) = dock.tabs_by_recent_use().first().copied() {
+                    let text = self.panel_reading_text(tab_id);
+                    cx.copy_to_clipboard(&text);
+                }
+            }
             AppAction::ClearLog=>{
                 self.data.build_manager.clear_log(cx, &dock, &mut self.data.file_system);
                 log_list.reset_scroll(cx);
@@ -322,7 +631,8 @@ impl MatchEvent for App{
                 
         match action.cast(){
             FileSystemAction::TreeLoaded => {
-                file_tree.redraw(cx);
+                file_tree.refresh(cx);
+                self.reopen_restored_tabs(cx);
                 //self.open_code_file_by_path(cx, "examples/slides/src/app.rs");
             }
             FileSystemAction::RecompileNeeded => {
@@ -333,6 +643,38 @@ impl MatchEvent for App{
                 self.data.build_manager.clear_log(cx, &dock, &mut self.data.file_system);
                 log_list.redraw(cx);
             }
+            FileSystemAction::SaveFailed => {
+                // don't close anything that would lose the changes that didn't make it to disk
+                self.unsaved_changes.cancel(cx, &self.ui);
+            }
+            FileSystemAction::SearchResultsChanged => {
+                self.ui.search_results(id!(search_results)).redraw(cx);
+            }
+            FileSystemAction::ConnectionChanged(state) => {
+                let text = match state {
+                    ConnectionState::Local => String::new(),
+                    ConnectionState::Connecting {attempt: 0} => "Connecting to the file server".to_string(),
+                    ConnectionState::Connecting {attempt} => format!("Reconnecting to the file server (attempt {})", attempt + 1),
+                    ConnectionState::Connected => "Connected to the file server".to_string(),
+                    ConnectionState::Offline {queued: 0} => "Offline, retrying soon".to_string(),
+                    ConnectionState::Offline {queued} => format!("Offline, {} changes waiting to be sent", queued),
+                };
+                self.ui.widget(id!(status_bar.connection)).set_text_and_redraw(cx, &text);
+            }
+            FileSystemAction::WorkspaceEditPreview {id, title, preview} => {
+                // a newer preview replaces one that is still open
+                if let Some(id) = self.previewed_workspace_edit.replace(id) {
+                    self.data.file_system.discard_pending_workspace_edit(id);
+                }
+                self.ui.label(id!(workspace_edit.title)).set_text(&title);
+                self.ui.label(id!(workspace_edit.message)).set_text(&preview);
+                self.ui.view(id!(workspace_edit)).set_visible_and_redraw(cx, true);
+            }
+            FileSystemAction::WorkspaceEditApplied(file_ids) => {
+                for file_id in file_ids {
+                    self.data.file_system.redraw_view_by_file_id(cx, file_id, &dock);
+                }
+            }
             FileSystemAction::None=>()
         }
                 
@@ -353,21 +695,29 @@ impl MatchEvent for App{
         if let Some(action) = action.as_widget_action(){
             match action.cast(){
                 CodeEditorAction::TextDidChange => {
-                    // lets write the file
-                    self.data.file_system.request_save_file_for_tab_id(action.path.from_end(1), false)
+                    // a new edit restarts edit history navigation at the most recent location
+                    self.data.file_system.reset_edit_history_cursor();
+                    // the save policy of the file decides if we write it now
+                    self.data.file_system.handle_text_did_change(cx, action.path.from_end(1))
+                }
+                CodeEditorAction::KeyFocusLost => {
+                    self.data.file_system.handle_key_focus_lost(action.path.from_end(1))
                 }
+                CodeEditorAction::Scrolled => {
+                    self.sync_linked_scroll(cx, action.path.from_end(1))
+                }
+                // the studio editor looks them up and applies them
+                CodeEditorAction::CodeActionsRequested |
+                CodeEditorAction::ApplyCodeAction(_) => {}
+                CodeEditorAction::VimModeChanged(_) => {}
                 CodeEditorAction::None=>{}
             }
             
             match action.cast(){
                 DockAction::TabCloseWasPressed(tab_id)=>{
-                    dock.close_tab(cx, tab_id);
-                    if self.data.build_manager.handle_tab_close(tab_id) {
-                        log_list.redraw(cx);
-                        run_list.redraw(cx);
+                    if self.unsaved_changes.request_close(cx, &self.ui, &[&self.data.file_system], CloseTarget::Tab(tab_id)) {
+                        self.close_tab(cx, tab_id);
                     }
-                    self.data.file_system.remove_tab(tab_id);
-                    self.data.file_system.ensure_unique_tab_names(cx, &dock);
                 }
                 DockAction::ShouldTabStartDrag(tab_id)=>{
                     dock.tab_start_drag(cx, tab_id, DragItem::FilePath {
@@ -376,35 +726,48 @@ impl MatchEvent for App{
                     });
                 }
                 DockAction::Drag(drag_event)=>{
-                    if drag_event.items.len() == 1 {
-                        if drag_event.modifiers.logo {
+                    if let [DragItem::FilePath {internal_id: Some(_), ..}] = drag_event.items.as_slice() {
+                        if drag_event.modifiers.shift { // floats the tab where it is dropped
+                            drag_event.response.set(DragResponse::Move);
+                        }
+                        else if drag_event.modifiers.logo {
                             dock.accept_drag(cx, drag_event, DragResponse::Copy);
                         }
                         else {
                             dock.accept_drag(cx, drag_event, DragResponse::Move);
                         }
                     }
+                    else if drag_event.items.iter().any( | item | self.dragged_file_id(item).is_some()) {
+                        dock.accept_drag(cx, drag_event, DragResponse::Copy);
+                    }
                 }
                 DockAction::Drop(drop_event)=>{
-                    if let DragItem::FilePath {path, internal_id} = &drop_event.items[0] {
-                        if let Some(internal_id) = internal_id { // from inside the dock
-                            if drop_event.modifiers.logo {
-                                let tab_id = dock.unique_tab_id(internal_id.0);
-                                dock.drop_clone(cx, drop_event.abs, *internal_id, tab_id, live_id!(CloseableTab));
-                            }
-                            else {
-                                dock.drop_move(cx, drop_event.abs, *internal_id);
-                            }
-                            self.data.file_system.ensure_unique_tab_names(cx, &dock);
+                    if let [DragItem::FilePath {internal_id: Some(internal_id), ..}] = drop_event.items.as_slice() { // from inside the dock
+                        if drop_event.modifiers.shift {
+                            dock.drop_float(cx, drop_event.abs, *internal_id);
                         }
-                        else { // external file, we have to create a new tab
-                            if let Some(file_id) = self.data.file_system.path_to_file_node_id(&path) {
+                        else if drop_event.modifiers.logo {
+                            let tab_id = dock.unique_tab_id(internal_id.0);
+                            dock.drop_clone(cx, drop_event.abs, *internal_id, tab_id, live_id!(CloseableTab));
+                        }
+                        else {
+                            dock.drop_move(cx, drop_event.abs, *internal_id);
+                        }
+                        self.data.file_system.ensure_unique_tab_names(cx, &dock);
+                    }
+                    else { // files from the file tree or from outside, each gets a new tab
+                        for item in drop_event.items.iter() {
+                            let Some(file_id) = self.dragged_file_id(item) else {
+                                continue
+                            };
+                            let path = self.data.file_system.file_node_path(file_id);
+                            if let Some(tab_kind) = self.tab_kinds.for_file(&path) {
                                 let tab_id = dock.unique_tab_id(file_id.0);
                                 self.data.file_system.request_open_file(tab_id, file_id);
-                                dock.drop_create(cx, drop_event.abs, tab_id, live_id!(StudioEditor), "".to_string(), live_id!(CloseableTab));
-                                self.data.file_system.ensure_unique_tab_names(cx, &dock)
+                                dock.drop_create(cx, drop_event.abs, tab_id, tab_kind.kind, "".to_string(), live_id!(CloseableTab));
                             }
                         }
+                        self.data.file_system.ensure_unique_tab_names(cx, &dock)
                     }
                 },
                 _=>()
@@ -415,28 +778,111 @@ impl MatchEvent for App{
     fn handle_key_down(&mut self, cx: &mut Cx, event: &KeyEvent){
         let KeyEvent {
             key_code,
-            modifiers: KeyModifiers {logo, control, ..},
+            modifiers: KeyModifiers {control, ..},
             ..
         } = event;
-        if *control || *logo {
-            if let KeyCode::Backtick = key_code {
-                cx.action(AppAction::StartRecompile)
+        let tab_switcher = self.ui.tab_switcher(id!(tab_switcher));
+        if tab_switcher.is_open() {
+            if let KeyCode::ReturnKey = key_code {
+                if let Some(tab_id) = tab_switcher.close(cx) {
+                    self.switch_to_tab(cx, tab_id);
+                }
+            }
+            else {
+                tab_switcher.handle_key_down(cx, event);
             }
-            else if let KeyCode::KeyK = key_code {
-                cx.action(AppAction::ClearLog)
+            return
+        }
+        if *control && *key_code == KeyCode::Tab {
+            self.open_tab_switcher(cx);
+            return
+        }
+        let was_pending = self.keymap.is_pending();
+        match self.keymap.handle_key_down(event) {
+            KeymapResult::Command(command) => {
+                if was_pending {
+                    self.end_key_sequence(cx);
+                }
+                self.run_command(cx, &command);
             }
-            else if let KeyCode::KeyR = key_code{
-                cx.action(AppAction::ReloadFileTree)
+            KeymapResult::Pending => {
+                // the keys of a sequence aren't typed into the editor
+                if !was_pending {
+                    cx.set_key_focus(Area::Empty);
+                }
+                cx.stop_timer(self.keymap_timer);
+                self.keymap_timer = cx.start_timeout(self.keymap.timeout);
+                self.ui.key_hints(id!(key_hints)).show(cx, self.keymap.pending_keys(), self.keymap.hints());
+            }
+            KeymapResult::Unbound => if was_pending {
+                self.end_key_sequence(cx);
+            }
+        }
+    }
+    
+    fn handle_key_up(&mut self, cx: &mut Cx, event: &KeyEvent){
+        // letting go of Ctrl picks the tab selected in the switcher
+        if let KeyCode::Control = event.key_code {
+            if let Some(tab_id) = self.ui.tab_switcher(id!(tab_switcher)).close(cx) {
+                self.switch_to_tab(cx, tab_id);
             }
         }
     }
     
+    fn handle_navigate(&mut self, cx: &mut Cx, event: &NavigateEvent){
+        let location = match event.direction {
+            NavigateDirection::Back => self.data.navigation_history.back(event.window_id),
+            NavigateDirection::Forward => self.data.navigation_history.forward(event.window_id),
+        };
+        if let Some(location) = location {
+            self.go_to_edit_location(cx, location.file_id, location.position);
+        }
+    }
+    
     fn handle_actions(&mut self, cx: &mut Cx, actions:&Actions){
         let file_tree = self.ui.file_tree(id!(file_tree));
-        let dock = self.ui.dock(id!(dock));
         for action in actions{
             self.handle_action(cx, action);
         }
+        if self.ui.button(id!(workspace_trust.trust_button)).clicked(&actions) {
+            self.set_workspace_trust(cx, WorkspaceTrust::Trusted);
+        }
+        if self.ui.button(id!(workspace_trust.restrict_button)).clicked(&actions) {
+            self.set_workspace_trust(cx, WorkspaceTrust::Restricted);
+        }
+        if self.ui.button(id!(workspace_edit.apply_button)).clicked(&actions) {
+            if let Some(id) = self.previewed_workspace_edit.take() {
+                self.data.file_system.apply_pending_workspace_edit(cx, id);
+            }
+            self.ui.view(id!(workspace_edit)).set_visible_and_redraw(cx, false);
+        }
+        if self.ui.button(id!(workspace_edit.cancel_button)).clicked(&actions) {
+            if let Some(id) = self.previewed_workspace_edit.take() {
+                self.data.file_system.discard_pending_workspace_edit(id);
+            }
+            self.ui.view(id!(workspace_edit)).set_visible_and_redraw(cx, false);
+        }
+        let search_input = self.ui.text_input(id!(search_input));
+        if search_input.returned(&actions).is_some() {
+            self.run_search(cx, SearchScope::Workspace);
+        }
+        else if search_input.changed(&actions).is_some() || [id!(search_match_case), id!(search_whole_word), id!(search_regex)].iter().any( | id | self.ui.check_box(*id).changed(&actions).is_some()) {
+            self.run_search(cx, SearchScope::OpenFiles);
+        }
+        let mut sync_scroll_tab_id = None;
+        if let Some(mut dock) = self.ui.dock(id!(dock)).borrow_mut() {
+            for (tab_id, (_, item)) in dock.items().iter() {
+                if item.button(id!(sync_scroll_button)).clicked(&actions) {
+                    sync_scroll_tab_id = Some(*tab_id);
+                }
+            }
+        }
+        if let Some(tab_id) = sync_scroll_tab_id {
+            self.toggle_scroll_link(tab_id);
+        }
+        if let Some(target) = self.unsaved_changes.handle_actions(cx, &self.ui, actions, &mut [&mut self.data.file_system]) {
+            self.close_target(cx, target);
+        }
         if let Some(file_id) = file_tree.should_file_start_drag(&actions) {
             let path = self.data.file_system.file_node_path(file_id);
             file_tree.file_start_drag(cx, file_id, DragItem::FilePath {
@@ -447,14 +893,7 @@ impl MatchEvent for App{
                             
         if let Some(file_id) = file_tree.file_clicked(&actions) {
             // ok lets open the file
-            let tab_id = dock.unique_tab_id(file_id.0);
-            self.data.file_system.request_open_file(tab_id, file_id);
-            // lets add a file tab 'some
-            let (tab_bar, pos) = dock.find_tab_bar_of_tab(live_id!(edit_first)).unwrap();
-            dock.create_and_select_tab(cx, tab_bar, tab_id, live_id!(StudioEditor), "".to_string(), live_id!(CloseableTab), Some(pos));
-                                        
-            // lets scan the entire doc for duplicates
-            self.data.file_system.ensure_unique_tab_names(cx, &dock)
+            self.open_file_tab(cx, file_id);
         }
     }
     
@@ -466,10 +905,49 @@ impl MatchEvent for App{
 impl AppMain for App {
     
     fn handle_event(&mut self, cx: &mut Cx, event: &Event) {
+        match event {
+            // closing a window opened for another one, like a torn out dock panel, keeps the app running
+            Event::WindowCloseRequested(e) if cx.windows[e.window_id].owner.is_none() => {
+                if !self.unsaved_changes.request_close(cx, &self.ui, &[&self.data.file_system], CloseTarget::App) {
+                    e.accept_close.set(false);
+                }
+            }
+            Event::AppQuitRequested(e) => {
+                if !self.unsaved_changes.request_close(cx, &self.ui, &[&self.data.file_system], CloseTarget::App) {
+                    e.accept_quit.set(false);
+                }
+            }
+            _ => ()
+        }
+        if self.keymap_timer.is_event(event).is_some() {
+            let command = self.keymap.timeout();
+            self.end_key_sequence(cx);
+            if let Some(command) = command {
+                self.run_command(cx, &command);
+            }
+        }
         self.match_event(cx, event);
         self.ui.handle_event(cx, event, &mut Scope::with_data(&mut self.data));
         
         self.data.file_system.handle_event(cx, event, &self.ui);
+        if let Some(target) = self.unsaved_changes.poll_saved(&mut [&mut self.data.file_system]) {
+            self.close_target(cx, target);
+        }
+        for evaluation in self.data.evaluator.handle_event(event) {
+            // evaluation results go to the console, below the expression they belong to
+            let log = &mut self.data.build_manager.log;
+            log.push((live_id!(evaluator), LogItem::Bare(LogItemBare {
+                level: LogLevel::Log,
+                line: format!("> {}", evaluation.input.lines().next().unwrap_or(""))
+            })));
+            for line in evaluation.output.lines() {
+                log.push((live_id!(evaluator), LogItem::Bare(LogItemBare {
+                    level: if evaluation.success {LogLevel::Log} else {LogLevel::Error},
+                    line: line.to_string()
+                })));
+            }
+            cx.action(AppAction::RedrawLog);
+        }
         self.data.build_manager.handle_event(cx, event, &mut self.data.file_system); 
 
         // process events on all run_views
@@ -483,27 +961,15 @@ impl AppMain for App {
             }
         }*/
          
-        if let Some(mut dock_items) = dock.needs_save(){
-            dock_items.retain(|di| {
-                if let DockItemStore::Tab{kind,..} = di{
-                    if kind.0 == live_id!(RunView){
-                        return false
-                    }
-                }
-                true 
-            }); 
-            let state = PersistentState{
-                dock_items
-            };
-            // alright lets save it to disk
-            let saved = state.serialize_ron();
-            let mut f = File::create("makepad_state.ron").expect("Unable to create file");
-            f.write_all(saved.as_bytes()).expect("Unable to write data");
+        if let Some(dock_items) = dock.needs_save(){
+            self.save_state(dock_items);
+        }
+        else if let Event::Shutdown = event {
+            // cursors and scrolling don't change the layout, so they are saved on the way out
+            let dock_items = dock.borrow().map(|dock| dock.to_store_items());
+            if let Some(dock_items) = dock_items {
+                self.save_state(dock_items);
+            }
         }
     }
 }
-
-#[derive(Clone, Debug, SerRon, DeRon)]
-struct PersistentState{
-    dock_items: Vec<DockItemStore>
-}
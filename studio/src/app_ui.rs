@@ -10,8 +10,12 @@ live_design!{
     import makepad_studio::log_list::LogList;
     import makepad_studio::run_list::RunList;
     import makepad_studio::profiler::Profiler;
+    import makepad_studio::tab_switcher::TabSwitcher;
+    import makepad_studio::search_results::SearchResults;
+    import makepad_studio::key_hints::KeyHints;
 
     ICO_SEARCH = dep("crate://self/resources/icons/Icon_Search.svg")
+    STATUS_BAR_HEIGHT = 22.0
 
     Logo = <Button> {
         draw_icon: {
@@ -112,6 +116,86 @@ live_design!{
         }
     }
 
+    StudioDialog = <View> {
+        visible: false,
+        width: Fill, height: Fill,
+        align: { x: 0.5, y: 0.3 }
+        show_bg: true,
+        draw_bg: { color: #0008 }
+        dialog = <RoundedView> {
+            width: 420., height: Fit,
+            flow: Down,
+            spacing: (THEME_SPACE_2),
+            padding: <THEME_MSPACE_3> {}
+            draw_bg: { color: (THEME_COLOR_BG_CONTAINER), radius: 4. }
+            title = <H3> { text: "" }
+            message = <P> {
+                draw_text: { wrap: Word }
+                text: ""
+            }
+            buttons = <View> {
+                width: Fill, height: Fit,
+                flow: Right,
+                spacing: (THEME_SPACE_2),
+                align: { x: 1.0 }
+            }
+        }
+    }
+
+    UnsavedChangesDialog = <StudioDialog> {
+        dialog = {
+            title = { text: "Unsaved changes" }
+            buttons = {
+                discard_button = <Button> { text: "Don't Save" }
+                cancel_button = <Button> { text: "Cancel" }
+                save_button = <Button> { text: "Save" }
+            }
+        }
+    }
+
+    WorkspaceTrustDialog = <StudioDialog> {
+        dialog = {
+            title = { text: "Do you trust the authors of this workspace?" }
+            message = {
+                text: "Building and running the workspace executes its build scripts and macros. In restricted mode you can browse and edit the files, but nothing is run."
+            }
+            buttons = {
+                restrict_button = <Button> { text: "Stay in Restricted Mode" }
+                trust_button = <Button> { text: "Trust" }
+            }
+        }
+    }
+
+    WorkspaceEditDialog = <StudioDialog> {
+        dialog = {
+            width: 640.,
+            message = {
+                draw_text: {
+                    text_style: <THEME_FONT_CODE> {},
+                    wrap: Line
+                }
+            }
+            buttons = {
+                cancel_button = <Button> { text: "Cancel" }
+                apply_button = <Button> { text: "Apply" }
+            }
+        }
+    }
+
+    StatusBar = <View> {
+        width: Fill, height: Fill,
+        align: { x: 0.0, y: 1.0 }
+        bar = <View> {
+            width: Fill, height: (STATUS_BAR_HEIGHT),
+            flow: Right,
+            align: { x: 1.0, y: 0.5 }
+            padding: { left: (THEME_SPACE_2), right: (THEME_SPACE_2) }
+            show_bg: true,
+            draw_bg: { color: (THEME_COLOR_BG_CONTAINER) }
+            connection = <P> { width: Fit, text: "", draw_text: { color: (THEME_COLOR_D_4) } }
+        }
+    }
+
     AppUI =  <Window> {
         margin: 5.
         caption_bar = { margin: {left: -100}, visible: true, caption_label = {label = {text: "Makepad Studio"}} },
@@ -165,8 +249,9 @@ live_design!{
 
             line = Line,
         }
-        body = {dock = <Dock> {
+        body = {flow: Overlay, dock = <Dock> {
             width: Fill, height: Fill,
+            margin: { bottom: (STATUS_BAR_HEIGHT) }
             tab_bar:{
                 OutlineFirstTab = <IconTab> {
                     spacing: (THEME_SPACE_2)
@@ -397,6 +482,7 @@ live_design!{
                         <ButtonFlat> { width: Fit, text: "Search"}
                         <ButtonFlat> { width: Fit, text: "Debug"}
                         <Filler> {}
+                        sync_scroll_button = <ButtonFlat> { width: Fit, text: "Sync Scroll"}
                         <ButtonFlat> { width: Fit, text: "Docs"}
                     }
                 }
@@ -477,12 +563,12 @@ live_design!{
                     content = {
                         padding: { right: (THEME_SPACE_2) }
                         spacing: (THEME_SPACE_2)
-                        <TextInput> {
+                        search_input = <TextInput> {
                             width: Fill,
                             empty_message: "Search",
                         }
 
-                        <CheckBoxCustom> {
+                        search_match_case = <CheckBoxCustom> {
                             text:""
                             draw_check: { check_type: None }
                             icon_walk: {width: 14.}
@@ -492,7 +578,7 @@ live_design!{
                                 svg_file: dep("crate://self/resources/icons/icon_search_case_sensitive.svg"),
                             }
                         }
-                        <CheckBoxCustom> {
+                        search_whole_word = <CheckBoxCustom> {
                             text:""
                             draw_check: { check_type: None }
                             icon_walk: {width: 16.}
@@ -502,7 +588,7 @@ live_design!{
                                 svg_file: dep("crate://self/resources/icons/icon_search_full_word.svg"),
                             }
                         }
-                        <CheckBoxCustom> {
+                        search_regex = <CheckBoxCustom> {
                             text:""
                             draw_check: { check_type: None }
                             icon_walk: {width: 12.}
@@ -514,11 +600,7 @@ live_design!{
                         }
                     }
                 }
-                <View> {
-                    flow: Down
-                    margin: <THEME_MSPACE_2> {}
-                    <P> { text: "this does not work yet." }
-                }
+                search_results = <SearchResults> {}
             }
             RunView = <RunView> {}
             StudioFileTree = <View> {
@@ -676,6 +758,6 @@ live_design!{
                 }
                 <Profiler> {}
             }
-        }}
+        }, status_bar = <StatusBar> {}, unsaved_changes = <UnsavedChangesDialog> {}, workspace_trust = <WorkspaceTrustDialog> {}, workspace_edit = <WorkspaceEditDialog> {}, tab_switcher = <TabSwitcher> {}, key_hints = <KeyHints> {}}
     }
 }
\ No newline at end of file
@@ -17,17 +17,21 @@ use {
         makepad_platform::studio::{AppToStudio,AppToStudioVec,EventSample, GPUSample, StudioToAppVec, StudioToApp},
         build_manager::{
             build_protocol::*,
-            build_client::BuildClient
+            build_client::BuildClient,
+            tasks::Task,
         },
         app::AppAction,
+        workspace_trust::WorkspaceTrust,
+        tool_environment::{find_in_path, spawn_error_message, ToolSettings},
+        settings::SettingsFile,
         makepad_shell::*,
     },
-    makepad_code_editor::{text, decoration::{Decoration, DecorationType}},
+    makepad_code_editor::{text, code_action::Diagnostic, decoration::{Decoration, DecorationType}},
     makepad_http::server::*,
     std::{
         sync::{Arc,Mutex},
         cell::RefCell,
-        collections::{HashMap,hash_map},
+        collections::{HashMap,HashSet,hash_map},
         io::prelude::*,
         path::PathBuf,
         path::Path,
@@ -125,6 +129,11 @@ pub struct BuildManager {
     recompile_timer: Timer,
     pub binaries: Vec<BuildBinary>,
     pub active: ActiveBuilds,
+    /// The tasks of the project, from its tasks file.
+    pub tasks: Vec<Task>,
+    pub active_tasks: HashSet<LiveId>,
+    /// Nothing gets built or run until the workspace is trusted.
+    pub trust: Option<WorkspaceTrust>,
     pub studio_http: String,
     pub recv_studio_msg: ToUIReceiver<(LiveId,AppToStudioVec)>,
     pub recv_external_ip: ToUIReceiver<SocketAddr>,
@@ -159,17 +168,58 @@ impl BuildManager {
         self.root_path = path.to_path_buf();
         self.clients = vec![BuildClient::new_with_local_server(&self.root_path)];
         
-        self.update_run_list(cx);
+        if self.is_trusted() {
+            self.update_run_list(cx);
+        }
         //self.recompile_timer = cx.start_timeout(self.recompile_timeout);
     }
     
+    /// Whether code from the workspace may be run, which it may not until the user decides.
+    pub fn is_trusted(&self) -> bool {
+        self.trust == Some(WorkspaceTrust::Trusted)
+    }
+    
+    pub fn set_trust(&mut self, cx: &mut Cx, trust: WorkspaceTrust) {
+        self.trust = Some(trust);
+        if self.is_trusted() {
+            // listing the binaries runs cargo, which we only do now
+            self.update_run_list(cx);
+        }
+        else {
+            self.clear_active_builds();
+            self.binaries.clear();
+            self.tasks.clear();
+        }
+    }
+    
     pub fn send_host_to_stdin(&self, item_id: LiveId, msg: HostToStdin) {
         self.clients[0].send_cmd_with_id(item_id, BuildCmd::HostToStdin(msg.to_json()));
     }
     
-    pub fn update_run_list(&mut self, _cx: &mut Cx) {
+    pub fn update_run_list(&mut self, cx: &mut Cx) {
         self.binaries.clear();
-        match shell_env_cap(&[], &self.root_path, "cargo", &["run", "--bin"]) {
+        match Task::load_all(&self.root_path) {
+            Ok(tasks) => self.tasks = tasks,
+            Err(err) => {
+                self.tasks.clear();
+                self.log.push((LiveId(0), LogItem::Bare(LogItemBare {
+                    level: LogLevel::Error,
+                    line: format!("{}: {}", Task::TASKS_FILE, err)
+                })));
+                cx.action(AppAction::RedrawLog)
+            }
+        }
+        let cargo = ToolSettings::load().resolve("cargo", &[]);
+        if find_in_path(&cargo.command).is_none() {
+            // otherwise the run list just stays empty without saying why
+            self.log.push((LiveId(0), LogItem::Bare(LogItemBare {
+                level: LogLevel::Error,
+                line: spawn_error_message(&cargo.command, &std::io::ErrorKind::NotFound.into())
+            })));
+            cx.action(AppAction::RedrawLog);
+            return
+        }
+        match shell_env_cap(&cargo.env_refs(), &self.root_path, &cargo.command, &["run", "--bin"]) {
             Ok(_) => {}
             // we expect it on stderr
             Err(e) => {
@@ -214,6 +264,9 @@ impl BuildManager {
     }
     
     pub fn start_recompile(&mut self, _cx: &mut Cx) {
+        if !self.is_trusted() {
+            return
+        }
         // alright so. a file was changed. now what.
         for (build_id, active_build) in &mut self.active.builds {
             self.clients[0].send_cmd_with_id(*build_id, BuildCmd::Stop);
@@ -231,6 +284,32 @@ impl BuildManager {
             self.clients[0].send_cmd_with_id(*build_id, BuildCmd::Stop);
         }
         self.active.builds.clear();
+        for task_id in self.active_tasks.drain() {
+            self.clients[0].send_cmd_with_id(task_id, BuildCmd::Stop);
+        }
+    }
+    
+    /// Runs a task of the project, restarting it if it already runs.
+    pub fn start_task(&mut self, name: &str) {
+        if !self.is_trusted() {
+            return
+        }
+        let Some(task) = self.tasks.iter().find( | task | task.name == name) else {
+            return
+        };
+        let task_id = task.as_id();
+        if self.active_tasks.contains(&task_id) {
+            self.clients[0].send_cmd_with_id(task_id, BuildCmd::Stop);
+        }
+        self.clients[0].send_cmd_with_id(task_id, BuildCmd::RunTask(task.clone()));
+        self.active_tasks.insert(task_id);
+    }
+    
+    pub fn stop_task(&mut self, name: &str) {
+        let task_id = Task::id_from_name(name);
+        if self.active_tasks.remove(&task_id) {
+            self.clients[0].send_cmd_with_id(task_id, BuildCmd::Stop);
+        }
     }
     
     pub fn clear_log(&mut self, cx: &mut Cx, dock: &DockRef, file_system: &mut FileSystem) {
@@ -241,6 +320,27 @@ impl BuildManager {
         self.profile.clear();
     }
     
+    /// The errors and warnings in the log for a file, for the code actions at the cursor.
+    pub fn diagnostics_for_file(&self, file_name: &str) -> Vec<Diagnostic> {
+        self.log.iter().filter_map( | (_, item) | {
+            let LogItem::Location(loc) = item else {
+                return None
+            };
+            let ty = match loc.level {
+                LogLevel::Error => DecorationType::Error,
+                LogLevel::Warning => DecorationType::Warning,
+                _ => return None
+            };
+            (loc.file_name == file_name).then(|| Diagnostic {
+                ty,
+                start: loc.start,
+                end: loc.end,
+                message: loc.message.clone(),
+                fixes: loc.fixes.clone(),
+            })
+        }).collect()
+    }
+    
     pub fn start_recompile_timer(&mut self, cx: &mut Cx) {
         cx.stop_timer(self.recompile_timer);
         self.recompile_timer = cx.start_timeout(self.recompile_timeout);
@@ -383,7 +483,8 @@ impl BuildManager {
                                 file_name: item.file_name,
                                 start,
                                 end,
-                                message: item.message
+                                message: item.message,
+                                fixes: Vec::new()
                             })));
                             cx.action(AppAction::RedrawLog)
                         }
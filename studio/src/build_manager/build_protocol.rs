@@ -1,7 +1,8 @@
 use crate::{
     makepad_live_id::LiveId,
     makepad_platform::log::LogLevel,
-    makepad_code_editor::text::{Position},
+    makepad_code_editor::{code_action::CodeAction, text::Position},
+    build_manager::tasks::Task,
 };
 
 
@@ -132,6 +133,7 @@ pub struct BuildCmdWrap {
 pub enum BuildCmd {
     Stop,
     Run(BuildProcess, String),
+    RunTask(Task),
     HostToStdin(String)
 }
 
@@ -147,7 +149,9 @@ pub struct LogItemLocation{
     pub file_name: String,
     pub start: Position,
     pub end: Position,
-    pub message: String
+    pub message: String,
+    /// What the tool that reported it suggests doing about it.
+    pub fixes: Vec<CodeAction>
 }
 
 #[derive(Clone, Debug)]
@@ -1,6 +1,6 @@
 use {
     crate::{
-        makepad_code_editor::text::{Position},
+        makepad_code_editor::{code_action::CodeAction, text::Position},
         makepad_micro_serde::*,
         makepad_live_id::*,
         makepad_platform::log::LogLevel,
@@ -12,7 +12,10 @@ use {
                 ChildStdIO
             },
             rustc_json::*,
+            tasks::Task,
         },
+        tool_environment::{spawn_error_message, ToolSettings},
+        settings::SettingsFile,
     },
     std::{
         collections::HashMap,
@@ -24,15 +27,15 @@ use {
 };
 
 struct BuildServerProcess {
-    cmd_id: LiveId,
     stdin_sender: Mutex<Sender<ChildStdIn> >,
     line_sender: Mutex<Sender<ChildStdIO> >,
 }
 
 struct BuildServerShared {
     path: PathBuf,
+    tools: ToolSettings,
     // here we should store our connections send slots
-    processes: HashMap<BuildProcess, BuildServerProcess>
+    processes: HashMap<LiveId, BuildServerProcess>
 }
 
 pub struct BuildServer {
@@ -41,9 +44,11 @@ pub struct BuildServer {
 
 impl BuildServer {
     pub fn new<P: Into<PathBuf >> (path: P) -> BuildServer {
+        let path = path.into();
         BuildServer {
             shared: Arc::new(RwLock::new(BuildServerShared {
-                path: path.into(),
+                tools: ToolSettings::load(),
+                path,
                 processes: Default::default()
             })),
         }
@@ -78,7 +83,7 @@ impl BuildConnection {
         let shared = self.shared.clone();
         
         let shared = shared.write().unwrap();
-        if let Some(proc) = shared.processes.values().find(|v| v.cmd_id == cmd_id) {
+        if let Some(proc) = shared.processes.get(&cmd_id) {
             let line_sender = proc.line_sender.lock().unwrap();
             let _ = line_sender.send(ChildStdIO::Kill);
         }
@@ -88,7 +93,10 @@ impl BuildConnection {
         let shared = self.shared.clone();
         let msg_sender = self.msg_sender.clone();
         // alright lets run a cargo check and parse its output
-        let path = shared.read().unwrap().path.clone();
+        let (path, cargo) = {
+            let shared = shared.read().unwrap();
+            (shared.path.clone(), shared.tools.resolve("cargo", &[]))
+        };
         
         let args: Vec<String> = match &what.target {
             BuildTarget::ReleaseStudio => vec![
@@ -261,12 +269,18 @@ impl BuildConnection {
             env.push(("RUSTUP_TOOLCHAIN", "nightly"));
         }
 
-        let process = ChildProcess::start("cargo", &args, path, &env, is_in_studio).expect("Cannot start process");
+        env.extend(cargo.env_refs());
+        let process = match ChildProcess::start(&cargo.command, &args, path, &env, is_in_studio) {
+            Ok(process) => process,
+            Err(err) => {
+                msg_sender.send_bare_message(cmd_id, LogLevel::Error, spawn_error_message(&cargo.command, &err));
+                return
+            }
+        };
 
         shared.write().unwrap().processes.insert(
-            what,
+            cmd_id,
             BuildServerProcess {
-                cmd_id,
                 stdin_sender: Mutex::new(process.stdin_sender.clone()),
                 line_sender: Mutex::new(process.line_sender.clone()),
             }
@@ -348,12 +362,64 @@ impl BuildConnection {
         });
     }
     
+    pub fn run_task(&self, task: Task, cmd_id: LiveId) {
+        let shared = self.shared.clone();
+        let msg_sender = self.msg_sender.clone();
+        let (root, tool) = {
+            let shared = shared.read().unwrap();
+            (shared.path.clone(), shared.tools.resolve(&task.command, &[&task.name]))
+        };
+        
+        let mut env: Vec<(&str, &str)> = task.env.iter().map( | (key, value) | (key.as_str(), value.as_str())).collect();
+        env.extend(tool.env_refs());
+        let process = match ChildProcess::start(&tool.command, &task.args, task.cwd_path(&root), &env, false) {
+            Ok(process) => process,
+            Err(err) => {
+                msg_sender.send_bare_message(cmd_id, LogLevel::Error, format!("task {}: {}", task.name, spawn_error_message(&tool.command, &err)));
+                return
+            }
+        };
+        
+        shared.write().unwrap().processes.insert(
+            cmd_id,
+            BuildServerProcess {
+                stdin_sender: Mutex::new(process.stdin_sender.clone()),
+                line_sender: Mutex::new(process.line_sender.clone()),
+            }
+        );
+        
+        std::thread::spawn(move || {
+            while let Ok(line) = process.line_receiver.recv() {
+                match line {
+                    ChildStdIO::StdOut(line) | ChildStdIO::StdErr(line) => {
+                        if let Some(problem) = task.match_line(&root, &line) {
+                            msg_sender.send_location_msg(cmd_id, problem.level, problem.file_name, problem.start, problem.end, problem.message, Vec::new());
+                        }
+                        else {
+                            msg_sender.send_bare_message(cmd_id, LogLevel::Log, line);
+                        }
+                    }
+                    ChildStdIO::Term => {
+                        msg_sender.send_bare_message(cmd_id, LogLevel::Log, format!("task {} finished", task.name));
+                        break;
+                    }
+                    ChildStdIO::Kill => {
+                        return process.kill();
+                    }
+                }
+            };
+        });
+    }
+    
     pub fn handle_cmd(&self, cmd_wrap: BuildCmdWrap) {
         match cmd_wrap.cmd {
             BuildCmd::Run(process, http) => {
                 // lets kill all other 'whats'
                 self.run(process, cmd_wrap.cmd_id, http);
             }
+            BuildCmd::RunTask(task) => {
+                self.run_task(task, cmd_wrap.cmd_id);
+            }
             BuildCmd::Stop => {
                 // lets kill all other 'whats'
                 self.stop(cmd_wrap.cmd_id);
@@ -362,13 +428,10 @@ impl BuildConnection {
                 // ok lets fetch the running process from the cmd_id
                 // and plug this msg on the standard input as serialiser json
                 if let Ok(shared) = self.shared.read() {
-                    for v in shared.processes.values() {
-                        if v.cmd_id == cmd_wrap.cmd_id {
-                            // lets send it on sender
-                            if let Ok(stdin_sender) = v.stdin_sender.lock() {
-                                let _ = stdin_sender.send(ChildStdIn::Send(msg));
-                            }
-                            break;
+                    if let Some(v) = shared.processes.get(&cmd_wrap.cmd_id) {
+                        // lets send it on sender
+                        if let Ok(stdin_sender) = v.stdin_sender.lock() {
+                            let _ = stdin_sender.send(ChildStdIn::Send(msg));
                         }
                     }
                 }
@@ -401,7 +464,7 @@ pub trait MsgSender: Send {
     }
     
 
-    fn send_location_msg(&self, cmd_id: LiveId, level: LogLevel, file_name: String, start: Position, end: Position, message: String) {
+    fn send_location_msg(&self, cmd_id: LiveId, level: LogLevel, file_name: String, start: Position, end: Position, message: String, fixes: Vec<CodeAction>) {
         self.send_message(
             BuildClientMessageWrap{
                 cmd_id,
@@ -410,7 +473,8 @@ pub trait MsgSender: Send {
                 file_name: file_name.replace("\\","/"),
                 start,
                 end,
-                message
+                message,
+                fixes
             }))
         });
     }
@@ -436,7 +500,7 @@ pub trait MsgSender: Send {
             }
             if let Some(span) = msg.spans.iter().find( | span | span.is_primary) {
                
-                self.send_location_msg(cmd_id, level, span.file_name.clone(),span.start(), span.end(), msg.message.clone());
+                self.send_location_msg(cmd_id, level, span.file_name.clone(),span.start(), span.end(), msg.message.clone(), msg.fixes());
                 /*
                 if let Some(label) = &span.label {
                     self.send_location_msg(cmd_id, level, span.file_name.clone(), range, label.clone());
@@ -4,4 +4,5 @@ pub mod build_server;
 pub mod build_manager;
 pub mod child_process;
 pub mod rustc_json;
+pub mod tasks;
  
\ No newline at end of file
@@ -1,7 +1,10 @@
 #![allow(dead_code)]
 use crate::{
     makepad_micro_serde::*,
-    makepad_code_editor::text::{Length, Position},
+    makepad_code_editor::{
+        code_action::{CodeAction, CodeActionKind, FileEdit, TextEdit, WorkspaceEdit},
+        text::{Length, Position},
+    },
 };
 
 // rust compiler output json structs
@@ -87,6 +90,49 @@ pub struct RustcMessage {
     pub rendered: Option<String>
 }
 
+impl RustcMessage {
+    /// The suggestions of the compiler that are meant to be applied as they are, like removing an
+    /// unused import. The replacements of a help message make up one fix, and can be in several
+    /// files.
+    pub fn fixes(&self) -> Vec<CodeAction> {
+        self.children.iter().filter_map( | child | {
+            let spans: Vec<&RustcSpan> = child.spans.iter().filter( | span | {
+                span.suggested_replacement.is_some() && matches!(
+                    span.suggestion_applicability.as_deref(),
+                    Some("MachineApplicable") | Some("MaybeIncorrect")
+                )
+            }).collect();
+            if spans.is_empty() {
+                return None
+            }
+            let mut edit = WorkspaceEdit::default();
+            for span in &spans {
+                let file_name = span.file_name.replace('\\', "/");
+                let text_edit = TextEdit {
+                    start: span.start(),
+                    end: span.end(),
+                    text: span.suggested_replacement.clone().unwrap(),
+                };
+                match edit.files.iter_mut().find( | file | file.file_name == file_name) {
+                    Some(file) => file.edits.push(text_edit),
+                    None => edit.files.push(FileEdit {file_name, edits: vec![text_edit]})
+                }
+            }
+            // "consider borrowing here" says more with what it would write
+            let title = match spans.as_slice() {
+                [span] => match span.suggested_replacement.as_deref() {
+                    Some(replacement) if !replacement.is_empty() && !replacement.contains('\n') && replacement.len() <= 40 => {
+                        format!("{}: `{}`", child.message, replacement)
+                    }
+                    _ => child.message.clone()
+                }
+                _ => child.message.clone()
+            };
+            Some(CodeAction {title, kind: CodeActionKind::QuickFix, edit})
+        }).collect()
+    }
+}
+
 #[derive(Clone, DeJson, Debug, Default)]
 pub struct RustcProfile {
     pub opt_level: String,
@@ -0,0 +1,223 @@
+use {
+    crate::{
+        makepad_code_editor::{regex::Regex, text::Position},
+        makepad_live_id::LiveId,
+        makepad_platform::log::LogLevel,
+        makepad_toml_parser::{parse_toml, Toml},
+    },
+    std::{
+        fs,
+        path::{Path, PathBuf},
+    },
+};
+
+/// A command of the project that can be run from the run list, like a test suite or a linter,
+/// declared in the tasks file in the root of the workspace:
+///
+/// ```toml
+/// tasks = ["test", "lint"]
+///
+/// [test]
+/// command = "cargo"
+/// args = ["test", "--workspace"]
+/// # relative to the root of the workspace
+/// cwd = "."
+/// env = {RUST_BACKTRACE = "1"}
+/// matchers = [
+///     # a level, a pattern, and what the groups of the pattern capture, in order
+///     ["error", "panicked at (.+):(\\d+):(\\d+):", "file line column"],
+/// ]
+///
+/// [lint]
+/// command = "npm"
+/// args = ["run", "lint"]
+/// cwd = "web"
+/// matchers = [
+///     ["warning", "^(\\S+): line (\\d+), col (\\d+), Warning - (.*)$", "file line column message"],
+///     ["error", "^(\\S+): line (\\d+), col (\\d+), Error - (.*)$", "file line column message"],
+/// ]
+/// ```
+///
+/// Output lines a matcher matches become diagnostics on the file they point at, the others are
+/// logged as they are.
+#[derive(Clone, Debug)]
+pub struct Task {
+    pub name: String,
+    pub command: String,
+    pub args: Vec<String>,
+    /// Relative to the root of the workspace.
+    pub cwd: String,
+    pub env: Vec<(String, String)>,
+    pub matchers: Vec<ProblemMatcher>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MatcherGroup {
+    File,
+    Line,
+    Column,
+    Message,
+}
+
+#[derive(Clone, Debug)]
+pub struct ProblemMatcher {
+    pub level: LogLevel,
+    pub regex: Regex,
+    /// What each group of `regex` captures, in order.
+    pub groups: Vec<MatcherGroup>,
+}
+
+/// A line of task output a matcher recognised.
+#[derive(Clone, Debug)]
+pub struct Problem {
+    pub level: LogLevel,
+    pub file_name: String,
+    pub start: Position,
+    pub end: Position,
+    pub message: String,
+}
+
+fn strings(value: Option<Toml>, key: &str) -> Result<Vec<String>, String> {
+    match value {
+        Some(Toml::Array(values)) => values
+            .into_iter()
+            .map( | value | value.into_str().ok_or(format!("{} must only contain strings", key)))
+            .collect(),
+        Some(_) => Err(format!("{} must be an array of strings", key)),
+        None => Ok(Vec::new()),
+    }
+}
+
+impl Task {
+    pub const TASKS_FILE: &'static str = "makepad_tasks.toml";
+
+    /// Loads the tasks file of the workspace at `root`. A workspace without one has no tasks.
+    pub fn load_all(root: &Path) -> Result<Vec<Task>, String> {
+        let Ok(data) = fs::read_to_string(root.join(Self::TASKS_FILE)) else {
+            return Ok(Vec::new())
+        };
+        Self::from_toml(&data)
+    }
+
+    pub fn from_toml(data: &str) -> Result<Vec<Task>, String> {
+        let mut toml = parse_toml(data).map_err( | err | format!("{:?}", err)) ?;
+        let names = strings(toml.remove("tasks"), "tasks") ?;
+        let mut tasks = Vec::new();
+        for name in names {
+            let command = toml.remove(&format!("{}.command", name))
+                .and_then( | command | command.into_str())
+                .ok_or(format!("{}.command is missing", name)) ?;
+            let args = strings(toml.remove(&format!("{}.args", name)), &format!("{}.args", name)) ?;
+            let cwd = match toml.remove(&format!("{}.cwd", name)) {
+                Some(cwd) => cwd.into_str().ok_or(format!("{}.cwd must be a string", name)) ?,
+                None => ".".to_string()
+            };
+            // the parser flattens inline tables into dotted keys
+            let env_prefix = format!("{}.env.", name);
+            let env_keys: Vec<String> = toml.keys().filter( | key | key.starts_with(&env_prefix)).cloned().collect();
+            let mut env = Vec::new();
+            for key in env_keys {
+                let value = toml.remove(&key).unwrap().into_str().ok_or(format!("{} must be a string", key)) ?;
+                env.push((key[env_prefix.len()..].to_string(), value));
+            }
+            env.sort();
+            let matchers = match toml.remove(&format!("{}.matchers", name)) {
+                Some(Toml::Array(matchers)) => matchers
+                    .into_iter()
+                    .map( | matcher | ProblemMatcher::from_toml(matcher, &name))
+                    .collect::<Result<Vec<_>, _ >> () ?,
+                Some(_) => return Err(format!("{}.matchers must be an array", name)),
+                None => Vec::new()
+            };
+            tasks.push(Task {name, command, args, cwd, env, matchers});
+        }
+        Ok(tasks)
+    }
+
+    pub fn as_id(&self) -> LiveId {
+        Self::id_from_name(&self.name)
+    }
+
+    pub fn id_from_name(name: &str) -> LiveId {
+        LiveId::from_str("task").bytes_append(name.as_bytes())
+    }
+
+    pub fn cwd_path(&self, root: &Path) -> PathBuf {
+        root.join(&self.cwd)
+    }
+
+    /// The first problem a matcher finds in a line of output. File names are made relative to
+    /// `root` like the ones in the file tree, so the diagnostics end up in the right editors.
+    pub fn match_line(&self, root: &Path, line: &str) -> Option<Problem> {
+        self.matchers.iter().find_map( | matcher | matcher.match_line(line)).map( | mut problem | {
+            let path = self.cwd_path(root).join(&problem.file_name);
+            let relative = path.strip_prefix(root).unwrap_or(&path);
+            // drop the ./ that a cwd of . leaves in the path
+            let relative: PathBuf = relative.components().filter( | component | component.as_os_str() != ".").collect();
+            problem.file_name = relative.to_string_lossy().replace('\\', "/");
+            problem
+        })
+    }
+}
+
+impl ProblemMatcher {
+    fn from_toml(matcher: Toml, task: &str) -> Result<Self, String> {
+        let error = || format!("the matchers of {} must be arrays of a level, a pattern and its groups", task);
+        let Toml::Array(matcher) = matcher else {
+            return Err(error())
+        };
+        let mut matcher = matcher.into_iter().map( | value | value.into_str());
+        let (Some(Some(level)), Some(Some(pattern)), Some(Some(groups)), None) = (
+            matcher.next(),
+            matcher.next(),
+            matcher.next(),
+            matcher.next()
+        ) else {
+            return Err(error())
+        };
+        let level = match level.as_str() {
+            "error" => LogLevel::Error,
+            "warning" => LogLevel::Warning,
+            "log" => LogLevel::Log,
+            _ => return Err(format!("unknown level {} in the matchers of {}", level, task))
+        };
+        let regex = Regex::new(&pattern).map_err( | err | format!("{}: {}", pattern, err)) ?;
+        let groups = groups.split_whitespace().map( | group | match group {
+            "file" => Ok(MatcherGroup::File),
+            "line" => Ok(MatcherGroup::Line),
+            "column" => Ok(MatcherGroup::Column),
+            "message" => Ok(MatcherGroup::Message),
+            _ => Err(format!("unknown group {} in the matchers of {}", group, task))
+        }).collect::<Result<Vec<_>, _ >> () ?;
+        if !groups.contains(&MatcherGroup::File) || !groups.contains(&MatcherGroup::Line) {
+            return Err(format!("the matchers of {} need a file and a line group", task))
+        }
+        if groups.len() > regex.group_count() {
+            return Err(format!("{} has fewer groups than {:?}", pattern, groups))
+        }
+        Ok(Self {level, regex, groups})
+    }
+
+    pub fn match_line(&self, line: &str) -> Option<Problem> {
+        let line = line.trim_end();
+        let captures = self.regex.captures(line) ?;
+        let group = | which | {
+            let index = self.groups.iter().position( | group | *group == which) ?;
+            let (start, end) = captures[index + 1] ?;
+            Some(&line[start..end])
+        };
+        // lines and columns in tool output count from 1
+        let line_index = group(MatcherGroup::Line) ?.parse::<usize>().ok() ?.saturating_sub(1);
+        let byte_index = group(MatcherGroup::Column)
+            .and_then( | column | column.parse::<usize>().ok())
+            .map_or(0, | column | column.saturating_sub(1));
+        let start = Position {line_index, byte_index};
+        Some(Problem {
+            level: self.level,
+            file_name: group(MatcherGroup::File) ?.to_string(),
+            start,
+            end: start,
+            message: group(MatcherGroup::Message).unwrap_or(line).to_string(),
+        })
+    }
+}
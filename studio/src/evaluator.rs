@@ -0,0 +1,116 @@
+use {
+    crate::{
+        makepad_micro_serde::*,
+        makepad_widgets::*,
+        tool_environment::{spawn_error_message, ResolvedTool, ToolSettings},
+        settings::SettingsFile,
+    },
+    std::{
+        io::Write,
+        path::{Path, PathBuf},
+        process::{Command, Stdio},
+        thread,
+    },
+};
+
+/// The external program that evaluates selections. The selected text is passed in place of an
+/// `{input}` argument if there is one, and on stdin otherwise. For instance `bc` with `["-l"]`
+/// for calculations, or a Rust script runner.
+#[derive(Clone, Debug, SerRon, DeRon)]
+pub struct EvaluatorSettings {
+    pub command: String,
+    pub args: Vec<String>,
+}
+
+impl Default for EvaluatorSettings {
+    fn default() -> Self {
+        Self {
+            command: "sh".to_string(),
+            args: Vec::new(),
+        }
+    }
+}
+
+impl SettingsFile for EvaluatorSettings {
+    const SETTINGS_FILE: &'static str = "evaluator.ron";
+}
+
+#[derive(Clone, Debug)]
+pub struct Evaluation {
+    pub input: String,
+    pub output: String,
+    pub success: bool,
+}
+
+/// Runs selections through the evaluator program, one thread per evaluation, and keeps the
+/// history of results.
+#[derive(Default)]
+pub struct Evaluator {
+    root_path: PathBuf,
+    settings: EvaluatorSettings,
+    tools: ToolSettings,
+    pub history: Vec<Evaluation>,
+    recv_evaluation: ToUIReceiver<Evaluation>,
+}
+
+impl Evaluator {
+    pub fn init(&mut self, root_path: &Path) {
+        self.root_path = root_path.to_path_buf();
+        self.settings = EvaluatorSettings::load();
+        self.tools = ToolSettings::load();
+    }
+
+    pub fn evaluate(&mut self, input: String) {
+        let settings = self.settings.clone();
+        let tool = self.tools.resolve(&settings.command, &[]);
+        let root_path = self.root_path.clone();
+        let sender = self.recv_evaluation.sender();
+        thread::spawn(move || {
+            let output = run_evaluator(&settings, &tool, &root_path, &input);
+            let (output, success) = match output {
+                Ok(output) => output,
+                Err(err) => (spawn_error_message(&tool.command, &err), false)
+            };
+            let _ = sender.send(Evaluation {input, output, success});
+        });
+    }
+
+    /// Returns the evaluations that finished since the last call.
+    pub fn handle_event(&mut self, event: &Event) -> Vec<Evaluation> {
+        let mut evaluations = Vec::new();
+        if let Event::Signal = event {
+            while let Ok(evaluation) = self.recv_evaluation.try_recv() {
+                self.history.push(evaluation.clone());
+                evaluations.push(evaluation);
+            }
+        }
+        evaluations
+    }
+}
+
+fn run_evaluator(settings: &EvaluatorSettings, tool: &ResolvedTool, root_path: &Path, input: &str) -> std::io::Result<(String, bool)> {
+    let input_as_arg = settings.args.iter().any( | arg | arg == "{input}");
+    let args: Vec<&str> = settings.args.iter()
+        .map( | arg | if arg == "{input}" {input} else {arg.as_str()})
+        .collect();
+    let mut child = Command::new(&tool.command)
+        .args(&args)
+        .envs(tool.env.clone())
+        .current_dir(root_path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn() ?;
+    if let Some(mut stdin) = child.stdin.take() {
+        if !input_as_arg {
+            stdin.write_all(input.as_bytes()) ?;
+            if !input.ends_with('\n') {
+                stdin.write_all(b"\n") ?;
+            }
+        }
+    }
+    let output = child.wait_with_output() ?;
+    let mut text = String::from_utf8_lossy(&output.stdout).to_string();
+    text.push_str(&String::from_utf8_lossy(&output.stderr));
+    Ok((text.trim_end().to_string(), output.status.success()))
+}
@@ -7,15 +7,41 @@ use {
     },
     std::{
         //env,
-        io::{Read, Write},
-        net::{ TcpStream},
-        sync::mpsc::{self, Receiver, Sender, TryRecvError},
+        collections::VecDeque,
+        io::{self, Read, Write},
+        net::{Shutdown, TcpStream, ToSocketAddrs},
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            mpsc::{self, Receiver, RecvTimeoutError, Sender, TryRecvError},
+            Arc,
+            Mutex,
+        },
         thread,
+        time::{Duration, Instant},
         path::Path,
         //path::PathBuf
     },
 };
 
+/// The first wait before reconnecting to a remote file server, doubled after every failed attempt.
+const MIN_BACKOFF: Duration = Duration::from_millis(500);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+/// How often the connection checks whether its reader is still alive while no requests come in.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The state of the connection to the file server, shown in the status bar.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ConnectionState {
+    /// The file server runs in this process, so the connection can't drop.
+    Local,
+    Connecting {attempt: u32},
+    Connected,
+    /// The connection dropped and is retried after a while. Requests made until then are queued
+    /// and sent once it is back.
+    Offline {queued: usize},
+}
+
 #[derive(Default)]
 pub struct FileClient {
 //    bind: Option<String>,
@@ -27,12 +53,19 @@ pub struct FileClientInner {
     pub request_sender: Sender<FileRequest>,
     pub message_signal: SignalToUI,
     pub message_receiver: Receiver<FileClientMessage>,
+    pub state: Arc<Mutex<ConnectionState>>,
 }
 
 impl FileClient {
+    /// Connects to the file server given with `--file-server=host:port`, or runs one in process
+    /// for `path`.
     pub fn init(&mut self, _cx:&mut Cx, path:&Path){
         if self.inner.is_none() {
-            self.inner = Some(FileClientInner::new_with_local_server(path))
+            let remote = std::env::args().find_map( | arg | arg.strip_prefix("--file-server=").map( | addr | addr.to_string()));
+            self.inner = Some(match remote {
+                Some(addr) => FileClientInner::new_connect_remote(&addr),
+                None => FileClientInner::new_with_local_server(path)
+            })
         }
     }
     
@@ -57,6 +90,10 @@ impl FileClient {
         }
         messages
     }
+    
+    pub fn connection_state(&self) -> ConnectionState {
+        *self.inner.as_ref().unwrap().state.lock().unwrap()
+    }
 }
 
 impl FileClientInner {
@@ -95,26 +132,164 @@ impl FileClientInner {
         Self {
             request_sender,
             message_signal,
-            message_receiver
+            message_receiver,
+            state: Arc::new(Mutex::new(ConnectionState::Local)),
         }
     }
     
+    /// Connects to a remote file server. The connection is supervised by its own thread, which
+    /// reconnects with exponential backoff whenever it drops, and holds on to the requests made
+    /// in the meantime instead of losing them.
     pub fn new_connect_remote(to_server: &str) -> Self {
         let (request_sender, request_receiver) = mpsc::channel();
         let message_signal = SignalToUI::new();
         let (message_sender, message_receiver) = mpsc::channel();
+        let state = Arc::new(Mutex::new(ConnectionState::Connecting {attempt: 0}));
         
-        let stream = TcpStream::connect(to_server).unwrap();
-        spawn_request_sender(request_receiver, stream.try_clone().unwrap());
-        spawn_response_or_notification_receiver(stream, message_signal.clone(), message_sender,);
+        spawn_connection_supervisor(
+            to_server.to_string(),
+            request_receiver,
+            message_signal.clone(),
+            message_sender,
+            state.clone(),
+        );
         
         Self {
             request_sender,
             message_signal,
-            message_receiver
+            message_receiver,
+            state,
+        }
+    }
+    
+}
+
+/// The requests that wait for the connection to come back, in order. Saves are full file
+/// contents, so only the last one for a file has to be sent.
+#[derive(Default)]
+struct RequestQueue {
+    requests: VecDeque<FileRequest>,
+}
+
+impl RequestQueue {
+    fn push(&mut self, request: FileRequest) {
+        if let FileRequest::SaveFile(_, _, file_id, _, _) = &request {
+            let file_id = *file_id;
+            // the older save still has the disk hash the newer one was based on
+            if let Some(index) = self.requests.iter().position( | queued | matches!(queued, FileRequest::SaveFile(_, _, id, _, _) if *id == file_id)) {
+                let FileRequest::SaveFile(path, text, _, was_patch, _) = request else {unreachable!()};
+                if let Some(FileRequest::SaveFile(queued_path, queued_text, _, queued_was_patch, _)) = self.requests.get_mut(index) {
+                    *queued_path = path;
+                    *queued_text = text;
+                    *queued_was_patch &= was_patch;
+                }
+                return
+            }
         }
+        self.requests.push_back(request);
     }
     
+    fn len(&self) -> usize {
+        self.requests.len()
+    }
+}
+
+fn set_state(state: &Mutex<ConnectionState>, message_signal: &SignalToUI, new_state: ConnectionState) {
+    *state.lock().unwrap() = new_state;
+    message_signal.set();
+}
+
+fn spawn_connection_supervisor(
+    to_server: String,
+    request_receiver: Receiver<FileRequest>,
+    message_signal: SignalToUI,
+    message_sender: Sender<FileClientMessage>,
+    state: Arc<Mutex<ConnectionState>>,
+) {
+    thread::spawn(move || {
+        let mut queue = RequestQueue::default();
+        let mut attempt = 0;
+        loop {
+            set_state(&state, &message_signal, ConnectionState::Connecting {attempt});
+            match connect(&to_server) {
+                Ok(stream) => {
+                    attempt = 0;
+                    set_state(&state, &message_signal, ConnectionState::Connected);
+                    let alive = Arc::new(AtomicBool::new(true));
+                    spawn_response_or_notification_receiver(
+                        stream.try_clone().unwrap(),
+                        message_signal.clone(),
+                        message_sender.clone(),
+                        alive.clone(),
+                    );
+                    if !run_connection(stream, &request_receiver, &mut queue, &alive) {
+                        // the app is gone, so is anyone waiting for responses
+                        return
+                    }
+                }
+                Err(err) => {
+                    log!("Cannot connect to file server {}: {}", to_server, err);
+                }
+            }
+            let backoff = MIN_BACKOFF.saturating_mul(1 << attempt.min(16)).min(MAX_BACKOFF);
+            attempt += 1;
+            let retry_at = Instant::now() + backoff;
+            set_state(&state, &message_signal, ConnectionState::Offline {queued: queue.len()});
+            while let Some(timeout) = retry_at.checked_duration_since(Instant::now()) {
+                match request_receiver.recv_timeout(timeout) {
+                    Ok(request) => {
+                        queue.push(request);
+                        set_state(&state, &message_signal, ConnectionState::Offline {queued: queue.len()});
+                    }
+                    Err(RecvTimeoutError::Timeout) => break,
+                    Err(RecvTimeoutError::Disconnected) => return,
+                }
+            }
+        }
+    });
+}
+
+fn connect(to_server: &str) -> io::Result<TcpStream> {
+    let addr = to_server.to_socket_addrs() ?.next().ok_or(io::ErrorKind::AddrNotAvailable) ?;
+    TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT)
+}
+
+/// Sends the queued requests and then the new ones until the connection drops. Returns false
+/// when the client itself went away.
+fn run_connection(mut stream: TcpStream, request_receiver: &Receiver<FileRequest>, queue: &mut RequestQueue, alive: &AtomicBool) -> bool {
+    // the queued saves carry the disk hash they were based on, so the server refuses the ones
+    // for files that were changed by someone else while we were offline
+    while let Some(request) = queue.requests.pop_front() {
+        if write_request(&mut stream, &request).is_err() {
+            queue.requests.push_front(request);
+            return true
+        }
+    }
+    loop {
+        if !alive.load(Ordering::Relaxed) {
+            return true
+        }
+        match request_receiver.recv_timeout(POLL_INTERVAL) {
+            Ok(request) => if write_request(&mut stream, &request).is_err() {
+                let _ = stream.shutdown(Shutdown::Both);
+                queue.push(request);
+                return true
+            }
+            Err(RecvTimeoutError::Timeout) => (),
+            Err(RecvTimeoutError::Disconnected) => {
+                let _ = stream.shutdown(Shutdown::Both);
+                return false
+            }
+        }
+    }
+}
+
+fn write_request(stream: &mut TcpStream, request: &FileRequest) -> io::Result<()> {
+    let mut request_bytes = Vec::new();
+    request.ser_bin(&mut request_bytes);
+    let len_bytes = (request_bytes.len() as u32).to_be_bytes();
+    stream.write_all(&len_bytes) ?;
+    stream.write_all(&request_bytes)
 }
 /*
 fn _spawn_connection_listener(listener: TcpListener, mut server: FileServer) {
@@ -168,38 +343,45 @@ fn _spawn_response_or_notification_sender(
         
         message.ser_bin(&mut message_bytes);
         
-        let len_bytes = message_bytes.len().to_be_bytes();
+        let len_bytes = (message_bytes.len() as u32).to_be_bytes();
         stream.write_all(&len_bytes).unwrap();
         stream.write_all(&message_bytes).unwrap();
     });
 }
 
-fn spawn_request_sender(request_receiver: Receiver<FileRequest>, mut stream: TcpStream) {
-    thread::spawn(move || loop {
-        let request = request_receiver.recv().unwrap();
-        let mut request_bytes = Vec::new();
-        request.ser_bin(&mut request_bytes);
-        let len_bytes = request_bytes.len().to_be_bytes();
-        stream.write_all(&len_bytes).unwrap();
-        stream.write_all(&request_bytes).unwrap();
-    });
-}
-
 fn spawn_response_or_notification_receiver(
     mut stream: TcpStream,
     message_signal: SignalToUI,
     message_sender: Sender<FileClientMessage>,
+    alive: Arc<AtomicBool>,
 ) {
-    thread::spawn(move || loop {
-        let mut len_bytes = [0; 4];
-        stream.read_exact(&mut len_bytes).unwrap();
-        
-        let len = u32::from_be_bytes(len_bytes);
-        let mut action_bytes = vec![0; len as usize];
-        stream.read_exact(&mut action_bytes).unwrap();
-        let action = DeBin::deserialize_bin(action_bytes.as_slice()).unwrap();
-        message_sender.send(action).unwrap();
-        message_signal.set()
+    thread::spawn(move || {
+        let mut read_message = || -> Result<FileClientMessage, String> {
+            let mut len_bytes = [0; 4];
+            stream.read_exact(&mut len_bytes).map_err( | err | err.to_string()) ?;
+            
+            let len = u32::from_be_bytes(len_bytes);
+            let mut action_bytes = vec![0; len as usize];
+            stream.read_exact(&mut action_bytes).map_err( | err | err.to_string()) ?;
+            DeBin::deserialize_bin(action_bytes.as_slice()).map_err( | err | format!("{:?}", err))
+        };
+        loop {
+            match read_message() {
+                Ok(action) => {
+                    if message_sender.send(action).is_err() {
+                        break
+                    }
+                    message_signal.set()
+                }
+                Err(err) => {
+                    log!("Lost the connection to the file server: {}", err);
+                    break
+                }
+            }
+        }
+        // the supervisor notices and reconnects
+        alive.store(false, Ordering::Relaxed);
+        let _ = stream.shutdown(Shutdown::Both);
     });
 }
 
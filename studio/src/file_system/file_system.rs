@@ -1,19 +1,36 @@
 use {
-    std::collections::{HashMap, hash_map},
+    std::collections::{HashMap, HashSet, hash_map},
     std::path::Path,
     crate::{
-        makepad_code_editor::{Document, decoration::{Decoration, DecorationSet}, Session},
+        makepad_code_editor::{Document, code_action::WorkspaceEdit, decoration::{Decoration, DecorationSet}, edit_location::EditLocation, search::{ProjectSearch, SearchQuery}, state::SessionState, Session},
         makepad_platform::makepad_live_compiler::LiveFileChange,
         makepad_widgets::*,
         makepad_widgets::file_tree::*,
-        file_system::FileClient,
+        file_system::{
+            ConnectionState,
+            FileClient,
+            follow::{FollowSettings, FollowedFile, LogColorizer},
+            large_file::LargeFileCache,
+            save_policy::{SavePolicies, SavePolicy},
+            syntax_rules::SyntaxRules,
+            workspace_edit::{self, PendingWorkspaceEdit},
+        },
+        unsaved_changes::{CloseTarget, UnsavedChangesProvider},
+        settings::SettingsFile,
         makepad_file_protocol::{
+            file_content_hash,
+            FileContentKind,
+            SearchRequest,
+            SearchResult,
+            SearchScope,
             FileRequest,
             FileError,
             FileResponse,
             FileClientMessage,
+            FileNotification,
             FileNodeData,
             FileTreeData,
+            GlobSet,
         },
     },
 };
@@ -21,12 +38,37 @@ use {
 #[derive(Default)]
 pub struct FileSystem {
     pub file_client: FileClient,
+    /// Last seen state of the connection to the file server.
+    pub connection_state: Option<ConnectionState>,
     pub root_path: String,
     pub file_nodes: LiveIdMap<LiveId, FileNode>,
     pub path_to_file_node_id: HashMap<String, LiveId>,
     pub tab_id_to_file_node_id: HashMap<LiveId, LiveId>,
     pub tab_id_to_session: HashMap<LiveId, Session>,
-    pub open_documents: HashMap<LiveId, OpenDoc>
+    /// The sessions of tabs from before a restart, applied once their documents are loaded.
+    pub restored_sessions: HashMap<LiveId, SessionState>,
+    pub open_documents: HashMap<LiveId, OpenDoc>,
+    pub edit_history_cursor: Option<u64>,
+    pub save_policies: SavePolicies,
+    pub dirty_file_node_ids: HashSet<LiveId>,
+    pub disk_hashes: HashMap<LiveId, u64>,
+    /// What the open files contain. Anything but text is shown as a read only hex dump.
+    pub content_kinds: HashMap<LiveId, FileContentKind>,
+    /// The open files that are too big to load, which are read from the server as they are viewed.
+    pub large_files: HashMap<LiveId, LargeFileCache>,
+    /// The open files that are followed as they grow, which have no document.
+    pub followed_files: HashMap<LiveId, FollowedFile>,
+    pub follow_settings: FollowSettings,
+    pub colorizers: Vec<LogColorizer>,
+    pub syntax_rules: SyntaxRules,
+    pub idle_save_timers: HashMap<LiveId, Timer>,
+    /// The folders that make up the workspace, relative to the root. Empty means just the root.
+    pub workspace_roots: Vec<String>,
+    pub search_id: u64,
+    pub search_results: Vec<SearchResult>,
+    /// Workspace edits waiting for their files to load or for their preview to be accepted.
+    pub workspace_edits: Vec<PendingWorkspaceEdit>,
+    pub workspace_edit_id: u64,
 }
 
 pub enum OpenDoc {
@@ -57,14 +99,26 @@ pub struct FileEdge {
 #[derive(DefaultNone, Debug, Clone)]
 pub enum FileSystemAction {
     TreeLoaded,
+    SaveFailed,
+    SearchResultsChanged,
+    ConnectionChanged(ConnectionState),
     RecompileNeeded,
     LiveReloadNeeded(LiveFileChange),
+    /// A workspace edit is ready and waits for `apply_pending_workspace_edit` after the user has
+    /// seen what it does.
+    WorkspaceEditPreview {id: u64, title: String, preview: String},
+    /// A workspace edit was applied to these files.
+    WorkspaceEditApplied(Vec<LiveId>),
     None
 }
 
 impl FileSystem {
     pub fn init(&mut self, cx: &mut Cx, path:&Path) {
         self.file_client.init(cx, path);
+        self.save_policies = SavePolicies::load();
+        self.follow_settings = FollowSettings::load();
+        self.colorizers = self.follow_settings.compile_colorizers();
+        self.syntax_rules = SyntaxRules::load();
         self.reload_file_tree();
     }
     
@@ -73,14 +127,40 @@ impl FileSystem {
     }
     
     pub fn remove_tab(&mut self, tab_id: LiveId) {
-        self.tab_id_to_file_node_id.remove(&tab_id);
+        let file_id = self.tab_id_to_file_node_id.remove(&tab_id);
         self.tab_id_to_session.remove(&tab_id);
+        self.restored_sessions.remove(&tab_id);
+        // a large file stays mapped on the server until no tab shows it
+        if let Some(file_id) = file_id {
+            if self.large_files.contains_key(&file_id) && self.file_node_id_to_tab_id(file_id).is_none() {
+                self.large_files.remove(&file_id);
+                self.open_documents.remove(&file_id);
+                self.file_client.send_request(FileRequest::CloseLargeFile(file_id.0));
+            }
+            if self.followed_files.contains_key(&file_id) && self.file_node_id_to_tab_id(file_id).is_none() {
+                self.followed_files.remove(&file_id);
+                self.file_client.send_request(FileRequest::UnfollowFile(file_id.0));
+            }
+        }
     }
     
     pub fn path_to_file_node_id(&self, path: &str) -> Option<LiveId> {
         self.path_to_file_node_id.get(path).cloned()
     }
     
+    /// The path `path_to_file_node_id` knows the file by.
+    pub fn file_node_id_to_path(&self, file_node_id: LiveId) -> Option<String> {
+        self.path_to_file_node_id.iter()
+            .find( | (_, id) | **id == file_node_id)
+            .map( | (path, _) | path.clone())
+    }
+    
+    /// The session state from before a restart for the tab, once its document is loaded.
+    pub fn take_restored_session(&mut self, tab_id: LiveId) -> Option<SessionState> {
+        self.get_session_mut(tab_id) ?;
+        self.restored_sessions.remove(&tab_id)
+    }
+    
     pub fn file_node_id_to_tab_id(&self, file_node: LiveId) -> Option<LiveId> {
         for (tab, id) in &self.tab_id_to_file_node_id {
             if *id == file_node {
@@ -105,7 +185,25 @@ impl FileSystem {
     
     pub fn handle_event(&mut self, cx: &mut Cx, event: &Event, ui: &WidgetRef) {
         
+        let idle_file_ids: Vec<LiveId> = self.idle_save_timers.iter()
+            .filter( | (_, timer) | timer.is_event(event).is_some())
+            .map( | (file_id, _) | *file_id)
+            .collect();
+        for file_id in idle_file_ids {
+            self.idle_save_timers.remove(&file_id);
+            self.request_save_file_for_file_node_id(file_id, false);
+        }
+        
         if let Event::Signal = event{
+            let state = self.file_client.connection_state();
+            if self.connection_state != Some(state) {
+                // files may have come or gone on the server while we were offline
+                if state == ConnectionState::Connected && matches!(self.connection_state, Some(ConnectionState::Offline {..})) {
+                    self.reload_file_tree();
+                }
+                self.connection_state = Some(state);
+                cx.action(FileSystemAction::ConnectionChanged(state));
+            }
             while let Ok(message) = self.file_client.inner.as_mut().unwrap().message_receiver.try_recv() {
                 match message {
                     FileClientMessage::Response(response) => match response {
@@ -116,30 +214,108 @@ impl FileSystem {
                         }
                         FileResponse::OpenFile(result) => {
                             match result {
-                                Ok((_unix_path, data, id)) => {
+                                Ok((unix_path, data, id, kind)) => {
                                     let file_id = LiveId(id);
+                                    if kind.is_text() {
+                                        self.disk_hashes.insert(file_id, file_content_hash(&data));
+                                    }
                                     let dock = ui.dock(id!(dock));
                                     for (tab_id, file_id) in &self.tab_id_to_file_node_id {
                                         if id == file_id.0 {
                                             dock.redraw_tab(cx, *tab_id);
                                         }
                                     }
+                                    // large files have no document until they are made editable
+                                    if let FileContentKind::LargeText {size} = kind {
+                                        self.large_files.insert(file_id, LargeFileCache::new(size));
+                                        self.content_kinds.insert(file_id, kind);
+                                        ui.redraw(cx);
+                                        continue
+                                    }
+                                    self.large_files.remove(&file_id);
+                                    self.content_kinds.insert(file_id, kind.clone());
                                     if let Some(OpenDoc::Decorations(dec)) = self.open_documents.get(&file_id) {
                                         let dec = dec.clone();
-                                        self.open_documents.insert(file_id, OpenDoc::Document(Document::new(data.into(), dec)));
-                                    }else {panic!()}
+                                        let document = Document::new(data.into(), dec);
+                                        // the gutter shows what changed since the file was loaded or saved
+                                        document.set_baseline(Some(&document.as_text()));
+                                        if let FileContentKind::Text {language} = &kind {
+                                            let rules = self.syntax_rules.for_file(&unix_path, language.as_deref());
+                                            if rules.is_some() {
+                                                document.set_tokenizer_rules(rules);
+                                            }
+                                        }
+                                        self.open_documents.insert(file_id, OpenDoc::Document(document));
+                                    }
+                                    // otherwise it was loaded twice, by a tab and a workspace edit,
+                                    // and the first document stays as it may have been edited already
                                     ui.redraw(cx);
                                 }
-                                Err(FileError::CannotOpen(_unix_path)) => {
+                                Err(FileError::CannotOpen(unix_path)) => {
+                                    self.fail_workspace_edits_for_path(&unix_path);
                                 }
                                 Err(FileError::Unknown(err)) => {
                                     log!("File error unknown {}", err);
                                     // ignore
                                 }
+                                Err(FileError::ExternallyModified(_)) => {
+                                }
+                            }
+                        }
+                        FileResponse::ReadLines(result) => match result {
+                            Ok(lines) => {
+                                let file_id = LiveId(lines.id);
+                                if let Some(large_file) = self.large_files.get_mut(&file_id) {
+                                    if let Some(text) = large_file.insert_lines(lines) {
+                                        cx.copy_to_clipboard(&text);
+                                    }
+                                    self.redraw_view_by_file_id(cx, file_id, &ui.dock(id!(dock)));
+                                }
+                            }
+                            Err(err) => {
+                                log!("Reading lines failed {:?}", err);
+                            }
+                        }
+                        FileResponse::SearchLargeFile(result) => match result {
+                            Ok(matches) => {
+                                let file_id = LiveId(matches.id);
+                                if let Some(large_file) = self.large_files.get_mut(&file_id) {
+                                    large_file.insert_matches(matches);
+                                    self.redraw_view_by_file_id(cx, file_id, &ui.dock(id!(dock)));
+                                }
+                            }
+                            Err(err) => {
+                                log!("Searching large file failed {:?}", err);
+                            }
+                        }
+                        FileResponse::CloseLargeFile => (),
+                        FileResponse::FollowFile(result) => if let Err(err) = result {
+                            log!("Following file failed {:?}", err);
+                        }
+                        FileResponse::UnfollowFile => (),
+                        FileResponse::Search(result) => match result {
+                            Ok((id, results)) => if id == self.search_id {
+                                // the open documents were searched already, with their unsaved edits
+                                let open_paths = self.open_document_paths();
+                                self.search_results.extend(results.into_iter().filter( | result | !open_paths.contains(&result.path)));
+                                self.search_results.sort_by( | a, b | (&a.path, a.line).cmp(&(&b.path, b.line)));
+                                cx.action(FileSystemAction::SearchResultsChanged)
+                            }
+                            Err(err) => {
+                                log!("Search failed {:?}", err);
                             }
                         }
                         FileResponse::SaveFile(result) => match result {
-                            Ok((path, old, new, _id, was_patch)) => {
+                            Ok((path, old, new, id, was_patch)) => {
+                                let file_id = LiveId(id);
+                                self.disk_hashes.insert(file_id, file_content_hash(&new));
+                                if let Some(OpenDoc::Document(doc)) = self.open_documents.get(&file_id) {
+                                    // edits made while the save was in flight keep the file dirty
+                                    if doc.as_text().to_string() == new {
+                                        self.dirty_file_node_ids.remove(&file_id);
+                                    }
+                                    doc.set_baseline(Some(&new.as_str().into()));
+                                }
                                 // alright file has been saved
                                 // now we need to check if a live_design!{} changed or something outside it
                                 if old != new && !was_patch {
@@ -170,17 +346,36 @@ impl FileSystem {
                                     }
                                 }
                             }
-                            Err(_) => {}
+                            Err(FileError::ExternallyModified(path)) => {
+                                log!("Not saving {}, it was modified outside of the editor", path);
+                                cx.action(FileSystemAction::SaveFailed)
+                            }
+                            Err(err) => {
+                                log!("Save failed {:?}", err);
+                                cx.action(FileSystemAction::SaveFailed)
+                            }
                             // ok we saved a file, we should check however what changed
                             // to see if we need a recompile
                             
                         }
                     },
-                    FileClientMessage::Notification(_notification) => {
-                        //self.editors.handle_collab_notification(cx, &mut state.editor_state, notification)
+                    FileClientMessage::Notification(notification) => match notification {
+                        FileNotification::FileAppended {id, text, reset} => {
+                            let file_id = LiveId(id);
+                            if let Some(followed) = self.followed_files.get_mut(&file_id) {
+                                followed.append(&text, reset);
+                                self.redraw_view_by_file_id(cx, file_id, &ui.dock(id!(dock)));
+                            }
+                        }
+                        FileNotification::FileChangedOnDisk => {
+                            //self.editors.handle_collab_notification(cx, &mut state.editor_state, notification)
+                        }
                     }
                 }
             }
+            if !self.workspace_edits.is_empty() {
+                self.poll_workspace_edits(cx);
+            }
         }
     }
 
@@ -190,10 +385,197 @@ impl FileSystem {
         }
     }
     
+    /// Applies a workspace edit, like a quick fix, a rename or a replace across files. The files
+    /// it touches that aren't open are loaded without showing them, and once they all are, it
+    /// either shows a preview with `FileSystemAction::WorkspaceEditPreview` or is applied right
+    /// away. Either all files get their edits or none do.
+    pub fn request_workspace_edit(&mut self, cx: &mut Cx, title: &str, edit: WorkspaceEdit, preview: bool) -> Result<(), String> {
+        for file in &edit.files {
+            let file_id = self.path_to_file_node_id(&file.file_name)
+                .ok_or(format!("{} is not in the workspace", file.file_name)) ?;
+            if self.followed_files.contains_key(&file_id) || self.large_files.contains_key(&file_id) || self.is_read_only(file_id) {
+                return Err(format!("{} is read-only", file.file_name))
+            }
+        }
+        for file in &edit.files {
+            let file_id = self.path_to_file_node_id(&file.file_name).unwrap();
+            self.load_file_hidden(file_id);
+        }
+        self.workspace_edit_id += 1;
+        self.workspace_edits.push(PendingWorkspaceEdit {
+            id: self.workspace_edit_id,
+            title: title.to_string(),
+            edit,
+            preview,
+            previewed: false,
+        });
+        self.poll_workspace_edits(cx);
+        Ok(())
+    }
+    
+    /// Applies a workspace edit whose preview was accepted.
+    pub fn apply_pending_workspace_edit(&mut self, cx: &mut Cx, id: u64) {
+        if let Some(index) = self.workspace_edits.iter().position( | pending | pending.id == id) {
+            let pending = self.workspace_edits.remove(index);
+            self.finish_workspace_edit(cx, &pending);
+        }
+    }
+    
+    pub fn discard_pending_workspace_edit(&mut self, id: u64) {
+        self.workspace_edits.retain( | pending | pending.id != id);
+    }
+    
+    /// Loads a file for a workspace edit, without a tab to show it in.
+    fn load_file_hidden(&mut self, file_id: LiveId) {
+        match self.open_documents.get(&file_id) {
+            Some(OpenDoc::Document(_)) => return,
+            // a tab is loading it already
+            Some(OpenDoc::Decorations(_)) if self.file_node_id_to_tab_id(file_id).is_some() => return,
+            _ => ()
+        }
+        let dec = match self.open_documents.remove(&file_id) {
+            Some(OpenDoc::Decorations(dec)) => dec,
+            _ => DecorationSet::new()
+        };
+        self.open_documents.insert(file_id, OpenDoc::Decorations(dec));
+        let path = self.file_node_path(file_id);
+        self.file_client.send_request(FileRequest::OpenFile(path, file_id.0));
+    }
+    
+    fn fail_workspace_edits_for_path(&mut self, unix_path: &str) {
+        let Some(file_name) = self.path_to_file_node_id.iter()
+            .find( | (_, file_id) | self.file_node_path(**file_id) == unix_path)
+            .map( | (file_name, _) | file_name.clone()) else {
+            return
+        };
+        self.workspace_edits.retain( | pending | {
+            let touches = pending.edit.files.iter().any( | file | file.file_name == file_name);
+            if touches {
+                log!("Cannot apply {}: {} cannot be opened", pending.title, file_name);
+            }
+            !touches
+        });
+    }
+    
+    /// Moves the workspace edits whose files have all loaded along, to their preview or to
+    /// being applied.
+    fn poll_workspace_edits(&mut self, cx: &mut Cx) {
+        let mut index = 0;
+        while index < self.workspace_edits.len() {
+            let pending = &self.workspace_edits[index];
+            let mut is_loaded = true;
+            let mut error = None;
+            for file in &pending.edit.files {
+                let Some(file_id) = self.path_to_file_node_id(&file.file_name) else {
+                    error = Some(format!("{} is not in the workspace", file.file_name));
+                    break
+                };
+                match self.open_documents.get(&file_id) {
+                    Some(OpenDoc::Document(_)) if self.is_read_only(file_id) => {
+                        error = Some(format!("{} is read-only", file.file_name));
+                    }
+                    Some(OpenDoc::Document(_)) => (),
+                    // large files and followed files never get a document
+                    Some(OpenDoc::Decorations(_)) if !self.content_kinds.contains_key(&file_id) => {
+                        is_loaded = false;
+                    }
+                    _ => {
+                        error = Some(format!("{} is too large to edit", file.file_name));
+                    }
+                }
+            }
+            if let Some(error) = error {
+                let pending = self.workspace_edits.remove(index);
+                log!("Cannot apply {}: {}", pending.title, error);
+                continue
+            }
+            if !is_loaded {
+                index += 1;
+                continue
+            }
+            if pending.preview {
+                if !pending.previewed {
+                    let files = pending.edit.files.iter().map( | file | {
+                        let file_id = self.path_to_file_node_id(&file.file_name).unwrap();
+                        let Some(OpenDoc::Document(document)) = self.open_documents.get(&file_id) else {
+                            unreachable!()
+                        };
+                        let text = document.as_text();
+                        // edits that don't fit are reported when applying
+                        let hunks = if file.check(&text).is_ok() {file.hunks(&text)} else {Vec::new()};
+                        (file.file_name.clone(), hunks)
+                    }).collect::<Vec<_ >> ();
+                    cx.action(FileSystemAction::WorkspaceEditPreview {
+                        id: pending.id,
+                        title: pending.title.clone(),
+                        preview: workspace_edit::preview_text(&files),
+                    });
+                    self.workspace_edits[index].previewed = true;
+                }
+                index += 1;
+                continue
+            }
+            let pending = self.workspace_edits.remove(index);
+            self.finish_workspace_edit(cx, &pending);
+        }
+    }
+    
+    fn finish_workspace_edit(&mut self, cx: &mut Cx, pending: &PendingWorkspaceEdit) {
+        match self.apply_workspace_edit(cx, &pending.edit) {
+            Ok(file_ids) => cx.action(FileSystemAction::WorkspaceEditApplied(file_ids)),
+            Err(err) => log!("Cannot apply {}: {}", pending.title, err)
+        }
+    }
+    
+    /// Applies a workspace edit to loaded documents, after checking that every file still fits
+    /// its edits. Each file gets one undo step. Files without a tab are saved right away, since
+    /// nothing shows that they have unsaved changes. Returns the files that changed.
+    fn apply_workspace_edit(&mut self, cx: &mut Cx, edit: &WorkspaceEdit) -> Result<Vec<LiveId>, String> {
+        let mut targets = Vec::new();
+        for file in &edit.files {
+            let file_id = self.path_to_file_node_id(&file.file_name)
+                .ok_or(format!("{} is not in the workspace", file.file_name)) ?;
+            let Some(OpenDoc::Document(document)) = self.open_documents.get(&file_id) else {
+                return Err(format!("{} is not loaded", file.file_name))
+            };
+            if self.is_read_only(file_id) {
+                return Err(format!("{} is read-only", file.file_name))
+            }
+            file.check(&document.as_text()) ?;
+            targets.push((file_id, document.clone(), file));
+        }
+        for (file_id, document, file) in &targets {
+            match self.file_node_id_to_tab_id(*file_id) {
+                Some(tab_id) => {
+                    if let Some(session) = self.get_session_mut(tab_id) {
+                        session.apply_text_edits(&file.edits);
+                    }
+                    self.handle_text_did_change(cx, tab_id);
+                }
+                None => {
+                    let session = Session::new(document.clone());
+                    session.apply_text_edits(&file.edits);
+                    session.handle_changes();
+                    self.dirty_file_node_ids.insert(*file_id);
+                    self.request_save_file_for_file_node_id(*file_id, false);
+                }
+            }
+        }
+        self.handle_sessions();
+        Ok(targets.into_iter().map( | (file_id, _, _) | file_id).collect())
+    }
+    
     pub fn request_open_file(&mut self, tab_id: LiveId, file_id: LiveId) {
         // ok lets see if we have a document
         // ifnot, we create a new one
         self.tab_id_to_file_node_id.insert(tab_id, file_id);
+        if self.followed_files.contains_key(&file_id) {
+            return
+        }
+        if self.follow_settings.follows_path(&self.file_node_relative_path(file_id)) {
+            self.follow_file(file_id);
+            return
+        }
         // move decorations to doc
         let dec = match self.open_documents.get(&file_id){
             Some(OpenDoc::Decorations(_))=> if let Some(OpenDoc::Decorations(dec)) = self.open_documents.remove(&file_id){
@@ -221,11 +603,228 @@ impl FileSystem {
     }
     
     pub fn request_save_file_for_file_node_id(&mut self, file_id: LiveId, was_patch:bool) {
+        // hex dumps of binaries must never be written back
+        if self.is_read_only(file_id) {
+            return
+        }
         if let Some(OpenDoc::Document(doc)) = self.open_documents.get(&file_id) {
             let text = doc.as_text().to_string();
             let path = self.file_node_path(file_id);
-            self.file_client.send_request(FileRequest::SaveFile(path.clone(), text, file_id.0, was_patch));
+            let disk_hash = self.disk_hashes.get(&file_id).cloned();
+            self.file_client.send_request(FileRequest::SaveFile(path.clone(), text, file_id.0, was_patch, disk_hash));
+        }
+    }
+    
+    /// Loads a large file into an editable document, which drops its memory map on the server.
+    pub fn make_large_file_editable(&mut self, file_id: LiveId) {
+        if self.large_files.contains_key(&file_id) {
+            let path = self.file_node_path(file_id);
+            self.file_client.send_request(FileRequest::OpenFileAsText(path, file_id.0));
+        }
+    }
+    
+    /// Shows the end of the file as it grows instead of its document. A large file stops being
+    /// mapped on the server.
+    pub fn follow_file(&mut self, file_id: LiveId) {
+        if self.large_files.remove(&file_id).is_some() {
+            self.file_client.send_request(FileRequest::CloseLargeFile(file_id.0));
         }
+        self.open_documents.remove(&file_id);
+        self.followed_files.insert(file_id, FollowedFile::default());
+        let path = self.file_node_path(file_id);
+        self.file_client.send_request(FileRequest::FollowFile(path, file_id.0));
+    }
+    
+    /// Asks for the lines of a large file that are about to be drawn.
+    pub fn request_large_file_lines(&mut self, file_id: LiveId, start: usize, end: usize) {
+        if let Some(large_file) = self.large_files.get_mut(&file_id) {
+            large_file.request_lines(file_id.0, start, end, &mut self.file_client.request_sender());
+        }
+    }
+    
+    /// The text of lines of a large file, if they were read already. Otherwise they are read
+    /// and put on the clipboard when they come in.
+    pub fn copy_large_file_lines(&mut self, file_id: LiveId, start: usize, end: usize) -> Option<String> {
+        let large_file = self.large_files.get_mut(&file_id)?;
+        large_file.copy(file_id.0, start, end, &mut self.file_client.request_sender())
+    }
+    
+    pub fn search_large_file(&mut self, file_id: LiveId, query: &str) {
+        if let Some(large_file) = self.large_files.get_mut(&file_id) {
+            large_file.search(file_id.0, query, &mut self.file_client.request_sender());
+        }
+    }
+    
+    pub fn step_large_file_match(&mut self, file_id: LiveId, forward: bool) {
+        if let Some(large_file) = self.large_files.get_mut(&file_id) {
+            large_file.step_match(file_id.0, forward, &mut self.file_client.request_sender());
+        }
+    }
+    
+    pub fn is_read_only(&self, file_id: LiveId) -> bool {
+        self.content_kinds.get(&file_id).map_or(false, | kind | !kind.is_text())
+    }
+    
+    pub fn is_tab_read_only(&self, tab_id: LiveId) -> bool {
+        self.tab_id_to_file_node_id.get(&tab_id).map_or(false, | file_id | self.is_read_only(*file_id))
+    }
+    
+    pub fn save_policy_for_tab_id(&self, tab_id: LiveId) -> Option<SavePolicy> {
+        let file_id = self.tab_id_to_file_node_id.get(&tab_id)?;
+        Some(self.save_policies.policy_for_path(&self.file_node_relative_path(*file_id)))
+    }
+    
+    /// Marks the document of the tab as modified and saves it if its policy says so.
+    pub fn handle_text_did_change(&mut self, cx: &mut Cx, tab_id: LiveId) {
+        if let Some(&file_id) = self.tab_id_to_file_node_id.get(&tab_id) {
+            self.dirty_file_node_ids.insert(file_id);
+            match self.save_policy_for_tab_id(tab_id) {
+                Some(SavePolicy::LiveWrite) => self.request_save_file_for_file_node_id(file_id, false),
+                Some(SavePolicy::AfterIdle(delay)) => {
+                    if let Some(timer) = self.idle_save_timers.remove(&file_id) {
+                        cx.stop_timer(timer);
+                    }
+                    self.idle_save_timers.insert(file_id, cx.start_timeout(delay));
+                }
+                Some(SavePolicy::OnFocusLost) | Some(SavePolicy::Explicit) | None => ()
+            }
+        }
+    }
+    
+    pub fn handle_key_focus_lost(&mut self, tab_id: LiveId) {
+        if let Some(&file_id) = self.tab_id_to_file_node_id.get(&tab_id) {
+            if let Some(SavePolicy::OnFocusLost) = self.save_policy_for_tab_id(tab_id) {
+                if self.dirty_file_node_ids.contains(&file_id) {
+                    self.request_save_file_for_file_node_id(file_id, false);
+                }
+            }
+        }
+    }
+    
+    /// Explicit save, writes every modified document regardless of its policy.
+    pub fn save_all_dirty_files(&mut self) {
+        let file_ids: Vec<LiveId> = self.dirty_file_node_ids.iter().cloned().collect();
+        for file_id in file_ids {
+            self.request_save_file_for_file_node_id(file_id, false);
+        }
+    }
+    
+    /// All recent edit locations of all open documents, oldest first.
+    pub fn edit_history(&self) -> Vec<(LiveId, EditLocation)> {
+        let mut history = Vec::new();
+        for (file_id, doc) in &self.open_documents {
+            if let OpenDoc::Document(doc) = doc {
+                history.extend(doc.edit_locations().iter().map( | location | (*file_id, *location)));
+            }
+        }
+        history.sort_by_key( | (_, location) | location.stamp);
+        history
+    }
+    
+    /// Steps the edit history cursor one location back in time, starting at the most recent edit.
+    pub fn prev_edit_location(&mut self) -> Option<(LiveId, EditLocation)> {
+        let history = self.edit_history();
+        let found = match self.edit_history_cursor {
+            Some(cursor) => history.iter().rev().find( | (_, location) | location.stamp < cursor),
+            None => history.last()
+        }.copied();
+        if let Some((_, location)) = found {
+            self.edit_history_cursor = Some(location.stamp);
+        }
+        found
+    }
+    
+    pub fn next_edit_location(&mut self) -> Option<(LiveId, EditLocation)> {
+        let cursor = self.edit_history_cursor?;
+        let history = self.edit_history();
+        let found = history.iter().find( | (_, location) | location.stamp > cursor).copied();
+        if let Some((_, location)) = found {
+            self.edit_history_cursor = Some(location.stamp);
+        }
+        found
+    }
+    
+    pub fn reset_edit_history_cursor(&mut self) {
+        self.edit_history_cursor = None;
+    }
+    
+    /// Starts a find-in-files search. The roots of `request` are filled in from the scope, and
+    /// results arrive as `FileSystemAction::SearchResultsChanged`.
+    pub fn search(&mut self, cx: &mut Cx, scope: SearchScope, request: SearchRequest) {
+        let query = SearchQuery {
+            text: request.query.clone(),
+            is_regex: false,
+            match_case: request.case_sensitive,
+            whole_word: request.whole_word,
+        };
+        self.search_project(cx, scope, query, request.include, request.exclude);
+    }
+    
+    /// Like `search`, for a query that can be a regex. The open documents are searched here, so
+    /// unsaved edits are found, and the file server searches the other files in the scope. The
+    /// file server only finds text as it is, so a regex only searches the open documents.
+    pub fn search_project(&mut self, cx: &mut Cx, scope: SearchScope, query: SearchQuery, include: Vec<String>, exclude: Vec<String>) {
+        self.search_id += 1;
+        self.search_results.clear();
+        let search = match ProjectSearch::new(query.clone()) {
+            Ok(search) if !query.text.is_empty() => search,
+            Ok(_) => {
+                cx.action(FileSystemAction::SearchResultsChanged);
+                return
+            }
+            Err(err) => {
+                log!("Invalid search {:?}", err);
+                cx.action(FileSystemAction::SearchResultsChanged);
+                return
+            }
+        };
+        let is_open_files = scope == SearchScope::OpenFiles;
+        let roots = match scope {
+            SearchScope::OpenFiles => vec!["".to_string()],
+            SearchScope::Folder(folder) => vec![folder],
+            SearchScope::Workspace if self.workspace_roots.is_empty() => vec!["".to_string()],
+            SearchScope::Workspace => self.workspace_roots.clone(),
+        };
+        let filter = GlobSet::new(&include, &exclude);
+        let mut documents = Vec::new();
+        for (file_id, doc) in &self.open_documents {
+            if let OpenDoc::Document(doc) = doc {
+                let path = self.file_node_relative_path(*file_id);
+                if filter.is_match(&path, false) && roots.iter().any( | root | is_in_folder(&path, root)) {
+                    documents.push((path, doc.as_text()));
+                }
+            }
+        }
+        let hits = search.search(documents.iter().map( | (path, text) | (path, &**text)));
+        self.search_results.extend(hits.map( | hit | SearchResult {
+            path: hit.document.clone(),
+            line: hit.start.line_index,
+            column_start: hit.start.byte_index,
+            // a match over more than one line is shown as far as the end of its first line
+            column_end: if hit.end.line_index == hit.start.line_index {hit.end.byte_index} else {hit.line_text.len()},
+            line_text: hit.line_text,
+        }));
+        self.search_results.sort_by( | a, b | (&a.path, a.line).cmp(&(&b.path, b.line)));
+        cx.action(FileSystemAction::SearchResultsChanged);
+        if is_open_files || query.is_regex {
+            return
+        }
+        self.file_client.send_request(FileRequest::Search(SearchRequest {
+            id: self.search_id,
+            query: query.text,
+            case_sensitive: query.match_case,
+            whole_word: query.whole_word,
+            roots,
+            include,
+            exclude,
+        }));
+    }
+    
+    fn open_document_paths(&self) -> HashSet<String> {
+        self.open_documents.iter().filter_map( | (file_id, doc) | match doc {
+            OpenDoc::Document(_) => Some(self.file_node_relative_path(*file_id)),
+            OpenDoc::Decorations(_) => None,
+        }).collect()
     }
     
     pub fn clear_decorations(&mut self, file_node_id: &LiveId) {
@@ -280,19 +879,22 @@ impl FileSystem {
     }
     
     
-    pub fn draw_file_node(&self, cx: &mut Cx2d, file_node_id: LiveId, file_tree: &mut FileTree) {
+    /// The tree as the file tree shows it, one indented line per node, with the contents of
+    /// closed folders left out.
+    pub fn file_node_reading_text(&self, file_node_id: LiveId, file_tree: &FileTree, depth: usize, out: &mut String) {
         if let Some(file_node) = self.file_nodes.get(&file_node_id) {
+            let indent = "  ".repeat(depth);
             match &file_node.child_edges {
                 Some(child_edges) => {
-                    if file_tree.begin_folder(cx, file_node_id, &file_node.name).is_ok() {
+                    push_reading_line(out, &format!("{}{}/", indent, file_node.name));
+                    if file_tree.is_folder_open(file_node_id) {
                         for child_edge in child_edges {
-                            self.draw_file_node(cx, child_edge.file_node_id, file_tree);
+                            self.file_node_reading_text(child_edge.file_node_id, file_tree, depth + 1, out);
                         }
-                        file_tree.end_folder();
                     }
                 }
                 None => {
-                    file_tree.file(cx, file_node_id, &file_node.name);
+                    push_reading_line(out, &format!("{}{}", indent, file_node.name));
                 }
             }
         }
@@ -315,6 +917,19 @@ impl FileSystem {
         path
     }
     
+    pub fn file_node_relative_path(&self, file_node_id: LiveId) -> String {
+        let mut path = String::new();
+        let mut file_node = &self.file_nodes[file_node_id];
+        while let Some(edge) = &file_node.parent_edge {
+            path.insert_str(0, &edge.name);
+            file_node = &self.file_nodes[edge.file_node_id];
+            if file_node.parent_edge.is_some() {
+                path.insert_str(0, "/");
+            }
+        }
+        path
+    }
+    
     pub fn ensure_unique_tab_names(&self, cx: &mut Cx, dock: &DockRef) {
         let mut min_diff: HashMap<LiveId, usize> = HashMap::new();
         let mut outer_path = Vec::new();
@@ -445,4 +1060,70 @@ impl FileSystem {
             tree_data.root,
         );
     }
-}
\ No newline at end of file
+}
+
+impl FileSystem {
+    /// The dirty files that would be lost if the target closed. A tab only loses its file when no
+    /// other tab shows the same document.
+    fn unsaved_file_node_ids(&self, target: CloseTarget) -> Vec<LiveId> {
+        match target {
+            CloseTarget::Tab(tab_id) => match self.tab_id_to_file_node_id.get(&tab_id) {
+                Some(file_id) if self.dirty_file_node_ids.contains(file_id) => {
+                    let shown_elsewhere = self.tab_id_to_file_node_id.iter().any( | (other_tab_id, other_file_id) | {
+                        *other_tab_id != tab_id && other_file_id == file_id
+                    });
+                    if shown_elsewhere {vec![]} else {vec![*file_id]}
+                }
+                _ => vec![]
+            }
+            CloseTarget::App => self.dirty_file_node_ids.iter().cloned().collect()
+        }
+    }
+}
+
+impl FileTreeSource for FileSystem {
+    fn children(&mut self, _cx: &mut Cx, folder_id: LiveId) -> Option<Vec<LiveId>> {
+        let child_edges = self.file_nodes.get(&folder_id)?.child_edges.as_ref()?;
+        Some(child_edges.iter().map( | child_edge | child_edge.file_node_id).collect())
+    }
+    
+    fn name(&self, node_id: LiveId) -> &str {
+        self.file_nodes.get(&node_id).map_or("", | file_node | &file_node.name)
+    }
+    
+    fn is_folder(&self, node_id: LiveId) -> bool {
+        self.file_nodes.get(&node_id).is_some_and( | file_node | !file_node.is_file())
+    }
+}
+
+impl UnsavedChangesProvider for FileSystem {
+    fn unsaved_documents(&self, target: CloseTarget) -> Vec<String> {
+        let mut paths: Vec<String> = self.unsaved_file_node_ids(target).into_iter()
+            .map( | file_id | self.file_node_relative_path(file_id))
+            .collect();
+        paths.sort();
+        paths
+    }
+    
+    fn save_unsaved(&mut self, target: CloseTarget) {
+        for file_id in self.unsaved_file_node_ids(target) {
+            self.request_save_file_for_file_node_id(file_id, false);
+        }
+    }
+    
+    fn discard_unsaved(&mut self, target: CloseTarget) {
+        for file_id in self.unsaved_file_node_ids(target) {
+            self.dirty_file_node_ids.remove(&file_id);
+            self.idle_save_timers.remove(&file_id);
+            // drop the edited document, so opening the file again reads it from disk
+            if let CloseTarget::Tab(_) = target {
+                self.open_documents.remove(&file_id);
+            }
+        }
+    }
+}
+
+/// Whether the file at `path` is inside `folder`, where an empty folder is the root.
+fn is_in_folder(path: &str, folder: &str) -> bool {
+    folder.is_empty() || path.strip_prefix(folder).map_or(false, | rest | rest.starts_with('/'))
+}
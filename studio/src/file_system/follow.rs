@@ -0,0 +1,161 @@
+use {
+    crate::{
+        makepad_micro_serde::*,
+        makepad_widgets::*,
+        makepad_code_editor::regex::Regex,
+        makepad_file_protocol::Glob,
+        settings::SettingsFile,
+    },
+    std::collections::VecDeque,
+};
+
+/// How many lines of a followed file are kept, older ones are dropped.
+pub const MAX_FOLLOWED_LINES: usize = 100_000;
+
+/// Colors the lines of followed files that match a pattern.
+#[derive(Clone, Debug, SerRon, DeRon)]
+pub struct LogColorizerRule {
+    /// A regular expression, like `(?i)error|panic` or `^\[\d+:\d+\]`.
+    pub pattern: String,
+    /// A color like `#f55` or `#ff5555`.
+    pub color: String,
+    /// Colors the whole line instead of only the text that matches.
+    pub whole_line: bool,
+}
+
+/// The follow settings: which files open following their end as they grow, like `tail -f`,
+/// instead of as a document, and how their lines are colored.
+#[derive(Clone, Debug, SerRon, DeRon)]
+pub struct FollowSettings {
+    /// Globs with gitignore conventions, matched against file paths relative to the root.
+    pub follow: Vec<String>,
+    pub colorizers: Vec<LogColorizerRule>,
+}
+
+impl Default for FollowSettings {
+    fn default() -> Self {
+        let rule = | pattern: &str, color: &str | LogColorizerRule {
+            pattern: pattern.to_string(),
+            color: color.to_string(),
+            whole_line: true,
+        };
+        Self {
+            follow: vec!["*.log".to_string()],
+            colorizers: vec![
+                rule("(?i)error|panic|fatal", "#f66"),
+                rule("(?i)warn", "#fc4"),
+            ],
+        }
+    }
+}
+
+impl SettingsFile for FollowSettings {
+    const SETTINGS_FILE: &'static str = "follow.ron";
+}
+
+impl FollowSettings {
+    pub fn follows_path(&self, path: &str) -> bool {
+        self.follow.iter().any( | pattern | Glob::new(pattern).is_match(path, false))
+    }
+
+    /// The colorizer rules that are valid. The others are logged and left out.
+    pub fn compile_colorizers(&self) -> Vec<LogColorizer> {
+        self.colorizers.iter().filter_map( | rule | {
+            let regex = Regex::new(&rule.pattern)
+                .map_err( | err | log!("Cannot parse colorizer pattern {}: {}", rule.pattern, err))
+                .ok() ?;
+            let Some(color) = parse_color(&rule.color) else {
+                log!("Cannot parse colorizer color {}", rule.color);
+                return None
+            };
+            Some(LogColorizer {regex, color, whole_line: rule.whole_line})
+        }).collect()
+    }
+}
+
+// Parses `#rgb`, `#rgba`, `#rrggbb` and `#rrggbbaa`.
+fn parse_color(color: &str) -> Option<Vec4> {
+    let hex = color.trim().strip_prefix('#') ?;
+    let value = u32::from_str_radix(hex, 16).ok() ?;
+    let expand = | value: u32, digits: u32 | (0..digits).rev().fold(0, | out, digit | {
+        let nibble = (value >> (digit * 4)) & 0xf;
+        (out << 8) | (nibble << 4) | nibble
+    });
+    let rgba = match hex.len() {
+        3 => (expand(value, 3) << 8) | 0xff,
+        4 => expand(value, 4),
+        6 => (value << 8) | 0xff,
+        8 => value,
+        _ => return None
+    };
+    Some(Vec4::from_u32(rgba))
+}
+
+/// A compiled `LogColorizerRule`.
+pub struct LogColorizer {
+    regex: Regex,
+    pub color: Vec4,
+    pub whole_line: bool,
+}
+
+/// How to color a line: the color of the whole line, if a whole line rule matches, and the
+/// byte ranges of the text that other rules match, in order and without overlaps. The first
+/// rule that matches wins.
+pub fn colorize_line(colorizers: &[LogColorizer], line: &str) -> (Option<Vec4>, Vec<(usize, usize, Vec4)>) {
+    let mut line_color = None;
+    let mut spans: Vec<(usize, usize, Vec4)> = Vec::new();
+    for colorizer in colorizers {
+        if colorizer.whole_line {
+            if line_color.is_none() && colorizer.regex.is_match(line) {
+                line_color = Some(colorizer.color);
+            }
+            continue
+        }
+        for (start, end) in colorizer.regex.find_iter(line).filter( | (start, end) | end > start) {
+            if spans.iter().all( | span | end <= span.0 || start >= span.1) {
+                spans.push((start, end, colorizer.color));
+            }
+        }
+    }
+    spans.sort_by_key( | span | span.0);
+    (line_color, spans)
+}
+
+/// The lines a followed file got so far. Only the last `MAX_FOLLOWED_LINES` are kept.
+#[derive(Default)]
+pub struct FollowedFile {
+    pub lines: VecDeque<String>,
+    /// How many lines were dropped from the start, so line numbers stay the same.
+    pub dropped_lines: usize,
+}
+
+impl FollowedFile {
+    pub fn line_count(&self) -> usize {
+        self.dropped_lines + self.lines.len()
+    }
+
+    /// The line with the given number, counting the dropped lines.
+    pub fn line(&self, line: usize) -> Option<&str> {
+        self.lines.get(line.checked_sub(self.dropped_lines) ?).map( | line | line.as_str())
+    }
+
+    pub fn append(&mut self, text: &str, reset: bool) {
+        if reset {
+            self.lines.clear();
+            self.dropped_lines = 0;
+        }
+        for line in text.lines() {
+            self.lines.push_back(line.to_string());
+        }
+        while self.lines.len() > MAX_FOLLOWED_LINES {
+            self.lines.pop_front();
+            self.dropped_lines += 1;
+        }
+    }
+
+    /// Forgets the lines so far, the ones that come in after are numbered on.
+    pub fn clear(&mut self) {
+        self.dropped_lines += self.lines.len();
+        self.lines.clear();
+    }
+}
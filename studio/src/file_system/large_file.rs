@@ -0,0 +1,186 @@
+use {
+    std::collections::{HashMap, HashSet},
+    crate::makepad_file_protocol::{
+        FileRequest,
+        LargeFileLines,
+        LargeFileMatch,
+        LargeFileMatches,
+    },
+};
+
+// Lines are read from the server in blocks of this many lines.
+const BLOCK_LINES: usize = 256;
+
+// How many blocks are kept, the ones furthest from what was read last are dropped.
+const MAX_BLOCKS: usize = 64;
+
+// Matches are asked for in batches of this many.
+const MATCH_BATCH: usize = 1000;
+
+/// Copying more lines than this from a large file is refused.
+pub const MAX_COPY_LINES: usize = 100_000;
+
+/// The search in a large file, which runs in batches on the server.
+#[derive(Default)]
+pub struct LargeFileSearch {
+    pub query: String,
+    pub matches: Vec<LargeFileMatch>,
+    pub current: Option<usize>,
+    pub next_line: usize,
+    pub done: bool,
+    requested: bool,
+}
+
+/// What the client knows of a file opened as `FileContentKind::LargeText`: the lines it read
+/// so far, and how much of the file the server has indexed. Lines are read as they come into
+/// view and forgotten again when they're far away.
+pub struct LargeFileCache {
+    pub size: u64,
+    pub indexed_lines: usize,
+    pub complete: bool,
+    blocks: HashMap<usize, Vec<String>>,
+    requested: HashSet<usize>,
+    pub search: LargeFileSearch,
+    /// A line the view should scroll to, when a search moves to a match.
+    pub jump_to: Option<usize>,
+    pending_copy: Option<(usize, usize)>,
+}
+
+impl LargeFileCache {
+    pub fn new(size: u64) -> Self {
+        Self {
+            size,
+            indexed_lines: 0,
+            complete: false,
+            blocks: HashMap::new(),
+            requested: HashSet::new(),
+            search: LargeFileSearch::default(),
+            jump_to: None,
+            pending_copy: None,
+        }
+    }
+
+    pub fn line(&self, line: usize) -> Option<&str> {
+        self.blocks.get(&(line / BLOCK_LINES))
+            .and_then( | block | block.get(line % BLOCK_LINES))
+            .map( | line | line.as_str())
+    }
+
+    /// Asks for the blocks of lines `start..end` that aren't there yet.
+    pub fn request_lines(&mut self, id: u64, start: usize, end: usize, send: &mut impl FnMut(FileRequest)) {
+        for block in start / BLOCK_LINES..(end + BLOCK_LINES - 1) / BLOCK_LINES {
+            if self.complete && block * BLOCK_LINES >= self.indexed_lines {
+                break
+            }
+            if !self.blocks.contains_key(&block) && self.requested.insert(block) {
+                send(FileRequest::ReadLines {id, start: block * BLOCK_LINES, count: BLOCK_LINES});
+            }
+        }
+    }
+
+    /// Stores lines that came in. Returns the text of a copy that was waiting for them.
+    pub fn insert_lines(&mut self, lines: LargeFileLines) -> Option<String> {
+        let block = lines.start / BLOCK_LINES;
+        self.indexed_lines = self.indexed_lines.max(lines.indexed_lines);
+        self.complete = lines.complete;
+        self.requested.remove(&block);
+        self.blocks.insert(block, lines.lines);
+        while self.blocks.len() > MAX_BLOCKS {
+            let furthest = *self.blocks.keys()
+                .filter( | other | self.pending_copy.map_or(true, | (start, end) | {
+                    **other < start / BLOCK_LINES || **other > end / BLOCK_LINES
+                }))
+                .max_by_key( | other | other.abs_diff(block))
+                .unwrap_or(&block);
+            if furthest == block {
+                break
+            }
+            self.blocks.remove(&furthest);
+        }
+        let (start, end) = self.pending_copy?;
+        let text = self.copy_lines(start, end)?;
+        self.pending_copy = None;
+        Some(text)
+    }
+
+    fn copy_lines(&self, start: usize, end: usize) -> Option<String> {
+        let mut text = String::new();
+        for line in start..end {
+            // lines past the end of a fully indexed file are simply not there
+            if self.complete && line >= self.indexed_lines {
+                break
+            }
+            text.push_str(self.line(line) ?);
+            text.push('\n');
+        }
+        Some(text)
+    }
+
+    /// The text of lines `start..end`, or `None` when they have to be read first, in which case
+    /// it comes back from `insert_lines`.
+    pub fn copy(&mut self, id: u64, start: usize, end: usize, send: &mut impl FnMut(FileRequest)) -> Option<String> {
+        let end = end.min(start + MAX_COPY_LINES);
+        if let Some(text) = self.copy_lines(start, end) {
+            return Some(text)
+        }
+        self.pending_copy = Some((start, end));
+        self.request_lines(id, start, end, send);
+        None
+    }
+
+    /// Starts a new search, or asks for the next batch of matches of the current one.
+    pub fn search(&mut self, id: u64, query: &str, send: &mut impl FnMut(FileRequest)) {
+        if query != self.search.query {
+            self.search = LargeFileSearch {query: query.to_string(), ..Default::default()};
+        }
+        if query.is_empty() || self.search.done || self.search.requested {
+            return
+        }
+        self.search.requested = true;
+        send(FileRequest::SearchLargeFile {
+            id,
+            query: query.to_string(),
+            from_line: self.search.next_line,
+            max_matches: MATCH_BATCH,
+        });
+    }
+
+    pub fn insert_matches(&mut self, matches: LargeFileMatches) {
+        if matches.query != self.search.query {
+            return
+        }
+        self.search.requested = false;
+        self.search.matches.extend(matches.matches);
+        self.search.next_line = matches.next_line;
+        self.search.done = matches.done;
+        if self.search.current.is_none() && !self.search.matches.is_empty() {
+            self.search.current = Some(0);
+            self.jump_to = Some(self.search.matches[0].line);
+        }
+    }
+
+    /// Moves to the next or previous match. Asks for more matches when it runs out of them.
+    pub fn step_match(&mut self, id: u64, forward: bool, send: &mut impl FnMut(FileRequest)) {
+        let count = self.search.matches.len();
+        let current = match (self.search.current, forward) {
+            (None, _) if count > 0 => 0,
+            (None, _) => return,
+            (Some(current), true) if current + 1 < count => current + 1,
+            (Some(_), true) => {
+                if !self.search.done {
+                    let query = self.search.query.clone();
+                    self.search(id, &query, send);
+                    return
+                }
+                0
+            }
+            (Some(current), false) => current.checked_sub(1).unwrap_or(count - 1),
+        };
+        self.search.current = Some(current);
+        self.jump_to = Some(self.search.matches[current].line);
+    }
+
+    pub fn current_match(&self) -> Option<&LargeFileMatch> {
+        self.search.matches.get(self.search.current?)
+    }
+}
@@ -9,3 +9,8 @@ pub mod file_client_wasm;
 pub use file_client_wasm::*;
 
 pub mod file_system;
+pub mod follow;
+pub mod large_file;
+pub mod save_policy;
+pub mod syntax_rules;
+pub mod workspace_edit;
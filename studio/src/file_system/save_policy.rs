@@ -0,0 +1,54 @@
+use crate::{
+    makepad_micro_serde::*,
+    makepad_file_protocol::Glob,
+    settings::SettingsFile,
+};
+
+/// When edits to a document are written back to disk.
+#[derive(Clone, Copy, Debug, PartialEq, SerRon, DeRon)]
+pub enum SavePolicy {
+    /// Only when saving explicitly with cmd/ctrl+S.
+    Explicit,
+    /// When the editor of the document loses key focus.
+    OnFocusLost,
+    /// When no edits were made for the given number of seconds.
+    AfterIdle(f64),
+    /// On every edit, so the running app hot reloads design changes as you type.
+    LiveWrite,
+}
+
+#[derive(Clone, Debug, SerRon, DeRon)]
+pub struct SavePolicyRule {
+    /// A glob with gitignore conventions, like `*.rs` or `examples/**`.
+    pub pattern: String,
+    pub policy: SavePolicy,
+}
+
+/// The save policy settings, matched against file paths relative to the root. The first matching
+/// rule wins.
+#[derive(Clone, Debug, SerRon, DeRon)]
+pub struct SavePolicies {
+    pub rules: Vec<SavePolicyRule>,
+    pub default: SavePolicy,
+}
+
+impl Default for SavePolicies {
+    fn default() -> Self {
+        Self {
+            rules: Vec::new(),
+            default: SavePolicy::LiveWrite,
+        }
+    }
+}
+
+impl SettingsFile for SavePolicies {
+    const SETTINGS_FILE: &'static str = "save_policies.ron";
+}
+
+impl SavePolicies {
+    pub fn policy_for_path(&self, path: &str) -> SavePolicy {
+        self.rules.iter()
+            .find( | rule | Glob::new(&rule.pattern).is_match(path, false))
+            .map_or(self.default, | rule | rule.policy)
+    }
+}
@@ -0,0 +1,51 @@
+use {
+    crate::{
+        makepad_widgets::*,
+        makepad_code_editor::TokenizerRules,
+    },
+    std::{fs, rc::Rc},
+};
+
+/// Syntax highlighting for file types the editor has no tokenizer for, declared as lists of
+/// regex rules in the TOML files of the settings folder. See `TokenizerRules` for the format.
+#[derive(Default)]
+pub struct SyntaxRules {
+    rules: Vec<Rc<TokenizerRules >>,
+}
+
+impl SyntaxRules {
+    pub const SETTINGS_DIR: &'static str = "makepad_syntaxes";
+
+    /// Loads the `.toml` files in the settings folder in the working directory, if there is one.
+    pub fn load() -> Self {
+        let Ok(entries) = fs::read_dir(Self::SETTINGS_DIR) else {
+            return Self::default()
+        };
+        let mut paths: Vec<_> = entries
+            .filter_map( | entry | entry.ok().map( | entry | entry.path()))
+            .filter( | path | path.extension().map_or(false, | extension | extension == "toml"))
+            .collect();
+        // the first file that claims an extension wins, so the order mustn't depend on the disk
+        paths.sort();
+        let mut rules = Vec::new();
+        for path in paths {
+            let parsed = fs::read_to_string(&path)
+                .map_err( | err | err.to_string())
+                .and_then( | data | TokenizerRules::from_toml(&data));
+            match parsed {
+                Ok(parsed) => rules.push(Rc::new(parsed)),
+                Err(err) => log!("Cannot load syntax rules {}: {}", path.display(), err)
+            }
+        }
+        Self {rules}
+    }
+
+    /// The rules for a file, by the language detected for it or by its extension.
+    pub fn for_file(&self, path: &str, language: Option<&str>) -> Option<Rc<TokenizerRules >> {
+        let extension = path.rsplit('/').next()?.rsplit_once('.').map( | (_, extension) | extension);
+        self.rules.iter().find( | rules | {
+            language == Some(rules.name.as_str())
+                || extension.is_some_and( | extension | rules.extensions.iter().any( | other | other == extension))
+        }).cloned()
+    }
+}
@@ -0,0 +1,35 @@
+use crate::makepad_code_editor::code_action::{Hunk, WorkspaceEdit};
+
+/// How many lines of a preview are shown before the rest is left out.
+const MAX_PREVIEW_LINES: usize = 60;
+
+/// A workspace edit that waits for the files it touches to load, or for the user to accept its
+/// preview.
+pub struct PendingWorkspaceEdit {
+    pub id: u64,
+    pub title: String,
+    pub edit: WorkspaceEdit,
+    pub preview: bool,
+    /// Whether the preview has been shown, so it is only shown once.
+    pub previewed: bool,
+}
+
+/// Renders the hunks of each file like a unified diff, with the path of the file above its
+/// hunks and line numbers counting from 1.
+pub fn preview_text(files: &[(String, Vec<Hunk>)]) -> String {
+    let mut lines = Vec::new();
+    for (file_name, hunks) in files {
+        lines.push(file_name.clone());
+        for hunk in hunks {
+            lines.push(format!("@@ line {}", hunk.line_index + 1));
+            lines.extend(hunk.old_lines.iter().map( | line | format!("- {}", line)));
+            lines.extend(hunk.new_lines.iter().map( | line | format!("+ {}", line)));
+        }
+    }
+    if lines.len() > MAX_PREVIEW_LINES {
+        let more = lines.len() - MAX_PREVIEW_LINES;
+        lines.truncate(MAX_PREVIEW_LINES);
+        lines.push(format!("... and {} more lines", more));
+    }
+    lines.join("\n")
+}
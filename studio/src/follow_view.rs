@@ -0,0 +1,211 @@
+use {
+    crate::{
+        file_system::{file_system::FileSystem, follow::colorize_line},
+        makepad_widgets::*,
+    },
+};
+
+live_design!{
+    import makepad_draw::shader::std::*;
+    import makepad_widgets::base::*;
+    import makepad_widgets::theme_desktop_dark::*;
+
+    FollowView = {{FollowView}} {
+        width: Fill, height: Fill,
+        scroll_bars: <ScrollBars> {}
+        draw_bg: { color: (THEME_COLOR_BG_CONTAINER) }
+        draw_gutter: {
+            text_style: <THEME_FONT_CODE> {},
+            color: (THEME_COLOR_TEXT_META),
+        }
+        draw_text: {
+            text_style: <THEME_FONT_CODE> {},
+            color: (THEME_COLOR_TEXT_DEFAULT),
+        }
+        draw_selection: { color: (THEME_COLOR_CTRL_SELECTED) }
+        toolbar: <DockToolbar> {
+            content = {
+                align: { x: 0., y: 0.5 }
+                spacing: (THEME_SPACE_2)
+                info = <Label> {
+                    width: Fit,
+                    draw_text: { color: (THEME_COLOR_TEXT_META) }
+                }
+                <View> { width: Fill, height: Fit }
+                clear = <ButtonFlat> { text: "Clear" }
+                follow = <ButtonFlat> { text: "Follow" }
+            }
+        }
+    }
+}
+
+/// Shows a file that is followed as it grows, like `tail -f`. It sticks to the bottom as lines
+/// come in, unless it is scrolled up, and colors the lines with the colorizers from the follow
+/// settings.
+#[derive(Live, LiveHook, LiveRegister)]
+pub struct FollowView {
+    #[walk] walk: Walk,
+    #[live] toolbar: View,
+    #[live] scroll_bars: ScrollBars,
+    #[live] draw_bg: DrawColor,
+    #[live] draw_gutter: DrawText,
+    #[live] draw_text: DrawText,
+    #[live] draw_selection: DrawColor,
+    #[rust] cell_size: DVec2,
+    #[rust] viewport_rect: Rect,
+    /// Where the scroll position is when at the bottom.
+    #[rust] bottom: f64,
+    #[rust(true)] stick_to_bottom: bool,
+    /// The selected lines, as the line the selection started at and the line it ends at.
+    #[rust] selection: Option<(usize, usize)>,
+}
+
+impl FollowView {
+    pub fn redraw(&mut self, cx: &mut Cx) {
+        self.scroll_bars.redraw(cx);
+        self.toolbar.redraw(cx);
+    }
+
+    fn selected_lines(&self) -> Option<(usize, usize)> {
+        let (anchor, cursor) = self.selection?;
+        Some((anchor.min(cursor), anchor.max(cursor) + 1))
+    }
+
+    fn line_at(&self, abs: DVec2) -> usize {
+        ((abs.y - self.viewport_rect.pos.y) / self.cell_size.y).max(0.0) as usize
+    }
+
+    pub fn draw_walk_follow(&mut self, cx: &mut Cx2d, file_system: &FileSystem, file_id: LiveId, walk: Walk) {
+        cx.begin_turtle(walk, Layout::flow_down());
+        let Some(followed) = file_system.followed_files.get(&file_id) else {
+            cx.end_turtle();
+            return
+        };
+        let info = format!(
+            "{} lines, {}",
+            followed.line_count(),
+            if self.stick_to_bottom {"following"} else {"scrolled up"}
+        );
+        self.toolbar.widget(id!(info)).set_text(&info);
+        self.toolbar.draw_all(cx, &mut Scope::empty());
+
+        self.cell_size = self.draw_text.text_style.font_size * self.draw_text.get_monospace_base(cx);
+        let line_count = followed.line_count();
+        let pad_left_top = dvec2(10.0, 10.0);
+        let content_height = line_count as f64 * self.cell_size.y + pad_left_top.y * 2.0;
+        self.bottom = (content_height - self.scroll_bars.get_scroll_view_visible().y).max(0.0);
+        if self.stick_to_bottom {
+            // the lines that just came in aren't part of the scroll view yet
+            self.scroll_bars.set_scroll_pos_no_clip(cx, dvec2(self.scroll_bars.get_scroll_pos().x, self.bottom));
+        }
+
+        self.scroll_bars.begin(cx, Walk::fill(), Layout::default());
+        let turtle_rect = cx.turtle().rect();
+        self.draw_bg.draw_abs(cx, cx.turtle().unscrolled_rect());
+        let scroll_pos = self.scroll_bars.get_scroll_pos();
+        let line_start = ((scroll_pos.y / self.cell_size.y) as usize).max(followed.dropped_lines);
+        let line_end = (line_start + (turtle_rect.size.y / self.cell_size.y) as usize + 2).min(line_count);
+
+        let gutter_width = (line_count.to_string().len() + 3) as f64 * self.cell_size.x;
+        self.viewport_rect = Rect {
+            pos: turtle_rect.pos + dvec2(gutter_width, 0.0) + pad_left_top,
+            size: turtle_rect.size - dvec2(gutter_width, 0.0) - pad_left_top,
+        };
+        let default_color = self.draw_text.color;
+        let mut max_width = 0.0f64;
+        for line in line_start..line_end {
+            let y = self.viewport_rect.pos.y + line as f64 * self.cell_size.y;
+            if let Some((start, end)) = self.selected_lines() {
+                if line >= start && line < end {
+                    self.draw_selection.draw_abs(cx, Rect {
+                        pos: dvec2(turtle_rect.pos.x, y),
+                        size: dvec2(turtle_rect.size.x.max(max_width), self.cell_size.y),
+                    });
+                }
+            }
+            self.draw_gutter.draw_abs(cx, dvec2(turtle_rect.pos.x + pad_left_top.x, y), &format!("{: >4}", line + 1));
+            let Some(text) = followed.line(line) else {
+                continue
+            };
+            let (line_color, spans) = colorize_line(&file_system.colorizers, text);
+            let line_color = line_color.unwrap_or(default_color);
+            // draws the text in pieces, the ones a colorizer matched in its color
+            let mut column = 0;
+            let mut draw_piece = | cx: &mut Cx2d, draw_text: &mut DrawText, piece: &str, color: Vec4 | {
+                if piece.is_empty() {
+                    return
+                }
+                draw_text.color = color;
+                draw_text.draw_abs(cx, dvec2(self.viewport_rect.pos.x + column as f64 * self.cell_size.x, y), piece);
+                column += piece.chars().count();
+            };
+            let mut offset = 0;
+            for (start, end, color) in spans {
+                draw_piece(cx, &mut self.draw_text, &text[offset..start], line_color);
+                draw_piece(cx, &mut self.draw_text, &text[start..end], color);
+                offset = end;
+            }
+            draw_piece(cx, &mut self.draw_text, &text[offset..], line_color);
+            max_width = max_width.max(column as f64 * self.cell_size.x);
+        }
+        self.draw_text.color = default_color;
+        cx.turtle_mut().set_used(
+            gutter_width + max_width + pad_left_top.x,
+            content_height,
+        );
+        self.scroll_bars.end(cx);
+        cx.end_turtle();
+    }
+
+    pub fn handle_event_follow(&mut self, cx: &mut Cx, event: &Event, file_system: &mut FileSystem, file_id: LiveId) {
+        if !self.scroll_bars.handle_event(cx, event).is_empty() {
+            // scrolling back down to the bottom follows again
+            self.stick_to_bottom = self.scroll_bars.get_scroll_pos().y >= self.bottom - self.cell_size.y * 0.5;
+            self.redraw(cx);
+        }
+        let actions = cx.capture_actions( | cx | self.toolbar.handle_event(cx, event, &mut Scope::empty()));
+        if self.toolbar.button(id!(clear)).clicked(&actions) {
+            if let Some(followed) = file_system.followed_files.get_mut(&file_id) {
+                followed.clear();
+            }
+            self.selection = None;
+            self.redraw(cx);
+        }
+        if self.toolbar.button(id!(follow)).clicked(&actions) {
+            self.stick_to_bottom = true;
+            self.redraw(cx);
+        }
+        match event.hits(cx, self.scroll_bars.area()) {
+            Hit::FingerDown(fe) => {
+                cx.set_key_focus(self.scroll_bars.area());
+                let line = self.line_at(fe.abs);
+                self.selection = match (fe.modifiers.shift, self.selection) {
+                    (true, Some((anchor, _))) => Some((anchor, line)),
+                    _ => Some((line, line)),
+                };
+                self.redraw(cx);
+            }
+            Hit::FingerMove(fe) => {
+                if let Some((anchor, _)) = self.selection {
+                    self.selection = Some((anchor, self.line_at(fe.abs)));
+                    self.redraw(cx);
+                }
+            }
+            Hit::TextCopy(ce) => {
+                if let (Some((start, end)), Some(followed)) = (self.selected_lines(), file_system.followed_files.get(&file_id)) {
+                    let mut text = String::new();
+                    for line in (start..end).filter_map( | line | followed.line(line)) {
+                        text.push_str(line);
+                        text.push('\n');
+                    }
+                    *ce.response.borrow_mut() = Some(text);
+                }
+            }
+            Hit::KeyDown(KeyEvent {key_code: KeyCode::Escape, ..}) => {
+                self.selection = None;
+                self.redraw(cx);
+            }
+            _ => ()
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use {
+    crate::{
+        makepad_widgets::*,
+        keymap::KeyHint,
+    },
+};
+
+live_design!{
+    import makepad_widgets::theme_desktop_dark::*;
+
+    KeyHints = {{KeyHints}} {
+        width: Fill, height: Fill,
+        align: { x: 1.0, y: 1.0 }
+        padding: { right: 20, bottom: 40 }
+        list_walk: { width: Fit, height: Fit }
+        list_layout: { flow: Down, padding: <THEME_MSPACE_2> {} }
+        row_walk: { width: Fit, height: 22 }
+        row_layout: { flow: Right, spacing: (THEME_SPACE_3), align: { y: 0.5 } }
+        key_walk: { width: 80, height: Fit }
+        draw_bg: { color: (THEME_COLOR_BG_CONTAINER) }
+        draw_pending: {
+            text_style: <THEME_FONT_BOLD> {}
+            color: (THEME_COLOR_TEXT_DEFAULT)
+        }
+        draw_key: {
+            text_style: <THEME_FONT_CODE> {}
+            color: (THEME_COLOR_TEXT_DEFAULT)
+        }
+        draw_command: {
+            text_style: <THEME_FONT_REGULAR> {}
+            color: (THEME_COLOR_TEXT_META)
+        }
+    }
+}
+
+/// Shows the keys that continue a pending key sequence, and what they do.
+#[derive(Live, LiveHook, Widget)]
+pub struct KeyHints {
+    #[walk] walk: Walk,
+    #[layout] layout: Layout,
+    #[redraw] #[live] draw_bg: DrawColor,
+    #[live] draw_pending: DrawText,
+    #[live] draw_key: DrawText,
+    #[live] draw_command: DrawText,
+    #[live] list_walk: Walk,
+    #[live] list_layout: Layout,
+    #[live] row_walk: Walk,
+    #[live] row_layout: Layout,
+    #[live] key_walk: Walk,
+    #[rust] pending_keys: String,
+    #[rust] hints: Vec<KeyHint>,
+}
+
+impl Widget for KeyHints {
+    fn draw_walk(&mut self, cx: &mut Cx2d, _scope: &mut Scope, walk: Walk) -> DrawStep {
+        if self.hints.is_empty() {
+            return DrawStep::done()
+        }
+        cx.begin_turtle(walk, self.layout);
+        self.draw_bg.begin(cx, self.list_walk, self.list_layout);
+        cx.begin_turtle(self.row_walk, self.row_layout);
+        self.draw_pending.draw_walk(cx, Walk::fit(), Align::default(), &self.pending_keys);
+        cx.end_turtle();
+        for hint in &self.hints {
+            cx.begin_turtle(self.row_walk, self.row_layout);
+            self.draw_key.draw_walk(cx, self.key_walk, Align::default(), &hint.key);
+            let command = match &hint.command {
+                Some(command) => command.replace('_', " "),
+                None => "...".to_string()
+            };
+            self.draw_command.draw_walk(cx, Walk::fit(), Align::default(), &command);
+            cx.end_turtle();
+        }
+        self.draw_bg.end(cx);
+        cx.end_turtle();
+        DrawStep::done()
+    }
+
+    fn handle_event(&mut self, _cx: &mut Cx, _event: &Event, _scope: &mut Scope) {
+    }
+}
+
+impl KeyHintsRef {
+    pub fn show(&self, cx: &mut Cx, pending_keys: String, hints: Vec<KeyHint>) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.pending_keys = pending_keys;
+            inner.hints = hints;
+            inner.draw_bg.redraw(cx);
+        }
+    }
+
+    pub fn hide(&self, cx: &mut Cx) {
+        self.show(cx, String::new(), Vec::new());
+    }
+}
@@ -0,0 +1,301 @@
+use crate::{
+    makepad_micro_serde::*,
+    makepad_widgets::*,
+    settings::SettingsFile,
+};
+
+/// How long a sequence waits for its next key, in seconds.
+const DEFAULT_TIMEOUT: f64 = 1.5;
+
+/// The bindings the studio comes with. Keymap settings are added in front of these, so they can
+/// take over any of their keys.
+const DEFAULT_BINDINGS: &[(&str, &str)] = &[
+    ("mod+backtick", "recompile"),
+    ("mod+k", "clear_log"),
+    ("mod+r", "reload_file_tree"),
+    ("mod+s", "save_all_files"),
+    ("mod+j", "prev_edit_location"),
+    ("mod+shift+j", "next_edit_location"),
+    ("mod+alt+c", "copy_panel_contents"),
+    ("mod+g r", "recompile"),
+    ("mod+g k", "clear_log"),
+    ("mod+g f", "reload_file_tree"),
+    ("mod+g s", "save_all_files"),
+    ("mod+g c", "copy_panel_contents"),
+];
+
+/// The names keys are written with, besides the characters `KeyCode::to_char` gives.
+const KEY_NAMES: &[(&str, KeyCode)] = &[
+    ("space", KeyCode::Space),
+    ("tab", KeyCode::Tab),
+    ("enter", KeyCode::ReturnKey),
+    ("escape", KeyCode::Escape),
+    ("backspace", KeyCode::Backspace),
+    ("delete", KeyCode::Delete),
+    ("backtick", KeyCode::Backtick),
+    ("up", KeyCode::ArrowUp),
+    ("down", KeyCode::ArrowDown),
+    ("left", KeyCode::ArrowLeft),
+    ("right", KeyCode::ArrowRight),
+    ("home", KeyCode::Home),
+    ("end", KeyCode::End),
+    ("pageup", KeyCode::PageUp),
+    ("pagedown", KeyCode::PageDown),
+    ("f1", KeyCode::F1),
+    ("f2", KeyCode::F2),
+    ("f3", KeyCode::F3),
+    ("f4", KeyCode::F4),
+    ("f5", KeyCode::F5),
+    ("f6", KeyCode::F6),
+    ("f7", KeyCode::F7),
+    ("f8", KeyCode::F8),
+    ("f9", KeyCode::F9),
+    ("f10", KeyCode::F10),
+    ("f11", KeyCode::F11),
+    ("f12", KeyCode::F12),
+];
+
+/// The keys that can be written as the character they type.
+const CHAR_KEYS: &[KeyCode] = &[
+    KeyCode::KeyA, KeyCode::KeyB, KeyCode::KeyC, KeyCode::KeyD, KeyCode::KeyE, KeyCode::KeyF,
+    KeyCode::KeyG, KeyCode::KeyH, KeyCode::KeyI, KeyCode::KeyJ, KeyCode::KeyK, KeyCode::KeyL,
+    KeyCode::KeyM, KeyCode::KeyN, KeyCode::KeyO, KeyCode::KeyP, KeyCode::KeyQ, KeyCode::KeyR,
+    KeyCode::KeyS, KeyCode::KeyT, KeyCode::KeyU, KeyCode::KeyV, KeyCode::KeyW, KeyCode::KeyX,
+    KeyCode::KeyY, KeyCode::KeyZ, KeyCode::Key0, KeyCode::Key1, KeyCode::Key2, KeyCode::Key3,
+    KeyCode::Key4, KeyCode::Key5, KeyCode::Key6, KeyCode::Key7, KeyCode::Key8, KeyCode::Key9,
+    KeyCode::Minus, KeyCode::Equals, KeyCode::Semicolon, KeyCode::Backslash, KeyCode::Comma,
+    KeyCode::Period, KeyCode::Slash,
+];
+
+/// A key with the modifiers held, written like `ctrl+k`, `mod+shift+j` or `space`. `mod` is
+/// ctrl or cmd, whichever the platform uses for shortcuts.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct KeyStroke {
+    pub key_code: KeyCode,
+    pub shift: bool,
+    pub alt: bool,
+    pub control: bool,
+    pub logo: bool,
+    /// Matches ctrl or cmd, instead of exactly `control` and `logo`.
+    pub primary: bool,
+}
+
+impl KeyStroke {
+    pub fn parse(stroke: &str) -> Result<Self, String> {
+        let mut parts: Vec<&str> = stroke.split('+').collect();
+        let key = parts.pop().unwrap_or("");
+        let mut result = Self {
+            key_code: key_code_from_name(key).ok_or_else( || format!("unknown key {:?}", key)) ?,
+            shift: false,
+            alt: false,
+            control: false,
+            logo: false,
+            primary: false,
+        };
+        for modifier in parts {
+            match modifier {
+                "shift" => result.shift = true,
+                "alt" => result.alt = true,
+                "ctrl" => result.control = true,
+                "cmd" => result.logo = true,
+                "mod" => result.primary = true,
+                _ => return Err(format!("unknown modifier {:?}", modifier))
+            }
+        }
+        Ok(result)
+    }
+
+    pub fn matches(&self, event: &KeyEvent) -> bool {
+        let modifiers = &event.modifiers;
+        let primary_matches = if self.primary {
+            modifiers.control || modifiers.logo
+        }
+        else {
+            modifiers.control == self.control && modifiers.logo == self.logo
+        };
+        event.key_code == self.key_code
+            && modifiers.shift == self.shift
+            && modifiers.alt == self.alt
+            && primary_matches
+    }
+
+    pub fn name(&self) -> String {
+        let mut name = String::new();
+        for (held, modifier) in [(self.primary, "mod+"), (self.control, "ctrl+"), (self.logo, "cmd+"), (self.alt, "alt+"), (self.shift, "shift+")] {
+            if held {
+                name.push_str(modifier);
+            }
+        }
+        match KEY_NAMES.iter().find( | (_, key_code) | *key_code == self.key_code) {
+            Some((key_name, _)) => name.push_str(key_name),
+            None => name.extend(self.key_code.to_char(false)),
+        }
+        name
+    }
+}
+
+fn key_code_from_name(name: &str) -> Option<KeyCode> {
+    if let Some((_, key_code)) = KEY_NAMES.iter().find( | (key_name, _) | *key_name == name) {
+        return Some(*key_code)
+    }
+    let mut chars = name.chars();
+    let char = chars.next().filter( | _ | chars.next().is_none()) ?;
+    CHAR_KEYS.iter().copied().find( | key_code | key_code.to_char(false) == Some(char))
+}
+
+#[derive(Clone, Debug, SerRon, DeRon)]
+pub struct KeyBindingRule {
+    /// Key strokes separated by spaces, like `mod+g s`.
+    pub keys: String,
+    pub command: String,
+}
+
+/// The keymap settings. Bindings with a command of `none` turn off a default binding.
+#[derive(Clone, Debug, Default, SerRon, DeRon)]
+pub struct KeymapSettings {
+    pub timeout: Option<f64>,
+    pub bindings: Vec<KeyBindingRule>,
+}
+
+impl SettingsFile for KeymapSettings {
+    const SETTINGS_FILE: &'static str = "keymap.ron";
+}
+
+#[derive(Clone, Debug)]
+pub struct KeyBinding {
+    pub keys: Vec<KeyStroke>,
+    pub command: String,
+}
+
+/// A key that continues the pending sequence, and what it leads to.
+#[derive(Clone, Debug, PartialEq)]
+pub struct KeyHint {
+    pub key: String,
+    /// The command it runs, or `None` when more keys follow.
+    pub command: Option<String>,
+}
+
+pub enum KeymapResult {
+    /// The keys so far ran the command.
+    Command(String),
+    /// The keys so far start a sequence, which waits for the next key.
+    Pending,
+    /// The key isn't bound, and ended a pending sequence if there was one.
+    Unbound,
+}
+
+/// Maps key strokes and sequences of them to commands. A sequence starts with its first stroke
+/// and waits `timeout` seconds for each next one, so a command tree can hang off a leader key
+/// like `mod+g`. When a sequence is both bound and the start of longer ones, the timeout runs
+/// the shorter one.
+pub struct Keymap {
+    pub bindings: Vec<KeyBinding>,
+    pub timeout: f64,
+    pending: Vec<KeyStroke>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self::from_settings(&KeymapSettings::default())
+    }
+}
+
+impl Keymap {
+    pub fn from_settings(settings: &KeymapSettings) -> Self {
+        let mut bindings: Vec<KeyBinding> = Vec::new();
+        let rules = settings.bindings.iter()
+            .map( | rule | (rule.keys.as_str(), rule.command.as_str()))
+            .chain(DEFAULT_BINDINGS.iter().copied());
+        for (keys, command) in rules {
+            let keys = match keys.split_whitespace().map(KeyStroke::parse).collect::<Result<Vec<_>, _>>() {
+                Ok(keys) if !keys.is_empty() => keys,
+                Ok(_) => continue,
+                Err(err) => {
+                    log!("Cannot parse key binding {:?}: {}", keys, err);
+                    continue
+                }
+            };
+            // the first binding of a sequence wins, which is how settings override defaults
+            if !bindings.iter().any( | binding | binding.keys == keys) {
+                bindings.push(KeyBinding {keys, command: command.to_string()});
+            }
+        }
+        bindings.retain( | binding | binding.command != "none");
+        Self {
+            bindings,
+            timeout: settings.timeout.unwrap_or(DEFAULT_TIMEOUT),
+            pending: Vec::new(),
+        }
+    }
+
+    pub fn is_pending(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    /// The names of the pending keys, like `mod+g`.
+    pub fn pending_keys(&self) -> String {
+        self.pending.iter().map( | stroke | stroke.name()).collect::<Vec<_>>().join(" ")
+    }
+
+    /// The bindings that continue the pending keys.
+    fn continuing<'a>(&'a self, event: Option<&'a KeyEvent>) -> impl Iterator<Item = &'a KeyBinding> + 'a {
+        self.bindings.iter().filter(move | binding | {
+            binding.keys.len() > self.pending.len()
+                && binding.keys.iter().zip(&self.pending).all( | (a, b) | a == b)
+                && event.map_or(true, | event | binding.keys[self.pending.len()].matches(event))
+        })
+    }
+
+    pub fn handle_key_down(&mut self, event: &KeyEvent) -> KeymapResult {
+        // holding down a modifier for the next stroke doesn't end the sequence
+        if let KeyCode::Control | KeyCode::Alt | KeyCode::Shift | KeyCode::Logo = event.key_code {
+            return if self.is_pending() {KeymapResult::Pending} else {KeymapResult::Unbound}
+        }
+        let matching: Vec<&KeyBinding> = self.continuing(Some(event)).collect();
+        let Some(first) = matching.first() else {
+            self.pending.clear();
+            return KeymapResult::Unbound
+        };
+        let stroke = first.keys[self.pending.len()];
+        let length = self.pending.len() + 1;
+        let exact = matching.iter().find( | binding | binding.keys.len() == length).map( | binding | binding.command.clone());
+        let longer = matching.iter().any( | binding | binding.keys.len() > length);
+        match (exact, longer) {
+            (Some(command), false) => {
+                self.pending.clear();
+                KeymapResult::Command(command)
+            }
+            _ => {
+                self.pending.push(stroke);
+                KeymapResult::Pending
+            }
+        }
+    }
+
+    /// Ends the pending sequence after its timeout, with the command bound to the keys so far if
+    /// there is one.
+    pub fn timeout(&mut self) -> Option<String> {
+        let pending = std::mem::take(&mut self.pending);
+        self.bindings.iter().find( | binding | binding.keys == pending).map( | binding | binding.command.clone())
+    }
+
+    pub fn cancel(&mut self) {
+        self.pending.clear();
+    }
+
+    /// What each next key does, in the order the bindings are listed.
+    pub fn hints(&self) -> Vec<KeyHint> {
+        let mut hints: Vec<KeyHint> = Vec::new();
+        for binding in self.continuing(None) {
+            let key = binding.keys[self.pending.len()].name();
+            let command = (binding.keys.len() == self.pending.len() + 1).then( || binding.command.clone());
+            match hints.iter_mut().find( | hint | hint.key == key) {
+                Some(hint) => if hint.command.is_none() {
+                    hint.command = command
+                }
+                None => hints.push(KeyHint {key, command})
+            }
+        }
+        hints
+    }
+}
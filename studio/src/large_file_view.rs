@@ -0,0 +1,229 @@
+use {
+    crate::{
+        file_system::file_system::FileSystem,
+        makepad_widgets::*,
+    },
+};
+
+live_design!{
+    import makepad_draw::shader::std::*;
+    import makepad_widgets::base::*;
+    import makepad_widgets::theme_desktop_dark::*;
+
+    LargeFileView = {{LargeFileView}} {
+        width: Fill, height: Fill,
+        scroll_bars: <ScrollBars> {}
+        draw_bg: { color: (THEME_COLOR_BG_CONTAINER) }
+        draw_gutter: {
+            text_style: <THEME_FONT_CODE> {},
+            color: (THEME_COLOR_TEXT_META),
+        }
+        draw_text: {
+            text_style: <THEME_FONT_CODE> {},
+            color: (THEME_COLOR_TEXT_DEFAULT),
+        }
+        draw_selection: { color: (THEME_COLOR_CTRL_SELECTED) }
+        draw_match: { color: #f806 }
+        toolbar: <DockToolbar> {
+            content = {
+                align: { x: 0., y: 0.5 }
+                spacing: (THEME_SPACE_2)
+                info = <Label> {
+                    width: Fit,
+                    draw_text: { color: (THEME_COLOR_TEXT_META) }
+                }
+                search = <TextInput> {
+                    width: 200,
+                    empty_message: "Search",
+                }
+                prev = <ButtonFlat> { text: "Previous" }
+                next = <ButtonFlat> { text: "Next" }
+                matches = <Label> { width: Fit }
+                <View> { width: Fill, height: Fit }
+                follow = <ButtonFlat> { text: "Follow" }
+                make_editable = <ButtonFlat> { text: "Make editable" }
+            }
+        }
+    }
+}
+
+/// Shows a file that was opened as `FileContentKind::LargeText`. Only the lines in view are
+/// read from the server, so it can be scrolled, searched and copied from, but not edited, until
+/// it is made editable, which loads all of it into a document.
+#[derive(Live, LiveHook, LiveRegister)]
+pub struct LargeFileView {
+    #[walk] walk: Walk,
+    #[live] toolbar: View,
+    #[live] scroll_bars: ScrollBars,
+    #[live] draw_bg: DrawColor,
+    #[live] draw_gutter: DrawText,
+    #[live] draw_text: DrawText,
+    #[live] draw_selection: DrawColor,
+    #[live] draw_match: DrawColor,
+    #[rust] cell_size: DVec2,
+    #[rust] viewport_rect: Rect,
+    /// The selected lines, as the line the selection started at and the line it ends at.
+    #[rust] selection: Option<(usize, usize)>,
+}
+
+impl LargeFileView {
+    pub fn redraw(&mut self, cx: &mut Cx) {
+        self.scroll_bars.redraw(cx);
+        self.toolbar.redraw(cx);
+    }
+
+    fn selected_lines(&self) -> Option<(usize, usize)> {
+        let (anchor, cursor) = self.selection?;
+        Some((anchor.min(cursor), anchor.max(cursor) + 1))
+    }
+
+    fn line_at(&self, abs: DVec2) -> usize {
+        ((abs.y - self.viewport_rect.pos.y) / self.cell_size.y).max(0.0) as usize
+    }
+
+    pub fn draw_walk_large_file(&mut self, cx: &mut Cx2d, file_system: &mut FileSystem, file_id: LiveId, walk: Walk) {
+        cx.begin_turtle(walk, Layout::flow_down());
+        let Some(large_file) = file_system.large_files.get_mut(&file_id) else {
+            cx.end_turtle();
+            return
+        };
+        let info = format!(
+            "{:.1} MB, {}{} lines, read only",
+            large_file.size as f64 / (1024.0 * 1024.0),
+            if large_file.complete {""} else {"at least "},
+            large_file.indexed_lines
+        );
+        self.toolbar.widget(id!(info)).set_text(&info);
+        let search = &large_file.search;
+        let matches = match (search.query.is_empty(), search.current) {
+            (true, _) => String::new(),
+            (false, Some(current)) => format!("{} of {}{}", current + 1, search.matches.len(), if search.done {""} else {"+"}),
+            (false, None) if search.done => "No matches".to_string(),
+            (false, None) => "Searching".to_string(),
+        };
+        self.toolbar.widget(id!(matches)).set_text(&matches);
+        self.toolbar.draw_all(cx, &mut Scope::empty());
+
+        self.cell_size = self.draw_text.text_style.font_size * self.draw_text.get_monospace_base(cx);
+        if let Some(line) = large_file.jump_to.take() {
+            let y = line as f64 * self.cell_size.y - self.viewport_rect.size.y * 0.5;
+            self.scroll_bars.set_scroll_pos(cx, dvec2(0.0, y.max(0.0)));
+        }
+        let current_match = large_file.current_match().cloned();
+        let indexed_lines = large_file.indexed_lines;
+        let complete = large_file.complete;
+
+        self.scroll_bars.begin(cx, Walk::fill(), Layout::default());
+        let turtle_rect = cx.turtle().rect();
+        self.draw_bg.draw_abs(cx, cx.turtle().unscrolled_rect());
+        let scroll_pos = self.scroll_bars.get_scroll_pos();
+        let line_start = (scroll_pos.y / self.cell_size.y) as usize;
+        let line_end = line_start + (turtle_rect.size.y / self.cell_size.y) as usize + 2;
+        let line_end = if complete {line_end.min(indexed_lines)} else {line_end};
+        file_system.request_large_file_lines(file_id, line_start, line_end);
+        let large_file = &file_system.large_files[&file_id];
+
+        let gutter_width = (line_end.to_string().len() + 3) as f64 * self.cell_size.x;
+        let pad_left_top = dvec2(10.0, 10.0);
+        self.viewport_rect = Rect {
+            pos: turtle_rect.pos + dvec2(gutter_width, 0.0) + pad_left_top,
+            size: turtle_rect.size - dvec2(gutter_width, 0.0) - pad_left_top,
+        };
+        let mut max_width = 0.0f64;
+        for line in line_start..line_end {
+            let y = self.viewport_rect.pos.y + line as f64 * self.cell_size.y;
+            if let Some((start, end)) = self.selected_lines() {
+                if line >= start && line < end {
+                    self.draw_selection.draw_abs(cx, Rect {
+                        pos: dvec2(turtle_rect.pos.x, y),
+                        size: dvec2(turtle_rect.size.x.max(max_width), self.cell_size.y),
+                    });
+                }
+            }
+            self.draw_gutter.draw_abs(cx, dvec2(turtle_rect.pos.x + pad_left_top.x, y), &format!("{: >4}", line + 1));
+            // lines that are still being read show as empty
+            let Some(text) = large_file.line(line) else {
+                continue
+            };
+            if let Some(found) = current_match.as_ref().filter( | found | found.line == line) {
+                let column = | byte: usize | text.get(..byte).map_or(0, | text | text.chars().count()) as f64;
+                self.draw_match.draw_abs(cx, Rect {
+                    pos: dvec2(self.viewport_rect.pos.x + column(found.column_start) * self.cell_size.x, y),
+                    size: dvec2((column(found.column_end) - column(found.column_start)) * self.cell_size.x, self.cell_size.y),
+                });
+            }
+            self.draw_text.draw_abs(cx, dvec2(self.viewport_rect.pos.x, y), text);
+            max_width = max_width.max(text.chars().count() as f64 * self.cell_size.x);
+        }
+        // until the whole file is indexed there is always more to scroll to
+        let line_count = if complete {indexed_lines} else {indexed_lines.max(line_end) + (line_end - line_start) * 4};
+        cx.turtle_mut().set_used(
+            gutter_width + max_width + pad_left_top.x,
+            line_count as f64 * self.cell_size.y + self.viewport_rect.size.y,
+        );
+        self.scroll_bars.end(cx);
+        cx.end_turtle();
+    }
+
+    pub fn handle_event_large_file(&mut self, cx: &mut Cx, event: &Event, file_system: &mut FileSystem, file_id: LiveId) {
+        if !self.scroll_bars.handle_event(cx, event).is_empty() {
+            self.redraw(cx);
+        }
+        let actions = cx.capture_actions( | cx | self.toolbar.handle_event(cx, event, &mut Scope::empty()));
+        if let Some(query) = self.toolbar.text_input(id!(search)).changed(&actions) {
+            file_system.search_large_file(file_id, &query);
+            self.redraw(cx);
+        }
+        if self.toolbar.text_input(id!(search)).returned(&actions).is_some() || self.toolbar.button(id!(next)).clicked(&actions) {
+            file_system.step_large_file_match(file_id, true);
+            self.redraw(cx);
+        }
+        if self.toolbar.button(id!(prev)).clicked(&actions) {
+            file_system.step_large_file_match(file_id, false);
+            self.redraw(cx);
+        }
+        if self.toolbar.button(id!(follow)).clicked(&actions) {
+            file_system.follow_file(file_id);
+            self.redraw(cx);
+        }
+        if self.toolbar.button(id!(make_editable)).clicked(&actions) {
+            file_system.make_large_file_editable(file_id);
+        }
+        match event.hits(cx, self.scroll_bars.area()) {
+            Hit::FingerDown(fe) => {
+                cx.set_key_focus(self.scroll_bars.area());
+                let line = self.line_at(fe.abs);
+                self.selection = match (fe.modifiers.shift, self.selection) {
+                    (true, Some((anchor, _))) => Some((anchor, line)),
+                    _ => Some((line, line)),
+                };
+                self.redraw(cx);
+            }
+            Hit::FingerMove(fe) => {
+                if let Some((anchor, _)) = self.selection {
+                    self.selection = Some((anchor, self.line_at(fe.abs)));
+                    self.redraw(cx);
+                }
+            }
+            Hit::TextCopy(ce) => {
+                if let Some((start, end)) = self.selected_lines() {
+                    *ce.response.borrow_mut() = file_system.copy_large_file_lines(file_id, start, end);
+                }
+            }
+            Hit::KeyDown(KeyEvent {
+                key_code: KeyCode::KeyF,
+                modifiers: KeyModifiers {logo, control, ..},
+                ..
+            }) if logo || control => {
+                if let Some(search) = self.toolbar.text_input(id!(search)).borrow() {
+                    search.set_key_focus(cx);
+                }
+            }
+            Hit::KeyDown(KeyEvent {key_code: KeyCode::Escape, ..}) => {
+                self.selection = None;
+                self.redraw(cx);
+            }
+            _ => ()
+        }
+    }
+}
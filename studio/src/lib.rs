@@ -4,12 +4,27 @@ pub mod app;
 pub mod app_ui;
 pub mod build_manager;
 pub mod file_system;
+pub mod evaluator;
 pub mod studio_editor;
+pub mod large_file_view;
+pub mod follow_view;
+pub mod tab_kinds;
 pub mod studio_file_tree;
 pub mod log_list;
 pub mod run_list;
 pub mod run_view;
 pub mod profiler;
+pub mod unsaved_changes;
+pub mod navigation_history;
+pub mod workspace_trust;
+pub mod persistent_state;
+pub mod tool_environment;
+pub mod tab_switcher;
+pub mod search_results;
+pub mod keymap;
+pub mod settings;
+pub mod key_hints;
+pub mod lsp;
 //pub use makepad_code_editor;
 
 pub use makepad_widgets::makepad_draw;
@@ -29,4 +44,5 @@ pub use makepad_file_server;
 pub use makepad_widgets;
 pub use makepad_code_editor;
 pub use makepad_shell;
+pub use makepad_toml_parser;
 
@@ -282,6 +282,23 @@ impl Widget for LogList {
         DrawStep::done()
     }
     
+    fn reading_text(&self, scope: &mut Scope, out: &mut String) {
+        let build_manager = &scope.data.get::<AppData>().unwrap().build_manager;
+        for (_build_id, log_item) in &build_manager.log {
+            match log_item {
+                LogItem::Bare(msg) => push_reading_line(out, &msg.line),
+                LogItem::Location(msg) => push_reading_line(out, &format!(
+                    "{}:{}:{}: {}",
+                    msg.file_name,
+                    msg.start.line_index + 1,
+                    msg.start.byte_index + 1,
+                    msg.message
+                )),
+                _ => ()
+            }
+        }
+    }
+    
     fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope){
         let log_list = self.view.portal_list(id!(list));
         self.view.handle_event(cx, event, scope);
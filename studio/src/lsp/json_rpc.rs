@@ -0,0 +1,195 @@
+use {
+    crate::makepad_micro_serde::*,
+    std::{
+        collections::HashMap,
+        io::{self, BufRead, Write},
+    },
+};
+
+/// A message of the JSON-RPC protocol that language servers speak over their stdio.
+#[derive(Clone, Debug)]
+pub enum JsonRpcMessage {
+    Request {id: JsonValue, method: String, params: JsonValue},
+    Response {id: JsonValue, result: Result<JsonValue, JsonRpcError>},
+    Notification {method: String, params: JsonValue},
+}
+
+#[derive(Clone, Debug)]
+pub struct JsonRpcError {
+    pub code: i64,
+    pub message: String,
+}
+
+impl JsonRpcMessage {
+    /// The message `value` holds, or `None` if it isn't one.
+    pub fn from_json(value: JsonValue) -> Option<Self> {
+        let JsonValue::Object(mut obj) = value else {
+            return None
+        };
+        let params = obj.remove("params").unwrap_or(JsonValue::Null);
+        match (obj.remove("id"), obj.remove("method")) {
+            (Some(id), Some(JsonValue::String(method))) => Some(Self::Request {id, method, params}),
+            (None, Some(JsonValue::String(method))) => Some(Self::Notification {method, params}),
+            (Some(id), None) => {
+                let result = match obj.remove("error") {
+                    Some(error) => Err(JsonRpcError {
+                        code: match error.key("code") {
+                            Some(JsonValue::I64(code)) => *code,
+                            Some(JsonValue::U64(code)) => *code as i64,
+                            _ => 0
+                        },
+                        message: error.key("message").and_then( | message | message.string()).cloned().unwrap_or_default(),
+                    }),
+                    None => Ok(obj.remove("result").unwrap_or(JsonValue::Null))
+                };
+                Some(Self::Response {id, result})
+            }
+            _ => None
+        }
+    }
+    
+    pub fn to_json(&self) -> JsonValue {
+        let mut obj = HashMap::new();
+        obj.insert("jsonrpc".to_string(), JsonValue::String("2.0".to_string()));
+        match self {
+            Self::Request {id, method, params} => {
+                obj.insert("id".to_string(), id.clone());
+                obj.insert("method".to_string(), JsonValue::String(method.clone()));
+                obj.insert("params".to_string(), params.clone());
+            }
+            Self::Response {id, result} => {
+                obj.insert("id".to_string(), id.clone());
+                match result {
+                    Ok(result) => {
+                        obj.insert("result".to_string(), result.clone());
+                    }
+                    Err(error) => {
+                        obj.insert("error".to_string(), object([
+                            ("code", JsonValue::I64(error.code)),
+                            ("message", JsonValue::String(error.message.clone())),
+                        ]));
+                    }
+                }
+            }
+            Self::Notification {method, params} => {
+                obj.insert("method".to_string(), JsonValue::String(method.clone()));
+                obj.insert("params".to_string(), params.clone());
+            }
+        }
+        JsonValue::Object(obj)
+    }
+}
+
+/// Builds a JSON object out of its fields.
+pub fn object<const N: usize>(fields: [(&str, JsonValue); N]) -> JsonValue {
+    JsonValue::Object(fields.into_iter().map( | (key, value) | (key.to_string(), value)).collect())
+}
+
+pub fn string(value: &str) -> JsonValue {
+    JsonValue::String(value.to_string())
+}
+
+/// Writes `message` behind the `Content-Length` header that frames it.
+pub fn write_message(writer: &mut impl Write, message: &JsonRpcMessage) -> io::Result<()> {
+    let body = message.to_json().serialize_json();
+    write!(writer, "Content-Length: {}\r\n\r\n{}", body.len(), body) ?;
+    writer.flush()
+}
+
+/// Reads the next framed message, or `None` once the stream ends. Frames that don't hold a
+/// message are skipped.
+pub fn read_message(reader: &mut impl BufRead) -> io::Result<Option<JsonRpcMessage>> {
+    loop {
+        let mut content_length = None;
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line) ? == 0 {
+                return Ok(None)
+            }
+            let line = line.trim_end();
+            if line.is_empty() {
+                // servers may log a stray blank line before the headers
+                if content_length.is_some() {
+                    break
+                }
+                continue
+            }
+            if let Some((name, value)) = line.split_once(':') {
+                if name.eq_ignore_ascii_case("Content-Length") {
+                    content_length = value.trim().parse::<usize>().ok();
+                }
+            }
+        }
+        let mut body = vec![0; content_length.unwrap()];
+        reader.read_exact(&mut body) ?;
+        let Ok(body) = String::from_utf8(body) else {
+            continue
+        };
+        if let Some(message) = JsonValue::deserialize_json(&body).ok().and_then(JsonRpcMessage::from_json) {
+            return Ok(Some(message))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(json: &str) -> Option<JsonRpcMessage> {
+        JsonRpcMessage::from_json(JsonValue::deserialize_json(json).unwrap())
+    }
+
+    #[test]
+    fn messages_are_told_apart_by_their_fields() {
+        assert!(matches!(
+            parse(r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}"#),
+            Some(JsonRpcMessage::Request {id: JsonValue::U64(1), method, params: JsonValue::Object(_)}) if method == "initialize"
+        ));
+        assert!(matches!(
+            parse(r#"{"jsonrpc":"2.0","method":"initialized"}"#),
+            Some(JsonRpcMessage::Notification {method, params: JsonValue::Null}) if method == "initialized"
+        ));
+        assert!(matches!(
+            parse(r#"{"jsonrpc":"2.0","id":"a","result":[1,2]}"#),
+            Some(JsonRpcMessage::Response {id: JsonValue::String(id), result: Ok(JsonValue::Array(_))}) if id == "a"
+        ));
+        assert!(matches!(
+            parse(r#"{"jsonrpc":"2.0","id":2,"error":{"code":-32601,"message":"unknown method"}}"#),
+            Some(JsonRpcMessage::Response {result: Err(JsonRpcError {code: -32601, message}), ..}) if message == "unknown method"
+        ));
+        assert!(parse(r#"{"jsonrpc":"2.0"}"#).is_none());
+        assert!(parse(r#"[1]"#).is_none());
+    }
+
+    #[test]
+    fn messages_survive_being_written_and_read() {
+        let messages = [
+            JsonRpcMessage::Request {id: JsonValue::U64(3), method: "textDocument/hover".to_string(), params: object([("uri", string("file:///ü.rs"))])},
+            JsonRpcMessage::Notification {method: "exit".to_string(), params: JsonValue::Null},
+            JsonRpcMessage::Response {id: JsonValue::U64(4), result: Err(JsonRpcError {code: 1, message: "failed".to_string()})},
+        ];
+        let mut data = Vec::new();
+        for message in &messages {
+            write_message(&mut data, message).unwrap();
+        }
+        let mut reader = &data[..];
+        assert!(matches!(
+            read_message(&mut reader).unwrap(),
+            Some(JsonRpcMessage::Request {id: JsonValue::U64(3), method, params}) if method == "textDocument/hover" && params.key("uri").and_then( | uri | uri.string()).map( | uri | uri.as_str()) == Some("file:///ü.rs")
+        ));
+        assert!(matches!(read_message(&mut reader).unwrap(), Some(JsonRpcMessage::Notification {method, ..}) if method == "exit"));
+        assert!(matches!(
+            read_message(&mut reader).unwrap(),
+            Some(JsonRpcMessage::Response {id: JsonValue::U64(4), result: Err(JsonRpcError {code: 1, message})}) if message == "failed"
+        ));
+        assert!(read_message(&mut reader).unwrap().is_none());
+    }
+
+    #[test]
+    fn reading_skips_blank_lines_and_frames_without_a_message() {
+        let data = "\r\nContent-Length: 2\r\n\r\n{}Content-Type: application/vscode-jsonrpc\r\ncontent-length: 17\r\n\r\n{\"method\":\"exit\"}";
+        let mut reader = data.as_bytes();
+        assert!(matches!(read_message(&mut reader).unwrap(), Some(JsonRpcMessage::Notification {method, ..}) if method == "exit"));
+        assert!(read_message(&mut reader).unwrap().is_none());
+    }
+}
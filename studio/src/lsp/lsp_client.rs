@@ -0,0 +1,757 @@
+use {
+    crate::{
+        makepad_micro_serde::*,
+        makepad_widgets::*,
+        makepad_code_editor::{
+            completion::{Completion, CompletionKind},
+            diagnostic::{Diagnostic, Severity},
+            text::{Change, Edit, Position, Text},
+        },
+        lsp::json_rpc::{self, object, string, JsonRpcError, JsonRpcMessage},
+    },
+    std::{
+        collections::HashMap,
+        fs,
+        io::{self, BufRead, BufReader},
+        path::{Path, PathBuf},
+        process::{Child, Command, Stdio},
+        sync::mpsc::{self, Receiver, Sender},
+        thread,
+        time::Duration,
+    },
+};
+
+/// How long a server gets to exit after being asked to, before it is killed.
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// JSON-RPC error code for requests the client doesn't handle.
+const METHOD_NOT_FOUND: i64 = -32601;
+
+/// Identifies a request, so its event can be told apart from those of earlier requests that
+/// were overtaken.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct LspRequestId(pub u64);
+
+/// What positions on the wire count the characters of a line in.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PositionEncoding {
+    Utf8,
+    /// The default of the protocol, used when the server doesn't pick UTF-8.
+    Utf16,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum DiagnosticSeverity {
+    Error,
+    Warning,
+    Information,
+    Hint,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct LspDiagnostic {
+    pub start: Position,
+    pub end: Position,
+    pub severity: DiagnosticSeverity,
+    pub message: String,
+    /// What reported it, like `rustc` or `clippy`.
+    pub source: Option<String>,
+    pub code: Option<String>,
+}
+
+impl LspDiagnostic {
+    /// The diagnostic as the editor shows it, see `Document::set_diagnostics`.
+    pub fn to_diagnostic(&self) -> Diagnostic {
+        Diagnostic {
+            severity: match self.severity {
+                DiagnosticSeverity::Error => Severity::Error,
+                DiagnosticSeverity::Warning => Severity::Warning,
+                DiagnosticSeverity::Information => Severity::Information,
+                DiagnosticSeverity::Hint => Severity::Hint,
+            },
+            start: self.start,
+            end: self.end,
+            message: match &self.code {
+                Some(code) => format!("{} [{}]", self.message, code),
+                None => self.message.clone()
+            },
+            source: self.source.clone(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct LspLocation {
+    pub path: PathBuf,
+    pub start: Position,
+    pub end: Position,
+}
+
+/// What a language server has to say, with positions already converted to those of the editor.
+#[derive(DefaultNone, Clone, Debug)]
+pub enum LspEvent {
+    /// The server answered the handshake, and requests made until then are sent.
+    Initialized,
+    /// The diagnostics of a file, which replace those it had.
+    Diagnostics {path: PathBuf, diagnostics: Vec<LspDiagnostic>},
+    /// Markdown to show for the hovered position, empty if there is nothing to show.
+    Hover {request_id: LspRequestId, contents: String},
+    Definition {request_id: LspRequestId, locations: Vec<LspLocation>},
+    Completions {request_id: LspRequestId, completions: Vec<Completion>},
+    RequestFailed {request_id: LspRequestId, message: String},
+    /// A message the server logged or wants to show, or a line it wrote to stderr.
+    Log(String),
+    /// The server exited or closed its stdout.
+    Exited,
+    None
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum RequestKind {
+    Initialize,
+    Hover,
+    Definition,
+    Completion,
+    Shutdown,
+}
+
+enum ServerOutput {
+    Message(JsonRpcMessage),
+    StdErr(String),
+    Closed,
+}
+
+/// The text the server has of an open file, kept to turn edits into ranges.
+struct OpenDocument {
+    uri: String,
+    version: i64,
+    text: Text,
+}
+
+/// A language server running as a child process, spoken to with JSON-RPC over its stdio.
+/// Output of the server is read on a thread, which signals the UI, and `handle_event` turns it
+/// into `LspEvent` actions.
+pub struct LspClient {
+    child: Option<Child>,
+    message_sender: Sender<JsonRpcMessage>,
+    output_receiver: Receiver<ServerOutput>,
+    next_id: u64,
+    requests: HashMap<u64, RequestKind>,
+    documents: HashMap<PathBuf, OpenDocument>,
+    encoding: PositionEncoding,
+    is_initialized: bool,
+    /// Messages made before the server answered the handshake.
+    queued: Vec<JsonRpcMessage>,
+}
+
+impl LspClient {
+    /// Starts the server with `cmd` and `args` in `root`, and sends it the handshake.
+    pub fn start(cmd: &str, args: &[String], root: &Path) -> Result<Self, io::Error> {
+        let mut child = Command::new(cmd)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .current_dir(root)
+            .spawn() ?;
+        
+        let (message_sender, message_receiver) = mpsc::channel::<JsonRpcMessage>();
+        let (output_sender, output_receiver) = mpsc::channel();
+        let signal = SignalToUI::new();
+        
+        let mut stdin = child.stdin.take().expect("stdin cannot be taken!");
+        let stdout = child.stdout.take().expect("stdout cannot be taken!");
+        let stderr = child.stderr.take().expect("stderr cannot be taken!");
+        
+        let _stdout_thread = {
+            let output_sender = output_sender.clone();
+            let signal = signal.clone();
+            thread::spawn(move || {
+                let mut reader = BufReader::new(stdout);
+                while let Ok(Some(message)) = json_rpc::read_message(&mut reader) {
+                    if output_sender.send(ServerOutput::Message(message)).is_err() {
+                        return
+                    }
+                    signal.set();
+                }
+                let _ = output_sender.send(ServerOutput::Closed);
+                signal.set();
+            })
+        };
+        
+        let _stderr_thread = {
+            thread::spawn(move || {
+                let mut reader = BufReader::new(stderr);
+                let mut line = String::new();
+                while let Ok(len) = reader.read_line(&mut line) {
+                    if len == 0 || output_sender.send(ServerOutput::StdErr(line.trim_end().to_string())).is_err() {
+                        break
+                    }
+                    signal.set();
+                    line.clear();
+                }
+            })
+        };
+        
+        let _stdin_thread = {
+            thread::spawn(move || {
+                while let Ok(message) = message_receiver.recv() {
+                    if json_rpc::write_message(&mut stdin, &message).is_err() {
+                        break
+                    }
+                }
+            })
+        };
+        
+        let mut client = Self {
+            child: Some(child),
+            message_sender,
+            output_receiver,
+            next_id: 0,
+            requests: HashMap::new(),
+            documents: HashMap::new(),
+            encoding: PositionEncoding::Utf16,
+            is_initialized: false,
+            queued: Vec::new(),
+        };
+        let root_uri = path_to_uri(root);
+        client.request(RequestKind::Initialize, "initialize", object([
+            ("processId", JsonValue::U64(std::process::id() as u64)),
+            ("rootUri", string(&root_uri)),
+            ("workspaceFolders", JsonValue::Array(vec![object([
+                ("uri", string(&root_uri)),
+                ("name", string(&root.file_name().map( | name | name.to_string_lossy().to_string()).unwrap_or_default())),
+            ])])),
+            ("capabilities", client_capabilities()),
+        ]));
+        Ok(client)
+    }
+    
+    pub fn encoding(&self) -> PositionEncoding {
+        self.encoding
+    }
+    
+    pub fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+    
+    pub fn is_open(&self, path: &Path) -> bool {
+        self.documents.contains_key(path)
+    }
+    
+    /// Tells the server the file at `path` is open in the editor with `text`, which the server
+    /// uses instead of what is on disk until `did_close`.
+    pub fn did_open(&mut self, path: &Path, language_id: &str, text: &Text) {
+        let uri = path_to_uri(path);
+        self.notify("textDocument/didOpen", object([
+            ("textDocument", object([
+                ("uri", string(&uri)),
+                ("languageId", string(language_id)),
+                ("version", JsonValue::I64(1)),
+                ("text", JsonValue::String(text.to_string())),
+            ])),
+        ]));
+        self.documents.insert(path.to_path_buf(), OpenDocument {uri, version: 1, text: text.clone()});
+    }
+    
+    /// Sends the edits made to an open file, in the order they were applied, as one change of
+    /// its version.
+    pub fn did_change(&mut self, path: &Path, edits: &[Edit]) {
+        if edits.is_empty() {
+            return
+        }
+        let encoding = self.encoding;
+        let Some(document) = self.documents.get_mut(path) else {
+            return
+        };
+        let mut content_changes = Vec::new();
+        for edit in edits {
+            let (start, end, new_text) = match &edit.change {
+                Change::Insert(position, text) => (*position, *position, text.to_string()),
+                Change::Delete(start, length) => (*start, *start + *length, String::new()),
+            };
+            content_changes.push(content_change(encoding, &document.text, start, end, new_text));
+            document.text.apply_change(edit.change.clone());
+        }
+        document.version += 1;
+        let params = versioned_change(document, content_changes);
+        self.notify("textDocument/didChange", params);
+    }
+    
+    /// Brings the server up to date with `text`, for when the edits that led to it aren't at
+    /// hand. Only the lines between the first and the last that changed are sent.
+    pub fn sync_text(&mut self, path: &Path, text: &Text) {
+        let encoding = self.encoding;
+        let Some(document) = self.documents.get_mut(path) else {
+            return
+        };
+        let old_lines = document.text.as_lines();
+        let new_lines = text.as_lines();
+        let prefix = old_lines.iter().zip(new_lines).take_while( | (old, new) | old == new).count();
+        if prefix == old_lines.len() && prefix == new_lines.len() {
+            return
+        }
+        let max_suffix = old_lines.len().min(new_lines.len()) - prefix;
+        let suffix = old_lines.iter().rev().zip(new_lines.iter().rev()).take(max_suffix).take_while( | (old, new) | old == new).count();
+        let old_end = old_lines.len() - suffix;
+        let new_end = new_lines.len() - suffix;
+        let changed_lines = &new_lines[prefix..new_end];
+        let end_of_text = Position {line_index: old_lines.len() - 1, byte_index: old_lines.last().unwrap().len()};
+        let (start, end, new_text) = if suffix > 0 {
+            // whole lines are replaced, up to the first line that is the same again
+            (
+                Position {line_index: prefix, byte_index: 0},
+                Position {line_index: old_end, byte_index: 0},
+                changed_lines.iter().map( | line | format!("{}\n", line)).collect(),
+            )
+        } else if prefix < old_end && prefix < new_end {
+            // the last line has no line break after it
+            (Position {line_index: prefix, byte_index: 0}, end_of_text, changed_lines.join("\n"))
+        } else {
+            // lines were only added or removed at the end
+            (
+                Position {line_index: prefix - 1, byte_index: old_lines[prefix - 1].len()},
+                end_of_text,
+                changed_lines.iter().map( | line | format!("\n{}", line)).collect(),
+            )
+        };
+        let content_changes = vec![content_change(encoding, &document.text, start, end, new_text)];
+        document.text = text.clone();
+        document.version += 1;
+        let params = versioned_change(document, content_changes);
+        self.notify("textDocument/didChange", params);
+    }
+    
+    pub fn did_save(&mut self, path: &Path) {
+        if let Some(document) = self.documents.get(path) {
+            let params = object([("textDocument", object([("uri", string(&document.uri))]))]);
+            self.notify("textDocument/didSave", params);
+        }
+    }
+    
+    pub fn did_close(&mut self, path: &Path) {
+        if let Some(document) = self.documents.remove(path) {
+            self.notify("textDocument/didClose", object([("textDocument", object([("uri", string(&document.uri))]))]));
+        }
+    }
+    
+    /// Asks what to show for `position`, answered with `LspEvent::Hover`.
+    pub fn hover(&mut self, path: &Path, position: Position) -> Option<LspRequestId> {
+        let params = self.text_document_position(path, position) ?;
+        Some(self.request(RequestKind::Hover, "textDocument/hover", params))
+    }
+    
+    /// Asks where the symbol at `position` is defined, answered with `LspEvent::Definition`.
+    pub fn definition(&mut self, path: &Path, position: Position) -> Option<LspRequestId> {
+        let params = self.text_document_position(path, position) ?;
+        Some(self.request(RequestKind::Definition, "textDocument/definition", params))
+    }
+    
+    /// Asks for completions at `position`, answered with `LspEvent::Completions`.
+    pub fn completion(&mut self, path: &Path, position: Position) -> Option<LspRequestId> {
+        let params = self.text_document_position(path, position) ?;
+        Some(self.request(RequestKind::Completion, "textDocument/completion", params))
+    }
+    
+    /// Turns what the server sent since the last call into events.
+    pub fn poll_events(&mut self) -> Vec<LspEvent> {
+        let mut events = Vec::new();
+        while let Ok(output) = self.output_receiver.try_recv() {
+            match output {
+                ServerOutput::Message(message) => self.handle_message(message, &mut events),
+                ServerOutput::StdErr(line) => events.push(LspEvent::Log(line)),
+                ServerOutput::Closed => {
+                    self.is_initialized = false;
+                    events.push(LspEvent::Exited)
+                }
+            }
+        }
+        events
+    }
+    
+    /// Posts an `LspEvent` action for everything the server sent.
+    pub fn handle_event(&mut self, cx: &mut Cx, event: &Event) {
+        if let Event::Signal = event {
+            for event in self.poll_events() {
+                cx.action(event);
+            }
+        }
+    }
+    
+    /// Asks the server to exit, and kills it if it hasn't after a while.
+    pub fn stop(mut self) {
+        self.request(RequestKind::Shutdown, "shutdown", JsonValue::Null);
+        self.notify("exit", JsonValue::Null);
+        if let Some(mut child) = self.child.take() {
+            thread::spawn(move || {
+                thread::sleep(SHUTDOWN_TIMEOUT);
+                if let Ok(None) = child.try_wait() {
+                    let _ = child.kill();
+                }
+                let _ = child.wait();
+            });
+        }
+    }
+    
+    fn handle_message(&mut self, message: JsonRpcMessage, events: &mut Vec<LspEvent>) {
+        match message {
+            JsonRpcMessage::Response {id, result} => {
+                let Some(id) = id.u64() else {
+                    return
+                };
+                let Some(kind) = self.requests.remove(&id) else {
+                    return
+                };
+                let request_id = LspRequestId(id);
+                let result = match result {
+                    Ok(result) => result,
+                    Err(error) => {
+                        events.push(LspEvent::RequestFailed {request_id, message: error.message});
+                        return
+                    }
+                };
+                match kind {
+                    RequestKind::Initialize => {
+                        if let Some(JsonValue::String(encoding)) = result.key("capabilities").and_then( | capabilities | capabilities.key("positionEncoding")) {
+                            if encoding == "utf-8" {
+                                self.encoding = PositionEncoding::Utf8;
+                            }
+                        }
+                        self.is_initialized = true;
+                        self.send(JsonRpcMessage::Notification {method: "initialized".to_string(), params: object([])});
+                        for message in std::mem::take(&mut self.queued) {
+                            self.send(message);
+                        }
+                        events.push(LspEvent::Initialized);
+                    }
+                    RequestKind::Hover => events.push(LspEvent::Hover {
+                        request_id,
+                        contents: result.key("contents").map(hover_contents).unwrap_or_default(),
+                    }),
+                    RequestKind::Definition => {
+                        let locations = match &result {
+                            JsonValue::Array(locations) => locations.iter().filter_map( | location | self.location(location)).collect(),
+                            JsonValue::Object(_) => self.location(&result).into_iter().collect(),
+                            _ => Vec::new()
+                        };
+                        events.push(LspEvent::Definition {request_id, locations});
+                    }
+                    RequestKind::Completion => events.push(LspEvent::Completions {
+                        request_id,
+                        completions: completions(&result),
+                    }),
+                    RequestKind::Shutdown => ()
+                }
+            }
+            JsonRpcMessage::Notification {method, params} => match method.as_str() {
+                "textDocument/publishDiagnostics" => {
+                    let Some(path) = params.key("uri").and_then( | uri | uri.string()).map( | uri | uri_to_path(uri)) else {
+                        return
+                    };
+                    let lines = self.lines_of(&path);
+                    let diagnostics = params.key("diagnostics").and_then( | diagnostics | diagnostics.array()).map( | diagnostics | {
+                        diagnostics.iter().filter_map( | diagnostic | self.diagnostic(&lines, diagnostic)).collect()
+                    }).unwrap_or_default();
+                    events.push(LspEvent::Diagnostics {path, diagnostics});
+                }
+                "window/logMessage" | "window/showMessage" => {
+                    if let Some(message) = params.key("message").and_then( | message | message.string()) {
+                        events.push(LspEvent::Log(message.clone()));
+                    }
+                }
+                _ => ()
+            }
+            JsonRpcMessage::Request {id, method, params} => {
+                // servers wait for an answer to these, so each gets one even if there is
+                // nothing to say
+                let result = match method.as_str() {
+                    "workspace/configuration" => Ok(JsonValue::Array(
+                        params.key("items").and_then( | items | items.array()).map( | items | items.iter().map( | _ | JsonValue::Null).collect()).unwrap_or_default()
+                    )),
+                    "window/workDoneProgress/create" | "client/registerCapability" | "client/unregisterCapability" => Ok(JsonValue::Null),
+                    _ => Err(JsonRpcError {code: METHOD_NOT_FOUND, message: format!("{} is not supported", method)})
+                };
+                self.send(JsonRpcMessage::Response {id, result});
+            }
+        }
+    }
+    
+    fn request(&mut self, kind: RequestKind, method: &str, params: JsonValue) -> LspRequestId {
+        self.next_id += 1;
+        let id = self.next_id;
+        self.requests.insert(id, kind);
+        let message = JsonRpcMessage::Request {id: JsonValue::U64(id), method: method.to_string(), params};
+        if kind == RequestKind::Initialize {
+            self.send(message);
+        }
+        else {
+            self.send_or_queue(message);
+        }
+        LspRequestId(id)
+    }
+    
+    fn notify(&mut self, method: &str, params: JsonValue) {
+        self.send_or_queue(JsonRpcMessage::Notification {method: method.to_string(), params});
+    }
+    
+    /// Holds messages back until the server answered the handshake, as the protocol asks.
+    fn send_or_queue(&mut self, message: JsonRpcMessage) {
+        if self.is_initialized {
+            self.send(message);
+        }
+        else {
+            self.queued.push(message);
+        }
+    }
+    
+    fn send(&self, message: JsonRpcMessage) {
+        let _ = self.message_sender.send(message);
+    }
+    
+    fn text_document_position(&self, path: &Path, position: Position) -> Option<JsonValue> {
+        let document = self.documents.get(path) ?;
+        Some(object([
+            ("textDocument", object([("uri", string(&document.uri))])),
+            ("position", to_lsp_position(self.encoding, document.text.as_lines(), position)),
+        ]))
+    }
+    
+    /// The lines of the file at `path`, as the server knows them if it is open, or else as
+    /// they are on disk. Needed to make sense of positions in UTF-16.
+    fn lines_of(&self, path: &Path) -> Vec<String> {
+        if let Some(document) = self.documents.get(path) {
+            return document.text.as_lines().to_vec()
+        }
+        if self.encoding == PositionEncoding::Utf8 {
+            return Vec::new()
+        }
+        fs::read_to_string(path).map( | text | text.lines().map( | line | line.to_string()).collect()).unwrap_or_default()
+    }
+    
+    fn diagnostic(&self, lines: &[String], value: &JsonValue) -> Option<LspDiagnostic> {
+        let (start, end) = from_lsp_range(self.encoding, lines, value.key("range") ?) ?;
+        Some(LspDiagnostic {
+            start,
+            end,
+            severity: match value.key("severity").and_then( | severity | severity.u64()) {
+                Some(2) => DiagnosticSeverity::Warning,
+                Some(3) => DiagnosticSeverity::Information,
+                Some(4) => DiagnosticSeverity::Hint,
+                _ => DiagnosticSeverity::Error,
+            },
+            message: value.key("message").and_then( | message | message.string()).cloned().unwrap_or_default(),
+            source: value.key("source").and_then( | source | source.string()).cloned(),
+            code: match value.key("code") {
+                Some(JsonValue::String(code)) => Some(code.clone()),
+                Some(JsonValue::U64(code)) => Some(code.to_string()),
+                Some(JsonValue::I64(code)) => Some(code.to_string()),
+                _ => None
+            },
+        })
+    }
+    
+    /// A `Location`, or a `LocationLink` which points at the name of what it links to.
+    fn location(&self, value: &JsonValue) -> Option<LspLocation> {
+        let (uri, range) = match value.key("targetUri") {
+            Some(uri) => (uri, value.key("targetSelectionRange").or(value.key("targetRange")) ?),
+            None => (value.key("uri") ?, value.key("range") ?)
+        };
+        let path = uri_to_path(uri.string() ?);
+        let (start, end) = from_lsp_range(self.encoding, &self.lines_of(&path), range) ?;
+        Some(LspLocation {path, start, end})
+    }
+}
+
+fn client_capabilities() -> JsonValue {
+    object([
+        ("general", object([
+            ("positionEncodings", JsonValue::Array(vec![string("utf-8"), string("utf-16")])),
+        ])),
+        ("textDocument", object([
+            ("synchronization", object([
+                ("didSave", JsonValue::Bool(true)),
+            ])),
+            ("publishDiagnostics", object([])),
+            ("hover", object([
+                ("contentFormat", JsonValue::Array(vec![string("markdown"), string("plaintext")])),
+            ])),
+            ("definition", object([
+                ("linkSupport", JsonValue::Bool(true)),
+            ])),
+            ("completion", object([
+                // completions are inserted as they are, placeholders and all
+                ("completionItem", object([("snippetSupport", JsonValue::Bool(false))])),
+            ])),
+        ])),
+        ("workspace", object([
+            ("workspaceFolders", JsonValue::Bool(true)),
+            ("configuration", JsonValue::Bool(true)),
+        ])),
+    ])
+}
+
+fn versioned_change(document: &OpenDocument, content_changes: Vec<JsonValue>) -> JsonValue {
+    object([
+        ("textDocument", object([
+            ("uri", string(&document.uri)),
+            ("version", JsonValue::I64(document.version)),
+        ])),
+        ("contentChanges", JsonValue::Array(content_changes)),
+    ])
+}
+
+fn content_change(encoding: PositionEncoding, text: &Text, start: Position, end: Position, new_text: String) -> JsonValue {
+    object([
+        ("range", object([
+            ("start", to_lsp_position(encoding, text.as_lines(), start)),
+            ("end", to_lsp_position(encoding, text.as_lines(), end)),
+        ])),
+        ("text", JsonValue::String(new_text)),
+    ])
+}
+
+pub fn to_lsp_position(encoding: PositionEncoding, lines: &[String], position: Position) -> JsonValue {
+    let character = match encoding {
+        PositionEncoding::Utf8 => position.byte_index,
+        PositionEncoding::Utf16 => lines.get(position.line_index).map( | line | {
+            line[..position.byte_index.min(line.len())].encode_utf16().count()
+        }).unwrap_or(position.byte_index),
+    };
+    object([
+        ("line", JsonValue::U64(position.line_index as u64)),
+        ("character", JsonValue::U64(character as u64)),
+    ])
+}
+
+/// The editor position of a position on the wire. Lines that aren't known are taken to be
+/// ASCII.
+pub fn from_lsp_position(encoding: PositionEncoding, lines: &[String], value: &JsonValue) -> Option<Position> {
+    let line_index = value.key("line") ?.u64() ? as usize;
+    let character = value.key("character") ?.u64() ? as usize;
+    let byte_index = match lines.get(line_index) {
+        Some(line) => match encoding {
+            PositionEncoding::Utf8 => {
+                let mut byte_index = character.min(line.len());
+                while !line.is_char_boundary(byte_index) {
+                    byte_index -= 1;
+                }
+                byte_index
+            }
+            PositionEncoding::Utf16 => {
+                let mut units = 0;
+                line.char_indices().find_map( | (byte_index, char) | {
+                    if units >= character {
+                        return Some(byte_index)
+                    }
+                    units += char.len_utf16();
+                    None
+                }).unwrap_or(line.len())
+            }
+        },
+        None => character,
+    };
+    Some(Position {line_index, byte_index})
+}
+
+fn from_lsp_range(encoding: PositionEncoding, lines: &[String], value: &JsonValue) -> Option<(Position, Position)> {
+    Some((
+        from_lsp_position(encoding, lines, value.key("start") ?) ?,
+        from_lsp_position(encoding, lines, value.key("end") ?) ?,
+    ))
+}
+
+/// Hover contents as markdown. These come as `MarkupContent`, or in older servers as one or more
+/// `MarkedString`s, which are either markdown or code in a language.
+fn hover_contents(value: &JsonValue) -> String {
+    match value {
+        JsonValue::String(markdown) => markdown.clone(),
+        JsonValue::Array(values) => values.iter().map(hover_contents).filter( | contents | !contents.is_empty()).collect::<Vec<_>>().join("\n\n"),
+        JsonValue::Object(_) => {
+            let value_str = value.key("value").and_then( | value | value.string()).cloned().unwrap_or_default();
+            match value.key("language").and_then( | language | language.string()) {
+                Some(language) => format!("```{}\n{}\n```", language, value_str),
+                None => value_str
+            }
+        }
+        _ => String::new()
+    }
+}
+
+/// The items of a completion response, which is a list of them or a `CompletionList`, best
+/// first in the order the server sorts them in.
+fn completions(value: &JsonValue) -> Vec<Completion> {
+    let items = match value {
+        JsonValue::Array(items) => items,
+        JsonValue::Object(_) => match value.key("items").and_then( | items | items.array()) {
+            Some(items) => items,
+            None => return Vec::new()
+        },
+        _ => return Vec::new()
+    };
+    let mut items: Vec<(String, Completion)> = items.iter().filter_map( | item | {
+        let label = item.key("label") ?.string() ?.clone();
+        let text = item.key("textEdit").and_then( | edit | edit.key("newText"))
+            .or(item.key("insertText"))
+            .and_then( | text | text.string())
+            .cloned()
+            .unwrap_or_else( || label.clone());
+        let sort_text = item.key("sortText").and_then( | sort_text | sort_text.string()).cloned().unwrap_or_else( || label.clone());
+        let kind = match item.key("kind").and_then( | kind | kind.u64()) {
+            Some(2 | 3 | 4) => CompletionKind::Function,
+            Some(5 | 10 | 20) => CompletionKind::Field,
+            Some(6 | 21) => CompletionKind::Variable,
+            Some(7 | 8 | 13 | 22 | 25) => CompletionKind::Type,
+            Some(9) => CompletionKind::Module,
+            Some(14) => CompletionKind::Keyword,
+            Some(15) => CompletionKind::Snippet,
+            _ => CompletionKind::Word,
+        };
+        Some((sort_text, Completion {label, kind, text, score: 0.0}))
+    }).collect();
+    items.sort_by( | (a, _), (b, _) | a.cmp(b));
+    let len = items.len();
+    items.into_iter().enumerate().map( | (index, (_, mut completion)) | {
+        completion.score = (len - index) as f64;
+        completion
+    }).collect()
+}
+
+/// The `file://` URI of `path`, with what isn't allowed in a URI percent-encoded.
+pub fn path_to_uri(path: &Path) -> String {
+    let path = path.to_string_lossy().replace('\\', "/");
+    let mut uri = "file://".to_string();
+    if !path.starts_with('/') {
+        // windows paths start with a drive letter
+        uri.push('/');
+    }
+    for byte in path.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' | b':' => uri.push(byte as char),
+            _ => uri.push_str(&format!("%{:02X}", byte))
+        }
+    }
+    uri
+}
+
+pub fn uri_to_path(uri: &str) -> PathBuf {
+    let path = uri.strip_prefix("file://").unwrap_or(uri);
+    let mut bytes = Vec::new();
+    let mut rest = path.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        if let (b'%', Some(hex)) = (byte, tail.get(..2)) {
+            if let Ok(decoded) = u8::from_str_radix(std::str::from_utf8(hex).unwrap_or(""), 16) {
+                bytes.push(decoded);
+                rest = &tail[2..];
+                continue
+            }
+        }
+        bytes.push(byte);
+        rest = tail;
+    }
+    let path = String::from_utf8_lossy(&bytes).to_string();
+    // `/C:/dir` on windows
+    match path.strip_prefix('/') {
+        Some(rest) if rest.as_bytes().get(1) == Some(&b':') => PathBuf::from(rest),
+        _ => PathBuf::from(path)
+    }
+}
@@ -0,0 +1,2 @@
+pub mod json_rpc;
+pub mod lsp_client;
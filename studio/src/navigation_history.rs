@@ -0,0 +1,80 @@
+use {
+    crate::{
+        makepad_code_editor::text::Position,
+        makepad_widgets::*,
+    },
+    std::collections::HashMap,
+};
+
+/// How many lines the cursor has to move at once to count as a jump.
+const JUMP_LINES: usize = 10;
+
+/// How many locations a window can go back to, older ones are dropped.
+const MAX_LOCATIONS: usize = 100;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NavigationLocation {
+    pub file_id: LiveId,
+    pub position: Position,
+}
+
+/// Where the cursor was before it jumped, so the back and forward mouse buttons and trackpad
+/// swipes can return there like in a browser. A jump is a move to another file, or far enough
+/// within one. Every window has its own history, shared by the editors in it.
+#[derive(Default)]
+pub struct NavigationHistory {
+    windows: HashMap<WindowId, WindowHistory>,
+}
+
+#[derive(Default)]
+struct WindowHistory {
+    back: Vec<NavigationLocation>,
+    forward: Vec<NavigationLocation>,
+    /// Where the cursor of the focused editor is now.
+    current: Option<NavigationLocation>,
+}
+
+impl NavigationHistory {
+    /// Tells the history where the cursor of the focused editor in a window is. If it jumped
+    /// there, where it was before is the location to go back to, and the ones to go forward to
+    /// are forgotten.
+    pub fn update(&mut self, window_id: WindowId, location: NavigationLocation) {
+        let window = self.windows.entry(window_id).or_default();
+        let Some(current) = window.current.replace(location) else {
+            return
+        };
+        let jumped = current.file_id != location.file_id
+            || current.position.line_index.abs_diff(location.position.line_index) >= JUMP_LINES;
+        if !jumped {
+            return
+        }
+        if window.back.last() != Some(&current) {
+            window.back.push(current);
+            if window.back.len() > MAX_LOCATIONS {
+                window.back.remove(0);
+            }
+        }
+        window.forward.clear();
+    }
+
+    /// The location to go back to. Going there doesn't count as a jump.
+    pub fn back(&mut self, window_id: WindowId) -> Option<NavigationLocation> {
+        let window = self.windows.get_mut(&window_id) ?;
+        let location = window.back.pop() ?;
+        if let Some(current) = window.current.replace(location) {
+            window.forward.push(current);
+        }
+        Some(location)
+    }
+
+    /// The location to go forward to again, after going back. Going there doesn't count as a
+    /// jump.
+    pub fn forward(&mut self, window_id: WindowId) -> Option<NavigationLocation> {
+        let window = self.windows.get_mut(&window_id) ?;
+        let location = window.forward.pop() ?;
+        if let Some(current) = window.current.replace(location) {
+            window.back.push(current);
+        }
+        Some(location)
+    }
+}
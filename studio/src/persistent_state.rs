@@ -0,0 +1,46 @@
+use {
+    crate::{
+        makepad_code_editor::state::SessionState,
+        makepad_widgets::*,
+        makepad_micro_serde::*,
+    },
+    std::fs,
+};
+
+/// What the studio brings back on the next start: the dock layout, which files its tabs show,
+/// and where each of them had its cursors and was scrolled to.
+#[derive(Clone, Debug, Default, SerRon, DeRon)]
+pub struct PersistentState {
+    pub dock_items: Vec<DockItemStore>,
+    pub tab_sessions: Vec<TabSession>,
+}
+
+#[derive(Clone, Debug, SerRon, DeRon)]
+pub struct TabSession {
+    pub tab_id: LiveIdStore,
+    /// The path of the file, as `FileSystem::path_to_file_node_id` knows it.
+    pub path: String,
+    pub state: SessionState,
+}
+
+impl PersistentState {
+    pub const STATE_FILE: &'static str = "makepad_state.ron";
+
+    /// The state saved by the last run, if there is one that can still be read.
+    pub fn load() -> Option<Self> {
+        let data = fs::read_to_string(Self::STATE_FILE).ok() ?;
+        match Self::deserialize_ron(&data) {
+            Ok(state) => Some(state),
+            Err(err) => {
+                log!("Cannot parse {}: {:?}", Self::STATE_FILE, err);
+                None
+            }
+        }
+    }
+
+    pub fn save(&self) {
+        if let Err(err) = fs::write(Self::STATE_FILE, self.serialize_ron()) {
+            log!("Cannot write {}: {}", Self::STATE_FILE, err);
+        }
+    }
+}
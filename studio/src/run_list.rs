@@ -183,6 +183,10 @@ live_design!{
                 check = <RunButton> {}
             }
 
+            Task = <BuildItem> {
+                check = <RunButton> { margin: {left: (THEME_SPACE_2)} }
+            }
+
             Empty = <BuildItem> {
                 height: Fit, width: Fill,
                 cursor: Default
@@ -237,6 +241,18 @@ impl RunList{
                 }
             }
         }
+        for task in &build_manager.tasks {
+            let is_even = counter & 1 == 0;
+            let item_id = task.as_id();
+            let item = list.item(cx, item_id, live_id!(Task)).unwrap().as_view();
+            item.apply_over(cx, live!{
+                check = {text: (&task.name)}
+                draw_bg: {is_even: (if is_even {1.0} else {0.0})}
+            });
+            item.check_box(id!(check)).set_selected(cx, build_manager.active_tasks.contains(&item_id));
+            item.draw_all(cx, &mut Scope::empty());
+            counter += 1;
+        }
         while list.space_left(cx)>0.0 {
             let is_even = counter & 1 == 0;
             let item_id = LiveId::from_str("empty").bytes_append(&counter.to_be_bytes());
@@ -257,7 +273,22 @@ impl WidgetMatchEvent for RunList{
     fn handle_actions(&mut self, cx: &mut Cx, actions: &Actions, scope: &mut Scope){
         let build_manager = &mut scope.data.get_mut::<AppData>().unwrap().build_manager;
         let run_list = self.view.flat_list(id!(list));
+        let tasks_allowed = build_manager.is_trusted();
         for (item_id, item) in run_list.items_with_actions(&actions) {
+            if let Some(task) = build_manager.tasks.iter().find( | task | task.as_id() == item_id) {
+                if let Some(change) = item.check_box(id!(check)).changed(&actions) {
+                    let name = task.name.clone();
+                    run_list.redraw(cx);
+                    if change && tasks_allowed {
+                        build_manager.start_task(&name);
+                    }
+                    else {
+                        build_manager.stop_task(&name);
+                    }
+                    cx.action(AppAction::ClearLog);
+                }
+                continue
+            }
             for binary in &mut build_manager.binaries {
                 let binary_name = binary.name.clone();
                 let id = LiveId::from_str(&binary.name);
@@ -269,7 +300,7 @@ impl WidgetMatchEvent for RunList{
                     if let Some(change) = item.check_box(id!(check)).changed(&actions) {
                         run_list.redraw(cx);
                         for i in 0..if change{1}else{BuildTarget::len()} {
-                            if change{
+                            if change && tasks_allowed{
                                 BuildManager::start_active_build(cx, build_manager.studio_http.clone(), &mut build_manager.active, &build_manager.clients[0], &binary_name, i);
                             }
                             else{
@@ -285,7 +316,7 @@ impl WidgetMatchEvent for RunList{
                         if item_id == id{
                             if let Some(change) = item.check_box(id!(check)).changed(&actions) {
                                 run_list.redraw(cx);
-                                if change{
+                                if change && tasks_allowed{
                                     BuildManager::start_active_build(cx, build_manager.studio_http.clone(), &mut build_manager.active, &build_manager.clients[0], &binary_name, i);
                                 }
                                 else{
@@ -321,6 +352,9 @@ impl Widget for RunList {
 impl BuildManager {
 
     pub fn run_app(&mut self, cx:&mut Cx, binary_name:&str){
+        if !self.is_trusted(){
+            return
+        }
         Self::start_active_build(cx, self.studio_http.clone(), &mut self.active, &self.clients[0], &binary_name, 0);
     }
     
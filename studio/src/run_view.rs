@@ -326,6 +326,9 @@ impl Widget for RunView {
             Hit::TextInput(e) => {
                 manager.send_host_to_stdin(run_view_id, HostToStdin::TextInput(e));
             }
+            Hit::TextComposition(e) => {
+                manager.send_host_to_stdin(run_view_id, HostToStdin::TextComposition(e));
+            }
             Hit::KeyDown(e) => {
                 manager.send_host_to_stdin(run_view_id, HostToStdin::KeyDown(e));
             }
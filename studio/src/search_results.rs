@@ -0,0 +1,98 @@
+use {
+    crate::{
+        makepad_platform::studio::JumpToFile,
+        app::{AppAction, AppData},
+        makepad_widgets::*,
+    },
+};
+
+live_design!{
+    import makepad_widgets::theme_desktop_dark::*;
+    import makepad_studio::log_list::LogItem;
+
+    SearchResults = {{SearchResults}}{
+        height: Fill, width: Fill,
+        list = <PortalList> {
+            grab_key_focus: true
+            drag_scrolling: false
+            height: Fill, width: Fill,
+            flow: Down
+            Result = <LogItem> {
+                location = <LinkLabel> {padding:0, margin: 0, text: ""}
+                body = <P> {width: Fill, margin: {left: 5, top:0, bottom:0}, padding: 0, draw_text: {wrap: Word}}
+            }
+            Empty = <LogItem> {
+                cursor: Default
+                width: Fill
+                height: 25,
+                body = <P> {  margin: 0, text: "" }
+            }
+        }
+    }
+}
+
+/// Lists the results of the last search, one line per match, with a link to where it is.
+#[derive(Live, LiveHook, Widget)]
+pub struct SearchResults{
+    #[deref] view:View
+}
+
+impl SearchResults{
+    fn draw_results(&mut self, cx: &mut Cx2d, list:&mut PortalList, data:&AppData){
+        let results = &data.file_system.search_results;
+        list.set_item_range(cx, 0, results.len());
+
+        while let Some(item_id) = list.next_visible_item(cx) {
+            let is_even = item_id & 1 == 0;
+            if let Some(result) = results.get(item_id as usize) {
+                let item = list.item(cx, item_id, live_id!(Result)).unwrap().as_view();
+                item.apply_over(cx, live!{
+                    location = {text: (format!("{}:{}", result.path, result.line + 1))}
+                    body = {text: (result.line_text.trim())}
+                    draw_bg: {is_even: (if is_even {1.0} else {0.0})}
+                });
+                item.draw_all(cx, &mut Scope::empty());
+                continue
+            }
+            let item = list.item(cx, item_id, live_id!(Empty)).unwrap().as_view();
+            item.apply_over(cx, live!{draw_bg: {is_even: (if is_even {1.0} else {0.0})}});
+            item.draw_all(cx, &mut Scope::empty());
+        }
+    }
+}
+
+impl Widget for SearchResults {
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope:&mut Scope, walk:Walk)->DrawStep{
+        while let Some(step) = self.view.draw_walk(cx, scope, walk).step(){
+            if let Some(mut list) = step.as_portal_list().borrow_mut(){
+                self.draw_results(cx, &mut *list, scope.data.get::<AppData>().unwrap())
+            }
+        }
+        DrawStep::done()
+    }
+
+    fn reading_text(&self, scope: &mut Scope, out: &mut String) {
+        for result in &scope.data.get::<AppData>().unwrap().file_system.search_results {
+            push_reading_line(out, &format!("{}:{}: {}", result.path, result.line + 1, result.line_text.trim()));
+        }
+    }
+
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope){
+        let list = self.view.portal_list(id!(list));
+        self.view.handle_event(cx, event, scope);
+        let data = scope.data.get::<AppData>().unwrap();
+        if let Event::Actions(actions) = event{
+            for (item_id, item) in list.items_with_actions(&actions) {
+                if item.link_label(id!(location)).pressed(&actions) {
+                    if let Some(result) = data.file_system.search_results.get(item_id as usize) {
+                        cx.action(AppAction::JumpTo(JumpToFile{
+                            file_name: result.path.clone(),
+                            line: result.line as u32,
+                            column: result.column_start as u32
+                        }));
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,52 @@
+use {
+    crate::{
+        makepad_micro_serde::*,
+        makepad_widgets::*,
+    },
+    std::{
+        env,
+        fs,
+        path::PathBuf,
+    },
+};
+
+/// The directory all settings files are in, `.makepad` in the home directory. It is outside of
+/// every workspace, as the workspace trust decisions are settings too, and a workspace must not
+/// be able to trust itself.
+pub fn settings_dir() -> Option<PathBuf> {
+    let home = env::var("HOME").or_else( | _ | env::var("USERPROFILE")).ok() ?;
+    Some(PathBuf::from(home).join(".makepad"))
+}
+
+/// Settings stored as a RON file in `settings_dir`. A missing file gives the defaults, and so
+/// does one that cannot be parsed, after logging why.
+pub trait SettingsFile: Default + SerRon + DeRon {
+    /// The name of the file in `settings_dir`.
+    const SETTINGS_FILE: &'static str;
+
+    fn settings_path() -> Option<PathBuf> {
+        Some(settings_dir() ?.join(Self::SETTINGS_FILE))
+    }
+
+    fn load() -> Self {
+        let Some(data) = Self::settings_path().and_then( | path | fs::read_to_string(path).ok()) else {
+            return Self::default()
+        };
+        match Self::deserialize_ron(&data) {
+            Ok(settings) => settings,
+            Err(err) => {
+                log!("Cannot parse {}: {:?}", Self::SETTINGS_FILE, err);
+                Self::default()
+            }
+        }
+    }
+
+    fn save(&self) {
+        let (Some(dir), Some(path)) = (settings_dir(), Self::settings_path()) else {
+            return
+        };
+        if let Err(err) = fs::create_dir_all(dir).and_then( | _ | fs::write(&path, self.serialize_ron())) {
+            log!("Cannot write {}: {}", path.display(), err);
+        }
+    }
+}
@@ -1,9 +1,17 @@
 
 use {
     crate::{
-        app::{AppData},
+        app::AppData,
+        large_file_view::LargeFileView,
+        follow_view::FollowView,
+        navigation_history::NavigationLocation,
+        file_system::file_system::OpenDoc,
         makepad_widgets::*,
-        makepad_code_editor::CodeEditor,
+        makepad_code_editor::{
+            code_action::{CodeAction, CodeActionRequest},
+            code_editor::CodeEditorAction,
+            CodeEditor,
+        },
     },
     std::{
         env,
@@ -12,16 +20,30 @@ use {
 
 live_design!{
     import makepad_code_editor::code_editor::CodeEditor;
+    import makepad_studio::large_file_view::LargeFileView;
+    import makepad_studio::follow_view::FollowView;
     
     StudioEditor = {{StudioEditor}}{
         editor: <CodeEditor>{
         }
+        large_file_view: <LargeFileView>{
+        }
+        follow_view: <FollowView>{
+        }
     }
 } 
  
 #[derive(Live, LiveHook, Widget)] 
 pub struct StudioEditor{
-    #[wrap] #[live] pub editor: CodeEditor
+    #[wrap] #[live] pub editor: CodeEditor,
+    /// Shows files that are too big to load into the editor.
+    #[live] large_file_view: LargeFileView,
+    /// Shows files that are followed as they grow.
+    #[live] follow_view: FollowView,
+    /// Where the cursor was when the navigation history was last told, while focused.
+    #[rust] navigation_location: Option<NavigationLocation>,
+    /// The code actions listed at the cursor, until one is chosen.
+    #[rust] code_actions: Vec<CodeAction>,
 }
 
 impl Widget for StudioEditor {
@@ -29,21 +51,164 @@ impl Widget for StudioEditor {
         // alright we have a scope, and an id, so now we can properly draw the editor.
         let session_id = scope.path.from_end(1);
         let app_scope = scope.data.get_mut::<AppData>().unwrap();
+        if let Some(&file_id) = app_scope.file_system.tab_id_to_file_node_id.get(&session_id) {
+            if app_scope.file_system.followed_files.contains_key(&file_id) {
+                self.follow_view.draw_walk_follow(cx, &app_scope.file_system, file_id, walk);
+                return DrawStep::done()
+            }
+            if app_scope.file_system.large_files.contains_key(&file_id) {
+                self.large_file_view.draw_walk_large_file(cx, &mut app_scope.file_system, file_id, walk);
+                return DrawStep::done()
+            }
+        }
+        self.editor.set_read_only(app_scope.file_system.is_tab_read_only(session_id));
+        let restored_session = app_scope.file_system.take_restored_session(session_id);
         if let Some(session) = app_scope.file_system.get_session_mut(session_id){
+            if let Some(state) = restored_session {
+                self.editor.restore_session_state(cx, session, &state);
+            }
             self.editor.draw_walk_editor(cx, session, walk);
         }
         DrawStep::done()
     }
     
+    fn reading_text(&self, scope: &mut Scope, out: &mut String) {
+        let session_id = scope.path.from_end(1);
+        let file_system = &scope.data.get::<AppData>().unwrap().file_system;
+        let Some(&file_id) = file_system.tab_id_to_file_node_id.get(&session_id) else {
+            return
+        };
+        if let Some(followed) = file_system.followed_files.get(&file_id) {
+            for line in followed.dropped_lines..followed.line_count() {
+                out.push_str(followed.line(line).unwrap_or(""));
+                out.push('\n');
+            }
+        }
+        else if file_system.large_files.contains_key(&file_id) {
+            push_reading_line(out, &format!("{} is too large to show as text", file_system.file_node_name(file_id)));
+        }
+        else if let Some(OpenDoc::Document(document)) = file_system.open_documents.get(&file_id) {
+            // empty lines are kept, so line numbers stay the same as in the editor
+            for line in document.as_text().as_lines() {
+                out.push_str(line);
+                out.push('\n');
+            }
+        }
+    }
+    
     fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope){
         let session_id = scope.path.from_end(1);
         let data = scope.data.get_mut::<AppData>().unwrap();
         let uid = self.widget_uid();
+        let file_id = data.file_system.tab_id_to_file_node_id.get(&session_id).copied();
+        if let Some(file_id) = file_id {
+            if data.file_system.followed_files.contains_key(&file_id) {
+                self.follow_view.handle_event_follow(cx, event, &mut data.file_system, file_id);
+                return
+            }
+            if data.file_system.large_files.contains_key(&file_id) {
+                self.large_file_view.handle_event_large_file(cx, event, &mut data.file_system, file_id);
+                return
+            }
+        }
+        // the words of the other open files are offered as completions too
+        if let Event::KeyDown(_) | Event::TextInput(_) = event {
+            if self.editor.has_key_focus(cx) {
+                let documents = data.file_system.open_documents.values().filter_map( | doc | match doc {
+                    OpenDoc::Document(document) => Some(document.clone()),
+                    OpenDoc::Decorations(_) => None
+                }).collect();
+                self.editor.completion_engine_mut().set_documents(documents);
+            }
+        }
+        let mut code_action_request = None;
         if let Some(session) = data.file_system.get_session_mut(session_id){
+            // cmd/ctrl+shift+E runs the selection through the evaluator
+            if let Event::KeyDown(KeyEvent {
+                key_code: KeyCode::KeyE,
+                modifiers: KeyModifiers {shift: true, logo, control, ..},
+                ..
+            }) = event {
+                if (*logo || *control) && self.editor.has_key_focus(cx) {
+                    let input = session.copy();
+                    if !input.trim().is_empty() {
+                        if data.build_manager.is_trusted() {
+                            data.evaluator.evaluate(input);
+                        }
+                        else {
+                            log!("Evaluating code is disabled in restricted mode");
+                        }
+                    }
+                }
+            }
             for action in self.editor.handle_event(cx, event, session){
+                if let CodeEditorAction::CodeActionsRequested | CodeEditorAction::ApplyCodeAction(_) = action {
+                    code_action_request = Some(action);
+                }
                 cx.widget_action(uid, &scope.path, action);
             }
+            // the navigation history follows the cursor of the focused editor, to see it jump
+            if !self.editor.has_key_focus(cx) {
+                self.navigation_location = None;
+            }
+            else if let (Some(file_id), Some(window_id)) = (file_id, cx.window_id_of(&self.editor.area())) {
+                let index = session.last_added_selection_index().unwrap_or(0);
+                if let Some(selection) = session.selections().get(index) {
+                    let location = NavigationLocation {
+                        file_id,
+                        position: selection.cursor.position,
+                    };
+                    if self.navigation_location != Some(location) {
+                        self.navigation_location = Some(location);
+                        data.navigation_history.update(window_id, location);
+                    }
+                }
+            }
             data.file_system.handle_sessions();
         }
+        match (code_action_request, file_id) {
+            (Some(CodeEditorAction::CodeActionsRequested), Some(file_id)) => {
+                self.open_code_actions(cx, data, session_id, file_id);
+            }
+            (Some(CodeEditorAction::ApplyCodeAction(index)), _) => {
+                self.apply_code_action(cx, data, index);
+            }
+            _ => ()
+        }
+    }
+}
+
+impl StudioEditor {
+    fn open_code_actions(&mut self, cx: &mut Cx, data: &mut AppData, session_id: LiveId, file_id: LiveId) {
+        let file_name = data.file_system.file_node_relative_path(file_id);
+        let diagnostics = data.build_manager.diagnostics_for_file(&file_name);
+        let Some(session) = data.file_system.get_session_mut(session_id) else {
+            return
+        };
+        let index = session.last_added_selection_index().unwrap_or(0);
+        let selection = session.selections()[index];
+        self.code_actions = data.code_actions.code_actions(&CodeActionRequest {
+            file_name: &file_name,
+            text: &session.document().as_text(),
+            start: selection.start(),
+            end: selection.end(),
+            diagnostics: &diagnostics,
+        });
+        if self.code_actions.is_empty() {
+            log!("No code actions at the cursor");
+        }
+        self.editor.open_code_actions(cx, &self.code_actions);
+    }
+    
+    fn apply_code_action(&mut self, cx: &mut Cx, data: &mut AppData, index: usize) {
+        let Some(action) = self.code_actions.get(index) else {
+            return
+        };
+        // edits to other files are shown first, since they happen out of sight
+        let preview = action.edit.files.len() > 1;
+        if let Err(err) = data.file_system.request_workspace_edit(cx, &action.title, action.edit.clone(), preview) {
+            log!("Cannot apply {}: {}", action.title, err)
+        }
+        self.code_actions.clear();
     }
 }
\ No newline at end of file
@@ -23,11 +23,10 @@ pub struct StudioFileTree{
 impl Widget for StudioFileTree {
     fn draw_walk(&mut self, cx: &mut Cx2d, scope:&mut Scope, walk:Walk)->DrawStep{
         while self.file_tree.draw_walk(cx, scope, walk).is_step() {
-            self.file_tree.set_folder_is_open(cx, live_id!(root).into(), true, Animate::No);
-            scope.data.get_mut::<AppData>().unwrap().file_system.draw_file_node(
+            self.file_tree.draw_source(
                 cx,
-                live_id!(root).into(),
-                &mut self.file_tree
+                live_id!(root),
+                &mut scope.data.get_mut::<AppData>().unwrap().file_system
             );
         }
         DrawStep::done()
@@ -36,4 +35,14 @@ impl Widget for StudioFileTree {
     fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope){
         self.file_tree.handle_event(cx, event, scope);
     }
+    
+    fn reading_text(&self, scope: &mut Scope, out: &mut String) {
+        // the root is always open but isn't drawn itself
+        let file_system = &scope.data.get::<AppData>().unwrap().file_system;
+        if let Some(root) = file_system.file_nodes.get(&live_id!(root)) {
+            for child_edge in root.child_edges.iter().flatten() {
+                file_system.file_node_reading_text(child_edge.file_node_id, &self.file_tree, 0, out);
+            }
+        }
+    }
 }
\ No newline at end of file
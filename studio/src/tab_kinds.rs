@@ -0,0 +1,72 @@
+use crate::makepad_widgets::*;
+
+/// A kind of content a dock tab can hold. The kind id is the name of the dock template that
+/// draws it, and is what the saved dock layout stores for each tab.
+#[derive(Clone, Debug)]
+pub struct TabKind {
+    pub kind: LiveId,
+    /// New tabs of this kind open in the tab bar that holds this tab.
+    pub open_next_to: LiveId,
+    /// Whether tabs of this kind are written to the saved layout. Tabs showing a process that
+    /// is gone after a restart aren't.
+    pub persistent: bool,
+    /// Whether this kind shows the contents of a file, and for which paths.
+    pub opens_file: fn(&str) -> bool,
+}
+
+fn never(_path: &str) -> bool {
+    false
+}
+
+fn always(_path: &str) -> bool {
+    true
+}
+
+/// All the kinds of tab content the studio knows about. New panel types register here instead
+/// of adding cases to the app.
+pub struct TabKindRegistry {
+    kinds: Vec<TabKind>,
+}
+
+impl Default for TabKindRegistry {
+    fn default() -> Self {
+        let mut registry = Self {kinds: Vec::new()};
+        // The code editor also shows binaries, as a read only hex dump.
+        registry.register(TabKind {
+            kind: live_id!(StudioEditor),
+            open_next_to: live_id!(edit_first),
+            persistent: true,
+            opens_file: always,
+        });
+        registry.register(TabKind {
+            kind: live_id!(RunView),
+            open_next_to: live_id!(run_first),
+            persistent: false,
+            opens_file: never,
+        });
+        registry
+    }
+}
+
+impl TabKindRegistry {
+    /// Registers a kind. Kinds registered later take precedence when picking one for a file.
+    pub fn register(&mut self, kind: TabKind) {
+        self.kinds.retain( | other | other.kind != kind.kind);
+        self.kinds.push(kind);
+    }
+
+    pub fn get(&self, kind: LiveId) -> Option<&TabKind> {
+        self.kinds.iter().find( | tab_kind | tab_kind.kind == kind)
+    }
+
+    /// The kind that opens the file at the given path.
+    pub fn for_file(&self, path: &str) -> Option<&TabKind> {
+        self.kinds.iter().rev().find( | tab_kind | (tab_kind.opens_file)(path))
+    }
+
+    /// Whether tabs of the kind are saved with the layout. Kinds that aren't registered, like the
+    /// fixed panels of the layout, always are.
+    pub fn is_persistent(&self, kind: LiveId) -> bool {
+        self.get(kind).map_or(true, | tab_kind | tab_kind.persistent)
+    }
+}
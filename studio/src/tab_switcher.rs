@@ -0,0 +1,183 @@
+use {
+    crate::{
+        makepad_widgets::*,
+        makepad_widgets::tab_bar::fuzzy_match,
+    },
+};
+
+live_design!{
+    import makepad_widgets::theme_desktop_dark::*;
+
+    TabSwitcher = {{TabSwitcher}} {
+        width: Fill, height: Fill,
+        align: { x: 0.5, y: 0.2 }
+        max_rows: 15
+        list_walk: { width: 520, height: Fit }
+        list_layout: { flow: Down, padding: <THEME_MSPACE_2> {} }
+        row_walk: { width: Fill, height: 24 }
+        row_layout: { flow: Right, spacing: (THEME_SPACE_3), align: { y: 0.5 }, padding: { left: (THEME_SPACE_2), right: (THEME_SPACE_2) } }
+        draw_bg: { color: (THEME_COLOR_BG_CONTAINER) }
+        draw_selected: { color: (THEME_COLOR_CTRL_SELECTED) }
+        draw_filter: {
+            text_style: <THEME_FONT_REGULAR> {}
+            color: (THEME_COLOR_TEXT_META)
+        }
+        draw_name: {
+            text_style: <THEME_FONT_REGULAR> {}
+            color: (THEME_COLOR_TEXT_DEFAULT)
+        }
+        draw_path: {
+            text_style: <THEME_FONT_REGULAR> {}
+            color: (THEME_COLOR_TEXT_META)
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct TabSwitcherItem {
+    pub tab_id: LiveId,
+    pub name: String,
+    /// The file the tab shows, relative to the workspace. Empty for tabs without a file.
+    pub path: String,
+}
+
+/// Lists the open tabs of all panels while Ctrl is held after Ctrl+Tab, the most recently used
+/// first. Tab and the arrow keys move through the list, typing filters it by name and path, and
+/// letting go of Ctrl picks the selected tab.
+#[derive(Live, LiveHook, Widget)]
+pub struct TabSwitcher {
+    #[walk] walk: Walk,
+    #[layout] layout: Layout,
+    #[redraw] #[live] draw_bg: DrawColor,
+    #[live] draw_selected: DrawColor,
+    #[live] draw_filter: DrawText,
+    #[live] draw_name: DrawText,
+    #[live] draw_path: DrawText,
+    #[live] list_walk: Walk,
+    #[live] list_layout: Layout,
+    #[live] row_walk: Walk,
+    #[live] row_layout: Layout,
+    #[live] max_rows: usize,
+    #[rust] items: Vec<TabSwitcherItem>,
+    #[rust] filter: String,
+    /// Which of the items the filter lets through is selected.
+    #[rust] selected: usize,
+    #[rust] is_open: bool,
+}
+
+impl Widget for TabSwitcher {
+    fn draw_walk(&mut self, cx: &mut Cx2d, _scope: &mut Scope, walk: Walk) -> DrawStep {
+        if !self.is_open {
+            return DrawStep::done()
+        }
+        cx.begin_turtle(walk, self.layout);
+        self.draw_bg.begin(cx, self.list_walk, self.list_layout);
+        cx.begin_turtle(self.row_walk, self.row_layout);
+        let filter = if self.filter.is_empty() {"Type to filter"} else {&self.filter};
+        self.draw_filter.draw_walk(cx, Walk::fit(), Align::default(), filter);
+        cx.end_turtle();
+        let visible = self.visible_items();
+        // keeps the selected row in view when there are more than fit
+        let first = (self.selected + 1).saturating_sub(self.max_rows);
+        for (row, index) in visible.iter().enumerate().skip(first).take(self.max_rows) {
+            let item = &self.items[*index];
+            cx.begin_turtle(self.row_walk, self.row_layout);
+            if row == self.selected {
+                self.draw_selected.draw_abs(cx, cx.turtle().rect());
+            }
+            self.draw_name.draw_walk(cx, Walk::fit(), Align::default(), &item.name);
+            self.draw_path.draw_walk(cx, Walk::fit(), Align::default(), &item.path);
+            cx.end_turtle();
+        }
+        self.draw_bg.end(cx);
+        cx.end_turtle();
+        DrawStep::done()
+    }
+
+    fn handle_event(&mut self, _cx: &mut Cx, _event: &Event, _scope: &mut Scope) {
+    }
+}
+
+impl TabSwitcher {
+    /// The indices of the items the filter lets through.
+    fn visible_items(&self) -> Vec<usize> {
+        self.items.iter().enumerate()
+            .filter( | (_, item) | fuzzy_match(&self.filter, &item.name) || fuzzy_match(&self.filter, &item.path))
+            .map( | (index, _) | index)
+            .collect()
+    }
+
+    fn move_selection(&mut self, cx: &mut Cx, backward: bool) {
+        let count = self.visible_items().len();
+        if count == 0 {
+            return
+        }
+        self.selected = if backward {(self.selected + count - 1) % count} else {(self.selected + 1) % count};
+        self.draw_bg.redraw(cx);
+    }
+
+    fn set_filter(&mut self, cx: &mut Cx, filter: String) {
+        self.filter = filter;
+        self.selected = 0;
+        self.draw_bg.redraw(cx);
+    }
+}
+
+impl TabSwitcherRef {
+    /// Opens the switcher on `items`, with the one after the current tab selected, so a quick
+    /// Ctrl+Tab goes back to the tab used before.
+    pub fn open(&self, cx: &mut Cx, items: Vec<TabSwitcherItem>) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.selected = if items.len() > 1 {1} else {0};
+            inner.items = items;
+            inner.filter.clear();
+            inner.is_open = true;
+            // keys go to the switcher, not to the editor that had focus
+            cx.set_key_focus(Area::Empty);
+            inner.draw_bg.redraw(cx);
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.borrow().map_or(false, | inner | inner.is_open)
+    }
+
+    /// Closes the switcher and returns the tab that was selected, unless it was cancelled with
+    /// Escape or nothing matched the filter.
+    pub fn close(&self, cx: &mut Cx) -> Option<LiveId> {
+        let mut inner = self.borrow_mut() ?;
+        if !inner.is_open {
+            return None
+        }
+        inner.is_open = false;
+        inner.draw_bg.redraw(cx);
+        let index = *inner.visible_items().get(inner.selected) ?;
+        Some(inner.items[index].tab_id)
+    }
+
+    pub fn handle_key_down(&self, cx: &mut Cx, event: &KeyEvent) {
+        let Some(mut inner) = self.borrow_mut() else {
+            return
+        };
+        match event.key_code {
+            KeyCode::Tab => inner.move_selection(cx, event.modifiers.shift),
+            KeyCode::ArrowDown => inner.move_selection(cx, false),
+            KeyCode::ArrowUp => inner.move_selection(cx, true),
+            KeyCode::Escape => {
+                inner.is_open = false;
+                inner.draw_bg.redraw(cx);
+                cx.revert_key_focus();
+            }
+            KeyCode::Backspace => {
+                let mut filter = inner.filter.clone();
+                filter.pop();
+                inner.set_filter(cx, filter);
+            }
+            // Ctrl is still held, so there is no text input, just the keys
+            key_code => if let Some(char) = key_code.to_char(false).filter( | char | !char.is_whitespace()) {
+                let filter = format!("{}{}", inner.filter, char);
+                inner.set_filter(cx, filter);
+            }
+        }
+    }
+}
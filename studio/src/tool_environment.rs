@@ -0,0 +1,178 @@
+use {
+    crate::{
+        makepad_micro_serde::*,
+        makepad_widgets::*,
+        settings::SettingsFile,
+    },
+    std::{
+        env,
+        io,
+        path::{Path, PathBuf},
+        process::Command,
+        sync::mpsc,
+        thread,
+        time::Duration,
+    },
+};
+
+/// How a tool is run, instead of looking it up on the PATH with the environment of studio.
+#[derive(Clone, Debug, SerRon, DeRon)]
+pub struct ToolOverride {
+    /// A command like `cargo` or `rustfmt`, or the name of a task.
+    pub tool: String,
+    /// What to run instead, usually an absolute path.
+    pub command: Option<String>,
+    /// Set on top of the environment the tool would get otherwise.
+    pub env: Vec<(String, String)>,
+}
+
+/// The tool overrides, for instance to point studio at a toolchain that isn't on the PATH.
+#[derive(Clone, Debug, Default, SerRon, DeRon)]
+pub struct ToolSettings {
+    pub tools: Vec<ToolOverride>,
+}
+
+/// A tool with its overrides applied, ready to spawn.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ResolvedTool {
+    pub command: String,
+    pub env: Vec<(String, String)>,
+}
+
+impl ResolvedTool {
+    pub fn env_refs(&self) -> Vec<(&str, &str)> {
+        self.env.iter().map( | (key, value) | (key.as_str(), value.as_str())).collect()
+    }
+}
+
+impl SettingsFile for ToolSettings {
+    const SETTINGS_FILE: &'static str = "tools.ron";
+}
+
+impl ToolSettings {
+    /// Applies the overrides for `command` and then the ones for `names`, like the name of the
+    /// task running it, so the more specific ones win.
+    pub fn resolve(&self, command: &str, names: &[&str]) -> ResolvedTool {
+        let mut resolved = ResolvedTool {
+            command: command.to_string(),
+            env: Vec::new(),
+        };
+        for name in [command].iter().chain(names) {
+            for tool in self.tools.iter().filter( | tool | tool.tool == *name) {
+                if let Some(command) = &tool.command {
+                    resolved.command = command.clone();
+                }
+                resolved.env.extend(tool.env.iter().cloned());
+            }
+        }
+        resolved
+    }
+}
+
+/// Where `command` would be found on the PATH, or the command itself if it is a path that exists.
+pub fn find_in_path(command: &str) -> Option<PathBuf> {
+    if command.contains('/') || command.contains('\\') {
+        let path = PathBuf::from(command);
+        return path.is_file().then_some(path)
+    }
+    let path = env::var_os("PATH") ?;
+    env::split_paths(&path).find_map( | dir | {
+        let path = dir.join(command);
+        if path.is_file() {
+            return Some(path)
+        }
+        let exe = dir.join(format!("{}.exe", command));
+        exe.is_file().then_some(exe)
+    })
+}
+
+/// Explains why a tool could not be started. A missing binary is the usual reason, so that case
+/// says where it was looked for and how to point studio at it.
+pub fn spawn_error_message(command: &str, err: &io::Error) -> String {
+    if err.kind() != io::ErrorKind::NotFound || find_in_path(command).is_some() {
+        return format!("Cannot start {}: {}", command, err)
+    }
+    let settings_path = ToolSettings::settings_path()
+        .map_or(ToolSettings::SETTINGS_FILE.to_string(), | path | path.display().to_string());
+    format!(
+        "Cannot start {}: it is not on the PATH ({}). Install it, or set its path with a command override in {}",
+        command,
+        env::var("PATH").unwrap_or_default(),
+        settings_path
+    )
+}
+
+/// Variables that describe the shell session that printed the environment, not the user's
+/// setup.
+const SESSION_VARS: &[&str] = &["_", "PWD", "OLDPWD", "SHLVL", "TERM", "TERM_PROGRAM", "TERM_PROGRAM_VERSION", "TERM_SESSION_ID"];
+
+const ENV_MARKER: &str = "_MAKEPAD_SHELL_ENV_";
+
+/// How long a slow shell profile gets before studio gives up on it.
+const SHELL_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Apps started from the Finder or the Dock on macOS get a bare environment from launchd instead
+/// of the one the user's shell profile sets up, so cargo and the other tools in ~/.cargo/bin or
+/// /opt/homebrew/bin are missing from the PATH. This asks the login shell for its environment
+/// and takes it over, so it has to run before studio spawns anything.
+pub fn import_login_shell_environment() {
+    if !launched_from_gui() {
+        return
+    }
+    match login_shell_environment() {
+        Ok(vars) => {
+            for (key, value) in vars {
+                if !SESSION_VARS.contains(&key.as_str()) {
+                    env::set_var(key, value);
+                }
+            }
+        }
+        Err(err) => log!("Cannot read the environment of the login shell: {}", err)
+    }
+    // rustup installs put cargo here, whether or not the profile says so
+    if let Some(home) = env::var_os("HOME") {
+        let cargo_bin = Path::new(&home).join(".cargo/bin");
+        let path = env::var_os("PATH").unwrap_or_default();
+        let mut paths: Vec<PathBuf> = env::split_paths(&path).collect();
+        if cargo_bin.is_dir() && !paths.contains(&cargo_bin) {
+            paths.push(cargo_bin);
+            if let Ok(path) = env::join_paths(paths) {
+                env::set_var("PATH", path);
+            }
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn launched_from_gui() -> bool {
+    use std::io::IsTerminal;
+    // from a terminal we already have the environment of the shell
+    !io::stdin().is_terminal() && env::var_os("TERM_PROGRAM").is_none()
+}
+
+#[cfg(not(target_os = "macos"))]
+fn launched_from_gui() -> bool {
+    false
+}
+
+fn login_shell_environment() -> Result<Vec<(String, String)>, String> {
+    let shell = env::var("SHELL").unwrap_or_else( | _ | "/bin/zsh".to_string());
+    let script = format!("printf {marker}; /usr/bin/env -0; printf {marker}", marker = ENV_MARKER);
+    let (sender, receiver) = mpsc::channel();
+    // interactive, so the rc files run too, which is where most people set their PATH
+    thread::spawn(move || {
+        let _ = sender.send(Command::new(&shell).args(["-l", "-i", "-c", &script]).output());
+    });
+    let output = receiver
+        .recv_timeout(SHELL_TIMEOUT)
+        .map_err( | _ | "the shell did not finish in time".to_string()) ?
+        .map_err( | err | err.to_string()) ?;
+    let output = String::from_utf8_lossy(&output.stdout);
+    // the profile can print whatever it likes around what we asked for
+    let vars = output.split(ENV_MARKER).nth(1).ok_or("the shell did not print its environment") ?;
+    Ok(vars
+        .split('\0')
+        .filter_map( | var | var.split_once('='))
+        .map( | (key, value) | (key.to_string(), value.to_string()))
+        .collect())
+}
@@ -0,0 +1,116 @@
+use crate::makepad_widgets::*;
+
+/// Something that is about to close and may take unsaved documents with it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CloseTarget {
+    Tab(LiveId),
+    /// Closing the studio window, or quitting the app.
+    App,
+}
+
+/// A source of documents that can have unsaved changes. The file system is one, anything else
+/// that holds user data can implement this to be part of the save/discard/cancel dialog.
+pub trait UnsavedChangesProvider {
+    /// The names of the documents that would lose changes if the target closed now.
+    fn unsaved_documents(&self, target: CloseTarget) -> Vec<String>;
+
+    /// Starts saving those documents. Saving may finish later, the target is closed once
+    /// `unsaved_documents` comes back empty.
+    fn save_unsaved(&mut self, target: CloseTarget);
+
+    /// Throws away the changes to those documents.
+    fn discard_unsaved(&mut self, target: CloseTarget);
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+enum UnsavedChangesState {
+    #[default]
+    Idle,
+    /// The dialog is up, waiting for an answer.
+    Asking(CloseTarget),
+    /// The user picked save, waiting for the saves to finish.
+    Saving(CloseTarget),
+}
+
+/// Keeps tabs, windows and the app from closing while they have unsaved documents, and runs the
+/// dialog that asks what to do with them.
+#[derive(Default)]
+pub struct UnsavedChanges {
+    state: UnsavedChangesState,
+}
+
+impl UnsavedChanges {
+    /// Call when the target is about to close. Returns true if it can close right away, otherwise
+    /// shows the dialog, and `handle_actions` or `poll_saved` return the target once it can close.
+    pub fn request_close(&mut self, cx: &mut Cx, ui: &WidgetRef, providers: &[&dyn UnsavedChangesProvider], target: CloseTarget) -> bool {
+        if self.state != UnsavedChangesState::Idle {
+            // already asking about something, closing everything wins
+            if target != CloseTarget::App {
+                return false
+            }
+        }
+        let documents: Vec<String> = providers.iter().flat_map( | provider | provider.unsaved_documents(target)).collect();
+        if documents.is_empty() {
+            return true
+        }
+        let message = if documents.len() == 1 {
+            format!("Do you want to save the changes you made to {}?", documents[0])
+        }
+        else {
+            format!("Do you want to save the changes you made to {} files?\n{}", documents.len(), documents.join("\n"))
+        };
+        ui.label(id!(unsaved_changes.message)).set_text(&message);
+        ui.view(id!(unsaved_changes)).set_visible_and_redraw(cx, true);
+        self.state = UnsavedChangesState::Asking(target);
+        false
+    }
+
+    /// Handles the dialog buttons. Returns the target if it should be closed now.
+    pub fn handle_actions(&mut self, cx: &mut Cx, ui: &WidgetRef, actions: &Actions, providers: &mut [&mut dyn UnsavedChangesProvider]) -> Option<CloseTarget> {
+        let target = match self.state {
+            UnsavedChangesState::Asking(target) => target,
+            _ => return None
+        };
+        if ui.button(id!(unsaved_changes.save_button)).clicked(actions) {
+            self.hide(cx, ui);
+            for provider in providers.iter_mut() {
+                provider.save_unsaved(target);
+            }
+            self.state = UnsavedChangesState::Saving(target);
+            return self.poll_saved(providers)
+        }
+        if ui.button(id!(unsaved_changes.discard_button)).clicked(actions) {
+            self.hide(cx, ui);
+            for provider in providers.iter_mut() {
+                provider.discard_unsaved(target);
+            }
+            self.state = UnsavedChangesState::Idle;
+            return Some(target)
+        }
+        if ui.button(id!(unsaved_changes.cancel_button)).clicked(actions) {
+            self.cancel(cx, ui);
+        }
+        None
+    }
+
+    /// Returns the target once all saves it was waiting for have finished.
+    pub fn poll_saved(&mut self, providers: &mut [&mut dyn UnsavedChangesProvider]) -> Option<CloseTarget> {
+        if let UnsavedChangesState::Saving(target) = self.state {
+            if providers.iter().all( | provider | provider.unsaved_documents(target).is_empty()) {
+                self.state = UnsavedChangesState::Idle;
+                return Some(target)
+            }
+        }
+        None
+    }
+
+    /// Gives up on closing, for instance because a save failed.
+    pub fn cancel(&mut self, cx: &mut Cx, ui: &WidgetRef) {
+        self.hide(cx, ui);
+        self.state = UnsavedChangesState::Idle;
+    }
+
+    fn hide(&self, cx: &mut Cx, ui: &WidgetRef) {
+        ui.view(id!(unsaved_changes)).set_visible_and_redraw(cx, false);
+    }
+}
@@ -0,0 +1,53 @@
+use {
+    crate::{
+        makepad_micro_serde::*,
+        settings::SettingsFile,
+    },
+    std::path::Path,
+};
+
+/// Whether code from a workspace may be run. Building a Rust project runs its build scripts and
+/// proc macros, so a workspace has to be trusted before we do.
+#[derive(Clone, Copy, Debug, PartialEq, SerRon, DeRon)]
+pub enum WorkspaceTrust {
+    Trusted,
+    /// Files can be browsed and edited, but nothing from the workspace is executed: no tasks or
+    /// binaries are built or run, and selections aren't evaluated.
+    Restricted,
+}
+
+#[derive(Clone, Debug, SerRon, DeRon)]
+pub struct WorkspaceTrustEntry {
+    pub path: String,
+    pub trust: WorkspaceTrust,
+}
+
+/// The trust decisions for all workspaces opened so far.
+#[derive(Clone, Debug, Default, SerRon, DeRon)]
+pub struct WorkspaceTrustSettings {
+    pub workspaces: Vec<WorkspaceTrustEntry>,
+}
+
+impl SettingsFile for WorkspaceTrustSettings {
+    const SETTINGS_FILE: &'static str = "workspace_trust.ron";
+}
+
+impl WorkspaceTrustSettings {
+    fn key(path: &Path) -> String {
+        path.canonicalize().unwrap_or(path.to_path_buf()).to_string_lossy().to_string()
+    }
+
+    /// The decision for the workspace, or `None` if the user hasn't been asked yet.
+    pub fn trust_for(&self, path: &Path) -> Option<WorkspaceTrust> {
+        let key = Self::key(path);
+        self.workspaces.iter().find( | entry | entry.path == key).map( | entry | entry.trust)
+    }
+
+    pub fn set_trust(&mut self, path: &Path, trust: WorkspaceTrust) {
+        let key = Self::key(path);
+        match self.workspaces.iter_mut().find( | entry | entry.path == key) {
+            Some(entry) => entry.trust = trust,
+            None => self.workspaces.push(WorkspaceTrustEntry {path: key, trust})
+        }
+    }
+}
@@ -25,15 +25,19 @@ pub fn generate_html(wasm:&str)->String{
         <title>{wasm}</title>
         <script type='module'>
             import {{WasmWebGL}} from './makepad_platform/web_gl.js'
+            import {{WasmWebGPU}} from './makepad_platform/web_gpu.js'
+            
+            // load the page with ?webgpu to render with WebGPU instead of WebGL
+            const WasmWebRenderer = new URLSearchParams(location.search).has('webgpu') ? WasmWebGPU : WasmWebGL;
                             
-            const wasm = await WasmWebGL.fetch_and_instantiate_wasm(
+            const wasm = await WasmWebRenderer.fetch_and_instantiate_wasm(
                 './{wasm}.wasm'
             );
                             
             class MyWasmApp {{
                 constructor(wasm) {{
                     let canvas = document.getElementsByClassName('full_canvas')[0];
-                    this.webgl = new WasmWebGL (wasm, this, canvas);
+                    this.webgl = new WasmWebRenderer (wasm, this, canvas);
                 }}
             }}
             let app = new MyWasmApp(wasm);
@@ -106,6 +110,8 @@ pub fn build(strip:bool, args: &[String]) -> Result<WasmBuildResult, String> {
             
             cp(&dep_dir.join("src/os/web/web_gl.js"), &app_dir.join("makepad_platform/web_gl.js"), false)?;
             
+            cp(&dep_dir.join("src/os/web/web_gpu.js"), &app_dir.join("makepad_platform/web_gpu.js"), false)?;
+            
             cp(&dep_dir.join("src/os/web/web_worker.js"), &app_dir.join("makepad_platform/web_worker.js"), false)?;
             
             cp(&dep_dir.join("src/os/web/web.js"), &app_dir.join("makepad_platform/web.js"), false)?;
@@ -36,6 +36,7 @@ pub fn derive_widget_node_impl(input: TokenStream) ->  TokenStream {
         let mut walk_field = None;
         let mut deref_field = None;
         let mut wrap_field = None;
+        let mut animator_field = None;
         let mut find_fields = Vec::new();
         let mut redraw_fields = Vec::new();
         for field in &mut fields {
@@ -54,6 +55,9 @@ pub fn derive_widget_node_impl(input: TokenStream) ->  TokenStream {
             if field.attrs.iter().find(|v| v.name == "wrap").is_some(){
                 wrap_field = Some(field.name.clone());
             }
+            if field.attrs.iter().find(|v| v.name == "animator").is_some(){
+                animator_field = Some(field.name.clone());
+            }
         }
         tb.add("impl").stream(generic.clone());
         tb.add("WidgetNode for").ident(&struct_name).stream(generic).stream(where_clause).add("{");
@@ -100,6 +104,24 @@ pub fn derive_widget_node_impl(input: TokenStream) ->  TokenStream {
             }
             
         }
+        if let Some(animator_field) = &animator_field{
+            tb.add("    fn animator_states_ptr(&self) -> Option<LivePtr> { self.").ident(&animator_field).add(".live_ptr }");
+            tb.add("    fn animator_state_is(&self, cx: &Cx, state: &[LiveId; 2]) -> bool { self.animator_in_state(cx, state) }");
+            tb.add("    fn animator_force_state(&mut self, cx: &mut Cx, state: &[LiveId; 2]) {");
+            tb.add("        self.animator_cut(cx, state);");
+            tb.add("        WidgetNode::redraw(self, cx);");
+            tb.add("    }");
+        }
+        if animator_field.is_some() || deref_field.is_some(){
+            tb.add("    fn recycle_node(&mut self, _cx: &mut Cx) {");
+            if let Some(animator_field) = &animator_field{
+                tb.add("    self.").ident(&animator_field).add(".swap_out_state();");
+            }
+            if let Some(deref_field) = &deref_field{
+                tb.add("    self.").ident(&deref_field).add(".recycle_node(_cx);");
+            }
+            tb.add("    }");
+        }
         tb.add("}");
         return tb.end();
     }
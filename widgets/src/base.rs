@@ -7,8 +7,10 @@ live_design!{
     import crate::splitter::SplitterBase;
     import crate::desktop_button::DesktopButtonBase;
     import crate::window::WindowBase;
+    import crate::fallback_dialog::FallbackDialogBase;
     import crate::multi_window::MultiWindowBase;
     import crate::drop_down::DropDownBase;
+    import crate::emoji_picker::EmojiPickerBase;
     import crate::file_tree::FileTreeBase;
     import crate::file_tree::FileTreeNodeBase;
     import crate::fold_button::FoldButtonBase;
@@ -23,6 +25,7 @@ live_design!{
     import crate::link_label::LinkLabelBase;
     import crate::portal_list::PortalListBase;
     import crate::flat_list::FlatListBase;
+    import crate::virtual_list::VirtualListBase;
     import crate::scroll_bars::ScrollBarsBase;
     import crate::view::ViewBase;
     import crate::nav_control::NavControlBase;
@@ -41,6 +44,7 @@ live_design!{
     import crate::text_input::TextInputBase;
     import crate::scroll_shadow::DrawScrollShadowBase;
     import crate::page_flip::PageFlipBase;
+    import crate::responsive_view::ResponsiveViewBase;
     import crate::stack_navigation::StackNavigationViewBase;
     import crate::stack_navigation::StackNavigationBase;
     import crate::expandable_panel::ExpandablePanelBase;
@@ -50,6 +54,7 @@ live_design!{
     import crate::html::HtmlLinkBase;
     import crate::markdown::MarkdownBase,
     import crate::root::RootBase;
+    import crate::node_graph::NodeGraphBase;
     
     import crate::designer::DesignerBase;
     import crate::designer_outline::DesignerOutlineBase;
@@ -58,6 +63,8 @@ live_design!{
     import crate::designer_outline_tree::DesignerOutlineTreeBase;
     import crate::designer_outline_tree::DesignerOutlineTreeNodeBase;
     import crate::designer_toolbox::DesignerToolboxBase
+    import crate::designer_state_machine::DesignerStateMachineBase
+    import crate::event_trace_view::EventTraceViewBase
     import crate::color_picker::ColorPicker;
     
     import crate::bare_step::BareStep;
@@ -123,7 +130,7 @@ live_design!{
             instance image_pan: vec2(0.0, 0.0)
             
             fn get_color_scale_pan(self, scale: vec2, pan: vec2) -> vec4 {
-                return sample2d(self.image, self.pos * scale + pan).xyzw;
+                return Pal::from_texture_color_space(sample2d(self.image, self.pos * scale + pan).xyzw, self.draw_texture_color_space);
             }
             
             fn get_color(self) -> vec4 {
@@ -250,7 +257,7 @@ live_design!{
                 let scaled_pos = original_pos / self.scale;
                 
                 // Take pixel color from the original image
-                let color = sample2d(self.image, scaled_pos).xyzw;
+                let color = Pal::from_texture_color_space(sample2d(self.image, scaled_pos).xyzw, self.draw_texture_color_space);
                 
                 let faded_color = color * vec4(1.0, 1.0, 1.0, self.opacity);
                 return faded_color;
@@ -802,7 +809,7 @@ live_design!{
                 return self.clip_and_transform_vertex(self.rect_pos, self.rect_size)
             }
             fn pixel(self) -> vec4 {
-                return sample2d_rt(self.image, self.pos * self.scale + self.shift) + vec4(self.marked, 0.0, 0.0, 0.0);
+                return Pal::from_texture_color_space(sample2d_rt(self.image, self.pos * self.scale + self.shift), self.draw_texture_color_space) + vec4(self.marked, 0.0, 0.0, 0.0);
             }
         }
     }
@@ -844,7 +851,7 @@ live_design!{
                     self.rect_size.y - (self.inset.y + self.inset.w + self.border_width * 2.0),
                     max(1.0, self.radius)
                 )
-                let color = sample2d_rt(self.image, self.pos * self.scale + self.shift);
+                let color = Pal::from_texture_color_space(sample2d_rt(self.image, self.pos * self.scale + self.shift), self.draw_texture_color_space);
                 sdf.fill_keep_premul(color);
                 if self.border_width > 0.0 {
                     sdf.stroke(self.get_border_color(), self.border_width)
@@ -892,6 +899,7 @@ live_design!{
     
     MultiWindow = <MultiWindowBase>{}
     PageFlip = <PageFlipBase>{}
+    ResponsiveView = <ResponsiveViewBase>{}
     KeyboardView = <KeyboardViewBase>{}
     // todo fix this by allowing reexporting imports
     // for now this works too\
@@ -901,14 +909,17 @@ live_design!{
     MarkdownBase = <MarkdownBase>{}
     KeyboardViewBase = <KeyboardViewBase>{}
     PageFlipBase = <PageFlipBase>{}
+    ResponsiveViewBase = <ResponsiveViewBase>{}
     ViewBase = <ViewBase>{}
     ButtonBase = <ButtonBase>{}
     CheckBoxBase = <CheckBoxBase>{}
     DockBase = <DockBase>{}
     MultiWindowBase = <MultiWindowBase>{}
     WindowBase = <WindowBase> {}
+    FallbackDialogBase = <FallbackDialogBase> {}
     DesktopButtonBase = <DesktopButtonBase> {}
     DropDownBase = <DropDownBase> {}
+    EmojiPickerBase = <EmojiPickerBase> {}
     FileTreeBase = <FileTreeBase> {}
     FileTreeNodeBase = <FileTreeNodeBase> {}
     FoldButtonBase = <FoldButtonBase> {}
@@ -921,6 +932,7 @@ live_design!{
     LinkLabelBase = <LinkLabelBase> {}
     PortalListBase = <PortalListBase> {}
     FlatListBase = <FlatListBase>{}
+    VirtualListBase = <VirtualListBase>{}
     NavControlBase = <NavControlBase> {}
     PopupMenuBase = <PopupMenuBase> {}
     PopupMenuItemBase = <PopupMenuItemBase> {}
@@ -943,6 +955,7 @@ live_design!{
     BareStep = <BareStep>{}
     TurtleStep = <TurtleStep>{}
     ColorPicker = <ColorPicker>{}
+    NodeGraphBase = <NodeGraphBase>{}
     
     DesignerBase = <DesignerBase>{}
     DesignerOutlineBase = <DesignerOutlineBase>{}
@@ -951,4 +964,6 @@ live_design!{
     DesignerOutlineTreeBase = <DesignerOutlineTreeBase> {}
     DesignerOutlineTreeNodeBase = <DesignerOutlineTreeNodeBase> {}
     DesignerToolboxBase = <DesignerToolboxBase> {}
+    DesignerStateMachineBase = <DesignerStateMachineBase> {}
+    EventTraceViewBase = <EventTraceViewBase> {}
 }
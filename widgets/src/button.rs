@@ -85,6 +85,10 @@ impl Widget for Button {
         self.draw_text
             .draw_walk(cx, self.label_walk, Align::default(), self.text.as_ref());
         self.draw_bg.end(cx);
+        cx.access_node(
+            AccessNode::new(LiveId(self.widget_uid().0), self.draw_bg.area(), AccessRole::Button)
+                .with_name(self.text.as_ref()),
+        );
         DrawStep::done()
     }
 
@@ -105,6 +109,7 @@ impl Button {
         self.draw_text
         .draw_walk(cx, self.label_walk, Align::default(), label);
         self.draw_bg.end(cx);
+        cx.access_node(AccessNode::new(LiveId(self.widget_uid().0), self.draw_bg.area(), AccessRole::Button).with_name(label));
     }
     
     
@@ -71,6 +71,13 @@ impl CheckBox {
         self.draw_icon.draw_walk(cx, self.icon_walk);
         self.draw_text.draw_walk(cx, self.label_walk, self.label_align, self.text.as_ref());
         self.draw_check.end(cx);
+        let role = if let CheckType::Radio = self.draw_check.check_type {AccessRole::RadioButton} else {AccessRole::CheckBox};
+        let checked = self.animator_in_state(cx, id!(selected.on));
+        cx.access_node(
+            AccessNode::new(LiveId(self.widget_uid().0), self.draw_check.area(), role)
+                .with_name(self.text.as_ref())
+                .with_checked(checked)
+        );
     }
 }
 
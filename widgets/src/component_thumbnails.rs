@@ -0,0 +1,164 @@
+use crate::{
+    makepad_draw::*,
+    widget::*,
+};
+use makepad_zune_png::{
+    PngEncoder,
+    makepad_zune_core::{
+        bit_depth::BitDepth,
+        colorspace::ColorSpace as PngColorSpace,
+        options::EncoderOptions,
+    },
+};
+use std::collections::HashMap;
+
+/// A component rendered to a PNG, as requested with `ComponentThumbnails::request_screenshot`.
+#[derive(Clone, Debug)]
+pub struct ComponentScreenshot {
+    pub ptr: LivePtr,
+    pub width: usize,
+    pub height: usize,
+    pub png: Vec<u8>,
+}
+
+struct Thumbnail {
+    component: WidgetRef,
+    layout_size: DVec2,
+    pass: Pass,
+    color_texture: Texture,
+    draw_list: DrawList2d,
+    dirty: bool,
+    screenshot: bool,
+}
+
+/// Renders live components to small offscreen textures, for the designer palette and for
+/// screenshots in documentation. A component is laid out at its own size and drawn at a dpi
+/// that fits it in the thumbnail, in a pass of its own. Thumbnails are cached per component
+/// and drawn again when the live DSL changes.
+///
+/// The host widget asks for textures with `thumbnail`, calls `draw` from its `draw_walk` to
+/// render the ones that are out of date, and passes its events through `handle_event`.
+pub struct ComponentThumbnails {
+    size: DVec2,
+    clear_color: Vec4,
+    thumbnails: HashMap<LivePtr, Thumbnail>,
+}
+
+impl ComponentThumbnails {
+    /// Thumbnails of at most `size` physical pixels.
+    pub fn new(size: DVec2) -> Self {
+        Self {
+            size,
+            clear_color: vec4(0.0, 0.0, 0.0, 0.0),
+            thumbnails: HashMap::new(),
+        }
+    }
+
+    pub fn set_clear_color(&mut self, clear_color: Vec4) {
+        self.clear_color = clear_color;
+    }
+
+    /// The texture with the thumbnail of the component, which is laid out at `layout_size`.
+    /// It is empty until the next `draw`.
+    pub fn thumbnail(&mut self, cx: &mut Cx, ptr: LivePtr, layout_size: DVec2) -> &Texture {
+        let clear_color = self.clear_color;
+        let thumbnail = self.thumbnails.entry(ptr).or_insert_with( || {
+            let pass = Pass::new_with_name(cx, "thumbnail");
+            let color_texture = Texture::new_with_format(cx, TextureFormat::RenderBGRAu8 {
+                size: TextureSize::Auto,
+            });
+            pass.add_color_texture(cx, &color_texture, PassClearColor::ClearWith(clear_color));
+            Thumbnail {
+                component: WidgetRef::new_from_ptr(cx, Some(ptr)),
+                layout_size,
+                pass,
+                color_texture,
+                draw_list: DrawList2d::new(cx),
+                dirty: true,
+                screenshot: false,
+            }
+        });
+        if thumbnail.layout_size != layout_size {
+            thumbnail.layout_size = layout_size;
+            thumbnail.dirty = true;
+        }
+        &thumbnail.color_texture
+    }
+
+    /// Forgets the thumbnails of components that aren't in `keep`.
+    pub fn retain(&mut self, keep: &[LivePtr]) {
+        self.thumbnails.retain( | ptr, _ | keep.contains(ptr));
+    }
+
+    /// Applies the components again and redraws all thumbnails.
+    pub fn invalidate(&mut self, cx: &mut Cx) {
+        for (ptr, thumbnail) in self.thumbnails.iter_mut() {
+            thumbnail.component.apply_from_ptr(cx, Some(*ptr));
+            thumbnail.dirty = true;
+        }
+    }
+
+    /// Renders the thumbnail of the component and returns it as a PNG from `handle_event`.
+    pub fn request_screenshot(&mut self, cx: &mut Cx, ptr: LivePtr, layout_size: DVec2) {
+        self.thumbnail(cx, ptr, layout_size);
+        let thumbnail = self.thumbnails.get_mut(&ptr).unwrap();
+        thumbnail.screenshot = true;
+        thumbnail.pass.request_read_back(cx, Rect {pos: DVec2::default(), size: layout_size});
+    }
+
+    /// Draws the thumbnails that are out of date, as child passes of the current pass.
+    pub fn draw(&mut self, cx: &mut Cx2d) {
+        for thumbnail in self.thumbnails.values_mut() {
+            if !thumbnail.dirty {
+                continue
+            }
+            thumbnail.dirty = false;
+            // lay out at the component size, and draw at the dpi that fits the thumbnail
+            let layout_size = thumbnail.layout_size;
+            let dpi_factor = (self.size.x / layout_size.x).min(self.size.y / layout_size.y);
+            thumbnail.pass.set_size(cx, layout_size);
+            cx.make_child_pass(&thumbnail.pass);
+            cx.begin_pass(&thumbnail.pass, Some(dpi_factor));
+            thumbnail.draw_list.begin_always(cx);
+            cx.begin_pass_sized_turtle(Layout::flow_down());
+            thumbnail.component.draw_all(cx, &mut Scope::empty());
+            cx.end_pass_sized_turtle();
+            thumbnail.draw_list.end(cx);
+            cx.end_pass(&thumbnail.pass);
+        }
+    }
+
+    pub fn handle_event(&mut self, cx: &mut Cx, event: &Event) -> Option<ComponentScreenshot> {
+        match event {
+            Event::LiveEdit => {
+                self.invalidate(cx);
+                None
+            }
+            Event::PassReadBack(rb) => {
+                let (ptr, thumbnail) = self.thumbnails.iter_mut()
+                    .find( | (_, thumbnail) | thumbnail.pass.pass_id() == rb.pass_id) ?;
+                if !thumbnail.screenshot || rb.pixels.is_empty() {
+                    return None
+                }
+                thumbnail.screenshot = false;
+                let mut rgba = Vec::with_capacity(rb.pixels.len() * 4);
+                for pixel in &rb.pixels {
+                    rgba.extend_from_slice(&[
+                        (pixel >> 16) as u8,
+                        (pixel >> 8) as u8,
+                        *pixel as u8,
+                        (pixel >> 24) as u8,
+                    ]);
+                }
+                let options = EncoderOptions::new(rb.width, rb.height, PngColorSpace::RGBA, BitDepth::Eight);
+                Some(ComponentScreenshot {
+                    ptr: *ptr,
+                    width: rb.width,
+                    height: rb.height,
+                    png: PngEncoder::new(&rgba, options).encode(),
+                })
+            }
+            _ => None
+        }
+    }
+}
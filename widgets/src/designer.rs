@@ -6,6 +6,8 @@ use crate::{
     designer_data::*,
     designer_view::*,
     designer_outline_tree::*,
+    designer_state_machine::*,
+    button::*,
     widget::*,
     makepad_platform::studio::*,
 };
@@ -61,6 +63,9 @@ impl WidgetMatchEvent for Designer{
     fn handle_actions(&mut self, cx: &mut Cx, actions: &Actions, _scope: &mut Scope){
         let outline_tree = self.ui.designer_outline_tree(id!(outline_tree));
         let designer_view = self.ui.designer_view(id!(designer_view));
+        if self.ui.button(id!(responsive_button)).clicked(&actions){
+            designer_view.toggle_responsive_preview(cx);
+        }
         if let Some((outline_id, km, tap_count)) = designer_view.selected(&actions){
             // select the right node in the filetree
             let path = self.data.construct_path(outline_id);
@@ -87,6 +92,8 @@ impl WidgetMatchEvent for Designer{
                         else{
                             designer_view.select_component_and_redraw(cx, None);
                             designer_view.view_file_and_redraw(cx, outline_id);
+                            self.data.selected = Some(outline_id);
+                            self.ui.redraw(cx);
                         }        
                     }
                     OutlineNode::Component{..}=>{
@@ -101,6 +108,8 @@ impl WidgetMatchEvent for Designer{
                             if let Some(file_id) = self.data.find_file_parent(outline_id){
                                 designer_view.select_component_and_redraw(cx, Some(outline_id));
                                 designer_view.view_file_and_redraw(cx, file_id);
+                                self.data.selected = Some(file_id);
+                                self.ui.redraw(cx);
                             }
                         }
                     }
@@ -114,6 +123,11 @@ impl WidgetMatchEvent for Designer{
 impl Widget for Designer {
     fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope){
         self.widget_match_event(cx, event, scope);
+        // the state machine panel polls with a timer, which is when it follows the selection
+        if let Event::Timer(_) = event{
+            let widget = self.ui.designer_view(id!(designer_view)).selected_widget();
+            self.ui.designer_state_machine(id!(state_machine)).set_widget(cx, widget);
+        }
         let mut scope = Scope::with_props(&self.data);
         self.ui.handle_event(cx, event, &mut scope);
     }
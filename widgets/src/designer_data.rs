@@ -36,6 +36,7 @@ pub enum OutlineNode{
 pub struct DesignerData{
     pub root: LiveId,
     pub node_map: HashMap<LiveId, OutlineNode>,
+    /// The file the designer view shows.
     pub selected: Option<LiveId>
 }
 
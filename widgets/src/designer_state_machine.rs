@@ -0,0 +1,269 @@
+use crate::{
+    makepad_derive_widget::*,
+    makepad_draw::*,
+    widget::*,
+};
+
+live_design!{
+    DrawStateNode = {{DrawStateNode}} {}
+    DesignerStateMachineBase = {{DesignerStateMachine}}{
+    }
+}
+
+#[derive(Live, LiveHook, LiveRegister)]#[repr(C)]
+struct DrawStateNode {
+    #[deref] draw_super: DrawQuad,
+    #[live] active: f32,
+    #[live] is_default: f32,
+    #[live] hover: f32,
+}
+
+/// A way into a state, as declared in its `from` block: the state it comes from, or `all`, and
+/// how it plays.
+#[derive(Clone, Debug)]
+pub struct StateTransition {
+    pub from: LiveId,
+    pub play: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct AnimatorStateInfo {
+    pub id: LiveId,
+    pub transitions: Vec<StateTransition>,
+}
+
+/// A track of an animator, with the states it switches between.
+#[derive(Clone, Debug)]
+pub struct AnimatorTrackInfo {
+    pub id: LiveId,
+    pub default: Option<LiveId>,
+    pub states: Vec<AnimatorStateInfo>,
+}
+
+/// Reads the tracks and states of an animator from its live DSL.
+pub fn animator_tracks(cx: &Cx, ptr: LivePtr) -> Vec<AnimatorTrackInfo> {
+    let registry = cx.live_registry.borrow();
+    if !registry.generation_valid(ptr) {
+        return Vec::new()
+    }
+    let (nodes, index) = registry.ptr_to_nodes_index(ptr);
+    let mut tracks = Vec::new();
+    let mut track_index = nodes.first_child(index);
+    while let Some(ti) = track_index {
+        if nodes[ti].origin.has_prop_type(LivePropType::Instance) {
+            let mut track = AnimatorTrackInfo {id: nodes[ti].id, default: None, states: Vec::new()};
+            let mut state_index = nodes.first_child(ti);
+            while let Some(si) = state_index {
+                if let (live_id!(default), LiveValue::Id(default)) = (nodes[si].id, &nodes[si].value) {
+                    track.default = Some(*default);
+                }
+                else if nodes[si].origin.has_prop_type(LivePropType::Instance) {
+                    let mut state = AnimatorStateInfo {id: nodes[si].id, transitions: Vec::new()};
+                    if let Some(from_index) = nodes.child_by_name(si, live_id!(from).as_field()) {
+                        let mut from = nodes.first_child(from_index);
+                        while let Some(fi) = from {
+                            state.transitions.push(StateTransition {
+                                from: nodes[fi].id,
+                                play: describe_play(nodes, fi),
+                            });
+                            from = nodes.next_child(fi);
+                        }
+                    }
+                    track.states.push(state);
+                }
+                state_index = nodes.next_child(si);
+            }
+            tracks.push(track);
+        }
+        track_index = nodes.next_child(ti);
+    }
+    tracks
+}
+
+fn describe_play(nodes: &[LiveNode], index: usize) -> String {
+    match &nodes[index].value {
+        LiveValue::BareEnum(variant) => variant.to_string(),
+        LiveValue::NamedEnum(variant) => {
+            match nodes.child_value_by_path(index, &[live_id!(duration).as_field()]).and_then( | v | v.as_float()) {
+                Some(duration) => format!("{} {}s", variant, duration),
+                None => variant.to_string()
+            }
+        }
+        _ => String::new()
+    }
+}
+
+#[derive(Clone, Debug, DefaultNone)]
+pub enum DesignerStateMachineAction {
+    None,
+    ForceState([LiveId; 2]),
+}
+
+/// Shows the animator of a widget as a graph per track, with the state it is in highlighted,
+/// the transitions into each state listed, and a click on a state forcing the widget into it.
+#[derive(Live, LiveHook, Widget)]
+pub struct DesignerStateMachine {
+    #[walk] walk: Walk,
+    #[redraw] #[live] draw_bg: DrawColor,
+    #[live] draw_state: DrawStateNode,
+    #[live] draw_line: DrawLine,
+    #[live] draw_track_label: DrawText,
+    #[live] draw_state_label: DrawText,
+    #[live] draw_transition: DrawText,
+    #[live] line_color: Vec4,
+    #[live(90.0)] state_width: f64,
+    #[live(24.0)] state_height: f64,
+    #[live(30.0)] state_spacing: f64,
+    #[live(0.1)] poll_interval: f64,
+    #[rust] widget: WidgetRef,
+    #[rust] tracks: Vec<AnimatorTrackInfo>,
+    #[rust] current: Vec<Option<LiveId>>,
+    #[rust] state_rects: Vec<([LiveId; 2], Rect)>,
+    #[rust] hover: Option<[LiveId; 2]>,
+    #[rust] timer: Timer,
+}
+
+impl DesignerStateMachine {
+    fn current_states(&self, cx: &Cx) -> Vec<Option<LiveId>> {
+        self.tracks.iter().map( | track | {
+            track.states.iter()
+                .find( | state | self.widget.animator_state_is(cx, &[track.id, state.id]))
+                .map( | state | state.id)
+        }).collect()
+    }
+
+    fn set_widget(&mut self, cx: &mut Cx, widget: WidgetRef) {
+        if self.widget == widget {
+            return
+        }
+        self.tracks = widget.animator_states_ptr()
+            .map( | ptr | animator_tracks(cx, ptr))
+            .unwrap_or_default();
+        self.widget = widget;
+        self.hover = None;
+        cx.stop_timer(self.timer);
+        if !self.tracks.is_empty() {
+            self.timer = cx.start_interval(self.poll_interval);
+        }
+        self.redraw(cx);
+    }
+}
+
+impl Widget for DesignerStateMachine {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        let uid = self.widget_uid();
+        if self.timer.is_event(event).is_some() || matches!(event, Event::LiveEdit) {
+            if let Event::LiveEdit = event {
+                self.tracks = self.widget.animator_states_ptr()
+                    .map( | ptr | animator_tracks(cx, ptr))
+                    .unwrap_or_default();
+                self.redraw(cx);
+            }
+            // the animator changes state without telling anyone, so look for changes
+            if self.current_states(cx) != self.current {
+                self.redraw(cx);
+            }
+        }
+        match event.hits(cx, self.draw_bg.area()) {
+            Hit::FingerHoverOver(fh) => {
+                let hover = self.state_rects.iter()
+                    .find( | (_, rect) | rect.contains(fh.abs))
+                    .map( | (state, _) | *state);
+                if hover.is_some() {
+                    cx.set_cursor(MouseCursor::Hand);
+                }
+                if hover != self.hover {
+                    self.hover = hover;
+                    self.redraw(cx);
+                }
+            }
+            Hit::FingerHoverOut(_) => {
+                if self.hover.take().is_some() {
+                    self.redraw(cx);
+                }
+            }
+            Hit::FingerDown(fe) => {
+                let state = self.state_rects.iter()
+                    .find( | (_, rect) | rect.contains(fe.abs))
+                    .map( | (state, _) | *state);
+                if let Some(state) = state {
+                    self.widget.animator_force_state(cx, &state);
+                    cx.widget_action(uid, &scope.path, DesignerStateMachineAction::ForceState(state));
+                    self.redraw(cx);
+                }
+            }
+            _ => ()
+        }
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, _scope: &mut Scope, walk: Walk) -> DrawStep {
+        self.current = self.current_states(cx);
+        self.draw_bg.begin(cx, walk, Layout::default());
+        let rect = cx.turtle().rect();
+        self.state_rects.clear();
+        let size = dvec2(self.state_width, self.state_height);
+        let mut y = rect.pos.y + 10.0;
+        for (track, current) in self.tracks.iter().zip(&self.current) {
+            self.draw_track_label.draw_abs(cx, dvec2(rect.pos.x + 10.0, y), &track.id.to_string());
+            y += 20.0;
+            let mut max_transitions = 0;
+            for (i, state) in track.states.iter().enumerate() {
+                let pos = dvec2(rect.pos.x + 10.0 + i as f64 * (size.x + self.state_spacing), y);
+                let state_pair = [track.id, state.id];
+                self.draw_state.active = if *current == Some(state.id) {1.0} else {0.0};
+                self.draw_state.is_default = if track.default == Some(state.id) {1.0} else {0.0};
+                self.draw_state.hover = if self.hover == Some(state_pair) {1.0} else {0.0};
+                self.draw_state.draw_abs(cx, Rect {pos, size});
+                self.draw_state_label.draw_abs(cx, pos + dvec2(6.0, 5.0), &state.id.to_string());
+                self.state_rects.push((state_pair, Rect {pos, size}));
+                for (j, transition) in state.transitions.iter().enumerate() {
+                    let text = format!("from {}: {}", transition.from, transition.play);
+                    self.draw_transition.draw_abs(cx, pos + dvec2(0.0, size.y + 4.0 + j as f64 * 12.0), &text);
+                }
+                max_transitions = max_transitions.max(state.transitions.len());
+            }
+            // arcs over the states for transitions from a specific state
+            for (i, state) in track.states.iter().enumerate() {
+                for transition in &state.transitions {
+                    let Some(from) = track.states.iter().position( | s | s.id == transition.from) else {
+                        continue
+                    };
+                    if from == i {
+                        continue
+                    }
+                    let x = | index: usize | rect.pos.x + 10.0 + index as f64 * (size.x + self.state_spacing) + size.x * 0.5;
+                    let (a, b) = (dvec2(x(from), y), dvec2(x(i), y));
+                    // arcs to the right go over, to the left under the row, so the two directions don't overlap
+                    let (a, b, bend) = if from < i {(a, b, -14.0)} else {
+                        (a + dvec2(0.0, size.y), b + dvec2(0.0, size.y), 14.0)
+                    };
+                    let color = self.line_color;
+                    self.draw_line.draw_bezier_abs(cx, &vec![a, a + dvec2(0.0, bend), b + dvec2(0.0, bend), b], color, 1.0);
+                    self.draw_line.draw_line_abs(cx, b, b + dvec2(-3.0, bend * 0.3), color, 1.0);
+                    self.draw_line.draw_line_abs(cx, b, b + dvec2(3.0, bend * 0.3), color, 1.0);
+                }
+            }
+            y += size.y + 8.0 + max_transitions as f64 * 12.0 + 16.0;
+        }
+        self.draw_bg.end(cx);
+        DrawStep::done()
+    }
+}
+
+impl DesignerStateMachineRef {
+    /// Shows the animator of `widget`, which can be an empty ref to show nothing.
+    pub fn set_widget(&self, cx: &mut Cx, widget: WidgetRef) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.set_widget(cx, widget);
+        }
+    }
+
+    pub fn forced_state(&self, actions: &Actions) -> Option<[LiveId; 2]> {
+        if let Some(item) = actions.find_widget_action(self.widget_uid()) {
+            if let DesignerStateMachineAction::ForceState(state) = item.cast() {
+                return Some(state)
+            }
+        }
+        None
+    }
+}
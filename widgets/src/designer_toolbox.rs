@@ -1,6 +1,7 @@
 use crate::{
     makepad_derive_widget::*,
     makepad_draw::*,
+    component_thumbnails::*,
     designer_data::*,
     view::View,
     widget::*,
@@ -13,18 +14,63 @@ live_design!{
 
 #[derive(Live, Widget, LiveHook)]
 pub struct DesignerToolbox {
-    #[deref] view: View
+    #[deref] view: View,
+    #[live] draw_thumbnail: DrawColor,
+    #[live] draw_thumbnail_label: DrawText,
+    #[live(96.0)] thumbnail_width: f64,
+    #[live(72.0)] thumbnail_height: f64,
+    #[rust(ComponentThumbnails::new(dvec2(192.0, 144.0)))] thumbnails: ComponentThumbnails,
+}
+
+impl DesignerToolbox{
+    /// Draws the palette, a thumbnail of each component in the file the designer shows.
+    fn draw_palette(&mut self, cx: &mut Cx2d, data:&DesignerData){
+        let Some(OutlineNode::File{children,..}) = data.selected.and_then(|id| data.node_map.get(&id)) else{
+            return
+        };
+        let rect = self.view.area().rect(cx);
+        let slot = dvec2(self.thumbnail_width, self.thumbnail_height);
+        let mut pos = dvec2(rect.pos.x + rect.size.x - slot.x - 10.0, rect.pos.y + 60.0);
+        let mut keep = Vec::new();
+        for child in children{
+            let Some(OutlineNode::Component{ptr, name,..}) = data.node_map.get(child) else{
+                continue
+            };
+            let layout_size = if let Some(info) = cx.live_registry.borrow().ptr_to_design_info(*ptr){
+                dvec2(info.dw, info.dh)
+            }
+            else{
+                dvec2(400.0, 300.0)
+            };
+            // keep the aspect ratio of the component in the palette slot
+            let scale = (slot.x / layout_size.x).min(slot.y / layout_size.y);
+            let size = layout_size * scale;
+            let texture = self.thumbnails.thumbnail(cx, *ptr, layout_size);
+            self.draw_thumbnail.draw_vars.set_texture(0, texture);
+            self.draw_thumbnail.draw_abs(cx, Rect{pos, size});
+            self.draw_thumbnail_label.draw_abs(cx, pos + dvec2(0.0, size.y + 2.0), name);
+            pos.y += size.y + 24.0;
+            keep.push(*ptr);
+        }
+        self.thumbnails.retain(&keep);
+        self.thumbnails.draw(cx);
+    }
 }
 
 impl Widget for DesignerToolbox {
     fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope){
+        if let Event::LiveEdit = event{
+            self.view.redraw(cx);
+        }
+        self.thumbnails.handle_event(cx, event);
         self.view.handle_event(cx, event, scope);
     }
     
     fn draw_walk(&mut self, cx: &mut Cx2d, scope:&mut Scope, _walk: Walk) -> DrawStep {
-        let _data = scope.props.get::<DesignerData>().unwrap();
+        let data = scope.props.get::<DesignerData>().unwrap();
         while let Some(_next) = self.view.draw(cx, &mut Scope::empty()).step() {
         }
+        self.draw_palette(cx, data);
         DrawStep::done()
     }
-}
\ No newline at end of file
+}
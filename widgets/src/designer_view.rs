@@ -4,12 +4,15 @@ use crate::{
     makepad_platform::studio::*,
     designer_data::*,
     turtle_step::*,
+    transform_gizmo::*,
     view::View,
     widget::*,
 };
 use std::collections::BTreeMap;
 
 live_design!{
+    DrawDesignerGrid = {{DrawDesignerGrid}}{}
+    
     DesignerViewBase = {{DesignerView}}{
     }
     
@@ -25,10 +28,80 @@ pub enum DesignerViewAction {
         id:LiveId, 
         km:KeyModifiers,
         tap_count: u32,
+    },
+    Transform{
+        id:LiveId,
+        phase: GizmoPhase,
+        rect: Rect,
     }
 }
 
 
+#[derive(Live, LiveHook, LiveRegister)]#[repr(C)]
+struct DrawDesignerGrid {
+    #[deref] draw_super: DrawColor,
+    #[live] grid_size: f32,
+    #[live] line_width: f32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GuideAxis{
+    /// A guide at a fixed x, dragged out of the left ruler.
+    Vertical,
+    /// A guide at a fixed y, dragged out of the top ruler.
+    Horizontal
+}
+
+/// An alignment guide on the canvas, which the gizmo snaps to.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DesignGuide{
+    pub axis: GuideAxis,
+    pub pos: f64
+}
+
+/// The ruler tick spacing in canvas units, a 1, 2 or 5 times a power of ten that keeps ticks
+/// at least `min_px` apart on screen.
+fn ruler_step(zoom:f64, min_px:f64)->f64{
+    let min = min_px * zoom;
+    let mut step = 1.0;
+    loop{
+        for m in [1.0, 2.0, 5.0]{
+            if step * m >= min{
+                return step * m
+            }
+        }
+        step *= 10.0;
+    }
+}
+
+/// A device size the responsive preview shows components at.
+#[derive(Clone, Debug)]
+pub struct DevicePreset{
+    pub name: String,
+    pub size: DVec2,
+    pub dpi_factor: f64
+}
+
+impl DevicePreset{
+    pub fn defaults()->Vec<DevicePreset>{
+        vec![
+            DevicePreset{name: "Phone".into(), size: dvec2(390.0, 844.0), dpi_factor: 3.0},
+            DevicePreset{name: "Tablet".into(), size: dvec2(820.0, 1180.0), dpi_factor: 2.0},
+            DevicePreset{name: "Desktop".into(), size: dvec2(1440.0, 900.0), dpi_factor: 1.0},
+        ]
+    }
+}
+
+/// A component drawn at the size and dpi of a device, in a pass of its own so the layout,
+/// and any `ResponsiveView` in it, is evaluated for that device.
+struct DevicePreview{
+    ptr: LivePtr,
+    component: WidgetRef,
+    pass: Pass,
+    color_texture: Texture,
+    draw_list: DrawList2d,
+}
+
 struct ContainerData{
     ptr: LivePtr,
     component: WidgetRef,
@@ -118,7 +191,8 @@ enum FingerMove{
     Pan{start_pan: DVec2},
     DragBody{ptr: LivePtr},
     DragEdge{edge: Edge, rect:Rect, id: LiveId},
-    DragAll{rects:BTreeMap<LiveId,Rect>}
+    DragAll{rects:BTreeMap<LiveId,Rect>},
+    DragGuide{index: usize}
 }
 
 #[derive(Live, Widget)]
@@ -133,6 +207,25 @@ pub struct DesignerView {
     #[rust] finger_move: Option<FingerMove>,
     #[live] container: Option<LivePtr>,
     #[live] draw_bg: DrawColor,
+    #[live] gizmo: TransformGizmo,
+    #[live] draw_grid: DrawDesignerGrid,
+    #[live] draw_ruler: DrawColor,
+    #[live] draw_ruler_text: DrawText,
+    #[live] draw_line: DrawLine,
+    #[live] tick_color: Vec4,
+    #[live] guide_color: Vec4,
+    #[live] snap_color: Vec4,
+    #[live(true)] show_grid: bool,
+    #[live(true)] show_rulers: bool,
+    /// Grid spacing in canvas units, which the gizmo snaps to while the grid is shown.
+    #[live(10.0)] grid_size: f64,
+    #[live(18.0)] ruler_size: f64,
+    #[rust] guides: Vec<DesignGuide>,
+    #[live] draw_preview: DrawColor,
+    #[live] draw_preview_label: DrawText,
+    #[rust] responsive_preview: bool,
+    #[rust(DevicePreset::defaults())] devices: Vec<DevicePreset>,
+    #[rust] previews: ComponentMap<usize, DevicePreview>,
     #[rust] view_file: Option<LiveId>,
     #[rust] selected_component: Option<LiveId>,
     #[rust] containers: ComponentMap<LiveId, ContainerData>,
@@ -186,6 +279,64 @@ impl DesignerView{
         cd.container.draw_all(cx, &mut Scope::with_props(cd))
     }
     
+    /// The component the responsive preview shows, the selected one or else the first one in
+    /// the file.
+    fn preview_component(&self, data:&DesignerData)->Option<LivePtr>{
+        if let Some(OutlineNode::Component{ptr,..}) = self.selected_component.and_then(|id| data.node_map.get(&id)){
+            return Some(*ptr)
+        }
+        if let Some(OutlineNode::File{children,..}) = self.view_file.and_then(|id| data.node_map.get(&id)){
+            for child in children{
+                if let Some(OutlineNode::Component{ptr,..}) = data.node_map.get(child){
+                    return Some(*ptr)
+                }
+            }
+        }
+        None
+    }
+    
+    fn draw_previews(&mut self, cx:&mut Cx2d, ptr:LivePtr){
+        let mut x = 50.0;
+        for (index, device) in self.devices.iter().enumerate(){
+            let clear_color = self.clear_color;
+            let preview = self.previews.get_or_insert(cx, index, |cx|{
+                let pass = Pass::new(cx);
+                let color_texture = Texture::new_with_format(cx, TextureFormat::RenderBGRAu8 {
+                    size: TextureSize::Auto,
+                });
+                pass.add_color_texture(cx, &color_texture, PassClearColor::ClearWith(clear_color));
+                DevicePreview{
+                    ptr,
+                    component: WidgetRef::new_from_ptr(cx, Some(ptr)),
+                    pass,
+                    color_texture,
+                    draw_list: DrawList2d::new(cx)
+                }
+            });
+            if preview.ptr != ptr || self.reapply{
+                preview.component.apply_from_ptr(cx, Some(ptr));
+                preview.ptr = ptr;
+            }
+            
+            cx.make_child_pass(&preview.pass);
+            cx.begin_pass(&preview.pass, Some(device.dpi_factor));
+            preview.draw_list.begin_always(cx);
+            cx.begin_pass_sized_turtle(Layout::flow_down());
+            preview.component.draw_all(cx, &mut Scope::empty());
+            cx.end_pass_sized_turtle();
+            preview.draw_list.end(cx);
+            cx.end_pass(&preview.pass);
+            
+            let rect = Rect{pos: dvec2(x, 50.0), size: device.size};
+            self.draw_preview.draw_vars.set_texture(0, &preview.color_texture);
+            self.draw_preview.draw_abs(cx, rect);
+            cx.set_pass_area(&preview.pass, self.draw_preview.area());
+            let label = format!("{} {}x{} @{}x", device.name, device.size.x, device.size.y, device.dpi_factor);
+            self.draw_preview_label.draw_abs(cx, rect.pos - dvec2(0.0, 20.0), &label);
+            x += device.size.x + 50.0;
+        }
+    }
+    
     fn select_component(&mut self, cx:&mut Cx, what_id:Option<LiveId>){
         for (id, comp) in self.containers.iter_mut(){
             if what_id == Some(*id){
@@ -200,6 +351,184 @@ impl DesignerView{
         self.selected_component = what_id;
     }
     
+    fn canvas_view(&self, cx:&Cx)->GizmoView{
+        GizmoView{
+            origin: self.area.rect(cx).pos,
+            offset: self.pan,
+            scale: self.zoom
+        }
+    }
+    
+    /// Puts the gizmo on the selected container, with the alignment guides and the edges and
+    /// centers of the other containers to snap to.
+    fn sync_gizmo(&mut self, cx:&Cx)->Option<LiveId>{
+        let id = self.selected_component?;
+        let rect = self.containers.get(&id)?.rect;
+        self.gizmo.view = self.canvas_view(cx);
+        self.gizmo.set_grid_size(if self.show_grid{self.grid_size} else {0.0});
+        if !self.gizmo.is_dragging(){
+            self.gizmo.transform = GizmoTransform{rect, rotation: 0.0};
+        }
+        self.gizmo.guides_x.clear();
+        self.gizmo.guides_y.clear();
+        for guide in &self.guides{
+            match guide.axis{
+                GuideAxis::Vertical=>self.gizmo.guides_x.push(guide.pos),
+                GuideAxis::Horizontal=>self.gizmo.guides_y.push(guide.pos),
+            }
+        }
+        for (other_id, cd) in self.containers.iter(){
+            if *other_id != id{
+                let r = cd.rect;
+                self.gizmo.guides_x.extend([r.pos.x, r.pos.x + r.size.x * 0.5, r.pos.x + r.size.x]);
+                self.gizmo.guides_y.extend([r.pos.y, r.pos.y + r.size.y * 0.5, r.pos.y + r.size.y]);
+            }
+        }
+        Some(id)
+    }
+    
+    /// The guide axis a ruler creates, if `abs` is on one.
+    fn ruler_at(&self, cx:&Cx, abs:DVec2)->Option<GuideAxis>{
+        if !self.show_rulers{
+            return None
+        }
+        let rel = abs - self.area.rect(cx).pos;
+        if rel.y < self.ruler_size && rel.x >= self.ruler_size{
+            Some(GuideAxis::Horizontal)
+        }
+        else if rel.x < self.ruler_size && rel.y >= self.ruler_size{
+            Some(GuideAxis::Vertical)
+        }
+        else{
+            None
+        }
+    }
+    
+    fn guide_at(&self, cx:&Cx, abs:DVec2)->Option<usize>{
+        let view = self.canvas_view(cx);
+        self.guides.iter().position(|guide|{
+            let pos = view.to_screen(dvec2(guide.pos, guide.pos));
+            match guide.axis{
+                GuideAxis::Vertical=>(pos.x - abs.x).abs() <= 3.0,
+                GuideAxis::Horizontal=>(pos.y - abs.y).abs() <= 3.0,
+            }
+        })
+    }
+    
+    /// Creates, moves and removes guides. Returns whether the hit was used for that.
+    fn handle_guides(&mut self, cx:&mut Cx, hit:&Hit)->bool{
+        match hit{
+            Hit::FingerHoverOver(fh) if self.finger_move.is_none()=>{
+                let axis = self.ruler_at(cx, fh.abs)
+                    .or_else(|| self.guide_at(cx, fh.abs).map(|index| self.guides[index].axis));
+                match axis{
+                    Some(GuideAxis::Vertical)=>cx.set_cursor(MouseCursor::EwResize),
+                    Some(GuideAxis::Horizontal)=>cx.set_cursor(MouseCursor::NsResize),
+                    None=>return false
+                }
+                true
+            }
+            Hit::FingerDown(fe)=>{
+                let index = if let Some(axis) = self.ruler_at(cx, fe.abs){
+                    let pos = self.canvas_view(cx).to_canvas(fe.abs);
+                    let pos = match axis{
+                        GuideAxis::Vertical=>pos.x.round(),
+                        GuideAxis::Horizontal=>pos.y.round(),
+                    };
+                    self.guides.push(DesignGuide{axis, pos});
+                    self.guides.len() - 1
+                }
+                else if let Some(index) = self.guide_at(cx, fe.abs){
+                    index
+                }
+                else{
+                    return false
+                };
+                self.finger_move = Some(FingerMove::DragGuide{index});
+                self.draw_bg.redraw(cx);
+                true
+            }
+            Hit::FingerMove(fe)=>{
+                let Some(FingerMove::DragGuide{index}) = self.finger_move else{
+                    return false
+                };
+                let pos = self.canvas_view(cx).to_canvas(fe.abs);
+                let guide = &mut self.guides[index];
+                guide.pos = match guide.axis{
+                    GuideAxis::Vertical=>pos.x.round(),
+                    GuideAxis::Horizontal=>pos.y.round(),
+                };
+                self.draw_bg.redraw(cx);
+                true
+            }
+            Hit::FingerUp(fe)=>{
+                let Some(FingerMove::DragGuide{index}) = self.finger_move else{
+                    return false
+                };
+                self.finger_move = None;
+                // dropping a guide back on a ruler removes it
+                if self.ruler_at(cx, fe.abs).is_some(){
+                    self.guides.remove(index);
+                }
+                self.draw_bg.redraw(cx);
+                true
+            }
+            _=>false
+        }
+    }
+    
+    fn draw_guides(&mut self, cx:&mut Cx2d, rect:Rect){
+        let view = self.canvas_view(cx);
+        let (snap_x, snap_y) = self.gizmo.snap_lines();
+        let lines = self.guides.iter().map(|guide| (guide.axis, guide.pos, self.guide_color))
+            .chain(snap_x.into_iter().map(|x| (GuideAxis::Vertical, x, self.snap_color)))
+            .chain(snap_y.into_iter().map(|y| (GuideAxis::Horizontal, y, self.snap_color)))
+            .collect::<Vec<_>>();
+        for (axis, pos, color) in lines{
+            let screen = view.to_screen(dvec2(pos, pos));
+            let (from, to) = match axis{
+                GuideAxis::Vertical=>(dvec2(screen.x, rect.pos.y), dvec2(screen.x, rect.pos.y + rect.size.y)),
+                GuideAxis::Horizontal=>(dvec2(rect.pos.x, screen.y), dvec2(rect.pos.x + rect.size.x, screen.y)),
+            };
+            self.draw_line.draw_line_abs(cx, from, to, color, 1.0);
+        }
+    }
+    
+    fn draw_rulers(&mut self, cx:&mut Cx2d, rect:Rect){
+        let view = self.canvas_view(cx);
+        let size = self.ruler_size;
+        self.draw_ruler.draw_abs(cx, Rect{pos: rect.pos, size: dvec2(rect.size.x, size)});
+        self.draw_ruler.draw_abs(cx, Rect{pos: dvec2(rect.pos.x, rect.pos.y + size), size: dvec2(size, rect.size.y - size)});
+        let step = ruler_step(self.zoom, 60.0);
+        let start = view.to_canvas(rect.pos + dvec2(size, size));
+        let end = view.to_canvas(rect.pos + rect.size);
+        
+        let mut x = (start.x / step * 2.0).floor() * step * 0.5;
+        while x <= end.x{
+            let sx = view.to_screen(dvec2(x, 0.0)).x;
+            let major = (x / step).fract().abs() < 0.001;
+            let length = if major{size * 0.5} else {size * 0.25};
+            let bottom = rect.pos.y + size;
+            self.draw_line.draw_line_abs(cx, dvec2(sx, bottom - length), dvec2(sx, bottom), self.tick_color, 1.0);
+            if major{
+                self.draw_ruler_text.draw_abs(cx, dvec2(sx + 2.0, rect.pos.y + 1.0), &format!("{}", x as i64));
+            }
+            x += step * 0.5;
+        }
+        let mut y = (start.y / step * 2.0).floor() * step * 0.5;
+        while y <= end.y{
+            let sy = view.to_screen(dvec2(0.0, y)).y;
+            let major = (y / step).fract().abs() < 0.001;
+            let length = if major{size * 0.5} else {size * 0.25};
+            let right = rect.pos.x + size;
+            self.draw_line.draw_line_abs(cx, dvec2(right - length, sy), dvec2(right, sy), self.tick_color, 1.0);
+            if major{
+                self.draw_ruler_text.draw_abs(cx, dvec2(rect.pos.x + 1.0, sy + 2.0), &format!("{}", y as i64));
+            }
+            y += step * 0.5;
+        }
+    }
+    
     fn patch_design_info(&mut self, cx:&mut Cx, id: LiveId, rect:Rect){
         if let Some(container) = self.containers.get_mut(&id){
             container.container.redraw(cx);
@@ -237,7 +566,26 @@ impl DesignerView{
 impl Widget for DesignerView {
     fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope){
         let uid = self.widget_uid();
-        match event.hits(cx, self.area) {
+        let hit = event.hits_with_options(cx, self.area, HitOptions::new().with_multi_touch(true));
+        if self.handle_guides(cx, &hit){
+            return
+        }
+        if let Some(id) = self.sync_gizmo(cx){
+            if let Some(action) = self.gizmo.handle_hit(cx, &hit){
+                match action.phase{
+                    GizmoPhase::Begin=>self.undo_group += 1,
+                    _=>self.patch_design_info(cx, id, action.transform.rect)
+                }
+                self.draw_bg.redraw(cx);
+                cx.widget_action(uid, &scope.path, DesignerViewAction::Transform{
+                    id,
+                    phase: action.phase,
+                    rect: action.transform.rect
+                });
+                return
+            }
+        }
+        match hit {
             Hit::FingerHoverOver(fh) =>{
                 
                 // alright so we hover over. lets determine the mouse cursor
@@ -294,6 +642,7 @@ impl Widget for DesignerView {
                                     // set selected component
                                     // unselect all other components
                                     self.select_component(cx, Some(*id));
+                                    self.draw_bg.redraw(cx);
                                     break;
                                 }
                                 None=>()
@@ -332,6 +681,18 @@ impl Widget for DesignerView {
                 
                 self.redraw(cx);
             }
+            Hit::FingerGesture(fg)=>{
+                // two fingers always pan and zoom, whatever the first one was doing
+                self.finger_move = Some(FingerMove::Pan{
+                    start_pan: self.pan
+                });
+                let last_zoom = self.zoom;
+                self.zoom = (self.zoom / fg.scale).max(0.01).min(10.0);
+                // keep what was between the fingers between them
+                let anchor = self.pan + (fg.abs - fg.pan - fg.rect.pos) * last_zoom;
+                self.pan = anchor - (fg.abs - fg.rect.pos) * self.zoom;
+                self.redraw(cx);
+            }
             Hit::FingerMove(fe) => {
                 match self.finger_move.as_ref().unwrap(){
                     FingerMove::Pan{start_pan} =>{
@@ -378,6 +739,7 @@ impl Widget for DesignerView {
                     FingerMove::DragBody{ptr:_}=>{
                         
                     }
+                    FingerMove::DragGuide{..}=>()
                 }
             }
             Hit::FingerUp(_) => {
@@ -415,9 +777,22 @@ impl Widget for DesignerView {
             
             let data = scope.props.get::<DesignerData>().unwrap();
             
+            // the grid covers the visible part of the canvas, unless it got too dense to see
+            if self.show_grid && self.grid_size / self.zoom >= 4.0{
+                let size = self.area.rect(cx).size;
+                self.draw_grid.grid_size = self.grid_size as f32;
+                self.draw_grid.line_width = self.zoom as f32;
+                self.draw_grid.draw_abs(cx, Rect{pos: self.pan, size: size * self.zoom});
+            }
+            
             // lets draw the component container windows and components
             
-            if let Some(view_file) = &self.view_file{
+            if self.responsive_preview{
+                if let Some(ptr) = self.preview_component(data){
+                    self.draw_previews(cx, ptr);
+                }
+            }
+            else if let Some(view_file) = &self.view_file{
                 // so either we have a file, or a single component.
                 match data.node_map.get(view_file){
                     Some(OutlineNode::File{children,..})=>{
@@ -435,12 +810,20 @@ impl Widget for DesignerView {
             cx.end_pass_sized_turtle_no_clip();
             self.draw_list.end(cx);
             cx.end_pass(&self.pass);
-            self.containers.retain_visible()
+            self.containers.retain_visible();
+            self.previews.retain_visible();
         }
         
         self.draw_bg.draw_vars.set_texture(0, self.color_texture.as_ref().unwrap());
         let rect = cx.walk_turtle_with_area(&mut self.area, walk);
         self.draw_bg.draw_abs(cx, rect);
+        self.draw_guides(cx, rect);
+        if self.sync_gizmo(cx).is_some(){
+            self.gizmo.draw(cx);
+        }
+        if self.show_rulers{
+            self.draw_rulers(cx, rect);
+        }
             
         cx.set_pass_area_with_origin(
             &self.pass,
@@ -472,6 +855,71 @@ impl DesignerViewRef{
         None
     }
     
+    /// Shows the selected component at the size of each device side by side, instead of the
+    /// components of the file where they were placed.
+    pub fn set_responsive_preview(&self, cx:&mut Cx, on:bool) {
+        if let Some(mut inner) = self.borrow_mut(){
+            inner.responsive_preview = on;
+            inner.redraw(cx);
+            inner.draw_bg.redraw(cx);
+        }
+    }
+    
+    pub fn toggle_responsive_preview(&self, cx:&mut Cx) {
+        let on = self.is_responsive_preview();
+        self.set_responsive_preview(cx, !on);
+    }
+    
+    pub fn is_responsive_preview(&self) -> bool {
+        self.borrow().map_or(false, |inner| inner.responsive_preview)
+    }
+    
+    pub fn set_preview_devices(&self, cx:&mut Cx, devices:Vec<DevicePreset>) {
+        if let Some(mut inner) = self.borrow_mut(){
+            inner.devices = devices;
+            inner.previews.clear();
+            inner.redraw(cx);
+        }
+    }
+    
+    /// The instance of the selected component, as drawn in the designer.
+    pub fn selected_widget(&self) -> WidgetRef {
+        if let Some(inner) = self.borrow(){
+            if let Some(cd) = inner.selected_component.and_then(|id| inner.containers.get(&id)){
+                return cd.component.clone()
+            }
+        }
+        WidgetRef::empty()
+    }
+    
+    pub fn set_grid(&self, cx:&mut Cx, show:bool, grid_size:f64) {
+        if let Some(mut inner) = self.borrow_mut(){
+            inner.show_grid = show;
+            inner.grid_size = grid_size;
+            inner.redraw(cx);
+        }
+    }
+    
+    pub fn guides(&self) -> Vec<DesignGuide> {
+        self.borrow().map(|inner| inner.guides.clone()).unwrap_or_default()
+    }
+    
+    pub fn clear_guides(&self, cx:&mut Cx) {
+        if let Some(mut inner) = self.borrow_mut(){
+            inner.guides.clear();
+            inner.draw_bg.redraw(cx);
+        }
+    }
+    
+    pub fn transformed(&self, actions: &Actions) -> Option<(LiveId,GizmoPhase,Rect)> {
+        if let Some(item) = actions.find_widget_action(self.widget_uid()) {
+            if let DesignerViewAction::Transform{id, phase, rect} = item.cast() {
+                return Some((id, phase, rect))
+            }
+        }
+        None
+    }
+    
     pub fn selected(&self, actions: &Actions) -> Option<(LiveId,KeyModifiers,u32)> {
         if let Some(item) = actions.find_widget_action(self.widget_uid()) {
             if let DesignerViewAction::Selected{id, km, tap_count} = item.cast() {
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::str::Chars;
 use crate::{
     makepad_micro_serde::*,
@@ -6,7 +6,8 @@ use crate::{
     widget::*,
     makepad_draw::*,
     splitter::{SplitterAction, Splitter, SplitterAlign, SplitterAxis},
-    tab_bar::{TabBarAction, TabBar},
+    tab_bar::{fuzzy_match, TabBarAction, TabBar},
+    tab_close_button::{TabCloseButton, TabCloseButtonAction},
 };
 
 live_design!{
@@ -48,9 +49,15 @@ pub struct Dock {
     #[live] padding_fill: DrawColor,
     #[live] border_size: f64,
     #[live] drag_quad: DrawColor,
+    #[live] floating_bg: DrawColor,
+    #[live] floating_title_bar: DrawColor,
+    #[live] floating_title: DrawText,
+    #[live(24.0)] floating_title_height: f64,
+    #[live] floating_close_button: Option<LivePtr>,
     
     #[live] tab_bar: Option<LivePtr>,
     #[live] splitter: Option<LivePtr>,
+    #[live] tab_order: TabOrder,
     
     #[rust] needs_save: bool,
     #[rust] area: Area,
@@ -61,8 +68,16 @@ pub struct Dock {
     #[rust] dock_items: HashMap<LiveId, DockItem>,
     #[rust] templates: HashMap<LiveId, LivePtr>,
     #[rust] items: ComponentMap<LiveId, (LiveId, WidgetRef)>,
+    /// The contents of closed tabs, handed out again for the next tab of the same kind.
+    #[rust] pool: ComponentPool<LiveId, WidgetRef>,
     #[rust] drop_state: Option<DropPosition>,
     #[rust] dock_item_iter_stack: Vec<(LiveId, usize)>,
+    /// The tabs that have been selected, the most recent first.
+    #[rust] recent_tabs: Vec<LiveId>,
+    /// Where a Ctrl+Tab cycle is in the recently used tabs.
+    #[rust] tab_cycle: Option<usize>,
+    /// The panels floating above the docked ones, the topmost last.
+    #[rust] floating: Vec<FloatingPanel>,
 }
 
 impl WidgetNode for Dock{
@@ -141,7 +156,73 @@ pub struct DockItemId {
 struct TabBarWrap {
     tab_bar: TabBar,
     contents_draw_list: DrawList2d,
-    contents_rect: Rect
+    contents_rect: Rect,
+    /// The tabs as drawn, in order and without the ones the filter hides.
+    visible_tabs: Vec<LiveId>
+}
+
+/// A panel that isn't part of the splits but floats above them, with a title bar it is moved
+/// by and a close button. Moving it over a docked panel docks it there again.
+struct FloatingPanel {
+    /// The `DockItem::Tabs` it shows.
+    id: LiveId,
+    rect: Rect,
+    draw_list: DrawList2d,
+    close_button: TabCloseButton,
+    title_area: Area,
+    bg_area: Area,
+    drag_start_pos: Option<DVec2>,
+    /// Set when the panel was torn out into a native window of its own.
+    window: Option<FloatingWindow>,
+}
+
+/// The native window a torn out floating panel is drawn in, owned by the dock's window.
+struct FloatingWindow {
+    window: WindowHandle,
+    pass: Pass,
+    draw_list: DrawList2d,
+    overlay: Overlay,
+    _depth_texture: Texture,
+}
+
+impl FloatingWindow {
+    fn open(cx: &mut Cx, owner: Option<WindowId>, title: &str, rect: Rect) -> Self {
+        // the window opens where the panel was, as far as we know where the dock's window is
+        let position = owner
+            .filter( | owner | cx.windows[*owner].is_created)
+            .map( | owner | cx.windows[owner].window_geom.position + rect.pos);
+        let window = WindowHandle::open(cx, title, rect.size, position);
+        window.set_owner(cx, owner);
+        let pass = Pass::new_with_name(cx, "dock floating window");
+        window.set_pass(cx, &pass);
+        let depth_texture = Texture::new_with_format(cx, TextureFormat::DepthD32{
+            size:TextureSize::Auto
+        });
+        pass.set_depth_texture(cx, &depth_texture, PassClearDepth::ClearWith(1.0));
+        Self {
+            window,
+            pass,
+            draw_list: DrawList2d::new(cx),
+            overlay: Overlay::new(cx),
+            _depth_texture: depth_texture,
+        }
+    }
+}
+
+/// The order the tabs of a tab bar are shown in. Only the manual order is stored, it is the
+/// one tabs are dragged around in and the others are computed from it when drawing.
+#[derive(Copy, Clone, Debug, PartialEq, Live, LiveHook)]
+#[live_ignore]
+pub enum TabOrder {
+    #[pick] Manual,
+    Alphabetical,
+    MostRecentlyUsed
+}
+
+impl Default for TabOrder {
+    fn default() -> Self {
+        TabOrder::Manual
+    }
 }
 
 #[derive(Copy, Debug, Clone)]
@@ -153,7 +234,9 @@ enum DrawStackItem {
     Tabs {id: LiveId},
     TabLabel {id: LiveId, index: usize},
     Tab {id: LiveId},
-    TabContent {id: LiveId}
+    TabContent {id: LiveId},
+    Floating {index: usize},
+    FloatingEnd {index: usize}
 }
 
 impl DrawStackItem {
@@ -284,6 +367,24 @@ pub enum DockItemStore{
     }
 }
 
+/// How the panels of a dock are arranged, as a tree that leaves out what is in the tabs. An
+/// application can write its default layout as one, and compare and change the layout of a
+/// running dock through `Dock::layout` and `Dock::apply_layout`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum DockLayout {
+    /// Panels next to each other along `axis`. The first child gets `ratio` of the space, more
+    /// than two children share what is left equally.
+    Split {
+        axis: SplitterAxis,
+        ratio: f64,
+        children: Vec<DockLayout>
+    },
+    Tabs {
+        tab_ids: Vec<LiveId>,
+        selected: usize
+    }
+}
+
 impl LiveHook for Dock {
     fn apply_value_instance(&mut self, cx: &mut Cx, apply: &mut Apply, index: usize, nodes: &[LiveNode]) -> usize {
         let id = nodes[index].id;
@@ -300,6 +401,7 @@ impl LiveHook for Dock {
                     else {
                         let live_ptr = cx.live_registry.borrow().file_id_index_to_live_ptr(file_id, index);
                         self.templates.insert(id, live_ptr);
+                        self.pool.clear();
                         // lets apply this thing over all our childnodes with that template
                         for (kind, node) in self.items.values_mut() {
                             if *kind == id {
@@ -374,95 +476,125 @@ impl Dock {
         cx.end_turtle_with_area(&mut self.area);
     }
     
-    fn find_drop_position(&self, cx: &Cx, abs: DVec2) -> Option<DropPosition> {
+    fn find_drop_position(&self, cx: &Cx, abs: DVec2, except: Option<LiveId>) -> Option<DropPosition> {
+        // floating panels are on top, and hide what is under them
+        for panel in self.floating.iter().rev() {
+            // panels in windows of their own aren't over the dock
+            if Some(panel.id) != except && panel.window.is_none() && panel.rect.contains(abs) {
+                let tab_bar = self.tab_bars.get(&panel.id) ?;
+                return Self::drop_position_in(cx, abs, panel.id, tab_bar).map( | pos | match pos.part {
+                    // floating panels don't split, what is dropped on them joins their tabs
+                    DropPart::Left | DropPart::Right | DropPart::Top | DropPart::Bottom => DropPosition {
+                        part: DropPart::Center,
+                        id: pos.id,
+                        rect: tab_bar.contents_rect
+                    },
+                    _ => pos
+                })
+            }
+        }
         for (tab_bar_id, tab_bar) in self.tab_bars.iter() {
-            let rect = tab_bar.contents_rect;
-            if let Some((tab_id, rect)) = tab_bar.tab_bar.is_over_tab(cx, abs) {
+            if !self.is_floating(*tab_bar_id) {
+                if let Some(pos) = Self::drop_position_in(cx, abs, *tab_bar_id, tab_bar) {
+                    return Some(pos)
+                }
+            }
+        }
+        None
+    }
+    
+    fn drop_position_in(cx: &Cx, abs: DVec2, tab_bar_id: LiveId, tab_bar: &TabBarWrap) -> Option<DropPosition> {
+        let rect = tab_bar.contents_rect;
+        if let Some((tab_id, rect)) = tab_bar.tab_bar.is_over_tab(cx, abs) {
+            return Some(DropPosition {
+                part: DropPart::Tab,
+                id: tab_id,
+                rect
+            })
+        }
+        else if let Some(rect) = tab_bar.tab_bar.is_over_tab_bar(cx, abs) {
+            return Some(DropPosition {
+                part: DropPart::TabBar,
+                id: tab_bar_id,
+                rect
+            })
+        }
+        else if rect.contains(abs) {
+            let top_left = rect.pos;
+            let bottom_right = rect.pos + rect.size;
+            if (abs.x - top_left.x) / rect.size.x < 0.1 {
                 return Some(DropPosition {
-                    part: DropPart::Tab,
-                    id: tab_id,
-                    rect
+                    part: DropPart::Left,
+                    id: tab_bar_id,
+                    rect: Rect {
+                        pos: rect.pos,
+                        size: DVec2 {
+                            x: rect.size.x / 2.0,
+                            y: rect.size.y,
+                        },
+                    }
                 })
-            }
-            else if let Some(rect) = tab_bar.tab_bar.is_over_tab_bar(cx, abs) {
+            } else if (bottom_right.x - abs.x) / rect.size.x < 0.1 {
                 return Some(DropPosition {
-                    part: DropPart::TabBar,
-                    id: *tab_bar_id,
+                    part: DropPart::Right,
+                    id: tab_bar_id,
+                    rect: Rect {
+                        pos: DVec2 {
+                            x: rect.pos.x + rect.size.x / 2.0,
+                            y: rect.pos.y,
+                        },
+                        size: DVec2 {
+                            x: rect.size.x / 2.0,
+                            y: rect.size.y,
+                        },
+                    }
+                })
+            } else if (abs.y - top_left.y) / rect.size.y < 0.1 {
+                return Some(DropPosition {
+                    part: DropPart::Top,
+                    id: tab_bar_id,
+                    rect: Rect {
+                        pos: rect.pos,
+                        size: DVec2 {
+                            x: rect.size.x,
+                            y: rect.size.y / 2.0,
+                        },
+                    }
+                })
+            } else if (bottom_right.y - abs.y) / rect.size.y < 0.1 {
+                return Some(DropPosition {
+                    part: DropPart::Bottom,
+                    id: tab_bar_id,
+                    rect: Rect {
+                        pos: DVec2 {
+                            x: rect.pos.x,
+                            y: rect.pos.y + rect.size.y / 2.0,
+                        },
+                        size: DVec2 {
+                            x: rect.size.x,
+                            y: rect.size.y / 2.0,
+                        },
+                    }
+                })
+            } else {
+                return Some(DropPosition {
+                    part: DropPart::Center,
+                    id: tab_bar_id,
                     rect
                 })
             }
-            else if rect.contains(abs) {
-                let top_left = rect.pos;
-                let bottom_right = rect.pos + rect.size;
-                if (abs.x - top_left.x) / rect.size.x < 0.1 {
-                    return Some(DropPosition {
-                        part: DropPart::Left,
-                        id: *tab_bar_id,
-                        rect: Rect {
-                            pos: rect.pos,
-                            size: DVec2 {
-                                x: rect.size.x / 2.0,
-                                y: rect.size.y,
-                            },
-                        }
-                    })
-                } else if (bottom_right.x - abs.x) / rect.size.x < 0.1 {
-                    return Some(DropPosition {
-                        part: DropPart::Right,
-                        id: *tab_bar_id,
-                        rect: Rect {
-                            pos: DVec2 {
-                                x: rect.pos.x + rect.size.x / 2.0,
-                                y: rect.pos.y,
-                            },
-                            size: DVec2 {
-                                x: rect.size.x / 2.0,
-                                y: rect.size.y,
-                            },
-                        }
-                    })
-                } else if (abs.y - top_left.y) / rect.size.y < 0.1 {
-                    return Some(DropPosition {
-                        part: DropPart::Top,
-                        id: *tab_bar_id,
-                        rect: Rect {
-                            pos: rect.pos,
-                            size: DVec2 {
-                                x: rect.size.x,
-                                y: rect.size.y / 2.0,
-                            },
-                        }
-                    })
-                } else if (bottom_right.y - abs.y) / rect.size.y < 0.1 {
-                    return Some(DropPosition {
-                        part: DropPart::Bottom,
-                        id: *tab_bar_id,
-                        rect: Rect {
-                            pos: DVec2 {
-                                x: rect.pos.x,
-                                y: rect.pos.y + rect.size.y / 2.0,
-                            },
-                            size: DVec2 {
-                                x: rect.size.x,
-                                y: rect.size.y / 2.0,
-                            },
-                        }
-                    })
-                } else {
-                    return Some(DropPosition {
-                        part: DropPart::Center,
-                        id: *tab_bar_id,
-                        rect
-                    })
-                }
-            }
         }
         None
     }
     
     pub fn to_store_items(&self)->Vec<DockItemStore>{
+        // floating panels are left out, the tabs in them are as good as closed on a restart
+        let floating = self.floating_items();
         let mut out = Vec::new();
         for (id, dock_item) in &self.dock_items{
+            if floating.contains(id){
+                continue
+            }
             match dock_item{
                 DockItem::Splitter {
                     axis,
@@ -507,8 +639,176 @@ impl Dock {
         out
     }
     
-    pub fn from_store_item(&mut self, _store:&[DockItemStore]){
+    /// Replaces the panels and tabs with ones saved by `to_store_items`, like the layout from
+    /// before a restart. Tabs left out of the store are dropped from their tab bars. Returns
+    /// false, and changes nothing, if the store has no root or splits into panels it lacks.
+    pub fn load_store_items(&mut self, cx: &mut Cx, store:&[DockItemStore])->bool{
+        let mut dock_items = HashMap::new();
+        for item in store{
+            match item{
+                DockItemStore::Splitter{id, axis, align, a, b}=>{
+                    dock_items.insert(id.0, DockItem::Splitter{axis:*axis, align:*align, a:a.0, b:b.0});
+                }
+                DockItemStore::Tabs{id, tabs, selected, closable}=>{
+                    dock_items.insert(id.0, DockItem::Tabs{
+                        tabs: tabs.iter().map(|v| v.0).collect(),
+                        selected: *selected,
+                        closable: *closable
+                    });
+                }
+                DockItemStore::Tab{id, name, template, kind}=>{
+                    dock_items.insert(id.0, DockItem::Tab{name: name.clone(), template: template.0, kind: kind.0});
+                }
+            }
+        }
+        let is_complete = dock_items.contains_key(&live_id!(root)) && dock_items.values().all(|item| match item{
+            DockItem::Splitter{a, b, ..}=>dock_items.contains_key(a) && dock_items.contains_key(b),
+            _=>true
+        });
+        if !is_complete{
+            return false
+        }
+        let tab_ids: HashSet<LiveId> = dock_items.iter()
+            .filter(|(_, item)| matches!(item, DockItem::Tab{..}))
+            .map(|(id, _)| *id)
+            .collect();
+        for item in dock_items.values_mut(){
+            if let DockItem::Tabs{tabs, selected, ..} = item{
+                let selected_id = tabs.get(*selected).copied();
+                tabs.retain(|id| tab_ids.contains(id));
+                *selected = selected_id.and_then(|id| tabs.iter().position(|v| *v == id)).unwrap_or(0);
+            }
+        }
+        self.dock_items = dock_items;
+        for mut panel in self.floating.drain(..) {
+            if let Some(window) = &mut panel.window {
+                window.window.close(cx);
+            }
+        }
+        // the contents of tabs that are gone go back to the pool
+        let stale: Vec<LiveId> = self.items.keys().filter(|id| !tab_ids.contains(id)).copied().collect();
+        for item_id in stale{
+            self.recycle_item(cx, item_id);
+        }
+        self.recent_tabs.retain(|id| tab_ids.contains(id));
+        self.tab_cycle = None;
+        self.drop_state = None;
+        self.needs_save = false;
+        for tab_bar in self.tab_bars.values_mut(){
+            tab_bar.contents_draw_list.redraw(cx);
+        }
+        self.area.redraw(cx);
+        true
+    }
+    
+    /// The docked panels as a `DockLayout`, without the floating ones.
+    pub fn layout(&self) -> DockLayout {
+        self.layout_of(live_id!(root))
+    }
+    
+    fn layout_of(&self, id: LiveId) -> DockLayout {
+        match self.dock_items.get(&id) {
+            Some(DockItem::Splitter {axis, align, a, b}) => DockLayout::Split {
+                axis: *axis,
+                ratio: match align {
+                    SplitterAlign::Weighted(weight) => *weight,
+                    _ => self.splitters.get(&id).map_or(0.5, | splitter | splitter.weight())
+                },
+                children: vec![self.layout_of(*a), self.layout_of(*b)]
+            },
+            Some(DockItem::Tabs {tabs, selected, ..}) => DockLayout::Tabs {
+                tab_ids: tabs.clone(),
+                selected: *selected
+            },
+            _ => DockLayout::Tabs {tab_ids: Vec::new(), selected: 0}
+        }
+    }
+    
+    /// Arranges the docked panels like `layout`. Tabs in it the dock doesn't have are left out,
+    /// and docked tabs it leaves out are closed. Panels that keep their first tab keep their id,
+    /// so their tab bars don't start over.
+    pub fn apply_layout(&mut self, cx: &mut Cx, layout: &DockLayout) {
+        let floating = self.floating_items();
+        let mut old_panels = HashMap::new();
+        for (id, item) in &self.dock_items {
+            if let DockItem::Tabs {tabs, closable, ..} = item {
+                if *id != live_id!(root) && !floating.contains(id) {
+                    for tab_id in tabs {
+                        old_panels.insert(*tab_id, (*id, *closable));
+                    }
+                }
+            }
+        }
+        self.dock_items.retain( | id, item | floating.contains(id) || matches!(item, DockItem::Tab {..}));
+        let mut placed = HashSet::new();
+        self.insert_layout(layout, Some(live_id!(root)), &mut old_panels, &mut placed);
         
+        let closed: Vec<LiveId> = self.dock_items.iter()
+            .filter( | (id, item) | matches!(item, DockItem::Tab {..}) && !placed.contains(*id) && !floating.contains(*id))
+            .map( | (id, _) | *id)
+            .collect();
+        for tab_id in closed {
+            self.dock_items.remove(&tab_id);
+            self.recycle_item(cx, tab_id);
+            self.recent_tabs.retain( | id | *id != tab_id);
+        }
+        self.tab_cycle = None;
+        self.drop_state = None;
+        self.needs_save = true;
+        for tab_bar in self.tab_bars.values_mut() {
+            tab_bar.contents_draw_list.redraw(cx);
+        }
+        self.area.redraw(cx);
+    }
+    
+    /// Adds the panels of `layout` to the dock items, the outermost one as `id`. Returns the id
+    /// it ended up with.
+    fn insert_layout(&mut self, layout: &DockLayout, id: Option<LiveId>, old_panels: &mut HashMap<LiveId, (LiveId, bool)>, placed: &mut HashSet<LiveId>) -> LiveId {
+        match layout {
+            DockLayout::Split {axis, ratio, children} => match children.as_slice() {
+                [] => self.insert_layout(&DockLayout::Tabs {tab_ids: Vec::new(), selected: 0}, id, old_panels, placed),
+                [child] => self.insert_layout(child, id, old_panels, placed),
+                [first, rest @ ..] => {
+                    let id = id.unwrap_or_else(LiveId::unique);
+                    let a = self.insert_layout(first, None, old_panels, placed);
+                    let b = if let [second] = rest {
+                        self.insert_layout(second, None, old_panels, placed)
+                    }
+                    else {
+                        let rest = DockLayout::Split {axis: *axis, ratio: 1.0 / rest.len() as f64, children: rest.to_vec()};
+                        self.insert_layout(&rest, None, old_panels, placed)
+                    };
+                    self.dock_items.insert(id, DockItem::Splitter {
+                        axis: *axis,
+                        align: SplitterAlign::Weighted(*ratio),
+                        a,
+                        b
+                    });
+                    id
+                }
+            },
+            DockLayout::Tabs {tab_ids, selected} => {
+                let dock_items = &self.dock_items;
+                let tabs: Vec<LiveId> = tab_ids.iter().copied()
+                    .filter( | tab_id | matches!(dock_items.get(tab_id), Some(DockItem::Tab {..})) && placed.insert(*tab_id))
+                    .collect();
+                let selected = tab_ids.get(*selected)
+                    .and_then( | selected | tabs.iter().position( | id | id == selected))
+                    .unwrap_or(0);
+                let old_panel = tabs.first().and_then( | tab_id | old_panels.get(tab_id)).copied();
+                let closable = old_panel.map_or(true, | (_, closable) | closable);
+                let id = match (id, old_panel) {
+                    (Some(id), _) => id,
+                    (None, Some((old_id, _))) => {
+                        old_panels.retain( | _, (panel_id, _) | *panel_id != old_id);
+                        old_id
+                    }
+                    (None, None) => LiveId::unique()
+                };
+                self.dock_items.insert(id, DockItem::Tabs {tabs, selected, closable});
+                id
+            }
+        }
     }
     
     pub fn item(&mut self, entry_id: LiveId) -> Option<WidgetRef> {
@@ -520,8 +820,9 @@ impl Dock {
     
     pub fn item_or_create(&mut self, cx: &mut Cx, entry_id: LiveId, template: LiveId) -> Option<WidgetRef> {
         if let Some(ptr) = self.templates.get(&template) {
+            let pool = &mut self.pool;
             let entry = self.items.get_or_insert(cx, entry_id, | cx | {
-                (template, WidgetRef::new_from_ptr(cx, Some(*ptr)))
+                (template, pool.take_or_new(cx, template, Some(*ptr)))
             });
             Some(entry.1.clone())
         }
@@ -531,12 +832,20 @@ impl Dock {
         }
     }
     
+    /// Puts the content of a closed tab in the pool for the next tab of its kind.
+    fn recycle_item(&mut self, cx: &mut Cx, item_id: LiveId) {
+        if let Some((kind, item)) = self.items.remove(&item_id) {
+            self.pool.put(cx, kind, item);
+        }
+    }
+    
     pub fn items(&mut self) -> &ComponentMap<LiveId, (LiveId, WidgetRef)> {
         &self.items
     }
     
     pub fn visible_items(&mut self) -> DockVisibleItemIterator {
         self.dock_item_iter_stack.clear();
+        self.dock_item_iter_stack.extend(self.floating.iter().map( | panel | (panel.id, 0)));
         self.dock_item_iter_stack.push((live_id!(root), 0));
         DockVisibleItemIterator {
             stack: &mut self.dock_item_iter_stack,
@@ -576,6 +885,14 @@ impl Dock {
     
     fn unsplit_tabs(&mut self, cx: &mut Cx, tabs_id: LiveId) {
         self.needs_save = true;
+        if let Some(index) = self.floating.iter().position( | panel | panel.id == tabs_id) {
+            if let Some(mut window) = self.floating.remove(index).window {
+                window.window.close(cx);
+            }
+            self.dock_items.remove(&tabs_id);
+            self.area.redraw(cx);
+            return
+        }
         for (splitter_id, item) in self.dock_items.iter_mut() {
             match *item {
                 DockItem::Splitter {a, b, ..} => {
@@ -602,6 +919,7 @@ impl Dock {
     
     fn select_tab(&mut self, cx: &mut Cx, tab_id: LiveId) {
         self.needs_save = true;        
+        self.touch_tab(cx, tab_id);
         for (tabs_id, item) in self.dock_items.iter_mut() {
             match item {
                 DockItem::Tabs {tabs, selected, ..} => if let Some(pos) = tabs.iter().position( | v | *v == tab_id) {
@@ -616,6 +934,83 @@ impl Dock {
         }
     }
     
+    fn touch_tab(&mut self, cx: &mut Cx, tab_id: LiveId) {
+        // a Ctrl+Tab cycle walks the order as it was when it started
+        if self.tab_cycle.is_some() || self.recent_tabs.first() == Some(&tab_id) {
+            return
+        }
+        self.recent_tabs.retain( | id | *id != tab_id);
+        self.recent_tabs.insert(0, tab_id);
+        if self.tab_order == TabOrder::MostRecentlyUsed {
+            self.area.redraw(cx);
+        }
+    }
+    
+    /// All tabs, the most recently selected first, followed by the ones that never were selected
+    /// in the order of the dock.
+    fn tabs_by_recent_use(&self) -> Vec<LiveId> {
+        let mut all_tabs = Vec::new();
+        let mut stack: Vec<LiveId> = self.floating.iter().rev().map( | panel | panel.id).collect();
+        stack.push(live_id!(root));
+        while let Some(id) = stack.pop() {
+            match self.dock_items.get(&id) {
+                Some(DockItem::Splitter {a, b, ..}) => {
+                    stack.push(*b);
+                    stack.push(*a);
+                }
+                Some(DockItem::Tabs {tabs, ..}) => all_tabs.extend(tabs.iter().copied()),
+                _ => ()
+            }
+        }
+        let mut tabs: Vec<LiveId> = self.recent_tabs.iter().copied().filter( | id | all_tabs.contains(id)).collect();
+        for id in all_tabs {
+            if !tabs.contains(&id) {
+                tabs.push(id);
+            }
+        }
+        tabs
+    }
+    
+    /// Selects the next tab in most recently used order, or the previous one. Repeated calls
+    /// walk further through the tabs until `end_tab_cycle`, like holding Ctrl and pressing Tab.
+    fn cycle_recent_tabs(&mut self, cx: &mut Cx, backward: bool) {
+        let tabs = self.tabs_by_recent_use();
+        if tabs.is_empty() {
+            return
+        }
+        let index = self.tab_cycle.unwrap_or(0);
+        let index = if backward {(index + tabs.len() - 1) % tabs.len()} else {(index + 1) % tabs.len()};
+        self.tab_cycle = Some(index);
+        self.select_tab(cx, tabs[index]);
+        self.area.redraw(cx);
+    }
+    
+    /// Ends a Ctrl+Tab cycle, which makes the tab it ended on the most recently used one.
+    fn end_tab_cycle(&mut self, cx: &mut Cx) -> Option<LiveId> {
+        let index = self.tab_cycle ?;
+        let tab_id = *self.tabs_by_recent_use().get(index) ?;
+        self.tab_cycle = None;
+        self.touch_tab(cx, tab_id);
+        Some(tab_id)
+    }
+    
+    /// The tabs of a tab bar in the order they are shown, without the ones `filter` hides.
+    fn visible_tabs(dock_items: &HashMap<LiveId, DockItem>, order: TabOrder, recent_tabs: &[LiveId], tabs: &[LiveId], filter: &str) -> Vec<LiveId> {
+        let name = | id: &LiveId | match dock_items.get(id) {
+            Some(DockItem::Tab {name, ..}) => name.as_str(),
+            _ => ""
+        };
+        let mut visible: Vec<LiveId> = tabs.iter().copied().filter( | id | fuzzy_match(filter, name(id))).collect();
+        match order {
+            TabOrder::Manual => (),
+            TabOrder::Alphabetical => visible.sort_by_cached_key( | id | name(id).to_lowercase()),
+            TabOrder::MostRecentlyUsed => visible.sort_by_key( | id | {
+                recent_tabs.iter().position( | recent | recent == id).unwrap_or(usize::MAX)
+            }),
+        }
+        visible
+    }
+    
     fn set_tab_title(&mut self, cx: &mut Cx, tab_id: LiveId, new_name:String) {
         self.needs_save = true;
         if let Some(DockItem::Tab{name, ..}) = self.dock_items.get_mut(&tab_id){
@@ -659,13 +1054,16 @@ impl Dock {
                     // remove from the tabs array
                     let tabs_id = *tabs_id;
                     tabs.remove(pos);
+                    if !keep_item {
+                        self.recent_tabs.retain( | id | *id != tab_id);
+                    }
                     if tabs.len() == 0 { // unsplit
                         if *closable {
                             self.unsplit_tabs(cx, tabs_id);
                         }
                         if !keep_item {
                             self.dock_items.remove(&tab_id);
-                            self.items.remove(&tab_id);
+                            self.recycle_item(cx, tab_id);
                         }
                         self.area.redraw(cx);
                         return None
@@ -675,7 +1073,7 @@ impl Dock {
                         self.select_tab(cx, next_tab);
                         if !keep_item {
                             self.dock_items.remove(&tab_id);
-                            self.items.remove(&tab_id);
+                            self.recycle_item(cx, tab_id);
                         }
                         self.area.redraw(cx);
                         return Some(tabs_id)
@@ -704,112 +1102,397 @@ impl Dock {
     }
     
     fn handle_drop(&mut self, cx: &mut Cx, abs: DVec2, item: LiveId, is_move: bool) -> bool {
-        if let Some(pos) = self.find_drop_position(cx, abs) {
-            self.needs_save = true;                    
-            // ok now what
-            // we have a pos
-            match pos.part {
-                DropPart::Left | DropPart::Right | DropPart::Top | DropPart::Bottom => {
-                    if is_move {
-                        if self.check_drop_is_noop(item, pos.id) {
-                            return false
-                        }
-                        self.close_tab(cx, item, true);
+        if let Some(pos) = self.find_drop_position(cx, abs, None) {
+            return self.place_tab(cx, pos.part, pos.id, item, is_move)
+        }
+        false
+    }
+    
+    /// Puts the tab `item` at `part` of `target`, which is a tab for `DropPart::Tab` and a
+    /// panel otherwise. With `is_move` the tab is taken out of the panel it was in first.
+    fn place_tab(&mut self, cx: &mut Cx, part: DropPart, target: LiveId, item: LiveId, is_move: bool) -> bool {
+        self.needs_save = true;
+        match part {
+            DropPart::Left | DropPart::Right | DropPart::Top | DropPart::Bottom => {
+                if is_move {
+                    if self.check_drop_is_noop(item, target) {
+                        return false
                     }
-                    let new_split = LiveId::unique();
-                    let new_tabs = LiveId::unique();
-                    self.set_parent_split(pos.id, new_split);
-                    self.dock_items.insert(new_split, match pos.part {
-                        DropPart::Left => DockItem::Splitter {
-                            axis: SplitterAxis::Horizontal,
-                            align: SplitterAlign::Weighted(0.5),
-                            a: new_tabs,
-                            b: pos.id,
-                        },
-                        DropPart::Right => DockItem::Splitter {
-                            axis: SplitterAxis::Horizontal,
-                            align: SplitterAlign::Weighted(0.5),
-                            a: pos.id,
-                            b: new_tabs
-                        },
-                        DropPart::Top => DockItem::Splitter {
-                            axis: SplitterAxis::Vertical,
-                            align: SplitterAlign::Weighted(0.5),
-                            a: new_tabs,
-                            b: pos.id,
-                        },
-                        DropPart::Bottom => DockItem::Splitter {
-                            axis: SplitterAxis::Vertical,
-                            align: SplitterAlign::Weighted(0.5),
-                            a: pos.id,
-                            b: new_tabs,
-                        },
-                        _ => panic!()
-                    });
-                    self.dock_items.insert(new_tabs, DockItem::Tabs {
-                        tabs: vec![item],
-                        closable: true,
-                        selected: 0,
-                    });
-                    return true
+                    self.close_tab(cx, item, true);
                 }
-                DropPart::Center => {
-                    if is_move {
-                        if self.check_drop_is_noop(item, pos.id) {
-                            return false
-                        }
-                        self.close_tab(cx, item, true);
+                let new_split = LiveId::unique();
+                let new_tabs = LiveId::unique();
+                self.set_parent_split(target, new_split);
+                self.dock_items.insert(new_split, match part {
+                    DropPart::Left => DockItem::Splitter {
+                        axis: SplitterAxis::Horizontal,
+                        align: SplitterAlign::Weighted(0.5),
+                        a: new_tabs,
+                        b: target,
+                    },
+                    DropPart::Right => DockItem::Splitter {
+                        axis: SplitterAxis::Horizontal,
+                        align: SplitterAlign::Weighted(0.5),
+                        a: target,
+                        b: new_tabs
+                    },
+                    DropPart::Top => DockItem::Splitter {
+                        axis: SplitterAxis::Vertical,
+                        align: SplitterAlign::Weighted(0.5),
+                        a: new_tabs,
+                        b: target,
+                    },
+                    DropPart::Bottom => DockItem::Splitter {
+                        axis: SplitterAxis::Vertical,
+                        align: SplitterAlign::Weighted(0.5),
+                        a: target,
+                        b: new_tabs,
+                    },
+                    _ => panic!()
+                });
+                self.dock_items.insert(new_tabs, DockItem::Tabs {
+                    tabs: vec![item],
+                    closable: true,
+                    selected: 0,
+                });
+                return true
+            }
+            DropPart::Center => {
+                if is_move {
+                    if self.check_drop_is_noop(item, target) {
+                        return false
                     }
-                    if let Some(DockItem::Tabs {tabs, selected, ..}) = self.dock_items.get_mut(&pos.id) {
-                        tabs.push(item);
-                        *selected = tabs.len() - 1;
-                        if let Some(tab_bar) = self.tab_bars.get(&pos.id) {
-                            tab_bar.contents_draw_list.redraw(cx);
-                        }
+                    self.close_tab(cx, item, true);
+                }
+                if let Some(DockItem::Tabs {tabs, selected, ..}) = self.dock_items.get_mut(&target) {
+                    tabs.push(item);
+                    *selected = tabs.len() - 1;
+                    if let Some(tab_bar) = self.tab_bars.get(&target) {
+                        tab_bar.contents_draw_list.redraw(cx);
                     }
-                    return true
                 }
-                DropPart::TabBar => {
-                    if is_move {
-                        if self.check_drop_is_noop(item, pos.id) {
-                            return false
-                        }
-                        self.close_tab(cx, item, true);
+                return true
+            }
+            DropPart::TabBar => {
+                if is_move {
+                    if self.check_drop_is_noop(item, target) {
+                        return false
+                    }
+                    self.close_tab(cx, item, true);
+                }
+                if let Some(DockItem::Tabs {tabs, selected, ..}) = self.dock_items.get_mut(&target) {
+                    tabs.push(item);
+                    *selected = tabs.len() - 1;
+                    if let Some(tab_bar) = self.tab_bars.get(&target) {
+                        tab_bar.contents_draw_list.redraw(cx);
                     }
-                    if let Some(DockItem::Tabs {tabs, selected, ..}) = self.dock_items.get_mut(&pos.id) {
-                        tabs.push(item);
-                        *selected = tabs.len() - 1;
-                        if let Some(tab_bar) = self.tab_bars.get(&pos.id) {
+                }
+                return true
+            }
+            // insert the ta
+            DropPart::Tab => {
+                if is_move {
+                    if target == item {
+                        return false
+                    }
+                    self.close_tab(cx, item, true);
+                }
+                let (tab_bar_id, pos) = self.find_tab_bar_of_tab(target).unwrap();
+                if let Some(DockItem::Tabs {tabs, selected, ..}) = self.dock_items.get_mut(&tab_bar_id) {
+                    //if let Some(pos) = tabs.iter().position( | v | *v == target) {
+                        let old = tabs[pos];
+                        tabs[pos] = item;
+                        tabs.push(old);
+                        *selected = pos;
+                        if let Some(tab_bar) = self.tab_bars.get(&tab_bar_id) {
                             tab_bar.contents_draw_list.redraw(cx);
                         }
+                    //}
+                }
+                return true
+            }
+        }
+    }
+    
+    /// Splits the panel `panel_id` at `part`, which has to be one of its edges, and puts the tab
+    /// `tab_id` in the new panel, taking it out of the panel it was in. Returns the new panel.
+    pub fn split_panel(&mut self, cx: &mut Cx, panel_id: LiveId, part: DropPart, tab_id: LiveId) -> Option<LiveId> {
+        if !matches!(part, DropPart::Left | DropPart::Right | DropPart::Top | DropPart::Bottom)
+            || !matches!(self.dock_items.get(&panel_id), Some(DockItem::Tabs {..}))
+            || !matches!(self.dock_items.get(&tab_id), Some(DockItem::Tab {..}))
+            || self.is_floating(panel_id) {
+            return None
+        }
+        let is_move = self.find_tab_bar_of_tab(tab_id).is_some();
+        if !self.place_tab(cx, part, panel_id, tab_id, is_move) {
+            return None
+        }
+        self.select_tab(cx, tab_id);
+        self.area.redraw(cx);
+        self.find_tab_bar_of_tab(tab_id).map( | (tabs_id, _) | tabs_id)
+    }
+    
+    /// Moves the tab `tab_id` to `index` of the panel `target_panel`, or to its end, and selects
+    /// it. The panel it leaves is closed when that was its last tab and it is closable.
+    pub fn move_tab(&mut self, cx: &mut Cx, tab_id: LiveId, target_panel: LiveId, index: Option<usize>) -> bool {
+        if !matches!(self.dock_items.get(&target_panel), Some(DockItem::Tabs {..}))
+            || !matches!(self.dock_items.get(&tab_id), Some(DockItem::Tab {..})) {
+            return false
+        }
+        if let Some((tabs_id, _)) = self.find_tab_bar_of_tab(tab_id) {
+            if tabs_id != target_panel {
+                self.close_tab(cx, tab_id, true);
+            }
+        }
+        if let Some(DockItem::Tabs {tabs, ..}) = self.dock_items.get_mut(&target_panel) {
+            tabs.retain( | id | *id != tab_id);
+            let index = index.unwrap_or(tabs.len()).min(tabs.len());
+            tabs.insert(index, tab_id);
+        }
+        self.needs_save = true;
+        self.select_tab(cx, tab_id);
+        self.area.redraw(cx);
+        true
+    }
+    
+    /// Closes the closable panels without tabs, which tabs moved or removed by hand can leave
+    /// behind. Returns how many were closed.
+    pub fn close_empty_panels(&mut self, cx: &mut Cx) -> usize {
+        let empty: Vec<LiveId> = self.dock_items.iter().filter_map( | (id, item) | match item {
+            DockItem::Tabs {tabs, closable: true, ..} if tabs.is_empty() => Some(*id),
+            _ => None
+        }).collect();
+        let mut closed = 0;
+        for tabs_id in empty {
+            self.unsplit_tabs(cx, tabs_id);
+            if !self.dock_items.contains_key(&tabs_id) {
+                closed += 1;
+            }
+        }
+        if closed > 0 {
+            self.area.redraw(cx);
+        }
+        closed
+    }
+    
+    fn is_floating(&self, panel_id: LiveId) -> bool {
+        self.floating.iter().any( | panel | panel.id == panel_id)
+    }
+    
+    /// The floating panels and their tabs.
+    fn floating_items(&self) -> HashSet<LiveId> {
+        self.floating.iter().flat_map( | panel | match self.dock_items.get(&panel.id) {
+            Some(DockItem::Tabs {tabs, ..}) => tabs.clone(),
+            _ => Vec::new()
+        }.into_iter().chain([panel.id])).collect()
+    }
+    
+    /// Takes the tab `tab_id` out of its panel and shows it in a new panel floating at `rect`,
+    /// above the docked ones. Returns the new panel.
+    pub fn float_tab(&mut self, cx: &mut Cx, tab_id: LiveId, rect: Rect) -> Option<LiveId> {
+        if !matches!(self.dock_items.get(&tab_id), Some(DockItem::Tab {..})) {
+            return None
+        }
+        if self.find_tab_bar_of_tab(tab_id).is_some() {
+            self.close_tab(cx, tab_id, true);
+        }
+        let panel_id = LiveId::unique();
+        self.dock_items.insert(panel_id, DockItem::Tabs {
+            tabs: vec![tab_id],
+            selected: 0,
+            closable: true
+        });
+        self.floating.push(FloatingPanel {
+            id: panel_id,
+            rect,
+            draw_list: DrawList2d::new(cx),
+            close_button: TabCloseButton::new_from_ptr(cx, self.floating_close_button),
+            title_area: Area::Empty,
+            bg_area: Area::Empty,
+            drag_start_pos: None,
+            window: None,
+        });
+        self.needs_save = true;
+        self.select_tab(cx, tab_id);
+        self.area.redraw(cx);
+        Some(panel_id)
+    }
+    
+    /// Moves the floating panel `panel_id` out of the dock's window into a native window of its
+    /// own, at the same place on the screen. Closing that window floats the panel in the dock's
+    /// window again. Returns false if there is no such panel, or the platform has one window only.
+    pub fn open_floating_in_window(&mut self, cx: &mut Cx, panel_id: LiveId) -> bool {
+        if cx.os_type().is_single_window() {
+            return false
+        }
+        let owner = cx.window_id_of(&self.area);
+        let title = Self::panel_title(&self.dock_items, panel_id).to_string();
+        let Some(panel) = self.floating.iter_mut().find( | panel | panel.id == panel_id) else {
+            return false
+        };
+        if panel.window.is_none() {
+            panel.window = Some(FloatingWindow::open(cx, owner, &title, panel.rect));
+            panel.title_area = Area::Empty;
+            panel.bg_area = Area::Empty;
+            panel.drag_start_pos = None;
+            self.drop_state = None;
+            self.drop_target_draw_list.redraw(cx);
+            self.area.redraw(cx);
+        }
+        true
+    }
+    
+    /// The name of the selected tab of a floating panel, which is what its title bar shows.
+    fn panel_title(dock_items: &HashMap<LiveId, DockItem>, panel_id: LiveId) -> &str {
+        match dock_items.get(&panel_id) {
+            Some(DockItem::Tabs {tabs, selected, ..}) => match tabs.get(*selected).and_then( | tab_id | dock_items.get(tab_id)) {
+                Some(DockItem::Tab {name, ..}) => name.as_str(),
+                _ => ""
+            }
+            _ => ""
+        }
+    }
+    
+    /// Moves the tabs of the floating panel `panel_id` to `part` of `target`, the way dropping
+    /// them there one by one would, which leaves the floating panel empty and closes it.
+    fn dock_floating(&mut self, cx: &mut Cx, panel_id: LiveId, part: DropPart, target: LiveId) {
+        let (tabs, selected) = match self.dock_items.get(&panel_id) {
+            Some(DockItem::Tabs {tabs, selected, ..}) => (tabs.clone(), *selected),
+            _ => return
+        };
+        if tabs.is_empty() || !self.place_tab(cx, part, target, tabs[0], true) {
+            return
+        }
+        if let Some((tabs_id, _)) = self.find_tab_bar_of_tab(tabs[0]) {
+            for tab_id in &tabs[1..] {
+                self.move_tab(cx, *tab_id, tabs_id, None);
+            }
+        }
+        if let Some(tab_id) = tabs.get(selected) {
+            self.select_tab(cx, *tab_id);
+        }
+        self.area.redraw(cx);
+    }
+    
+    /// Moves floating panels by their title bars, docks them when they are let go of over a
+    /// docked panel and asks to close their tabs when their close button is pressed. Presses
+    /// on a floating panel don't get through to what is under it. Double clicking the title bar
+    /// tears the panel out into a window of its own.
+    fn handle_floating_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        let uid = self.widget_uid();
+        match event {
+            Event::WindowClosed(wc) => {
+                // a torn out panel whose window was closed floats in the dock's window again
+                for panel in &mut self.floating {
+                    if panel.window.as_ref().map_or(false, | window | window.window.window_id() == wc.window_id) {
+                        panel.window = None;
+                        self.area.redraw(cx);
                     }
-                    return true
                 }
-                // insert the ta
-                DropPart::Tab => {
-                    if is_move {
-                        if pos.id == item {
-                            return false
-                        }
-                        self.close_tab(cx, item, true);
+            }
+            Event::WindowGeomChange(wg) => {
+                for panel in &mut self.floating {
+                    if panel.window.as_ref().map_or(false, | window | window.window.window_id() == wg.window_id) {
+                        // keep the size the window was given for when the panel comes back
+                        panel.rect.size = wg.new_geom.inner_size;
+                        self.area.redraw(cx);
                     }
-                    let (tab_bar_id, pos) = self.find_tab_bar_of_tab(pos.id).unwrap();
-                    if let Some(DockItem::Tabs {tabs, selected, ..}) = self.dock_items.get_mut(&tab_bar_id) {
-                        //if let Some(pos) = tabs.iter().position( | v | *v == pos.id) {
-                            let old = tabs[pos];
-                            tabs[pos] = item;
-                            tabs.push(old);
-                            *selected = pos;
-                            if let Some(tab_bar) = self.tab_bars.get(&tab_bar_id) {
-                                tab_bar.contents_draw_list.redraw(cx);
-                            }
-                        //}
+                }
+            }
+            _ => ()
+        }
+        for index in (0..self.floating.len()).rev() {
+            let panel = &mut self.floating[index];
+            let panel_id = panel.id;
+            if let TabCloseButtonAction::WasPressed = panel.close_button.handle_event(cx, event) {
+                if let Some(DockItem::Tabs {tabs, ..}) = self.dock_items.get(&panel_id) {
+                    for tab_id in tabs {
+                        cx.widget_action(uid, &scope.path, DockAction::TabCloseWasPressed(*tab_id));
+                    }
+                }
+            }
+            match event.hits(cx, panel.title_area) {
+                Hit::FingerDown(fe) if fe.tap_count == 2 => {
+                    self.open_floating_in_window(cx, panel_id);
+                    return
+                }
+                Hit::FingerDown(_) => {
+                    panel.drag_start_pos = Some(panel.rect.pos);
+                    // the panel that is grabbed goes on top
+                    let panel = self.floating.remove(index);
+                    self.floating.push(panel);
+                    self.area.redraw(cx);
+                    return
+                }
+                Hit::FingerMove(fe) => if let Some(start_pos) = panel.drag_start_pos {
+                    panel.rect.pos = start_pos + fe.abs - fe.abs_start;
+                    if let Some(tab_bar) = self.tab_bars.get(&panel_id) {
+                        tab_bar.contents_draw_list.redraw(cx);
                     }
-                    return true
+                    self.drop_state = self.find_drop_position(cx, fe.abs, Some(panel_id));
+                    self.drop_target_draw_list.redraw(cx);
+                    self.area.redraw(cx);
                 }
+                Hit::FingerUp(_) => {
+                    panel.drag_start_pos = None;
+                    if let Some(pos) = self.drop_state.take() {
+                        self.dock_floating(cx, panel_id, pos.part, pos.id);
+                        self.drop_target_draw_list.redraw(cx);
+                        return
+                    }
+                }
+                _ => ()
+            }
+            event.hits(cx, self.floating[index].bg_area);
+        }
+    }
+    
+    fn handle_tab_bar_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope, panel_id: LiveId) {
+        let uid = self.widget_uid();
+        let tab_bar = match self.tab_bars.get_mut(&panel_id) {
+            Some(tab_bar) => tab_bar,
+            None => return
+        };
+        let mut pressed_tab = None;
+        let mut moved_tab = None;
+        for action in cx.capture_actions(|cx| tab_bar.tab_bar.handle_event(cx, event, scope)) {
+            match action.as_widget_action().cast() {
+                TabBarAction::ShouldTabStartDrag(item) => cx.widget_action(uid, &scope.path, DockAction::ShouldTabStartDrag(item)),
+                TabBarAction::TabWasPressed(tab_id) => {
+                    self.needs_save = true;
+                    if let Some(DockItem::Tabs {tabs, selected, ..}) = self.dock_items.get_mut(&panel_id) {
+                        if let Some(sel) = tabs.iter().position( | v | *v == tab_id) {
+                            *selected = sel;
+                            pressed_tab = Some(tab_id);
+                            tab_bar.contents_draw_list.redraw(cx);
+                            cx.widget_action(uid, &scope.path, DockAction::TabWasPressed(tab_id))
+                        }
+                        else {
+                            log!("Cannot find tab {}", tab_id.0);
+                        }
+                    }
+                }
+                TabBarAction::TabCloseWasPressed(tab_id) => {
+                    cx.widget_action(uid, &scope.path, DockAction::TabCloseWasPressed(tab_id))
+                }
+                TabBarAction::TabWasMoved {tab_id, target_id} => {
+                    moved_tab = Some((tab_id, target_id));
+                }
+                TabBarAction::FilterChanged(_) => {
+                    self.area.redraw(cx);
+                }
+                TabBarAction::None=>()
+            }
+        };
+        if let Some(tab_id) = pressed_tab {
+            self.touch_tab(cx, tab_id);
+        }
+        // only the manual order is the one tabs are dragged around in
+        if let (Some((tab_id, target_id)), TabOrder::Manual) = (moved_tab, self.tab_order) {
+            if let Some((tabs_id, index)) = self.find_tab_bar_of_tab(target_id) {
+                self.move_tab(cx, tab_id, tabs_id, Some(index));
             }
         }
-        false
     }
     
     fn drop_create(&mut self, cx: &mut Cx, abs: DVec2, item: LiveId, kind: LiveId, name: String, template:LiveId) {
@@ -898,6 +1581,22 @@ impl Widget for Dock {
     fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope:&mut Scope) {
         // call handle on all tab bars, splitters,
         let uid = self.widget_uid();
+        // floating panels are on top, so they get the events first and use up what hits them
+        let floating_ids: Vec<LiveId> = self.floating.iter().rev().map( | panel | panel.id).collect();
+        let floating_tabs: Vec<LiveId> = floating_ids.iter().flat_map( | id | match self.dock_items.get(id) {
+            Some(DockItem::Tabs {tabs, ..}) => tabs.clone(),
+            _ => Vec::new()
+        }).collect();
+        for panel_id in &floating_ids {
+            self.handle_tab_bar_event(cx, event, scope, *panel_id);
+        }
+        for tab_id in &floating_tabs {
+            if let Some((_, item)) = self.items.get_mut(tab_id) {
+                scope.with_id(*tab_id, | scope | item.handle_event(cx, event, scope));
+            }
+        }
+        self.handle_floating_event(cx, event, scope);
+        
         let dock_items = &mut self.dock_items;
         for (panel_id, splitter) in self.splitters.iter_mut() {
             for action in cx.capture_actions(|cx| splitter.handle_event(cx, event, scope)) {
@@ -916,32 +1615,14 @@ impl Widget for Dock {
                 }
             };
         }
-        for (panel_id, tab_bar) in self.tab_bars.iter_mut() {
-            let contents_view = &mut tab_bar.contents_draw_list;
-            for action in cx.capture_actions(|cx| tab_bar.tab_bar.handle_event(cx, event, scope)) {
-                match action.as_widget_action().cast() {
-                    TabBarAction::ShouldTabStartDrag(item) => cx.widget_action(uid, &scope.path, DockAction::ShouldTabStartDrag(item)),
-                    TabBarAction::TabWasPressed(tab_id) => {
-                        self.needs_save = true;
-                        if let Some(DockItem::Tabs {tabs, selected, ..}) = dock_items.get_mut(&panel_id) {
-                            if let Some(sel) = tabs.iter().position( | v | *v == tab_id) {
-                                *selected = sel;
-                                contents_view.redraw(cx);
-                                cx.widget_action(uid, &scope.path, DockAction::TabWasPressed(tab_id))
-                            }
-                            else {
-                                log!("Cannot find tab {}", tab_id.0);
-                            }
-                        }
-                    }
-                    TabBarAction::TabCloseWasPressed(tab_id) => {
-                        cx.widget_action(uid, &scope.path, DockAction::TabCloseWasPressed(tab_id))
-                    }
-                    TabBarAction::None=>()
-                }
-            };
+        let panel_ids: Vec<LiveId> = self.tab_bars.keys().copied().filter( | id | !floating_ids.contains(id)).collect();
+        for panel_id in panel_ids {
+            self.handle_tab_bar_event(cx, event, scope, panel_id);
         }
         for (id,(_templ_id, item)) in self.items.iter_mut() {
+            if floating_tabs.contains(id) {
+                continue
+            }
             scope.with_id(*id, |scope|{
                item.handle_event(cx, event, scope);
             });
@@ -977,7 +1658,8 @@ impl Widget for Dock {
     fn draw_walk(&mut self, cx: &mut Cx2d, scope:&mut Scope, walk: Walk) -> DrawStep {
         if self.draw_state.begin_with(cx, &self.dock_items, | _, dock_items | {
             let id = live_id!(root);
-            vec![DrawStackItem::from_dock_item(id, dock_items.get(&id))]
+            // the floating panels are drawn after the docked ones
+            vec![DrawStackItem::Floating {index: 0}, DrawStackItem::from_dock_item(id, dock_items.get(&id))]
         }) {
             self.begin(cx, walk);
         }
@@ -1017,7 +1699,7 @@ impl Widget for Dock {
                     splitter.end(cx);
                 }
                 Some(DrawStackItem::Tabs {id}) => {
-                    if let Some(DockItem::Tabs {selected, ..}) = self.dock_items.get(&id) {
+                    if let Some(DockItem::Tabs {tabs, selected, ..}) = self.dock_items.get(&id) {
                         // lets draw the tabs
                         let tab_bar = self.tab_bar;
                         let tab_bar = self.tab_bars.get_or_insert(cx, id, | cx | {
@@ -1025,11 +1707,19 @@ impl Widget for Dock {
                                 tab_bar: TabBar::new_from_ptr(cx, tab_bar),
                                 contents_draw_list: DrawList2d::new(cx),
                                 contents_rect: Rect::default(),
+                                visible_tabs: Vec::new(),
                                 //full_rect: Rect::default(),
                             }
                         });
+                        let visible_tabs = Self::visible_tabs(&self.dock_items, self.tab_order, &self.recent_tabs, tabs, tab_bar.tab_bar.filter());
+                        // a selected tab the filter hides leaves none of the drawn ones selected
+                        let selected_visible = tabs.get(*selected)
+                            .and_then( | selected | visible_tabs.iter().position( | id | id == selected))
+                            .unwrap_or(usize::MAX);
+                        tab_bar.visible_tabs = visible_tabs;
+                        tab_bar.tab_bar.set_tab_count(tabs.len());
                         let walk = tab_bar.tab_bar.walk(cx);
-                        tab_bar.tab_bar.begin(cx, Some(*selected), walk);
+                        tab_bar.tab_bar.begin(cx, Some(selected_visible), walk);
                         stack.push(DrawStackItem::TabLabel {id, index: 0});
                     }
                     else {panic!()}
@@ -1037,9 +1727,9 @@ impl Widget for Dock {
                 Some(DrawStackItem::TabLabel {id, index}) => {
                     if let Some(DockItem::Tabs {tabs, selected, ..}) = self.dock_items.get(&id) {
                         let tab_bar = self.tab_bars.get_mut(&id).unwrap();
-                        if index < tabs.len() {
-                            if let Some(DockItem::Tab {name, template, ..}) = self.dock_items.get(&tabs[index]) {
-                                tab_bar.tab_bar.draw_tab(cx, tabs[index].into(), name, *template);
+                        if let Some(tab_id) = tab_bar.visible_tabs.get(index).copied() {
+                            if let Some(DockItem::Tab {name, template, ..}) = self.dock_items.get(&tab_id) {
+                                tab_bar.tab_bar.draw_tab(cx, tab_id, name, *template);
                             }
                             stack.push(DrawStackItem::TabLabel {id, index: index + 1});
                         }
@@ -1060,8 +1750,9 @@ impl Widget for Dock {
                     stack.push(DrawStackItem::Tab {id});
                     if let Some(DockItem::Tab {kind, ..}) = self.dock_items.get(&id) {
                         if let Some(ptr) = self.templates.get(&kind) {
+                            let pool = &mut self.pool;
                             let (_, entry) = self.items.get_or_insert(cx, id, | cx | {
-                                (*kind, WidgetRef::new_from_ptr(cx, Some(*ptr)))
+                                (*kind, pool.take_or_new(cx, *kind, Some(*ptr)))
                             });
                             scope.with_id(id, |scope|{
                                 entry.draw(cx, scope)
@@ -1079,6 +1770,60 @@ impl Widget for Dock {
                     }
                     else {panic!()}
                 }
+                Some(DrawStackItem::Floating {index}) => {
+                    if let Some(FloatingPanel {id, window: Some(window), ..}) = self.floating.get_mut(index) {
+                        // a torn out panel fills its window, the title bar is the window's
+                        let id = *id;
+                        cx.begin_pass(&window.pass, None);
+                        window.draw_list.begin_always(cx);
+                        cx.begin_pass_sized_turtle(Layout::flow_down());
+                        window.overlay.begin(cx);
+                        let rect = cx.turtle().rect();
+                        self.floating_bg.draw_abs(cx, rect);
+                        cx.begin_turtle(Walk::abs_rect(rect), Layout::flow_down());
+                        stack.push(DrawStackItem::FloatingEnd {index});
+                        stack.push(DrawStackItem::from_dock_item(id, self.dock_items.get(&id)));
+                    }
+                    else if let Some(panel) = self.floating.get_mut(index) {
+                        let id = panel.id;
+                        let rect = panel.rect;
+                        panel.draw_list.begin_overlay_last(cx);
+                        cx.begin_pass_sized_turtle(Layout::flow_down());
+                        self.floating_bg.draw_abs(cx, rect);
+                        panel.bg_area = self.floating_bg.area();
+                        
+                        let title_rect = Rect {pos: rect.pos, size: dvec2(rect.size.x, self.floating_title_height)};
+                        self.floating_title_bar.begin(cx, Walk::abs_rect(title_rect), Layout::flow_right().with_align_y(0.5).with_padding_left(8.0));
+                        let title = Self::panel_title(&self.dock_items, id);
+                        self.floating_title.draw_walk(cx, Walk::size(Size::Fill, Size::Fit), Align::default(), title);
+                        panel.close_button.draw(cx);
+                        self.floating_title_bar.end(cx);
+                        panel.title_area = self.floating_title_bar.area();
+                        
+                        let contents_rect = Rect {
+                            pos: rect.pos + dvec2(0.0, self.floating_title_height),
+                            size: dvec2(rect.size.x, (rect.size.y - self.floating_title_height).max(0.0))
+                        };
+                        cx.begin_turtle(Walk::abs_rect(contents_rect), Layout::flow_down());
+                        stack.push(DrawStackItem::FloatingEnd {index});
+                        stack.push(DrawStackItem::from_dock_item(id, self.dock_items.get(&id)));
+                    }
+                }
+                Some(DrawStackItem::FloatingEnd {index}) => {
+                    cx.end_turtle();
+                    let panel = &mut self.floating[index];
+                    if let Some(window) = &mut panel.window {
+                        window.overlay.end(cx);
+                        cx.end_pass_sized_turtle();
+                        window.draw_list.end(cx);
+                        cx.end_pass(&window.pass);
+                    }
+                    else {
+                        cx.end_pass_sized_turtle();
+                        panel.draw_list.end(cx);
+                    }
+                    stack.push(DrawStackItem::Floating {index: index + 1});
+                }
                 Some(DrawStackItem::Invalid) => {}
                 None => {
                     break
@@ -1091,6 +1836,34 @@ impl Widget for Dock {
         
         DrawStep::done()
     }
+    
+    fn reading_text(&self, scope: &mut Scope, out: &mut String) {
+        // panels in the order they are laid out, each with its tab names and the selected tab,
+        // followed by the floating ones
+        let mut stack: Vec<LiveId> = self.floating.iter().rev().map( | panel | panel.id).collect();
+        stack.push(live_id!(root));
+        while let Some(id) = stack.pop() {
+            match self.dock_items.get(&id) {
+                Some(DockItem::Splitter {a, b, ..}) => {
+                    stack.push(*b);
+                    stack.push(*a);
+                }
+                Some(DockItem::Tabs {tabs, selected, ..}) => {
+                    let names: Vec<&str> = tabs.iter().filter_map( | tab_id | match self.dock_items.get(tab_id) {
+                        Some(DockItem::Tab {name, ..}) => Some(name.as_str()),
+                        _ => None
+                    }).collect();
+                    push_reading_line(out, &names.join(" | "));
+                    if let Some(tab_id) = tabs.get(*selected) {
+                        if let Some((_, item)) = self.items.get(tab_id) {
+                            scope.with_id(*tab_id, | scope | item.reading_text(scope, out));
+                        }
+                    }
+                }
+                _ => ()
+            }
+        }
+    }
 }
 
 impl DockRef {
@@ -1119,7 +1892,7 @@ impl DockRef {
     // user wants to drag, set dh accordingly
     pub fn accept_drag(&self, cx: &mut Cx, dh: DragHitEvent, dr: DragResponse) {
         if let Some(mut dock) = self.borrow_mut() {
-            if let Some(pos) = dock.find_drop_position(cx, dh.abs) {
+            if let Some(pos) = dock.find_drop_position(cx, dh.abs, None) {
                 dh.response.set(dr);
                 dock.drop_state = Some(pos);
             }
@@ -1154,6 +1927,53 @@ impl DockRef {
         }
     }
     
+    pub fn split_panel(&self, cx: &mut Cx, panel_id: LiveId, part: DropPart, tab_id: LiveId) -> Option<LiveId> {
+        if let Some(mut dock) = self.borrow_mut() {
+            return dock.split_panel(cx, panel_id, part, tab_id);
+        }
+        None
+    }
+    
+    pub fn move_tab(&self, cx: &mut Cx, tab_id: LiveId, target_panel: LiveId, index: Option<usize>) -> bool {
+        if let Some(mut dock) = self.borrow_mut() {
+            return dock.move_tab(cx, tab_id, target_panel, index);
+        }
+        false
+    }
+    
+    pub fn close_empty_panels(&self, cx: &mut Cx) -> usize {
+        if let Some(mut dock) = self.borrow_mut() {
+            return dock.close_empty_panels(cx);
+        }
+        0
+    }
+    
+    pub fn float_tab(&self, cx: &mut Cx, tab_id: LiveId, rect: Rect) -> Option<LiveId> {
+        if let Some(mut dock) = self.borrow_mut() {
+            return dock.float_tab(cx, tab_id, rect);
+        }
+        None
+    }
+    
+    pub fn open_floating_in_window(&self, cx: &mut Cx, panel_id: LiveId) -> bool {
+        if let Some(mut dock) = self.borrow_mut() {
+            return dock.open_floating_in_window(cx, panel_id);
+        }
+        false
+    }
+    
+    /// Floats the tab `item` at `abs`, as big as the panel it was in.
+    pub fn drop_float(&self, cx: &mut Cx, abs: DVec2, item: LiveId) {
+        if let Some(mut dock) = self.borrow_mut() {
+            let tabs_id = dock.find_tab_bar_of_tab(item).map( | (tabs_id, _) | tabs_id);
+            let size = match tabs_id.and_then( | tabs_id | dock.tab_bars.get(&tabs_id)) {
+                Some(tab_bar) => tab_bar.contents_rect.size + dvec2(0.0, dock.floating_title_height),
+                None => dvec2(400.0, 300.0)
+            };
+            dock.float_tab(cx, item, Rect {pos: abs, size});
+        }
+    }
+    
     pub fn create_and_select_tab(&self, cx: &mut Cx, parent: LiveId, item: LiveId, kind: LiveId, name: String, template:LiveId, insert_after:Option<usize>)->Option<WidgetRef> {
         if let Some(mut dock) = self.borrow_mut() {
             dock.create_and_select_tab(cx, parent, item, kind, name, template, insert_after)
@@ -1199,6 +2019,42 @@ impl DockRef {
         }
     }
     
+    pub fn tab_title(&self, tab_id: LiveId) -> Option<String> {
+        if let Some(dock) = self.borrow() {
+            if let Some(DockItem::Tab {name, ..}) = dock.dock_items.get(&tab_id) {
+                return Some(name.clone())
+            }
+        }
+        None
+    }
+    
+    pub fn set_tab_order(&self, cx: &mut Cx, tab_order: TabOrder) {
+        if let Some(mut dock) = self.borrow_mut() {
+            dock.tab_order = tab_order;
+            dock.area.redraw(cx);
+        }
+    }
+    
+    pub fn tabs_by_recent_use(&self) -> Vec<LiveId> {
+        if let Some(dock) = self.borrow() {
+            return dock.tabs_by_recent_use();
+        }
+        Vec::new()
+    }
+    
+    pub fn cycle_recent_tabs(&self, cx: &mut Cx, backward: bool) {
+        if let Some(mut dock) = self.borrow_mut() {
+            dock.cycle_recent_tabs(cx, backward);
+        }
+    }
+    
+    pub fn end_tab_cycle(&self, cx: &mut Cx) -> Option<LiveId> {
+        if let Some(mut dock) = self.borrow_mut() {
+            return dock.end_tab_cycle(cx);
+        }
+        None
+    }
+    
     pub fn unique_tab_id(&self, base:u64)->LiveId{
         if let Some(dock) = self.borrow() {
             let mut id = LiveId(base);
@@ -1212,6 +2068,26 @@ impl DockRef {
         LiveId(0)
     }
         
+    pub fn layout(&self) -> Option<DockLayout> {
+        if let Some(dock) = self.borrow() {
+            return Some(dock.layout());
+        }
+        None
+    }
+    
+    pub fn apply_layout(&self, cx: &mut Cx, layout: &DockLayout) {
+        if let Some(mut dock) = self.borrow_mut() {
+            dock.apply_layout(cx, layout);
+        }
+    }
+    
+    pub fn load_store_items(&self, cx: &mut Cx, store:&[DockItemStore])->bool{
+        if let Some(mut dock) = self.borrow_mut() {
+            return dock.load_store_items(cx, store)
+        }
+        false
+    }
+    
     pub fn needs_save(&self)->Option<Vec<DockItemStore>>{
         if let Some(mut dock) = self.borrow_mut() {
             if dock.needs_save{
@@ -0,0 +1,833 @@
+use {
+    std::{
+        rc::Rc,
+        cell::RefCell,
+    },
+    crate::{
+        makepad_derive_widget::*,
+        makepad_draw::*,
+    },
+};
+
+live_design!{
+    EmojiPickerBase = {{EmojiPicker}} {}
+}
+
+/// How many picked symbols the recently used tab remembers.
+const MAX_RECENT: usize = 32;
+
+struct Category {
+    name: &'static str,
+    /// One symbol per line, followed by its name.
+    symbols: &'static str,
+}
+
+// A small embedded index rather than the full unicode emoji list, it only needs to cover
+// what people commonly type.
+const CATEGORIES: &[Category] = &[
+    Category {
+        name: "Smileys",
+        symbols: "
+            😀 grinning face
+            😃 grinning face with big eyes
+            😄 grinning face with smiling eyes
+            😁 beaming face
+            😆 laughing
+            😅 sweat smile
+            🤣 rolling on the floor laughing
+            😂 tears of joy
+            🙂 slightly smiling face
+            🙃 upside down face
+            😉 winking face
+            😊 smiling face with smiling eyes blush
+            😇 halo innocent
+            🥰 smiling face with hearts
+            😍 heart eyes
+            🤩 star struck
+            😘 face blowing a kiss
+            😋 yum delicious
+            😛 tongue
+            😜 winking face with tongue
+            🤪 zany face
+            🤔 thinking face
+            🤨 raised eyebrow
+            😐 neutral face
+            😑 expressionless face
+            😶 face without mouth
+            🙄 rolling eyes
+            😏 smirking face
+            😬 grimacing face
+            😌 relieved face
+            😔 pensive face
+            😪 sleepy face
+            😴 sleeping face
+            😷 face with medical mask
+            🤒 face with thermometer sick
+            🤯 exploding head mind blown
+            🥳 partying face
+            😎 sunglasses cool
+            🤓 nerd face
+            😕 confused face
+            😟 worried face
+            😮 open mouth surprised
+            😲 astonished face
+            😳 flushed face
+            🥺 pleading face
+            😢 crying face
+            😭 loudly crying face sob
+            😱 screaming in fear
+            😤 face with steam triumph
+            😡 pouting face angry
+            😠 angry face
+            🤬 face with symbols on mouth cursing
+            💀 skull
+            💩 pile of poo
+            🤡 clown face
+            👻 ghost
+            👽 alien
+            🤖 robot
+            😺 grinning cat
+            🙈 see no evil monkey
+            🙉 hear no evil monkey
+            🙊 speak no evil monkey
+        ",
+    },
+    Category {
+        name: "People",
+        symbols: "
+            👋 waving hand wave
+            🤚 raised back of hand
+            ✋ raised hand
+            👌 ok hand
+            🤌 pinched fingers
+            ✌ victory hand peace
+            🤞 crossed fingers luck
+            🤟 love you gesture
+            🤘 sign of the horns rock
+            👈 backhand index pointing left
+            👉 backhand index pointing right
+            👆 backhand index pointing up
+            👇 backhand index pointing down
+            👍 thumbs up like yes
+            👎 thumbs down dislike no
+            ✊ raised fist
+            👊 oncoming fist punch
+            👏 clapping hands
+            🙌 raising hands hooray
+            👐 open hands
+            🤝 handshake deal
+            🙏 folded hands please thanks pray
+            💪 flexed biceps strong
+            🧠 brain
+            👀 eyes look
+            👁 eye
+            👶 baby
+            🧒 child
+            👩 woman
+            👨 man
+            🧑 person
+            👵 old woman
+            👴 old man
+            🧑‍💻 technologist coder
+            🕵 detective
+            👷 construction worker
+            🤷 person shrugging
+            🤦 person facepalming
+            🙋 person raising hand
+            🏃 person running
+            💃 woman dancing
+            🕺 man dancing
+        ",
+    },
+    Category {
+        name: "Nature",
+        symbols: "
+            🐶 dog face
+            🐱 cat face
+            🐭 mouse face
+            🐰 rabbit face bunny
+            🦊 fox
+            🐻 bear
+            🐼 panda
+            🐨 koala
+            🐯 tiger face
+            🦁 lion
+            🐮 cow face
+            🐷 pig face
+            🐸 frog
+            🐵 monkey face
+            🐔 chicken
+            🐧 penguin
+            🐦 bird
+            🦆 duck
+            🦉 owl
+            🐝 honeybee bee
+            🦋 butterfly
+            🐌 snail
+            🐞 lady beetle ladybug
+            🐢 turtle
+            🐍 snake
+            🦀 crab rust ferris
+            🐙 octopus
+            🐬 dolphin
+            🐳 spouting whale
+            🦈 shark
+            🌸 cherry blossom flower
+            🌹 rose
+            🌻 sunflower
+            🌷 tulip
+            🌱 seedling sprout
+            🌲 evergreen tree
+            🌳 deciduous tree
+            🌵 cactus
+            🍀 four leaf clover luck
+            🍁 maple leaf
+            🍂 fallen leaf autumn
+            🌍 globe earth europe africa
+            🌙 crescent moon night
+            ⭐ star
+            🌟 glowing star
+            ☀ sun
+            ⛅ sun behind cloud
+            🌧 cloud with rain
+            ⛈ cloud with lightning and rain storm
+            ❄ snowflake cold
+            🌈 rainbow
+            🔥 fire hot lit
+            💧 droplet water
+            🌊 water wave ocean
+        ",
+    },
+    Category {
+        name: "Food",
+        symbols: "
+            🍏 green apple
+            🍎 red apple
+            🍐 pear
+            🍊 tangerine orange
+            🍋 lemon
+            🍌 banana
+            🍉 watermelon
+            🍇 grapes
+            🍓 strawberry
+            🍒 cherries
+            🍑 peach
+            🥭 mango
+            🍍 pineapple
+            🥥 coconut
+            🥝 kiwi fruit
+            🍅 tomato
+            🥑 avocado
+            🥦 broccoli
+            🌽 ear of corn
+            🥕 carrot
+            🥐 croissant
+            🍞 bread
+            🧀 cheese wedge
+            🥚 egg
+            🍳 cooking egg
+            🥓 bacon
+            🍔 hamburger burger
+            🍟 french fries
+            🍕 pizza
+            🌭 hot dog
+            🌮 taco
+            🍝 spaghetti pasta
+            🍜 steaming bowl ramen noodles
+            🍣 sushi
+            🍦 soft ice cream
+            🍩 doughnut donut
+            🍪 cookie
+            🎂 birthday cake
+            🍰 shortcake
+            🍫 chocolate bar
+            🍿 popcorn
+            ☕ hot beverage coffee tea
+            🍵 teacup without handle
+            🍺 beer mug
+            🍻 clinking beer mugs cheers
+            🍷 wine glass
+            🥂 clinking glasses toast
+        ",
+    },
+    Category {
+        name: "Objects",
+        symbols: "
+            ⚽ soccer ball football
+            🏀 basketball
+            🎾 tennis
+            🎮 video game controller
+            🎲 game die dice
+            🎯 direct hit bullseye target
+            🎨 artist palette
+            🎵 musical note
+            🎧 headphone
+            🎉 party popper tada
+            🎁 wrapped gift present
+            🏆 trophy
+            🥇 first place medal
+            🚀 rocket launch ship
+            ✈ airplane
+            🚗 automobile car
+            🚲 bicycle
+            🏠 house home
+            ⌚ watch
+            📱 mobile phone
+            💻 laptop computer
+            ⌨ keyboard
+            🖥 desktop computer
+            🖱 computer mouse
+            💾 floppy disk save
+            📷 camera
+            🔋 battery
+            🔌 electric plug
+            💡 light bulb idea
+            🔦 flashlight
+            📚 books
+            📖 open book
+            📝 memo note
+            ✏ pencil
+            📌 pushpin pin
+            📎 paperclip
+            📁 file folder
+            📅 calendar
+            📈 chart increasing
+            📉 chart decreasing
+            📦 package box
+            ✉ envelope mail
+            🔒 locked
+            🔓 unlocked
+            🔑 key
+            🔨 hammer
+            🔧 wrench
+            ⚙ gear settings
+            🧪 test tube
+            🔬 microscope
+            🔭 telescope
+            🧲 magnet
+            💣 bomb
+            🐛 bug
+            ⏰ alarm clock
+            ⌛ hourglass done
+            ⏳ hourglass not done
+            💰 money bag
+            💎 gem stone
+        ",
+    },
+    Category {
+        name: "Hearts",
+        symbols: "
+            ❤ red heart love
+            🧡 orange heart
+            💛 yellow heart
+            💚 green heart
+            💙 blue heart
+            💜 purple heart
+            🖤 black heart
+            🤍 white heart
+            💔 broken heart
+            💕 two hearts
+            💖 sparkling heart
+            💯 hundred points
+            💢 anger symbol
+            💥 collision boom
+            💫 dizzy
+            💬 speech balloon
+            💭 thought balloon
+            💤 zzz sleep
+            ✨ sparkles
+            ✅ check mark button done
+            ❌ cross mark
+            ❓ question mark
+            ❗ exclamation mark
+            ⚠ warning
+            🚫 prohibited
+            ⛔ no entry
+            ♻ recycling symbol
+            🔴 red circle
+            🟠 orange circle
+            🟡 yellow circle
+            🟢 green circle
+            🔵 blue circle
+            🟣 purple circle
+            ⚫ black circle
+            ⚪ white circle
+            🏁 chequered flag finish
+            🚩 triangular flag
+            🏳 white flag
+        ",
+    },
+    Category {
+        name: "Symbols",
+        symbols: "
+            ← leftwards arrow
+            → rightwards arrow
+            ↑ upwards arrow
+            ↓ downwards arrow
+            ↔ left right arrow
+            ↕ up down arrow
+            ⇐ leftwards double arrow
+            ⇒ rightwards double arrow implies
+            ⇔ left right double arrow iff
+            ↩ leftwards arrow with hook return
+            ↪ rightwards arrow with hook
+            ⏎ return symbol enter
+            ⌘ place of interest command
+            ⌥ option key
+            ⇧ upwards white arrow shift
+            ⌫ erase to the left backspace
+            × multiplication sign times
+            ÷ division sign
+            ± plus minus sign
+            − minus sign
+            ≈ almost equal to
+            ≠ not equal to
+            ≤ less than or equal to
+            ≥ greater than or equal to
+            ∞ infinity
+            √ square root
+            ∑ n ary summation sum
+            ∏ n ary product
+            ∫ integral
+            ∂ partial differential
+            ∆ increment delta
+            ∇ nabla
+            ∈ element of
+            ∉ not an element of
+            ∅ empty set
+            ∩ intersection
+            ∪ union
+            ⊂ subset of
+            ∀ for all
+            ∃ there exists
+            ¬ not sign
+            ∧ logical and
+            ∨ logical or
+            ⊕ circled plus xor
+            ° degree sign
+            ‰ per mille sign
+            α greek small letter alpha
+            β greek small letter beta
+            γ greek small letter gamma
+            δ greek small letter delta
+            ε greek small letter epsilon
+            θ greek small letter theta
+            λ greek small letter lambda
+            μ greek small letter mu micro
+            π greek small letter pi
+            σ greek small letter sigma
+            τ greek small letter tau
+            φ greek small letter phi
+            ω greek small letter omega
+            Ω greek capital letter omega ohm
+            € euro sign
+            £ pound sign
+            ¥ yen sign
+            ₿ bitcoin sign
+            ¢ cent sign
+            © copyright sign
+            ® registered sign
+            ™ trade mark sign
+            § section sign
+            ¶ pilcrow paragraph sign
+            † dagger
+            • bullet
+            … horizontal ellipsis
+            — em dash
+            – en dash
+            « left pointing double angle quotation mark
+            » right pointing double angle quotation mark
+            ✓ check mark
+            ✗ ballot x
+            ★ black star
+            ☆ white star
+            ♠ black spade suit
+            ♥ black heart suit
+            ♦ black diamond suit
+            ♣ black club suit
+            ─ box drawings light horizontal
+            │ box drawings light vertical
+            ┌ box drawings light down and right
+            ┐ box drawings light down and left
+            └ box drawings light up and right
+            ┘ box drawings light up and left
+            ├ box drawings light vertical and right
+            ┤ box drawings light vertical and left
+            ┼ box drawings light vertical and horizontal
+            █ full block
+            ░ light shade
+            ▲ black up pointing triangle
+            ▼ black down pointing triangle
+            ◆ black diamond
+            ● black circle
+            ○ white circle
+            ■ black square
+            □ white square
+        ",
+    },
+];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct EmojiSymbol {
+    pub symbol: &'static str,
+    pub name: &'static str,
+}
+
+impl Category {
+    fn symbols(&self) -> impl Iterator<Item = EmojiSymbol> {
+        self.symbols.lines().filter_map( | line | {
+            let (symbol, name) = line.trim().split_once(' ') ?;
+            Some(EmojiSymbol {symbol, name})
+        })
+    }
+}
+
+/// The recently picked symbols, most recent first. Shared between all pickers.
+#[derive(Default)]
+struct EmojiPickerRecent(Vec<EmojiSymbol>);
+
+/// Pickers are shared between all the text inputs and editors that use the same style, like
+/// popup menus are between drop downs.
+#[derive(Default, Clone)]
+pub struct EmojiPickerGlobal {
+    pub map: Rc<RefCell<ComponentMap<LivePtr, EmojiPicker >> >
+}
+
+impl EmojiPickerGlobal {
+    /// Call from `after_apply` of the component that owns `emoji_picker`.
+    pub fn register(cx: &mut Cx, apply: &Apply, emoji_picker: Option<LivePtr>) {
+        let Some(emoji_picker) = emoji_picker else {
+            return
+        };
+        if !apply.from.is_from_doc() {
+            return
+        }
+        let global = cx.global::<EmojiPickerGlobal>().clone();
+        let mut map = global.map.borrow_mut();
+
+        // when live styling clean up old style references
+        map.retain( | k, _ | cx.live_registry.borrow().generation_valid(*k));
+
+        map.get_or_insert(cx, emoji_picker, | cx | {
+            EmojiPicker::new_from_ptr(cx, Some(emoji_picker))
+        });
+    }
+}
+
+#[derive(Clone, Debug, DefaultNone)]
+pub enum EmojiPickerAction {
+    Selected(String),
+    Closed,
+    None,
+}
+
+/// The key that opens the picker: ctrl+. and, like on macos, ctrl+cmd+space.
+pub fn is_emoji_picker_key(ke: &KeyEvent) -> bool {
+    match ke.key_code {
+        KeyCode::Period => ke.modifiers.control && !ke.modifiers.shift && !ke.modifiers.alt,
+        KeyCode::Space => ke.modifiers.control && ke.modifiers.logo,
+        _ => false
+    }
+}
+
+enum PickerHit {
+    Tab(usize),
+    Symbol(usize),
+}
+
+/// A searchable grid of emoji and symbols drawn in an overlay at the caret. It never takes the
+/// key focus, the text input or editor that opened it forwards its key events while it is open.
+#[derive(Live, LiveHook, LiveRegister)]
+pub struct EmojiPicker {
+    #[live] draw_list: DrawList2d,
+    #[live] draw_bg: DrawQuad,
+    #[live] draw_selected: DrawQuad,
+    #[live] draw_tab: DrawQuad,
+    #[live] draw_symbol: DrawText,
+    #[live] draw_query: DrawText,
+    #[live] draw_name: DrawText,
+
+    #[layout] layout: Layout,
+
+    #[live(28.0)] cell_size: f64,
+    #[live(9usize)] columns: usize,
+    #[live(6usize)] rows: usize,
+    #[live(20.0)] line_height: f64,
+    #[live] empty_query: String,
+
+    #[rust] query: String,
+    /// 0 is the recently used tab, the embedded categories follow.
+    #[rust] category: usize,
+    #[rust] selected: usize,
+    #[rust] first_row: usize,
+    #[rust] recent: Vec<EmojiSymbol>,
+    #[rust] symbols: Vec<EmojiSymbol>,
+}
+
+impl EmojiPicker {
+    /// Resets the picker for a new caret, showing the recently used symbols if there are any.
+    pub fn open(&mut self, cx: &mut Cx) {
+        self.recent = cx.global::<EmojiPickerRecent>().0.clone();
+        self.query.clear();
+        self.category = if self.recent.is_empty() {1} else {0};
+        self.update_symbols();
+        self.draw_list.redraw(cx);
+    }
+
+    pub fn size(&self) -> DVec2 {
+        dvec2(
+            self.columns as f64 * self.cell_size + self.layout.padding.left + self.layout.padding.right,
+            (self.rows + 1) as f64 * self.cell_size + 2.0 * self.line_height + self.layout.padding.top + self.layout.padding.bottom
+        )
+    }
+
+    pub fn picker_contains_pos(&self, cx: &mut Cx, pos: DVec2) -> bool {
+        self.draw_bg.area().clipped_rect(cx).contains(pos)
+    }
+
+    fn update_symbols(&mut self) {
+        self.selected = 0;
+        self.first_row = 0;
+        if self.query.is_empty() {
+            self.symbols = if self.category == 0 {
+                self.recent.clone()
+            }
+            else {
+                CATEGORIES[self.category - 1].symbols().collect()
+            };
+            return
+        }
+        let query = self.query.to_lowercase();
+        self.symbols = CATEGORIES.iter().flat_map( | category | category.symbols()).filter( | symbol | {
+            query.split_whitespace().all( | word | symbol.name.contains(word))
+        }).collect();
+    }
+
+    fn select(&mut self, index: usize) {
+        if self.symbols.is_empty() {
+            return
+        }
+        self.selected = index.min(self.symbols.len() - 1);
+        // keep the selected symbol in view
+        let row = self.selected / self.columns;
+        if row < self.first_row {
+            self.first_row = row;
+        }
+        else if row >= self.first_row + self.rows {
+            self.first_row = row + 1 - self.rows;
+        }
+    }
+
+    fn scroll(&mut self, rows: isize) {
+        let total_rows = (self.symbols.len() + self.columns - 1) / self.columns;
+        let max_first_row = total_rows.saturating_sub(self.rows);
+        self.first_row = self.first_row.saturating_add_signed(rows).min(max_first_row);
+    }
+
+    fn set_category(&mut self, category: usize) {
+        self.query.clear();
+        self.category = category;
+        self.update_symbols();
+    }
+
+    fn pick(&mut self, cx: &mut Cx, dispatch_action: &mut dyn FnMut(&mut Cx, EmojiPickerAction)) {
+        let Some(symbol) = self.symbols.get(self.selected).copied() else {
+            return
+        };
+        let recent = &mut cx.global::<EmojiPickerRecent>().0;
+        recent.retain( | s | *s != symbol);
+        recent.insert(0, symbol);
+        recent.truncate(MAX_RECENT);
+        dispatch_action(cx, EmojiPickerAction::Selected(symbol.symbol.to_string()));
+    }
+
+    fn hit_at(&self, cx: &Cx, abs: DVec2) -> Option<PickerHit> {
+        let pos = abs - self.draw_bg.area().rect(cx).pos - self.layout.padding.left_top();
+        if pos.x < 0.0 || pos.x >= self.columns as f64 * self.cell_size {
+            return None
+        }
+        let column = (pos.x / self.cell_size) as usize;
+        let y = pos.y - self.line_height;
+        if y < 0.0 {
+            return None
+        }
+        if y < self.cell_size {
+            return (column <= CATEGORIES.len()).then_some(PickerHit::Tab(column))
+        }
+        let row = ((y - self.cell_size) / self.cell_size) as usize;
+        if row >= self.rows {
+            return None
+        }
+        let index = (self.first_row + row) * self.columns + column;
+        (index < self.symbols.len()).then_some(PickerHit::Symbol(index))
+    }
+
+    /// Handles the picker's own mouse input, and the key input of `focus_area`, the text input or
+    /// editor it was opened from, which is redrawn along with it.
+    pub fn handle_event_with(
+        &mut self,
+        cx: &mut Cx,
+        event: &Event,
+        focus_area: Area,
+        dispatch_action: &mut dyn FnMut(&mut Cx, EmojiPickerAction),
+    ) {
+        let mut changed = false;
+        if cx.has_key_focus(focus_area) {
+            match event {
+                Event::TextInput(te) => {
+                    let input: String = te.input.chars().filter( | c | !c.is_control()).collect();
+                    if input.len() > 0 {
+                        self.query.push_str(&input);
+                        self.update_symbols();
+                        changed = true;
+                    }
+                }
+                Event::KeyDown(ke) => {
+                    changed = true;
+                    match ke.key_code {
+                        KeyCode::Escape => dispatch_action(cx, EmojiPickerAction::Closed),
+                        KeyCode::ReturnKey => self.pick(cx, dispatch_action),
+                        KeyCode::Backspace => {
+                            if self.query.pop().is_some() {
+                                self.update_symbols();
+                            }
+                        }
+                        KeyCode::ArrowLeft => self.select(self.selected.saturating_sub(1)),
+                        KeyCode::ArrowRight => self.select(self.selected + 1),
+                        KeyCode::ArrowUp => self.select(self.selected.saturating_sub(self.columns)),
+                        KeyCode::ArrowDown => self.select(self.selected + self.columns),
+                        KeyCode::PageUp => self.select(self.selected.saturating_sub(self.columns * self.rows)),
+                        KeyCode::PageDown => self.select(self.selected + self.columns * self.rows),
+                        KeyCode::Tab => {
+                            let count = CATEGORIES.len() + 1;
+                            if ke.modifiers.shift {
+                                self.set_category((self.category + count - 1) % count);
+                            }
+                            else {
+                                self.set_category((self.category + 1) % count);
+                            }
+                        }
+                        _ => changed = false
+                    }
+                }
+                _ => ()
+            }
+        }
+
+        match event.hits(cx, self.draw_bg.area()) {
+            Hit::FingerHoverIn(_) => {
+                cx.set_cursor(MouseCursor::Hand);
+            }
+            Hit::FingerHoverOver(fe) => {
+                if let Some(PickerHit::Symbol(index)) = self.hit_at(cx, fe.abs) {
+                    if index != self.selected {
+                        self.selected = index;
+                        changed = true;
+                    }
+                }
+            }
+            Hit::FingerScroll(fe) => {
+                self.scroll(if fe.scroll.y > 0.0 {1} else {-1});
+                changed = true;
+            }
+            Hit::FingerDown(fe) => match self.hit_at(cx, fe.abs) {
+                Some(PickerHit::Tab(category)) => {
+                    self.set_category(category);
+                    changed = true;
+                }
+                Some(PickerHit::Symbol(index)) => {
+                    self.selected = index;
+                    self.pick(cx, dispatch_action);
+                }
+                None => ()
+            }
+            _ => ()
+        }
+
+        // clicking outside of the picker closes it
+        if let Event::MouseDown(e) = event {
+            if !self.picker_contains_pos(cx, e.abs) {
+                dispatch_action(cx, EmojiPickerAction::Closed);
+            }
+        }
+
+        if changed {
+            self.draw_list.redraw(cx);
+            focus_area.redraw(cx);
+        }
+    }
+
+    /// Draws the picker below `caret`, a rect relative to `area`, or above it when there is no
+    /// room below.
+    pub fn draw_at_caret(&mut self, cx: &mut Cx2d, area: Area, caret: Rect) {
+        self.draw_list.begin_overlay_reuse(cx);
+        cx.begin_pass_sized_turtle(Layout::flow_down());
+
+        let size = self.size();
+        let pass_size = cx.current_pass_size();
+        let area_pos = area.rect(cx).pos;
+        let mut shift = dvec2(caret.pos.x, caret.pos.y + caret.size.y);
+        if area_pos.y + shift.y + size.y > pass_size.y {
+            shift.y = caret.pos.y - size.y;
+        }
+        shift.x = shift.x.min(pass_size.x - size.x - area_pos.x).max(-area_pos.x);
+        shift.y = shift.y.max(-area_pos.y);
+
+        self.draw_bg.begin(cx, Walk::fixed_size(size), Layout::default());
+        let origin = cx.turtle().rect().pos + self.layout.padding.left_top();
+        let width = self.columns as f64 * self.cell_size;
+        let cell = dvec2(self.cell_size, self.cell_size);
+
+        let query_rect = Rect {pos: origin, size: dvec2(width, self.line_height)};
+        if self.query.is_empty() {
+            self.draw_query.draw_walk(cx, Walk::abs_rect(query_rect), Align {x: 0.0, y: 0.5}, &self.empty_query);
+            let category = if self.category == 0 {"Recent"} else {CATEGORIES[self.category - 1].name};
+            self.draw_name.draw_walk(cx, Walk::abs_rect(query_rect), Align {x: 1.0, y: 0.5}, category);
+        }
+        else {
+            self.draw_query.draw_walk(cx, Walk::abs_rect(query_rect), Align {x: 0.0, y: 0.5}, &self.query);
+        }
+
+        let tabs_pos = origin + dvec2(0.0, self.line_height);
+        for tab in 0..=CATEGORIES.len() {
+            let rect = Rect {pos: tabs_pos + dvec2(tab as f64 * self.cell_size, 0.0), size: cell};
+            if self.query.is_empty() && tab == self.category {
+                self.draw_tab.draw_abs(cx, rect);
+            }
+            let icon = if tab == 0 {"🕘"} else {CATEGORIES[tab - 1].symbols().next().map_or("", | s | s.symbol)};
+            self.draw_symbol.draw_walk(cx, Walk::abs_rect(rect), Align {x: 0.5, y: 0.5}, icon);
+        }
+
+        let grid_pos = tabs_pos + dvec2(0.0, self.cell_size);
+        let first = self.first_row * self.columns;
+        let last = (first + self.rows * self.columns).min(self.symbols.len());
+        for index in first..last {
+            let row = (index - first) / self.columns;
+            let column = (index - first) % self.columns;
+            let rect = Rect {pos: grid_pos + dvec2(column as f64, row as f64) * self.cell_size, size: cell};
+            if index == self.selected {
+                self.draw_selected.draw_abs(cx, rect);
+            }
+            self.draw_symbol.draw_walk(cx, Walk::abs_rect(rect), Align {x: 0.5, y: 0.5}, self.symbols[index].symbol);
+        }
+
+        let name_rect = Rect {
+            pos: grid_pos + dvec2(0.0, self.rows as f64 * self.cell_size),
+            size: dvec2(width, self.line_height)
+        };
+        let name = if let Some(symbol) = self.symbols.get(self.selected) {
+            symbol.name
+        }
+        else if self.category == 0 && self.query.is_empty() {
+            "No recently used symbols"
+        }
+        else {
+            "No matches"
+        };
+        self.draw_name.draw_walk(cx, Walk::abs_rect(name_rect), Align {x: 0.0, y: 0.5}, name);
+
+        self.draw_bg.end(cx);
+        cx.end_pass_sized_turtle_with_shift(area, shift);
+        self.draw_list.end(cx);
+    }
+}
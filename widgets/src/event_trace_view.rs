@@ -0,0 +1,184 @@
+use crate::{
+    makepad_derive_widget::*,
+    makepad_draw::*,
+    widget::*,
+    widget_match_event::*,
+    view::*,
+    portal_list::*,
+    check_box::*,
+    button::*,
+    text_input::*,
+};
+
+live_design!{
+    EventTraceViewBase = {{EventTraceView}} {}
+}
+
+/// A developer panel that lists the events the app gets and the actions its widgets send, as
+/// they happen, with the time since the entry before. The list can be narrowed down to kinds of
+/// entries, to entries that contain a text, and to pointer events inside a rect. The input
+/// events that are listed can be copied as stdin messages that play them again.
+#[derive(Live, Widget)]
+pub struct EventTraceView {
+    #[deref] view: View,
+    /// Starts recording when the panel is created.
+    #[live(true)] record: bool,
+    #[live(true)] show_pointer: bool,
+    #[live(true)] show_keyboard: bool,
+    #[live(true)] show_actions: bool,
+    #[live(false)] show_other: bool,
+    #[rust] query: String,
+    #[rust] area: Option<Rect>,
+    #[rust] generation: u64,
+    #[rust] rows: Vec<EventTraceEntry>,
+}
+
+impl LiveHook for EventTraceView {
+    fn after_new_from_doc(&mut self, cx: &mut Cx) {
+        if self.record {
+            cx.event_trace.set_enabled(true);
+        }
+        self.check_box(id!(record)).set_selected(cx, self.record);
+        self.check_box(id!(pointer)).set_selected(cx, self.show_pointer);
+        self.check_box(id!(keyboard)).set_selected(cx, self.show_keyboard);
+        self.check_box(id!(actions)).set_selected(cx, self.show_actions);
+        self.check_box(id!(other)).set_selected(cx, self.show_other);
+    }
+}
+
+impl EventTraceView {
+    fn shows(&self, entry: &EventTraceEntry) -> bool {
+        let kind = match entry.kind {
+            EventTraceKind::Pointer => self.show_pointer,
+            EventTraceKind::Keyboard => self.show_keyboard,
+            EventTraceKind::Action => self.show_actions,
+            EventTraceKind::Other => self.show_other,
+        };
+        if !kind {
+            return false
+        }
+        if let (Some(area), EventTraceKind::Pointer) = (self.area, entry.kind) {
+            if !entry.abs.is_some_and( | abs | area.contains(abs)) {
+                return false
+            }
+        }
+        self.query.is_empty()
+            || entry.name.to_lowercase().contains(&self.query)
+            || entry.detail.to_lowercase().contains(&self.query)
+    }
+
+    fn update_rows(&mut self, cx: &Cx) {
+        self.generation = cx.event_trace.generation();
+        self.rows = cx.event_trace.entries().into_iter()
+            .filter( | entry | self.shows(entry))
+            .collect();
+    }
+
+    fn refilter(&mut self, cx: &mut Cx) {
+        self.update_rows(cx);
+        self.view.redraw(cx);
+    }
+
+    fn draw_rows(&mut self, cx: &mut Cx2d, list: &mut PortalList) {
+        list.set_item_range(cx, 0, self.rows.len());
+        while let Some(item_id) = list.next_visible_item(cx) {
+            let is_even = if item_id & 1 == 0 {1.0} else {0.0};
+            let Some(entry) = self.rows.get(item_id) else {
+                let item = list.item(cx, item_id, live_id!(Empty)).unwrap();
+                item.apply_over(cx, live!{draw_bg: {is_even: (is_even)}});
+                item.draw_all(cx, &mut Scope::empty());
+                continue
+            };
+            let delta = match item_id.checked_sub(1).and_then( | prev | self.rows.get(prev)) {
+                Some(prev) => format!("+{:.1}ms", (entry.time - prev.time) * 1000.0),
+                None => String::new()
+            };
+            let name = match entry.abs {
+                Some(abs) => format!("{} {:.0},{:.0}", entry.name, abs.x, abs.y),
+                None => entry.name.clone()
+            };
+            let item = list.item(cx, item_id, live_id!(Entry)).unwrap();
+            item.apply_over(cx, live!{
+                time = {text: (format!("{:.3}", entry.time))}
+                delta = {text: (delta)}
+                name = {text: (name)}
+                detail = {text: (&entry.detail)}
+                draw_bg: {is_even: (is_even)}
+            });
+            item.draw_all(cx, &mut Scope::empty());
+        }
+    }
+}
+
+impl Widget for EventTraceView {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        self.widget_match_event(cx, event, scope);
+        self.view.handle_event(cx, event, scope);
+        if cx.event_trace.generation() != self.generation {
+            self.view.redraw(cx);
+        }
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        if cx.event_trace.generation() != self.generation {
+            self.update_rows(cx);
+        }
+        while let Some(step) = self.view.draw_walk(cx, scope, walk).step() {
+            if let Some(mut list) = step.as_portal_list().borrow_mut() {
+                self.draw_rows(cx, &mut *list);
+            }
+        }
+        DrawStep::done()
+    }
+}
+
+impl WidgetMatchEvent for EventTraceView {
+    fn handle_actions(&mut self, cx: &mut Cx, actions: &Actions, _scope: &mut Scope) {
+        if let Some(record) = self.check_box(id!(record)).changed(actions) {
+            self.record = record;
+            cx.event_trace.set_enabled(record);
+        }
+        let kinds = [
+            (id!(pointer), &mut self.show_pointer),
+            (id!(keyboard), &mut self.show_keyboard),
+            (id!(actions), &mut self.show_actions),
+            (id!(other), &mut self.show_other),
+        ];
+        let mut changed = false;
+        for (id, show) in kinds {
+            if let Some(value) = self.view.check_box(id).changed(actions) {
+                *show = value;
+                changed = true;
+            }
+        }
+        if let Some(query) = self.text_input(id!(filter)).changed(actions) {
+            self.query = query.to_lowercase();
+            changed = true;
+        }
+        if changed {
+            self.refilter(cx);
+        }
+        if self.button(id!(clear)).clicked(actions) {
+            cx.event_trace.clear();
+            self.refilter(cx);
+        }
+        if self.button(id!(copy_replay)).clicked(actions) {
+            cx.copy_to_clipboard(&event_trace_replay(&self.rows));
+        }
+    }
+}
+
+impl EventTraceViewRef {
+    /// Only lists pointer events inside `area`, in window coordinates, or all of them for `None`.
+    pub fn set_area_filter(&self, cx: &mut Cx, area: Option<Rect>) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.area = area;
+            inner.refilter(cx);
+        }
+    }
+
+    /// The input events that are listed, as stdin messages, one JSON object per line.
+    pub fn replay_snippet(&self) -> String {
+        self.borrow().map( | inner | event_trace_replay(&inner.rows)).unwrap_or_default()
+    }
+}
@@ -0,0 +1,158 @@
+use {
+    std::path::PathBuf,
+    crate::{
+        makepad_derive_widget::*,
+        makepad_draw::*,
+        button::ButtonWidgetExt,
+        label::LabelWidgetExt,
+        text_input::TextInputWidgetExt,
+        view::*,
+        widget::*,
+    }
+};
+
+live_design!{
+    FallbackDialogBase = {{FallbackDialog}} {}
+}
+
+/// Stands in for the native dialogs on platforms that have none, like the web. The window draws
+/// it over everything else and keeps pointer input away from the rest of the UI while it's open.
+/// File dialogs get a path to type in, there is no file browser.
+#[derive(Live, LiveHook, Widget)]
+pub struct FallbackDialog {
+    #[deref] view: View,
+    #[live] draw_list: DrawList2d,
+    /// The first one is showing, the rest were asked for while it was open.
+    #[rust] dialogs: Vec<(LiveId, Dialog)>,
+    /// Key focus can only go to the path input once it has been drawn.
+    #[rust] focus_pending: bool,
+}
+
+impl FallbackDialog {
+    pub fn is_open(&self) -> bool {
+        self.dialogs.len() > 0
+    }
+
+    fn show_first(&mut self, cx: &mut Cx) {
+        let dialog = match self.dialogs.first() {
+            Some((_, dialog)) => dialog.clone(),
+            None => return
+        };
+        self.label(id!(title)).set_text(dialog.title());
+        let (message, ok, cancel) = match &dialog {
+            Dialog::OpenFile(d) | Dialog::SaveFile(d) | Dialog::SelectFolder(d) => {
+                let path = match (&d.location, &d.filename) {
+                    (Some(location), Some(filename)) => location.join(filename),
+                    (Some(location), None) => location.clone(),
+                    (None, Some(filename)) => PathBuf::from(filename),
+                    (None, None) => PathBuf::new()
+                };
+                self.text_input(id!(path.input)).set_text(&path.to_string_lossy());
+                let ok = match dialog {
+                    Dialog::OpenFile(_) => "Open",
+                    Dialog::SaveFile(_) => "Save",
+                    _ => "Select"
+                };
+                (None, ok, Some("Cancel"))
+            }
+            Dialog::Message(d) => {
+                match d.buttons {
+                    MessageDialogButtons::Ok => (Some(&d.message), "OK", None),
+                    MessageDialogButtons::OkCancel => (Some(&d.message), "OK", Some("Cancel")),
+                    MessageDialogButtons::YesNo => (Some(&d.message), "Yes", Some("No")),
+                }
+            }
+        };
+        self.view(id!(message)).set_visible(message.is_some());
+        self.label(id!(message.label)).set_text(message.map_or("", | m | m.as_str()));
+        self.view(id!(path)).set_visible(message.is_none());
+        self.button(id!(buttons.ok)).set_text(ok);
+        self.view(id!(buttons.cancel)).set_visible(cancel.is_some());
+        self.button(id!(buttons.cancel.button)).set_text(cancel.unwrap_or(""));
+        self.focus_pending = true;
+        cx.redraw_all();
+    }
+
+    fn close(&mut self, cx: &mut Cx, result: DialogResult) {
+        if self.dialogs.len() == 0 {
+            return
+        }
+        let (dialog_id, _) = self.dialogs.remove(0);
+        cx.close_dialog(dialog_id, result);
+        if self.dialogs.len() > 0 {
+            self.show_first(cx);
+        }
+        else {
+            cx.set_key_focus(Area::Empty);
+            cx.redraw_all();
+        }
+    }
+
+    fn accept(&mut self, cx: &mut Cx) {
+        let result = match self.dialogs.first() {
+            Some((_, Dialog::Message(_))) => DialogResult::Accepted,
+            Some(_) => {
+                let path = self.text_input(id!(path.input)).text();
+                let path = path.trim();
+                if path.is_empty() {
+                    return
+                }
+                DialogResult::Paths(vec![PathBuf::from(path)])
+            }
+            None => return
+        };
+        self.close(cx, result);
+    }
+}
+
+impl Widget for FallbackDialog {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        if let Event::Dialog(DialogEvent::ShowFallback {dialog_id, dialog}) = event {
+            self.dialogs.push((*dialog_id, dialog.clone()));
+            if self.dialogs.len() == 1 {
+                self.show_first(cx);
+            }
+            return
+        }
+        if !self.is_open() {
+            return
+        }
+        if let Event::KeyDown(ke) = event {
+            match ke.key_code {
+                KeyCode::Escape => return self.close(cx, DialogResult::Cancelled),
+                KeyCode::ReturnKey => return self.accept(cx),
+                _ => ()
+            }
+        }
+        let actions = cx.capture_actions( | cx | self.view.handle_event(cx, event, scope));
+        if self.button(id!(buttons.ok)).clicked(&actions) {
+            self.accept(cx);
+        }
+        else if self.button(id!(buttons.cancel.button)).clicked(&actions) {
+            self.close(cx, DialogResult::Cancelled);
+        }
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        if !self.is_open() {
+            return DrawStep::done()
+        }
+        self.draw_list.begin_overlay_reuse(cx);
+        cx.begin_pass_sized_turtle(Layout::flow_down());
+        while self.view.draw_walk(cx, scope, walk).is_step() {}
+        cx.end_pass_sized_turtle();
+        self.draw_list.end(cx);
+        if self.focus_pending {
+            self.focus_pending = false;
+            if let Some(input) = self.text_input(id!(path.input)).borrow() {
+                if self.view(id!(path)).is_visible() {
+                    input.set_key_focus(cx);
+                }
+                else {
+                    cx.set_key_focus(self.view.area());
+                }
+            }
+        }
+        DrawStep::done()
+    }
+}
@@ -101,19 +101,54 @@ pub struct FileTree {
     #[rust] open_nodes: HashSet<LiveId>,
     
     #[rust] tree_nodes: ComponentMap<LiveId, (FileTreeNode, LiveId)>,
+    #[rust] node_pool: ComponentPool<LiveId, FileTreeNode>,
     
     #[rust] count: usize,
     #[rust] stack: Vec<f64>,
+    
+    #[rust] rows: Vec<FileTreeRow>,
+    #[rust(true)] rows_dirty: bool,
+}
+
+/// Where `FileTree::draw_source` gets its nodes from. The children of a folder are only asked
+/// for once it is open, so a source can put off loading them until then.
+pub trait FileTreeSource {
+    /// The children of `folder_id` in the order they are shown, or `None` while they are still
+    /// being loaded. Call `FileTree::refresh` once they are there.
+    fn children(&mut self, cx: &mut Cx, folder_id: LiveId) -> Option<Vec<LiveId>>;
+    fn name(&self, node_id: LiveId) -> &str;
+    fn is_folder(&self, node_id: LiveId) -> bool;
+}
+
+#[derive(Clone, Copy)]
+struct FileTreeRow {
+    node_id: LiveId,
+    depth: usize,
+    is_folder: bool,
+}
+
+impl Recycle for FileTreeNode {
+    fn recycle(&mut self, _cx: &mut Cx) {
+        self.animator.swap_out_state();
+    }
 }
 
 impl LiveHook for FileTree {
+    fn before_apply(&mut self, _cx: &mut Cx, apply: &mut Apply, _index: usize, _nodes: &[LiveNode]) {
+        if let ApplyFrom::UpdateFromDoc {..} = apply.from {
+            self.node_pool.clear();
+        }
+    }
+    
     fn after_apply(&mut self, cx: &mut Cx, apply: &mut Apply, index: usize, nodes: &[LiveNode]) {
         for (_, (tree_node, id)) in self.tree_nodes.iter_mut() {
             if let Some(index) = nodes.child_by_name(index, id.as_field()) {
                 tree_node.apply(cx, apply, index, nodes);
             }
         }
-        self.scroll_bars.redraw(cx);
+        if apply.any_changed() {
+            self.scroll_bars.redraw(cx);
+        }
     }
 }
 
@@ -123,6 +158,12 @@ pub enum FileTreeAction {
     FileClicked(LiveId),
     FolderClicked(LiveId),
     ShouldFileStartDrag(LiveId),
+    /// A folder was opened, for a host that loads children itself rather than in its source.
+    FolderOpened(LiveId),
+    /// F2 was pressed on the selected node.
+    ShouldRename(LiveId),
+    /// Delete was pressed on the selected node.
+    ShouldDelete(LiveId),
 }
 
 pub enum FileTreeNodeAction {
@@ -257,7 +298,10 @@ impl FileTree {
         self.scroll_bars.end(cx);
         
         let selected_node_id = self.selected_node_id;
-        self.tree_nodes.retain_visible_and( | node_id, _ | Some(*node_id) == selected_node_id);
+        let node_pool = &mut self.node_pool;
+        self.tree_nodes.retain_visible_into( | node_id, _ | Some(*node_id) == selected_node_id, | _, (tree_node, template) | {
+            node_pool.put(cx, template, tree_node)
+        });
     }
     
     pub fn is_even(count: usize) -> f32 {
@@ -293,8 +337,9 @@ impl FileTree {
         
         if self.should_node_draw(cx) {
             let folder_node = self.folder_node;
+            let node_pool = &mut self.node_pool;
             let (tree_node, _) = self.tree_nodes.get_or_insert(cx, node_id, | cx | {
-                let mut tree_node = FileTreeNode::new_from_ptr(cx, folder_node);
+                let mut tree_node = node_pool.take_or_new(cx, live_id!(folder_node), folder_node);
                 if is_open {
                     tree_node.set_folder_is_open(cx, true, Animate::No)
                 }
@@ -330,13 +375,77 @@ impl FileTree {
         }
         if self.should_node_draw(cx) {
             let file_node = self.file_node;
+            let node_pool = &mut self.node_pool;
             let (tree_node, _) = self.tree_nodes.get_or_insert(cx, node_id, | cx | {
-                (FileTreeNode::new_from_ptr(cx, file_node), live_id!(file_node))
+                (node_pool.take_or_new(cx, live_id!(file_node), file_node), live_id!(file_node))
             });
             tree_node.draw_file(cx, name, Self::is_even(self.count), self.node_height, self.stack.len(), scale);
         }
     }
     
+    /// Draws the nodes `source` has under `root_id`, which isn't shown itself. Only the rows in
+    /// view are drawn and the rest is walked over in one go, so large trees stay fast. Open and
+    /// close don't animate here, as the rows below a folder move at once.
+    pub fn draw_source(&mut self, cx: &mut Cx2d, root_id: LiveId, source: &mut dyn FileTreeSource) {
+        if self.rows_dirty {
+            self.rows.clear();
+            self.push_rows(cx, source, root_id, 0);
+            self.rows_dirty = false;
+        }
+        let node_height = self.node_height;
+        let first = ((self.scroll_bars.get_scroll_pos().y / node_height).max(0.0) as usize).min(self.rows.len());
+        cx.walk_turtle(Walk::size(Size::Fill, Size::Fixed(first as f64 * node_height)));
+        self.count = first;
+        let mut index = first;
+        while index < self.rows.len() && cx.walk_turtle_would_be_visible(Walk::size(Size::Fill, Size::Fixed(node_height))) {
+            let row = self.rows[index];
+            self.count += 1;
+            let is_even = Self::is_even(self.count);
+            let name = source.name(row.node_id);
+            if row.is_folder {
+                let is_open = self.open_nodes.contains(&row.node_id);
+                let folder_node = self.folder_node;
+                let node_pool = &mut self.node_pool;
+                let (tree_node, _) = self.tree_nodes.get_or_insert(cx, row.node_id, | cx | {
+                    let mut tree_node = node_pool.take_or_new(cx, live_id!(folder_node), folder_node);
+                    if is_open {
+                        tree_node.set_folder_is_open(cx, true, Animate::No)
+                    }
+                    (tree_node, live_id!(folder_node))
+                });
+                tree_node.draw_folder(cx, name, is_even, node_height, row.depth, 1.0);
+            }
+            else {
+                let file_node = self.file_node;
+                let node_pool = &mut self.node_pool;
+                let (tree_node, _) = self.tree_nodes.get_or_insert(cx, row.node_id, | cx | {
+                    (node_pool.take_or_new(cx, live_id!(file_node), file_node), live_id!(file_node))
+                });
+                tree_node.draw_file(cx, name, is_even, node_height, row.depth, 1.0);
+            }
+            index += 1;
+        }
+        cx.walk_turtle(Walk::size(Size::Fill, Size::Fixed((self.rows.len() - index) as f64 * node_height)));
+    }
+    
+    fn push_rows(&mut self, cx: &mut Cx, source: &mut dyn FileTreeSource, folder_id: LiveId, depth: usize) {
+        let Some(children) = source.children(cx, folder_id) else {return};
+        for node_id in children {
+            let is_folder = source.is_folder(node_id);
+            self.rows.push(FileTreeRow {node_id, depth, is_folder});
+            if is_folder && self.open_nodes.contains(&node_id) {
+                self.push_rows(cx, source, node_id, depth + 1);
+            }
+        }
+    }
+    
+    /// Makes `draw_source` ask its source for the tree again, after nodes were added, removed
+    /// or finished loading.
+    pub fn refresh(&mut self, cx: &mut Cx) {
+        self.rows_dirty = true;
+        self.scroll_bars.redraw(cx);
+    }
+    
     pub fn forget(&mut self) {
         self.tree_nodes.clear();
     }
@@ -354,6 +463,10 @@ impl FileTree {
         }
     }
     
+    pub fn is_folder_open(&self, node_id: LiveId) -> bool {
+        self.open_nodes.contains(&node_id)
+    }
+    
     pub fn set_folder_is_open(
         &mut self,
         cx: &mut Cx,
@@ -362,10 +475,10 @@ impl FileTree {
         animate: Animate,
     ) {
         if is_open {
-            self.open_nodes.insert(node_id);
+            self.rows_dirty |= self.open_nodes.insert(node_id);
         }
         else {
-            self.open_nodes.remove(&node_id);
+            self.rows_dirty |= self.open_nodes.remove(&node_id);
         }
         if let Some((tree_node, _)) = self.tree_nodes.get_mut(&node_id) {
             tree_node.set_folder_is_open(cx, is_open, animate);
@@ -412,9 +525,12 @@ impl Widget for FileTree {
             match node_action {
                 FileTreeNodeAction::Opening => {
                     self.open_nodes.insert(node_id);
+                    self.rows_dirty = true;
+                    cx.widget_action(uid, &scope.path, FileTreeAction::FolderOpened(node_id));
                 }
                 FileTreeNodeAction::Closing => {
                     self.open_nodes.remove(&node_id);
+                    self.rows_dirty = true;
                 }
                 FileTreeNodeAction::WasClicked => {
                     cx.set_key_focus(self.scroll_bars.area());
@@ -450,6 +566,13 @@ impl Widget for FileTree {
                     self.tree_nodes.get_mut(&node_id).unwrap().0.set_is_focussed(cx, false, Animate::Yes);
                 }
             }
+            Hit::KeyDown(ke) => if let Some(node_id) = self.selected_node_id {
+                match ke.key_code {
+                    KeyCode::F2 => cx.widget_action(uid, &scope.path, FileTreeAction::ShouldRename(node_id)),
+                    KeyCode::Delete => cx.widget_action(uid, &scope.path, FileTreeAction::ShouldDelete(node_id)),
+                    _ => ()
+                }
+            }
             _ => ()
         }
     }
@@ -496,6 +619,39 @@ impl FileTreeRef{
     }
     
     
+    pub fn folder_opened(&self, actions: &Actions) -> Option<LiveId> {
+        if let Some(item) = actions.find_widget_action(self.widget_uid()) {
+            if let FileTreeAction::FolderOpened(file_id) = item.cast() {
+                return Some(file_id)
+            }
+        }
+        None
+    }
+    
+    pub fn should_rename(&self, actions: &Actions) -> Option<LiveId> {
+        if let Some(item) = actions.find_widget_action(self.widget_uid()) {
+            if let FileTreeAction::ShouldRename(file_id) = item.cast() {
+                return Some(file_id)
+            }
+        }
+        None
+    }
+    
+    pub fn should_delete(&self, actions: &Actions) -> Option<LiveId> {
+        if let Some(item) = actions.find_widget_action(self.widget_uid()) {
+            if let FileTreeAction::ShouldDelete(file_id) = item.cast() {
+                return Some(file_id)
+            }
+        }
+        None
+    }
+    
+    pub fn refresh(&self, cx: &mut Cx) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.refresh(cx);
+        }
+    }
+    
     pub fn file_start_drag(&self, cx: &mut Cx, _file_id: LiveId, item: DragItem) {
         cx.start_dragging(vec![item]);
     }
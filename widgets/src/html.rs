@@ -67,7 +67,10 @@ pub struct Html {
 
 // alright lets parse the HTML
 impl LiveHook for Html {
-    fn after_apply_from(&mut self, _cx: &mut Cx, _apply:&mut Apply) {
+    fn after_apply_from(&mut self, _cx: &mut Cx, apply:&mut Apply) {
+        if !apply.changed(live_id!(body)) {
+            return
+        }
         let mut errors = Some(Vec::new());
         let new_doc = parse_html(&*self.body, &mut errors);
         if new_doc != self.doc{
@@ -14,6 +14,7 @@ pub mod icon;
 pub mod link_label;
 pub mod drop_down;
 pub mod popup_menu;
+pub mod emoji_picker;
 pub mod check_box;
 pub mod radio_button;
 pub mod text_input;
@@ -34,6 +35,7 @@ pub mod stack_navigation;
 pub mod expandable_panel;
 pub mod desktop_button;
 pub mod window;
+pub mod fallback_dialog;
 pub mod scroll_shadow;
 pub mod window_menu;
 pub mod html;
@@ -45,15 +47,20 @@ pub mod video;
 pub mod rotated_image;
 pub mod slide_panel;
 pub mod page_flip;
+pub mod responsive_view;
 pub mod keyboard_view;
 pub mod flat_list;
 pub mod file_tree;
+pub mod virtual_list;
 pub mod slides_view;
 pub mod color_picker;
+pub mod node_graph;
+pub mod transform_gizmo;
 pub mod root;
 
 pub mod debug_view;
 pub mod performance_view;
+pub mod event_trace_view;
 pub mod nav_control;
 
 pub mod view;
@@ -74,9 +81,11 @@ pub mod turtle_step;
 pub mod designer;
 pub mod designer_outline_tree;
 pub mod designer_view;
+pub mod component_thumbnails;
 pub mod designer_outline;
 pub mod designer_data;
 pub mod designer_toolbox;
+pub mod designer_state_machine;
 
 pub use crate::{
     data_binding::{DataBindingStore, DataBindingMap},
@@ -92,24 +101,31 @@ pub use crate::{
     html::*,
     check_box::*,
     drop_down::*,
+    emoji_picker::*,
     video::*,
     radio_button::*,
     text_input::*,
     link_label::*,
     portal_list::*,
     flat_list::*,
+    virtual_list::*,
     page_flip::*,
+    responsive_view::*,
     slide_panel::*,
     fold_button::*,
     dock::*,
     stack_navigation::*,
     expandable_panel::*,
     window::*,
+    fallback_dialog::FallbackDialog,
     multi_window::*,
     scroll_bars::{ScrollBars},
     scroll_shadow::{DrawScrollShadow},
     scroll_bar::{ScrollBar},
     slides_view::{SlidesView},
+    node_graph::*,
+    transform_gizmo::*,
+    component_thumbnails::{ComponentThumbnails, ComponentScreenshot},
     widget_match_event::WidgetMatchEvent,
     widget::{
         WidgetSet,
@@ -126,6 +142,7 @@ pub use crate::{
         WidgetActionCast,
         WidgetActionOptionApi,
         WidgetRef,
+        push_reading_line,
         Widget,
         WidgetNode,
         WidgetRegistry,
@@ -138,8 +155,10 @@ pub use crate::{
 pub fn live_design(cx: &mut Cx) {
     makepad_draw::live_design(cx);
     crate::page_flip::live_design(cx);
+    crate::responsive_view::live_design(cx);
     crate::debug_view::live_design(cx);
     crate::performance_view::live_design(cx);
+    crate::event_trace_view::live_design(cx);
     crate::fold_header::live_design(cx);
     crate::splitter::live_design(cx);
     crate::base::live_design(cx);
@@ -159,6 +178,7 @@ pub fn live_design(cx: &mut Cx) {
     crate::scroll_shadow::live_design(cx);
     crate::button::live_design(cx);
     crate::desktop_button::live_design(cx);
+    crate::fallback_dialog::live_design(cx);
     crate::window::live_design(cx);
     crate::window_menu::live_design(cx);
     crate::scroll_bar::live_design(cx);
@@ -167,6 +187,7 @@ pub fn live_design(cx: &mut Cx) {
     crate::radio_button::live_design(cx);
     crate::popup_menu::live_design(cx);
     crate::drop_down::live_design(cx);
+    crate::emoji_picker::live_design(cx);
     crate::multi_window::live_design(cx);
     crate::portal_list::live_design(cx);
     crate::flat_list::live_design(cx);
@@ -175,7 +196,10 @@ pub fn live_design(cx: &mut Cx) {
     crate::tab_bar::live_design(cx);
     crate::dock::live_design(cx);
     crate::color_picker::live_design(cx);
+    crate::node_graph::live_design(cx);
+    crate::transform_gizmo::live_design(cx);
     crate::file_tree::live_design(cx);
+    crate::virtual_list::live_design(cx);
     crate::slides_view::live_design(cx);
     crate::tab_close_button::live_design(cx);
     crate::keyboard_view::live_design(cx);
@@ -194,4 +218,5 @@ pub fn live_design(cx: &mut Cx) {
     crate::designer_outline::live_design(cx);
     crate::designer_outline_tree::live_design(cx);
     crate::designer_toolbox::live_design(cx);
+    crate::designer_state_machine::live_design(cx);
 }
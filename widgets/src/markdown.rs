@@ -26,8 +26,11 @@ pub struct Markdown{
 
 // alright lets parse the HTML
 impl LiveHook for Markdown{
-    fn after_apply_from(&mut self, _cx: &mut Cx, _apply:&mut Apply) {
-        self.parse_text();
+    fn after_apply_from(&mut self, _cx: &mut Cx, apply:&mut Apply) {
+        // parsing a large document again for every apply adds up during hot reload
+        if apply.changed(live_id!(body)) {
+            self.parse_text();
+        }
     }
 }
  
@@ -0,0 +1,651 @@
+use {
+    crate::{
+        makepad_derive_widget::*,
+        makepad_draw::*,
+        makepad_micro_serde::*,
+        widget::*,
+    }
+};
+
+live_design!{
+    DrawNodeGraphBg = {{DrawNodeGraphBg}} {}
+    DrawNodeQuad = {{DrawNodeQuad}} {}
+    DrawPortQuad = {{DrawPortQuad}} {}
+    NodeGraphBase = {{NodeGraph}} {}
+}
+
+#[derive(Live, LiveHook, LiveRegister)]#[repr(C)]
+struct DrawNodeGraphBg {
+    #[deref] draw_super: DrawQuad,
+    #[live] pan: Vec2,
+    #[live] zoom: f32,
+}
+
+#[derive(Live, LiveHook, LiveRegister)]#[repr(C)]
+struct DrawNodeQuad {
+    #[deref] draw_super: DrawQuad,
+    #[live] title_height: f32,
+    #[live] selected: f32,
+}
+
+#[derive(Live, LiveHook, LiveRegister)]#[repr(C)]
+struct DrawPortQuad {
+    #[deref] draw_super: DrawQuad,
+    #[live] color: Vec4,
+    #[live] connected: f32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, SerRon, DeRon)]
+pub enum PortDirection {
+    Input,
+    Output,
+}
+
+/// A port of a node. Ports connect when their types match, an empty type connects to anything.
+#[derive(Clone, Debug, PartialEq, SerRon, DeRon)]
+pub struct GraphPort {
+    pub name: String,
+    pub port_type: String,
+}
+
+impl GraphPort {
+    pub fn new(name: &str, port_type: &str) -> Self {
+        Self {name: name.to_string(), port_type: port_type.to_string()}
+    }
+
+    pub fn accepts(&self, other: &GraphPort) -> bool {
+        self.port_type.is_empty() || other.port_type.is_empty() || self.port_type == other.port_type
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, SerRon, DeRon)]
+pub struct GraphNode {
+    pub id: u64,
+    pub title: String,
+    /// Position of the top left corner, in graph coordinates.
+    pub x: f64,
+    pub y: f64,
+    pub inputs: Vec<GraphPort>,
+    pub outputs: Vec<GraphPort>,
+}
+
+impl GraphNode {
+    pub fn pos(&self) -> DVec2 {
+        dvec2(self.x, self.y)
+    }
+
+    pub fn ports(&self, direction: PortDirection) -> &[GraphPort] {
+        match direction {
+            PortDirection::Input => &self.inputs,
+            PortDirection::Output => &self.outputs,
+        }
+    }
+}
+
+/// A connection always runs from an output port to an input port.
+#[derive(Clone, Copy, Debug, PartialEq, SerRon, DeRon)]
+pub struct GraphConnection {
+    pub from_node: u64,
+    pub from_port: usize,
+    pub to_node: u64,
+    pub to_port: usize,
+}
+
+/// The graph a `NodeGraph` edits. It round trips through RON, so apps can store it as is.
+#[derive(Clone, Debug, Default, PartialEq, SerRon, DeRon)]
+pub struct NodeGraphData {
+    pub nodes: Vec<GraphNode>,
+    pub connections: Vec<GraphConnection>,
+}
+
+impl NodeGraphData {
+    pub fn node(&self, id: u64) -> Option<&GraphNode> {
+        self.nodes.iter().find( | node | node.id == id)
+    }
+
+    pub fn node_mut(&mut self, id: u64) -> Option<&mut GraphNode> {
+        self.nodes.iter_mut().find( | node | node.id == id)
+    }
+
+    /// Adds a node and returns its id.
+    pub fn add_node(&mut self, title: &str, pos: DVec2, inputs: Vec<GraphPort>, outputs: Vec<GraphPort>) -> u64 {
+        let id = self.nodes.iter().map( | node | node.id + 1).max().unwrap_or(0);
+        self.nodes.push(GraphNode {
+            id,
+            title: title.to_string(),
+            x: pos.x,
+            y: pos.y,
+            inputs,
+            outputs
+        });
+        id
+    }
+
+    /// Removes the nodes and all their connections.
+    pub fn remove_nodes(&mut self, ids: &[u64]) {
+        self.nodes.retain( | node | !ids.contains(&node.id));
+        self.connections.retain( | c | !ids.contains(&c.from_node) && !ids.contains(&c.to_node));
+    }
+
+    pub fn port(&self, node: u64, port: usize, direction: PortDirection) -> Option<&GraphPort> {
+        self.node(node).and_then( | node | node.ports(direction).get(port))
+    }
+
+    /// Whether the connection joins existing ports of different nodes with compatible types.
+    pub fn is_valid_connection(&self, connection: &GraphConnection) -> bool {
+        if connection.from_node == connection.to_node {
+            return false
+        }
+        match (
+            self.port(connection.from_node, connection.from_port, PortDirection::Output),
+            self.port(connection.to_node, connection.to_port, PortDirection::Input)
+        ) {
+            (Some(from), Some(to)) => from.accepts(to),
+            _ => false
+        }
+    }
+
+    /// The connection feeding an input port, if any.
+    pub fn connection_to(&self, node: u64, port: usize) -> Option<GraphConnection> {
+        self.connections.iter().find( | c | c.to_node == node && c.to_port == port).cloned()
+    }
+
+    /// Adds the connection. An input takes one connection, so the one it had is returned.
+    pub fn connect(&mut self, connection: GraphConnection) -> Option<GraphConnection> {
+        let replaced = self.connection_to(connection.to_node, connection.to_port);
+        self.connections.retain( | c | !(c.to_node == connection.to_node && c.to_port == connection.to_port));
+        self.connections.push(connection);
+        replaced
+    }
+
+    pub fn disconnect(&mut self, connection: &GraphConnection) {
+        self.connections.retain( | c | c != connection);
+    }
+}
+
+/// Decides whether a connection the user drew may be made. It's asked after the port types
+/// were found compatible, so it only needs the rules of the graph at hand, like keeping it acyclic.
+pub type ConnectionValidator = Box<dyn Fn(&NodeGraphData, &GraphConnection) -> bool>;
+
+#[derive(Clone, Debug, DefaultNone)]
+pub enum NodeGraphAction {
+    None,
+    Connected(GraphConnection),
+    Disconnected(GraphConnection),
+    /// The user drew a connection that the port types or the validator didn't allow.
+    ConnectionRejected(GraphConnection),
+    NodesMoved(Vec<u64>),
+    NodesRemoved(Vec<u64>),
+    SelectionChanged(Vec<u64>),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct PortRef {
+    node: u64,
+    port: usize,
+    direction: PortDirection,
+}
+
+enum GraphDrag {
+    Pan {start_pan: DVec2},
+    Nodes {start: Vec<(u64, DVec2)>},
+    Connect {from: PortRef, abs: DVec2},
+    Select {start: DVec2, end: DVec2, additive: bool},
+}
+
+#[derive(Live, LiveHook, Widget)]
+pub struct NodeGraph {
+    #[walk] walk: Walk,
+    #[layout] layout: Layout,
+    #[redraw] #[rust] area: Area,
+
+    #[live] draw_bg: DrawNodeGraphBg,
+    #[live] draw_node: DrawNodeQuad,
+    #[live] draw_port: DrawPortQuad,
+    #[live] draw_wire: DrawLine,
+    #[live] draw_selection: DrawColor,
+    #[live] draw_title: DrawText,
+    #[live] draw_port_name: DrawText,
+
+    #[live(160.0)] node_width: f64,
+    #[live(24.0)] title_height: f64,
+    #[live(20.0)] port_height: f64,
+    #[live(5.0)] port_radius: f64,
+    #[live(2.0)] wire_width: f64,
+    #[live] wire_color: Vec4,
+    #[live(0.1)] min_zoom: f64,
+    #[live(4.0)] max_zoom: f64,
+
+    #[rust] graph: NodeGraphData,
+    #[rust] selected: Vec<u64>,
+    #[rust(1.0)] zoom: f64,
+    #[rust] pan: DVec2,
+    #[rust] drag: Option<GraphDrag>,
+    #[rust] validator: Option<ConnectionValidator>,
+}
+
+impl NodeGraph {
+    fn origin(&self, cx: &Cx) -> DVec2 {
+        self.area.rect(cx).pos
+    }
+
+    fn to_graph(&self, origin: DVec2, abs: DVec2) -> DVec2 {
+        (abs - origin - self.pan) / self.zoom
+    }
+
+    fn to_screen(&self, origin: DVec2, pos: DVec2) -> DVec2 {
+        origin + pos * self.zoom + self.pan
+    }
+
+    fn node_size(&self, node: &GraphNode) -> DVec2 {
+        let rows = node.inputs.len().max(node.outputs.len()) as f64;
+        dvec2(self.node_width, self.title_height + rows * self.port_height + self.port_height * 0.5)
+    }
+
+    /// The center of a port, in graph coordinates.
+    fn port_pos(&self, node: &GraphNode, port: usize, direction: PortDirection) -> DVec2 {
+        let x = match direction {
+            PortDirection::Input => node.x,
+            PortDirection::Output => node.x + self.node_width,
+        };
+        dvec2(x, node.y + self.title_height + (port as f64 + 0.5) * self.port_height)
+    }
+
+    fn port_at(&self, pos: DVec2) -> Option<PortRef> {
+        let radius = self.port_radius * 2.0;
+        for node in self.graph.nodes.iter().rev() {
+            for direction in [PortDirection::Input, PortDirection::Output] {
+                for port in 0..node.ports(direction).len() {
+                    if (self.port_pos(node, port, direction) - pos).length() <= radius {
+                        return Some(PortRef {node: node.id, port, direction})
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    fn node_at(&self, pos: DVec2) -> Option<u64> {
+        self.graph.nodes.iter().rev().find( | node | {
+            Rect {pos: node.pos(), size: self.node_size(node)}.contains(pos)
+        }).map( | node | node.id)
+    }
+
+    /// The connection from `a` to `b`, when they are an output and an input in either order.
+    fn connection_between(a: PortRef, b: PortRef) -> Option<GraphConnection> {
+        let (from, to) = match (a.direction, b.direction) {
+            (PortDirection::Output, PortDirection::Input) => (a, b),
+            (PortDirection::Input, PortDirection::Output) => (b, a),
+            _ => return None
+        };
+        Some(GraphConnection {
+            from_node: from.node,
+            from_port: from.port,
+            to_node: to.node,
+            to_port: to.port
+        })
+    }
+
+    fn accepts(&self, connection: &GraphConnection) -> bool {
+        self.graph.is_valid_connection(connection)
+            && self.validator.as_ref().map_or(true, | validator | validator(&self.graph, connection))
+    }
+
+    fn set_selected(&mut self, cx: &mut Cx, scope: &mut Scope, selected: Vec<u64>) {
+        if selected != self.selected {
+            self.selected = selected;
+            cx.widget_action(self.widget_uid(), &scope.path, NodeGraphAction::SelectionChanged(self.selected.clone()));
+        }
+    }
+
+    fn selection_rect(start: DVec2, end: DVec2) -> Rect {
+        let pos = dvec2(start.x.min(end.x), start.y.min(end.y));
+        Rect {pos, size: dvec2((start.x - end.x).abs(), (start.y - end.y).abs())}
+    }
+
+    fn port_color(port: &GraphPort) -> Vec4 {
+        if port.port_type.is_empty() {
+            return vec4(0.8, 0.8, 0.8, 1.0)
+        }
+        let hue = (LiveId::from_str(&port.port_type).0 % 360) as f32 / 360.0;
+        Vec4::from_hsva(vec4(hue, 0.5, 0.9, 1.0))
+    }
+
+    fn draw_connection(&mut self, cx: &mut Cx2d, from: DVec2, to: DVec2, color: Vec4) {
+        let bend = ((to.x - from.x).abs() * 0.5).max(40.0 * self.zoom);
+        let points = vec![to, to - dvec2(bend, 0.0), from + dvec2(bend, 0.0), from];
+        self.draw_wire.draw_bezier_abs(cx, &points, color, self.wire_width * self.zoom);
+    }
+
+    fn draw_graph(&mut self, cx: &mut Cx2d, rect: Rect) {
+        let origin = rect.pos;
+        self.draw_bg.pan = self.pan.into_vec2();
+        self.draw_bg.zoom = self.zoom as f32;
+        self.draw_bg.draw_abs(cx, rect);
+
+        let wires: Vec<(DVec2, DVec2)> = self.graph.connections.iter().filter_map( | c | {
+            let from = self.graph.node(c.from_node)?;
+            let to = self.graph.node(c.to_node)?;
+            Some((
+                self.to_screen(origin, self.port_pos(from, c.from_port, PortDirection::Output)),
+                self.to_screen(origin, self.port_pos(to, c.to_port, PortDirection::Input))
+            ))
+        }).collect();
+        for (from, to) in wires {
+            self.draw_connection(cx, from, to, self.wire_color);
+        }
+
+        self.draw_title.font_scale = self.zoom;
+        self.draw_port_name.font_scale = self.zoom;
+        let nodes = std::mem::take(&mut self.graph.nodes);
+        for node in &nodes {
+            let pos = self.to_screen(origin, node.pos());
+            self.draw_node.selected = if self.selected.contains(&node.id) {1.0} else {0.0};
+            self.draw_node.title_height = (self.title_height * self.zoom) as f32;
+            let size = self.node_size(node) * self.zoom;
+            self.draw_node.draw_abs(cx, Rect {pos, size});
+            self.draw_title.draw_abs(cx, pos + dvec2(8.0, 6.0) * self.zoom, &node.title);
+
+            for direction in [PortDirection::Input, PortDirection::Output] {
+                for (index, port) in node.ports(direction).iter().enumerate() {
+                    let center = self.to_screen(origin, self.port_pos(node, index, direction));
+                    let connected = self.graph.connections.iter().any( | c | match direction {
+                        PortDirection::Input => c.to_node == node.id && c.to_port == index,
+                        PortDirection::Output => c.from_node == node.id && c.from_port == index,
+                    });
+                    let radius = self.port_radius * self.zoom;
+                    self.draw_port.color = Self::port_color(port);
+                    self.draw_port.connected = if connected {1.0} else {0.0};
+                    self.draw_port.draw_abs(cx, Rect {
+                        pos: center - dvec2(radius, radius),
+                        size: dvec2(radius, radius) * 2.0
+                    });
+                    let label_y = center.y - self.port_height * 0.3 * self.zoom;
+                    let label_pos = match direction {
+                        PortDirection::Input => dvec2(center.x + radius * 2.5, label_y),
+                        // right aligning needs the text width, keep the label in the right half instead
+                        PortDirection::Output => dvec2(center.x - self.node_width * 0.45 * self.zoom, label_y),
+                    };
+                    self.draw_port_name.draw_abs(cx, label_pos, &port.name);
+                }
+            }
+        }
+        self.graph.nodes = nodes;
+
+        match &self.drag {
+            Some(GraphDrag::Connect {from, abs}) => {
+                let (from, abs) = (*from, *abs);
+                if let Some(node) = self.graph.node(from.node) {
+                    let port = self.to_screen(origin, self.port_pos(node, from.port, from.direction));
+                    let (a, b) = match from.direction {
+                        PortDirection::Output => (port, abs),
+                        PortDirection::Input => (abs, port),
+                    };
+                    self.draw_connection(cx, a, b, self.wire_color);
+                }
+            }
+            Some(GraphDrag::Select {start, end, ..}) => {
+                let rect = Self::selection_rect(*start, *end);
+                self.draw_selection.draw_abs(cx, rect);
+            }
+            _ => ()
+        }
+    }
+
+    fn handle_finger_up(&mut self, cx: &mut Cx, scope: &mut Scope, abs: DVec2) {
+        let uid = self.widget_uid();
+        let origin = self.origin(cx);
+        match self.drag.take() {
+            Some(GraphDrag::Connect {from, ..}) => {
+                let target = self.port_at(self.to_graph(origin, abs));
+                if let Some(connection) = target.and_then( | to | Self::connection_between(from, to)) {
+                    if self.accepts(&connection) {
+                        if let Some(replaced) = self.graph.connect(connection) {
+                            cx.widget_action(uid, &scope.path, NodeGraphAction::Disconnected(replaced));
+                        }
+                        cx.widget_action(uid, &scope.path, NodeGraphAction::Connected(connection));
+                    }
+                    else {
+                        cx.widget_action(uid, &scope.path, NodeGraphAction::ConnectionRejected(connection));
+                    }
+                }
+            }
+            Some(GraphDrag::Nodes {start}) => {
+                let moved: Vec<u64> = start.iter().filter( | (id, pos) | {
+                    self.graph.node(*id).map_or(false, | node | node.pos() != *pos)
+                }).map( | (id, _) | *id).collect();
+                if !moved.is_empty() {
+                    cx.widget_action(uid, &scope.path, NodeGraphAction::NodesMoved(moved));
+                }
+            }
+            Some(GraphDrag::Select {start, end, additive}) => {
+                let rect = Self::selection_rect(self.to_graph(origin, start), self.to_graph(origin, end));
+                let mut selected = if additive {self.selected.clone()} else {Vec::new()};
+                for node in &self.graph.nodes {
+                    let node_rect = Rect {pos: node.pos(), size: self.node_size(node)};
+                    if rect.intersects(node_rect) && !selected.contains(&node.id) {
+                        selected.push(node.id);
+                    }
+                }
+                self.set_selected(cx, scope, selected);
+            }
+            _ => ()
+        }
+        self.redraw(cx);
+    }
+}
+
+impl Widget for NodeGraph {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        let uid = self.widget_uid();
+        match event.hits_with_options(cx, self.area, HitOptions::new().with_multi_touch(true)) {
+            Hit::FingerDown(fe) => {
+                cx.set_key_focus(self.area);
+                let origin = self.origin(cx);
+                let pos = self.to_graph(origin, fe.abs);
+                let is_pan = fe.modifiers.alt || fe.device.mouse_button().map_or(false, | button | button != 0);
+                if is_pan {
+                    self.drag = Some(GraphDrag::Pan {start_pan: self.pan});
+                }
+                else if let Some(port) = self.port_at(pos) {
+                    // dragging a connected input picks its connection back up
+                    let existing = if port.direction == PortDirection::Input {
+                        self.graph.connection_to(port.node, port.port)
+                    }
+                    else {
+                        None
+                    };
+                    let from = if let Some(connection) = existing {
+                        self.graph.disconnect(&connection);
+                        cx.widget_action(uid, &scope.path, NodeGraphAction::Disconnected(connection));
+                        PortRef {node: connection.from_node, port: connection.from_port, direction: PortDirection::Output}
+                    }
+                    else {
+                        port
+                    };
+                    self.drag = Some(GraphDrag::Connect {from, abs: fe.abs});
+                }
+                else if let Some(node) = self.node_at(pos) {
+                    let mut selected = self.selected.clone();
+                    if fe.modifiers.shift {
+                        if let Some(index) = selected.iter().position( | id | *id == node) {
+                            selected.remove(index);
+                        }
+                        else {
+                            selected.push(node);
+                        }
+                    }
+                    else if !selected.contains(&node) {
+                        selected = vec![node];
+                    }
+                    self.set_selected(cx, scope, selected);
+                    // bring the node to the front
+                    if let Some(index) = self.graph.nodes.iter().position( | n | n.id == node) {
+                        let node = self.graph.nodes.remove(index);
+                        self.graph.nodes.push(node);
+                    }
+                    let start = self.selected.iter().filter_map( | id | {
+                        self.graph.node(*id).map( | node | (*id, node.pos()))
+                    }).collect();
+                    self.drag = Some(GraphDrag::Nodes {start});
+                }
+                else {
+                    self.drag = Some(GraphDrag::Select {start: fe.abs, end: fe.abs, additive: fe.modifiers.shift});
+                }
+                self.redraw(cx);
+            }
+            Hit::FingerMove(fe) => {
+                let zoom = self.zoom;
+                match &mut self.drag {
+                    Some(GraphDrag::Pan {start_pan}) => {
+                        self.pan = *start_pan + (fe.abs - fe.abs_start);
+                    }
+                    Some(GraphDrag::Nodes {start}) => {
+                        let delta = (fe.abs - fe.abs_start) / zoom;
+                        let start = start.clone();
+                        for (id, pos) in start {
+                            if let Some(node) = self.graph.node_mut(id) {
+                                node.x = pos.x + delta.x;
+                                node.y = pos.y + delta.y;
+                            }
+                        }
+                    }
+                    Some(GraphDrag::Connect {abs, ..}) => {
+                        *abs = fe.abs;
+                    }
+                    Some(GraphDrag::Select {end, ..}) => {
+                        *end = fe.abs;
+                    }
+                    None => ()
+                }
+                self.redraw(cx);
+            }
+            Hit::FingerUp(fe) => {
+                self.handle_finger_up(cx, scope, fe.abs);
+            }
+            Hit::FingerGesture(fg) => {
+                if fg.phase == GesturePhase::Start {
+                    // a second finger came down, undo what the first one started
+                    if let Some(GraphDrag::Nodes {start}) = self.drag.take() {
+                        for (id, pos) in start {
+                            if let Some(node) = self.graph.node_mut(id) {
+                                node.x = pos.x;
+                                node.y = pos.y;
+                            }
+                        }
+                    }
+                    self.drag = None;
+                }
+                let origin = self.origin(cx);
+                let anchor = self.to_graph(origin, fg.abs - fg.pan);
+                self.zoom = (self.zoom * fg.scale).max(self.min_zoom).min(self.max_zoom);
+                // pinch around the point between the fingers and follow it when they move
+                self.pan = fg.abs - origin - anchor * self.zoom;
+                self.redraw(cx);
+            }
+            Hit::FingerScroll(fs) => {
+                let origin = self.origin(cx);
+                let anchor = self.to_graph(origin, fs.abs);
+                let step = fs.scroll.y.abs().min(200.0) / 500.0;
+                let zoom = if fs.scroll.y < 0.0 {self.zoom * (1.0 + step)} else {self.zoom * (1.0 - step)};
+                self.zoom = zoom.max(self.min_zoom).min(self.max_zoom);
+                // keep the point under the finger in place
+                self.pan = fs.abs - origin - anchor * self.zoom;
+                self.redraw(cx);
+            }
+            Hit::KeyDown(ke) => match ke.key_code {
+                KeyCode::Delete | KeyCode::Backspace if !self.selected.is_empty() => {
+                    let removed = std::mem::take(&mut self.selected);
+                    for connection in self.graph.connections.clone() {
+                        if removed.contains(&connection.from_node) || removed.contains(&connection.to_node) {
+                            cx.widget_action(uid, &scope.path, NodeGraphAction::Disconnected(connection));
+                        }
+                    }
+                    self.graph.remove_nodes(&removed);
+                    cx.widget_action(uid, &scope.path, NodeGraphAction::NodesRemoved(removed));
+                    cx.widget_action(uid, &scope.path, NodeGraphAction::SelectionChanged(Vec::new()));
+                    self.redraw(cx);
+                }
+                KeyCode::KeyA if ke.modifiers.control || ke.modifiers.logo => {
+                    let all = self.graph.nodes.iter().map( | node | node.id).collect();
+                    self.set_selected(cx, scope, all);
+                    self.redraw(cx);
+                }
+                _ => ()
+            }
+            _ => ()
+        }
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, _scope: &mut Scope, walk: Walk) -> DrawStep {
+        cx.begin_turtle(walk, self.layout);
+        let rect = cx.turtle().rect();
+        self.draw_graph(cx, rect);
+        cx.end_turtle_with_area(&mut self.area);
+        DrawStep::done()
+    }
+}
+
+impl NodeGraphRef {
+    pub fn set_graph(&self, cx: &mut Cx, graph: NodeGraphData) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.selected.retain( | id | graph.node(*id).is_some());
+            inner.graph = graph;
+            inner.redraw(cx);
+        }
+    }
+
+    pub fn graph(&self) -> Option<NodeGraphData> {
+        self.borrow().map( | inner | inner.graph.clone())
+    }
+
+    /// Changes the graph in place and redraws it.
+    pub fn update_graph(&self, cx: &mut Cx, f: impl FnOnce(&mut NodeGraphData)) {
+        if let Some(mut inner) = self.borrow_mut() {
+            f(&mut inner.graph);
+            inner.redraw(cx);
+        }
+    }
+
+    pub fn set_connection_validator(&self, validator: ConnectionValidator) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.validator = Some(validator);
+        }
+    }
+
+    pub fn selected(&self) -> Vec<u64> {
+        self.borrow().map_or(Vec::new(), | inner | inner.selected.clone())
+    }
+
+    /// The graph as RON, for saving.
+    pub fn to_ron(&self) -> Option<String> {
+        self.borrow().map( | inner | inner.graph.serialize_ron())
+    }
+
+    pub fn load_ron(&self, cx: &mut Cx, ron: &str) -> Result<(), DeRonErr> {
+        let graph = NodeGraphData::deserialize_ron(ron) ?;
+        self.set_graph(cx, graph);
+        Ok(())
+    }
+
+    pub fn connected(&self, actions: &Actions) -> Option<GraphConnection> {
+        if let NodeGraphAction::Connected(connection) = actions.find_widget_action(self.widget_uid()).cast() {
+            return Some(connection)
+        }
+        None
+    }
+
+    pub fn disconnected(&self, actions: &Actions) -> Option<GraphConnection> {
+        if let NodeGraphAction::Disconnected(connection) = actions.find_widget_action(self.widget_uid()).cast() {
+            return Some(connection)
+        }
+        None
+    }
+
+    pub fn selection_changed(&self, actions: &Actions) -> Option<Vec<u64>> {
+        if let NodeGraphAction::SelectionChanged(selected) = actions.find_widget_action(self.widget_uid()).cast() {
+            return Some(selected)
+        }
+        None
+    }
+}
@@ -52,7 +52,9 @@ impl LiveHook for PopupMenu {
                 node.apply(cx, apply, index, nodes);
             }
         }
-        self.draw_list.redraw(cx);
+        if apply.any_changed() {
+            self.draw_list.redraw(cx);
+        }
     }
 }
 
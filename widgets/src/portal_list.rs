@@ -78,6 +78,8 @@ pub struct PortalList {
     
     #[rust] templates: ComponentMap<LiveId, LivePtr>,
     #[rust] items: ComponentMap<(usize, LiveId), WidgetRef>,
+    /// Items that scrolled out of view, handed out again for the items scrolling into view.
+    #[rust] pool: ComponentPool<LiveId, WidgetRef>,
     //#[rust(DragState::None)] drag_state: DragState,
     #[rust(ScrollState::Stopped)] scroll_state: ScrollState
 }
@@ -93,6 +95,7 @@ impl LiveHook for PortalList {
     fn before_apply(&mut self, _cx: &mut Cx, apply: &mut Apply, _index: usize, _nodes: &[LiveNode]) {
         if let ApplyFrom::UpdateFromDoc {..} = apply.from {
             self.templates.clear();
+            self.pool.clear();
         }
     }
     
@@ -295,7 +298,8 @@ impl PortalList {
             }
         }        
         if !self.keep_invisible{
-            self.items.retain_visible();
+            let pool = &mut self.pool;
+            self.items.retain_visible_into( | _, _ | false, | (_, template), item | pool.put(cx, template, item));
         }
 
         cx.end_turtle_with_area(&mut self.area);
@@ -482,6 +486,9 @@ impl PortalList {
     ///
     /// If you care whether the widget already existed or not, use [`PortalList::item_with_existed()`] instead.
     ///
+    /// New widgets come from the items that scrolled out of view when there are any,
+    /// reset to the `template` with [`Widget::recycle()`], so set all of their content.
+    ///
     /// ## Return
     /// * If a widget already existed for the given `entry_id`, this returns a reference to that widget.
     /// * If a new widget was created successfully, this returns a reference to that new widget.
@@ -503,9 +510,10 @@ impl PortalList {
     pub fn item_with_existed(&mut self, cx: &mut Cx, entry_id: usize, template: LiveId) -> Option<(WidgetRef, bool)> {
         if let Some(ptr) = self.templates.get(&template) {
             let mut already_existed = true;
+            let pool = &mut self.pool;
             let entry = self.items.get_or_insert(cx, (entry_id, template), | cx | {
                 already_existed = false;
-                WidgetRef::new_from_ptr(cx, Some(*ptr))
+                pool.take_or_new(cx, template, Some(*ptr))
             });
             Some((entry.clone(), already_existed))
         } else {
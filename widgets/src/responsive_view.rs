@@ -0,0 +1,142 @@
+use crate::{
+    widget::*,
+    makepad_derive_widget::*,
+    makepad_draw::*,
+};
+
+live_design!{
+    ResponsiveViewBase = {{ResponsiveView}} {}
+}
+
+/// Picks one of its children by the width it gets to draw in, so a layout can change with the
+/// size of the screen. The children are the `compact`, `medium` and `wide` variants of the
+/// content; a missing one falls back to the next smaller one that is there. The width is checked
+/// on every draw, which is what lets the designer preview the same DSL at several sizes.
+#[derive(Live, LiveRegisterWidget, WidgetRef, WidgetSet)]
+pub struct ResponsiveView {
+    #[rust] area: Area,
+    #[walk] walk: Walk,
+    #[layout] layout: Layout,
+    /// The width from which the `medium` variant is used.
+    #[live(600.0)] medium_width: f64,
+    /// The width from which the `wide` variant is used.
+    #[live(1024.0)] wide_width: f64,
+    #[rust] active: LiveId,
+    #[rust] draw_state: DrawStateWrap<Walk>,
+    #[rust] pointers: ComponentMap<LiveId, LivePtr>,
+    #[rust] variants: ComponentMap<LiveId, WidgetRef>,
+}
+
+impl LiveHook for ResponsiveView {
+    fn before_apply(&mut self, _cx: &mut Cx, apply: &mut Apply, _index: usize, _nodes: &[LiveNode]) {
+        if let ApplyFrom::UpdateFromDoc {..} = apply.from {
+            self.pointers.clear();
+        }
+    }
+
+    fn apply_value_instance(&mut self, cx: &mut Cx, apply: &mut Apply, index: usize, nodes: &[LiveNode]) -> usize {
+        let id = nodes[index].id;
+        match apply.from {
+            ApplyFrom::NewFromDoc {file_id} | ApplyFrom::UpdateFromDoc {file_id,..} => {
+                if nodes[index].origin.has_prop_type(LivePropType::Instance) {
+                    let live_ptr = cx.live_registry.borrow().file_id_index_to_live_ptr(file_id, index);
+                    self.pointers.insert(id, live_ptr);
+                    if let Some(node) = self.variants.get_mut(&id) {
+                        node.apply(cx, apply, index, nodes);
+                    }
+                }
+                else {
+                    cx.apply_error_no_matching_field(live_error_origin!(), index, nodes);
+                }
+            }
+            _ => ()
+        }
+        nodes.skip_node(index)
+    }
+}
+
+impl ResponsiveView {
+    /// The variant to use at a width.
+    pub fn variant_for_width(&self, width: f64) -> Option<LiveId> {
+        let order = [live_id!(compact), live_id!(medium), live_id!(wide)];
+        let fits = if width >= self.wide_width {3} else if width >= self.medium_width {2} else {1};
+        order[..fits].iter().rev()
+            .chain(order[fits..].iter())
+            .find( | id | self.pointers.get(id).is_some())
+            .copied()
+    }
+
+    fn variant(&mut self, cx: &mut Cx, id: LiveId) -> Option<WidgetRef> {
+        let ptr = *self.pointers.get(&id) ?;
+        Some(self.variants.get_or_insert(cx, id, | cx | {
+            WidgetRef::new_from_ptr(cx, Some(ptr))
+        }).clone())
+    }
+}
+
+impl WidgetNode for ResponsiveView {
+    fn walk(&mut self, _cx: &mut Cx) -> Walk {
+        self.walk
+    }
+
+    fn redraw(&mut self, cx: &mut Cx) {
+        self.area.redraw(cx)
+    }
+
+    fn find_widgets(&mut self, path: &[LiveId], cached: WidgetCache, results: &mut WidgetSet) {
+        if let Some(variant) = self.variants.get_mut(&path[0]) {
+            if path.len() == 1 {
+                results.push(variant.clone());
+            }
+            else {
+                variant.find_widgets(&path[1..], cached, results);
+            }
+        }
+        for variant in self.variants.values_mut() {
+            variant.find_widgets(path, cached, results);
+        }
+    }
+}
+
+impl Widget for ResponsiveView {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        let uid = self.widget_uid();
+        if let Some(variant) = self.variants.get_mut(&self.active) {
+            let item_uid = variant.widget_uid();
+            cx.group_widget_actions(uid, item_uid, | cx | {
+                variant.handle_event(cx, event, scope)
+            });
+        }
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        if self.draw_state.begin(cx, walk) {
+            let turtle = cx.turtle();
+            let mut width = turtle.eval_width(walk.width, walk.margin, turtle.layout().flow);
+            if width.is_nan() {
+                width = turtle.width_left();
+            }
+            self.active = self.variant_for_width(width).unwrap_or(LiveId(0));
+            cx.begin_turtle(walk, self.layout);
+        }
+        if self.draw_state.get().is_some() {
+            if let Some(variant) = self.variant(cx, self.active) {
+                let walk = variant.walk(cx);
+                variant.draw_walk(cx, scope, walk) ?;
+            }
+            self.draw_state.end();
+            cx.end_turtle_with_area(&mut self.area);
+        }
+        DrawStep::done()
+    }
+}
+
+impl ResponsiveViewRef {
+    /// The variant that was drawn last.
+    pub fn active_variant(&self) -> Option<LiveId> {
+        self.borrow().map( | inner | inner.active)
+    }
+}
+
+impl ResponsiveViewSet {
+}
@@ -18,7 +18,7 @@ pub struct DrawSplitter {
     #[live] is_vertical: f32,
 }
 
-#[derive(Copy, Clone, Debug, Live, LiveHook, SerRon, DeRon)]
+#[derive(Copy, Clone, Debug, PartialEq, Live, LiveHook, SerRon, DeRon)]
 #[live_ignore]
 pub enum SplitterAxis {
     #[pick] Horizontal,
@@ -253,6 +253,16 @@ impl Splitter {
         self.align = align;
     }
     
+    /// Where the split is as a fraction of the splitter, as it was last drawn. Unlike `align`
+    /// this is also a fraction for the aligns that keep a distance to one side.
+    pub fn weight(&self) -> f64 {
+        let size = match self.axis {
+            SplitterAxis::Horizontal => self.rect.size.x,
+            SplitterAxis::Vertical => self.rect.size.y,
+        };
+        if size > 0.0 {self.position / size} else {0.5}
+    }
+    
     fn margin(&self) -> Margin {
         match self.axis {
             SplitterAxis::Horizontal => Margin {
@@ -13,6 +13,8 @@ live_design!{
 pub struct Tab {
     #[rust] is_selected: bool,
     #[rust] is_dragging: bool,
+    /// Whether the tab was dragged out of its bar, after which its moves aren't reported.
+    #[rust] is_dragged_out: bool,
     
     #[live] draw_bg: DrawQuad,
     #[live] draw_icon: DrawIcon,
@@ -39,7 +41,8 @@ pub struct Tab {
 pub enum TabAction {
     WasPressed,
     CloseWasPressed,
-    ShouldTabStartDrag,
+    /// The tab is dragged further than `min_drag_dist`, and the finger is now at this position.
+    WasDragged(DVec2),
     ShouldTabStopDrag
     //DragHit(DragHit)
 }
@@ -79,6 +82,10 @@ impl Tab {
         self.draw_bg.area()
     }
     
+    pub fn set_dragged_out(&mut self) {
+        self.is_dragged_out = true;
+    }
+    
     pub fn handle_event_with(
         &mut self,
         cx: &mut Cx,
@@ -105,7 +112,9 @@ impl Tab {
             Hit::FingerMove(e) => {
                 if !self.is_dragging && (e.abs - e.abs_start).length() > self.min_drag_dist {
                     self.is_dragging = true;
-                    dispatch_action(cx, TabAction::ShouldTabStartDrag);
+                }
+                if self.is_dragging && !self.is_dragged_out {
+                    dispatch_action(cx, TabAction::WasDragged(e.abs));
                 }
             }
             Hit::FingerUp(_) => {
@@ -113,8 +122,10 @@ impl Tab {
                     dispatch_action(cx, TabAction::ShouldTabStopDrag);
                     self.is_dragging = false;
                 }
+                self.is_dragged_out = false;
             }
             Hit::FingerDown(_) => {
+                self.is_dragged_out = false;
                 dispatch_action(cx, TabAction::WasPressed);
             }
             _ => {}
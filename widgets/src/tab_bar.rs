@@ -21,11 +21,38 @@ pub struct TabBar {
     #[live] draw_fill: DrawColor,
     #[walk] walk: Walk,
     
+    #[live] draw_filter: DrawColor,
+    #[live] draw_filter_text: DrawText,
+    #[live] filter_walk: Walk,
+    #[live] filter_layout: Layout,
+    /// Tab bars with at least this many tabs get a box to filter their tabs by name.
+    #[live(12usize)] filter_min_tabs: usize,
+    #[rust] filter: String,
+    #[rust] tab_count: usize,
+    #[rust] filter_shown: bool,
+    
+    #[live] draw_overflow_button: DrawQuad,
+    #[live(24.0)] overflow_button_width: f64,
+    #[live] overflow_menu: DrawList2d,
+    #[live] draw_overflow_menu: DrawColor,
+    #[live] draw_overflow_hover: DrawColor,
+    #[live] draw_overflow_item: DrawText,
+    #[live(200.0)] overflow_menu_width: f64,
+    #[live(24.0)] overflow_item_height: f64,
+    /// Whether the tabs didn't fit the last time they were drawn, which shows the button that
+    /// opens a menu of all of them.
+    #[rust] overflowing: bool,
+    #[rust] overflow_open: bool,
+    #[rust] overflow_hover: Option<usize>,
+    /// A tab picked from the overflow menu, scrolled into view when it is drawn.
+    #[rust] reveal_tab: Option<LiveId>,
+    
     #[rust] draw_state: DrawStateWrap<()>,
     
     #[rust] view_area: Area,
 
     #[rust] tab_order: Vec<LiveId>,
+    #[rust] tab_names: Vec<String>,
     
     #[rust] is_dragged: bool,
     
@@ -81,9 +108,46 @@ impl Widget for TabBar{
             self.view_area.redraw(cx);
         };
                 
+        match event.hits(cx, self.draw_filter.area()) {
+            Hit::FingerDown(_) => {
+                cx.set_key_focus(self.draw_filter.area());
+                self.draw_filter.redraw(cx);
+            }
+            Hit::KeyFocus(_) | Hit::KeyFocusLost(_) => {
+                self.draw_filter.redraw(cx);
+            }
+            Hit::TextInput(te) => {
+                self.filter.push_str(&te.input);
+                cx.widget_action(uid, &scope.path, TabBarAction::FilterChanged(self.filter.clone()));
+            }
+            Hit::KeyDown(ke) => match ke.key_code {
+                KeyCode::Backspace => {
+                    self.filter.pop();
+                    cx.widget_action(uid, &scope.path, TabBarAction::FilterChanged(self.filter.clone()));
+                }
+                KeyCode::Escape => {
+                    self.filter.clear();
+                    cx.revert_key_focus();
+                    cx.widget_action(uid, &scope.path, TabBarAction::FilterChanged(self.filter.clone()));
+                }
+                _ => ()
+            }
+            _ => ()
+        }
+        
+        if let Hit::FingerDown(_) = event.hits(cx, self.draw_overflow_button.area()) {
+            self.overflow_open = !self.overflow_open;
+            self.overflow_hover = None;
+            self.scroll_bars.redraw(cx);
+        }
+        if self.overflow_open {
+            self.handle_overflow_menu_event(cx, event, scope);
+        }
+        
         if let Some(tab_id) = self.next_selected_tab_id.take() {
             cx.widget_action(uid, &scope.path, TabBarAction::TabWasPressed(tab_id));
         }
+        let mut dragged_tab = None;
         for (tab_id, (tab,_)) in self.tabs.iter_mut() {
             tab.handle_event_with(cx, event, &mut | cx, action | match action {
                 TabAction::WasPressed => {
@@ -92,8 +156,8 @@ impl Widget for TabBar{
                 TabAction::CloseWasPressed => {
                     cx.widget_action(uid, &scope.path, TabBarAction::TabCloseWasPressed(*tab_id));
                 }
-                TabAction::ShouldTabStartDrag=>{
-                    cx.widget_action(uid, &scope.path, TabBarAction::ShouldTabStartDrag(*tab_id));
+                TabAction::WasDragged(abs)=>{
+                    dragged_tab = Some((*tab_id, abs));
                 }
                 TabAction::ShouldTabStopDrag=>{
                 }/*
@@ -102,6 +166,9 @@ impl Widget for TabBar{
                 }*/
             });
         }
+        if let Some((tab_id, abs)) = dragged_tab {
+            self.handle_tab_drag(cx, scope, tab_id, abs);
+        }
         /*
         match event.drag_hits(cx, self.scroll_bars.area()) {
             DragHit::NoHit=>(),
@@ -153,8 +220,37 @@ impl TabBar {
         //if selected_tab.is_some(){
         //    self.selected_tab_id = None
         // }
-        self.scroll_bars.begin(cx, walk, Layout::flow_right());
+        self.filter_shown = self.tab_count >= self.filter_min_tabs || !self.filter.is_empty();
+        if self.filter_shown || self.overflowing {
+            // the filter box and the overflow button stay put while the tabs scroll
+            cx.begin_turtle(walk, Layout::flow_right());
+            if self.filter_shown {
+                self.draw_filter_box(cx);
+            }
+            let margin_right = if self.overflowing {self.overflow_button_width} else {0.0};
+            self.scroll_bars.begin(cx, Walk::fill().with_margin_right(margin_right), Layout::flow_right());
+        }
+        else {
+            self.scroll_bars.begin(cx, walk, Layout::flow_right());
+        }
         self.tab_order.clear();
+        self.tab_names.clear();
+        cx.begin_access_group(LiveId(self as *const _ as u64), AccessRole::TabList, "");
+    }
+    
+    fn draw_filter_box(&mut self, cx: &mut Cx2d) {
+        self.draw_filter.begin(cx, self.filter_walk, self.filter_layout);
+        let text = if cx.has_key_focus(self.draw_filter.area()) {
+            format!("{}|", self.filter)
+        }
+        else if self.filter.is_empty() {
+            "Filter tabs".to_string()
+        }
+        else {
+            self.filter.clone()
+        };
+        self.draw_filter_text.draw_walk(cx, Walk::fit(), Align::default(), &text);
+        self.draw_filter.end(cx);
     }
     
     pub fn end(&mut self, cx: &mut Cx2d) {
@@ -171,9 +267,142 @@ impl TabBar {
         self.tabs.retain_visible();
         self.draw_fill.draw_walk(cx, Walk::size(Size::Fill, Size::Fill));
         self.scroll_bars.end(cx);
+        cx.end_access_group(self.scroll_bars.area());
+        if self.filter_shown || self.overflowing {
+            if self.overflowing {
+                self.draw_overflow(cx);
+            }
+            cx.end_turtle();
+        }
+        // whether the button is needed is only known now, so it shows from the next draw on
+        let overflowing = self.scroll_bars.get_scroll_view_total().x > self.scroll_bars.get_scroll_view_visible().x + 0.5;
+        if overflowing != self.overflowing {
+            self.overflowing = overflowing;
+            self.overflow_open &= overflowing;
+            self.scroll_bars.redraw(cx);
+        }
+    }
+    
+    /// Draws the button at the end of the tab bar, and the menu of all tabs when it is open.
+    fn draw_overflow(&mut self, cx: &mut Cx2d) {
+        let rect = cx.turtle().rect();
+        let button_rect = Rect {
+            pos: dvec2(rect.pos.x + rect.size.x - self.overflow_button_width, rect.pos.y),
+            size: dvec2(self.overflow_button_width, rect.size.y)
+        };
+        self.draw_overflow_button.draw_abs(cx, button_rect);
+        if !self.overflow_open {
+            return
+        }
+        self.overflow_menu.begin_overlay_reuse(cx);
+        cx.begin_pass_sized_turtle(Layout::flow_down());
+        let menu_pos = button_rect.pos + dvec2(button_rect.size.x - self.overflow_menu_width, button_rect.size.y);
+        self.draw_overflow_menu.draw_abs(cx, Rect {
+            pos: menu_pos,
+            size: dvec2(self.overflow_menu_width, self.overflow_item_height * self.tab_names.len() as f64)
+        });
+        for (index, name) in self.tab_names.iter().enumerate() {
+            let row = Rect {
+                pos: menu_pos + dvec2(0.0, index as f64 * self.overflow_item_height),
+                size: dvec2(self.overflow_menu_width, self.overflow_item_height)
+            };
+            if self.overflow_hover == Some(index) {
+                self.draw_overflow_hover.draw_abs(cx, row);
+            }
+            cx.begin_turtle(Walk::abs_rect(row), Layout::flow_right().with_align_y(0.5).with_padding_left(8.0));
+            self.draw_overflow_item.draw_walk(cx, Walk::fit(), Align::default(), name);
+            cx.end_turtle();
+        }
+        cx.end_pass_sized_turtle();
+        self.overflow_menu.end(cx);
+    }
+    
+    /// The row of the overflow menu at `abs`, which is also the index of its tab in `tab_order`.
+    fn overflow_row(&self, cx: &Cx, abs: DVec2) -> Option<usize> {
+        let rect = self.draw_overflow_menu.area().rect(cx);
+        if !rect.contains(abs) {
+            return None
+        }
+        let index = ((abs.y - rect.pos.y) / self.overflow_item_height) as usize;
+        if index < self.tab_order.len() {Some(index)} else {None}
+    }
+    
+    fn handle_overflow_menu_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        let uid = self.widget_uid();
+        match event.hits(cx, self.draw_overflow_menu.area()) {
+            Hit::FingerHoverIn(fe) | Hit::FingerHoverOver(fe) => {
+                let hover = self.overflow_row(cx, fe.abs);
+                if hover != self.overflow_hover {
+                    self.overflow_hover = hover;
+                    self.scroll_bars.redraw(cx);
+                }
+            }
+            Hit::FingerHoverOut(_) => {
+                self.overflow_hover = None;
+                self.scroll_bars.redraw(cx);
+            }
+            Hit::FingerDown(fe) => {
+                if let Some(tab_id) = self.overflow_row(cx, fe.abs).map( | index | self.tab_order[index]) {
+                    self.overflow_open = false;
+                    self.reveal_tab = Some(tab_id);
+                    self.scroll_bars.redraw(cx);
+                    cx.widget_action(uid, &scope.path, TabBarAction::TabWasPressed(tab_id));
+                }
+            }
+            _ => ()
+        }
+        // a press anywhere else closes the menu
+        if let Event::MouseDown(e) = event {
+            let is_inside = self.draw_overflow_menu.area().rect(cx).contains(e.abs)
+                || self.draw_overflow_button.area().rect(cx).contains(e.abs);
+            if !is_inside {
+                self.overflow_open = false;
+                self.scroll_bars.redraw(cx);
+            }
+        }
+    }
+    
+    /// Moves a dragged tab to where it is over another tab of the bar, or starts dragging it
+    /// out of the bar once it leaves it, to be dropped elsewhere in the dock.
+    fn handle_tab_drag(&mut self, cx: &mut Cx, scope: &mut Scope, tab_id: LiveId, abs: DVec2) {
+        let uid = self.widget_uid();
+        let rect = self.scroll_bars.area().rect(cx);
+        if abs.y < rect.pos.y || abs.y > rect.pos.y + rect.size.y {
+            if let Some((tab, _)) = self.tabs.get_mut(&tab_id) {
+                tab.set_dragged_out();
+            }
+            cx.widget_action(uid, &scope.path, TabBarAction::ShouldTabStartDrag(tab_id));
+            return
+        }
+        let from = self.tab_order.iter().position( | id | *id == tab_id);
+        if let (Some(from), Some((target_id, target_rect))) = (from, self.is_over_tab(cx, abs)) {
+            let to = self.tab_order.iter().position( | id | *id == target_id);
+            // only past the middle of the other tab, so tabs of different widths don't swap back
+            let center = target_rect.pos.x + target_rect.size.x / 2.0;
+            let is_past = match to {
+                Some(to) if to > from => abs.x > center,
+                Some(to) if to < from => abs.x < center,
+                _ => false
+            };
+            if is_past {
+                cx.widget_action(uid, &scope.path, TabBarAction::TabWasMoved {tab_id, target_id});
+            }
+        }
+    }
+    
+    /// How many tabs the tab bar has, including the ones its filter hides. Decides whether it
+    /// shows the filter box, so it is set before drawing.
+    pub fn set_tab_count(&mut self, tab_count: usize) {
+        self.tab_count = tab_count;
+    }
+    
+    /// What the tabs are filtered by, see `fuzzy_match`.
+    pub fn filter(&self) -> &str {
+        &self.filter
     }
     
     pub fn draw_tab(&mut self, cx: &mut Cx2d, tab_id: LiveId, name: &str, template:LiveId) {
+        self.tab_names.push(name.to_string());
         if let Some(selected_tab) = self.selected_tab {
             let tab_order_len = self.tab_order.len();
             let tab = self.get_or_create_tab(cx, tab_id, template);
@@ -194,6 +423,13 @@ impl TabBar {
             let tab = self.get_or_create_tab(cx, tab_id, template);
             tab.draw(cx, name);
         }
+        let (tab, _) = &self.tabs[tab_id];
+        cx.access_node(AccessNode::new(tab_id, tab.area(), AccessRole::Tab).with_name(name).with_selected(tab.is_selected()));
+        if self.reveal_tab == Some(tab_id) {
+            self.reveal_tab = None;
+            let rect = self.tabs[tab_id].0.area().rect(cx);
+            self.scroll_bars.scroll_into_view_abs(cx, rect);
+        }
     }
     
     fn get_or_create_tab(&mut self, cx: &mut Cx, tab_id: LiveId, template:LiveId) -> &mut Tab {
@@ -275,7 +511,17 @@ pub enum TabBarAction {
     TabWasPressed(LiveId),
     ShouldTabStartDrag(LiveId),
     TabCloseWasPressed(LiveId),
+    /// A tab was dragged past `target_id`, and wants to take its place.
+    TabWasMoved {tab_id: LiveId, target_id: LiveId},
+    FilterChanged(String),
     None
     //DragHitTab(DragHit, LiveId),
     //DragHitTabBar(DragHit)
 }
+
+/// Whether the characters of `filter` appear in `name` in order, ignoring case, like `dkrs` in
+/// `dock.rs`.
+pub fn fuzzy_match(filter: &str, name: &str) -> bool {
+    let mut chars = name.chars().flat_map( | c | c.to_lowercase());
+    filter.chars().flat_map( | c | c.to_lowercase()).all( | f | chars.any( | c | c == f))
+}
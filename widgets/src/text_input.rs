@@ -2,6 +2,7 @@ use {
     crate::{
         makepad_derive_widget::*,
         makepad_draw::*,
+        emoji_picker::*,
         widget::*,
     }
 };
@@ -35,7 +36,7 @@ pub struct DrawLabel {
     #[live] is_empty: f32,
 }
 
-#[derive(Live, LiveHook, Widget)]
+#[derive(Live, Widget)]
 pub struct TextInput {
     #[animator] animator: Animator,
     
@@ -63,6 +64,8 @@ pub struct TextInput {
     
     #[live] pub text: String,
     #[live] ascii_only: bool,
+    #[live] emoji_picker: Option<LivePtr>,
+    #[rust] emoji_picker_open: bool,
     #[rust] double_tap_start: Option<(usize, usize)>,
     #[rust] undo_id: u64,
     
@@ -73,14 +76,50 @@ pub struct TextInput {
     #[rust] cursor_head: usize
 }
 
+impl LiveHook for TextInput {
+    fn after_apply(&mut self, cx: &mut Cx, apply: &mut Apply, _index: usize, _nodes: &[LiveNode]) {
+        EmojiPickerGlobal::register(cx, apply, self.emoji_picker);
+    }
+}
+
 impl Widget for TextInput {
     fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
         let uid = self.widget_uid();
         if self.animator_handle_event(cx, event).must_redraw() {
             self.draw_bg.redraw(cx);
         }
+        if self.emoji_picker_open {
+            let global = cx.global::<EmojiPickerGlobal>().clone();
+            let mut map = global.map.borrow_mut();
+            let picker = map.get_mut(&self.emoji_picker.unwrap()).unwrap();
+            let mut picked = None;
+            let mut close = false;
+            picker.handle_event_with(cx, event, self.draw_bg.area(), &mut | _, action | {
+                match action {
+                    EmojiPickerAction::Selected(symbol) => picked = Some(symbol),
+                    EmojiPickerAction::Closed => close = true,
+                    _ => ()
+                }
+            });
+            if let Some(symbol) = picked {
+                self.undo_id += 1;
+                self.create_undo(UndoGroup::TextInput(self.undo_id));
+                if self.change(cx, &symbol){self.push_change_action(uid, scope, cx)}
+                close = true;
+            }
+            if close {
+                self.set_emoji_picker_closed(cx);
+            }
+            // the picker gets the key input while it is open
+            if cx.has_key_focus(self.draw_bg.area()) {
+                if let Event::KeyDown(_) | Event::KeyUp(_) | Event::TextInput(_) = event {
+                    return
+                }
+            }
+        }
         match event.hits(cx, self.draw_bg.area()) {
             Hit::KeyFocusLost(_) => {
+                self.set_emoji_picker_closed(cx);
                 self.animator_play(cx, id!(focus.off));
                 cx.hide_text_ime();
                 //cx.widget_action(uid, &scope.path, TextInputAction::Return(self.text.clone()));
@@ -132,7 +171,9 @@ impl Widget for TextInput {
                 }
             }
             Hit::KeyDown(ke) => match ke.key_code {
-                                
+                _ if is_emoji_picker_key(&ke) => {
+                    self.set_emoji_picker_open(cx);
+                }
                 KeyCode::Tab => {
                     // dispatch_action(cx, self, TextInputAction::Tab(key.mod_shift));
                 }
@@ -353,6 +394,17 @@ impl Widget for TextInput {
     fn set_text(&mut self, v: &str) {
         self.filter_input(&v, None);
     }
+    
+    fn recycle(&mut self, cx: &mut Cx) {
+        self.recycle_node(cx);
+        self.emoji_picker_open = false;
+        self.double_tap_start = None;
+        self.last_undo = None;
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+        self.cursor_tail = 0;
+        self.cursor_head = 0;
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, DefaultNone)]
@@ -528,6 +580,24 @@ impl TextInput {
         cx.set_key_focus(self.draw_bg.area());
     }
     
+    pub fn set_emoji_picker_open(&mut self, cx: &mut Cx) {
+        if self.emoji_picker.is_none() || self.read_only || self.numeric_only || self.ascii_only {
+            return
+        }
+        let global = cx.global::<EmojiPickerGlobal>().clone();
+        let mut map = global.map.borrow_mut();
+        map.get_mut(&self.emoji_picker.unwrap()).unwrap().open(cx);
+        self.emoji_picker_open = true;
+        self.draw_bg.redraw(cx);
+    }
+    
+    pub fn set_emoji_picker_closed(&mut self, cx: &mut Cx) {
+        if self.emoji_picker_open {
+            self.emoji_picker_open = false;
+            self.draw_bg.redraw(cx);
+        }
+    }
+    
     pub fn filter_input(&mut self, input: &str, output: Option<&mut String>) {
         let output = if let Some(output) = output {
             output
@@ -627,6 +697,24 @@ impl TextInput {
             }
         }
         self.draw_bg.end(cx);
+        // a secret field only says how long it is
+        let value = if self.secret {"•".repeat(self.text.chars().count())} else {self.text.clone()};
+        cx.access_node(
+            AccessNode::new(LiveId(self.widget_uid().0), self.draw_bg.area(), AccessRole::TextInput)
+                .with_name(&self.empty_message)
+                .with_value(&value)
+        );
+        
+        if self.emoji_picker_open {
+            let global = cx.global::<EmojiPickerGlobal>().clone();
+            let mut map = global.map.borrow_mut();
+            let picker = map.get_mut(&self.emoji_picker.unwrap()).unwrap();
+            let caret = Rect {
+                pos: dvec2(head.x, head.y - top_drop) - turtle_rect.pos,
+                size: dvec2(self.cursor_size, line_spacing)
+            };
+            picker.draw_at_caret(cx, self.draw_bg.area(), caret);
+        }
         
         if  cx.has_key_focus(self.draw_bg.area()) {
             // ok so. if we have the IME we should inject a tracking point
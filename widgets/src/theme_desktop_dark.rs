@@ -1657,91 +1657,6 @@ live_design! {
         }
     }
 
-    WindowMenu = <WindowMenuBase> { height: 0, width: 0, }
-
-    Window = <WindowBase> {
-        pass: { clear_color: (THEME_COLOR_BG_APP) }
-        flow: Down
-        nav_control: <NavControl> {}
-        caption_bar = <SolidView> {
-            visible: false,
-
-            flow: Right
-
-            draw_bg: {color: (THEME_COLOR_APP_CAPTION_BAR)}
-            height: 27,
-            caption_label = <View> {
-                width: Fill, height: Fill,
-                align: {x: 0.5, y: 0.5},
-                label = <Label> {text: "Makepad", margin: {left: 100}}
-            }
-            windows_buttons = <View> {
-                visible: false,
-                width: Fit, height: Fit,
-                min = <DesktopButton> {draw_bg: {button_type: WindowsMin}}
-                max = <DesktopButton> {draw_bg: {button_type: WindowsMax}}
-                close = <DesktopButton> {draw_bg: {button_type: WindowsClose}}
-            }
-            web_fullscreen = <View> {
-                visible: false,
-                width: Fit, height: Fit,
-                fullscreen = <DesktopButton> {draw_bg: {button_type: Fullscreen}}
-            }
-            web_xr = <View> {
-                visible: false,
-                width: Fit, height: Fit,
-                xr_on = <DesktopButton> {draw_bg: {button_type: XRMode}}
-            }
-        }
-
-        window_menu = <WindowMenu> {
-            main = Main{items:[app]}
-            app = Sub { name:"Makepad", items:[quit] }
-            quit = Item {
-                name:"Quit",
-                shift: false,
-                key: KeyQ,
-                enabled: true
-            }
-        }
-        body = <KeyboardView> {
-            width: Fill, height: Fill,
-            keyboard_min_shift: 30,
-        }
-
-        cursor: Default
-        mouse_cursor_size: vec2(20, 20),
-        draw_cursor: {
-            instance border_width: 1.5
-            instance color: (THEME_COLOR_CURSOR_BG)
-            instance border_color: (THEME_COLOR_CURSOR_BORDER)
-
-            fn get_color(self) -> vec4 {
-                return self.color
-            }
-
-            fn get_border_color(self) -> vec4 {
-                return self.border_color
-            }
-
-            fn pixel(self) -> vec4 {
-                let sdf = Sdf2d::viewport(self.pos * self.rect_size)
-                sdf.move_to(1.0, 1.0);
-                sdf.line_to(self.rect_size.x - 1.0, self.rect_size.y * 0.5)
-                sdf.line_to(self.rect_size.x * 0.5, self.rect_size.y - 1.0)
-                sdf.close_path();
-                sdf.fill_keep(self.get_color())
-                if self.border_width > 0.0 {
-                    sdf.stroke(self.get_border_color(), self.border_width)
-                }
-                return sdf.result
-            }
-        }
-        window: {
-            inner_size: vec2(1024, 768)
-        }
-    }
-
     Splitter = <SplitterBase> {
         draw_splitter: {
             uniform border_radius: 1.0
@@ -1977,6 +1892,35 @@ live_design! {
         draw_fill: {
             color: (THEME_COLOR_D_1)
         }
+        draw_filter: {
+            color: (THEME_COLOR_D_2)
+        }
+        draw_filter_text: {
+            text_style: <THEME_FONT_REGULAR> {}
+            color: (THEME_COLOR_TEXT_INACTIVE)
+        }
+        filter_walk: {width: 120, height: Fill, margin: {right: (THEME_SPACE_1)}}
+        filter_layout: {align: {y: 0.5}, padding: {left: (THEME_SPACE_2), right: (THEME_SPACE_2)}}
+        draw_overflow_button: {
+            fn pixel(self) -> vec4 {
+                let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+                let c = self.rect_size * 0.5;
+                sdf.move_to(c.x - 4.0, c.y - 2.0);
+                sdf.line_to(c.x, c.y + 2.0);
+                sdf.line_to(c.x + 4.0, c.y - 2.0);
+                return sdf.stroke(THEME_COLOR_TEXT_DEFAULT, 1.0);
+            }
+        }
+        draw_overflow_menu: {
+            color: (THEME_COLOR_BG_CONTAINER)
+        }
+        draw_overflow_hover: {
+            color: (THEME_COLOR_BG_HIGHLIGHT)
+        }
+        draw_overflow_item: {
+            text_style: <THEME_FONT_REGULAR> {}
+            color: (THEME_COLOR_TEXT_DEFAULT)
+        }
 
         width: Fill, height: (THEME_TAB_HEIGHT)
 
@@ -2026,6 +1970,14 @@ live_design! {
             draw_depth: 10.0
             color: (THEME_COLOR_DRAG_QUAD)
         }
+        floating_bg: {color: (THEME_COLOR_BG_CONTAINER)}
+        floating_title_bar: {color: (THEME_COLOR_BG_APP)}
+        floating_title: {
+            text_style: <THEME_FONT_REGULAR> {}
+            color: (THEME_COLOR_TEXT_DEFAULT)
+        }
+        floating_title_height: (THEME_TAB_HEIGHT)
+        floating_close_button: <TabCloseButton> {}
         tab_bar: <TabBar> {}
         splitter: <Splitter> {}
     }
@@ -2113,6 +2065,26 @@ live_design! {
 
     TabBarMinimal = <TabBarBase> {
         tab: <TabMinimal> {}
+        draw_overflow_button: {
+            fn pixel(self) -> vec4 {
+                let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+                let c = self.rect_size * 0.5;
+                sdf.move_to(c.x - 4.0, c.y - 2.0);
+                sdf.line_to(c.x, c.y + 2.0);
+                sdf.line_to(c.x + 4.0, c.y - 2.0);
+                return sdf.stroke(THEME_COLOR_TEXT_DEFAULT, 1.0);
+            }
+        }
+        draw_overflow_menu: {
+            color: (THEME_COLOR_BG_CONTAINER)
+        }
+        draw_overflow_hover: {
+            color: (THEME_COLOR_BG_HIGHLIGHT)
+        }
+        draw_overflow_item: {
+            text_style: <THEME_FONT_REGULAR> {}
+            color: (THEME_COLOR_TEXT_DEFAULT)
+        }
         draw_drag: {
             draw_depth: 10
             color: (THEME_COLOR_BG_CONTAINER)
@@ -2168,6 +2140,14 @@ live_design! {
             draw_depth: 10.0
             color: (THEME_COLOR_DRAG_QUAD)
         }
+        floating_bg: {color: (THEME_COLOR_BG_CONTAINER)}
+        floating_title_bar: {color: (THEME_COLOR_BG_APP)}
+        floating_title: {
+            text_style: <THEME_FONT_REGULAR> {}
+            color: (THEME_COLOR_TEXT_DEFAULT)
+        }
+        floating_title_height: (THEME_TAB_HEIGHT)
+        floating_close_button: <TabCloseButton> {}
         tab_bar: <TabBarMinimal> {}
         splitter: <Splitter> {}
     }
@@ -2695,7 +2675,76 @@ live_design! {
         }
     }
 
+    VirtualList = <VirtualListBase> {
+        width: Fill, height: Fill
+        flow: Down,
+        clip_x: true,
+        clip_y: true
+
+        scroll_bars: <ScrollBars> {show_scroll_x: false, show_scroll_y: true}
+        row_height: (THEME_DATA_ITEM_HEIGHT),
+        draw_selected: {color: (THEME_COLOR_CTRL_SELECTED)}
+    }
+
+
 
+    NodeGraph = <NodeGraphBase> {
+        width: Fill, height: Fill,
+        node_width: 160.0,
+        title_height: 24.0,
+        port_height: 20.0,
+        port_radius: 5.0,
+        wire_width: 2.0,
+        wire_color: (THEME_COLOR_U_4),
+
+        draw_bg: {
+            fn pixel(self) -> vec4 {
+                // a grid that moves and scales with the graph
+                let spacing = 20.0 * self.zoom;
+                let p = self.pos * self.rect_size - self.pan;
+                let grid = abs(fract(p / spacing + 0.5) - 0.5) * spacing;
+                let line = 1.0 - clamp(min(grid.x, grid.y), 0.0, 1.0);
+                return mix(THEME_COLOR_BG_CONTAINER, THEME_COLOR_BG_HIGHLIGHT, line * 0.5);
+            }
+        }
+
+        draw_node: {
+            fn pixel(self) -> vec4 {
+                let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+                sdf.box(1.0, 1.0, self.rect_size.x - 2.0, self.rect_size.y - 2.0, THEME_CORNER_RADIUS);
+                sdf.fill_keep(THEME_COLOR_FLOATING_BG);
+                sdf.stroke(mix(THEME_COLOR_BEVEL_SHADOW, THEME_COLOR_MAKEPAD, self.selected), 1.5);
+                sdf.box(1.0, 1.0, self.rect_size.x - 2.0, self.title_height, THEME_CORNER_RADIUS);
+                sdf.fill(THEME_COLOR_CTRL_HOVER);
+                return sdf.result;
+            }
+        }
+
+        draw_port: {
+            fn pixel(self) -> vec4 {
+                let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+                let r = self.rect_size.x * 0.5;
+                sdf.circle(r, r, r - 1.0);
+                sdf.fill_keep(mix(THEME_COLOR_BG_CONTAINER, self.color, self.connected));
+                sdf.stroke(self.color, 1.5);
+                return sdf.result;
+            }
+        }
+
+        draw_selection: {
+            color: (THEME_COLOR_DRAG_QUAD)
+        }
+
+        draw_title: {
+            text_style: <THEME_FONT_BOLD> {font_size: (THEME_FONT_SIZE_P)}
+            color: (THEME_COLOR_TEXT_DEFAULT)
+        }
+
+        draw_port_name: {
+            text_style: <THEME_FONT_REGULAR> {font_size: (THEME_FONT_SIZE_BASE)}
+            color: (THEME_COLOR_TEXT_META)
+        }
+    }
 
     FoldButton = <FoldButtonBase> {
         // TODO: adda  focus states
@@ -3206,6 +3255,80 @@ live_design! {
     ScrollYView = <ViewBase> {scroll_bars: <ScrollBars> {show_scroll_x: false, show_scroll_y: true}}
 
 
+    EmojiPicker = <EmojiPickerBase> {
+        padding: <THEME_MSPACE_1> {}
+        cell_size: 28.0,
+        columns: 9,
+        rows: 6,
+        line_height: 20.0,
+        empty_query: "Search emoji and symbols",
+
+        draw_bg: {
+            instance color: (THEME_COLOR_FLOATING_BG)
+            instance border_width: 1.0,
+            instance radius: 2.0
+
+            fn pixel(self) -> vec4 {
+                let sdf = Sdf2d::viewport(self.pos * self.rect_size)
+                sdf.box(
+                    self.border_width,
+                    self.border_width,
+                    self.rect_size.x - self.border_width * 2.0,
+                    self.rect_size.y - self.border_width * 2.0,
+                    max(1.0, self.radius)
+                )
+                sdf.fill_keep(self.color)
+                sdf.stroke(mix(THEME_COLOR_BEVEL_LIGHT, THEME_COLOR_BEVEL_SHADOW, pow(self.pos.y, 0.35)), THEME_BEVELING)
+                return sdf.result;
+            }
+        }
+
+        draw_selected: {
+            fn pixel(self) -> vec4 {
+                let sdf = Sdf2d::viewport(self.pos * self.rect_size)
+                sdf.box(1.0, 1.0, self.rect_size.x - 2.0, self.rect_size.y - 2.0, 2.0)
+                sdf.fill(THEME_COLOR_CTRL_HOVER)
+                return sdf.result;
+            }
+        }
+
+        draw_tab: {
+            fn pixel(self) -> vec4 {
+                let sdf = Sdf2d::viewport(self.pos * self.rect_size)
+                sdf.rect(2.0, self.rect_size.y - 2.0, self.rect_size.x - 4.0, 2.0)
+                sdf.fill(THEME_COLOR_TEXT_SELECTED)
+                return sdf.result;
+            }
+        }
+
+        draw_symbol: {
+            text_style: <THEME_FONT_REGULAR> {
+                font_size: 12.0
+            }
+            fn get_color(self) -> vec4 {
+                return THEME_COLOR_TEXT_SELECTED
+            }
+        }
+
+        draw_query: {
+            text_style: <THEME_FONT_REGULAR> {
+                font_size: (THEME_FONT_SIZE_P)
+            }
+            fn get_color(self) -> vec4 {
+                return THEME_COLOR_TEXT_DEFAULT
+            }
+        }
+
+        draw_name: {
+            text_style: <THEME_FONT_REGULAR> {
+                font_size: (THEME_FONT_SIZE_BASE)
+            }
+            fn get_color(self) -> vec4 {
+                return THEME_COLOR_TEXT_META
+            }
+        }
+    }
+
     TextInput = <TextInputBase> {
         width: 200, height: Fit,
         padding: <THEME_MSPACE_2> {}
@@ -3219,6 +3342,7 @@ live_design! {
         on_focus_select_all: false,
         empty_message: "0",
         clip_x: false, clip_y: false,
+        emoji_picker: <EmojiPicker> {}
 
         draw_text: {
             instance hover: 0.0
@@ -3375,6 +3499,127 @@ live_design! {
         }
     }
 
+    FallbackDialog = <FallbackDialogBase> {
+        width: Fill, height: Fill,
+        align: {x: 0.5, y: 0.5},
+        show_bg: true,
+        draw_bg: {color: #0008}
+        dialog = <RoundedView> {
+            width: 400, height: Fit,
+            flow: Down,
+            padding: <THEME_MSPACE_3> {},
+            spacing: (THEME_SPACE_2),
+            draw_bg: {color: (THEME_COLOR_FLOATING_BG), radius: (THEME_CORNER_RADIUS)}
+            title = <Label> {
+                draw_text: {text_style: <THEME_FONT_BOLD> {}}
+            }
+            message = <View> {
+                width: Fill, height: Fit,
+                label = <Label> {width: Fill}
+            }
+            path = <View> {
+                width: Fill, height: Fit,
+                input = <TextInput> {width: Fill}
+            }
+            buttons = <View> {
+                width: Fill, height: Fit,
+                align: {x: 1.0},
+                spacing: (THEME_SPACE_2),
+                cancel = <View> {
+                    width: Fit, height: Fit,
+                    button = <Button> {text: "Cancel"}
+                }
+                ok = <Button> {text: "OK"}
+            }
+        }
+    }
+
+    WindowMenu = <WindowMenuBase> { height: 0, width: 0, }
+
+    Window = <WindowBase> {
+        pass: { clear_color: (THEME_COLOR_BG_APP) }
+        flow: Down
+        nav_control: <NavControl> {}
+        fallback_dialog: <FallbackDialog> {}
+        caption_bar = <SolidView> {
+            visible: false,
+
+            flow: Right
+
+            draw_bg: {color: (THEME_COLOR_APP_CAPTION_BAR)}
+            height: 27,
+            caption_label = <View> {
+                width: Fill, height: Fill,
+                align: {x: 0.5, y: 0.5},
+                label = <Label> {text: "Makepad", margin: {left: 100}}
+            }
+            windows_buttons = <View> {
+                visible: false,
+                width: Fit, height: Fit,
+                min = <DesktopButton> {draw_bg: {button_type: WindowsMin}}
+                max = <DesktopButton> {draw_bg: {button_type: WindowsMax}}
+                close = <DesktopButton> {draw_bg: {button_type: WindowsClose}}
+            }
+            web_fullscreen = <View> {
+                visible: false,
+                width: Fit, height: Fit,
+                fullscreen = <DesktopButton> {draw_bg: {button_type: Fullscreen}}
+            }
+            web_xr = <View> {
+                visible: false,
+                width: Fit, height: Fit,
+                xr_on = <DesktopButton> {draw_bg: {button_type: XRMode}}
+            }
+        }
+
+        window_menu = <WindowMenu> {
+            main = Main{items:[app]}
+            app = Sub { name:"Makepad", items:[quit] }
+            quit = Item {
+                name:"Quit",
+                shift: false,
+                key: KeyQ,
+                enabled: true
+            }
+        }
+        body = <KeyboardView> {
+            width: Fill, height: Fill,
+            keyboard_min_shift: 30,
+        }
+
+        cursor: Default
+        mouse_cursor_size: vec2(20, 20),
+        draw_cursor: {
+            instance border_width: 1.5
+            instance color: (THEME_COLOR_CURSOR_BG)
+            instance border_color: (THEME_COLOR_CURSOR_BORDER)
+
+            fn get_color(self) -> vec4 {
+                return self.color
+            }
+
+            fn get_border_color(self) -> vec4 {
+                return self.border_color
+            }
+
+            fn pixel(self) -> vec4 {
+                let sdf = Sdf2d::viewport(self.pos * self.rect_size)
+                sdf.move_to(1.0, 1.0);
+                sdf.line_to(self.rect_size.x - 1.0, self.rect_size.y * 0.5)
+                sdf.line_to(self.rect_size.x * 0.5, self.rect_size.y - 1.0)
+                sdf.close_path();
+                sdf.fill_keep(self.get_color())
+                if self.border_width > 0.0 {
+                    sdf.stroke(self.get_border_color(), self.border_width)
+                }
+                return sdf.result
+            }
+        }
+        window: {
+            inner_size: vec2(1024, 768)
+        }
+    }
+
     Slider = <SliderBase> {
         min: 0.0, max: 1.0,
         step: 0.0,
@@ -4052,6 +4297,21 @@ live_design! {
         height: Fill
         show_bg: false
 
+        draw_thumbnail: {
+            texture image: texture2d
+            fn pixel(self) -> vec4 {
+                let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+                sdf.box(0.5, 0.5, self.rect_size.x - 1.0, self.rect_size.y - 1.0, 2.0);
+                sdf.fill_keep(Pal::from_texture_color_space(sample2d_rt(self.image, self.pos), self.draw_texture_color_space));
+                sdf.stroke(THEME_COLOR_BEVEL_LIGHT, 1.0);
+                return sdf.result;
+            }
+        }
+        draw_thumbnail_label: {
+            text_style: <THEME_FONT_REGULAR> {font_size: (THEME_FONT_SIZE_BASE)}
+            color: (THEME_COLOR_TEXT_META)
+        }
+
         <DockToolbar> {
             content = {
                 align: { x: 0., y: 0.5 }
@@ -4144,6 +4404,7 @@ live_design! {
                     }
                 }
                 <Filler> {}
+                responsive_button = <ButtonFlat> { width: Fit, text: "Responsive" }
                 <Vr> {}
                 <P> { width: Fit, text: "Canvas" }
                 <RoundedView> {
@@ -4491,11 +4752,140 @@ live_design! {
                 return self.clip_and_transform_vertex(self.rect_pos, self.rect_size)
             }
             fn pixel(self) -> vec4 {
-                return sample2d_rt(self.image, self.pos * self.scale + self.shift);
+                return Pal::from_texture_color_space(sample2d_rt(self.image, self.pos * self.scale + self.shift), self.draw_texture_color_space);
             }
         }
         container: <DesignerContainer>{
         }
+        tick_color: (THEME_COLOR_TEXT_META)
+        guide_color: #0cf
+        snap_color: #f0a
+        draw_grid: {
+            color: #fff2
+            fn pixel(self) -> vec4 {
+                // the rect is in canvas units, and the line width is one screen pixel of them
+                let p = self.rect_pos + self.pos * self.rect_size;
+                let grid = abs(fract(p / self.grid_size + 0.5) - 0.5) * self.grid_size;
+                let line = 1.0 - clamp(min(grid.x, grid.y) / self.line_width, 0.0, 1.0);
+                return vec4(self.color.rgb * self.color.a * line, self.color.a * line);
+            }
+        }
+        draw_ruler: {
+            color: (THEME_COLOR_BG_CONTAINER)
+        }
+        draw_ruler_text: {
+            text_style: <THEME_FONT_REGULAR> {font_size: 6.0}
+            color: (THEME_COLOR_TEXT_META)
+        }
+        draw_preview: {
+            texture image: texture2d
+            fn pixel(self) -> vec4 {
+                return Pal::from_texture_color_space(sample2d_rt(self.image, self.pos), self.draw_texture_color_space);
+            }
+        }
+        draw_preview_label: {
+            text_style: <THEME_FONT_BOLD> {font_size: (THEME_FONT_SIZE_P)}
+            color: (THEME_COLOR_TEXT_DEFAULT)
+        }
+    }
+
+    EventTraceView = <EventTraceViewBase> {
+        width: Fill, height: Fill,
+        flow: Down,
+        <DockToolbar> {
+            content = {
+                align: { x: 0., y: 0.5 }
+                spacing: (THEME_SPACE_2)
+                record = <CheckBoxToggle> { text: "Record" }
+                pointer = <CheckBox> { text: "Pointer" }
+                keyboard = <CheckBox> { text: "Keyboard" }
+                actions = <CheckBox> { text: "Actions" }
+                other = <CheckBox> { text: "Other" }
+                filter = <TextInput> {
+                    width: Fill,
+                    empty_message: "Filter",
+                }
+                clear = <ButtonFlat> { text: "Clear" }
+                copy_replay = <ButtonFlat> { text: "Copy replay" }
+            }
+        }
+        list = <PortalList> {
+            auto_tail: true
+            drag_scrolling: false
+            width: Fill, height: Fill,
+            flow: Down
+            Entry = <View> {
+                width: Fill, height: Fit,
+                padding: <THEME_MSPACE_1> {}
+                spacing: (THEME_SPACE_2)
+                show_bg: true,
+                draw_bg: {
+                    instance is_even: 0.0
+                    fn pixel(self) -> vec4 {
+                        return mix(THEME_COLOR_BG_EVEN, THEME_COLOR_BG_ODD, self.is_even);
+                    }
+                }
+                time = <Label> {
+                    width: 60,
+                    draw_text: { text_style: <THEME_FONT_CODE> {}, color: (THEME_COLOR_TEXT_META) }
+                }
+                delta = <Label> {
+                    width: 70,
+                    draw_text: { text_style: <THEME_FONT_CODE> {}, color: (THEME_COLOR_TEXT_META) }
+                }
+                name = <Label> {
+                    width: 160,
+                    draw_text: { text_style: <THEME_FONT_CODE> {}, color: (THEME_COLOR_TEXT_DEFAULT) }
+                }
+                detail = <Label> {
+                    width: Fill,
+                    draw_text: { text_style: <THEME_FONT_CODE> {}, color: (THEME_COLOR_TEXT_DEFAULT), wrap: Word }
+                }
+            }
+            Empty = <View> {
+                width: Fill, height: 20,
+                show_bg: true,
+                draw_bg: {
+                    instance is_even: 0.0
+                    fn pixel(self) -> vec4 {
+                        return mix(THEME_COLOR_BG_EVEN, THEME_COLOR_BG_ODD, self.is_even);
+                    }
+                }
+            }
+        }
+    }
+
+    DesignerStateMachine = <DesignerStateMachineBase>{
+        width: Fill, height: 220.
+        line_color: (THEME_COLOR_TEXT_META)
+        draw_bg: {
+            color: (THEME_COLOR_BG_CONTAINER)
+        }
+        draw_state: {
+            fn pixel(self) -> vec4 {
+                let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+                sdf.box(1.0, 1.0, self.rect_size.x - 2.0, self.rect_size.y - 2.0, THEME_CORNER_RADIUS);
+                sdf.fill_keep(mix(
+                    mix(THEME_COLOR_CTRL_DEFAULT, THEME_COLOR_CTRL_HOVER, self.hover),
+                    THEME_COLOR_MAKEPAD,
+                    self.active
+                ));
+                sdf.stroke(mix(THEME_COLOR_BEVEL_SHADOW, THEME_COLOR_TEXT_DEFAULT, self.is_default), 1.0);
+                return sdf.result;
+            }
+        }
+        draw_track_label: {
+            text_style: <THEME_FONT_BOLD> {font_size: (THEME_FONT_SIZE_P)}
+            color: (THEME_COLOR_TEXT_DEFAULT)
+        }
+        draw_state_label: {
+            text_style: <THEME_FONT_REGULAR> {font_size: (THEME_FONT_SIZE_P)}
+            color: (THEME_COLOR_TEXT_DEFAULT)
+        }
+        draw_transition: {
+            text_style: <THEME_FONT_REGULAR> {font_size: (THEME_FONT_SIZE_BASE)}
+            color: (THEME_COLOR_TEXT_META)
+        }
     }
 
     Designer = <DesignerBase>{
@@ -4568,6 +4958,7 @@ live_design! {
                     outline_tree = <DesignerOutlineTree>{
 
                     }
+                    state_machine = <DesignerStateMachine> {}
                 }
             }
         }
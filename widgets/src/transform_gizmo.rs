@@ -0,0 +1,413 @@
+use {
+    crate::{
+        makepad_draw::*,
+    }
+};
+
+live_design!{
+    import makepad_draw::shader::std::*;
+
+    DrawGizmoHandle = {{DrawGizmoHandle}} {}
+
+    TransformGizmo = {{TransformGizmo}} {
+        outline_color: #5af
+        draw_handle: {
+            fn pixel(self) -> vec4 {
+                let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+                let r = self.rect_size.x * 0.5;
+                if self.is_rotate > 0.5 {
+                    sdf.circle(r, r, r - 1.0);
+                }
+                else {
+                    sdf.box(1.0, 1.0, self.rect_size.x - 2.0, self.rect_size.y - 2.0, 1.0);
+                }
+                sdf.fill_keep(mix(#f, #5af, self.hover));
+                sdf.stroke(#5af, 1.0);
+                return sdf.result;
+            }
+        }
+    }
+}
+
+#[derive(Live, LiveHook, LiveRegister)]#[repr(C)]
+struct DrawGizmoHandle {
+    #[deref] draw_super: DrawQuad,
+    #[live] hover: f32,
+    #[live] is_rotate: f32,
+}
+
+/// The part of the gizmo under the finger.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GizmoHandle {
+    Move,
+    Left,
+    Right,
+    Top,
+    Bottom,
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+    Rotate,
+}
+
+impl GizmoHandle {
+    const RESIZE: [GizmoHandle; 8] = [
+        Self::TopLeft, Self::Top, Self::TopRight, Self::Right,
+        Self::BottomRight, Self::Bottom, Self::BottomLeft, Self::Left,
+    ];
+
+    /// Which edges the handle moves, -1 for left/top, 1 for right/bottom.
+    fn sides(&self) -> DVec2 {
+        match self {
+            Self::Left => dvec2(-1.0, 0.0),
+            Self::Right => dvec2(1.0, 0.0),
+            Self::Top => dvec2(0.0, -1.0),
+            Self::Bottom => dvec2(0.0, 1.0),
+            Self::TopLeft => dvec2(-1.0, -1.0),
+            Self::TopRight => dvec2(1.0, -1.0),
+            Self::BottomLeft => dvec2(-1.0, 1.0),
+            Self::BottomRight => dvec2(1.0, 1.0),
+            Self::Move | Self::Rotate => dvec2(0.0, 0.0),
+        }
+    }
+
+    fn is_corner(&self) -> bool {
+        let sides = self.sides();
+        sides.x != 0.0 && sides.y != 0.0
+    }
+
+    fn cursor(&self) -> MouseCursor {
+        match self {
+            Self::Move => MouseCursor::Move,
+            Self::Left | Self::Right => MouseCursor::EwResize,
+            Self::Top | Self::Bottom => MouseCursor::NsResize,
+            Self::TopLeft | Self::BottomRight => MouseCursor::NwseResize,
+            Self::TopRight | Self::BottomLeft => MouseCursor::NeswResize,
+            Self::Rotate => MouseCursor::Crosshair,
+        }
+    }
+}
+
+/// A rect in canvas coordinates, rotated by `rotation` degrees around its center.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct GizmoTransform {
+    pub rect: Rect,
+    pub rotation: f64,
+}
+
+impl GizmoTransform {
+    fn center(&self) -> DVec2 {
+        self.rect.pos + self.rect.size * 0.5
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GizmoPhase {
+    Begin,
+    Drag,
+    Commit,
+    /// Escape was pressed, the transform is back where it began.
+    Cancel,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct GizmoAction {
+    pub phase: GizmoPhase,
+    pub handle: GizmoHandle,
+    pub transform: GizmoTransform,
+}
+
+/// How canvas coordinates map to the screen: `canvas = (abs - origin) * scale + offset`.
+#[derive(Clone, Copy, Debug)]
+pub struct GizmoView {
+    pub origin: DVec2,
+    pub offset: DVec2,
+    pub scale: f64,
+}
+
+impl Default for GizmoView {
+    fn default() -> Self {
+        Self {origin: DVec2::default(), offset: DVec2::default(), scale: 1.0}
+    }
+}
+
+impl GizmoView {
+    pub fn to_canvas(&self, abs: DVec2) -> DVec2 {
+        (abs - self.origin) * self.scale + self.offset
+    }
+
+    pub fn to_screen(&self, pos: DVec2) -> DVec2 {
+        (pos - self.offset) / self.scale + self.origin
+    }
+}
+
+struct GizmoDrag {
+    handle: GizmoHandle,
+    start: GizmoTransform,
+    start_abs: DVec2,
+}
+
+fn rotate(v: DVec2, degrees: f64) -> DVec2 {
+    let (sin, cos) = degrees.to_radians().sin_cos();
+    dvec2(v.x * cos - v.y * sin, v.x * sin + v.y * cos)
+}
+
+/// Move, resize and rotate handles around a rect on a canvas, for the designer and any app that
+/// manipulates objects on screen. The host widget sets the transform and the view, draws the
+/// gizmo on top of its content and passes its hits through `handle_hit`, which returns the
+/// transform as it changes. Holding shift constrains a move to one axis, keeps the aspect ratio
+/// on corners and rotates in steps; alt resizes around the center; control turns snapping off.
+#[derive(Live, LiveHook, LiveRegister)]
+pub struct TransformGizmo {
+    #[live] draw_handle: DrawGizmoHandle,
+    #[live] draw_outline: DrawLine,
+    #[live] outline_color: Vec4,
+    #[live(8.0)] handle_size: f64,
+    #[live(24.0)] rotate_offset: f64,
+    #[live(true)] resizable: bool,
+    #[live(false)] rotatable: bool,
+    /// Grid spacing in canvas units, 0 to only snap to guides.
+    #[live(10.0)] grid_size: f64,
+    /// How close to a guide, in screen pixels, before snapping to it.
+    #[live(6.0)] snap_distance: f64,
+    #[live(15.0)] rotate_step: f64,
+    #[live(4.0)] min_size: f64,
+
+    #[rust] pub transform: GizmoTransform,
+    #[rust] pub view: GizmoView,
+    /// Positions to snap to, in canvas coordinates, like the edges of other objects.
+    #[rust] pub guides_x: Vec<f64>,
+    #[rust] pub guides_y: Vec<f64>,
+    #[rust] hover: Option<GizmoHandle>,
+    #[rust] drag: Option<GizmoDrag>,
+}
+
+impl TransformGizmo {
+    pub fn is_dragging(&self) -> bool {
+        self.drag.is_some()
+    }
+
+    /// Sets the grid spacing in canvas units, 0 to only snap to guides.
+    pub fn set_grid_size(&mut self, grid_size: f64) {
+        self.grid_size = grid_size;
+    }
+
+    /// The guides that an edge or the center of the transform lines up with during a drag, to
+    /// show as snap lines.
+    pub fn snap_lines(&self) -> (Vec<f64>, Vec<f64>) {
+        if self.drag.is_none() {
+            return (Vec::new(), Vec::new())
+        }
+        let r = self.transform.rect;
+        let lined_up = | edges: [f64; 3], guides: &[f64] | -> Vec<f64> {
+            guides.iter()
+                .filter( | guide | edges.iter().any( | edge | (*edge - **guide).abs() < 0.001))
+                .copied()
+                .collect()
+        };
+        (
+            lined_up([r.pos.x, r.pos.x + r.size.x * 0.5, r.pos.x + r.size.x], &self.guides_x),
+            lined_up([r.pos.y, r.pos.y + r.size.y * 0.5, r.pos.y + r.size.y], &self.guides_y),
+        )
+    }
+
+    /// The corners and edge centers in screen coordinates, in the order of `GizmoHandle::RESIZE`.
+    fn handle_positions(&self) -> [DVec2; 8] {
+        let t = &self.transform;
+        let center = t.center();
+        let half = t.rect.size * 0.5;
+        GizmoHandle::RESIZE.map( | handle | {
+            self.view.to_screen(center + rotate(half * handle.sides(), t.rotation))
+        })
+    }
+
+    fn rotate_handle_position(&self) -> DVec2 {
+        let t = &self.transform;
+        let top = self.view.to_screen(t.center() + rotate(dvec2(0.0, -t.rect.size.y * 0.5), t.rotation));
+        top + rotate(dvec2(0.0, -self.rotate_offset), t.rotation)
+    }
+
+    pub fn handle_at(&self, abs: DVec2) -> Option<GizmoHandle> {
+        let reach = self.handle_size;
+        if self.rotatable && (self.rotate_handle_position() - abs).length() <= reach {
+            return Some(GizmoHandle::Rotate)
+        }
+        if self.resizable {
+            for (handle, pos) in GizmoHandle::RESIZE.iter().zip(self.handle_positions()) {
+                if (pos - abs).length() <= reach {
+                    return Some(*handle)
+                }
+            }
+        }
+        let local = rotate(self.view.to_canvas(abs) - self.transform.center(), -self.transform.rotation);
+        let half = self.transform.rect.size * 0.5;
+        if local.x.abs() <= half.x && local.y.abs() <= half.y {
+            return Some(GizmoHandle::Move)
+        }
+        None
+    }
+
+    /// Snaps a canvas position to the closest guide within reach, or else to the grid. Returns
+    /// the snapped position and how far it moved.
+    fn snap(&self, value: f64, guides: &[f64]) -> (f64, f64) {
+        let reach = self.snap_distance * self.view.scale;
+        let guide = guides.iter()
+            .map( | guide | (*guide, (*guide - value).abs()))
+            .filter( | (_, distance) | *distance <= reach)
+            .min_by( | a, b | a.1.total_cmp(&b.1));
+        if let Some((guide, distance)) = guide {
+            return (guide, distance)
+        }
+        if self.grid_size > 0.0 {
+            let snapped = (value / self.grid_size).round() * self.grid_size;
+            return (snapped, (snapped - value).abs())
+        }
+        (value, 0.0)
+    }
+
+    /// The shift that snaps the best of a set of edges, like the left, center and right of a rect.
+    fn snap_edges(&self, edges: &[f64], guides: &[f64]) -> f64 {
+        edges.iter()
+            .map( | edge | {
+                let (snapped, distance) = self.snap(*edge, guides);
+                (snapped - *edge, distance)
+            })
+            .min_by( | a, b | a.1.total_cmp(&b.1))
+            .map_or(0.0, | (shift, _) | shift)
+    }
+
+    fn drag_transform(&self, drag: &GizmoDrag, abs: DVec2, modifiers: &KeyModifiers) -> GizmoTransform {
+        let start = drag.start;
+        let snapping = !modifiers.control && !modifiers.logo;
+        let mut delta = self.view.to_canvas(abs) - self.view.to_canvas(drag.start_abs);
+        match drag.handle {
+            GizmoHandle::Move => {
+                if modifiers.shift {
+                    if delta.x.abs() > delta.y.abs() {delta.y = 0.0} else {delta.x = 0.0}
+                }
+                let mut rect = Rect {pos: start.rect.pos + delta, size: start.rect.size};
+                if snapping {
+                    let (pos, size) = (rect.pos, rect.size);
+                    if !modifiers.shift || delta.x != 0.0 {
+                        rect.pos.x += self.snap_edges(&[pos.x, pos.x + size.x * 0.5, pos.x + size.x], &self.guides_x);
+                    }
+                    if !modifiers.shift || delta.y != 0.0 {
+                        rect.pos.y += self.snap_edges(&[pos.y, pos.y + size.y * 0.5, pos.y + size.y], &self.guides_y);
+                    }
+                }
+                GizmoTransform {rect, rotation: start.rotation}
+            }
+            GizmoHandle::Rotate => {
+                let center = start.center();
+                let from = self.view.to_canvas(drag.start_abs) - center;
+                let to = self.view.to_canvas(abs) - center;
+                let mut rotation = start.rotation + (to.angle_in_radians() - from.angle_in_radians()).to_degrees();
+                if modifiers.shift && self.rotate_step > 0.0 {
+                    rotation = (rotation / self.rotate_step).round() * self.rotate_step;
+                }
+                GizmoTransform {rect: start.rect, rotation: rotation.rem_euclid(360.0)}
+            }
+            handle => {
+                let sides = handle.sides();
+                let local = rotate(delta, -start.rotation) * sides;
+                let from_center = modifiers.alt;
+                let grow = if from_center {local * 2.0} else {local};
+                let mut size = start.rect.size + grow;
+                if modifiers.shift && handle.is_corner() && start.rect.size.x > 0.0 && start.rect.size.y > 0.0 {
+                    let scale = (size.x / start.rect.size.x).max(size.y / start.rect.size.y);
+                    size = start.rect.size * scale;
+                }
+                size = dvec2(size.x.max(self.min_size), size.y.max(self.min_size));
+                // keep the opposite edge, or the center, where it was
+                let anchor = if from_center {DVec2::default()} else {start.rect.size * sides * -0.5};
+                let new_anchor = if from_center {DVec2::default()} else {size * sides * -0.5};
+                let center = start.center() + rotate(anchor, start.rotation) - rotate(new_anchor, start.rotation);
+                let mut rect = Rect {pos: center - size * 0.5, size};
+                // snapping the moving edges only makes sense while they are axis aligned
+                if snapping && start.rotation == 0.0 && !from_center && !(modifiers.shift && handle.is_corner()) {
+                    if sides.x > 0.0 {
+                        rect.size.x = (self.snap(rect.pos.x + rect.size.x, &self.guides_x).0 - rect.pos.x).max(self.min_size);
+                    }
+                    else if sides.x < 0.0 {
+                        let right = rect.pos.x + rect.size.x;
+                        rect.pos.x = self.snap(rect.pos.x, &self.guides_x).0.min(right - self.min_size);
+                        rect.size.x = right - rect.pos.x;
+                    }
+                    if sides.y > 0.0 {
+                        rect.size.y = (self.snap(rect.pos.y + rect.size.y, &self.guides_y).0 - rect.pos.y).max(self.min_size);
+                    }
+                    else if sides.y < 0.0 {
+                        let bottom = rect.pos.y + rect.size.y;
+                        rect.pos.y = self.snap(rect.pos.y, &self.guides_y).0.min(bottom - self.min_size);
+                        rect.size.y = bottom - rect.pos.y;
+                    }
+                }
+                GizmoTransform {rect, rotation: start.rotation}
+            }
+        }
+    }
+
+    /// Handles a hit the host widget got for its area. Returns the transform when a drag begins,
+    /// moves, commits or is cancelled.
+    pub fn handle_hit(&mut self, cx: &mut Cx, hit: &Hit) -> Option<GizmoAction> {
+        match hit {
+            Hit::FingerHoverOver(fh) if self.drag.is_none() => {
+                let hover = self.handle_at(fh.abs);
+                if let Some(handle) = hover {
+                    cx.set_cursor(handle.cursor());
+                }
+                if hover != self.hover {
+                    self.hover = hover;
+                    self.draw_handle.redraw(cx);
+                }
+                None
+            }
+            Hit::FingerDown(fe) => {
+                let handle = self.handle_at(fe.abs) ?;
+                self.drag = Some(GizmoDrag {handle, start: self.transform, start_abs: fe.abs});
+                Some(GizmoAction {phase: GizmoPhase::Begin, handle, transform: self.transform})
+            }
+            Hit::FingerMove(fe) => {
+                let drag = self.drag.as_ref() ?;
+                let handle = drag.handle;
+                self.transform = self.drag_transform(drag, fe.abs, &fe.modifiers);
+                Some(GizmoAction {phase: GizmoPhase::Drag, handle, transform: self.transform})
+            }
+            Hit::FingerUp(_) => {
+                let drag = self.drag.take() ?;
+                Some(GizmoAction {phase: GizmoPhase::Commit, handle: drag.handle, transform: self.transform})
+            }
+            Hit::KeyDown(ke) if ke.key_code == KeyCode::Escape => {
+                let drag = self.drag.take() ?;
+                self.transform = drag.start;
+                Some(GizmoAction {phase: GizmoPhase::Cancel, handle: drag.handle, transform: self.transform})
+            }
+            _ => None
+        }
+    }
+
+    pub fn draw(&mut self, cx: &mut Cx2d) {
+        let corners = self.handle_positions();
+        let color = self.outline_color;
+        for i in (0..8).step_by(2) {
+            self.draw_outline.draw_line_abs(cx, corners[i], corners[(i + 2) % 8], color, 1.0);
+        }
+        let half = dvec2(self.handle_size, self.handle_size) * 0.5;
+        if self.rotatable {
+            let pos = self.rotate_handle_position();
+            let top = corners[1];
+            self.draw_outline.draw_line_abs(cx, top, pos, color, 1.0);
+            self.draw_handle.is_rotate = 1.0;
+            self.draw_handle.hover = if self.hover == Some(GizmoHandle::Rotate) {1.0} else {0.0};
+            self.draw_handle.draw_abs(cx, Rect {pos: pos - half, size: half * 2.0});
+        }
+        if self.resizable {
+            self.draw_handle.is_rotate = 0.0;
+            for (handle, pos) in GizmoHandle::RESIZE.iter().zip(corners) {
+                self.draw_handle.hover = if self.hover == Some(*handle) {1.0} else {0.0};
+                self.draw_handle.draw_abs(cx, Rect {pos: pos - half, size: half * 2.0});
+            }
+        }
+    }
+}
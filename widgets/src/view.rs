@@ -517,6 +517,16 @@ impl WidgetNode for View {
         }
     }
 
+    fn recycle_node(&mut self, cx: &mut Cx) {
+        self.animator.swap_out_state();
+        if let Some(scroll_bars) = &mut self.scroll_bars_obj {
+            scroll_bars.set_scroll_pos(cx, dvec2(0.0, 0.0));
+        }
+        for child in self.children.values_mut() {
+            child.recycle(cx);
+        }
+    }
+
     fn find_widgets(&mut self, path: &[LiveId], cached: WidgetCache, results: &mut WidgetSet) {
         match cached {
             WidgetCache::Yes | WidgetCache::Clear => {
@@ -667,6 +677,17 @@ impl Widget for View {
         self.visible
     }
 
+    fn reading_text(&self, scope: &mut Scope, out: &mut String) {
+        if !self.visible {
+            return
+        }
+        for id in &self.draw_order {
+            if let Some(child) = self.children.get(id) {
+                scope.with_id(*id, | scope | child.reading_text(scope, out));
+            }
+        }
+    }
+
     fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
         // the beginning state
         if self.draw_state.begin(cx, DrawState::Drawing(0, false)) {
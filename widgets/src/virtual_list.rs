@@ -0,0 +1,223 @@
+use crate::{
+    makepad_derive_widget::*,
+    makepad_draw::*,
+    widget::*,
+    scroll_bars::ScrollBars,
+};
+
+live_design!{
+    VirtualListBase = {{VirtualList}} {}
+}
+
+/// A scrolling list that only draws the rows in view, so it stays fast for any number of them.
+/// The host draws the rows with a closure from `draw_rows`. Rows can be of any height: a row
+/// counts as `row_height` until it was drawn once, and as the height it had from then on.
+#[derive(Live, LiveHook, Widget)]
+pub struct VirtualList {
+    #[redraw] #[live] scroll_bars: ScrollBars,
+    #[walk] walk: Walk,
+    #[layout] layout: Layout,
+    #[live] draw_selected: DrawColor,
+    #[live(20.0)] row_height: f64,
+
+    #[rust] heights: Vec<f64>,
+    #[rust] offsets: Vec<f64>,
+    #[rust] selected_row: Option<usize>,
+    #[rust] draw_state: DrawStateWrap<()>,
+}
+
+#[derive(Clone, Debug, DefaultNone)]
+pub enum VirtualListAction {
+    None,
+    SelectionChanged(usize),
+    /// Return was pressed on the selected row, or it was double clicked.
+    RowActivated(usize),
+}
+
+impl VirtualList {
+    pub fn begin(&mut self, cx: &mut Cx2d, walk: Walk) {
+        self.scroll_bars.begin(cx, walk, self.layout);
+    }
+
+    pub fn end(&mut self, cx: &mut Cx2d) {
+        self.scroll_bars.end(cx);
+    }
+
+    /// Draws the rows of a list of `row_count` that are in view, calling `draw_row` for each
+    /// inside a turtle of its own. Call between the steps of `draw_walk`.
+    pub fn draw_rows(&mut self, cx: &mut Cx2d, row_count: usize, mut draw_row: impl FnMut(&mut Cx2d, usize)) {
+        self.set_row_count(row_count);
+        let first = self.row_at(self.scroll_bars.get_scroll_pos().y).unwrap_or(row_count);
+        cx.walk_turtle(Walk::size(Size::Fill, Size::Fixed(self.offsets[first])));
+        let mut heights_changed = false;
+        let mut index = first;
+        while index < row_count && cx.walk_turtle_would_be_visible(Walk::size(Size::Fill, Size::Fixed(self.heights[index]))) {
+            let walk = Walk::size(Size::Fill, Size::Fit);
+            let rect = if self.selected_row == Some(index) {
+                self.draw_selected.begin(cx, walk, Layout::flow_down());
+                draw_row(cx, index);
+                let rect = cx.end_turtle();
+                self.draw_selected.draw_vars.area.set_rect(cx, &rect);
+                rect
+            }
+            else {
+                cx.begin_turtle(walk, Layout::flow_down());
+                draw_row(cx, index);
+                cx.end_turtle()
+            };
+            if rect.size.y != self.heights[index] {
+                self.heights[index] = rect.size.y;
+                heights_changed = true;
+            }
+            index += 1;
+        }
+        if heights_changed {
+            self.update_offsets();
+        }
+        cx.walk_turtle(Walk::size(Size::Fill, Size::Fixed(self.offsets[row_count] - self.offsets[index])));
+    }
+
+    fn set_row_count(&mut self, row_count: usize) {
+        if self.heights.len() != row_count || self.offsets.is_empty() {
+            self.heights.resize(row_count, self.row_height);
+            self.update_offsets();
+        }
+        if self.selected_row.is_some_and( | row | row >= row_count) {
+            self.selected_row = None;
+        }
+    }
+
+    fn update_offsets(&mut self) {
+        self.offsets.clear();
+        let mut offset = 0.0;
+        self.offsets.push(offset);
+        for height in &self.heights {
+            offset += height;
+            self.offsets.push(offset);
+        }
+    }
+
+    /// The row at `y` from the top of the list, if there is one there.
+    fn row_at(&self, y: f64) -> Option<usize> {
+        let row = self.offsets.partition_point( | offset | *offset <= y).saturating_sub(1);
+        if row < self.heights.len() {Some(row)} else {None}
+    }
+
+    pub fn selected_row(&self) -> Option<usize> {
+        self.selected_row
+    }
+
+    pub fn set_selected_row(&mut self, cx: &mut Cx, row: Option<usize>) {
+        self.selected_row = row.filter( | row | *row < self.heights.len());
+        if let Some(row) = self.selected_row {
+            self.scroll_to_row(cx, row);
+        }
+        self.scroll_bars.redraw(cx);
+    }
+
+    /// Scrolls smoothly until `row` is in view.
+    pub fn scroll_to_row(&mut self, cx: &mut Cx, row: usize) {
+        if row < self.heights.len() {
+            let rect = Rect {pos: dvec2(0.0, self.offsets[row]), size: dvec2(0.0, self.heights[row])};
+            self.scroll_bars.scroll_into_view(cx, rect);
+        }
+    }
+
+    fn select_row(&mut self, cx: &mut Cx, scope: &mut Scope, row: usize) {
+        if self.selected_row != Some(row) {
+            self.set_selected_row(cx, Some(row));
+            cx.widget_action(self.widget_uid(), &scope.path, VirtualListAction::SelectionChanged(row));
+        }
+    }
+}
+
+impl Widget for VirtualList {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        let uid = self.widget_uid();
+        self.scroll_bars.handle_event(cx, event);
+
+        let row_count = self.heights.len();
+        match event.hits(cx, self.scroll_bars.area()) {
+            Hit::FingerDown(fe) => {
+                cx.set_key_focus(self.scroll_bars.area());
+                let y = fe.abs.y - fe.rect.pos.y + self.scroll_bars.get_scroll_pos().y;
+                if let Some(row) = self.row_at(y) {
+                    self.select_row(cx, scope, row);
+                    if fe.tap_count == 2 {
+                        cx.widget_action(uid, &scope.path, VirtualListAction::RowActivated(row));
+                    }
+                }
+            }
+            Hit::KeyDown(ke) if row_count > 0 => {
+                let page = self.scroll_bars.get_scroll_view_visible().y;
+                let selected = self.selected_row;
+                let offset = selected.map_or(0.0, | row | self.offsets[row]);
+                let row = match ke.key_code {
+                    KeyCode::ArrowUp => selected.map_or(row_count - 1, | row | row.saturating_sub(1)),
+                    KeyCode::ArrowDown => selected.map_or(0, | row | (row + 1).min(row_count - 1)),
+                    KeyCode::PageUp => self.row_at((offset - page).max(0.0)).unwrap_or(0),
+                    KeyCode::PageDown => self.row_at(offset + page).unwrap_or(row_count - 1),
+                    KeyCode::Home => 0,
+                    KeyCode::End => row_count - 1,
+                    KeyCode::ReturnKey => {
+                        if let Some(row) = selected {
+                            cx.widget_action(uid, &scope.path, VirtualListAction::RowActivated(row));
+                        }
+                        return
+                    }
+                    _ => return
+                };
+                self.select_row(cx, scope, row);
+            }
+            _ => ()
+        }
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, _scope: &mut Scope, walk: Walk) -> DrawStep {
+        if self.draw_state.begin(cx, ()) {
+            self.begin(cx, walk);
+            return DrawStep::make_step()
+        }
+        if let Some(()) = self.draw_state.get() {
+            self.end(cx);
+            self.draw_state.end();
+        }
+        DrawStep::done()
+    }
+}
+
+impl VirtualListRef {
+    pub fn selection_changed(&self, actions: &Actions) -> Option<usize> {
+        if let Some(item) = actions.find_widget_action(self.widget_uid()) {
+            if let VirtualListAction::SelectionChanged(row) = item.cast() {
+                return Some(row)
+            }
+        }
+        None
+    }
+
+    pub fn row_activated(&self, actions: &Actions) -> Option<usize> {
+        if let Some(item) = actions.find_widget_action(self.widget_uid()) {
+            if let VirtualListAction::RowActivated(row) = item.cast() {
+                return Some(row)
+            }
+        }
+        None
+    }
+
+    pub fn selected_row(&self) -> Option<usize> {
+        self.borrow().and_then( | inner | inner.selected_row())
+    }
+
+    pub fn set_selected_row(&self, cx: &mut Cx, row: Option<usize>) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.set_selected_row(cx, row);
+        }
+    }
+
+    pub fn scroll_to_row(&self, cx: &mut Cx, row: usize) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.scroll_to_row(cx, row);
+        }
+    }
+}
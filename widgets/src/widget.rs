@@ -26,6 +26,16 @@ pub trait WidgetNode: LiveApply{
     fn find_widgets(&mut self, _path: &[LiveId], _cached: WidgetCache, _results: &mut WidgetSet);
     fn walk(&mut self, _cx:&mut Cx) -> Walk;
     fn redraw(&mut self, _cx: &mut Cx);
+    
+    // the animator of the widget, for tools that show its states and force them.
+    // derived for widgets with an #[animator] field
+    fn animator_states_ptr(&self) -> Option<LivePtr> {None}
+    fn animator_state_is(&self, _cx: &Cx, _state: &[LiveId; 2]) -> bool {false}
+    fn animator_force_state(&mut self, _cx: &mut Cx, _state: &[LiveId; 2]) {}
+    
+    // clears the state of the widget and the widgets in it for reuse, see `Widget::recycle`.
+    // derived to forget the state of an #[animator] field and to recurse into a #[deref] field
+    fn recycle_node(&mut self, _cx: &mut Cx) {}
 }
 
 pub trait Widget: WidgetNode {
@@ -82,6 +92,20 @@ pub trait Widget: WidgetNode {
         self.set_text(v);
         self.redraw(cx);
     }
+    
+    /// Appends the content of the widget to `out` as plain text, one line per item in reading
+    /// order. Containers recurse into their children, so this works on any panel.
+    fn reading_text(&self, _scope: &mut Scope, out: &mut String) {
+        push_reading_line(out, &self.text());
+    }
+    
+    /// Clears the state the widget built up while in use, so a pool can hand it out again in
+    /// place of a new one. The pool applies the template again afterwards, which puts the live
+    /// fields back, so only state outside of those needs clearing. Containers recurse into
+    /// their children.
+    fn recycle(&mut self, cx: &mut Cx) {
+        self.recycle_node(cx);
+    }
     /*
     fn create_child(
         &mut self,
@@ -144,6 +168,15 @@ impl DrawStepApi for DrawStep {
 
 pub type DrawStep = Result<(), WidgetRef>;
 
+/// Appends `line` to a reading text, skipping empty lines.
+pub fn push_reading_line(out: &mut String, line: &str) {
+    if line.trim().is_empty() {
+        return
+    }
+    out.push_str(line.trim_end());
+    out.push('\n');
+}
+
 generate_any_trait_api!(Widget);
 
 pub trait WidgetFactory {
@@ -469,6 +502,26 @@ impl WidgetRef {
         }
     }
     
+    pub fn animator_states_ptr(&self) -> Option<LivePtr> {
+        if let Some(inner) = self.0.borrow().as_ref() {
+            return inner.widget.animator_states_ptr()
+        }
+        None
+    }
+    
+    pub fn animator_state_is(&self, cx: &Cx, state: &[LiveId; 2]) -> bool {
+        if let Some(inner) = self.0.borrow().as_ref() {
+            return inner.widget.animator_state_is(cx, state)
+        }
+        false
+    }
+    
+    pub fn animator_force_state(&self, cx: &mut Cx, state: &[LiveId; 2]) {
+        if let Some(inner) = self.0.borrow_mut().as_mut() {
+            inner.widget.animator_force_state(cx, state)
+        }
+    }
+    
     pub fn is_visible(&self) -> bool {
         if let Some(inner) = self.0.borrow().as_ref() {
             return inner.widget.is_visible()
@@ -503,6 +556,18 @@ impl WidgetRef {
         }
     }
     
+    pub fn reading_text(&self, scope: &mut Scope, out: &mut String) {
+        if let Some(inner) = self.0.borrow().as_ref() {
+            inner.widget.reading_text(scope, out)
+        }
+    }
+    
+    pub fn recycle(&self, cx: &mut Cx) {
+        if let Some(inner) = self.0.borrow_mut().as_mut() {
+            inner.widget.recycle(cx)
+        }
+    }
+    
     pub fn borrow_mut<T: 'static + Widget>(&self) -> Option<std::cell::RefMut<'_, T >> {
         if let Ok(ret) = std::cell::RefMut::filter_map(self.0.borrow_mut(), | inner | {
             if let Some(inner) = inner.as_mut() {
@@ -564,6 +629,7 @@ impl WidgetRef {
                 *inner = Some(WidgetRefInner{
                     widget: component,
                 });
+                apply.mark_changed();
                 if let Some(component) = &mut *inner {
                     return component.widget.apply(cx, apply, index, nodes);
                 }
@@ -580,6 +646,12 @@ impl WidgetRef {
     }
 }
 
+impl Recycle for WidgetRef {
+    fn recycle(&mut self, cx: &mut Cx) {
+        <WidgetRef>::recycle(self, cx)
+    }
+}
+
 impl LiveHook for WidgetRef {}
 impl LiveApply for WidgetRef {
     fn apply(&mut self, cx: &mut Cx, apply: &mut Apply, index: usize, nodes: &[LiveNode]) -> usize {
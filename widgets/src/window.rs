@@ -4,6 +4,7 @@ use crate::{
     performance_view::PerformanceView,
     makepad_draw::*,
     nav_control::NavControl,
+    fallback_dialog::FallbackDialog,
     desktop_button::*,
     view::*,
     widget::*,
@@ -25,6 +26,7 @@ pub struct Window {
     #[live] debug_view: DebugView,
     #[live] performance_view: PerformanceView,
     #[live] nav_control: NavControl,
+    #[live] fallback_dialog: FallbackDialog,
     #[live] window: WindowHandle,
     #[live] stdin_size: DrawColor,
     #[rust(Overlay::new(cx))] overlay: Overlay,
@@ -115,11 +117,19 @@ pub enum WindowAction {
     EventForOtherWindow,
     WindowClosed,
     WindowGeomChange(WindowGeomChangeEvent),
+    /// The window was moved to a display with another scale, it is redrawn for it already.
+    DpiChanged(DpiChangedEvent),
     None
 }
 
 impl Window {
 
+    /// Events from windows opened on behalf of this one, like dock panels that were torn
+    /// out, are handled by our widgets too.
+    fn owns_window(&self, cx: &Cx, window_id: WindowId) -> bool {
+        cx.windows.is_window_or_owned_by(window_id, self.window.window_id())
+    }
+    
     pub fn begin(&mut self, cx: &mut Cx2d) -> Redrawing {
 
         if !cx.will_redraw(&mut self.main_draw_list, Walk::default()) {
@@ -139,6 +149,7 @@ impl Window {
     
     pub fn end(&mut self, cx: &mut Cx2d) {
         //while self.frame.draw_widget_continue(cx).is_not_done() {}
+        self.fallback_dialog.draw_all(cx, &mut Scope::empty());
         self.debug_view.draw(cx);
         
         // lets draw our cursor
@@ -192,6 +203,7 @@ impl Widget for Window {
         
         self.nav_control.handle_event(cx, event, self.main_draw_list.draw_list_id());
         self.overlay.handle_event(cx, event);
+        self.fallback_dialog.handle_event(cx, event, scope);
         if self.demo_next_frame.is_event(event).is_some(){
             if self.demo{
                 self.demo_next_frame = cx.new_next_frame();
@@ -199,12 +211,15 @@ impl Widget for Window {
             cx.repaint_pass_and_child_passes(self.pass.pass_id());
         }
         let is_for_other_window = match event {
-            Event::WindowCloseRequested(ev) => ev.window_id != self.window.window_id(),
+            Event::WindowCloseRequested(ev) => !self.owns_window(cx, ev.window_id),
             Event::WindowClosed(ev) => {
                 if ev.window_id == self.window.window_id() {
-                    cx.widget_action(uid, &scope.path, WindowAction::WindowClosed)
+                    cx.widget_action(uid, &scope.path, WindowAction::WindowClosed);
+                    true
+                }
+                else {
+                    !self.owns_window(cx, ev.window_id)
                 }
-                true
             }
             Event::WindowGeomChange(ev) => {
                 if ev.window_id == self.window.window_id() {
@@ -226,8 +241,14 @@ impl Widget for Window {
                     cx.widget_action(uid, &scope.path, WindowAction::WindowGeomChange(ev.clone()));
                     return
                 }
-                true
+                !self.owns_window(cx, ev.window_id)
             },
+            Event::DpiChanged(ev) => {
+                if ev.window_id == self.window.window_id() {
+                    cx.widget_action(uid, &scope.path, WindowAction::DpiChanged(ev.clone()));
+                }
+                !self.owns_window(cx, ev.window_id)
+            }
             Event::WindowDragQuery(dq) => {
                 if dq.window_id == self.window.window_id() {
 
@@ -248,11 +269,13 @@ impl Widget for Window {
                 }
                 true
             }
-            Event::TouchUpdate(ev) => ev.window_id != self.window.window_id(),
-            Event::MouseDown(ev) => ev.window_id != self.window.window_id(),
-            Event::MouseMove(ev) => ev.window_id != self.window.window_id(),
-            Event::MouseUp(ev) => ev.window_id != self.window.window_id(),
-            Event::Scroll(ev) => ev.window_id != self.window.window_id(),
+            Event::TouchUpdate(ev) => !self.owns_window(cx, ev.window_id),
+            Event::MouseDown(ev) => !self.owns_window(cx, ev.window_id),
+            Event::MouseMove(ev) => !self.owns_window(cx, ev.window_id),
+            Event::MouseUp(ev) => !self.owns_window(cx, ev.window_id),
+            Event::Scroll(ev) => !self.owns_window(cx, ev.window_id),
+            Event::Drag(ev) => !self.owns_window(cx, ev.window_id),
+            Event::Drop(ev) => !self.owns_window(cx, ev.window_id),
             _ => false
         };
         
@@ -260,7 +283,9 @@ impl Widget for Window {
             cx.widget_action(uid, &scope.path, WindowAction::EventForOtherWindow);
             return
         }
-        else {
+        // an open fallback dialog is modal, the rest of the window doesn't get pointer or key input
+        let is_blocked_by_dialog = self.fallback_dialog.is_open() && (event.requires_visibility() || matches!(event, Event::KeyDown(_) | Event::KeyUp(_) | Event::TextInput(_)));
+        if !is_blocked_by_dialog {
             self.view.handle_event(cx, event, scope);
         }
         